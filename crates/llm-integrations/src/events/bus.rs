@@ -3,10 +3,9 @@
 use super::{EventBus, SchemaEvent};
 use async_trait::async_trait;
 use anyhow::Result;
+use schema_registry_core::retry::{ErrorClass, RetryPolicy, RetryRule};
 use std::sync::Arc;
 use std::time::Duration;
-use tokio_retry::strategy::ExponentialBackoff;
-use tokio_retry::Retry;
 use tracing::{info, warn, error};
 
 /// In-memory event bus (for testing and simple deployments)
@@ -43,25 +42,23 @@ impl EventBus for InMemoryEventBus {
 
         let handlers = self.handlers.read().await;
 
-        // Define retry strategy: 3 attempts with exponential backoff
-        let retry_strategy = ExponentialBackoff::from_millis(100)
-            .max_delay(Duration::from_secs(2))
-            .take(3);
+        // Handler errors are opaque, so every failure is retried: 3 retries
+        // with exponential backoff
+        let policy = RetryPolicy::new("event_handler").with_rule(
+            ErrorClass::Transient,
+            RetryRule::backoff(4, Duration::from_millis(100), Duration::from_secs(2)),
+        );
 
         for (idx, handler) in handlers.iter().enumerate() {
-            let event_clone = event.clone();
-            let handler_clone = Arc::clone(handler);
-
-            // Retry with exponential backoff
-            let result = Retry::spawn(retry_strategy.clone(), move || {
-                let event = event_clone.clone();
-                let handler = Arc::clone(&handler_clone);
-
-                async move {
-                    handler(event)
-                        .map_err(|e| anyhow::anyhow!("Handler failed: {}", e))
-                }
-            }).await;
+            let result = policy
+                .retry_with(
+                    |_| ErrorClass::Transient,
+                    || async {
+                        handler(event.clone())
+                            .map_err(|e| anyhow::anyhow!("Handler failed: {}", e))
+                    },
+                )
+                .await;
 
             match result {
                 Ok(_) => info!(handler_idx = idx, "Event handler executed successfully"),
@@ -3,17 +3,23 @@
 use super::WebhookConfig;
 use crate::events::SchemaEvent;
 use anyhow::Result;
+use hmac::{Hmac, Mac};
 use reqwest::Client;
+use schema_registry_core::retry::{Classify, ErrorClass, RetryPolicy, RetryRule};
+use schema_registry_core::secret_ref::SecretResolver;
+use sha2::Sha256;
 use std::sync::Arc;
 use std::time::Duration;
-use tokio_retry::strategy::ExponentialBackoff;
-use tokio_retry::Retry;
 use tracing::{info, warn, error};
 
+/// Header carrying the HMAC-SHA256 signature of the request body
+pub const SIGNATURE_HEADER: &str = "X-Webhook-Signature";
+
 /// Webhook dispatcher
 pub struct WebhookDispatcher {
     client: Client,
     configs: Vec<WebhookConfig>,
+    secret_resolver: Option<Arc<dyn SecretResolver>>,
 }
 
 impl WebhookDispatcher {
@@ -26,9 +32,30 @@ impl WebhookDispatcher {
         Ok(Self {
             client,
             configs,
+            secret_resolver: None,
         })
     }
 
+    /// Resolve webhook signing secrets through a secrets manager
+    pub fn with_secret_resolver(mut self, resolver: Arc<dyn SecretResolver>) -> Self {
+        self.secret_resolver = Some(resolver);
+        self
+    }
+
+    /// Sign a request body with the webhook's secret, if it has one
+    async fn signature(&self, config: &WebhookConfig, body: &str) -> Result<Option<String>> {
+        let Some(secret_ref) = &config.secret else {
+            return Ok(None);
+        };
+        let resolver = self.secret_resolver.as_ref().ok_or_else(|| {
+            anyhow::anyhow!("Webhook {} has a signing secret but no secret resolver is configured", config.url)
+        })?;
+
+        // The resolved value is dropped as soon as the signature is computed
+        let secret = resolver.resolve(secret_ref).await?;
+        Ok(Some(sign(secret.expose().as_bytes(), body)))
+    }
+
     /// Dispatch event to all configured webhooks
     pub async fn dispatch(&self, event: &SchemaEvent) -> Result<()> {
         info!(
@@ -45,24 +72,28 @@ impl WebhookDispatcher {
     }
 
     /// Dispatch to a single webhook with retry
+    ///
+    /// Connection failures, timeouts and `5xx`/`429` responses are retried;
+    /// other `4xx` responses fail at once.
     async fn dispatch_to_webhook(&self, event: &SchemaEvent, config: &WebhookConfig) -> Result<()> {
-        let retry_strategy = ExponentialBackoff::from_millis(500)
-            .max_delay(Duration::from_secs(5))
-            .take(config.max_retries as usize);
+        let backoff = RetryRule::backoff(
+            config.max_retries.saturating_add(1),
+            Duration::from_millis(500),
+            Duration::from_secs(5),
+        );
+        let policy = RetryPolicy::new("webhook_delivery")
+            .with_rule(ErrorClass::Transient, backoff)
+            .with_rule(ErrorClass::RateLimited, backoff);
 
-        let client = self.client.clone();
-        let url = config.url.clone();
         let event_json = serde_json::to_string(event)?;
-        let headers = config.headers.clone();
-
-        let result = Retry::spawn(retry_strategy, move || {
-            let client = client.clone();
-            let url = url.clone();
-            let body = event_json.clone();
-            let headers = headers.clone();
+        let mut headers = config.headers.clone();
+        if let Some(signature) = self.signature(config, &event_json).await? {
+            headers.insert(SIGNATURE_HEADER.to_string(), signature);
+        }
 
-            async move {
-                let mut request = client.post(&url);
+        let result = policy
+            .retry(|| async {
+                let mut request = self.client.post(&config.url);
 
                 // Add custom headers
                 for (key, value) in &headers {
@@ -71,18 +102,25 @@ impl WebhookDispatcher {
 
                 let response = request
                     .header("Content-Type", "application/json")
-                    .body(body.clone())
+                    .body(event_json.clone())
                     .send()
                     .await
-                    .map_err(|e| anyhow::anyhow!("HTTP request failed: {}", e))?;
-
-                if !response.status().is_success() {
-                    anyhow::bail!("Webhook returned error status: {}", response.status());
+                    .map_err(DeliveryError::Request)?;
+
+                let status = response.status();
+                if !status.is_success() {
+                    let retry_after = response
+                        .headers()
+                        .get(reqwest::header::RETRY_AFTER)
+                        .and_then(|v| v.to_str().ok())
+                        .and_then(|v| v.parse::<u64>().ok())
+                        .map(Duration::from_secs);
+                    return Err(DeliveryError::Status { status, retry_after });
                 }
 
-                Ok::<(), anyhow::Error>(())
-            }
-        }).await;
+                Ok(())
+            })
+            .await;
 
         match result {
             Ok(_) => {
@@ -95,7 +133,7 @@ impl WebhookDispatcher {
                     error = %e,
                     "Webhook delivery failed after all retries"
                 );
-                Err(e)
+                Err(e.into())
             }
         }
     }
@@ -118,12 +156,51 @@ impl WebhookDispatcher {
     }
 }
 
+/// Why one delivery attempt failed
+#[derive(Debug, thiserror::Error)]
+enum DeliveryError {
+    #[error("HTTP request failed: {0}")]
+    Request(reqwest::Error),
+
+    #[error("Webhook returned error status: {status}")]
+    Status {
+        status: reqwest::StatusCode,
+        retry_after: Option<Duration>,
+    },
+}
+
+impl Classify for DeliveryError {
+    fn class(&self) -> ErrorClass {
+        match self {
+            DeliveryError::Request(e) if e.is_builder() => ErrorClass::Permanent,
+            DeliveryError::Request(_) => ErrorClass::Transient,
+            DeliveryError::Status { status, .. } => ErrorClass::from_http_status(status.as_u16()),
+        }
+    }
+
+    fn retry_after(&self) -> Option<Duration> {
+        match self {
+            DeliveryError::Status { retry_after, .. } => *retry_after,
+            DeliveryError::Request(_) => None,
+        }
+    }
+}
+
+/// `sha256=<hex HMAC-SHA256 of body>`
+fn sign(secret: &[u8], body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(body.as_bytes());
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use uuid::Uuid;
     use wiremock::{MockServer, Mock, ResponseTemplate};
-    use wiremock::matchers::{method, path};
+    use async_trait::async_trait;
+    use schema_registry_core::secret_ref::{SecretRef, SecretValue};
+    use wiremock::matchers::{header, header_exists, method, path};
 
     #[tokio::test]
     async fn test_webhook_dispatch_success() {
@@ -152,6 +229,64 @@ mod tests {
         assert!(dispatcher.dispatch(&event).await.is_ok());
     }
 
+    struct FixedResolver;
+
+    #[async_trait]
+    impl SecretResolver for FixedResolver {
+        async fn resolve(&self, secret: &SecretRef) -> schema_registry_core::Result<SecretValue> {
+            assert_eq!(secret.name(), "webhooks/rag");
+            Ok(SecretValue::new("whsec_test"))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_webhook_dispatch_signed() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/webhook"))
+            .and(header_exists(SIGNATURE_HEADER))
+            .and(header("X-Api-Key", "k"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let config = WebhookConfig {
+            url: format!("{}/webhook", mock_server.uri()),
+            headers: [("X-Api-Key".to_string(), "k".to_string())].into(),
+            secret: Some(SecretRef::new("webhooks/rag")),
+            ..Default::default()
+        };
+        assert!(!format!("{:?}", config).contains("\"k\""));
+        assert_eq!(config.redacted().headers["X-Api-Key"], schema_registry_core::secret_ref::REDACTED);
+
+        let event = SchemaEvent::registered(
+            Uuid::new_v4(),
+            "test".to_string(),
+            "User".to_string(),
+            "1.0.0".to_string(),
+        );
+
+        // Without a resolver the secret cannot be used, so nothing is sent unsigned
+        let unsigned = WebhookDispatcher::new(vec![config.clone()]).unwrap();
+        assert!(unsigned.dispatch(&event).await.is_err());
+
+        let dispatcher = WebhookDispatcher::new(vec![config])
+            .unwrap()
+            .with_secret_resolver(Arc::new(FixedResolver));
+        assert!(dispatcher.dispatch(&event).await.is_ok());
+    }
+
+    #[test]
+    fn test_sign() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            sign(b"Jefe", "what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
     #[tokio::test]
     async fn test_webhook_dispatch_retry() {
         let mock_server = MockServer::start().await;
@@ -187,4 +322,33 @@ mod tests {
 
         assert!(dispatcher.dispatch(&event).await.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_webhook_client_errors_are_not_retried() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/webhook"))
+            .respond_with(ResponseTemplate::new(400))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let config = WebhookConfig {
+            url: format!("{}/webhook", mock_server.uri()),
+            max_retries: 3,
+            ..Default::default()
+        };
+
+        let dispatcher = WebhookDispatcher::new(vec![config]).unwrap();
+
+        let event = SchemaEvent::registered(
+            Uuid::new_v4(),
+            "test".to_string(),
+            "User".to_string(),
+            "1.0.0".to_string(),
+        );
+
+        assert!(dispatcher.dispatch(&event).await.is_err());
+    }
 }
@@ -4,11 +4,12 @@ pub mod dispatcher;
 
 pub use dispatcher::*;
 
+use schema_registry_core::secret_ref::{redact_map, SecretRef};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// Webhook configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct WebhookConfig {
     /// Webhook URL
     pub url: String,
@@ -22,8 +23,30 @@ pub struct WebhookConfig {
     /// Timeout in seconds (default: 10)
     pub timeout_secs: u64,
 
-    /// Secret for HMAC signature verification
-    pub secret: Option<String>,
+    /// Secrets manager reference to the HMAC signing secret, resolved at dispatch time
+    pub secret: Option<SecretRef>,
+}
+
+impl WebhookConfig {
+    /// Copy safe to return from APIs and include in exports
+    pub fn redacted(&self) -> Self {
+        Self {
+            headers: redact_map(&self.headers),
+            ..self.clone()
+        }
+    }
+}
+
+impl std::fmt::Debug for WebhookConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebhookConfig")
+            .field("url", &self.url)
+            .field("headers", &redact_map(&self.headers))
+            .field("max_retries", &self.max_retries)
+            .field("timeout_secs", &self.timeout_secs)
+            .field("secret", &self.secret)
+            .finish()
+    }
 }
 
 impl Default for WebhookConfig {
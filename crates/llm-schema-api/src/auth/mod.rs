@@ -67,6 +67,7 @@ pub enum Permission {
     AdminAccess,
     UserManagement,
     ConfigManagement,
+    SecurityReview,
 
     // Metrics and monitoring
     MetricsRead,
@@ -89,6 +90,7 @@ impl Permission {
             Permission::AdminAccess => "admin:access",
             Permission::UserManagement => "admin:users",
             Permission::ConfigManagement => "admin:config",
+            Permission::SecurityReview => "security:review",
             Permission::MetricsRead => "metrics:read",
             Permission::HealthCheck => "health:check",
         }
@@ -109,6 +111,7 @@ impl Permission {
             "admin:access" => Some(Permission::AdminAccess),
             "admin:users" => Some(Permission::UserManagement),
             "admin:config" => Some(Permission::ConfigManagement),
+            "security:review" => Some(Permission::SecurityReview),
             "metrics:read" => Some(Permission::MetricsRead),
             "health:check" => Some(Permission::HealthCheck),
             _ => None,
@@ -143,6 +146,7 @@ impl Role {
                 Permission::AdminAccess,
                 Permission::UserManagement,
                 Permission::ConfigManagement,
+                Permission::SecurityReview,
                 Permission::MetricsRead,
                 Permission::HealthCheck,
             ]),
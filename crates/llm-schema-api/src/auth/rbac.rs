@@ -28,6 +28,7 @@ impl RbacEnforcer {
                 Permission::AdminAccess,
                 Permission::UserManagement,
                 Permission::ConfigManagement,
+                Permission::SecurityReview,
                 Permission::MetricsRead,
                 Permission::HealthCheck,
             ],
@@ -0,0 +1,286 @@
+//! Wire format of the Confluent Schema Registry REST API
+//!
+//! Kafka serializers and tools written for Confluent Schema Registry call
+//! `/subjects/{subject}/versions`, `/schemas/ids/{id}` and `/config/{subject}`
+//! and parse the bodies defined here. The server serves those paths from its
+//! own storage; this module is what such a client sees of them: request and
+//! response bodies, error codes, version ids and 4-byte schema IDs.
+
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use schema_registry_core::types::SerializationFormat;
+use serde::{Deserialize, Serialize};
+
+/// Content type of Confluent Schema Registry responses
+pub const CONTENT_TYPE: &str = "application/vnd.schemaregistry.v1+json";
+
+/// An error in the shape Confluent clients parse, e.g.
+/// `{"error_code": 40401, "message": "Subject 'orders-value' not found."}`
+#[derive(Debug)]
+pub struct ConfluentError {
+    pub status: StatusCode,
+    pub error_code: u32,
+    pub message: String,
+}
+
+impl ConfluentError {
+    pub fn new(status: StatusCode, error_code: u32, message: String) -> Self {
+        Self {
+            status,
+            error_code,
+            message,
+        }
+    }
+
+    pub fn subject_not_found(subject: &str) -> Self {
+        Self::new(
+            StatusCode::NOT_FOUND,
+            40401,
+            format!("Subject '{}' not found.", subject),
+        )
+    }
+
+    pub fn version_not_found(version: &str) -> Self {
+        Self::new(
+            StatusCode::NOT_FOUND,
+            40402,
+            format!("Version {} not found.", version),
+        )
+    }
+
+    pub fn schema_not_found() -> Self {
+        Self::new(StatusCode::NOT_FOUND, 40403, "Schema not found".to_string())
+    }
+
+    pub fn no_subject_config(subject: &str) -> Self {
+        Self::new(
+            StatusCode::NOT_FOUND,
+            40408,
+            format!(
+                "Subject '{}' does not have subject-level compatibility configured",
+                subject
+            ),
+        )
+    }
+
+    pub fn invalid_schema(message: String) -> Self {
+        Self::new(StatusCode::UNPROCESSABLE_ENTITY, 42201, message)
+    }
+
+    pub fn invalid_version(version: &str) -> Self {
+        Self::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            42202,
+            format!(
+                "The specified version '{}' is not a valid version id. Allowed values are between [1, 2^31-1] and the string \"latest\"",
+                version
+            ),
+        )
+    }
+
+    pub fn invalid_compatibility(level: &str) -> Self {
+        Self::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            42203,
+            format!("Invalid compatibility level '{}'", level),
+        )
+    }
+}
+
+impl IntoResponse for ConfluentError {
+    fn into_response(self) -> Response {
+        let body = serde_json::json!({
+            "error_code": self.error_code,
+            "message": self.message,
+        });
+        (self.status, [("content-type", CONTENT_TYPE)], Json(body)).into_response()
+    }
+}
+
+/// A response body with the content type Confluent clients expect
+pub fn json<T: Serialize>(body: T) -> Response {
+    ([("content-type", CONTENT_TYPE)], Json(body)).into_response()
+}
+
+/// The schema ID serializers write in front of each message
+///
+/// The wire format has 4 bytes for it, so a global ID past `i32::MAX` can't
+/// be handed to a serializer. Storage refuses such IDs; this is the check
+/// at the edge.
+pub fn wire_id(global_id: i64) -> Result<i32, ConfluentError> {
+    i32::try_from(global_id)
+        .ok()
+        .filter(|id| *id > 0)
+        .ok_or_else(|| {
+            ConfluentError::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                50001,
+                format!(
+                    "Schema ID {} does not fit the 4-byte ID of the wire format",
+                    global_id
+                ),
+            )
+        })
+}
+
+/// The `schemaType` of a stored format, left out for Avro as Confluent does
+pub fn schema_type(format: &str) -> Option<String> {
+    (format != "AVRO").then(|| format.to_string())
+}
+
+/// A version named in a path: a number, or `latest` (also `-1`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VersionId {
+    Latest,
+    Number(i32),
+}
+
+impl VersionId {
+    pub fn parse(version: &str) -> Result<Self, ConfluentError> {
+        match version {
+            "latest" | "-1" => Ok(Self::Latest),
+            number => number
+                .parse()
+                .ok()
+                .filter(|n| *n >= 1)
+                .map(Self::Number)
+                .ok_or_else(|| ConfluentError::invalid_version(version)),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfluentSchemaRequest {
+    pub schema: String,
+    /// `AVRO` when not given, as in Confluent
+    #[serde(default)]
+    pub schema_type: Option<String>,
+    #[serde(default)]
+    pub references: Vec<serde_json::Value>,
+}
+
+impl ConfluentSchemaRequest {
+    /// The stored format name, which is also the Confluent `schemaType`
+    pub fn format(&self) -> Result<(&'static str, SerializationFormat), ConfluentError> {
+        match self.schema_type.as_deref().unwrap_or("AVRO") {
+            "AVRO" => Ok(("AVRO", SerializationFormat::Avro)),
+            "JSON" => Ok(("JSON", SerializationFormat::JsonSchema)),
+            "PROTOBUF" => Ok(("PROTOBUF", SerializationFormat::Protobuf)),
+            other => Err(ConfluentError::invalid_schema(format!(
+                "Unknown schema type '{}'",
+                other
+            ))),
+        }
+    }
+}
+
+/// A version of a subject
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfluentSchema {
+    pub subject: String,
+    pub id: i32,
+    pub version: i32,
+    /// Left out for Avro, as Confluent does
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema_type: Option<String>,
+    pub schema: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfluentSchemaById {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema_type: Option<String>,
+    pub schema: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ConfluentCompatibilityQuery {
+    #[serde(default)]
+    pub verbose: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ConfluentConfigQuery {
+    #[serde(default, rename = "defaultToGlobal")]
+    pub default_to_global: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ConfluentConfigRequest {
+    pub compatibility: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_ids() {
+        assert_eq!(VersionId::parse("latest").unwrap(), VersionId::Latest);
+        assert_eq!(VersionId::parse("-1").unwrap(), VersionId::Latest);
+        assert_eq!(VersionId::parse("7").unwrap(), VersionId::Number(7));
+        assert_eq!(
+            VersionId::parse("2147483647").unwrap(),
+            VersionId::Number(i32::MAX)
+        );
+        for invalid in ["0", "-2", "2147483648", "1.0.0", "first"] {
+            let error = VersionId::parse(invalid).unwrap_err();
+            assert_eq!(error.error_code, 42202, "{}", invalid);
+            assert_eq!(error.status, StatusCode::UNPROCESSABLE_ENTITY);
+        }
+    }
+
+    #[test]
+    fn test_wire_ids_fit_four_bytes() {
+        assert_eq!(wire_id(1).unwrap(), 1);
+        assert_eq!(wire_id(i64::from(i32::MAX)).unwrap(), i32::MAX);
+        assert_eq!(
+            wire_id(i64::from(i32::MAX) + 1).unwrap_err().error_code,
+            50001
+        );
+        assert!(wire_id(0).is_err());
+    }
+
+    #[test]
+    fn test_schema_requests_default_to_avro() {
+        let req: ConfluentSchemaRequest =
+            serde_json::from_str(r#"{"schema": "\"string\""}"#).unwrap();
+        assert_eq!(req.format().unwrap().0, "AVRO");
+        assert_eq!(schema_type("AVRO"), None);
+
+        let req: ConfluentSchemaRequest =
+            serde_json::from_str(r#"{"schema": "{}", "schemaType": "JSON"}"#).unwrap();
+        assert_eq!(req.format().unwrap().1, SerializationFormat::JsonSchema);
+        assert_eq!(schema_type("JSON").as_deref(), Some("JSON"));
+
+        let req: ConfluentSchemaRequest =
+            serde_json::from_str(r#"{"schema": "{}", "schemaType": "XML"}"#).unwrap();
+        assert_eq!(req.format().unwrap_err().error_code, 42201);
+    }
+
+    #[test]
+    fn test_schema_serializes_like_confluent() {
+        let schema = ConfluentSchema {
+            subject: "orders-value".to_string(),
+            id: 3,
+            version: 2,
+            schema_type: schema_type("AVRO"),
+            schema: "\"string\"".to_string(),
+        };
+        assert_eq!(
+            serde_json::to_value(&schema).unwrap(),
+            serde_json::json!({
+                "subject": "orders-value",
+                "id": 3,
+                "version": 2,
+                "schema": "\"string\"",
+            })
+        );
+    }
+}
@@ -74,11 +74,16 @@ pub struct RegisterSchemaResponse {
     pub checksum: ::prost::alloc::string::String,
 }
 /// Schema Retrieval
+///
+/// A read_mask naming SchemaInfo fields (`id`, `version`, `state`, `metadata`)
+/// returns only those fields; without one every field is returned.
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GetSchemaRequest {
     #[prost(string, tag = "1")]
     pub schema_id: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "2")]
+    pub read_mask: ::core::option::Option<::prost_types::FieldMask>,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -87,6 +92,8 @@ pub struct GetSchemaByVersionRequest {
     pub subject: ::prost::alloc::string::String,
     #[prost(string, tag = "2")]
     pub version: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "3")]
+    pub read_mask: ::core::option::Option<::prost_types::FieldMask>,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -107,6 +114,8 @@ pub struct ListSchemasRequest {
     pub offset: ::core::option::Option<i32>,
     #[prost(enumeration = "SchemaState", optional, tag = "5")]
     pub state: ::core::option::Option<i32>,
+    #[prost(message, optional, tag = "6")]
+    pub read_mask: ::core::option::Option<::prost_types::FieldMask>,
 }
 /// Schema Metadata Updates
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -166,6 +175,8 @@ pub struct ListVersionsResponse {
 pub struct GetLatestVersionRequest {
     #[prost(string, tag = "1")]
     pub subject: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "2")]
+    pub read_mask: ::core::option::Option<::prost_types::FieldMask>,
 }
 /// Validation
 #[allow(clippy::derive_partial_eq_without_eq)]
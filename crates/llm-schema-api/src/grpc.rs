@@ -1 +1,381 @@
+//! gRPC transport: message size limits and compression
+//!
+//! Descriptor sets and large schemas outgrow tonic's 4 MiB default, so code
+//! hosting [`SchemaRegistryServer`] or calling it with the generated client
+//! takes its limits and codecs from a [`GrpcTransport`]. The registry server
+//! itself only serves REST and GraphQL, and the Rust SDK calls REST, so
+//! neither goes through this module. Compression is negotiated per call:
+//! responses are only compressed with an encoding the client listed in
+//! `grpc-accept-encoding`.
+//!
+//! A message over the limit is refused with `RESOURCE_EXHAUSTED`, carrying
+//! its size and the limit in the `x-message-bytes` and `x-max-message-bytes`
+//! metadata, rather than tonic's bare `OUT_OF_RANGE`.
+
+/// Generated messages, client and server of `schema_registry.v1`
+pub mod v1 {
+    #![allow(clippy::all)]
+    include!("generated/schema_registry.v1.rs");
+}
+
+use serde::{Deserialize, Serialize};
+use tonic::codec::CompressionEncoding;
+use tonic::codegen::{http, Body, Bytes, StdError};
+use tonic::metadata::MetadataValue;
+use tonic::{Code, Status};
+use tower::util::MapResponseLayer;
+use v1::schema_registry_client::SchemaRegistryClient;
+use v1::schema_registry_server::{SchemaRegistry, SchemaRegistryServer};
+
 pub struct GrpcApi;
+
+/// Largest message either end decodes or encodes by default
+pub const DEFAULT_MAX_MESSAGE_BYTES: usize = 16 * 1024 * 1024;
+
+/// Metadata key holding the size of a refused message
+pub const MESSAGE_BYTES_KEY: &str = "x-message-bytes";
+
+/// Metadata key holding the limit a refused message exceeded
+pub const MAX_MESSAGE_BYTES_KEY: &str = "x-max-message-bytes";
+
+/// A message compression codec
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    fn encoding(self) -> CompressionEncoding {
+        match self {
+            Compression::Gzip => CompressionEncoding::Gzip,
+            Compression::Zstd => CompressionEncoding::Zstd,
+        }
+    }
+}
+
+/// Size limits and codecs of a gRPC endpoint
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GrpcTransport {
+    /// Largest message accepted, after decompression
+    pub max_decoding_message_bytes: usize,
+    /// Largest message sent, before compression
+    pub max_encoding_message_bytes: usize,
+    /// Codecs accepted and offered, most preferred first; clients compress
+    /// requests with the first. Empty turns compression off.
+    pub compression: Vec<Compression>,
+}
+
+impl Default for GrpcTransport {
+    fn default() -> Self {
+        Self {
+            max_decoding_message_bytes: DEFAULT_MAX_MESSAGE_BYTES,
+            max_encoding_message_bytes: DEFAULT_MAX_MESSAGE_BYTES,
+            compression: vec![Compression::Zstd, Compression::Gzip],
+        }
+    }
+}
+
+impl GrpcTransport {
+    /// Serve `service` with these limits and codecs
+    ///
+    /// Wrap the server in [`GrpcTransport::size_error_layer`] to
+    /// refuse oversized requests with a structured status.
+    pub fn server<T: SchemaRegistry>(&self, service: T) -> SchemaRegistryServer<T> {
+        let mut server = SchemaRegistryServer::new(service)
+            .max_decoding_message_size(self.max_decoding_message_bytes)
+            .max_encoding_message_size(self.max_encoding_message_bytes);
+        for compression in &self.compression {
+            server = server
+                .accept_compressed(compression.encoding())
+                .send_compressed(compression.encoding());
+        }
+        server
+    }
+
+    /// Apply these limits and codecs to `client`
+    pub fn client<T>(&self, client: SchemaRegistryClient<T>) -> SchemaRegistryClient<T>
+    where
+        T: tonic::client::GrpcService<tonic::body::BoxBody>,
+        T::Error: Into<StdError>,
+        T::ResponseBody: Body<Data = Bytes> + Send + 'static,
+        <T::ResponseBody as Body>::Error: Into<StdError> + Send,
+    {
+        let mut client = client
+            .max_decoding_message_size(self.max_decoding_message_bytes)
+            .max_encoding_message_size(self.max_encoding_message_bytes);
+        for compression in &self.compression {
+            client = client.accept_compressed(compression.encoding());
+        }
+        match self.compression.first() {
+            Some(compression) => client.send_compressed(compression.encoding()),
+            None => client,
+        }
+    }
+
+    /// Connect to `endpoint` with these limits and codecs
+    pub async fn connect<D>(
+        &self,
+        endpoint: D,
+    ) -> Result<SchemaRegistryClient<tonic::transport::Channel>, tonic::transport::Error>
+    where
+        D: TryInto<tonic::transport::Endpoint>,
+        D::Error: Into<StdError>,
+    {
+        Ok(self.client(SchemaRegistryClient::connect(endpoint).await?))
+    }
+
+    /// Layer turning size-limit refusals into structured statuses
+    pub fn size_error_layer<B>() -> MapResponseLayer<fn(http::Response<B>) -> http::Response<B>> {
+        MapResponseLayer::new(restructure_size_error::<B> as fn(_) -> _)
+    }
+}
+
+/// Replace tonic's refusal of an oversized message with [`size_error`]
+fn restructure_size_error<B>(mut response: http::Response<B>) -> http::Response<B> {
+    // Refusals end the call at once, so their status is in the headers
+    let Some(structured) = Status::from_header_map(response.headers())
+        .as_ref()
+        .and_then(size_error)
+    else {
+        return response;
+    };
+    let headers = response.headers_mut();
+    headers.remove("grpc-status");
+    headers.remove("grpc-message");
+    headers.remove("grpc-status-details-bin");
+    if let Err(status) = structured.add_header(headers) {
+        tracing::warn!(error = %status, "Failed to encode size-limit status");
+    }
+    response
+}
+
+/// The structured status for a message refused for its size, if `status` is
+/// tonic's refusal of one
+///
+/// Clients can call this on the status of a failed call to get the same
+/// metadata for messages their own limits refused.
+pub fn size_error(status: &Status) -> Option<Status> {
+    if status.code() != Code::OutOfRange {
+        return None;
+    }
+    // tonic reports "... message length too large: found N bytes, the limit is: M bytes"
+    let (_, sizes) = status.message().split_once("message length too large: found ")?;
+    let (found, rest) = sizes.split_once(" bytes")?;
+    let limit = rest.split_once("the limit is: ")?.1.split_once(" bytes")?.0;
+    let (found, limit) = (found.parse::<usize>().ok()?, limit.parse::<usize>().ok()?);
+
+    let mut structured = Status::resource_exhausted(format!(
+        "Message of {} bytes exceeds the {} byte limit; enable gzip or zstd compression or raise the limit",
+        found, limit
+    ));
+    let metadata = structured.metadata_mut();
+    metadata.insert(MESSAGE_BYTES_KEY, MetadataValue::from(found as u64));
+    metadata.insert(MAX_MESSAGE_BYTES_KEY, MetadataValue::from(limit as u64));
+    Some(structured)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_size_refusals_become_structured() {
+        let refused = Status::out_of_range(
+            "Error, message length too large: found 20971520 bytes, the limit is: 16777216 bytes",
+        );
+        let structured = size_error(&refused).unwrap();
+        assert_eq!(structured.code(), Code::ResourceExhausted);
+        assert_eq!(
+            structured.metadata().get(MESSAGE_BYTES_KEY).unwrap(),
+            "20971520"
+        );
+        assert_eq!(
+            structured.metadata().get(MAX_MESSAGE_BYTES_KEY).unwrap(),
+            "16777216"
+        );
+
+        // Other statuses pass through
+        assert!(size_error(&Status::out_of_range("Page 3 of 2")).is_none());
+        assert!(size_error(&Status::not_found("Schema not found")).is_none());
+    }
+
+    /// Answers every call with `UNIMPLEMENTED`
+    struct Unimplemented;
+
+    type EmptyStream<T> = tonic::codegen::tokio_stream::Empty<Result<T, Status>>;
+
+    #[tonic::async_trait]
+    impl SchemaRegistry for Unimplemented {
+        type ListSchemasStream = EmptyStream<v1::SchemaInfo>;
+        type BatchValidateStream = EmptyStream<v1::ValidationReport>;
+        type BatchCheckCompatibilityStream = EmptyStream<v1::CompatibilityReport>;
+        type StreamSchemaChangesStream = EmptyStream<v1::SchemaChangeEvent>;
+
+        async fn register_schema(
+            &self,
+            _: tonic::Request<v1::RegisterSchemaRequest>,
+        ) -> Result<tonic::Response<v1::RegisterSchemaResponse>, Status> {
+            Err(Status::unimplemented("register_schema"))
+        }
+        async fn get_schema(
+            &self,
+            _: tonic::Request<v1::GetSchemaRequest>,
+        ) -> Result<tonic::Response<v1::GetSchemaResponse>, Status> {
+            Err(Status::unimplemented("get_schema"))
+        }
+        async fn get_schema_by_version(
+            &self,
+            _: tonic::Request<v1::GetSchemaByVersionRequest>,
+        ) -> Result<tonic::Response<v1::GetSchemaResponse>, Status> {
+            Err(Status::unimplemented("get_schema_by_version"))
+        }
+        async fn list_schemas(
+            &self,
+            _: tonic::Request<v1::ListSchemasRequest>,
+        ) -> Result<tonic::Response<Self::ListSchemasStream>, Status> {
+            Err(Status::unimplemented("list_schemas"))
+        }
+        async fn update_schema_metadata(
+            &self,
+            _: tonic::Request<v1::UpdateSchemaMetadataRequest>,
+        ) -> Result<tonic::Response<v1::UpdateSchemaMetadataResponse>, Status> {
+            Err(Status::unimplemented("update_schema_metadata"))
+        }
+        async fn delete_schema(
+            &self,
+            _: tonic::Request<v1::DeleteSchemaRequest>,
+        ) -> Result<tonic::Response<()>, Status> {
+            Err(Status::unimplemented("delete_schema"))
+        }
+        async fn list_versions(
+            &self,
+            _: tonic::Request<v1::ListVersionsRequest>,
+        ) -> Result<tonic::Response<v1::ListVersionsResponse>, Status> {
+            Err(Status::unimplemented("list_versions"))
+        }
+        async fn get_latest_version(
+            &self,
+            _: tonic::Request<v1::GetLatestVersionRequest>,
+        ) -> Result<tonic::Response<v1::GetSchemaResponse>, Status> {
+            Err(Status::unimplemented("get_latest_version"))
+        }
+        async fn validate_data(
+            &self,
+            _: tonic::Request<v1::ValidateDataRequest>,
+        ) -> Result<tonic::Response<v1::ValidationReport>, Status> {
+            Err(Status::unimplemented("validate_data"))
+        }
+        async fn validate_schema(
+            &self,
+            _: tonic::Request<v1::ValidateSchemaRequest>,
+        ) -> Result<tonic::Response<v1::SchemaValidationReport>, Status> {
+            Err(Status::unimplemented("validate_schema"))
+        }
+        async fn batch_validate(
+            &self,
+            _: tonic::Request<tonic::Streaming<v1::ValidateDataRequest>>,
+        ) -> Result<tonic::Response<Self::BatchValidateStream>, Status> {
+            Err(Status::unimplemented("batch_validate"))
+        }
+        async fn check_compatibility(
+            &self,
+            _: tonic::Request<v1::CompatibilityCheckRequest>,
+        ) -> Result<tonic::Response<v1::CompatibilityReport>, Status> {
+            Err(Status::unimplemented("check_compatibility"))
+        }
+        async fn batch_check_compatibility(
+            &self,
+            _: tonic::Request<tonic::Streaming<v1::CompatibilityCheckRequest>>,
+        ) -> Result<tonic::Response<Self::BatchCheckCompatibilityStream>, Status> {
+            Err(Status::unimplemented("batch_check_compatibility"))
+        }
+        async fn search_schemas(
+            &self,
+            _: tonic::Request<v1::SearchSchemasRequest>,
+        ) -> Result<tonic::Response<v1::SearchSchemasResponse>, Status> {
+            Err(Status::unimplemented("search_schemas"))
+        }
+        async fn get_dependencies(
+            &self,
+            _: tonic::Request<v1::GetDependenciesRequest>,
+        ) -> Result<tonic::Response<v1::DependenciesResponse>, Status> {
+            Err(Status::unimplemented("get_dependencies"))
+        }
+        async fn get_dependents(
+            &self,
+            _: tonic::Request<v1::GetDependentsRequest>,
+        ) -> Result<tonic::Response<v1::DependenciesResponse>, Status> {
+            Err(Status::unimplemented("get_dependents"))
+        }
+        async fn list_subjects(
+            &self,
+            _: tonic::Request<v1::ListSubjectsRequest>,
+        ) -> Result<tonic::Response<v1::ListSubjectsResponse>, Status> {
+            Err(Status::unimplemented("list_subjects"))
+        }
+        async fn get_subject_versions(
+            &self,
+            _: tonic::Request<v1::GetSubjectVersionsRequest>,
+        ) -> Result<tonic::Response<v1::GetSubjectVersionsResponse>, Status> {
+            Err(Status::unimplemented("get_subject_versions"))
+        }
+        async fn stream_schema_changes(
+            &self,
+            _: tonic::Request<v1::StreamRequest>,
+        ) -> Result<tonic::Response<Self::StreamSchemaChangesStream>, Status> {
+            Err(Status::unimplemented("stream_schema_changes"))
+        }
+        async fn health_check(
+            &self,
+            _: tonic::Request<()>,
+        ) -> Result<tonic::Response<v1::HealthCheckResponse>, Status> {
+            Err(Status::unimplemented("health_check"))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_oversized_requests_are_refused_through_the_layer() {
+        use tower::ServiceExt;
+
+        let transport = GrpcTransport {
+            max_decoding_message_bytes: 1024,
+            ..Default::default()
+        };
+        let service = tower::ServiceBuilder::new()
+            .layer(GrpcTransport::size_error_layer())
+            .service(transport.server(Unimplemented));
+
+        // An uncompressed frame declaring a 2 KiB message
+        let mut frame = vec![0u8];
+        frame.extend_from_slice(&2048u32.to_be_bytes());
+        frame.resize(5 + 2048, 0);
+        let request = http::Request::builder()
+            .method("POST")
+            .uri("/schema_registry.v1.SchemaRegistry/RegisterSchema")
+            .header("content-type", "application/grpc")
+            .header("te", "trailers")
+            .body(tonic::transport::Body::from(frame))
+            .unwrap();
+
+        let response = service.oneshot(request).await.unwrap();
+        let status = Status::from_header_map(response.headers()).unwrap();
+        assert_eq!(status.code(), Code::ResourceExhausted, "{}", status);
+        assert_eq!(status.metadata().get(MESSAGE_BYTES_KEY).unwrap(), "2048");
+        assert_eq!(status.metadata().get(MAX_MESSAGE_BYTES_KEY).unwrap(), "1024");
+    }
+
+    #[test]
+    fn test_transport_defaults_negotiate_both_codecs() {
+        let transport: GrpcTransport =
+            serde_json::from_str(r#"{"max_decoding_message_bytes": 1048576}"#).unwrap();
+        assert_eq!(transport.max_decoding_message_bytes, 1024 * 1024);
+        assert_eq!(transport.max_encoding_message_bytes, DEFAULT_MAX_MESSAGE_BYTES);
+        assert_eq!(
+            transport.compression,
+            [Compression::Zstd, Compression::Gzip]
+        );
+    }
+}
@@ -1,6 +1,7 @@
 //! API layer: REST (Axum) and gRPC (Tonic)
 pub mod rest;
 pub mod grpc;
+pub mod confluent;
 
 pub struct ApiServer {}
 impl ApiServer {
@@ -3,6 +3,7 @@ use axum::{
     response::{IntoResponse, Response},
     Json,
 };
+use schema_registry_core::canonical_json;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 use utoipa::ToSchema;
@@ -11,6 +12,7 @@ use utoipa::ToSchema;
 pub struct ApiError {
     pub error_code: String,
     pub message: String,
+    #[serde(serialize_with = "canonical_json::optional_value")]
     pub details: Option<serde_json::Value>,
     pub request_id: Option<String>,
 }
@@ -1,4 +1,5 @@
 use chrono::{DateTime, Utc};
+use schema_registry_core::canonical_json;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use utoipa::ToSchema;
@@ -85,7 +86,7 @@ pub struct SchemaMetadata {
 
     pub compatibility_level: CompatibilityLevel,
 
-    #[serde(flatten)]
+    #[serde(flatten, serialize_with = "canonical_json::sorted_value_map")]
     pub custom: HashMap<String, serde_json::Value>,
 }
 
@@ -102,7 +103,10 @@ pub struct SchemaInfo {
 
     pub schema_type: SchemaType,
 
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "canonical_json::optional_value"
+    )]
     pub schema_content: Option<serde_json::Value>,
 
     pub metadata: SchemaMetadata,
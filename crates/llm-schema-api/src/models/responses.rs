@@ -1,5 +1,6 @@
 use super::*;
 use chrono::{DateTime, Utc};
+use schema_registry_core::canonical_json;
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 use uuid::Uuid;
@@ -150,6 +151,7 @@ pub struct ListSubjectsResponse {
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct HealthCheckResponse {
     pub status: HealthStatus,
+    #[serde(serialize_with = "canonical_json::sorted_map")]
     pub components: HashMap<String, ComponentHealth>,
     pub version: String,
     pub timestamp: DateTime<Utc>,
@@ -167,6 +169,7 @@ pub enum HealthStatus {
 pub struct ComponentHealth {
     pub status: ComponentStatus,
     pub message: Option<String>,
+    #[serde(serialize_with = "canonical_json::sorted_map")]
     pub details: HashMap<String, String>,
 }
 
@@ -189,6 +192,7 @@ pub struct SchemaChangeEvent {
     pub subject: String,
     pub version: String,
     pub timestamp: DateTime<Utc>,
+    #[serde(serialize_with = "canonical_json::sorted_map")]
     pub metadata: HashMap<String, String>,
     pub changed_by: Option<String>,
 }
@@ -156,7 +156,9 @@ impl AggregationData {
                     count: data.count,
                     success_count: data.success_count,
                     avg_latency_ms: avg,
+                    p50_latency_ms: percentile(&data.latencies, 50),
                     p95_latency_ms: percentile(&data.latencies, 95),
+                    p99_latency_ms: percentile(&data.latencies, 99),
                 };
                 (*op, stats)
             })
@@ -0,0 +1,150 @@
+//! Schema size and complexity aggregation
+//!
+//! Collects the complexity metrics measured at registration so they can be
+//! summarised across the registry and the largest schemas can be found.
+
+use crate::types::SchemaId;
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use schema_registry_core::complexity::SchemaComplexity;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Complexity of one registered schema version
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SchemaComplexityEntry {
+    pub schema_id: SchemaId,
+    /// Subject the version belongs to
+    pub subject: String,
+    pub complexity: SchemaComplexity,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// Registry-wide complexity summary
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ComplexitySummary {
+    pub schema_count: usize,
+    pub total_size_bytes: u64,
+    pub avg_size_bytes: f64,
+    pub avg_field_count: f64,
+    pub max_field_count: u32,
+    pub max_depth: u32,
+    pub avg_constraint_count: f64,
+}
+
+/// In-memory complexity metrics keyed by schema
+pub struct ComplexityTracker {
+    entries: RwLock<HashMap<SchemaId, SchemaComplexityEntry>>,
+}
+
+impl ComplexityTracker {
+    pub fn new() -> Self {
+        Self {
+            entries: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Record (or replace) the metrics of a schema version
+    pub fn record(&self, schema_id: SchemaId, subject: impl Into<String>, complexity: SchemaComplexity) {
+        let entry = SchemaComplexityEntry {
+            schema_id: schema_id.clone(),
+            subject: subject.into(),
+            complexity,
+            recorded_at: Utc::now(),
+        };
+        self.entries.write().insert(schema_id, entry);
+    }
+
+    /// Metrics of a single schema version
+    pub fn get(&self, schema_id: &SchemaId) -> Option<SchemaComplexityEntry> {
+        self.entries.read().get(schema_id).cloned()
+    }
+
+    /// Every recorded schema version
+    pub fn entries(&self) -> Vec<SchemaComplexityEntry> {
+        self.entries.read().values().cloned().collect()
+    }
+
+    /// Aggregate metrics over all recorded schemas
+    pub fn summary(&self) -> ComplexitySummary {
+        let entries = self.entries.read();
+        if entries.is_empty() {
+            return ComplexitySummary::default();
+        }
+
+        let count = entries.len();
+        let mut summary = ComplexitySummary {
+            schema_count: count,
+            ..Default::default()
+        };
+        let mut total_fields = 0u64;
+        let mut total_constraints = 0u64;
+        for entry in entries.values() {
+            let c = &entry.complexity;
+            summary.total_size_bytes += c.size_bytes;
+            summary.max_field_count = summary.max_field_count.max(c.field_count);
+            summary.max_depth = summary.max_depth.max(c.max_depth);
+            total_fields += c.field_count as u64;
+            total_constraints += c.constraint_count as u64;
+        }
+        summary.avg_size_bytes = summary.total_size_bytes as f64 / count as f64;
+        summary.avg_field_count = total_fields as f64 / count as f64;
+        summary.avg_constraint_count = total_constraints as f64 / count as f64;
+        summary
+    }
+
+    /// Schemas with the most fields, ties broken by size
+    pub fn most_complex(&self, limit: usize) -> Vec<SchemaComplexityEntry> {
+        let mut entries: Vec<_> = self.entries.read().values().cloned().collect();
+        entries.sort_by(|a, b| {
+            b.complexity
+                .field_count
+                .cmp(&a.complexity.field_count)
+                .then(b.complexity.size_bytes.cmp(&a.complexity.size_bytes))
+        });
+        entries.truncate(limit);
+        entries
+    }
+}
+
+impl Default for ComplexityTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn complexity(size_bytes: u64, field_count: u32, max_depth: u32) -> SchemaComplexity {
+        SchemaComplexity {
+            size_bytes,
+            field_count,
+            max_depth,
+            constraint_count: 2,
+        }
+    }
+
+    #[test]
+    fn test_summary_and_most_complex() {
+        let tracker = ComplexityTracker::new();
+        assert_eq!(tracker.summary().schema_count, 0);
+
+        tracker.record("com.a.User".into(), "com.a.User", complexity(100, 4, 1));
+        tracker.record("com.a.Order".into(), "com.a.Order", complexity(300, 10, 3));
+        tracker.record("com.a.Item".into(), "com.a.Item", complexity(200, 10, 2));
+
+        let summary = tracker.summary();
+        assert_eq!(summary.schema_count, 3);
+        assert_eq!(summary.total_size_bytes, 600);
+        assert_eq!(summary.avg_size_bytes, 200.0);
+        assert_eq!(summary.avg_field_count, 8.0);
+        assert_eq!(summary.max_field_count, 10);
+        assert_eq!(summary.max_depth, 3);
+        assert_eq!(summary.avg_constraint_count, 2.0);
+
+        let top: Vec<_> = tracker.most_complex(2).into_iter().map(|e| e.subject).collect();
+        assert_eq!(top, vec!["com.a.Order", "com.a.Item"]);
+    }
+}
@@ -6,14 +6,17 @@
 use crate::aggregator::DataAggregator;
 use crate::error::{AnalyticsError, Result};
 use crate::event_bus::{EventBus, EventConsumer, EventProcessor};
+use crate::feature_usage::{FeatureDimension, FeatureUsageReport, FeatureUsageTracker};
 use crate::query::QueryExecutor;
-use crate::reports::ReportGenerator;
+use crate::reports::{ReportGenerator, UsageHeatReport};
+use crate::rollup::{Rollup, RollupConfig, RollupStore};
 use crate::storage::{AnalyticsStorage, StorageConfig};
 use crate::types::{
     Operation, PerformanceMetrics, SchemaHealthScore, SchemaId, SchemaStats, SchemaUsageEvent,
     TimePeriod, TopSchemaEntry, UsageStats,
 };
 use chrono::{DateTime, Duration, Utc};
+use schema_registry_compatibility::history::{CompatibilityCheckObserver, CompatibilityCheckRecord};
 use std::sync::Arc;
 use tokio::sync::watch;
 use tracing::{debug, info};
@@ -35,6 +38,9 @@ pub struct AnalyticsConfig {
 
     /// Time periods to aggregate
     pub aggregation_periods: Vec<TimePeriod>,
+
+    /// Retention of the downsampled history
+    pub rollup_config: RollupConfig,
 }
 
 impl Default for AnalyticsConfig {
@@ -50,6 +56,7 @@ impl Default for AnalyticsConfig {
                 TimePeriod::Hour1,
                 TimePeriod::Day1,
             ],
+            rollup_config: RollupConfig::default(),
         }
     }
 }
@@ -65,12 +72,18 @@ pub struct AnalyticsEngine {
     /// Analytics storage
     storage: Arc<AnalyticsStorage>,
 
+    /// Downsampled history (1m, 1h, 1d)
+    rollups: Arc<RollupStore>,
+
     /// Query executor
     query_executor: Arc<QueryExecutor>,
 
     /// Report generator
     report_generator: Arc<ReportGenerator>,
 
+    /// Uses of compatibility modes, formats and API and SDK versions
+    feature_usage: Arc<FeatureUsageTracker>,
+
     /// Shutdown signal
     shutdown_tx: watch::Sender<bool>,
     shutdown_rx: watch::Receiver<bool>,
@@ -94,6 +107,7 @@ impl AnalyticsEngine {
         let storage = Arc::new(AnalyticsStorage::with_config(
             config.storage_config.clone(),
         ));
+        let rollups = Arc::new(RollupStore::with_config(config.rollup_config.clone()));
 
         let query_executor = Arc::new(QueryExecutor::new(
             storage.clone(),
@@ -111,8 +125,10 @@ impl AnalyticsEngine {
             event_bus,
             aggregator,
             storage,
+            rollups,
             query_executor,
             report_generator,
+            feature_usage: Arc::new(FeatureUsageTracker::new()),
             shutdown_tx,
             shutdown_rx,
             config,
@@ -143,6 +159,7 @@ impl AnalyticsEngine {
         if self.config.auto_cleanup {
             let storage = self.storage.clone();
             let aggregator = self.aggregator.clone();
+            let rollups = self.rollups.clone();
             let interval = self.config.cleanup_interval_seconds;
             let mut shutdown = self.shutdown_rx.clone();
 
@@ -151,6 +168,10 @@ impl AnalyticsEngine {
                     tokio::select! {
                         _ = tokio::time::sleep(tokio::time::Duration::from_secs(interval)) => {
                             debug!("Running scheduled cleanup");
+                            // Roll up first so nothing is dropped unsummarized
+                            if let Err(e) = rollups.run(&storage, Utc::now()) {
+                                tracing::error!(error = %e, "Rollup failed");
+                            }
                             if let Err(e) = storage.cleanup() {
                                 tracing::error!(error = %e, "Storage cleanup failed");
                             }
@@ -203,6 +224,27 @@ impl AnalyticsEngine {
         self.aggregator.get_stats(period, start_time, end_time, schema_id)
     }
 
+    /// Get downsampled history for one rollup tier (1m, 1h or 1d)
+    pub fn get_rollups(
+        &self,
+        period: TimePeriod,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+        schema_id: Option<&SchemaId>,
+    ) -> Result<Vec<Rollup>> {
+        self.rollups.query(period, start_time, end_time, schema_id)
+    }
+
+    /// Get long-range history at the finest resolution still retained
+    pub fn get_trend(
+        &self,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+        schema_id: Option<&SchemaId>,
+    ) -> Result<Vec<Rollup>> {
+        self.rollups.trend(start_time, end_time, schema_id)
+    }
+
     /// Get latest usage statistics
     pub fn get_latest_stats(&self, period: TimePeriod) -> Option<UsageStats> {
         self.aggregator.get_latest_stats(period, None)
@@ -228,6 +270,11 @@ impl AnalyticsEngine {
         self.report_generator.generate_health_scorecard(schema_id)
     }
 
+    /// Get a schema's usage over the last `days` days
+    pub fn get_usage_heat(&self, schema_id: &SchemaId, days: i64) -> Result<UsageHeatReport> {
+        self.report_generator.generate_usage_heat(schema_id, days)
+    }
+
     /// Get performance metrics
     pub fn get_performance_metrics(&self) -> Result<PerformanceMetrics> {
         // Get recent stats to compute performance metrics
@@ -300,6 +347,26 @@ impl AnalyticsEngine {
         self.report_generator.clone()
     }
 
+    /// Count a use of a feature, returning the value it was counted under
+    pub fn record_feature_use(&self, dimension: FeatureDimension, value: &str) -> String {
+        self.feature_usage.record(dimension, value)
+    }
+
+    /// Declare every value a feature dimension can take, so unused ones are
+    /// reported
+    pub fn set_known_feature_values<I, S>(&self, dimension: FeatureDimension, values: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.feature_usage.set_known_values(dimension, values);
+    }
+
+    /// Get feature usage over the last `days` days
+    pub fn get_feature_usage_report(&self, days: u32) -> FeatureUsageReport {
+        self.feature_usage.report(days)
+    }
+
     /// Shutdown the analytics engine gracefully
     pub async fn shutdown(&self) -> Result<()> {
         info!("Shutting down analytics engine");
@@ -325,6 +392,7 @@ impl AnalyticsEngine {
             total_schemas_tracked: storage_stats.total_schemas,
             total_clients: storage_stats.total_clients,
             aggregation_windows: self.aggregator.aggregation_count(),
+            rollup_buckets: self.rollups.bucket_count(),
         }
     }
 }
@@ -335,6 +403,33 @@ impl Default for AnalyticsEngine {
     }
 }
 
+/// Counts every recorded compatibility check as a `CheckCompatibility` operation
+#[async_trait::async_trait]
+impl CompatibilityCheckObserver for AnalyticsEngine {
+    async fn on_check(&self, record: &CompatibilityCheckRecord) {
+        let mut event = SchemaUsageEvent::new(
+            record.schema_id,
+            Operation::CheckCompatibility,
+            record.caller.clone().unwrap_or_else(|| "unknown".to_string()),
+            "unknown".to_string(),
+            record.latency_ms,
+            true,
+        )
+        .with_metadata("subject".to_string(), record.subject.clone())
+        .with_metadata("mode".to_string(), record.mode.to_string())
+        .with_metadata("is_compatible".to_string(), record.is_compatible.to_string())
+        .with_metadata(
+            "compared_schema_id".to_string(),
+            record.compared_schema_id.to_string(),
+        );
+        if let Some(caller) = &record.caller {
+            event = event.with_principal(caller.clone());
+        }
+        event.timestamp = record.checked_at;
+        self.try_record_event(event);
+    }
+}
+
 /// Event processor implementation
 struct AnalyticsProcessor {
     storage: Arc<AnalyticsStorage>,
@@ -362,6 +457,7 @@ pub struct EngineStats {
     pub total_schemas_tracked: usize,
     pub total_clients: usize,
     pub aggregation_windows: usize,
+    pub rollup_buckets: usize,
 }
 
 #[cfg(test)]
@@ -517,4 +613,39 @@ mod tests {
 
         engine.shutdown().await.unwrap();
     }
+
+    #[tokio::test]
+    async fn test_compatibility_check_observer() {
+        use schema_registry_core::{
+            traits::CompatibilityResult, types::CompatibilityMode, versioning::SemanticVersion,
+        };
+
+        let engine = AnalyticsEngine::new();
+        engine.start().await.unwrap();
+
+        let schema_id = Uuid::new_v4();
+        let result = CompatibilityResult {
+            is_compatible: false,
+            mode: CompatibilityMode::Backward,
+            violations: vec![],
+            exempted_violations: vec![],
+            checked_versions: vec![],
+        };
+        let record = CompatibilityCheckRecord::new(
+            "com.example.User",
+            (schema_id, SemanticVersion::new(2, 0, 0)),
+            (Uuid::new_v4(), SemanticVersion::new(1, 0, 0)),
+            &result,
+            std::time::Duration::from_millis(12),
+        )
+        .with_caller("ci");
+        engine.on_check(&record).await;
+
+        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+
+        let stats = engine.get_schema_stats(&schema_id.into()).unwrap();
+        assert_eq!(stats.compatibility_check_count, 1);
+
+        engine.shutdown().await.unwrap();
+    }
 }
@@ -0,0 +1,292 @@
+//! Usage of registry features, to guide deprecations
+//!
+//! Before investing in a compatibility mode, format or API version, or
+//! dropping one, it helps to know whether anyone still uses it. Requests
+//! record each feature they touch as a dimension and a value, e.g.
+//! `compatibility_mode = FORWARD_TRANSITIVE`, counted per day. A report over
+//! the last N days lists each value's uses and when it was last seen, along
+//! with the values nobody used in that window.
+//!
+//! SDK versions come from a client-supplied header, so each dimension keeps
+//! at most [`MAX_VALUES_PER_DIMENSION`] distinct values; uses of later ones
+//! are counted as [`OTHER_VALUE`].
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+
+/// Distinct values tracked per dimension
+pub const MAX_VALUES_PER_DIMENSION: usize = 100;
+
+/// Value uses are counted under once a dimension is full
+pub const OTHER_VALUE: &str = "other";
+
+/// Longest value kept; longer ones are truncated
+const MAX_VALUE_LENGTH: usize = 64;
+
+/// Days of daily counts kept
+const RETENTION_DAYS: i64 = 400;
+
+/// What kind of feature a use is of
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeatureDimension {
+    CompatibilityMode,
+    Format,
+    ApiVersion,
+    SdkVersion,
+}
+
+impl FeatureDimension {
+    pub const ALL: [FeatureDimension; 4] = [
+        FeatureDimension::CompatibilityMode,
+        FeatureDimension::Format,
+        FeatureDimension::ApiVersion,
+        FeatureDimension::SdkVersion,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FeatureDimension::CompatibilityMode => "compatibility_mode",
+            FeatureDimension::Format => "format",
+            FeatureDimension::ApiVersion => "api_version",
+            FeatureDimension::SdkVersion => "sdk_version",
+        }
+    }
+}
+
+impl fmt::Display for FeatureDimension {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Uses of one value of a dimension within a report's window
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeatureValueUsage {
+    pub value: String,
+    pub uses: u64,
+    /// Percentage of the dimension's uses in the window
+    pub share: f64,
+    pub last_seen: DateTime<Utc>,
+}
+
+/// Uses of every value of one dimension
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DimensionUsage {
+    pub dimension: FeatureDimension,
+    pub total_uses: u64,
+    /// Most used first
+    pub values: Vec<FeatureValueUsage>,
+    /// Known or previously seen values with no uses in the window
+    pub unused: Vec<String>,
+}
+
+/// Feature usage over the last `days` days
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeatureUsageReport {
+    pub days: u32,
+    pub since: DateTime<Utc>,
+    pub generated_at: DateTime<Utc>,
+    pub dimensions: Vec<DimensionUsage>,
+}
+
+#[derive(Debug, Clone)]
+struct ValueCounter {
+    daily: BTreeMap<NaiveDate, u64>,
+    last_seen: DateTime<Utc>,
+}
+
+/// In-memory daily counts of feature uses
+pub struct FeatureUsageTracker {
+    counters: RwLock<HashMap<FeatureDimension, HashMap<String, ValueCounter>>>,
+    /// Values reported as unused even before their first use
+    known: RwLock<HashMap<FeatureDimension, BTreeSet<String>>>,
+}
+
+impl FeatureUsageTracker {
+    pub fn new() -> Self {
+        Self {
+            counters: RwLock::new(HashMap::new()),
+            known: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Declare every value a dimension can take, e.g. all compatibility
+    /// modes, so unused ones show up in reports
+    pub fn set_known_values<I, S>(&self, dimension: FeatureDimension, values: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.known
+            .write()
+            .insert(dimension, values.into_iter().map(Into::into).collect());
+    }
+
+    /// Count a use of `value`, returning the value it was counted under
+    pub fn record(&self, dimension: FeatureDimension, value: &str) -> String {
+        self.record_at(dimension, value, Utc::now())
+    }
+
+    fn record_at(&self, dimension: FeatureDimension, value: &str, at: DateTime<Utc>) -> String {
+        let mut value: String = value.trim().chars().take(MAX_VALUE_LENGTH).collect();
+        if value.is_empty() {
+            value = OTHER_VALUE.to_string();
+        }
+
+        let mut counters = self.counters.write();
+        let values = counters.entry(dimension).or_default();
+        if !values.contains_key(&value) && values.len() >= MAX_VALUES_PER_DIMENSION {
+            value = OTHER_VALUE.to_string();
+        }
+        let counter = values.entry(value.clone()).or_insert_with(|| ValueCounter {
+            daily: BTreeMap::new(),
+            last_seen: at,
+        });
+        let day = at.date_naive();
+        if !counter.daily.contains_key(&day) {
+            let cutoff = day - Duration::days(RETENTION_DAYS);
+            counter.daily.retain(|d, _| *d > cutoff);
+        }
+        *counter.daily.entry(day).or_insert(0) += 1;
+        counter.last_seen = counter.last_seen.max(at);
+        value
+    }
+
+    /// Uses over the last `days` days, today included
+    pub fn report(&self, days: u32) -> FeatureUsageReport {
+        self.report_at(days, Utc::now())
+    }
+
+    fn report_at(&self, days: u32, now: DateTime<Utc>) -> FeatureUsageReport {
+        let days = days.max(1);
+        let first_day = now.date_naive() - Duration::days(days as i64 - 1);
+        let counters = self.counters.read();
+        let known = self.known.read();
+
+        let dimensions = FeatureDimension::ALL
+            .iter()
+            .map(|dimension| {
+                let mut values = Vec::new();
+                let mut unused: BTreeSet<String> =
+                    known.get(dimension).cloned().unwrap_or_default();
+                for (value, counter) in counters.get(dimension).into_iter().flatten() {
+                    let uses: u64 = counter.daily.range(first_day..).map(|(_, n)| n).sum();
+                    if uses == 0 {
+                        unused.insert(value.clone());
+                        continue;
+                    }
+                    unused.remove(value);
+                    values.push(FeatureValueUsage {
+                        value: value.clone(),
+                        uses,
+                        share: 0.0,
+                        last_seen: counter.last_seen,
+                    });
+                }
+
+                let total_uses: u64 = values.iter().map(|v| v.uses).sum();
+                for value in &mut values {
+                    value.share = value.uses as f64 * 100.0 / total_uses as f64;
+                }
+                values.sort_by(|a, b| b.uses.cmp(&a.uses).then(a.value.cmp(&b.value)));
+                DimensionUsage {
+                    dimension: *dimension,
+                    total_uses,
+                    values,
+                    unused: unused.into_iter().collect(),
+                }
+            })
+            .collect();
+
+        FeatureUsageReport {
+            days,
+            since: first_day
+                .and_hms_opt(0, 0, 0)
+                .map(|midnight| midnight.and_utc())
+                .unwrap_or(now),
+            generated_at: now,
+            dimensions,
+        }
+    }
+}
+
+impl Default for FeatureUsageTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dimension(report: &FeatureUsageReport, dimension: FeatureDimension) -> &DimensionUsage {
+        report
+            .dimensions
+            .iter()
+            .find(|d| d.dimension == dimension)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_report_counts_uses_in_window_and_lists_unused_values() {
+        let tracker = FeatureUsageTracker::new();
+        tracker.set_known_values(
+            FeatureDimension::CompatibilityMode,
+            ["BACKWARD", "FORWARD_TRANSITIVE", "FULL"],
+        );
+        let now = Utc::now();
+        for _ in 0..3 {
+            tracker.record_at(FeatureDimension::CompatibilityMode, "BACKWARD", now);
+        }
+        tracker.record_at(
+            FeatureDimension::CompatibilityMode,
+            "FULL",
+            now - Duration::days(1),
+        );
+        tracker.record_at(
+            FeatureDimension::CompatibilityMode,
+            "NONE",
+            now - Duration::days(60),
+        );
+
+        let report = tracker.report_at(30, now);
+        let modes = dimension(&report, FeatureDimension::CompatibilityMode);
+        assert_eq!(modes.total_uses, 4);
+        assert_eq!(modes.values[0].value, "BACKWARD");
+        assert_eq!(modes.values[0].share, 75.0);
+        assert_eq!(modes.values[1].value, "FULL");
+        assert_eq!(modes.unused, vec!["FORWARD_TRANSITIVE", "NONE"]);
+
+        let year = tracker.report_at(365, now);
+        assert_eq!(
+            dimension(&year, FeatureDimension::CompatibilityMode).unused,
+            vec!["FORWARD_TRANSITIVE"]
+        );
+    }
+
+    #[test]
+    fn test_client_supplied_values_are_bounded() {
+        let tracker = FeatureUsageTracker::new();
+        for i in 0..MAX_VALUES_PER_DIMENSION {
+            tracker.record(FeatureDimension::SdkVersion, &format!("python/1.{}", i));
+        }
+        assert_eq!(
+            tracker.record(FeatureDimension::SdkVersion, "python/9.9"),
+            OTHER_VALUE
+        );
+        assert_eq!(
+            tracker.record(FeatureDimension::SdkVersion, "python/1.0"),
+            "python/1.0"
+        );
+        let long = "x".repeat(500);
+        assert_eq!(
+            tracker.record(FeatureDimension::ApiVersion, &long).len(),
+            MAX_VALUE_LENGTH
+        );
+    }
+}
@@ -0,0 +1,387 @@
+//! Capacity forecasting
+//!
+//! Fits simple growth models to capacity history (schemas per namespace,
+//! requests per day, storage bytes) and projects when each series reaches
+//! its quota. Both a linear and an exponential model are fitted; the one
+//! with the better R² is used.
+//!
+//! [`CapacityHistory`] is serializable so a history exported from a running
+//! registry can be forecast offline, e.g. with `schema-cli analytics forecast`.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// One observation of a growing quantity
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GrowthSample {
+    pub at: DateTime<Utc>,
+    pub value: f64,
+}
+
+impl GrowthSample {
+    pub fn new(at: DateTime<Utc>, value: f64) -> Self {
+        Self { at, value }
+    }
+}
+
+/// Growth model
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FitModel {
+    /// `value = intercept + rate * days`
+    Linear,
+    /// `value = intercept * e^(rate * days)`
+    Exponential,
+}
+
+/// Growth model fitted to a series
+///
+/// Time is measured in days since `origin`, the first sample.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrendFit {
+    pub model: FitModel,
+    pub origin: DateTime<Utc>,
+    pub intercept: f64,
+    pub rate: f64,
+    /// Goodness of fit on the original scale, 1.0 is a perfect fit
+    pub r_squared: f64,
+}
+
+impl TrendFit {
+    /// Fit both models and keep the better one
+    ///
+    /// Needs at least two samples at different times. The exponential model
+    /// is only tried when every value is positive.
+    pub fn fit(samples: &[GrowthSample]) -> Option<TrendFit> {
+        let origin = samples.iter().map(|s| s.at).min()?;
+        let points: Vec<(f64, f64)> = samples
+            .iter()
+            .map(|s| (days_between(origin, s.at), s.value))
+            .collect();
+
+        let (intercept, rate) = least_squares(&points)?;
+        let mut best = TrendFit {
+            model: FitModel::Linear,
+            origin,
+            intercept,
+            rate,
+            r_squared: 0.0,
+        };
+        best.r_squared = best.r_squared_on(&points);
+
+        if points.iter().all(|(_, y)| *y > 0.0) {
+            let logs: Vec<(f64, f64)> = points.iter().map(|(t, y)| (*t, y.ln())).collect();
+            if let Some((log_intercept, rate)) = least_squares(&logs) {
+                let mut exponential = TrendFit {
+                    model: FitModel::Exponential,
+                    origin,
+                    intercept: log_intercept.exp(),
+                    rate,
+                    r_squared: 0.0,
+                };
+                exponential.r_squared = exponential.r_squared_on(&points);
+                if exponential.r_squared > best.r_squared {
+                    best = exponential;
+                }
+            }
+        }
+
+        Some(best)
+    }
+
+    /// Value the model predicts at `at`
+    pub fn predict(&self, at: DateTime<Utc>) -> f64 {
+        self.predict_days(days_between(self.origin, at))
+    }
+
+    /// When the model first reaches `target`, `None` if it never does
+    ///
+    /// Only growing series reach a target; the result may lie in the past.
+    pub fn reaches(&self, target: f64) -> Option<DateTime<Utc>> {
+        let days = match self.model {
+            FitModel::Linear if self.rate > 0.0 => (target - self.intercept) / self.rate,
+            FitModel::Exponential if self.rate > 0.0 && self.intercept > 0.0 && target > 0.0 => {
+                (target / self.intercept).ln() / self.rate
+            }
+            _ => return None,
+        };
+        let seconds = (days * 86_400.0).round();
+        if !seconds.is_finite() || seconds.abs() > 1e12 {
+            return None;
+        }
+        Some(self.origin + Duration::seconds(seconds as i64))
+    }
+
+    fn predict_days(&self, days: f64) -> f64 {
+        match self.model {
+            FitModel::Linear => self.intercept + self.rate * days,
+            FitModel::Exponential => self.intercept * (self.rate * days).exp(),
+        }
+    }
+
+    fn r_squared_on(&self, points: &[(f64, f64)]) -> f64 {
+        let mean = points.iter().map(|(_, y)| y).sum::<f64>() / points.len() as f64;
+        let total: f64 = points.iter().map(|(_, y)| (y - mean).powi(2)).sum();
+        let residual: f64 = points
+            .iter()
+            .map(|(t, y)| (y - self.predict_days(*t)).powi(2))
+            .sum();
+        if total == 0.0 {
+            if residual < 1e-9 {
+                1.0
+            } else {
+                0.0
+            }
+        } else {
+            1.0 - residual / total
+        }
+    }
+}
+
+/// Forecast of one series
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GrowthTrend {
+    /// Namespace, or the name of the series
+    pub name: String,
+    /// Latest observed value
+    pub current: f64,
+    /// Growth per day at the latest sample, per the fitted model
+    pub growth_per_day: f64,
+    /// Value the model predicts at the end of the horizon
+    pub projected: f64,
+    pub fit: TrendFit,
+    pub samples: usize,
+    pub quota: Option<f64>,
+    /// When the quota is projected to be reached
+    pub quota_reached_at: Option<DateTime<Utc>>,
+}
+
+impl GrowthTrend {
+    /// Fit a series and project it to `horizon_end`
+    pub fn forecast(
+        name: impl Into<String>,
+        samples: &[GrowthSample],
+        quota: Option<f64>,
+        horizon_end: DateTime<Utc>,
+    ) -> Option<GrowthTrend> {
+        let fit = TrendFit::fit(samples)?;
+        let latest = samples.iter().max_by_key(|s| s.at)?;
+
+        let growth_per_day = match fit.model {
+            FitModel::Linear => fit.rate,
+            FitModel::Exponential => fit.rate * fit.predict(latest.at),
+        };
+        let quota_reached_at = quota.and_then(|quota| {
+            if latest.value >= quota {
+                Some(latest.at)
+            } else {
+                fit.reaches(quota).map(|at| at.max(latest.at))
+            }
+        });
+
+        Some(GrowthTrend {
+            name: name.into(),
+            current: latest.value,
+            growth_per_day,
+            projected: fit.predict(horizon_end),
+            fit,
+            samples: samples.len(),
+            quota,
+            quota_reached_at,
+        })
+    }
+
+    /// Whether the quota is projected to be reached by `deadline`
+    pub fn reaches_quota_by(&self, deadline: DateTime<Utc>) -> bool {
+        self.quota_reached_at.is_some_and(|at| at <= deadline)
+    }
+}
+
+/// Quotas to project against
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CapacityQuotas {
+    /// Schemas per namespace, unless overridden in `namespace_max_schemas`
+    pub max_schemas_per_namespace: Option<u64>,
+    pub namespace_max_schemas: HashMap<String, u64>,
+    pub max_requests_per_day: Option<u64>,
+    pub max_storage_bytes: Option<u64>,
+}
+
+impl CapacityQuotas {
+    fn schemas_in(&self, namespace: &str) -> Option<f64> {
+        self.namespace_max_schemas
+            .get(namespace)
+            .copied()
+            .or(self.max_schemas_per_namespace)
+            .map(|max| max as f64)
+    }
+}
+
+/// Capacity history to forecast from
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CapacityHistory {
+    pub schemas_per_namespace: BTreeMap<String, Vec<GrowthSample>>,
+    pub requests_per_day: Vec<GrowthSample>,
+    pub storage_bytes: Vec<GrowthSample>,
+}
+
+impl CapacityHistory {
+    /// Project every series `horizon_days` ahead of `now`
+    ///
+    /// Series with fewer than two samples are left out.
+    pub fn forecast(
+        &self,
+        quotas: &CapacityQuotas,
+        horizon_days: i64,
+        now: DateTime<Utc>,
+    ) -> CapacityForecastReport {
+        let horizon_end = now + Duration::days(horizon_days);
+        let namespaces = self
+            .schemas_per_namespace
+            .iter()
+            .filter_map(|(namespace, samples)| {
+                GrowthTrend::forecast(
+                    namespace,
+                    samples,
+                    quotas.schemas_in(namespace),
+                    horizon_end,
+                )
+            })
+            .collect();
+
+        CapacityForecastReport {
+            generated_at: now,
+            horizon_end,
+            namespaces,
+            requests_per_day: GrowthTrend::forecast(
+                "requests_per_day",
+                &self.requests_per_day,
+                quotas.max_requests_per_day.map(|max| max as f64),
+                horizon_end,
+            ),
+            storage_bytes: GrowthTrend::forecast(
+                "storage_bytes",
+                &self.storage_bytes,
+                quotas.max_storage_bytes.map(|max| max as f64),
+                horizon_end,
+            ),
+        }
+    }
+}
+
+/// Growth trends and projected quota dates
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapacityForecastReport {
+    pub generated_at: DateTime<Utc>,
+    pub horizon_end: DateTime<Utc>,
+    /// Schemas per namespace
+    pub namespaces: Vec<GrowthTrend>,
+    pub requests_per_day: Option<GrowthTrend>,
+    pub storage_bytes: Option<GrowthTrend>,
+}
+
+impl CapacityForecastReport {
+    /// Series projected to reach their quota within the horizon, soonest first
+    pub fn at_risk(&self) -> Vec<&GrowthTrend> {
+        let mut at_risk: Vec<_> = self
+            .namespaces
+            .iter()
+            .chain(&self.requests_per_day)
+            .chain(&self.storage_bytes)
+            .filter(|trend| trend.reaches_quota_by(self.horizon_end))
+            .collect();
+        at_risk.sort_by_key(|trend| trend.quota_reached_at);
+        at_risk
+    }
+}
+
+fn days_between(from: DateTime<Utc>, to: DateTime<Utc>) -> f64 {
+    (to - from).num_seconds() as f64 / 86_400.0
+}
+
+/// Ordinary least squares, `None` if all points share one time
+fn least_squares(points: &[(f64, f64)]) -> Option<(f64, f64)> {
+    let n = points.len() as f64;
+    let mean_t = points.iter().map(|(t, _)| t).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let variance: f64 = points.iter().map(|(t, _)| (t - mean_t).powi(2)).sum();
+    if points.len() < 2 || variance == 0.0 {
+        return None;
+    }
+    let covariance: f64 = points
+        .iter()
+        .map(|(t, y)| (t - mean_t) * (y - mean_y))
+        .sum();
+    let slope = covariance / variance;
+    Some((mean_y - slope * mean_t, slope))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn series(start: DateTime<Utc>, values: &[f64]) -> Vec<GrowthSample> {
+        values
+            .iter()
+            .enumerate()
+            .map(|(day, value)| GrowthSample::new(start + Duration::days(day as i64), *value))
+            .collect()
+    }
+
+    #[test]
+    fn test_linear_and_exponential_fits() {
+        let start = Utc::now() - Duration::days(10);
+
+        let linear = TrendFit::fit(&series(start, &[10.0, 12.0, 14.0, 16.0, 18.0])).unwrap();
+        assert_eq!(linear.model, FitModel::Linear);
+        assert!((linear.rate - 2.0).abs() < 1e-9);
+        assert!((linear.r_squared - 1.0).abs() < 1e-9);
+        let reached = linear.reaches(30.0).unwrap();
+        assert_eq!((reached - start).num_days(), 10);
+
+        let exponential = TrendFit::fit(&series(start, &[100.0, 200.0, 400.0, 800.0])).unwrap();
+        assert_eq!(exponential.model, FitModel::Exponential);
+        assert!((exponential.predict(start + Duration::days(4)) - 1600.0).abs() < 1e-6);
+
+        let flat = TrendFit::fit(&series(start, &[5.0, 5.0, 5.0])).unwrap();
+        assert_eq!(flat.reaches(10.0), None);
+        assert!(TrendFit::fit(&series(start, &[5.0])).is_none());
+    }
+
+    #[test]
+    fn test_forecast_report_flags_quotas_in_horizon() {
+        let now = Utc::now();
+        let start = now - Duration::days(4);
+        let mut history = CapacityHistory::default();
+        history.schemas_per_namespace.insert(
+            "com.fast".to_string(),
+            series(start, &[10.0, 20.0, 30.0, 40.0, 50.0]),
+        );
+        history.schemas_per_namespace.insert(
+            "com.slow".to_string(),
+            series(start, &[1.0, 1.0, 2.0, 2.0, 2.0]),
+        );
+        history.storage_bytes = series(start, &[1000.0, 1100.0]);
+
+        let quotas = CapacityQuotas {
+            max_schemas_per_namespace: Some(100),
+            namespace_max_schemas: HashMap::from([("com.slow".to_string(), 1)]),
+            max_storage_bytes: Some(1_000_000),
+            ..Default::default()
+        };
+        let report = history.forecast(&quotas, 90, now);
+
+        let fast = &report.namespaces[0];
+        assert_eq!(fast.name, "com.fast");
+        assert_eq!(fast.growth_per_day, 10.0);
+        assert_eq!((fast.quota_reached_at.unwrap() - start).num_days(), 9);
+
+        // Already over quota
+        assert_eq!(report.namespaces[1].quota_reached_at, Some(now));
+        assert!(report.requests_per_day.is_none());
+
+        let at_risk: Vec<_> = report.at_risk().iter().map(|t| t.name.clone()).collect();
+        assert_eq!(at_risk, vec!["com.slow", "com.fast"]);
+    }
+}
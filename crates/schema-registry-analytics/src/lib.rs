@@ -4,11 +4,15 @@
 //!
 //! - Real-time event streaming and processing
 //! - Time-series data aggregation (1m, 5m, 1h, 1d intervals)
+//! - Historical rollups downsampled 1m → 1h → 1d for a year of trends
 //! - Schema usage tracking and metrics
 //! - Performance analytics
 //! - Popular schema identification
 //! - Health scorecards and anomaly detection
 //! - Comprehensive reporting system
+//! - Capacity forecasts with projected quota dates
+//! - Feature usage (compatibility modes, formats, API and SDK versions) to
+//!   guide deprecations
 //!
 //! ## Quick Start
 //!
@@ -145,6 +149,7 @@
 //! - **Event Bus**: Real-time event streaming using tokio broadcast channels
 //! - **Aggregator**: Time-series data aggregation with configurable windows
 //! - **Storage**: In-memory storage with retention policies (prepared for TimescaleDB)
+//! - **Rollups**: Downsampled 1m/1h/1d history that outlives the raw events
 //! - **Query Executor**: High-level query interface with filtering and pagination
 //! - **Report Generator**: Automated reporting and anomaly detection
 //!
@@ -187,24 +192,37 @@
 //! - Advanced anomaly detection with ML models
 
 pub mod aggregator;
+pub mod complexity;
 pub mod engine;
 pub mod error;
 pub mod event_bus;
+pub mod feature_usage;
+pub mod forecast;
 pub mod query;
 pub mod reports;
+pub mod rollup;
 pub mod storage;
 pub mod types;
 
 // Re-export main types for convenience
 pub use aggregator::DataAggregator;
+pub use complexity::{ComplexitySummary, ComplexityTracker, SchemaComplexityEntry};
 pub use engine::{AnalyticsConfig, AnalyticsEngine, EngineStats};
 pub use error::{AnalyticsError, Result};
 pub use event_bus::{EventBus, EventConsumer, EventProcessor, EventReceiver};
+pub use feature_usage::{
+    DimensionUsage, FeatureDimension, FeatureUsageReport, FeatureUsageTracker, FeatureValueUsage,
+};
+pub use forecast::{
+    CapacityForecastReport, CapacityHistory, CapacityQuotas, FitModel, GrowthSample, GrowthTrend,
+    TrendFit,
+};
 pub use query::{QueryBuilder, QueryExecutor};
 pub use reports::{
-    Anomaly, AnomalySeverity, AnomalyType, DailyUsageSummary, MonthlyAggregateReport,
-    ReportGenerator, WeeklyTrendsReport,
+    Anomaly, AnomalySeverity, AnomalyType, DailyUsage, DailyUsageSummary, MonthlyAggregateReport,
+    ReportGenerator, UsageHeatReport, WeeklyTrendsReport,
 };
+pub use rollup::{Rollup, RollupConfig, RollupRun, RollupStore};
 pub use storage::{AnalyticsStorage, StorageConfig, StorageStats};
 pub use types::{
     AnalyticsQuery, CompatibilityPerformance, FormatPerformance, LatencyDistribution, Operation,
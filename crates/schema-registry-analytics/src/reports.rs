@@ -1,16 +1,19 @@
 //! Reporting system for analytics summaries
 //!
 //! This module provides various report generators for daily, weekly, and monthly
-//! summaries, health scorecards, and anomaly detection.
+//! summaries, health scorecards, usage heat reports, and anomaly detection.
 
+use crate::complexity::ComplexityTracker;
 use crate::error::Result;
-use crate::query::QueryExecutor;
+use crate::forecast::{CapacityForecastReport, CapacityHistory, CapacityQuotas, GrowthSample};
+use crate::query::{QueryBuilder, QueryExecutor};
 use crate::storage::AnalyticsStorage;
 use crate::types::{
     Operation, SchemaHealthScore, SchemaId, SchemaTrend, TimePeriod, TopSchemaEntry,
 };
-use chrono::{DateTime, Datelike, Duration, Utc};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
 use std::sync::Arc;
 
 /// Daily usage summary report
@@ -144,10 +147,46 @@ pub enum AnomalySeverity {
     Info,
 }
 
+/// Recent use of one schema, reviewed before it is archived or deleted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageHeatReport {
+    /// Schema the report covers
+    pub schema_id: SchemaId,
+    /// Number of days looked back
+    pub days: i64,
+    /// Operations in the window
+    pub total_operations: u64,
+    /// Operations by type
+    pub operations_breakdown: Vec<OperationBreakdown>,
+    /// Operations per day, oldest first, including days without any
+    pub daily: Vec<DailyUsage>,
+    /// Clients that used the schema, sorted
+    pub clients: Vec<String>,
+    /// Most recent operation, if any
+    pub last_used: Option<DateTime<Utc>>,
+}
+
+impl UsageHeatReport {
+    /// Whether the schema was used at all in the window
+    pub fn in_use(&self) -> bool {
+        self.total_operations > 0
+    }
+}
+
+/// Operations on one day
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DailyUsage {
+    /// Day (UTC)
+    pub date: NaiveDate,
+    /// Operations that day
+    pub count: u64,
+}
+
 /// Report generator
 pub struct ReportGenerator {
     query_executor: Arc<QueryExecutor>,
     storage: Arc<AnalyticsStorage>,
+    complexity: Option<Arc<ComplexityTracker>>,
 }
 
 impl ReportGenerator {
@@ -156,9 +195,16 @@ impl ReportGenerator {
         Self {
             query_executor,
             storage,
+            complexity: None,
         }
     }
 
+    /// Use recorded schema sizes for schema and storage growth in forecasts
+    pub fn with_complexity(mut self, complexity: Arc<ComplexityTracker>) -> Self {
+        self.complexity = Some(complexity);
+        self
+    }
+
     /// Generate daily usage summary
     pub fn generate_daily_summary(&self, date: DateTime<Utc>) -> Result<DailyUsageSummary> {
         let start = date.date_naive().and_hms_opt(0, 0, 0)
@@ -388,6 +434,56 @@ impl ReportGenerator {
         })
     }
 
+    /// Generate a usage heat report for one schema over the last `days` days
+    ///
+    /// Built from raw events, so `days` must fall within their retention.
+    pub fn generate_usage_heat(&self, schema_id: &SchemaId, days: i64) -> Result<UsageHeatReport> {
+        let query = QueryBuilder::last_days(days)
+            .schema(schema_id.clone())
+            .build();
+        let events = self.query_executor.query_events(&query)?;
+
+        let first_day = query.start_time.date_naive();
+        let mut daily: Vec<DailyUsage> = first_day
+            .iter_days()
+            .take_while(|date| *date <= query.end_time.date_naive())
+            .map(|date| DailyUsage { date, count: 0 })
+            .collect();
+        let mut operations: HashMap<Operation, u64> = HashMap::new();
+        let mut clients = BTreeSet::new();
+        let mut last_used: Option<DateTime<Utc>> = None;
+        for event in &events {
+            let day = (event.timestamp.date_naive() - first_day).num_days();
+            if let Some(entry) = usize::try_from(day).ok().and_then(|i| daily.get_mut(i)) {
+                entry.count += 1;
+            }
+            *operations.entry(event.operation).or_default() += 1;
+            clients.insert(event.client_id.clone());
+            last_used = last_used.max(Some(event.timestamp));
+        }
+
+        let total_operations = events.len() as u64;
+        let mut operations_breakdown: Vec<OperationBreakdown> = operations
+            .into_iter()
+            .map(|(operation, count)| OperationBreakdown {
+                operation,
+                count,
+                percentage: count as f64 / total_operations as f64 * 100.0,
+            })
+            .collect();
+        operations_breakdown.sort_by_key(|b| std::cmp::Reverse(b.count));
+
+        Ok(UsageHeatReport {
+            schema_id: schema_id.clone(),
+            days,
+            total_operations,
+            operations_breakdown,
+            daily,
+            clients: clients.into_iter().collect(),
+            last_used,
+        })
+    }
+
     /// Detect anomalies in recent data
     pub fn detect_anomalies(&self, lookback_hours: i64) -> Result<Vec<Anomaly>> {
         let stats = self.query_executor.query_recent(
@@ -455,6 +551,80 @@ impl ReportGenerator {
         Ok(anomalies)
     }
 
+    /// Collect daily capacity samples over the last `lookback_days`
+    ///
+    /// Schema counts and storage bytes are cumulative over the recorded
+    /// schema versions, sampled at the end of each day (today: now); requests
+    /// per day come from the completed daily aggregates, starting at the
+    /// first day with traffic.
+    pub fn capacity_history(&self, lookback_days: i64) -> Result<CapacityHistory> {
+        let now = Utc::now();
+        let today = TimePeriod::Day1.round_down(now);
+        let mut history = CapacityHistory::default();
+
+        if let Some(complexity) = &self.complexity {
+            let mut entries = complexity.entries();
+            entries.sort_by_key(|e| e.recorded_at);
+
+            for day in (0..lookback_days).rev() {
+                let sample_at = (today - Duration::days(day - 1)).min(now);
+                let mut schemas: std::collections::HashMap<String, f64> = Default::default();
+                let mut bytes = 0u64;
+                for entry in entries.iter().take_while(|e| e.recorded_at <= sample_at) {
+                    let namespace = match entry.subject.rfind('.') {
+                        Some(dot) => &entry.subject[..dot],
+                        None => "default",
+                    };
+                    *schemas.entry(namespace.to_string()).or_default() += 1.0;
+                    bytes += entry.complexity.size_bytes;
+                }
+                for (namespace, count) in schemas {
+                    history
+                        .schemas_per_namespace
+                        .entry(namespace)
+                        .or_default()
+                        .push(GrowthSample::new(sample_at, count));
+                }
+                if bytes > 0 {
+                    history
+                        .storage_bytes
+                        .push(GrowthSample::new(sample_at, bytes as f64));
+                }
+            }
+        }
+
+        let daily = self
+            .query_executor
+            .query_recent(Duration::days(lookback_days), TimePeriod::Day1)?;
+        history.requests_per_day = daily
+            .iter()
+            .filter(|s| s.window_end <= today)
+            .skip_while(|s| s.total_count == 0)
+            .map(|s| GrowthSample::new(s.window_start, s.total_count as f64))
+            .collect();
+
+        Ok(history)
+    }
+
+    /// Generate a capacity forecast for quarterly planning
+    ///
+    /// Fits growth trends to the last `lookback_days` and projects when each
+    /// quota is reached, looking `horizon_days` ahead.
+    pub fn generate_capacity_forecast(
+        &self,
+        lookback_days: i64,
+        horizon_days: i64,
+        quotas: &CapacityQuotas,
+    ) -> Result<CapacityForecastReport> {
+        if lookback_days < 2 || horizon_days < 1 {
+            return Err(crate::error::AnalyticsError::invalid_parameter(
+                "forecast needs a lookback of at least 2 days and a positive horizon",
+            ));
+        }
+        let history = self.capacity_history(lookback_days)?;
+        Ok(history.forecast(quotas, horizon_days, Utc::now()))
+    }
+
     /// Export report to JSON
     pub fn export_to_json<T: Serialize>(&self, report: &T) -> Result<String> {
         serde_json::to_string_pretty(report)
@@ -519,6 +689,58 @@ mod tests {
         assert!(scorecard.success_rate_score >= 80); // ~90% success
     }
 
+    #[test]
+    fn test_usage_heat() {
+        let generator = setup();
+        let schema_id = Uuid::new_v4();
+        let other_id = Uuid::new_v4();
+
+        let mut old = SchemaUsageEvent::new(
+            schema_id,
+            Operation::Validate,
+            "billing".to_string(),
+            "us-west-1".to_string(),
+            5,
+            true,
+        );
+        old.timestamp = Utc::now() - Duration::days(3);
+        generator.storage.store_event(old).unwrap();
+        for (id, client) in [
+            (schema_id, "orders"),
+            (schema_id, "orders"),
+            (other_id, "search"),
+        ] {
+            let event = SchemaUsageEvent::new(
+                id,
+                Operation::Read,
+                client.to_string(),
+                "us-west-1".to_string(),
+                5,
+                true,
+            );
+            generator.storage.store_event(event).unwrap();
+        }
+
+        let report = generator
+            .generate_usage_heat(&schema_id.into(), 30)
+            .unwrap();
+        assert!(report.in_use());
+        assert_eq!(report.total_operations, 3);
+        assert_eq!(report.clients, vec!["billing", "orders"]);
+        assert_eq!(report.operations_breakdown[0].operation, Operation::Read);
+        assert_eq!(report.operations_breakdown[0].count, 2);
+        assert_eq!(report.daily.len(), 31);
+        assert_eq!(report.daily.last().unwrap().count, 2);
+        assert_eq!(report.daily[report.daily.len() - 4].count, 1);
+        assert_eq!(report.daily.iter().map(|d| d.count).sum::<u64>(), 3);
+
+        let unused = generator
+            .generate_usage_heat(&Uuid::new_v4().into(), 30)
+            .unwrap();
+        assert!(!unused.in_use());
+        assert!(unused.last_used.is_none());
+    }
+
     #[test]
     fn test_anomaly_detection() {
         let generator = setup();
@@ -527,6 +749,38 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_capacity_forecast() {
+        let complexity = Arc::new(ComplexityTracker::new());
+        let generator = setup().with_complexity(complexity.clone());
+        for name in ["User", "Order", "Item"] {
+            complexity.record(
+                format!("com.shop.{}", name).into(),
+                format!("com.shop.{}", name),
+                schema_registry_core::complexity::SchemaComplexity {
+                    size_bytes: 500,
+                    ..Default::default()
+                },
+            );
+        }
+
+        // Recorded today, so only today's sample has them
+        let history = generator.capacity_history(7).unwrap();
+        let shop = &history.schemas_per_namespace["com.shop"];
+        assert_eq!(shop.len(), 1);
+        assert_eq!(shop[0].value, 3.0);
+        assert_eq!(history.storage_bytes[0].value, 1500.0);
+
+        // One sample is not a trend
+        let quotas = CapacityQuotas {
+            max_schemas_per_namespace: Some(10),
+            ..Default::default()
+        };
+        let report = generator.generate_capacity_forecast(30, 90, &quotas).unwrap();
+        assert!(report.namespaces.is_empty());
+        assert!(generator.generate_capacity_forecast(1, 90, &quotas).is_err());
+    }
+
     #[test]
     fn test_export_to_json() {
         let generator = setup();
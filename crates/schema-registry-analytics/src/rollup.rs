@@ -0,0 +1,465 @@
+//! Historical rollups with downsampling
+//!
+//! Raw events are only kept for `StorageConfig::detailed_retention_days`.
+//! Longer trends come from rollups: every [`RollupStore::run`] summarizes
+//! completed minutes of raw events into 1m buckets, completed hours of 1m
+//! buckets into 1h buckets and completed days of 1h buckets into 1d buckets,
+//! then drops the buckets that are past their tier's retention.
+//!
+//! A bucket keeps counts and the latency sum, min and max, so buckets merge
+//! into coarser ones without loss. Percentiles are not kept.
+//!
+//! Each tier remembers how far it has been rolled up. An event that arrives
+//! after its minute was rolled up is still stored raw, but is not added to
+//! the rollups.
+
+use crate::error::{AnalyticsError, Result};
+use crate::storage::AnalyticsStorage;
+use crate::types::{Operation, SchemaId, SchemaUsageEvent, TimePeriod};
+use chrono::{DateTime, Duration, Utc};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use tracing::debug;
+
+/// Rollup tiers, finest first
+pub const ROLLUP_PERIODS: [TimePeriod; 3] =
+    [TimePeriod::Minute1, TimePeriod::Hour1, TimePeriod::Day1];
+
+/// How long each rollup tier is kept
+///
+/// A tier must be kept for at least one period of the next coarser tier,
+/// otherwise buckets can be dropped before they are rolled up.
+#[derive(Debug, Clone)]
+pub struct RollupConfig {
+    /// Days of 1m buckets
+    pub minute_retention_days: i64,
+
+    /// Days of 1h buckets
+    pub hour_retention_days: i64,
+
+    /// Days of 1d buckets
+    pub day_retention_days: i64,
+}
+
+impl Default for RollupConfig {
+    fn default() -> Self {
+        Self {
+            minute_retention_days: 2,
+            hour_retention_days: 35,
+            day_retention_days: 400,
+        }
+    }
+}
+
+impl RollupConfig {
+    /// Retention of a rollup tier, `None` for periods that aren't rolled up
+    pub fn retention(&self, period: TimePeriod) -> Option<Duration> {
+        match period {
+            TimePeriod::Minute1 => Some(Duration::days(self.minute_retention_days)),
+            TimePeriod::Hour1 => Some(Duration::days(self.hour_retention_days)),
+            TimePeriod::Day1 => Some(Duration::days(self.day_retention_days)),
+            TimePeriod::Minute5 => None,
+        }
+    }
+}
+
+/// Summary of one window, globally or for one schema
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Rollup {
+    pub period: TimePeriod,
+    pub window_start: DateTime<Utc>,
+    /// `None` for the global rollup
+    pub schema_id: Option<SchemaId>,
+    pub total_count: u64,
+    pub success_count: u64,
+    pub failure_count: u64,
+    pub latency_sum_ms: u64,
+    pub min_latency_ms: u64,
+    pub max_latency_ms: u64,
+    pub operations: HashMap<Operation, u64>,
+}
+
+impl Rollup {
+    fn empty(period: TimePeriod, window_start: DateTime<Utc>, schema_id: Option<SchemaId>) -> Self {
+        Self {
+            period,
+            window_start,
+            schema_id,
+            total_count: 0,
+            success_count: 0,
+            failure_count: 0,
+            latency_sum_ms: 0,
+            min_latency_ms: u64::MAX,
+            max_latency_ms: 0,
+            operations: HashMap::new(),
+        }
+    }
+
+    fn add_event(&mut self, event: &SchemaUsageEvent) {
+        self.total_count += 1;
+        if event.success {
+            self.success_count += 1;
+        } else {
+            self.failure_count += 1;
+        }
+        self.latency_sum_ms += event.latency_ms;
+        self.min_latency_ms = self.min_latency_ms.min(event.latency_ms);
+        self.max_latency_ms = self.max_latency_ms.max(event.latency_ms);
+        *self.operations.entry(event.operation).or_insert(0) += 1;
+    }
+
+    fn merge(&mut self, other: &Rollup) {
+        self.total_count += other.total_count;
+        self.success_count += other.success_count;
+        self.failure_count += other.failure_count;
+        self.latency_sum_ms += other.latency_sum_ms;
+        self.min_latency_ms = self.min_latency_ms.min(other.min_latency_ms);
+        self.max_latency_ms = self.max_latency_ms.max(other.max_latency_ms);
+        for (operation, count) in &other.operations {
+            *self.operations.entry(*operation).or_insert(0) += count;
+        }
+    }
+
+    pub fn window_end(&self) -> DateTime<Utc> {
+        self.window_start + Duration::seconds(self.period.duration_seconds())
+    }
+
+    pub fn avg_latency_ms(&self) -> f64 {
+        if self.total_count > 0 {
+            self.latency_sum_ms as f64 / self.total_count as f64
+        } else {
+            0.0
+        }
+    }
+
+    pub fn success_rate(&self) -> f64 {
+        if self.total_count > 0 {
+            self.success_count as f64 / self.total_count as f64
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Outcome of one rollup run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RollupRun {
+    /// Raw events summarized into 1m buckets
+    pub events_rolled_up: usize,
+    /// Buckets merged into a coarser tier
+    pub buckets_downsampled: usize,
+    /// Buckets dropped past retention
+    pub buckets_pruned: usize,
+}
+
+type Buckets = BTreeMap<i64, HashMap<Option<SchemaId>, Rollup>>;
+
+#[derive(Default)]
+struct Tier {
+    buckets: Buckets,
+    /// Windows before this timestamp are complete
+    rolled_up_to: Option<i64>,
+}
+
+impl Tier {
+    fn bucket(
+        &mut self,
+        period: TimePeriod,
+        window_start: i64,
+        schema_id: Option<SchemaId>,
+    ) -> &mut Rollup {
+        self.buckets
+            .entry(window_start)
+            .or_default()
+            .entry(schema_id.clone())
+            .or_insert_with(|| {
+                let start = DateTime::from_timestamp(window_start, 0).unwrap_or_default();
+                Rollup::empty(period, start, schema_id)
+            })
+    }
+}
+
+/// Downsampled history for the 1m, 1h and 1d tiers
+pub struct RollupStore {
+    tiers: RwLock<[Tier; 3]>,
+    config: RollupConfig,
+}
+
+impl RollupStore {
+    pub fn new() -> Self {
+        Self::with_config(RollupConfig::default())
+    }
+
+    pub fn with_config(config: RollupConfig) -> Self {
+        Self {
+            tiers: RwLock::new(Default::default()),
+            config,
+        }
+    }
+
+    /// Roll up completed windows and drop expired buckets
+    ///
+    /// Run this before [`AnalyticsStorage::cleanup`] so raw events are
+    /// summarized before they are dropped.
+    pub fn run(&self, storage: &AnalyticsStorage, now: DateTime<Utc>) -> Result<RollupRun> {
+        let mut run = RollupRun::default();
+        let mut tiers = self.tiers.write();
+
+        // Raw events into 1m buckets
+        let minute_end = TimePeriod::Minute1.round_down(now);
+        let minute_start = match tiers[0].rolled_up_to {
+            Some(ts) => DateTime::from_timestamp(ts, 0),
+            None => storage.get_storage_stats().oldest_event,
+        };
+        if let Some(start) = minute_start.filter(|start| *start < minute_end) {
+            let minutes = &mut tiers[0];
+            for event in storage.get_events(start, minute_end, None)? {
+                let window = TimePeriod::Minute1.round_down(event.timestamp).timestamp();
+                minutes
+                    .bucket(TimePeriod::Minute1, window, None)
+                    .add_event(&event);
+                minutes
+                    .bucket(TimePeriod::Minute1, window, Some(event.schema_id.clone()))
+                    .add_event(&event);
+                run.events_rolled_up += 1;
+            }
+            minutes.rolled_up_to = Some(minute_end.timestamp());
+        }
+
+        // 1m into 1h, then 1h into 1d
+        for i in 1..ROLLUP_PERIODS.len() {
+            let (finer, coarser) = tiers.split_at_mut(i);
+            run.buckets_downsampled +=
+                downsample(&finer[i - 1], &mut coarser[0], ROLLUP_PERIODS[i]);
+        }
+
+        for (tier, period) in tiers.iter_mut().zip(ROLLUP_PERIODS) {
+            let Some(retention) = self.config.retention(period) else {
+                continue;
+            };
+            let cutoff = period.round_down(now - retention).timestamp();
+            let kept = tier.buckets.split_off(&cutoff);
+            let expired = std::mem::replace(&mut tier.buckets, kept);
+            run.buckets_pruned += expired.values().map(HashMap::len).sum::<usize>();
+        }
+
+        if run != RollupRun::default() {
+            debug!(
+                events = run.events_rolled_up,
+                downsampled = run.buckets_downsampled,
+                pruned = run.buckets_pruned,
+                "Rolled up analytics history"
+            );
+        }
+
+        Ok(run)
+    }
+
+    /// Buckets of one tier overlapping `[start_time, end_time)`
+    ///
+    /// Windows without traffic are omitted.
+    pub fn query(
+        &self,
+        period: TimePeriod,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+        schema_id: Option<&SchemaId>,
+    ) -> Result<Vec<Rollup>> {
+        if start_time >= end_time {
+            return Err(AnalyticsError::InvalidTimeRange {
+                start: start_time.to_rfc3339(),
+                end: end_time.to_rfc3339(),
+            });
+        }
+        let index = tier_index(period).ok_or_else(|| {
+            AnalyticsError::invalid_parameter(format!("{:?} is not a rollup period", period))
+        })?;
+
+        let tiers = self.tiers.read();
+        let start = period.round_down(start_time).timestamp();
+        Ok(tiers[index]
+            .buckets
+            .range(start..end_time.timestamp())
+            .filter_map(|(_, group)| group.get(&schema_id.cloned()).cloned())
+            .collect())
+    }
+
+    /// Buckets of the finest tier that still covers `start_time`
+    pub fn trend(
+        &self,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+        schema_id: Option<&SchemaId>,
+    ) -> Result<Vec<Rollup>> {
+        let now = Utc::now();
+        let period = ROLLUP_PERIODS
+            .into_iter()
+            .find(|period| {
+                self.config
+                    .retention(*period)
+                    .is_some_and(|retention| start_time >= now - retention)
+            })
+            .unwrap_or(TimePeriod::Day1);
+        self.query(period, start_time, end_time, schema_id)
+    }
+
+    /// Number of buckets across all tiers
+    pub fn bucket_count(&self) -> usize {
+        self.tiers
+            .read()
+            .iter()
+            .flat_map(|tier| tier.buckets.values())
+            .map(HashMap::len)
+            .sum()
+    }
+}
+
+impl Default for RollupStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn tier_index(period: TimePeriod) -> Option<usize> {
+    ROLLUP_PERIODS.iter().position(|p| *p == period)
+}
+
+/// Merge the finer tier's completed windows of `period` into the coarser tier
+fn downsample(finer: &Tier, coarser: &mut Tier, period: TimePeriod) -> usize {
+    let Some(finer_to) = finer.rolled_up_to else {
+        return 0;
+    };
+    let Some(end) = DateTime::from_timestamp(finer_to, 0).map(|t| period.round_down(t).timestamp())
+    else {
+        return 0;
+    };
+    let start = coarser.rolled_up_to.unwrap_or(i64::MIN);
+    if start >= end {
+        return 0;
+    }
+
+    let mut merged = 0;
+    for (&window, group) in finer.buckets.range(start..end) {
+        let coarse_window = window - window.rem_euclid(period.duration_seconds());
+        for (schema_id, rollup) in group {
+            coarser
+                .bucket(period, coarse_window, schema_id.clone())
+                .merge(rollup);
+            merged += 1;
+        }
+    }
+    coarser.rolled_up_to = Some(end);
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn event_at(
+        schema_id: Uuid,
+        timestamp: DateTime<Utc>,
+        latency_ms: u64,
+        success: bool,
+    ) -> SchemaUsageEvent {
+        let mut event = SchemaUsageEvent::new(
+            schema_id,
+            Operation::Read,
+            "client".to_string(),
+            "us-west-1".to_string(),
+            latency_ms,
+            success,
+        );
+        event.timestamp = timestamp;
+        event
+    }
+
+    #[test]
+    fn test_downsampling_cascade() {
+        let storage = AnalyticsStorage::new();
+        let rollups = RollupStore::new();
+        let schema = Uuid::new_v4();
+        let now = Utc::now();
+        let day = TimePeriod::Day1.round_down(now) - Duration::days(1);
+
+        storage
+            .store_event(event_at(schema, day + Duration::minutes(5), 10, true))
+            .unwrap();
+        storage
+            .store_event(event_at(schema, day + Duration::minutes(5), 30, false))
+            .unwrap();
+        storage
+            .store_event(event_at(schema, day + Duration::hours(3), 20, true))
+            .unwrap();
+
+        let run = rollups.run(&storage, now).unwrap();
+        assert_eq!(run.events_rolled_up, 3);
+
+        let minutes = rollups
+            .query(
+                TimePeriod::Minute1,
+                day,
+                day + Duration::days(1),
+                Some(&schema.into()),
+            )
+            .unwrap();
+        assert_eq!(minutes.len(), 2);
+        assert_eq!(minutes[0].total_count, 2);
+        assert_eq!(minutes[0].avg_latency_ms(), 20.0);
+
+        let days = rollups
+            .query(TimePeriod::Day1, day, day + Duration::days(1), None)
+            .unwrap();
+        assert_eq!(days.len(), 1);
+        let total = &days[0];
+        assert_eq!(
+            (total.total_count, total.success_count, total.failure_count),
+            (3, 2, 1)
+        );
+        assert_eq!((total.min_latency_ms, total.max_latency_ms), (10, 30));
+        assert_eq!(total.operations[&Operation::Read], 3);
+
+        // A second run adds nothing
+        let run = rollups.run(&storage, now).unwrap();
+        assert_eq!((run.events_rolled_up, run.buckets_downsampled), (0, 0));
+        let days = rollups
+            .query(TimePeriod::Day1, day, day + Duration::days(1), None)
+            .unwrap();
+        assert_eq!(days[0].total_count, 3);
+
+        assert!(rollups.query(TimePeriod::Minute5, day, now, None).is_err());
+    }
+
+    #[test]
+    fn test_expired_buckets_are_pruned() {
+        let storage = AnalyticsStorage::new();
+        let rollups = RollupStore::new();
+        let now = Utc::now();
+        let old = now - Duration::days(10);
+        storage
+            .store_event(event_at(Uuid::new_v4(), old, 5, true))
+            .unwrap();
+
+        let run = rollups.run(&storage, now).unwrap();
+        assert!(run.buckets_pruned > 0);
+
+        // Minutes are gone, hours and days keep the history
+        let range = (old - Duration::days(1), now);
+        assert!(rollups
+            .query(TimePeriod::Minute1, range.0, range.1, None)
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            rollups
+                .query(TimePeriod::Hour1, range.0, range.1, None)
+                .unwrap()
+                .len(),
+            1
+        );
+        let trend = rollups.trend(range.0, range.1, None).unwrap();
+        assert_eq!(trend[0].period, TimePeriod::Hour1);
+        assert_eq!(trend[0].total_count, 1);
+    }
+}
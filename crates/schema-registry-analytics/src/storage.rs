@@ -573,6 +573,7 @@ mod tests {
             operation: Operation::Read,
             timestamp: Utc::now() - chrono::Duration::days(100),
             client_id: "client-1".to_string(),
+            principal: None,
             region: "us-west-1".to_string(),
             latency_ms: 100,
             success: true,
@@ -599,6 +600,7 @@ mod tests {
             operation: Operation::Read,
             timestamp: Utc::now() - chrono::Duration::days(2),
             client_id: "client-1".to_string(),
+            principal: None,
             region: "us-west-1".to_string(),
             latency_ms: 100,
             success: true,
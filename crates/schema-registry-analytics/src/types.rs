@@ -126,6 +126,9 @@ pub struct SchemaUsageEvent {
     pub timestamp: DateTime<Utc>,
     /// Client/application identifier
     pub client_id: String,
+    /// Authenticated principal, e.g. a user ID or a workload's SPIFFE ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub principal: Option<String>,
     /// Region/datacenter identifier
     pub region: String,
     /// Operation latency in milliseconds
@@ -154,6 +157,7 @@ impl SchemaUsageEvent {
             operation,
             timestamp: Utc::now(),
             client_id,
+            principal: None,
             region,
             latency_ms,
             success,
@@ -177,6 +181,7 @@ impl SchemaUsageEvent {
             operation,
             timestamp: Utc::now(),
             client_id,
+            principal: None,
             region,
             latency_ms,
             success: false,
@@ -190,6 +195,12 @@ impl SchemaUsageEvent {
         self.metadata.insert(key, value);
         self
     }
+
+    /// Record the authenticated principal that performed the operation
+    pub fn with_principal(mut self, principal: impl Into<String>) -> Self {
+        self.principal = Some(principal.into());
+        self
+    }
 }
 
 /// Aggregated usage statistics for a time period
@@ -260,8 +271,14 @@ pub struct OperationStats {
     pub success_count: u64,
     /// Average latency
     pub avg_latency_ms: f64,
+    /// 50th percentile latency
+    #[serde(default)]
+    pub p50_latency_ms: u64,
     /// 95th percentile latency
     pub p95_latency_ms: u64,
+    /// 99th percentile latency
+    #[serde(default)]
+    pub p99_latency_ms: u64,
 }
 
 /// Statistics for a specific region
@@ -549,6 +566,13 @@ mod tests {
         assert_eq!(event.region, "us-west-1");
         assert_eq!(event.latency_ms, 150);
         assert!(event.success);
+        assert!(event.principal.is_none());
+
+        let event = event.with_principal("spiffe://prod.example.org/ns/payments/sa/producer");
+        assert_eq!(
+            event.principal.as_deref(),
+            Some("spiffe://prod.example.org/ns/payments/sa/producer")
+        );
     }
 
     #[test]
@@ -0,0 +1,323 @@
+//! Minimal HTTP client for the registry REST API
+
+use schema_registry_analytics::{
+    Anomaly, CapacityHistory, Operation, SchemaHealthScore, TopSchemaEntry, UsageHeatReport,
+};
+use schema_registry_core::subject_config::{SubjectConfigChange, SubjectConfigFile};
+use schema_registry_lineage::Consumer;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::error::{CliError, Result};
+
+/// A version entry from `GET /api/v1/subjects/{subject}/versions`
+#[derive(Debug, Clone, Deserialize)]
+pub struct SubjectVersion {
+    pub id: String,
+    pub version: String,
+    #[serde(default)]
+    pub state: String,
+}
+
+/// States whose content the registry no longer serves (410 and 423)
+const UNREADABLE_STATES: [&str; 2] = ["DELETED", "QUARANTINED"];
+
+#[derive(Debug, Deserialize)]
+struct SubjectVersionsResponse {
+    versions: Vec<SubjectVersion>,
+}
+
+/// A schema from `GET /api/v1/schemas/{id}`
+#[derive(Debug, Clone, Deserialize)]
+pub struct RemoteSchema {
+    pub version: String,
+    pub format: String,
+    pub content: String,
+}
+
+/// Recent use and known consumers from `GET /api/v1/schemas/{id}/usage`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaUsage {
+    pub schema_id: String,
+    pub usage: UsageHeatReport,
+    pub consumers: Vec<Consumer>,
+}
+
+/// An archived or deleted schema
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetiredSchema {
+    pub subject: String,
+    pub state: String,
+    pub forced: bool,
+    #[serde(flatten)]
+    pub usage: SchemaUsage,
+}
+
+/// Header naming the operation ID that undoes a destructive request
+const OPERATION_ID_HEADER: &str = "x-operation-id";
+
+/// The response to a request the registry snapshotted before running
+#[derive(Debug, Clone)]
+pub struct Undoable<T> {
+    pub value: T,
+    /// Pass to `admin undo --operation-id`; `None` when nothing was changed
+    pub operation_id: Option<String>,
+}
+
+/// What `POST /api/v1/admin/operations/{id}/undo` put back
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UndoneOperation {
+    pub operation_id: String,
+    pub operation: String,
+    pub restored: RestoredRows,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RestoredRows {
+    pub schemas: u64,
+    pub dependencies: u64,
+    pub skipped_dependencies: u64,
+    pub events: u64,
+    pub subjects: u64,
+}
+
+pub struct RegistryClient {
+    http: reqwest::Client,
+    base_url: String,
+    api_key: Option<String>,
+}
+
+impl RegistryClient {
+    pub fn new(config: &Config) -> Result<Self> {
+        let timeout = if config.timeout_seconds == 0 { 30 } else { config.timeout_seconds };
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(timeout))
+            .build()
+            .map_err(|e| CliError::ConfigError(format!("Failed to create HTTP client: {}", e)))?;
+
+        Ok(Self {
+            http,
+            base_url: config.registry_url.trim_end_matches('/').to_string(),
+            api_key: config.api_key.clone(),
+        })
+    }
+
+    /// Versions of a subject whose content can still be fetched, oldest first
+    ///
+    /// Deleted and quarantined versions are left out.
+    pub async fn subject_versions(&self, subject: &str) -> Result<Vec<SubjectVersion>> {
+        Ok(self
+            .all_subject_versions(subject)
+            .await?
+            .into_iter()
+            .filter(|entry| !UNREADABLE_STATES.contains(&entry.state.as_str()))
+            .collect())
+    }
+
+    async fn all_subject_versions(&self, subject: &str) -> Result<Vec<SubjectVersion>> {
+        let response: SubjectVersionsResponse =
+            self.get(&format!("/api/v1/subjects/{}/versions", subject)).await?;
+        Ok(response.versions)
+    }
+
+    pub async fn get_schema(&self, id: &str) -> Result<RemoteSchema> {
+        self.get(&format!("/api/v1/schemas/{}", id)).await
+    }
+
+    /// The schema registered as `version` of a subject
+    ///
+    /// Fails without fetching it when the version was deleted or quarantined.
+    pub async fn schema_at_version(&self, subject: &str, version: &str) -> Result<RemoteSchema> {
+        let entry = self
+            .all_subject_versions(subject)
+            .await?
+            .into_iter()
+            .find(|entry| entry.version == version)
+            .ok_or_else(|| CliError::NotFound(format!("{} version {}", subject, version)))?;
+        if UNREADABLE_STATES.contains(&entry.state.as_str()) {
+            return Err(CliError::ApiError(format!(
+                "{} version {} is {} and can't be read",
+                subject,
+                version,
+                entry.state.to_lowercase()
+            )));
+        }
+        self.get_schema(&entry.id).await
+    }
+
+    /// Who used a schema in the last 30 days, and what is known to read it
+    pub async fn schema_usage(&self, id: &str) -> Result<SchemaUsage> {
+        self.get(&format!("/api/v1/schemas/{}/usage", id)).await
+    }
+
+    /// Archive a schema; a reason forces it through even if the schema is in use
+    pub async fn archive_schema(&self, id: &str, reason: Option<&str>) -> Result<RetiredSchema> {
+        let path = format!("/api/v1/schemas/{}/archive", id);
+        let request = self.http.post(format!("{}{}", self.base_url, path));
+        self.send("POST", &path, with_force(request, reason)).await
+    }
+
+    /// Delete a schema; a reason forces it through even if the schema is in use
+    pub async fn delete_schema(
+        &self,
+        id: &str,
+        reason: Option<&str>,
+    ) -> Result<Undoable<RetiredSchema>> {
+        let path = format!("/api/v1/schemas/{}", id);
+        let request = self.http.delete(format!("{}{}", self.base_url, path));
+        self.send_undoable("DELETE", &path, with_force(request, reason))
+            .await
+    }
+
+    /// Restore what an earlier destructive request changed
+    pub async fn undo_operation(&self, operation_id: &str) -> Result<Undoable<UndoneOperation>> {
+        let path = format!("/api/v1/admin/operations/{}/undo", operation_id);
+        let request = self.http.post(format!("{}{}", self.base_url, path));
+        self.send_undoable("POST", &path, request).await
+    }
+
+    /// Most used schemas for an operation, highest first
+    pub async fn top_schemas(&self, operation: Operation, limit: usize) -> Result<Vec<TopSchemaEntry>> {
+        self.get(&format!("/api/v1/analytics/top?operation={}&limit={}", operation, limit))
+            .await
+    }
+
+    pub async fn subject_health(&self, subject: &str) -> Result<SchemaHealthScore> {
+        self.get(&format!("/api/v1/analytics/subjects/{}/health", subject)).await
+    }
+
+    /// Anomalies detected over the last `hours`
+    pub async fn anomalies(&self, hours: i64) -> Result<Vec<Anomaly>> {
+        self.get(&format!("/api/v1/analytics/anomalies?hours={}", hours)).await
+    }
+
+    /// Daily capacity samples over the last `lookback_days` (admin only)
+    pub async fn capacity_history(&self, lookback_days: i64) -> Result<CapacityHistory> {
+        self.get(&format!("/api/v1/admin/capacity/history?lookback_days={}", lookback_days))
+            .await
+    }
+
+    /// The registry's subject settings in config file form
+    pub async fn subject_config(&self) -> Result<SubjectConfigFile> {
+        self.get("/api/v1/config/subjects").await
+    }
+
+    /// Apply a subject config file; returns the changes made (or, on a dry
+    /// run, the changes that would be made)
+    pub async fn apply_subject_config(
+        &self,
+        file: &SubjectConfigFile,
+        prune: bool,
+        dry_run: bool,
+    ) -> Result<Undoable<Vec<SubjectConfigChange>>> {
+        let path = format!("/api/v1/admin/config/subjects?prune={}&dry_run={}", prune, dry_run);
+        let request = self.http.post(format!("{}{}", self.base_url, path)).json(file);
+        self.send_undoable("POST", &path, request).await
+    }
+
+    /// Bindings generated from a schema, in `lang` (rust, python or ts)
+    pub async fn codegen(&self, id: &str, lang: &str) -> Result<String> {
+        let path = format!("/api/v1/schemas/{}/codegen?lang={}", id, lang);
+        let request = self.http.get(format!("{}{}", self.base_url, path));
+        let url = format!("{}{}", self.base_url, path);
+        self.execute("GET", &path, request)
+            .await?
+            .text()
+            .await
+            .map_err(|e| CliError::ApiError(format!("Invalid response from {}: {}", url, e)))
+    }
+
+    /// Send a JSON body with `method` (`POST` or `PUT`), returning the
+    /// response body
+    pub async fn send_json(
+        &self,
+        method: &str,
+        path: &str,
+        body: &serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        let url = format!("{}{}", self.base_url, path);
+        let request = match method {
+            "PUT" => self.http.put(url),
+            _ => self.http.post(url),
+        };
+        self.send(method, path, request.json(body)).await
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+        let request = self.http.get(format!("{}{}", self.base_url, path));
+        self.send("GET", path, request).await
+    }
+
+    async fn send<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        path: &str,
+        request: reqwest::RequestBuilder,
+    ) -> Result<T> {
+        let url = format!("{}{}", self.base_url, path);
+        self.execute(method, path, request)
+            .await?
+            .json()
+            .await
+            .map_err(|e| CliError::SerializationError(format!("Invalid response from {}: {}", url, e)))
+    }
+
+    /// Like [`Self::send`], keeping the operation ID the registry returns
+    async fn send_undoable<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        path: &str,
+        request: reqwest::RequestBuilder,
+    ) -> Result<Undoable<T>> {
+        let url = format!("{}{}", self.base_url, path);
+        let response = self.execute(method, path, request).await?;
+        let operation_id = response
+            .headers()
+            .get(OPERATION_ID_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        let value = response
+            .json()
+            .await
+            .map_err(|e| CliError::SerializationError(format!("Invalid response from {}: {}", url, e)))?;
+        Ok(Undoable { value, operation_id })
+    }
+
+    /// Send a request, turning error statuses into errors
+    async fn execute(
+        &self,
+        method: &str,
+        path: &str,
+        mut request: reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response> {
+        let url = format!("{}{}", self.base_url, path);
+        if let Some(key) = &self.api_key {
+            request = request.bearer_auth(key);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| CliError::ApiError(format!("{} {} failed: {}", method, url, e)))?;
+
+        let status = response.status();
+        if status == reqwest::StatusCode::NOT_FOUND {
+            return Err(CliError::NotFound(path.to_string()));
+        }
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(CliError::ApiError(format!("{} {} returned {}: {}", method, url, status, body)));
+        }
+        Ok(response)
+    }
+}
+
+/// Force a retirement when a reason is given
+fn with_force(request: reqwest::RequestBuilder, reason: Option<&str>) -> reqwest::RequestBuilder {
+    match reason {
+        Some(reason) => request.query(&[("force", "true"), ("reason", reason)]),
+        None => request,
+    }
+}
@@ -1,8 +1,16 @@
 //! Administrative commands
 
+use std::path::{Path, PathBuf};
+
 use clap::Subcommand;
+use schema_registry_core::fixtures::{Fixtures, SeedRequest};
 
-use crate::{config::Config, error::Result, output};
+use crate::client::RegistryClient;
+use crate::{
+    config::Config,
+    error::{CliError, Result},
+    output,
+};
 
 #[derive(Subcommand)]
 pub enum AdminCommand {
@@ -55,6 +63,28 @@ pub enum AdminCommand {
         confirm: bool,
     },
 
+    /// Undo a schema delete or subject config apply from its snapshot
+    Undo {
+        /// Operation ID printed by the command being undone
+        #[arg(long)]
+        operation_id: String,
+
+        /// Don't ask for confirmation
+        #[arg(short, long)]
+        confirm: bool,
+    },
+
+    /// Load namespaces, schemas, webhooks and lineage from a fixture file
+    Seed {
+        /// Fixture file (YAML)
+        #[arg(short, long)]
+        file: PathBuf,
+
+        /// Show the requests without sending them
+        #[arg(long)]
+        dry_run: bool,
+    },
+
     /// Cache management
     #[command(subcommand)]
     Cache(CacheCommand),
@@ -138,6 +168,10 @@ pub async fn execute(cmd: AdminCommand, config: &Config, format: output::OutputF
         AdminCommand::Restore { file, confirm } => {
             restore_backup(config, &file, confirm, format).await
         }
+        AdminCommand::Undo { operation_id, confirm } => {
+            undo_operation(config, &operation_id, confirm, format).await
+        }
+        AdminCommand::Seed { file, dry_run } => seed(config, &file, dry_run, format).await,
         AdminCommand::Cache(cache_cmd) => execute_cache(cache_cmd, config, format).await,
         AdminCommand::Metrics { metric_type } => {
             show_metrics(config, metric_type.as_deref(), format).await
@@ -316,6 +350,83 @@ async fn restore_backup(_config: &Config, file: &str, confirm: bool, _format: ou
     Ok(())
 }
 
+async fn undo_operation(
+    config: &Config,
+    operation_id: &str,
+    confirm: bool,
+    format: output::OutputFormat,
+) -> Result<()> {
+    if !confirm && !output::confirm(&format!("Really undo operation {}?", operation_id))? {
+        output::print_warning("Operation not undone");
+        return Ok(());
+    }
+
+    let client = RegistryClient::new(config)?;
+    let undone = client.undo_operation(operation_id).await?;
+    let restored = &undone.value.restored;
+    match format {
+        output::OutputFormat::Json | output::OutputFormat::Yaml => output::print(&undone.value, format)?,
+        _ => output::print_table(
+            vec!["Restored", "Count"],
+            vec![
+                vec!["Schemas".to_string(), restored.schemas.to_string()],
+                vec!["Dependencies".to_string(), restored.dependencies.to_string()],
+                vec!["Events".to_string(), restored.events.to_string()],
+                vec!["Subject configs".to_string(), restored.subjects.to_string()],
+            ],
+        ),
+    }
+    if restored.skipped_dependencies > 0 {
+        output::print_warning(&format!(
+            "{} dependency edge(s) not restored; the schemas they point to are gone",
+            restored.skipped_dependencies
+        ));
+    }
+    output::print_success(&format!("Undid {} ({})", operation_id, undone.value.operation));
+    if let Some(operation_id) = undone.operation_id {
+        output::print_info(&format!("Undo this undo with: schema-cli admin undo --operation-id {}", operation_id));
+    }
+    Ok(())
+}
+
+async fn seed(config: &Config, file: &Path, dry_run: bool, format: output::OutputFormat) -> Result<()> {
+    let requests = read_fixtures(file)?;
+    if dry_run {
+        match format {
+            output::OutputFormat::Table | output::OutputFormat::Plain => output::print_table(
+                vec!["Method", "Path", "Creates"],
+                requests
+                    .iter()
+                    .map(|r| vec![r.method.to_string(), r.path.clone(), r.description.clone()])
+                    .collect(),
+            ),
+            _ => output::print(&requests, format)?,
+        }
+        output::print_success(&format!("Would send {} request(s) from {}", requests.len(), file.display()));
+        return Ok(());
+    }
+
+    let client = RegistryClient::new(config)?;
+    for request in &requests {
+        client
+            .send_json(request.method, &request.path, &request.body)
+            .await
+            .map_err(|e| CliError::Other(format!("Seeding {} failed: {}", request.description, e)))?;
+        output::print_info(&format!("Created {}", request.description));
+    }
+    output::print_success(&format!("Seeded {} item(s) from {}", requests.len(), file.display()));
+    Ok(())
+}
+
+/// Read a fixture file and turn it into the requests that create it
+fn read_fixtures(path: &Path) -> Result<Vec<SeedRequest>> {
+    let content = std::fs::read_to_string(path)?;
+    let fixtures: Fixtures = serde_yaml::from_str(&content)?;
+    fixtures
+        .requests()
+        .map_err(|e| CliError::ValidationError(format!("{}: {}", path.display(), e)))
+}
+
 async fn execute_cache(cmd: CacheCommand, _config: &Config, _format: output::OutputFormat) -> Result<()> {
     match cmd {
         CacheCommand::Stats => {
@@ -364,3 +475,29 @@ async fn show_metrics(_config: &Config, metric_type: Option<&str>, _format: outp
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_fixtures_from_yaml() {
+        let path = std::env::temp_dir().join(format!("fixtures-{}.yaml", uuid::Uuid::new_v4()));
+        std::fs::write(
+            &path,
+            "namespaces:\n  com.example:\n    owner: identity-team\n\
+             schemas:\n  - subject: com.example.User\n    versions:\n      - schema: {type: object}\n\
+             lineage:\n  - from: com.example.User\n    entity: {id: billing, type: APPLICATION}\n    relation: USED_BY\n",
+        )
+        .unwrap();
+
+        let requests = read_fixtures(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let requests = requests.unwrap();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[0].method, "PUT");
+        assert_eq!(requests[1].body["schema"]["type"], "object");
+        assert_eq!(requests[2].description, "lineage com.example.User -> billing");
+    }
+}
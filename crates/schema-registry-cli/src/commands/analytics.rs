@@ -1,8 +1,19 @@
 //! Analytics commands
 
+use std::future::Future;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
 use clap::Subcommand;
+use schema_registry_analytics::{CapacityHistory, CapacityQuotas, GrowthTrend, Operation};
 
-use crate::{config::Config, error::Result, output};
+use crate::{
+    client::RegistryClient,
+    config::Config,
+    error::{CliError, Result},
+    output,
+};
 
 #[derive(Subcommand)]
 pub enum AnalyticsCommand {
@@ -33,9 +44,13 @@ pub enum AnalyticsCommand {
         #[arg(short, long, default_value = "10")]
         limit: usize,
 
-        /// Metric (reads, writes, validations, errors)
-        #[arg(short, long, default_value = "reads")]
-        metric: String,
+        /// Operation to rank by (read, write, validate, compatibility, delete, search)
+        #[arg(long, alias = "metric", default_value = "read")]
+        operation: String,
+
+        /// Refresh every N seconds until interrupted
+        #[arg(short, long, value_name = "SECONDS")]
+        watch: Option<u64>,
     },
 
     /// Show validation metrics
@@ -55,10 +70,53 @@ pub enum AnalyticsCommand {
         percentile: u8,
     },
 
-    /// Show health score
+    /// Show the health score of a subject
     Health {
-        /// Schema ID
-        id: Option<String>,
+        /// Subject name (namespace.name)
+        subject: String,
+
+        /// Refresh every N seconds until interrupted
+        #[arg(short, long, value_name = "SECONDS")]
+        watch: Option<u64>,
+    },
+
+    /// Show error-rate and latency anomalies
+    Anomalies {
+        /// Look-back window in hours
+        #[arg(long, default_value = "24")]
+        hours: i64,
+
+        /// Refresh every N seconds until interrupted
+        #[arg(short, long, value_name = "SECONDS")]
+        watch: Option<u64>,
+    },
+
+    /// Forecast capacity growth and when quotas will be reached
+    Forecast {
+        /// Capacity history saved from the registry (JSON); fetched from
+        /// the registry when not given
+        #[arg(long)]
+        history: Option<PathBuf>,
+
+        /// Days of history to fetch from the registry
+        #[arg(long, default_value = "90")]
+        lookback_days: i64,
+
+        /// Days to project ahead
+        #[arg(long, default_value = "90")]
+        horizon_days: i64,
+
+        /// Schema quota per namespace
+        #[arg(long)]
+        max_schemas: Option<u64>,
+
+        /// Request quota per day
+        #[arg(long)]
+        max_requests_per_day: Option<u64>,
+
+        /// Storage quota in bytes
+        #[arg(long)]
+        max_storage_bytes: Option<u64>,
     },
 }
 
@@ -70,8 +128,10 @@ pub async fn execute(cmd: AnalyticsCommand, config: &Config, format: output::Out
         AnalyticsCommand::Report { report_type, format: report_format } => {
             generate_report(config, &report_type, report_format.as_deref(), format).await
         }
-        AnalyticsCommand::Top { limit, metric } => {
-            show_top_schemas(config, limit, &metric, format).await
+        AnalyticsCommand::Top { limit, operation, watch: interval } => {
+            let operation = parse_operation(&operation)?;
+            let client = RegistryClient::new(config)?;
+            watch(interval, || show_top_schemas(&client, limit, operation, format)).await
         }
         AnalyticsCommand::Validation { id, range } => {
             show_validation_metrics(config, id.as_deref(), &range, format).await
@@ -79,8 +139,33 @@ pub async fn execute(cmd: AnalyticsCommand, config: &Config, format: output::Out
         AnalyticsCommand::Performance { percentile } => {
             show_performance_metrics(config, percentile, format).await
         }
-        AnalyticsCommand::Health { id } => {
-            show_health_score(config, id.as_deref(), format).await
+        AnalyticsCommand::Health { subject, watch: interval } => {
+            let client = RegistryClient::new(config)?;
+            watch(interval, || show_health_score(&client, &subject, format)).await
+        }
+        AnalyticsCommand::Anomalies { hours, watch: interval } => {
+            let client = RegistryClient::new(config)?;
+            watch(interval, || show_anomalies(&client, hours, format)).await
+        }
+        AnalyticsCommand::Forecast {
+            history,
+            lookback_days,
+            horizon_days,
+            max_schemas,
+            max_requests_per_day,
+            max_storage_bytes,
+        } => {
+            let quotas = CapacityQuotas {
+                max_schemas_per_namespace: max_schemas,
+                max_requests_per_day,
+                max_storage_bytes,
+                ..Default::default()
+            };
+            let history: CapacityHistory = match history {
+                Some(path) => serde_json::from_str(&std::fs::read_to_string(path)?)?,
+                None => RegistryClient::new(config)?.capacity_history(lookback_days).await?,
+            };
+            show_forecast(&history, horizon_days, &quotas, format)
         }
     }
 }
@@ -127,16 +212,31 @@ async fn generate_report(
     Ok(())
 }
 
-async fn show_top_schemas(_config: &Config, limit: usize, metric: &str, _format: output::OutputFormat) -> Result<()> {
-    output::print_info(&format!("Top {} schemas by {}", limit, metric));
+async fn show_top_schemas(
+    client: &RegistryClient,
+    limit: usize,
+    operation: Operation,
+    format: output::OutputFormat,
+) -> Result<()> {
+    let entries = client.top_schemas(operation, limit).await?;
+    if matches!(format, output::OutputFormat::Json | output::OutputFormat::Yaml) {
+        return output::print(&entries, format);
+    }
 
+    output::print_info(&format!("Top {} schemas by {}", limit, operation));
     output::print_table(
-        vec!["Rank", "Schema", "Subject", metric],
-        vec![
-            vec!["1".to_string(), "abc-123".to_string(), "com.example.User".to_string(), "45,123".to_string()],
-            vec!["2".to_string(), "def-456".to_string(), "com.example.Order".to_string(), "32,456".to_string()],
-            vec!["3".to_string(), "ghi-789".to_string(), "com.example.Product".to_string(), "28,789".to_string()],
-        ],
+        vec!["Rank", "Schema", "Count", "Trend"],
+        entries
+            .iter()
+            .map(|entry| {
+                vec![
+                    entry.rank.to_string(),
+                    entry.schema_id.to_string(),
+                    entry.value.to_string(),
+                    entry.trend.map(|t| format!("{:+}", t)).unwrap_or_else(|| "-".to_string()),
+                ]
+            })
+            .collect(),
     );
 
     Ok(())
@@ -182,21 +282,209 @@ async fn show_performance_metrics(_config: &Config, percentile: u8, _format: out
     Ok(())
 }
 
-async fn show_health_score(_config: &Config, id: Option<&str>, _format: output::OutputFormat) -> Result<()> {
-    let scope = id.map(|s| format!("schema {}", s)).unwrap_or_else(|| "registry".to_string());
-    output::print_info(&format!("Health score for {}", scope));
+async fn show_health_score(
+    client: &RegistryClient,
+    subject: &str,
+    format: output::OutputFormat,
+) -> Result<()> {
+    let health = client.subject_health(subject).await?;
+    if matches!(format, output::OutputFormat::Json | output::OutputFormat::Yaml) {
+        return output::print(&health, format);
+    }
 
-    println!("\nOverall Health: 92/100 (Excellent)");
-    println!("\nComponent Scores:");
+    output::print_info(&format!("Health score for {}", subject));
+    println!("\nOverall Health: {}/100 ({})", health.overall_score, health_label(health.overall_score));
     output::print_table(
         vec!["Component", "Score", "Status"],
-        vec![
-            vec!["Availability".to_string(), "98".to_string(), "✓ Healthy".to_string()],
-            vec!["Performance".to_string(), "95".to_string(), "✓ Healthy".to_string()],
-            vec!["Error Rate".to_string(), "88".to_string(), "⚠ Warning".to_string()],
-            vec!["Validation Success".to_string(), "92".to_string(), "✓ Healthy".to_string()],
-        ],
+        [
+            ("Success Rate", health.success_rate_score),
+            ("Performance", health.performance_score),
+            ("Activity", health.activity_score),
+        ]
+        .iter()
+        .map(|(name, score)| vec![name.to_string(), score.to_string(), health_status(*score)])
+        .collect(),
+    );
+
+    if health.is_zombie {
+        output::print_warning(&format!(
+            "No access in {} days; the schema looks unused",
+            health.days_since_last_access
+        ));
+    }
+    for recommendation in &health.recommendations {
+        output::print_info(recommendation);
+    }
+
+    Ok(())
+}
+
+async fn show_anomalies(client: &RegistryClient, hours: i64, format: output::OutputFormat) -> Result<()> {
+    let anomalies = client.anomalies(hours).await?;
+    if matches!(format, output::OutputFormat::Json | output::OutputFormat::Yaml) {
+        return output::print(&anomalies, format);
+    }
+
+    if anomalies.is_empty() {
+        output::print_success(&format!("No anomalies in the last {} hours", hours));
+        return Ok(());
+    }
+
+    output::print_info(&format!("{} anomalies in the last {} hours", anomalies.len(), hours));
+    output::print_table(
+        vec!["Detected", "Severity", "Type", "Schema", "Description"],
+        anomalies
+            .iter()
+            .map(|anomaly| {
+                vec![
+                    anomaly.detected_at.format("%Y-%m-%d %H:%M").to_string(),
+                    output::code_of(&anomaly.severity),
+                    output::code_of(&anomaly.anomaly_type),
+                    anomaly.schema_id.as_ref().map(ToString::to_string).unwrap_or_else(|| "-".to_string()),
+                    anomaly.description.clone(),
+                ]
+            })
+            .collect(),
     );
 
     Ok(())
 }
+
+fn health_label(score: u8) -> &'static str {
+    match score {
+        90..=100 => "Excellent",
+        75..=89 => "Good",
+        50..=74 => "Fair",
+        _ => "Poor",
+    }
+}
+
+fn health_status(score: u8) -> String {
+    if score >= 75 {
+        "✓ Healthy".to_string()
+    } else if score >= 50 {
+        "⚠ Warning".to_string()
+    } else {
+        "✗ Critical".to_string()
+    }
+}
+
+fn parse_operation(operation: &str) -> Result<Operation> {
+    match operation.trim().to_lowercase().as_str() {
+        "read" | "reads" => Ok(Operation::Read),
+        "write" | "writes" | "register" => Ok(Operation::Write),
+        "validate" | "validation" | "validations" => Ok(Operation::Validate),
+        "compatibility" | "check_compatibility" => Ok(Operation::CheckCompatibility),
+        "delete" | "deletes" => Ok(Operation::Delete),
+        "state_transition" => Ok(Operation::StateTransition),
+        "search" | "searches" => Ok(Operation::Search),
+        other => Err(CliError::ValidationError(format!("Unknown operation: {}", other))),
+    }
+}
+
+/// Run `render` once, or redraw it every `interval` seconds until Ctrl-C
+///
+/// While watching, a failed refresh is shown in place of the view rather
+/// than ending the loop, so a brief registry outage doesn't close the
+/// dashboard.
+async fn watch<F, Fut>(interval: Option<u64>, mut render: F) -> Result<()>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<()>>,
+{
+    let Some(seconds) = interval else {
+        return render().await;
+    };
+    let period = Duration::from_secs(seconds.max(1));
+
+    loop {
+        output::clear_screen();
+        println!(
+            "Every {}s, last refresh {} (Ctrl-C to exit)\n",
+            seconds,
+            Utc::now().format("%H:%M:%S")
+        );
+        if let Err(e) = render().await {
+            output::print_error_msg(&e.to_string());
+        }
+
+        tokio::select! {
+            _ = tokio::time::sleep(period) => {}
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+        }
+    }
+}
+
+fn show_forecast(
+    history: &CapacityHistory,
+    horizon_days: i64,
+    quotas: &CapacityQuotas,
+    format: output::OutputFormat,
+) -> Result<()> {
+    let report = history.forecast(quotas, horizon_days, Utc::now());
+
+    if matches!(format, output::OutputFormat::Json | output::OutputFormat::Yaml) {
+        return output::print(&report, format);
+    }
+
+    output::print_info(&format!(
+        "Capacity forecast through {}",
+        report.horizon_end.format("%Y-%m-%d")
+    ));
+
+    let series = report
+        .namespaces
+        .iter()
+        .map(|trend| (format!("schemas: {}", trend.name), trend))
+        .chain(report.requests_per_day.iter().map(|t| ("requests/day".to_string(), t)))
+        .chain(report.storage_bytes.iter().map(|t| ("storage".to_string(), t)));
+    let rows = series
+        .map(|(label, trend)| {
+            let value = |v: f64| {
+                if trend.name == "storage_bytes" {
+                    output::format_size(v.max(0.0) as u64)
+                } else {
+                    format!("{:.0}", v)
+                }
+            };
+            vec![
+                label,
+                value(trend.current),
+                format!("{:+.1}/day", trend.growth_per_day),
+                value(trend.projected),
+                format!("{:?} (R² {:.2})", trend.fit.model, trend.fit.r_squared),
+                quota_status(trend, &report.horizon_end),
+            ]
+        })
+        .collect();
+    output::print_table(
+        vec!["Series", "Current", "Growth", "Projected", "Model", "Quota reached"],
+        rows,
+    );
+
+    let at_risk = report.at_risk();
+    if at_risk.is_empty() {
+        output::print_success("No quota is projected to be reached within the horizon");
+    } else {
+        for trend in at_risk {
+            output::print_warning(&format!(
+                "{} reaches its quota around {}",
+                trend.name,
+                trend.quota_reached_at.unwrap_or(report.generated_at).format("%Y-%m-%d")
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn quota_status(trend: &GrowthTrend, horizon_end: &DateTime<Utc>) -> String {
+    match (trend.quota, trend.quota_reached_at) {
+        (None, _) => "-".to_string(),
+        (Some(_), None) => "not growing".to_string(),
+        (Some(_), Some(at)) if at > *horizon_end => {
+            format!("{} (after horizon)", at.format("%Y-%m-%d"))
+        }
+        (Some(_), Some(at)) => at.format("%Y-%m-%d").to_string(),
+    }
+}
@@ -1,34 +1,53 @@
 //! Migration commands
 
-use clap::Subcommand;
+use std::path::{Path, PathBuf};
 
-use crate::{config::Config, error::Result, output};
+use clap::Subcommand;
+use schema_registry_core::versioning::SemanticVersion;
+use schema_registry_migration::{Language, MigrationEngine, MigrationPlan, MigrationValidator};
+use serde::Serialize;
+
+use crate::{
+    client::RegistryClient,
+    commands::schema::parse_format,
+    config::Config,
+    error::{CliError, Result},
+    output,
+};
 
 #[derive(Subcommand)]
 pub enum MigrationCommand {
-    /// Generate migration code
+    /// Generate migration code between two registered versions of a subject
     Generate {
-        /// From schema (ID or version)
+        /// Subject name (namespace.name)
+        #[arg(short, long)]
+        subject: String,
+
+        /// Version to migrate from
         #[arg(short, long)]
         from: String,
 
-        /// To schema (ID or version)
+        /// Version to migrate to
         #[arg(short, long)]
         to: String,
 
-        /// Target language (python, typescript, java, go, sql)
-        #[arg(short, long)]
-        language: String,
+        /// Target languages, comma separated (python, typescript, java, go, sql)
+        #[arg(short, long = "lang", alias = "language", value_delimiter = ',', default_value = "sql")]
+        languages: Vec<String>,
 
-        /// Output file
-        #[arg(short, long)]
-        output: Option<String>,
+        /// Directory to write the generated files and plan to
+        #[arg(long, alias = "output", default_value = ".")]
+        out: PathBuf,
     },
 
-    /// Validate migration
+    /// Validate a migration plan written by `migration generate`
     Validate {
-        /// Migration file
-        file: String,
+        /// Plan file (YAML)
+        plan: PathBuf,
+
+        /// Sample records to dry-run the migration against (JSON array)
+        #[arg(short, long)]
+        data: Option<PathBuf>,
     },
 
     /// Generate rollback script
@@ -71,11 +90,15 @@ pub enum MigrationCommand {
 
 pub async fn execute(cmd: MigrationCommand, config: &Config, format: output::OutputFormat) -> Result<()> {
     match cmd {
-        MigrationCommand::Generate { from, to, language, output: output_file } => {
-            generate_migration(config, &from, &to, &language, output_file.as_deref(), format).await
+        MigrationCommand::Generate { subject, from, to, languages, out } => {
+            let languages = languages
+                .iter()
+                .map(|l| parse_language(l))
+                .collect::<Result<Vec<_>>>()?;
+            generate_migration(config, &subject, &from, &to, &languages, &out, format).await
         }
-        MigrationCommand::Validate { file } => {
-            validate_migration(config, &file, format).await
+        MigrationCommand::Validate { plan, data } => {
+            validate_migration(config, &plan, data.as_deref(), format).await
         }
         MigrationCommand::Rollback { migration, output: output_file } => {
             generate_rollback(config, &migration, output_file.as_deref(), format).await
@@ -93,48 +116,186 @@ pub async fn execute(cmd: MigrationCommand, config: &Config, format: output::Out
 }
 
 async fn generate_migration(
-    _config: &Config,
+    config: &Config,
+    subject: &str,
     from: &str,
     to: &str,
-    language: &str,
-    output_file: Option<&str>,
-    _format: output::OutputFormat,
+    languages: &[Language],
+    out: &Path,
+    format: output::OutputFormat,
 ) -> Result<()> {
-    output::print_info(&format!(
-        "Generating {} migration: {} -> {}",
-        language, from, to
-    ));
-
-    let default_output = format!("migration_{}_to_{}.{}", from, to, get_extension(language));
-    let output_path = output_file.unwrap_or(&default_output);
+    let from_version: SemanticVersion = from
+        .parse()
+        .map_err(|_| CliError::ValidationError(format!("Invalid version: {}", from)))?;
+    let to_version: SemanticVersion = to
+        .parse()
+        .map_err(|_| CliError::ValidationError(format!("Invalid version: {}", to)))?;
+
+    output::print_info(&format!("Generating migration for {}: {} -> {}", subject, from, to));
+
+    let client = RegistryClient::new(config)?;
+    let old = client.schema_at_version(subject, from).await?;
+    let new = client.schema_at_version(subject, to).await?;
+
+    let (namespace, name) = match subject.rsplit_once('.') {
+        Some((namespace, name)) => (namespace.to_string(), name.to_string()),
+        None => ("default".to_string(), subject.to_string()),
+    };
+    let plan = MigrationEngine::new(parse_format(&new.format)?)
+        .generate_migration_from_content(
+            &old.content,
+            &new.content,
+            from_version,
+            to_version,
+            name.clone(),
+            namespace,
+            languages.to_vec(),
+        )
+        .map_err(|e| CliError::Other(format!("Migration generation failed: {}", e)))?;
+
+    std::fs::create_dir_all(out)?;
+    let stem = format!("{}_{}_to_{}", name, from, to).replace(['.', '-'], "_").to_lowercase();
+    let mut written = Vec::new();
+
+    for language in languages {
+        let Some(code) = plan.code_templates.get(language) else {
+            continue;
+        };
+        let extension = get_extension(&language.to_string());
+        let files = [
+            (format!("{}.{}", stem, extension), Some(&code.migration_code)),
+            (format!("{}_test.{}", stem, extension), code.test_code.as_ref()),
+            (format!("{}_rollback.{}", stem, extension), code.rollback_code.as_ref()),
+            (format!("{}_{}.md", stem, language), code.documentation.as_ref()),
+        ];
+        for (file_name, contents) in files {
+            if let Some(contents) = contents {
+                let path = out.join(file_name);
+                std::fs::write(&path, contents)?;
+                written.push(path);
+            }
+        }
+    }
 
-    // Mock generation
-    println!("\nGenerated migration code:");
-    println!("  Language: {}", language);
-    println!("  Transformations: 3");
-    println!("    - Field added: email_verified (default: false)");
-    println!("    - Field renamed: full_name -> display_name");
-    println!("    - Type changed: age (int32 -> int64)");
-    println!("  Complexity score: 2.5/10");
-    println!("  Estimated effort: 1.5 hours");
+    let plan_path = out.join(format!("{}.plan.yaml", stem));
+    std::fs::write(&plan_path, serde_yaml::to_string(&plan)?)?;
+    written.push(plan_path);
 
-    output::print_success(&format!("Migration code saved to: {}", output_path));
+    match format {
+        output::OutputFormat::Json | output::OutputFormat::Yaml => {
+            let files: Vec<String> = written.iter().map(|p| p.display().to_string()).collect();
+            output::print(&files, format)?;
+        }
+        _ => {
+            println!("\nMigration plan:");
+            println!("  Strategy: {:?}", plan.strategy);
+            println!("  Risk level: {}", plan.risk_level);
+            println!("  Changes: {} ({} breaking)", plan.diff.changes.len(), plan.diff.breaking_changes.len());
+            println!("  Complexity score: {:.2}", plan.diff.complexity_score);
+            println!("\nFiles:");
+            for path in &written {
+                println!("  {}", path.display());
+            }
+            output::print_success(&format!("Migration written to {}", out.display()));
+        }
+    }
 
     Ok(())
 }
 
-async fn validate_migration(_config: &Config, file: &str, _format: output::OutputFormat) -> Result<()> {
-    output::print_info(&format!("Validating migration: {}", file));
+#[derive(Debug, Serialize)]
+struct MigrationValidationOutput {
+    valid: bool,
+    risk_level: String,
+    errors: Vec<String>,
+    warnings: Vec<String>,
+    info: Vec<String>,
+    dry_run: Option<DryRunOutput>,
+}
 
-    // Mock validation
-    println!("\nValidation results:");
-    println!("  ✓ Syntax is valid");
-    println!("  ✓ All transformations are reversible");
-    println!("  ✓ No data loss detected");
-    println!("  ⚠ Warning: Type widening detected (may affect performance)");
+#[derive(Debug, Serialize)]
+struct DryRunOutput {
+    total: usize,
+    successful: usize,
+    failed: usize,
+    success_rate: f64,
+    errors: Vec<String>,
+}
 
-    output::print_success("Migration is valid");
+async fn validate_migration(
+    _config: &Config,
+    plan_file: &Path,
+    data: Option<&Path>,
+    format: output::OutputFormat,
+) -> Result<()> {
+    let plan: MigrationPlan = serde_yaml::from_str(&std::fs::read_to_string(plan_file)?)?;
+    let validator = MigrationValidator::new();
+
+    let report = validator
+        .validate(&plan)
+        .map_err(|e| CliError::Other(format!("Validation failed: {}", e)))?;
+    let dry_run = match data {
+        Some(path) => {
+            let samples: Vec<serde_json::Value> = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+            let dry_run = validator
+                .dry_run(&plan, &samples)
+                .map_err(|e| CliError::Other(format!("Dry run failed: {}", e)))?;
+            Some(DryRunOutput {
+                total: dry_run.total,
+                successful: dry_run.successful,
+                failed: dry_run.failed,
+                success_rate: dry_run.success_rate,
+                errors: dry_run.errors,
+            })
+        }
+        None => None,
+    };
+
+    let result = MigrationValidationOutput {
+        valid: report.valid,
+        risk_level: report.risk_level.to_string(),
+        errors: report.errors,
+        warnings: report.warnings,
+        info: report.info,
+        dry_run,
+    };
+
+    match format {
+        output::OutputFormat::Json | output::OutputFormat::Yaml => output::print(&result, format)?,
+        _ => {
+            output::print_info(&format!("Validating migration: {}", plan_file.display()));
+            for error in &result.errors {
+                output::print_error_msg(error);
+            }
+            for warning in &result.warnings {
+                output::print_warning(warning);
+            }
+            for info in &result.info {
+                output::print_info(info);
+            }
+            if let Some(dry_run) = &result.dry_run {
+                println!(
+                    "\nDry run: {}/{} records migrated ({:.1}%)",
+                    dry_run.successful,
+                    dry_run.total,
+                    dry_run.success_rate * 100.0
+                );
+                for error in &dry_run.errors {
+                    println!("  {}", error);
+                }
+            }
+        }
+    }
+
+    let dry_run_failed = result.dry_run.as_ref().is_some_and(|d| d.failed > 0);
+    if !result.valid || dry_run_failed {
+        return Err(CliError::ValidationError(format!(
+            "Migration {} is not safe to apply",
+            plan_file.display()
+        )));
+    }
 
+    output::print_success("Migration is valid");
     Ok(())
 }
 
@@ -205,7 +366,18 @@ async fn test_migration(_config: &Config, file: &str, data: Option<&str>, _forma
     Ok(())
 }
 
-fn get_extension(language: &str) -> &str {
+fn parse_language(language: &str) -> Result<Language> {
+    match language.trim().to_lowercase().as_str() {
+        "python" | "py" => Ok(Language::Python),
+        "typescript" | "ts" => Ok(Language::TypeScript),
+        "java" => Ok(Language::Java),
+        "go" => Ok(Language::Go),
+        "sql" => Ok(Language::Sql),
+        other => Err(CliError::ValidationError(format!("Unsupported language: {}", other))),
+    }
+}
+
+fn get_extension(language: &str) -> &'static str {
     match language.to_lowercase().as_str() {
         "python" => "py",
         "typescript" => "ts",
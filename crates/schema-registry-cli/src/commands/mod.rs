@@ -6,3 +6,4 @@ pub mod benchmark;
 pub mod lineage;
 pub mod migration;
 pub mod schema;
+pub mod subject_config;
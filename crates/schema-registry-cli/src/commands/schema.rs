@@ -1,10 +1,25 @@
 //! Schema management commands
 
 use clap::Subcommand;
+use schema_registry_analytics::DailyUsage;
+use schema_registry_compatibility::CompatibilityCheckerImpl;
+use schema_registry_core::{
+    canonical_form,
+    traits::{CompatibilityChecker, RemediationSuggestion},
+    CompatibilityMode, RegisteredSchema, SchemaLifecycle, SchemaMetadata, SchemaState,
+    SerializationFormat,
+};
+use schema_registry_core::versioning::SemanticVersion;
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 use uuid::Uuid;
 
-use crate::{config::Config, error::Result, output};
+use crate::{
+    client::{RegistryClient, SchemaUsage},
+    config::Config,
+    error::{CliError, Result},
+    output,
+};
 
 #[derive(Subcommand)]
 pub enum SchemaCommand {
@@ -75,20 +90,118 @@ pub enum SchemaCommand {
         mode: String,
     },
 
+    /// Check a local schema file against a subject's registered versions
+    ///
+    /// Exits non-zero when the schema breaks compatibility, so it can run as a
+    /// pre-commit hook.
+    Check {
+        /// Schema file to check
+        #[arg(short, long)]
+        file: PathBuf,
+
+        /// Subject the schema will be registered under
+        #[arg(short, long)]
+        subject: String,
+
+        /// Compatibility mode (BACKWARD, FORWARD, FULL, NONE, or a *_TRANSITIVE mode)
+        #[arg(short, long, default_value = "BACKWARD")]
+        mode: String,
+
+        /// Schema type (JSON, AVRO, PROTOBUF)
+        #[arg(short = 't', long, default_value = "JSON")]
+        schema_type: String,
+
+        /// Don't contact the registry; check against --previous files instead
+        #[arg(long)]
+        offline: bool,
+
+        /// Prior versions of the schema, oldest first (with --offline)
+        #[arg(short, long, requires = "offline")]
+        previous: Vec<PathBuf>,
+    },
+
+    /// Render Markdown reference docs for a schema file
+    ///
+    /// Deprecated fields are shown struck through, with their replacement.
+    Docs {
+        /// Schema file to document
+        file: PathBuf,
+
+        /// Schema type (JSON, AVRO)
+        #[arg(short = 't', long, default_value = "JSON")]
+        schema_type: String,
+
+        /// Page title (defaults to the file name)
+        #[arg(long)]
+        title: Option<String>,
+
+        /// Write to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Generate typed bindings from a registered schema
+    Codegen {
+        /// Schema ID
+        id: String,
+
+        /// Target language (rust, python, ts)
+        #[arg(short, long, default_value = "rust")]
+        lang: String,
+
+        /// Write to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
     /// Get schema versions
     Versions {
         /// Subject name
         subject: String,
     },
 
+    /// Archive a schema
+    ///
+    /// Shows the schema's last 30 days of usage and its known consumers
+    /// first. A schema still in use is only archived with --force and a
+    /// --reason, which is recorded in the audit log.
+    Archive {
+        /// Schema ID
+        id: String,
+
+        /// Don't ask for confirmation
+        #[arg(short, long)]
+        confirm: bool,
+
+        /// Archive the schema even though it is still in use
+        #[arg(long, requires = "reason")]
+        force: bool,
+
+        /// Why the schema is archived while in use
+        #[arg(long)]
+        reason: Option<String>,
+    },
+
     /// Delete a schema
+    ///
+    /// Shows the schema's last 30 days of usage and its known consumers
+    /// first. A schema still in use is only deleted with --force and a
+    /// --reason, which is recorded in the audit log.
     Delete {
         /// Schema ID
         id: String,
 
-        /// Confirm deletion
+        /// Don't ask for confirmation
         #[arg(short, long)]
         confirm: bool,
+
+        /// Delete the schema even though it is still in use
+        #[arg(long, requires = "reason")]
+        force: bool,
+
+        /// Why the schema is deleted while in use
+        #[arg(long)]
+        reason: Option<String>,
     },
 
     /// Search schemas
@@ -128,11 +241,33 @@ pub async fn execute(cmd: SchemaCommand, config: &Config, format: output::Output
         SchemaCommand::Compatible { old, new, mode } => {
             check_compatibility(config, &old, &new, &mode, format).await
         }
+        SchemaCommand::Check { file, subject, mode, schema_type, offline, previous } => {
+            check_file(config, &file, &subject, &mode, &schema_type, offline, &previous, format).await
+        }
+        SchemaCommand::Docs { file, schema_type, title, output } => {
+            render_docs(&file, &schema_type, title.as_deref(), output.as_deref())
+        }
+        SchemaCommand::Codegen { id, lang, output } => {
+            let code = RegistryClient::new(config)?.codegen(&id, &lang).await?;
+            match output {
+                Some(path) => {
+                    std::fs::write(&path, code)?;
+                    output::print_success(&format!("Bindings written to {}", path.display()));
+                }
+                None => print!("{}", code),
+            }
+            Ok(())
+        }
         SchemaCommand::Versions { subject } => {
             list_versions(config, &subject, format).await
         }
-        SchemaCommand::Delete { id, confirm } => {
-            delete_schema(config, &id, confirm, format).await
+        SchemaCommand::Archive { id, confirm, force, reason } => {
+            let reason = reason.filter(|_| force);
+            retire_schema(config, &id, Retirement::Archive, confirm, reason.as_deref(), format).await
+        }
+        SchemaCommand::Delete { id, confirm, force, reason } => {
+            let reason = reason.filter(|_| force);
+            retire_schema(config, &id, Retirement::Delete, confirm, reason.as_deref(), format).await
         }
         SchemaCommand::Search { query, limit } => {
             search_schemas(config, &query, limit, format).await
@@ -259,6 +394,7 @@ async fn check_compatibility(
     mode: &str,
     _format: output::OutputFormat,
 ) -> Result<()> {
+    let mode = parse_mode(mode)?;
     output::print_info(&format!(
         "Checking compatibility: {} -> {} (mode: {})",
         old, new, mode
@@ -269,6 +405,231 @@ async fn check_compatibility(
     Ok(())
 }
 
+#[derive(Debug, Serialize)]
+pub struct CompatibilityReport {
+    pub subject: String,
+    pub mode: String,
+    pub compatible: bool,
+    pub checked_versions: Vec<String>,
+    pub violations: Vec<ViolationReport>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ViolationReport {
+    pub code: String,
+    pub severity: String,
+    pub field_path: String,
+    pub description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<RemediationSuggestion>,
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn check_file(
+    config: &Config,
+    file: &std::path::Path,
+    subject: &str,
+    mode: &str,
+    schema_type: &str,
+    offline: bool,
+    previous: &[PathBuf],
+    format: output::OutputFormat,
+) -> Result<()> {
+    let mode = parse_mode(mode)?;
+    let schema_format = parse_format(schema_type)?;
+    let content = std::fs::read_to_string(file)?;
+
+    let mut history = Vec::new();
+    if offline {
+        for (index, path) in previous.iter().enumerate() {
+            let version = SemanticVersion::new(index as u32 + 1, 0, 0);
+            history.push(build_schema(subject, version, schema_format, std::fs::read_to_string(path)?));
+        }
+    } else {
+        let client = RegistryClient::new(config)?;
+        let versions = match client.subject_versions(subject).await {
+            Ok(versions) => versions,
+            Err(CliError::NotFound(_)) => Vec::new(),
+            Err(e) => return Err(e),
+        };
+        for entry in versions {
+            let remote = client.get_schema(&entry.id).await?;
+            let version = remote.version.parse().map_err(|_| {
+                CliError::ApiError(format!("Invalid version {} for schema {}", remote.version, entry.id))
+            })?;
+            history.push(build_schema(subject, version, parse_format(&remote.format)?, remote.content));
+        }
+    }
+
+    if history.is_empty() || mode == CompatibilityMode::None {
+        output::print_info(&format!("Nothing to check for {} (mode: {})", subject, mode));
+        return Ok(());
+    }
+
+    // Non-transitive modes only compare against the latest version
+    let targets = if mode.is_transitive() {
+        &history[..]
+    } else {
+        &history[history.len() - 1..]
+    };
+    // Check as the next minor version, so deprecated fields past their
+    // notice period may be dropped
+    let mut new_version = history
+        .iter()
+        .map(|schema| schema.version.clone())
+        .max()
+        .unwrap_or_else(|| SemanticVersion::new(0, 0, 0));
+    new_version.increment_minor();
+    let new_version = new_version.with_prerelease("local".to_string());
+    let candidate = build_schema(subject, new_version, schema_format, content);
+
+    let result = CompatibilityCheckerImpl::new()
+        .check_transitive_compatibility(&candidate, targets, mode)
+        .await
+        .map_err(|e| CliError::ValidationError(e.to_string()))?;
+
+    let report = CompatibilityReport {
+        subject: subject.to_string(),
+        mode: mode.to_string(),
+        compatible: result.is_compatible,
+        checked_versions: result.checked_versions.iter().map(ToString::to_string).collect(),
+        violations: result
+            .violations
+            .iter()
+            .map(|v| ViolationReport {
+                code: output::code_of(&v.violation_type),
+                severity: output::code_of(&v.severity),
+                field_path: v.field_path.clone(),
+                description: v.description.clone(),
+                suggestion: v.suggestion.clone(),
+            })
+            .collect(),
+    };
+
+    match format {
+        output::OutputFormat::Table | output::OutputFormat::Plain => {
+            if !report.violations.is_empty() {
+                output::print_table(
+                    vec!["Code", "Severity", "Field", "Description", "Suggestion"],
+                    report.violations.iter().map(|v| vec![
+                        v.code.clone(),
+                        v.severity.clone(),
+                        v.field_path.clone(),
+                        v.description.clone(),
+                        v.suggestion.as_ref().map(|s| s.message.clone()).unwrap_or_default(),
+                    ]).collect(),
+                );
+            }
+        }
+        _ => {
+            output::print(&report, format)?;
+        }
+    }
+
+    if !report.compatible {
+        return Err(CliError::Incompatible(format!(
+            "{} violation(s) against {} {} (versions: {})",
+            report.violations.len(),
+            subject,
+            mode,
+            report.checked_versions.join(", ")
+        )));
+    }
+
+    output::print_success(&format!(
+        "{} is {} compatible with versions {}",
+        file.display(),
+        mode,
+        report.checked_versions.join(", ")
+    ));
+    Ok(())
+}
+
+fn render_docs(
+    file: &std::path::Path,
+    schema_type: &str,
+    title: Option<&str>,
+    output_path: Option<&std::path::Path>,
+) -> Result<()> {
+    let format = parse_format(schema_type)?;
+    if format == SerializationFormat::Protobuf {
+        return Err(CliError::ValidationError(
+            "Docs can only be rendered for JSON and Avro schemas".to_string(),
+        ));
+    }
+    let content = std::fs::read_to_string(file)?;
+    let title = match title {
+        Some(title) => title.to_string(),
+        None => file
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "Schema".to_string()),
+    };
+
+    let markdown = crate::docs::render_markdown(&title, &content, format);
+    match output_path {
+        Some(path) => {
+            std::fs::write(path, markdown)?;
+            output::print_success(&format!("Docs written to {}", path.display()));
+        }
+        None => print!("{}", markdown),
+    }
+    Ok(())
+}
+
+fn parse_mode(mode: &str) -> Result<CompatibilityMode> {
+    CompatibilityMode::parse_lenient(mode).map_err(|e| CliError::ValidationError(e.to_string()))
+}
+
+pub(crate) fn parse_format(schema_type: &str) -> Result<SerializationFormat> {
+    match schema_type.trim().to_uppercase().as_str() {
+        "JSON" | "JSON_SCHEMA" | "JSONSCHEMA" => Ok(SerializationFormat::JsonSchema),
+        "AVRO" => Ok(SerializationFormat::Avro),
+        "PROTOBUF" | "PROTO" => Ok(SerializationFormat::Protobuf),
+        other => Err(CliError::ValidationError(format!("Unsupported schema type: {}", other))),
+    }
+}
+
+fn build_schema(
+    subject: &str,
+    version: SemanticVersion,
+    format: SerializationFormat,
+    content: String,
+) -> RegisteredSchema {
+    let (namespace, name) = match subject.rsplit_once('.') {
+        Some((namespace, name)) => (namespace.to_string(), name.to_string()),
+        None => ("default".to_string(), subject.to_string()),
+    };
+    let id = Uuid::new_v4();
+    let now = chrono::Utc::now();
+
+    RegisteredSchema {
+        id,
+        name,
+        namespace,
+        version,
+        format,
+        content_hash: canonical_form::content_hash(format, &content),
+        content,
+        description: String::new(),
+        compatibility_mode: CompatibilityMode::None,
+        state: SchemaState::Active,
+        metadata: SchemaMetadata {
+            created_at: now,
+            created_by: "schema-cli".to_string(),
+            updated_at: now,
+            updated_by: "schema-cli".to_string(),
+            activated_at: None,
+            deprecation: None,
+            deletion: None,
+            custom: std::collections::HashMap::new(),
+        },
+        tags: vec![],
+        examples: vec![],
+        lifecycle: SchemaLifecycle::new(id),
+    }
+}
+
 async fn list_versions(_config: &Config, subject: &str, format: output::OutputFormat) -> Result<()> {
     output::print_info(&format!("Listing versions for subject: {}", subject));
 
@@ -289,18 +650,137 @@ async fn list_versions(_config: &Config, subject: &str, format: output::OutputFo
     Ok(())
 }
 
-async fn delete_schema(_config: &Config, id: &str, confirm: bool, _format: output::OutputFormat) -> Result<()> {
-    if !confirm {
-        output::print_warning("Deletion not confirmed. Use --confirm to proceed.");
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Retirement {
+    Archive,
+    Delete,
+}
+
+impl Retirement {
+    fn verb(self) -> &'static str {
+        match self {
+            Retirement::Archive => "archive",
+            Retirement::Delete => "delete",
+        }
+    }
+}
+
+/// Archive or delete a schema after showing who still uses it
+///
+/// `reason` is given only with --force; without it a schema in use is left
+/// alone.
+async fn retire_schema(
+    config: &Config,
+    id: &str,
+    retirement: Retirement,
+    confirm: bool,
+    reason: Option<&str>,
+    format: output::OutputFormat,
+) -> Result<()> {
+    let client = RegistryClient::new(config)?;
+    let usage = client.schema_usage(id).await?;
+    if matches!(
+        format,
+        output::OutputFormat::Json | output::OutputFormat::Yaml
+    ) {
+        output::print(&usage, format)?;
+    } else {
+        print_usage(id, &usage);
+    }
+
+    let verb = retirement.verb();
+    if usage.usage.in_use() && reason.is_none() {
+        return Err(CliError::ValidationError(format!(
+            "Schema {} was used {} times in the last {} days; pass --force with a --reason to {} it anyway",
+            id, usage.usage.total_operations, usage.usage.days, verb
+        )));
+    }
+    if !confirm && !output::confirm(&format!("Really {} schema {}?", verb, id))? {
+        output::print_warning(&format!("Schema not {}d", verb));
         return Ok(());
     }
 
-    output::print_info(&format!("Deleting schema: {}", id));
-    // TODO: Implement actual deletion
-    output::print_success("Schema deleted");
+    let (retired, operation_id) = match retirement {
+        Retirement::Archive => (client.archive_schema(id, reason).await?, None),
+        Retirement::Delete => {
+            let deleted = client.delete_schema(id, reason).await?;
+            (deleted.value, deleted.operation_id)
+        }
+    };
+    output::print_success(&format!("Schema {} ({}) {}d", id, retired.subject, verb));
+    if retired.forced {
+        output::print_warning("Retired while in use; the reason was recorded in the audit log");
+    }
+    if let Some(operation_id) = operation_id {
+        output::print_info(&format!(
+            "Undo with: schema-cli admin undo --operation-id {}",
+            operation_id
+        ));
+    }
     Ok(())
 }
 
+fn print_usage(id: &str, usage: &SchemaUsage) {
+    let report = &usage.usage;
+    output::print_info(&format!(
+        "Usage of schema {} over the last {} days",
+        id, report.days
+    ));
+    println!(
+        "\n{} operations  {}",
+        report.total_operations,
+        heat_line(&report.daily)
+    );
+    if let Some(last_used) = report.last_used {
+        println!("Last used: {}", last_used.to_rfc3339());
+    }
+    if !report.clients.is_empty() {
+        println!("Clients: {}", report.clients.join(", "));
+    }
+    if !report.operations_breakdown.is_empty() {
+        output::print_table(
+            vec!["Operation", "Count"],
+            report
+                .operations_breakdown
+                .iter()
+                .map(|b| vec![b.operation.to_string(), b.count.to_string()])
+                .collect(),
+        );
+    }
+
+    if usage.consumers.is_empty() {
+        output::print_info("No known consumers in the lineage graph");
+    } else {
+        output::print_table(
+            vec!["Consumer", "Type", "Relation"],
+            usage
+                .consumers
+                .iter()
+                .map(|c| {
+                    vec![
+                        c.name.clone(),
+                        output::code_of(&c.entity_type),
+                        c.relation.to_string(),
+                    ]
+                })
+                .collect(),
+        );
+    }
+}
+
+/// One block per day, scaled to the busiest day; days without use are dots
+fn heat_line(daily: &[DailyUsage]) -> String {
+    const LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let peak = daily.iter().map(|d| d.count).max().unwrap_or(0);
+    daily
+        .iter()
+        .map(|d| match d.count {
+            0 => '·',
+            count => LEVELS[(count * 7 / peak) as usize],
+        })
+        .collect()
+}
+
 async fn search_schemas(_config: &Config, query: &str, limit: usize, format: output::OutputFormat) -> Result<()> {
     output::print_info(&format!("Searching schemas: {} (limit: {})", query, limit));
 
@@ -318,3 +798,57 @@ async fn search_schemas(_config: &Config, query: &str, limit: usize, format: out
     output::print(&results, format)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use schema_registry_core::types::ViolationType;
+
+    #[test]
+    fn test_parse_mode_accepts_cli_spellings() {
+        assert_eq!(parse_mode("backward").unwrap(), CompatibilityMode::Backward);
+        assert_eq!(parse_mode("FULL-TRANSITIVE").unwrap(), CompatibilityMode::FullTransitive);
+        assert!(parse_mode("SIDEWAYS").is_err());
+    }
+
+    #[test]
+    fn test_violation_codes() {
+        assert_eq!(output::code_of(&ViolationType::FieldRemoved), "FIELD_REMOVED");
+        assert_eq!(output::code_of(&ViolationType::EnumValueRemoved), "ENUM_VALUE_REMOVED");
+    }
+
+    #[test]
+    fn test_heat_line_scales_to_busiest_day() {
+        let day = |offset, count| DailyUsage {
+            date: chrono::NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()
+                + chrono::Duration::days(offset),
+            count,
+        };
+        let daily = vec![day(0, 0), day(1, 1), day(2, 4), day(3, 8)];
+        assert_eq!(heat_line(&daily), "·▁▄█");
+        assert_eq!(heat_line(&[day(0, 0), day(1, 0)]), "··");
+    }
+
+    #[tokio::test]
+    async fn test_offline_check_against_identical_previous_version() {
+        let dir = std::env::temp_dir().join(format!("schema-cli-check-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("user.json");
+        std::fs::write(&file, r#"{"type":"object"}"#).unwrap();
+
+        let result = check_file(
+            &Config::default(),
+            &file,
+            "com.example.User",
+            "BACKWARD_TRANSITIVE",
+            "JSON",
+            true,
+            &[file.clone(), file.clone()],
+            output::OutputFormat::Json,
+        )
+        .await;
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(result.is_ok());
+    }
+}
@@ -0,0 +1,165 @@
+//! Subject config file commands
+
+use std::path::{Path, PathBuf};
+
+use clap::Subcommand;
+use schema_registry_core::subject_config::{SubjectConfigChange, SubjectConfigFile};
+
+use crate::{
+    client::RegistryClient,
+    config::Config,
+    error::{CliError, Result},
+    output,
+};
+
+#[derive(Subcommand)]
+pub enum SubjectConfigCommand {
+    /// Write the registry's subject settings as a config file
+    Export {
+        /// Write to this file instead of stdout
+        #[arg(short, long)]
+        file: Option<PathBuf>,
+    },
+
+    /// Show how the registry differs from a committed config file
+    Diff {
+        /// Subject config file (YAML)
+        file: PathBuf,
+
+        /// Exit non-zero when the registry has drifted, for CI
+        #[arg(long)]
+        exit_code: bool,
+    },
+
+    /// Make the registry match a committed config file
+    Apply {
+        /// Subject config file (YAML)
+        file: PathBuf,
+
+        /// Also remove settings of subjects the file doesn't mention
+        #[arg(long)]
+        prune: bool,
+
+        /// Show the changes without making them
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+pub async fn execute(cmd: SubjectConfigCommand, config: &Config, format: output::OutputFormat) -> Result<()> {
+    let client = RegistryClient::new(config)?;
+    match cmd {
+        SubjectConfigCommand::Export { file } => {
+            let yaml = serde_yaml::to_string(&client.subject_config().await?)?;
+            match file {
+                Some(path) => {
+                    std::fs::write(&path, yaml)?;
+                    output::print_success(&format!("Subject config written to {}", path.display()));
+                }
+                None => print!("{}", yaml),
+            }
+            Ok(())
+        }
+        SubjectConfigCommand::Diff { file, exit_code } => {
+            let committed = read_file(&file)?;
+            let changes = committed.diff(&client.subject_config().await?);
+            print_changes(&changes, format)?;
+            if changes.is_empty() {
+                output::print_success(&format!("Registry matches {}", file.display()));
+            } else if exit_code {
+                return Err(CliError::Other(format!(
+                    "{} subject(s) differ from {}",
+                    changes.len(),
+                    file.display()
+                )));
+            }
+            Ok(())
+        }
+        SubjectConfigCommand::Apply { file, prune, dry_run } => {
+            let committed = read_file(&file)?;
+            let applied = client.apply_subject_config(&committed, prune, dry_run).await?;
+            let changes = applied.value;
+            print_changes(&changes, format)?;
+            let verb = if dry_run { "Would apply" } else { "Applied" };
+            output::print_success(&format!("{} {} change(s) from {}", verb, changes.len(), file.display()));
+            if let Some(operation_id) = applied.operation_id {
+                output::print_info(&format!("Undo with: schema-cli admin undo --operation-id {}", operation_id));
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Read and normalize a config file
+fn read_file(path: &Path) -> Result<SubjectConfigFile> {
+    let content = std::fs::read_to_string(path)?;
+    let file: SubjectConfigFile = serde_yaml::from_str(&content)?;
+    file.normalized()
+        .map_err(|e| CliError::ValidationError(format!("{}: {}", path.display(), e)))
+}
+
+fn print_changes(changes: &[SubjectConfigChange], format: output::OutputFormat) -> Result<()> {
+    match format {
+        output::OutputFormat::Table | output::OutputFormat::Plain => {
+            if !changes.is_empty() {
+                output::print_table(
+                    vec!["Action", "Subject", "Details"],
+                    changes.iter().map(|change| vec![
+                        change_action(change).to_string(),
+                        change.subject().to_string(),
+                        change_details(change),
+                    ]).collect(),
+                );
+            }
+            Ok(())
+        }
+        _ => output::print(&changes, format),
+    }
+}
+
+fn change_action(change: &SubjectConfigChange) -> &'static str {
+    match change {
+        SubjectConfigChange::Create { .. } => "create",
+        SubjectConfigChange::Update { .. } => "update",
+        SubjectConfigChange::Delete { .. } => "delete",
+    }
+}
+
+fn change_details(change: &SubjectConfigChange) -> String {
+    match change {
+        SubjectConfigChange::Create { config, .. } | SubjectConfigChange::Delete { config, .. } => {
+            serde_json::to_string(config).unwrap_or_default()
+        }
+        SubjectConfigChange::Update { fields, .. } => format!("changed: {}", fields.join(", ")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_file_normalizes_subjects() {
+        let path = std::env::temp_dir().join(format!("subjects-{}.yaml", uuid::Uuid::new_v4()));
+        std::fs::write(
+            &path,
+            "subjects:\n  com.example.*:\n    compatibilityLevel: FULL\n    normalize: true\n",
+        )
+        .unwrap();
+
+        let file = read_file(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let file = file.unwrap();
+        assert_eq!(file.subjects["com.example"].normalize, Some(true));
+        assert_eq!(
+            change_details(&SubjectConfigChange::Update {
+                subject: "com.example".to_string(),
+                from: Default::default(),
+                to: Default::default(),
+                fields: vec!["compatibilityLevel".to_string(), "normalize".to_string()],
+            }),
+            "changed: compatibilityLevel, normalize"
+        );
+    }
+}
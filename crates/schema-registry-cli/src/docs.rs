@@ -0,0 +1,86 @@
+//! Markdown reference docs for schemas
+
+use schema_registry_core::{deprecation, SerializationFormat};
+
+/// Render a field reference table for schema content
+///
+/// Deprecated fields stay in the table, struck through, with the version
+/// that deprecated them and their replacement.
+pub fn render_markdown(title: &str, content: &str, format: SerializationFormat) -> String {
+    let fields = deprecation::fields(content, format);
+
+    let mut out = format!("# {}\n\n", title);
+    if fields.is_empty() {
+        out.push_str("_No fields._\n");
+        return out;
+    }
+
+    out.push_str("| Field | Type | Description |\n");
+    out.push_str("|-------|------|-------------|\n");
+    for field in fields {
+        let mut description = String::new();
+        let name = match &field.deprecation {
+            Some(deprecation) => {
+                description.push_str("**Deprecated**");
+                if let Some(since) = &deprecation.since {
+                    description.push_str(&format!(" since {}", since));
+                }
+                if let Some(replacement) = &deprecation.replacement {
+                    description.push_str(&format!("; use `{}` instead", replacement));
+                }
+                if let Some(message) = &deprecation.message {
+                    description.push_str(&format!(". {}", message));
+                }
+                format!("~~`{}`~~", field.path)
+            }
+            None => format!("`{}`", field.path),
+        };
+        if let Some(text) = &field.description {
+            if !description.is_empty() {
+                description.push_str(". ");
+            }
+            description.push_str(text);
+        }
+
+        out.push_str(&format!(
+            "| {} | {} | {} |\n",
+            name,
+            cell(field.type_name.as_deref().unwrap_or("")),
+            cell(&description)
+        ));
+    }
+    out
+}
+
+/// Keep pipes and line breaks from splitting a table cell
+fn cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deprecated_fields_are_struck_through() {
+        let schema = r#"{
+            "type": "object",
+            "properties": {
+                "email": {
+                    "type": "string",
+                    "description": "Primary contact",
+                    "x-deprecated": {"since": "1.2.0", "replacement": "email_address"}
+                },
+                "email_address": {"type": ["string", "null"]}
+            }
+        }"#;
+
+        let markdown = render_markdown("User", schema, SerializationFormat::JsonSchema);
+
+        assert!(markdown.starts_with("# User\n"));
+        assert!(markdown.contains(
+            "| ~~`email`~~ | string | **Deprecated** since 1.2.0; use `email_address` instead. Primary contact |"
+        ));
+        assert!(markdown.contains("| `email_address` | string \\| null |  |"));
+    }
+}
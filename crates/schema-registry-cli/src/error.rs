@@ -22,6 +22,9 @@ pub enum CliError {
     #[error("I/O error: {0}")]
     IoError(#[from] std::io::Error),
 
+    #[error("Incompatible schema: {0}")]
+    Incompatible(String),
+
     #[error("Serialization error: {0}")]
     SerializationError(String),
 
@@ -60,6 +63,10 @@ pub fn print_error(error: &CliError) {
             eprintln!("\n{}", "Hint:".yellow().bold());
             eprintln!("  Check that the registry URL is correct and the server is running");
         }
+        CliError::Incompatible(_) => {
+            eprintln!("\n{}", "Hint:".yellow().bold());
+            eprintln!("  Make the change additive, or register it as a new major version");
+        }
         _ => {}
     }
 }
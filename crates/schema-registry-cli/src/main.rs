@@ -3,13 +3,15 @@
 //! A comprehensive command-line interface for managing schemas, lineage tracking,
 //! analytics, migrations, and administrative operations.
 
+mod client;
 mod commands;
 mod config;
+mod docs;
 mod error;
 mod output;
 
 use clap::{Parser, Subcommand};
-use commands::{admin, analytics, benchmark, lineage, migration, schema};
+use commands::{admin, analytics, benchmark, lineage, migration, schema, subject_config};
 use error::Result;
 use tracing_subscriber::{fmt, prelude::*, EnvFilter};
 
@@ -62,6 +64,10 @@ enum Commands {
     #[command(subcommand)]
     Migration(migration::MigrationCommand),
 
+    /// Subject config file commands (export, diff, apply)
+    #[command(subcommand)]
+    SubjectConfig(subject_config::SubjectConfigCommand),
+
     /// Administrative commands
     #[command(subcommand)]
     Admin(admin::AdminCommand),
@@ -118,6 +124,7 @@ async fn run(cli: Cli) -> Result<()> {
         Commands::Lineage(cmd) => lineage::execute(cmd, &config, cli.output).await,
         Commands::Analytics(cmd) => analytics::execute(cmd, &config, cli.output).await,
         Commands::Migration(cmd) => migration::execute(cmd, &config, cli.output).await,
+        Commands::SubjectConfig(cmd) => subject_config::execute(cmd, &config, cli.output).await,
         Commands::Admin(cmd) => admin::execute(cmd, &config, cli.output).await,
         Commands::Benchmark(cmd) => benchmark::execute(cmd, &config, cli.output).await,
         Commands::Init { url, force } => {
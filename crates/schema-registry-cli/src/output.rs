@@ -4,6 +4,7 @@ use clap::ValueEnum;
 use colored::Colorize;
 use comfy_table::{presets::UTF8_FULL, Cell, Table};
 use serde::Serialize;
+use std::io::Write;
 
 use crate::error::Result;
 
@@ -59,10 +60,32 @@ pub fn print_warning(message: &str) {
     println!("{} {}", "⚠".yellow().bold(), message);
 }
 
+/// Ask a yes/no question on the terminal; anything but "y" or "yes" is no
+pub fn confirm(question: &str) -> Result<bool> {
+    print!("{} {} [y/N] ", "?".cyan().bold(), question);
+    std::io::stdout().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
 pub fn print_error_msg(message: &str) {
     eprintln!("{} {}", "✗".red().bold(), message);
 }
 
+/// The name a unit enum serializes to, e.g. `FIELD_REMOVED`
+pub fn code_of<T: Serialize>(value: &T) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(code)) => code,
+        _ => "UNKNOWN".to_string(),
+    }
+}
+
+/// Clear the terminal and move the cursor home, for redrawing watch views
+pub fn clear_screen() {
+    print!("\x1B[2J\x1B[H");
+}
+
 pub fn format_size(bytes: u64) -> String {
     const KB: u64 = 1024;
     const MB: u64 = KB * 1024;
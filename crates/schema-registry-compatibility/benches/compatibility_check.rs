@@ -0,0 +1,149 @@
+//! Compatibility check latency across schema sizes, formats and modes
+//!
+//! The checker's target is a p95 under 25ms. `cargo xtask bench-compare`
+//! runs this benchmark and fails when a case's p95 exceeds the target or
+//! regresses against `benchmarks/baselines/compatibility_check.json`.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use schema_registry_compatibility::CompatibilityCheckerImpl;
+use schema_registry_core::{
+    schema::{RegisteredSchema, SchemaMetadata},
+    state::{SchemaLifecycle, SchemaState},
+    traits::CompatibilityChecker,
+    types::{CompatibilityMode, SerializationFormat},
+    versioning::SemanticVersion,
+};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::hint::black_box;
+use uuid::Uuid;
+
+/// Top-level field counts: a typical event, a wide record and an outlier
+const SIZES: &[usize] = &[10, 100, 1_000];
+
+/// Earlier versions a transitive check compares against
+const HISTORY: usize = 10;
+
+const MODES: &[CompatibilityMode] = &[
+    CompatibilityMode::Backward,
+    CompatibilityMode::Forward,
+    CompatibilityMode::Full,
+    CompatibilityMode::FullTransitive,
+];
+
+/// Every fifth field is a nested record, so field paths are two levels deep
+fn content(format: SerializationFormat, fields: usize) -> String {
+    match format {
+        SerializationFormat::Avro => {
+            let fields: Vec<Value> = (0..fields)
+                .map(|i| {
+                    if i % 5 == 0 {
+                        json!({
+                            "name": format!("field_{}", i),
+                            "type": {
+                                "type": "record",
+                                "name": format!("Nested{}", i),
+                                "fields": [
+                                    {"name": "id", "type": "string"},
+                                    {"name": "count", "type": "long"}
+                                ]
+                            }
+                        })
+                    } else {
+                        json!({"name": format!("field_{}", i), "type": ["null", "string"], "default": null})
+                    }
+                })
+                .collect();
+            json!({"type": "record", "name": "Event", "fields": fields}).to_string()
+        }
+        _ => {
+            let properties: serde_json::Map<String, Value> = (0..fields)
+                .map(|i| {
+                    let schema = if i % 5 == 0 {
+                        json!({
+                            "type": "object",
+                            "properties": {"id": {"type": "string"}, "count": {"type": "integer"}}
+                        })
+                    } else {
+                        json!({"type": "string", "description": format!("Field number {}", i)})
+                    };
+                    (format!("field_{}", i), schema)
+                })
+                .collect();
+            json!({"type": "object", "properties": properties}).to_string()
+        }
+    }
+}
+
+fn schema(format: SerializationFormat, fields: usize, minor: u32) -> RegisteredSchema {
+    let id = Uuid::new_v4();
+    let content = content(format, fields);
+    let now = chrono::Utc::now();
+    RegisteredSchema {
+        id,
+        namespace: "bench".to_string(),
+        name: "Event".to_string(),
+        version: SemanticVersion::new(1, minor, 0),
+        format,
+        content_hash: RegisteredSchema::calculate_content_hash(&content),
+        content,
+        description: String::new(),
+        compatibility_mode: CompatibilityMode::Backward,
+        state: SchemaState::Active,
+        metadata: SchemaMetadata {
+            created_at: now,
+            created_by: "bench".to_string(),
+            updated_at: now,
+            updated_by: "bench".to_string(),
+            activated_at: None,
+            deprecation: None,
+            deletion: None,
+            custom: HashMap::new(),
+        },
+        tags: Vec::new(),
+        examples: Vec::new(),
+        lifecycle: SchemaLifecycle::new(id),
+    }
+}
+
+fn bench_compatibility_check(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .expect("benchmark runtime");
+    // Every iteration repeats the same pair, which the cache would answer
+    let checker = CompatibilityCheckerImpl::new().without_cache();
+    let mut group = c.benchmark_group("compatibility_check");
+
+    for format in [SerializationFormat::JsonSchema, SerializationFormat::Avro] {
+        for &fields in SIZES {
+            // Each version adds a field, so every check scans both schemas in full
+            let history: Vec<RegisteredSchema> = (0..HISTORY)
+                .map(|minor| schema(format, fields + minor, minor as u32))
+                .collect();
+            let new_schema = schema(format, fields + HISTORY, HISTORY as u32);
+            let previous = &history[HISTORY - 1];
+
+            for &mode in MODES {
+                let id = BenchmarkId::new(format!("{}/{}", format, mode), fields);
+                group.bench_with_input(id, &mode, |b, &mode| {
+                    b.to_async(&runtime).iter(|| async {
+                        let result = if mode.is_transitive() {
+                            checker
+                                .check_transitive_compatibility(&new_schema, &history, mode)
+                                .await
+                        } else {
+                            checker
+                                .check_compatibility(&new_schema, previous, mode)
+                                .await
+                        };
+                        black_box(result.expect("compatibility check"))
+                    })
+                });
+            }
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_compatibility_check);
+criterion_main!(benches);
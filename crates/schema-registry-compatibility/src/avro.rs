@@ -0,0 +1,773 @@
+//! Avro schema resolution
+//!
+//! Avro data is always read with both schemas at hand, and the specification
+//! says how a reader resolves data from a writer: record fields are matched by
+//! name or by the reader's aliases, fields the writer lacks take the reader's
+//! default, numbers may be promoted (`int` to `long`, `float` or `double`,
+//! `long` to `float` or `double`, `float` to `double`) and `string` and `bytes`
+//! are interchangeable. Each writer union member must resolve to a member of
+//! the reader's union. [`incompatibilities`] reports what can't be resolved.
+//!
+//! Names are compared unqualified, as resolution does, and logical types by
+//! their underlying type.
+
+use crate::trace::Tracer;
+use crate::Direction;
+use schema_registry_core::{
+    traits::CompatibilityViolation,
+    types::{RemediationAction, ViolationType},
+};
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+
+/// Named types of a schema by unqualified name
+pub(crate) type Names<'a> = HashMap<&'a str, &'a Map<String, Value>>;
+
+const PRIMITIVES: &[&str] = &[
+    "null", "boolean", "int", "long", "float", "double", "bytes", "string",
+];
+
+/// Writer data a reader can't resolve
+///
+/// Reader fields the writer lacks and that have no default are reported as
+/// `RequiredAdded` when the reader is the new schema and as `FieldRemoved`
+/// when it is the old one, so the caller can apply deprecation notices to
+/// removals.
+pub(crate) fn incompatibilities(
+    reader: &Value,
+    writer: &Value,
+    direction: Direction,
+    tracer: &mut Tracer,
+) -> Vec<CompatibilityViolation> {
+    let mut resolution = Resolution {
+        direction,
+        tracer,
+        reader_names: named_types(reader),
+        writer_names: named_types(writer),
+        visiting: HashSet::new(),
+        violations: Vec::new(),
+    };
+    resolution.resolve(reader, writer, "");
+    resolution.violations
+}
+
+/// An Avro schema with named references looked up
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Node<'a> {
+    Primitive(&'a str),
+    Record(&'a Map<String, Value>),
+    Enum(&'a Map<String, Value>),
+    Fixed(&'a Map<String, Value>),
+    Array(&'a Value),
+    Map(&'a Value),
+    Union(&'a [Value]),
+}
+
+impl Node<'_> {
+    /// Name of the type, as used in violation descriptions
+    fn type_name(&self) -> String {
+        match self {
+            Node::Primitive(name) => name.to_string(),
+            Node::Record(map) | Node::Enum(map) | Node::Fixed(map) => {
+                short_name(map).unwrap_or("unnamed").to_string()
+            }
+            Node::Array(_) => "array".to_string(),
+            Node::Map(_) => "map".to_string(),
+            Node::Union(members) => format!("union of {} types", members.len()),
+        }
+    }
+}
+
+struct Resolution<'a, 't> {
+    direction: Direction,
+    tracer: &'t mut Tracer,
+    reader_names: Names<'a>,
+    writer_names: Names<'a>,
+    /// Reader and writer records being resolved, so a recursive type stops
+    /// at its first repetition
+    visiting: HashSet<(String, String)>,
+    violations: Vec<CompatibilityViolation>,
+}
+
+impl<'a> Resolution<'a, '_> {
+    fn resolve(&mut self, reader: &'a Value, writer: &'a Value, path: &str) {
+        let (Some(reader_node), Some(writer_node)) = (
+            node(reader, &self.reader_names),
+            node(writer, &self.writer_names),
+        ) else {
+            return;
+        };
+
+        if let Node::Union(writer_members) = writer_node {
+            // Every member the writer may have used must resolve
+            for member in writer_members {
+                let Some(member_node) = node(member, &self.writer_names) else {
+                    continue;
+                };
+                match self.select(reader, member_node) {
+                    Some(selected) => {
+                        let reader_name = self.direction.reader;
+                        self.tracer
+                            .passed("avro.union_member", Some(self.direction), path, || {
+                                format!(
+                                    "The {} schema reads the union member {}",
+                                    reader_name,
+                                    member_node.type_name()
+                                )
+                            });
+                        self.resolve(selected, member, path)
+                    }
+                    None => {
+                        let member_type = member_node.type_name();
+                        let suggestion = if self.direction.reader_is_new {
+                            format!("Let {} read {} again", place(path), member_type)
+                        } else {
+                            format!(
+                                "Remove {} from the union of {}, since the old schema can't read it",
+                                member_type,
+                                place(path)
+                            )
+                        };
+                        self.push(
+                            "avro.union_member",
+                            ViolationType::TypeChanged,
+                            path,
+                            Some(member),
+                            Some(reader),
+                            format!(
+                                "{} may hold {} in the {} schema, which the {} schema can't read",
+                                describe(path),
+                                member_type,
+                                self.direction.writer,
+                                self.direction.reader
+                            ),
+                            (RemediationAction::RestoreType, suggestion),
+                        )
+                    }
+                }
+            }
+            return;
+        }
+
+        let Some(selected) = self.select(reader, writer_node) else {
+            let suggestion = if self.direction.reader_is_new {
+                format!(
+                    "Change {} back to {}, or to a type {} promotes to",
+                    place(path),
+                    writer_node.type_name(),
+                    writer_node.type_name()
+                )
+            } else {
+                format!(
+                    "Change {} back to {}, or to a type that promotes to it",
+                    place(path),
+                    reader_node.type_name()
+                )
+            };
+            self.push(
+                "avro.type",
+                ViolationType::TypeChanged,
+                path,
+                Some(writer),
+                Some(reader),
+                format!(
+                    "{} is {} in the {} schema, which the {} schema's {} can't read",
+                    describe(path),
+                    writer_node.type_name(),
+                    self.direction.writer,
+                    self.direction.reader,
+                    reader_node.type_name()
+                ),
+                (RemediationAction::RestoreType, suggestion),
+            );
+            return;
+        };
+        let Some(selected_node) = node(selected, &self.reader_names) else {
+            return;
+        };
+        let reader_name = self.direction.reader;
+        self.tracer
+            .passed("avro.type", Some(self.direction), path, || {
+                format!(
+                    "The {} schema's {} reads {}",
+                    reader_name,
+                    selected_node.type_name(),
+                    writer_node.type_name()
+                )
+            });
+
+        match (selected_node, writer_node) {
+            (Node::Record(reader_record), Node::Record(writer_record)) => {
+                self.resolve_record(reader_record, writer_record, path);
+            }
+            (Node::Enum(reader_enum), Node::Enum(writer_enum)) => {
+                self.resolve_enum(reader_enum, writer_enum, path);
+            }
+            (Node::Fixed(reader_fixed), Node::Fixed(writer_fixed))
+                if reader_fixed.get("size") != writer_fixed.get("size") =>
+            {
+                let old_size = if self.direction.reader_is_new {
+                    writer_fixed.get("size")
+                } else {
+                    reader_fixed.get("size")
+                };
+                let suggestion = format!(
+                    "Set the size of {} back to {}",
+                    place(path),
+                    old_size.map(Value::to_string).unwrap_or_default()
+                );
+                self.push(
+                    "avro.fixed_size",
+                    ViolationType::TypeChanged,
+                    &join(path, "size"),
+                    writer_fixed.get("size"),
+                    reader_fixed.get("size"),
+                    format!(
+                        "{} has a different fixed size in the {} schema than in the {} schema",
+                        describe(path),
+                        self.direction.reader,
+                        self.direction.writer
+                    ),
+                    (RemediationAction::RestoreType, suggestion),
+                );
+            }
+            (Node::Fixed(_), Node::Fixed(_)) => {
+                self.tracer
+                    .passed("avro.fixed_size", Some(self.direction), path, || {
+                        "Both schemas have the same fixed size".to_string()
+                    });
+            }
+            (Node::Array(reader_items), Node::Array(writer_items)) => {
+                self.resolve(reader_items, writer_items, &format!("{}[]", path));
+            }
+            (Node::Map(reader_values), Node::Map(writer_values)) => {
+                self.resolve(reader_values, writer_values, &format!("{}{{}}", path));
+            }
+            _ => {}
+        }
+    }
+
+    /// The reader schema that resolves writer data of type `writer`: the
+    /// reader itself, or the first member of its union that can
+    fn select(&self, reader: &'a Value, writer: Node<'a>) -> Option<&'a Value> {
+        let reader_node = node(reader, &self.reader_names)?;
+        let Node::Union(members) = reader_node else {
+            return accepts(reader_node, writer).then_some(reader);
+        };
+        let candidates = || {
+            members
+                .iter()
+                .filter_map(|member| Some((member, node(member, &self.reader_names)?)))
+        };
+        // A member of the same type wins over one the writer's type promotes to
+        candidates()
+            .find(|(_, member)| same_type(*member, writer))
+            .or_else(|| candidates().find(|(_, member)| accepts(*member, writer)))
+            .map(|(member, _)| member)
+    }
+
+    fn resolve_record(
+        &mut self,
+        reader: &'a Map<String, Value>,
+        writer: &'a Map<String, Value>,
+        path: &str,
+    ) {
+        let key = (
+            short_name(reader).unwrap_or_default().to_string(),
+            short_name(writer).unwrap_or_default().to_string(),
+        );
+        if !self.visiting.insert(key.clone()) {
+            return;
+        }
+
+        let writer_fields = record_fields(writer);
+        for reader_field in record_fields(reader) {
+            let Some(name) = reader_field.get("name").and_then(Value::as_str) else {
+                continue;
+            };
+            let field_path = join(path, name);
+            let aliases = aliases(reader_field);
+            let writer_field = writer_fields.iter().find(|field| {
+                field
+                    .get("name")
+                    .and_then(Value::as_str)
+                    .is_some_and(|writer_name| {
+                        writer_name == name || aliases.contains(&writer_name)
+                    })
+            });
+            let reader_name = self.direction.reader;
+            match (writer_field, reader_field.get("type")) {
+                (Some(writer_field), Some(reader_type)) => {
+                    self.tracer
+                        .passed("avro.field", Some(self.direction), &field_path, || {
+                            format!(
+                                "The {} schema's field matches writer field '{}'",
+                                reader_name,
+                                writer_field
+                                    .get("name")
+                                    .and_then(Value::as_str)
+                                    .unwrap_or_default()
+                            )
+                        });
+                    if let Some(writer_type) = writer_field.get("type") {
+                        self.resolve(reader_type, writer_type, &field_path);
+                    }
+                }
+                (None, _) if reader_field.contains_key("default") => {
+                    self.tracer
+                        .passed("avro.field", Some(self.direction), &field_path, || {
+                            format!(
+                                "The writer lacks the field, and the {} schema gives it a default",
+                                reader_name
+                            )
+                        });
+                }
+                (None, _) if self.direction.reader_is_new => {
+                    let description = format!(
+                        "Field '{}' was added to the new schema without a default, so old data can't be read",
+                        field_path
+                    );
+                    let suggestion = format!("Give field '{}' a default", field_path);
+                    self.push(
+                        "avro.field",
+                        ViolationType::RequiredAdded,
+                        &field_path,
+                        None,
+                        None,
+                        description,
+                        (RemediationAction::AddDefault, suggestion),
+                    );
+                }
+                (None, _) => {
+                    let description = format!(
+                        "Field '{}' was removed, and the old schema has no default for it",
+                        field_path
+                    );
+                    let suggestion = format!("Restore field '{}'", field_path);
+                    self.push(
+                        "avro.field",
+                        ViolationType::FieldRemoved,
+                        &field_path,
+                        None,
+                        None,
+                        description,
+                        (RemediationAction::RestoreField, suggestion),
+                    );
+                }
+                (Some(_), None) => {}
+            }
+        }
+        self.visiting.remove(&key);
+    }
+
+    fn resolve_enum(
+        &mut self,
+        reader: &Map<String, Value>,
+        writer: &Map<String, Value>,
+        path: &str,
+    ) {
+        let reader_name = self.direction.reader;
+        // Unknown symbols resolve to the reader's default
+        if reader.contains_key("default") {
+            self.tracer
+                .passed("avro.enum_symbols", Some(self.direction), path, || {
+                    format!(
+                        "The {} schema's enum has a default for unknown symbols",
+                        reader_name
+                    )
+                });
+            return;
+        }
+        let reader_symbols = symbols(reader);
+        let removed: Vec<Value> = symbols(writer)
+            .into_iter()
+            .filter(|symbol| !reader_symbols.contains(symbol))
+            .map(|symbol| Value::String(symbol.to_string()))
+            .collect();
+        if removed.is_empty() {
+            self.tracer
+                .passed("avro.enum_symbols", Some(self.direction), path, || {
+                    format!("The {} schema knows every symbol", reader_name)
+                });
+            return;
+        }
+        let listed = removed
+            .iter()
+            .filter_map(Value::as_str)
+            .collect::<Vec<_>>()
+            .join(", ");
+        let suggestion = if self.direction.reader_is_new {
+            format!(
+                "Restore symbols {} to {}, or give the enum a default",
+                listed,
+                place(path)
+            )
+        } else {
+            format!(
+                "Remove symbols {} from {}, since the old schema's enum has no default",
+                listed,
+                place(path)
+            )
+        };
+        self.push(
+            "avro.enum_symbols",
+            ViolationType::EnumValueRemoved,
+            path,
+            Some(&Value::Array(removed)),
+            None,
+            format!(
+                "{} has symbols {} in the {} schema that the {} schema lacks, and it has no default",
+                describe(path),
+                listed,
+                self.direction.writer,
+                self.direction.reader
+            ),
+            (RemediationAction::RestoreEnumValues, suggestion),
+        );
+    }
+
+    /// Report a violation of `rule`
+    #[allow(clippy::too_many_arguments)]
+    fn push(
+        &mut self,
+        rule: &str,
+        violation_type: ViolationType,
+        path: &str,
+        writer_value: Option<&Value>,
+        reader_value: Option<&Value>,
+        description: String,
+        (action, suggestion): (RemediationAction, String),
+    ) {
+        self.tracer
+            .failed(rule, Some(self.direction), path, || description.clone());
+        let violation = self.direction.violation(
+            violation_type,
+            path,
+            writer_value,
+            reader_value,
+            description,
+        );
+        self.violations
+            .push(violation.with_suggestion(action, suggestion));
+    }
+}
+
+pub(crate) fn node<'a>(schema: &'a Value, names: &Names<'a>) -> Option<Node<'a>> {
+    match schema {
+        Value::String(name) => named(name, names),
+        Value::Array(members) => Some(Node::Union(members)),
+        Value::Object(map) => match map.get("type")? {
+            Value::String(type_name) => match type_name.as_str() {
+                "record" | "error" => Some(Node::Record(map)),
+                "enum" => Some(Node::Enum(map)),
+                "fixed" => Some(Node::Fixed(map)),
+                "array" => map.get("items").map(Node::Array),
+                "map" => map.get("values").map(Node::Map),
+                // A primitive with a logical type, or a reference
+                other => named(other, names),
+            },
+            nested => node(nested, names),
+        },
+        _ => None,
+    }
+}
+
+fn named<'a>(name: &'a str, names: &Names<'a>) -> Option<Node<'a>> {
+    if PRIMITIVES.contains(&name) {
+        return Some(Node::Primitive(name));
+    }
+    let definition = *names.get(unqualified(name))?;
+    match definition.get("type").and_then(Value::as_str) {
+        Some("enum") => Some(Node::Enum(definition)),
+        Some("fixed") => Some(Node::Fixed(definition)),
+        _ => Some(Node::Record(definition)),
+    }
+}
+
+pub(crate) fn named_types(schema: &Value) -> Names<'_> {
+    let mut names = HashMap::new();
+    collect_names(schema, &mut names);
+    names
+}
+
+fn collect_names<'a>(schema: &'a Value, names: &mut Names<'a>) {
+    match schema {
+        Value::Array(members) => {
+            for member in members {
+                collect_names(member, names);
+            }
+        }
+        Value::Object(map) => match map.get("type") {
+            Some(Value::String(type_name)) => match type_name.as_str() {
+                "record" | "error" | "enum" | "fixed" => {
+                    if let Some(name) = short_name(map) {
+                        names.entry(name).or_insert(map);
+                    }
+                    for field in record_fields(map) {
+                        if let Some(field_type) = field.get("type") {
+                            collect_names(field_type, names);
+                        }
+                    }
+                }
+                "array" => {
+                    if let Some(items) = map.get("items") {
+                        collect_names(items, names);
+                    }
+                }
+                "map" => {
+                    if let Some(values) = map.get("values") {
+                        collect_names(values, names);
+                    }
+                }
+                _ => {}
+            },
+            Some(nested) => collect_names(nested, names),
+            None => {}
+        },
+        _ => {}
+    }
+}
+
+/// Whether a non-union reader can read writer data of type `writer`
+fn accepts(reader: Node<'_>, writer: Node<'_>) -> bool {
+    match (reader, writer) {
+        (Node::Primitive(reader), Node::Primitive(writer)) => promotes(writer, reader),
+        (Node::Record(reader), Node::Record(writer))
+        | (Node::Enum(reader), Node::Enum(writer))
+        | (Node::Fixed(reader), Node::Fixed(writer)) => names_match(reader, writer),
+        (Node::Array(_), Node::Array(_)) | (Node::Map(_), Node::Map(_)) => true,
+        _ => false,
+    }
+}
+
+fn same_type(reader: Node<'_>, writer: Node<'_>) -> bool {
+    match (reader, writer) {
+        (Node::Primitive(reader), Node::Primitive(writer)) => reader == writer,
+        _ => accepts(reader, writer),
+    }
+}
+
+/// Whether data written as `writer` can be read as `reader`
+fn promotes(writer: &str, reader: &str) -> bool {
+    writer == reader
+        || matches!(
+            (writer, reader),
+            ("int", "long" | "float" | "double")
+                | ("long", "float" | "double")
+                | ("float", "double")
+                | ("string", "bytes")
+                | ("bytes", "string")
+        )
+}
+
+/// Whether the reader's name or one of its aliases is the writer's name
+fn names_match(reader: &Map<String, Value>, writer: &Map<String, Value>) -> bool {
+    let Some(writer_name) = short_name(writer) else {
+        return false;
+    };
+    short_name(reader) == Some(writer_name) || aliases(reader).contains(&writer_name)
+}
+
+fn unqualified(name: &str) -> &str {
+    name.rsplit('.').next().unwrap_or(name)
+}
+
+fn short_name(map: &Map<String, Value>) -> Option<&str> {
+    map.get("name").and_then(Value::as_str).map(unqualified)
+}
+
+fn aliases(map: &Map<String, Value>) -> Vec<&str> {
+    map.get("aliases")
+        .and_then(Value::as_array)
+        .map(|aliases| {
+            aliases
+                .iter()
+                .filter_map(Value::as_str)
+                .map(unqualified)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub(crate) fn record_fields(map: &Map<String, Value>) -> Vec<&Map<String, Value>> {
+    map.get("fields")
+        .and_then(Value::as_array)
+        .map(|fields| fields.iter().filter_map(Value::as_object).collect())
+        .unwrap_or_default()
+}
+
+pub(crate) fn symbols(map: &Map<String, Value>) -> Vec<&str> {
+    map.get("symbols")
+        .and_then(Value::as_array)
+        .map(|symbols| symbols.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default()
+}
+
+fn join(prefix: &str, name: &str) -> String {
+    if prefix.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", prefix, name)
+    }
+}
+
+fn describe(path: &str) -> String {
+    if path.is_empty() {
+        "The schema".to_string()
+    } else {
+        format!("Field '{}'", path)
+    }
+}
+
+fn place(path: &str) -> String {
+    if path.is_empty() {
+        "the schema".to_string()
+    } else {
+        format!("field '{}'", path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn record(fields: Value) -> Value {
+        json!({"type": "record", "name": "User", "namespace": "com.example", "fields": fields})
+    }
+
+    fn backward(old: &Value, new: &Value) -> Vec<CompatibilityViolation> {
+        incompatibilities(new, old, Direction::BACKWARD, &mut Tracer::off())
+    }
+
+    fn forward(old: &Value, new: &Value) -> Vec<CompatibilityViolation> {
+        incompatibilities(old, new, Direction::FORWARD, &mut Tracer::off())
+    }
+
+    #[test]
+    fn test_fields_need_defaults_to_be_added_or_removed() {
+        let old = record(json!([{"name": "id", "type": "string"}]));
+        let new = record(json!([
+            {"name": "id", "type": "string"},
+            {"name": "email", "type": "string"},
+            {"name": "locale", "type": "string", "default": "en"},
+        ]));
+
+        let violations = backward(&old, &new);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].violation_type, ViolationType::RequiredAdded);
+        assert_eq!(violations[0].field_path, "email");
+        let suggestion = violations[0].suggestion.as_ref().unwrap();
+        assert_eq!(suggestion.action, RemediationAction::AddDefault);
+        assert_eq!(suggestion.message, "Give field 'email' a default");
+        assert!(forward(&old, &new).is_empty());
+
+        // Removing `email` again leaves old readers without a value for it
+        let violations = forward(&new, &old);
+        let paths: Vec<&str> = violations.iter().map(|v| v.field_path.as_str()).collect();
+        assert_eq!(paths, ["email"]);
+        assert_eq!(violations[0].violation_type, ViolationType::FieldRemoved);
+    }
+
+    #[test]
+    fn test_numeric_promotion_is_one_way() {
+        let old =
+            record(json!([{"name": "count", "type": "int"}, {"name": "blob", "type": "bytes"}]));
+        let new =
+            record(json!([{"name": "count", "type": "long"}, {"name": "blob", "type": "string"}]));
+
+        assert!(backward(&old, &new).is_empty());
+        let violations = forward(&old, &new);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].violation_type, ViolationType::TypeChanged);
+        assert_eq!(violations[0].field_path, "count");
+        assert_eq!(violations[0].old_value, Some(json!("int")));
+        assert_eq!(violations[0].new_value, Some(json!("long")));
+        assert_eq!(
+            violations[0].suggestion.as_ref().unwrap().message,
+            "Change field 'count' back to int, or to a type that promotes to it"
+        );
+    }
+
+    #[test]
+    fn test_union_members() {
+        let old = record(json!([{"name": "note", "type": ["null", "string"], "default": null}]));
+        let added =
+            record(json!([{"name": "note", "type": ["null", "string", "int"], "default": null}]));
+
+        // Old data always resolves to a member of the wider union
+        assert!(backward(&old, &added).is_empty());
+        // Old readers can't read an int
+        let violations = forward(&old, &added);
+        assert_eq!(violations.len(), 1);
+        assert!(violations[0].description.contains("may hold int"));
+
+        // A union reader reads its members' data, promoted or not
+        let narrow = record(json!([{"name": "note", "type": "int"}]));
+        let wide = record(json!([{"name": "note", "type": ["null", "double"], "default": null}]));
+        assert!(backward(&narrow, &wide).is_empty());
+        assert_eq!(forward(&narrow, &wide).len(), 2);
+    }
+
+    #[test]
+    fn test_aliases_and_enum_defaults() {
+        let old = json!({
+            "type": "record",
+            "name": "Order",
+            "fields": [
+                {"name": "state", "type": {"type": "enum", "name": "State", "symbols": ["OPEN", "HELD", "CLOSED"]}},
+                {"name": "customer_id", "type": "string"},
+            ],
+        });
+        let new = json!({
+            "type": "record",
+            "name": "PurchaseOrder",
+            "aliases": ["com.example.Order"],
+            "fields": [
+                {"name": "state", "type": {"type": "enum", "name": "State", "symbols": ["OPEN", "CLOSED"]}},
+                {"name": "customer", "aliases": ["customer_id"], "type": "string"},
+            ],
+        });
+
+        let violations = backward(&old, &new);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(
+            violations[0].violation_type,
+            ViolationType::EnumValueRemoved
+        );
+        assert_eq!(violations[0].field_path, "state");
+        assert_eq!(violations[0].old_value, Some(json!(["HELD"])));
+
+        // A default catches symbols the reader doesn't know
+        let mut with_default = new.clone();
+        with_default["fields"][0]["type"]["default"] = json!("OPEN");
+        assert!(backward(&old, &with_default).is_empty());
+
+        // Old readers don't know the new record name
+        assert_eq!(
+            forward(&old, &new)[0].violation_type,
+            ViolationType::TypeChanged
+        );
+    }
+
+    #[test]
+    fn test_recursive_and_referenced_types() {
+        let schema = |next_type: &str| {
+            json!({
+                "type": "record",
+                "name": "Node",
+                "fields": [
+                    {"name": "value", "type": next_type},
+                    {"name": "next", "type": ["null", "Node"], "default": null},
+                    {"name": "size", "type": {"type": "fixed", "name": "Hash", "size": 16}},
+                    {"name": "previous", "type": "Hash"},
+                ],
+            })
+        };
+
+        assert!(backward(&schema("int"), &schema("long")).is_empty());
+        let violations = forward(&schema("int"), &schema("long"));
+        let paths: Vec<&str> = violations.iter().map(|v| v.field_path.as_str()).collect();
+        assert_eq!(paths, ["value"]);
+    }
+}
@@ -0,0 +1,127 @@
+//! Cache of diffs between pairs of schema contents
+//!
+//! A transitive check compares a new schema with every earlier version, and
+//! the next registration of the subject repeats most of those comparisons.
+//! What a comparison finds depends only on the two contents and the mode, so
+//! findings are cached by `(old_hash, new_hash, mode)`. Whether a removed field
+//! had served its deprecation notice depends on the versions involved, so
+//! removals are cached as candidates and judged on every check.
+//!
+//! Lookups are counted in `schema_registry_compatibility_cache_lookups_total{result}`
+//! and in [`CacheStats`].
+
+use moka::future::Cache;
+use schema_registry_core::{traits::CompatibilityViolation, types::CompatibilityMode};
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Schema pairs kept by default
+pub const DEFAULT_CACHE_CAPACITY: u64 = 10_000;
+
+/// Something a comparison found
+#[derive(Debug, Clone)]
+pub(crate) enum Finding {
+    /// A field of the old schema the new one dropped, which may be allowed
+    /// once its deprecation notice has passed
+    Removed(String),
+    Violation(CompatibilityViolation),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct PairKey {
+    old_hash: String,
+    new_hash: String,
+    mode: CompatibilityMode,
+}
+
+impl PairKey {
+    fn new(old_hash: &str, new_hash: &str, mode: CompatibilityMode) -> Self {
+        Self {
+            old_hash: old_hash.to_string(),
+            new_hash: new_hash.to_string(),
+            mode,
+        }
+    }
+}
+
+/// Lookup counts since the cache was created or cleared
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Approximate number of cached pairs
+    pub entries: u64,
+}
+
+impl CacheStats {
+    /// Share of lookups answered from the cache, `None` before the first one
+    pub fn hit_ratio(&self) -> Option<f64> {
+        let lookups = self.hits + self.misses;
+        (lookups > 0).then(|| self.hits as f64 / lookups as f64)
+    }
+}
+
+/// Findings per schema pair, least recently used pairs evicted first
+pub struct CompatibilityCache {
+    pairs: Cache<PairKey, Arc<Vec<Finding>>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl CompatibilityCache {
+    pub fn new(capacity: u64) -> Self {
+        Self {
+            pairs: Cache::new(capacity),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    pub(crate) async fn get(
+        &self,
+        old_hash: &str,
+        new_hash: &str,
+        mode: CompatibilityMode,
+    ) -> Option<Arc<Vec<Finding>>> {
+        let found = self
+            .pairs
+            .get(&PairKey::new(old_hash, new_hash, mode))
+            .await;
+        let (counter, result) = match found {
+            Some(_) => (&self.hits, "hit"),
+            None => (&self.misses, "miss"),
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        metrics::counter!("schema_registry_compatibility_cache_lookups_total", "result" => result)
+            .increment(1);
+        found
+    }
+
+    pub(crate) async fn insert(
+        &self,
+        old_hash: &str,
+        new_hash: &str,
+        mode: CompatibilityMode,
+        findings: Arc<Vec<Finding>>,
+    ) {
+        self.pairs
+            .insert(PairKey::new(old_hash, new_hash, mode), findings)
+            .await;
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self.pairs.entry_count(),
+        }
+    }
+
+    /// Drop every cached pair and reset the counts
+    pub fn clear(&self) {
+        self.pairs.invalidate_all();
+        self.hits.store(0, Ordering::Relaxed);
+        self.misses.store(0, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,901 @@
+//! Compatibility between Avro and JSON Schema
+//!
+//! Teams often describe one event in Avro for Kafka and in JSON Schema for
+//! REST. Both formats are normalized into [`Shape`]s, a model of records,
+//! fields and primitives that either can express, and the shapes are compared
+//! the way Avro resolves a writer's data: a reader must accept every type the
+//! writer may produce, each field it requires must always be present in the
+//! writer's data, and the writer may only use enum values the reader knows.
+//! Numbers may widen, and `string` reads enum values.
+//!
+//! Only what both formats express is compared. JSON Schema constraints such as
+//! `pattern` or `maxLength`, Avro aliases, logical types and record names are
+//! not. A JSON `integer` is a 64-bit integer unless its `format` or bounds
+//! keep it within 32 bits, and a `$ref`, an `allOf` or a missing `type`
+//! matches anything.
+
+use crate::avro::{self, Node};
+use crate::trace::Tracer;
+use crate::Direction;
+use schema_registry_core::{
+    schema::RegisteredSchema,
+    traits::CompatibilityViolation,
+    types::{CompatibilityMode, RemediationAction, SerializationFormat, ViolationType},
+};
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+
+/// What comparing an Avro schema with a JSON Schema finds under `mode`
+///
+/// Protobuf schemas and content that doesn't parse have nothing to compare.
+/// Reader fields missing from the writer are reported as `FieldRemoved` when
+/// the reader is the old schema, so the caller can apply deprecation notices.
+pub(crate) fn incompatibilities(
+    new_schema: &RegisteredSchema,
+    old_schema: &RegisteredSchema,
+    mode: CompatibilityMode,
+    tracer: &mut Tracer,
+) -> Vec<CompatibilityViolation> {
+    compare_contents(
+        (new_schema.format, &new_schema.content),
+        (old_schema.format, &old_schema.content),
+        mode,
+        tracer,
+    )
+}
+
+fn compare_contents(
+    (new_format, new_content): (SerializationFormat, &str),
+    (old_format, old_content): (SerializationFormat, &str),
+    mode: CompatibilityMode,
+    tracer: &mut Tracer,
+) -> Vec<CompatibilityViolation> {
+    let (Some(new), Some(old)) = (
+        normalize(new_format, new_content),
+        normalize(old_format, old_content),
+    ) else {
+        return Vec::new();
+    };
+    let mut violations = Vec::new();
+    if crate::reads_backward(mode) {
+        violations.extend(compare(&new, new_format, &old, Direction::BACKWARD, tracer));
+    }
+    if crate::reads_forward(mode) {
+        violations.extend(compare(&old, old_format, &new, Direction::FORWARD, tracer));
+    }
+    violations
+}
+
+/// A type as both formats understand it
+#[derive(Debug, Clone, PartialEq)]
+enum Shape {
+    /// Not described precisely enough to compare
+    Any,
+    Null,
+    Boolean,
+    Int,
+    Long,
+    Float,
+    Double,
+    String,
+    Bytes,
+    Enum {
+        symbols: Vec<String>,
+        /// Whether unknown symbols are read, as an Avro enum with a default does
+        open: bool,
+    },
+    Array(Box<Shape>),
+    Map(Box<Shape>),
+    Record {
+        fields: Vec<Field>,
+        /// Whether fields it doesn't list are rejected
+        closed: bool,
+    },
+    Union(Vec<Shape>),
+}
+
+impl Shape {
+    /// Name of the type, as used in violation descriptions
+    fn type_name(&self) -> String {
+        match self {
+            Shape::Any => "any value",
+            Shape::Null => "null",
+            Shape::Boolean => "boolean",
+            Shape::Int => "32-bit integer",
+            Shape::Long => "64-bit integer",
+            Shape::Float => "float",
+            Shape::Double => "double",
+            Shape::String => "string",
+            Shape::Bytes => "bytes",
+            Shape::Enum { .. } => "enum",
+            Shape::Array(_) => "array",
+            Shape::Map(_) => "map",
+            Shape::Record { .. } => "record",
+            Shape::Union(members) => return format!("union of {} types", members.len()),
+        }
+        .to_string()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Field {
+    name: String,
+    shape: Shape,
+    /// Whether written data always has the field
+    always_present: bool,
+    /// Whether a reader does without the field: an Avro field with a
+    /// default, or a JSON property that isn't required
+    optional: bool,
+}
+
+fn normalize(format: SerializationFormat, content: &str) -> Option<Shape> {
+    let content: Value = serde_json::from_str(content).ok()?;
+    match format {
+        SerializationFormat::Avro => Some(AvroShapes::new(&content).shape(&content)),
+        SerializationFormat::JsonSchema => Some(json_shape(&content)),
+        SerializationFormat::Protobuf => None,
+    }
+}
+
+struct AvroShapes<'a> {
+    names: avro::Names<'a>,
+    /// Records being normalized, so a recursive type stops at its first
+    /// repetition
+    visiting: HashSet<&'a str>,
+}
+
+impl<'a> AvroShapes<'a> {
+    fn new(schema: &'a Value) -> Self {
+        Self {
+            names: avro::named_types(schema),
+            visiting: HashSet::new(),
+        }
+    }
+
+    fn shape(&mut self, schema: &'a Value) -> Shape {
+        let Some(node) = avro::node(schema, &self.names) else {
+            return Shape::Any;
+        };
+        match node {
+            Node::Primitive(name) => match name {
+                "null" => Shape::Null,
+                "boolean" => Shape::Boolean,
+                "int" => Shape::Int,
+                "long" => Shape::Long,
+                "float" => Shape::Float,
+                "double" => Shape::Double,
+                "bytes" => Shape::Bytes,
+                "string" => Shape::String,
+                _ => Shape::Any,
+            },
+            Node::Enum(map) => Shape::Enum {
+                symbols: avro::symbols(map).into_iter().map(str::to_string).collect(),
+                open: map.contains_key("default"),
+            },
+            Node::Fixed(_) => Shape::Bytes,
+            Node::Array(items) => Shape::Array(Box::new(self.shape(items))),
+            Node::Map(values) => Shape::Map(Box::new(self.shape(values))),
+            Node::Union(members) => {
+                Shape::Union(members.iter().map(|member| self.shape(member)).collect())
+            }
+            Node::Record(map) => {
+                let name = map.get("name").and_then(Value::as_str).unwrap_or_default();
+                if !self.visiting.insert(name) {
+                    return Shape::Any;
+                }
+                let fields = avro::record_fields(map)
+                    .into_iter()
+                    .filter_map(|field| {
+                        Some(Field {
+                            name: field.get("name")?.as_str()?.to_string(),
+                            shape: field
+                                .get("type")
+                                .map_or(Shape::Any, |field_type| self.shape(field_type)),
+                            always_present: true,
+                            optional: field.contains_key("default"),
+                        })
+                    })
+                    .collect();
+                self.visiting.remove(name);
+                Shape::Record {
+                    fields,
+                    closed: false,
+                }
+            }
+        }
+    }
+}
+
+fn json_shape(schema: &Value) -> Shape {
+    let Value::Object(schema) = schema else {
+        return Shape::Any;
+    };
+    if let Some(members) = schema
+        .get("anyOf")
+        .or_else(|| schema.get("oneOf"))
+        .and_then(Value::as_array)
+    {
+        return Shape::Union(members.iter().map(json_shape).collect());
+    }
+    if let Some(values) = schema.get("enum").and_then(Value::as_array) {
+        return json_enum(values);
+    }
+    if let Some(value) = schema.get("const") {
+        return json_enum(std::slice::from_ref(value));
+    }
+
+    let mut members: Vec<Shape> = match schema.get("type") {
+        Some(Value::String(type_name)) => vec![json_type(type_name, schema)],
+        Some(Value::Array(type_names)) => type_names
+            .iter()
+            .filter_map(Value::as_str)
+            .map(|type_name| json_type(type_name, schema))
+            .collect(),
+        _ => return Shape::Any,
+    };
+    match members.len() {
+        1 => members.remove(0),
+        _ => Shape::Union(members),
+    }
+}
+
+/// String values become enum symbols and `null` a union member; other values
+/// have no Avro counterpart
+fn json_enum(values: &[Value]) -> Shape {
+    let symbols: Vec<String> = values
+        .iter()
+        .filter_map(Value::as_str)
+        .map(str::to_string)
+        .collect();
+    let nullable = values.contains(&Value::Null);
+    if symbols.len() + usize::from(nullable) < values.len() {
+        return Shape::Any;
+    }
+    let symbols = Shape::Enum {
+        symbols,
+        open: false,
+    };
+    if nullable {
+        Shape::Union(vec![symbols, Shape::Null])
+    } else {
+        symbols
+    }
+}
+
+fn json_type(type_name: &str, schema: &Map<String, Value>) -> Shape {
+    match type_name {
+        "null" => Shape::Null,
+        "boolean" => Shape::Boolean,
+        "integer" if fits_int(schema) => Shape::Int,
+        "integer" => Shape::Long,
+        "number" if schema.get("format").and_then(Value::as_str) == Some("float") => Shape::Float,
+        "number" => Shape::Double,
+        "string" => Shape::String,
+        "array" => Shape::Array(Box::new(schema.get("items").map_or(Shape::Any, json_shape))),
+        "object" => json_object(schema),
+        _ => Shape::Any,
+    }
+}
+
+/// Whether an integer's format or bounds keep it within 32 bits
+fn fits_int(schema: &Map<String, Value>) -> bool {
+    if schema.get("format").and_then(Value::as_str) == Some("int32") {
+        return true;
+    }
+    let bound = |keyword: &str| schema.get(keyword).and_then(Value::as_f64);
+    matches!(
+        (bound("minimum"), bound("maximum")),
+        (Some(minimum), Some(maximum))
+            if minimum >= f64::from(i32::MIN) && maximum <= f64::from(i32::MAX)
+    )
+}
+
+/// An object with `properties` is a record, and one without a map of its
+/// `additionalProperties`
+fn json_object(schema: &Map<String, Value>) -> Shape {
+    let additional = schema.get("additionalProperties");
+    let Some(properties) = schema.get("properties").and_then(Value::as_object) else {
+        return match additional {
+            Some(values @ Value::Object(_)) => Shape::Map(Box::new(json_shape(values))),
+            _ => Shape::Map(Box::new(Shape::Any)),
+        };
+    };
+    let required: Vec<&str> = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|fields| fields.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    let fields = properties
+        .iter()
+        .map(|(name, property)| {
+            let is_required = required.contains(&name.as_str());
+            Field {
+                name: name.clone(),
+                shape: json_shape(property),
+                always_present: is_required,
+                optional: !is_required,
+            }
+        })
+        .collect();
+    Shape::Record {
+        fields,
+        closed: additional == Some(&Value::Bool(false)),
+    }
+}
+
+fn compare(
+    reader: &Shape,
+    reader_format: SerializationFormat,
+    writer: &Shape,
+    direction: Direction,
+    tracer: &mut Tracer,
+) -> Vec<CompatibilityViolation> {
+    let mut comparison = Comparison {
+        direction,
+        reader_format,
+        tracer,
+        violations: Vec::new(),
+    };
+    comparison.compare(reader, writer, "");
+    comparison.violations
+}
+
+struct Comparison<'t> {
+    direction: Direction,
+    reader_format: SerializationFormat,
+    tracer: &'t mut Tracer,
+    violations: Vec<CompatibilityViolation>,
+}
+
+impl Comparison<'_> {
+    fn compare(&mut self, reader: &Shape, writer: &Shape, path: &str) {
+        match writer {
+            Shape::Any => self.passed("cross_format.type", path, || {
+                "The writer's type isn't described precisely enough to compare".to_string()
+            }),
+            // Every member the writer may have used must be readable
+            Shape::Union(members) => {
+                for member in members {
+                    self.compare(reader, member, path);
+                }
+            }
+            _ => match select(reader, writer) {
+                Some(selected) => {
+                    let reader_name = self.direction.reader;
+                    self.passed("cross_format.type", path, || {
+                        format!(
+                            "The {} schema's {} reads {}",
+                            reader_name,
+                            selected.type_name(),
+                            writer.type_name()
+                        )
+                    });
+                    self.compare_selected(selected, writer, path)
+                }
+                None => self.push_type_change(reader, writer, path),
+            },
+        }
+    }
+
+    /// Compare the contents of a reader type that accepts `writer`
+    fn compare_selected(&mut self, reader: &Shape, writer: &Shape, path: &str) {
+        match (reader, writer) {
+            (
+                Shape::Record {
+                    fields: reader_fields,
+                    closed,
+                },
+                Shape::Record {
+                    fields: writer_fields,
+                    ..
+                },
+            ) => self.compare_fields(reader_fields, *closed, writer_fields, path),
+            // An open record reads as a map of its field values
+            (Shape::Map(values), Shape::Record { fields, .. }) => {
+                for field in fields {
+                    self.compare(values, &field.shape, &join(path, &field.name));
+                }
+            }
+            (
+                Shape::Enum {
+                    symbols: reader_symbols,
+                    open: false,
+                },
+                Shape::Enum {
+                    symbols: writer_symbols,
+                    ..
+                },
+            ) => self.compare_symbols(reader_symbols, writer_symbols, path),
+            (Shape::Array(reader_items), Shape::Array(writer_items)) => {
+                self.compare(reader_items, writer_items, &format!("{}[]", path));
+            }
+            (Shape::Map(reader_values), Shape::Map(writer_values)) => {
+                self.compare(reader_values, writer_values, &format!("{}{{}}", path));
+            }
+            _ => {}
+        }
+    }
+
+    fn compare_fields(
+        &mut self,
+        reader_fields: &[Field],
+        closed: bool,
+        writer_fields: &[Field],
+        path: &str,
+    ) {
+        for reader_field in reader_fields {
+            let field_path = join(path, &reader_field.name);
+            let writer_field = writer_fields
+                .iter()
+                .find(|field| field.name == reader_field.name);
+            if let Some(writer_field) = writer_field {
+                self.compare(&reader_field.shape, &writer_field.shape, &field_path);
+            }
+            if reader_field.optional || writer_field.is_some_and(|field| field.always_present) {
+                let reader_name = self.direction.reader;
+                self.passed("cross_format.field", &field_path, || {
+                    if reader_field.optional {
+                        format!("The {} schema doesn't require the field", reader_name)
+                    } else {
+                        "The writer always has the field".to_string()
+                    }
+                });
+                continue;
+            }
+
+            let absence = if writer_field.is_some() {
+                "optional"
+            } else {
+                "missing"
+            };
+            let (violation_type, (action, suggestion)) = if self.direction.reader_is_new {
+                (ViolationType::RequiredAdded, self.relax(&field_path, path))
+            } else if writer_field.is_none() {
+                (
+                    ViolationType::FieldRemoved,
+                    (
+                        RemediationAction::RestoreField,
+                        format!("Restore field '{}' in the new schema", field_path),
+                    ),
+                )
+            } else {
+                (
+                    ViolationType::RequiredAdded,
+                    (
+                        RemediationAction::KeepRequired,
+                        format!("Keep field '{}' required in the new schema", field_path),
+                    ),
+                )
+            };
+            self.push(
+                "cross_format.field",
+                violation_type,
+                &field_path,
+                None,
+                None,
+                format!(
+                    "Field '{}' is required by the {} schema but {} in the {} schema",
+                    field_path, self.direction.reader, absence, self.direction.writer
+                ),
+                (action, suggestion),
+            );
+        }
+
+        if !closed {
+            return;
+        }
+        let reported = self.violations.len();
+        for writer_field in writer_fields {
+            if reader_fields
+                .iter()
+                .any(|field| field.name == writer_field.name)
+            {
+                continue;
+            }
+            let field_path = join(path, &writer_field.name);
+            let (action, suggestion) = if self.direction.reader_is_new {
+                (
+                    RemediationAction::RestoreField,
+                    format!("Add field '{}' to the new schema", field_path),
+                )
+            } else {
+                (
+                    RemediationAction::RemoveField,
+                    format!(
+                        "Remove field '{}', which the old schema's additionalProperties: false rejects",
+                        field_path
+                    ),
+                )
+            };
+            self.push(
+                "cross_format.closed_record",
+                ViolationType::ConstraintAdded,
+                &field_path,
+                None,
+                None,
+                format!(
+                    "Field '{}' is written by the {} schema but rejected by the {} schema, which allows no other fields",
+                    field_path, self.direction.writer, self.direction.reader
+                ),
+                (action, suggestion),
+            );
+        }
+        if self.violations.len() == reported {
+            let reader_name = self.direction.reader;
+            self.passed("cross_format.closed_record", path, || {
+                format!(
+                    "The {} schema knows every field the writer has",
+                    reader_name
+                )
+            });
+        }
+    }
+
+    /// How the new schema stops requiring a field, depending on its format
+    fn relax(&self, field_path: &str, record_path: &str) -> (RemediationAction, String) {
+        match self.reader_format {
+            SerializationFormat::Avro => (
+                RemediationAction::AddDefault,
+                format!("Give field '{}' a default", field_path),
+            ),
+            _ => (
+                RemediationAction::MarkOptional,
+                format!(
+                    "Remove '{}' from the required fields of {}",
+                    field_path.rsplit('.').next().unwrap_or(field_path),
+                    place(record_path)
+                ),
+            ),
+        }
+    }
+
+    fn compare_symbols(&mut self, reader: &[String], writer: &[String], path: &str) {
+        let removed: Vec<Value> = writer
+            .iter()
+            .filter(|symbol| !reader.contains(symbol))
+            .map(|symbol| Value::String(symbol.clone()))
+            .collect();
+        if removed.is_empty() {
+            let reader_name = self.direction.reader;
+            self.passed("cross_format.enum_symbols", path, || {
+                format!("The {} schema allows every value", reader_name)
+            });
+            return;
+        }
+        let listed = removed
+            .iter()
+            .filter_map(Value::as_str)
+            .collect::<Vec<_>>()
+            .join(", ");
+        let suggestion = if self.direction.reader_is_new {
+            format!("Add {} to the values of {}", listed, place(path))
+        } else {
+            format!(
+                "Remove {} from {}, since the old schema doesn't allow them",
+                listed,
+                place(path)
+            )
+        };
+        self.push(
+            "cross_format.enum_symbols",
+            ViolationType::EnumValueRemoved,
+            path,
+            Some(&Value::Array(removed)),
+            None,
+            format!(
+                "{} allows {} in the {} schema but not in the {} schema",
+                describe(path),
+                listed,
+                self.direction.writer,
+                self.direction.reader
+            ),
+            (RemediationAction::RestoreEnumValues, suggestion),
+        );
+    }
+
+    fn push_type_change(&mut self, reader: &Shape, writer: &Shape, path: &str) {
+        // Reading any string into a closed set of values narrows it rather
+        // than changing its type
+        let violation_type = match (reader, writer) {
+            (Shape::Enum { .. }, Shape::String) => ViolationType::ConstraintAdded,
+            _ => ViolationType::TypeChanged,
+        };
+        let suggestion = if self.direction.reader_is_new {
+            format!(
+                "Change {} to a type that reads {}",
+                place(path),
+                writer.type_name()
+            )
+        } else {
+            format!(
+                "Change {} to a type the old schema's {} reads",
+                place(path),
+                reader.type_name()
+            )
+        };
+        let (writer_type, reader_type) = (
+            Value::String(writer.type_name()),
+            Value::String(reader.type_name()),
+        );
+        self.push(
+            "cross_format.type",
+            violation_type,
+            path,
+            Some(&writer_type),
+            Some(&reader_type),
+            format!(
+                "{} may be {} in the {} schema, which the {} schema's {} can't read",
+                describe(path),
+                writer.type_name(),
+                self.direction.writer,
+                self.direction.reader,
+                reader.type_name()
+            ),
+            (RemediationAction::RestoreType, suggestion),
+        );
+    }
+
+    fn passed(&mut self, rule: &str, path: &str, reason: impl FnOnce() -> String) {
+        self.tracer.passed(rule, Some(self.direction), path, reason);
+    }
+
+    /// Report a violation of `rule`
+    #[allow(clippy::too_many_arguments)]
+    fn push(
+        &mut self,
+        rule: &str,
+        violation_type: ViolationType,
+        path: &str,
+        writer_value: Option<&Value>,
+        reader_value: Option<&Value>,
+        description: String,
+        (action, suggestion): (RemediationAction, String),
+    ) {
+        self.tracer
+            .failed(rule, Some(self.direction), path, || description.clone());
+        let violation = self.direction.violation(
+            violation_type,
+            path,
+            writer_value,
+            reader_value,
+            description,
+        );
+        self.violations
+            .push(violation.with_suggestion(action, suggestion));
+    }
+}
+
+/// The reader type that reads writer data of type `writer`: the reader
+/// itself, or the first member of its union that can, preferring one of the
+/// same type over one the writer's type widens to
+fn select<'r>(reader: &'r Shape, writer: &Shape) -> Option<&'r Shape> {
+    let Shape::Union(members) = reader else {
+        return accepts(reader, writer).then_some(reader);
+    };
+    members
+        .iter()
+        .find(|member| {
+            !matches!(member, Shape::Union(_))
+                && std::mem::discriminant(*member) == std::mem::discriminant(writer)
+        })
+        .or_else(|| members.iter().find_map(|member| select(member, writer)))
+}
+
+/// Whether a non-union reader reads writer data of type `writer`
+fn accepts(reader: &Shape, writer: &Shape) -> bool {
+    match (reader, writer) {
+        (Shape::Any, _) | (_, Shape::Any) => true,
+        (Shape::Enum { .. }, Shape::Enum { .. })
+        | (Shape::Enum { open: true, .. }, Shape::String)
+        | (Shape::String, Shape::Enum { .. })
+        | (Shape::Array(_), Shape::Array(_))
+        | (Shape::Map(_), Shape::Map(_))
+        | (Shape::Map(_), Shape::Record { .. })
+        | (Shape::Record { .. }, Shape::Record { .. }) => true,
+        _ => widens(writer, reader),
+    }
+}
+
+/// Whether a primitive written as `writer` reads as `reader`
+fn widens(writer: &Shape, reader: &Shape) -> bool {
+    matches!(
+        (writer, reader),
+        (Shape::Null, Shape::Null)
+            | (Shape::Boolean, Shape::Boolean)
+            | (
+                Shape::Int,
+                Shape::Int | Shape::Long | Shape::Float | Shape::Double
+            )
+            | (Shape::Long, Shape::Long | Shape::Float | Shape::Double)
+            | (Shape::Float, Shape::Float | Shape::Double)
+            | (Shape::Double, Shape::Double)
+            | (Shape::String | Shape::Bytes, Shape::String | Shape::Bytes)
+    )
+}
+
+fn join(prefix: &str, name: &str) -> String {
+    if prefix.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", prefix, name)
+    }
+}
+
+fn describe(path: &str) -> String {
+    if path.is_empty() {
+        "The schema".to_string()
+    } else {
+        format!("Field '{}'", path)
+    }
+}
+
+fn place(path: &str) -> String {
+    if path.is_empty() {
+        "the schema".to_string()
+    } else {
+        format!("field '{}'", path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    type Version = (SerializationFormat, String);
+
+    fn avro(fields: Value) -> Version {
+        let record = json!({"type": "record", "name": "OrderPlaced", "fields": fields});
+        (SerializationFormat::Avro, record.to_string())
+    }
+
+    fn json_schema(properties: Value, required: Value) -> Version {
+        json_document(json!({"type": "object", "properties": properties, "required": required}))
+    }
+
+    fn json_document(schema: Value) -> Version {
+        (SerializationFormat::JsonSchema, schema.to_string())
+    }
+
+    fn check(new: &Version, old: &Version, mode: CompatibilityMode) -> Vec<CompatibilityViolation> {
+        compare_contents((new.0, &new.1), (old.0, &old.1), mode, &mut Tracer::off())
+    }
+
+    #[test]
+    fn test_same_event_in_both_formats_is_fully_compatible() {
+        let avro = avro(json!([
+            {"name": "id", "type": "string"},
+            {"name": "quantity", "type": "int"},
+            {"name": "status", "type": {"type": "enum", "name": "Status", "symbols": ["PLACED", "PAID"]}},
+            {"name": "note", "type": ["null", "string"], "default": null},
+            {"name": "tags", "type": {"type": "array", "items": "string"}},
+        ]));
+        let json = json_schema(
+            json!({
+                "id": {"type": "string"},
+                "quantity": {"type": "integer", "format": "int32"},
+                "status": {"type": "string", "enum": ["PLACED", "PAID"]},
+                "note": {"type": ["string", "null"]},
+                "tags": {"type": "array", "items": {"type": "string"}},
+            }),
+            json!(["id", "quantity", "status", "tags"]),
+        );
+
+        assert!(check(&json, &avro, CompatibilityMode::Full).is_empty());
+        assert!(check(&avro, &json, CompatibilityMode::Full).is_empty());
+    }
+
+    #[test]
+    fn test_json_requiring_a_field_avro_lacks() {
+        let old = avro(json!([{"name": "id", "type": "string"}]));
+        let new = json_schema(
+            json!({"id": {"type": "string"}, "email": {"type": "string"}}),
+            json!(["id", "email"]),
+        );
+
+        let violations = check(&new, &old, CompatibilityMode::Backward);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].violation_type, ViolationType::RequiredAdded);
+        assert_eq!(violations[0].field_path, "email");
+        let suggestion = violations[0].suggestion.as_ref().unwrap();
+        assert_eq!(suggestion.action, RemediationAction::MarkOptional);
+        assert_eq!(
+            suggestion.message,
+            "Remove 'email' from the required fields of the schema"
+        );
+        assert!(check(&new, &old, CompatibilityMode::Forward).is_empty());
+    }
+
+    #[test]
+    fn test_avro_reader_needs_fields_json_may_omit() {
+        let old = json_schema(
+            json!({"id": {"type": "string"}, "email": {"type": "string"}}),
+            json!(["id"]),
+        );
+        let new = avro(json!([
+            {"name": "id", "type": "string"},
+            {"name": "email", "type": "string"},
+        ]));
+
+        let violations = check(&new, &old, CompatibilityMode::Backward);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].field_path, "email");
+        assert_eq!(
+            violations[0].description,
+            "Field 'email' is required by the new schema but optional in the old schema"
+        );
+        assert_eq!(
+            violations[0].suggestion.as_ref().unwrap().action,
+            RemediationAction::AddDefault
+        );
+    }
+
+    #[test]
+    fn test_narrowed_types_and_enums_break() {
+        let old = json_schema(
+            json!({
+                "quantity": {"type": "integer"},
+                "status": {"enum": ["PLACED", "PAID", "REFUNDED"]},
+                "channel": {"type": "string"},
+            }),
+            json!(["quantity", "status", "channel"]),
+        );
+        let new = avro(json!([
+            {"name": "quantity", "type": "int"},
+            {"name": "status", "type": {"type": "enum", "name": "Status", "symbols": ["PLACED", "PAID"]}},
+            {"name": "channel", "type": {"type": "enum", "name": "Channel", "symbols": ["WEB"]}},
+        ]));
+
+        let violations = check(&new, &old, CompatibilityMode::Backward);
+        let found: Vec<(&str, &ViolationType)> = violations
+            .iter()
+            .map(|v| (v.field_path.as_str(), &v.violation_type))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("quantity", &ViolationType::TypeChanged),
+                ("status", &ViolationType::EnumValueRemoved),
+                ("channel", &ViolationType::ConstraintAdded),
+            ]
+        );
+        assert_eq!(violations[1].old_value, Some(json!(["REFUNDED"])));
+
+        // The wider JSON types read everything Avro writes
+        assert!(check(&new, &old, CompatibilityMode::Forward).is_empty());
+    }
+
+    #[test]
+    fn test_closed_json_object_rejects_extra_avro_fields() {
+        let old = json_document(json!({
+            "type": "object",
+            "properties": {"id": {"type": "string"}},
+            "required": ["id"],
+            "additionalProperties": false,
+        }));
+        let new = avro(json!([
+            {"name": "id", "type": "string"},
+            {"name": "source", "type": "string", "default": "web"},
+        ]));
+
+        let violations = check(&new, &old, CompatibilityMode::Forward);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].violation_type, ViolationType::ConstraintAdded);
+        assert_eq!(violations[0].field_path, "source");
+        assert_eq!(
+            violations[0].suggestion.as_ref().unwrap().action,
+            RemediationAction::RemoveField
+        );
+    }
+
+    #[test]
+    fn test_recursive_avro_record_terminates() {
+        let tree = avro(json!([
+            {"name": "id", "type": "string"},
+            {"name": "parent", "type": ["null", "OrderPlaced"], "default": null},
+        ]));
+        let json = json_schema(json!({"id": {"type": "string"}}), json!(["id"]));
+
+        assert!(check(&json, &tree, CompatibilityMode::Full).is_empty());
+    }
+}
@@ -0,0 +1,440 @@
+//! Compatibility policy exemptions
+//!
+//! An exemption lets an admin knowingly ship one kind of breaking change on a
+//! single subject for a limited time, instead of switching the whole subject
+//! to `NONE` and forgetting to switch it back. Violations covered by an active
+//! exemption are moved from `violations` to `exempted_violations` on the
+//! [`CompatibilityResult`], so they still show up in change history.
+//!
+//! Exemptions are kept in an [`ExemptionStore`]. The server persists them to
+//! PostgreSQL, so every replica applies the same exemptions and they survive
+//! restarts; [`InMemoryExemptionStore`] is provided for tests.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use schema_registry_core::{
+    error::{Error, Result},
+    traits::CompatibilityResult,
+    types::{ViolationSeverity, ViolationType},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+/// Longest time an exemption may stay active
+pub const MAX_EXEMPTION_DAYS: i64 = 90;
+
+/// A time-boxed override for one violation type on one subject
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompatibilityExemption {
+    /// Exemption ID
+    pub id: Uuid,
+    /// Subject the exemption applies to
+    pub subject: String,
+    /// Violation type that is allowed
+    pub violation_type: ViolationType,
+    /// Why the breaking change is being shipped
+    pub reason: String,
+    /// Admin who granted the exemption
+    pub granted_by: String,
+    /// When the exemption was granted
+    pub granted_at: DateTime<Utc>,
+    /// When the exemption stops applying
+    pub expires_at: DateTime<Utc>,
+    /// When the exemption was revoked, if it was
+    pub revoked_at: Option<DateTime<Utc>>,
+    /// Admin who revoked the exemption
+    pub revoked_by: Option<String>,
+}
+
+impl CompatibilityExemption {
+    /// Whether the exemption applies at the given time
+    pub fn is_active_at(&self, now: DateTime<Utc>) -> bool {
+        self.revoked_at.is_none() && now < self.expires_at
+    }
+}
+
+/// Request to grant an exemption
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExemptionRequest {
+    /// Subject the exemption applies to
+    pub subject: String,
+    /// Violation type that is allowed
+    pub violation_type: ViolationType,
+    /// Why the breaking change is being shipped
+    pub reason: String,
+    /// When the exemption stops applying
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Durable storage for exemptions
+#[async_trait]
+pub trait ExemptionStore: Send + Sync {
+    /// Persist a newly granted exemption
+    async fn insert(&self, exemption: &CompatibilityExemption) -> Result<()>;
+
+    /// Mark an exemption revoked, keeping the first revocation
+    ///
+    /// Returns `None` when no exemption has the ID.
+    async fn revoke(
+        &self,
+        id: Uuid,
+        revoked_by: &str,
+        revoked_at: DateTime<Utc>,
+    ) -> Result<Option<CompatibilityExemption>>;
+
+    /// Get an exemption by ID
+    async fn get(&self, id: Uuid) -> Result<Option<CompatibilityExemption>>;
+
+    /// Exemptions in the order they were granted, optionally only those for
+    /// one subject or those active at a given time
+    async fn list(
+        &self,
+        subject: Option<&str>,
+        active_at: Option<DateTime<Utc>>,
+    ) -> Result<Vec<CompatibilityExemption>>;
+}
+
+/// Exemptions held in memory, lost on restart
+#[derive(Default)]
+pub struct InMemoryExemptionStore {
+    exemptions: RwLock<HashMap<Uuid, CompatibilityExemption>>,
+}
+
+impl InMemoryExemptionStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl ExemptionStore for InMemoryExemptionStore {
+    async fn insert(&self, exemption: &CompatibilityExemption) -> Result<()> {
+        self.exemptions
+            .write()
+            .await
+            .insert(exemption.id, exemption.clone());
+        Ok(())
+    }
+
+    async fn revoke(
+        &self,
+        id: Uuid,
+        revoked_by: &str,
+        revoked_at: DateTime<Utc>,
+    ) -> Result<Option<CompatibilityExemption>> {
+        let mut exemptions = self.exemptions.write().await;
+        let Some(exemption) = exemptions.get_mut(&id) else {
+            return Ok(None);
+        };
+        if exemption.revoked_at.is_none() {
+            exemption.revoked_at = Some(revoked_at);
+            exemption.revoked_by = Some(revoked_by.to_string());
+        }
+        Ok(Some(exemption.clone()))
+    }
+
+    async fn get(&self, id: Uuid) -> Result<Option<CompatibilityExemption>> {
+        Ok(self.exemptions.read().await.get(&id).cloned())
+    }
+
+    async fn list(
+        &self,
+        subject: Option<&str>,
+        active_at: Option<DateTime<Utc>>,
+    ) -> Result<Vec<CompatibilityExemption>> {
+        let mut exemptions: Vec<_> = self
+            .exemptions
+            .read()
+            .await
+            .values()
+            .filter(|e| subject.is_none_or(|s| e.subject == s))
+            .filter(|e| active_at.is_none_or(|at| e.is_active_at(at)))
+            .cloned()
+            .collect();
+        exemptions.sort_by_key(|e| e.granted_at);
+        Ok(exemptions)
+    }
+}
+
+/// Grants exemptions and applies them to compatibility results
+pub struct ExemptionRegistry {
+    store: Arc<dyn ExemptionStore>,
+}
+
+impl Default for ExemptionRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ExemptionRegistry {
+    /// Registry backed by an [`InMemoryExemptionStore`]
+    pub fn new() -> Self {
+        Self::with_store(Arc::new(InMemoryExemptionStore::new()))
+    }
+
+    /// Registry backed by the given store
+    pub fn with_store(store: Arc<dyn ExemptionStore>) -> Self {
+        Self { store }
+    }
+
+    /// Grant a new exemption
+    pub async fn grant(
+        &self,
+        request: ExemptionRequest,
+        granted_by: &str,
+    ) -> Result<CompatibilityExemption> {
+        let now = Utc::now();
+
+        if request.reason.trim().is_empty() {
+            return Err(Error::ValidationError(
+                "Exemption reason must not be empty".to_string(),
+            ));
+        }
+        if request.expires_at <= now {
+            return Err(Error::ValidationError(
+                "Exemption expiry must be in the future".to_string(),
+            ));
+        }
+        if request.expires_at > now + Duration::days(MAX_EXEMPTION_DAYS) {
+            return Err(Error::ValidationError(format!(
+                "Exemptions may not last longer than {} days",
+                MAX_EXEMPTION_DAYS
+            )));
+        }
+
+        let exemption = CompatibilityExemption {
+            id: Uuid::new_v4(),
+            subject: request.subject,
+            violation_type: request.violation_type,
+            reason: request.reason,
+            granted_by: granted_by.to_string(),
+            granted_at: now,
+            expires_at: request.expires_at,
+            revoked_at: None,
+            revoked_by: None,
+        };
+
+        tracing::info!(
+            exemption_id = %exemption.id,
+            subject = %exemption.subject,
+            violation_type = ?exemption.violation_type,
+            expires_at = %exemption.expires_at,
+            "Compatibility exemption granted"
+        );
+
+        self.store.insert(&exemption).await?;
+        Ok(exemption)
+    }
+
+    /// Revoke an exemption before it expires
+    ///
+    /// Returns `None` when no exemption has the ID.
+    pub async fn revoke(
+        &self,
+        id: Uuid,
+        revoked_by: &str,
+    ) -> Result<Option<CompatibilityExemption>> {
+        self.store.revoke(id, revoked_by, Utc::now()).await
+    }
+
+    /// Get an exemption by ID
+    pub async fn get(&self, id: Uuid) -> Result<Option<CompatibilityExemption>> {
+        self.store.get(id).await
+    }
+
+    /// List exemptions, optionally including expired and revoked ones
+    pub async fn list(&self, include_inactive: bool) -> Result<Vec<CompatibilityExemption>> {
+        let active_at = (!include_inactive).then(Utc::now);
+        self.store.list(None, active_at).await
+    }
+
+    /// Active exemptions for a subject
+    pub async fn active_for(&self, subject: &str) -> Result<Vec<CompatibilityExemption>> {
+        self.store.list(Some(subject), Some(Utc::now())).await
+    }
+
+    /// Move violations covered by active exemptions out of the blocking set
+    ///
+    /// The result becomes compatible once no remaining violation is
+    /// `min_severity_to_fail` or worse. Returns the IDs of the exemptions
+    /// that were used.
+    pub async fn apply(
+        &self,
+        subject: &str,
+        result: &mut CompatibilityResult,
+        min_severity_to_fail: ViolationSeverity,
+    ) -> Result<Vec<Uuid>> {
+        if result.violations.is_empty() {
+            return Ok(Vec::new());
+        }
+        let active = self.active_for(subject).await?;
+        if active.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut applied = Vec::new();
+        let (exempted, remaining): (Vec<_>, Vec<_>) =
+            result.violations.drain(..).partition(|violation| {
+                match active
+                    .iter()
+                    .find(|e| e.violation_type == violation.violation_type)
+                {
+                    Some(exemption) => {
+                        if !applied.contains(&exemption.id) {
+                            applied.push(exemption.id);
+                        }
+                        true
+                    }
+                    None => false,
+                }
+            });
+
+        result.violations = remaining;
+        result.exempted_violations.extend(exempted);
+        if !crate::fails(&result.violations, min_severity_to_fail) {
+            result.is_compatible = true;
+        }
+
+        if !applied.is_empty() {
+            tracing::warn!(
+                subject = %subject,
+                exemptions = ?applied,
+                "Breaking changes allowed by compatibility exemption"
+            );
+        }
+        Ok(applied)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use schema_registry_core::{traits::CompatibilityViolation, CompatibilityMode};
+
+    fn violation(violation_type: ViolationType) -> CompatibilityViolation {
+        CompatibilityViolation {
+            violation_type,
+            field_path: "$.properties.email".to_string(),
+            old_value: None,
+            new_value: None,
+            severity: ViolationSeverity::Breaking,
+            description: "breaking".to_string(),
+            suggestion: None,
+        }
+    }
+
+    fn request(subject: &str, expires_in: Duration) -> ExemptionRequest {
+        ExemptionRequest {
+            subject: subject.to_string(),
+            violation_type: ViolationType::FieldRemoved,
+            reason: "Field removal coordinated with all consumers".to_string(),
+            expires_at: Utc::now() + expires_in,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_grant_validation() {
+        let registry = ExemptionRegistry::new();
+
+        assert!(registry
+            .grant(request("s", Duration::days(-1)), "admin")
+            .await
+            .is_err());
+        assert!(registry
+            .grant(
+                request("s", Duration::days(MAX_EXEMPTION_DAYS + 1)),
+                "admin"
+            )
+            .await
+            .is_err());
+
+        let mut empty_reason = request("s", Duration::days(1));
+        empty_reason.reason = " ".to_string();
+        assert!(registry.grant(empty_reason, "admin").await.is_err());
+
+        assert!(registry
+            .grant(request("s", Duration::days(7)), "admin")
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_apply_only_matching_violations() {
+        let registry = ExemptionRegistry::new();
+        let exemption = registry
+            .grant(request("com.example.User", Duration::days(7)), "admin")
+            .await
+            .unwrap();
+
+        let mut result = CompatibilityResult {
+            is_compatible: false,
+            mode: CompatibilityMode::Backward,
+            violations: vec![
+                violation(ViolationType::FieldRemoved),
+                violation(ViolationType::TypeChanged),
+            ],
+            exempted_violations: vec![],
+            checked_versions: vec![],
+        };
+
+        let applied = registry
+            .apply("com.example.User", &mut result, ViolationSeverity::Breaking)
+            .await
+            .unwrap();
+        assert_eq!(applied, vec![exemption.id]);
+        assert_eq!(result.violations.len(), 1);
+        assert_eq!(result.exempted_violations.len(), 1);
+        assert!(!result.is_compatible);
+
+        // Other subjects are unaffected
+        let mut other = CompatibilityResult {
+            is_compatible: false,
+            mode: CompatibilityMode::Backward,
+            violations: vec![violation(ViolationType::FieldRemoved)],
+            exempted_violations: vec![],
+            checked_versions: vec![],
+        };
+        assert!(registry
+            .apply("com.example.Order", &mut other, ViolationSeverity::Breaking)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(!other.is_compatible);
+    }
+
+    #[tokio::test]
+    async fn test_revoked_exemption_no_longer_applies() {
+        let registry = ExemptionRegistry::new();
+        let exemption = registry
+            .grant(request("s", Duration::days(7)), "admin")
+            .await
+            .unwrap();
+        let revoked = registry.revoke(exemption.id, "admin").await.unwrap();
+        assert_eq!(revoked.unwrap().revoked_by.as_deref(), Some("admin"));
+        assert!(registry
+            .revoke(Uuid::new_v4(), "admin")
+            .await
+            .unwrap()
+            .is_none());
+
+        let mut result = CompatibilityResult {
+            is_compatible: false,
+            mode: CompatibilityMode::Backward,
+            violations: vec![violation(ViolationType::FieldRemoved)],
+            exempted_violations: vec![],
+            checked_versions: vec![],
+        };
+        assert!(registry
+            .apply("s", &mut result, ViolationSeverity::Breaking)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(!result.is_compatible);
+        assert!(registry.list(false).await.unwrap().is_empty());
+        assert_eq!(registry.list(true).await.unwrap().len(), 1);
+    }
+}
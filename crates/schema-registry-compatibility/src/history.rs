@@ -0,0 +1,349 @@
+//! Compatibility check history
+//!
+//! Every compatibility check is recorded as a [`CompatibilityCheckRecord`]
+//! through a [`CompatibilityHistoryStore`]. Deployments bring their own store
+//! (the server persists to PostgreSQL); [`InMemoryCompatibilityHistory`] is
+//! provided for tests and single-node setups.
+//!
+//! [`CompatibilityHistory`] writes to the store and then hands each record to
+//! its [`CompatibilityCheckObserver`]s, which is how analytics learns about
+//! checks without the store having to know about it.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use schema_registry_core::{
+    error::Result,
+    traits::{CompatibilityResult, CompatibilityViolation},
+    types::CompatibilityMode,
+    versioning::SemanticVersion,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+/// Records returned by a query when no limit is given
+pub const DEFAULT_HISTORY_LIMIT: usize = 100;
+
+/// Most records a single query may return
+pub const MAX_HISTORY_LIMIT: usize = 1000;
+
+// ============================================================================
+// Records and Queries
+// ============================================================================
+
+/// One compatibility check and its outcome
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompatibilityCheckRecord {
+    /// Record ID
+    pub id: Uuid,
+    /// Authenticated principal that asked for the check, `anonymous` without
+    /// credentials
+    pub caller: Option<String>,
+    /// Client ID the caller reported about itself; not verified
+    #[serde(default)]
+    pub client_id: Option<String>,
+    /// Subject of the checked schema
+    pub subject: String,
+    /// Schema being checked
+    pub schema_id: Uuid,
+    pub version: SemanticVersion,
+    /// Schema it was checked against
+    pub compared_schema_id: Uuid,
+    pub compared_version: SemanticVersion,
+    pub mode: CompatibilityMode,
+    pub is_compatible: bool,
+    pub violations: Vec<CompatibilityViolation>,
+    /// Violations allowed by an exemption
+    #[serde(default)]
+    pub exempted_violations: Vec<CompatibilityViolation>,
+    /// Time taken by the check
+    pub latency_ms: u64,
+    pub checked_at: DateTime<Utc>,
+}
+
+impl CompatibilityCheckRecord {
+    /// Record the outcome of checking `schema_id` against `compared_schema_id`
+    pub fn new(
+        subject: impl Into<String>,
+        (schema_id, version): (Uuid, SemanticVersion),
+        (compared_schema_id, compared_version): (Uuid, SemanticVersion),
+        result: &CompatibilityResult,
+        latency: Duration,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            caller: None,
+            client_id: None,
+            subject: subject.into(),
+            schema_id,
+            version,
+            compared_schema_id,
+            compared_version,
+            mode: result.mode,
+            is_compatible: result.is_compatible,
+            violations: result.violations.clone(),
+            exempted_violations: result.exempted_violations.clone(),
+            latency_ms: latency.as_millis() as u64,
+            checked_at: Utc::now(),
+        }
+    }
+
+    /// Set the caller
+    pub fn with_caller(mut self, caller: impl Into<String>) -> Self {
+        self.caller = Some(caller.into());
+        self
+    }
+
+    /// Set the client ID the caller reported
+    pub fn with_client_id(mut self, client_id: impl Into<String>) -> Self {
+        self.client_id = Some(client_id.into());
+        self
+    }
+}
+
+/// Filter for [`CompatibilityHistoryStore::query`]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CompatibilityHistoryQuery {
+    #[serde(default)]
+    pub subject: Option<String>,
+    #[serde(default)]
+    pub caller: Option<String>,
+    #[serde(default)]
+    pub is_compatible: Option<bool>,
+    /// Only checks at or after this time
+    #[serde(default)]
+    pub since: Option<DateTime<Utc>>,
+    /// Only checks before this time
+    #[serde(default)]
+    pub until: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+impl CompatibilityHistoryQuery {
+    /// Whether a record passes every filter
+    pub fn matches(&self, record: &CompatibilityCheckRecord) -> bool {
+        self.subject.as_ref().is_none_or(|s| &record.subject == s)
+            && self
+                .caller
+                .as_ref()
+                .is_none_or(|c| record.caller.as_ref() == Some(c))
+            && self.is_compatible.is_none_or(|c| record.is_compatible == c)
+            && self.since.is_none_or(|t| record.checked_at >= t)
+            && self.until.is_none_or(|t| record.checked_at < t)
+    }
+
+    /// Requested limit, capped at [`MAX_HISTORY_LIMIT`]
+    pub fn effective_limit(&self) -> usize {
+        self.limit
+            .unwrap_or(DEFAULT_HISTORY_LIMIT)
+            .min(MAX_HISTORY_LIMIT)
+    }
+}
+
+// ============================================================================
+// Stores and Observers
+// ============================================================================
+
+/// Durable storage for compatibility check history
+#[async_trait]
+pub trait CompatibilityHistoryStore: Send + Sync {
+    /// Persist a record
+    async fn record(&self, record: &CompatibilityCheckRecord) -> Result<()>;
+
+    /// Matching records, newest first
+    async fn query(
+        &self,
+        query: &CompatibilityHistoryQuery,
+    ) -> Result<Vec<CompatibilityCheckRecord>>;
+}
+
+/// Notified after a check has been persisted
+#[async_trait]
+pub trait CompatibilityCheckObserver: Send + Sync {
+    async fn on_check(&self, record: &CompatibilityCheckRecord);
+}
+
+/// Bounded in-memory history, dropping the oldest records when full
+pub struct InMemoryCompatibilityHistory {
+    records: RwLock<VecDeque<CompatibilityCheckRecord>>,
+    capacity: usize,
+}
+
+impl InMemoryCompatibilityHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            records: RwLock::new(VecDeque::new()),
+            capacity,
+        }
+    }
+}
+
+impl Default for InMemoryCompatibilityHistory {
+    fn default() -> Self {
+        Self::new(10_000)
+    }
+}
+
+#[async_trait]
+impl CompatibilityHistoryStore for InMemoryCompatibilityHistory {
+    async fn record(&self, record: &CompatibilityCheckRecord) -> Result<()> {
+        let mut records = self.records.write().await;
+        if records.len() >= self.capacity {
+            records.pop_front();
+        }
+        records.push_back(record.clone());
+        Ok(())
+    }
+
+    async fn query(
+        &self,
+        query: &CompatibilityHistoryQuery,
+    ) -> Result<Vec<CompatibilityCheckRecord>> {
+        Ok(self
+            .records
+            .read()
+            .await
+            .iter()
+            .rev()
+            .filter(|r| query.matches(r))
+            .take(query.effective_limit())
+            .cloned()
+            .collect())
+    }
+}
+
+/// Records checks in a store and notifies observers
+pub struct CompatibilityHistory {
+    store: Arc<dyn CompatibilityHistoryStore>,
+    observers: Vec<Arc<dyn CompatibilityCheckObserver>>,
+}
+
+impl CompatibilityHistory {
+    pub fn new(store: Arc<dyn CompatibilityHistoryStore>) -> Self {
+        Self {
+            store,
+            observers: Vec::new(),
+        }
+    }
+
+    /// Add an observer notified of every persisted check
+    pub fn with_observer(mut self, observer: Arc<dyn CompatibilityCheckObserver>) -> Self {
+        self.observers.push(observer);
+        self
+    }
+
+    /// Persist a check, then notify observers
+    ///
+    /// Observers are not notified when the store rejects the record.
+    pub async fn record(&self, record: CompatibilityCheckRecord) -> Result<()> {
+        self.store.record(&record).await?;
+        for observer in &self.observers {
+            observer.on_check(&record).await;
+        }
+        Ok(())
+    }
+
+    pub async fn query(
+        &self,
+        query: &CompatibilityHistoryQuery,
+    ) -> Result<Vec<CompatibilityCheckRecord>> {
+        self.store.query(query).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn record(subject: &str, caller: &str, is_compatible: bool) -> CompatibilityCheckRecord {
+        let result = CompatibilityResult {
+            is_compatible,
+            mode: CompatibilityMode::Backward,
+            violations: vec![],
+            exempted_violations: vec![],
+            checked_versions: vec![],
+        };
+        CompatibilityCheckRecord::new(
+            subject,
+            (Uuid::new_v4(), SemanticVersion::new(2, 0, 0)),
+            (Uuid::new_v4(), SemanticVersion::new(1, 0, 0)),
+            &result,
+            Duration::from_millis(7),
+        )
+        .with_caller(caller)
+    }
+
+    #[derive(Default)]
+    struct CountingObserver(AtomicUsize);
+
+    #[async_trait]
+    impl CompatibilityCheckObserver for CountingObserver {
+        async fn on_check(&self, _record: &CompatibilityCheckRecord) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_record_and_query() {
+        let observer = Arc::new(CountingObserver::default());
+        let history = CompatibilityHistory::new(Arc::new(InMemoryCompatibilityHistory::default()))
+            .with_observer(observer.clone());
+
+        history
+            .record(record("com.a.User", "ci", true))
+            .await
+            .unwrap();
+        history
+            .record(record("com.a.User", "alice", false))
+            .await
+            .unwrap();
+        history
+            .record(record("com.a.Order", "ci", true))
+            .await
+            .unwrap();
+        assert_eq!(observer.0.load(Ordering::SeqCst), 3);
+
+        let all = history
+            .query(&CompatibilityHistoryQuery::default())
+            .await
+            .unwrap();
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[0].subject, "com.a.Order");
+        assert_eq!(all[0].latency_ms, 7);
+
+        let query = CompatibilityHistoryQuery {
+            subject: Some("com.a.User".to_string()),
+            is_compatible: Some(false),
+            ..Default::default()
+        };
+        let failed = history.query(&query).await.unwrap();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].caller.as_deref(), Some("alice"));
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_capacity() {
+        let store = InMemoryCompatibilityHistory::new(2);
+        for subject in ["a", "b", "c"] {
+            store.record(&record(subject, "ci", true)).await.unwrap();
+        }
+
+        let query = CompatibilityHistoryQuery {
+            limit: Some(10),
+            ..Default::default()
+        };
+        let subjects: Vec<_> = store
+            .query(&query)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|r| r.subject)
+            .collect();
+        assert_eq!(subjects, vec!["c", "b"]);
+    }
+}
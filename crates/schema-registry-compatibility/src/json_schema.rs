@@ -0,0 +1,677 @@
+//! Structural diffing of JSON Schemas
+//!
+//! A reader schema is compatible with a writer schema when it accepts every
+//! document the writer accepts. [`narrowings`] walks both schemas side by side
+//! and reports each place where the reader accepts less: fields it newly
+//! requires, types it dropped, enum values it removed and constraints it
+//! tightened. Backward compatibility reads old data with the new schema, so the
+//! new schema is the reader; forward compatibility swaps the two.
+//!
+//! `$ref`, `allOf`, `anyOf`, `oneOf` and `not` are compared only by the
+//! keywords beside them, not resolved.
+
+use crate::trace::Tracer;
+use crate::Direction;
+use schema_registry_core::{
+    traits::CompatibilityViolation,
+    types::{RemediationAction, ViolationType},
+};
+use serde_json::{Map, Value};
+
+/// Keywords giving a lower bound; a reader raising one rejects writer data
+const LOWER_BOUNDS: &[&str] = &[
+    "minimum",
+    "exclusiveMinimum",
+    "minLength",
+    "minItems",
+    "minProperties",
+];
+
+/// Keywords giving an upper bound; a reader lowering one rejects writer data
+const UPPER_BOUNDS: &[&str] = &[
+    "maximum",
+    "exclusiveMaximum",
+    "maxLength",
+    "maxItems",
+    "maxProperties",
+];
+
+/// Keywords that restrict values in ways that can't be ordered
+const EXACT_CONSTRAINTS: &[&str] = &["pattern", "format", "multipleOf"];
+
+/// Places where `reader` rejects documents that `writer` accepts
+///
+/// Content that isn't a JSON object on either side is not compared.
+pub(crate) fn narrowings(
+    reader: &Value,
+    writer: &Value,
+    direction: Direction,
+    tracer: &mut Tracer,
+) -> Vec<CompatibilityViolation> {
+    let mut diff = Diff {
+        direction,
+        tracer,
+        violations: Vec::new(),
+    };
+    if let (Value::Object(reader), Value::Object(writer)) = (reader, writer) {
+        diff.compare(reader, writer, "");
+    }
+    diff.violations
+}
+
+struct Diff<'t> {
+    direction: Direction,
+    tracer: &'t mut Tracer,
+    violations: Vec<CompatibilityViolation>,
+}
+
+impl Diff<'_> {
+    fn compare(&mut self, reader: &Map<String, Value>, writer: &Map<String, Value>, path: &str) {
+        self.check("json_schema.type", path, "accepts every type", |diff| {
+            diff.compare_types(reader, writer, path)
+        });
+        self.check("json_schema.enum", path, "allows every value", |diff| {
+            diff.compare_enums(reader, writer, path)
+        });
+        self.check(
+            "json_schema.constraint",
+            path,
+            "has no stricter bound or constraint",
+            |diff| diff.compare_constraints(reader, writer, path),
+        );
+        self.check(
+            "json_schema.required",
+            path,
+            "requires no field that may be missing",
+            |diff| diff.compare_required(reader, writer, path),
+        );
+        self.check(
+            "json_schema.closed_properties",
+            path,
+            "allows every field",
+            |diff| diff.compare_closed_properties(reader, writer, path),
+        );
+
+        for (name, reader_property, writer_property) in shared_properties(reader, writer) {
+            self.compare(reader_property, writer_property, &join(path, name));
+        }
+        if let (Some(Value::Object(reader_items)), Some(Value::Object(writer_items))) =
+            (reader.get("items"), writer.get("items"))
+        {
+            self.compare(reader_items, writer_items, &format!("{}[]", path));
+        }
+    }
+
+    /// Evaluate `rule` at `path`, tracing each violation `compare` reports,
+    /// or that the reader `passes` when there are none
+    fn check(&mut self, rule: &str, path: &str, passes: &str, compare: impl FnOnce(&mut Self)) {
+        let reported = self.violations.len();
+        compare(self);
+        let direction = Some(self.direction);
+        if self.violations.len() == reported {
+            let reader = self.direction.reader;
+            self.tracer.passed(rule, direction, path, || {
+                format!("The {} schema {} here", reader, passes)
+            });
+            return;
+        }
+        for violation in &self.violations[reported..] {
+            self.tracer
+                .failed(rule, direction, &violation.field_path, || {
+                    violation.description.clone()
+                });
+        }
+    }
+
+    fn compare_types(
+        &mut self,
+        reader: &Map<String, Value>,
+        writer: &Map<String, Value>,
+        path: &str,
+    ) {
+        let Some(reader_types) = types(reader) else {
+            return;
+        };
+        // A writer without a type accepts every type
+        let writer_types = types(writer).unwrap_or_else(|| {
+            ["array", "boolean", "null", "number", "object", "string"]
+                .map(str::to_string)
+                .to_vec()
+        });
+        // Every integer is also a number
+        let accepts = |t: &String| {
+            reader_types.contains(t)
+                || (t.as_str() == "integer" && reader_types.iter().any(|r| r == "number"))
+        };
+        let dropped: Vec<&String> = writer_types.iter().filter(|t| !accepts(t)).collect();
+        if dropped.is_empty() {
+            return;
+        }
+        let dropped = dropped
+            .iter()
+            .map(|t| t.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let suggestion = if self.direction.reader_is_new {
+            format!("Let {} accept {} again", place(path), dropped)
+        } else {
+            format!(
+                "Stop {} accepting {}, which the old schema rejects",
+                place(path),
+                dropped
+            )
+        };
+        self.push(
+            ViolationType::TypeChanged,
+            path,
+            writer.get("type"),
+            reader.get("type"),
+            format!(
+                "{} accepts {} in the {} schema but not the {} schema",
+                describe(path),
+                dropped,
+                self.direction.writer,
+                self.direction.reader
+            ),
+            (RemediationAction::RestoreType, suggestion),
+        );
+    }
+
+    fn compare_enums(
+        &mut self,
+        reader: &Map<String, Value>,
+        writer: &Map<String, Value>,
+        path: &str,
+    ) {
+        let Some(reader_values) = allowed_values(reader) else {
+            return;
+        };
+        let Some(writer_values) = allowed_values(writer) else {
+            let suggestion = if self.direction.reader_is_new {
+                format!(
+                    "Drop the fixed values of {}, which the old schema didn't have",
+                    place(path)
+                )
+            } else {
+                format!("Keep {} limited to the old schema's values", place(path))
+            };
+            self.push(
+                ViolationType::ConstraintAdded,
+                path,
+                None,
+                reader.get("enum").or_else(|| reader.get("const")),
+                format!(
+                    "{} is limited to fixed values in the {} schema but not the {} schema",
+                    describe(path),
+                    self.direction.reader,
+                    self.direction.writer
+                ),
+                (RemediationAction::RestoreConstraint, suggestion),
+            );
+            return;
+        };
+        let removed: Vec<Value> = writer_values
+            .into_iter()
+            .filter(|value| !reader_values.contains(value))
+            .collect();
+        if removed.is_empty() {
+            return;
+        }
+        let listed = removed
+            .iter()
+            .map(Value::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        let suggestion = if self.direction.reader_is_new {
+            format!("Allow {} in {} again", listed, place(path))
+        } else {
+            format!(
+                "Remove {} from {}, which the old schema rejects",
+                listed,
+                place(path)
+            )
+        };
+        self.push(
+            ViolationType::EnumValueRemoved,
+            path,
+            Some(&Value::Array(removed)),
+            None,
+            format!(
+                "{} allows {} in the {} schema but not the {} schema",
+                describe(path),
+                listed,
+                self.direction.writer,
+                self.direction.reader
+            ),
+            (RemediationAction::RestoreEnumValues, suggestion),
+        );
+    }
+
+    fn compare_constraints(
+        &mut self,
+        reader: &Map<String, Value>,
+        writer: &Map<String, Value>,
+        path: &str,
+    ) {
+        for &keyword in LOWER_BOUNDS.iter().chain(UPPER_BOUNDS) {
+            let Some(reader_bound) = reader.get(keyword).and_then(Value::as_f64) else {
+                continue;
+            };
+            let tightened = match writer.get(keyword).and_then(Value::as_f64) {
+                None => true,
+                Some(writer_bound) if LOWER_BOUNDS.contains(&keyword) => {
+                    reader_bound > writer_bound
+                }
+                Some(writer_bound) => reader_bound < writer_bound,
+            };
+            if tightened {
+                self.push_constraint(keyword, reader, writer, path);
+            }
+        }
+
+        for &keyword in EXACT_CONSTRAINTS {
+            if reader
+                .get(keyword)
+                .is_some_and(|value| writer.get(keyword) != Some(value))
+            {
+                self.push_constraint(keyword, reader, writer, path);
+            }
+        }
+
+        if reader.get("uniqueItems") == Some(&Value::Bool(true))
+            && writer.get("uniqueItems") != Some(&Value::Bool(true))
+        {
+            self.push_constraint("uniqueItems", reader, writer, path);
+        }
+        if reader.get("additionalProperties") == Some(&Value::Bool(false))
+            && writer.get("additionalProperties") != Some(&Value::Bool(false))
+        {
+            self.push_constraint("additionalProperties", reader, writer, path);
+        }
+    }
+
+    fn push_constraint(
+        &mut self,
+        keyword: &str,
+        reader: &Map<String, Value>,
+        writer: &Map<String, Value>,
+        path: &str,
+    ) {
+        let description = match writer.get(keyword) {
+            Some(_) => format!(
+                "{} has a stricter '{}' in the {} schema than in the {} schema",
+                describe(path),
+                keyword,
+                self.direction.reader,
+                self.direction.writer
+            ),
+            None => format!(
+                "{} gained '{}' in the {} schema, which the {} schema doesn't have",
+                describe(path),
+                keyword,
+                self.direction.reader,
+                self.direction.writer
+            ),
+        };
+        let old_value = if self.direction.reader_is_new {
+            writer.get(keyword)
+        } else {
+            reader.get(keyword)
+        };
+        let suggestion = match old_value {
+            Some(value) => format!("Set '{}' of {} back to {}", keyword, place(path), value),
+            None => format!("Remove '{}' from {}", keyword, place(path)),
+        };
+        self.push(
+            ViolationType::ConstraintAdded,
+            &join(path, keyword),
+            writer.get(keyword),
+            reader.get(keyword),
+            description,
+            (RemediationAction::RestoreConstraint, suggestion),
+        );
+    }
+
+    fn compare_required(
+        &mut self,
+        reader: &Map<String, Value>,
+        writer: &Map<String, Value>,
+        path: &str,
+    ) {
+        let writer_required = required(writer);
+        for field in required(reader) {
+            if writer_required.contains(&field) {
+                continue;
+            }
+            let field_path = join(path, field);
+            let description = format!(
+                "Field '{}' is required by the {} schema but optional in the {} schema",
+                field_path, self.direction.reader, self.direction.writer
+            );
+            let suggestion = if self.direction.reader_is_new {
+                (
+                    RemediationAction::MarkOptional,
+                    format!(
+                        "Remove '{}' from the required fields of {}",
+                        field,
+                        place(path)
+                    ),
+                )
+            } else {
+                (
+                    RemediationAction::KeepRequired,
+                    format!(
+                        "Keep field '{}' required, since the old schema requires it",
+                        field_path
+                    ),
+                )
+            };
+            self.push(
+                ViolationType::RequiredAdded,
+                &field_path,
+                None,
+                None,
+                description,
+                suggestion,
+            );
+        }
+    }
+
+    /// Dropping a field only matters to readers that allow no others
+    fn compare_closed_properties(
+        &mut self,
+        reader: &Map<String, Value>,
+        writer: &Map<String, Value>,
+        path: &str,
+    ) {
+        if reader.get("additionalProperties") != Some(&Value::Bool(false)) {
+            return;
+        }
+        let reader_properties = reader.get("properties").and_then(Value::as_object);
+        for (name, _) in properties(writer) {
+            if reader_properties.is_some_and(|properties| properties.contains_key(name)) {
+                continue;
+            }
+            let field_path = join(path, name);
+            let description = format!(
+                "Field '{}' of the {} schema isn't allowed by the {} schema, which permits no additional properties",
+                field_path, self.direction.writer, self.direction.reader
+            );
+            let (violation_type, suggestion) = if self.direction.reader_is_new {
+                (
+                    ViolationType::FieldRemoved,
+                    (
+                        RemediationAction::RestoreField,
+                        format!(
+                            "Restore field '{}' or allow additional properties",
+                            field_path
+                        ),
+                    ),
+                )
+            } else {
+                (
+                    ViolationType::ConstraintAdded,
+                    (
+                        RemediationAction::RemoveField,
+                        format!(
+                            "Remove field '{}', since the old schema permits no additional properties",
+                            field_path
+                        ),
+                    ),
+                )
+            };
+            self.push(
+                violation_type,
+                &field_path,
+                None,
+                None,
+                description,
+                suggestion,
+            );
+        }
+    }
+
+    fn push(
+        &mut self,
+        violation_type: ViolationType,
+        path: &str,
+        writer_value: Option<&Value>,
+        reader_value: Option<&Value>,
+        description: String,
+        (action, suggestion): (RemediationAction, String),
+    ) {
+        let violation = self.direction.violation(
+            violation_type,
+            path,
+            writer_value,
+            reader_value,
+            description,
+        );
+        self.violations
+            .push(violation.with_suggestion(action, suggestion));
+    }
+}
+
+/// Declared types, with `integer` kept distinct from `number`
+fn types(schema: &Map<String, Value>) -> Option<Vec<String>> {
+    match schema.get("type")? {
+        Value::String(name) => Some(vec![name.clone()]),
+        Value::Array(names) => Some(
+            names
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect(),
+        ),
+        _ => None,
+    }
+}
+
+/// Declared properties by name
+fn properties(schema: &Map<String, Value>) -> impl Iterator<Item = (&String, &Value)> {
+    schema
+        .get("properties")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+}
+
+/// Properties declared as schemas on both sides, in the writer's order
+fn shared_properties<'a>(
+    reader: &'a Map<String, Value>,
+    writer: &'a Map<String, Value>,
+) -> impl Iterator<Item = (&'a str, &'a Map<String, Value>, &'a Map<String, Value>)> {
+    properties(writer).filter_map(|(name, writer_property)| {
+        let reader_property = reader.get("properties").and_then(|p| p.get(name));
+        match (reader_property, writer_property) {
+            (Some(Value::Object(reader_property)), Value::Object(writer_property)) => {
+                Some((name.as_str(), reader_property, writer_property))
+            }
+            _ => None,
+        }
+    })
+}
+
+/// Values allowed by `enum` or `const`, if either restricts them
+fn allowed_values(schema: &Map<String, Value>) -> Option<Vec<Value>> {
+    match (schema.get("enum"), schema.get("const")) {
+        (Some(Value::Array(values)), _) => Some(values.clone()),
+        (_, Some(value)) => Some(vec![value.clone()]),
+        _ => None,
+    }
+}
+
+fn required(schema: &Map<String, Value>) -> Vec<&str> {
+    schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|fields| fields.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default()
+}
+
+fn join(prefix: &str, name: &str) -> String {
+    if prefix.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", prefix, name)
+    }
+}
+
+fn describe(path: &str) -> String {
+    if path.is_empty() {
+        "The schema".to_string()
+    } else {
+        format!("Field '{}'", path)
+    }
+}
+
+/// `describe` for the middle of a sentence
+fn place(path: &str) -> String {
+    if path.is_empty() {
+        "the schema".to_string()
+    } else {
+        format!("field '{}'", path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn backward(old: Value, new: Value) -> Vec<CompatibilityViolation> {
+        narrowings(&new, &old, Direction::BACKWARD, &mut Tracer::off())
+    }
+
+    #[test]
+    fn test_adding_a_required_field() {
+        let old =
+            json!({"type": "object", "properties": {"id": {"type": "string"}}, "required": ["id"]});
+        let new = json!({
+            "type": "object",
+            "properties": {"id": {"type": "string"}, "email": {"type": "string"}},
+            "required": ["id", "email"],
+        });
+
+        let violations = backward(old.clone(), new.clone());
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].violation_type, ViolationType::RequiredAdded);
+        assert_eq!(violations[0].field_path, "email");
+        assert!(violations[0]
+            .description
+            .contains("required by the new schema"));
+
+        // Old readers don't mind the new field
+        assert!(narrowings(&old, &new, Direction::FORWARD, &mut Tracer::off()).is_empty());
+    }
+
+    #[test]
+    fn test_suggestions_fix_the_new_schema() {
+        let old = json!({"properties": {"name": {"type": "string", "maxLength": 100}}, "required": ["id"]});
+        let new = json!({"properties": {"name": {"type": "string", "maxLength": 50}}, "required": ["email"]});
+
+        let suggestions: Vec<_> = backward(old.clone(), new.clone())
+            .into_iter()
+            .filter_map(|v| v.suggestion)
+            .collect();
+        assert_eq!(suggestions.len(), 2);
+        assert_eq!(suggestions[0].action, RemediationAction::MarkOptional);
+        assert_eq!(
+            suggestions[0].message,
+            "Remove 'email' from the required fields of the schema"
+        );
+        assert_eq!(suggestions[1].action, RemediationAction::RestoreConstraint);
+        assert_eq!(
+            suggestions[1].message,
+            "Set 'maxLength' of field 'name' back to 100"
+        );
+
+        // Dropping `id` from `required` is fixed by requiring it again
+        let forward = narrowings(&old, &new, Direction::FORWARD, &mut Tracer::off());
+        let suggestion = forward[0].suggestion.as_ref().unwrap();
+        assert_eq!(suggestion.action, RemediationAction::KeepRequired);
+        assert!(suggestion.message.contains("'id'"));
+    }
+
+    #[test]
+    fn test_narrowing_a_type() {
+        let old = json!({"properties": {"amount": {"type": ["number", "string"]}, "count": {"type": "integer"}}});
+        let new =
+            json!({"properties": {"amount": {"type": "number"}, "count": {"type": "number"}}});
+
+        let violations = backward(old.clone(), new.clone());
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].violation_type, ViolationType::TypeChanged);
+        assert_eq!(violations[0].field_path, "amount");
+        assert_eq!(violations[0].old_value, Some(json!(["number", "string"])));
+        assert_eq!(violations[0].new_value, Some(json!("number")));
+
+        // Widening `integer` to `number` breaks old readers instead
+        let forward = narrowings(&old, &new, Direction::FORWARD, &mut Tracer::off());
+        assert_eq!(forward.len(), 1);
+        assert_eq!(forward[0].field_path, "count");
+        assert_eq!(forward[0].old_value, Some(json!("integer")));
+    }
+
+    #[test]
+    fn test_removing_enum_values() {
+        let old = json!({"properties": {"status": {"enum": ["ACTIVE", "PAUSED", "DELETED"]}}});
+        let new = json!({"properties": {"status": {"enum": ["ACTIVE", "DELETED", "ARCHIVED"]}}});
+
+        let violations = backward(old.clone(), new.clone());
+        assert_eq!(violations.len(), 1);
+        assert_eq!(
+            violations[0].violation_type,
+            ViolationType::EnumValueRemoved
+        );
+        assert_eq!(violations[0].old_value, Some(json!(["PAUSED"])));
+
+        let forward = narrowings(&old, &new, Direction::FORWARD, &mut Tracer::off());
+        assert_eq!(forward[0].violation_type, ViolationType::EnumValueRemoved);
+        assert!(forward[0].description.contains("\"ARCHIVED\""));
+    }
+
+    #[test]
+    fn test_tightening_constraints() {
+        let old = json!({"properties": {
+            "name": {"type": "string", "maxLength": 100},
+            "tags": {"type": "array", "items": {"type": "string", "minLength": 1}},
+        }});
+        let new = json!({
+            "additionalProperties": false,
+            "properties": {
+                "name": {"type": "string", "maxLength": 50, "pattern": "^[a-z]+$"},
+                "tags": {"type": "array", "items": {"type": "string", "minLength": 1}, "uniqueItems": true},
+            },
+        });
+
+        let paths: Vec<String> = backward(old.clone(), new.clone())
+            .into_iter()
+            .inspect(|v| assert_eq!(v.violation_type, ViolationType::ConstraintAdded))
+            .map(|v| v.field_path)
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                "additionalProperties",
+                "name.maxLength",
+                "name.pattern",
+                "tags.uniqueItems"
+            ]
+        );
+
+        // Loosening is fine in the other direction
+        assert!(narrowings(&old, &new, Direction::FORWARD, &mut Tracer::off()).is_empty());
+    }
+
+    #[test]
+    fn test_closed_reader_rejects_dropped_fields() {
+        let old = json!({"properties": {"id": {"type": "string"}, "legacy": {"type": "string"}}});
+        let new = json!({"properties": {"id": {"type": "string"}}, "additionalProperties": false});
+
+        let violations = backward(old, new);
+        assert!(violations
+            .iter()
+            .any(|v| v.violation_type == ViolationType::FieldRemoved && v.field_path == "legacy"));
+    }
+}
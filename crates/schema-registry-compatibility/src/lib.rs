@@ -4,19 +4,707 @@
 
 use async_trait::async_trait;
 use schema_registry_core::{
-    error::Result,
+    bounded_json::{self, JsonLimits},
+    config_manager_adapter::{CompatibilityPolicy, RuleLevel},
+    deadline,
+    deprecation::{self, FieldDeprecation, DEFAULT_NOTICE_MINOR_VERSIONS},
+    error::{Error, Result},
+    messages::violation_code,
     schema::RegisteredSchema,
-    traits::{CompatibilityChecker, CompatibilityResult},
-    types::CompatibilityMode,
+    traits::{CompatibilityChecker, CompatibilityResult, CompatibilityViolation},
+    types::{
+        CompatibilityMode, RemediationAction, SerializationFormat, ViolationSeverity, ViolationType,
+    },
+    versioning::SemanticVersion,
 };
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::task::JoinSet;
+
+mod avro;
+pub mod cache;
+mod cross_format;
+pub mod exemptions;
+pub mod history;
+mod json_schema;
+pub mod reverification;
+pub mod trace;
+
+use cache::Finding;
+pub use cache::{CacheStats, CompatibilityCache, DEFAULT_CACHE_CAPACITY};
+pub use exemptions::{
+    CompatibilityExemption, ExemptionRegistry, ExemptionRequest, ExemptionStore,
+    InMemoryExemptionStore,
+};
+pub use history::{
+    CompatibilityCheckObserver, CompatibilityCheckRecord, CompatibilityHistory,
+    CompatibilityHistoryQuery, CompatibilityHistoryStore, InMemoryCompatibilityHistory,
+};
+pub use reverification::{
+    BreakageReportSink, ConsumerManifest, ConsumerManifestStore, NamespaceBreakageReport,
+    PinnedDependency, ReverificationJob, SubjectVersions,
+};
+use trace::Tracer;
+pub use trace::{CompatibilityTrace, RuleOutcome, TraceDirection, TraceStep, VersionTrace};
+
+/// Previous versions a transitive check diffs at once by default
+pub const DEFAULT_TRANSITIVE_CONCURRENCY: usize = 8;
 
 /// Compatibility checker
-pub struct CompatibilityCheckerImpl {}
+pub struct CompatibilityCheckerImpl {
+    limits: JsonLimits,
+    deprecation_notice: u32,
+    /// `None` when every pair is diffed afresh
+    cache: Option<CompatibilityCache>,
+    /// Whether Avro and JSON Schema versions are compared with each other
+    cross_format: bool,
+    policy: CompatibilityPolicy,
+    /// Most previous versions a transitive check diffs at once
+    transitive_concurrency: usize,
+    /// Least severe violation that makes schemas incompatible
+    min_severity_to_fail: ViolationSeverity,
+}
 
 impl CompatibilityCheckerImpl {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            limits: JsonLimits::default(),
+            deprecation_notice: DEFAULT_NOTICE_MINOR_VERSIONS,
+            cache: Some(CompatibilityCache::new(DEFAULT_CACHE_CAPACITY)),
+            cross_format: false,
+            policy: CompatibilityPolicy::default(),
+            transitive_concurrency: DEFAULT_TRANSITIVE_CONCURRENCY,
+            min_severity_to_fail: ViolationSeverity::Breaking,
+        }
+    }
+
+    /// Keep the findings of up to `capacity` schema pairs
+    pub fn with_cache_capacity(mut self, capacity: u64) -> Self {
+        self.cache = Some(CompatibilityCache::new(capacity));
+        self
+    }
+
+    /// Diff every pair afresh, e.g. to measure the diff itself
+    pub fn without_cache(mut self) -> Self {
+        self.cache = None;
+        self
+    }
+
+    /// Hit and miss counts of the pair cache, `None` when it is disabled
+    pub fn cache_stats(&self) -> Option<CacheStats> {
+        self.cache.as_ref().map(CompatibilityCache::stats)
+    }
+
+    /// Set how many minor versions a field must stay deprecated before
+    /// removing it is not a breaking change
+    pub fn with_deprecation_notice(mut self, minor_versions: u32) -> Self {
+        self.deprecation_notice = minor_versions;
+        self
+    }
+
+    /// Compare Avro and JSON Schema versions of a subject field by field,
+    /// instead of treating versions of different formats as compatible
+    pub fn with_cross_format(mut self) -> Self {
+        self.cross_format = true;
+        self
+    }
+
+    pub fn cross_format(&self) -> bool {
+        self.cross_format
+    }
+
+    /// Disable or downgrade rules per namespace, e.g. as loaded by
+    /// `ConfigConsumerExt::load_compatibility_policy`
+    ///
+    /// Downgraded violations are still reported, with the severity of their
+    /// level, but only those at [`min_severity_to_fail`](Self::with_min_severity_to_fail)
+    /// or worse make schemas incompatible.
+    pub fn with_policy(mut self, policy: CompatibilityPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub fn policy(&self) -> &CompatibilityPolicy {
+        &self.policy
+    }
+
+    /// Diff up to `concurrency` previous versions at once in transitive
+    /// checks; 1 diffs them one after another
+    pub fn with_transitive_concurrency(mut self, concurrency: usize) -> Self {
+        self.transitive_concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Make violations of `severity` or worse fail checks; by default only
+    /// breaking ones do
+    ///
+    /// Less severe violations are still reported, but leave
+    /// `is_compatible` true.
+    pub fn with_min_severity_to_fail(mut self, severity: ViolationSeverity) -> Self {
+        self.min_severity_to_fail = severity;
+        self
+    }
+
+    pub fn min_severity_to_fail(&self) -> ViolationSeverity {
+        self.min_severity_to_fail
+    }
+
+    /// Set the limits enforced before JSON-based schemas are parsed
+    pub fn with_limits(mut self, limits: JsonLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    fn check_limits(&self, schema: &RegisteredSchema) -> Result<()> {
+        if matches!(schema.format, SerializationFormat::JsonSchema | SerializationFormat::Avro) {
+            bounded_json::check(schema.content.as_bytes(), &self.limits)?;
+        }
+        Ok(())
+    }
+
+    async fn check_against(
+        &self,
+        new_schema: &RegisteredSchema,
+        old_schema: &RegisteredSchema,
+        mode: CompatibilityMode,
+        deprecations: &HashMap<String, FieldDeprecation>,
+    ) -> Result<CompatibilityResult> {
+        let findings = match self.known_findings(new_schema, old_schema, mode).await {
+            Some(findings) => findings,
+            None => {
+                self.check_limits(new_schema)?;
+                self.check_limits(old_schema)?;
+                let findings = Arc::new(diff_pair(
+                    new_schema,
+                    old_schema,
+                    mode,
+                    self.cross_format,
+                    &mut Tracer::off(),
+                ));
+                self.remember(new_schema, old_schema, mode, &findings).await;
+                findings
+            }
+        };
+        Ok(self.result_from(
+            new_schema,
+            old_schema,
+            mode,
+            &findings,
+            deprecations,
+            &mut Tracer::off(),
+        ))
+    }
+
+    /// Check `new_schema` against `previous_versions` like
+    /// [`CompatibilityChecker::check_transitive_compatibility`], tracing
+    /// every rule evaluated
+    ///
+    /// Every pair is diffed, bypassing the pair cache, and one version at a
+    /// time.
+    pub async fn explain(
+        &self,
+        new_schema: &RegisteredSchema,
+        previous_versions: &[RegisteredSchema],
+        mode: CompatibilityMode,
+    ) -> Result<(CompatibilityResult, CompatibilityTrace)> {
+        let deprecations = deprecations_in(previous_versions);
+        let mut violations = Vec::new();
+        let mut checked_versions = Vec::new();
+        let mut trace = CompatibilityTrace {
+            mode,
+            versions: Vec::new(),
+        };
+
+        for old_schema in previous_versions {
+            // Stop once the caller's deadline has passed or it went away
+            deadline::checkpoint().await?;
+            let mut tracer = Tracer::on();
+            let findings = if new_schema.content_hash == old_schema.content_hash {
+                tracer.passed("content_hash", None, "", || {
+                    "Both versions have the same content".to_string()
+                });
+                Vec::new()
+            } else {
+                self.check_limits(new_schema)?;
+                self.check_limits(old_schema)?;
+                diff_pair(new_schema, old_schema, mode, self.cross_format, &mut tracer)
+            };
+            let result = self.result_from(
+                new_schema,
+                old_schema,
+                mode,
+                &findings,
+                &deprecations,
+                &mut tracer,
+            );
+            trace.versions.push(VersionTrace {
+                version: old_schema.version.clone(),
+                is_compatible: result.is_compatible,
+                steps: tracer.into_steps(),
+            });
+            violations.extend(result.violations);
+            checked_versions.extend(result.checked_versions);
+
+            if !result.is_compatible && !mode.is_transitive() {
+                break;
+            }
+        }
+
+        let result = CompatibilityResult {
+            is_compatible: !fails(&violations, self.min_severity_to_fail),
+            mode,
+            violations,
+            exempted_violations: Vec::new(),
+            checked_versions,
+        };
+        Ok((result, trace))
+    }
+
+    /// Findings of a pair that needn't be diffed: none for identical
+    /// content, or the cached ones
+    async fn known_findings(
+        &self,
+        new_schema: &RegisteredSchema,
+        old_schema: &RegisteredSchema,
+        mode: CompatibilityMode,
+    ) -> Option<Arc<Vec<Finding>>> {
+        if new_schema.content_hash == old_schema.content_hash {
+            return Some(Arc::new(Vec::new()));
+        }
+        match &self.cache {
+            Some(cache) => {
+                cache
+                    .get(&old_schema.content_hash, &new_schema.content_hash, mode)
+                    .await
+            }
+            None => None,
+        }
+    }
+
+    async fn remember(
+        &self,
+        new_schema: &RegisteredSchema,
+        old_schema: &RegisteredSchema,
+        mode: CompatibilityMode,
+        findings: &Arc<Vec<Finding>>,
+    ) {
+        if let Some(cache) = &self.cache {
+            cache
+                .insert(
+                    &old_schema.content_hash,
+                    &new_schema.content_hash,
+                    mode,
+                    findings.clone(),
+                )
+                .await;
+        }
+    }
+
+    /// Diff the previous versions at `misses` concurrently, filling in their
+    /// findings
+    ///
+    /// Each diff runs on the blocking pool with its own copy of the schemas,
+    /// at most `transitive_concurrency` at a time.
+    async fn diff_concurrently(
+        &self,
+        new_schema: &RegisteredSchema,
+        previous_versions: &[RegisteredSchema],
+        mode: CompatibilityMode,
+        misses: Vec<usize>,
+        findings: &mut [Option<Arc<Vec<Finding>>>],
+    ) -> Result<()> {
+        let shared_new = Arc::new(new_schema.clone());
+        let mut misses = misses.into_iter();
+        let mut diffs = JoinSet::new();
+        loop {
+            while diffs.len() < self.transitive_concurrency {
+                let Some(index) = misses.next() else {
+                    break;
+                };
+                let new_schema = shared_new.clone();
+                let old_schema = previous_versions[index].clone();
+                let cross_format = self.cross_format;
+                diffs.spawn_blocking(move || {
+                    let findings = diff_pair(
+                        &new_schema,
+                        &old_schema,
+                        mode,
+                        cross_format,
+                        &mut Tracer::off(),
+                    );
+                    (index, findings)
+                });
+            }
+            // Stop once the caller's deadline has passed or it went away;
+            // dropping the set aborts the diffs that haven't started
+            deadline::checkpoint().await?;
+            let Some(joined) = diffs.join_next().await else {
+                return Ok(());
+            };
+            let (index, diffed) = joined.map_err(|e| {
+                if e.is_panic() {
+                    std::panic::resume_unwind(e.into_panic());
+                }
+                Error::InternalError(format!("Compatibility diff did not finish: {}", e))
+            })?;
+            let diffed = Arc::new(diffed);
+            self.remember(new_schema, &previous_versions[index], mode, &diffed)
+                .await;
+            findings[index] = Some(diffed);
+        }
+    }
+
+    /// The result of checking a pair with `findings`
+    fn result_from(
+        &self,
+        new_schema: &RegisteredSchema,
+        old_schema: &RegisteredSchema,
+        mode: CompatibilityMode,
+        findings: &[Finding],
+        deprecations: &HashMap<String, FieldDeprecation>,
+        tracer: &mut Tracer,
+    ) -> CompatibilityResult {
+        let violations: Vec<CompatibilityViolation> = findings
+            .iter()
+            .filter_map(|finding| match finding {
+                Finding::Removed(path) => {
+                    self.removal(path.clone(), &new_schema.version, deprecations, tracer)
+                }
+                Finding::Violation(violation) => Some(violation.clone()),
+            })
+            .collect();
+        if tracer.is_on() {
+            self.trace_policy(&new_schema.namespace, &violations, tracer);
+        }
+        let violations = self.policy.apply(&new_schema.namespace, violations);
+
+        CompatibilityResult {
+            is_compatible: !fails(&violations, self.min_severity_to_fail),
+            mode,
+            violations,
+            exempted_violations: Vec::new(),
+            checked_versions: vec![old_schema.version.clone()],
+        }
     }
+
+    /// Trace the violations whose rule the policy doesn't enforce as breaking
+    fn trace_policy(
+        &self,
+        namespace: &str,
+        violations: &[CompatibilityViolation],
+        tracer: &mut Tracer,
+    ) {
+        for violation in violations {
+            let treatment = match self.policy.level(namespace, &violation.violation_type) {
+                RuleLevel::Breaking => continue,
+                RuleLevel::Off => "switches off",
+                RuleLevel::Info => "reports for information only",
+                RuleLevel::Warning => "downgrades to a warning",
+            };
+            tracer.passed("policy", None, &violation.field_path, || {
+                format!(
+                    "The policy {} {} in namespace '{}'",
+                    treatment,
+                    violation_code(&violation.violation_type),
+                    namespace
+                )
+            });
+        }
+    }
+
+    /// The violation for removing a field in `version`, if it is one
+    ///
+    /// Removing a deprecated field is not a violation once its notice period
+    /// has passed by that version.
+    fn removal(
+        &self,
+        path: String,
+        version: &SemanticVersion,
+        deprecations: &HashMap<String, FieldDeprecation>,
+        tracer: &mut Tracer,
+    ) -> Option<CompatibilityViolation> {
+        let (description, action, suggestion) = match deprecations.get(&path) {
+            Some(deprecation) if deprecation.notice_elapsed(version, self.deprecation_notice) => {
+                tracing::debug!(field = %path, "Deprecated field removed after its notice period");
+                tracer.passed("deprecation.notice", None, &path, || {
+                    format!(
+                        "The field was deprecated and its notice of {} minor version(s) ended by {}",
+                        self.deprecation_notice, version
+                    )
+                });
+                return None;
+            }
+            Some(deprecation) => {
+                let since = deprecation
+                    .since
+                    .as_ref()
+                    .map(ToString::to_string)
+                    .unwrap_or_default();
+                (
+                    format!(
+                        "Field '{}' was removed before its deprecation notice period ended (deprecated since {}, notice of {} minor version(s))",
+                        path, since, self.deprecation_notice
+                    ),
+                    RemediationAction::RestoreField,
+                    format!(
+                        "Keep field '{}' until {} minor version(s) after {}",
+                        path, self.deprecation_notice, since
+                    ),
+                )
+            }
+            None => (
+                format!(
+                    "Field '{}' was removed without being deprecated first",
+                    path
+                ),
+                RemediationAction::DeprecateField,
+                format!(
+                    "Restore field '{}' marked deprecated, and remove it {} minor version(s) later",
+                    path, self.deprecation_notice
+                ),
+            ),
+        };
+        tracer.failed("deprecation.notice", None, &path, || description.clone());
+        let violation = CompatibilityViolation {
+            violation_type: ViolationType::FieldRemoved,
+            field_path: path,
+            old_value: None,
+            new_value: None,
+            severity: ViolationSeverity::Breaking,
+            description,
+            suggestion: None,
+        };
+        Some(violation.with_suggestion(action, suggestion))
+    }
+}
+
+/// What comparing two schemas finds under `mode`
+///
+/// Schemas of different formats are only compared with `cross_format` set.
+fn diff_pair(
+    new_schema: &RegisteredSchema,
+    old_schema: &RegisteredSchema,
+    mode: CompatibilityMode,
+    cross_format: bool,
+    tracer: &mut Tracer,
+) -> Vec<Finding> {
+    match (new_schema.format, old_schema.format) {
+        // Avro resolution decides which missing fields matter: those with a
+        // default don't
+        (SerializationFormat::Avro, SerializationFormat::Avro) => structural_violations(
+            new_schema,
+            old_schema,
+            mode,
+            avro::incompatibilities,
+            tracer,
+        )
+        .into_iter()
+        .map(|violation| match violation.violation_type {
+            ViolationType::FieldRemoved => Finding::Removed(violation.field_path),
+            _ => Finding::Violation(violation),
+        })
+        .collect(),
+        (new_format, old_format) if new_format == old_format => {
+            let mut findings = Vec::new();
+            if reads_forward(mode) {
+                let removed: Vec<String> = removed_fields(new_schema, old_schema).collect();
+                if removed.is_empty() {
+                    tracer.passed("fields.removed", Some(Direction::FORWARD), "", || {
+                        "The new schema keeps every field of the old one".to_string()
+                    });
+                }
+                for path in &removed {
+                    tracer.failed("fields.removed", Some(Direction::FORWARD), path, || {
+                        format!(
+                            "Field '{}' of the old schema is missing from the new one",
+                            path
+                        )
+                    });
+                }
+                findings.extend(removed.into_iter().map(Finding::Removed));
+            }
+            if new_format == SerializationFormat::JsonSchema {
+                findings.extend(
+                    structural_violations(
+                        new_schema,
+                        old_schema,
+                        mode,
+                        json_schema::narrowings,
+                        tracer,
+                    )
+                    .into_iter()
+                    .map(Finding::Violation),
+                );
+            }
+            findings
+        }
+        _ if cross_format => cross_format::incompatibilities(new_schema, old_schema, mode, tracer)
+            .into_iter()
+            .map(|violation| match violation.violation_type {
+                ViolationType::FieldRemoved => Finding::Removed(violation.field_path),
+                _ => Finding::Violation(violation),
+            })
+            .collect(),
+        (new_format, old_format) => {
+            tracer.passed("format", None, "", || {
+                format!(
+                    "{} and {} versions are only compared with cross-format checks on",
+                    new_format, old_format
+                )
+            });
+            Vec::new()
+        }
+    }
+}
+
+/// Paths of the old schema's fields missing from the new one
+fn removed_fields(
+    new_schema: &RegisteredSchema,
+    old_schema: &RegisteredSchema,
+) -> impl Iterator<Item = String> {
+    let new_fields: Vec<String> = deprecation::fields(&new_schema.content, new_schema.format)
+        .into_iter()
+        .map(|field| field.path)
+        .collect();
+
+    deprecation::fields(&old_schema.content, old_schema.format)
+        .into_iter()
+        .map(|field| field.path)
+        .filter(move |path| !new_fields.contains(path))
+}
+
+/// Structural changes between two schemas that break `mode`, as found by `diff`
+///
+/// Content that doesn't parse was already rejected by the limit check or by
+/// validation at registration, so it has nothing to compare.
+fn structural_violations(
+    new_schema: &RegisteredSchema,
+    old_schema: &RegisteredSchema,
+    mode: CompatibilityMode,
+    diff: fn(
+        &serde_json::Value,
+        &serde_json::Value,
+        Direction,
+        &mut Tracer,
+    ) -> Vec<CompatibilityViolation>,
+    tracer: &mut Tracer,
+) -> Vec<CompatibilityViolation> {
+    let (Ok(new), Ok(old)) = (
+        serde_json::from_str::<serde_json::Value>(&new_schema.content),
+        serde_json::from_str::<serde_json::Value>(&old_schema.content),
+    ) else {
+        return Vec::new();
+    };
+    let mut violations = Vec::new();
+    if reads_backward(mode) {
+        violations.extend(diff(&new, &old, Direction::BACKWARD, tracer));
+    }
+    if reads_forward(mode) {
+        violations.extend(diff(&old, &new, Direction::FORWARD, tracer));
+    }
+    violations
+}
+
+/// Which schema reads and which wrote, as named in violation descriptions
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Direction {
+    pub reader: &'static str,
+    pub writer: &'static str,
+    /// Whether the reader is the new schema
+    pub reader_is_new: bool,
+}
+
+impl Direction {
+    /// The new schema reads data written with the old one
+    pub(crate) const BACKWARD: Direction = Direction {
+        reader: "new",
+        writer: "old",
+        reader_is_new: true,
+    };
+
+    /// The old schema reads data written with the new one
+    pub(crate) const FORWARD: Direction = Direction {
+        reader: "old",
+        writer: "new",
+        reader_is_new: false,
+    };
+
+    /// A breaking violation, giving the writer's and reader's values as old
+    /// and new according to the direction
+    pub(crate) fn violation(
+        self,
+        violation_type: ViolationType,
+        path: &str,
+        writer_value: Option<&serde_json::Value>,
+        reader_value: Option<&serde_json::Value>,
+        description: String,
+    ) -> CompatibilityViolation {
+        let (old_value, new_value) = if self.reader_is_new {
+            (writer_value.cloned(), reader_value.cloned())
+        } else {
+            (reader_value.cloned(), writer_value.cloned())
+        };
+        CompatibilityViolation {
+            violation_type,
+            field_path: path.to_string(),
+            old_value,
+            new_value,
+            severity: ViolationSeverity::Breaking,
+            description,
+            suggestion: None,
+        }
+    }
+}
+
+/// Whether any of `violations` is `min_severity` or worse, making schemas
+/// incompatible
+pub(crate) fn fails(violations: &[CompatibilityViolation], min_severity: ViolationSeverity) -> bool {
+    violations
+        .iter()
+        .any(|violation| violation.severity.is_at_least(min_severity))
+}
+
+/// Whether readers of the new schema must be able to read old data
+fn reads_backward(mode: CompatibilityMode) -> bool {
+    matches!(
+        mode,
+        CompatibilityMode::Backward
+            | CompatibilityMode::Full
+            | CompatibilityMode::BackwardTransitive
+            | CompatibilityMode::FullTransitive
+    )
+}
+
+/// Whether readers of the old schema must be able to read new data
+fn reads_forward(mode: CompatibilityMode) -> bool {
+    matches!(
+        mode,
+        CompatibilityMode::Forward
+            | CompatibilityMode::Full
+            | CompatibilityMode::ForwardTransitive
+            | CompatibilityMode::FullTransitive
+    )
+}
+
+/// Deprecations declared across `versions`, by field path
+///
+/// A field keeps the earliest version that deprecated it. Without an explicit
+/// `since`, the first version carrying the annotation counts as the start of
+/// its notice period.
+fn deprecations_in(versions: &[RegisteredSchema]) -> HashMap<String, FieldDeprecation> {
+    let mut deprecations: HashMap<String, FieldDeprecation> = HashMap::new();
+    for schema in versions {
+        for mut found in deprecation::field_deprecations(&schema.content, schema.format) {
+            found.since.get_or_insert_with(|| schema.version.clone());
+            match deprecations.get(&found.path) {
+                Some(existing) if existing.since <= found.since => {}
+                _ => {
+                    deprecations.insert(found.path.clone(), found);
+                }
+            }
+        }
+    }
+    deprecations
 }
 
 impl Default for CompatibilityCheckerImpl {
@@ -33,23 +721,8 @@ impl CompatibilityChecker for CompatibilityCheckerImpl {
         old_schema: &RegisteredSchema,
         mode: CompatibilityMode,
     ) -> Result<CompatibilityResult> {
-        // Fast path: identical content hashes
-        if new_schema.content_hash == old_schema.content_hash {
-            return Ok(CompatibilityResult {
-                is_compatible: true,
-                mode,
-                violations: Vec::new(),
-                checked_versions: vec![old_schema.version.clone()],
-            });
-        }
-
-        // Detailed compatibility check would go here
-        Ok(CompatibilityResult {
-            is_compatible: true,
-            mode,
-            violations: Vec::new(),
-            checked_versions: vec![old_schema.version.clone()],
-        })
+        let deprecations = deprecations_in(std::slice::from_ref(old_schema));
+        self.check_against(new_schema, old_schema, mode, &deprecations).await
     }
 
     async fn check_transitive_compatibility(
@@ -59,11 +732,53 @@ impl CompatibilityChecker for CompatibilityCheckerImpl {
         mode: CompatibilityMode,
     ) -> Result<CompatibilityResult> {
         let mut all_violations = Vec::new();
+        let mut exempted_violations = Vec::new();
         let mut checked_versions = Vec::new();
+        // Older versions predate a field's deprecation, so gather them all
+        let deprecations = deprecations_in(previous_versions);
 
-        for old_schema in previous_versions {
-            let result = self.check_compatibility(new_schema, old_schema, mode).await?;
+        // Diff the versions the cache can't answer concurrently up front.
+        // Non-transitive checks stop at the first incompatible version, so
+        // they keep diffing one version at a time.
+        let mut findings = Vec::with_capacity(previous_versions.len());
+        let mut misses = Vec::new();
+        if mode.is_transitive() && self.transitive_concurrency > 1 {
+            for (index, old_schema) in previous_versions.iter().enumerate() {
+                let known = self.known_findings(new_schema, old_schema, mode).await;
+                if known.is_none() {
+                    self.check_limits(old_schema)?;
+                    misses.push(index);
+                }
+                findings.push(known);
+            }
+        }
+        if misses.len() > 1 {
+            self.check_limits(new_schema)?;
+            self.diff_concurrently(new_schema, previous_versions, mode, misses, &mut findings)
+                .await?;
+        }
+
+        // Merge in version order, so the result doesn't depend on which diff
+        // finished first
+        for (index, old_schema) in previous_versions.iter().enumerate() {
+            // Stop once the caller's deadline has passed or it went away
+            deadline::checkpoint().await?;
+            let result = match findings.get(index).cloned().flatten() {
+                Some(found) => self.result_from(
+                    new_schema,
+                    old_schema,
+                    mode,
+                    &found,
+                    &deprecations,
+                    &mut Tracer::off(),
+                ),
+                None => {
+                    self.check_against(new_schema, old_schema, mode, &deprecations)
+                        .await?
+                }
+            };
             all_violations.extend(result.violations);
+            exempted_violations.extend(result.exempted_violations);
             checked_versions.extend(result.checked_versions);
 
             if !result.is_compatible && !mode.is_transitive() {
@@ -72,9 +787,10 @@ impl CompatibilityChecker for CompatibilityCheckerImpl {
         }
 
         Ok(CompatibilityResult {
-            is_compatible: all_violations.is_empty(),
+            is_compatible: !fails(&all_violations, self.min_severity_to_fail),
             mode,
             violations: all_violations,
+            exempted_violations,
             checked_versions,
         })
     }
@@ -348,4 +1064,408 @@ mod tests {
         assert!(compat.is_compatible);
         assert!(compat.violations.is_empty());
     }
+
+    #[tokio::test]
+    async fn test_check_compatibility_rejects_oversized_schema() {
+        let checker = CompatibilityCheckerImpl::new().with_limits(JsonLimits {
+            max_depth: 2,
+            ..JsonLimits::default()
+        });
+        let schema1 = create_test_schema(SemanticVersion::new(1, 0, 0), "{}", "hash1");
+        let schema2 = create_test_schema(SemanticVersion::new(1, 1, 0), r#"{"a": {"b": {}}}"#, "hash2");
+
+        let result = checker.check_compatibility(
+            &schema2,
+            &schema1,
+            CompatibilityMode::Backward,
+        ).await;
+
+        assert!(result.unwrap_err().is_limit_exceeded());
+    }
+
+    const USER_V1: &str = r#"{"type":"object","properties":{"email":{"type":"string"},"name":{"type":"string"}}}"#;
+    const USER_V2: &str = r#"{"type":"object","properties":{"email":{"type":"string","x-deprecated":{"since":"1.2.0","replacement":"name"}},"name":{"type":"string"}}}"#;
+    const USER_V3: &str = r#"{"type":"object","properties":{"name":{"type":"string"}}}"#;
+
+    #[tokio::test]
+    async fn test_removing_field_breaks_forward_compatibility() {
+        let checker = CompatibilityCheckerImpl::new();
+        let old_schema = create_test_schema(SemanticVersion::new(1, 0, 0), USER_V1, "hash1");
+        let new_schema = create_test_schema(SemanticVersion::new(1, 1, 0), USER_V3, "hash3");
+
+        let forward = checker
+            .check_compatibility(&new_schema, &old_schema, CompatibilityMode::Forward)
+            .await
+            .unwrap();
+        assert!(!forward.is_compatible);
+        assert_eq!(forward.violations[0].violation_type, ViolationType::FieldRemoved);
+        assert_eq!(forward.violations[0].field_path, "email");
+        let suggestion = forward.violations[0].suggestion.as_ref().unwrap();
+        assert_eq!(suggestion.action, RemediationAction::DeprecateField);
+        assert!(suggestion
+            .message
+            .starts_with("Restore field 'email' marked deprecated"));
+
+        let backward = checker
+            .check_compatibility(&new_schema, &old_schema, CompatibilityMode::Backward)
+            .await
+            .unwrap();
+        assert!(backward.is_compatible);
+    }
+
+    #[tokio::test]
+    async fn test_removing_deprecated_field_after_notice_period() {
+        let checker = CompatibilityCheckerImpl::new();
+        let old_schema = create_test_schema(SemanticVersion::new(1, 2, 0), USER_V2, "hash2");
+
+        let too_soon = create_test_schema(SemanticVersion::new(1, 2, 1), USER_V3, "hash3");
+        let result = checker
+            .check_compatibility(&too_soon, &old_schema, CompatibilityMode::Full)
+            .await
+            .unwrap();
+        assert!(!result.is_compatible);
+        assert!(result.violations[0].description.contains("notice period"));
+
+        let after_notice = create_test_schema(SemanticVersion::new(1, 3, 0), USER_V3, "hash3");
+        let result = checker
+            .check_compatibility(&after_notice, &old_schema, CompatibilityMode::Full)
+            .await
+            .unwrap();
+        assert!(result.is_compatible);
+
+        let longer_notice = CompatibilityCheckerImpl::new().with_deprecation_notice(2);
+        let result = longer_notice
+            .check_compatibility(&after_notice, &old_schema, CompatibilityMode::Full)
+            .await
+            .unwrap();
+        assert!(!result.is_compatible);
+    }
+
+    #[tokio::test]
+    async fn test_structural_changes_break_the_modes_they_affect() {
+        let checker = CompatibilityCheckerImpl::new();
+        let old_schema = create_test_schema(
+            SemanticVersion::new(1, 0, 0),
+            r#"{"type":"object","properties":{"name":{"type":"string"},"status":{"enum":["A","B"]}}}"#,
+            "hash1",
+        );
+        // Requires `name` and drops enum value "B"
+        let new_schema = create_test_schema(
+            SemanticVersion::new(1, 1, 0),
+            r#"{"type":"object","properties":{"name":{"type":"string"},"status":{"enum":["A"]}},"required":["name"]}"#,
+            "hash2",
+        );
+
+        let backward = checker
+            .check_compatibility(&new_schema, &old_schema, CompatibilityMode::Backward)
+            .await
+            .unwrap();
+        assert!(!backward.is_compatible);
+        let types: Vec<ViolationType> = backward.violations.iter().map(|v| v.violation_type.clone()).collect();
+        assert_eq!(types, [ViolationType::RequiredAdded, ViolationType::EnumValueRemoved]);
+
+        let forward = checker
+            .check_compatibility(&new_schema, &old_schema, CompatibilityMode::Forward)
+            .await
+            .unwrap();
+        assert!(forward.is_compatible);
+
+        let full = checker
+            .check_compatibility(&new_schema, &old_schema, CompatibilityMode::Full)
+            .await
+            .unwrap();
+        assert_eq!(full.violations.len(), 2);
+
+        let none = checker
+            .check_compatibility(&new_schema, &old_schema, CompatibilityMode::None)
+            .await
+            .unwrap();
+        assert!(none.is_compatible);
+    }
+
+    #[tokio::test]
+    async fn test_avro_removals_need_a_default_or_deprecation() {
+        let checker = CompatibilityCheckerImpl::new();
+        let avro = |version: SemanticVersion, content: &str, hash: &str| RegisteredSchema {
+            format: SerializationFormat::Avro,
+            ..create_test_schema(version, content, hash)
+        };
+        let old_schema = avro(
+            SemanticVersion::new(1, 0, 0),
+            r#"{"type":"record","name":"User","fields":[{"name":"id","type":"string"},{"name":"email","type":"string"},{"name":"locale","type":"string","default":"en"}]}"#,
+            "hash1",
+        );
+        let new_schema = avro(
+            SemanticVersion::new(1, 1, 0),
+            r#"{"type":"record","name":"User","fields":[{"name":"id","type":"string"}]}"#,
+            "hash2",
+        );
+
+        let full = checker
+            .check_compatibility(&new_schema, &old_schema, CompatibilityMode::Full)
+            .await
+            .unwrap();
+        let paths: Vec<&str> = full.violations.iter().map(|v| v.field_path.as_str()).collect();
+        assert_eq!(paths, ["email"]);
+        assert!(full.violations[0].description.contains("without being deprecated first"));
+    }
+
+    #[tokio::test]
+    async fn test_transitive_check_uses_deprecations_from_any_version() {
+        let checker = CompatibilityCheckerImpl::new();
+        let history = vec![
+            create_test_schema(SemanticVersion::new(1, 0, 0), USER_V1, "hash1"),
+            create_test_schema(SemanticVersion::new(1, 2, 0), USER_V2, "hash2"),
+        ];
+        let new_schema = create_test_schema(SemanticVersion::new(1, 3, 0), USER_V3, "hash3");
+
+        let result = checker
+            .check_transitive_compatibility(&new_schema, &history, CompatibilityMode::FullTransitive)
+            .await
+            .unwrap();
+        assert!(result.is_compatible);
+        assert_eq!(result.checked_versions.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_repeated_transitive_check_hits_pair_cache() {
+        let checker = CompatibilityCheckerImpl::new();
+        let history = vec![
+            create_test_schema(SemanticVersion::new(1, 0, 0), USER_V1, "hash1"),
+            create_test_schema(SemanticVersion::new(1, 1, 0), USER_V2, "hash2"),
+        ];
+        let new_schema = create_test_schema(SemanticVersion::new(1, 2, 0), USER_V3, "hash3");
+
+        let first = checker
+            .check_transitive_compatibility(&new_schema, &history, CompatibilityMode::FullTransitive)
+            .await
+            .unwrap();
+        let stats = checker.cache_stats().unwrap();
+        assert_eq!((stats.hits, stats.misses), (0, 2));
+
+        let second = checker
+            .check_transitive_compatibility(&new_schema, &history, CompatibilityMode::FullTransitive)
+            .await
+            .unwrap();
+        let stats = checker.cache_stats().unwrap();
+        assert_eq!((stats.hits, stats.misses), (2, 2));
+        assert_eq!(stats.hit_ratio(), Some(0.5));
+        assert_eq!(first.violations.len(), second.violations.len());
+        assert!(!second.is_compatible);
+
+        let uncached = CompatibilityCheckerImpl::new().without_cache();
+        assert!(uncached.cache_stats().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_transitive_check_merges_in_version_order() {
+        let history: Vec<_> = (0..24)
+            .map(|minor| {
+                let content = if minor % 3 == 0 { USER_V1 } else { USER_V2 };
+                let hash = format!("hash-{}", minor);
+                create_test_schema(SemanticVersion::new(1, minor, 0), content, &hash)
+            })
+            .collect();
+        let new_schema = create_test_schema(SemanticVersion::new(2, 0, 0), USER_V3, "hash-new");
+        let mode = CompatibilityMode::FullTransitive;
+
+        let serial = CompatibilityCheckerImpl::new()
+            .without_cache()
+            .with_transitive_concurrency(1)
+            .check_transitive_compatibility(&new_schema, &history, mode)
+            .await
+            .unwrap();
+        let concurrent = CompatibilityCheckerImpl::new()
+            .with_transitive_concurrency(4)
+            .check_transitive_compatibility(&new_schema, &history, mode)
+            .await
+            .unwrap();
+
+        let versions: Vec<_> = history.iter().map(|s| s.version.clone()).collect();
+        assert_eq!(concurrent.checked_versions, versions);
+        assert_eq!(concurrent.is_compatible, serial.is_compatible);
+        assert_eq!(
+            format!("{:?}", concurrent.violations),
+            format!("{:?}", serial.violations)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_explain_traces_every_rule_evaluated() {
+        let checker = CompatibilityCheckerImpl::new();
+        let history = vec![
+            create_test_schema(SemanticVersion::new(1, 0, 0), USER_V1, "hash1"),
+            create_test_schema(SemanticVersion::new(1, 1, 0), USER_V3, "hash3"),
+        ];
+        let new_schema = create_test_schema(SemanticVersion::new(1, 2, 0), USER_V3, "hash3");
+        let mode = CompatibilityMode::FullTransitive;
+
+        let (result, trace) = checker.explain(&new_schema, &history, mode).await.unwrap();
+        let checked = checker
+            .check_transitive_compatibility(&new_schema, &history, mode)
+            .await
+            .unwrap();
+        assert_eq!(result.is_compatible, checked.is_compatible);
+        assert_eq!(result.violations.len(), checked.violations.len());
+
+        // The removal fails twice: the field is gone, and it wasn't deprecated
+        let failures: Vec<_> = trace
+            .failures()
+            .map(|(version, step)| {
+                (
+                    version.to_string(),
+                    step.rule.as_str(),
+                    step.pointer.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            failures,
+            [
+                ("1.0.0".to_string(), "fields.removed", "/email"),
+                ("1.0.0".to_string(), "deprecation.notice", "/email"),
+            ]
+        );
+        let passed: Vec<_> = trace.versions[0]
+            .steps
+            .iter()
+            .filter(|step| step.outcome == RuleOutcome::Passed)
+            .map(|step| (step.rule.as_str(), step.direction))
+            .collect();
+        assert!(passed.contains(&("json_schema.type", Some(TraceDirection::Backward))));
+        assert!(passed.contains(&("json_schema.required", Some(TraceDirection::Forward))));
+
+        // Identical content needs no rules
+        assert!(trace.versions[1].is_compatible);
+        assert_eq!(trace.versions[1].steps.len(), 1);
+        assert_eq!(trace.versions[1].steps[0].rule, "content_hash");
+    }
+
+    #[tokio::test]
+    async fn test_cross_format_versions_are_compared_when_enabled() {
+        let old_schema = RegisteredSchema {
+            format: SerializationFormat::Avro,
+            ..create_test_schema(
+                SemanticVersion::new(1, 0, 0),
+                r#"{"type":"record","name":"User","fields":[{"name":"email","type":"string"},{"name":"name","type":"string"}]}"#,
+                "hash1",
+            )
+        };
+        let new_schema = create_test_schema(SemanticVersion::new(1, 1, 0), USER_V3, "hash2");
+
+        let same_format_only = CompatibilityCheckerImpl::new()
+            .check_compatibility(&new_schema, &old_schema, CompatibilityMode::Full)
+            .await
+            .unwrap();
+        assert!(same_format_only.is_compatible);
+
+        let checker = CompatibilityCheckerImpl::new().with_cross_format();
+        assert!(checker.cross_format());
+        let result = checker
+            .check_compatibility(&new_schema, &old_schema, CompatibilityMode::Full)
+            .await
+            .unwrap();
+        let paths: Vec<&str> = result.violations.iter().map(|v| v.field_path.as_str()).collect();
+        assert_eq!(paths, ["email", "name"]);
+        assert!(result.violations[0].description.contains("without being deprecated first"));
+    }
+
+    #[tokio::test]
+    async fn test_policy_downgrades_and_disables_rules_per_namespace() {
+        use schema_registry_core::config_manager_adapter::RuleLevel;
+
+        let old_schema = create_test_schema(
+            SemanticVersion::new(1, 0, 0),
+            r#"{"type":"object","properties":{"status":{"enum":["new","paid"]},"note":{"type":"string"}}}"#,
+            "hash1",
+        );
+        let new_schema = create_test_schema(
+            SemanticVersion::new(1, 1, 0),
+            r#"{"type":"object","properties":{"status":{"enum":["new"]},"note":{"type":"string","maxLength":10}}}"#,
+            "hash2",
+        );
+        let policy = CompatibilityPolicy {
+            rules: HashMap::new(),
+            namespaces: HashMap::from([(
+                "test".to_string(),
+                HashMap::from([
+                    (ViolationType::EnumValueRemoved, RuleLevel::Warning),
+                    (ViolationType::ConstraintAdded, RuleLevel::Off),
+                ]),
+            )]),
+        };
+
+        let strict = CompatibilityCheckerImpl::new()
+            .check_compatibility(&new_schema, &old_schema, CompatibilityMode::Backward)
+            .await
+            .unwrap();
+        assert!(!strict.is_compatible);
+        assert_eq!(strict.violations.len(), 2);
+
+        let checker = CompatibilityCheckerImpl::new().with_policy(policy);
+        let result = checker
+            .check_compatibility(&new_schema, &old_schema, CompatibilityMode::Backward)
+            .await
+            .unwrap();
+        assert!(result.is_compatible);
+        assert_eq!(result.violations.len(), 1);
+        assert_eq!(
+            result.violations[0].violation_type,
+            ViolationType::EnumValueRemoved
+        );
+        assert_eq!(result.violations[0].severity, ViolationSeverity::Warning);
+
+        let elsewhere = RegisteredSchema {
+            namespace: "other".to_string(),
+            ..new_schema.clone()
+        };
+        let result = checker
+            .check_compatibility(&elsewhere, &old_schema, CompatibilityMode::Backward)
+            .await
+            .unwrap();
+        assert!(!result.is_compatible);
+    }
+
+    #[tokio::test]
+    async fn test_min_severity_to_fail_decides_which_violations_fail() {
+        use schema_registry_core::config_manager_adapter::RuleLevel;
+
+        let old_schema = create_test_schema(
+            SemanticVersion::new(1, 0, 0),
+            r#"{"type":"object","properties":{"status":{"enum":["new","paid"]}}}"#,
+            "hash1",
+        );
+        let new_schema = create_test_schema(
+            SemanticVersion::new(1, 1, 0),
+            r#"{"type":"object","properties":{"status":{"enum":["new"]}}}"#,
+            "hash2",
+        );
+        let policy = CompatibilityPolicy {
+            rules: HashMap::from([(ViolationType::EnumValueRemoved, RuleLevel::Warning)]),
+            namespaces: HashMap::new(),
+        };
+
+        // Warnings are reported without failing the check by default
+        let lenient = CompatibilityCheckerImpl::new().with_policy(policy.clone());
+        let result = lenient
+            .check_compatibility(&new_schema, &old_schema, CompatibilityMode::Backward)
+            .await
+            .unwrap();
+        assert!(result.is_compatible);
+        assert_eq!(result.violations[0].severity, ViolationSeverity::Warning);
+
+        let strict = CompatibilityCheckerImpl::new()
+            .with_policy(policy)
+            .with_min_severity_to_fail(ViolationSeverity::Warning);
+        let result = strict
+            .check_transitive_compatibility(
+                &new_schema,
+                std::slice::from_ref(&old_schema),
+                CompatibilityMode::BackwardTransitive,
+            )
+            .await
+            .unwrap();
+        assert!(!result.is_compatible);
+        assert_eq!(result.violations.len(), 1);
+    }
 }
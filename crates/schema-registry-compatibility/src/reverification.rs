@@ -0,0 +1,567 @@
+//! Scheduled compatibility re-verification against consumer manifests
+//!
+//! Consumers register a manifest listing the subjects they read and the
+//! version of each they are pinned to. The [`ReverificationJob`] periodically
+//! (nightly by default) checks the newest releasable version of every subject
+//! against each pinned version, and produces a per-namespace
+//! [`NamespaceBreakageReport`] answering "who will break at the next release".
+//!
+//! A consumer pinned to an older version keeps reading data written with the
+//! newer schema, so pins are checked in [`CompatibilityMode::Forward`].
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use schema_registry_core::{
+    error::Result,
+    events::{EventPayload, EventType, SchemaEvent},
+    schema::RegisteredSchema,
+    state::SchemaState,
+    traits::{CompatibilityChecker, CompatibilityViolation, EventPublisher, SchemaStorage},
+    types::CompatibilityMode,
+    versioning::SemanticVersion,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+/// Default interval between runs
+pub const DEFAULT_REVERIFICATION_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+// ============================================================================
+// Consumer Manifests
+// ============================================================================
+
+/// A subject version a consumer depends on
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PinnedDependency {
+    /// Schema namespace
+    pub namespace: String,
+    /// Schema name
+    pub name: String,
+    /// Version the consumer is built against
+    pub pinned_version: SemanticVersion,
+}
+
+/// Schemas a consumer reads and the versions it is pinned to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsumerManifest {
+    /// Consumer ID
+    pub consumer_id: Uuid,
+    /// Consumer name
+    pub consumer_name: String,
+    /// Team or person to contact about breakages
+    pub owner: Option<String>,
+    /// Pinned schema dependencies
+    pub dependencies: Vec<PinnedDependency>,
+}
+
+/// Source of registered consumer manifests
+#[async_trait]
+pub trait ConsumerManifestStore: Send + Sync {
+    /// List all registered manifests
+    async fn list_manifests(&self) -> Result<Vec<ConsumerManifest>>;
+}
+
+/// In-memory manifest store
+#[derive(Default)]
+pub struct InMemoryManifestStore {
+    manifests: RwLock<HashMap<Uuid, ConsumerManifest>>,
+}
+
+impl InMemoryManifestStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register or replace a consumer manifest
+    pub async fn register(&self, manifest: ConsumerManifest) {
+        self.manifests
+            .write()
+            .await
+            .insert(manifest.consumer_id, manifest);
+    }
+
+    /// Remove a consumer manifest
+    pub async fn unregister(&self, consumer_id: Uuid) -> Option<ConsumerManifest> {
+        self.manifests.write().await.remove(&consumer_id)
+    }
+}
+
+#[async_trait]
+impl ConsumerManifestStore for InMemoryManifestStore {
+    async fn list_manifests(&self) -> Result<Vec<ConsumerManifest>> {
+        Ok(self.manifests.read().await.values().cloned().collect())
+    }
+}
+
+/// Source of the stored versions of a subject
+///
+/// Implemented for every [`SchemaStorage`]; deployments that keep versions
+/// elsewhere can implement it directly.
+#[async_trait]
+pub trait SubjectVersions: Send + Sync {
+    /// Every stored version of a subject, in any order
+    async fn versions(&self, namespace: &str, name: &str) -> Result<Vec<RegisteredSchema>>;
+}
+
+#[async_trait]
+impl<T: SchemaStorage + ?Sized> SubjectVersions for T {
+    async fn versions(&self, namespace: &str, name: &str) -> Result<Vec<RegisteredSchema>> {
+        self.find_by_name(namespace, name).await
+    }
+}
+
+// ============================================================================
+// Reports
+// ============================================================================
+
+/// A consumer that will break when the candidate version is released
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PredictedBreakage {
+    pub consumer_id: Uuid,
+    pub consumer_name: String,
+    pub owner: Option<String>,
+    pub subject: String,
+    pub pinned_version: SemanticVersion,
+    pub candidate_schema_id: Uuid,
+    pub candidate_version: SemanticVersion,
+    pub violations: Vec<CompatibilityViolation>,
+}
+
+/// A pin that could not be checked because the pinned version does not exist
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnresolvedPin {
+    pub consumer_id: Uuid,
+    pub consumer_name: String,
+    pub subject: String,
+    pub pinned_version: SemanticVersion,
+}
+
+/// "Who will break at next release" report for one namespace
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NamespaceBreakageReport {
+    pub namespace: String,
+    pub generated_at: DateTime<Utc>,
+    /// Number of subjects checked
+    pub subjects_checked: usize,
+    /// Number of consumer pins checked
+    pub pins_checked: usize,
+    pub breakages: Vec<PredictedBreakage>,
+    pub unresolved: Vec<UnresolvedPin>,
+}
+
+impl NamespaceBreakageReport {
+    /// Whether any consumer in the namespace is predicted to break
+    pub fn has_breakages(&self) -> bool {
+        !self.breakages.is_empty()
+    }
+}
+
+/// Destination for completed namespace reports
+#[async_trait]
+pub trait BreakageReportSink: Send + Sync {
+    async fn deliver(&self, report: &NamespaceBreakageReport) -> Result<()>;
+}
+
+// ============================================================================
+// Job
+// ============================================================================
+
+/// Periodic re-verification of active schemas against consumer pins
+pub struct ReverificationJob {
+    storage: Arc<dyn SubjectVersions>,
+    checker: Arc<dyn CompatibilityChecker>,
+    manifests: Arc<dyn ConsumerManifestStore>,
+    publisher: Option<Arc<dyn EventPublisher>>,
+    sinks: Vec<Arc<dyn BreakageReportSink>>,
+    interval: Duration,
+}
+
+impl ReverificationJob {
+    pub fn new(
+        storage: Arc<dyn SubjectVersions>,
+        checker: Arc<dyn CompatibilityChecker>,
+        manifests: Arc<dyn ConsumerManifestStore>,
+    ) -> Self {
+        Self {
+            storage,
+            checker,
+            manifests,
+            publisher: None,
+            sinks: Vec::new(),
+            interval: DEFAULT_REVERIFICATION_INTERVAL,
+        }
+    }
+
+    /// Publish a notification event for every schema with predicted breakages
+    pub fn with_publisher(mut self, publisher: Arc<dyn EventPublisher>) -> Self {
+        self.publisher = Some(publisher);
+        self
+    }
+
+    /// Deliver completed reports to an additional sink
+    pub fn with_sink(mut self, sink: Arc<dyn BreakageReportSink>) -> Self {
+        self.sinks.push(sink);
+        self
+    }
+
+    /// Set the interval between runs
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Run the job on its interval until the task is aborted
+    pub fn spawn(self: Arc<Self>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(self.interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.run_once().await {
+                    tracing::error!(error = %e, "Compatibility re-verification failed");
+                }
+            }
+        })
+    }
+
+    /// Check every pinned dependency once and deliver the reports
+    pub async fn run_once(&self) -> Result<Vec<NamespaceBreakageReport>> {
+        let manifests = self.manifests.list_manifests().await?;
+        let generated_at = Utc::now();
+
+        // Group pins by subject so each subject is loaded once
+        let mut pins: BTreeMap<(String, String), Vec<(&ConsumerManifest, &PinnedDependency)>> =
+            BTreeMap::new();
+        for manifest in &manifests {
+            for dependency in &manifest.dependencies {
+                pins.entry((dependency.namespace.clone(), dependency.name.clone()))
+                    .or_default()
+                    .push((manifest, dependency));
+            }
+        }
+
+        let mut reports: BTreeMap<String, NamespaceBreakageReport> = BTreeMap::new();
+        for ((namespace, name), subject_pins) in pins {
+            let report =
+                reports
+                    .entry(namespace.clone())
+                    .or_insert_with(|| NamespaceBreakageReport {
+                        namespace: namespace.clone(),
+                        generated_at,
+                        subjects_checked: 0,
+                        pins_checked: 0,
+                        breakages: Vec::new(),
+                        unresolved: Vec::new(),
+                    });
+            report.subjects_checked += 1;
+
+            let versions = self.storage.versions(&namespace, &name).await?;
+            let Some(candidate) = release_candidate(&versions) else {
+                continue;
+            };
+            let subject = format!("{}.{}", namespace, name);
+
+            for (manifest, dependency) in subject_pins {
+                report.pins_checked += 1;
+                if dependency.pinned_version >= candidate.version {
+                    continue;
+                }
+
+                let Some(pinned) = versions
+                    .iter()
+                    .find(|s| s.version == dependency.pinned_version)
+                else {
+                    report.unresolved.push(UnresolvedPin {
+                        consumer_id: manifest.consumer_id,
+                        consumer_name: manifest.consumer_name.clone(),
+                        subject: subject.clone(),
+                        pinned_version: dependency.pinned_version.clone(),
+                    });
+                    continue;
+                };
+
+                let result = self
+                    .checker
+                    .check_compatibility(candidate, pinned, CompatibilityMode::Forward)
+                    .await?;
+                if !result.is_compatible {
+                    report.breakages.push(PredictedBreakage {
+                        consumer_id: manifest.consumer_id,
+                        consumer_name: manifest.consumer_name.clone(),
+                        owner: manifest.owner.clone(),
+                        subject: subject.clone(),
+                        pinned_version: dependency.pinned_version.clone(),
+                        candidate_schema_id: candidate.id,
+                        candidate_version: candidate.version.clone(),
+                        violations: result.violations,
+                    });
+                }
+            }
+        }
+
+        let reports: Vec<_> = reports.into_values().collect();
+        for report in &reports {
+            self.deliver(report).await;
+        }
+
+        tracing::info!(
+            namespaces = reports.len(),
+            breakages = reports.iter().map(|r| r.breakages.len()).sum::<usize>(),
+            "Compatibility re-verification completed"
+        );
+        Ok(reports)
+    }
+
+    async fn deliver(&self, report: &NamespaceBreakageReport) {
+        for sink in &self.sinks {
+            if let Err(e) = sink.deliver(report).await {
+                tracing::warn!(namespace = %report.namespace, error = %e, "Failed to deliver breakage report");
+            }
+        }
+
+        let Some(publisher) = &self.publisher else {
+            return;
+        };
+
+        let mut by_schema: BTreeMap<Uuid, Vec<&PredictedBreakage>> = BTreeMap::new();
+        for breakage in &report.breakages {
+            by_schema
+                .entry(breakage.candidate_schema_id)
+                .or_default()
+                .push(breakage);
+        }
+
+        for (schema_id, breakages) in by_schema {
+            let mut data = HashMap::new();
+            data.insert("namespace".to_string(), serde_json::json!(report.namespace));
+            data.insert("breakages".to_string(), serde_json::json!(breakages));
+
+            let event = SchemaEvent::new(
+                EventType::ConsumerBreakagePredicted,
+                schema_id,
+                breakages[0].candidate_version.clone(),
+                "compatibility-reverification".to_string(),
+                EventPayload::Generic { data },
+            );
+            if let Err(e) = publisher.publish(event).await {
+                tracing::warn!(schema_id = %schema_id, error = %e, "Failed to publish breakage notification");
+            }
+        }
+    }
+}
+
+/// Newest version that is active or about to be released
+fn release_candidate(versions: &[RegisteredSchema]) -> Option<&RegisteredSchema> {
+    versions
+        .iter()
+        .filter(|s| matches!(s.state, SchemaState::Active | SchemaState::Registered))
+        .max_by(|a, b| a.version.cmp(&b.version))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use schema_registry_core::{
+        error::Error,
+        schema::SchemaMetadata,
+        state::SchemaLifecycle,
+        traits::CompatibilityResult,
+        types::{SerializationFormat, ViolationSeverity, ViolationType},
+    };
+
+    fn schema(version: SemanticVersion, state: SchemaState) -> RegisteredSchema {
+        let id = Uuid::new_v4();
+        RegisteredSchema {
+            id,
+            namespace: "com.example".to_string(),
+            name: "User".to_string(),
+            version,
+            format: SerializationFormat::JsonSchema,
+            content: "{}".to_string(),
+            content_hash: Uuid::new_v4().to_string(),
+            description: String::new(),
+            compatibility_mode: CompatibilityMode::Backward,
+            state,
+            metadata: SchemaMetadata {
+                created_at: Utc::now(),
+                created_by: "test".to_string(),
+                updated_at: Utc::now(),
+                updated_by: "test".to_string(),
+                activated_at: None,
+                deprecation: None,
+                deletion: None,
+                custom: HashMap::new(),
+            },
+            tags: vec![],
+            examples: vec![],
+            lifecycle: SchemaLifecycle::new(id),
+        }
+    }
+
+    struct FixedStorage(Vec<RegisteredSchema>);
+
+    #[async_trait]
+    impl SchemaStorage for FixedStorage {
+        async fn store(&self, _schema: RegisteredSchema) -> Result<()> {
+            Ok(())
+        }
+        async fn retrieve(
+            &self,
+            id: Uuid,
+            _version: Option<SemanticVersion>,
+        ) -> Result<RegisteredSchema> {
+            Err(Error::SchemaNotFound(id.to_string()))
+        }
+        async fn retrieve_by_hash(&self, _content_hash: &str) -> Result<Option<RegisteredSchema>> {
+            Ok(None)
+        }
+        async fn update(&self, _schema: RegisteredSchema) -> Result<()> {
+            Ok(())
+        }
+        async fn delete(&self, _id: Uuid, _version: SemanticVersion) -> Result<()> {
+            Ok(())
+        }
+        async fn list_versions(&self, _id: Uuid) -> Result<Vec<SemanticVersion>> {
+            Ok(vec![])
+        }
+        async fn find_by_name(&self, namespace: &str, name: &str) -> Result<Vec<RegisteredSchema>> {
+            Ok(self
+                .0
+                .iter()
+                .filter(|s| s.namespace == namespace && s.name == name)
+                .cloned()
+                .collect())
+        }
+    }
+
+    /// Treats any major version bump as breaking
+    struct MajorBumpChecker;
+
+    #[async_trait]
+    impl CompatibilityChecker for MajorBumpChecker {
+        async fn check_compatibility(
+            &self,
+            new_schema: &RegisteredSchema,
+            old_schema: &RegisteredSchema,
+            mode: CompatibilityMode,
+        ) -> Result<CompatibilityResult> {
+            let breaking = new_schema.version.major > old_schema.version.major;
+            Ok(CompatibilityResult {
+                is_compatible: !breaking,
+                mode,
+                violations: if breaking {
+                    vec![CompatibilityViolation {
+                        violation_type: ViolationType::FieldRemoved,
+                        field_path: "$.email".to_string(),
+                        old_value: None,
+                        new_value: None,
+                        severity: ViolationSeverity::Breaking,
+                        description: "email removed".to_string(),
+                        suggestion: None,
+                    }]
+                } else {
+                    vec![]
+                },
+                exempted_violations: vec![],
+                checked_versions: vec![old_schema.version.clone()],
+            })
+        }
+
+        async fn check_transitive_compatibility(
+            &self,
+            new_schema: &RegisteredSchema,
+            previous_versions: &[RegisteredSchema],
+            mode: CompatibilityMode,
+        ) -> Result<CompatibilityResult> {
+            self.check_compatibility(new_schema, &previous_versions[0], mode)
+                .await
+        }
+    }
+
+    fn manifest(name: &str, pinned_version: SemanticVersion) -> ConsumerManifest {
+        ConsumerManifest {
+            consumer_id: Uuid::new_v4(),
+            consumer_name: name.to_string(),
+            owner: Some("team-data".to_string()),
+            dependencies: vec![PinnedDependency {
+                namespace: "com.example".to_string(),
+                name: "User".to_string(),
+                pinned_version,
+            }],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reports_consumers_that_will_break() {
+        let storage = Arc::new(FixedStorage(vec![
+            schema(SemanticVersion::new(1, 0, 0), SchemaState::Deprecated),
+            schema(SemanticVersion::new(1, 1, 0), SchemaState::Active),
+            schema(SemanticVersion::new(2, 0, 0), SchemaState::Registered),
+            schema(SemanticVersion::new(3, 0, 0), SchemaState::Draft),
+        ]));
+        let manifests = Arc::new(InMemoryManifestStore::new());
+        manifests
+            .register(manifest("billing", SemanticVersion::new(1, 0, 0)))
+            .await;
+        manifests
+            .register(manifest("search", SemanticVersion::new(2, 0, 0)))
+            .await;
+        manifests
+            .register(manifest("legacy", SemanticVersion::new(0, 9, 0)))
+            .await;
+
+        let job = ReverificationJob::new(storage, Arc::new(MajorBumpChecker), manifests);
+        let reports = job.run_once().await.unwrap();
+
+        assert_eq!(reports.len(), 1);
+        let report = &reports[0];
+        assert_eq!(report.namespace, "com.example");
+        assert_eq!(report.pins_checked, 3);
+
+        // Draft 3.0.0 is not a release candidate, so 2.0.0 is checked
+        assert_eq!(report.breakages.len(), 1);
+        assert_eq!(report.breakages[0].consumer_name, "billing");
+        assert_eq!(
+            report.breakages[0].candidate_version,
+            SemanticVersion::new(2, 0, 0)
+        );
+
+        assert_eq!(report.unresolved.len(), 1);
+        assert_eq!(report.unresolved[0].consumer_name, "legacy");
+    }
+
+    #[tokio::test]
+    async fn test_reports_are_delivered_to_sinks() {
+        struct CollectingSink(RwLock<Vec<String>>);
+
+        #[async_trait]
+        impl BreakageReportSink for CollectingSink {
+            async fn deliver(&self, report: &NamespaceBreakageReport) -> Result<()> {
+                self.0.write().await.push(report.namespace.clone());
+                Ok(())
+            }
+        }
+
+        let storage = Arc::new(FixedStorage(vec![schema(
+            SemanticVersion::new(1, 0, 0),
+            SchemaState::Active,
+        )]));
+        let manifests = Arc::new(InMemoryManifestStore::new());
+        manifests
+            .register(manifest("billing", SemanticVersion::new(1, 0, 0)))
+            .await;
+
+        let sink = Arc::new(CollectingSink(RwLock::new(vec![])));
+        let job = ReverificationJob::new(storage, Arc::new(MajorBumpChecker), manifests)
+            .with_sink(sink.clone());
+        let reports = job.run_once().await.unwrap();
+
+        assert!(!reports[0].has_breakages());
+        assert_eq!(*sink.0.read().await, vec!["com.example".to_string()]);
+    }
+}
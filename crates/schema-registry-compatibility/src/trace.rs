@@ -0,0 +1,222 @@
+//! Decision traces of compatibility checks
+//!
+//! A traced check records every rule it evaluates as a [`TraceStep`]: the
+//! rule, the place it examined and why it passed or failed there. Traces are
+//! for debugging contested results, so they are only built when asked for
+//! with [`CompatibilityCheckerImpl::explain`](crate::CompatibilityCheckerImpl::explain)
+//! and never cached.
+//!
+//! Rules are named after the part of the checker that evaluates them:
+//!
+//! - `json_schema.type`, `json_schema.enum`, `json_schema.constraint`,
+//!   `json_schema.required` and `json_schema.closed_properties` compare two
+//!   JSON Schemas
+//! - `avro.union_member`, `avro.type`, `avro.fixed_size`, `avro.field` and
+//!   `avro.enum_symbols` resolve Avro data
+//! - `cross_format.type`, `cross_format.field`, `cross_format.closed_record`
+//!   and `cross_format.enum_symbols` compare Avro with JSON Schema
+//! - `fields.removed` looks for fields of other formats missing from the new
+//!   schema, and `deprecation.notice` decides whether a removal is allowed
+//! - `content_hash`, `format` and `policy` record identical versions,
+//!   versions that aren't compared and rule levels set by the policy
+
+use crate::Direction;
+use schema_registry_core::{types::CompatibilityMode, versioning::SemanticVersion};
+use serde::{Deserialize, Serialize};
+
+/// Whether a rule held at the place it examined
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleOutcome {
+    Passed,
+    Failed,
+}
+
+/// Which schema read data written with the other when a rule was evaluated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TraceDirection {
+    /// The new schema read old data
+    Backward,
+    /// The old schema read new data
+    Forward,
+}
+
+impl From<Direction> for TraceDirection {
+    fn from(direction: Direction) -> Self {
+        if direction.reader_is_new {
+            TraceDirection::Backward
+        } else {
+            TraceDirection::Forward
+        }
+    }
+}
+
+/// One rule evaluated at one place
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TraceStep {
+    /// Rule evaluated, such as `json_schema.required`
+    pub rule: String,
+    /// Which schema read the other's data, for rules that resolve data
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub direction: Option<TraceDirection>,
+    /// RFC 6901 pointer to the examined place in documents of the schemas,
+    /// with `*` standing for any array element or map value
+    pub pointer: String,
+    pub outcome: RuleOutcome,
+    /// Why the rule passed or failed
+    pub reason: String,
+}
+
+/// Rules evaluated comparing the new schema with one previous version
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VersionTrace {
+    pub version: SemanticVersion,
+    /// Whether the new schema is compatible with this version
+    pub is_compatible: bool,
+    pub steps: Vec<TraceStep>,
+}
+
+/// Every rule a compatibility check evaluated, by previous version
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompatibilityTrace {
+    pub mode: CompatibilityMode,
+    pub versions: Vec<VersionTrace>,
+}
+
+impl CompatibilityTrace {
+    /// Steps that failed, with the version they were evaluated against
+    pub fn failures(&self) -> impl Iterator<Item = (&SemanticVersion, &TraceStep)> {
+        self.versions.iter().flat_map(|version| {
+            version
+                .steps
+                .iter()
+                .filter(|step| step.outcome == RuleOutcome::Failed)
+                .map(move |step| (&version.version, step))
+        })
+    }
+}
+
+/// Records steps when tracing, and nothing otherwise
+///
+/// Reasons are built lazily, so untraced checks don't pay for them.
+#[derive(Debug, Default)]
+pub(crate) struct Tracer {
+    steps: Option<Vec<TraceStep>>,
+}
+
+impl Tracer {
+    /// A tracer that records nothing
+    pub(crate) fn off() -> Self {
+        Self::default()
+    }
+
+    /// A tracer that records every step
+    pub(crate) fn on() -> Self {
+        Self {
+            steps: Some(Vec::new()),
+        }
+    }
+
+    pub(crate) fn is_on(&self) -> bool {
+        self.steps.is_some()
+    }
+
+    /// Record `rule` holding at field path `path`
+    pub(crate) fn passed(
+        &mut self,
+        rule: &str,
+        direction: Option<Direction>,
+        path: &str,
+        reason: impl FnOnce() -> String,
+    ) {
+        self.record(rule, direction, path, RuleOutcome::Passed, reason);
+    }
+
+    /// Record `rule` failing at field path `path`
+    pub(crate) fn failed(
+        &mut self,
+        rule: &str,
+        direction: Option<Direction>,
+        path: &str,
+        reason: impl FnOnce() -> String,
+    ) {
+        self.record(rule, direction, path, RuleOutcome::Failed, reason);
+    }
+
+    fn record(
+        &mut self,
+        rule: &str,
+        direction: Option<Direction>,
+        path: &str,
+        outcome: RuleOutcome,
+        reason: impl FnOnce() -> String,
+    ) {
+        if let Some(steps) = &mut self.steps {
+            steps.push(TraceStep {
+                rule: rule.to_string(),
+                direction: direction.map(TraceDirection::from),
+                pointer: pointer(path),
+                outcome,
+                reason: reason(),
+            });
+        }
+    }
+
+    pub(crate) fn into_steps(self) -> Vec<TraceStep> {
+        self.steps.unwrap_or_default()
+    }
+}
+
+/// The JSON pointer of a field path such as `orders[].id`
+fn pointer(path: &str) -> String {
+    let mut pointer = String::new();
+    if path.is_empty() {
+        return pointer;
+    }
+    for segment in path.split('.') {
+        let mut name = segment;
+        let mut wildcards = 0;
+        while let Some(rest) = name.strip_suffix("[]").or_else(|| name.strip_suffix("{}")) {
+            name = rest;
+            wildcards += 1;
+        }
+        if !name.is_empty() {
+            pointer.push('/');
+            pointer.push_str(&name.replace('~', "~0").replace('/', "~1"));
+        }
+        pointer.push_str(&"/*".repeat(wildcards));
+    }
+    pointer
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_field_paths_become_pointers() {
+        assert_eq!(pointer(""), "");
+        assert_eq!(pointer("name"), "/name");
+        assert_eq!(pointer("orders[].id"), "/orders/*/id");
+        assert_eq!(pointer("tags{}[]"), "/tags/*/*");
+        assert_eq!(pointer("[]"), "/*");
+        assert_eq!(pointer("a/b.c~d"), "/a~1b/c~0d");
+    }
+
+    #[test]
+    fn test_untraced_checks_record_nothing() {
+        let mut tracer = Tracer::off();
+        tracer.failed("json_schema.type", None, "id", || unreachable!());
+        assert!(!tracer.is_on());
+        assert!(tracer.into_steps().is_empty());
+
+        let mut tracer = Tracer::on();
+        tracer.passed("json_schema.type", Some(Direction::FORWARD), "id", || {
+            "accepted".to_string()
+        });
+        let steps = tracer.into_steps();
+        assert_eq!(steps[0].direction, Some(TraceDirection::Forward));
+        assert_eq!(steps[0].pointer, "/id");
+    }
+}
@@ -0,0 +1,295 @@
+//! Depth- and size-bounded JSON parsing
+//!
+//! `serde_json` builds the whole document before callers get a chance to look
+//! at it, so an untrusted multi-megabyte, deeply nested payload can exhaust
+//! memory or the stack. The functions here make a cheap single pass over the
+//! raw bytes first and reject documents that exceed the configured
+//! [`JsonLimits`] before any tree is built.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::config_manager_adapter::PerformanceThresholds;
+use crate::error::Error;
+
+/// Limits applied to untrusted JSON input
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JsonLimits {
+    /// Maximum document size in bytes
+    pub max_bytes: usize,
+    /// Maximum nesting depth of objects and arrays
+    pub max_depth: usize,
+    /// Maximum number of values and keys in the document
+    pub max_nodes: usize,
+    /// Maximum length of a single string in bytes
+    pub max_string_length: usize,
+}
+
+impl Default for JsonLimits {
+    fn default() -> Self {
+        Self::from(&PerformanceThresholds::default())
+    }
+}
+
+impl From<&PerformanceThresholds> for JsonLimits {
+    fn from(thresholds: &PerformanceThresholds) -> Self {
+        Self {
+            max_bytes: thresholds.max_json_bytes,
+            max_depth: thresholds.max_depth,
+            max_nodes: thresholds.max_json_nodes,
+            max_string_length: thresholds.max_string_length,
+        }
+    }
+}
+
+/// Statistics gathered while scanning a document
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JsonStats {
+    /// Document size in bytes
+    pub bytes: usize,
+    /// Deepest nesting level encountered
+    pub max_depth: usize,
+    /// Number of values and keys
+    pub nodes: usize,
+    /// Longest string in bytes
+    pub longest_string: usize,
+}
+
+/// Structured error returned when a document exceeds a limit
+#[derive(Debug, thiserror::Error)]
+pub enum JsonLimitError {
+    /// Document is larger than allowed
+    #[error("JSON document is {actual} bytes, exceeding the limit of {max} bytes")]
+    TooLarge {
+        /// Actual size
+        actual: usize,
+        /// Configured limit
+        max: usize,
+    },
+
+    /// Nesting is deeper than allowed
+    #[error("JSON nesting depth exceeds the limit of {max} at byte offset {offset}")]
+    TooDeep {
+        /// Configured limit
+        max: usize,
+        /// Byte offset where the limit was crossed
+        offset: usize,
+    },
+
+    /// Too many values and keys
+    #[error("JSON document contains more than {max} nodes")]
+    TooManyNodes {
+        /// Configured limit
+        max: usize,
+    },
+
+    /// A single string is longer than allowed
+    #[error("JSON string at byte offset {offset} exceeds the limit of {max} bytes")]
+    StringTooLong {
+        /// Configured limit
+        max: usize,
+        /// Byte offset where the string starts
+        offset: usize,
+    },
+
+    /// Document is within limits but is not valid JSON
+    #[error("Invalid JSON: {0}")]
+    Parse(#[from] serde_json::Error),
+}
+
+impl JsonLimitError {
+    /// Stable machine-readable code for the exceeded limit
+    pub fn code(&self) -> &'static str {
+        match self {
+            JsonLimitError::TooLarge { .. } => "json-size-exceeded",
+            JsonLimitError::TooDeep { .. } => "json-depth-exceeded",
+            JsonLimitError::TooManyNodes { .. } => "json-nodes-exceeded",
+            JsonLimitError::StringTooLong { .. } => "json-string-length-exceeded",
+            JsonLimitError::Parse(_) => "json-parse-error",
+        }
+    }
+
+    /// Whether the error was caused by a limit rather than malformed input
+    pub fn is_limit_exceeded(&self) -> bool {
+        !matches!(self, JsonLimitError::Parse(_))
+    }
+
+    /// The configured limit that was exceeded, if any
+    pub fn limit(&self) -> Option<usize> {
+        match self {
+            JsonLimitError::TooLarge { max, .. }
+            | JsonLimitError::TooDeep { max, .. }
+            | JsonLimitError::TooManyNodes { max }
+            | JsonLimitError::StringTooLong { max, .. } => Some(*max),
+            JsonLimitError::Parse(_) => None,
+        }
+    }
+}
+
+impl From<JsonLimitError> for Error {
+    fn from(e: JsonLimitError) -> Self {
+        match e {
+            JsonLimitError::Parse(e) => Error::ParseError(e.to_string()),
+            other => Error::LimitExceeded(other.to_string()),
+        }
+    }
+}
+
+/// Scan a document and enforce limits without building a value tree
+pub fn check(input: &[u8], limits: &JsonLimits) -> Result<JsonStats, JsonLimitError> {
+    if input.len() > limits.max_bytes {
+        return Err(JsonLimitError::TooLarge {
+            actual: input.len(),
+            max: limits.max_bytes,
+        });
+    }
+
+    let mut stats = JsonStats {
+        bytes: input.len(),
+        ..JsonStats::default()
+    };
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut in_scalar = false;
+    let mut string_start = 0usize;
+
+    for (offset, &byte) in input.iter().enumerate() {
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if byte == b'\\' {
+                escaped = true;
+            } else if byte == b'"' {
+                in_string = false;
+                let length = offset - string_start - 1;
+                stats.longest_string = stats.longest_string.max(length);
+            }
+
+            if in_string && offset - string_start > limits.max_string_length {
+                return Err(JsonLimitError::StringTooLong {
+                    max: limits.max_string_length,
+                    offset: string_start,
+                });
+            }
+            continue;
+        }
+
+        match byte {
+            b'{' | b'[' => {
+                depth += 1;
+                if depth > limits.max_depth {
+                    return Err(JsonLimitError::TooDeep {
+                        max: limits.max_depth,
+                        offset,
+                    });
+                }
+                stats.max_depth = stats.max_depth.max(depth);
+                stats.nodes += 1;
+                in_scalar = false;
+            }
+            b'}' | b']' => {
+                depth = depth.saturating_sub(1);
+                in_scalar = false;
+            }
+            b'"' => {
+                in_string = true;
+                string_start = offset;
+                stats.nodes += 1;
+                in_scalar = false;
+            }
+            b',' | b':' | b' ' | b'\t' | b'\n' | b'\r' => {
+                in_scalar = false;
+            }
+            _ => {
+                if !in_scalar {
+                    in_scalar = true;
+                    stats.nodes += 1;
+                }
+            }
+        }
+
+        if stats.nodes > limits.max_nodes {
+            return Err(JsonLimitError::TooManyNodes {
+                max: limits.max_nodes,
+            });
+        }
+    }
+
+    Ok(stats)
+}
+
+/// Parse a string into `T` after enforcing limits
+pub fn from_str<T: DeserializeOwned>(input: &str, limits: &JsonLimits) -> Result<T, JsonLimitError> {
+    from_slice(input.as_bytes(), limits)
+}
+
+/// Parse bytes into `T` after enforcing limits
+pub fn from_slice<T: DeserializeOwned>(input: &[u8], limits: &JsonLimits) -> Result<T, JsonLimitError> {
+    check(input, limits)?;
+    Ok(serde_json::from_slice(input)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits() -> JsonLimits {
+        JsonLimits {
+            max_bytes: 1024,
+            max_depth: 4,
+            max_nodes: 20,
+            max_string_length: 16,
+        }
+    }
+
+    #[test]
+    fn test_within_limits() {
+        let value: serde_json::Value =
+            from_str(r#"{"type": "object", "properties": {"a": {"type": "string"}}}"#, &limits())
+                .unwrap();
+        assert_eq!(value["type"], "object");
+
+        let stats = check(br#"{"a": [1, 2, true, null]}"#, &limits()).unwrap();
+        assert_eq!(stats.max_depth, 2);
+        assert_eq!(stats.nodes, 7);
+    }
+
+    #[test]
+    fn test_depth_limit() {
+        let err = check(b"[[[[[1]]]]]", &limits()).unwrap_err();
+        assert!(matches!(err, JsonLimitError::TooDeep { max: 4, offset: 4 }));
+        assert_eq!(err.code(), "json-depth-exceeded");
+    }
+
+    #[test]
+    fn test_brackets_inside_strings_are_ignored() {
+        let stats = check(br#"{"a": "[[[[[[\"]]]"}"#, &limits()).unwrap();
+        assert_eq!(stats.max_depth, 1);
+    }
+
+    #[test]
+    fn test_size_node_and_string_limits() {
+        let big = vec![b' '; 2048];
+        assert!(matches!(check(&big, &limits()), Err(JsonLimitError::TooLarge { .. })));
+
+        let many = format!("[{}]", vec!["1"; 30].join(","));
+        assert!(matches!(
+            check(many.as_bytes(), &limits()),
+            Err(JsonLimitError::TooManyNodes { max: 20 })
+        ));
+
+        let long = format!(r#"{{"a": "{}"}}"#, "x".repeat(32));
+        assert!(matches!(
+            check(long.as_bytes(), &limits()),
+            Err(JsonLimitError::StringTooLong { max: 16, .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_errors_are_not_limit_errors() {
+        let err = from_str::<serde_json::Value>("{ invalid }", &limits()).unwrap_err();
+        assert!(!err.is_limit_exceeded());
+        assert!(matches!(Error::from(err), Error::ParseError(_)));
+    }
+}
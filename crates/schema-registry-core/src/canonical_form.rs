@@ -0,0 +1,291 @@
+//! Canonical schema forms
+//!
+//! Registering the same schema with its keys reordered or its whitespace
+//! changed shouldn't create a new version. Content hashes are computed over
+//! a canonical form of the content, so such variants share a hash: the
+//! registry deduplicates them and the compatibility checker takes its
+//! identical-schema fast path.
+//!
+//! The canonical forms only drop what can't change meaning:
+//!
+//! - JSON Schema: object keys sorted, insignificant whitespace removed
+//! - Avro: as JSON Schema, plus names fully qualified (with `namespace`
+//!   folded into `name`) and `{"type": "int"}` written as `"int"`. Unlike
+//!   Avro's Parsing Canonical Form, `doc`, `default`, `aliases` and `order`
+//!   are kept, as defaults and aliases decide schema resolution.
+//! - Protobuf: comments removed and tokens separated by single spaces
+//!
+//! The stored content is only rewritten for namespaces with the `normalize`
+//! policy; otherwise only its hash is.
+
+use crate::canonical_json;
+use crate::error::{Error, Result};
+use crate::types::SerializationFormat;
+use serde_json::{Map, Value};
+
+const AVRO_PRIMITIVES: [&str; 8] = [
+    "null", "boolean", "int", "long", "float", "double", "bytes", "string",
+];
+
+/// The canonical form of `content`
+///
+/// Fails if JSON Schema or Avro content isn't JSON.
+pub fn canonicalize(format: SerializationFormat, content: &str) -> Result<String> {
+    match format {
+        SerializationFormat::JsonSchema => canonical_json_string(&parse_json(content)?),
+        SerializationFormat::Avro => {
+            let schema = avro_schema(parse_json(content)?, None);
+            canonical_json_string(&schema)
+        }
+        SerializationFormat::Protobuf => Ok(proto_tokens(content).join(" ")),
+    }
+}
+
+/// SHA-256 of the canonical form of `content`, hex encoded
+///
+/// Content without a canonical form is hashed as it is.
+pub fn content_hash(format: SerializationFormat, content: &str) -> String {
+    use sha2::{Digest, Sha256};
+    let canonical = canonicalize(format, content);
+    let bytes = match &canonical {
+        Ok(canonical) => canonical.as_bytes(),
+        Err(_) => content.as_bytes(),
+    };
+    hex::encode(Sha256::digest(bytes))
+}
+
+fn parse_json(content: &str) -> Result<Value> {
+    serde_json::from_str(content)
+        .map_err(|e| Error::ParseError(format!("Schema content is not JSON: {}", e)))
+}
+
+fn canonical_json_string(value: &Value) -> Result<String> {
+    canonical_json::to_string(value).map_err(|e| Error::SerializationError(e.to_string()))
+}
+
+/// Normalize the Avro schema at a schema position, within `namespace`
+fn avro_schema(schema: Value, namespace: Option<&str>) -> Value {
+    match schema {
+        Value::String(name) => Value::String(avro_reference(name, namespace)),
+        Value::Array(branches) => Value::Array(
+            branches
+                .into_iter()
+                .map(|branch| avro_schema(branch, namespace))
+                .collect(),
+        ),
+        Value::Object(object) => avro_object(object, namespace),
+        other => other,
+    }
+}
+
+fn avro_object(mut object: Map<String, Value>, namespace: Option<&str>) -> Value {
+    // {"type": "int"} is the primitive itself
+    if object.len() == 1 {
+        if let Some(Value::String(primitive)) = object.get("type") {
+            if AVRO_PRIMITIVES.contains(&primitive.as_str()) {
+                return Value::String(primitive.clone());
+            }
+        }
+    }
+
+    let kind = object.get("type").and_then(Value::as_str).map(str::to_string);
+    let named = matches!(kind.as_deref(), Some("record" | "error" | "enum" | "fixed"));
+    let mut inner_namespace = namespace.map(str::to_string);
+    if named {
+        if let Some(Value::String(name)) = object.get("name") {
+            let own_namespace = object.get("namespace").and_then(Value::as_str);
+            let fullname = match (name.contains('.'), own_namespace.or(namespace)) {
+                (false, Some(ns)) if !ns.is_empty() => format!("{}.{}", ns, name),
+                _ => name.clone(),
+            };
+            inner_namespace = fullname.rsplit_once('.').map(|(ns, _)| ns.to_string());
+            object.insert("name".to_string(), Value::String(fullname));
+            object.remove("namespace");
+        }
+    }
+    let inner_namespace = inner_namespace.as_deref();
+
+    match kind.as_deref() {
+        Some("record" | "error") => {
+            if let Some(Value::Array(fields)) = object.remove("fields") {
+                let fields = fields
+                    .into_iter()
+                    .map(|field| match field {
+                        Value::Object(mut field) => {
+                            if let Some(field_type) = field.remove("type") {
+                                field.insert(
+                                    "type".to_string(),
+                                    avro_schema(field_type, inner_namespace),
+                                );
+                            }
+                            Value::Object(field)
+                        }
+                        other => other,
+                    })
+                    .collect();
+                object.insert("fields".to_string(), Value::Array(fields));
+            }
+        }
+        Some("array") => {
+            if let Some(items) = object.remove("items") {
+                object.insert("items".to_string(), avro_schema(items, inner_namespace));
+            }
+        }
+        Some("map") => {
+            if let Some(values) = object.remove("values") {
+                object.insert("values".to_string(), avro_schema(values, inner_namespace));
+            }
+        }
+        Some(_) if named => {}
+        // A primitive with attributes, a reference, or a nested type as in
+        // {"type": {"type": "array", ...}}
+        _ => {
+            if let Some(inner) = object.remove("type") {
+                object.insert("type".to_string(), avro_schema(inner, namespace));
+            }
+        }
+    }
+    Value::Object(object)
+}
+
+/// Qualify a reference to a named type with the enclosing namespace
+fn avro_reference(name: String, namespace: Option<&str>) -> String {
+    match namespace {
+        Some(ns) if !ns.is_empty() && !name.contains('.') && !is_avro_keyword(&name) => {
+            format!("{}.{}", ns, name)
+        }
+        _ => name,
+    }
+}
+
+fn is_avro_keyword(name: &str) -> bool {
+    AVRO_PRIMITIVES.contains(&name)
+        || matches!(name, "record" | "error" | "enum" | "fixed" | "array" | "map")
+}
+
+/// The tokens of a `.proto` file, without comments
+fn proto_tokens(content: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut chars = content.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '/' if chars.peek() == Some(&'/') => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = '\0';
+                for c in chars.by_ref() {
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+            }
+            '"' | '\'' => {
+                let mut token = String::from(c);
+                while let Some(next) = chars.next() {
+                    token.push(next);
+                    if next == '\\' {
+                        if let Some(escaped) = chars.next() {
+                            token.push(escaped);
+                        }
+                    } else if next == c {
+                        break;
+                    }
+                }
+                tokens.push(token);
+            }
+            '{' | '}' | '[' | ']' | '(' | ')' | '<' | '>' | ';' | '=' | ',' | ':' => {
+                tokens.push(c.to_string());
+            }
+            c => {
+                let mut token = String::from(c);
+                while let Some(&next) = chars.peek() {
+                    if next.is_whitespace() || "{}[]()<>;=,:\"'/".contains(next) {
+                        break;
+                    }
+                    token.push(next);
+                    chars.next();
+                }
+                tokens.push(token);
+            }
+        }
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_schema_ignores_key_order_and_whitespace() {
+        let a = r#"{"type": "object", "properties": {"id": {"type": "string"}}}"#;
+        let b = "{\n  \"properties\": {\"id\": {\"type\": \"string\"}},\n  \"type\": \"object\"\n}";
+        assert_eq!(
+            content_hash(SerializationFormat::JsonSchema, a),
+            content_hash(SerializationFormat::JsonSchema, b)
+        );
+
+        let c = r#"{"type": "object", "properties": {"id": {"type": "integer"}}}"#;
+        assert_ne!(
+            content_hash(SerializationFormat::JsonSchema, a),
+            content_hash(SerializationFormat::JsonSchema, c)
+        );
+    }
+
+    #[test]
+    fn test_avro_names_and_primitives_are_normalized() {
+        let a = r#"{
+            "type": "record", "name": "Event", "namespace": "com.llm",
+            "fields": [
+                {"name": "id", "type": {"type": "string"}},
+                {"name": "next", "type": ["null", "Event"], "default": null}
+            ]
+        }"#;
+        let b = r#"{"name": "com.llm.Event", "type": "record", "fields": [
+            {"type": "string", "name": "id"},
+            {"default": null, "name": "next", "type": ["null", "com.llm.Event"]}
+        ]}"#;
+        assert_eq!(
+            canonicalize(SerializationFormat::Avro, a).unwrap(),
+            canonicalize(SerializationFormat::Avro, b).unwrap()
+        );
+
+        // Field order is part of the binary encoding, so it still counts
+        let swapped = r#"{"type": "record", "name": "com.llm.Event", "fields": [
+            {"name": "next", "type": ["null", "com.llm.Event"], "default": null},
+            {"name": "id", "type": "string"}
+        ]}"#;
+        assert_ne!(
+            canonicalize(SerializationFormat::Avro, a).unwrap(),
+            canonicalize(SerializationFormat::Avro, swapped).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_protobuf_ignores_comments_and_layout() {
+        let a = "syntax = \"proto3\";\n\n// An event\nmessage Event {\n  string id = 1; /* key */\n}\n";
+        let b = "syntax=\"proto3\"; message Event{string id=1;}";
+        assert_eq!(
+            canonicalize(SerializationFormat::Protobuf, a).unwrap(),
+            canonicalize(SerializationFormat::Protobuf, b).unwrap()
+        );
+        assert_eq!(
+            canonicalize(SerializationFormat::Protobuf, b).unwrap(),
+            "syntax = \"proto3\" ; message Event { string id = 1 ; }"
+        );
+    }
+
+    #[test]
+    fn test_unparsable_content_hashes_as_is() {
+        assert!(canonicalize(SerializationFormat::JsonSchema, "{not json").is_err());
+        assert_eq!(content_hash(SerializationFormat::JsonSchema, "{not json").len(), 64);
+    }
+}
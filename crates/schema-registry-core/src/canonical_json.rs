@@ -0,0 +1,137 @@
+//! Byte-stable JSON serialization
+//!
+//! API responses are diffed by downstream systems, so two responses for the
+//! same registry state must serialize identically. `HashMap` iterates in a
+//! per-process random order, and `serde_json::Map` keeps insertion order
+//! whenever any crate in the build enables serde_json's `preserve_order`
+//! feature. The helpers here write every object with its keys sorted,
+//! recursively, independent of both.
+//!
+//! Use them on map and [`Value`] fields of serialized models:
+//!
+//! ```
+//! use schema_registry_core::canonical_json;
+//! use serde::Serialize;
+//! use std::collections::HashMap;
+//!
+//! #[derive(Serialize)]
+//! struct Response {
+//!     #[serde(serialize_with = "canonical_json::sorted_value_map")]
+//!     metadata: HashMap<String, serde_json::Value>,
+//! }
+//! ```
+
+use serde::ser::{SerializeMap, SerializeSeq};
+use serde::{Serialize, Serializer};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// A JSON value that serializes with object keys in sorted order
+pub struct Canonical<'a>(pub &'a Value);
+
+impl Serialize for Canonical<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.0 {
+            Value::Object(object) => {
+                let mut entries: Vec<_> = object.iter().collect();
+                entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+                let mut map = serializer.serialize_map(Some(entries.len()))?;
+                for (key, value) in entries {
+                    map.serialize_entry(key, &Canonical(value))?;
+                }
+                map.end()
+            }
+            Value::Array(items) => {
+                let mut seq = serializer.serialize_seq(Some(items.len()))?;
+                for item in items {
+                    seq.serialize_element(&Canonical(item))?;
+                }
+                seq.end()
+            }
+            other => other.serialize(serializer),
+        }
+    }
+}
+
+/// `serialize_with` helper for [`Value`] fields
+pub fn value<S: Serializer>(value: &Value, serializer: S) -> Result<S::Ok, S::Error> {
+    Canonical(value).serialize(serializer)
+}
+
+/// `serialize_with` helper for `Option<Value>` fields
+pub fn optional_value<S: Serializer>(value: &Option<Value>, serializer: S) -> Result<S::Ok, S::Error> {
+    value.as_ref().map(Canonical).serialize(serializer)
+}
+
+/// `serialize_with` helper for `HashMap` fields, writing entries in key order
+pub fn sorted_map<S, K, V>(map: &HashMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    K: Serialize + Ord,
+    V: Serialize,
+{
+    let mut entries: Vec<_> = map.iter().collect();
+    entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+    let mut out = serializer.serialize_map(Some(entries.len()))?;
+    for (key, value) in entries {
+        out.serialize_entry(key, value)?;
+    }
+    out.end()
+}
+
+/// `serialize_with` helper for `HashMap<String, Value>` fields, sorting the
+/// map and every object nested in its values
+pub fn sorted_value_map<S: Serializer>(
+    map: &HashMap<String, Value>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let mut entries: Vec<_> = map.iter().collect();
+    entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+    let mut out = serializer.serialize_map(Some(entries.len()))?;
+    for (key, value) in entries {
+        out.serialize_entry(key, &Canonical(value))?;
+    }
+    out.end()
+}
+
+/// Serialize `value` to a canonical JSON string
+pub fn to_string(value: &Value) -> serde_json::Result<String> {
+    serde_json::to_string(&Canonical(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_nested_objects_are_sorted() {
+        let value = json!({"b": [{"z": 1, "a": 2}], "a": {"d": null, "c": true}});
+        assert_eq!(
+            to_string(&value).unwrap(),
+            r#"{"a":{"c":true,"d":null},"b":[{"a":2,"z":1}]}"#
+        );
+    }
+
+    #[test]
+    fn test_sorted_value_map_is_stable() {
+        #[derive(Serialize)]
+        struct Model {
+            #[serde(serialize_with = "sorted_value_map")]
+            metadata: HashMap<String, Value>,
+        }
+
+        // Each HashMap gets its own random iteration order
+        let model = |keys: Vec<i32>| Model {
+            metadata: keys
+                .into_iter()
+                .map(|i| (format!("key{:02}", i), json!({"y": i, "x": i})))
+                .collect(),
+        };
+        let first = serde_json::to_string(&model((0..32).collect())).unwrap();
+        let second = serde_json::to_string(&model((0..32).rev().collect())).unwrap();
+
+        assert_eq!(first, second);
+        assert!(first.starts_with(r#"{"metadata":{"key00":{"x":0,"y":0},"key01""#));
+    }
+}
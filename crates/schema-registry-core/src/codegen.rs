@@ -0,0 +1,1135 @@
+//! Typed bindings generated from schema content
+//!
+//! [`generate`] turns a registered schema into source code consumers can
+//! check in instead of copying the schema document: serde structs for Rust,
+//! pydantic models for Python and interfaces for TypeScript.
+//!
+//! JSON Schema objects, Avro records and Protobuf messages become types, and
+//! string enums, Avro enums and Protobuf enums become enums. Nested objects
+//! are named after their parent and field (`InferenceEventUsage`); `$ref`s to
+//! `$defs` and Avro named types keep their own names. Anything without a
+//! precise mapping (mixed unions, unknown references) is typed as arbitrary
+//! JSON rather than rejected. Protobuf messages keep their proto field names.
+
+use crate::error::{Error, Result};
+use crate::types::SerializationFormat;
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+
+/// Bumped whenever generated output changes, so cached bindings are rebuilt
+pub const GENERATOR_VERSION: u32 = 1;
+
+/// Target language of generated bindings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Language {
+    /// serde structs and enums
+    Rust,
+    /// pydantic models and `str` enums
+    Python,
+    /// interfaces and string literal unions
+    TypeScript,
+}
+
+impl Language {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Language::Rust => "rs",
+            Language::Python => "py",
+            Language::TypeScript => "ts",
+        }
+    }
+
+    /// Conventional file name for the bindings of schema `name`
+    pub fn file_name(&self, name: &str) -> String {
+        match self {
+            Language::Rust | Language::Python => format!("{}.{}", snake_case(name), self.extension()),
+            Language::TypeScript => format!("{}.{}", pascal_case(name), self.extension()),
+        }
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Language::Rust => write!(f, "rust"),
+            Language::Python => write!(f, "python"),
+            Language::TypeScript => write!(f, "ts"),
+        }
+    }
+}
+
+impl FromStr for Language {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "rust" | "rs" => Ok(Language::Rust),
+            "python" | "py" => Ok(Language::Python),
+            "ts" | "typescript" => Ok(Language::TypeScript),
+            other => Err(Error::ValidationError(format!(
+                "unsupported codegen language '{}': expected rust, python or ts",
+                other
+            ))),
+        }
+    }
+}
+
+/// Generate `language` bindings for schema content
+///
+/// `name` names the root type when the schema doesn't (a JSON Schema without
+/// a `title`) and appears in the generated file's header.
+pub fn generate(name: &str, content: &str, format: SerializationFormat, language: Language) -> Result<String> {
+    let mut model = Model::default();
+    match format {
+        SerializationFormat::JsonSchema => {
+            let schema: Value = serde_json::from_str(content)
+                .map_err(|e| Error::ParseError(format!("invalid JSON Schema: {}", e)))?;
+            let root = schema.get("title").and_then(Value::as_str).unwrap_or(name);
+            let mut reader = JsonSchemaReader {
+                root: &schema,
+                model: &mut model,
+                defined: HashMap::new(),
+                pending: None,
+            };
+            let (ty, _) = reader.read(&schema, &pascal_case(root));
+            if !matches!(ty, Ty::Named(_)) {
+                return Err(Error::ValidationError(
+                    "only JSON Schemas whose root is an object or enum can generate bindings".to_string(),
+                ));
+            }
+        }
+        SerializationFormat::Avro => {
+            let schema: Value = serde_json::from_str(content)
+                .map_err(|e| Error::ParseError(format!("invalid Avro schema: {}", e)))?;
+            AvroReader {
+                model: &mut model,
+                named: HashMap::new(),
+            }
+            .read(&schema);
+        }
+        SerializationFormat::Protobuf => read_protobuf(content, &mut model)?,
+    }
+
+    if model.types.is_empty() {
+        return Err(Error::ValidationError("schema declares no types to generate".to_string()));
+    }
+    let header = format!("Generated by the schema registry from {}. Do not edit.", name);
+    Ok(match language {
+        Language::Rust => render_rust(&model, &header),
+        Language::Python => render_python(&model, &header),
+        Language::TypeScript => render_typescript(&model, &header),
+    })
+}
+
+// ----------------------------------------------------------------------------
+// Model
+// ----------------------------------------------------------------------------
+
+#[derive(Debug, Clone, PartialEq)]
+enum Ty {
+    String,
+    Integer,
+    Number,
+    Boolean,
+    /// Arbitrary JSON
+    Any,
+    Array(Box<Ty>),
+    /// String-keyed map
+    Map(Box<Ty>),
+    Named(String),
+}
+
+#[derive(Debug)]
+struct Field {
+    name: String,
+    ty: Ty,
+    /// May be missing or null
+    optional: bool,
+    doc: Option<String>,
+    deprecated: bool,
+}
+
+#[derive(Debug)]
+enum Kind {
+    Record(Vec<Field>),
+    Enum(Vec<String>),
+}
+
+#[derive(Debug)]
+struct TypeDef {
+    name: String,
+    doc: Option<String>,
+    kind: Kind,
+}
+
+/// Types in dependency order, so each is declared before it's used
+#[derive(Debug, Default)]
+struct Model {
+    types: Vec<TypeDef>,
+    names: HashSet<String>,
+}
+
+impl Model {
+    /// Claim a type name, numbering it if it's already taken
+    fn claim(&mut self, name: &str) -> String {
+        let mut candidate = name.to_string();
+        let mut n = 2;
+        while !self.names.insert(candidate.clone()) {
+            candidate = format!("{}{}", name, n);
+            n += 1;
+        }
+        candidate
+    }
+}
+
+fn doc_of(map: &Map<String, Value>, key: &str) -> Option<String> {
+    map.get(key).and_then(Value::as_str).map(str::to_string)
+}
+
+// ----------------------------------------------------------------------------
+// Readers
+// ----------------------------------------------------------------------------
+
+struct JsonSchemaReader<'a> {
+    root: &'a Value,
+    model: &'a mut Model,
+    /// Types of the `$ref` targets read so far
+    defined: HashMap<String, Ty>,
+    /// Name claimed for the next record or enum, when reading a definition
+    pending: Option<String>,
+}
+
+impl JsonSchemaReader<'_> {
+    /// The type of `schema` and whether it admits null
+    fn read(&mut self, schema: &Value, hint: &str) -> (Ty, bool) {
+        let Value::Object(map) = schema else {
+            return (Ty::Any, false);
+        };
+
+        if let Some(reference) = map.get("$ref").and_then(Value::as_str) {
+            return (self.reference(reference), false);
+        }
+
+        for key in ["anyOf", "oneOf"] {
+            if let Some(Value::Array(options)) = map.get(key) {
+                let (nulls, rest): (Vec<&Value>, Vec<&Value>) = options
+                    .iter()
+                    .partition(|option| option.get("type").and_then(Value::as_str) == Some("null"));
+                return match rest.as_slice() {
+                    [only] => {
+                        let (ty, nullable) = self.read(only, hint);
+                        (ty, nullable || !nulls.is_empty())
+                    }
+                    _ => (Ty::Any, !nulls.is_empty()),
+                };
+            }
+        }
+
+        if let Some(Value::Array(values)) = map.get("enum") {
+            let nullable = values.iter().any(Value::is_null);
+            let symbols: Option<Vec<String>> = values
+                .iter()
+                .filter(|value| !value.is_null())
+                .map(|value| value.as_str().map(str::to_string))
+                .collect();
+            return match symbols {
+                Some(symbols) if !symbols.is_empty() => {
+                    let name = self.claim(hint);
+                    self.model.types.push(TypeDef {
+                        name: name.clone(),
+                        doc: doc_of(map, "description"),
+                        kind: Kind::Enum(symbols),
+                    });
+                    (Ty::Named(name), nullable)
+                }
+                _ => (Ty::Any, nullable),
+            };
+        }
+
+        let (type_name, nullable) = match map.get("type") {
+            Some(Value::String(name)) => (Some(name.as_str()), false),
+            Some(Value::Array(names)) => {
+                let names: Vec<&str> = names.iter().filter_map(Value::as_str).collect();
+                let nullable = names.contains(&"null");
+                let rest: Vec<&str> = names.into_iter().filter(|name| *name != "null").collect();
+                match rest.as_slice() {
+                    [only] => (Some(*only), nullable),
+                    _ => (None, nullable),
+                }
+            }
+            _ if map.contains_key("properties") => (Some("object"), false),
+            _ => (None, false),
+        };
+
+        let ty = match type_name {
+            Some("string") => Ty::String,
+            Some("integer") => Ty::Integer,
+            Some("number") => Ty::Number,
+            Some("boolean") => Ty::Boolean,
+            Some("array") => {
+                let items = map.get("items").map(|items| self.read(items, hint).0);
+                Ty::Array(Box::new(items.unwrap_or(Ty::Any)))
+            }
+            Some("object") => match map.get("properties") {
+                Some(Value::Object(properties)) => self.record(map, properties, hint),
+                _ => match map.get("additionalProperties") {
+                    Some(values @ Value::Object(_)) => Ty::Map(Box::new(self.read(values, hint).0)),
+                    _ => Ty::Map(Box::new(Ty::Any)),
+                },
+            },
+            _ => Ty::Any,
+        };
+        (ty, nullable)
+    }
+
+    fn claim(&mut self, hint: &str) -> String {
+        self.pending.take().unwrap_or_else(|| self.model.claim(hint))
+    }
+
+    fn record(&mut self, map: &Map<String, Value>, properties: &Map<String, Value>, hint: &str) -> Ty {
+        let name = self.claim(hint);
+        let required: HashSet<&str> = map
+            .get("required")
+            .and_then(Value::as_array)
+            .map(|required| required.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default();
+
+        let mut fields = Vec::new();
+        for (field_name, property) in properties {
+            let (ty, nullable) = self.read(property, &format!("{}{}", name, pascal_case(field_name)));
+            fields.push(Field {
+                name: field_name.clone(),
+                ty,
+                optional: nullable || !required.contains(field_name.as_str()),
+                doc: property.get("description").and_then(Value::as_str).map(str::to_string),
+                deprecated: property.get("deprecated").and_then(Value::as_bool) == Some(true)
+                    || property.get("x-deprecated").is_some(),
+            });
+        }
+        self.model.types.push(TypeDef {
+            name: name.clone(),
+            doc: doc_of(map, "description"),
+            kind: Kind::Record(fields),
+        });
+        Ty::Named(name)
+    }
+
+    fn reference(&mut self, reference: &str) -> Ty {
+        let Some(target) = reference
+            .strip_prefix("#/$defs/")
+            .or_else(|| reference.strip_prefix("#/definitions/"))
+        else {
+            return Ty::Any;
+        };
+        let container = if reference.starts_with("#/$defs/") { "$defs" } else { "definitions" };
+        let root = self.root;
+        let Some(schema) = root.get(container).and_then(|defs| defs.get(target)) else {
+            return Ty::Any;
+        };
+
+        if let Some(ty) = self.defined.get(reference) {
+            return ty.clone();
+        }
+        // Claim the name first, so recursive references resolve to it
+        let name = self.model.claim(&pascal_case(target));
+        self.defined.insert(reference.to_string(), Ty::Named(name.clone()));
+        self.pending = Some(name.clone());
+        let (ty, _) = self.read(schema, &name);
+        self.pending = None;
+        self.defined.insert(reference.to_string(), ty.clone());
+        ty
+    }
+}
+
+struct AvroReader<'a> {
+    model: &'a mut Model,
+    /// Generated names of the named types read so far, by full and short name
+    named: HashMap<String, String>,
+}
+
+impl AvroReader<'_> {
+    /// The type of `schema` and whether it admits null
+    fn read(&mut self, schema: &Value) -> (Ty, bool) {
+        match schema {
+            Value::String(name) => (self.primitive(name), name == "null"),
+            Value::Array(union) => {
+                let nullable = union.iter().any(|branch| branch.as_str() == Some("null"));
+                let rest: Vec<&Value> = union.iter().filter(|branch| branch.as_str() != Some("null")).collect();
+                match rest.as_slice() {
+                    [only] => (self.read(only).0, nullable),
+                    _ => {
+                        // Still declare the named types of every branch
+                        for branch in rest {
+                            self.read(branch);
+                        }
+                        (Ty::Any, nullable)
+                    }
+                }
+            }
+            Value::Object(map) => {
+                let ty = match map.get("type") {
+                    Some(Value::String(kind)) => match kind.as_str() {
+                        "record" | "error" => self.record(map),
+                        "enum" => self.enumeration(map),
+                        "array" => Ty::Array(Box::new(map.get("items").map_or(Ty::Any, |items| self.read(items).0))),
+                        "map" => Ty::Map(Box::new(map.get("values").map_or(Ty::Any, |values| self.read(values).0))),
+                        "fixed" => Ty::String,
+                        other => self.primitive(other),
+                    },
+                    Some(nested) => return self.read(nested),
+                    None => Ty::Any,
+                };
+                (ty, false)
+            }
+            _ => (Ty::Any, false),
+        }
+    }
+
+    fn primitive(&self, name: &str) -> Ty {
+        match name {
+            "string" | "bytes" => Ty::String,
+            "int" | "long" => Ty::Integer,
+            "float" | "double" => Ty::Number,
+            "boolean" => Ty::Boolean,
+            "null" => Ty::Any,
+            // A reference to a named type declared earlier
+            named => self.named.get(named).map_or(Ty::Any, |name| Ty::Named(name.clone())),
+        }
+    }
+
+    fn type_name(&mut self, map: &Map<String, Value>) -> String {
+        let full = map.get("name").and_then(Value::as_str).unwrap_or("Record");
+        let short = full.rsplit('.').next().unwrap_or(full);
+        let name = self.model.claim(&pascal_case(short));
+        self.named.insert(full.to_string(), name.clone());
+        self.named.insert(short.to_string(), name.clone());
+        name
+    }
+
+    fn record(&mut self, map: &Map<String, Value>) -> Ty {
+        let name = self.type_name(map);
+        let mut fields = Vec::new();
+        for field in map.get("fields").and_then(Value::as_array).into_iter().flatten() {
+            let Some(field_name) = field.get("name").and_then(Value::as_str) else {
+                continue;
+            };
+            let (ty, nullable) = field.get("type").map_or((Ty::Any, false), |ty| self.read(ty));
+            fields.push(Field {
+                name: field_name.to_string(),
+                ty,
+                optional: nullable,
+                doc: field.get("doc").and_then(Value::as_str).map(str::to_string),
+                deprecated: field
+                    .get("deprecated")
+                    .is_some_and(|deprecated| deprecated.is_object() || deprecated.as_bool() == Some(true)),
+            });
+        }
+        self.model.types.push(TypeDef {
+            name: name.clone(),
+            doc: doc_of(map, "doc"),
+            kind: Kind::Record(fields),
+        });
+        Ty::Named(name)
+    }
+
+    fn enumeration(&mut self, map: &Map<String, Value>) -> Ty {
+        let name = self.type_name(map);
+        let symbols = map
+            .get("symbols")
+            .and_then(Value::as_array)
+            .map(|symbols| symbols.iter().filter_map(Value::as_str).map(str::to_string).collect())
+            .unwrap_or_default();
+        self.model.types.push(TypeDef {
+            name: name.clone(),
+            doc: doc_of(map, "doc"),
+            kind: Kind::Enum(symbols),
+        });
+        Ty::Named(name)
+    }
+}
+
+/// Split `.proto` content into tokens, dropping comments
+fn proto_tokens(content: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut chars = content.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '/' if chars.peek() == Some(&'/') => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut last = ' ';
+                for c in chars.by_ref() {
+                    if last == '*' && c == '/' {
+                        break;
+                    }
+                    last = c;
+                }
+            }
+            '"' | '\'' => {
+                let mut text = String::from(c);
+                for next in chars.by_ref() {
+                    text.push(next);
+                    if next == c {
+                        break;
+                    }
+                }
+                tokens.push(text);
+            }
+            c if c.is_whitespace() => {}
+            c if c.is_alphanumeric() || c == '_' || c == '.' => {
+                let mut word = String::from(c);
+                while let Some(&next) = chars.peek() {
+                    if next.is_alphanumeric() || next == '_' || next == '.' {
+                        word.push(next);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                tokens.push(word);
+            }
+            c => tokens.push(c.to_string()),
+        }
+    }
+    tokens
+}
+
+fn read_protobuf(content: &str, model: &mut Model) -> Result<()> {
+    let tokens = proto_tokens(content);
+    // Declare every message and enum name up front, so fields can refer to
+    // types declared later in the file
+    let declared: HashSet<String> = tokens
+        .windows(2)
+        .filter(|pair| pair[0] == "message" || pair[0] == "enum")
+        .map(|pair| pair[1].clone())
+        .collect();
+
+    let mut reader = ProtoReader {
+        tokens: &tokens,
+        pos: 0,
+        declared: &declared,
+        model,
+    };
+    while reader.pos < tokens.len() {
+        match reader.next() {
+            Some("message") => reader.message()?,
+            Some("enum") => reader.enumeration()?,
+            // syntax, package, import, option and service declarations
+            _ => reader.skip_statement(),
+        }
+    }
+    Ok(())
+}
+
+struct ProtoReader<'a> {
+    tokens: &'a [String],
+    pos: usize,
+    declared: &'a HashSet<String>,
+    model: &'a mut Model,
+}
+
+impl<'a> ProtoReader<'a> {
+    fn next(&mut self) -> Option<&'a str> {
+        let tokens = self.tokens;
+        let token = tokens.get(self.pos)?;
+        self.pos += 1;
+        Some(token)
+    }
+
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.pos).map(String::as_str)
+    }
+
+    fn expect(&mut self, expected: &str) -> Result<()> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            other => Err(Error::ParseError(format!(
+                "invalid Protobuf schema: expected '{}', found '{}'",
+                expected,
+                other.unwrap_or("end of file")
+            ))),
+        }
+    }
+
+    fn ident(&mut self) -> Result<String> {
+        self.next()
+            .map(str::to_string)
+            .ok_or_else(|| Error::ParseError("invalid Protobuf schema: unexpected end of file".to_string()))
+    }
+
+    /// Skip to the end of a statement or block
+    fn skip_statement(&mut self) {
+        let mut depth = 0usize;
+        while let Some(token) = self.next() {
+            match token {
+                "{" => depth += 1,
+                "}" if depth <= 1 => return,
+                "}" => depth -= 1,
+                ";" if depth == 0 => return,
+                _ => {}
+            }
+        }
+    }
+
+    fn scalar(&self, name: &str) -> Ty {
+        match name {
+            "string" | "bytes" => Ty::String,
+            "double" | "float" => Ty::Number,
+            "bool" => Ty::Boolean,
+            "int32" | "int64" | "uint32" | "uint64" | "sint32" | "sint64" | "fixed32" | "fixed64"
+            | "sfixed32" | "sfixed64" => Ty::Integer,
+            named => {
+                let short = named.rsplit('.').next().unwrap_or(named);
+                if self.declared.contains(short) {
+                    Ty::Named(pascal_case(short))
+                } else {
+                    Ty::Any
+                }
+            }
+        }
+    }
+
+    fn message(&mut self) -> Result<()> {
+        let name = pascal_case(&self.ident()?);
+        self.model.names.insert(name.clone());
+        self.expect("{")?;
+        let fields = self.fields(false)?;
+        self.model.types.push(TypeDef {
+            name,
+            doc: None,
+            kind: Kind::Record(fields),
+        });
+        Ok(())
+    }
+
+    /// Fields up to the closing brace, including nested declarations
+    fn fields(&mut self, in_oneof: bool) -> Result<Vec<Field>> {
+        let mut fields = Vec::new();
+        loop {
+            let token = self.ident()?;
+            match token.as_str() {
+                "}" => return Ok(fields),
+                ";" => {}
+                "message" => self.message()?,
+                "enum" => self.enumeration()?,
+                "oneof" => {
+                    self.ident()?;
+                    self.expect("{")?;
+                    fields.extend(self.fields(true)?);
+                }
+                "option" | "reserved" | "extensions" | "extend" => {
+                    self.pos -= 1;
+                    self.skip_statement();
+                }
+                _ => {
+                    let (label, type_name) = match token.as_str() {
+                        "repeated" | "optional" | "required" => (Some(token.clone()), self.ident()?),
+                        _ => (None, token),
+                    };
+                    let ty = if type_name == "map" {
+                        self.expect("<")?;
+                        self.ident()?;
+                        self.expect(",")?;
+                        let values = self.ident()?;
+                        self.expect(">")?;
+                        Ty::Map(Box::new(self.scalar(&values)))
+                    } else {
+                        self.scalar(&type_name)
+                    };
+                    let field_name = self.ident()?;
+
+                    let mut deprecated = false;
+                    while let Some(token) = self.next() {
+                        match token {
+                            ";" => break,
+                            "deprecated" => {
+                                deprecated = self.tokens.get(self.pos + 1).is_some_and(|value| value == "true");
+                            }
+                            _ => {}
+                        }
+                    }
+
+                    let repeated = label.as_deref() == Some("repeated");
+                    let optional = in_oneof || label.as_deref() == Some("optional") || matches!(ty, Ty::Named(_));
+                    fields.push(Field {
+                        name: field_name,
+                        ty: if repeated { Ty::Array(Box::new(ty)) } else { ty },
+                        optional,
+                        doc: None,
+                        deprecated,
+                    });
+                }
+            }
+        }
+    }
+
+    fn enumeration(&mut self) -> Result<()> {
+        let name = pascal_case(&self.ident()?);
+        let name = self.model.claim(&name);
+        self.expect("{")?;
+        let mut symbols = Vec::new();
+        loop {
+            match self.peek() {
+                Some("}") => {
+                    self.pos += 1;
+                    break;
+                }
+                Some("option") | Some("reserved") => self.skip_statement(),
+                Some(_) => {
+                    symbols.push(self.ident()?);
+                    self.skip_statement();
+                }
+                None => return Err(Error::ParseError("invalid Protobuf schema: unclosed enum".to_string())),
+            }
+        }
+        self.model.types.push(TypeDef {
+            name,
+            doc: None,
+            kind: Kind::Enum(symbols),
+        });
+        Ok(())
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Names
+// ----------------------------------------------------------------------------
+
+/// Words of an identifier in any casing: `modelID-v2` is `model`, `ID`, `v2`
+fn words(name: &str) -> Vec<String> {
+    let chars: Vec<char> = name.chars().collect();
+    let mut words = Vec::new();
+    let mut current = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        if c.is_uppercase() && !current.is_empty() {
+            let prev = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|next| next.is_lowercase());
+            if prev.is_lowercase() || prev.is_ascii_digit() || (prev.is_uppercase() && next_is_lower) {
+                words.push(std::mem::take(&mut current));
+            }
+        }
+        current.push(c);
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+fn pascal_case(name: &str) -> String {
+    let pascal: String = words(name)
+        .iter()
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars.flat_map(char::to_lowercase)).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect();
+    match pascal.chars().next() {
+        None => "Type".to_string(),
+        Some(first) if first.is_ascii_digit() => format!("T{}", pascal),
+        Some(_) => pascal,
+    }
+}
+
+fn snake_case(name: &str) -> String {
+    let snake = words(name)
+        .iter()
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<_>>()
+        .join("_");
+    match snake.chars().next() {
+        None => "field".to_string(),
+        Some(first) if first.is_ascii_digit() => format!("_{}", snake),
+        Some(_) => snake,
+    }
+}
+
+/// `name`, suffixed with `_` if it is reserved
+fn unreserved(name: String, reserved: &[&str]) -> String {
+    if reserved.contains(&name.as_str()) {
+        format!("{}_", name)
+    } else {
+        name
+    }
+}
+
+const RUST_KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let", "loop", "macro",
+    "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return", "self", "Self", "static",
+    "struct", "super", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use", "virtual", "where",
+    "while", "yield",
+];
+
+/// Python keywords, plus names that would shadow `BaseModel` members
+const PYTHON_RESERVED: &[&str] = &[
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue", "def",
+    "del", "elif", "else", "except", "finally", "for", "from", "global", "if", "import", "in", "is", "lambda",
+    "nonlocal", "not", "or", "pass", "raise", "return", "try", "while", "with", "yield", "construct", "copy",
+    "dict", "json", "model_config", "model_fields", "schema", "validate",
+];
+
+fn doc_lines(out: &mut String, indent: &str, prefix: &str, doc: Option<&str>, deprecated: bool) {
+    if let Some(doc) = doc {
+        for line in doc.lines() {
+            if line.trim().is_empty() {
+                out.push_str(&format!("{}{}\n", indent, prefix.trim_end()));
+            } else {
+                out.push_str(&format!("{}{}{}\n", indent, prefix, line));
+            }
+        }
+    }
+    if deprecated {
+        out.push_str(&format!("{}{}Deprecated.\n", indent, prefix));
+    }
+}
+
+/// A string literal valid in both Python and TypeScript
+fn quoted(text: &str) -> String {
+    serde_json::to_string(text).unwrap_or_default()
+}
+
+fn python_docstring(doc: &str) -> String {
+    format!("    \"\"\"{}\"\"\"\n\n", doc.replace('\\', "\\\\").replace("\"\"\"", "\\\"\\\"\\\""))
+}
+
+// ----------------------------------------------------------------------------
+// Renderers
+// ----------------------------------------------------------------------------
+
+fn rust_type(ty: &Ty) -> String {
+    match ty {
+        Ty::String => "String".to_string(),
+        Ty::Integer => "i64".to_string(),
+        Ty::Number => "f64".to_string(),
+        Ty::Boolean => "bool".to_string(),
+        Ty::Any => "serde_json::Value".to_string(),
+        Ty::Array(items) => format!("Vec<{}>", rust_type(items)),
+        Ty::Map(values) => format!("std::collections::HashMap<String, {}>", rust_type(values)),
+        Ty::Named(name) => name.clone(),
+    }
+}
+
+fn render_rust(model: &Model, header: &str) -> String {
+    let mut out = format!("// {}\n\nuse serde::{{Deserialize, Serialize}};\n", header);
+    for def in &model.types {
+        out.push('\n');
+        doc_lines(&mut out, "", "/// ", def.doc.as_deref(), false);
+        match &def.kind {
+            Kind::Record(fields) => {
+                out.push_str("#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]\n");
+                out.push_str(&format!("pub struct {} {{\n", def.name));
+                for field in fields {
+                    doc_lines(&mut out, "    ", "/// ", field.doc.as_deref(), field.deprecated);
+                    let ident = unreserved(snake_case(&field.name), RUST_KEYWORDS);
+                    let mut serde = Vec::new();
+                    if ident != field.name {
+                        serde.push(format!("rename = {:?}", field.name));
+                    }
+                    let mut ty = rust_type(&field.ty);
+                    if field.optional {
+                        serde.push("default, skip_serializing_if = \"Option::is_none\"".to_string());
+                        ty = format!("Option<{}>", ty);
+                    }
+                    if !serde.is_empty() {
+                        out.push_str(&format!("    #[serde({})]\n", serde.join(", ")));
+                    }
+                    out.push_str(&format!("    pub {}: {},\n", ident, ty));
+                }
+                out.push_str("}\n");
+            }
+            Kind::Enum(symbols) => {
+                out.push_str("#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]\n");
+                out.push_str(&format!("pub enum {} {{\n", def.name));
+                let mut seen = HashSet::new();
+                for symbol in symbols {
+                    let mut variant = pascal_case(symbol);
+                    while !seen.insert(variant.clone()) {
+                        variant.push('_');
+                    }
+                    if variant != *symbol {
+                        out.push_str(&format!("    #[serde(rename = {:?})]\n", symbol));
+                    }
+                    out.push_str(&format!("    {},\n", variant));
+                }
+                out.push_str("}\n");
+            }
+        }
+    }
+    out
+}
+
+fn python_type(ty: &Ty) -> String {
+    match ty {
+        Ty::String => "str".to_string(),
+        Ty::Integer => "int".to_string(),
+        Ty::Number => "float".to_string(),
+        Ty::Boolean => "bool".to_string(),
+        Ty::Any => "Any".to_string(),
+        Ty::Array(items) => format!("List[{}]", python_type(items)),
+        Ty::Map(values) => format!("Dict[str, {}]", python_type(values)),
+        Ty::Named(name) => name.clone(),
+    }
+}
+
+fn render_python(model: &Model, header: &str) -> String {
+    let mut out = format!(
+        "# {}\n\nfrom __future__ import annotations\n\nfrom enum import Enum\nfrom typing import Any, Dict, List, Optional\n\nfrom pydantic import BaseModel, Field\n",
+        header
+    );
+    for def in &model.types {
+        out.push_str("\n\n");
+        match &def.kind {
+            Kind::Record(fields) => {
+                out.push_str(&format!("class {}(BaseModel):\n", def.name));
+                if let Some(doc) = &def.doc {
+                    out.push_str(&python_docstring(doc));
+                }
+                if fields.is_empty() {
+                    out.push_str("    pass\n");
+                }
+                for field in fields {
+                    if field.deprecated {
+                        out.push_str("    # Deprecated.\n");
+                    }
+                    let ident = unreserved(snake_case(&field.name), PYTHON_RESERVED);
+                    let mut args = Vec::new();
+                    if field.optional {
+                        args.push("default=None".to_string());
+                    }
+                    if ident != field.name {
+                        args.push(format!("alias={}", quoted(&field.name)));
+                    }
+                    if let Some(doc) = &field.doc {
+                        args.push(format!("description={}", quoted(doc)));
+                    }
+                    let ty = if field.optional {
+                        format!("Optional[{}]", python_type(&field.ty))
+                    } else {
+                        python_type(&field.ty)
+                    };
+                    let default = match args.as_slice() {
+                        [] => String::new(),
+                        [only] if only == "default=None" => " = None".to_string(),
+                        _ => format!(" = Field({})", args.join(", ")),
+                    };
+                    out.push_str(&format!("    {}: {}{}\n", ident, ty, default));
+                }
+            }
+            Kind::Enum(symbols) => {
+                out.push_str(&format!("class {}(str, Enum):\n", def.name));
+                if let Some(doc) = &def.doc {
+                    out.push_str(&python_docstring(doc));
+                }
+                if symbols.is_empty() {
+                    out.push_str("    pass\n");
+                }
+                let mut seen = HashSet::new();
+                for symbol in symbols {
+                    let mut member = unreserved(snake_case(symbol).to_uppercase(), PYTHON_RESERVED);
+                    while !seen.insert(member.clone()) {
+                        member.push('_');
+                    }
+                    out.push_str(&format!("    {} = {}\n", member, quoted(symbol)));
+                }
+            }
+        }
+    }
+    out
+}
+
+fn typescript_type(ty: &Ty) -> String {
+    match ty {
+        Ty::String => "string".to_string(),
+        Ty::Integer | Ty::Number => "number".to_string(),
+        Ty::Boolean => "boolean".to_string(),
+        Ty::Any => "unknown".to_string(),
+        Ty::Array(items) => format!("{}[]", typescript_type(items)),
+        Ty::Map(values) => format!("Record<string, {}>", typescript_type(values)),
+        Ty::Named(name) => name.clone(),
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_' || first == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+fn render_typescript(model: &Model, header: &str) -> String {
+    let mut out = format!("// {}\n", header);
+    for def in &model.types {
+        out.push('\n');
+        if let Some(doc) = &def.doc {
+            out.push_str(&format!("/** {} */\n", doc.replace("*/", "*\\/")));
+        }
+        match &def.kind {
+            Kind::Record(fields) => {
+                out.push_str(&format!("export interface {} {{\n", def.name));
+                for field in fields {
+                    let mut tags = Vec::new();
+                    if let Some(doc) = &field.doc {
+                        tags.push(doc.replace("*/", "*\\/"));
+                    }
+                    if field.deprecated {
+                        tags.push("@deprecated".to_string());
+                    }
+                    if !tags.is_empty() {
+                        out.push_str(&format!("  /** {} */\n", tags.join(" ")));
+                    }
+                    let key = if is_identifier(&field.name) {
+                        field.name.clone()
+                    } else {
+                        quoted(&field.name)
+                    };
+                    if field.optional {
+                        out.push_str(&format!("  {}?: {} | null;\n", key, typescript_type(&field.ty)));
+                    } else {
+                        out.push_str(&format!("  {}: {};\n", key, typescript_type(&field.ty)));
+                    }
+                }
+                out.push_str("}\n");
+            }
+            Kind::Enum(symbols) => {
+                let members: Vec<String> = symbols.iter().map(|symbol| quoted(symbol)).collect();
+                let members = if members.is_empty() { "never".to_string() } else { members.join(" | ") };
+                out.push_str(&format!("export type {} = {};\n", def.name, members));
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EVENT: &str = r#"{
+        "title": "InferenceEvent",
+        "description": "One model invocation",
+        "type": "object",
+        "properties": {
+            "modelId": {"type": "string", "description": "Model that served the request"},
+            "latencyMs": {"type": ["integer", "null"]},
+            "provider": {"enum": ["open_ai", "anthropic"]},
+            "usage": {
+                "type": "object",
+                "properties": {"promptTokens": {"type": "integer"}},
+                "required": ["promptTokens"]
+            },
+            "tags": {"type": "array", "items": {"type": "string"}},
+            "type": {"type": "string", "deprecated": true}
+        },
+        "required": ["modelId", "provider", "usage", "type"]
+    }"#;
+
+    #[test]
+    fn test_rust_bindings_from_json_schema() {
+        let code = generate("telemetry.InferenceEvent", EVENT, SerializationFormat::JsonSchema, Language::Rust).unwrap();
+
+        assert!(code.starts_with("// Generated by the schema registry from telemetry.InferenceEvent. Do not edit.\n"));
+        assert!(code.contains("pub enum InferenceEventProvider {\n    #[serde(rename = \"open_ai\")]\n    OpenAi,"));
+        assert!(code.contains("pub struct InferenceEventUsage {\n    #[serde(rename = \"promptTokens\")]\n    pub prompt_tokens: i64,\n}"));
+        assert!(code.contains(
+            "/// One model invocation\n#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]\npub struct InferenceEvent {"
+        ));
+        assert!(code.contains(
+            "    #[serde(rename = \"latencyMs\", default, skip_serializing_if = \"Option::is_none\")]\n    pub latency_ms: Option<i64>,"
+        ));
+        assert!(code.contains("    /// Model that served the request\n    #[serde(rename = \"modelId\")]\n    pub model_id: String,"));
+        assert!(code.contains("    /// Deprecated.\n    #[serde(rename = \"type\")]\n    pub type_: String,"));
+        assert!(code.contains("    pub tags: Option<Vec<String>>,"));
+        // Nested types come first
+        assert!(code.find("pub struct InferenceEventUsage").unwrap() < code.find("pub struct InferenceEvent {").unwrap());
+    }
+
+    #[test]
+    fn test_python_and_typescript_bindings() {
+        let python = generate("InferenceEvent", EVENT, SerializationFormat::JsonSchema, Language::Python).unwrap();
+        assert!(python.contains("class InferenceEventProvider(str, Enum):\n    OPEN_AI = \"open_ai\"\n    ANTHROPIC = \"anthropic\"\n"));
+        assert!(python.contains("class InferenceEvent(BaseModel):\n    \"\"\"One model invocation\"\"\"\n\n"));
+        assert!(python.contains(
+            "    model_id: str = Field(alias=\"modelId\", description=\"Model that served the request\")\n"
+        ));
+        assert!(python.contains("    latency_ms: Optional[int] = Field(default=None, alias=\"latencyMs\")\n"));
+        assert!(python.contains("    tags: Optional[List[str]] = None\n"));
+        assert!(python.contains("    # Deprecated.\n    type: str\n"));
+
+        let ts = generate("InferenceEvent", EVENT, SerializationFormat::JsonSchema, Language::TypeScript).unwrap();
+        assert!(ts.contains("export type InferenceEventProvider = \"open_ai\" | \"anthropic\";\n"));
+        assert!(ts.contains("/** One model invocation */\nexport interface InferenceEvent {\n"));
+        assert!(ts.contains("  latencyMs?: number | null;\n"));
+        assert!(ts.contains("  /** @deprecated */\n  type: string;\n"));
+        assert!(ts.contains("  usage: InferenceEventUsage;\n"));
+    }
+
+    #[test]
+    fn test_avro_and_protobuf_bindings() {
+        let avro = r#"{
+            "type": "record",
+            "name": "com.example.User",
+            "doc": "A user",
+            "fields": [
+                {"name": "id", "type": "long"},
+                {"name": "email", "type": ["null", "string"], "default": null},
+                {"name": "status", "type": {"type": "enum", "name": "Status", "symbols": ["ACTIVE", "BANNED"]}},
+                {"name": "previous", "type": ["null", "Status"]},
+                {"name": "labels", "type": {"type": "map", "values": "string"}}
+            ]
+        }"#;
+        let ts = generate("com.example.User", avro, SerializationFormat::Avro, Language::TypeScript).unwrap();
+        assert!(ts.contains("export type Status = \"ACTIVE\" | \"BANNED\";\n"));
+        assert!(ts.contains("/** A user */\nexport interface User {\n  id: number;\n  email?: string | null;\n"));
+        assert!(ts.contains("  previous?: Status | null;\n  labels: Record<string, string>;\n"));
+
+        let proto = r#"
+            syntax = "proto3";
+            package telemetry;
+
+            // Token counts
+            message Usage {
+                int32 prompt_tokens = 1;
+                repeated string models = 2 [deprecated = true];
+                map<string, double> costs = 3;
+                Kind kind = 4;
+                enum Kind { KIND_UNSPECIFIED = 0; CHAT = 1; }
+            }
+        "#;
+        let rust = generate("telemetry.Usage", proto, SerializationFormat::Protobuf, Language::Rust).unwrap();
+        assert!(rust.contains("pub enum Kind {\n    #[serde(rename = \"KIND_UNSPECIFIED\")]\n    KindUnspecified,"));
+        assert!(rust.contains("pub struct Usage {\n    pub prompt_tokens: i64,\n    /// Deprecated.\n    pub models: Vec<String>,\n"));
+        assert!(rust.contains("    pub costs: std::collections::HashMap<String, f64>,\n"));
+        assert!(rust.contains("    pub kind: Option<Kind>,\n"));
+    }
+
+    #[test]
+    fn test_languages_and_unsupported_roots() {
+        assert_eq!("TypeScript".parse::<Language>().unwrap(), Language::TypeScript);
+        assert_eq!("py".parse::<Language>().unwrap(), Language::Python);
+        assert!("java".parse::<Language>().is_err());
+        assert_eq!(Language::Rust.file_name("telemetry.InferenceEvent"), "telemetry_inference_event.rs");
+        assert_eq!(Language::TypeScript.file_name("InferenceEvent"), "InferenceEvent.ts");
+
+        assert!(generate("S", r#"{"type": "string"}"#, SerializationFormat::JsonSchema, Language::Rust).is_err());
+        assert!(generate("S", "not json", SerializationFormat::Avro, Language::Rust).is_err());
+    }
+}
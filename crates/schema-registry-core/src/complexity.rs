@@ -0,0 +1,374 @@
+//! Schema size and complexity metrics
+//!
+//! Metrics are measured once at registration time and stored with each
+//! version, so growth can be tracked release over release without
+//! re-parsing old schemas.
+
+use crate::types::SerializationFormat;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::OnceLock;
+
+/// JSON Schema keywords counted as constraints
+const JSON_SCHEMA_CONSTRAINTS: &[&str] = &[
+    "minimum",
+    "maximum",
+    "exclusiveMinimum",
+    "exclusiveMaximum",
+    "multipleOf",
+    "minLength",
+    "maxLength",
+    "pattern",
+    "format",
+    "enum",
+    "const",
+    "minItems",
+    "maxItems",
+    "uniqueItems",
+    "minProperties",
+    "maxProperties",
+];
+
+/// Size and complexity of one schema version
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchemaComplexity {
+    /// Raw content size in bytes
+    pub size_bytes: u64,
+    /// Number of fields, including nested ones
+    pub field_count: u32,
+    /// Deepest level of field nesting (top-level fields are depth 1)
+    pub max_depth: u32,
+    /// Number of value constraints (required fields, bounds, patterns, enums, ...)
+    pub constraint_count: u32,
+}
+
+impl SchemaComplexity {
+    /// Measure schema content
+    ///
+    /// Content that cannot be parsed only reports its size.
+    pub fn measure(content: &str, format: SerializationFormat) -> Self {
+        let mut complexity = SchemaComplexity {
+            size_bytes: content.len() as u64,
+            ..Default::default()
+        };
+
+        match format {
+            SerializationFormat::JsonSchema => {
+                if let Ok(schema) = serde_json::from_str::<Value>(content) {
+                    measure_json_schema(&schema, 0, &mut complexity);
+                }
+            }
+            SerializationFormat::Avro => {
+                if let Ok(schema) = serde_json::from_str::<Value>(content) {
+                    measure_avro(&schema, 0, &mut complexity);
+                }
+            }
+            SerializationFormat::Protobuf => measure_protobuf(content, &mut complexity),
+        }
+
+        complexity
+    }
+}
+
+fn measure_json_schema(schema: &Value, depth: u32, complexity: &mut SchemaComplexity) {
+    let Value::Object(map) = schema else {
+        if let Value::Array(items) = schema {
+            for item in items {
+                measure_json_schema(item, depth, complexity);
+            }
+        }
+        return;
+    };
+
+    for keyword in JSON_SCHEMA_CONSTRAINTS {
+        if map.contains_key(*keyword) {
+            complexity.constraint_count += 1;
+        }
+    }
+    if let Some(Value::Array(required)) = map.get("required") {
+        complexity.constraint_count += required.len() as u32;
+    }
+
+    for (key, value) in map {
+        match key.as_str() {
+            "properties" | "patternProperties" => {
+                if let Value::Object(properties) = value {
+                    complexity.field_count += properties.len() as u32;
+                    complexity.max_depth = complexity.max_depth.max(depth + 1);
+                    for property in properties.values() {
+                        measure_json_schema(property, depth + 1, complexity);
+                    }
+                }
+            }
+            "$defs" | "definitions" => {
+                if let Value::Object(defs) = value {
+                    for def in defs.values() {
+                        measure_json_schema(def, depth, complexity);
+                    }
+                }
+            }
+            // Subschemas that describe the same level
+            "items" | "additionalProperties" | "allOf" | "anyOf" | "oneOf" | "not" | "if"
+            | "then" | "else" => measure_json_schema(value, depth, complexity),
+            _ => {}
+        }
+    }
+}
+
+fn measure_avro(schema: &Value, depth: u32, complexity: &mut SchemaComplexity) {
+    match schema {
+        // Unions
+        Value::Array(types) => {
+            for t in types {
+                measure_avro(t, depth, complexity);
+            }
+        }
+        Value::Object(map) => match map.get("type").and_then(Value::as_str) {
+            Some("record") | Some("error") => {
+                if let Some(Value::Array(fields)) = map.get("fields") {
+                    complexity.field_count += fields.len() as u32;
+                    complexity.max_depth = complexity.max_depth.max(depth + 1);
+                    for field in fields {
+                        // Fields without a default must always be present
+                        if field.get("default").is_none() {
+                            complexity.constraint_count += 1;
+                        }
+                        if let Some(field_type) = field.get("type") {
+                            measure_avro(field_type, depth + 1, complexity);
+                        }
+                    }
+                }
+            }
+            Some("enum") => complexity.constraint_count += 1,
+            Some("fixed") => complexity.constraint_count += 1,
+            Some("array") => {
+                if let Some(items) = map.get("items") {
+                    measure_avro(items, depth, complexity);
+                }
+            }
+            Some("map") => {
+                if let Some(values) = map.get("values") {
+                    measure_avro(values, depth, complexity);
+                }
+            }
+            _ => {}
+        },
+        _ => {}
+    }
+}
+
+fn measure_protobuf(content: &str, complexity: &mut SchemaComplexity) {
+    static FIELD: OnceLock<Regex> = OnceLock::new();
+    let field = FIELD.get_or_init(|| {
+        Regex::new(r"^\s*(?:(repeated|optional|required)\s+)?(?:map<[^>]+>|[\w.]+)\s+\w+\s*=\s*\d+")
+            .expect("valid protobuf field pattern")
+    });
+
+    // Depth counts nested message blocks only
+    let mut blocks: Vec<bool> = Vec::new();
+    let mut message_depth = 0u32;
+    for line in content.lines() {
+        let line = line.split("//").next().unwrap_or("");
+        let trimmed = line.trim_start();
+
+        if let Some(caps) = field.captures(line) {
+            if !trimmed.starts_with("option ") && !trimmed.starts_with("reserved ") {
+                complexity.field_count += 1;
+                complexity.max_depth = complexity.max_depth.max(message_depth);
+                if caps.get(1).map(|m| m.as_str()) == Some("required") {
+                    complexity.constraint_count += 1;
+                }
+            }
+        }
+
+        for c in line.chars() {
+            match c {
+                '{' => {
+                    let is_message = trimmed.starts_with("message ");
+                    if is_message {
+                        message_depth += 1;
+                    }
+                    blocks.push(is_message);
+                }
+                '}' if blocks.pop() == Some(true) => {
+                    message_depth = message_depth.saturating_sub(1);
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Limits on how much a subject may grow from one version to the next
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComplexityGrowthThresholds {
+    /// Maximum growth in field count, in percent
+    pub max_field_growth_percent: f64,
+    /// Maximum growth in size, in percent
+    pub max_size_growth_percent: f64,
+    /// Maximum increase in nesting depth
+    pub max_depth_increase: u32,
+    /// Maximum growth in constraint count, in percent
+    pub max_constraint_growth_percent: f64,
+}
+
+impl Default for ComplexityGrowthThresholds {
+    fn default() -> Self {
+        Self {
+            max_field_growth_percent: 50.0,
+            max_size_growth_percent: 100.0,
+            max_depth_increase: 2,
+            max_constraint_growth_percent: 100.0,
+        }
+    }
+}
+
+/// A metric that grew beyond its threshold
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ComplexityAlert {
+    pub metric: String,
+    pub previous: u64,
+    pub current: u64,
+    pub message: String,
+}
+
+impl ComplexityGrowthThresholds {
+    /// Compare a new version against the previous one
+    pub fn check(&self, previous: &SchemaComplexity, current: &SchemaComplexity) -> Vec<ComplexityAlert> {
+        let mut alerts = Vec::new();
+
+        let mut check_percent = |metric: &str, before: u64, after: u64, limit: f64| {
+            if before == 0 || after <= before {
+                return;
+            }
+            let growth = (after - before) as f64 / before as f64 * 100.0;
+            if growth > limit {
+                alerts.push(ComplexityAlert {
+                    metric: metric.to_string(),
+                    previous: before,
+                    current: after,
+                    message: format!(
+                        "{} grew {:.0}% ({} -> {}), above the {:.0}% threshold",
+                        metric, growth, before, after, limit
+                    ),
+                });
+            }
+        };
+        check_percent(
+            "field_count",
+            previous.field_count as u64,
+            current.field_count as u64,
+            self.max_field_growth_percent,
+        );
+        check_percent(
+            "size_bytes",
+            previous.size_bytes,
+            current.size_bytes,
+            self.max_size_growth_percent,
+        );
+        check_percent(
+            "constraint_count",
+            previous.constraint_count as u64,
+            current.constraint_count as u64,
+            self.max_constraint_growth_percent,
+        );
+
+        let depth_increase = current.max_depth.saturating_sub(previous.max_depth);
+        if depth_increase > self.max_depth_increase {
+            alerts.push(ComplexityAlert {
+                metric: "max_depth".to_string(),
+                previous: previous.max_depth as u64,
+                current: current.max_depth as u64,
+                message: format!(
+                    "max_depth increased by {} ({} -> {}), above the threshold of {}",
+                    depth_increase, previous.max_depth, current.max_depth, self.max_depth_increase
+                ),
+            });
+        }
+
+        alerts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_measure_json_schema() {
+        let schema = r#"{
+            "type": "object",
+            "required": ["id", "address"],
+            "properties": {
+                "id": {"type": "string", "format": "uuid"},
+                "age": {"type": "integer", "minimum": 0, "maximum": 150},
+                "address": {
+                    "type": "object",
+                    "properties": {
+                        "street": {"type": "string", "maxLength": 100},
+                        "zip": {"type": "string", "pattern": "^[0-9]{5}$"}
+                    }
+                },
+                "tags": {"type": "array", "items": {"type": "object", "properties": {"name": {"type": "string"}}}}
+            }
+        }"#;
+
+        let c = SchemaComplexity::measure(schema, SerializationFormat::JsonSchema);
+        assert_eq!(c.size_bytes, schema.len() as u64);
+        assert_eq!(c.field_count, 7);
+        assert_eq!(c.max_depth, 2);
+        // 2 required + format + minimum + maximum + maxLength + pattern
+        assert_eq!(c.constraint_count, 7);
+    }
+
+    #[test]
+    fn test_measure_avro_and_protobuf() {
+        let avro = r#"{
+            "type": "record", "name": "User",
+            "fields": [
+                {"name": "id", "type": "string"},
+                {"name": "email", "type": ["null", "string"], "default": null},
+                {"name": "address", "type": {"type": "record", "name": "Address",
+                    "fields": [{"name": "city", "type": "string"}]}}
+            ]
+        }"#;
+        let c = SchemaComplexity::measure(avro, SerializationFormat::Avro);
+        assert_eq!(c.field_count, 4);
+        assert_eq!(c.max_depth, 2);
+        assert_eq!(c.constraint_count, 3);
+
+        let proto = r#"
+            syntax = "proto3";
+            message User {
+              string id = 1;
+              repeated string tags = 2;
+              message Address {
+                string city = 1; // nested
+              }
+              Address address = 3;
+              map<string, string> labels = 4;
+            }
+        "#;
+        let c = SchemaComplexity::measure(proto, SerializationFormat::Protobuf);
+        assert_eq!(c.field_count, 5);
+        assert_eq!(c.max_depth, 2);
+    }
+
+    #[test]
+    fn test_growth_alerts() {
+        let thresholds = ComplexityGrowthThresholds::default();
+        let previous = SchemaComplexity { size_bytes: 1000, field_count: 10, max_depth: 2, constraint_count: 4 };
+
+        let modest = SchemaComplexity { size_bytes: 1200, field_count: 12, max_depth: 3, constraint_count: 5 };
+        assert!(thresholds.check(&previous, &modest).is_empty());
+
+        let large = SchemaComplexity { size_bytes: 2500, field_count: 20, max_depth: 5, constraint_count: 5 };
+        let metrics: Vec<_> = thresholds
+            .check(&previous, &large)
+            .into_iter()
+            .map(|a| a.metric)
+            .collect();
+        assert_eq!(metrics, vec!["field_count", "size_bytes", "max_depth"]);
+    }
+}
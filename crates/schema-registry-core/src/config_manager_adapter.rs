@@ -16,6 +16,9 @@
 //! 3. **Runtime Refresh**: Optional hooks for live configuration updates
 
 use llm_config_core::{ConfigManager, Environment, ConfigValue, Result as ConfigResult};
+use crate::secret_ref::{redact_map, SecretRef};
+use crate::traits::CompatibilityViolation;
+use crate::types::{ViolationSeverity, ViolationType};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::Path;
@@ -514,18 +517,42 @@ pub enum SchemaSourceType {
 }
 
 /// Authentication configuration for schema sources
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct SourceAuthConfig {
     /// Authentication type
     pub auth_type: String,
 
-    /// Credentials key reference (not the actual credentials)
-    pub credentials_key: Option<String>,
+    /// Secrets manager reference to the credentials, resolved when the source is read
+    pub credentials_key: Option<SecretRef>,
 
     /// Additional auth parameters
+    ///
+    /// Credentials belong in `credentials_key`; values of parameters whose
+    /// names look sensitive are redacted from `Debug` output and [`Self::redacted`].
     pub params: HashMap<String, String>,
 }
 
+impl SourceAuthConfig {
+    /// Copy safe to return from APIs and include in exports
+    pub fn redacted(&self) -> Self {
+        Self {
+            auth_type: self.auth_type.clone(),
+            credentials_key: self.credentials_key.clone(),
+            params: redact_map(&self.params),
+        }
+    }
+}
+
+impl std::fmt::Debug for SourceAuthConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SourceAuthConfig")
+            .field("auth_type", &self.auth_type)
+            .field("credentials_key", &self.credentials_key)
+            .field("params", &redact_map(&self.params))
+            .finish()
+    }
+}
+
 // ============================================================================
 // Phase 2B: Storage Paths Configuration Adapter
 // ============================================================================
@@ -813,6 +840,10 @@ pub struct ValidationSettingsConfig {
 
     /// Result filtering and reporting
     pub reporting: ValidationReportingConfig,
+
+    /// Suspicious content patterns and regex (ReDoS) enforcement
+    #[serde(default)]
+    pub security: SecurityValidationSettings,
 }
 
 impl Default for ValidationSettingsConfig {
@@ -823,6 +854,7 @@ impl Default for ValidationSettingsConfig {
             custom_rules: CustomRulesConfig::default(),
             performance: PerformanceThresholds::default(),
             reporting: ValidationReportingConfig::default(),
+            security: SecurityValidationSettings::default(),
         }
     }
 }
@@ -906,6 +938,30 @@ pub struct PerformanceThresholds {
 
     /// Warn on potential performance issues
     pub warn_on_issues: bool,
+
+    /// Maximum size of a JSON document accepted by the parser, in bytes
+    #[serde(default = "default_max_json_bytes")]
+    pub max_json_bytes: usize,
+
+    /// Maximum number of values and keys in a JSON document
+    #[serde(default = "default_max_json_nodes")]
+    pub max_json_nodes: usize,
+
+    /// Maximum length of a single JSON string, in bytes
+    #[serde(default = "default_max_string_length")]
+    pub max_string_length: usize,
+}
+
+fn default_max_json_bytes() -> usize {
+    10 * 1024 * 1024
+}
+
+fn default_max_json_nodes() -> usize {
+    100_000
+}
+
+fn default_max_string_length() -> usize {
+    1024 * 1024
 }
 
 impl Default for PerformanceThresholds {
@@ -916,6 +972,9 @@ impl Default for PerformanceThresholds {
             max_array_items: Some(10000),
             max_regex_complexity: 100,
             warn_on_issues: true,
+            max_json_bytes: default_max_json_bytes(),
+            max_json_nodes: default_max_json_nodes(),
+            max_string_length: default_max_string_length(),
         }
     }
 }
@@ -951,6 +1010,193 @@ impl Default for ValidationReportingConfig {
     }
 }
 
+/// Security validation settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecurityValidationSettings {
+    /// Enable security validation
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Quarantine schemas matching a quarantining pattern instead of only
+    /// warning
+    #[serde(default = "default_true")]
+    pub quarantine_on_findings: bool,
+
+    /// Patterns searched for in schema content; replaces the defaults
+    #[serde(default = "default_security_patterns")]
+    pub patterns: Vec<SecurityPatternRule>,
+
+    /// How `pattern` regexes that risk catastrophic backtracking are treated
+    #[serde(default)]
+    pub regex_enforcement: RegexEnforcement,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_security_patterns() -> Vec<SecurityPatternRule> {
+    [
+        ("eval", "Contains potentially dangerous eval keyword"),
+        ("exec", "Contains potentially dangerous exec keyword"),
+        ("__proto__", "Contains prototype pollution pattern"),
+        ("constructor", "Contains constructor access pattern"),
+    ]
+    .into_iter()
+    .map(|(pattern, message)| SecurityPatternRule {
+        name: pattern.trim_matches('_').to_string(),
+        pattern: pattern.to_string(),
+        message: message.to_string(),
+        case_sensitive: false,
+        blocking: false,
+        quarantine: true,
+    })
+    .collect()
+}
+
+impl Default for SecurityValidationSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            quarantine_on_findings: true,
+            patterns: default_security_patterns(),
+            regex_enforcement: RegexEnforcement::default(),
+        }
+    }
+}
+
+/// A pattern that marks schema content as suspicious
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SecurityPatternRule {
+    /// Rule name, reported with each match
+    pub name: String,
+
+    /// Regular expression searched for in the schema content
+    pub pattern: String,
+
+    /// Message reported when the pattern matches
+    pub message: String,
+
+    /// Match case-sensitively; patterns ignore case by default
+    #[serde(default)]
+    pub case_sensitive: bool,
+
+    /// Reject the schema instead of warning
+    #[serde(default)]
+    pub blocking: bool,
+
+    /// Quarantine the schema for review when the pattern matches
+    #[serde(default = "default_true")]
+    pub quarantine: bool,
+}
+
+/// How regex complexity findings are enforced
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RegexEnforcement {
+    /// Report every issue as a warning
+    #[default]
+    Warn,
+    /// Reject catastrophic patterns and patterns over the complexity threshold
+    Reject,
+    /// Only accept patterns in the RE2-compatible, linear-time subset
+    SafeSubset,
+}
+
+// ============================================================================
+// Compatibility Policy Configuration Adapter
+// ============================================================================
+
+/// How a compatibility rule is enforced
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleLevel {
+    /// Violations of the rule are dropped
+    Off,
+    /// Reported for information only
+    Info,
+    /// Reported as a warning, without making schemas incompatible
+    Warning,
+    /// Reported and make schemas incompatible, the default for every rule
+    Breaking,
+}
+
+impl RuleLevel {
+    /// Severity of violations reported under this level, `None` when off
+    pub fn severity(self) -> Option<ViolationSeverity> {
+        match self {
+            RuleLevel::Off => None,
+            RuleLevel::Info => Some(ViolationSeverity::Info),
+            RuleLevel::Warning => Some(ViolationSeverity::Warning),
+            RuleLevel::Breaking => Some(ViolationSeverity::Breaking),
+        }
+    }
+}
+
+/// Compatibility rules operators have disabled or downgraded
+///
+/// Rules are the violation types the compatibility checker reports. Levels
+/// set on a namespace apply to the namespaces below it, and the nearest
+/// namespace setting a rule wins over `rules`, which apply everywhere:
+///
+/// ```json
+/// {
+///   "rules": {"ENUM_VALUE_REMOVED": "warning"},
+///   "namespaces": {"com.example.analytics": {"CONSTRAINT_ADDED": "off"}}
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CompatibilityPolicy {
+    /// Levels for every namespace
+    #[serde(default)]
+    pub rules: HashMap<ViolationType, RuleLevel>,
+
+    /// Levels for a namespace and its descendants
+    #[serde(default)]
+    pub namespaces: HashMap<String, HashMap<ViolationType, RuleLevel>>,
+}
+
+impl CompatibilityPolicy {
+    /// Whether every rule is enforced as breaking everywhere
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty() && self.namespaces.values().all(HashMap::is_empty)
+    }
+
+    /// Level of `rule` for schemas in `namespace`
+    pub fn level(&self, namespace: &str, rule: &ViolationType) -> RuleLevel {
+        let mut scope = Some(namespace);
+        while let Some(current) = scope {
+            if let Some(level) = self
+                .namespaces
+                .get(current)
+                .and_then(|rules| rules.get(rule))
+            {
+                return *level;
+            }
+            scope = current.rsplit_once('.').map(|(parent, _)| parent);
+        }
+        self.rules.get(rule).copied().unwrap_or(RuleLevel::Breaking)
+    }
+
+    /// Drop violations of rules that are off in `namespace`, and give the
+    /// rest the severity of their rule's level
+    pub fn apply(
+        &self,
+        namespace: &str,
+        violations: Vec<CompatibilityViolation>,
+    ) -> Vec<CompatibilityViolation> {
+        violations
+            .into_iter()
+            .filter_map(|mut violation| {
+                violation.severity = self
+                    .level(namespace, &violation.violation_type)
+                    .severity()?;
+                Some(violation)
+            })
+            .collect()
+    }
+}
+
 // ============================================================================
 // Phase 2B: Extended Config Consumer Trait
 // ============================================================================
@@ -972,6 +1218,9 @@ pub trait ConfigConsumerExt: ConfigConsumer {
 
     /// Load comprehensive validation settings
     fn load_validation_settings(&self) -> Result<ValidationSettingsConfig, ConfigError>;
+
+    /// Load the compatibility rule overrides
+    fn load_compatibility_policy(&self) -> Result<CompatibilityPolicy, ConfigError>;
 }
 
 impl ConfigConsumerExt for ConfigManagerAdapter {
@@ -1030,6 +1279,20 @@ impl ConfigConsumerExt for ConfigManagerAdapter {
         debug!("Using default validation settings configuration");
         Ok(ValidationSettingsConfig::default())
     }
+
+    fn load_compatibility_policy(&self) -> Result<CompatibilityPolicy, ConfigError> {
+        info!("Loading compatibility policy from Config Manager");
+
+        if let Ok(Some(value)) = self.get_config_value("compatibility-policy") {
+            // A policy that doesn't parse would silently re-enable every rule
+            let policy = self.parse_value::<CompatibilityPolicy>(&value)?;
+            debug!("Loaded compatibility policy from Config Manager");
+            return Ok(policy);
+        }
+
+        debug!("Using default compatibility policy");
+        Ok(CompatibilityPolicy::default())
+    }
 }
 
 // ============================================================================
@@ -1096,6 +1359,118 @@ mod tests {
         assert!(config.llm.enabled);
         assert!(config.custom_rules.enabled);
         assert!(config.reporting.include_warnings);
+        assert_eq!(config.security.patterns.len(), 4);
+        assert_eq!(config.security.regex_enforcement, RegexEnforcement::Warn);
+    }
+
+    #[test]
+    fn test_security_settings_default_missing_keys() {
+        let security: SecurityValidationSettings = serde_json::from_value(serde_json::json!({
+            "regex_enforcement": "safe_subset",
+            "patterns": [
+                {"name": "script", "pattern": "<script", "message": "Contains a script tag", "blocking": true}
+            ]
+        }))
+        .unwrap();
+        assert!(security.enabled);
+        assert_eq!(security.regex_enforcement, RegexEnforcement::SafeSubset);
+        assert_eq!(security.patterns.len(), 1);
+        assert!(security.patterns[0].blocking);
+        assert!(security.patterns[0].quarantine);
+        assert!(!security.patterns[0].case_sensitive);
+    }
+
+    #[test]
+    fn test_compatibility_policy_nearest_namespace_wins() {
+        let policy: CompatibilityPolicy = serde_json::from_value(serde_json::json!({
+            "rules": {"ENUM_VALUE_REMOVED": "warning"},
+            "namespaces": {
+                "com.example": {"ENUM_VALUE_REMOVED": "off", "CONSTRAINT_ADDED": "info"},
+                "com.example.payments": {"ENUM_VALUE_REMOVED": "breaking"}
+            }
+        }))
+        .unwrap();
+
+        let enum_removed = ViolationType::EnumValueRemoved;
+        assert_eq!(policy.level("org.other", &enum_removed), RuleLevel::Warning);
+        assert_eq!(
+            policy.level("com.example.web", &enum_removed),
+            RuleLevel::Off
+        );
+        assert_eq!(
+            policy.level("com.example.payments.cards", &enum_removed),
+            RuleLevel::Breaking
+        );
+        assert_eq!(
+            policy.level("com.examples", &enum_removed),
+            RuleLevel::Warning
+        );
+        assert_eq!(
+            policy.level("com.example.payments", &ViolationType::ConstraintAdded),
+            RuleLevel::Info
+        );
+        assert_eq!(
+            policy.level("com.example", &ViolationType::FieldRemoved),
+            RuleLevel::Breaking
+        );
+        assert!(!policy.is_empty());
+        assert!(CompatibilityPolicy::default().is_empty());
+    }
+
+    #[test]
+    fn test_compatibility_policy_apply() {
+        let violation = |violation_type| CompatibilityViolation {
+            violation_type,
+            field_path: "status".to_string(),
+            old_value: None,
+            new_value: None,
+            severity: ViolationSeverity::Breaking,
+            description: String::new(),
+            suggestion: None,
+        };
+        let policy = CompatibilityPolicy {
+            rules: HashMap::from([
+                (ViolationType::EnumValueRemoved, RuleLevel::Warning),
+                (ViolationType::ConstraintAdded, RuleLevel::Off),
+            ]),
+            namespaces: HashMap::new(),
+        };
+
+        let applied = policy.apply(
+            "com.example",
+            vec![
+                violation(ViolationType::EnumValueRemoved),
+                violation(ViolationType::ConstraintAdded),
+                violation(ViolationType::FieldRemoved),
+            ],
+        );
+        let severities: Vec<_> = applied
+            .iter()
+            .map(|v| (v.violation_type.clone(), v.severity))
+            .collect();
+        assert_eq!(
+            severities,
+            [
+                (ViolationType::EnumValueRemoved, ViolationSeverity::Warning),
+                (ViolationType::FieldRemoved, ViolationSeverity::Breaking),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_source_auth_config_redaction() {
+        let auth: SourceAuthConfig = serde_json::from_value(serde_json::json!({
+            "auth_type": "basic",
+            "credentials_key": "sources/confluent",
+            "params": {"username": "svc", "password": "hunter2"}
+        }))
+        .unwrap();
+        assert_eq!(auth.credentials_key, Some(SecretRef::new("sources/confluent")));
+
+        let debug = format!("{:?}", auth);
+        assert!(!debug.contains("hunter2"));
+        assert!(debug.contains("svc"));
+        assert_eq!(auth.redacted().params["password"], crate::secret_ref::REDACTED);
     }
 
     #[test]
@@ -0,0 +1,188 @@
+//! Request deadlines
+//!
+//! A client states how long it is willing to wait with `grpc-timeout` or
+//! `X-Request-Timeout`. The transport layer turns that into a [`Deadline`]
+//! and runs the request inside [`Deadline::scope`], which makes it available
+//! to the service layer through [`Deadline::current`] without threading it
+//! through every signature.
+//!
+//! Long-running work calls [`checkpoint`] between units of work. A checkpoint
+//! fails once the deadline has passed and yields to the runtime otherwise, so
+//! work for a client that has gone away stops at the next checkpoint when its
+//! future is dropped.
+
+use crate::error::{Error, Result};
+use std::future::Future;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// gRPC timeout header, e.g. `500m` or `30S`
+pub const GRPC_TIMEOUT_HEADER: &str = "grpc-timeout";
+
+/// HTTP timeout header in seconds, e.g. `2.5`
+pub const REQUEST_TIMEOUT_HEADER: &str = "x-request-timeout";
+
+tokio::task_local! {
+    static CURRENT: Deadline;
+}
+
+/// Point in time by which a request must be answered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Deadline(Instant);
+
+impl Deadline {
+    /// Deadline `timeout` from now
+    pub fn after(timeout: Duration) -> Self {
+        Self(Instant::now() + timeout)
+    }
+
+    pub fn instant(&self) -> Instant {
+        self.0
+    }
+
+    /// Time left, zero once expired
+    pub fn remaining(&self) -> Duration {
+        self.0.saturating_duration_since(Instant::now())
+    }
+
+    pub fn is_expired(&self) -> bool {
+        Instant::now() >= self.0
+    }
+
+    /// Fail with [`Error::DeadlineExceeded`] once expired
+    pub fn check(&self) -> Result<()> {
+        if self.is_expired() {
+            Err(Error::DeadlineExceeded("Request deadline exceeded".to_string()))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// The earlier of two optional deadlines
+    pub fn earliest(a: Option<Deadline>, b: Option<Deadline>) -> Option<Deadline> {
+        match (a, b) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+
+    /// Deadline of the current request, if it has one
+    pub fn current() -> Option<Deadline> {
+        CURRENT.try_with(|d| *d).ok()
+    }
+
+    /// Run `future` with this deadline as the current one
+    ///
+    /// A deadline already in scope is kept if it is earlier, so nested calls
+    /// can only shorten it.
+    pub async fn scope<F: Future>(self, future: F) -> F::Output {
+        let deadline = Deadline::earliest(Some(self), Deadline::current()).unwrap_or(self);
+        CURRENT.scope(deadline, future).await
+    }
+
+    /// Run `future` in scope, failing if it doesn't finish in time
+    ///
+    /// The future is dropped when the deadline passes, so it must not leave
+    /// partial state behind at its await points.
+    pub async fn run<F: Future>(self, future: F) -> Result<F::Output> {
+        tokio::time::timeout_at(self.0, self.scope(future))
+            .await
+            .map_err(|_| Error::DeadlineExceeded("Request deadline exceeded".to_string()))
+    }
+}
+
+/// Cancellation point for long-running work
+///
+/// Fails once the current deadline has passed; otherwise yields so a
+/// dropped request stops here.
+pub async fn checkpoint() -> Result<()> {
+    if let Some(deadline) = Deadline::current() {
+        deadline.check()?;
+    }
+    tokio::task::yield_now().await;
+    Ok(())
+}
+
+/// Parse a `grpc-timeout` value
+///
+/// The format is up to 8 digits followed by a unit: `H`ours, `M`inutes,
+/// `S`econds, `m`illiseconds, `u` microseconds or `n`anoseconds.
+pub fn parse_grpc_timeout(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if value.len() < 2 || value.len() > 9 {
+        return None;
+    }
+    let (amount, unit) = value.split_at(value.len() - 1);
+    if !amount.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let amount: u64 = amount.parse().ok()?;
+    match unit {
+        "H" => Some(Duration::from_secs(amount * 3600)),
+        "M" => Some(Duration::from_secs(amount * 60)),
+        "S" => Some(Duration::from_secs(amount)),
+        "m" => Some(Duration::from_millis(amount)),
+        "u" => Some(Duration::from_micros(amount)),
+        "n" => Some(Duration::from_nanos(amount)),
+        _ => None,
+    }
+}
+
+/// Parse an `X-Request-Timeout` value in (fractional) seconds
+pub fn parse_request_timeout(value: &str) -> Option<Duration> {
+    let seconds: f64 = value.trim().parse().ok()?;
+    if seconds.is_finite() && seconds > 0.0 {
+        Duration::try_from_secs_f64(seconds).ok()
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_timeouts() {
+        assert_eq!(parse_grpc_timeout("500m"), Some(Duration::from_millis(500)));
+        assert_eq!(parse_grpc_timeout("2H"), Some(Duration::from_secs(7200)));
+        assert_eq!(parse_grpc_timeout("30S"), Some(Duration::from_secs(30)));
+        assert_eq!(parse_grpc_timeout("123456789S"), None);
+        assert_eq!(parse_grpc_timeout("10x"), None);
+        assert_eq!(parse_grpc_timeout("-1S"), None);
+        assert_eq!(parse_grpc_timeout("S"), None);
+
+        assert_eq!(parse_request_timeout("2.5"), Some(Duration::from_millis(2500)));
+        assert_eq!(parse_request_timeout("0"), None);
+        assert_eq!(parse_request_timeout("soon"), None);
+    }
+
+    #[tokio::test]
+    async fn test_scope_keeps_earliest_deadline() {
+        assert!(Deadline::current().is_none());
+
+        let outer = Deadline::after(Duration::from_secs(1));
+        let inner = Deadline::after(Duration::from_secs(60));
+        let seen = outer
+            .scope(async { inner.scope(async { Deadline::current() }).await })
+            .await;
+        assert_eq!(seen, Some(outer));
+    }
+
+    #[tokio::test]
+    async fn test_run_cancels_at_deadline() {
+        let result = Deadline::after(Duration::from_millis(20))
+            .run(tokio::time::sleep(Duration::from_secs(60)))
+            .await;
+        assert!(matches!(result, Err(Error::DeadlineExceeded(_))));
+
+        let result = Deadline::after(Duration::from_millis(5))
+            .scope(async {
+                checkpoint().await?;
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                checkpoint().await
+            })
+            .await;
+        assert!(matches!(result, Err(Error::DeadlineExceeded(_))));
+    }
+}
@@ -0,0 +1,389 @@
+//! Field-level deprecation annotations
+//!
+//! Individual fields can be marked deprecated ahead of their removal, so
+//! consumers get a notice period before a version drops them:
+//!
+//! - JSON Schema: the standard `"deprecated": true` keyword, optionally with
+//!   an `"x-deprecated": {"since": "1.2.0", "replacement": "email_address"}`
+//!   annotation (which implies `deprecated` on its own)
+//! - Avro: a field attribute, either `"deprecated": true` or
+//!   `"deprecated": {"since": "1.2.0", "replacement": "email_address"}`
+//!
+//! Both annotations accept an optional `"message"`. Protobuf schemas aren't
+//! read; their `[deprecated = true]` option carries no version.
+//!
+//! Fields are addressed by dotted paths (`address.street`), with `[]` marking
+//! the items of an array (`lines[].sku`).
+
+use crate::types::SerializationFormat;
+use crate::versioning::SemanticVersion;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Minor versions a field must stay deprecated before its removal is
+/// non-breaking
+pub const DEFAULT_NOTICE_MINOR_VERSIONS: u32 = 1;
+
+/// A field marked deprecated in a schema
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldDeprecation {
+    /// Path of the field, e.g. `address.street`
+    pub path: String,
+    /// Version that first deprecated the field
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since: Option<SemanticVersion>,
+    /// Field consumers should use instead
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replacement: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl FieldDeprecation {
+    pub fn new(path: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            since: None,
+            replacement: None,
+            message: None,
+        }
+    }
+
+    pub fn with_since(mut self, since: SemanticVersion) -> Self {
+        self.since = Some(since);
+        self
+    }
+
+    pub fn with_replacement(mut self, replacement: impl Into<String>) -> Self {
+        self.replacement = Some(replacement.into());
+        self
+    }
+
+    /// Whether the notice period has passed by `version`
+    ///
+    /// The period counts minor versions after `since`; any later major
+    /// version is past it. A deprecation without `since` has no notice period
+    /// to measure, so it never passes.
+    pub fn notice_elapsed(&self, version: &SemanticVersion, notice_minor_versions: u32) -> bool {
+        let Some(since) = &self.since else {
+            return false;
+        };
+        version.major > since.major
+            || (version.major == since.major
+                && version.minor >= since.minor.saturating_add(notice_minor_versions))
+    }
+
+    /// Consumer-facing warning for data that still uses the field
+    pub fn warning(&self) -> String {
+        let mut warning = format!("Field '{}' is deprecated", self.path);
+        if let Some(since) = &self.since {
+            warning.push_str(&format!(" since {}", since));
+        }
+        if let Some(replacement) = &self.replacement {
+            warning.push_str(&format!("; use '{}' instead", replacement));
+        }
+        if let Some(message) = &self.message {
+            warning.push_str(&format!(" ({})", message));
+        }
+        warning
+    }
+}
+
+/// A field declared by a schema, with its deprecation if it has one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaField {
+    pub path: String,
+    /// Declared type, e.g. `string` or `null | long`
+    pub type_name: Option<String>,
+    /// JSON Schema `description` or Avro `doc`
+    pub description: Option<String>,
+    pub deprecation: Option<FieldDeprecation>,
+}
+
+/// Fields declared by schema content
+///
+/// Avro fields keep their declaration order; JSON Schema properties are
+/// sorted by name. Content that cannot be parsed, and Protobuf content,
+/// declares none.
+pub fn fields(content: &str, format: SerializationFormat) -> Vec<SchemaField> {
+    let mut fields = Vec::new();
+    let Ok(schema) = serde_json::from_str::<Value>(content) else {
+        return fields;
+    };
+    match format {
+        SerializationFormat::JsonSchema => collect_json_schema(&schema, "", &mut fields),
+        SerializationFormat::Avro => collect_avro(&schema, "", &mut fields),
+        SerializationFormat::Protobuf => {}
+    }
+    fields
+}
+
+/// Deprecated fields declared by schema content
+pub fn field_deprecations(content: &str, format: SerializationFormat) -> Vec<FieldDeprecation> {
+    fields(content, format)
+        .into_iter()
+        .filter_map(|field| field.deprecation)
+        .collect()
+}
+
+/// The deprecations whose fields are present in `data`
+pub fn used_in<'a>(deprecations: &'a [FieldDeprecation], data: &Value) -> Vec<&'a FieldDeprecation> {
+    deprecations
+        .iter()
+        .filter(|deprecation| {
+            let segments: Vec<&str> = deprecation.path.split('.').collect();
+            is_present(data, &segments)
+        })
+        .collect()
+}
+
+fn is_present(data: &Value, segments: &[&str]) -> bool {
+    let Some((segment, rest)) = segments.split_first() else {
+        return true;
+    };
+    let (key, is_array) = match segment.strip_suffix("[]") {
+        Some(key) => (key, true),
+        None => (*segment, false),
+    };
+    let Some(value) = data.as_object().and_then(|map| map.get(key)) else {
+        return false;
+    };
+    match (is_array, value) {
+        (true, Value::Array(items)) => items.iter().any(|item| is_present(item, rest)),
+        (true, _) => false,
+        (false, value) => is_present(value, rest),
+    }
+}
+
+fn join(prefix: &str, name: &str) -> String {
+    if prefix.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", prefix, name)
+    }
+}
+
+/// Read the `since`, `replacement` and `message` of an annotation object
+fn annotation(path: String, details: Option<&Map<String, Value>>) -> FieldDeprecation {
+    let text = |key: &str| {
+        details
+            .and_then(|d| d.get(key))
+            .and_then(Value::as_str)
+            .map(str::to_string)
+    };
+    FieldDeprecation {
+        path,
+        since: text("since").and_then(|since| since.parse().ok()),
+        replacement: text("replacement"),
+        message: text("message"),
+    }
+}
+
+fn collect_json_schema(schema: &Value, prefix: &str, fields: &mut Vec<SchemaField>) {
+    let Value::Object(map) = schema else {
+        return;
+    };
+
+    if let Some(Value::Object(properties)) = map.get("properties") {
+        for (name, property) in properties {
+            let path = join(prefix, name);
+            let details = property.get("x-deprecated").and_then(Value::as_object);
+            let deprecated = details.is_some()
+                || property.get("deprecated").and_then(Value::as_bool) == Some(true);
+            fields.push(SchemaField {
+                path: path.clone(),
+                type_name: json_schema_type(property),
+                description: property.get("description").and_then(Value::as_str).map(str::to_string),
+                deprecation: deprecated.then(|| annotation(path.clone(), details)),
+            });
+            collect_json_schema(property, &path, fields);
+        }
+    }
+
+    if let Some(items) = map.get("items") {
+        if items.get("properties").is_some() {
+            collect_json_schema(items, &format!("{}[]", prefix), fields);
+        }
+    }
+}
+
+fn json_schema_type(property: &Value) -> Option<String> {
+    match property.get("type")? {
+        Value::String(name) => Some(name.clone()),
+        Value::Array(names) => Some(
+            names
+                .iter()
+                .filter_map(Value::as_str)
+                .collect::<Vec<_>>()
+                .join(" | "),
+        ),
+        _ => None,
+    }
+}
+
+fn avro_type(field_type: &Value) -> Option<String> {
+    match field_type {
+        Value::String(name) => Some(name.clone()),
+        Value::Array(types) => Some(
+            types
+                .iter()
+                .filter_map(avro_type)
+                .collect::<Vec<_>>()
+                .join(" | "),
+        ),
+        Value::Object(map) => map
+            .get("name")
+            .or_else(|| map.get("type"))
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        _ => None,
+    }
+}
+
+fn collect_avro(schema: &Value, prefix: &str, fields: &mut Vec<SchemaField>) {
+    match schema {
+        // Unions
+        Value::Array(types) => {
+            for t in types {
+                collect_avro(t, prefix, fields);
+            }
+        }
+        Value::Object(map) => match map.get("type").and_then(Value::as_str) {
+            Some("record") | Some("error") => {
+                let Some(Value::Array(record_fields)) = map.get("fields") else {
+                    return;
+                };
+                for field in record_fields {
+                    let Some(name) = field.get("name").and_then(Value::as_str) else {
+                        continue;
+                    };
+                    let path = join(prefix, name);
+                    let deprecation = match field.get("deprecated") {
+                        Some(Value::Bool(true)) => Some(annotation(path.clone(), None)),
+                        Some(Value::Object(details)) => Some(annotation(path.clone(), Some(details))),
+                        _ => None,
+                    };
+                    fields.push(SchemaField {
+                        path: path.clone(),
+                        type_name: field.get("type").and_then(avro_type),
+                        description: field.get("doc").and_then(Value::as_str).map(str::to_string),
+                        deprecation,
+                    });
+                    if let Some(field_type) = field.get("type") {
+                        collect_avro(field_type, &path, fields);
+                    }
+                }
+            }
+            Some("array") => {
+                if let Some(items) = map.get("items") {
+                    collect_avro(items, &format!("{}[]", prefix), fields);
+                }
+            }
+            _ => {}
+        },
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_json_schema_deprecations() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "email": {
+                    "type": "string",
+                    "deprecated": true,
+                    "x-deprecated": {"since": "1.2.0", "replacement": "email_address"}
+                },
+                "email_address": {"type": "string"},
+                "lines": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {"sku": {"type": "string", "deprecated": true}}
+                    }
+                }
+            }
+        })
+        .to_string();
+
+        let deprecations = field_deprecations(&schema, SerializationFormat::JsonSchema);
+        assert_eq!(
+            deprecations,
+            vec![
+                FieldDeprecation::new("email")
+                    .with_since(SemanticVersion::new(1, 2, 0))
+                    .with_replacement("email_address"),
+                FieldDeprecation::new("lines[].sku"),
+            ]
+        );
+        assert_eq!(fields(&schema, SerializationFormat::JsonSchema).len(), 4);
+    }
+
+    #[test]
+    fn test_avro_deprecations() {
+        let schema = json!({
+            "type": "record",
+            "name": "User",
+            "fields": [
+                {"name": "id", "type": "string"},
+                {"name": "legacy_id", "type": "long", "deprecated": true},
+                {"name": "address", "type": {
+                    "type": "record",
+                    "name": "Address",
+                    "fields": [{
+                        "name": "zip",
+                        "type": "string",
+                        "deprecated": {"since": "2.1.0", "replacement": "postal_code", "message": "US only"}
+                    }]
+                }}
+            ]
+        })
+        .to_string();
+
+        let deprecations = field_deprecations(&schema, SerializationFormat::Avro);
+        assert_eq!(deprecations.len(), 2);
+        assert_eq!(deprecations[0].path, "legacy_id");
+        assert_eq!(deprecations[1].path, "address.zip");
+        assert_eq!(
+            deprecations[1].warning(),
+            "Field 'address.zip' is deprecated since 2.1.0; use 'postal_code' instead (US only)"
+        );
+    }
+
+    #[test]
+    fn test_used_in_follows_paths() {
+        let deprecations = vec![
+            FieldDeprecation::new("email"),
+            FieldDeprecation::new("lines[].sku"),
+            FieldDeprecation::new("address.zip"),
+        ];
+        let data = json!({
+            "email": "a@example.com",
+            "lines": [{"qty": 1}, {"sku": "X-1"}],
+            "address": {"street": "Main St"}
+        });
+
+        let used: Vec<&str> = used_in(&deprecations, &data)
+            .iter()
+            .map(|d| d.path.as_str())
+            .collect();
+        assert_eq!(used, vec!["email", "lines[].sku"]);
+    }
+
+    #[test]
+    fn test_notice_elapsed() {
+        let deprecation = FieldDeprecation::new("email").with_since(SemanticVersion::new(1, 2, 0));
+
+        assert!(!deprecation.notice_elapsed(&SemanticVersion::new(1, 2, 5), 1));
+        assert!(deprecation.notice_elapsed(&SemanticVersion::new(1, 3, 0), 1));
+        assert!(!deprecation.notice_elapsed(&SemanticVersion::new(1, 3, 0), 2));
+        assert!(deprecation.notice_elapsed(&SemanticVersion::new(2, 0, 0), 2));
+        assert!(!FieldDeprecation::new("email").notice_elapsed(&SemanticVersion::new(9, 0, 0), 1));
+    }
+}
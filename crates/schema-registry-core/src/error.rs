@@ -60,6 +60,14 @@ pub enum Error {
     #[error("Configuration error: {0}")]
     ConfigError(String),
 
+    /// Input exceeded a configured resource limit
+    #[error("Limit exceeded: {0}")]
+    LimitExceeded(String),
+
+    /// The request's deadline passed before the work finished
+    #[error("Deadline exceeded: {0}")]
+    DeadlineExceeded(String),
+
     /// Security error
     #[error("Security error: {0}")]
     SecurityError(String),
@@ -92,6 +100,16 @@ impl Error {
         matches!(self, Error::ValidationError(_))
     }
 
+    /// Check if the error is a limit-exceeded error
+    pub fn is_limit_exceeded(&self) -> bool {
+        matches!(self, Error::LimitExceeded(_))
+    }
+
+    /// Check if the error is a deadline-exceeded error
+    pub fn is_deadline_exceeded(&self) -> bool {
+        matches!(self, Error::DeadlineExceeded(_))
+    }
+
     /// Check if the error is a compatibility error
     pub fn is_compatibility_error(&self) -> bool {
         matches!(self, Error::CompatibilityError(_))
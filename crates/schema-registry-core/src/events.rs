@@ -40,6 +40,15 @@ pub enum EventType {
     ConsumerUnregistered,
     /// Usage threshold exceeded
     UsageThresholdExceeded,
+    /// Schema was quarantined after failing security validation
+    SchemaQuarantined,
+    /// Schema was released from quarantine by a security reviewer
+    SchemaQuarantineCleared,
+    /// A version was registered with breaking changes allowed by a
+    /// compatibility exemption
+    CompatibilityExemptionApplied,
+    /// Scheduled re-verification predicts a consumer will break at next release
+    ConsumerBreakagePredicted,
 }
 
 /// Base schema event
@@ -62,6 +71,7 @@ pub struct SchemaEvent {
     /// Event payload
     pub payload: EventPayload,
     /// Additional metadata
+    #[serde(serialize_with = "crate::canonical_json::sorted_value_map")]
     pub metadata: HashMap<String, serde_json::Value>,
 }
 
@@ -134,6 +144,14 @@ pub enum EventPayload {
         mode: String,
     },
 
+    /// Compatibility exemption applied event
+    CompatibilityExemptionApplied {
+        /// Exemptions that were used
+        exemption_ids: Vec<Uuid>,
+        /// Violations that were allowed
+        violations: Vec<serde_json::Value>,
+    },
+
     /// Validation failed event
     ValidationFailed {
         /// Validation errors
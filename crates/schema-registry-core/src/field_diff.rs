@@ -0,0 +1,299 @@
+//! Field-level schema diffs
+//!
+//! A new version's changes, field by field, and the part of them a consumer
+//! cares about. Consumers declare a [`FieldManifest`] of the fields they
+//! read; [`FieldManifest::relevant`] keeps the changes touching one of them,
+//! so a notification says "fields you read that changed" instead of
+//! carrying the whole schema.
+//!
+//! Fields are addressed as in [`crate::deprecation`]: dotted paths, with
+//! `[]` marking the items of an array. Protobuf content declares no fields,
+//! so its versions never differ field by field.
+//!
+//! ```
+//! use schema_registry_core::field_diff::{diff, FieldManifest};
+//! use schema_registry_core::types::SerializationFormat;
+//!
+//! let previous = r#"{"properties": {"id": {"type": "string"}, "total": {"type": "integer"}}}"#;
+//! let current = r#"{"properties": {"id": {"type": "string"}, "total": {"type": "number"}, "note": {"type": "string"}}}"#;
+//! let changes = diff(previous, current, SerializationFormat::JsonSchema);
+//! assert_eq!(changes.len(), 2);
+//!
+//! let manifest = FieldManifest::new(["id", "total"]);
+//! let relevant = manifest.relevant(&changes);
+//! assert_eq!(relevant.len(), 1);
+//! assert_eq!(relevant[0].path(), "total");
+//! ```
+
+use crate::deprecation::{self, FieldDeprecation, SchemaField};
+use crate::error::{Error, Result};
+use crate::types::SerializationFormat;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Most fields one manifest may declare
+pub const MAX_MANIFEST_FIELDS: usize = 500;
+
+/// A change to one field between two versions
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum FieldChange {
+    Added {
+        path: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        type_name: Option<String>,
+    },
+    Removed {
+        path: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        type_name: Option<String>,
+    },
+    TypeChanged {
+        path: String,
+        from: Option<String>,
+        to: Option<String>,
+    },
+    /// The field is newly marked deprecated
+    Deprecated {
+        path: String,
+        deprecation: FieldDeprecation,
+    },
+}
+
+impl FieldChange {
+    pub fn path(&self) -> &str {
+        match self {
+            FieldChange::Added { path, .. }
+            | FieldChange::Removed { path, .. }
+            | FieldChange::TypeChanged { path, .. }
+            | FieldChange::Deprecated { path, .. } => path,
+        }
+    }
+}
+
+/// Field changes from `previous` to `current` content
+///
+/// Changes to fields of `current` come in its declaration order, followed by
+/// the fields it removed.
+pub fn diff(previous: &str, current: &str, format: SerializationFormat) -> Vec<FieldChange> {
+    let previous_fields = deprecation::fields(previous, format);
+    let current_fields = deprecation::fields(current, format);
+    let by_path: HashMap<&str, &SchemaField> = previous_fields
+        .iter()
+        .map(|field| (field.path.as_str(), field))
+        .collect();
+
+    let mut changes = Vec::new();
+    for field in &current_fields {
+        let Some(before) = by_path.get(field.path.as_str()) else {
+            changes.push(FieldChange::Added {
+                path: field.path.clone(),
+                type_name: field.type_name.clone(),
+            });
+            continue;
+        };
+        if before.type_name != field.type_name {
+            changes.push(FieldChange::TypeChanged {
+                path: field.path.clone(),
+                from: before.type_name.clone(),
+                to: field.type_name.clone(),
+            });
+        }
+        if let (None, Some(deprecation)) = (&before.deprecation, &field.deprecation) {
+            changes.push(FieldChange::Deprecated {
+                path: field.path.clone(),
+                deprecation: deprecation.clone(),
+            });
+        }
+    }
+
+    let kept: HashSet<&str> = current_fields
+        .iter()
+        .map(|field| field.path.as_str())
+        .collect();
+    changes.extend(
+        previous_fields
+            .iter()
+            .filter(|field| !kept.contains(field.path.as_str()))
+            .map(|field| FieldChange::Removed {
+                path: field.path.clone(),
+                type_name: field.type_name.clone(),
+            }),
+    );
+    changes
+}
+
+/// The fields a consumer reads
+///
+/// A declared field covers the fields nested in it, so `address` is touched
+/// by a change to `address.street`. A change to an enclosing field touches
+/// the fields within: retyping `address` affects a reader of
+/// `address.street`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct FieldManifest {
+    fields: Vec<String>,
+}
+
+impl FieldManifest {
+    pub fn new<I, S>(fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut manifest = Self::default();
+        for field in fields {
+            let field: String = field.into();
+            let field = field.trim();
+            if !manifest.fields.iter().any(|f| f == field) {
+                manifest.fields.push(field.to_string());
+            }
+        }
+        manifest
+    }
+
+    pub fn fields(&self) -> &[String] {
+        &self.fields
+    }
+
+    /// Check the manifest declares between one and [`MAX_MANIFEST_FIELDS`]
+    /// well-formed paths
+    pub fn validate(&self) -> Result<()> {
+        if self.fields.is_empty() {
+            return Err(Error::ValidationError(
+                "A field manifest must declare at least one field".to_string(),
+            ));
+        }
+        if self.fields.len() > MAX_MANIFEST_FIELDS {
+            return Err(Error::ValidationError(format!(
+                "A field manifest may declare at most {} fields",
+                MAX_MANIFEST_FIELDS
+            )));
+        }
+        if let Some(field) = self
+            .fields
+            .iter()
+            .find(|field| field.is_empty() || field.split('.').any(str::is_empty))
+        {
+            return Err(Error::ValidationError(format!(
+                "Invalid field path '{}'",
+                field
+            )));
+        }
+        Ok(())
+    }
+
+    /// Whether a change at `path` touches a declared field
+    pub fn touches(&self, path: &str) -> bool {
+        self.fields
+            .iter()
+            .any(|field| is_within(path, field) || is_within(field, path))
+    }
+
+    /// The changes touching a declared field
+    pub fn relevant<'a>(&self, changes: &'a [FieldChange]) -> Vec<&'a FieldChange> {
+        changes
+            .iter()
+            .filter(|change| self.touches(change.path()))
+            .collect()
+    }
+}
+
+/// Whether `path` is `ancestor` or nested in it
+fn is_within(path: &str, ancestor: &str) -> bool {
+    match path.strip_prefix(ancestor) {
+        Some("") => true,
+        Some(rest) => rest.starts_with('.') || rest.starts_with("[]"),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PREVIOUS: &str = r#"{
+        "type": "record", "name": "Order",
+        "fields": [
+            {"name": "id", "type": "string"},
+            {"name": "address", "type": {"type": "record", "name": "Address", "fields": [
+                {"name": "street", "type": "string"},
+                {"name": "zip", "type": "string"}
+            ]}},
+            {"name": "coupon", "type": ["null", "string"]}
+        ]
+    }"#;
+
+    const CURRENT: &str = r#"{
+        "type": "record", "name": "Order",
+        "fields": [
+            {"name": "id", "type": "string", "deprecated": {"replacement": "order_id"}},
+            {"name": "order_id", "type": "string"},
+            {"name": "address", "type": {"type": "record", "name": "Address", "fields": [
+                {"name": "street", "type": "string"},
+                {"name": "zip", "type": "int"}
+            ]}}
+        ]
+    }"#;
+
+    #[test]
+    fn test_diff_reports_each_kind_of_change() {
+        let changes = diff(PREVIOUS, CURRENT, SerializationFormat::Avro);
+        assert_eq!(
+            changes,
+            vec![
+                FieldChange::Deprecated {
+                    path: "id".to_string(),
+                    deprecation: FieldDeprecation::new("id").with_replacement("order_id"),
+                },
+                FieldChange::Added {
+                    path: "order_id".to_string(),
+                    type_name: Some("string".to_string()),
+                },
+                FieldChange::TypeChanged {
+                    path: "address.zip".to_string(),
+                    from: Some("string".to_string()),
+                    to: Some("int".to_string()),
+                },
+                FieldChange::Removed {
+                    path: "coupon".to_string(),
+                    type_name: Some("null | string".to_string()),
+                },
+            ]
+        );
+        assert!(diff(CURRENT, CURRENT, SerializationFormat::Avro).is_empty());
+    }
+
+    #[test]
+    fn test_manifest_keeps_changes_to_fields_read() {
+        let changes = diff(PREVIOUS, CURRENT, SerializationFormat::Avro);
+
+        // A reader of the whole address sees the zip change; a reader of the
+        // street alone doesn't
+        let paths = |manifest: FieldManifest| -> Vec<String> {
+            manifest
+                .relevant(&changes)
+                .iter()
+                .map(|change| change.path().to_string())
+                .collect()
+        };
+        assert_eq!(
+            paths(FieldManifest::new(["address", "coupon"])),
+            ["address.zip", "coupon"]
+        );
+        assert!(paths(FieldManifest::new(["address.street"])).is_empty());
+
+        // Changes to an enclosing field reach the fields within it
+        assert!(FieldManifest::new(["address.street"]).touches("address"));
+        assert!(!FieldManifest::new(["address"]).touches("addresses"));
+        assert!(FieldManifest::new(["lines"]).touches("lines[].sku"));
+    }
+
+    #[test]
+    fn test_manifest_validation() {
+        assert!(FieldManifest::new(["id", " id "]).validate().is_ok());
+        assert_eq!(FieldManifest::new(["id", " id "]).fields(), ["id"]);
+        assert!(FieldManifest::new(Vec::<String>::new()).validate().is_err());
+        assert!(FieldManifest::new(["address..zip"]).validate().is_err());
+    }
+}
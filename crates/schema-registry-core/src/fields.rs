@@ -0,0 +1,188 @@
+//! Sparse fieldsets
+//!
+//! Callers that need only a few fields of a large response name them, as
+//! `?fields=id,version,state` over REST or a `FieldMask` over gRPC, and get a
+//! response holding just those fields. A dotted path such as `metadata.owner`
+//! selects part of a nested object.
+//!
+//! ```
+//! use schema_registry_core::fields::FieldSelection;
+//! use serde_json::json;
+//!
+//! let selection = FieldSelection::parse("id,metadata.owner", &["id", "metadata", "content"]).unwrap();
+//! let trimmed = selection.apply(json!({
+//!     "id": "a",
+//!     "content": "...",
+//!     "metadata": {"owner": "payments", "team": "core"},
+//! }));
+//! assert_eq!(trimmed, json!({"id": "a", "metadata": {"owner": "payments"}}));
+//! ```
+
+use crate::error::{Error, Result};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+/// Fields selected at one level of a response
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Selected {
+    /// The whole field
+    All,
+    /// Only these of its fields
+    Fields(BTreeMap<String, Selected>),
+}
+
+impl Selected {
+    fn insert(&mut self, path: &[&str]) {
+        let Selected::Fields(fields) = self else {
+            return;
+        };
+        match path {
+            [] => {}
+            [last] => {
+                fields.insert(last.to_string(), Selected::All);
+            }
+            [first, rest @ ..] => fields
+                .entry(first.to_string())
+                .or_insert_with(|| Selected::Fields(BTreeMap::new()))
+                .insert(rest),
+        }
+    }
+
+    fn apply(&self, value: Value) -> Value {
+        let Selected::Fields(fields) = self else {
+            return value;
+        };
+        match value {
+            Value::Object(mut object) => {
+                let mut trimmed = Map::new();
+                for (name, selected) in fields {
+                    if let Some(value) = object.remove(name) {
+                        trimmed.insert(name.clone(), selected.apply(value));
+                    }
+                }
+                Value::Object(trimmed)
+            }
+            Value::Array(items) => Value::Array(items.into_iter().map(|v| self.apply(v)).collect()),
+            other => other,
+        }
+    }
+}
+
+/// The fields of a response a caller asked for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldSelection {
+    root: Selected,
+}
+
+impl FieldSelection {
+    /// Parse a comma-separated list of field paths
+    ///
+    /// The first segment of every path must be one of `known`; anything
+    /// below it is not checked.
+    pub fn parse(fields: &str, known: &[&str]) -> Result<Self> {
+        Self::from_paths(fields.split(','), known)
+    }
+
+    /// Build a selection from field paths, such as those of a `FieldMask`
+    pub fn from_paths<I, S>(paths: I, known: &[&str]) -> Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut root = Selected::Fields(BTreeMap::new());
+        for path in paths {
+            let path = path.as_ref().trim();
+            if path.is_empty() {
+                continue;
+            }
+            let segments: Vec<&str> = path.split('.').collect();
+            if segments.iter().any(|s| s.is_empty()) {
+                return Err(Error::ValidationError(format!(
+                    "invalid field path '{}'",
+                    path
+                )));
+            }
+            if !known.contains(&segments[0]) {
+                return Err(Error::ValidationError(format!(
+                    "unknown field '{}': expected one of {}",
+                    segments[0],
+                    known.join(", ")
+                )));
+            }
+            root.insert(&segments);
+        }
+        if root == Selected::Fields(BTreeMap::new()) {
+            return Err(Error::ValidationError("no fields selected".to_string()));
+        }
+        Ok(Self { root })
+    }
+
+    /// Whether any part of a top-level field is selected
+    pub fn includes(&self, field: &str) -> bool {
+        match &self.root {
+            Selected::All => true,
+            Selected::Fields(fields) => fields.contains_key(field),
+        }
+    }
+
+    /// Keep only the selected fields of a response
+    ///
+    /// Arrays are trimmed element by element, so a selection made for one
+    /// item applies as well to a list of them.
+    pub fn apply(&self, value: Value) -> Value {
+        self.root.apply(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const KNOWN: &[&str] = &["id", "version", "state", "metadata", "content"];
+
+    #[test]
+    fn test_selects_top_level_and_nested_fields() {
+        let selection = FieldSelection::parse(" id, state ,metadata.owner", KNOWN).unwrap();
+        assert!(selection.includes("metadata"));
+        assert!(!selection.includes("content"));
+
+        let value = json!({
+            "id": "a",
+            "version": "1.0.0",
+            "state": "ACTIVE",
+            "content": "{\"type\":\"object\"}",
+            "metadata": {"owner": "payments", "team": "core"},
+        });
+        assert_eq!(
+            selection.apply(value),
+            json!({"id": "a", "state": "ACTIVE", "metadata": {"owner": "payments"}})
+        );
+    }
+
+    #[test]
+    fn test_whole_field_wins_over_its_parts() {
+        let selection = FieldSelection::from_paths(["metadata.owner", "metadata"], KNOWN).unwrap();
+        let value = json!({"metadata": {"owner": "payments", "team": "core"}});
+        assert_eq!(selection.apply(value.clone()), value);
+    }
+
+    #[test]
+    fn test_applies_to_each_item_of_a_list() {
+        let selection = FieldSelection::parse("id", KNOWN).unwrap();
+        let value = json!([{"id": "a", "content": "x"}, {"id": "b", "content": "y"}]);
+        assert_eq!(selection.apply(value), json!([{"id": "a"}, {"id": "b"}]));
+    }
+
+    #[test]
+    fn test_rejects_unknown_and_empty_selections() {
+        let err = FieldSelection::parse("id,owner", KNOWN)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("unknown field 'owner'"));
+        assert!(err.contains("id, version, state, metadata, content"));
+
+        assert!(FieldSelection::parse("metadata..owner", KNOWN).is_err());
+        assert!(FieldSelection::parse(" , ", KNOWN).is_err());
+    }
+}
@@ -0,0 +1,366 @@
+//! Declarative fixtures for seeding a registry
+//!
+//! A fixture file describes a registry's contents in one place, so a demo
+//! environment or a bug report's setup is one command away:
+//!
+//! ```yaml
+//! namespaces:
+//!   com.example:
+//!     owner: identity-team
+//!     compatibility_mode: BACKWARD
+//! schemas:
+//!   - subject: com.example.User
+//!     format: JSON
+//!     versions:
+//!       - schema: {type: object, properties: {id: {type: string}}}
+//!       - version: 1.1.0
+//!         schema: {type: object, properties: {id: {type: string}, email: {type: string}}}
+//!   - subject: com.example.Order
+//!     versions:
+//!       - schema: {type: object, properties: {user_id: {type: string}}}
+//! webhooks:
+//!   - subject: com.example.User
+//!     consumer: billing
+//!     url: https://hooks.example.com/billing
+//!     fields: [email]
+//! lineage:
+//!   - from: com.example.Order
+//!     to: com.example.User
+//!   - from: com.example.User@1.1.0
+//!     entity: {id: billing-service, type: APPLICATION}
+//!     relation: USED_BY
+//! ```
+//!
+//! [`Fixtures::requests`] turns the file into the REST API requests that
+//! create it, in dependency order: namespace settings, then schema versions
+//! oldest first, then consumer webhooks and lineage edges. Lineage edges name
+//! schemas by subject, optionally `@version`, and the registry resolves them
+//! to schema IDs, so the requests can be sent one after another without
+//! reading the responses.
+
+use crate::error::{Error, Result};
+use crate::namespace::NamespaceSettings;
+use crate::types::CompatibilityMode;
+use crate::versioning::SemanticVersion;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// The contents of a fixture file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Fixtures {
+    /// Explicit settings per namespace path
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub namespaces: BTreeMap<String, NamespaceSettings>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub schemas: Vec<SchemaFixture>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub webhooks: Vec<WebhookFixture>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub lineage: Vec<LineageFixture>,
+}
+
+/// A subject and the versions registered under it, oldest first
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SchemaFixture {
+    pub subject: String,
+    /// `JSON`, `AVRO` or `PROTOBUF`
+    #[serde(default = "default_format")]
+    pub format: String,
+    /// Compatibility mode the versions are registered with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compatibility_mode: Option<CompatibilityMode>,
+    pub versions: Vec<VersionFixture>,
+}
+
+fn default_format() -> String {
+    "JSON".to_string()
+}
+
+/// One version of a subject
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct VersionFixture {
+    /// Semantic version; the subject's next major version when not given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Schema document, for JSON Schema and Avro
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<serde_json::Value>,
+    /// Raw schema content, e.g. Protobuf source
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub metadata: HashMap<String, serde_json::Value>,
+}
+
+/// A consumer of a subject and the webhook told when its fields change
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WebhookFixture {
+    pub subject: String,
+    pub consumer: String,
+    pub url: String,
+    pub fields: Vec<String>,
+}
+
+/// A lineage edge from a schema to another schema or an external entity
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LineageFixture {
+    /// `subject` or `subject@version`
+    pub from: String,
+    /// `subject` or `subject@version`; exclusive with `entity`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entity: Option<EntityFixture>,
+    /// Lineage relation, e.g. `DEPENDS_ON` (the default) or `USED_BY`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relation: Option<String>,
+}
+
+/// An application, pipeline or model in the lineage graph
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EntityFixture {
+    pub id: String,
+    /// `APPLICATION`, `PIPELINE` or `MODEL`
+    #[serde(rename = "type")]
+    pub entity_type: String,
+    /// Defaults to the ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+/// A REST API request that creates part of a fixture
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SeedRequest {
+    /// `PUT` or `POST`
+    pub method: &'static str,
+    pub path: String,
+    pub body: serde_json::Value,
+    /// What the request creates, for progress output
+    pub description: String,
+}
+
+/// Split `subject@version` into its subject and version
+pub fn parse_schema_ref(reference: &str) -> Result<(&str, Option<SemanticVersion>)> {
+    match reference.rsplit_once('@') {
+        Some((subject, version)) => Ok((subject, Some(version.parse()?))),
+        None => Ok((reference, None)),
+    }
+}
+
+impl Fixtures {
+    /// Check the fixtures describe something the registry can create
+    pub fn validate(&self) -> Result<()> {
+        let mut subjects = HashSet::new();
+        for schema in &self.schemas {
+            if !subjects.insert(schema.subject.as_str()) {
+                return Err(invalid(format!(
+                    "Subject '{}' is listed twice",
+                    schema.subject
+                )));
+            }
+            if !matches!(schema.format.as_str(), "JSON" | "AVRO" | "PROTOBUF") {
+                return Err(invalid(format!(
+                    "Subject '{}' has unknown format '{}'; use JSON, AVRO or PROTOBUF",
+                    schema.subject, schema.format
+                )));
+            }
+            if schema.versions.is_empty() {
+                return Err(invalid(format!(
+                    "Subject '{}' has no versions",
+                    schema.subject
+                )));
+            }
+            for version in &schema.versions {
+                if version.schema.is_none() == version.content.is_none() {
+                    return Err(invalid(format!(
+                        "Each version of '{}' needs exactly one of schema or content",
+                        schema.subject
+                    )));
+                }
+                if let Some(v) = &version.version {
+                    v.parse::<SemanticVersion>()?;
+                }
+            }
+        }
+
+        for webhook in &self.webhooks {
+            if webhook.fields.is_empty() {
+                return Err(invalid(format!(
+                    "Webhook for consumer '{}' of '{}' lists no fields",
+                    webhook.consumer, webhook.subject
+                )));
+            }
+        }
+
+        for edge in &self.lineage {
+            if edge.to.is_some() == edge.entity.is_some() {
+                return Err(invalid(format!(
+                    "Lineage edge from '{}' needs exactly one of to or entity",
+                    edge.from
+                )));
+            }
+            parse_schema_ref(&edge.from)?;
+            if let Some(to) = &edge.to {
+                parse_schema_ref(to)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The requests that create these fixtures, in the order to send them
+    pub fn requests(&self) -> Result<Vec<SeedRequest>> {
+        self.validate()?;
+        let mut requests = Vec::new();
+
+        for (namespace, settings) in &self.namespaces {
+            requests.push(SeedRequest {
+                method: "PUT",
+                path: format!("/api/v1/namespaces/{}/settings", namespace),
+                body: serde_json::to_value(settings)?,
+                description: format!("namespace {}", namespace),
+            });
+        }
+
+        for schema in &self.schemas {
+            for (index, version) in schema.versions.iter().enumerate() {
+                let mut body = json!({
+                    "subject": schema.subject,
+                    "schema_type": schema.format,
+                    "format": schema.format,
+                    "schema": version.schema.clone().unwrap_or(serde_json::Value::Null),
+                    "tags": version.tags,
+                    "metadata": version.metadata,
+                });
+                let fields = body.as_object_mut().expect("body is an object");
+                if let Some(content) = &version.content {
+                    fields.insert("content".to_string(), json!(content));
+                }
+                if let Some(mode) = &schema.compatibility_mode {
+                    fields.insert("compatibility_mode".to_string(), json!(mode));
+                }
+                if let Some(state) = &version.state {
+                    fields.insert("state".to_string(), json!(state));
+                }
+                if let Some(description) = &version.description {
+                    fields.insert("description".to_string(), json!(description));
+                }
+                let label = match &version.version {
+                    Some(v) => {
+                        let v: SemanticVersion = v.parse()?;
+                        fields.insert("version_major".to_string(), json!(v.major));
+                        fields.insert("version_minor".to_string(), json!(v.minor));
+                        fields.insert("version_patch".to_string(), json!(v.patch));
+                        v.to_string()
+                    }
+                    None => format!("#{}", index + 1),
+                };
+                requests.push(SeedRequest {
+                    method: "POST",
+                    path: "/api/v1/schemas".to_string(),
+                    body,
+                    description: format!("{} {}", schema.subject, label),
+                });
+            }
+        }
+
+        for webhook in &self.webhooks {
+            requests.push(SeedRequest {
+                method: "PUT",
+                path: format!(
+                    "/api/v1/subjects/{}/consumers/{}",
+                    webhook.subject, webhook.consumer
+                ),
+                body: json!({"webhook_url": webhook.url, "fields": webhook.fields}),
+                description: format!("webhook {} on {}", webhook.consumer, webhook.subject),
+            });
+        }
+
+        for edge in &self.lineage {
+            let target = match (&edge.to, &edge.entity) {
+                (Some(to), _) => to.as_str(),
+                (None, Some(entity)) => entity.id.as_str(),
+                (None, None) => "",
+            };
+            requests.push(SeedRequest {
+                method: "POST",
+                path: "/api/v1/lineage/edges".to_string(),
+                body: serde_json::to_value(edge)?,
+                description: format!("lineage {} -> {}", edge.from, target),
+            });
+        }
+
+        Ok(requests)
+    }
+}
+
+fn invalid(message: String) -> Error {
+    Error::ValidationError(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixtures() -> Fixtures {
+        serde_json::from_value(json!({
+            "namespaces": {"com.example": {"owner": "identity-team"}},
+            "schemas": [{
+                "subject": "com.example.User",
+                "versions": [
+                    {"schema": {"type": "object"}},
+                    {"version": "1.1.0", "schema": {"type": "object", "properties": {"id": {"type": "string"}}}}
+                ]
+            }],
+            "webhooks": [{"subject": "com.example.User", "consumer": "billing", "url": "https://hooks.example.com", "fields": ["id"]}],
+            "lineage": [{"from": "com.example.User@1.1.0", "entity": {"id": "billing-service", "type": "APPLICATION"}, "relation": "USED_BY"}]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_requests_are_in_dependency_order() {
+        let requests = fixtures().requests().unwrap();
+        let paths: Vec<_> = requests.iter().map(|r| r.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "/api/v1/namespaces/com.example/settings",
+                "/api/v1/schemas",
+                "/api/v1/schemas",
+                "/api/v1/subjects/com.example.User/consumers/billing",
+                "/api/v1/lineage/edges",
+            ]
+        );
+        assert!(requests[1].body.get("version_major").is_none());
+        assert_eq!(requests[2].body["version_minor"], 1);
+        assert_eq!(requests[2].description, "com.example.User 1.1.0");
+    }
+
+    #[test]
+    fn test_validate_rejects_incomplete_fixtures() {
+        let mut missing_content = fixtures();
+        missing_content.schemas[0].versions[0].schema = None;
+        assert!(missing_content.validate().is_err());
+
+        let mut bad_version = fixtures();
+        bad_version.lineage[0].from = "com.example.User@latest".to_string();
+        assert!(bad_version.validate().is_err());
+
+        let mut both_targets = fixtures();
+        both_targets.lineage[0].to = Some("com.example.Order".to_string());
+        assert!(both_targets.validate().is_err());
+    }
+}
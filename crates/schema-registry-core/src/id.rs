@@ -0,0 +1,361 @@
+//! Schema identifier generation
+//!
+//! Schema IDs are always [`Uuid`]s so that existing rows keep working, but the
+//! bits inside them are produced by a pluggable [`IdGenerator`]. The default is
+//! UUIDv7, which is time-ordered and keeps PostgreSQL B-tree indexes compact.
+//! A snowflake generator is available for deployments that want IDs that are
+//! sortable *and* encode the issuing worker.
+//!
+//! Legacy random (v4) IDs remain valid everywhere; [`IdKind::of`] can be used
+//! to tell them apart from time-sortable IDs during a migration.
+
+use chrono::{DateTime, TimeZone, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::error::{Error, Result};
+
+/// Custom epoch for snowflake IDs (2024-01-01T00:00:00Z) in milliseconds
+pub const SNOWFLAKE_EPOCH_MS: i64 = 1_704_067_200_000;
+
+const WORKER_ID_BITS: u64 = 10;
+const SEQUENCE_BITS: u64 = 12;
+const MAX_WORKER_ID: u64 = (1 << WORKER_ID_BITS) - 1;
+const MAX_SEQUENCE: u64 = (1 << SEQUENCE_BITS) - 1;
+
+/// Trait for generating schema identifiers
+pub trait IdGenerator: Send + Sync {
+    /// Generate a new unique identifier
+    fn generate(&self) -> Uuid;
+
+    /// Strategy implemented by this generator
+    fn strategy(&self) -> IdStrategy;
+}
+
+/// Configurable ID generation strategy
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum IdStrategy {
+    /// Time-ordered UUIDv7 (default)
+    #[default]
+    UuidV7,
+    /// Twitter-style snowflake packed into a UUIDv8
+    Snowflake {
+        /// Worker ID (0-1023), must be unique per registry instance
+        worker_id: u16,
+    },
+    /// Random UUIDv4 (legacy behaviour)
+    UuidV4,
+}
+
+impl std::str::FromStr for IdStrategy {
+    type Err = Error;
+
+    /// Parse a strategy name such as `uuidv7`, `uuidv4` or `snowflake:7`
+    ///
+    /// A snowflake strategy must name its worker: replicas sharing a worker id
+    /// can issue the same ID, so there is no default.
+    fn from_str(s: &str) -> Result<Self> {
+        let lower = s.trim().to_lowercase();
+        match lower.split_once(':') {
+            Some(("snowflake", worker)) => {
+                let worker_id = worker.parse::<u16>().map_err(|_| {
+                    Error::ConfigError(format!("Invalid snowflake worker id: {}", worker))
+                })?;
+                Ok(IdStrategy::Snowflake { worker_id })
+            }
+            None => match lower.as_str() {
+                "uuidv7" | "uuid_v7" | "v7" => Ok(IdStrategy::UuidV7),
+                "uuidv4" | "uuid_v4" | "v4" => Ok(IdStrategy::UuidV4),
+                "snowflake" => Err(Error::ConfigError(
+                    "Snowflake id strategy requires a worker id, e.g. snowflake:7".to_string(),
+                )),
+                _ => Err(Error::ConfigError(format!("Unknown id strategy: {}", s))),
+            },
+            _ => Err(Error::ConfigError(format!("Unknown id strategy: {}", s))),
+        }
+    }
+}
+
+impl IdStrategy {
+    /// Build a shareable generator for this strategy
+    pub fn build(self) -> Result<Arc<dyn IdGenerator>> {
+        Ok(match self {
+            IdStrategy::UuidV7 => Arc::new(UuidV7Generator),
+            IdStrategy::UuidV4 => Arc::new(UuidV4Generator),
+            IdStrategy::Snowflake { worker_id } => Arc::new(SnowflakeGenerator::new(worker_id)?),
+        })
+    }
+}
+
+/// Generates time-ordered UUIDv7 identifiers
+#[derive(Debug, Default, Clone, Copy)]
+pub struct UuidV7Generator;
+
+impl IdGenerator for UuidV7Generator {
+    fn generate(&self) -> Uuid {
+        Uuid::now_v7()
+    }
+
+    fn strategy(&self) -> IdStrategy {
+        IdStrategy::UuidV7
+    }
+}
+
+/// Generates random UUIDv4 identifiers
+#[derive(Debug, Default, Clone, Copy)]
+pub struct UuidV4Generator;
+
+impl IdGenerator for UuidV4Generator {
+    fn generate(&self) -> Uuid {
+        Uuid::new_v4()
+    }
+
+    fn strategy(&self) -> IdStrategy {
+        IdStrategy::UuidV4
+    }
+}
+
+/// Snowflake ID generator
+///
+/// Produces 64-bit IDs laid out as `41 bits timestamp | 10 bits worker | 12 bits sequence`
+/// and packs them into a UUIDv8 whose byte order preserves the snowflake ordering.
+#[derive(Debug)]
+pub struct SnowflakeGenerator {
+    worker_id: u64,
+    state: Mutex<SnowflakeState>,
+}
+
+#[derive(Debug, Default)]
+struct SnowflakeState {
+    last_timestamp: i64,
+    sequence: u64,
+}
+
+impl SnowflakeGenerator {
+    /// Create a new snowflake generator for the given worker
+    pub fn new(worker_id: u16) -> Result<Self> {
+        if u64::from(worker_id) > MAX_WORKER_ID {
+            return Err(Error::ConfigError(format!(
+                "Snowflake worker id {} exceeds maximum {}",
+                worker_id, MAX_WORKER_ID
+            )));
+        }
+
+        Ok(Self {
+            worker_id: u64::from(worker_id),
+            state: Mutex::new(SnowflakeState::default()),
+        })
+    }
+
+    /// Generate the next raw 64-bit snowflake
+    ///
+    /// Never blocks: once a millisecond's sequence is used up, IDs carry on
+    /// in the following millisecond and run ahead of the wall clock until it
+    /// catches up.
+    pub fn next_id(&self) -> u64 {
+        let mut state = self.state.lock();
+        let mut now = Self::current_millis();
+
+        // Never go backwards, even if the wall clock does
+        if now < state.last_timestamp {
+            now = state.last_timestamp;
+        }
+
+        if now == state.last_timestamp {
+            state.sequence = (state.sequence + 1) & MAX_SEQUENCE;
+            if state.sequence == 0 {
+                // Sequence exhausted for this millisecond: borrow the next one
+                // rather than wait for the clock, which may be behind by far
+                // more than a millisecond after stepping backwards
+                now = state.last_timestamp + 1;
+            }
+        } else {
+            state.sequence = 0;
+        }
+        state.last_timestamp = now;
+
+        ((now - SNOWFLAKE_EPOCH_MS) as u64) << (WORKER_ID_BITS + SEQUENCE_BITS)
+            | self.worker_id << SEQUENCE_BITS
+            | state.sequence
+    }
+
+    fn current_millis() -> i64 {
+        Utc::now().timestamp_millis().max(SNOWFLAKE_EPOCH_MS)
+    }
+}
+
+impl IdGenerator for SnowflakeGenerator {
+    fn generate(&self) -> Uuid {
+        snowflake_to_uuid(self.next_id())
+    }
+
+    fn strategy(&self) -> IdStrategy {
+        IdStrategy::Snowflake {
+            worker_id: self.worker_id as u16,
+        }
+    }
+}
+
+/// Pack a snowflake into a UUIDv8, keeping the version and variant bits valid
+pub fn snowflake_to_uuid(snowflake: u64) -> Uuid {
+    let mut bytes = [0u8; 16];
+    bytes[..6].copy_from_slice(&(snowflake >> 16).to_be_bytes()[2..]);
+    let low = (snowflake & 0xFFFF) as u16;
+    bytes[6] = 0x80 | (low >> 12) as u8;
+    bytes[7] = (low >> 4) as u8;
+    bytes[8] = 0x80 | (low & 0x0F) as u8;
+    Uuid::from_bytes(bytes)
+}
+
+/// Extract the snowflake from an ID produced by [`SnowflakeGenerator`]
+pub fn uuid_to_snowflake(id: &Uuid) -> Option<u64> {
+    if IdKind::of(id) != IdKind::Snowflake {
+        return None;
+    }
+
+    let bytes = id.as_bytes();
+    let mut high = [0u8; 8];
+    high[2..].copy_from_slice(&bytes[..6]);
+    let low = (u64::from(bytes[6] & 0x0F) << 12)
+        | (u64::from(bytes[7]) << 4)
+        | u64::from(bytes[8] & 0x0F);
+    Some(u64::from_be_bytes(high) << 16 | low)
+}
+
+/// Kind of identifier, used to distinguish legacy IDs from time-sortable ones
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IdKind {
+    /// Random UUIDv4 (legacy)
+    Random,
+    /// Time-ordered UUIDv7
+    TimeOrdered,
+    /// Snowflake packed into a UUIDv8
+    Snowflake,
+    /// Any other UUID version
+    Other,
+}
+
+impl IdKind {
+    /// Classify an existing identifier
+    pub fn of(id: &Uuid) -> Self {
+        match id.get_version_num() {
+            4 => IdKind::Random,
+            7 => IdKind::TimeOrdered,
+            8 => IdKind::Snowflake,
+            _ => IdKind::Other,
+        }
+    }
+
+    /// Whether IDs of this kind sort by creation time
+    pub fn is_time_sortable(&self) -> bool {
+        matches!(self, IdKind::TimeOrdered | IdKind::Snowflake)
+    }
+}
+
+/// Creation time embedded in a time-sortable ID, if any
+pub fn embedded_timestamp(id: &Uuid) -> Option<DateTime<Utc>> {
+    match IdKind::of(id) {
+        IdKind::TimeOrdered => {
+            let (secs, nanos) = id.get_timestamp()?.to_unix();
+            Utc.timestamp_opt(secs as i64, nanos).single()
+        }
+        IdKind::Snowflake => {
+            let snowflake = uuid_to_snowflake(id)?;
+            let millis = (snowflake >> (WORKER_ID_BITS + SEQUENCE_BITS)) as i64 + SNOWFLAKE_EPOCH_MS;
+            Utc.timestamp_millis_opt(millis).single()
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uuid_v7_is_time_ordered() {
+        let generator = UuidV7Generator;
+        let first = generator.generate();
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        let second = generator.generate();
+
+        assert_eq!(IdKind::of(&first), IdKind::TimeOrdered);
+        assert!(first < second);
+        assert!(embedded_timestamp(&first).is_some());
+    }
+
+    #[test]
+    fn test_snowflake_ids_are_monotonic() {
+        let generator = SnowflakeGenerator::new(42).unwrap();
+        let ids: Vec<Uuid> = (0..5000).map(|_| generator.generate()).collect();
+
+        for pair in ids.windows(2) {
+            assert!(pair[0] < pair[1]);
+        }
+        assert!(ids.iter().all(|id| IdKind::of(id) == IdKind::Snowflake));
+    }
+
+    #[test]
+    fn test_snowflake_advances_past_exhausted_sequence_without_waiting() {
+        let generator = SnowflakeGenerator::new(3).unwrap();
+        // As if the clock had stepped back an hour after the last ID
+        let ahead = SnowflakeGenerator::current_millis() + 3_600_000;
+        {
+            let mut state = generator.state.lock();
+            state.last_timestamp = ahead;
+            state.sequence = MAX_SEQUENCE;
+        }
+
+        let started = std::time::Instant::now();
+        let raw = generator.next_id();
+        assert!(started.elapsed() < std::time::Duration::from_secs(1));
+
+        let timestamp = (raw >> (WORKER_ID_BITS + SEQUENCE_BITS)) as i64 + SNOWFLAKE_EPOCH_MS;
+        assert_eq!(timestamp, ahead + 1);
+        assert_eq!(raw & MAX_SEQUENCE, 0);
+        assert!(generator.next_id() > raw);
+    }
+
+    #[test]
+    fn test_snowflake_uuid_roundtrip() {
+        let generator = SnowflakeGenerator::new(7).unwrap();
+        let raw = generator.next_id();
+        let id = snowflake_to_uuid(raw);
+
+        assert_eq!(id.get_version_num(), 8);
+        assert_eq!(uuid_to_snowflake(&id), Some(raw));
+        assert_eq!((raw >> SEQUENCE_BITS) & MAX_WORKER_ID, 7);
+    }
+
+    #[test]
+    fn test_snowflake_rejects_invalid_worker() {
+        assert!(SnowflakeGenerator::new(1024).is_err());
+    }
+
+    #[test]
+    fn test_legacy_v4_ids_remain_valid() {
+        let id = Uuid::new_v4();
+        assert_eq!(IdKind::of(&id), IdKind::Random);
+        assert!(!IdKind::of(&id).is_time_sortable());
+        assert!(embedded_timestamp(&id).is_none());
+        assert!(uuid_to_snowflake(&id).is_none());
+    }
+
+    #[test]
+    fn test_strategy_parsing() {
+        assert_eq!("uuidv7".parse::<IdStrategy>().unwrap(), IdStrategy::UuidV7);
+        assert_eq!("UUIDv4".parse::<IdStrategy>().unwrap(), IdStrategy::UuidV4);
+        assert_eq!(
+            "snowflake:12".parse::<IdStrategy>().unwrap(),
+            IdStrategy::Snowflake { worker_id: 12 }
+        );
+        assert!("ulid".parse::<IdStrategy>().is_err());
+        assert!("snowflake".parse::<IdStrategy>().is_err());
+        assert!("snowflake:".parse::<IdStrategy>().is_err());
+        assert_eq!(IdStrategy::default(), IdStrategy::UuidV7);
+    }
+}
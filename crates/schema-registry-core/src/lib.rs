@@ -11,10 +11,29 @@
 //! - Error types
 //! - Event system
 
+pub mod bounded_json;
+pub mod canonical_form;
+pub mod canonical_json;
+pub mod codegen;
+pub mod complexity;
+pub mod deadline;
+pub mod deprecation;
 pub mod error;
 pub mod events;
+pub mod field_diff;
+pub mod fields;
+pub mod fixtures;
+pub mod id;
+pub mod messages;
+pub mod metadata_patch;
+pub mod namespace;
+pub mod plugin;
+pub mod resource;
+pub mod retry;
 pub mod schema;
+pub mod secret_ref;
 pub mod state;
+pub mod subject_config;
 pub mod traits;
 pub mod types;
 pub mod versioning;
@@ -25,8 +44,22 @@ pub mod startup;
 pub mod config_refresh;
 
 // Re-export commonly used types
+pub use bounded_json::{JsonLimitError, JsonLimits};
+pub use complexity::SchemaComplexity;
+pub use deadline::Deadline;
+pub use deprecation::FieldDeprecation;
 pub use error::{Error, Result};
+pub use fields::FieldSelection;
+pub use id::{IdGenerator, IdStrategy};
+pub use messages::{Message, MessageCatalog, MessageCatalogs};
+pub use metadata_patch::MetadataPatch;
+pub use secret_ref::{SecretRef, SecretResolver, SecretValue};
+pub use namespace::{EffectiveSettings, NamespaceSettings, NamespaceTree, Visibility};
+pub use plugin::{InterceptorChain, RegistrationContext, RegistrationInterceptor, Veto};
+pub use resource::{ResourceStore, SchemaResource, SchemaSpec};
+pub use retry::{Classify, ErrorClass, RetryBudget, RetryPolicy};
 pub use schema::{RegisteredSchema, SchemaInput, SchemaMetadata};
 pub use state::{SchemaState, StateTransition, SchemaLifecycle};
+pub use subject_config::{SubjectConfig, SubjectConfigChange, SubjectConfigFile};
 pub use types::{CompatibilityMode, SerializationFormat};
 pub use versioning::SemanticVersion;
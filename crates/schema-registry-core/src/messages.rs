@@ -0,0 +1,373 @@
+//! Localized messages for validation and compatibility results
+//!
+//! Every violation the registry reports has a stable code, such as
+//! `compatibility.field_removed` or `validation.deprecated_field`, that
+//! clients can match on whatever language the text is in. The text comes from
+//! a catalog for the caller's locale, chosen from the `Accept-Language` header.
+//!
+//! A catalog is a JSON object mapping codes to templates, loaded from a
+//! `<locale>.json` file:
+//!
+//! ```json
+//! {"compatibility.field_removed": "Le champ « {field} » a été supprimé"}
+//! ```
+//!
+//! Templates name parameters in braces; `{{` and `}}` are literal braces, and a
+//! parameter the message doesn't have is left as written. A code with no
+//! template in the chosen catalog keeps the registry's own English text, so a
+//! partial translation never hides a violation.
+
+use crate::deprecation::FieldDeprecation;
+use crate::error::{Error, Result};
+use crate::traits::CompatibilityViolation;
+use crate::types::ViolationType;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Locale of the registry's own messages
+pub const DEFAULT_LOCALE: &str = "en";
+
+/// Data doesn't match its schema
+pub const DATA_MISMATCH: &str = "validation.data_mismatch";
+
+/// Data uses a field the schema deprecates
+pub const DEPRECATED_FIELD: &str = "validation.deprecated_field";
+
+/// Every code a catalog can translate, with the parameters its templates get
+pub const CODES: &[(&str, &[&str])] = &[
+    (DATA_MISMATCH, &["field", "detail"]),
+    (
+        DEPRECATED_FIELD,
+        &["field", "since", "replacement", "message"],
+    ),
+    ("compatibility.field_removed", VIOLATION_PARAMS),
+    ("compatibility.type_changed", VIOLATION_PARAMS),
+    ("compatibility.required_added", VIOLATION_PARAMS),
+    ("compatibility.constraint_added", VIOLATION_PARAMS),
+    ("compatibility.enum_value_removed", VIOLATION_PARAMS),
+    ("compatibility.format_changed", VIOLATION_PARAMS),
+];
+
+const VIOLATION_PARAMS: &[&str] = &["field", "old", "new", "description"];
+
+/// Stable code of a compatibility violation type
+pub fn violation_code(violation_type: &ViolationType) -> &'static str {
+    match violation_type {
+        ViolationType::FieldRemoved => "compatibility.field_removed",
+        ViolationType::TypeChanged => "compatibility.type_changed",
+        ViolationType::RequiredAdded => "compatibility.required_added",
+        ViolationType::ConstraintAdded => "compatibility.constraint_added",
+        ViolationType::EnumValueRemoved => "compatibility.enum_value_removed",
+        ViolationType::FormatChanged => "compatibility.format_changed",
+    }
+}
+
+/// A message rendered for one locale
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Message {
+    pub code: String,
+    /// Path of the field the message is about
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+    pub message: String,
+}
+
+/// Templates for one locale, by code
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessageCatalog {
+    locale: String,
+    templates: BTreeMap<String, String>,
+}
+
+impl MessageCatalog {
+    /// Locale tags are matched case-insensitively, so they're kept lowercase
+    pub fn new(locale: impl Into<String>, templates: BTreeMap<String, String>) -> Self {
+        Self {
+            locale: locale.into().to_ascii_lowercase(),
+            templates,
+        }
+    }
+
+    /// Parse a catalog file's JSON object of templates
+    pub fn from_json(locale: impl Into<String>, json: &str) -> Result<Self> {
+        let locale = locale.into();
+        let templates = serde_json::from_str(json).map_err(|e| {
+            Error::ConfigError(format!("Invalid message catalog '{}': {}", locale, e))
+        })?;
+        Ok(Self::new(locale, templates))
+    }
+
+    pub fn locale(&self) -> &str {
+        &self.locale
+    }
+
+    pub fn template(&self, code: &str) -> Option<&str> {
+        self.templates.get(code).map(String::as_str)
+    }
+
+    /// Codes this catalog translates
+    pub fn codes(&self) -> impl Iterator<Item = &str> {
+        self.templates.keys().map(String::as_str)
+    }
+}
+
+/// Substitute `{name}` placeholders in `template`
+pub fn interpolate(template: &str, params: &[(&str, &str)]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find(['{', '}']) {
+        out.push_str(&rest[..start]);
+        let tail = &rest[start..];
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            out.push_str(&tail[..1]);
+            rest = &tail[2..];
+            continue;
+        }
+        let placeholder = tail
+            .strip_prefix('{')
+            .and_then(|after| after.find('}').map(|end| &after[..end]));
+        let value = placeholder.and_then(|name| {
+            params
+                .iter()
+                .find(|(param, _)| *param == name)
+                .map(|(_, value)| *value)
+        });
+        match (placeholder, value) {
+            (Some(name), Some(value)) => {
+                out.push_str(value);
+                rest = &tail[name.len() + 2..];
+            }
+            _ => {
+                out.push_str(&tail[..1]);
+                rest = &tail[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Every loaded catalog, by locale
+#[derive(Debug, Clone, Default)]
+pub struct MessageCatalogs {
+    catalogs: BTreeMap<String, MessageCatalog>,
+}
+
+impl MessageCatalogs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a catalog, replacing any for the same locale
+    pub fn with_catalog(mut self, catalog: MessageCatalog) -> Self {
+        self.catalogs.insert(catalog.locale.clone(), catalog);
+        self
+    }
+
+    /// Load every `<locale>.json` file in `dir`; other files are ignored
+    pub fn load_dir(dir: impl AsRef<Path>) -> Result<Self> {
+        let mut catalogs = Self::new();
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            let Some(locale) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+            let json = std::fs::read_to_string(&path)?;
+            catalogs = catalogs.with_catalog(MessageCatalog::from_json(locale, &json)?);
+        }
+        Ok(catalogs)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.catalogs.is_empty()
+    }
+
+    /// Every catalog, sorted by locale
+    pub fn catalogs(&self) -> impl Iterator<Item = &MessageCatalog> {
+        self.catalogs.values()
+    }
+
+    pub fn catalog(&self, locale: &str) -> Option<&MessageCatalog> {
+        self.catalogs.get(&locale.to_ascii_lowercase())
+    }
+
+    /// Locale to answer in for an `Accept-Language` header
+    ///
+    /// Ranges are tried by descending quality, each first as written and then
+    /// by its primary subtag (`fr-CA` falls back to `fr`). English, `*`, or no
+    /// match at all answers in [`DEFAULT_LOCALE`].
+    pub fn negotiate(&self, accept_language: Option<&str>) -> &str {
+        let mut ranges: Vec<(&str, f32)> = accept_language
+            .unwrap_or_default()
+            .split(',')
+            .filter_map(|range| {
+                let mut parts = range.split(';');
+                let tag = parts.next()?.trim();
+                let quality = parts
+                    .filter_map(|param| param.trim().strip_prefix("q="))
+                    .find_map(|q| q.trim().parse::<f32>().ok())
+                    .unwrap_or(1.0);
+                (!tag.is_empty() && quality > 0.0).then_some((tag, quality))
+            })
+            .collect();
+        // Stable, so equal qualities keep the header's order
+        ranges.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        for (tag, _) in ranges {
+            let tag = tag.to_ascii_lowercase();
+            let primary = tag.split('-').next().unwrap_or_default();
+            if tag == "*" || primary == DEFAULT_LOCALE {
+                break;
+            }
+            for candidate in [tag.as_str(), primary] {
+                if let Some((locale, _)) = self.catalogs.get_key_value(candidate) {
+                    return locale;
+                }
+            }
+        }
+        DEFAULT_LOCALE
+    }
+
+    /// Render `code` in `locale`, or keep `english` if it has no template
+    pub fn render(
+        &self,
+        locale: &str,
+        code: &str,
+        params: &[(&str, &str)],
+        english: String,
+    ) -> String {
+        match self
+            .catalog(locale)
+            .and_then(|catalog| catalog.template(code))
+        {
+            Some(template) => interpolate(template, params),
+            None => english,
+        }
+    }
+
+    /// A compatibility violation in `locale`
+    pub fn violation(&self, locale: &str, violation: &CompatibilityViolation) -> Message {
+        let value = |value: &Option<serde_json::Value>| match value {
+            Some(serde_json::Value::String(s)) => s.clone(),
+            Some(value) => value.to_string(),
+            None => String::new(),
+        };
+        let code = violation_code(&violation.violation_type);
+        let (old, new) = (value(&violation.old_value), value(&violation.new_value));
+        let params = [
+            ("field", violation.field_path.as_str()),
+            ("old", old.as_str()),
+            ("new", new.as_str()),
+            ("description", violation.description.as_str()),
+        ];
+        Message {
+            code: code.to_string(),
+            field: Some(violation.field_path.clone()),
+            message: self.render(locale, code, &params, violation.description.clone()),
+        }
+    }
+
+    /// A notice, in `locale`, that data uses a deprecated field
+    pub fn deprecation(&self, locale: &str, deprecation: &FieldDeprecation) -> Message {
+        let since = deprecation
+            .since
+            .as_ref()
+            .map(ToString::to_string)
+            .unwrap_or_default();
+        let params = [
+            ("field", deprecation.path.as_str()),
+            ("since", since.as_str()),
+            (
+                "replacement",
+                deprecation.replacement.as_deref().unwrap_or_default(),
+            ),
+            (
+                "message",
+                deprecation.message.as_deref().unwrap_or_default(),
+            ),
+        ];
+        Message {
+            code: DEPRECATED_FIELD.to_string(),
+            field: Some(deprecation.path.clone()),
+            message: self.render(locale, DEPRECATED_FIELD, &params, deprecation.warning()),
+        }
+    }
+
+    /// A notice, in `locale`, that the value at `field` doesn't match its
+    /// schema, as the validator's English `detail` explains
+    pub fn data_mismatch(&self, locale: &str, field: Option<&str>, detail: &str) -> Message {
+        let params = [("field", field.unwrap_or_default()), ("detail", detail)];
+        Message {
+            code: DATA_MISMATCH.to_string(),
+            field: field.map(str::to_string),
+            message: self.render(locale, DATA_MISMATCH, &params, detail.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ViolationSeverity;
+
+    fn french() -> MessageCatalogs {
+        MessageCatalogs::new().with_catalog(
+            MessageCatalog::from_json(
+                "fr",
+                r#"{"compatibility.field_removed": "Le champ « {field} » a été supprimé ({{{old}}})"}"#,
+            )
+            .unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_interpolation_leaves_unknown_placeholders() {
+        assert_eq!(
+            interpolate("{field} {{literal}} {missing} {", &[("field", "id")]),
+            "id {literal} {missing} {"
+        );
+    }
+
+    #[test]
+    fn test_negotiation_prefers_quality_then_primary_subtag() {
+        let catalogs = french().with_catalog(MessageCatalog::new("DE", BTreeMap::new()));
+
+        assert_eq!(catalogs.negotiate(Some("fr-CA, de;q=0.9")), "fr");
+        assert_eq!(catalogs.negotiate(Some("fr;q=0.5, de")), "de");
+        assert_eq!(catalogs.negotiate(Some("en-US, fr;q=0.8")), DEFAULT_LOCALE);
+        assert_eq!(catalogs.negotiate(Some("ja, fr;q=0")), DEFAULT_LOCALE);
+        assert_eq!(catalogs.negotiate(None), DEFAULT_LOCALE);
+    }
+
+    #[test]
+    fn test_untranslated_codes_keep_english_text() {
+        let catalogs = french();
+        let violation = CompatibilityViolation {
+            violation_type: ViolationType::FieldRemoved,
+            field_path: "email".to_string(),
+            old_value: Some(serde_json::json!("string")),
+            new_value: None,
+            severity: ViolationSeverity::Breaking,
+            description: "Field 'email' was removed".to_string(),
+            suggestion: None,
+        };
+
+        let message = catalogs.violation("fr", &violation);
+        assert_eq!(message.code, "compatibility.field_removed");
+        assert_eq!(
+            message.message,
+            "Le champ « email » a été supprimé ({string})"
+        );
+        assert_eq!(
+            catalogs.violation(DEFAULT_LOCALE, &violation).message,
+            "Field 'email' was removed"
+        );
+        let mismatch = catalogs.data_mismatch("fr", Some("/id"), "Expected string, found integer");
+        assert_eq!(mismatch.field.as_deref(), Some("/id"));
+        assert_eq!(mismatch.message, "Expected string, found integer");
+    }
+}
@@ -0,0 +1,262 @@
+//! Metadata-only updates
+//!
+//! A schema's description, tags and custom metadata document it without
+//! changing the data it accepts, so fixing them doesn't register a new
+//! version. A [`MetadataPatch`] updates them in place and leaves the content,
+//! its hash and the version untouched.
+//!
+//! Fields left out of a patch are kept. `description` and `tags` are replaced
+//! as a whole, while `metadata` is merged key by key, with `null` removing a
+//! key. The [`RESERVED_METADATA_KEYS`] are written by the registry itself and
+//! can't be patched:
+//!
+//! ```
+//! use schema_registry_core::metadata_patch::{Documentation, MetadataPatch};
+//! use serde_json::json;
+//!
+//! let mut documentation = Documentation {
+//!     description: Some("Inference evnt".to_string()),
+//!     tags: vec!["llm".to_string()],
+//!     metadata: [("team".to_string(), json!("ml")), ("draft".to_string(), json!(true))].into(),
+//! };
+//! let patch: MetadataPatch = serde_json::from_value(json!({
+//!     "description": "Inference event",
+//!     "metadata": {"draft": null},
+//! }))
+//! .unwrap();
+//! assert!(patch.apply(&mut documentation).unwrap());
+//! assert_eq!(documentation.description.as_deref(), Some("Inference event"));
+//! assert_eq!(documentation.tags, ["llm"]);
+//! assert_eq!(documentation.metadata.len(), 1);
+//! ```
+
+use crate::canonical_json;
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Longest description, in characters
+pub const MAX_DESCRIPTION_LENGTH: usize = 1000;
+/// Most tags on one version
+pub const MAX_TAGS: usize = 50;
+/// Longest tag, in characters
+pub const MAX_TAG_LENGTH: usize = 50;
+/// Largest custom metadata, in bytes of JSON
+pub const MAX_METADATA_BYTES: usize = 10_240;
+/// Metadata keys only the registry writes: the owner searches filter on and
+/// the tombstone of a deleted version
+pub const RESERVED_METADATA_KEYS: [&str; 2] = ["owner", "deletion"];
+
+/// The documenting fields of a stored version
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Documentation {
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default, serialize_with = "canonical_json::sorted_value_map")]
+    pub metadata: HashMap<String, Value>,
+}
+
+impl Documentation {
+    /// Check the fields against the metadata limits
+    pub fn validate(&self) -> Result<()> {
+        let mut problems = Vec::new();
+        if let Some(description) = &self.description {
+            if description.chars().count() > MAX_DESCRIPTION_LENGTH {
+                problems.push(format!(
+                    "description is longer than {} characters",
+                    MAX_DESCRIPTION_LENGTH
+                ));
+            }
+        }
+        if self.tags.len() > MAX_TAGS {
+            problems.push(format!("more than {} tags", MAX_TAGS));
+        }
+        for tag in &self.tags {
+            if tag.is_empty() || tag.chars().count() > MAX_TAG_LENGTH {
+                problems.push(format!(
+                    "tag '{}' must be 1 to {} characters",
+                    tag, MAX_TAG_LENGTH
+                ));
+            } else if !tag
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'))
+            {
+                problems.push(format!(
+                    "tag '{}' may only hold letters, digits, '-', '_', '.' and ':'",
+                    tag
+                ));
+            }
+        }
+        let metadata_bytes = serde_json::to_vec(&self.metadata)
+            .map(|json| json.len())
+            .unwrap_or(usize::MAX);
+        if metadata_bytes > MAX_METADATA_BYTES {
+            problems.push(format!(
+                "metadata is larger than {} bytes",
+                MAX_METADATA_BYTES
+            ));
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::ValidationError(format!(
+                "Invalid schema metadata: {}",
+                problems.join("; ")
+            )))
+        }
+    }
+}
+
+/// Changes to the documenting fields of a version
+///
+/// Unknown fields are refused, so a patch can't carry content changes that
+/// would otherwise be silently dropped.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MetadataPatch {
+    /// New description; an empty one clears it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// New tags, replacing the current ones
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+    /// Keys to set, or to remove when `null`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<HashMap<String, Value>>,
+}
+
+impl MetadataPatch {
+    /// Whether the patch names nothing to change
+    pub fn is_empty(&self) -> bool {
+        self.description.is_none() && self.tags.is_none() && self.metadata.is_none()
+    }
+
+    /// Apply the patch to `documentation`, returning whether it changed
+    ///
+    /// The result is validated before `documentation` is touched, so a
+    /// refused patch leaves it as it was.
+    pub fn apply(&self, documentation: &mut Documentation) -> Result<bool> {
+        let mut patched = documentation.clone();
+        if let Some(description) = &self.description {
+            let description = description.trim();
+            patched.description = (!description.is_empty()).then(|| description.to_string());
+        }
+        if let Some(tags) = &self.tags {
+            patched.tags.clear();
+            for tag in tags {
+                if !patched.tags.contains(tag) {
+                    patched.tags.push(tag.clone());
+                }
+            }
+        }
+        if let Some(metadata) = &self.metadata {
+            if let Some(key) = metadata
+                .keys()
+                .find(|key| RESERVED_METADATA_KEYS.contains(&key.as_str()))
+            {
+                return Err(Error::ValidationError(format!(
+                    "Metadata key '{}' is reserved",
+                    key
+                )));
+            }
+            for (key, value) in metadata {
+                match value {
+                    Value::Null => patched.metadata.remove(key),
+                    value => patched.metadata.insert(key.clone(), value.clone()),
+                };
+            }
+        }
+        patched.validate()?;
+
+        let changed = patched != *documentation;
+        *documentation = patched;
+        Ok(changed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_unchanged_fields_are_kept() {
+        let mut documentation = Documentation {
+            description: Some("Orders".to_string()),
+            tags: vec!["sales".to_string()],
+            metadata: HashMap::from([("owner".to_string(), json!("sales"))]),
+        };
+        let patch = MetadataPatch {
+            tags: Some(vec!["sales".to_string(), "pii".to_string(), "pii".to_string()]),
+            ..Default::default()
+        };
+        assert!(patch.apply(&mut documentation).unwrap());
+        assert_eq!(documentation.description.as_deref(), Some("Orders"));
+        assert_eq!(documentation.tags, ["sales", "pii"]);
+
+        // Applying it again changes nothing
+        assert!(!patch.apply(&mut documentation).unwrap());
+    }
+
+    #[test]
+    fn test_invalid_patches_leave_documentation_alone() {
+        let mut documentation = Documentation::default();
+        let patch = MetadataPatch {
+            description: Some("x".repeat(MAX_DESCRIPTION_LENGTH + 1)),
+            tags: Some(vec!["has space".to_string()]),
+            ..Default::default()
+        };
+        let error = patch.apply(&mut documentation).unwrap_err().to_string();
+        assert!(error.contains("description"));
+        assert!(error.contains("'has space'"));
+        assert_eq!(documentation, Documentation::default());
+    }
+
+    #[test]
+    fn test_content_changes_are_refused() {
+        let patch = serde_json::from_value::<MetadataPatch>(json!({
+            "description": "Orders",
+            "content": "{}",
+        }));
+        assert!(patch.is_err());
+        assert!(serde_json::from_value::<MetadataPatch>(json!({}))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_reserved_keys_are_refused() {
+        let mut documentation = Documentation {
+            metadata: HashMap::from([("deletion".to_string(), json!({"by": "alice"}))]),
+            ..Default::default()
+        };
+        for key in RESERVED_METADATA_KEYS {
+            let patch = MetadataPatch {
+                metadata: Some(HashMap::from([(key.to_string(), Value::Null)])),
+                ..Default::default()
+            };
+            let error = patch.apply(&mut documentation).unwrap_err().to_string();
+            assert!(error.contains(key));
+        }
+        assert_eq!(documentation.metadata["deletion"], json!({"by": "alice"}));
+    }
+
+    #[test]
+    fn test_metadata_serializes_in_key_order() {
+        let documentation = Documentation {
+            metadata: HashMap::from([
+                ("b".to_string(), json!({"y": 1, "x": 2})),
+                ("a".to_string(), json!(true)),
+            ]),
+            ..Default::default()
+        };
+        assert_eq!(
+            serde_json::to_string(&documentation).unwrap(),
+            r#"{"description":null,"tags":[],"metadata":{"a":true,"b":{"x":2,"y":1}}}"#
+        );
+    }
+}
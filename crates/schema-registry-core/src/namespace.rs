@@ -0,0 +1,559 @@
+//! Hierarchical namespaces with inherited settings
+//!
+//! Namespaces are dot-separated paths (`com.example.payments`). Settings set
+//! on a namespace apply to every namespace below it unless a descendant sets
+//! its own value. [`NamespaceTree::effective`] resolves the settings for a
+//! namespace and records which ancestor each value came from.
+//!
+//! The compatibility mode can also be overridden per deployment environment,
+//! so one subject can evolve under `BACKWARD` in `dev` and `FULL_TRANSITIVE`
+//! in `prod`. An environment override set anywhere above a namespace takes
+//! precedence over plain compatibility modes; among overrides for the same
+//! environment the nearest namespace wins.
+//!
+//! Namespaces are private unless they, or their nearest configured ancestor,
+//! are marked [`Visibility::Public`]. Only public namespaces are served by the
+//! server's unauthenticated partner API.
+//!
+//! A namespace's owner and the channel its owner is notified on change
+//! together, through [`NamespaceTree::set_ownership`].
+
+use crate::error::{Error, Result};
+use crate::types::CompatibilityMode;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Settings explicitly configured on one namespace
+///
+/// `None` (or a missing policy key) means "inherit from the parent".
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NamespaceSettings {
+    /// Compatibility mode for new schemas
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compatibility_mode: Option<CompatibilityMode>,
+    /// Compatibility mode overrides by environment name, inherited key by key
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub environments: BTreeMap<String, CompatibilityMode>,
+    /// Owning team or person
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    /// Webhook URL notices for the owner are posted to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notification_channel: Option<String>,
+    /// Maximum number of schemas in the namespace, not counting deleted ones
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_schemas: Option<u64>,
+    /// Maximum number of versions of a single schema, not counting deleted ones
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_versions_per_schema: Option<u64>,
+    /// Named policies, inherited key by key
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub policies: BTreeMap<String, serde_json::Value>,
+    /// Whether the namespace is readable without credentials
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub visibility: Option<Visibility>,
+}
+
+/// Who may read a namespace's schemas
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Visibility {
+    /// Only through the authenticated API
+    #[default]
+    Private,
+    /// Also through the read-only public API
+    Public,
+}
+
+/// Where an effective setting came from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "namespace", rename_all = "snake_case")]
+pub enum SettingSource {
+    /// Set on this namespace or one of its ancestors
+    Namespace(String),
+    /// Registry-wide default
+    Default,
+}
+
+/// A resolved setting and its origin
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EffectiveValue<T> {
+    pub value: T,
+    pub source: SettingSource,
+}
+
+/// Fully resolved settings for a namespace
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EffectiveSettings {
+    pub namespace: String,
+    pub compatibility_mode: EffectiveValue<CompatibilityMode>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub environments: BTreeMap<String, EffectiveValue<CompatibilityMode>>,
+    pub owner: EffectiveValue<Option<String>>,
+    pub notification_channel: EffectiveValue<Option<String>>,
+    pub max_schemas: EffectiveValue<Option<u64>>,
+    pub max_versions_per_schema: EffectiveValue<Option<u64>>,
+    pub policies: BTreeMap<String, EffectiveValue<serde_json::Value>>,
+    pub visibility: EffectiveValue<Visibility>,
+}
+
+impl EffectiveSettings {
+    /// The compatibility mode in `environment`, and which scope provided it
+    ///
+    /// Without an environment, or without an override for it, this is the
+    /// plain compatibility mode.
+    pub fn compatibility_in(&self, environment: Option<&str>) -> EffectiveCompatibility {
+        match environment.and_then(|env| Some((env, self.environments.get(env)?))) {
+            Some((env, effective)) => EffectiveCompatibility {
+                mode: effective.value,
+                source: effective.source.clone(),
+                environment: Some(env.to_string()),
+            },
+            None => EffectiveCompatibility {
+                mode: self.compatibility_mode.value,
+                source: self.compatibility_mode.source.clone(),
+                environment: None,
+            },
+        }
+    }
+}
+
+/// A resolved compatibility mode and the scope it came from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EffectiveCompatibility {
+    pub mode: CompatibilityMode,
+    pub source: SettingSource,
+    /// Environment whose override applied, `None` for the plain mode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub environment: Option<String>,
+}
+
+/// Normalize and validate a namespace path
+///
+/// A trailing `.*` wildcard is accepted and refers to the namespace itself.
+pub fn normalize(namespace: &str) -> Result<String> {
+    let namespace = namespace.trim();
+    let namespace = namespace.strip_suffix(".*").unwrap_or(namespace);
+
+    if namespace.is_empty() {
+        return Err(Error::ValidationError("Namespace must not be empty".to_string()));
+    }
+    for segment in namespace.split('.') {
+        if segment.is_empty()
+            || !segment
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            return Err(Error::ValidationError(format!(
+                "Invalid namespace '{}': segments must be non-empty and contain only letters, digits, '_' or '-'",
+                namespace
+            )));
+        }
+    }
+    Ok(namespace.to_string())
+}
+
+/// The namespace followed by each of its ancestors, nearest first
+///
+/// `com.example.payments` yields `com.example.payments`, `com.example`, `com`.
+pub fn ancestors(namespace: &str) -> impl Iterator<Item = &str> {
+    let mut next = Some(namespace);
+    std::iter::from_fn(move || {
+        let current = next?;
+        next = current.rfind('.').map(|pos| &current[..pos]);
+        Some(current)
+    })
+}
+
+/// Settings for all configured namespaces
+pub struct NamespaceTree {
+    settings: RwLock<HashMap<String, NamespaceSettings>>,
+    default_compatibility_mode: CompatibilityMode,
+}
+
+impl NamespaceTree {
+    /// Create an empty tree with the given registry-wide compatibility mode
+    pub fn new(default_compatibility_mode: CompatibilityMode) -> Self {
+        Self {
+            settings: RwLock::new(HashMap::new()),
+            default_compatibility_mode,
+        }
+    }
+
+    /// Replace the explicit settings of a namespace
+    pub fn set(&self, namespace: &str, settings: NamespaceSettings) -> Result<()> {
+        let namespace = normalize(namespace)?;
+        self.settings.write().insert(namespace, settings);
+        Ok(())
+    }
+
+    /// Explicit settings of a namespace, without inheritance
+    pub fn get(&self, namespace: &str) -> Option<NamespaceSettings> {
+        let namespace = normalize(namespace).ok()?;
+        self.settings.read().get(&namespace).cloned()
+    }
+
+    /// Explicit settings of every configured namespace
+    pub fn all(&self) -> BTreeMap<String, NamespaceSettings> {
+        self.settings
+            .read()
+            .iter()
+            .map(|(namespace, settings)| (namespace.clone(), settings.clone()))
+            .collect()
+    }
+
+    /// Replace the explicit settings of every namespace at once
+    ///
+    /// Namespaces missing from `settings` lose their explicit settings.
+    pub fn replace_all(&self, settings: BTreeMap<String, NamespaceSettings>) -> Result<()> {
+        let settings = settings
+            .into_iter()
+            .map(|(namespace, settings)| Ok((normalize(&namespace)?, settings)))
+            .collect::<Result<HashMap<_, _>>>()?;
+        *self.settings.write() = settings;
+        Ok(())
+    }
+
+    /// Remove the explicit settings of a namespace so it inherits everything
+    pub fn remove(&self, namespace: &str) -> Option<NamespaceSettings> {
+        let namespace = normalize(namespace).ok()?;
+        self.settings.write().remove(&namespace)
+    }
+
+    /// Whether a namespace is effectively public
+    ///
+    /// Invalid namespaces are never public.
+    pub fn is_public(&self, namespace: &str) -> bool {
+        let Ok(namespace) = normalize(namespace) else {
+            return false;
+        };
+        let settings = self.settings.read();
+        let visibility = ancestors(&namespace).find_map(|current| settings.get(current)?.visibility);
+        visibility == Some(Visibility::Public)
+    }
+
+    /// Namespaces that set their visibility explicitly
+    ///
+    /// A namespace is public when the longest of these that is it or one of
+    /// its ancestors is public; this lets storage filter by visibility
+    /// without resolving every namespace.
+    pub fn visibility_rules(&self) -> BTreeMap<String, Visibility> {
+        self.settings
+            .read()
+            .iter()
+            .filter_map(|(namespace, settings)| Some((namespace.clone(), settings.visibility?)))
+            .collect()
+    }
+
+    /// Resolve the effective settings of a namespace
+    pub fn effective(&self, namespace: &str) -> Result<EffectiveSettings> {
+        let namespace = normalize(namespace)?;
+        Ok(self.resolve(&self.settings.read(), namespace))
+    }
+
+    /// Hand a namespace to `owner`, notified on `notification_channel`
+    ///
+    /// Both are set on the namespace itself in one step, and only if its
+    /// effective owner is still `expected_owner`; otherwise nothing changes.
+    /// Returns the namespace's explicit settings from before the change.
+    pub fn set_ownership(
+        &self,
+        namespace: &str,
+        expected_owner: Option<&str>,
+        owner: &str,
+        notification_channel: Option<String>,
+    ) -> Result<Option<NamespaceSettings>> {
+        let namespace = normalize(namespace)?;
+        let mut settings = self.settings.write();
+        let current = self.resolve(&settings, namespace.clone()).owner.value;
+        if current.as_deref() != expected_owner {
+            return Err(Error::StateTransitionError(format!(
+                "Namespace '{}' is now owned by {}",
+                namespace,
+                current.as_deref().unwrap_or("nobody")
+            )));
+        }
+
+        let previous = settings.get(&namespace).cloned();
+        let entry = settings.entry(namespace).or_default();
+        entry.owner = Some(owner.to_string());
+        entry.notification_channel = notification_channel;
+        Ok(previous)
+    }
+
+    fn resolve(
+        &self,
+        settings: &HashMap<String, NamespaceSettings>,
+        namespace: String,
+    ) -> EffectiveSettings {
+        let mut effective = EffectiveSettings {
+            namespace: namespace.clone(),
+            compatibility_mode: EffectiveValue {
+                value: self.default_compatibility_mode,
+                source: SettingSource::Default,
+            },
+            environments: BTreeMap::new(),
+            owner: EffectiveValue { value: None, source: SettingSource::Default },
+            notification_channel: EffectiveValue { value: None, source: SettingSource::Default },
+            max_schemas: EffectiveValue { value: None, source: SettingSource::Default },
+            max_versions_per_schema: EffectiveValue { value: None, source: SettingSource::Default },
+            policies: BTreeMap::new(),
+            visibility: EffectiveValue { value: Visibility::default(), source: SettingSource::Default },
+        };
+
+        // Walk from the root down so nearer namespaces override their parents
+        let chain: Vec<&str> = ancestors(&namespace).collect();
+        for current in chain.into_iter().rev() {
+            let Some(explicit) = settings.get(current) else {
+                continue;
+            };
+            let source = || SettingSource::Namespace(current.to_string());
+
+            if let Some(mode) = explicit.compatibility_mode {
+                effective.compatibility_mode = EffectiveValue { value: mode, source: source() };
+            }
+            for (environment, mode) in &explicit.environments {
+                effective
+                    .environments
+                    .insert(environment.clone(), EffectiveValue { value: *mode, source: source() });
+            }
+            if let Some(owner) = &explicit.owner {
+                effective.owner = EffectiveValue { value: Some(owner.clone()), source: source() };
+            }
+            if let Some(channel) = &explicit.notification_channel {
+                effective.notification_channel =
+                    EffectiveValue { value: Some(channel.clone()), source: source() };
+            }
+            if let Some(max) = explicit.max_schemas {
+                effective.max_schemas = EffectiveValue { value: Some(max), source: source() };
+            }
+            if let Some(max) = explicit.max_versions_per_schema {
+                effective.max_versions_per_schema =
+                    EffectiveValue { value: Some(max), source: source() };
+            }
+            for (name, value) in &explicit.policies {
+                effective
+                    .policies
+                    .insert(name.clone(), EffectiveValue { value: value.clone(), source: source() });
+            }
+            if let Some(visibility) = explicit.visibility {
+                effective.visibility = EffectiveValue { value: visibility, source: source() };
+            }
+        }
+
+        effective
+    }
+}
+
+impl Default for NamespaceTree {
+    fn default() -> Self {
+        Self::new(CompatibilityMode::Backward)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_and_ancestors() {
+        assert_eq!(normalize("com.example.payments.*").unwrap(), "com.example.payments");
+        assert!(normalize("com..example").is_err());
+        assert!(normalize("").is_err());
+
+        let chain: Vec<_> = ancestors("com.example.payments").collect();
+        assert_eq!(chain, vec!["com.example.payments", "com.example", "com"]);
+    }
+
+    #[test]
+    fn test_effective_settings_inherit_and_override() {
+        let tree = NamespaceTree::default();
+        tree.set(
+            "com.example",
+            NamespaceSettings {
+                compatibility_mode: Some(CompatibilityMode::Full),
+                owner: Some("platform".to_string()),
+                max_schemas: Some(100),
+                policies: BTreeMap::from([
+                    ("require_docs".to_string(), serde_json::json!(true)),
+                    ("pii".to_string(), serde_json::json!("deny")),
+                ]),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        tree.set(
+            "com.example.payments",
+            NamespaceSettings {
+                owner: Some("payments".to_string()),
+                policies: BTreeMap::from([("pii".to_string(), serde_json::json!("encrypt"))]),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let effective = tree.effective("com.example.payments.refunds").unwrap();
+        assert_eq!(effective.compatibility_mode.value, CompatibilityMode::Full);
+        assert_eq!(
+            effective.compatibility_mode.source,
+            SettingSource::Namespace("com.example".to_string())
+        );
+        assert_eq!(effective.owner.value.as_deref(), Some("payments"));
+        assert_eq!(
+            effective.owner.source,
+            SettingSource::Namespace("com.example.payments".to_string())
+        );
+        assert_eq!(effective.max_schemas.value, Some(100));
+        assert_eq!(effective.max_versions_per_schema.source, SettingSource::Default);
+        assert_eq!(effective.policies["pii"].value, serde_json::json!("encrypt"));
+        assert_eq!(
+            effective.policies["require_docs"].source,
+            SettingSource::Namespace("com.example".to_string())
+        );
+
+        // Unrelated namespaces only see defaults
+        let other = tree.effective("org.other").unwrap();
+        assert_eq!(other.compatibility_mode.value, CompatibilityMode::Backward);
+        assert_eq!(other.compatibility_mode.source, SettingSource::Default);
+    }
+
+    #[test]
+    fn test_environment_overrides() {
+        let tree = NamespaceTree::default();
+        tree.set(
+            "com.example",
+            NamespaceSettings {
+                environments: BTreeMap::from([
+                    ("prod".to_string(), CompatibilityMode::FullTransitive),
+                    ("staging".to_string(), CompatibilityMode::Full),
+                ]),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        tree.set(
+            "com.example.orders",
+            NamespaceSettings {
+                compatibility_mode: Some(CompatibilityMode::None),
+                environments: BTreeMap::from([("staging".to_string(), CompatibilityMode::Forward)]),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let effective = tree.effective("com.example.orders").unwrap();
+
+        // Overrides from further up beat a nearer plain mode
+        let prod = effective.compatibility_in(Some("prod"));
+        assert_eq!(prod.mode, CompatibilityMode::FullTransitive);
+        assert_eq!(prod.source, SettingSource::Namespace("com.example".to_string()));
+        assert_eq!(prod.environment.as_deref(), Some("prod"));
+
+        // Among overrides the nearest wins
+        let staging = effective.compatibility_in(Some("staging"));
+        assert_eq!(staging.mode, CompatibilityMode::Forward);
+        assert_eq!(staging.source, SettingSource::Namespace("com.example.orders".to_string()));
+
+        // Environments without an override use the plain mode
+        let dev = effective.compatibility_in(Some("dev"));
+        assert_eq!(dev.mode, CompatibilityMode::None);
+        assert_eq!(dev.environment, None);
+        assert_eq!(effective.compatibility_in(None), dev);
+
+        let other = tree.effective("org.other").unwrap().compatibility_in(Some("prod"));
+        assert_eq!(other.mode, CompatibilityMode::Backward);
+        assert_eq!(other.source, SettingSource::Default);
+    }
+
+    #[test]
+    fn test_visibility_is_private_unless_inherited_from_a_public_namespace() {
+        let tree = NamespaceTree::default();
+        let visible = |visibility| NamespaceSettings { visibility: Some(visibility), ..Default::default() };
+        tree.set("partners", visible(Visibility::Public)).unwrap();
+        tree.set("partners.internal", visible(Visibility::Private)).unwrap();
+        tree.set("partners.internal.shared", visible(Visibility::Public)).unwrap();
+
+        assert!(tree.is_public("partners"));
+        assert!(tree.is_public("partners.catalog.v2"));
+        assert!(!tree.is_public("partners.internal.billing"));
+        assert!(tree.is_public("partners.internal.shared.events"));
+        // Prefixes only match whole segments
+        assert!(!tree.is_public("partnership"));
+        assert!(!tree.is_public("com.example"));
+        assert!(!tree.is_public("partners..bad"));
+
+        let effective = tree.effective("partners.internal.billing").unwrap();
+        assert_eq!(effective.visibility.value, Visibility::Private);
+        assert_eq!(
+            effective.visibility.source,
+            SettingSource::Namespace("partners.internal".to_string())
+        );
+        assert_eq!(tree.effective("com.example").unwrap().visibility.source, SettingSource::Default);
+        assert_eq!(tree.visibility_rules().len(), 3);
+    }
+
+    #[test]
+    fn test_set_ownership_only_from_the_expected_owner() {
+        let tree = NamespaceTree::default();
+        tree.set(
+            "com.example",
+            NamespaceSettings {
+                owner: Some("platform".to_string()),
+                notification_channel: Some("https://hooks.example.com/platform".to_string()),
+                max_schemas: Some(100),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        // The inherited owner hands over the child namespace
+        let previous = tree
+            .set_ownership(
+                "com.example.payments",
+                Some("platform"),
+                "payments",
+                Some("https://hooks.example.com/payments".to_string()),
+            )
+            .unwrap();
+        assert_eq!(previous, None);
+        let effective = tree.effective("com.example.payments.refunds").unwrap();
+        assert_eq!(effective.owner.value.as_deref(), Some("payments"));
+        assert_eq!(
+            effective.notification_channel.value.as_deref(),
+            Some("https://hooks.example.com/payments")
+        );
+        assert_eq!(effective.max_schemas.value, Some(100));
+
+        let err = tree
+            .set_ownership("com.example.payments", Some("platform"), "billing", None)
+            .unwrap_err();
+        assert!(matches!(err, Error::StateTransitionError(_)));
+        assert_eq!(
+            tree.get("com.example.payments").unwrap().owner.as_deref(),
+            Some("payments")
+        );
+    }
+
+    #[test]
+    fn test_replace_all_drops_namespaces_not_given() {
+        let tree = NamespaceTree::default();
+        tree.set("com.example", NamespaceSettings::default())
+            .unwrap();
+
+        let mut settings = BTreeMap::new();
+        settings.insert(
+            "com.example.payments.*".to_string(),
+            NamespaceSettings {
+                owner: Some("payments".to_string()),
+                ..Default::default()
+            },
+        );
+        tree.replace_all(settings).unwrap();
+
+        assert!(tree.get("com.example").is_none());
+        assert_eq!(
+            tree.get("com.example.payments").unwrap().owner.as_deref(),
+            Some("payments")
+        );
+    }
+}
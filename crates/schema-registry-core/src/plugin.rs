@@ -0,0 +1,291 @@
+//! Registration plugins
+//!
+//! A [`RegistrationInterceptor`] is called at three points while a schema is
+//! registered:
+//!
+//! 1. [`RegistrationStage::PreValidate`] - before quotas and security scans
+//! 2. [`RegistrationStage::PreCommit`] - after validation, before the schema is stored
+//! 3. [`RegistrationStage::PostCommit`] - after the schema has been stored
+//!
+//! The pre-stages may add to the schema's metadata and tags or veto the
+//! registration; changes to any other field of the [`SchemaInput`] are
+//! discarded. Post-commit hooks are notifications and cannot undo anything.
+//!
+//! Plugins are compiled in and added to an [`InterceptorChain`]. Plugins in
+//! other runtimes (e.g. WASM) are hosted by an interceptor that forwards the
+//! calls.
+
+use crate::schema::SchemaInput;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use thiserror::Error;
+use uuid::Uuid;
+
+/// Point in the registration at which an interceptor is called
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RegistrationStage {
+    PreValidate,
+    PreCommit,
+    PostCommit,
+}
+
+impl std::fmt::Display for RegistrationStage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RegistrationStage::PreValidate => write!(f, "pre_validate"),
+            RegistrationStage::PreCommit => write!(f, "pre_commit"),
+            RegistrationStage::PostCommit => write!(f, "post_commit"),
+        }
+    }
+}
+
+/// What the registry knows about a registration besides the schema itself
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegistrationContext {
+    /// Subject as given by the client
+    pub subject: String,
+    /// Principal registering the schema, if known
+    pub principal: Option<String>,
+    /// Assigned ID; set from the pre-commit stage on
+    pub schema_id: Option<Uuid>,
+}
+
+impl RegistrationContext {
+    pub fn new(subject: impl Into<String>) -> Self {
+        Self {
+            subject: subject.into(),
+            principal: None,
+            schema_id: None,
+        }
+    }
+
+    /// Set the principal
+    pub fn with_principal(mut self, principal: impl Into<String>) -> Self {
+        self.principal = Some(principal.into());
+        self
+    }
+}
+
+/// Registration rejected by a plugin
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+#[error("Registration rejected by plugin '{plugin}' at {stage}: {reason}")]
+pub struct Veto {
+    pub plugin: String,
+    pub stage: RegistrationStage,
+    pub reason: String,
+}
+
+/// Hooks into schema registration
+///
+/// Every hook has a no-op default, so a plugin only implements the stages it
+/// cares about. Return `Err(reason)` from a pre-stage to veto.
+#[async_trait]
+pub trait RegistrationInterceptor: Send + Sync {
+    /// Name reported in vetoes and logs
+    fn name(&self) -> &str;
+
+    async fn pre_validate(
+        &self,
+        _input: &mut SchemaInput,
+        _ctx: &RegistrationContext,
+    ) -> std::result::Result<(), String> {
+        Ok(())
+    }
+
+    async fn pre_commit(
+        &self,
+        _input: &mut SchemaInput,
+        _ctx: &RegistrationContext,
+    ) -> std::result::Result<(), String> {
+        Ok(())
+    }
+
+    async fn post_commit(&self, _input: &SchemaInput, _ctx: &RegistrationContext) {}
+}
+
+/// Interceptors run in the order they were added
+#[derive(Clone, Default)]
+pub struct InterceptorChain {
+    interceptors: Vec<Arc<dyn RegistrationInterceptor>>,
+}
+
+impl InterceptorChain {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an interceptor after the existing ones
+    pub fn with(mut self, interceptor: Arc<dyn RegistrationInterceptor>) -> Self {
+        self.interceptors.push(interceptor);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.interceptors.is_empty()
+    }
+
+    /// Names of the interceptors, in order
+    pub fn names(&self) -> Vec<String> {
+        self.interceptors.iter().map(|i| i.name().to_string()).collect()
+    }
+
+    /// Run a pre-stage, stopping at the first veto
+    ///
+    /// Each interceptor sees the metadata and tags left by the previous ones.
+    pub async fn run_pre(
+        &self,
+        stage: RegistrationStage,
+        input: &mut SchemaInput,
+        ctx: &RegistrationContext,
+    ) -> std::result::Result<(), Veto> {
+        for interceptor in &self.interceptors {
+            let mut candidate = input.clone();
+            let outcome = match stage {
+                RegistrationStage::PreValidate => {
+                    interceptor.pre_validate(&mut candidate, ctx).await
+                }
+                RegistrationStage::PreCommit => interceptor.pre_commit(&mut candidate, ctx).await,
+                RegistrationStage::PostCommit => Ok(()),
+            };
+            if let Err(reason) = outcome {
+                return Err(Veto {
+                    plugin: interceptor.name().to_string(),
+                    stage,
+                    reason,
+                });
+            }
+            input.metadata = candidate.metadata;
+            input.tags = candidate.tags;
+        }
+        Ok(())
+    }
+
+    /// Notify every interceptor of a stored schema
+    pub async fn run_post_commit(&self, input: &SchemaInput, ctx: &RegistrationContext) {
+        for interceptor in &self.interceptors {
+            interceptor.post_commit(input, ctx).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{CompatibilityMode, SerializationFormat};
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn input(name: &str) -> SchemaInput {
+        SchemaInput {
+            name: name.to_string(),
+            namespace: "com.example".to_string(),
+            format: SerializationFormat::JsonSchema,
+            content: r#"{"type": "object"}"#.to_string(),
+            description: String::new(),
+            compatibility_mode: CompatibilityMode::Backward,
+            auto_activate: false,
+            version: None,
+            metadata: HashMap::new(),
+            tags: vec![],
+            examples: vec![],
+        }
+    }
+
+    struct NamingConvention;
+
+    #[async_trait]
+    impl RegistrationInterceptor for NamingConvention {
+        fn name(&self) -> &str {
+            "naming-convention"
+        }
+
+        async fn pre_validate(
+            &self,
+            input: &mut SchemaInput,
+            _ctx: &RegistrationContext,
+        ) -> std::result::Result<(), String> {
+            if input.name.chars().next().is_some_and(|c| c.is_uppercase()) {
+                Ok(())
+            } else {
+                Err(format!("'{}' must be PascalCase", input.name))
+            }
+        }
+    }
+
+    #[derive(Default)]
+    struct BillingTagger {
+        committed: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl RegistrationInterceptor for BillingTagger {
+        fn name(&self) -> &str {
+            "billing-tagger"
+        }
+
+        async fn pre_commit(
+            &self,
+            input: &mut SchemaInput,
+            ctx: &RegistrationContext,
+        ) -> std::result::Result<(), String> {
+            input
+                .metadata
+                .insert("cost_center".to_string(), serde_json::json!("platform"));
+            input.tags.push("billed".to_string());
+            // Anything but metadata and tags is discarded
+            input.content = "{}".to_string();
+            assert!(ctx.schema_id.is_some());
+            Ok(())
+        }
+
+        async fn post_commit(&self, _input: &SchemaInput, _ctx: &RegistrationContext) {
+            self.committed.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stages_and_metadata() {
+        let tagger = Arc::new(BillingTagger::default());
+        let chain = InterceptorChain::new()
+            .with(Arc::new(NamingConvention))
+            .with(tagger.clone());
+        assert_eq!(chain.names(), vec!["naming-convention", "billing-tagger"]);
+
+        let mut schema = input("User");
+        let mut ctx = RegistrationContext::new("com.example.User").with_principal("ci");
+        chain
+            .run_pre(RegistrationStage::PreValidate, &mut schema, &ctx)
+            .await
+            .unwrap();
+        ctx.schema_id = Some(Uuid::new_v4());
+        chain
+            .run_pre(RegistrationStage::PreCommit, &mut schema, &ctx)
+            .await
+            .unwrap();
+        chain.run_post_commit(&schema, &ctx).await;
+
+        assert_eq!(schema.metadata["cost_center"], "platform");
+        assert_eq!(schema.tags, vec!["billed"]);
+        assert_eq!(schema.content, r#"{"type": "object"}"#);
+        assert_eq!(tagger.committed.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_veto_stops_chain() {
+        let chain = InterceptorChain::new()
+            .with(Arc::new(NamingConvention))
+            .with(Arc::new(BillingTagger::default()));
+
+        let mut schema = input("user");
+        let ctx = RegistrationContext::new("com.example.user");
+        let veto = chain
+            .run_pre(RegistrationStage::PreValidate, &mut schema, &ctx)
+            .await
+            .unwrap_err();
+        assert_eq!(veto.plugin, "naming-convention");
+        assert_eq!(veto.stage, RegistrationStage::PreValidate);
+        assert!(veto.to_string().contains("PascalCase"));
+    }
+}
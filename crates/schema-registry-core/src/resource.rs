@@ -0,0 +1,543 @@
+//! Declarative schema resources for operators
+//!
+//! A [`SchemaResource`] describes a schema the way a Kubernetes custom
+//! resource would: a desired [`SchemaSpec`], a `metadata.generation` that
+//! increases whenever the spec changes, and a [`SchemaResourceStatus`] whose
+//! `observedGeneration` and conditions report how far reconciliation got.
+//!
+//! [`ResourceStore`] keeps the resources, assigns a store-wide
+//! `resourceVersion` to every change and retains recent [`WatchEvent`]s so a
+//! client can list once and then watch from the returned version.
+
+use crate::error::{Error, Result};
+use crate::types::CompatibilityMode;
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::time::Duration;
+use thiserror::Error;
+use tokio::sync::Notify;
+use uuid::Uuid;
+
+/// API group and version of schema resources
+pub const API_VERSION: &str = "registry.llm-dev-ops.io/v1";
+
+/// Kind of a single schema resource
+pub const SCHEMA_KIND: &str = "Schema";
+
+/// Condition reporting whether the spec has been applied to the registry
+pub const CONDITION_READY: &str = "Ready";
+
+/// Number of watch events retained by default
+pub const DEFAULT_WATCH_HISTORY: usize = 1000;
+
+/// Longest accepted resource name (DNS subdomain length)
+const MAX_NAME_LENGTH: usize = 253;
+
+// ============================================================================
+// Resource Model
+// ============================================================================
+
+/// Standard object metadata
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObjectMeta {
+    pub name: String,
+    pub uid: Uuid,
+    /// Incremented on every spec change
+    pub generation: u64,
+    /// Store-wide version of the last change to this object
+    pub resource_version: u64,
+    pub creation_timestamp: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, String>,
+}
+
+/// Desired state of a schema
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaSpec {
+    pub subject: String,
+    /// Semantic version to register the schema as
+    pub version: String,
+    /// `JSON`, `AVRO` or `PROTOBUF`
+    pub schema_type: String,
+    pub schema: serde_json::Value,
+    /// Inherited from the namespace settings when not given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compatibility_mode: Option<CompatibilityMode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConditionStatus {
+    True,
+    False,
+    Unknown,
+}
+
+/// Latest observation of one aspect of a resource
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Condition {
+    #[serde(rename = "type")]
+    pub condition_type: String,
+    pub status: ConditionStatus,
+    /// CamelCase machine-readable reason
+    pub reason: String,
+    pub message: String,
+    /// Generation the condition was computed for
+    pub observed_generation: u64,
+    /// When `status` last changed
+    pub last_transition_time: DateTime<Utc>,
+}
+
+impl Condition {
+    pub fn new(
+        condition_type: impl Into<String>,
+        status: ConditionStatus,
+        reason: impl Into<String>,
+        message: impl Into<String>,
+        observed_generation: u64,
+    ) -> Self {
+        Self {
+            condition_type: condition_type.into(),
+            status,
+            reason: reason.into(),
+            message: message.into(),
+            observed_generation,
+            last_transition_time: Utc::now(),
+        }
+    }
+}
+
+/// Observed state of a schema resource
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaResourceStatus {
+    /// Generation of the spec the status reflects
+    pub observed_generation: u64,
+    #[serde(default)]
+    pub conditions: Vec<Condition>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema_id: Option<Uuid>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub registered_version: Option<String>,
+}
+
+impl SchemaResourceStatus {
+    /// Condition of the given type
+    pub fn condition(&self, condition_type: &str) -> Option<&Condition> {
+        self.conditions
+            .iter()
+            .find(|c| c.condition_type == condition_type)
+    }
+
+    /// Add or replace a condition
+    ///
+    /// `lastTransitionTime` is kept when the status doesn't change.
+    pub fn set_condition(&mut self, mut condition: Condition) {
+        match self
+            .conditions
+            .iter_mut()
+            .find(|c| c.condition_type == condition.condition_type)
+        {
+            Some(existing) => {
+                if existing.status == condition.status {
+                    condition.last_transition_time = existing.last_transition_time;
+                }
+                *existing = condition;
+            }
+            None => self.conditions.push(condition),
+        }
+    }
+}
+
+/// A schema managed declaratively
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaResource {
+    pub api_version: String,
+    pub kind: String,
+    pub metadata: ObjectMeta,
+    pub spec: SchemaSpec,
+    #[serde(default)]
+    pub status: SchemaResourceStatus,
+}
+
+impl SchemaResource {
+    /// A resource first applied with `spec`, not yet reconciled
+    ///
+    /// The resource version is left at `0` for the store to assign.
+    pub fn new(name: &str, spec: SchemaSpec, labels: BTreeMap<String, String>) -> Result<Self> {
+        validate_name(name)?;
+        let mut status = SchemaResourceStatus::default();
+        status.set_condition(Condition::new(
+            CONDITION_READY,
+            ConditionStatus::Unknown,
+            "Pending",
+            "Waiting for reconciliation",
+            0,
+        ));
+        Ok(Self {
+            api_version: API_VERSION.to_string(),
+            kind: SCHEMA_KIND.to_string(),
+            metadata: ObjectMeta {
+                name: name.to_string(),
+                uid: Uuid::new_v4(),
+                generation: 1,
+                resource_version: 0,
+                creation_timestamp: Utc::now(),
+                labels,
+            },
+            spec,
+            status,
+        })
+    }
+
+    /// The resource with `spec` and `labels` applied, or `None` when they
+    /// are unchanged
+    ///
+    /// The generation only increases when the spec or labels change, so
+    /// re-applying the same object is a no-op.
+    pub fn reapplied(&self, spec: SchemaSpec, labels: BTreeMap<String, String>) -> Option<Self> {
+        if self.spec == spec && self.metadata.labels == labels {
+            return None;
+        }
+        let mut resource = self.clone();
+        resource.metadata.generation += 1;
+        resource.metadata.labels = labels;
+        resource.spec = spec;
+        Some(resource)
+    }
+
+    /// Whether the status reflects the current spec
+    pub fn is_reconciled(&self) -> bool {
+        self.status.observed_generation == self.metadata.generation
+    }
+
+    /// Whether the current spec has been applied successfully
+    pub fn is_ready(&self) -> bool {
+        self.is_reconciled()
+            && self
+                .status
+                .condition(CONDITION_READY)
+                .is_some_and(|c| c.status == ConditionStatus::True)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum WatchEventType {
+    Added,
+    Modified,
+    Deleted,
+}
+
+/// A change to a resource, as delivered to watchers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WatchEvent {
+    #[serde(rename = "type")]
+    pub event_type: WatchEventType,
+    pub object: SchemaResource,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum WatchError {
+    /// The requested version is older than the retained history; relist
+    #[error("Resource version {requested} is too old, the oldest available is {oldest}")]
+    Expired { requested: u64, oldest: u64 },
+}
+
+// ============================================================================
+// Resource Store
+// ============================================================================
+
+struct StoreInner {
+    resources: BTreeMap<String, SchemaResource>,
+    resource_version: u64,
+    /// Events keyed by the resource version they produced
+    events: VecDeque<(u64, WatchEvent)>,
+}
+
+/// In-memory schema resources with a bounded watch history
+pub struct ResourceStore {
+    inner: RwLock<StoreInner>,
+    changed: Notify,
+    history: usize,
+}
+
+impl ResourceStore {
+    pub fn new() -> Self {
+        Self::with_history(DEFAULT_WATCH_HISTORY)
+    }
+
+    /// Retain up to `history` events for watchers
+    pub fn with_history(history: usize) -> Self {
+        Self {
+            inner: RwLock::new(StoreInner {
+                resources: BTreeMap::new(),
+                resource_version: 0,
+                events: VecDeque::new(),
+            }),
+            changed: Notify::new(),
+            history,
+        }
+    }
+
+    /// Create or update the desired state of a resource
+    ///
+    /// See [`SchemaResource::reapplied`] for when the generation increases.
+    pub fn apply(
+        &self,
+        name: &str,
+        spec: SchemaSpec,
+        labels: BTreeMap<String, String>,
+    ) -> Result<SchemaResource> {
+        let mut inner = self.inner.write();
+        let (event_type, mut resource) = match inner.resources.get(name) {
+            Some(existing) => match existing.reapplied(spec, labels) {
+                Some(resource) => (WatchEventType::Modified, resource),
+                None => return Ok(existing.clone()),
+            },
+            None => (
+                WatchEventType::Added,
+                SchemaResource::new(name, spec, labels)?,
+            ),
+        };
+        resource.metadata.resource_version = inner.resource_version + 1;
+
+        self.commit(&mut inner, event_type, resource.clone());
+        Ok(resource)
+    }
+
+    /// Record the outcome of reconciling `generation` of a resource
+    ///
+    /// Returns `None` without changing anything when the resource was deleted
+    /// or its spec changed since `generation`; the newer generation will be
+    /// reconciled on its own.
+    pub fn update_status(
+        &self,
+        name: &str,
+        generation: u64,
+        status: SchemaResourceStatus,
+    ) -> Option<SchemaResource> {
+        let mut inner = self.inner.write();
+        let existing = inner.resources.get(name)?;
+        if existing.metadata.generation != generation {
+            return None;
+        }
+
+        let mut resource = existing.clone();
+        resource.metadata.resource_version = inner.resource_version + 1;
+        resource.status = status;
+        self.commit(&mut inner, WatchEventType::Modified, resource.clone());
+        Some(resource)
+    }
+
+    pub fn get(&self, name: &str) -> Option<SchemaResource> {
+        self.inner.read().resources.get(name).cloned()
+    }
+
+    /// All resources and the store's current resource version
+    pub fn list(&self) -> (Vec<SchemaResource>, u64) {
+        let inner = self.inner.read();
+        (inner.resources.values().cloned().collect(), inner.resource_version)
+    }
+
+    pub fn delete(&self, name: &str) -> Option<SchemaResource> {
+        let mut inner = self.inner.write();
+        let mut resource = inner.resources.get(name)?.clone();
+        resource.metadata.resource_version = inner.resource_version + 1;
+        self.commit(&mut inner, WatchEventType::Deleted, resource.clone());
+        Some(resource)
+    }
+
+    /// Events after `resource_version`, oldest first
+    pub fn events_since(&self, resource_version: u64) -> std::result::Result<Vec<WatchEvent>, WatchError> {
+        let inner = self.inner.read();
+        if let Some((oldest, _)) = inner.events.front() {
+            // Versions up to `oldest - 1` are still covered by the retained history
+            if resource_version + 1 < *oldest {
+                return Err(WatchError::Expired {
+                    requested: resource_version,
+                    oldest: *oldest,
+                });
+            }
+        }
+        Ok(inner
+            .events
+            .iter()
+            .filter(|(version, _)| *version > resource_version)
+            .map(|(_, event)| event.clone())
+            .collect())
+    }
+
+    /// Wait up to `timeout` for events after `resource_version`
+    ///
+    /// Returns an empty list when nothing changed before the timeout.
+    pub async fn watch(
+        &self,
+        resource_version: u64,
+        timeout: Duration,
+    ) -> std::result::Result<Vec<WatchEvent>, WatchError> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let changed = self.changed.notified();
+            let events = self.events_since(resource_version)?;
+            if !events.is_empty() {
+                return Ok(events);
+            }
+            if tokio::time::timeout_at(deadline, changed).await.is_err() {
+                return Ok(Vec::new());
+            }
+        }
+    }
+
+    fn commit(&self, inner: &mut StoreInner, event_type: WatchEventType, resource: SchemaResource) {
+        let version = resource.metadata.resource_version;
+        inner.resource_version = version;
+        match event_type {
+            WatchEventType::Deleted => {
+                inner.resources.remove(&resource.metadata.name);
+            }
+            _ => {
+                inner
+                    .resources
+                    .insert(resource.metadata.name.clone(), resource.clone());
+            }
+        }
+
+        inner.events.push_back((
+            version,
+            WatchEvent {
+                event_type,
+                object: resource,
+            },
+        ));
+        while inner.events.len() > self.history {
+            inner.events.pop_front();
+        }
+        self.changed.notify_waiters();
+    }
+}
+
+impl Default for ResourceStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Names are lowercase alphanumerics, `-` and `.`, as in Kubernetes
+fn validate_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_NAME_LENGTH
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '.')
+        && name.starts_with(|c: char| c.is_ascii_alphanumeric())
+        && name.ends_with(|c: char| c.is_ascii_alphanumeric());
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::ValidationError(format!(
+            "Invalid resource name '{}': use lowercase letters, digits, '-' and '.'",
+            name
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn spec(version: &str) -> SchemaSpec {
+        SchemaSpec {
+            subject: "com.example.User".to_string(),
+            version: version.to_string(),
+            schema_type: "JSON".to_string(),
+            schema: json!({"type": "object"}),
+            compatibility_mode: None,
+            description: None,
+            tags: vec![],
+        }
+    }
+
+    #[test]
+    fn test_generation_and_status() {
+        let store = ResourceStore::new();
+        let created = store.apply("user", spec("1.0.0"), BTreeMap::new()).unwrap();
+        assert_eq!(created.metadata.generation, 1);
+        assert!(!created.is_reconciled());
+        assert_eq!(
+            created.status.condition(CONDITION_READY).unwrap().status,
+            ConditionStatus::Unknown
+        );
+
+        // Re-applying the same spec changes nothing
+        let same = store.apply("user", spec("1.0.0"), BTreeMap::new()).unwrap();
+        assert_eq!(same.metadata.resource_version, created.metadata.resource_version);
+
+        let mut status = created.status.clone();
+        status.observed_generation = 1;
+        status.set_condition(Condition::new(CONDITION_READY, ConditionStatus::True, "Registered", "", 1));
+        let reconciled = store.update_status("user", 1, status.clone()).unwrap();
+        assert!(reconciled.is_reconciled());
+        assert!(reconciled.is_ready());
+
+        // A status computed for an old generation is discarded
+        let updated = store.apply("user", spec("1.1.0"), BTreeMap::new()).unwrap();
+        assert_eq!(updated.metadata.generation, 2);
+        assert!(store.update_status("user", 1, status).is_none());
+        assert!(!store.get("user").unwrap().is_reconciled());
+
+        assert!(store.apply("User_1", spec("1.0.0"), BTreeMap::new()).is_err());
+    }
+
+    #[test]
+    fn test_set_condition_keeps_transition_time() {
+        let mut status = SchemaResourceStatus::default();
+        status.set_condition(Condition::new(CONDITION_READY, ConditionStatus::False, "Invalid", "a", 1));
+        let first = status.condition(CONDITION_READY).unwrap().last_transition_time;
+
+        status.set_condition(Condition::new(CONDITION_READY, ConditionStatus::False, "Invalid", "b", 2));
+        let condition = status.condition(CONDITION_READY).unwrap();
+        assert_eq!(condition.last_transition_time, first);
+        assert_eq!(condition.message, "b");
+        assert_eq!(status.conditions.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_watch() {
+        let store = std::sync::Arc::new(ResourceStore::with_history(2));
+        let (_, version) = store.list();
+
+        let timed_out = store.watch(version, Duration::from_millis(10)).await.unwrap();
+        assert!(timed_out.is_empty());
+
+        let watcher = {
+            let store = store.clone();
+            tokio::spawn(async move { store.watch(version, Duration::from_secs(5)).await })
+        };
+        tokio::task::yield_now().await;
+        store.apply("user", spec("1.0.0"), BTreeMap::new()).unwrap();
+        let events = watcher.await.unwrap().unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event_type, WatchEventType::Added);
+
+        store.apply("user", spec("1.1.0"), BTreeMap::new()).unwrap();
+        store.delete("user").unwrap();
+        assert_eq!(
+            store.events_since(version),
+            Err(WatchError::Expired { requested: 0, oldest: 2 })
+        );
+        let events = store.events_since(1).unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1].event_type, WatchEventType::Deleted);
+    }
+}
@@ -0,0 +1,511 @@
+//! Retry policies shared by storage, webhooks, event handlers and federation
+//!
+//! Whether a failed call is worth repeating depends on what went wrong, not on
+//! which backend failed: a dropped connection or a `503` may well succeed a
+//! moment later, a unique violation or a `400` never will. [`Classify`] sorts
+//! errors into an [`ErrorClass`], and a [`RetryPolicy`] holds one
+//! [`RetryRule`] per class.
+//!
+//! Retries add load exactly when a backend is struggling. A shared
+//! [`RetryBudget`] caps them at a share of first attempts, so an outage turns
+//! into fast failures instead of a retry storm.
+//!
+//! Every retry is counted in `schema_registry_retries_total{operation, class}`,
+//! and every call that fails after retrying was allowed in
+//! `schema_registry_retries_exhausted_total{operation, class, reason}`, where
+//! `reason` is `attempts` or `budget`.
+//!
+//! Only retry calls that are safe to repeat: reads, and writes that are
+//! idempotent. A write whose outcome is unknown after a dropped connection may
+//! already have been applied.
+
+use parking_lot::Mutex;
+use rand::Rng;
+use std::fmt::Display;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How a failure should be handled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorClass {
+    /// The backend was briefly unavailable; the call may succeed if repeated
+    Transient,
+    /// The backend asked callers to slow down
+    RateLimited,
+    /// The call conflicts with data already stored
+    Conflict,
+    /// Repeating the call fails the same way
+    Permanent,
+}
+
+impl ErrorClass {
+    /// Whether the default policy retries this class
+    pub fn is_retryable(self) -> bool {
+        matches!(self, ErrorClass::Transient | ErrorClass::RateLimited)
+    }
+
+    /// HTTP status code an API should answer with
+    pub fn http_status(self) -> u16 {
+        match self {
+            ErrorClass::Transient => 503,
+            ErrorClass::RateLimited => 429,
+            ErrorClass::Conflict => 409,
+            ErrorClass::Permanent => 500,
+        }
+    }
+
+    /// Value of the `class` metric label
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorClass::Transient => "transient",
+            ErrorClass::RateLimited => "rate_limited",
+            ErrorClass::Conflict => "conflict",
+            ErrorClass::Permanent => "permanent",
+        }
+    }
+
+    /// Class of an HTTP response status
+    ///
+    /// `408`, `425` and the `5xx` gateway and availability errors are
+    /// transient; other `5xx` and all remaining `4xx` are permanent.
+    pub fn from_http_status(status: u16) -> Self {
+        match status {
+            429 => ErrorClass::RateLimited,
+            409 => ErrorClass::Conflict,
+            408 | 425 | 500 | 502 | 503 | 504 => ErrorClass::Transient,
+            _ => ErrorClass::Permanent,
+        }
+    }
+}
+
+/// Errors that can be sorted into an [`ErrorClass`]
+pub trait Classify {
+    fn class(&self) -> ErrorClass;
+
+    /// How long the other side asked us to wait, e.g. from `Retry-After`
+    fn retry_after(&self) -> Option<Duration> {
+        None
+    }
+}
+
+#[cfg(feature = "sqlx")]
+impl Classify for sqlx::Error {
+    fn class(&self) -> ErrorClass {
+        match self {
+            sqlx::Error::Database(e) => e
+                .code()
+                .map_or(ErrorClass::Permanent, |code| classify_sqlstate(&code)),
+            sqlx::Error::Io(_)
+            | sqlx::Error::PoolTimedOut
+            | sqlx::Error::PoolClosed
+            | sqlx::Error::WorkerCrashed => ErrorClass::Transient,
+            _ => ErrorClass::Permanent,
+        }
+    }
+}
+
+#[cfg(feature = "redis")]
+impl Classify for redis::RedisError {
+    fn class(&self) -> ErrorClass {
+        if self.is_timeout()
+            || self.is_io_error()
+            || self.is_connection_dropped()
+            || self.is_connection_refusal()
+        {
+            return ErrorClass::Transient;
+        }
+        match self.kind() {
+            redis::ErrorKind::BusyLoadingError
+            | redis::ErrorKind::TryAgain
+            | redis::ErrorKind::ClusterDown
+            | redis::ErrorKind::MasterDown
+            | redis::ErrorKind::ReadOnly => ErrorClass::Transient,
+            _ => ErrorClass::Permanent,
+        }
+    }
+}
+
+/// Classify a PostgreSQL SQLSTATE code
+pub fn classify_sqlstate(code: &str) -> ErrorClass {
+    match code {
+        // unique_violation, exclusion_violation
+        "23505" | "23P01" => ErrorClass::Conflict,
+        // serialization_failure, deadlock_detected, lock_not_available,
+        // too_many_connections, admin_shutdown, crash_shutdown,
+        // cannot_connect_now
+        "40001" | "40P01" | "55P03" | "53300" | "57P01" | "57P02" | "57P03" => {
+            ErrorClass::Transient
+        }
+        // connection_exception and its subclasses
+        _ if code.starts_with("08") => ErrorClass::Transient,
+        _ => ErrorClass::Permanent,
+    }
+}
+
+/// How failures of one class are retried
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetryRule {
+    /// Fail at once
+    Never,
+    /// Retry with jittered exponential backoff
+    Backoff {
+        /// Attempts in total, including the first
+        max_attempts: u32,
+        /// Backoff before the first retry
+        initial_backoff: Duration,
+        /// Upper bound for the backoff
+        max_backoff: Duration,
+    },
+}
+
+impl RetryRule {
+    pub fn backoff(max_attempts: u32, initial_backoff: Duration, max_backoff: Duration) -> Self {
+        RetryRule::Backoff { max_attempts, initial_backoff, max_backoff }
+    }
+
+    fn retries(&self) -> bool {
+        matches!(self, RetryRule::Backoff { max_attempts, .. } if *max_attempts > 1)
+    }
+
+    /// Delay before retry number `retry` (starting at 1), `None` once the
+    /// attempts are used up
+    ///
+    /// The backoff doubles with each retry, and the delay is picked at random
+    /// between half the backoff and all of it so that callers failing together
+    /// don't retry together.
+    pub fn delay(&self, retry: u32) -> Option<Duration> {
+        let RetryRule::Backoff { max_attempts, initial_backoff, max_backoff } = *self else {
+            return None;
+        };
+        if retry >= max_attempts {
+            return None;
+        }
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+        let backoff = initial_backoff.saturating_mul(factor).min(max_backoff);
+        let millis = backoff.as_millis() as u64;
+        Some(Duration::from_millis(rand::thread_rng().gen_range(millis / 2..=millis)))
+    }
+
+    fn max_backoff(&self) -> Duration {
+        match self {
+            RetryRule::Never => Duration::ZERO,
+            RetryRule::Backoff { max_backoff, .. } => *max_backoff,
+        }
+    }
+}
+
+/// Caps retries at a share of first attempts
+///
+/// Each call deposits `ratio` of a token and each retry takes a whole one, so
+/// in steady state retries add at most `ratio` to the load. The budget starts
+/// full and never holds more than `max_tokens`, which lets a quiet service
+/// absorb a short burst of failures.
+#[derive(Debug)]
+pub struct RetryBudget {
+    ratio: f64,
+    max_tokens: f64,
+    tokens: Mutex<f64>,
+}
+
+impl RetryBudget {
+    pub fn new(ratio: f64, max_tokens: u32) -> Self {
+        Self {
+            ratio,
+            max_tokens: f64::from(max_tokens),
+            tokens: Mutex::new(f64::from(max_tokens)),
+        }
+    }
+
+    /// Count a first attempt
+    pub fn deposit(&self) {
+        let mut tokens = self.tokens.lock();
+        *tokens = (*tokens + self.ratio).min(self.max_tokens);
+    }
+
+    /// Take a token for a retry, `false` when the budget is spent
+    pub fn try_withdraw(&self) -> bool {
+        let mut tokens = self.tokens.lock();
+        if *tokens >= 1.0 {
+            *tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Retries that could be made right now
+    pub fn available(&self) -> u32 {
+        *self.tokens.lock() as u32
+    }
+}
+
+impl Default for RetryBudget {
+    /// Retries may add 10% to the load, after a burst of 10
+    fn default() -> Self {
+        Self::new(0.1, 10)
+    }
+}
+
+/// Retry rules per error class, with an optional shared budget
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Value of the `operation` metric label
+    pub operation: &'static str,
+    pub transient: RetryRule,
+    pub rate_limited: RetryRule,
+    pub conflict: RetryRule,
+    pub permanent: RetryRule,
+    /// Shared by every policy that guards the same backend
+    pub budget: Option<Arc<RetryBudget>>,
+}
+
+impl RetryPolicy {
+    /// Three attempts for transient failures starting at 50ms, three for rate
+    /// limiting starting at 500ms, no retries otherwise, no budget
+    pub fn new(operation: &'static str) -> Self {
+        Self {
+            operation,
+            transient: RetryRule::backoff(3, Duration::from_millis(50), Duration::from_secs(1)),
+            rate_limited: RetryRule::backoff(3, Duration::from_millis(500), Duration::from_secs(10)),
+            conflict: RetryRule::Never,
+            permanent: RetryRule::Never,
+            budget: None,
+        }
+    }
+
+    pub fn rule(&self, class: ErrorClass) -> &RetryRule {
+        match class {
+            ErrorClass::Transient => &self.transient,
+            ErrorClass::RateLimited => &self.rate_limited,
+            ErrorClass::Conflict => &self.conflict,
+            ErrorClass::Permanent => &self.permanent,
+        }
+    }
+
+    pub fn with_rule(mut self, class: ErrorClass, rule: RetryRule) -> Self {
+        match class {
+            ErrorClass::Transient => self.transient = rule,
+            ErrorClass::RateLimited => self.rate_limited = rule,
+            ErrorClass::Conflict => self.conflict = rule,
+            ErrorClass::Permanent => self.permanent = rule,
+        }
+        self
+    }
+
+    pub fn with_budget(mut self, budget: Arc<RetryBudget>) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Run `op`, repeating it as the rule for each failure's class allows
+    pub async fn retry<T, E, F, Fut>(&self, op: F) -> Result<T, E>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        E: Classify + Display,
+    {
+        self.run(op, |e: &E| (e.class(), e.retry_after())).await
+    }
+
+    /// Like [`retry`](Self::retry), for errors classified by `classify`
+    pub async fn retry_with<T, E, F, Fut, C>(&self, classify: C, op: F) -> Result<T, E>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        E: Display,
+        C: Fn(&E) -> ErrorClass,
+    {
+        self.run(op, |e: &E| (classify(e), None)).await
+    }
+
+    async fn run<T, E, F, Fut, C>(&self, mut op: F, classify: C) -> Result<T, E>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        E: Display,
+        C: Fn(&E) -> (ErrorClass, Option<Duration>),
+    {
+        if let Some(budget) = &self.budget {
+            budget.deposit();
+        }
+        let mut attempt = 1;
+        loop {
+            let error = match op().await {
+                Ok(value) => return Ok(value),
+                Err(error) => error,
+            };
+            let (class, retry_after) = classify(&error);
+            let rule = self.rule(class);
+            let Some(delay) = rule.delay(attempt) else {
+                if rule.retries() {
+                    self.record_exhausted(class, "attempts");
+                }
+                return Err(error);
+            };
+            if let Some(budget) = &self.budget {
+                if !budget.try_withdraw() {
+                    self.record_exhausted(class, "budget");
+                    return Err(error);
+                }
+            }
+            // Honour the server's hint, within the rule's bound
+            let delay = retry_after.map_or(delay, |after| after.min(rule.max_backoff()));
+
+            metrics::counter!(
+                "schema_registry_retries_total",
+                "operation" => self.operation,
+                "class" => class.as_str()
+            )
+            .increment(1);
+            tracing::warn!(
+                operation = self.operation,
+                attempt,
+                class = class.as_str(),
+                error = %error,
+                retry_in_ms = delay.as_millis() as u64,
+                "Retrying failed call"
+            );
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    fn record_exhausted(&self, class: ErrorClass, reason: &'static str) {
+        metrics::counter!(
+            "schema_registry_retries_exhausted_total",
+            "operation" => self.operation,
+            "class" => class.as_str(),
+            "reason" => reason
+        )
+        .increment(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[derive(Debug)]
+    struct TestError(ErrorClass);
+
+    impl Display for TestError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{:?}", self.0)
+        }
+    }
+
+    impl Classify for TestError {
+        fn class(&self) -> ErrorClass {
+            self.0
+        }
+    }
+
+    fn fast_policy() -> RetryPolicy {
+        let fast = RetryRule::backoff(3, Duration::from_millis(1), Duration::from_millis(2));
+        RetryPolicy::new("test")
+            .with_rule(ErrorClass::Transient, fast)
+            .with_rule(ErrorClass::RateLimited, fast)
+    }
+
+    #[test]
+    fn test_classify_sqlstate_and_http_status() {
+        assert_eq!(classify_sqlstate("23505"), ErrorClass::Conflict);
+        assert_eq!(classify_sqlstate("40001"), ErrorClass::Transient);
+        assert_eq!(classify_sqlstate("08006"), ErrorClass::Transient);
+        assert_eq!(classify_sqlstate("42P01"), ErrorClass::Permanent);
+
+        assert_eq!(ErrorClass::from_http_status(503), ErrorClass::Transient);
+        assert_eq!(ErrorClass::from_http_status(429), ErrorClass::RateLimited);
+        assert_eq!(ErrorClass::from_http_status(400), ErrorClass::Permanent);
+        assert_eq!(ErrorClass::from_http_status(501), ErrorClass::Permanent);
+    }
+
+    #[cfg(all(feature = "sqlx", feature = "redis"))]
+    #[test]
+    fn test_classify_redis_and_sqlx_errors() {
+        let busy = redis::RedisError::from((redis::ErrorKind::BusyLoadingError, "loading"));
+        let io = redis::RedisError::from(std::io::Error::from(std::io::ErrorKind::ConnectionReset));
+        let wrong_type = redis::RedisError::from((redis::ErrorKind::TypeError, "wrong type"));
+
+        assert_eq!(busy.class(), ErrorClass::Transient);
+        assert_eq!(io.class(), ErrorClass::Transient);
+        assert_eq!(wrong_type.class(), ErrorClass::Permanent);
+        assert_eq!(sqlx::Error::PoolTimedOut.class(), ErrorClass::Transient);
+        assert_eq!(sqlx::Error::RowNotFound.class(), ErrorClass::Permanent);
+    }
+
+    #[test]
+    fn test_delay_is_jittered_within_backoff() {
+        let rule = RetryRule::backoff(40, Duration::from_millis(50), Duration::from_secs(1));
+        for _ in 0..100 {
+            let first = rule.delay(1).unwrap();
+            assert!(first >= Duration::from_millis(25) && first <= Duration::from_millis(50));
+            assert!(rule.delay(30).unwrap() <= Duration::from_secs(1));
+        }
+        assert_eq!(rule.delay(40), None);
+        assert_eq!(RetryRule::Never.delay(1), None);
+    }
+
+    #[tokio::test]
+    async fn test_retry_recovers_from_transient_errors() {
+        let calls = AtomicU32::new(0);
+        let result = fast_policy()
+            .retry(|| async {
+                if calls.fetch_add(1, Ordering::SeqCst) == 0 {
+                    Err(TestError(ErrorClass::Transient))
+                } else {
+                    Ok(7)
+                }
+            })
+            .await;
+
+        assert_eq!(result.unwrap(), 7);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_retry_follows_class_rules() {
+        let calls = AtomicU32::new(0);
+        let attempts = |class| {
+            calls.store(0, Ordering::SeqCst);
+            let calls = &calls;
+            async move {
+                let _: Result<(), _> = fast_policy()
+                    .retry(|| async {
+                        calls.fetch_add(1, Ordering::SeqCst);
+                        Err(TestError(class))
+                    })
+                    .await;
+                calls.load(Ordering::SeqCst)
+            }
+        };
+
+        assert_eq!(attempts(ErrorClass::Transient).await, 3);
+        assert_eq!(attempts(ErrorClass::RateLimited).await, 3);
+        assert_eq!(attempts(ErrorClass::Conflict).await, 1);
+        assert_eq!(attempts(ErrorClass::Permanent).await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_budget_limits_retries() {
+        let budget = Arc::new(RetryBudget::new(0.5, 2));
+        let policy = fast_policy().with_budget(budget.clone());
+        let calls = AtomicU32::new(0);
+
+        for _ in 0..3 {
+            let _: Result<(), _> = policy
+                .retry(|| async {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    Err(TestError(ErrorClass::Transient))
+                })
+                .await;
+        }
+
+        // The first call spends both starting tokens; the next two deposit
+        // one token between them
+        assert_eq!(calls.load(Ordering::SeqCst), 3 + 3);
+        assert_eq!(budget.available(), 0);
+    }
+}
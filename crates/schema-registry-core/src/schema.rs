@@ -29,6 +29,7 @@ pub struct SchemaInput {
     /// Optional version (if not specified, will be auto-calculated)
     pub version: Option<SemanticVersion>,
     /// Additional metadata
+    #[serde(serialize_with = "crate::canonical_json::sorted_value_map")]
     pub metadata: HashMap<String, serde_json::Value>,
     /// Schema tags for categorization
     pub tags: Vec<String>,
@@ -87,6 +88,7 @@ pub struct SchemaMetadata {
     /// Deletion information
     pub deletion: Option<DeletionInfo>,
     /// Custom metadata fields
+    #[serde(serialize_with = "crate::canonical_json::sorted_value_map")]
     pub custom: HashMap<String, serde_json::Value>,
 }
 
@@ -0,0 +1,142 @@
+//! References to secrets held by a secrets manager
+//!
+//! Configuration that needs a credential (webhook signing secrets, schema
+//! source credentials, ...) stores a [`SecretRef`] naming the secret instead
+//! of the value itself. The value is fetched through a [`SecretResolver`] only
+//! when it is used, and comes back as a [`SecretValue`] that never prints or
+//! serializes its contents.
+
+use crate::error::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize, Serializer};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Placeholder shown instead of secret values
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Key fragments that mark a header or parameter as holding a credential
+const SENSITIVE_KEY_FRAGMENTS: &[&str] = &[
+    "authorization",
+    "password",
+    "secret",
+    "token",
+    "api-key",
+    "api_key",
+    "apikey",
+    "credential",
+    "private",
+    "cookie",
+];
+
+/// Name of a secret in the secrets manager
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SecretRef(String);
+
+impl SecretRef {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self(name.into())
+    }
+
+    pub fn name(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for SecretRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "secret:{}", self.0)
+    }
+}
+
+/// A resolved secret value
+///
+/// `Debug`, `Display` and `Serialize` all emit [`REDACTED`]; use
+/// [`expose`](Self::expose) at the point where the value is actually needed.
+#[derive(Clone, PartialEq, Eq)]
+pub struct SecretValue(String);
+
+impl SecretValue {
+    pub fn new(value: impl Into<String>) -> Self {
+        Self(value.into())
+    }
+
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for SecretValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl fmt::Display for SecretValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl Serialize for SecretValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(REDACTED)
+    }
+}
+
+/// Looks up secret values by reference
+#[async_trait]
+pub trait SecretResolver: Send + Sync {
+    async fn resolve(&self, secret: &SecretRef) -> Result<SecretValue>;
+}
+
+/// Whether a header or parameter name suggests it holds a credential
+pub fn is_sensitive_key(key: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    SENSITIVE_KEY_FRAGMENTS.iter().any(|fragment| key.contains(fragment))
+}
+
+/// Copy of a map with the values of sensitive keys replaced by [`REDACTED`]
+pub fn redact_map(map: &HashMap<String, String>) -> HashMap<String, String> {
+    map.iter()
+        .map(|(key, value)| {
+            let value = if is_sensitive_key(key) {
+                REDACTED.to_string()
+            } else {
+                value.clone()
+            };
+            (key.clone(), value)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secret_value_is_never_printed() {
+        let value = SecretValue::new("hunter2");
+        assert_eq!(value.expose(), "hunter2");
+        assert_eq!(format!("{:?}", value), REDACTED);
+        assert_eq!(value.to_string(), REDACTED);
+        assert_eq!(serde_json::to_string(&value).unwrap(), format!("\"{}\"", REDACTED));
+
+        let secret = SecretRef::new("webhooks/billing");
+        assert_eq!(serde_json::to_string(&secret).unwrap(), "\"webhooks/billing\"");
+    }
+
+    #[test]
+    fn test_redact_map() {
+        let map = HashMap::from([
+            ("Authorization".to_string(), "Bearer abc".to_string()),
+            ("X-Api-Key".to_string(), "k".to_string()),
+            ("region".to_string(), "us-east-1".to_string()),
+        ]);
+        let redacted = redact_map(&map);
+        assert_eq!(redacted["Authorization"], REDACTED);
+        assert_eq!(redacted["X-Api-Key"], REDACTED);
+        assert_eq!(redacted["region"], "us-east-1");
+    }
+}
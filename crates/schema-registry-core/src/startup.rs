@@ -15,6 +15,7 @@
 use crate::config_manager_adapter::{
     ConfigConsumer, ConfigConsumerExt, ConfigManagerAdapter, GlobalConfig, SchemaPolicies, ConfigError,
     SchemaSourcesConfig, StoragePathsConfig, VersioningPoliciesConfig, ValidationSettingsConfig,
+    CompatibilityPolicy,
 };
 use llm_config_core::Environment;
 use std::path::PathBuf;
@@ -73,6 +74,9 @@ pub struct StartupContext {
 
     /// Validation settings configuration (Phase 2B)
     pub validation_settings: ValidationSettingsConfig,
+
+    /// Compatibility rules disabled or downgraded per namespace
+    pub compatibility_policy: CompatibilityPolicy,
 }
 
 impl Default for StartupContext {
@@ -85,6 +89,7 @@ impl Default for StartupContext {
             storage_paths: StoragePathsConfig::default(),
             versioning_policies: VersioningPoliciesConfig::default(),
             validation_settings: ValidationSettingsConfig::default(),
+            compatibility_policy: CompatibilityPolicy::default(),
         }
     }
 }
@@ -236,6 +241,22 @@ pub async fn initialize_with_config_manager(
         }
     };
 
+    // Load compatibility rule overrides
+    let compatibility_policy = match adapter.load_compatibility_policy() {
+        Ok(policy) => {
+            info!("Compatibility policy loaded ({} namespace overrides)", policy.namespaces.len());
+            policy
+        }
+        Err(e) => {
+            if config.require_config {
+                return Err(e);
+            } else {
+                warn!("Failed to load compatibility policy, enforcing every rule: {}", e);
+                CompatibilityPolicy::default()
+            }
+        }
+    };
+
     info!("Schema Registry initialization complete (Phase 2B)");
     info!("Server will listen on {}:{}", global_config.server.host, global_config.server.port);
     info!("Validation: max_schema_size={} bytes, strict_mode={}",
@@ -254,6 +275,7 @@ pub async fn initialize_with_config_manager(
         storage_paths,
         versioning_policies,
         validation_settings,
+        compatibility_policy,
     })
 }
 
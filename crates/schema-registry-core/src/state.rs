@@ -35,6 +35,8 @@ pub enum SchemaState {
     Abandoned,
     /// In process of reverting to previous version
     RollingBack,
+    /// Stored but unservable until cleared by a security review
+    Quarantined,
 }
 
 impl std::fmt::Display for SchemaState {
@@ -51,11 +53,36 @@ impl std::fmt::Display for SchemaState {
             SchemaState::Archived => write!(f, "ARCHIVED"),
             SchemaState::Abandoned => write!(f, "ABANDONED"),
             SchemaState::RollingBack => write!(f, "ROLLING_BACK"),
+            SchemaState::Quarantined => write!(f, "QUARANTINED"),
         }
     }
 }
 
 impl SchemaState {
+    /// Every state, in lifecycle order
+    pub const ALL: [SchemaState; 12] = [
+        SchemaState::Draft,
+        SchemaState::Validating,
+        SchemaState::ValidationFailed,
+        SchemaState::CompatibilityCheck,
+        SchemaState::IncompatibleRejected,
+        SchemaState::Registered,
+        SchemaState::Active,
+        SchemaState::Deprecated,
+        SchemaState::Archived,
+        SchemaState::Abandoned,
+        SchemaState::RollingBack,
+        SchemaState::Quarantined,
+    ];
+
+    /// States this one can transition to, other than itself
+    pub fn next_states(&self) -> Vec<SchemaState> {
+        Self::ALL
+            .into_iter()
+            .filter(|&target| target != *self && self.can_transition_to(target))
+            .collect()
+    }
+
     /// Check if transition to another state is valid
     pub fn can_transition_to(&self, target: SchemaState) -> bool {
         match (self, target) {
@@ -65,6 +92,7 @@ impl SchemaState {
             // From Validating
             (SchemaState::Validating, SchemaState::ValidationFailed) => true,
             (SchemaState::Validating, SchemaState::CompatibilityCheck) => true,
+            (SchemaState::Validating, SchemaState::Quarantined) => true,
 
             // From ValidationFailed
             (SchemaState::ValidationFailed, SchemaState::Draft) => true,
@@ -91,6 +119,10 @@ impl SchemaState {
             (SchemaState::Deprecated, SchemaState::Archived) => true,
             (SchemaState::Deprecated, SchemaState::Active) => true, // Reactivation
 
+            // From Quarantined
+            (SchemaState::Quarantined, SchemaState::CompatibilityCheck) => true, // Cleared by review
+            (SchemaState::Quarantined, SchemaState::Abandoned) => true, // Rejected by review
+
             // From RollingBack
             (SchemaState::RollingBack, SchemaState::Active) => true,
             (SchemaState::RollingBack, SchemaState::Deprecated) => true,
@@ -113,6 +145,11 @@ impl SchemaState {
     pub fn is_active(&self) -> bool {
         matches!(self, SchemaState::Active)
     }
+
+    /// Check if the schema is held for security review and must not be served
+    pub fn is_quarantined(&self) -> bool {
+        matches!(self, SchemaState::Quarantined)
+    }
 }
 
 /// State transition record
@@ -131,6 +168,7 @@ pub struct StateTransition {
     /// Optional reason for the transition
     pub reason: Option<String>,
     /// Additional metadata
+    #[serde(serialize_with = "crate::canonical_json::sorted_value_map")]
     pub metadata: HashMap<String, serde_json::Value>,
 }
 
@@ -251,6 +289,19 @@ mod tests {
         assert!(!SchemaState::Abandoned.can_transition_to(SchemaState::Draft));
     }
 
+    #[test]
+    fn test_next_states() {
+        assert_eq!(
+            SchemaState::Active.next_states(),
+            vec![SchemaState::Deprecated, SchemaState::RollingBack]
+        );
+        assert_eq!(
+            SchemaState::Deprecated.next_states(),
+            vec![SchemaState::Active, SchemaState::Archived]
+        );
+        assert!(SchemaState::Archived.next_states().is_empty());
+    }
+
     #[test]
     fn test_terminal_states() {
         assert!(SchemaState::Archived.is_terminal());
@@ -258,6 +309,16 @@ mod tests {
         assert!(!SchemaState::Active.is_terminal());
     }
 
+    #[test]
+    fn test_quarantine_transitions() {
+        assert!(SchemaState::Validating.can_transition_to(SchemaState::Quarantined));
+        assert!(SchemaState::Quarantined.can_transition_to(SchemaState::CompatibilityCheck));
+        assert!(SchemaState::Quarantined.can_transition_to(SchemaState::Abandoned));
+        assert!(!SchemaState::Quarantined.can_transition_to(SchemaState::Active));
+        assert!(SchemaState::Quarantined.is_quarantined());
+        assert!(!SchemaState::Quarantined.is_terminal());
+    }
+
     #[test]
     fn test_lifecycle_transition() {
         let schema_id = Uuid::new_v4();
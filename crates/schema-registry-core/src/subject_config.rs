@@ -0,0 +1,354 @@
+//! Subject config files
+//!
+//! Teams keep compatibility settings next to their schemas, in a file with one
+//! entry per subject (or namespace) in the style of Confluent's subject
+//! config:
+//!
+//! ```yaml
+//! subjects:
+//!   com.example.payments:
+//!     compatibilityLevel: FULL_TRANSITIVE
+//!     normalize: true
+//!     environments:
+//!       dev: BACKWARD
+//!     owner: payments-team
+//!     notificationChannel: https://hooks.example.com/payments
+//!     visibility: private
+//!     policies:
+//!       require-docs: true
+//! ```
+//!
+//! Each entry maps onto the explicit [`NamespaceSettings`] of that path, with
+//! `normalize` kept as the [`NORMALIZE_POLICY`] policy. A file can be exported
+//! from a [`NamespaceTree`], diffed against it to detect drift, and applied to
+//! it.
+
+use crate::error::Result;
+use crate::namespace::{self, NamespaceSettings, NamespaceTree, Visibility};
+use crate::types::CompatibilityMode;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Policy that holds a subject's `normalize` flag
+pub const NORMALIZE_POLICY: &str = "normalize";
+
+/// Settings of one subject as written in a config file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct SubjectConfig {
+    #[serde(default, alias = "compatibility", skip_serializing_if = "Option::is_none")]
+    pub compatibility_level: Option<CompatibilityMode>,
+    /// Whether schemas are normalized before they are compared and stored
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub normalize: Option<bool>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub environments: BTreeMap<String, CompatibilityMode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notification_channel: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_schemas: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_versions_per_schema: Option<u64>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub policies: BTreeMap<String, serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub visibility: Option<Visibility>,
+}
+
+impl SubjectConfig {
+    /// Names of the settings that differ from `other`, as written in the file
+    pub fn changed_fields(&self, other: &SubjectConfig) -> Vec<&'static str> {
+        let mut fields = Vec::new();
+        if self.compatibility_level != other.compatibility_level {
+            fields.push("compatibilityLevel");
+        }
+        if self.normalize != other.normalize {
+            fields.push("normalize");
+        }
+        if self.environments != other.environments {
+            fields.push("environments");
+        }
+        if self.owner != other.owner {
+            fields.push("owner");
+        }
+        if self.notification_channel != other.notification_channel {
+            fields.push("notificationChannel");
+        }
+        if self.max_schemas != other.max_schemas {
+            fields.push("maxSchemas");
+        }
+        if self.max_versions_per_schema != other.max_versions_per_schema {
+            fields.push("maxVersionsPerSchema");
+        }
+        if self.policies != other.policies {
+            fields.push("policies");
+        }
+        if self.visibility != other.visibility {
+            fields.push("visibility");
+        }
+        fields
+    }
+}
+
+impl From<NamespaceSettings> for SubjectConfig {
+    fn from(mut settings: NamespaceSettings) -> Self {
+        let normalize = settings
+            .policies
+            .remove(NORMALIZE_POLICY)
+            .and_then(|value| value.as_bool());
+        Self {
+            compatibility_level: settings.compatibility_mode,
+            normalize,
+            environments: settings.environments,
+            owner: settings.owner,
+            notification_channel: settings.notification_channel,
+            max_schemas: settings.max_schemas,
+            max_versions_per_schema: settings.max_versions_per_schema,
+            policies: settings.policies,
+            visibility: settings.visibility,
+        }
+    }
+}
+
+impl From<SubjectConfig> for NamespaceSettings {
+    fn from(config: SubjectConfig) -> Self {
+        let mut policies = config.policies;
+        if let Some(normalize) = config.normalize {
+            policies.insert(NORMALIZE_POLICY.to_string(), serde_json::Value::Bool(normalize));
+        }
+        Self {
+            compatibility_mode: config.compatibility_level,
+            environments: config.environments,
+            owner: config.owner,
+            notification_channel: config.notification_channel,
+            max_schemas: config.max_schemas,
+            max_versions_per_schema: config.max_versions_per_schema,
+            policies,
+            visibility: config.visibility,
+        }
+    }
+}
+
+/// A committed set of subject configs
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SubjectConfigFile {
+    #[serde(default)]
+    pub subjects: BTreeMap<String, SubjectConfig>,
+}
+
+/// One difference between a config file and the registry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum SubjectConfigChange {
+    /// The file configures a subject the registry has no settings for
+    Create { subject: String, config: SubjectConfig },
+    /// Both configure the subject, differently
+    Update {
+        subject: String,
+        from: SubjectConfig,
+        to: SubjectConfig,
+        fields: Vec<String>,
+    },
+    /// The registry configures a subject the file doesn't mention
+    Delete { subject: String, config: SubjectConfig },
+}
+
+impl SubjectConfigChange {
+    pub fn subject(&self) -> &str {
+        match self {
+            SubjectConfigChange::Create { subject, .. }
+            | SubjectConfigChange::Update { subject, .. }
+            | SubjectConfigChange::Delete { subject, .. } => subject,
+        }
+    }
+}
+
+impl SubjectConfigFile {
+    /// The explicit settings of every namespace in the tree
+    pub fn export(tree: &NamespaceTree) -> Self {
+        Self {
+            subjects: tree
+                .all()
+                .into_iter()
+                .map(|(namespace, settings)| (namespace, settings.into()))
+                .collect(),
+        }
+    }
+
+    /// Validate and normalize the subject paths, e.g. `com.example.*`
+    pub fn normalized(self) -> Result<Self> {
+        let mut subjects = BTreeMap::new();
+        for (subject, config) in self.subjects {
+            subjects.insert(namespace::normalize(&subject)?, config);
+        }
+        Ok(Self { subjects })
+    }
+
+    /// Changes that would make `current` match this file, by subject
+    ///
+    /// Both sides are expected to be normalized.
+    pub fn diff(&self, current: &SubjectConfigFile) -> Vec<SubjectConfigChange> {
+        let mut changes = Vec::new();
+        for (subject, config) in &self.subjects {
+            match current.subjects.get(subject) {
+                None => changes.push(SubjectConfigChange::Create {
+                    subject: subject.clone(),
+                    config: config.clone(),
+                }),
+                Some(existing) if existing != config => changes.push(SubjectConfigChange::Update {
+                    subject: subject.clone(),
+                    fields: config
+                        .changed_fields(existing)
+                        .into_iter()
+                        .map(str::to_string)
+                        .collect(),
+                    from: existing.clone(),
+                    to: config.clone(),
+                }),
+                Some(_) => {}
+            }
+        }
+        for (subject, config) in &current.subjects {
+            if !self.subjects.contains_key(subject) {
+                changes.push(SubjectConfigChange::Delete {
+                    subject: subject.clone(),
+                    config: config.clone(),
+                });
+            }
+        }
+        changes.sort_by(|a, b| a.subject().cmp(b.subject()));
+        changes
+    }
+
+    /// Make the tree match this file and return the changes made
+    ///
+    /// Subjects configured only in the registry are left alone unless
+    /// `prune` is set.
+    pub fn apply(&self, tree: &NamespaceTree, prune: bool) -> Result<Vec<SubjectConfigChange>> {
+        let desired = self.clone().normalized()?;
+        let mut changes = desired.diff(&Self::export(tree));
+        if !prune {
+            changes.retain(|change| !matches!(change, SubjectConfigChange::Delete { .. }));
+        }
+
+        for change in &changes {
+            match change {
+                SubjectConfigChange::Create { subject, config }
+                | SubjectConfigChange::Update { subject, to: config, .. } => {
+                    tree.set(subject, config.clone().into())?;
+                }
+                SubjectConfigChange::Delete { subject, .. } => {
+                    tree.remove(subject);
+                }
+            }
+        }
+        Ok(changes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn committed() -> SubjectConfigFile {
+        let mut subjects = BTreeMap::new();
+        subjects.insert(
+            "com.example.payments".to_string(),
+            SubjectConfig {
+                compatibility_level: Some(CompatibilityMode::FullTransitive),
+                normalize: Some(true),
+                ..Default::default()
+            },
+        );
+        subjects.insert(
+            "com.example.orders".to_string(),
+            SubjectConfig {
+                compatibility_level: Some(CompatibilityMode::Forward),
+                ..Default::default()
+            },
+        );
+        SubjectConfigFile { subjects }
+    }
+
+    #[test]
+    fn test_normalize_round_trips_through_policies() {
+        let config = SubjectConfig {
+            compatibility_level: Some(CompatibilityMode::Full),
+            normalize: Some(true),
+            ..Default::default()
+        };
+
+        let settings = NamespaceSettings::from(config.clone());
+        assert_eq!(settings.policies[NORMALIZE_POLICY], serde_json::json!(true));
+        assert_eq!(SubjectConfig::from(settings), config);
+    }
+
+    #[test]
+    fn test_diff_reports_drift() {
+        let tree = NamespaceTree::default();
+        tree.set(
+            "com.example.payments",
+            NamespaceSettings {
+                compatibility_mode: Some(CompatibilityMode::Backward),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        tree.set("com.example.legacy", NamespaceSettings::default()).unwrap();
+
+        let changes = committed().diff(&SubjectConfigFile::export(&tree));
+
+        assert_eq!(changes.len(), 3);
+        assert!(matches!(&changes[0], SubjectConfigChange::Delete { subject, .. } if subject == "com.example.legacy"));
+        assert!(matches!(&changes[1], SubjectConfigChange::Create { subject, .. } if subject == "com.example.orders"));
+        match &changes[2] {
+            SubjectConfigChange::Update { subject, fields, .. } => {
+                assert_eq!(subject, "com.example.payments");
+                assert_eq!(fields, &vec!["compatibilityLevel".to_string(), "normalize".to_string()]);
+            }
+            other => panic!("unexpected change {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_apply_prunes_only_when_asked() {
+        let tree = NamespaceTree::default();
+        tree.set("com.example.legacy", NamespaceSettings::default()).unwrap();
+
+        let applied = committed().apply(&tree, false).unwrap();
+        assert_eq!(applied.len(), 2);
+        assert!(tree.get("com.example.legacy").is_some());
+        assert_eq!(
+            tree.effective("com.example.payments.refunds").unwrap().compatibility_mode.value,
+            CompatibilityMode::FullTransitive
+        );
+
+        let applied = committed().apply(&tree, true).unwrap();
+        assert_eq!(applied.len(), 1);
+        assert!(tree.get("com.example.legacy").is_none());
+        assert!(committed().diff(&SubjectConfigFile::export(&tree)).is_empty());
+    }
+
+    #[test]
+    fn test_confluent_style_file() {
+        let file: SubjectConfigFile = serde_json::from_value(serde_json::json!({
+            "subjects": {
+                "com.example.*": {"compatibility": "BACKWARD_TRANSITIVE", "normalize": false}
+            }
+        }))
+        .unwrap();
+        let file = file.normalized().unwrap();
+
+        assert_eq!(
+            file.subjects["com.example"].compatibility_level,
+            Some(CompatibilityMode::BackwardTransitive)
+        );
+        assert!(serde_json::from_value::<SubjectConfigFile>(serde_json::json!({
+            "subjects": {"com.example": {"compatibilityLevl": "FULL"}}
+        }))
+        .is_err());
+    }
+}
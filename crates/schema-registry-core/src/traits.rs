@@ -9,7 +9,7 @@ use uuid::Uuid;
 use crate::error::Result;
 use crate::events::SchemaEvent;
 use crate::schema::{RegisteredSchema, SchemaInput};
-use crate::types::CompatibilityMode;
+use crate::types::{CompatibilityMode, RemediationAction};
 use crate::versioning::SemanticVersion;
 
 /// Trait for schema storage operations
@@ -89,6 +89,8 @@ pub struct CompatibilityResult {
     pub mode: CompatibilityMode,
     /// List of compatibility violations
     pub violations: Vec<CompatibilityViolation>,
+    /// Violations allowed by an active compatibility exemption
+    pub exempted_violations: Vec<CompatibilityViolation>,
     /// Versions that were checked
     pub checked_versions: Vec<SemanticVersion>,
 }
@@ -108,6 +110,33 @@ pub struct CompatibilityViolation {
     pub severity: crate::types::ViolationSeverity,
     /// Description of the violation
     pub description: String,
+    /// How to fix the new schema, when the checker knows
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<RemediationSuggestion>,
+}
+
+impl CompatibilityViolation {
+    /// Attach a suggested fix
+    pub fn with_suggestion(
+        mut self,
+        action: RemediationAction,
+        message: impl Into<String>,
+    ) -> Self {
+        self.suggestion = Some(RemediationSuggestion {
+            action,
+            message: message.into(),
+        });
+        self
+    }
+}
+
+/// A suggested fix for a compatibility violation
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct RemediationSuggestion {
+    /// What kind of change fixes it
+    pub action: RemediationAction,
+    /// The change spelled out for the field involved
+    pub message: String,
 }
 
 /// Trait for compatibility checking
@@ -162,6 +191,7 @@ mod tests {
             is_compatible: true,
             mode: CompatibilityMode::Backward,
             violations: vec![],
+            exempted_violations: vec![],
             checked_versions: vec![],
         };
         assert!(result.is_compatible);
@@ -1,6 +1,8 @@
 //! Core type definitions
 
-use serde::{Deserialize, Serialize};
+use crate::error::{Error, Result};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::str::FromStr;
 
 /// Serialization format for schemas
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -14,6 +16,15 @@ pub enum SerializationFormat {
     Protobuf,
 }
 
+impl SerializationFormat {
+    /// All supported serialization formats
+    pub const ALL: [SerializationFormat; 3] = [
+        SerializationFormat::JsonSchema,
+        SerializationFormat::Avro,
+        SerializationFormat::Protobuf,
+    ];
+}
+
 impl std::fmt::Display for SerializationFormat {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -25,8 +36,11 @@ impl std::fmt::Display for SerializationFormat {
 }
 
 /// Compatibility mode for schema evolution
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
-#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+///
+/// Serializes as its canonical name (`BACKWARD_TRANSITIVE`). Deserializing is
+/// lenient like [`CompatibilityMode::parse_lenient`], so hand-written config
+/// files may say `backward-transitive`; unknown modes are still rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum CompatibilityMode {
     /// New schema can read old data
     Backward,
@@ -46,19 +60,87 @@ pub enum CompatibilityMode {
 
 impl std::fmt::Display for CompatibilityMode {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            CompatibilityMode::Backward => write!(f, "BACKWARD"),
-            CompatibilityMode::Forward => write!(f, "FORWARD"),
-            CompatibilityMode::Full => write!(f, "FULL"),
-            CompatibilityMode::None => write!(f, "NONE"),
-            CompatibilityMode::BackwardTransitive => write!(f, "BACKWARD_TRANSITIVE"),
-            CompatibilityMode::ForwardTransitive => write!(f, "FORWARD_TRANSITIVE"),
-            CompatibilityMode::FullTransitive => write!(f, "FULL_TRANSITIVE"),
-        }
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for CompatibilityMode {
+    type Err = Error;
+
+    /// Parse a canonical mode name such as `BACKWARD` or `FULL_TRANSITIVE`
+    fn from_str(s: &str) -> Result<Self> {
+        CompatibilityMode::ALL
+            .into_iter()
+            .find(|mode| mode.as_str() == s)
+            .ok_or_else(|| Error::ValidationError(CompatibilityMode::unknown(s)))
+    }
+}
+
+impl Serialize for CompatibilityMode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for CompatibilityMode {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        CompatibilityMode::find_lenient(&s)
+            .ok_or_else(|| serde::de::Error::custom(CompatibilityMode::unknown(&s)))
     }
 }
 
 impl CompatibilityMode {
+    /// All supported compatibility modes
+    pub const ALL: [CompatibilityMode; 7] = [
+        CompatibilityMode::Backward,
+        CompatibilityMode::Forward,
+        CompatibilityMode::Full,
+        CompatibilityMode::None,
+        CompatibilityMode::BackwardTransitive,
+        CompatibilityMode::ForwardTransitive,
+        CompatibilityMode::FullTransitive,
+    ];
+
+    /// Canonical name, as displayed and serialized
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CompatibilityMode::Backward => "BACKWARD",
+            CompatibilityMode::Forward => "FORWARD",
+            CompatibilityMode::Full => "FULL",
+            CompatibilityMode::None => "NONE",
+            CompatibilityMode::BackwardTransitive => "BACKWARD_TRANSITIVE",
+            CompatibilityMode::ForwardTransitive => "FORWARD_TRANSITIVE",
+            CompatibilityMode::FullTransitive => "FULL_TRANSITIVE",
+        }
+    }
+
+    /// Parse a mode ignoring case, surrounding whitespace, and whether words
+    /// are separated by `_`, `-` or spaces (`full-transitive`)
+    ///
+    /// Use this for input typed by people; [`FromStr`] accepts only the
+    /// canonical names.
+    pub fn parse_lenient(s: &str) -> Result<Self> {
+        CompatibilityMode::find_lenient(s)
+            .ok_or_else(|| Error::ValidationError(CompatibilityMode::unknown(s)))
+    }
+
+    fn find_lenient(s: &str) -> Option<Self> {
+        let normalized = s.trim().to_ascii_uppercase().replace(['-', ' '], "_");
+        CompatibilityMode::ALL
+            .into_iter()
+            .find(|mode| mode.as_str() == normalized)
+    }
+
+    fn unknown(s: &str) -> String {
+        let valid: Vec<&str> = CompatibilityMode::ALL.iter().map(|m| m.as_str()).collect();
+        format!(
+            "unknown compatibility mode '{}': expected one of {}",
+            s,
+            valid.join(", ")
+        )
+    }
+
     /// Check if this is a transitive mode
     pub fn is_transitive(&self) -> bool {
         matches!(
@@ -71,7 +153,7 @@ impl CompatibilityMode {
 }
 
 /// Type of compatibility violation
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum ViolationType {
     /// Field was removed
@@ -100,6 +182,62 @@ pub enum ViolationSeverity {
     Info,
 }
 
+impl ViolationSeverity {
+    /// Whether this severity is `threshold` or more severe
+    pub fn is_at_least(self, threshold: ViolationSeverity) -> bool {
+        self.rank() >= threshold.rank()
+    }
+
+    fn rank(self) -> u8 {
+        match self {
+            ViolationSeverity::Info => 0,
+            ViolationSeverity::Warning => 1,
+            ViolationSeverity::Breaking => 2,
+        }
+    }
+}
+
+impl FromStr for ViolationSeverity {
+    type Err = Error;
+
+    /// Parse `BREAKING`, `WARNING` or `INFO`, in any case
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_uppercase().as_str() {
+            "BREAKING" => Ok(ViolationSeverity::Breaking),
+            "WARNING" => Ok(ViolationSeverity::Warning),
+            "INFO" => Ok(ViolationSeverity::Info),
+            _ => Err(Error::ValidationError(format!(
+                "Unknown violation severity '{}'; expected BREAKING, WARNING or INFO",
+                s
+            ))),
+        }
+    }
+}
+
+/// Kind of change to the new schema that resolves a compatibility violation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RemediationAction {
+    /// Give the field a default value
+    AddDefault,
+    /// Stop requiring the field
+    MarkOptional,
+    /// Keep requiring the field
+    KeepRequired,
+    /// Put the removed field back
+    RestoreField,
+    /// Put the field back marked deprecated, to be removed after its notice period
+    DeprecateField,
+    /// Drop the field that was added
+    RemoveField,
+    /// Change the type back, or to one both schemas can read
+    RestoreType,
+    /// Bring the enum values back in line with the previous version
+    RestoreEnumValues,
+    /// Bring the constraint back in line with the previous version
+    RestoreConstraint,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -121,4 +259,68 @@ mod tests {
         assert!(CompatibilityMode::ForwardTransitive.is_transitive());
         assert!(CompatibilityMode::FullTransitive.is_transitive());
     }
+
+    #[test]
+    fn test_compatibility_mode_strict_parsing() {
+        for mode in CompatibilityMode::ALL {
+            assert_eq!(mode.to_string().parse::<CompatibilityMode>().unwrap(), mode);
+        }
+        assert!("backward".parse::<CompatibilityMode>().is_err());
+        assert!("FULL-TRANSITIVE".parse::<CompatibilityMode>().is_err());
+    }
+
+    #[test]
+    fn test_compatibility_mode_lenient_parsing() {
+        assert_eq!(
+            CompatibilityMode::parse_lenient(" backward ").unwrap(),
+            CompatibilityMode::Backward
+        );
+        assert_eq!(
+            CompatibilityMode::parse_lenient("full-transitive").unwrap(),
+            CompatibilityMode::FullTransitive
+        );
+        assert_eq!(
+            CompatibilityMode::parse_lenient("Forward Transitive").unwrap(),
+            CompatibilityMode::ForwardTransitive
+        );
+
+        let err = CompatibilityMode::parse_lenient("SIDEWAYS")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("'SIDEWAYS'"));
+        assert!(err.contains("BACKWARD, FORWARD, FULL, NONE, BACKWARD_TRANSITIVE"));
+    }
+
+    #[test]
+    fn test_compatibility_mode_serde() {
+        assert_eq!(
+            serde_json::to_string(&CompatibilityMode::BackwardTransitive).unwrap(),
+            "\"BACKWARD_TRANSITIVE\""
+        );
+        let mode: CompatibilityMode = serde_json::from_str("\"backward_transitive\"").unwrap();
+        assert_eq!(mode, CompatibilityMode::BackwardTransitive);
+
+        let err = serde_json::from_str::<CompatibilityMode>("\"LATEST\"").unwrap_err();
+        assert!(err.to_string().contains("expected one of BACKWARD"));
+    }
+
+    #[test]
+    fn test_violation_severity_thresholds() {
+        assert!(ViolationSeverity::Breaking.is_at_least(ViolationSeverity::Warning));
+        assert!(ViolationSeverity::Warning.is_at_least(ViolationSeverity::Warning));
+        assert!(!ViolationSeverity::Info.is_at_least(ViolationSeverity::Warning));
+        assert_eq!(
+            "warning".parse::<ViolationSeverity>().unwrap(),
+            ViolationSeverity::Warning
+        );
+        assert!("fatal".parse::<ViolationSeverity>().is_err());
+    }
+
+    #[test]
+    fn test_all_lists_are_distinct() {
+        let modes: std::collections::HashSet<_> = CompatibilityMode::ALL.iter().collect();
+        assert_eq!(modes.len(), CompatibilityMode::ALL.len());
+        let formats: std::collections::HashSet<_> = SerializationFormat::ALL.iter().collect();
+        assert_eq!(formats.len(), SerializationFormat::ALL.len());
+    }
 }
@@ -10,8 +10,8 @@ use crate::graph_store::GraphStore;
 use crate::impact::ImpactAnalyzer;
 use crate::tracker::{DependencyTracker, DependencyTrackerImpl};
 use crate::types::{
-    CircularDependency, Dependency, DependencyGraph, DependencyTarget, Dependent, ImpactReport,
-    RelationType, SchemaChange, SchemaId, SchemaNode,
+    CircularDependency, Consumer, Dependency, DependencyGraph, DependencyTarget, Dependent,
+    EntityType, ImpactReport, RelationType, SchemaChange, SchemaId, SchemaNode,
 };
 use async_trait::async_trait;
 use std::collections::HashMap;
@@ -90,6 +90,43 @@ impl LineageEngine {
         self.tracker.get_downstream(schema_id).await
     }
 
+    /// Get the known consumers of a schema
+    ///
+    /// A schema the graph has never seen has none.
+    pub async fn get_consumers(&self, schema_id: SchemaId) -> Result<Vec<Consumer>> {
+        if !self.contains_schema(&schema_id) {
+            return Ok(Vec::new());
+        }
+
+        let mut consumers = Vec::new();
+        for dependency in self.get_upstream(schema_id).await? {
+            if let DependencyTarget::External(entity) = dependency.to {
+                let reads = dependency.relation.is_application_relation()
+                    || dependency.relation.is_model_relation()
+                    || dependency.relation == RelationType::ConsumedBy;
+                if reads {
+                    consumers.push(Consumer {
+                        id: entity.id,
+                        name: entity.name,
+                        entity_type: entity.entity_type,
+                        relation: dependency.relation,
+                    });
+                }
+            }
+        }
+        for dependent in self.get_downstream(schema_id).await? {
+            if dependent.node.schema_id != schema_id {
+                consumers.push(Consumer {
+                    id: dependent.node.schema_id.to_string(),
+                    name: dependent.node.fqn,
+                    entity_type: EntityType::Schema,
+                    relation: dependent.relation,
+                });
+            }
+        }
+        Ok(consumers)
+    }
+
     /// Get transitive dependencies with depth limit
     pub async fn get_transitive(
         &self,
@@ -347,6 +384,7 @@ impl LineageTracker for LineageEngine {
 mod tests {
     use super::*;
     use schema_registry_core::versioning::SemanticVersion;
+    use crate::types::ExternalEntity;
 
     fn create_test_schema(id: SchemaId, name: &str) -> SchemaNode {
         SchemaNode::new(
@@ -435,6 +473,45 @@ mod tests {
         assert!(report.is_breaking());
     }
 
+    #[tokio::test]
+    async fn test_get_consumers() {
+        let engine = LineageEngine::new();
+
+        let id1 = SchemaId::new_v4();
+        let id2 = SchemaId::new_v4();
+        let user = create_test_schema(id1, "User");
+        let profile = create_test_schema(id2, "Profile");
+        let entity = |id: &str, entity_type| ExternalEntity {
+            id: id.to_string(),
+            entity_type,
+            name: id.to_string(),
+            metadata: HashMap::new(),
+        };
+
+        engine.track_dependency(
+            profile,
+            DependencyTarget::Schema(user.clone()),
+            RelationType::DependsOn
+        ).await.unwrap();
+        engine.track_dependency(
+            user.clone(),
+            DependencyTarget::External(entity("checkout", EntityType::Application)),
+            RelationType::UsedBy
+        ).await.unwrap();
+        engine.track_dependency(
+            user,
+            DependencyTarget::External(entity("ingest", EntityType::Pipeline)),
+            RelationType::ProducedBy
+        ).await.unwrap();
+
+        let consumers = engine.get_consumers(id1).await.unwrap();
+        assert_eq!(consumers.len(), 2);
+        assert!(consumers.iter().any(|c| c.name == "checkout" && c.relation == RelationType::UsedBy));
+        assert!(consumers.iter().any(|c| c.id == id2.to_string() && c.entity_type == EntityType::Schema));
+
+        assert!(engine.get_consumers(SchemaId::new_v4()).await.unwrap().is_empty());
+    }
+
     #[tokio::test]
     async fn test_detect_circular() {
         let engine = LineageEngine::new();
@@ -160,9 +160,9 @@ pub use graph_store::{GraphStats, GraphStore};
 pub use impact::{ImpactAnalyzer, ImpactSummary};
 pub use tracker::{DependencyTracker, DependencyTrackerImpl};
 pub use types::{
-    CircularDependency, Dependency, DependencyGraph, DependencyTarget, Dependent, EntityType,
-    ExternalEntity, ImpactReport, LineageFilter, RelationType, RiskLevel, SchemaChange, SchemaId,
-    SchemaNode,
+    CircularDependency, Consumer, Dependency, DependencyGraph, DependencyTarget, Dependent,
+    EntityType, ExternalEntity, ImpactReport, LineageFilter, RelationType, RiskLevel, SchemaChange,
+    SchemaId, SchemaNode,
 };
 
 #[cfg(test)]
@@ -206,6 +206,20 @@ pub struct Dependent {
     pub created_at: DateTime<Utc>,
 }
 
+/// Something that reads a schema: an application, pipeline or model using it,
+/// or another schema built on it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Consumer {
+    /// Entity ID, or schema ID for a schema
+    pub id: String,
+    /// Human-readable name, or fully qualified name for a schema
+    pub name: String,
+    /// Kind of consumer
+    pub entity_type: EntityType,
+    /// How it uses the schema
+    pub relation: RelationType,
+}
+
 /// Complete dependency graph structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DependencyGraph {
@@ -0,0 +1,320 @@
+//! Analytics aggregates as Prometheus metrics
+//!
+//! [`AnalyticsCollector`] reads the analytics engine at scrape time, so
+//! dashboards can chart schema usage without calling the analytics REST API:
+//!
+//! - `schema_registry_analytics_schema_requests_total{schema, operation}` -
+//!   requests per schema; only the `top_k` busiest schemas get their own
+//!   label, the rest are summed under `schema="other"`. Requests are added
+//!   to whichever series the schema belongs to at the scrape that sees them,
+//!   so a schema moving in or out of the top K never makes a series go down
+//! - `schema_registry_analytics_operation_latency_seconds{operation, quantile}` -
+//!   p50/p95/p99 over the most recent aggregation window
+//! - `schema_registry_analytics_operation_error_ratio{operation}` - share of
+//!   failed requests over the same window
+//!
+//! Register it with [`MetricsCollector::register_analytics`](crate::MetricsCollector::register_analytics).
+
+use chrono::{Duration, Utc};
+use prometheus::core::{Collector, Desc};
+use prometheus::proto::MetricFamily;
+use prometheus::{GaugeVec, IntCounterVec, Opts};
+use schema_registry_analytics::{AnalyticsEngine, SchemaStats, TimePeriod, UsageStats};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+/// Label for schemas outside the top K
+pub const OTHER_SCHEMAS: &str = "other";
+
+/// What the collector exports
+#[derive(Debug, Clone)]
+pub struct AnalyticsExportConfig {
+    /// Schemas exported with their own label
+    pub top_k: usize,
+    /// Aggregation window for latency quantiles and error ratios
+    pub window: TimePeriod,
+}
+
+impl Default for AnalyticsExportConfig {
+    fn default() -> Self {
+        Self {
+            top_k: 20,
+            window: TimePeriod::Minute5,
+        }
+    }
+}
+
+/// Prometheus collector backed by the analytics engine
+pub struct AnalyticsCollector {
+    engine: Arc<AnalyticsEngine>,
+    config: AnalyticsExportConfig,
+    schema_requests: IntCounterVec,
+    /// Per schema and operation, the engine's count already added to
+    /// `schema_requests`
+    exported: Mutex<HashMap<(String, &'static str), u64>>,
+    operation_latency: GaugeVec,
+    operation_error_ratio: GaugeVec,
+}
+
+impl AnalyticsCollector {
+    pub fn new(
+        engine: Arc<AnalyticsEngine>,
+        config: AnalyticsExportConfig,
+    ) -> Result<Self, prometheus::Error> {
+        Ok(Self {
+            engine,
+            config,
+            schema_requests: IntCounterVec::new(
+                Opts::new(
+                    "schema_registry_analytics_schema_requests_total",
+                    "Requests per schema and operation, top schemas only",
+                ),
+                &["schema", "operation"],
+            )?,
+            exported: Mutex::new(HashMap::new()),
+            operation_latency: GaugeVec::new(
+                Opts::new(
+                    "schema_registry_analytics_operation_latency_seconds",
+                    "Operation latency quantiles over the latest analytics window",
+                ),
+                &["operation", "quantile"],
+            )?,
+            operation_error_ratio: GaugeVec::new(
+                Opts::new(
+                    "schema_registry_analytics_operation_error_ratio",
+                    "Share of failed operations over the latest analytics window",
+                ),
+                &["operation"],
+            )?,
+        })
+    }
+
+    /// Add the requests counted since the last scrape
+    fn update_schema_requests(&self) {
+        let mut stats = self.engine.get_all_schema_stats();
+        stats.sort_by(|a, b| b.total_operations.cmp(&a.total_operations));
+        let top_k = self.config.top_k.min(stats.len());
+
+        let mut exported = self.exported.lock().unwrap();
+        let mut present = HashSet::new();
+        for (rank, schema) in stats.iter().enumerate() {
+            let schema_id = schema.schema_id.to_string();
+            let label = if rank < top_k {
+                schema_id.as_str()
+            } else {
+                OTHER_SCHEMAS
+            };
+            for (operation, count) in operation_counts(schema) {
+                let seen = exported.entry((schema_id.clone(), operation)).or_default();
+                // The engine's count only drops when it forgot the schema,
+                // so everything it has counted since is new
+                let added = if count >= *seen { count - *seen } else { count };
+                *seen = count;
+                if added > 0 {
+                    self.schema_requests
+                        .with_label_values(&[label, operation])
+                        .inc_by(added);
+                }
+            }
+            present.insert(schema_id);
+        }
+        exported.retain(|(schema_id, _), _| present.contains(schema_id));
+    }
+
+    fn update_window_stats(&self) {
+        self.operation_latency.reset();
+        self.operation_error_ratio.reset();
+
+        let Some(window) = self.latest_window() else {
+            return;
+        };
+        for (operation, stats) in &window.operations {
+            let operation = operation.to_string().to_lowercase();
+            for (quantile, latency_ms) in [
+                ("0.5", stats.p50_latency_ms),
+                ("0.95", stats.p95_latency_ms),
+                ("0.99", stats.p99_latency_ms),
+            ] {
+                self.operation_latency
+                    .with_label_values(&[&operation, quantile])
+                    .set(latency_ms as f64 / 1000.0);
+            }
+            if stats.count > 0 {
+                let failures = stats.count.saturating_sub(stats.success_count);
+                self.operation_error_ratio
+                    .with_label_values(&[&operation])
+                    .set(failures as f64 / stats.count as f64);
+            }
+        }
+    }
+
+    /// Most recent window with traffic: the current one, else the previous
+    fn latest_window(&self) -> Option<UsageStats> {
+        let now = Utc::now();
+        let start = now - Duration::seconds(2 * self.config.window.duration_seconds());
+        self.engine
+            .get_usage_stats(self.config.window, start, now, None)
+            .ok()?
+            .into_iter()
+            .rev()
+            .find(|stats| stats.total_count > 0)
+    }
+}
+
+/// A schema's requests by operation
+fn operation_counts(stats: &SchemaStats) -> [(&'static str, u64); 5] {
+    let counted = [
+        ("read", stats.read_count),
+        ("write", stats.write_count),
+        ("validate", stats.validation_count),
+        ("check_compatibility", stats.compatibility_check_count),
+    ];
+    let other = stats
+        .total_operations
+        .saturating_sub(counted.iter().map(|(_, count)| count).sum());
+    let [read, write, validate, check_compatibility] = counted;
+    [read, write, validate, check_compatibility, ("other", other)]
+}
+
+impl Collector for AnalyticsCollector {
+    fn desc(&self) -> Vec<&Desc> {
+        self.schema_requests
+            .desc()
+            .into_iter()
+            .chain(self.operation_latency.desc())
+            .chain(self.operation_error_ratio.desc())
+            .collect()
+    }
+
+    fn collect(&self) -> Vec<MetricFamily> {
+        self.update_schema_requests();
+        self.update_window_stats();
+
+        self.schema_requests
+            .collect()
+            .into_iter()
+            .chain(self.operation_latency.collect())
+            .chain(self.operation_error_ratio.collect())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prometheus::{Registry, TextEncoder};
+    use schema_registry_analytics::{Operation, SchemaUsageEvent};
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn test_top_k_and_window_metrics() {
+        let engine = Arc::new(AnalyticsEngine::new());
+        engine.start().await.unwrap();
+
+        let busy = Uuid::new_v4();
+        for (schema, reads) in [(busy, 5), (Uuid::new_v4(), 2), (Uuid::new_v4(), 1)] {
+            for i in 0..reads {
+                let event = SchemaUsageEvent::new(
+                    schema,
+                    Operation::Read,
+                    "client".to_string(),
+                    "us-west-1".to_string(),
+                    20,
+                    i != 0,
+                );
+                engine.record_event(event).unwrap();
+            }
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+        let collector = AnalyticsCollector::new(
+            engine.clone(),
+            AnalyticsExportConfig {
+                top_k: 1,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let registry = Registry::new();
+        registry.register(Box::new(collector)).unwrap();
+        let export = TextEncoder::new()
+            .encode_to_string(&registry.gather())
+            .unwrap();
+
+        assert!(export.contains(&format!(
+            "schema_registry_analytics_schema_requests_total{{operation=\"read\",schema=\"{}\"}} 5",
+            busy
+        )));
+        assert!(export.contains(
+            "schema_registry_analytics_schema_requests_total{operation=\"read\",schema=\"other\"} 3"
+        ));
+        assert!(export.contains(
+            "schema_registry_analytics_operation_latency_seconds{operation=\"read\",quantile=\"0.99\"} 0.02"
+        ));
+        assert!(export
+            .contains("schema_registry_analytics_operation_error_ratio{operation=\"read\"} 0.375"));
+
+        engine.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_schema_requests_never_decrease() {
+        let engine = Arc::new(AnalyticsEngine::new());
+        engine.start().await.unwrap();
+        let record = |schema: Uuid, reads: usize| {
+            for _ in 0..reads {
+                let event = SchemaUsageEvent::new(
+                    schema,
+                    Operation::Read,
+                    "client".to_string(),
+                    "us-west-1".to_string(),
+                    20,
+                    true,
+                );
+                engine.record_event(event).unwrap();
+            }
+        };
+        let settle = || tokio::time::sleep(std::time::Duration::from_millis(100));
+
+        let (first, second) = (Uuid::new_v4(), Uuid::new_v4());
+        record(first, 3);
+        record(second, 1);
+        settle().await;
+
+        let registry = Registry::new();
+        let collector = AnalyticsCollector::new(
+            engine.clone(),
+            AnalyticsExportConfig {
+                top_k: 1,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        registry.register(Box::new(collector)).unwrap();
+        let series = |schema: &str| {
+            format!(
+                "schema_registry_analytics_schema_requests_total{{operation=\"read\",schema=\"{}\"}}",
+                schema
+            )
+        };
+
+        let export = TextEncoder::new()
+            .encode_to_string(&registry.gather())
+            .unwrap();
+        assert!(export.contains(&format!("{} 3", series(&first.to_string()))));
+        assert!(export.contains(&format!("{} 1", series(OTHER_SCHEMAS))));
+
+        // The second schema overtakes the first: its new reads get its own
+        // series, and neither existing series goes down
+        record(second, 5);
+        settle().await;
+        let export = TextEncoder::new()
+            .encode_to_string(&registry.gather())
+            .unwrap();
+        assert!(export.contains(&format!("{} 3", series(&first.to_string()))));
+        assert!(export.contains(&format!("{} 1", series(OTHER_SCHEMAS))));
+        assert!(export.contains(&format!("{} 5", series(&second.to_string()))));
+
+        engine.shutdown().await.unwrap();
+    }
+}
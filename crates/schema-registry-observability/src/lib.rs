@@ -6,16 +6,25 @@
 //! - Structured JSON logging with correlation IDs
 //! - HTTP and gRPC middleware for automatic instrumentation
 //! - SLI/SLO monitoring support
+//! - Analytics aggregates (top schemas, latency quantiles, error ratios) as metrics
+//! - Label scrubbing so scraped metrics don't expose subject names
+//! - A `metrics` recorder exporting library metrics to the Prometheus registry
 
+pub mod analytics;
 pub mod logging;
 pub mod metrics;
 pub mod middleware;
+pub mod recorder;
+pub mod scrub;
 pub mod tracing_setup;
 
+pub use analytics::{AnalyticsCollector, AnalyticsExportConfig};
 pub use metrics::MetricsCollector;
+pub use recorder::PrometheusRecorder;
+pub use scrub::LabelScrubber;
 pub use tracing_setup::{
-    init_tracing, setup_tracing, shutdown_tracing, TracingConfig,
-    context as trace_context, correlation,
+    detect_resource_attributes, init_tracing, setup_tracing, shutdown_tracing, ExporterConfig,
+    ExporterKind, SamplingControl, TracingConfig, context as trace_context, correlation,
 };
 pub use logging::{LogContext, LogSamplingConfig, ModuleLogLevels};
 pub use middleware::{
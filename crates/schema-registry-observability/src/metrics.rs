@@ -6,11 +6,13 @@
 //! - Storage metrics (cache hit rate, DB connections, query duration)
 //! - System metrics (memory, CPU, goroutines)
 
+use crate::analytics::{AnalyticsCollector, AnalyticsExportConfig};
 use prometheus::{
     register_counter_vec, register_gauge_vec, register_histogram_vec, register_int_counter_vec,
     register_int_gauge_vec, CounterVec, GaugeVec, HistogramVec, IntCounterVec, IntGaugeVec,
     Registry, TextEncoder,
 };
+use schema_registry_analytics::AnalyticsEngine;
 use std::sync::Arc;
 
 /// Comprehensive metrics collector for the schema registry
@@ -501,6 +503,16 @@ impl MetricsCollector {
         encoder.encode_to_string(&metric_families)
     }
 
+    /// Exports analytics aggregates alongside the registry's own metrics
+    pub fn register_analytics(
+        &self,
+        engine: Arc<AnalyticsEngine>,
+        config: AnalyticsExportConfig,
+    ) -> Result<(), prometheus::Error> {
+        let collector = AnalyticsCollector::new(engine, config)?;
+        self.registry.register(Box::new(collector))
+    }
+
     /// Gets metric count for reporting
     pub fn metric_count(&self) -> usize {
         self.registry.gather().len()
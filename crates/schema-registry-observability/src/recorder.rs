@@ -0,0 +1,322 @@
+//! Bridge from the `metrics` facade to a Prometheus registry
+//!
+//! Library crates (core, storage, compatibility, security) record through the
+//! `metrics` macros, while the server exposes the `prometheus` default
+//! registry. [`PrometheusRecorder`] registers each `metrics` series as a
+//! Prometheus collector the first time it is used, so both kinds of metrics
+//! are scraped from the same `/metrics` page and pass through the same label
+//! scrubbing.
+//!
+//! Names are reduced to Prometheus' character set, so
+//! `schema_registry.cache.l1.hits` is exported as
+//! `schema_registry_cache_l1_hits`. A name another collector already
+//! registered, or a name reused with different labels, is not exported.
+
+use metrics::{
+    Counter, CounterFn, Gauge, GaugeFn, Histogram, HistogramFn, Key, KeyName, Metadata, Recorder,
+    SharedString, Unit,
+};
+use prometheus::{GaugeVec, HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone, Copy)]
+enum Kind {
+    Counter,
+    Gauge,
+    Histogram,
+}
+
+#[derive(Clone)]
+enum Family {
+    Counter(IntCounterVec),
+    Gauge(GaugeVec),
+    Histogram(HistogramVec),
+}
+
+/// A registered collector and its label names
+struct Registered {
+    labels: Vec<String>,
+    family: Family,
+}
+
+/// `metrics` recorder that registers its series in a Prometheus registry
+pub struct PrometheusRecorder {
+    registry: Registry,
+    descriptions: Mutex<HashMap<String, String>>,
+    /// Collectors by exported name; `None` for names that can't be exported
+    families: Mutex<HashMap<String, Option<Registered>>>,
+}
+
+impl PrometheusRecorder {
+    pub fn new(registry: Registry) -> Self {
+        Self {
+            registry,
+            descriptions: Mutex::new(HashMap::new()),
+            families: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn describe(&self, key: KeyName, description: SharedString) {
+        self.descriptions
+            .lock()
+            .unwrap()
+            .insert(sanitize(key.as_str()), description.into_owned());
+    }
+
+    /// The collector for `key`, `None` if it can't be exported or was
+    /// registered with other labels
+    fn family(&self, key: &Key, kind: Kind) -> Option<Family> {
+        let name = sanitize(key.name());
+        let labels: Vec<String> = key.labels().map(|label| sanitize(label.key())).collect();
+        let mut families = self.families.lock().unwrap();
+        if let Some(registered) = families.get(&name) {
+            return registered
+                .as_ref()
+                .filter(|registered| registered.labels == labels)
+                .map(|registered| registered.family.clone());
+        }
+
+        let label_names: Vec<&str> = labels.iter().map(String::as_str).collect();
+        let help = self
+            .descriptions
+            .lock()
+            .unwrap()
+            .get(&name)
+            .cloned()
+            .unwrap_or_else(|| name.clone());
+        let family = match self.register(kind, &name, &help, &label_names) {
+            Ok(family) => family,
+            Err(e) => {
+                tracing::warn!(
+                    metric = %name,
+                    error = %e,
+                    "Metric can't be exported to Prometheus"
+                );
+                families.insert(name, None);
+                return None;
+            }
+        };
+        families.insert(
+            name,
+            Some(Registered {
+                labels,
+                family: family.clone(),
+            }),
+        );
+        Some(family)
+    }
+
+    fn register(
+        &self,
+        kind: Kind,
+        name: &str,
+        help: &str,
+        labels: &[&str],
+    ) -> prometheus::Result<Family> {
+        let family = match kind {
+            Kind::Counter => {
+                let vec = IntCounterVec::new(Opts::new(name, help), labels)?;
+                self.registry.register(Box::new(vec.clone()))?;
+                Family::Counter(vec)
+            }
+            Kind::Gauge => {
+                let vec = GaugeVec::new(Opts::new(name, help), labels)?;
+                self.registry.register(Box::new(vec.clone()))?;
+                Family::Gauge(vec)
+            }
+            Kind::Histogram => {
+                let vec = HistogramVec::new(HistogramOpts::new(name, help), labels)?;
+                self.registry.register(Box::new(vec.clone()))?;
+                Family::Histogram(vec)
+            }
+        };
+        Ok(family)
+    }
+}
+
+impl Recorder for PrometheusRecorder {
+    fn describe_counter(&self, key: KeyName, _unit: Option<Unit>, description: SharedString) {
+        self.describe(key, description);
+    }
+
+    fn describe_gauge(&self, key: KeyName, _unit: Option<Unit>, description: SharedString) {
+        self.describe(key, description);
+    }
+
+    fn describe_histogram(&self, key: KeyName, _unit: Option<Unit>, description: SharedString) {
+        self.describe(key, description);
+    }
+
+    fn register_counter(&self, key: &Key, _metadata: &Metadata<'_>) -> Counter {
+        match self.family(key, Kind::Counter) {
+            Some(Family::Counter(vec)) => vec
+                .get_metric_with_label_values(&label_values(key))
+                .map(|counter| Counter::from_arc(Arc::new(CounterHandle(counter))))
+                .unwrap_or_else(|_| Counter::noop()),
+            _ => Counter::noop(),
+        }
+    }
+
+    fn register_gauge(&self, key: &Key, _metadata: &Metadata<'_>) -> Gauge {
+        match self.family(key, Kind::Gauge) {
+            Some(Family::Gauge(vec)) => vec
+                .get_metric_with_label_values(&label_values(key))
+                .map(|gauge| Gauge::from_arc(Arc::new(GaugeHandle(gauge))))
+                .unwrap_or_else(|_| Gauge::noop()),
+            _ => Gauge::noop(),
+        }
+    }
+
+    fn register_histogram(&self, key: &Key, _metadata: &Metadata<'_>) -> Histogram {
+        match self.family(key, Kind::Histogram) {
+            Some(Family::Histogram(vec)) => vec
+                .get_metric_with_label_values(&label_values(key))
+                .map(|histogram| Histogram::from_arc(Arc::new(HistogramHandle(histogram))))
+                .unwrap_or_else(|_| Histogram::noop()),
+            _ => Histogram::noop(),
+        }
+    }
+}
+
+/// Record `metrics` series in the default Prometheus registry
+///
+/// Only the first recorder installed in a process takes effect; returns
+/// `false` if one was already installed.
+pub fn install() -> bool {
+    metrics::set_global_recorder(PrometheusRecorder::new(
+        prometheus::default_registry().clone(),
+    ))
+    .is_ok()
+}
+
+struct CounterHandle(prometheus::IntCounter);
+
+impl CounterFn for CounterHandle {
+    fn increment(&self, value: u64) {
+        self.0.inc_by(value);
+    }
+
+    fn absolute(&self, value: u64) {
+        let current = self.0.get();
+        if value > current {
+            self.0.inc_by(value - current);
+        }
+    }
+}
+
+struct GaugeHandle(prometheus::Gauge);
+
+impl GaugeFn for GaugeHandle {
+    fn increment(&self, value: f64) {
+        self.0.add(value);
+    }
+
+    fn decrement(&self, value: f64) {
+        self.0.sub(value);
+    }
+
+    fn set(&self, value: f64) {
+        self.0.set(value);
+    }
+}
+
+struct HistogramHandle(prometheus::Histogram);
+
+impl HistogramFn for HistogramHandle {
+    fn record(&self, value: f64) {
+        self.0.observe(value);
+    }
+}
+
+fn label_values(key: &Key) -> Vec<&str> {
+    key.labels().map(|label| label.value()).collect()
+}
+
+/// Replace characters Prometheus doesn't allow in names with `_`
+fn sanitize(name: &str) -> String {
+    let mut sanitized: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == ':' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if sanitized.starts_with(|c: char| c.is_ascii_digit()) {
+        sanitized.insert(0, '_');
+    }
+    sanitized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prometheus::proto::MetricType;
+
+    fn family<'a>(
+        families: &'a [prometheus::proto::MetricFamily],
+        name: &str,
+    ) -> &'a prometheus::proto::MetricFamily {
+        families.iter().find(|f| f.get_name() == name).unwrap()
+    }
+
+    #[test]
+    fn test_records_into_registry() {
+        let registry = Registry::new();
+        let recorder = PrometheusRecorder::new(registry.clone());
+        metrics::with_local_recorder(&recorder, || {
+            metrics::describe_counter!("schema_registry.cache.l1.hits", "L1 cache hits");
+            metrics::counter!("schema_registry.cache.l1.hits", "tier" => "l1").increment(2);
+            metrics::counter!("schema_registry.cache.l1.hits", "tier" => "l1").increment(1);
+            metrics::gauge!("schema_registry_cache_hit_rate", "tier" => "l2").set(0.5);
+            metrics::histogram!("schema_registry_fill_seconds").record(0.2);
+        });
+
+        let families = registry.gather();
+        let hits = family(&families, "schema_registry_cache_l1_hits");
+        assert_eq!(hits.get_field_type(), MetricType::COUNTER);
+        assert_eq!(hits.get_help(), "L1 cache hits");
+        assert_eq!(hits.get_metric()[0].get_label()[0].get_value(), "l1");
+        assert_eq!(hits.get_metric()[0].get_counter().get_value(), 3.0);
+        let hit_rate = family(&families, "schema_registry_cache_hit_rate");
+        assert_eq!(hit_rate.get_metric()[0].get_gauge().get_value(), 0.5);
+        let fill = family(&families, "schema_registry_fill_seconds");
+        assert_eq!(fill.get_metric()[0].get_histogram().get_sample_count(), 1);
+    }
+
+    #[test]
+    fn test_conflicting_names_are_not_exported() {
+        let registry = Registry::new();
+        let taken =
+            IntCounterVec::new(Opts::new("taken_total", "Registered elsewhere"), &["a"]).unwrap();
+        registry.register(Box::new(taken.clone())).unwrap();
+        let recorder = PrometheusRecorder::new(registry.clone());
+        metrics::with_local_recorder(&recorder, || {
+            metrics::counter!("taken_total", "a" => "x").increment(1);
+            metrics::counter!("labelled_total", "a" => "x").increment(1);
+            // Same name with other labels
+            metrics::counter!("labelled_total", "b" => "y").increment(1);
+            // Same name as another kind
+            metrics::gauge!("labelled_total", "a" => "x").set(5.0);
+        });
+
+        assert_eq!(taken.with_label_values(&["x"]).get(), 0);
+        let families = registry.gather();
+        let labelled = family(&families, "labelled_total");
+        assert_eq!(labelled.get_metric().len(), 1);
+        assert_eq!(labelled.get_metric()[0].get_counter().get_value(), 1.0);
+    }
+
+    #[test]
+    fn test_sanitize() {
+        assert_eq!(
+            sanitize("schema_registry.s3.uploads"),
+            "schema_registry_s3_uploads"
+        );
+        assert_eq!(sanitize("9lives"), "_9lives");
+        assert_eq!(sanitize("a:b-c"), "a:b_c");
+    }
+}
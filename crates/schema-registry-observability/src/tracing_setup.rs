@@ -1,22 +1,33 @@
 //! Distributed tracing setup with OpenTelemetry and Jaeger
 //!
 //! This module configures:
-//! - OpenTelemetry trace exporter (OTLP to Jaeger)
+//! - OpenTelemetry trace exporters (OTLP to Jaeger, stdout for debugging)
+//! - Resource attributes detected from the host and Kubernetes downward API
 //! - Tracing subscriber with JSON formatting
 //! - Correlation IDs for request tracking
-//! - 10% head-based sampling strategy
+//! - Head-based sampling (10% by default), per exporter and adjustable at runtime
 //! - Trace context propagation
 
 use opentelemetry::{
     global,
-    trace::TraceError,
-    KeyValue,
+    trace::{
+        Link, SamplingResult, SpanKind, TraceError, TraceId, TraceResult, TracerProvider as _,
+    },
+    Context, KeyValue,
 };
+use opentelemetry_otlp::WithExportConfig;
 use opentelemetry_sdk::{
-    trace::{RandomIdGenerator, Sampler},
+    export::trace::SpanData,
+    trace::{
+        BatchSpanProcessor, RandomIdGenerator, Sampler, ShouldSample, Span, SpanProcessor,
+        TracerProvider,
+    },
     Resource,
 };
-use opentelemetry_otlp::WithExportConfig;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use tracing::info;
 use tracing_subscriber::{
     fmt::{self, format::FmtSpan},
@@ -35,8 +46,10 @@ pub struct TracingConfig {
     pub environment: String,
     /// OTLP endpoint (e.g., http://jaeger:4317)
     pub otlp_endpoint: String,
-    /// Sampling rate (0.0 to 1.0, default 0.1 = 10%)
+    /// Sampling rate (0.0 to 1.0, default 0.1 = 10%) of exporters that don't set their own
     pub sampling_rate: f64,
+    /// Where spans are exported (`TRACE_EXPORTERS`, default OTLP only)
+    pub exporters: Vec<ExporterConfig>,
     /// Enable JSON logging
     pub json_logs: bool,
     /// Log level
@@ -45,16 +58,24 @@ pub struct TracingConfig {
 
 impl Default for TracingConfig {
     fn default() -> Self {
+        let otlp_endpoint =
+            std::env::var("OTLP_ENDPOINT").unwrap_or_else(|_| "http://jaeger:4317".to_string());
+        let sampling_rate = std::env::var("TRACE_SAMPLING_RATE")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(0.1);
         Self {
             service_name: "schema-registry".to_string(),
             service_version: env!("CARGO_PKG_VERSION").to_string(),
             environment: std::env::var("ENVIRONMENT").unwrap_or_else(|_| "dev".to_string()),
-            otlp_endpoint: std::env::var("OTLP_ENDPOINT")
-                .unwrap_or_else(|_| "http://jaeger:4317".to_string()),
-            sampling_rate: std::env::var("TRACE_SAMPLING_RATE")
+            exporters: std::env::var("TRACE_EXPORTERS")
                 .ok()
-                .and_then(|s| s.parse().ok())
-                .unwrap_or(0.1),
+                .and_then(|spec| {
+                    ExporterConfig::parse_list(&spec, &otlp_endpoint, sampling_rate).ok()
+                })
+                .unwrap_or_else(|| vec![ExporterConfig::otlp(&otlp_endpoint, sampling_rate)]),
+            otlp_endpoint,
+            sampling_rate,
             json_logs: std::env::var("JSON_LOGS")
                 .ok()
                 .and_then(|s| s.parse().ok())
@@ -64,10 +85,361 @@ impl Default for TracingConfig {
     }
 }
 
+/// Where an exporter sends spans
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExporterKind {
+    /// OTLP over gRPC to a collector
+    Otlp { endpoint: String },
+    /// One line per span on stdout, for debugging
+    Stdout,
+}
+
+/// A span exporter and the share of traces it receives
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExporterConfig {
+    pub kind: ExporterKind,
+    /// Share of traces exported (0.0 to 1.0)
+    pub sampling_rate: f64,
+}
+
+impl ExporterConfig {
+    pub fn otlp(endpoint: &str, sampling_rate: f64) -> Self {
+        Self {
+            kind: ExporterKind::Otlp {
+                endpoint: endpoint.to_string(),
+            },
+            sampling_rate,
+        }
+    }
+
+    pub fn stdout(sampling_rate: f64) -> Self {
+        Self {
+            kind: ExporterKind::Stdout,
+            sampling_rate,
+        }
+    }
+
+    /// Name the exporter's sampling rate is adjusted by
+    pub fn name(&self) -> &'static str {
+        match self.kind {
+            ExporterKind::Otlp { .. } => "otlp",
+            ExporterKind::Stdout => "stdout",
+        }
+    }
+
+    /// Parse a `TRACE_EXPORTERS` list such as `otlp,stdout:1.0`
+    ///
+    /// Each entry is an exporter name with an optional sampling rate;
+    /// entries without one use `default_rate`.
+    pub fn parse_list(
+        spec: &str,
+        otlp_endpoint: &str,
+        default_rate: f64,
+    ) -> Result<Vec<Self>, TraceError> {
+        let mut exporters: Vec<Self> = Vec::new();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (name, rate) = match entry.split_once(':') {
+                Some((name, rate)) => {
+                    let rate = rate
+                        .trim()
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|r| (0.0..=1.0).contains(r))
+                        .ok_or_else(|| {
+                            TraceError::from(format!("Invalid sampling rate in '{}'", entry))
+                        })?;
+                    (name.trim(), rate)
+                }
+                None => (entry, default_rate),
+            };
+            let exporter = match name {
+                "otlp" => Self::otlp(otlp_endpoint, rate),
+                "stdout" => Self::stdout(rate),
+                other => {
+                    return Err(TraceError::from(format!(
+                        "Unknown trace exporter '{}': expected otlp or stdout",
+                        other
+                    )))
+                }
+            };
+            if exporters.iter().any(|e| e.name() == exporter.name()) {
+                return Err(TraceError::from(format!(
+                    "Trace exporter '{}' is listed twice",
+                    name
+                )));
+            }
+            exporters.push(exporter);
+        }
+        if exporters.is_empty() {
+            return Err(TraceError::from(
+                "TRACE_EXPORTERS names no exporters".to_string(),
+            ));
+        }
+        Ok(exporters)
+    }
+}
+
+/// Sampling rates of the configured exporters, adjustable while running
+///
+/// Spans are recorded at the highest rate of any exporter; each exporter then
+/// keeps the traces that fall within its own rate. Decisions are made from
+/// the trace ID the same way as `Sampler::TraceIdRatioBased`, so an exporter
+/// with a lower rate receives a subset of whole traces, never partial ones.
+#[derive(Debug, Clone, Default)]
+pub struct SamplingControl {
+    rates: Arc<BTreeMap<String, Arc<AtomicU64>>>,
+}
+
+impl SamplingControl {
+    /// Control starting at the exporters' configured rates
+    pub fn new(exporters: &[ExporterConfig]) -> Self {
+        let rates = exporters
+            .iter()
+            .map(|e| {
+                let rate = e.sampling_rate.clamp(0.0, 1.0);
+                (
+                    e.name().to_string(),
+                    Arc::new(AtomicU64::new(rate.to_bits())),
+                )
+            })
+            .collect();
+        Self {
+            rates: Arc::new(rates),
+        }
+    }
+
+    /// Current sampling rate of each exporter
+    pub fn rates(&self) -> BTreeMap<String, f64> {
+        self.rates
+            .iter()
+            .map(|(name, rate)| (name.clone(), f64::from_bits(rate.load(Ordering::Relaxed))))
+            .collect()
+    }
+
+    /// Change an exporter's sampling rate; new traces use it immediately
+    pub fn set(&self, exporter: &str, rate: f64) -> Result<(), TraceError> {
+        self.update(&BTreeMap::from([(exporter.to_string(), rate)]))
+    }
+
+    /// Change several exporters' sampling rates; none change if any is invalid
+    pub fn update(&self, rates: &BTreeMap<String, f64>) -> Result<(), TraceError> {
+        let mut changes = Vec::with_capacity(rates.len());
+        for (exporter, &rate) in rates {
+            if !(0.0..=1.0).contains(&rate) {
+                return Err(TraceError::from(format!(
+                    "Sampling rate {} of '{}' is not between 0.0 and 1.0",
+                    rate, exporter
+                )));
+            }
+            let slot = self.rates.get(exporter).ok_or_else(|| {
+                TraceError::from(format!(
+                    "Unknown trace exporter '{}': configured exporters are {}",
+                    exporter,
+                    self.rates.keys().cloned().collect::<Vec<_>>().join(", ")
+                ))
+            })?;
+            changes.push((slot, rate));
+        }
+        for (slot, rate) in changes {
+            slot.store(rate.to_bits(), Ordering::Relaxed);
+        }
+        Ok(())
+    }
+
+    fn slot(&self, exporter: &str) -> Arc<AtomicU64> {
+        self.rates[exporter].clone()
+    }
+
+    fn max_rate(&self) -> f64 {
+        self.rates
+            .values()
+            .map(|rate| f64::from_bits(rate.load(Ordering::Relaxed)))
+            .fold(0.0, f64::max)
+    }
+}
+
+/// Whether a trace falls within `rate`, decided as `Sampler::TraceIdRatioBased` does
+fn trace_sampled(trace_id: TraceId, rate: f64) -> bool {
+    if rate >= 1.0 {
+        return true;
+    }
+    let upper_bound = (rate.max(0.0) * (1u64 << 63) as f64) as u64;
+    let bytes = trace_id.to_bytes();
+    let low = u64::from_be_bytes(bytes[8..].try_into().expect("trace IDs are 16 bytes"));
+    (low >> 1) < upper_bound
+}
+
+/// Parent-based ratio sampler following the highest exporter rate
+#[derive(Debug, Clone)]
+struct AdjustableSampler {
+    control: SamplingControl,
+}
+
+impl ShouldSample for AdjustableSampler {
+    fn should_sample(
+        &self,
+        parent_context: Option<&Context>,
+        trace_id: TraceId,
+        name: &str,
+        span_kind: &SpanKind,
+        attributes: &[KeyValue],
+        links: &[Link],
+    ) -> SamplingResult {
+        Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(
+            self.control.max_rate(),
+        )))
+        .should_sample(parent_context, trace_id, name, span_kind, attributes, links)
+    }
+}
+
+/// Passes an exporter only the traces within its sampling rate
+#[derive(Debug)]
+struct SampledProcessor<P> {
+    rate: Arc<AtomicU64>,
+    inner: P,
+}
+
+impl<P: SpanProcessor> SpanProcessor for SampledProcessor<P> {
+    fn on_start(&self, span: &mut Span, cx: &Context) {
+        self.inner.on_start(span, cx);
+    }
+
+    fn on_end(&self, span: SpanData) {
+        let rate = f64::from_bits(self.rate.load(Ordering::Relaxed));
+        if trace_sampled(span.span_context.trace_id(), rate) {
+            self.inner.on_end(span);
+        }
+    }
+
+    fn force_flush(&self) -> TraceResult<()> {
+        self.inner.force_flush()
+    }
+
+    fn shutdown(&mut self) -> TraceResult<()> {
+        self.inner.shutdown()
+    }
+}
+
+/// Writes each finished span to stdout as one line
+#[derive(Debug)]
+struct StdoutSpanProcessor;
+
+impl SpanProcessor for StdoutSpanProcessor {
+    fn on_start(&self, _span: &mut Span, _cx: &Context) {}
+
+    fn on_end(&self, span: SpanData) {
+        let duration = span
+            .end_time
+            .duration_since(span.start_time)
+            .unwrap_or_default();
+        let _ = writeln!(
+            std::io::stdout().lock(),
+            "span {} trace_id={} span_id={} parent_span_id={} duration_ms={:.3} status={:?} attributes={:?}",
+            span.name,
+            span.span_context.trace_id(),
+            span.span_context.span_id(),
+            span.parent_span_id,
+            duration.as_secs_f64() * 1000.0,
+            span.status,
+            span.attributes,
+        );
+    }
+
+    fn force_flush(&self) -> TraceResult<()> {
+        std::io::stdout()
+            .flush()
+            .map_err(|e| TraceError::from(e.to_string()))
+    }
+
+    fn shutdown(&mut self) -> TraceResult<()> {
+        self.force_flush()
+    }
+}
+
+/// Resource attributes describing this process and where it runs
+///
+/// Kubernetes attributes come from the downward API variables `POD_NAME`,
+/// `POD_NAMESPACE`, `POD_UID` and `NODE_NAME`; inside a cluster without them
+/// the pod name falls back to `HOSTNAME`. Attributes in
+/// `OTEL_RESOURCE_ATTRIBUTES` (`key=value,...`) override detected ones.
+pub fn detect_resource_attributes(
+    config: &TracingConfig,
+    env: impl Fn(&str) -> Option<String>,
+) -> BTreeMap<String, String> {
+    let mut attributes = BTreeMap::from([
+        ("service.name".to_string(), config.service_name.clone()),
+        (
+            "service.version".to_string(),
+            config.service_version.clone(),
+        ),
+        (
+            "deployment.environment".to_string(),
+            config.environment.clone(),
+        ),
+        (
+            "telemetry.sdk.name".to_string(),
+            "opentelemetry".to_string(),
+        ),
+        ("telemetry.sdk.language".to_string(), "rust".to_string()),
+        ("process.pid".to_string(), std::process::id().to_string()),
+    ]);
+    let env = |name: &str| env(name).filter(|v| !v.is_empty());
+
+    let hostname = env("HOSTNAME");
+    if let Some(hostname) = &hostname {
+        attributes.insert("host.name".to_string(), hostname.clone());
+    }
+
+    let in_cluster = env("KUBERNETES_SERVICE_HOST").is_some() || env("POD_NAME").is_some();
+    if in_cluster {
+        for (attribute, variable) in [
+            ("k8s.namespace.name", "POD_NAMESPACE"),
+            ("k8s.pod.uid", "POD_UID"),
+            ("k8s.node.name", "NODE_NAME"),
+        ] {
+            if let Some(value) = env(variable) {
+                attributes.insert(attribute.to_string(), value);
+            }
+        }
+        if let Some(pod) = env("POD_NAME").or(hostname) {
+            attributes.insert("service.instance.id".to_string(), pod.clone());
+            attributes.insert("k8s.pod.name".to_string(), pod);
+        }
+    }
+
+    for pair in env("OTEL_RESOURCE_ATTRIBUTES")
+        .unwrap_or_default()
+        .split(',')
+    {
+        if let Some((key, value)) = pair.split_once('=') {
+            if !key.trim().is_empty() {
+                attributes.insert(key.trim().to_string(), value.trim().to_string());
+            }
+        }
+    }
+    attributes
+}
+
+/// Service account namespace file mounted into every pod
+const K8S_NAMESPACE_FILE: &str = "/var/run/secrets/kubernetes.io/serviceaccount/namespace";
+
+/// Process environment, with the namespace read from the service account
+/// when the downward API doesn't provide it
+fn process_env(name: &str) -> Option<String> {
+    std::env::var(name).ok().or_else(|| {
+        (name == "POD_NAMESPACE")
+            .then(|| std::fs::read_to_string(K8S_NAMESPACE_FILE).ok())
+            .flatten()
+            .map(|namespace| namespace.trim().to_string())
+    })
+}
+
 /// Initializes comprehensive tracing and logging
-pub fn init_tracing(config: TracingConfig) -> Result<(), TraceError> {
+///
+/// Returns the control for adjusting the exporters' sampling rates.
+pub fn init_tracing(config: TracingConfig) -> Result<SamplingControl, TraceError> {
     // Initialize OpenTelemetry tracer
-    let tracer = init_tracer(&config)?;
+    let (tracer, sampling) = init_tracer(&config)?;
 
     // Create tracing layers
     let telemetry_layer = tracing_opentelemetry::layer().with_tracer(tracer);
@@ -113,41 +485,65 @@ pub fn init_tracing(config: TracingConfig) -> Result<(), TraceError> {
         service = %config.service_name,
         version = %config.service_version,
         environment = %config.environment,
-        otlp_endpoint = %config.otlp_endpoint,
-        sampling_rate = %config.sampling_rate,
+        exporters = ?sampling.rates(),
         "Tracing initialized successfully"
     );
 
-    Ok(())
+    Ok(sampling)
 }
 
-/// Initializes OpenTelemetry tracer with OTLP exporter
-fn init_tracer(config: &TracingConfig) -> Result<opentelemetry_sdk::trace::Tracer, TraceError> {
-    let exporter = opentelemetry_otlp::new_exporter()
-        .tonic()
-        .with_endpoint(&config.otlp_endpoint);
+/// Initializes the OpenTelemetry tracer with every configured exporter
+fn init_tracer(
+    config: &TracingConfig,
+) -> Result<(opentelemetry_sdk::trace::Tracer, SamplingControl), TraceError> {
+    if config.exporters.is_empty() {
+        return Err(TraceError::from(
+            "No trace exporters configured".to_string(),
+        ));
+    }
+    let sampling = SamplingControl::new(&config.exporters);
 
+    let resource = detect_resource_attributes(config, process_env)
+        .into_iter()
+        .map(|(key, value)| KeyValue::new(key, value));
     let trace_config = opentelemetry_sdk::trace::config()
-        .with_sampler(Sampler::ParentBased(Box::new(
-            Sampler::TraceIdRatioBased(config.sampling_rate),
-        )))
+        .with_sampler(AdjustableSampler {
+            control: sampling.clone(),
+        })
         .with_id_generator(RandomIdGenerator::default())
         .with_max_events_per_span(128)
         .with_max_attributes_per_span(64)
         .with_max_links_per_span(64)
-        .with_resource(Resource::new(vec![
-            KeyValue::new("service.name", config.service_name.clone()),
-            KeyValue::new("service.version", config.service_version.clone()),
-            KeyValue::new("deployment.environment", config.environment.clone()),
-            KeyValue::new("telemetry.sdk.name", "opentelemetry"),
-            KeyValue::new("telemetry.sdk.language", "rust"),
-        ]));
-
-    opentelemetry_otlp::new_pipeline()
-        .tracing()
-        .with_exporter(exporter)
-        .with_trace_config(trace_config)
-        .install_batch(opentelemetry_sdk::runtime::Tokio)
+        .with_resource(Resource::new(resource));
+
+    let mut provider = TracerProvider::builder().with_config(trace_config);
+    for exporter in &config.exporters {
+        let rate = sampling.slot(exporter.name());
+        provider = match &exporter.kind {
+            ExporterKind::Otlp { endpoint } => {
+                let otlp = opentelemetry_otlp::SpanExporterBuilder::from(
+                    opentelemetry_otlp::new_exporter()
+                        .tonic()
+                        .with_endpoint(endpoint),
+                )
+                .build_span_exporter()?;
+                provider.with_span_processor(SampledProcessor {
+                    rate,
+                    inner: BatchSpanProcessor::builder(otlp, opentelemetry_sdk::runtime::Tokio)
+                        .build(),
+                })
+            }
+            ExporterKind::Stdout => provider.with_span_processor(SampledProcessor {
+                rate,
+                inner: StdoutSpanProcessor,
+            }),
+        };
+    }
+    let provider = provider.build();
+
+    let tracer = provider.tracer(config.service_name.clone());
+    global::set_tracer_provider(provider);
+    Ok((tracer, sampling))
 }
 
 /// Shuts down tracing gracefully, flushing remaining spans
@@ -158,7 +554,7 @@ pub fn shutdown_tracing() {
 
 /// Helper function to setup basic tracing (for convenience)
 pub fn setup_tracing() -> Result<(), TraceError> {
-    init_tracing(TracingConfig::default())
+    init_tracing(TracingConfig::default()).map(|_| ())
 }
 
 /// Middleware helper for extracting/injecting trace context
@@ -289,6 +685,101 @@ mod tests {
         assert!(config.sampling_rate >= 0.0 && config.sampling_rate <= 1.0);
     }
 
+    #[test]
+    fn test_parse_exporter_list() {
+        let exporters =
+            ExporterConfig::parse_list("otlp, stdout:1.0", "http://collector:4317", 0.1).unwrap();
+        assert_eq!(
+            exporters,
+            vec![
+                ExporterConfig::otlp("http://collector:4317", 0.1),
+                ExporterConfig::stdout(1.0),
+            ]
+        );
+
+        assert!(ExporterConfig::parse_list("zipkin", "", 0.1).is_err());
+        assert!(ExporterConfig::parse_list("otlp:1.5", "", 0.1).is_err());
+        assert!(ExporterConfig::parse_list("otlp,otlp:0.5", "", 0.1).is_err());
+        assert!(ExporterConfig::parse_list(" , ", "", 0.1).is_err());
+    }
+
+    #[test]
+    fn test_sampling_rates_adjust_at_runtime() {
+        let control = SamplingControl::new(&[
+            ExporterConfig::otlp("http://collector:4317", 0.1),
+            ExporterConfig::stdout(0.0),
+        ]);
+        assert_eq!(control.max_rate(), 0.1);
+
+        control.set("stdout", 0.5).unwrap();
+        assert_eq!(control.rates()["stdout"], 0.5);
+        assert_eq!(control.max_rate(), 0.5);
+
+        assert!(control.set("stdout", 1.5).is_err());
+        assert!(control.set("zipkin", 0.5).is_err());
+
+        // A rejected update leaves every rate as it was
+        let update = BTreeMap::from([("otlp".to_string(), 1.0), ("zipkin".to_string(), 0.5)]);
+        assert!(control.update(&update).is_err());
+        assert_eq!(control.rates()["otlp"], 0.1);
+    }
+
+    #[test]
+    fn test_lower_rates_sample_a_subset_of_traces() {
+        let trace_ids: Vec<TraceId> = (0..1000u128)
+            .map(|i| TraceId::from_u128(i.wrapping_mul(0x9E37_79B9_7F4A_7C15_F39C_C060_5CED_C835)))
+            .collect();
+        let sampled = |rate: f64| -> Vec<TraceId> {
+            trace_ids
+                .iter()
+                .copied()
+                .filter(|id| trace_sampled(*id, rate))
+                .collect()
+        };
+
+        let low = sampled(0.1);
+        let high = sampled(0.5);
+        assert!(low.iter().all(|id| high.contains(id)));
+        assert!(low.len() < high.len());
+        assert_eq!(sampled(1.0).len(), trace_ids.len());
+        assert!(sampled(0.0).is_empty());
+    }
+
+    #[test]
+    fn test_detects_kubernetes_resource_attributes() {
+        let config = TracingConfig::default();
+        let env: BTreeMap<&str, &str> = BTreeMap::from([
+            ("KUBERNETES_SERVICE_HOST", "10.0.0.1"),
+            ("HOSTNAME", "schema-registry-7d9f-abcde"),
+            ("POD_NAMESPACE", "registry"),
+            ("NODE_NAME", "node-3"),
+            (
+                "OTEL_RESOURCE_ATTRIBUTES",
+                "team=platform, deployment.environment=prod-eu",
+            ),
+        ]);
+        let attributes =
+            detect_resource_attributes(&config, |name| env.get(name).map(|v| v.to_string()));
+
+        assert_eq!(attributes["k8s.pod.name"], "schema-registry-7d9f-abcde");
+        assert_eq!(attributes["k8s.namespace.name"], "registry");
+        assert_eq!(attributes["k8s.node.name"], "node-3");
+        assert_eq!(
+            attributes["service.instance.id"],
+            "schema-registry-7d9f-abcde"
+        );
+        assert_eq!(attributes["team"], "platform");
+        assert_eq!(attributes["deployment.environment"], "prod-eu");
+        assert!(!attributes.contains_key("k8s.pod.uid"));
+
+        // Outside a cluster there are no Kubernetes attributes
+        let attributes = detect_resource_attributes(&config, |name| {
+            (name == "HOSTNAME").then(|| "laptop".to_string())
+        });
+        assert_eq!(attributes["host.name"], "laptop");
+        assert!(!attributes.keys().any(|key| key.starts_with("k8s.")));
+    }
+
     #[test]
     fn test_correlation_id_generation() {
         let id1 = correlation::generate_correlation_id();
@@ -12,9 +12,27 @@ use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
-use tokio::sync::RwLock;
+use tokio::sync::{Notify, RwLock};
 use uuid::Uuid;
 
+/// Metadata key holding the sanitized payload of the request behind an event
+pub const REQUEST_PAYLOAD_KEY: &str = "request";
+
+/// Replaces secret values in sanitized request payloads
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Key fragments marking a value as a secret
+const SECRET_KEYS: &[&str] = &[
+    "password",
+    "secret",
+    "token",
+    "authorization",
+    "api_key",
+    "apikey",
+    "credential",
+    "private_key",
+];
+
 // =============================================================================
 // Audit Event Types
 // =============================================================================
@@ -38,18 +56,31 @@ pub enum AuditEventType {
     RoleRevoked,
     PermissionGranted,
     PermissionRevoked,
+    GroupMembershipChanged,
+    GroupMappingChanged,
+    ImpersonationStarted,
+    ImpersonatedRequest,
 
     // Schema operations
     SchemaRegistered,
     SchemaUpdated,
     SchemaDeleted,
     SchemaValidated,
+    CompatibilityChecked,
     SchemaPublished,
     SchemaDeprecated,
+    SchemaQuarantined,
+    QuarantineCleared,
+    QuarantineRejected,
+    RegistrationReplayed,
 
     // Configuration changes
     ConfigurationChanged,
     CompatibilityModeChanged,
+    CompatibilityExemptionGranted,
+    CompatibilityExemptionRevoked,
+    CompatibilityExemptionApplied,
+    ChangeFreezeOverridden,
     RetentionPolicyChanged,
 
     // Security events
@@ -63,6 +94,8 @@ pub enum AuditEventType {
     SystemStopped,
     BackupCreated,
     BackupRestored,
+    DegradedModeEntered,
+    DegradedModeExited,
 }
 
 impl AuditEventType {
@@ -72,12 +105,22 @@ impl AuditEventType {
             | Self::AuthorizationDenied
             | Self::SecurityViolation
             | Self::SuspiciousActivity
-            | Self::AccessDenied => AuditSeverity::Warning,
+            | Self::AccessDenied
+            | Self::DegradedModeEntered => AuditSeverity::Warning,
 
             Self::TokenRevoked
             | Self::RoleRevoked
             | Self::PermissionRevoked
-            | Self::SchemaDeleted => AuditSeverity::Important,
+            | Self::GroupMembershipChanged
+            | Self::GroupMappingChanged
+            | Self::ImpersonationStarted
+            | Self::SchemaDeleted
+            | Self::SchemaQuarantined
+            | Self::QuarantineCleared
+            | Self::QuarantineRejected
+            | Self::CompatibilityExemptionGranted
+            | Self::CompatibilityExemptionApplied
+            | Self::ChangeFreezeOverridden => AuditSeverity::Important,
 
             _ => AuditSeverity::Info,
         }
@@ -116,6 +159,10 @@ pub struct AuditEvent {
     /// User email
     pub user_email: Option<String>,
 
+    /// Admin acting as `user_id` through an impersonation token
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub impersonator: Option<String>,
+
     /// Source IP address
     pub source_ip: Option<String>,
 
@@ -173,6 +220,7 @@ impl AuditEvent {
             timestamp,
             user_id: None,
             user_email: None,
+            impersonator: None,
             source_ip: None,
             user_agent: None,
             correlation_id: None,
@@ -208,6 +256,9 @@ impl AuditEvent {
         if let Some(resource_id) = &self.resource_id {
             hasher.update(resource_id.as_bytes());
         }
+        if let Some(impersonator) = &self.impersonator {
+            hasher.update(impersonator.as_bytes());
+        }
 
         let result = hasher.finalize();
         hex::encode(result)
@@ -232,6 +283,12 @@ impl AuditEvent {
         self
     }
 
+    /// Record the admin impersonating the event's user
+    pub fn with_impersonator(mut self, impersonator: String) -> Self {
+        self.impersonator = Some(impersonator);
+        self
+    }
+
     /// Set request context
     pub fn with_request_context(
         mut self,
@@ -251,6 +308,50 @@ impl AuditEvent {
         self.resource_id = Some(resource_id);
         self
     }
+
+    /// Record the request behind the event, with its secrets redacted
+    pub fn with_request_payload(self, payload: serde_json::Value) -> Self {
+        self.with_metadata(REQUEST_PAYLOAD_KEY.to_string(), sanitize_payload(payload))
+    }
+
+    /// The sanitized request recorded with the event, if any
+    pub fn request_payload(&self) -> Option<&serde_json::Value> {
+        self.metadata.get(REQUEST_PAYLOAD_KEY)
+    }
+}
+
+/// Redact the secrets in a request payload
+///
+/// A string or number under a key naming a secret, such as `api_token` or
+/// `password`, is replaced with [`REDACTED`]. Objects and arrays under such
+/// keys are walked rather than dropped, so a schema declaring a `token`
+/// property keeps its definition.
+pub fn sanitize_payload(payload: serde_json::Value) -> serde_json::Value {
+    use serde_json::Value;
+
+    match payload {
+        Value::Object(object) => Value::Object(
+            object
+                .into_iter()
+                .map(|(key, value)| {
+                    let value = match value {
+                        Value::String(_) | Value::Number(_) if is_secret_key(&key) => {
+                            Value::String(REDACTED.to_string())
+                        }
+                        value => sanitize_payload(value),
+                    };
+                    (key, value)
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.into_iter().map(sanitize_payload).collect()),
+        value => value,
+    }
+}
+
+fn is_secret_key(key: &str) -> bool {
+    let key = key.to_ascii_lowercase().replace('-', "_");
+    SECRET_KEYS.iter().any(|secret| key.contains(secret))
 }
 
 // =============================================================================
@@ -260,6 +361,7 @@ impl AuditEvent {
 pub struct AuditLogger {
     events: Arc<RwLock<Vec<AuditEvent>>>,
     last_hash: Arc<RwLock<String>>,
+    appended: Arc<Notify>,
 }
 
 impl AuditLogger {
@@ -267,6 +369,7 @@ impl AuditLogger {
         Self {
             events: Arc::new(RwLock::new(Vec::new())),
             last_hash: Arc::new(RwLock::new("genesis".to_string())),
+            appended: Arc::new(Notify::new()),
         }
     }
 
@@ -290,6 +393,8 @@ impl AuditLogger {
         // Store event
         let mut events = self.events.write().await;
         events.push(event.clone());
+        drop(events);
+        self.appended.notify_waiters();
 
         // Log to tracing
         match event.severity {
@@ -371,11 +476,35 @@ impl AuditLogger {
             .collect()
     }
 
+    /// The event with ID `id`, if this instance holds it
+    pub async fn find(&self, id: &str) -> Option<AuditEvent> {
+        let events = self.events.read().await;
+        events.iter().find(|e| e.id == id).cloned()
+    }
+
     /// Get event count
     pub async fn count(&self) -> usize {
         let events = self.events.read().await;
         events.len()
     }
+
+    /// Up to `limit` events starting at position `offset` in the log
+    pub async fn events_from(&self, offset: usize, limit: usize) -> Vec<AuditEvent> {
+        let events = self.events.read().await;
+        events.iter().skip(offset).take(limit).cloned().collect()
+    }
+
+    /// Wait until the log holds more than `offset` events
+    pub async fn wait_for_events(&self, offset: usize) {
+        loop {
+            // Register before checking so an append in between is not missed
+            let appended = self.appended.notified();
+            if self.count().await > offset {
+                return;
+            }
+            appended.await;
+        }
+    }
 }
 
 impl Default for AuditLogger {
@@ -574,4 +703,33 @@ mod tests {
 
         assert!(event.verify_hash());
     }
+
+    #[tokio::test]
+    async fn test_request_payloads_are_sanitized_and_found() {
+        let logger = AuditLogger::new();
+        let event = AuditEvent::new(
+            AuditEventType::SchemaRegistered,
+            "Schema registered".to_string(),
+            AuditResult::Success,
+            String::new(),
+        )
+        .with_request_payload(serde_json::json!({
+            "subject": "auth.Session",
+            "metadata": {"webhook_secret": "s3cr3t", "API-Token": 42},
+            "schema": {"properties": {"token": {"type": "string"}}},
+        }));
+        let id = event.id.clone();
+        logger.log(event).await;
+
+        let found = logger.find(&id).await.unwrap();
+        assert_eq!(
+            found.request_payload(),
+            Some(&serde_json::json!({
+                "subject": "auth.Session",
+                "metadata": {"webhook_secret": REDACTED, "API-Token": REDACTED},
+                "schema": {"properties": {"token": {"type": "string"}}},
+            }))
+        );
+        assert!(logger.find("missing").await.is_none());
+    }
 }
@@ -73,6 +73,17 @@ pub struct TokenClaims {
     pub jti: String,
     /// Token type (access or refresh)
     pub token_type: TokenType,
+    /// Actor holding the token on behalf of `sub` (RFC 8693 `act` claim)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub act: Option<ActorClaim>,
+    /// Namespaces a scoped token is restricted to; unrestricted when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespaces: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActorClaim {
+    pub sub: String,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -104,6 +115,8 @@ impl TokenClaims {
             aud: "llm-schema-registry-api".to_string(),
             jti: Uuid::new_v4().to_string(),
             token_type: TokenType::Access,
+            act: None,
+            namespaces: None,
         }
     }
 
@@ -124,9 +137,16 @@ impl TokenClaims {
             aud: "llm-schema-registry-api".to_string(),
             jti: Uuid::new_v4().to_string(),
             token_type: TokenType::Refresh,
+            act: None,
+            namespaces: None,
         }
     }
 
+    /// The original identity when this token impersonates `sub`
+    pub fn impersonator(&self) -> Option<&str> {
+        self.act.as_ref().map(|actor| actor.sub.as_str())
+    }
+
     pub fn is_expired(&self) -> bool {
         let now = SystemTime::now()
             .duration_since(UNIX_EPOCH)
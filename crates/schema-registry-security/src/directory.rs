@@ -0,0 +1,601 @@
+//! Directory-backed Authentication Providers
+//!
+//! Registry roles can be derived from directory groups instead of being
+//! assigned per user:
+//! - [`LdapAuthProvider`] binds against LDAP / Active Directory on login and
+//!   reads the user's group memberships
+//! - [`OidcGroupProvider`] reads group memberships from an already verified
+//!   OIDC token's group claim
+//!
+//! Both resolve groups to roles through a shared [`GroupRoleMapper`], whose
+//! mapping table is loaded from a [`GroupMappingSource`] and refreshed
+//! periodically. Changes to the mapping table and to a user's groups are
+//! written to the audit log.
+
+use async_trait::async_trait;
+use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+
+use crate::audit::{AuditEvent, AuditEventType, AuditLogger, AuditResult};
+use crate::auth::{AuthError, Result};
+
+// =============================================================================
+// Provider Interface
+// =============================================================================
+
+/// Credentials presented to an authentication provider
+#[derive(Debug, Clone)]
+pub enum Credentials {
+    /// Username and password, checked by binding against a directory
+    Password { username: String, password: String },
+    /// Claims of an OIDC ID or access token whose signature was already verified
+    OidcClaims(serde_json::Value),
+    /// SPIFFE X.509 SVID from an mTLS handshake
+    X509Svid(crate::spiffe::X509Svid),
+    /// SPIFFE JWT-SVID bearer token
+    JwtSvid(String),
+}
+
+/// A user authenticated by a provider, with roles resolved from their groups
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuthenticatedIdentity {
+    pub user_id: String,
+    pub email: Option<String>,
+    pub groups: Vec<String>,
+    pub roles: Vec<String>,
+    /// Name of the provider that authenticated the user
+    pub provider: String,
+}
+
+/// Pluggable authentication provider
+#[async_trait]
+pub trait AuthProvider: Send + Sync {
+    /// Provider name, recorded on the authenticated identity
+    fn name(&self) -> &str;
+
+    /// Authenticate the credentials and resolve the user's roles
+    async fn authenticate(&self, credentials: &Credentials) -> Result<AuthenticatedIdentity>;
+}
+
+// =============================================================================
+// Group Mapping
+// =============================================================================
+
+/// Roles granted to members of a directory group
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GroupRoleMapping {
+    /// Group name or full DN, matched case-insensitively
+    pub group: String,
+    pub roles: Vec<String>,
+}
+
+/// Source of the group mapping table
+#[async_trait]
+pub trait GroupMappingSource: Send + Sync {
+    async fn load(&self) -> Result<Vec<GroupRoleMapping>>;
+}
+
+/// Mapping table fixed at startup
+pub struct StaticMappingSource {
+    mappings: Vec<GroupRoleMapping>,
+}
+
+impl StaticMappingSource {
+    pub fn new(mappings: Vec<GroupRoleMapping>) -> Self {
+        Self { mappings }
+    }
+}
+
+#[async_trait]
+impl GroupMappingSource for StaticMappingSource {
+    async fn load(&self) -> Result<Vec<GroupRoleMapping>> {
+        Ok(self.mappings.clone())
+    }
+}
+
+/// Mapping table read from a JSON file, e.g. a mounted ConfigMap
+///
+/// The file contains an array of `{"group": ..., "roles": [...]}` objects.
+pub struct JsonFileMappingSource {
+    path: PathBuf,
+}
+
+impl JsonFileMappingSource {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+#[async_trait]
+impl GroupMappingSource for JsonFileMappingSource {
+    async fn load(&self) -> Result<Vec<GroupRoleMapping>> {
+        let content = tokio::fs::read_to_string(&self.path).await.map_err(|e| {
+            AuthError::InternalError(format!(
+                "Failed to read group mapping file {}: {}",
+                self.path.display(),
+                e
+            ))
+        })?;
+        serde_json::from_str(&content).map_err(|e| {
+            AuthError::InternalError(format!("Invalid group mapping file: {}", e))
+        })
+    }
+}
+
+/// The common name of a group DN (`CN=Schema Admins,OU=Groups,DC=corp` yields
+/// `Schema Admins`), or the input unchanged if it is not a DN
+pub fn group_common_name(group: &str) -> &str {
+    let first = group.split(',').next().unwrap_or(group).trim();
+    match first.split_once('=') {
+        Some((attr, value)) if attr.trim().eq_ignore_ascii_case("cn") => value.trim(),
+        _ => group,
+    }
+}
+
+type MappingTable = HashMap<String, BTreeSet<String>>;
+
+/// Resolves directory groups to registry roles
+pub struct GroupRoleMapper {
+    /// Lower-cased group name or DN -> roles
+    table: RwLock<MappingTable>,
+    source: Arc<dyn GroupMappingSource>,
+    audit_logger: Option<Arc<AuditLogger>>,
+    /// Groups seen at each user's previous login
+    known_groups: RwLock<HashMap<String, BTreeSet<String>>>,
+}
+
+impl GroupRoleMapper {
+    /// Create a mapper; call [`refresh`](Self::refresh) to load the table
+    pub fn new(source: Arc<dyn GroupMappingSource>) -> Self {
+        Self {
+            table: RwLock::new(HashMap::new()),
+            source,
+            audit_logger: None,
+            known_groups: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Audit mapping table and group membership changes
+    pub fn with_audit_logger(mut self, audit_logger: Arc<AuditLogger>) -> Self {
+        self.audit_logger = Some(audit_logger);
+        self
+    }
+
+    /// Reload the mapping table from its source
+    ///
+    /// Returns whether the table changed. A failed load keeps the current table.
+    pub async fn refresh(&self) -> Result<bool> {
+        let mut table = MappingTable::new();
+        for mapping in self.source.load().await? {
+            table
+                .entry(mapping.group.trim().to_lowercase())
+                .or_default()
+                .extend(mapping.roles);
+        }
+
+        let previous = {
+            let mut current = self.table.write().await;
+            if *current == table {
+                return Ok(false);
+            }
+            std::mem::replace(&mut *current, table.clone())
+        };
+
+        let changed: Vec<&String> = table
+            .keys()
+            .chain(previous.keys())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .filter(|group| table.get(*group) != previous.get(*group))
+            .collect();
+        tracing::info!(groups = changed.len(), "Group role mapping changed");
+
+        if let Some(logger) = &self.audit_logger {
+            let event = AuditEvent::new(
+                AuditEventType::GroupMappingChanged,
+                "Group role mapping changed".to_string(),
+                AuditResult::Success,
+                String::new(),
+            )
+            .with_resource("group_mapping".to_string(), "directory".to_string())
+            .with_metadata("changed_groups".to_string(), serde_json::json!(changed))
+            .with_metadata("mapped_groups".to_string(), serde_json::json!(table.len()));
+            logger.log(event).await;
+        }
+        Ok(true)
+    }
+
+    /// Refresh the mapping table on a fixed interval
+    pub fn spawn_refresh(self: Arc<Self>, interval: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.refresh().await {
+                    tracing::warn!(error = %e, "Failed to refresh group role mapping");
+                }
+            }
+        })
+    }
+
+    /// Roles granted by the given groups, sorted and deduplicated
+    ///
+    /// A group matches a mapping by full DN or by its common name.
+    pub async fn roles_for(&self, groups: &[String]) -> Vec<String> {
+        let table = self.table.read().await;
+        let mut roles = BTreeSet::new();
+        for group in groups {
+            let full = group.trim().to_lowercase();
+            let common_name = group_common_name(group.trim()).to_lowercase();
+            for key in [full, common_name] {
+                if let Some(mapped) = table.get(&key) {
+                    roles.extend(mapped.iter().cloned());
+                }
+            }
+        }
+        roles.into_iter().collect()
+    }
+
+    /// Resolve a user's roles and audit any change in their groups since the
+    /// previous login
+    pub async fn resolve(
+        &self,
+        provider: &str,
+        user_id: String,
+        email: Option<String>,
+        groups: Vec<String>,
+    ) -> AuthenticatedIdentity {
+        let roles = self.roles_for(&groups).await;
+        let current: BTreeSet<String> = groups.iter().cloned().collect();
+
+        let previous = self
+            .known_groups
+            .write()
+            .await
+            .insert(user_id.clone(), current.clone());
+        if let (Some(previous), Some(logger)) = (previous, &self.audit_logger) {
+            if previous != current {
+                let added: Vec<&String> = current.difference(&previous).collect();
+                let removed: Vec<&String> = previous.difference(&current).collect();
+                let event = AuditEvent::new(
+                    AuditEventType::GroupMembershipChanged,
+                    "Directory group membership changed".to_string(),
+                    AuditResult::Success,
+                    String::new(),
+                )
+                .with_user(user_id.clone(), email.clone())
+                .with_metadata("provider".to_string(), serde_json::json!(provider))
+                .with_metadata("added_groups".to_string(), serde_json::json!(added))
+                .with_metadata("removed_groups".to_string(), serde_json::json!(removed))
+                .with_metadata("roles".to_string(), serde_json::json!(roles));
+                logger.log(event).await;
+            }
+        }
+
+        AuthenticatedIdentity {
+            user_id,
+            email,
+            groups,
+            roles,
+            provider: provider.to_string(),
+        }
+    }
+}
+
+// =============================================================================
+// LDAP / Active Directory
+// =============================================================================
+
+/// Entry returned by a successful directory bind
+#[derive(Debug, Clone)]
+pub struct DirectoryUser {
+    pub username: String,
+    pub dn: String,
+    pub email: Option<String>,
+    /// Group DNs from `memberOf` (or an equivalent group search)
+    pub groups: Vec<String>,
+}
+
+/// Connection to an LDAP or Active Directory server
+#[async_trait]
+pub trait DirectoryClient: Send + Sync {
+    /// Bind as the user and look up their entry and groups
+    ///
+    /// Returns [`AuthError::InvalidCredentials`] when the bind is rejected.
+    async fn bind(&self, username: &str, password: &str) -> Result<DirectoryUser>;
+}
+
+/// Authenticates users by binding against a directory
+pub struct LdapAuthProvider {
+    client: Arc<dyn DirectoryClient>,
+    mapper: Arc<GroupRoleMapper>,
+}
+
+impl LdapAuthProvider {
+    pub fn new(client: Arc<dyn DirectoryClient>, mapper: Arc<GroupRoleMapper>) -> Self {
+        Self { client, mapper }
+    }
+}
+
+#[async_trait]
+impl AuthProvider for LdapAuthProvider {
+    fn name(&self) -> &str {
+        "ldap"
+    }
+
+    async fn authenticate(&self, credentials: &Credentials) -> Result<AuthenticatedIdentity> {
+        let Credentials::Password { username, password } = credentials else {
+            return Err(AuthError::MissingAuth);
+        };
+        // An empty password is an unauthenticated bind, which most servers accept
+        if password.is_empty() {
+            return Err(AuthError::InvalidCredentials);
+        }
+
+        let user = self.client.bind(username, password).await?;
+        Ok(self
+            .mapper
+            .resolve(self.name(), user.username, user.email, user.groups)
+            .await)
+    }
+}
+
+// =============================================================================
+// OIDC Group Claims
+// =============================================================================
+
+/// Checks the signature, issuer, audience and expiry of OIDC tokens
+pub struct OidcTokenVerifier {
+    decoding_key: DecodingKey,
+    validation: Validation,
+}
+
+impl OidcTokenVerifier {
+    /// Verify RS256 tokens against the identity provider's public key
+    pub fn rs256(public_key_pem: &[u8], issuer: &str, audience: &str) -> Result<Self> {
+        let decoding_key = DecodingKey::from_rsa_pem(public_key_pem)
+            .map_err(|e| AuthError::InternalError(format!("Invalid OIDC public key: {}", e)))?;
+        Ok(Self::with_key(
+            decoding_key,
+            Algorithm::RS256,
+            issuer,
+            audience,
+        ))
+    }
+
+    /// Verify HS256 tokens signed with a shared secret
+    pub fn hs256(secret: &[u8], issuer: &str, audience: &str) -> Self {
+        Self::with_key(
+            DecodingKey::from_secret(secret),
+            Algorithm::HS256,
+            issuer,
+            audience,
+        )
+    }
+
+    fn with_key(
+        decoding_key: DecodingKey,
+        algorithm: Algorithm,
+        issuer: &str,
+        audience: &str,
+    ) -> Self {
+        let mut validation = Validation::new(algorithm);
+        validation.set_issuer(&[issuer]);
+        validation.set_audience(&[audience]);
+        Self {
+            decoding_key,
+            validation,
+        }
+    }
+
+    /// The claims of a valid token
+    pub fn verify(&self, token: &str) -> Result<serde_json::Value> {
+        decode::<serde_json::Value>(token, &self.decoding_key, &self.validation)
+            .map(|data| data.claims)
+            .map_err(|e| match e.kind() {
+                jsonwebtoken::errors::ErrorKind::ExpiredSignature => AuthError::TokenExpired,
+                _ => AuthError::InvalidToken(e.to_string()),
+            })
+    }
+}
+
+/// Maps the group claim of a verified OIDC token to registry roles
+pub struct OidcGroupProvider {
+    mapper: Arc<GroupRoleMapper>,
+    groups_claim: String,
+}
+
+impl OidcGroupProvider {
+    /// Read groups from the `groups` claim
+    pub fn new(mapper: Arc<GroupRoleMapper>) -> Self {
+        Self {
+            mapper,
+            groups_claim: "groups".to_string(),
+        }
+    }
+
+    /// Read groups from a different claim, e.g. `roles` on Azure AD
+    pub fn with_groups_claim(mut self, claim: impl Into<String>) -> Self {
+        self.groups_claim = claim.into();
+        self
+    }
+}
+
+#[async_trait]
+impl AuthProvider for OidcGroupProvider {
+    fn name(&self) -> &str {
+        "oidc"
+    }
+
+    async fn authenticate(&self, credentials: &Credentials) -> Result<AuthenticatedIdentity> {
+        let Credentials::OidcClaims(claims) = credentials else {
+            return Err(AuthError::MissingAuth);
+        };
+        let user_id = claims
+            .get("sub")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| AuthError::InvalidToken("Missing 'sub' claim".to_string()))?
+            .to_string();
+        let email = claims
+            .get("email")
+            .and_then(|v| v.as_str())
+            .map(str::to_string);
+
+        // Some providers send a single group as a string
+        let groups = match claims.get(&self.groups_claim) {
+            Some(serde_json::Value::Array(values)) => values
+                .iter()
+                .filter_map(|v| v.as_str().map(str::to_string))
+                .collect(),
+            Some(serde_json::Value::String(group)) => vec![group.clone()],
+            _ => Vec::new(),
+        };
+
+        Ok(self.mapper.resolve(self.name(), user_id, email, groups).await)
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::AuditEventFilter;
+
+    struct FakeDirectory;
+
+    #[async_trait]
+    impl DirectoryClient for FakeDirectory {
+        async fn bind(&self, username: &str, password: &str) -> Result<DirectoryUser> {
+            if password != "secret" {
+                return Err(AuthError::InvalidCredentials);
+            }
+            Ok(DirectoryUser {
+                username: username.to_string(),
+                dn: format!("CN={},OU=Users,DC=corp,DC=example", username),
+                email: Some(format!("{}@example.com", username)),
+                groups: vec![
+                    "CN=Schema Admins,OU=Groups,DC=corp,DC=example".to_string(),
+                    "CN=Everyone,OU=Groups,DC=corp,DC=example".to_string(),
+                ],
+            })
+        }
+    }
+
+    fn mapping(group: &str, roles: &[&str]) -> GroupRoleMapping {
+        GroupRoleMapping {
+            group: group.to_string(),
+            roles: roles.iter().map(|r| r.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_group_common_name() {
+        assert_eq!(group_common_name("CN=Schema Admins,OU=Groups,DC=corp"), "Schema Admins");
+        assert_eq!(group_common_name("cn=devs"), "devs");
+        assert_eq!(group_common_name("platform-team"), "platform-team");
+        assert_eq!(group_common_name("OU=Groups,DC=corp"), "OU=Groups,DC=corp");
+    }
+
+    #[tokio::test]
+    async fn test_ldap_bind_maps_groups_to_roles() {
+        let source = Arc::new(StaticMappingSource::new(vec![
+            mapping("schema admins", &["admin", "developer"]),
+            mapping("CN=Everyone,OU=Groups,DC=corp,DC=example", &["viewer"]),
+            mapping("Unrelated", &["auditor"]),
+        ]));
+        let mapper = Arc::new(GroupRoleMapper::new(source));
+        assert!(mapper.refresh().await.unwrap());
+        assert!(!mapper.refresh().await.unwrap());
+
+        let provider = LdapAuthProvider::new(Arc::new(FakeDirectory), mapper);
+        let identity = provider
+            .authenticate(&Credentials::Password {
+                username: "alice".to_string(),
+                password: "secret".to_string(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(identity.roles, vec!["admin", "developer", "viewer"]);
+        assert_eq!(identity.provider, "ldap");
+
+        let rejected = provider
+            .authenticate(&Credentials::Password {
+                username: "alice".to_string(),
+                password: String::new(),
+            })
+            .await;
+        assert!(matches!(rejected, Err(AuthError::InvalidCredentials)));
+    }
+
+    #[tokio::test]
+    async fn test_oidc_group_changes_are_audited() {
+        let logger = Arc::new(AuditLogger::new());
+        let source = Arc::new(StaticMappingSource::new(vec![mapping("devs", &["developer"])]));
+        let mapper = Arc::new(GroupRoleMapper::new(source).with_audit_logger(logger.clone()));
+        mapper.refresh().await.unwrap();
+        let provider = OidcGroupProvider::new(mapper);
+
+        let login = |groups: serde_json::Value| {
+            Credentials::OidcClaims(serde_json::json!({"sub": "bob", "groups": groups}))
+        };
+        let identity = provider.authenticate(&login(serde_json::json!(["devs"]))).await.unwrap();
+        assert_eq!(identity.roles, vec!["developer"]);
+
+        // Same groups again: no membership event
+        provider.authenticate(&login(serde_json::json!(["devs"]))).await.unwrap();
+        let identity = provider.authenticate(&login(serde_json::json!("ops"))).await.unwrap();
+        assert!(identity.roles.is_empty());
+
+        let events = logger.get_events(AuditEventFilter::default()).await;
+        let changes: Vec<_> = events
+            .iter()
+            .filter(|e| e.event_type == AuditEventType::GroupMembershipChanged)
+            .collect();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].metadata["added_groups"], serde_json::json!(["ops"]));
+        assert_eq!(changes[0].metadata["removed_groups"], serde_json::json!(["devs"]));
+        assert!(events
+            .iter()
+            .any(|e| e.event_type == AuditEventType::GroupMappingChanged));
+    }
+
+    #[test]
+    fn test_oidc_tokens_are_checked_before_their_claims_are_used() {
+        use jsonwebtoken::{encode, EncodingKey, Header};
+
+        let verifier =
+            OidcTokenVerifier::hs256(b"idp-secret", "https://idp.example.com", "registry");
+        let token = |issuer: &str, key: &[u8]| {
+            let claims = serde_json::json!({
+                "sub": "bob",
+                "groups": ["devs"],
+                "iss": issuer,
+                "aud": "registry",
+                "exp": chrono::Utc::now().timestamp() + 60,
+            });
+            encode(&Header::default(), &claims, &EncodingKey::from_secret(key)).unwrap()
+        };
+
+        let claims = verifier
+            .verify(&token("https://idp.example.com", b"idp-secret"))
+            .unwrap();
+        assert_eq!(claims["sub"], "bob");
+        assert!(matches!(
+            verifier.verify(&token("https://idp.example.com", b"forged")),
+            Err(AuthError::InvalidToken(_))
+        ));
+        assert!(matches!(
+            verifier.verify(&token("https://other.example.com", b"idp-secret")),
+            Err(AuthError::InvalidToken(_))
+        ));
+    }
+}
@@ -0,0 +1,996 @@
+//! Change Freezes
+//!
+//! Freeze windows block changes to a namespace subtree, in some or all
+//! environments, during critical periods such as a release or a peak trading
+//! week. A window either recurs on a cron schedule for a fixed duration or
+//! runs once between two instants.
+//!
+//! Changes that fall into an active window are rejected unless the principal
+//! holds the `freeze:override` permission and states a reason. Every override
+//! is written to the audit log and forwarded to a notification sink.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, Duration, DurationRound, NaiveDate, Timelike, Utc};
+use schema_registry_core::namespace;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::audit::{AuditEvent, AuditEventType, AuditLogger, AuditResult};
+
+/// Permission required to make changes during a freeze
+pub const FREEZE_OVERRIDE_PERMISSION: &str = "freeze:override";
+
+/// Request header carrying the reason for overriding a freeze
+pub const FREEZE_OVERRIDE_HEADER: &str = "x-freeze-override";
+
+/// Longest a recurring window may last
+pub const MAX_FREEZE_DURATION_MINUTES: u32 = 31 * 24 * 60;
+
+// =============================================================================
+// Schedules
+// =============================================================================
+
+/// Five-field cron expression: minute, hour, day of month, month, day of week
+///
+/// Fields accept `*`, values, ranges, lists and `/` steps; days of week run
+/// from 0 (Sunday) to 7 (Sunday again). As in cron, when both day fields are
+/// restricted a time matches if either does. Times are UTC.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct CronSchedule {
+    expression: String,
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    any_day_of_month: bool,
+    any_day_of_week: bool,
+}
+
+impl CronSchedule {
+    /// Whether the schedule fires in the minute containing `at`
+    pub fn matches(&self, at: DateTime<Utc>) -> bool {
+        let bit = |set: u64, value: u32| set & (1 << value) != 0;
+        bit(self.minutes, at.minute())
+            && bit(self.hours, at.hour())
+            && self.matches_date(at.date_naive())
+    }
+
+    /// Latest minute at or before `at` in which the schedule fires, if it
+    /// isn't before `earliest`
+    ///
+    /// Walks back a day at a time and picks the hour and minute from the
+    /// field bit sets, so looking back a month takes at most 32 steps.
+    pub fn latest_between(
+        &self,
+        earliest: DateTime<Utc>,
+        at: DateTime<Utc>,
+    ) -> Option<DateTime<Utc>> {
+        let mut date = at.date_naive();
+        let (mut hour, mut minute) = (at.hour(), at.minute());
+        while date >= earliest.date_naive() {
+            if self.matches_date(date) {
+                // The current hour only counts if it has a minute left
+                let fire = match highest(self.minutes, minute) {
+                    Some(m) if self.hours & (1 << hour) != 0 => Some((hour, m)),
+                    _ => hour
+                        .checked_sub(1)
+                        .and_then(|h| highest(self.hours, h))
+                        .and_then(|h| Some((h, highest(self.minutes, 59)?))),
+                };
+                if let Some((h, m)) = fire {
+                    let fire = date.and_hms_opt(h, m, 0)?.and_utc();
+                    return (fire >= earliest).then_some(fire);
+                }
+            }
+            date = date.pred_opt()?;
+            (hour, minute) = (23, 59);
+        }
+        None
+    }
+
+    fn matches_date(&self, date: NaiveDate) -> bool {
+        let bit = |set: u64, value: u32| set & (1 << value) != 0;
+        if !bit(self.months, date.month()) {
+            return false;
+        }
+        let day_of_month = bit(self.days_of_month, date.day());
+        let day_of_week = bit(self.days_of_week, date.weekday().num_days_from_sunday());
+        match (self.any_day_of_month, self.any_day_of_week) {
+            (true, true) => true,
+            (true, false) => day_of_week,
+            (false, true) => day_of_month,
+            (false, false) => day_of_month || day_of_week,
+        }
+    }
+}
+
+/// Largest value in a field bit set that is at most `max`
+fn highest(set: u64, max: u32) -> Option<u32> {
+    let set = set & ((2u64 << max) - 1);
+    (set != 0).then(|| 63 - set.leading_zeros())
+}
+
+/// Parse one cron field into a bit set of the values it allows
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64, String> {
+    let mut set = 0u64;
+    for item in field.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .map_err(|_| format!("invalid step '{}'", step))?;
+                if step == 0 {
+                    return Err("step must be positive".to_string());
+                }
+                (range, step)
+            }
+            None => (item, 1),
+        };
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (parse_value(start, min, max)?, parse_value(end, min, max)?)
+        } else {
+            let start = parse_value(range, min, max)?;
+            // `5/15` means every 15 from 5 onwards
+            (start, if item.contains('/') { max } else { start })
+        };
+        if start > end {
+            return Err(format!("range '{}' is reversed", range));
+        }
+        for value in (start..=end).step_by(step as usize) {
+            set |= 1 << value;
+        }
+    }
+    Ok(set)
+}
+
+fn parse_value(value: &str, min: u32, max: u32) -> Result<u32, String> {
+    value
+        .parse()
+        .ok()
+        .filter(|v| (min..=max).contains(v))
+        .ok_or_else(|| format!("'{}' is not between {} and {}", value, min, max))
+}
+
+impl FromStr for CronSchedule {
+    type Err = FreezeError;
+
+    fn from_str(expression: &str) -> Result<Self, Self::Err> {
+        let invalid = |message: String| {
+            FreezeError::InvalidWindow(format!(
+                "Invalid cron expression '{}': {}",
+                expression, message
+            ))
+        };
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minute, hour, day_of_month, month, day_of_week] = fields[..] else {
+            return Err(invalid("expected 5 fields".to_string()));
+        };
+
+        let mut days_of_week = parse_field(day_of_week, 0, 7).map_err(invalid)?;
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week = (days_of_week | 1) & !(1 << 7);
+        }
+        Ok(Self {
+            expression: fields.join(" "),
+            minutes: parse_field(minute, 0, 59).map_err(invalid)?,
+            hours: parse_field(hour, 0, 23).map_err(invalid)?,
+            days_of_month: parse_field(day_of_month, 1, 31).map_err(invalid)?,
+            months: parse_field(month, 1, 12).map_err(invalid)?,
+            days_of_week,
+            any_day_of_month: day_of_month.starts_with('*'),
+            any_day_of_week: day_of_week.starts_with('*'),
+        })
+    }
+}
+
+impl TryFrom<String> for CronSchedule {
+    type Error = FreezeError;
+
+    fn try_from(expression: String) -> Result<Self, Self::Error> {
+        expression.parse()
+    }
+}
+
+impl From<CronSchedule> for String {
+    fn from(schedule: CronSchedule) -> Self {
+        schedule.expression
+    }
+}
+
+impl fmt::Display for CronSchedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.expression)
+    }
+}
+
+/// When a freeze window is in force
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum FreezeSchedule {
+    /// Starts whenever the cron expression fires and lasts `duration_minutes`
+    Recurring {
+        cron: CronSchedule,
+        duration_minutes: u32,
+    },
+    /// In force from `starts_at` until `ends_at`
+    Fixed {
+        starts_at: DateTime<Utc>,
+        ends_at: DateTime<Utc>,
+    },
+}
+
+// =============================================================================
+// Freeze Windows
+// =============================================================================
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FreezeWindow {
+    #[serde(default)]
+    pub name: String,
+    /// Namespaces frozen along with their descendants; empty freezes all of them
+    #[serde(default)]
+    pub namespaces: Vec<String>,
+    /// Environments the window applies in; empty applies in every environment
+    #[serde(default)]
+    pub environments: Vec<String>,
+    #[serde(flatten)]
+    pub schedule: FreezeSchedule,
+    #[serde(default)]
+    pub reason: String,
+}
+
+impl FreezeWindow {
+    /// Check the window and normalize its namespaces
+    pub fn validate(mut self) -> Result<Self, FreezeError> {
+        if self.name.trim().is_empty() {
+            return Err(FreezeError::InvalidWindow(
+                "Freeze window name must not be empty".to_string(),
+            ));
+        }
+        self.namespaces = self
+            .namespaces
+            .iter()
+            .map(|ns| namespace::normalize(ns))
+            .collect::<Result<_, _>>()
+            .map_err(|e| {
+                FreezeError::InvalidWindow(format!("Freeze window '{}': {}", self.name, e))
+            })?;
+
+        match &self.schedule {
+            FreezeSchedule::Recurring {
+                duration_minutes, ..
+            } if *duration_minutes == 0 || *duration_minutes > MAX_FREEZE_DURATION_MINUTES => {
+                Err(FreezeError::InvalidWindow(format!(
+                    "Freeze window '{}' must last between 1 and {} minutes",
+                    self.name, MAX_FREEZE_DURATION_MINUTES
+                )))
+            }
+            FreezeSchedule::Fixed { starts_at, ends_at } if ends_at <= starts_at => {
+                Err(FreezeError::InvalidWindow(format!(
+                    "Freeze window '{}' must end after it starts",
+                    self.name
+                )))
+            }
+            _ => Ok(self),
+        }
+    }
+
+    /// Whether the window covers a namespace in an environment
+    ///
+    /// Windows limited to some environments never apply to a registry that
+    /// doesn't know its environment.
+    pub fn applies_to(&self, namespace: &str, environment: Option<&str>) -> bool {
+        let in_environment = self.environments.is_empty()
+            || environment.is_some_and(|env| self.environments.iter().any(|e| e == env));
+        let in_namespace = self.namespaces.is_empty()
+            || namespace::ancestors(namespace)
+                .any(|ns| self.namespaces.iter().any(|frozen| frozen == ns));
+        in_environment && in_namespace
+    }
+
+    /// End of the occurrence in force at `at`, if any
+    pub fn in_force_until(&self, at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match &self.schedule {
+            FreezeSchedule::Fixed { starts_at, ends_at } => {
+                (*starts_at <= at && at < *ends_at).then_some(*ends_at)
+            }
+            FreezeSchedule::Recurring {
+                cron,
+                duration_minutes,
+            } => {
+                // The latest start within the duration ends last
+                let duration = Duration::minutes(i64::from(*duration_minutes));
+                let minute = at.duration_trunc(Duration::minutes(1)).ok()?;
+                cron.latest_between(minute - duration + Duration::minutes(1), minute)
+                    .map(|start| start + duration)
+                    .filter(|end| *end > at)
+            }
+        }
+    }
+}
+
+/// Freeze windows as committed in a configuration file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChangeFreezeConfig {
+    #[serde(default)]
+    pub windows: Vec<FreezeWindow>,
+}
+
+/// A window in force for a namespace
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ActiveFreeze {
+    pub window: String,
+    pub reason: String,
+    pub until: DateTime<Utc>,
+}
+
+/// A change to check against the freeze windows
+#[derive(Debug, Clone)]
+pub struct FreezeCheck<'a> {
+    pub namespace: &'a str,
+    /// What the change does, e.g. `register_schema`
+    pub operation: &'a str,
+    pub principal: &'a str,
+    pub permissions: &'a [String],
+    /// Why the principal is overriding the freeze; none means no override
+    pub override_reason: Option<&'a str>,
+}
+
+/// Notification sent when a change is made during a freeze
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FreezeOverrideNotice {
+    pub freeze: ActiveFreeze,
+    pub namespace: String,
+    pub operation: String,
+    pub principal: String,
+    pub reason: String,
+    pub overridden_at: DateTime<Utc>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum FreezeError {
+    #[error("{0}")]
+    InvalidWindow(String),
+
+    #[error(
+        "Changes to '{namespace}' are frozen until {} by '{}'{}; send an X-Freeze-Override reason with the freeze:override permission to override",
+        freeze.until.to_rfc3339(),
+        freeze.window,
+        if freeze.reason.is_empty() { String::new() } else { format!(" ({})", freeze.reason) }
+    )]
+    Frozen {
+        namespace: String,
+        freeze: ActiveFreeze,
+    },
+
+    #[error("{principal} lacks the freeze:override permission needed to change '{namespace}' during '{}'", freeze.window)]
+    OverrideDenied {
+        principal: String,
+        namespace: String,
+        freeze: ActiveFreeze,
+    },
+
+    #[error("Freeze window storage error: {0}")]
+    Storage(String),
+}
+
+// =============================================================================
+// Window Stores
+// =============================================================================
+
+/// Durable storage for the windows set through the admin API
+#[async_trait]
+pub trait FreezeWindowStore: Send + Sync {
+    /// Windows ordered by name
+    async fn list(&self) -> Result<Vec<FreezeWindow>, FreezeError>;
+
+    /// Add a window or replace the one with the same name
+    async fn put(&self, window: &FreezeWindow) -> Result<(), FreezeError>;
+
+    /// Returns `None` when no window has the name
+    async fn remove(&self, name: &str) -> Result<Option<FreezeWindow>, FreezeError>;
+}
+
+/// Windows held in memory, lost on restart
+#[derive(Default)]
+pub struct InMemoryFreezeWindowStore {
+    windows: RwLock<BTreeMap<String, FreezeWindow>>,
+}
+
+impl InMemoryFreezeWindowStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl FreezeWindowStore for InMemoryFreezeWindowStore {
+    async fn list(&self) -> Result<Vec<FreezeWindow>, FreezeError> {
+        Ok(self.windows.read().await.values().cloned().collect())
+    }
+
+    async fn put(&self, window: &FreezeWindow) -> Result<(), FreezeError> {
+        self.windows
+            .write()
+            .await
+            .insert(window.name.clone(), window.clone());
+        Ok(())
+    }
+
+    async fn remove(&self, name: &str) -> Result<Option<FreezeWindow>, FreezeError> {
+        Ok(self.windows.write().await.remove(name))
+    }
+}
+
+// =============================================================================
+// Notification Sinks
+// =============================================================================
+
+/// Destination for freeze override notifications
+#[async_trait]
+pub trait FreezeNotificationSink: Send + Sync {
+    async fn notify(&self, notice: FreezeOverrideNotice);
+}
+
+/// Default sink that emits notices through tracing
+pub struct TracingFreezeNotificationSink;
+
+#[async_trait]
+impl FreezeNotificationSink for TracingFreezeNotificationSink {
+    async fn notify(&self, notice: FreezeOverrideNotice) {
+        tracing::warn!(
+            window = %notice.freeze.window,
+            namespace = %notice.namespace,
+            operation = %notice.operation,
+            principal = %notice.principal,
+            reason = %notice.reason,
+            "Change freeze overridden"
+        );
+    }
+}
+
+/// Posts each notice as JSON, e.g. to a chat webhook
+pub struct WebhookFreezeNotificationSink {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl WebhookFreezeNotificationSink {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            client: reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs(10))
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+}
+
+#[async_trait]
+impl FreezeNotificationSink for WebhookFreezeNotificationSink {
+    async fn notify(&self, notice: FreezeOverrideNotice) {
+        // The override is already audited, so a failed delivery is only logged
+        let result = self
+            .client
+            .post(&self.url)
+            .json(&notice)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+        if let Err(e) = result {
+            tracing::warn!(url = %self.url, error = %e, "Failed to deliver freeze override notice");
+        }
+    }
+}
+
+// =============================================================================
+// Change Freeze Manager
+// =============================================================================
+
+pub struct ChangeFreezeManager {
+    /// Windows from the change freeze file, which the admin API can't change
+    configured: RwLock<BTreeMap<String, FreezeWindow>>,
+    /// Windows set through the admin API
+    store: Arc<dyn FreezeWindowStore>,
+    /// Deployment environment of this registry
+    environment: Option<String>,
+    audit_logger: Arc<AuditLogger>,
+    sink: Arc<dyn FreezeNotificationSink>,
+}
+
+impl ChangeFreezeManager {
+    /// Manager keeping admin API windows in an [`InMemoryFreezeWindowStore`]
+    pub fn new(
+        environment: Option<String>,
+        audit_logger: Arc<AuditLogger>,
+        sink: Arc<dyn FreezeNotificationSink>,
+    ) -> Self {
+        Self {
+            configured: RwLock::new(BTreeMap::new()),
+            store: Arc::new(InMemoryFreezeWindowStore::new()),
+            environment,
+            audit_logger,
+            sink,
+        }
+    }
+
+    /// Keep admin API windows in the given store instead
+    pub fn with_store(mut self, store: Arc<dyn FreezeWindowStore>) -> Self {
+        self.store = store;
+        self
+    }
+
+    /// Replace the windows of the configuration file
+    pub async fn load(&self, config: ChangeFreezeConfig) -> Result<(), FreezeError> {
+        let mut windows = BTreeMap::new();
+        for window in config.windows {
+            let window = window.validate()?;
+            if windows.contains_key(&window.name) {
+                return Err(FreezeError::InvalidWindow(format!(
+                    "Duplicate freeze window '{}'",
+                    window.name
+                )));
+            }
+            windows.insert(window.name.clone(), window);
+        }
+        *self.configured.write().await = windows;
+        Ok(())
+    }
+
+    /// Add a window or replace the one with the same name
+    pub async fn put(&self, window: FreezeWindow) -> Result<FreezeWindow, FreezeError> {
+        let window = window.validate()?;
+        self.ensure_not_configured(&window.name).await?;
+        self.store.put(&window).await?;
+        Ok(window)
+    }
+
+    pub async fn remove(&self, name: &str) -> Result<Option<FreezeWindow>, FreezeError> {
+        self.ensure_not_configured(name).await?;
+        self.store.remove(name).await
+    }
+
+    async fn ensure_not_configured(&self, name: &str) -> Result<(), FreezeError> {
+        if self.configured.read().await.contains_key(name) {
+            return Err(FreezeError::InvalidWindow(format!(
+                "Freeze window '{}' is set by the change freeze file",
+                name
+            )));
+        }
+        Ok(())
+    }
+
+    /// Windows of the configuration file and the admin API, ordered by name
+    pub async fn list(&self) -> Result<Vec<FreezeWindow>, FreezeError> {
+        let mut windows = self.store.list().await?;
+        windows.extend(self.configured.read().await.values().cloned());
+        windows.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(windows)
+    }
+
+    /// The window in force for a namespace at `at` that ends last, if any
+    pub async fn active_at(
+        &self,
+        namespace: &str,
+        at: DateTime<Utc>,
+    ) -> Result<Option<ActiveFreeze>, FreezeError> {
+        Ok(self
+            .list()
+            .await?
+            .into_iter()
+            .filter(|w| w.applies_to(namespace, self.environment.as_deref()))
+            .filter_map(|w| {
+                Some(ActiveFreeze {
+                    until: w.in_force_until(at)?,
+                    window: w.name,
+                    reason: w.reason,
+                })
+            })
+            .max_by_key(|freeze| freeze.until))
+    }
+
+    /// Allow or reject a change against the windows in force now
+    ///
+    /// Returns the freeze that was overridden, if any.
+    pub async fn check(
+        &self,
+        change: FreezeCheck<'_>,
+    ) -> Result<Option<ActiveFreeze>, FreezeError> {
+        let Some(freeze) = self.active_at(change.namespace, Utc::now()).await? else {
+            return Ok(None);
+        };
+        let Some(reason) = change
+            .override_reason
+            .map(str::trim)
+            .filter(|r| !r.is_empty())
+        else {
+            return Err(FreezeError::Frozen {
+                namespace: change.namespace.to_string(),
+                freeze,
+            });
+        };
+
+        if !change
+            .permissions
+            .iter()
+            .any(|p| p == FREEZE_OVERRIDE_PERMISSION)
+        {
+            let event = AuditEvent::new(
+                AuditEventType::AccessDenied,
+                "Change freeze override denied".to_string(),
+                AuditResult::Failure,
+                String::new(),
+            )
+            .with_user(change.principal.to_string(), None)
+            .with_resource("namespace".to_string(), change.namespace.to_string())
+            .with_metadata(
+                "freeze_window".to_string(),
+                serde_json::json!(freeze.window),
+            )
+            .with_metadata("operation".to_string(), serde_json::json!(change.operation));
+            self.audit_logger.log(event).await;
+
+            return Err(FreezeError::OverrideDenied {
+                principal: change.principal.to_string(),
+                namespace: change.namespace.to_string(),
+                freeze,
+            });
+        }
+
+        let event = AuditEvent::new(
+            AuditEventType::ChangeFreezeOverridden,
+            format!("Change freeze '{}' overridden", freeze.window),
+            AuditResult::Success,
+            String::new(),
+        )
+        .with_user(change.principal.to_string(), None)
+        .with_resource("namespace".to_string(), change.namespace.to_string())
+        .with_metadata(
+            "freeze_window".to_string(),
+            serde_json::json!(freeze.window),
+        )
+        .with_metadata("operation".to_string(), serde_json::json!(change.operation))
+        .with_metadata("reason".to_string(), serde_json::json!(reason))
+        .with_metadata("frozen_until".to_string(), serde_json::json!(freeze.until));
+        self.audit_logger.log(event).await;
+
+        // Delivery can take a while and the override is already audited, so
+        // the change doesn't wait for it
+        let notice = FreezeOverrideNotice {
+            freeze: freeze.clone(),
+            namespace: change.namespace.to_string(),
+            operation: change.operation.to_string(),
+            principal: change.principal.to_string(),
+            reason: reason.to_string(),
+            overridden_at: Utc::now(),
+        };
+        let sink = self.sink.clone();
+        tokio::spawn(async move { sink.notify(notice).await });
+
+        Ok(Some(freeze))
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::AuditEventFilter;
+    use tokio::sync::mpsc;
+
+    struct ChannelSink(mpsc::UnboundedSender<FreezeOverrideNotice>);
+
+    #[async_trait]
+    impl FreezeNotificationSink for ChannelSink {
+        async fn notify(&self, notice: FreezeOverrideNotice) {
+            self.0.send(notice).unwrap();
+        }
+    }
+
+    fn at(timestamp: &str) -> DateTime<Utc> {
+        timestamp.parse().unwrap()
+    }
+
+    fn window(
+        namespaces: &[&str],
+        environments: &[&str],
+        schedule: FreezeSchedule,
+    ) -> FreezeWindow {
+        FreezeWindow {
+            name: "release".to_string(),
+            namespaces: namespaces.iter().map(|ns| ns.to_string()).collect(),
+            environments: environments.iter().map(|env| env.to_string()).collect(),
+            schedule,
+            reason: "Quarterly release".to_string(),
+        }
+    }
+
+    fn frozen_now() -> FreezeSchedule {
+        FreezeSchedule::Fixed {
+            starts_at: Utc::now() - Duration::hours(1),
+            ends_at: Utc::now() + Duration::hours(1),
+        }
+    }
+
+    #[test]
+    fn test_cron_schedule() {
+        let weekdays: CronSchedule = "*/15 9-17 * * 1-5".parse().unwrap();
+        assert!(weekdays.matches(at("2026-10-16T09:45:00Z"))); // Friday
+        assert!(!weekdays.matches(at("2026-10-16T09:50:00Z")));
+        assert!(!weekdays.matches(at("2026-10-17T09:45:00Z"))); // Saturday
+
+        let sundays: CronSchedule = "0 0 * * 7".parse().unwrap();
+        assert!(sundays.matches(at("2026-10-18T00:00:00Z")));
+        assert_eq!(
+            sundays.latest_between(at("2026-09-18T00:00:00Z"), at("2026-10-17T23:59:00Z")),
+            Some(at("2026-10-11T00:00:00Z"))
+        );
+        assert_eq!(
+            sundays.latest_between(at("2026-10-12T00:00:00Z"), at("2026-10-17T23:59:00Z")),
+            None
+        );
+
+        // Either day field matches when both are restricted
+        let either: CronSchedule = "0 0 1 * 5".parse().unwrap();
+        assert!(either.matches(at("2026-11-01T00:00:00Z")));
+        assert!(either.matches(at("2026-10-16T00:00:00Z")));
+        assert!(!either.matches(at("2026-10-15T00:00:00Z")));
+
+        for invalid in [
+            "* * * *",
+            "60 * * * *",
+            "*/0 * * * *",
+            "5-1 * * * *",
+            "a * * * *",
+        ] {
+            assert!(invalid.parse::<CronSchedule>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_recurring_window_in_force() {
+        // Fridays from 18:00 until Monday 06:00
+        let weekend = window(
+            &[],
+            &[],
+            FreezeSchedule::Recurring {
+                cron: "0 18 * * 5".parse().unwrap(),
+                duration_minutes: 60 * 60,
+            },
+        );
+
+        let monday = at("2026-10-19T06:00:00Z");
+        assert_eq!(
+            weekend.in_force_until(at("2026-10-16T18:00:00Z")),
+            Some(monday)
+        );
+        assert_eq!(
+            weekend.in_force_until(at("2026-10-18T12:34:56Z")),
+            Some(monday)
+        );
+        assert_eq!(weekend.in_force_until(monday), None);
+        assert_eq!(weekend.in_force_until(at("2026-10-16T17:59:59Z")), None);
+    }
+
+    #[test]
+    fn test_window_scope() {
+        let scoped = window(&["com.shop"], &["production"], frozen_now())
+            .validate()
+            .unwrap();
+
+        assert!(scoped.applies_to("com.shop", Some("production")));
+        assert!(scoped.applies_to("com.shop.orders", Some("production")));
+        assert!(!scoped.applies_to("com.shopping", Some("production")));
+        assert!(!scoped.applies_to("com.shop", Some("staging")));
+        assert!(!scoped.applies_to("com.shop", None));
+
+        let everywhere = window(&[], &[], frozen_now());
+        assert!(everywhere.applies_to("anything", None));
+
+        assert!(window(&["com..shop"], &[], frozen_now())
+            .validate()
+            .is_err());
+        let reversed = FreezeSchedule::Fixed {
+            starts_at: Utc::now(),
+            ends_at: Utc::now() - Duration::hours(1),
+        };
+        assert!(window(&[], &[], reversed).validate().is_err());
+    }
+
+    #[test]
+    fn test_config_file_format() {
+        let config: ChangeFreezeConfig = serde_json::from_value(serde_json::json!({
+            "windows": [
+                {
+                    "name": "weekend",
+                    "namespaces": ["com.shop"],
+                    "cron": "0 18 * * 5",
+                    "duration_minutes": 3600
+                },
+                {
+                    "name": "peak",
+                    "environments": ["production"],
+                    "starts_at": "2026-11-27T00:00:00Z",
+                    "ends_at": "2026-12-01T00:00:00Z",
+                    "reason": "Peak trading"
+                }
+            ]
+        }))
+        .unwrap();
+
+        assert!(matches!(
+            config.windows[0].schedule,
+            FreezeSchedule::Recurring { .. }
+        ));
+        assert!(matches!(
+            config.windows[1].schedule,
+            FreezeSchedule::Fixed { .. }
+        ));
+        assert!(serde_json::from_value::<FreezeWindow>(serde_json::json!({
+            "name": "bad",
+            "cron": "every friday",
+            "duration_minutes": 60
+        }))
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_changes_rejected_during_freeze() {
+        let logger = Arc::new(AuditLogger::new());
+        let manager = ChangeFreezeManager::new(
+            Some("production".to_string()),
+            logger,
+            Arc::new(TracingFreezeNotificationSink),
+        );
+        manager
+            .put(window(&["com.shop"], &["production"], frozen_now()))
+            .await
+            .unwrap();
+
+        let change = |namespace| FreezeCheck {
+            namespace,
+            operation: "register_schema",
+            principal: "developer",
+            permissions: &[],
+            override_reason: None,
+        };
+        let result = manager.check(change("com.shop.orders")).await;
+        assert!(matches!(result, Err(FreezeError::Frozen { .. })));
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("Quarterly release"));
+        assert!(manager
+            .check(change("com.billing"))
+            .await
+            .unwrap()
+            .is_none());
+
+        manager.remove("release").await.unwrap();
+        assert!(manager
+            .check(change("com.shop.orders"))
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_override_requires_permission_and_is_audited() {
+        let logger = Arc::new(AuditLogger::new());
+        let (notices, mut received) = mpsc::unbounded_channel();
+        let manager =
+            ChangeFreezeManager::new(None, logger.clone(), Arc::new(ChannelSink(notices)));
+        manager.put(window(&[], &[], frozen_now())).await.unwrap();
+
+        let denied = manager
+            .check(FreezeCheck {
+                namespace: "com.shop",
+                operation: "register_schema",
+                principal: "developer",
+                permissions: &["schema:write".to_string()],
+                override_reason: Some("Hotfix"),
+            })
+            .await;
+        assert!(matches!(denied, Err(FreezeError::OverrideDenied { .. })));
+
+        // A blank reason is no override at all
+        let blank = manager
+            .check(FreezeCheck {
+                namespace: "com.shop",
+                operation: "register_schema",
+                principal: "oncall",
+                permissions: &[FREEZE_OVERRIDE_PERMISSION.to_string()],
+                override_reason: Some("  "),
+            })
+            .await;
+        assert!(matches!(blank, Err(FreezeError::Frozen { .. })));
+
+        let overridden = manager
+            .check(FreezeCheck {
+                namespace: "com.shop",
+                operation: "register_schema",
+                principal: "oncall",
+                permissions: &[FREEZE_OVERRIDE_PERMISSION.to_string()],
+                override_reason: Some("INC-42 hotfix"),
+            })
+            .await
+            .unwrap();
+        assert_eq!(overridden.unwrap().window, "release");
+        // The notice is delivered in the background
+        assert_eq!(received.recv().await.unwrap().reason, "INC-42 hotfix");
+        assert!(received.try_recv().is_err());
+
+        let overrides = logger
+            .get_events(AuditEventFilter {
+                event_types: Some(vec![AuditEventType::ChangeFreezeOverridden]),
+                ..Default::default()
+            })
+            .await;
+        assert_eq!(overrides.len(), 1);
+        assert_eq!(overrides[0].metadata["reason"], "INC-42 hotfix");
+
+        let denials = logger
+            .get_events(AuditEventFilter {
+                event_types: Some(vec![AuditEventType::AccessDenied]),
+                ..Default::default()
+            })
+            .await;
+        assert_eq!(denials.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_file_windows_cant_be_changed_through_the_store() {
+        let manager = ChangeFreezeManager::new(
+            None,
+            Arc::new(AuditLogger::new()),
+            Arc::new(TracingFreezeNotificationSink),
+        );
+        manager
+            .load(ChangeFreezeConfig {
+                windows: vec![window(&["com.shop"], &[], frozen_now())],
+            })
+            .await
+            .unwrap();
+        let mut peak = window(&["com.billing"], &[], frozen_now());
+        peak.name = "peak".to_string();
+        manager.put(peak).await.unwrap();
+
+        let names: Vec<_> = manager
+            .list()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|w| w.name)
+            .collect();
+        assert_eq!(names, ["peak", "release"]);
+        assert!(matches!(
+            manager.remove("release").await,
+            Err(FreezeError::InvalidWindow(_))
+        ));
+        assert!(matches!(
+            manager.put(window(&[], &[], frozen_now())).await,
+            Err(FreezeError::InvalidWindow(_))
+        ));
+
+        // Reloading the file leaves the admin API's windows alone
+        manager.load(ChangeFreezeConfig::default()).await.unwrap();
+        assert!(manager
+            .active_at("com.billing", Utc::now())
+            .await
+            .unwrap()
+            .is_some());
+        assert!(manager.remove("peak").await.unwrap().is_some());
+    }
+}
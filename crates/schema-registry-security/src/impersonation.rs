@@ -0,0 +1,282 @@
+//! Admin impersonation for support scenarios
+//!
+//! An admin can obtain a short-lived access token that acts as another
+//! principal, e.g. to reproduce a customer's permission issue. The admin's own
+//! identity travels in the token's `act` claim, so every audit record written
+//! for the impersonated session names both the principal and the admin.
+//!
+//! Impersonation is disabled unless explicitly enabled in
+//! [`ImpersonationConfig`].
+
+use crate::audit::{AuditEvent, AuditEventType, AuditLogger, AuditResult};
+use crate::auth::{ActorClaim, AuthError, JwtManager, TokenClaims};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+
+/// Response header carrying the impersonating admin's identity
+pub const IMPERSONATED_BY_HEADER: &str = "X-Impersonated-By";
+
+// =============================================================================
+// Errors
+// =============================================================================
+
+#[derive(Debug, Error)]
+pub enum ImpersonationError {
+    #[error("Impersonation is disabled")]
+    Disabled,
+
+    #[error("A reason is required to impersonate a principal")]
+    MissingReason,
+
+    #[error("Cannot impersonate yourself")]
+    SelfImpersonation,
+
+    #[error("Requested TTL of {requested}s exceeds the maximum of {max}s")]
+    TtlTooLong { requested: u64, max: u64 },
+
+    #[error("Not an impersonation token")]
+    NotImpersonation,
+
+    #[error(transparent)]
+    Auth(#[from] AuthError),
+}
+
+pub type Result<T> = std::result::Result<T, ImpersonationError>;
+
+// =============================================================================
+// Configuration
+// =============================================================================
+
+#[derive(Debug, Clone)]
+pub struct ImpersonationConfig {
+    /// Off by default
+    pub enabled: bool,
+    /// Lifetime of a token when the request doesn't ask for one
+    pub default_ttl: Duration,
+    /// Longest lifetime an admin may request
+    pub max_ttl: Duration,
+}
+
+impl Default for ImpersonationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            default_ttl: Duration::from_secs(5 * 60),
+            max_ttl: Duration::from_secs(15 * 60),
+        }
+    }
+}
+
+// =============================================================================
+// Requests and Grants
+// =============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImpersonationRequest {
+    /// Principal to act as
+    pub principal: String,
+    #[serde(default)]
+    pub email: Option<String>,
+    #[serde(default)]
+    pub roles: Vec<String>,
+    #[serde(default)]
+    pub permissions: Vec<String>,
+    /// Why the admin needs to act as the principal (e.g. a ticket reference)
+    pub reason: String,
+    #[serde(default)]
+    pub ttl_seconds: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImpersonationGrant {
+    pub token: String,
+    pub token_id: String,
+    pub principal: String,
+    pub impersonator: String,
+    /// Unix timestamp
+    pub expires_at: u64,
+}
+
+// =============================================================================
+// Impersonation Manager
+// =============================================================================
+
+pub struct ImpersonationManager {
+    config: ImpersonationConfig,
+    jwt: Arc<JwtManager>,
+    audit_logger: Arc<AuditLogger>,
+}
+
+impl ImpersonationManager {
+    pub fn new(
+        config: ImpersonationConfig,
+        jwt: Arc<JwtManager>,
+        audit_logger: Arc<AuditLogger>,
+    ) -> Self {
+        Self {
+            config,
+            jwt,
+            audit_logger,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// Issue a token for `admin` acting as the requested principal
+    pub async fn issue(&self, admin: &str, request: ImpersonationRequest) -> Result<ImpersonationGrant> {
+        if !self.config.enabled {
+            return Err(ImpersonationError::Disabled);
+        }
+        if request.reason.trim().is_empty() {
+            return Err(ImpersonationError::MissingReason);
+        }
+        if request.principal == admin {
+            return Err(ImpersonationError::SelfImpersonation);
+        }
+
+        let max = self.config.max_ttl.as_secs();
+        let ttl = request
+            .ttl_seconds
+            .unwrap_or_else(|| self.config.default_ttl.as_secs());
+        if ttl > max {
+            return Err(ImpersonationError::TtlTooLong { requested: ttl, max });
+        }
+
+        let mut claims = TokenClaims::new_access_token(
+            request.principal.clone(),
+            request.email,
+            request.roles,
+            request.permissions,
+        );
+        claims.exp = claims.iat + ttl;
+        claims.act = Some(ActorClaim {
+            sub: admin.to_string(),
+        });
+        let token = self.jwt.generate_token(&claims)?;
+
+        let event = AuditEvent::new(
+            AuditEventType::ImpersonationStarted,
+            "Impersonation token issued".to_string(),
+            AuditResult::Success,
+            String::new(),
+        )
+        .with_user(request.principal.clone(), None)
+        .with_impersonator(admin.to_string())
+        .with_resource("token".to_string(), claims.jti.clone())
+        .with_metadata("reason".to_string(), json!(request.reason))
+        .with_metadata("roles".to_string(), json!(claims.roles))
+        .with_metadata("expires_at".to_string(), json!(claims.exp));
+        self.audit_logger.log(event).await;
+
+        Ok(ImpersonationGrant {
+            token,
+            token_id: claims.jti,
+            principal: request.principal,
+            impersonator: admin.to_string(),
+            expires_at: claims.exp,
+        })
+    }
+
+    /// Verify an impersonation token
+    ///
+    /// Tokens stop working as soon as impersonation is disabled, even if they
+    /// haven't expired yet.
+    pub async fn verify(&self, token: &str) -> Result<TokenClaims> {
+        if !self.config.enabled {
+            return Err(ImpersonationError::Disabled);
+        }
+        let claims = self.jwt.verify_token(token).await?;
+        if claims.act.is_none() {
+            return Err(ImpersonationError::NotImpersonation);
+        }
+        Ok(claims)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::AuditEventFilter;
+    use crate::auth::TokenRevocationList;
+
+    fn manager(enabled: bool) -> (ImpersonationManager, Arc<AuditLogger>) {
+        let jwt = Arc::new(JwtManager::new_hs256(
+            b"test-secret-key-minimum-32-bytes-long",
+            Arc::new(TokenRevocationList::new()),
+        ));
+        let logger = Arc::new(AuditLogger::new());
+        let config = ImpersonationConfig {
+            enabled,
+            ..Default::default()
+        };
+        (ImpersonationManager::new(config, jwt, logger.clone()), logger)
+    }
+
+    fn request(ttl_seconds: Option<u64>) -> ImpersonationRequest {
+        ImpersonationRequest {
+            principal: "customer-42".to_string(),
+            email: None,
+            roles: vec!["developer".to_string()],
+            permissions: vec![],
+            reason: "SUP-1234: cannot publish schema".to_string(),
+            ttl_seconds,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_disabled_by_default() {
+        assert!(!ImpersonationConfig::default().enabled);
+
+        let (manager, logger) = manager(false);
+        let result = manager.issue("alice", request(None)).await;
+        assert!(matches!(result, Err(ImpersonationError::Disabled)));
+        assert_eq!(logger.count().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_issue_and_verify_preserves_admin_identity() {
+        let (manager, logger) = manager(true);
+        let grant = manager.issue("alice", request(Some(60))).await.unwrap();
+        assert_eq!(grant.impersonator, "alice");
+
+        let claims = manager.verify(&grant.token).await.unwrap();
+        assert_eq!(claims.sub, "customer-42");
+        assert_eq!(claims.impersonator(), Some("alice"));
+        assert_eq!(claims.exp, claims.iat + 60);
+
+        let events = logger.get_events(AuditEventFilter::default()).await;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event_type, AuditEventType::ImpersonationStarted);
+        assert_eq!(events[0].user_id.as_deref(), Some("customer-42"));
+        assert_eq!(events[0].impersonator.as_deref(), Some("alice"));
+        assert!(logger.verify_chain_integrity().await);
+    }
+
+    #[tokio::test]
+    async fn test_constraints() {
+        let (manager, _) = manager(true);
+
+        let result = manager.issue("alice", request(Some(3600))).await;
+        assert!(matches!(result, Err(ImpersonationError::TtlTooLong { max: 900, .. })));
+
+        let mut own = request(None);
+        own.principal = "alice".to_string();
+        let result = manager.issue("alice", own).await;
+        assert!(matches!(result, Err(ImpersonationError::SelfImpersonation)));
+
+        let mut unexplained = request(None);
+        unexplained.reason = "  ".to_string();
+        let result = manager.issue("alice", unexplained).await;
+        assert!(matches!(result, Err(ImpersonationError::MissingReason)));
+
+        let plain = TokenClaims::new_access_token("bob".to_string(), None, vec![], vec![]);
+        let token = manager.jwt.generate_token(&plain).unwrap();
+        let result = manager.verify(&token).await;
+        assert!(matches!(result, Err(ImpersonationError::NotImpersonation)));
+    }
+}
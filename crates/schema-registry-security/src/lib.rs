@@ -1,7 +1,7 @@
 //! Security layer: RBAC, ABAC, signatures, audit logging, secrets management
 //!
 //! This module provides comprehensive security features including:
-//! - Authentication (JWT, OAuth, mTLS)
+//! - Authentication (JWT, OAuth, mTLS, SPIFFE SVIDs)
 //! - Authorization (RBAC, ABAC)
 //! - Audit logging (tamper-proof, hash-chained)
 //! - Secrets management (rotation, encryption)
@@ -11,12 +11,55 @@ pub mod rbac;
 pub mod abac;
 pub mod audit;
 pub mod secrets;
+pub mod siem;
 pub mod auth;
+pub mod directory;
+pub mod freeze;
+pub mod impersonation;
+pub mod quarantine;
+pub mod signing;
+pub mod spiffe;
+pub mod token_exchange;
 pub mod soc2;
 
 pub use audit::{AuditEvent, AuditEventType, AuditLogger, AuditResult, AuditSeverity};
-pub use auth::{JwtManager, TokenClaims, TokenRevocationList, TokenType};
-pub use secrets::{Secret, SecretMetadata, SecretsManager, RotationPolicy};
+pub use auth::{ActorClaim, JwtManager, TokenClaims, TokenRevocationList, TokenType};
+pub use directory::{
+    AuthProvider, AuthenticatedIdentity, Credentials, DirectoryClient, GroupMappingSource,
+    GroupRoleMapper, GroupRoleMapping, JsonFileMappingSource, LdapAuthProvider,
+    OidcGroupProvider, OidcTokenVerifier, StaticMappingSource,
+};
+pub use freeze::{
+    ActiveFreeze, ChangeFreezeConfig, ChangeFreezeManager, CronSchedule, FreezeCheck,
+    FreezeError, FreezeNotificationSink, FreezeSchedule, FreezeWindow, FreezeWindowStore,
+    InMemoryFreezeWindowStore, FREEZE_OVERRIDE_HEADER, FREEZE_OVERRIDE_PERMISSION,
+};
+pub use impersonation::{
+    ImpersonationConfig, ImpersonationGrant, ImpersonationManager, ImpersonationRequest,
+    IMPERSONATED_BY_HEADER,
+};
+pub use quarantine::{
+    QuarantineManager, QuarantineRecord, QuarantineStatus, SecurityNotificationSink,
+    WebhookNotificationSink, SECURITY_REVIEW_PERMISSION,
+};
+pub use signing::{DocumentSignature, DocumentSigner, SigningError};
+pub use spiffe::{
+    JwtAuthorityConfig, JwtSvidPeek, SpiffeAuthProvider, SpiffeConfig, SpiffeId, SpiffeRoleMapping,
+    X509Svid,
+};
+pub use token_exchange::{
+    ParentCredential, ScopedTokenGrant, TokenExchangeConfig, TokenExchangeError,
+    TokenExchangeRequest, TokenExchanger, TokenScope, SCHEMA_READ_PERMISSION,
+    SCHEMA_WRITE_PERMISSION,
+};
+pub use secrets::{
+    EncryptedSecretsBackend, EnvelopeEncryptor, KeyEncryptionKey, RotationPolicy, Secret,
+    SecretMetadata, SecretsManager,
+};
+pub use siem::{
+    AuditSink, AuditSource, AuditStreamer, DeliveryLedger, HttpBatchFormat, HttpBatchSink,
+    SinkError, SinkStatus, StreamConfig, SyslogSink, SyslogTransport,
+};
 pub use soc2::{
     AllControls, AvailabilityControls, ComplianceMetrics, ComplianceMonitor, ComplianceReporter,
     ConfidentialityControls, ControlStatus, EvidenceCollector, ProcessingIntegrityControls,
@@ -0,0 +1,495 @@
+//! Schema Quarantine
+//!
+//! Schemas that trip the security validation step (eval/exec keywords,
+//! prototype pollution patterns, ...) are stored but must not be served until
+//! a reviewer holding the `security:review` permission clears them.
+//!
+//! Every quarantine decision is written to the audit log and forwarded to a
+//! security team notification sink: a webhook, or the log when none is
+//! configured.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::RwLock;
+
+use crate::audit::{AuditEvent, AuditEventType, AuditLogger, AuditResult};
+
+/// Permission required to clear or reject a quarantined schema
+pub const SECURITY_REVIEW_PERMISSION: &str = "security:review";
+
+// =============================================================================
+// Quarantine Types
+// =============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum QuarantineStatus {
+    /// Awaiting security review
+    Pending,
+    /// Reviewed and released for serving
+    Cleared,
+    /// Reviewed and permanently rejected
+    Rejected,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuarantineRecord {
+    pub schema_id: String,
+    pub subject: String,
+    pub reasons: Vec<String>,
+    pub status: QuarantineStatus,
+    pub quarantined_at: u64,
+    pub quarantined_by: String,
+    pub reviewed_at: Option<u64>,
+    pub reviewed_by: Option<String>,
+    pub review_note: Option<String>,
+}
+
+impl QuarantineRecord {
+    /// Whether the schema may be served to clients
+    pub fn is_servable(&self) -> bool {
+        self.status == QuarantineStatus::Cleared
+    }
+}
+
+/// Notification sent to the security team when quarantine state changes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuarantineNotice {
+    pub record: QuarantineRecord,
+    pub event_type: AuditEventType,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum QuarantineError {
+    #[error("Schema {0} is not quarantined")]
+    NotQuarantined(String),
+
+    #[error("Schema {0} has already been reviewed")]
+    AlreadyReviewed(String),
+
+    #[error("Reviewer {0} lacks the security:review permission")]
+    MissingPermission(String),
+}
+
+// =============================================================================
+// Notification Sink
+// =============================================================================
+
+/// Destination for security team notifications
+#[async_trait]
+pub trait SecurityNotificationSink: Send + Sync {
+    async fn notify(&self, notice: QuarantineNotice);
+}
+
+/// Default sink that emits notices through tracing
+pub struct TracingNotificationSink;
+
+#[async_trait]
+impl SecurityNotificationSink for TracingNotificationSink {
+    async fn notify(&self, notice: QuarantineNotice) {
+        tracing::warn!(
+            schema_id = %notice.record.schema_id,
+            subject = %notice.record.subject,
+            status = ?notice.record.status,
+            reasons = ?notice.record.reasons,
+            "Security team notification: schema quarantine"
+        );
+    }
+}
+
+/// Posts each notice as JSON, e.g. to the security team's alerting webhook
+pub struct WebhookNotificationSink {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl WebhookNotificationSink {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            client: reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs(10))
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+}
+
+#[async_trait]
+impl SecurityNotificationSink for WebhookNotificationSink {
+    async fn notify(&self, notice: QuarantineNotice) {
+        // The decision is already audited, so a failed delivery is only logged
+        let result = self
+            .client
+            .post(&self.url)
+            .json(&notice)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+        if let Err(e) = result {
+            tracing::warn!(url = %self.url, error = %e, "Failed to deliver quarantine notice");
+        }
+    }
+}
+
+// =============================================================================
+// Quarantine Manager
+// =============================================================================
+
+pub struct QuarantineManager {
+    records: Arc<RwLock<HashMap<String, QuarantineRecord>>>,
+    audit_logger: Arc<AuditLogger>,
+    sink: Arc<dyn SecurityNotificationSink>,
+}
+
+impl QuarantineManager {
+    pub fn new(audit_logger: Arc<AuditLogger>, sink: Arc<dyn SecurityNotificationSink>) -> Self {
+        Self {
+            records: Arc::new(RwLock::new(HashMap::new())),
+            audit_logger,
+            sink,
+        }
+    }
+
+    /// Place a schema in quarantine
+    pub async fn quarantine(
+        &self,
+        schema_id: String,
+        subject: String,
+        reasons: Vec<String>,
+        actor: String,
+    ) -> QuarantineRecord {
+        let record = QuarantineRecord {
+            schema_id: schema_id.clone(),
+            subject,
+            reasons,
+            status: QuarantineStatus::Pending,
+            quarantined_at: now(),
+            quarantined_by: actor.clone(),
+            reviewed_at: None,
+            reviewed_by: None,
+            review_note: None,
+        };
+
+        self.records.write().await.insert(schema_id, record.clone());
+        self.record_decision(AuditEventType::SchemaQuarantined, &record, actor).await;
+        record
+    }
+
+    /// Release a quarantined schema after security review
+    pub async fn clear(
+        &self,
+        schema_id: &str,
+        reviewer: &str,
+        reviewer_permissions: &[String],
+        note: Option<String>,
+    ) -> Result<QuarantineRecord, QuarantineError> {
+        self.review(schema_id, reviewer, reviewer_permissions, note, QuarantineStatus::Cleared)
+            .await
+    }
+
+    /// Release a schema held in quarantine, whether or not it has a record
+    ///
+    /// Records are kept in memory, so a schema quarantined before a restart
+    /// has none. One is restored from `subject` and `quarantined_at` so the
+    /// release is reviewed and audited like any other.
+    pub async fn release(
+        &self,
+        schema_id: &str,
+        subject: &str,
+        quarantined_at: u64,
+        reviewer: &str,
+        reviewer_permissions: &[String],
+        note: Option<String>,
+    ) -> Result<QuarantineRecord, QuarantineError> {
+        self.records
+            .write()
+            .await
+            .entry(schema_id.to_string())
+            .or_insert_with(|| QuarantineRecord {
+                schema_id: schema_id.to_string(),
+                subject: subject.to_string(),
+                reasons: Vec::new(),
+                status: QuarantineStatus::Pending,
+                quarantined_at,
+                quarantined_by: "registration".to_string(),
+                reviewed_at: None,
+                reviewed_by: None,
+                review_note: None,
+            });
+        self.clear(schema_id, reviewer, reviewer_permissions, note)
+            .await
+    }
+
+    /// Permanently reject a quarantined schema after security review
+    pub async fn reject(
+        &self,
+        schema_id: &str,
+        reviewer: &str,
+        reviewer_permissions: &[String],
+        note: Option<String>,
+    ) -> Result<QuarantineRecord, QuarantineError> {
+        self.review(schema_id, reviewer, reviewer_permissions, note, QuarantineStatus::Rejected)
+            .await
+    }
+
+    /// Get the quarantine record for a schema, if any
+    pub async fn get(&self, schema_id: &str) -> Option<QuarantineRecord> {
+        self.records.read().await.get(schema_id).cloned()
+    }
+
+    /// Whether a schema may be served (never quarantined, or cleared)
+    pub async fn is_servable(&self, schema_id: &str) -> bool {
+        self.records
+            .read()
+            .await
+            .get(schema_id)
+            .map(|r| r.is_servable())
+            .unwrap_or(true)
+    }
+
+    /// Drop the record of a schema that no longer exists
+    pub async fn forget(&self, schema_id: &str) {
+        self.records.write().await.remove(schema_id);
+    }
+
+    /// List schemas awaiting review
+    pub async fn pending(&self) -> Vec<QuarantineRecord> {
+        self.records
+            .read()
+            .await
+            .values()
+            .filter(|r| r.status == QuarantineStatus::Pending)
+            .cloned()
+            .collect()
+    }
+
+    /// Check that a reviewer may decide on a quarantined schema, auditing a
+    /// refusal
+    ///
+    /// Callers that change the schema's stored state check this first, so a
+    /// refused review changes nothing.
+    pub async fn authorize(
+        &self,
+        schema_id: &str,
+        reviewer: &str,
+        reviewer_permissions: &[String],
+    ) -> Result<(), QuarantineError> {
+        if reviewer_permissions.iter().any(|p| p == SECURITY_REVIEW_PERMISSION) {
+            return Ok(());
+        }
+        let event = AuditEvent::new(
+            AuditEventType::AccessDenied,
+            "Quarantine review denied".to_string(),
+            AuditResult::Failure,
+            String::new(),
+        )
+        .with_user(reviewer.to_string(), None)
+        .with_resource("schema".to_string(), schema_id.to_string());
+        self.audit_logger.log(event).await;
+
+        Err(QuarantineError::MissingPermission(reviewer.to_string()))
+    }
+
+    async fn review(
+        &self,
+        schema_id: &str,
+        reviewer: &str,
+        reviewer_permissions: &[String],
+        note: Option<String>,
+        status: QuarantineStatus,
+    ) -> Result<QuarantineRecord, QuarantineError> {
+        self.authorize(schema_id, reviewer, reviewer_permissions).await?;
+
+        let record = {
+            let mut records = self.records.write().await;
+            let record = records
+                .get_mut(schema_id)
+                .ok_or_else(|| QuarantineError::NotQuarantined(schema_id.to_string()))?;
+
+            if record.status != QuarantineStatus::Pending {
+                return Err(QuarantineError::AlreadyReviewed(schema_id.to_string()));
+            }
+
+            record.status = status;
+            record.reviewed_at = Some(now());
+            record.reviewed_by = Some(reviewer.to_string());
+            record.review_note = note;
+            record.clone()
+        };
+
+        let event_type = match status {
+            QuarantineStatus::Cleared => AuditEventType::QuarantineCleared,
+            _ => AuditEventType::QuarantineRejected,
+        };
+        self.record_decision(event_type, &record, reviewer.to_string()).await;
+
+        Ok(record)
+    }
+
+    async fn record_decision(
+        &self,
+        event_type: AuditEventType,
+        record: &QuarantineRecord,
+        actor: String,
+    ) {
+        let event = AuditEvent::new(
+            event_type,
+            format!("Schema quarantine {:?}", record.status),
+            AuditResult::Success,
+            String::new(),
+        )
+        .with_user(actor, None)
+        .with_resource("schema".to_string(), record.schema_id.clone())
+        .with_metadata("subject".to_string(), serde_json::json!(record.subject))
+        .with_metadata("reasons".to_string(), serde_json::json!(record.reasons));
+        self.audit_logger.log(event).await;
+
+        self.sink
+            .notify(QuarantineNotice {
+                record: record.clone(),
+                event_type,
+            })
+            .await;
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::AuditEventFilter;
+
+    struct CountingSink(RwLock<usize>);
+
+    #[async_trait]
+    impl SecurityNotificationSink for CountingSink {
+        async fn notify(&self, _notice: QuarantineNotice) {
+            *self.0.write().await += 1;
+        }
+    }
+
+    fn manager() -> (QuarantineManager, Arc<AuditLogger>, Arc<CountingSink>) {
+        let logger = Arc::new(AuditLogger::new());
+        let sink = Arc::new(CountingSink(RwLock::new(0)));
+        let manager = QuarantineManager::new(logger.clone(), sink.clone());
+        (manager, logger, sink)
+    }
+
+    #[tokio::test]
+    async fn test_quarantine_and_clear() {
+        let (manager, logger, sink) = manager();
+
+        manager
+            .quarantine(
+                "schema-1".to_string(),
+                "com.example.User".to_string(),
+                vec!["Contains prototype pollution pattern".to_string()],
+                "validation-pipeline".to_string(),
+            )
+            .await;
+        assert!(!manager.is_servable("schema-1").await);
+        assert_eq!(manager.pending().await.len(), 1);
+
+        let record = manager
+            .clear(
+                "schema-1",
+                "security-admin",
+                &[SECURITY_REVIEW_PERMISSION.to_string()],
+                Some("False positive".to_string()),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(record.status, QuarantineStatus::Cleared);
+        assert!(manager.is_servable("schema-1").await);
+        assert_eq!(*sink.0.read().await, 2);
+
+        let filter = AuditEventFilter {
+            event_types: Some(vec![
+                AuditEventType::SchemaQuarantined,
+                AuditEventType::QuarantineCleared,
+            ]),
+            ..Default::default()
+        };
+        assert_eq!(logger.get_events(filter).await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_review_requires_permission() {
+        let (manager, logger, _sink) = manager();
+
+        manager
+            .quarantine("schema-2".to_string(), "s".to_string(), vec![], "pipeline".to_string())
+            .await;
+
+        let result = manager
+            .clear("schema-2", "developer", &["schema:write".to_string()], None)
+            .await;
+        assert!(matches!(result, Err(QuarantineError::MissingPermission(_))));
+        assert!(!manager.is_servable("schema-2").await);
+
+        let filter = AuditEventFilter {
+            event_types: Some(vec![AuditEventType::AccessDenied]),
+            ..Default::default()
+        };
+        assert_eq!(logger.get_events(filter).await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_release_restores_a_missing_record() {
+        let (manager, logger, _sink) = manager();
+        let permissions = vec![SECURITY_REVIEW_PERMISSION.to_string()];
+
+        let record = manager
+            .release(
+                "schema-4",
+                "com.example.User",
+                1_700_000_000,
+                "admin",
+                &permissions,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(record.status, QuarantineStatus::Cleared);
+        assert_eq!(record.subject, "com.example.User");
+        assert_eq!(record.quarantined_at, 1_700_000_000);
+        assert!(manager.is_servable("schema-4").await);
+
+        let filter = AuditEventFilter {
+            event_types: Some(vec![AuditEventType::QuarantineCleared]),
+            ..Default::default()
+        };
+        assert_eq!(logger.get_events(filter).await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_reject_is_final() {
+        let (manager, _logger, _sink) = manager();
+        let permissions = vec![SECURITY_REVIEW_PERMISSION.to_string()];
+
+        manager
+            .quarantine("schema-3".to_string(), "s".to_string(), vec![], "pipeline".to_string())
+            .await;
+        manager.reject("schema-3", "admin", &permissions, None).await.unwrap();
+
+        assert!(!manager.is_servable("schema-3").await);
+        assert!(matches!(
+            manager.clear("schema-3", "admin", &permissions, None).await,
+            Err(QuarantineError::AlreadyReviewed(_))
+        ));
+    }
+}
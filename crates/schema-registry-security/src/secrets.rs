@@ -3,11 +3,18 @@
 //! This module provides secure secrets management with:
 //! - Automatic rotation (90-day max age)
 //! - Integration with HashiCorp Vault and AWS Secrets Manager
-//! - Encrypted storage
+//! - Encrypted storage (envelope encryption with per-secret data keys)
 //! - Audit logging
 
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Nonce};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use rand::RngCore;
+use schema_registry_core::secret_ref::{SecretRef, SecretResolver, SecretValue, REDACTED};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
 use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 use thiserror::Error;
@@ -93,7 +100,7 @@ pub enum RotationPolicy {
 // Secret Types
 // =============================================================================
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub enum SecretType {
     /// JWT signing key (symmetric or asymmetric)
     JwtSigningKey {
@@ -117,6 +124,177 @@ pub enum SecretType {
     EncryptedString {
         value: String,
     },
+    /// Any of the above, envelope-encrypted at rest
+    Encrypted(EnvelopeCiphertext),
+}
+
+impl SecretType {
+    /// The value a secret reference resolves to
+    pub fn primary_value(&self) -> Option<&str> {
+        match self {
+            SecretType::JwtSigningKey { private_key, .. } => Some(private_key),
+            SecretType::ApiKey { key, .. } => Some(key),
+            SecretType::DatabaseCredentials { password, .. } => Some(password),
+            SecretType::EncryptedString { value } => Some(value),
+            SecretType::Encrypted(_) => None,
+        }
+    }
+}
+
+impl fmt::Debug for SecretType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SecretType::JwtSigningKey { algorithm, public_key, .. } => f
+                .debug_struct("JwtSigningKey")
+                .field("algorithm", algorithm)
+                .field("public_key", public_key)
+                .field("private_key", &REDACTED)
+                .finish(),
+            SecretType::ApiKey { scope, .. } => f
+                .debug_struct("ApiKey")
+                .field("key", &REDACTED)
+                .field("scope", scope)
+                .finish(),
+            SecretType::DatabaseCredentials { username, host, port, .. } => f
+                .debug_struct("DatabaseCredentials")
+                .field("username", username)
+                .field("password", &REDACTED)
+                .field("host", host)
+                .field("port", port)
+                .finish(),
+            SecretType::EncryptedString { .. } => f
+                .debug_struct("EncryptedString")
+                .field("value", &REDACTED)
+                .finish(),
+            SecretType::Encrypted(envelope) => f.debug_tuple("Encrypted").field(envelope).finish(),
+        }
+    }
+}
+
+// =============================================================================
+// Envelope Encryption
+// =============================================================================
+
+/// A value encrypted with a random data key, which is itself encrypted
+/// ("wrapped") with a key-encryption key
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnvelopeCiphertext {
+    /// ID of the key-encryption key that wrapped the data key
+    pub kek_id: String,
+    /// Base64 nonce and wrapped data key
+    pub wrapped_key: String,
+    /// Base64 nonce and ciphertext
+    pub ciphertext: String,
+}
+
+/// Key-encryption key (KEK) used to wrap per-secret data keys
+pub struct KeyEncryptionKey {
+    id: String,
+    cipher: Aes256Gcm,
+}
+
+impl KeyEncryptionKey {
+    pub fn new(id: impl Into<String>, key: &[u8; 32]) -> Self {
+        Self {
+            id: id.into(),
+            cipher: Aes256Gcm::new(key.into()),
+        }
+    }
+
+    /// Load a KEK from 32 base64-encoded bytes, e.g. from an environment variable
+    pub fn from_base64(id: impl Into<String>, key: &str) -> Result<Self> {
+        let bytes = BASE64
+            .decode(key.trim())
+            .map_err(|e| SecretsError::InvalidFormat(format!("KEK is not valid base64: {}", e)))?;
+        let key: [u8; 32] = bytes
+            .try_into()
+            .map_err(|_| SecretsError::InvalidFormat("KEK must be 32 bytes".to_string()))?;
+        Ok(Self::new(id, &key))
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+fn seal(cipher: &Aes256Gcm, plaintext: &[u8]) -> Result<String> {
+    let mut nonce = [0u8; 12];
+    rand::thread_rng().fill_bytes(&mut nonce);
+    let mut sealed = nonce.to_vec();
+    sealed.extend(
+        cipher
+            .encrypt(Nonce::from_slice(&nonce), plaintext)
+            .map_err(|_| SecretsError::EncryptionError("Encryption failed".to_string()))?,
+    );
+    Ok(BASE64.encode(sealed))
+}
+
+fn open(cipher: &Aes256Gcm, sealed: &str) -> Result<Vec<u8>> {
+    let sealed = BASE64
+        .decode(sealed)
+        .map_err(|e| SecretsError::EncryptionError(format!("Invalid ciphertext encoding: {}", e)))?;
+    if sealed.len() < 12 {
+        return Err(SecretsError::EncryptionError("Ciphertext too short".to_string()));
+    }
+    let (nonce, ciphertext) = sealed.split_at(12);
+    cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| SecretsError::EncryptionError("Decryption failed".to_string()))
+}
+
+/// Encrypts values under the active KEK and decrypts values wrapped by any
+/// known KEK, so KEKs can be rotated without re-encrypting everything at once
+pub struct EnvelopeEncryptor {
+    active: KeyEncryptionKey,
+    retired: HashMap<String, KeyEncryptionKey>,
+}
+
+impl EnvelopeEncryptor {
+    pub fn new(active: KeyEncryptionKey) -> Self {
+        Self {
+            active,
+            retired: HashMap::new(),
+        }
+    }
+
+    /// Keep a previous KEK for decrypting values it wrapped
+    pub fn with_retired_key(mut self, key: KeyEncryptionKey) -> Self {
+        self.retired.insert(key.id.clone(), key);
+        self
+    }
+
+    pub fn encrypt(&self, plaintext: &[u8]) -> Result<EnvelopeCiphertext> {
+        let mut data_key = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut data_key);
+        let data_cipher = Aes256Gcm::new(&data_key.into());
+
+        let envelope = EnvelopeCiphertext {
+            kek_id: self.active.id.clone(),
+            wrapped_key: seal(&self.active.cipher, &data_key)?,
+            ciphertext: seal(&data_cipher, plaintext)?,
+        };
+        data_key.fill(0);
+        Ok(envelope)
+    }
+
+    pub fn decrypt(&self, envelope: &EnvelopeCiphertext) -> Result<Vec<u8>> {
+        let kek = if envelope.kek_id == self.active.id {
+            &self.active
+        } else {
+            self.retired.get(&envelope.kek_id).ok_or_else(|| {
+                SecretsError::EncryptionError(format!("Unknown key-encryption key '{}'", envelope.kek_id))
+            })?
+        };
+
+        let mut data_key = open(&kek.cipher, &envelope.wrapped_key)?;
+        let key: [u8; 32] = data_key
+            .as_slice()
+            .try_into()
+            .map_err(|_| SecretsError::EncryptionError("Invalid data key length".to_string()))?;
+        let plaintext = open(&Aes256Gcm::new(&key.into()), &envelope.ciphertext);
+        data_key.fill(0);
+        plaintext
+    }
 }
 
 // =============================================================================
@@ -238,32 +416,74 @@ impl SecretsBackend for InMemorySecretsBackend {
 
     async fn rotate(&self, name: &str) -> Result<Secret> {
         let current = self.retrieve(name, None).await?;
+        let new_secret = next_version(&current)?;
+        self.store(&new_secret).await?;
+        Ok(new_secret)
+    }
+}
 
-        // Create new version with incremented version number
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+// =============================================================================
+// Encrypted Secrets Backend
+// =============================================================================
 
-        let new_metadata = SecretMetadata {
-            id: Uuid::new_v4().to_string(),
-            name: current.metadata.name.clone(),
-            version: current.metadata.version + 1,
-            created_at: now,
-            expires_at: now + (90 * 86400), // 90 days
-            rotated_at: Some(now),
-            rotation_policy: current.metadata.rotation_policy.clone(),
-            tags: current.metadata.tags.clone(),
-        };
+/// Wraps a backend so secret values are envelope-encrypted before they
+/// reach it and decrypted on the way out
+pub struct EncryptedSecretsBackend {
+    inner: Arc<dyn SecretsBackend>,
+    encryptor: EnvelopeEncryptor,
+}
+
+impl EncryptedSecretsBackend {
+    pub fn new(inner: Arc<dyn SecretsBackend>, encryptor: EnvelopeEncryptor) -> Self {
+        Self { inner, encryptor }
+    }
+
+    fn decrypt_secret(&self, mut secret: Secret) -> Result<Secret> {
+        if let SecretType::Encrypted(envelope) = &secret.secret_type {
+            let plaintext = self.encryptor.decrypt(envelope)?;
+            secret.secret_type = serde_json::from_slice(&plaintext)
+                .map_err(|e| SecretsError::InvalidFormat(e.to_string()))?;
+        }
+        Ok(secret)
+    }
+}
 
-        // Generate new secret value (implementation depends on secret type)
-        let new_secret = Secret {
-            metadata: new_metadata,
-            secret_type: rotate_secret_value(&current.secret_type)?,
+#[async_trait::async_trait]
+impl SecretsBackend for EncryptedSecretsBackend {
+    async fn store(&self, secret: &Secret) -> Result<()> {
+        let secret_type = match &secret.secret_type {
+            SecretType::Encrypted(envelope) => SecretType::Encrypted(envelope.clone()),
+            plain => {
+                let plaintext = serde_json::to_vec(plain)
+                    .map_err(|e| SecretsError::InvalidFormat(e.to_string()))?;
+                SecretType::Encrypted(self.encryptor.encrypt(&plaintext)?)
+            }
         };
+        self.inner
+            .store(&Secret {
+                metadata: secret.metadata.clone(),
+                secret_type,
+            })
+            .await
+    }
 
-        self.store(&new_secret).await?;
+    async fn retrieve(&self, name: &str, version: Option<u32>) -> Result<Secret> {
+        let secret = self.inner.retrieve(name, version).await?;
+        self.decrypt_secret(secret)
+    }
+
+    async fn list(&self) -> Result<Vec<SecretMetadata>> {
+        self.inner.list().await
+    }
 
+    async fn delete(&self, name: &str, version: Option<u32>) -> Result<()> {
+        self.inner.delete(name, version).await
+    }
+
+    async fn rotate(&self, name: &str) -> Result<Secret> {
+        let current = self.retrieve(name, None).await?;
+        let new_secret = next_version(&current)?;
+        self.store(&new_secret).await?;
         Ok(new_secret)
     }
 }
@@ -369,10 +589,55 @@ impl SecretsManager {
     }
 }
 
+#[async_trait::async_trait]
+impl SecretResolver for SecretsManager {
+    async fn resolve(&self, secret: &SecretRef) -> schema_registry_core::Result<SecretValue> {
+        let stored = self
+            .get_secret(secret.name())
+            .await
+            .map_err(|e| schema_registry_core::Error::SecurityError(e.to_string()))?;
+        stored
+            .secret_type
+            .primary_value()
+            .map(SecretValue::new)
+            .ok_or_else(|| {
+                schema_registry_core::Error::SecurityError(format!(
+                    "Secret '{}' could not be decrypted",
+                    secret.name()
+                ))
+            })
+    }
+}
+
 // =============================================================================
 // Helper Functions
 // =============================================================================
 
+/// Build the next version of a secret with a freshly generated value
+fn next_version(current: &Secret) -> Result<Secret> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+
+    let metadata = SecretMetadata {
+        id: Uuid::new_v4().to_string(),
+        name: current.metadata.name.clone(),
+        version: current.metadata.version + 1,
+        created_at: now,
+        expires_at: now + (90 * 86400), // 90 days
+        rotated_at: Some(now),
+        rotation_policy: current.metadata.rotation_policy.clone(),
+        tags: current.metadata.tags.clone(),
+    };
+
+    // Generate new secret value (implementation depends on secret type)
+    Ok(Secret {
+        metadata,
+        secret_type: rotate_secret_value(&current.secret_type)?,
+    })
+}
+
 /// Rotate secret value based on type
 fn rotate_secret_value(secret_type: &SecretType) -> Result<SecretType> {
     match secret_type {
@@ -402,6 +667,9 @@ fn rotate_secret_value(secret_type: &SecretType) -> Result<SecretType> {
         SecretType::EncryptedString { .. } => Ok(SecretType::EncryptedString {
             value: generate_secure_key(32),
         }),
+        SecretType::Encrypted(_) => Err(SecretsError::RotationFailed(
+            "Secret must be decrypted before it can be rotated".to_string(),
+        )),
     }
 }
 
@@ -518,4 +786,51 @@ mod tests {
         assert_eq!(rotated.metadata.version, 2);
         assert!(rotated.metadata.rotated_at.is_some());
     }
+
+    #[tokio::test]
+    async fn test_envelope_encryption_at_rest() {
+        let inner = Arc::new(InMemorySecretsBackend::new());
+        let old_kek = KeyEncryptionKey::new("kek-1", &[1u8; 32]);
+        let backend = EncryptedSecretsBackend::new(inner.clone(), EnvelopeEncryptor::new(old_kek));
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let secret = Secret {
+            metadata: SecretMetadata {
+                id: Uuid::new_v4().to_string(),
+                name: "webhooks/billing".to_string(),
+                version: 1,
+                created_at: now,
+                expires_at: now + 3600,
+                rotated_at: None,
+                rotation_policy: RotationPolicy::Manual,
+                tags: HashMap::new(),
+            },
+            secret_type: SecretType::EncryptedString {
+                value: "whsec_plaintext".to_string(),
+            },
+        };
+        backend.store(&secret).await.unwrap();
+
+        // The wrapped backend only ever sees ciphertext
+        let raw = inner.retrieve("webhooks/billing", None).await.unwrap();
+        assert!(matches!(raw.secret_type, SecretType::Encrypted(_)));
+        assert!(!serde_json::to_string(&raw).unwrap().contains("whsec_plaintext"));
+
+        // Values wrapped by a retired KEK still decrypt after rotating the KEK
+        let encryptor = EnvelopeEncryptor::new(KeyEncryptionKey::new("kek-2", &[2u8; 32]))
+            .with_retired_key(KeyEncryptionKey::new("kek-1", &[1u8; 32]));
+        let manager = SecretsManager::new(
+            Arc::new(EncryptedSecretsBackend::new(inner, encryptor)),
+            RotationConfig::default(),
+        );
+        let value = manager
+            .resolve(&SecretRef::new("webhooks/billing"))
+            .await
+            .unwrap();
+        assert_eq!(value.expose(), "whsec_plaintext");
+        assert!(!format!("{:?}", secret).contains("whsec_plaintext"));
+    }
 }
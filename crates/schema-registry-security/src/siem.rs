@@ -0,0 +1,809 @@
+//! Audit Log Streaming to External SIEMs
+//!
+//! Streams every [`AuditEvent`] to Splunk, Elastic or any syslog collector:
+//! - [`SyslogSink`]: CEF messages in RFC 5424 syslog frames over UDP or TCP
+//! - [`HttpBatchSink`]: batched HTTPS POSTs (JSON array, Splunk HEC or
+//!   Elastic bulk format)
+//!
+//! An [`AuditStreamer`] per sink reads from an [`AuditSource`] by position
+//! rather than through an in-memory queue. Logging never waits on a sink, a
+//! slow sink simply falls behind, and after an outage the streamer replays
+//! everything it missed from its last delivered position. With a durable
+//! source and a [`DeliveryLedger`] that holds across restarts too.
+
+use async_trait::async_trait;
+use chrono::{TimeZone, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+use tokio::io::AsyncWriteExt;
+use tokio::net::{TcpStream, UdpSocket};
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+
+use crate::audit::{AuditEvent, AuditLogger, AuditSeverity};
+
+// =============================================================================
+// Sink Interface
+// =============================================================================
+
+#[derive(Debug, Error)]
+pub enum SinkError {
+    #[error("Transport error: {0}")]
+    Transport(String),
+
+    #[error("Sink rejected batch: {0}")]
+    Rejected(String),
+
+    #[error("Audit log unavailable: {0}")]
+    Source(String),
+}
+
+/// Destination for audit events
+#[async_trait]
+pub trait AuditSink: Send + Sync {
+    /// Name used in status reports and metrics
+    fn name(&self) -> &str;
+
+    /// Deliver a batch; on error the whole batch is retried
+    async fn send(&self, events: &[AuditEvent]) -> Result<(), SinkError>;
+}
+
+/// Ordered log of audit events a streamer reads from
+///
+/// Every event has a position greater than the one before it; positions may
+/// skip numbers.
+#[async_trait]
+pub trait AuditSource: Send + Sync {
+    /// Up to `limit` events after `position`, each with its own position
+    async fn events_after(
+        &self,
+        position: u64,
+        limit: usize,
+    ) -> Result<Vec<(u64, AuditEvent)>, SinkError>;
+
+    /// Number of events after `position`
+    async fn count_after(&self, position: u64) -> Result<u64, SinkError>;
+
+    /// Return once there may be events after `position`
+    async fn wait_for_events(&self, position: u64);
+}
+
+/// The in-memory log; an event's position is its index plus one
+#[async_trait]
+impl AuditSource for AuditLogger {
+    async fn events_after(
+        &self,
+        position: u64,
+        limit: usize,
+    ) -> Result<Vec<(u64, AuditEvent)>, SinkError> {
+        Ok(self
+            .events_from(position as usize, limit)
+            .await
+            .into_iter()
+            .zip(position + 1..)
+            .map(|(event, position)| (position, event))
+            .collect())
+    }
+
+    async fn count_after(&self, position: u64) -> Result<u64, SinkError> {
+        Ok((self.count().await as u64).saturating_sub(position))
+    }
+
+    async fn wait_for_events(&self, position: u64) {
+        AuditLogger::wait_for_events(self, position as usize).await
+    }
+}
+
+/// Durable record of how far each sink got
+#[async_trait]
+pub trait DeliveryLedger: Send + Sync {
+    /// Take over delivery to `sink`, returning its last delivered position
+    ///
+    /// Waits while another streamer holds the sink, so only one delivers to
+    /// it at a time.
+    async fn claim(&self, sink: &str) -> Result<u64, SinkError>;
+
+    /// Record that everything up to `position` reached `sink`
+    async fn record(&self, sink: &str, position: u64) -> Result<(), SinkError>;
+}
+
+// =============================================================================
+// CEF Formatting
+// =============================================================================
+
+/// CEF severity (0-10) for an audit severity
+fn cef_severity(severity: AuditSeverity) -> u8 {
+    match severity {
+        AuditSeverity::Info => 3,
+        AuditSeverity::Warning => 6,
+        AuditSeverity::Important => 8,
+        AuditSeverity::Critical => 10,
+    }
+}
+
+fn escape_cef_header(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('|', "\\|")
+}
+
+fn escape_cef_extension(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('=', "\\=")
+        .replace('\r', "\\r")
+        .replace('\n', "\\n")
+}
+
+/// Format an event as an ArcSight Common Event Format (CEF) record
+pub fn to_cef(event: &AuditEvent) -> String {
+    let mut extension = vec![
+        ("rt", (event.timestamp * 1000).to_string()),
+        ("externalId", event.id.clone()),
+        ("outcome", format!("{:?}", event.result)),
+    ];
+    if let Some(user) = &event.user_id {
+        extension.push(("suser", user.clone()));
+    }
+    if let Some(impersonator) = &event.impersonator {
+        extension.push(("cs5Label", "impersonator".to_string()));
+        extension.push(("cs5", impersonator.clone()));
+    }
+    if let Some(ip) = &event.source_ip {
+        extension.push(("src", ip.clone()));
+    }
+    if let Some(agent) = &event.user_agent {
+        extension.push(("requestClientApplication", agent.clone()));
+    }
+    if let Some(resource_type) = &event.resource_type {
+        extension.push(("cs1Label", "resourceType".to_string()));
+        extension.push(("cs1", resource_type.clone()));
+    }
+    if let Some(resource_id) = &event.resource_id {
+        extension.push(("cs2Label", "resourceId".to_string()));
+        extension.push(("cs2", resource_id.clone()));
+    }
+    if let Some(correlation_id) = &event.correlation_id {
+        extension.push(("cs3Label", "correlationId".to_string()));
+        extension.push(("cs3", correlation_id.clone()));
+    }
+    extension.push(("cs4Label", "eventHash".to_string()));
+    extension.push(("cs4", event.event_hash.clone()));
+
+    let extension = extension
+        .into_iter()
+        .map(|(key, value)| format!("{}={}", key, escape_cef_extension(&value)))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    format!(
+        "CEF:0|LLM Dev Ops|Schema Registry|{}|{:?}|{}|{}|{}",
+        escape_cef_header(env!("CARGO_PKG_VERSION")),
+        event.event_type,
+        escape_cef_header(&event.action),
+        cef_severity(event.severity),
+        extension
+    )
+}
+
+// =============================================================================
+// Syslog Sink
+// =============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyslogTransport {
+    Udp,
+    /// Octet-counted framing (RFC 6587)
+    Tcp,
+}
+
+/// Sends CEF records to a syslog collector
+pub struct SyslogSink {
+    name: String,
+    address: String,
+    transport: SyslogTransport,
+    hostname: String,
+    app_name: String,
+}
+
+impl SyslogSink {
+    pub fn new(address: impl Into<String>, transport: SyslogTransport) -> Self {
+        Self {
+            name: "syslog".to_string(),
+            address: address.into(),
+            transport,
+            hostname: std::env::var("HOSTNAME").unwrap_or_else(|_| "-".to_string()),
+            app_name: "schema-registry".to_string(),
+        }
+    }
+
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// RFC 5424 message carrying the event as CEF
+    pub fn frame(&self, event: &AuditEvent) -> String {
+        // Facility 10 (security/authorization)
+        let severity = match event.severity {
+            AuditSeverity::Info => 6,
+            AuditSeverity::Warning => 4,
+            AuditSeverity::Important => 3,
+            AuditSeverity::Critical => 2,
+        };
+        let timestamp = Utc
+            .timestamp_opt(event.timestamp as i64, 0)
+            .single()
+            .unwrap_or_else(Utc::now)
+            .to_rfc3339();
+        format!(
+            "<{}>1 {} {} {} - - - {}",
+            10 * 8 + severity,
+            timestamp,
+            self.hostname,
+            self.app_name,
+            to_cef(event)
+        )
+    }
+}
+
+#[async_trait]
+impl AuditSink for SyslogSink {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn send(&self, events: &[AuditEvent]) -> Result<(), SinkError> {
+        let transport_error = |e: std::io::Error| SinkError::Transport(e.to_string());
+        match self.transport {
+            SyslogTransport::Udp => {
+                let socket = UdpSocket::bind("0.0.0.0:0").await.map_err(transport_error)?;
+                socket.connect(&self.address).await.map_err(transport_error)?;
+                for event in events {
+                    socket
+                        .send(self.frame(event).as_bytes())
+                        .await
+                        .map_err(transport_error)?;
+                }
+            }
+            SyslogTransport::Tcp => {
+                let mut stream = TcpStream::connect(&self.address).await.map_err(transport_error)?;
+                let mut buffer = Vec::new();
+                for event in events {
+                    let message = self.frame(event);
+                    buffer.extend(format!("{} {}", message.len(), message).as_bytes());
+                }
+                stream.write_all(&buffer).await.map_err(transport_error)?;
+                stream.flush().await.map_err(transport_error)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// =============================================================================
+// HTTPS Batch Sink
+// =============================================================================
+
+/// Request body layout for [`HttpBatchSink`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HttpBatchFormat {
+    /// A JSON array of events
+    JsonArray,
+    /// Splunk HTTP Event Collector: one `{"time", "event"}` object per line
+    SplunkHec,
+    /// Elasticsearch `_bulk` API: index action and document per event
+    ElasticBulk,
+}
+
+/// Encode a batch of events as a request body
+pub fn encode_batch(format: HttpBatchFormat, events: &[AuditEvent]) -> Result<String, SinkError> {
+    let mut body = String::new();
+    match format {
+        HttpBatchFormat::JsonArray => body = encode(&events)?,
+        HttpBatchFormat::SplunkHec => {
+            for event in events {
+                #[derive(Serialize)]
+                struct HecEvent<'a> {
+                    time: u64,
+                    sourcetype: &'a str,
+                    event: &'a AuditEvent,
+                }
+                body.push_str(&encode(&HecEvent {
+                    time: event.timestamp,
+                    sourcetype: "schema_registry:audit",
+                    event,
+                })?);
+                body.push('\n');
+            }
+        }
+        HttpBatchFormat::ElasticBulk => {
+            for event in events {
+                body.push_str(&format!("{{\"index\":{{\"_id\":\"{}\"}}}}\n", event.id));
+                body.push_str(&encode(event)?);
+                body.push('\n');
+            }
+        }
+    }
+    Ok(body)
+}
+
+fn encode<T: Serialize>(value: &T) -> Result<String, SinkError> {
+    serde_json::to_string(value).map_err(|e| SinkError::Rejected(e.to_string()))
+}
+
+/// Posts batches of events to an HTTPS collector
+pub struct HttpBatchSink {
+    name: String,
+    url: String,
+    format: HttpBatchFormat,
+    headers: HashMap<String, String>,
+    client: reqwest::Client,
+}
+
+impl HttpBatchSink {
+    pub fn new(url: impl Into<String>, format: HttpBatchFormat) -> Self {
+        Self {
+            name: "http".to_string(),
+            url: url.into(),
+            format,
+            headers: HashMap::new(),
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Add a request header, e.g. `Authorization: Splunk <token>`
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.insert(name.into(), value.into());
+        self
+    }
+}
+
+#[async_trait]
+impl AuditSink for HttpBatchSink {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn send(&self, events: &[AuditEvent]) -> Result<(), SinkError> {
+        let content_type = match self.format {
+            HttpBatchFormat::JsonArray | HttpBatchFormat::SplunkHec => "application/json",
+            HttpBatchFormat::ElasticBulk => "application/x-ndjson",
+        };
+        let mut request = self
+            .client
+            .post(&self.url)
+            .header("Content-Type", content_type)
+            .body(encode_batch(self.format, events)?);
+        for (name, value) in &self.headers {
+            request = request.header(name, value);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| SinkError::Transport(e.to_string()))?;
+        let status = response.status();
+        if status.is_success() {
+            Ok(())
+        } else if status.is_server_error() || status.as_u16() == 429 {
+            Err(SinkError::Transport(format!("Collector returned {}", status)))
+        } else {
+            Err(SinkError::Rejected(format!("Collector returned {}", status)))
+        }
+    }
+}
+
+// =============================================================================
+// Streamer
+// =============================================================================
+
+#[derive(Debug, Clone)]
+pub struct StreamConfig {
+    /// Maximum events per delivery
+    pub batch_size: usize,
+    /// Maximum time to wait for new events before checking again
+    pub flush_interval: Duration,
+    /// First retry delay after a failed delivery
+    pub initial_backoff: Duration,
+    /// Upper bound for the retry delay
+    pub max_backoff: Duration,
+}
+
+impl Default for StreamConfig {
+    fn default() -> Self {
+        Self {
+            batch_size: 100,
+            flush_interval: Duration::from_secs(1),
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Delivery progress of one sink
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SinkStatus {
+    pub sink: String,
+    /// Position in the audit log up to which events were delivered
+    pub delivered_position: u64,
+    /// Events logged but not yet delivered
+    pub pending: u64,
+    /// Age of the oldest undelivered event
+    pub lag_seconds: u64,
+    pub delivered_total: u64,
+    pub consecutive_failures: u32,
+    pub last_error: Option<String>,
+    pub last_delivery_at: Option<u64>,
+}
+
+/// Streams the audit log to one sink
+pub struct AuditStreamer {
+    source: Arc<dyn AuditSource>,
+    sink: Arc<dyn AuditSink>,
+    ledger: Option<Arc<dyn DeliveryLedger>>,
+    config: StreamConfig,
+    status: RwLock<SinkStatus>,
+}
+
+impl AuditStreamer {
+    /// Stream every event in the log, starting with the oldest
+    pub fn new(source: Arc<dyn AuditSource>, sink: Arc<dyn AuditSink>) -> Self {
+        let status = SinkStatus {
+            sink: sink.name().to_string(),
+            ..Default::default()
+        };
+        Self {
+            source,
+            sink,
+            ledger: None,
+            config: StreamConfig::default(),
+            status: RwLock::new(status),
+        }
+    }
+
+    /// Resume from, and keep, the sink's position in a ledger
+    ///
+    /// [`AuditStreamer::spawn`] claims the sink before streaming.
+    pub fn with_ledger(mut self, ledger: Arc<dyn DeliveryLedger>) -> Self {
+        self.ledger = Some(ledger);
+        self
+    }
+
+    pub fn with_config(mut self, config: StreamConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Resume from a previously delivered position
+    pub fn starting_at(mut self, position: u64) -> Self {
+        self.status.get_mut().delivered_position = position;
+        self
+    }
+
+    pub async fn status(&self) -> SinkStatus {
+        self.status.read().await.clone()
+    }
+
+    /// Re-send everything after `position`, e.g. after the SIEM lost data
+    pub async fn replay_from(&self, position: u64) {
+        tracing::info!(sink = %self.sink.name(), position, "Replaying audit events");
+        self.status.write().await.delivered_position = position;
+    }
+
+    /// Deliver all pending events, returning how many were sent
+    pub async fn deliver_pending(&self) -> Result<usize, SinkError> {
+        let mut delivered = 0;
+        loop {
+            let position = self.status.read().await.delivered_position;
+            let batch = self
+                .source
+                .events_after(position, self.config.batch_size.max(1))
+                .await?;
+            let Some(&(last, _)) = batch.last() else {
+                break;
+            };
+            let batch: Vec<AuditEvent> = batch.into_iter().map(|(_, event)| event).collect();
+
+            if let Err(e) = self.sink.send(&batch).await {
+                let mut status = self.status.write().await;
+                status.consecutive_failures += 1;
+                status.last_error = Some(e.to_string());
+                drop(status);
+                metrics::counter!("schema_registry.audit.sink.failures", "sink" => self.sink.name().to_string())
+                    .increment(1);
+                self.update_lag().await;
+                return Err(e);
+            }
+
+            if let Some(ledger) = &self.ledger {
+                // Losing the record only means a re-delivery after a restart
+                if let Err(e) = ledger.record(self.sink.name(), last).await {
+                    tracing::warn!(
+                        sink = %self.sink.name(),
+                        error = %e,
+                        "Failed to record audit delivery"
+                    );
+                }
+            }
+            let mut status = self.status.write().await;
+            status.delivered_position = last;
+            status.delivered_total += batch.len() as u64;
+            status.consecutive_failures = 0;
+            status.last_error = None;
+            status.last_delivery_at = Some(now());
+            drop(status);
+            metrics::counter!("schema_registry.audit.sink.delivered", "sink" => self.sink.name().to_string())
+                .increment(batch.len() as u64);
+            delivered += batch.len();
+        }
+        self.update_lag().await;
+        Ok(delivered)
+    }
+
+    async fn update_lag(&self) {
+        let position = self.status.read().await.delivered_position;
+        let (Ok(pending), Ok(oldest)) = (
+            self.source.count_after(position).await,
+            self.source.events_after(position, 1).await,
+        ) else {
+            return;
+        };
+        let lag_seconds = oldest
+            .first()
+            .map(|(_, oldest)| now().saturating_sub(oldest.timestamp))
+            .unwrap_or(0);
+
+        let mut status = self.status.write().await;
+        status.pending = pending;
+        status.lag_seconds = lag_seconds;
+        drop(status);
+
+        let sink = self.sink.name().to_string();
+        metrics::gauge!("schema_registry.audit.sink.pending_events", "sink" => sink.clone())
+            .set(pending as f64);
+        metrics::gauge!("schema_registry.audit.sink.lag_seconds", "sink" => sink)
+            .set(lag_seconds as f64);
+    }
+
+    /// Stream in the background until the task is aborted
+    pub fn spawn(self: Arc<Self>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut backoff = self.config.initial_backoff;
+            if let Some(ledger) = &self.ledger {
+                loop {
+                    match ledger.claim(self.sink.name()).await {
+                        Ok(position) => {
+                            self.status.write().await.delivered_position = position;
+                            break;
+                        }
+                        Err(e) => {
+                            tracing::warn!(
+                                sink = %self.sink.name(),
+                                error = %e,
+                                retry_in_ms = backoff.as_millis() as u64,
+                                "Failed to claim audit sink"
+                            );
+                            tokio::time::sleep(backoff).await;
+                            backoff = (backoff * 2).min(self.config.max_backoff);
+                        }
+                    }
+                }
+                backoff = self.config.initial_backoff;
+            }
+            loop {
+                match self.deliver_pending().await {
+                    Ok(_) => {
+                        backoff = self.config.initial_backoff;
+                        let position = self.status.read().await.delivered_position;
+                        let _ = tokio::time::timeout(
+                            self.config.flush_interval,
+                            self.source.wait_for_events(position),
+                        )
+                        .await;
+                    }
+                    Err(e) => {
+                        tracing::warn!(
+                            sink = %self.sink.name(),
+                            error = %e,
+                            retry_in_ms = backoff.as_millis() as u64,
+                            "Audit sink delivery failed"
+                        );
+                        tokio::time::sleep(backoff).await;
+                        backoff = (backoff * 2).min(self.config.max_backoff);
+                    }
+                }
+            }
+        })
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::{AuditEventType, AuditResult};
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use tokio::sync::Mutex;
+
+    /// Fails while `outage` is non-zero, counting it down on each attempt
+    struct FlakySink {
+        outage: AtomicU32,
+        received: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl AuditSink for FlakySink {
+        fn name(&self) -> &str {
+            "flaky"
+        }
+
+        async fn send(&self, events: &[AuditEvent]) -> Result<(), SinkError> {
+            if self.outage.load(Ordering::SeqCst) > 0 {
+                self.outage.fetch_sub(1, Ordering::SeqCst);
+                return Err(SinkError::Transport("connection refused".to_string()));
+            }
+            self.received
+                .lock()
+                .await
+                .extend(events.iter().map(|e| e.id.clone()));
+            Ok(())
+        }
+    }
+
+    fn event(action: &str) -> AuditEvent {
+        AuditEvent::new(
+            AuditEventType::SchemaRegistered,
+            action.to_string(),
+            AuditResult::Success,
+            String::new(),
+        )
+        .with_user("alice".to_string(), None)
+    }
+
+    #[test]
+    fn test_cef_format_and_escaping() {
+        let cef = to_cef(&event("Registered a|b").with_resource("schema".to_string(), "x=1".to_string()));
+        assert!(cef.starts_with("CEF:0|LLM Dev Ops|Schema Registry|"));
+        assert!(cef.contains("|SchemaRegistered|Registered a\\|b|3|"));
+        assert!(cef.contains("suser=alice"));
+        assert!(cef.contains("cs2=x\\=1"));
+
+        let frame = SyslogSink::new("127.0.0.1:514", SyslogTransport::Udp).frame(&event("x"));
+        assert!(frame.starts_with("<86>1 "));
+        assert!(frame.contains(" schema-registry - - - CEF:0|"));
+    }
+
+    #[test]
+    fn test_encode_batch_formats() {
+        let events = vec![event("a"), event("b")];
+
+        let array: serde_json::Value =
+            serde_json::from_str(&encode_batch(HttpBatchFormat::JsonArray, &events).unwrap()).unwrap();
+        assert_eq!(array.as_array().unwrap().len(), 2);
+
+        let hec = encode_batch(HttpBatchFormat::SplunkHec, &events).unwrap();
+        let first: serde_json::Value = serde_json::from_str(hec.lines().next().unwrap()).unwrap();
+        assert_eq!(first["sourcetype"], "schema_registry:audit");
+        assert_eq!(first["event"]["action"], "a");
+
+        let bulk = encode_batch(HttpBatchFormat::ElasticBulk, &events).unwrap();
+        assert_eq!(bulk.lines().count(), 4);
+        assert!(bulk.starts_with(&format!("{{\"index\":{{\"_id\":\"{}\"}}}}", events[0].id)));
+    }
+
+    #[tokio::test]
+    async fn test_streamer_replays_after_outage() {
+        let logger = Arc::new(AuditLogger::new());
+        for i in 0..5 {
+            logger.log(event(&format!("event {}", i))).await;
+        }
+        let sink = Arc::new(FlakySink {
+            outage: AtomicU32::new(2),
+            received: Mutex::new(Vec::new()),
+        });
+        let streamer = AuditStreamer::new(logger.clone(), sink.clone()).with_config(StreamConfig {
+            batch_size: 2,
+            ..Default::default()
+        });
+
+        // Outage: nothing is lost, the events stay pending
+        assert!(streamer.deliver_pending().await.is_err());
+        assert!(streamer.deliver_pending().await.is_err());
+        let status = streamer.status().await;
+        assert_eq!(status.pending, 5);
+        assert_eq!(status.consecutive_failures, 2);
+
+        // Recovery delivers the backlog in order, in batches
+        assert_eq!(streamer.deliver_pending().await.unwrap(), 5);
+        let expected: Vec<String> = logger
+            .events_from(0, 10)
+            .await
+            .into_iter()
+            .map(|e| e.id)
+            .collect();
+        assert_eq!(*sink.received.lock().await, expected);
+
+        let status = streamer.status().await;
+        assert_eq!(status.delivered_position, 5);
+        assert_eq!(status.pending, 0);
+        assert_eq!(status.consecutive_failures, 0);
+
+        streamer.replay_from(3).await;
+        assert_eq!(streamer.deliver_pending().await.unwrap(), 2);
+    }
+
+    #[derive(Default)]
+    struct MemoryLedger {
+        positions: Mutex<HashMap<String, u64>>,
+    }
+
+    #[async_trait]
+    impl DeliveryLedger for MemoryLedger {
+        async fn claim(&self, sink: &str) -> Result<u64, SinkError> {
+            Ok(self.positions.lock().await.get(sink).copied().unwrap_or(0))
+        }
+
+        async fn record(&self, sink: &str, position: u64) -> Result<(), SinkError> {
+            self.positions
+                .lock()
+                .await
+                .insert(sink.to_string(), position);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_streamer_resumes_from_ledger() {
+        let logger = Arc::new(AuditLogger::new());
+        for i in 0..3 {
+            logger.log(event(&format!("event {}", i))).await;
+        }
+        let ledger = Arc::new(MemoryLedger::default());
+        ledger.record("flaky", 1).await.unwrap();
+        let sink = Arc::new(FlakySink {
+            outage: AtomicU32::new(0),
+            received: Mutex::new(Vec::new()),
+        });
+
+        let streamer =
+            Arc::new(AuditStreamer::new(logger.clone(), sink.clone()).with_ledger(ledger.clone()));
+        let task = streamer.clone().spawn();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while streamer.status().await.delivered_position < 3 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        task.abort();
+
+        // The event delivered before the restart is not sent again
+        assert_eq!(sink.received.lock().await.len(), 2);
+        assert_eq!(ledger.claim("flaky").await.unwrap(), 3);
+        let after = logger.events_after(1, 10).await.unwrap();
+        assert_eq!(
+            after.iter().map(|(p, _)| *p).collect::<Vec<_>>(),
+            vec![2, 3]
+        );
+    }
+}
@@ -0,0 +1,166 @@
+//! Detached signatures for exported JSON documents
+//!
+//! Documents handed to auditors, such as a schema version's provenance, carry
+//! a signature so they can be checked after they leave the registry. The
+//! signature covers the document's canonical JSON (object keys sorted), so a
+//! consumer that re-serializes the document in another key order can still
+//! verify it.
+
+use chrono::{DateTime, Utc};
+use jsonwebtoken::{crypto, Algorithm, DecodingKey, EncodingKey};
+use schema_registry_core::canonical_json;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+/// Shortest HMAC key accepted for signing
+pub const MIN_SIGNING_KEY_LEN: usize = 32;
+
+#[derive(Debug, Error)]
+pub enum SigningError {
+    #[error("Signing key must be at least {MIN_SIGNING_KEY_LEN} bytes")]
+    KeyTooShort,
+
+    #[error("Document cannot be serialized: {0}")]
+    Serialization(#[from] serde_json::Error),
+
+    #[error("Signing failed: {0}")]
+    Crypto(#[from] jsonwebtoken::errors::Error),
+}
+
+pub type Result<T> = std::result::Result<T, SigningError>;
+
+/// Signature over a document's canonical JSON
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DocumentSignature {
+    pub algorithm: Algorithm,
+    /// Identifies the key, so verifiers can pick it after a rotation
+    pub key_id: String,
+    /// Hex SHA-256 of the canonical document
+    pub digest: String,
+    /// Base64url signature of the canonical document
+    pub value: String,
+    pub signed_at: DateTime<Utc>,
+}
+
+/// Signs and verifies documents with one key
+pub struct DocumentSigner {
+    key_id: String,
+    algorithm: Algorithm,
+    encoding_key: EncodingKey,
+    decoding_key: DecodingKey,
+}
+
+impl DocumentSigner {
+    /// Sign with HMAC-SHA256
+    pub fn new_hs256(key_id: impl Into<String>, secret: &[u8]) -> Result<Self> {
+        if secret.len() < MIN_SIGNING_KEY_LEN {
+            return Err(SigningError::KeyTooShort);
+        }
+        Ok(Self {
+            key_id: key_id.into(),
+            algorithm: Algorithm::HS256,
+            encoding_key: EncodingKey::from_secret(secret),
+            decoding_key: DecodingKey::from_secret(secret),
+        })
+    }
+
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    pub fn sign<T: Serialize>(&self, document: &T) -> Result<DocumentSignature> {
+        let canonical = canonical_bytes(document)?;
+        Ok(DocumentSignature {
+            algorithm: self.algorithm,
+            key_id: self.key_id.clone(),
+            digest: hex::encode(Sha256::digest(&canonical)),
+            value: crypto::sign(&canonical, &self.encoding_key, self.algorithm)?,
+            signed_at: Utc::now(),
+        })
+    }
+
+    /// Whether `signature` was made by this key over `document` as it is now
+    pub fn verify<T: Serialize>(
+        &self,
+        document: &T,
+        signature: &DocumentSignature,
+    ) -> Result<bool> {
+        if signature.algorithm != self.algorithm || signature.key_id != self.key_id {
+            return Ok(false);
+        }
+        let canonical = canonical_bytes(document)?;
+        if hex::encode(Sha256::digest(&canonical)) != signature.digest {
+            return Ok(false);
+        }
+        Ok(crypto::verify(
+            &signature.value,
+            &canonical,
+            &self.decoding_key,
+            self.algorithm,
+        )?)
+    }
+}
+
+fn canonical_bytes<T: Serialize>(document: &T) -> Result<Vec<u8>> {
+    let value = serde_json::to_value(document)?;
+    Ok(canonical_json::to_string(&value)?.into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const KEY: &[u8] = b"provenance-signing-key-for-tests-0123456789";
+
+    #[test]
+    fn test_signature_verifies_regardless_of_key_order() {
+        let signer = DocumentSigner::new_hs256("test", KEY).unwrap();
+        let signature = signer
+            .sign(&json!({"b": 1, "a": {"d": 2, "c": 3}}))
+            .unwrap();
+
+        assert_eq!(signature.algorithm, Algorithm::HS256);
+        assert_eq!(signature.key_id, "test");
+        let reordered: serde_json::Value =
+            serde_json::from_str(r#"{"a": {"c": 3, "d": 2}, "b": 1}"#).unwrap();
+        assert!(signer.verify(&reordered, &signature).unwrap());
+    }
+
+    #[test]
+    fn test_tampered_document_fails_verification() {
+        let signer = DocumentSigner::new_hs256("test", KEY).unwrap();
+        let signature = signer.sign(&json!({"approved_by": "alice"})).unwrap();
+
+        assert!(!signer
+            .verify(&json!({"approved_by": "mallory"}), &signature)
+            .unwrap());
+
+        let mut forged = signature.clone();
+        forged.digest = hex::encode(Sha256::digest(br#"{"approved_by":"mallory"}"#));
+        assert!(!signer
+            .verify(&json!({"approved_by": "mallory"}), &forged)
+            .unwrap());
+    }
+
+    #[test]
+    fn test_other_key_fails_verification() {
+        let signer = DocumentSigner::new_hs256("test", KEY).unwrap();
+        let document = json!({"version": "1.0.0"});
+        let signature = signer.sign(&document).unwrap();
+
+        let other = DocumentSigner::new_hs256("test", &[7u8; 32]).unwrap();
+        assert!(!other.verify(&document, &signature).unwrap());
+        let rotated = DocumentSigner::new_hs256("next", KEY).unwrap();
+        assert!(!rotated.verify(&document, &signature).unwrap());
+    }
+
+    #[test]
+    fn test_short_key_is_rejected() {
+        assert!(matches!(
+            DocumentSigner::new_hs256("test", b"too short"),
+            Err(SigningError::KeyTooShort)
+        ));
+    }
+}
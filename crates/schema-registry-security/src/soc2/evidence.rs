@@ -129,6 +129,7 @@ pub enum EvidenceType {
     SchemaUpdates,
     SchemaValidations,
     SchemaDeletions,
+    CompatibilityCheckHistory,
     SchemaAccessLog,
 }
 
@@ -153,7 +154,10 @@ impl EvidenceType {
                 AuditEventType::PermissionRevoked,
             ],
             EvidenceType::AuthorizationDenials => vec![AuditEventType::AuthorizationDenied],
-            EvidenceType::ChangeRequests => vec![AuditEventType::ConfigurationChanged],
+            EvidenceType::ChangeRequests => vec![
+                AuditEventType::ConfigurationChanged,
+                AuditEventType::ChangeFreezeOverridden,
+            ],
             EvidenceType::SecurityAlerts => vec![
                 AuditEventType::SecurityViolation,
                 AuditEventType::SuspiciousActivity,
@@ -163,6 +167,10 @@ impl EvidenceType {
             EvidenceType::SchemaRegistrations => vec![AuditEventType::SchemaRegistered],
             EvidenceType::SchemaUpdates => vec![AuditEventType::SchemaUpdated],
             EvidenceType::SchemaDeletions => vec![AuditEventType::SchemaDeleted],
+            EvidenceType::CompatibilityCheckHistory => vec![
+                AuditEventType::CompatibilityChecked,
+                AuditEventType::CompatibilityExemptionApplied,
+            ],
             EvidenceType::PasswordChangeHistory => vec![AuditEventType::PasswordChanged],
             _ => vec![],
         }
@@ -181,6 +189,9 @@ impl EvidenceType {
             EvidenceType::BackupReports => "Backup execution and verification logs",
             EvidenceType::EncryptionReport => "Encryption status of data at rest and in transit",
             EvidenceType::DataDeletionLog => "PII and sensitive data deletion requests",
+            EvidenceType::CompatibilityCheckHistory => {
+                "Compatibility checks run before schema changes, with callers and outcomes"
+            }
             _ => "Compliance evidence",
         }
     }
@@ -370,6 +381,10 @@ impl EvidenceCollector {
             self.collect_evidence(EvidenceType::SchemaDeletions, date_range.clone())
                 .await?,
         );
+        evidence.push(
+            self.collect_evidence(EvidenceType::CompatibilityCheckHistory, date_range.clone())
+                .await?,
+        );
 
         Ok(evidence)
     }
@@ -0,0 +1,439 @@
+//! Self-service exchange of credentials for short-lived scoped tokens
+//!
+//! Automation such as CI pipelines shouldn't need a long-lived admin key to
+//! publish one team's schemas. A caller holding a parent credential can
+//! exchange it for a token restricted to some namespaces and permissions,
+//! which expires after minutes rather than months. A scoped token can itself
+//! be exchanged, but only for a narrower one.
+//!
+//! Every exchange is audited with the parent identity, so changes made with
+//! a scoped token trace back to whoever minted it.
+
+use crate::audit::{AuditEvent, AuditEventType, AuditLogger, AuditResult};
+use crate::auth::{AuthError, JwtManager, TokenClaims};
+use crate::freeze::FREEZE_OVERRIDE_PERMISSION;
+use crate::quarantine::SECURITY_REVIEW_PERMISSION;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+
+/// Permission to read schemas
+pub const SCHEMA_READ_PERMISSION: &str = "schema:read";
+
+/// Permission to register, update and retire schemas
+pub const SCHEMA_WRITE_PERMISSION: &str = "schema:write";
+
+/// Permissions a scoped token may carry
+pub const SCOPED_PERMISSIONS: [&str; 4] = [
+    SCHEMA_READ_PERMISSION,
+    SCHEMA_WRITE_PERMISSION,
+    FREEZE_OVERRIDE_PERMISSION,
+    SECURITY_REVIEW_PERMISSION,
+];
+
+// =============================================================================
+// Errors
+// =============================================================================
+
+#[derive(Debug, Error)]
+pub enum TokenExchangeError {
+    #[error("Token exchange is disabled")]
+    Disabled,
+
+    #[error("At least one namespace, and no blank ones, is required")]
+    NoNamespaces,
+
+    #[error("At least one permission is required")]
+    NoPermissions,
+
+    #[error("Unknown permission '{0}'")]
+    UnknownPermission(String),
+
+    #[error("The parent credential doesn't cover {0}")]
+    Escalation(String),
+
+    #[error("Requested TTL of {requested}s exceeds the maximum of {max}s")]
+    TtlTooLong { requested: u64, max: u64 },
+
+    #[error("Not a scoped token")]
+    NotScoped,
+
+    #[error(transparent)]
+    Auth(#[from] AuthError),
+}
+
+pub type Result<T> = std::result::Result<T, TokenExchangeError>;
+
+// =============================================================================
+// Configuration
+// =============================================================================
+
+#[derive(Debug, Clone)]
+pub struct TokenExchangeConfig {
+    /// Off by default
+    pub enabled: bool,
+    /// Lifetime of a token when the request doesn't ask for one
+    pub default_ttl: Duration,
+    /// Longest lifetime a caller may request
+    pub max_ttl: Duration,
+}
+
+impl Default for TokenExchangeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            default_ttl: Duration::from_secs(15 * 60),
+            max_ttl: Duration::from_secs(60 * 60),
+        }
+    }
+}
+
+// =============================================================================
+// Scopes
+// =============================================================================
+
+/// The namespaces and permissions a scoped token is restricted to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenScope {
+    /// Namespaces covered, each with its descendants
+    pub namespaces: Vec<String>,
+    pub permissions: Vec<String>,
+}
+
+impl TokenScope {
+    /// Whether `namespace` is one of the scope's namespaces or below one
+    pub fn allows_namespace(&self, namespace: &str) -> bool {
+        self.namespaces.iter().any(|scope| {
+            namespace == scope
+                || namespace
+                    .strip_prefix(scope.as_str())
+                    .is_some_and(|rest| rest.starts_with('.'))
+        })
+    }
+
+    pub fn allows_permission(&self, permission: &str) -> bool {
+        self.permissions.iter().any(|p| p == permission)
+    }
+
+    /// The scope of a token's claims, `None` for unscoped tokens
+    pub fn from_claims(claims: &TokenClaims) -> Option<Self> {
+        claims.namespaces.as_ref().map(|namespaces| Self {
+            namespaces: namespaces.clone(),
+            permissions: claims.permissions.clone(),
+        })
+    }
+}
+
+/// The credential presented for an exchange
+#[derive(Debug, Clone)]
+pub struct ParentCredential {
+    pub principal: String,
+    /// `None` for unrestricted credentials such as the admin token
+    pub scope: Option<TokenScope>,
+    /// ID of the parent token, when it is a scoped token
+    pub token_id: Option<String>,
+    /// When the parent token expires, as a Unix timestamp
+    pub expires_at: Option<u64>,
+}
+
+// =============================================================================
+// Requests and Grants
+// =============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenExchangeRequest {
+    pub namespaces: Vec<String>,
+    pub permissions: Vec<String>,
+    #[serde(default)]
+    pub ttl_seconds: Option<u64>,
+    /// Token from an external identity provider to exchange instead of the
+    /// caller's own credential (RFC 8693 `subject_token`)
+    ///
+    /// The exchanger ignores it; the caller resolves it into the parent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subject_token: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScopedTokenGrant {
+    pub token: String,
+    pub token_id: String,
+    /// Identity of the parent credential, which the token acts as
+    pub principal: String,
+    pub namespaces: Vec<String>,
+    pub permissions: Vec<String>,
+    /// Unix timestamp
+    pub expires_at: u64,
+}
+
+// =============================================================================
+// Token Exchanger
+// =============================================================================
+
+pub struct TokenExchanger {
+    config: TokenExchangeConfig,
+    jwt: Arc<JwtManager>,
+    audit_logger: Arc<AuditLogger>,
+}
+
+impl TokenExchanger {
+    pub fn new(
+        config: TokenExchangeConfig,
+        jwt: Arc<JwtManager>,
+        audit_logger: Arc<AuditLogger>,
+    ) -> Self {
+        Self {
+            config,
+            jwt,
+            audit_logger,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// Exchange `parent` for a token with the requested scope
+    ///
+    /// The scope must lie within the parent's, and the token never outlives
+    /// its parent.
+    pub async fn exchange(
+        &self,
+        parent: &ParentCredential,
+        request: TokenExchangeRequest,
+    ) -> Result<ScopedTokenGrant> {
+        if !self.config.enabled {
+            return Err(TokenExchangeError::Disabled);
+        }
+        if request.namespaces.is_empty() || request.namespaces.iter().any(|ns| ns.trim().is_empty())
+        {
+            return Err(TokenExchangeError::NoNamespaces);
+        }
+        if request.permissions.is_empty() {
+            return Err(TokenExchangeError::NoPermissions);
+        }
+        if let Some(unknown) = request
+            .permissions
+            .iter()
+            .find(|p| !SCOPED_PERMISSIONS.contains(&p.as_str()))
+        {
+            return Err(TokenExchangeError::UnknownPermission(unknown.clone()));
+        }
+        if let Some(scope) = &parent.scope {
+            if let Some(namespace) = request
+                .namespaces
+                .iter()
+                .find(|ns| !scope.allows_namespace(ns))
+            {
+                return Err(TokenExchangeError::Escalation(format!(
+                    "namespace '{}'",
+                    namespace
+                )));
+            }
+            if let Some(permission) = request
+                .permissions
+                .iter()
+                .find(|p| !scope.allows_permission(p))
+            {
+                return Err(TokenExchangeError::Escalation(format!(
+                    "permission '{}'",
+                    permission
+                )));
+            }
+        }
+
+        let max = self.config.max_ttl.as_secs();
+        let ttl = request
+            .ttl_seconds
+            .unwrap_or_else(|| self.config.default_ttl.as_secs());
+        if ttl > max {
+            return Err(TokenExchangeError::TtlTooLong {
+                requested: ttl,
+                max,
+            });
+        }
+
+        let mut namespaces = request.namespaces;
+        namespaces.sort();
+        namespaces.dedup();
+        let mut permissions = request.permissions;
+        permissions.sort();
+        permissions.dedup();
+
+        let mut claims = TokenClaims::new_access_token(
+            parent.principal.clone(),
+            None,
+            vec![],
+            permissions.clone(),
+        );
+        claims.exp = claims.iat + ttl;
+        if let Some(parent_expires_at) = parent.expires_at {
+            claims.exp = claims.exp.min(parent_expires_at);
+        }
+        claims.namespaces = Some(namespaces.clone());
+        let token = self.jwt.generate_token(&claims)?;
+
+        let mut event = AuditEvent::new(
+            AuditEventType::TokenGenerated,
+            "Scoped token issued".to_string(),
+            AuditResult::Success,
+            String::new(),
+        )
+        .with_user(parent.principal.clone(), None)
+        .with_resource("token".to_string(), claims.jti.clone())
+        .with_metadata("namespaces".to_string(), json!(namespaces))
+        .with_metadata("permissions".to_string(), json!(permissions))
+        .with_metadata("expires_at".to_string(), json!(claims.exp));
+        if let Some(parent_token) = &parent.token_id {
+            event = event.with_metadata("parent_token".to_string(), json!(parent_token));
+        }
+        self.audit_logger.log(event).await;
+
+        Ok(ScopedTokenGrant {
+            token,
+            token_id: claims.jti,
+            principal: parent.principal.clone(),
+            namespaces,
+            permissions,
+            expires_at: claims.exp,
+        })
+    }
+
+    /// Verify a scoped token
+    ///
+    /// Tokens stop working as soon as exchange is disabled, even if they
+    /// haven't expired yet.
+    pub async fn verify(&self, token: &str) -> Result<(TokenClaims, TokenScope)> {
+        if !self.config.enabled {
+            return Err(TokenExchangeError::Disabled);
+        }
+        let claims = self.jwt.verify_token(token).await?;
+        let scope = TokenScope::from_claims(&claims).ok_or(TokenExchangeError::NotScoped)?;
+        Ok((claims, scope))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::AuditEventFilter;
+    use crate::auth::TokenRevocationList;
+
+    fn exchanger() -> (TokenExchanger, Arc<AuditLogger>) {
+        let jwt = Arc::new(JwtManager::new_hs256(
+            b"test-secret-key-minimum-32-bytes-long",
+            Arc::new(TokenRevocationList::new()),
+        ));
+        let logger = Arc::new(AuditLogger::new());
+        let config = TokenExchangeConfig {
+            enabled: true,
+            ..Default::default()
+        };
+        (TokenExchanger::new(config, jwt, logger.clone()), logger)
+    }
+
+    fn admin() -> ParentCredential {
+        ParentCredential {
+            principal: "ci-admin".to_string(),
+            scope: None,
+            token_id: None,
+            expires_at: None,
+        }
+    }
+
+    fn request(namespaces: &[&str], permissions: &[&str]) -> TokenExchangeRequest {
+        TokenExchangeRequest {
+            namespaces: namespaces.iter().map(|s| s.to_string()).collect(),
+            permissions: permissions.iter().map(|s| s.to_string()).collect(),
+            ttl_seconds: None,
+            subject_token: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_exchange_is_scoped_and_audited() {
+        let (exchanger, logger) = exchanger();
+        let grant = exchanger
+            .exchange(&admin(), request(&["payments"], &[SCHEMA_WRITE_PERMISSION]))
+            .await
+            .unwrap();
+
+        let (claims, scope) = exchanger.verify(&grant.token).await.unwrap();
+        assert_eq!(claims.sub, "ci-admin");
+        assert_eq!(claims.exp, claims.iat + 15 * 60);
+        assert!(scope.allows_namespace("payments"));
+        assert!(scope.allows_namespace("payments.refunds"));
+        assert!(!scope.allows_namespace("payments-legacy"));
+        assert!(!scope.allows_permission(SCHEMA_READ_PERMISSION));
+
+        let events = logger.get_events(AuditEventFilter::default()).await;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event_type, AuditEventType::TokenGenerated);
+        assert_eq!(events[0].user_id.as_deref(), Some("ci-admin"));
+    }
+
+    #[tokio::test]
+    async fn test_scoped_parents_can_only_narrow() {
+        let (exchanger, _) = exchanger();
+        let parent = ParentCredential {
+            principal: "ci-admin".to_string(),
+            scope: Some(TokenScope {
+                namespaces: vec!["payments".to_string()],
+                permissions: vec![SCHEMA_READ_PERMISSION.to_string()],
+            }),
+            token_id: Some("parent-jti".to_string()),
+            expires_at: None,
+        };
+
+        let narrower = exchanger
+            .exchange(
+                &parent,
+                request(&["payments.refunds"], &[SCHEMA_READ_PERMISSION]),
+            )
+            .await;
+        assert!(narrower.is_ok());
+
+        let wider = exchanger
+            .exchange(&parent, request(&["billing"], &[SCHEMA_READ_PERMISSION]))
+            .await;
+        assert!(matches!(wider, Err(TokenExchangeError::Escalation(_))));
+
+        let stronger = exchanger
+            .exchange(&parent, request(&["payments"], &[SCHEMA_WRITE_PERMISSION]))
+            .await;
+        assert!(matches!(stronger, Err(TokenExchangeError::Escalation(_))));
+    }
+
+    #[tokio::test]
+    async fn test_constraints() {
+        let (exchanger, _) = exchanger();
+
+        let mut long = request(&["payments"], &[SCHEMA_READ_PERMISSION]);
+        long.ttl_seconds = Some(24 * 3600);
+        let result = exchanger.exchange(&admin(), long).await;
+        assert!(matches!(
+            result,
+            Err(TokenExchangeError::TtlTooLong { max: 3600, .. })
+        ));
+
+        let result = exchanger
+            .exchange(&admin(), request(&["payments"], &["admin:*"]))
+            .await;
+        assert!(matches!(
+            result,
+            Err(TokenExchangeError::UnknownPermission(_))
+        ));
+
+        let result = exchanger
+            .exchange(&admin(), request(&[], &[SCHEMA_READ_PERMISSION]))
+            .await;
+        assert!(matches!(result, Err(TokenExchangeError::NoNamespaces)));
+
+        let plain = TokenClaims::new_access_token("bob".to_string(), None, vec![], vec![]);
+        let token = exchanger.jwt.generate_token(&plain).unwrap();
+        let result = exchanger.verify(&token).await;
+        assert!(matches!(result, Err(TokenExchangeError::NotScoped)));
+    }
+}
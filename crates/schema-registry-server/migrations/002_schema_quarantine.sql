@@ -0,0 +1,5 @@
+-- Allow schemas to be held in quarantine pending security review
+
+ALTER TABLE schemas DROP CONSTRAINT IF EXISTS schemas_state_check;
+ALTER TABLE schemas ADD CONSTRAINT schemas_state_check
+    CHECK (state IN ('DRAFT', 'ACTIVE', 'DEPRECATED', 'ARCHIVED', 'DELETED', 'QUARANTINED'));
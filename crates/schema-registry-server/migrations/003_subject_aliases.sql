@@ -0,0 +1,13 @@
+-- Keep old subject names resolving after a rename
+
+CREATE TABLE IF NOT EXISTS subject_aliases (
+    alias_namespace VARCHAR(255) NOT NULL,
+    alias_name VARCHAR(255) NOT NULL,
+    target_namespace VARCHAR(255) NOT NULL,
+    target_name VARCHAR(255) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_by VARCHAR(255),
+    PRIMARY KEY (alias_namespace, alias_name)
+);
+
+CREATE INDEX idx_subject_aliases_target ON subject_aliases(target_namespace, target_name);
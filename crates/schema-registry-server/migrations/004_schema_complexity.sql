@@ -0,0 +1,7 @@
+-- Size and complexity metrics measured at registration time
+-- Rows registered before this migration keep NULL metrics
+
+ALTER TABLE schemas ADD COLUMN IF NOT EXISTS size_bytes BIGINT;
+ALTER TABLE schemas ADD COLUMN IF NOT EXISTS field_count INT;
+ALTER TABLE schemas ADD COLUMN IF NOT EXISTS max_depth INT;
+ALTER TABLE schemas ADD COLUMN IF NOT EXISTS constraint_count INT;
@@ -0,0 +1,23 @@
+-- Keep every compatibility check instead of one row per schema pair and mode
+
+DO $$
+DECLARE
+    constraint_name TEXT;
+BEGIN
+    FOR constraint_name IN
+        SELECT conname FROM pg_constraint
+        WHERE conrelid = 'compatibility_checks'::regclass AND contype = 'u'
+    LOOP
+        EXECUTE format('ALTER TABLE compatibility_checks DROP CONSTRAINT %I', constraint_name);
+    END LOOP;
+END $$;
+
+ALTER TABLE compatibility_checks ADD COLUMN IF NOT EXISTS subject VARCHAR(511);
+ALTER TABLE compatibility_checks ADD COLUMN IF NOT EXISTS caller VARCHAR(255);
+ALTER TABLE compatibility_checks ADD COLUMN IF NOT EXISTS schema_version VARCHAR(50);
+ALTER TABLE compatibility_checks ADD COLUMN IF NOT EXISTS compared_version VARCHAR(50);
+ALTER TABLE compatibility_checks ADD COLUMN IF NOT EXISTS exempted_violations JSONB DEFAULT '[]';
+ALTER TABLE compatibility_checks ADD COLUMN IF NOT EXISTS latency_ms BIGINT;
+
+CREATE INDEX IF NOT EXISTS idx_compat_subject_checked_at ON compatibility_checks(subject, checked_at DESC);
+CREATE INDEX IF NOT EXISTS idx_compat_checked_at ON compatibility_checks(checked_at DESC);
@@ -0,0 +1,15 @@
+-- Compatibility checks and validations that took longer than the slow-operation threshold
+
+CREATE TABLE IF NOT EXISTS slow_operations (
+    id UUID PRIMARY KEY,
+    operation VARCHAR(50) NOT NULL,
+    schema_id UUID NOT NULL,
+    subject VARCHAR(511),
+    schema_size_bytes BIGINT NOT NULL,
+    compatibility_mode VARCHAR(50),
+    duration_ms BIGINT NOT NULL,
+    recorded_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_slow_operations_recorded_at ON slow_operations(recorded_at DESC);
+CREATE INDEX IF NOT EXISTS idx_slow_operations_schema_id ON slow_operations(schema_id, recorded_at DESC);
@@ -0,0 +1,19 @@
+-- Per-subject counter for auto-assigned versions
+--
+-- The row is bumped inside the registering transaction, so its row lock
+-- serializes concurrent registrations of one subject and a rolled-back
+-- registration gives its number back.
+
+CREATE TABLE IF NOT EXISTS subject_version_sequences (
+    namespace VARCHAR(255) NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    last_version INT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (namespace, name)
+);
+
+INSERT INTO subject_version_sequences (namespace, name, last_version)
+SELECT namespace, name, MAX(version_major)
+FROM schemas
+GROUP BY namespace, name
+ON CONFLICT (namespace, name) DO NOTHING;
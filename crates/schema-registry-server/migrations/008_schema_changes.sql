@@ -0,0 +1,36 @@
+-- Change feed of the schemas table, read by clients to invalidate their caches
+--
+-- A trigger appends every insert, update and delete, so no write path can skip
+-- the feed. The advisory lock is held until the writing transaction ends, so
+-- sequence numbers become visible in order and a reader that has seen a
+-- sequence has seen every earlier one.
+
+CREATE TABLE IF NOT EXISTS schema_changes (
+    sequence BIGSERIAL PRIMARY KEY,
+    schema_id UUID NOT NULL,
+    namespace VARCHAR(255) NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    change VARCHAR(16) NOT NULL CHECK (change IN ('CREATED', 'UPDATED', 'DELETED')),
+    changed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE OR REPLACE FUNCTION record_schema_change()
+RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM pg_advisory_xact_lock(hashtext('schema_changes'));
+    IF TG_OP = 'DELETE' THEN
+        INSERT INTO schema_changes (schema_id, namespace, name, change)
+        VALUES (OLD.id, OLD.namespace, OLD.name, 'DELETED');
+        RETURN OLD;
+    END IF;
+    INSERT INTO schema_changes (schema_id, namespace, name, change)
+    VALUES (NEW.id, NEW.namespace, NEW.name, CASE TG_OP WHEN 'INSERT' THEN 'CREATED' ELSE 'UPDATED' END);
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS record_schemas_change ON schemas;
+CREATE TRIGGER record_schemas_change
+    AFTER INSERT OR UPDATE OR DELETE ON schemas
+    FOR EACH ROW
+    EXECUTE FUNCTION record_schema_change();
@@ -0,0 +1,13 @@
+-- Fields each consumer of a subject reads, and where to tell it they changed
+
+CREATE TABLE IF NOT EXISTS consumer_field_manifests (
+    namespace VARCHAR(255) NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    consumer VARCHAR(255) NOT NULL,
+    webhook_url TEXT NOT NULL,
+    fields TEXT[] NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_by VARCHAR(255),
+    PRIMARY KEY (namespace, name, consumer)
+);
@@ -0,0 +1,10 @@
+-- Content hashes are unique within a subject, not across the registry
+--
+-- Registration only deduplicates content within its subject, so the same
+-- document registered under two subjects is two versions rather than a
+-- constraint violation.
+
+ALTER TABLE schemas DROP CONSTRAINT IF EXISTS schemas_content_hash_key;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_schemas_subject_content_hash
+    ON schemas(namespace, name, content_hash);
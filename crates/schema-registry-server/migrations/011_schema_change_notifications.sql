@@ -0,0 +1,22 @@
+-- Announce schema changes on the schema_changes notification channel
+--
+-- Replicas listen on the channel to wake requests waiting for a version to
+-- change state. Postgres delivers notifications when the writing transaction
+-- commits, so a woken waiter reads the committed row.
+
+CREATE OR REPLACE FUNCTION record_schema_change()
+RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM pg_advisory_xact_lock(hashtext('schema_changes'));
+    IF TG_OP = 'DELETE' THEN
+        INSERT INTO schema_changes (schema_id, namespace, name, change)
+        VALUES (OLD.id, OLD.namespace, OLD.name, 'DELETED');
+        PERFORM pg_notify('schema_changes', OLD.id::text);
+        RETURN OLD;
+    END IF;
+    INSERT INTO schema_changes (schema_id, namespace, name, change)
+    VALUES (NEW.id, NEW.namespace, NEW.name, CASE TG_OP WHEN 'INSERT' THEN 'CREATED' ELSE 'UPDATED' END);
+    PERFORM pg_notify('schema_changes', NEW.id::text);
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,10 @@
+-- Integer IDs for the Confluent-compatible API
+--
+-- Kafka serializers put a 4-byte schema ID in front of every message, so each
+-- version gets a monotonically assigned integer next to its UUID. Existing
+-- versions are numbered in no particular order. The column takes explicit
+-- values so that restoring a snapshot keeps the IDs messages already carry.
+
+ALTER TABLE schemas ADD COLUMN IF NOT EXISTS global_id BIGINT GENERATED BY DEFAULT AS IDENTITY;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_schemas_global_id ON schemas(global_id);
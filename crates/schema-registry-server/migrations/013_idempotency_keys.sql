@@ -0,0 +1,20 @@
+-- Results of registrations sent with an Idempotency-Key
+--
+-- A retried registration carrying a key already used by the same principal
+-- gets the stored response back, headers included, rather than registering
+-- again. Keys are only accepted with credentials, so one caller never gets
+-- another's response. A row without a status belongs to a registration still
+-- running. Rows are kept for a day.
+
+CREATE TABLE IF NOT EXISTS idempotency_keys (
+    principal TEXT NOT NULL,
+    idempotency_key VARCHAR(255) NOT NULL,
+    request_hash VARCHAR(64) NOT NULL,
+    response_status SMALLINT,
+    response_headers JSONB,
+    response_body BYTEA,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (principal, idempotency_key)
+);
+
+CREATE INDEX IF NOT EXISTS idx_idempotency_keys_created_at ON idempotency_keys(created_at);
@@ -0,0 +1,17 @@
+-- Time-boxed compatibility exemptions, shared by every replica
+
+CREATE TABLE IF NOT EXISTS compatibility_exemptions (
+    id UUID PRIMARY KEY,
+    subject VARCHAR(511) NOT NULL,
+    violation_type VARCHAR(100) NOT NULL,
+    reason TEXT NOT NULL,
+    granted_by VARCHAR(255) NOT NULL,
+    granted_at TIMESTAMPTZ NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    revoked_at TIMESTAMPTZ,
+    revoked_by VARCHAR(255)
+);
+
+CREATE INDEX IF NOT EXISTS idx_compat_exemptions_subject ON compatibility_exemptions(subject, expires_at)
+    WHERE revoked_at IS NULL;
+CREATE INDEX IF NOT EXISTS idx_compat_exemptions_granted_at ON compatibility_exemptions(granted_at);
@@ -0,0 +1,25 @@
+-- Subject versions each consumer is pinned to, and the nightly reports of
+-- which pins the next release would break
+
+CREATE TABLE IF NOT EXISTS consumer_manifests (
+    consumer_id UUID PRIMARY KEY,
+    consumer_name VARCHAR(255) NOT NULL,
+    owner VARCHAR(255),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_by VARCHAR(255)
+);
+
+CREATE TABLE IF NOT EXISTS consumer_pins (
+    consumer_id UUID NOT NULL REFERENCES consumer_manifests(consumer_id) ON DELETE CASCADE,
+    namespace VARCHAR(255) NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    pinned_version VARCHAR(50) NOT NULL,
+    PRIMARY KEY (consumer_id, namespace, name)
+);
+
+CREATE TABLE IF NOT EXISTS consumer_breakage_reports (
+    namespace VARCHAR(255) NOT NULL,
+    generated_at TIMESTAMPTZ NOT NULL,
+    report JSONB NOT NULL,
+    PRIMARY KEY (namespace, generated_at)
+);
@@ -0,0 +1,9 @@
+-- Explicit settings of namespaces and subjects, kept in memory by every
+-- replica and reloaded when the namespace_settings channel is notified
+
+CREATE TABLE IF NOT EXISTS namespace_settings (
+    namespace TEXT PRIMARY KEY,
+    settings JSONB NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_by VARCHAR(255)
+);
@@ -0,0 +1,16 @@
+-- Audit events of every replica, streamed to the SIEM sinks from here so
+-- delivery survives restarts
+
+CREATE TABLE IF NOT EXISTS audit_events (
+    seq BIGSERIAL PRIMARY KEY,
+    event_id TEXT NOT NULL UNIQUE,
+    event JSONB NOT NULL,
+    stored_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Last event each sink received
+CREATE TABLE IF NOT EXISTS audit_sink_positions (
+    sink TEXT PRIMARY KEY,
+    delivered_seq BIGINT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
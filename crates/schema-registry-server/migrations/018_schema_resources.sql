@@ -0,0 +1,13 @@
+-- Declarative schema resources, and the changes watchers read from
+
+CREATE TABLE IF NOT EXISTS schema_resources (
+    name TEXT PRIMARY KEY,
+    resource JSONB NOT NULL
+);
+
+-- The most recent changes, keyed by the resourceVersion they produced
+CREATE TABLE IF NOT EXISTS schema_resource_events (
+    resource_version BIGINT PRIMARY KEY,
+    event JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
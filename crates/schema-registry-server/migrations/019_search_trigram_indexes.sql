@@ -0,0 +1,12 @@
+-- Trigram indexes for keyword search
+--
+-- Search matches `%term%` against these columns, which a B-tree index can't
+-- serve. With trigram indexes a term of three or more characters is looked up
+-- instead of scanning every schema.
+
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+CREATE INDEX IF NOT EXISTS idx_schemas_name_trgm ON schemas USING GIN (name gin_trgm_ops);
+CREATE INDEX IF NOT EXISTS idx_schemas_namespace_trgm ON schemas USING GIN (namespace gin_trgm_ops);
+CREATE INDEX IF NOT EXISTS idx_schemas_description_trgm ON schemas USING GIN (description gin_trgm_ops);
+CREATE INDEX IF NOT EXISTS idx_schemas_content_trgm ON schemas USING GIN (content gin_trgm_ops);
@@ -0,0 +1,17 @@
+-- Requests to hand a namespace to another team, pending and decided
+
+CREATE TABLE IF NOT EXISTS ownership_transfers (
+    id UUID PRIMARY KEY,
+    namespace TEXT NOT NULL,
+    status VARCHAR(20) NOT NULL,
+    requested_at TIMESTAMPTZ NOT NULL,
+    deadline TIMESTAMPTZ NOT NULL,
+    transfer JSONB NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_ownership_transfers_namespace
+    ON ownership_transfers (namespace, requested_at DESC);
+
+-- Pending transfers are swept for expiry
+CREATE INDEX IF NOT EXISTS idx_ownership_transfers_pending
+    ON ownership_transfers (deadline) WHERE status = 'pending';
@@ -0,0 +1,77 @@
+-- Stored version numbers and wire-sized IDs for the Confluent-compatible API
+--
+-- Confluent clients expect a subject's version numbers never to change, so
+-- each version keeps the number it was given when registered instead of its
+-- position among the subject's versions. Numbers are taken from a per-subject
+-- counter that only goes up, so a new minor version or a permanently deleted
+-- version doesn't renumber the others and a deleted number is not reused.
+-- Existing versions keep the positions they were served under so far.
+--
+-- Serializers put the global ID in 4 bytes in front of every message, so it
+-- may not grow past a signed 32-bit integer.
+
+ALTER TABLE schemas ADD COLUMN IF NOT EXISTS confluent_version INT;
+
+UPDATE schemas
+SET confluent_version = numbered.position
+FROM (
+    SELECT id, ROW_NUMBER() OVER (
+        PARTITION BY namespace, name
+        ORDER BY version_major, version_minor, version_patch
+    ) AS position
+    FROM schemas
+) AS numbered
+WHERE schemas.id = numbered.id AND schemas.confluent_version IS NULL;
+
+ALTER TABLE schemas ALTER COLUMN confluent_version SET NOT NULL;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_schemas_subject_confluent_version
+    ON schemas(namespace, name, confluent_version);
+
+CREATE TABLE IF NOT EXISTS confluent_version_sequences (
+    namespace VARCHAR(255) NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    last_version INT NOT NULL,
+    PRIMARY KEY (namespace, name)
+);
+
+INSERT INTO confluent_version_sequences (namespace, name, last_version)
+SELECT namespace, name, MAX(confluent_version)
+FROM schemas
+GROUP BY namespace, name
+ON CONFLICT (namespace, name) DO NOTHING;
+
+-- Number every inserted version, however it is registered. Bumping the
+-- counter row locks it until the transaction ends, so concurrent
+-- registrations of one subject get consecutive numbers, and a rolled-back
+-- registration gives its number back. An explicit number, e.g. from a
+-- restored snapshot, is kept and moves the counter past it.
+CREATE OR REPLACE FUNCTION assign_confluent_version()
+RETURNS TRIGGER AS $$
+DECLARE
+    assigned INT;
+BEGIN
+    INSERT INTO confluent_version_sequences (namespace, name, last_version)
+    VALUES (NEW.namespace, NEW.name, COALESCE(NEW.confluent_version, 1))
+    ON CONFLICT (namespace, name) DO UPDATE
+    SET last_version = CASE
+        WHEN NEW.confluent_version IS NULL THEN confluent_version_sequences.last_version + 1
+        ELSE GREATEST(confluent_version_sequences.last_version, NEW.confluent_version)
+    END
+    RETURNING last_version INTO assigned;
+    NEW.confluent_version := COALESCE(NEW.confluent_version, assigned);
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS assign_schemas_confluent_version ON schemas;
+CREATE TRIGGER assign_schemas_confluent_version
+    BEFORE INSERT ON schemas
+    FOR EACH ROW
+    EXECUTE FUNCTION assign_confluent_version();
+
+ALTER TABLE schemas ALTER COLUMN global_id SET MAXVALUE 2147483647;
+
+ALTER TABLE schemas DROP CONSTRAINT IF EXISTS schemas_global_id_wire_range;
+ALTER TABLE schemas ADD CONSTRAINT schemas_global_id_wire_range
+    CHECK (global_id BETWEEN 1 AND 2147483647);
@@ -0,0 +1,11 @@
+-- Content hashes are unique among a subject's live versions only
+--
+-- A deleted version stays as a tombstone so its number isn't reused, but
+-- registering its content again makes a new version rather than returning
+-- the tombstone.
+
+DROP INDEX IF EXISTS idx_schemas_subject_content_hash;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_schemas_subject_live_content_hash
+    ON schemas(namespace, name, content_hash)
+    WHERE state <> 'DELETED';
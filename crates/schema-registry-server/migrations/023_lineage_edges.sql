@@ -0,0 +1,30 @@
+-- Lineage edges recorded by admins, from a schema to another schema or to an
+-- application, pipeline or model
+--
+-- Schemas aren't referenced by foreign key, so an edge outlives a permanent
+-- delete and is back when the delete is undone; reads skip edges whose
+-- schema is gone.
+
+CREATE TABLE IF NOT EXISTS lineage_edges (
+    id BIGSERIAL PRIMARY KEY,
+    from_schema_id UUID NOT NULL,
+    to_schema_id UUID,
+    entity JSONB,
+    relation VARCHAR(30) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_by TEXT NOT NULL,
+    CONSTRAINT lineage_edges_one_target CHECK ((to_schema_id IS NULL) <> (entity IS NULL))
+);
+
+-- A schema has one edge to each schema or entity; recording it again keeps
+-- the first
+CREATE UNIQUE INDEX IF NOT EXISTS idx_lineage_edges_schema_target
+    ON lineage_edges (from_schema_id, to_schema_id) WHERE to_schema_id IS NOT NULL;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_lineage_edges_entity_target
+    ON lineage_edges (from_schema_id, (entity->>'entity_type'), (entity->>'id'))
+    WHERE entity IS NOT NULL;
+
+-- Dependents are looked up by the schema they point at
+CREATE INDEX IF NOT EXISTS idx_lineage_edges_to_schema
+    ON lineage_edges (to_schema_id) WHERE to_schema_id IS NOT NULL;
@@ -0,0 +1,38 @@
+-- Confluent version counters start past the versions a subject already has
+--
+-- A subject's rows can reach it without its counter, e.g. when a renamed
+-- subject's versions move to the new name, so a missing or lagging counter
+-- starts from the highest number already stored, as `allocate_version` does
+-- for auto-assigned versions.
+
+CREATE OR REPLACE FUNCTION assign_confluent_version()
+RETURNS TRIGGER AS $$
+DECLARE
+    assigned INT;
+BEGIN
+    INSERT INTO confluent_version_sequences (namespace, name, last_version)
+    VALUES (
+        NEW.namespace,
+        NEW.name,
+        COALESCE(
+            NEW.confluent_version,
+            1 + COALESCE(
+                (SELECT MAX(confluent_version) FROM schemas
+                 WHERE namespace = NEW.namespace AND name = NEW.name),
+                0
+            )
+        )
+    )
+    ON CONFLICT (namespace, name) DO UPDATE
+    SET last_version = CASE
+        WHEN NEW.confluent_version IS NULL THEN GREATEST(
+            confluent_version_sequences.last_version,
+            EXCLUDED.last_version - 1
+        ) + 1
+        ELSE GREATEST(confluent_version_sequences.last_version, NEW.confluent_version)
+    END
+    RETURNING last_version INTO assigned;
+    NEW.confluent_version := COALESCE(NEW.confluent_version, assigned);
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
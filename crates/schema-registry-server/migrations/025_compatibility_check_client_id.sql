@@ -0,0 +1,6 @@
+-- The client ID a compatibility check's caller reported about itself
+--
+-- `caller` holds the authenticated principal; the unverified `X-Client-Id`
+-- header is kept apart from it.
+
+ALTER TABLE compatibility_checks ADD COLUMN IF NOT EXISTS client_id VARCHAR(255);
@@ -0,0 +1,8 @@
+-- Change freeze windows set through the admin API, shared by every replica;
+-- windows from the change freeze file stay in each replica's memory
+
+CREATE TABLE IF NOT EXISTS freeze_windows (
+    name TEXT PRIMARY KEY,
+    window_definition JSONB NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,149 @@
+//! Durable audit log for SIEM streaming
+//!
+//! Each replica's in-memory [`AuditLogger`] is relayed into the `audit_events`
+//! table, and the SIEM sinks stream from that table instead of the logger, so
+//! events logged before a restart are still delivered and every replica's
+//! events reach the SIEM. How far each sink got is kept in
+//! `audit_sink_positions`, and a sink is streamed by one replica at a time,
+//! the one holding its advisory lock.
+//!
+//! [`AuditLogger`]: schema_registry_security::AuditLogger
+
+use async_trait::async_trait;
+use schema_registry_security::audit::AuditEvent;
+use schema_registry_security::siem::{AuditSink, AuditSource, DeliveryLedger, SinkError};
+use sqlx::pool::PoolConnection;
+use sqlx::{PgPool, Postgres};
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+/// Name of the relay from the in-memory logger into the table
+pub const RELAY_SINK: &str = "audit_store";
+
+/// How long an event must have been stored before it is streamed
+///
+/// Sequence numbers are taken when a replica's insert starts, so an insert
+/// that commits late can land behind events already streamed. Holding events
+/// back until concurrent inserts have committed keeps them from being skipped.
+const SETTLE_DELAY: Duration = Duration::from_secs(5);
+
+/// How often [`AuditSource::wait_for_events`] checks the table again
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The `audit_events` table as a sink, a source and a delivery ledger
+pub struct PostgresAuditLog {
+    db: PgPool,
+    /// Connections holding the advisory lock of each claimed sink
+    claims: Mutex<HashMap<String, PoolConnection<Postgres>>>,
+}
+
+impl PostgresAuditLog {
+    pub fn new(db: PgPool) -> Self {
+        Self {
+            db,
+            claims: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+fn storage_error(e: impl std::fmt::Display) -> SinkError {
+    SinkError::Source(e.to_string())
+}
+
+#[async_trait]
+impl AuditSink for PostgresAuditLog {
+    fn name(&self) -> &str {
+        RELAY_SINK
+    }
+
+    async fn send(&self, events: &[AuditEvent]) -> Result<(), SinkError> {
+        let events = serde_json::to_value(events).map_err(storage_error)?;
+        sqlx::query(
+            "INSERT INTO audit_events (event_id, event) \
+             SELECT e->>'id', e FROM jsonb_array_elements($1) AS e \
+             ON CONFLICT (event_id) DO NOTHING",
+        )
+        .bind(events)
+        .execute(&self.db)
+        .await
+        .map_err(|e| SinkError::Transport(e.to_string()))?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl AuditSource for PostgresAuditLog {
+    async fn events_after(
+        &self,
+        position: u64,
+        limit: usize,
+    ) -> Result<Vec<(u64, AuditEvent)>, SinkError> {
+        let rows: Vec<(i64, serde_json::Value)> = sqlx::query_as(
+            "SELECT seq, event FROM audit_events \
+             WHERE seq > $1 AND stored_at <= NOW() - make_interval(secs => $2) \
+             ORDER BY seq LIMIT $3",
+        )
+        .bind(position as i64)
+        .bind(SETTLE_DELAY.as_secs_f64())
+        .bind(limit as i64)
+        .fetch_all(&self.db)
+        .await
+        .map_err(storage_error)?;
+        rows.into_iter()
+            .map(|(seq, event)| {
+                Ok((
+                    seq as u64,
+                    serde_json::from_value(event).map_err(storage_error)?,
+                ))
+            })
+            .collect()
+    }
+
+    async fn count_after(&self, position: u64) -> Result<u64, SinkError> {
+        let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM audit_events WHERE seq > $1")
+            .bind(position as i64)
+            .fetch_one(&self.db)
+            .await
+            .map_err(storage_error)?;
+        Ok(count as u64)
+    }
+
+    async fn wait_for_events(&self, _position: u64) {
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+#[async_trait]
+impl DeliveryLedger for PostgresAuditLog {
+    async fn claim(&self, sink: &str) -> Result<u64, SinkError> {
+        let mut conn = self.db.acquire().await.map_err(storage_error)?;
+        sqlx::query("SELECT pg_advisory_lock(hashtext('audit_sink:' || $1))")
+            .bind(sink)
+            .execute(&mut *conn)
+            .await
+            .map_err(storage_error)?;
+        let position: Option<(i64,)> =
+            sqlx::query_as("SELECT delivered_seq FROM audit_sink_positions WHERE sink = $1")
+                .bind(sink)
+                .fetch_optional(&mut *conn)
+                .await
+                .map_err(storage_error)?;
+        self.claims.lock().await.insert(sink.to_string(), conn);
+        Ok(position.map(|(seq,)| seq as u64).unwrap_or(0))
+    }
+
+    async fn record(&self, sink: &str, position: u64) -> Result<(), SinkError> {
+        sqlx::query(
+            "INSERT INTO audit_sink_positions (sink, delivered_seq) VALUES ($1, $2) \
+             ON CONFLICT (sink) DO UPDATE \
+             SET delivered_seq = EXCLUDED.delivered_seq, updated_at = NOW()",
+        )
+        .bind(sink)
+        .bind(position as i64)
+        .execute(&self.db)
+        .await
+        .map_err(storage_error)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,143 @@
+//! Fan-out of schema changes to requests waiting on them
+//!
+//! The trigger that fills the `schema_changes` feed also sends each changed
+//! schema's ID on the `schema_changes` Postgres notification channel, so every
+//! write is announced whichever replica made it. Each replica runs one
+//! listener relaying the IDs to a broadcast channel, and a request waiting for
+//! a version to change state wakes as soon as it does rather than polling.
+//!
+//! Notifications sent while the listener reconnects are lost, so waiters also
+//! re-read what they wait on every [`RECHECK_INTERVAL`].
+
+use sqlx::postgres::PgListener;
+use sqlx::PgPool;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::{self, error::RecvError};
+use uuid::Uuid;
+
+/// Postgres channel the `schema_changes` trigger notifies
+pub const CHANNEL: &str = "schema_changes";
+
+/// How often waiters re-read what they wait on without a notification
+pub const RECHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Changes a slow subscriber may fall behind by before it's told it lagged
+const CAPACITY: usize = 1024;
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+/// IDs of changed schemas, relayed to every subscriber
+pub struct ChangeBus {
+    sender: broadcast::Sender<Uuid>,
+}
+
+impl ChangeBus {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(CAPACITY);
+        Self { sender }
+    }
+
+    /// Tell subscribers `schema_id` changed
+    pub fn publish(&self, schema_id: Uuid) {
+        // No subscribers is the usual case, not an error
+        let _ = self.sender.send(schema_id);
+    }
+
+    /// Subscribe before reading what to wait on, so a change made in between
+    /// isn't missed
+    pub fn subscribe(&self) -> ChangeSubscription {
+        ChangeSubscription {
+            receiver: self.sender.subscribe(),
+        }
+    }
+
+    /// Relay the `schema_changes` channel until the process exits,
+    /// reconnecting with backoff when the connection drops
+    pub fn spawn_listener(self: Arc<Self>, db: PgPool) {
+        tokio::spawn(async move {
+            let mut delay = RECONNECT_DELAY;
+            loop {
+                if let Err(e) = self.relay(&db, &mut delay).await {
+                    tracing::warn!(
+                        error = %e,
+                        retry_in_ms = delay.as_millis() as u64,
+                        "Schema change listener disconnected"
+                    );
+                }
+                tokio::time::sleep(delay).await;
+                delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+            }
+        });
+    }
+
+    async fn relay(&self, db: &PgPool, delay: &mut Duration) -> Result<(), sqlx::Error> {
+        let mut listener = PgListener::connect_with(db).await?;
+        listener.listen(CHANNEL).await?;
+        *delay = RECONNECT_DELAY;
+        loop {
+            let notification = listener.recv().await?;
+            match notification.payload().parse::<Uuid>() {
+                Ok(schema_id) => self.publish(schema_id),
+                Err(_) => tracing::debug!(
+                    payload = notification.payload(),
+                    "Ignoring malformed schema change notification"
+                ),
+            }
+        }
+    }
+}
+
+impl Default for ChangeBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// One waiter's view of the bus
+pub struct ChangeSubscription {
+    receiver: broadcast::Receiver<Uuid>,
+}
+
+impl ChangeSubscription {
+    /// Wait up to `timeout` for `schema_id` to change
+    ///
+    /// Returns `true` when it may have changed: it was announced, or the
+    /// subscription fell behind and can't tell.
+    pub async fn changed(&mut self, schema_id: Uuid, timeout: Duration) -> bool {
+        let wait = async {
+            loop {
+                match self.receiver.recv().await {
+                    Ok(id) if id == schema_id => return true,
+                    Ok(_) => {}
+                    Err(RecvError::Lagged(_)) => return true,
+                    Err(RecvError::Closed) => return std::future::pending().await,
+                }
+            }
+        };
+        tokio::time::timeout(timeout, wait).await.unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_subscription_wakes_for_its_schema_only() {
+        let bus = ChangeBus::new();
+        let watched = Uuid::new_v4();
+        let mut subscription = bus.subscribe();
+
+        bus.publish(Uuid::new_v4());
+        let changed = subscription
+            .changed(watched, Duration::from_millis(20))
+            .await;
+        assert!(!changed);
+
+        bus.publish(Uuid::new_v4());
+        bus.publish(watched);
+        assert!(subscription.changed(watched, Duration::from_secs(1)).await);
+    }
+}
@@ -0,0 +1,636 @@
+//! Confluent-compatible API
+//!
+//! Serves Kafka serializers and tools written for Confluent Schema
+//! Registry from the registry's own subjects and versions.
+
+use super::*;
+use llm_schema_api::confluent::{
+    self, ConfluentCompatibilityQuery, ConfluentConfigQuery, ConfluentConfigRequest,
+    ConfluentError, ConfluentSchema, ConfluentSchemaById, ConfluentSchemaRequest, VersionId,
+};
+
+/// Errors of the native API keep their status; the error code is the status
+/// followed by `01`, as Confluent does for errors without a code of their own
+impl From<AppError> for ConfluentError {
+    fn from(e: AppError) -> Self {
+        if let AppError::InvalidInput(message) = e {
+            return Self::invalid_schema(message);
+        }
+        let message = e.to_string();
+        let status = e.into_response().status();
+        Self::new(status, u32::from(status.as_u16()) * 100 + 1, message)
+    }
+}
+
+/// Parse a schema request body, reporting problems the way Confluent does
+fn parse_confluent_request(
+    state: &AppState,
+    body: &[u8],
+) -> Result<ConfluentSchemaRequest, ConfluentError> {
+    let req: ConfluentSchemaRequest = bounded_json::from_slice(body, &state.json_limits)
+        .map_err(|e| ConfluentError::from(AppError::from(e)))?;
+    if !req.references.is_empty() {
+        return Err(ConfluentError::invalid_schema(
+            "Schema references are not supported".to_string(),
+        ));
+    }
+    Ok(req)
+}
+
+/// A version of a subject, numbered the way the Confluent API numbers it
+struct ConfluentVersion {
+    id: Uuid,
+    global_id: i64,
+    /// Number given when the version was stored, from 1; never reused
+    number: i32,
+    version: SemanticVersion,
+    format: String,
+    content: String,
+    content_hash: String,
+    state: String,
+}
+
+impl ConfluentVersion {
+    /// Deleted and quarantined versions keep their number but aren't served
+    fn is_servable(&self) -> bool {
+        !matches!(self.state.as_str(), "DELETED" | "QUARANTINED")
+    }
+
+    fn to_schema(&self, subject: &str) -> Result<ConfluentSchema, ConfluentError> {
+        Ok(ConfluentSchema {
+            subject: subject.to_string(),
+            id: confluent::wire_id(self.global_id)?,
+            version: self.number,
+            schema_type: confluent::schema_type(&self.format),
+            schema: self.content.clone(),
+        })
+    }
+}
+
+/// Every version of a subject, in the order they were stored
+///
+/// Each version keeps the number it was given when stored, whichever API
+/// registered it, so adding a minor version or permanently deleting one
+/// doesn't renumber the others.
+async fn confluent_versions(
+    state: &AppState,
+    namespace: &str,
+    name: &str,
+) -> Result<Vec<ConfluentVersion>, AppError> {
+    type Row = (
+        Uuid,
+        i64,
+        i32,
+        i32,
+        i32,
+        i32,
+        String,
+        String,
+        String,
+        String,
+    );
+    let rows: Vec<Row> = sqlx::query_as(
+        r#"
+        SELECT id, global_id, confluent_version, version_major, version_minor, version_patch,
+               format, content, content_hash, state
+        FROM schemas
+        WHERE namespace = $1 AND name = $2
+        ORDER BY confluent_version
+        "#,
+    )
+    .bind(namespace)
+    .bind(name)
+    .fetch_all(&state.db)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            let (id, global_id, number, major, minor, patch, format, content, content_hash, state) =
+                row;
+            ConfluentVersion {
+                id,
+                global_id,
+                number,
+                version: SemanticVersion::new(major as u32, minor as u32, patch as u32),
+                format,
+                content,
+                content_hash,
+                state,
+            }
+        })
+        .collect())
+}
+
+/// Resolve a Confluent subject and load its versions
+///
+/// Returns the subject under its current name; a subject without servable
+/// versions is not found.
+async fn confluent_subject(
+    state: &AppState,
+    subject: &str,
+) -> Result<(String, String, Vec<ConfluentVersion>), ConfluentError> {
+    let (namespace, name) = split_subject(subject);
+    let (namespace, name, _) = resolve_subject(state, namespace, name).await?;
+    let versions = confluent_versions(state, &namespace, &name).await?;
+    if !versions.iter().any(ConfluentVersion::is_servable) {
+        return Err(ConfluentError::subject_not_found(subject));
+    }
+    Ok((namespace, name, versions))
+}
+
+/// The version named in a path: a number or `latest` (also `-1`)
+fn confluent_version<'a>(
+    versions: &'a [ConfluentVersion],
+    version: &str,
+) -> Result<&'a ConfluentVersion, ConfluentError> {
+    let found = match VersionId::parse(version)? {
+        VersionId::Latest => versions.iter().rev().find(|v| v.is_servable()),
+        VersionId::Number(number) => versions
+            .iter()
+            .find(|v| v.number == number && v.is_servable()),
+    };
+    found.ok_or_else(|| ConfluentError::version_not_found(version))
+}
+
+/// The compatibility level of a subject
+///
+/// A level set through `PUT /config/{subject}` is kept as the settings of the
+/// subject's own path and wins; otherwise the subject inherits its
+/// namespace's mode. Registrations through either API are checked at this
+/// level.
+pub(crate) fn subject_compatibility(
+    state: &AppState,
+    namespace: &str,
+    name: &str,
+) -> Result<CompatibilityMode, AppError> {
+    let subject_level = state
+        .namespaces
+        .get(&subject_of(namespace, name))
+        .and_then(|settings| settings.compatibility_mode);
+    if let Some(mode) = subject_level {
+        return Ok(mode);
+    }
+    let settings = state
+        .namespaces
+        .effective(namespace)
+        .map_err(|e| AppError::InvalidInput(e.to_string()))?;
+    Ok(settings.compatibility_in(state.environment.as_deref()).mode)
+}
+
+/// Check `content` against `previous` versions of a subject in `mode`
+///
+/// Active exemptions apply as they do for the native compatibility check.
+async fn confluent_check(
+    state: &AppState,
+    (namespace, name): (&str, &str),
+    (format, content): (&str, &str),
+    previous: &[&ConfluentVersion],
+    mode: CompatibilityMode,
+) -> Result<CompatibilityResult, AppError> {
+    let next = previous
+        .iter()
+        .map(|v| v.version.major + 1)
+        .max()
+        .unwrap_or(1);
+    let candidate = comparable_schema(
+        Uuid::nil(),
+        namespace,
+        name,
+        format,
+        content,
+        SemanticVersion::new(next, 0, 0),
+    );
+    let previous: Vec<RegisteredSchema> = previous
+        .iter()
+        .map(|v| {
+            comparable_schema(
+                v.id,
+                namespace,
+                name,
+                &v.format,
+                &v.content,
+                v.version.clone(),
+            )
+        })
+        .collect();
+    let (result, _) = check_with_exemptions(
+        state,
+        &subject_of(namespace, name),
+        &candidate,
+        &previous,
+        mode,
+    )
+    .await?;
+    Ok(result)
+}
+
+/// Subjects with at least one servable version
+pub(crate) async fn confluent_list_subjects(
+    State(state): State<AppState>,
+) -> Result<Response, ConfluentError> {
+    let rows: Vec<(String, String)> = sqlx::query_as(
+        r#"
+        SELECT DISTINCT namespace, name
+        FROM schemas
+        WHERE state NOT IN ('DELETED', 'QUARANTINED')
+        ORDER BY namespace, name
+        "#,
+    )
+    .fetch_all(&state.db)
+    .await
+    .map_err(AppError::from)?;
+    let subjects: Vec<String> = rows.iter().map(|(ns, name)| subject_of(ns, name)).collect();
+    Ok(confluent::json(subjects))
+}
+
+/// Numbers of a subject's servable versions
+pub(crate) async fn confluent_list_versions(
+    State(state): State<AppState>,
+    Path(subject): Path<String>,
+) -> Result<Response, ConfluentError> {
+    let (_, _, versions) = confluent_subject(&state, &subject).await?;
+    let numbers: Vec<i32> = versions
+        .iter()
+        .filter(|v| v.is_servable())
+        .map(|v| v.number)
+        .collect();
+    Ok(confluent::json(numbers))
+}
+
+pub(crate) async fn confluent_get_version(
+    State(state): State<AppState>,
+    Path((subject, version)): Path<(String, String)>,
+) -> Result<Response, ConfluentError> {
+    let (namespace, name, versions) = confluent_subject(&state, &subject).await?;
+    let found = confluent_version(&versions, &version)?;
+    let schema = found.to_schema(&subject_of(&namespace, &name))?;
+    Ok(confluent::json(schema))
+}
+
+/// The schema text of a version on its own
+pub(crate) async fn confluent_get_version_schema(
+    State(state): State<AppState>,
+    Path((subject, version)): Path<(String, String)>,
+) -> Result<Response, ConfluentError> {
+    let (_, _, versions) = confluent_subject(&state, &subject).await?;
+    let found = confluent_version(&versions, &version)?;
+    Ok((
+        [("content-type", confluent::CONTENT_TYPE)],
+        found.content.clone(),
+    )
+        .into_response())
+}
+
+/// A schema by the integer ID serializers embed in messages
+pub(crate) async fn confluent_get_schema_by_id(
+    State(state): State<AppState>,
+    Path(global_id): Path<i64>,
+) -> Result<Response, ConfluentError> {
+    let id = schema_id_by_global_id(&state, global_id)
+        .await
+        .map_err(|e| match e {
+            AppError::NotFound(_) => ConfluentError::schema_not_found(),
+            e => e.into(),
+        })?;
+    let schema = fetch_schema(&state, id).await.map_err(|e| match e {
+        AppError::NotFound(_) | AppError::Gone(_) | AppError::Quarantined(_) => {
+            ConfluentError::schema_not_found()
+        }
+        e => e.into(),
+    })?;
+    Ok(confluent::json(ConfluentSchemaById {
+        schema_type: confluent::schema_type(&schema.format),
+        schema: schema.content,
+    }))
+}
+
+/// Register a schema under a subject, returning its integer ID
+///
+/// Content already registered under the subject returns the existing ID.
+/// Otherwise the schema goes through the native registration pipeline as the
+/// subject's next major version, `ACTIVE` so producers can use it at once.
+/// Its compatibility gate checks the subject's earlier versions at the
+/// subject's compatibility level.
+pub(crate) async fn confluent_register(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    impersonation: Option<Extension<Impersonation>>,
+    scoped: Option<Extension<ScopedToken>>,
+    Path(subject): Path<String>,
+    body: Bytes,
+) -> Result<Response, ConfluentError> {
+    let req = parse_confluent_request(&state, &body)?;
+    let (format, serialization_format) = req.format()?;
+    let (namespace, name) = split_subject(&subject);
+    let (namespace, name, _) = resolve_subject(&state, namespace, name).await?;
+    let versions = confluent_versions(&state, &namespace, &name).await?;
+
+    let content_hash = canonical_form::content_hash(serialization_format, &req.schema);
+    if let Some(existing) = versions
+        .iter()
+        .find(|v| v.content_hash == content_hash && v.is_servable())
+    {
+        let id = confluent::wire_id(existing.global_id)?;
+        return Ok(confluent::json(serde_json::json!({ "id": id })));
+    }
+
+    let mode = subject_compatibility(&state, &namespace, &name)?;
+    let registration = RegisterSchemaRequest {
+        subject: subject_of(&namespace, &name),
+        schema: serde_json::Value::Null,
+        schema_type: String::new(),
+        namespace: None,
+        name: None,
+        version_major: None,
+        version_minor: None,
+        version_patch: None,
+        format: Some(format.to_string()),
+        content: Some(req.schema),
+        state: SchemaState::Active.to_string(),
+        compatibility_mode: Some(mode),
+        description: None,
+        tags: Vec::new(),
+        metadata: HashMap::new(),
+    };
+    let (_, _, Json(registered)) = register_schema(
+        State(state.clone()),
+        headers,
+        impersonation,
+        scoped,
+        BoundedJson(registration),
+    )
+    .await
+    .map_err(|e| match e {
+        AppError::Incompatible(result) => ConfluentError::new(
+            StatusCode::CONFLICT,
+            409,
+            format!(
+                "Schema being registered is incompatible with an earlier schema for subject \"{}\", details: [{}]",
+                subject_of(&namespace, &name),
+                describe_violations(&result.violations).join("; ")
+            ),
+        ),
+        e => e.into(),
+    })?;
+    let id = confluent::wire_id(registered.global_id)?;
+    Ok(confluent::json(serde_json::json!({ "id": id })))
+}
+
+/// Find the version of a subject with the given content
+pub(crate) async fn confluent_lookup(
+    State(state): State<AppState>,
+    Path(subject): Path<String>,
+    body: Bytes,
+) -> Result<Response, ConfluentError> {
+    let req = parse_confluent_request(&state, &body)?;
+    let (format, serialization_format) = req.format()?;
+    let (namespace, name, versions) = confluent_subject(&state, &subject).await?;
+    let content_hash = canonical_form::content_hash(serialization_format, &req.schema);
+    let found = versions
+        .iter()
+        .find(|v| v.is_servable() && v.format == format && v.content_hash == content_hash)
+        .ok_or_else(ConfluentError::schema_not_found)?;
+    let schema = found.to_schema(&subject_of(&namespace, &name))?;
+    Ok(confluent::json(schema))
+}
+
+/// Whether a schema is compatible with one version of a subject
+///
+/// The check uses the subject's compatibility level, against that version
+/// only; `?verbose=true` adds the violations as `messages`.
+pub(crate) async fn confluent_check_compatibility(
+    State(state): State<AppState>,
+    Path((subject, version)): Path<(String, String)>,
+    Query(query): Query<ConfluentCompatibilityQuery>,
+    body: Bytes,
+) -> Result<Response, ConfluentError> {
+    let started = std::time::Instant::now();
+    let req = parse_confluent_request(&state, &body)?;
+    let (format, _) = req.format()?;
+    let (namespace, name, versions) = confluent_subject(&state, &subject).await?;
+    let against = confluent_version(&versions, &version)?;
+    let mode = match subject_compatibility(&state, &namespace, &name)? {
+        CompatibilityMode::None => {
+            let body = serde_json::json!({ "is_compatible": true });
+            return Ok(confluent::json(body));
+        }
+        mode => mode,
+    };
+    let result = confluent_check(
+        &state,
+        (&namespace, &name),
+        (format, &req.schema),
+        &[against],
+        mode,
+    )
+    .await?;
+    observe_operation(
+        &state,
+        "compatibility_check",
+        against.id,
+        Some(subject_of(&namespace, &name)),
+        req.schema.len() + against.content.len(),
+        Some(result.mode.to_string()),
+        started.elapsed(),
+    );
+    let mut body = serde_json::json!({ "is_compatible": result.is_compatible });
+    if query.verbose {
+        body["messages"] = serde_json::json!(describe_violations(&result.violations));
+    }
+    Ok(confluent::json(body))
+}
+
+/// The compatibility level of subjects without one of their own
+///
+/// Confluent's global level is the level of the `default` namespace, which
+/// holds subjects without a namespace such as `orders-value`.
+pub(crate) async fn confluent_get_global_config(
+    State(state): State<AppState>,
+) -> Result<Response, ConfluentError> {
+    let settings = state
+        .namespaces
+        .effective("default")
+        .map_err(|e| AppError::InvalidInput(e.to_string()))?;
+    let mode = settings.compatibility_in(state.environment.as_deref()).mode;
+    let body = serde_json::json!({ "compatibilityLevel": mode });
+    Ok(confluent::json(body))
+}
+
+pub(crate) async fn confluent_put_global_config(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, ConfluentError> {
+    set_confluent_compatibility(&state, &headers, "default", &body).await
+}
+
+/// The compatibility level set on a subject itself
+///
+/// Without one, Confluent answers 40408 unless `defaultToGlobal=true`, which
+/// returns the level the subject inherits.
+pub(crate) async fn confluent_get_config(
+    State(state): State<AppState>,
+    Path(subject): Path<String>,
+    Query(query): Query<ConfluentConfigQuery>,
+) -> Result<Response, ConfluentError> {
+    let (namespace, name) = split_subject(&subject);
+    let (namespace, name, _) = resolve_subject(&state, namespace, name).await?;
+    let subject = subject_of(&namespace, &name);
+    let subject_level = state
+        .namespaces
+        .get(&subject)
+        .and_then(|settings| settings.compatibility_mode);
+    let mode = match subject_level {
+        Some(mode) => mode,
+        None if query.default_to_global => subject_compatibility(&state, &namespace, &name)?,
+        None => return Err(ConfluentError::no_subject_config(&subject)),
+    };
+    let body = serde_json::json!({ "compatibilityLevel": mode });
+    Ok(confluent::json(body))
+}
+
+pub(crate) async fn confluent_put_config(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(subject): Path<String>,
+    body: Bytes,
+) -> Result<Response, ConfluentError> {
+    let (namespace, name) = split_subject(&subject);
+    let (namespace, name, _) = resolve_subject(&state, namespace, name).await?;
+    set_confluent_compatibility(&state, &headers, &subject_of(&namespace, &name), &body).await
+}
+
+/// Remove a subject's own compatibility level, returning the level it had
+pub(crate) async fn confluent_delete_config(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(subject): Path<String>,
+) -> Result<Response, ConfluentError> {
+    let (namespace, name) = split_subject(&subject);
+    let (namespace, name, _) = resolve_subject(&state, namespace, name).await?;
+    let subject = subject_of(&namespace, &name);
+    let admin = require_admin(&state, &headers)?;
+    let actor = ChangeActor::from_request(&state, &headers, None, None)?;
+    ensure_unfrozen(&state, &actor, &subject, "update_subject_compatibility").await?;
+
+    let write = state.namespace_store.lock().await;
+    let mut settings = state.namespaces.get(&subject).unwrap_or_default();
+    let Some(previous) = settings.compatibility_mode.take() else {
+        return Err(ConfluentError::no_subject_config(&subject));
+    };
+    if settings == NamespaceSettings::default() {
+        state.namespaces.remove(&subject);
+    } else {
+        state
+            .namespaces
+            .set(&subject, settings)
+            .map_err(|e| AppError::InvalidInput(e.to_string()))?;
+    }
+    write
+        .commit([&subject], &admin)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    log_compatibility_change(&state, admin, &subject, None).await;
+    let body = serde_json::json!({ "compatibilityLevel": previous });
+    Ok(confluent::json(body))
+}
+
+/// Set the compatibility level kept on `path`, leaving its other settings be
+async fn set_confluent_compatibility(
+    state: &AppState,
+    headers: &HeaderMap,
+    path: &str,
+    body: &[u8],
+) -> Result<Response, ConfluentError> {
+    let admin = require_admin(state, headers)?;
+    let req: ConfluentConfigRequest =
+        bounded_json::from_slice(body, &state.json_limits).map_err(AppError::from)?;
+    let mode: CompatibilityMode = req
+        .compatibility
+        .parse()
+        .map_err(|_| ConfluentError::invalid_compatibility(&req.compatibility))?;
+    let actor = ChangeActor::from_request(state, headers, None, None)?;
+    ensure_unfrozen(state, &actor, path, "update_subject_compatibility").await?;
+
+    let write = state.namespace_store.lock().await;
+    let mut settings = state.namespaces.get(path).unwrap_or_default();
+    settings.compatibility_mode = Some(mode);
+    state
+        .namespaces
+        .set(path, settings)
+        .map_err(|e| AppError::InvalidInput(e.to_string()))?;
+    write
+        .commit([path], &admin)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    log_compatibility_change(state, admin, path, Some(mode)).await;
+    let body = serde_json::json!({ "compatibility": mode });
+    Ok(confluent::json(body))
+}
+
+async fn log_compatibility_change(
+    state: &AppState,
+    admin: String,
+    subject: &str,
+    mode: Option<CompatibilityMode>,
+) {
+    let event = AuditEvent::new(
+        AuditEventType::ConfigurationChanged,
+        "Subject compatibility level updated".to_string(),
+        AuditResult::Success,
+        String::new(),
+    )
+    .with_user(admin, None)
+    .with_resource("namespace".to_string(), subject.to_string())
+    .with_metadata("compatibility".to_string(), serde_json::json!(mode));
+    state.audit_logger.log(event).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(number: i32, state: &str) -> ConfluentVersion {
+        ConfluentVersion {
+            id: Uuid::new_v4(),
+            global_id: i64::from(number),
+            number,
+            version: SemanticVersion::new(number as u32, 0, 0),
+            format: "JSON".to_string(),
+            content: "{}".to_string(),
+            content_hash: String::new(),
+            state: state.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_deleted_and_quarantined_versions_are_not_served() {
+        let versions = [
+            version(1, "ACTIVE"),
+            version(2, "DELETED"),
+            version(3, "QUARANTINED"),
+        ];
+
+        let number = |version: &str| confluent_version(&versions, version).map(|v| v.number);
+
+        assert_eq!(number("latest").unwrap(), 1);
+        assert_eq!(number("1").unwrap(), 1);
+        for version in ["2", "3", "4"] {
+            assert_eq!(number(version).unwrap_err().error_code, 40402);
+        }
+        assert_eq!(number("0").unwrap_err().error_code, 42202);
+    }
+
+    #[test]
+    fn test_native_errors_keep_their_status() {
+        let error = ConfluentError::from(AppError::NotFound("Schema x not found".to_string()));
+        assert_eq!(error.status, StatusCode::NOT_FOUND);
+        assert_eq!(error.error_code, 40401);
+
+        // Invalid schemas are reported with Confluent's own code
+        let error = ConfluentError::from(AppError::InvalidInput("bad".to_string()));
+        assert_eq!(error.status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(error.error_code, 42201);
+        assert_eq!(error.message, "bad");
+    }
+}
@@ -0,0 +1,259 @@
+//! Field diff webhooks for subject consumers
+//!
+//! Consumers register a manifest of the fields they read from a subject,
+//! with a webhook URL. When a new version is registered, each consumer whose
+//! fields changed since the previous version gets a notice listing just those
+//! changes; consumers untouched by the new version hear nothing.
+//!
+//! Notices are sent after the version is committed and don't hold up the
+//! registration. A failed delivery is logged and not retried. The HTTP client
+//! is built for the first notice, so deployments without consumer manifests
+//! never build one.
+
+use chrono::{DateTime, Utc};
+use schema_registry_core::field_diff::{self, FieldChange, FieldManifest};
+use schema_registry_core::types::SerializationFormat;
+use serde::Serialize;
+use sqlx::PgPool;
+use std::sync::OnceLock;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// The fields one consumer reads from a subject
+#[derive(Debug, Clone, Serialize)]
+pub struct ConsumerManifest {
+    pub consumer: String,
+    pub webhook_url: String,
+    pub fields: FieldManifest,
+    pub updated_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_by: Option<String>,
+}
+
+/// A newly registered version, as far as notices are concerned
+#[derive(Debug, Clone)]
+pub struct RegisteredVersion {
+    pub schema_id: Uuid,
+    pub subject: String,
+    pub namespace: String,
+    pub name: String,
+    pub version: (i32, i32, i32),
+    pub format: SerializationFormat,
+    pub content: String,
+}
+
+/// Payload posted to a consumer's webhook
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldChangeNotice {
+    pub subject: String,
+    pub consumer: String,
+    pub schema_id: Uuid,
+    pub version: String,
+    pub previous_version: String,
+    /// Changes touching the consumer's declared fields
+    pub changes: Vec<FieldChange>,
+}
+
+/// The notice each consumer should get for `changes`, with its webhook URL
+///
+/// Consumers none of whose fields changed are left out.
+pub fn notices(
+    registered: &RegisteredVersion,
+    previous_version: &str,
+    changes: &[FieldChange],
+    manifests: &[ConsumerManifest],
+) -> Vec<(String, FieldChangeNotice)> {
+    let (major, minor, patch) = registered.version;
+    manifests
+        .iter()
+        .filter_map(|manifest| {
+            let relevant = manifest.fields.relevant(changes);
+            if relevant.is_empty() {
+                return None;
+            }
+            let notice = FieldChangeNotice {
+                subject: registered.subject.clone(),
+                consumer: manifest.consumer.clone(),
+                schema_id: registered.schema_id,
+                version: format!("{}.{}.{}", major, minor, patch),
+                previous_version: previous_version.to_string(),
+                changes: relevant.into_iter().cloned().collect(),
+            };
+            Some((manifest.webhook_url.clone(), notice))
+        })
+        .collect()
+}
+
+/// Reads consumer manifests and delivers their notices
+pub struct ConsumerNotifier {
+    db: PgPool,
+    client: OnceLock<reqwest::Client>,
+}
+
+impl ConsumerNotifier {
+    pub fn new(db: PgPool) -> Self {
+        Self {
+            db,
+            client: OnceLock::new(),
+        }
+    }
+
+    fn client(&self) -> &reqwest::Client {
+        self.client.get_or_init(|| {
+            reqwest::Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()
+                .unwrap_or_default()
+        })
+    }
+
+    /// Manifests of the consumers of a subject, by consumer name
+    pub async fn manifests(
+        &self,
+        namespace: &str,
+        name: &str,
+    ) -> Result<Vec<ConsumerManifest>, sqlx::Error> {
+        let rows: Vec<(String, String, Vec<String>, DateTime<Utc>, Option<String>)> =
+            sqlx::query_as(
+                r#"
+                SELECT consumer, webhook_url, fields, updated_at, updated_by
+                FROM consumer_field_manifests
+                WHERE namespace = $1 AND name = $2
+                ORDER BY consumer
+                "#,
+            )
+            .bind(namespace)
+            .bind(name)
+            .fetch_all(&self.db)
+            .await?;
+        Ok(rows
+            .into_iter()
+            .map(
+                |(consumer, webhook_url, fields, updated_at, updated_by)| ConsumerManifest {
+                    consumer,
+                    webhook_url,
+                    fields: FieldManifest::new(fields),
+                    updated_at,
+                    updated_by,
+                },
+            )
+            .collect())
+    }
+
+    /// Notify the consumers whose fields changed since the version before
+    /// `registered`
+    pub async fn notify(&self, registered: RegisteredVersion) {
+        if let Err(e) = self.try_notify(&registered).await {
+            tracing::warn!(
+                schema_id = %registered.schema_id,
+                error = %e,
+                "Failed to prepare consumer field change notices"
+            );
+        }
+    }
+
+    async fn try_notify(&self, registered: &RegisteredVersion) -> Result<(), sqlx::Error> {
+        let manifests = self
+            .manifests(&registered.namespace, &registered.name)
+            .await?;
+        if manifests.is_empty() {
+            return Ok(());
+        }
+
+        let (major, minor, patch) = registered.version;
+        let previous: Option<(i32, i32, i32, String)> = sqlx::query_as(
+            r#"
+            SELECT version_major, version_minor, version_patch, content
+            FROM schemas
+            WHERE namespace = $1 AND name = $2
+              AND (version_major, version_minor, version_patch) < ($3, $4, $5)
+            ORDER BY version_major DESC, version_minor DESC, version_patch DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(&registered.namespace)
+        .bind(&registered.name)
+        .bind(major)
+        .bind(minor)
+        .bind(patch)
+        .fetch_optional(&self.db)
+        .await?;
+        // A subject's first version changes nothing consumers already read
+        let Some((prev_major, prev_minor, prev_patch, previous_content)) = previous else {
+            return Ok(());
+        };
+
+        let changes = field_diff::diff(&previous_content, &registered.content, registered.format);
+        let previous_version = format!("{}.{}.{}", prev_major, prev_minor, prev_patch);
+        for (url, notice) in notices(registered, &previous_version, &changes, &manifests) {
+            let result = self
+                .client()
+                .post(&url)
+                .json(&notice)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status());
+            if let Err(e) = result {
+                tracing::warn!(
+                    consumer = %notice.consumer,
+                    url = %url,
+                    error = %e,
+                    "Failed to deliver field change notice"
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(consumer: &str, fields: &[&str]) -> ConsumerManifest {
+        ConsumerManifest {
+            consumer: consumer.to_string(),
+            webhook_url: format!("https://{}.example.com/hooks/schema", consumer),
+            fields: FieldManifest::new(fields.iter().copied()),
+            updated_at: Utc::now(),
+            updated_by: None,
+        }
+    }
+
+    #[test]
+    fn test_only_consumers_reading_changed_fields_are_notified() {
+        let registered = RegisteredVersion {
+            schema_id: Uuid::new_v4(),
+            subject: "billing.Invoice".to_string(),
+            namespace: "billing".to_string(),
+            name: "Invoice".to_string(),
+            version: (2, 0, 0),
+            format: SerializationFormat::JsonSchema,
+            content: r#"{"properties": {"id": {"type": "string"}, "total": {"type": "number"}}}"#
+                .to_string(),
+        };
+        let changes = field_diff::diff(
+            r#"{"properties": {"id": {"type": "string"}, "total": {"type": "integer"}, "memo": {"type": "string"}}}"#,
+            &registered.content,
+            registered.format,
+        );
+        let manifests = [
+            manifest("ledger", &["id", "total"]),
+            manifest("search", &["id"]),
+            manifest("archive", &["memo"]),
+        ];
+
+        let notices = notices(&registered, "1.0.0", &changes, &manifests);
+        let consumers: Vec<&str> = notices.iter().map(|(_, n)| n.consumer.as_str()).collect();
+        assert_eq!(consumers, ["ledger", "archive"]);
+
+        let (url, ledger) = &notices[0];
+        assert_eq!(url, "https://ledger.example.com/hooks/schema");
+        assert_eq!(ledger.version, "2.0.0");
+        assert_eq!(ledger.previous_version, "1.0.0");
+        assert_eq!(
+            serde_json::to_value(&ledger.changes).unwrap(),
+            serde_json::json!([{"change": "type_changed", "path": "total", "from": "integer", "to": "number"}])
+        );
+    }
+}
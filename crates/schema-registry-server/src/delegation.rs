@@ -0,0 +1,356 @@
+//! Delegated credentials
+//!
+//! Impersonation tokens, scoped tokens exchanged for the admin token, a
+//! scoped parent or an OIDC token, and SPIFFE workload tokens, along with
+//! the middleware resolving them.
+
+use super::*;
+use schema_registry_security::{
+    directory::{AuthProvider, Credentials},
+    impersonation::{
+        ImpersonationError, ImpersonationGrant, ImpersonationRequest, IMPERSONATED_BY_HEADER,
+    },
+    spiffe::peek_jwt_svid,
+    token_exchange::{
+        ParentCredential, ScopedTokenGrant, TokenExchangeError, TokenExchangeRequest,
+        SCHEMA_READ_PERMISSION, SCOPED_PERMISSIONS,
+    },
+};
+
+/// Issue a short-lived token acting as another principal
+pub(crate) async fn impersonate(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    BoundedJson(req): BoundedJson<ImpersonationRequest>,
+) -> Result<(StatusCode, Json<ImpersonationGrant>), AppError> {
+    let admin = require_admin(&state, &headers)?;
+    let manager = state
+        .impersonation
+        .as_ref()
+        .ok_or_else(|| AppError::NotFound("Impersonation is disabled".to_string()))?;
+
+    let grant = manager.issue(&admin, req).await.map_err(|e| match e {
+        ImpersonationError::Auth(_) => AppError::Internal(e.to_string()),
+        _ => AppError::InvalidInput(e.to_string()),
+    })?;
+    tracing::warn!(
+        admin = %grant.impersonator,
+        principal = %grant.principal,
+        expires_at = grant.expires_at,
+        "Impersonation token issued"
+    );
+
+    Ok((StatusCode::CREATED, Json(grant)))
+}
+
+/// Identity of a request made with an impersonation token
+#[derive(Debug, Clone)]
+pub(crate) struct Impersonation {
+    pub(crate) principal: String,
+    pub(crate) impersonator: String,
+    /// Permissions granted to the token
+    pub(crate) permissions: Vec<String>,
+}
+
+impl Impersonation {
+    /// Attribute an audit event to the impersonated principal and the admin
+    pub(crate) fn attribute(&self, event: AuditEvent) -> AuditEvent {
+        event
+            .with_user(self.principal.clone(), None)
+            .with_impersonator(self.impersonator.clone())
+    }
+}
+
+/// Resolve impersonation tokens, audit the request and tag the response
+///
+/// Bearer tokens other than the admin token must be valid impersonation
+/// tokens while impersonation is enabled. Like the principal's own
+/// credentials, a token needs `schema:read` for reads and `schema:write` for
+/// changes.
+pub(crate) async fn impersonation_context(
+    State(state): State<AppState>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let Some(manager) = state.impersonation.as_ref() else {
+        return Ok(next.run(request).await);
+    };
+    if request.extensions().get::<ScopedToken>().is_some() {
+        return Ok(next.run(request).await);
+    }
+    let token = request
+        .headers()
+        .get("authorization")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .filter(|token| Some(*token) != state.admin_token.as_deref())
+        .map(str::to_string);
+    let Some(token) = token else {
+        return Ok(next.run(request).await);
+    };
+
+    let claims = manager
+        .verify(&token)
+        .await
+        .map_err(|e| AppError::Unauthorized(e.to_string()))?;
+    let impersonation = Impersonation {
+        principal: claims.sub.clone(),
+        impersonator: claims.impersonator().unwrap_or_default().to_string(),
+        permissions: claims.permissions.clone(),
+    };
+    let action = format!("{} {}", request.method(), request.uri().path());
+    let required = if request.method().is_safe() {
+        SCHEMA_READ_PERMISSION
+    } else {
+        SCHEMA_WRITE_PERMISSION
+    };
+    request.extensions_mut().insert(impersonation.clone());
+
+    let mut response = if impersonation.permissions.iter().any(|p| p == required) {
+        next.run(request).await
+    } else {
+        AppError::Forbidden(format!(
+            "Impersonation token {} lacks the {} permission",
+            claims.jti, required
+        ))
+        .into_response()
+    };
+    if let Ok(value) = HeaderValue::from_str(&impersonation.impersonator) {
+        response.headers_mut().insert(IMPERSONATED_BY_HEADER, value);
+    }
+
+    let result = if response.status().is_success() {
+        AuditResult::Success
+    } else {
+        AuditResult::Failure
+    };
+    let event = impersonation
+        .attribute(AuditEvent::new(
+            AuditEventType::ImpersonatedRequest,
+            action,
+            result,
+            String::new(),
+        ))
+        .with_resource("token".to_string(), claims.jti)
+        .with_metadata("status".to_string(), serde_json::json!(response.status().as_u16()));
+    state.audit_logger.log(event).await;
+
+    Ok(response)
+}
+
+/// Longest lifetime of a scoped token, unless configured otherwise
+pub(crate) const DEFAULT_TOKEN_EXCHANGE_MAX_TTL_SECS: u64 = 3600;
+
+/// How often the group role mapping file is re-read, unless configured otherwise
+pub(crate) const DEFAULT_GROUP_ROLE_MAPPING_REFRESH_SECS: u64 = 300;
+
+/// Audience OIDC tokens must be issued for, unless configured otherwise
+pub(crate) const DEFAULT_OIDC_AUDIENCE: &str = "llm-schema-registry";
+
+/// Directory role that may request any scope, like the admin token
+const DIRECTORY_ADMIN_ROLE: &str = "admin";
+
+/// OIDC sign-in with registry roles derived from the user's directory groups
+pub(crate) struct DirectorySignIn {
+    pub(crate) verifier: OidcTokenVerifier,
+    pub(crate) provider: OidcGroupProvider,
+}
+
+impl DirectorySignIn {
+    /// The parent credential an OIDC token stands for in a token exchange
+    ///
+    /// Users with the `admin` role may request any scope. Other roles that
+    /// name a scoped token permission grant it in every namespace; roles the
+    /// registry doesn't know are ignored.
+    async fn parent(
+        &self,
+        token: &str,
+        namespaces: &[String],
+    ) -> Result<ParentCredential, AppError> {
+        let claims = self
+            .verifier
+            .verify(token)
+            .map_err(|e| AppError::Unauthorized(e.to_string()))?;
+        let identity = self
+            .provider
+            .authenticate(&Credentials::OidcClaims(claims.clone()))
+            .await
+            .map_err(|e| AppError::Unauthorized(e.to_string()))?;
+
+        let scope = if identity.roles.iter().any(|r| r == DIRECTORY_ADMIN_ROLE) {
+            None
+        } else {
+            let permissions: Vec<String> = identity
+                .roles
+                .into_iter()
+                .filter(|role| SCOPED_PERMISSIONS.contains(&role.as_str()))
+                .collect();
+            if permissions.is_empty() {
+                return Err(AppError::Forbidden(format!(
+                    "None of {}'s directory groups grants a registry role",
+                    identity.user_id
+                )));
+            }
+            Some(TokenScope {
+                namespaces: namespaces.to_vec(),
+                permissions,
+            })
+        };
+        Ok(ParentCredential {
+            principal: identity.user_id,
+            scope,
+            token_id: claims["jti"].as_str().map(str::to_string),
+            expires_at: claims["exp"].as_u64(),
+        })
+    }
+}
+
+/// Exchange the admin token, a scoped token or an OIDC token for a scoped
+/// token
+///
+/// A scoped parent can only be exchanged for a narrower token that expires
+/// no later than itself. An OIDC token is passed as `subject_token` and
+/// grants what its user's directory groups map to.
+pub(crate) async fn exchange_token(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    scoped: Option<Extension<ScopedToken>>,
+    BoundedJson(mut req): BoundedJson<TokenExchangeRequest>,
+) -> Result<(StatusCode, Json<ScopedTokenGrant>), AppError> {
+    let exchanger = state
+        .token_exchange
+        .as_ref()
+        .ok_or_else(|| AppError::NotFound("Token exchange is disabled".to_string()))?;
+    let parent = match (req.subject_token.take(), scoped) {
+        (Some(token), _) => {
+            let directory = state.directory.as_ref().ok_or_else(|| {
+                AppError::InvalidInput("Directory sign-in is disabled".to_string())
+            })?;
+            directory.parent(&token, &req.namespaces).await?
+        }
+        (None, Some(Extension(scoped))) => ParentCredential {
+            principal: scoped.principal,
+            scope: Some(scoped.scope),
+            token_id: Some(scoped.token_id),
+            expires_at: Some(scoped.expires_at),
+        },
+        (None, None) => ParentCredential {
+            principal: require_admin(&state, &headers)?,
+            scope: None,
+            token_id: None,
+            expires_at: None,
+        },
+    };
+
+    let grant = exchanger.exchange(&parent, req).await.map_err(|e| match e {
+        TokenExchangeError::Auth(_) => AppError::Internal(e.to_string()),
+        TokenExchangeError::Escalation(_) => AppError::Forbidden(e.to_string()),
+        _ => AppError::InvalidInput(e.to_string()),
+    })?;
+    tracing::info!(
+        parent = %grant.principal,
+        token_id = %grant.token_id,
+        namespaces = ?grant.namespaces,
+        permissions = ?grant.permissions,
+        expires_at = grant.expires_at,
+        "Scoped token issued"
+    );
+
+    Ok((StatusCode::CREATED, Json(grant)))
+}
+
+/// Identity of a request made with a scoped token
+#[derive(Debug, Clone)]
+pub(crate) struct ScopedToken {
+    /// Identity of the credential the token was exchanged for
+    pub(crate) principal: String,
+    pub(crate) token_id: String,
+    pub(crate) scope: TokenScope,
+    /// Unix timestamp
+    pub(crate) expires_at: u64,
+}
+
+/// Resolve scoped tokens and JWT-SVIDs and hold them to their read
+/// permission
+///
+/// Reads need `schema:read`; changes are checked against the token's
+/// namespaces and `schema:write` by [`ensure_unfrozen`]. Other bearer tokens
+/// are left to [`impersonation_context`] and the handlers.
+pub(crate) async fn scoped_token_context(
+    State(state): State<AppState>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let token = request
+        .headers()
+        .get("authorization")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .filter(|token| Some(*token) != state.admin_token.as_deref());
+    let Some(token) = token else {
+        return Ok(next.run(request).await);
+    };
+    let scoped = match (state.spiffe.as_ref(), state.token_exchange.as_ref()) {
+        (Some(spiffe), _) if peek_jwt_svid(token).is_some() => {
+            workload_token(spiffe, token).await?
+        }
+        (_, Some(exchanger)) => match exchanger.verify(token).await {
+            Ok((claims, scope)) => ScopedToken {
+                principal: claims.sub,
+                token_id: claims.jti,
+                scope,
+                expires_at: claims.exp,
+            },
+            Err(TokenExchangeError::NotScoped) => return Ok(next.run(request).await),
+            // Impersonation tokens are signed with another key
+            Err(_) if state.impersonation.is_some() => return Ok(next.run(request).await),
+            Err(e) => return Err(AppError::Unauthorized(e.to_string())),
+        },
+        _ => return Ok(next.run(request).await),
+    };
+
+    if request.method().is_safe() && !scoped.scope.allows_permission(SCHEMA_READ_PERMISSION) {
+        return Err(AppError::Forbidden(format!(
+            "Token {} lacks the {} permission",
+            scoped.token_id, SCHEMA_READ_PERMISSION
+        )));
+    }
+    request.extensions_mut().insert(scoped);
+    Ok(next.run(request).await)
+}
+
+/// Authenticate a workload by its JWT-SVID
+///
+/// The workload acts like a scoped token holder: its SPIFFE ID is the
+/// principal, and the roles and namespaces its ID maps to are the scope.
+/// Roles that aren't scoped token permissions are ignored.
+async fn workload_token(spiffe: &SpiffeAuthProvider, token: &str) -> Result<ScopedToken, AppError> {
+    let identity = spiffe
+        .authenticate(&Credentials::JwtSvid(token.to_string()))
+        .await
+        .map_err(|e| AppError::Unauthorized(e.to_string()))?;
+    let Some(svid) = peek_jwt_svid(token) else {
+        return Err(AppError::Unauthorized("Malformed JWT-SVID".to_string()));
+    };
+    let permissions: Vec<String> = identity
+        .roles
+        .into_iter()
+        .filter(|role| SCOPED_PERMISSIONS.contains(&role.as_str()))
+        .collect();
+    let namespaces = spiffe.namespaces_for(&svid.id);
+    if permissions.is_empty() || namespaces.is_empty() {
+        return Err(AppError::Forbidden(format!(
+            "{} is not granted a registry role in any namespace",
+            identity.user_id
+        )));
+    }
+    Ok(ScopedToken {
+        token_id: svid.token_id.unwrap_or_else(|| identity.user_id.clone()),
+        principal: identity.user_id,
+        scope: TokenScope {
+            namespaces,
+            permissions,
+        },
+        expires_at: svid.expires_at.unwrap_or_default(),
+    })
+}
@@ -0,0 +1,238 @@
+//! Live schema events for UIs and sidecars
+//!
+//! Write paths publish core [`SchemaEvent`]s through [`PgEventPublisher`],
+//! which sends each one as JSON on the `schema_events` Postgres notification
+//! channel, so an event is heard whichever replica made the change. Each
+//! replica runs one listener relaying the events to the subscribers of its
+//! stream endpoints.
+//!
+//! Delivery is best effort: events sent while the listener reconnects are
+//! lost, and a subscriber that falls too far behind is told how many it
+//! missed. Clients that must see every change follow the change feed.
+
+use async_trait::async_trait;
+use schema_registry_core::error::{Error, Result};
+use schema_registry_core::events::{EventType, SchemaEvent};
+use schema_registry_core::traits::EventPublisher;
+use sqlx::postgres::PgListener;
+use sqlx::PgPool;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+/// Postgres channel events are sent on
+pub const CHANNEL: &str = "schema_events";
+
+/// Event metadata key holding the namespace of the changed schema
+pub const NAMESPACE_KEY: &str = "namespace";
+
+/// Events a slow subscriber may fall behind by before it's told it lagged
+const CAPACITY: usize = 1024;
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+/// Schema events heard from any replica, relayed to every subscriber
+pub struct EventStream {
+    sender: broadcast::Sender<Arc<SchemaEvent>>,
+}
+
+impl EventStream {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(CAPACITY);
+        Self { sender }
+    }
+
+    /// Subscribe to the events `filter` lets through
+    pub fn subscribe(&self, filter: EventFilter) -> EventSubscription {
+        EventSubscription {
+            receiver: self.sender.subscribe(),
+            filter,
+        }
+    }
+
+    /// Relay the `schema_events` channel until the process exits,
+    /// reconnecting with backoff when the connection drops
+    pub fn spawn_listener(self: Arc<Self>, db: PgPool) {
+        tokio::spawn(async move {
+            let mut delay = RECONNECT_DELAY;
+            loop {
+                if let Err(e) = self.relay(&db, &mut delay).await {
+                    tracing::warn!(
+                        error = %e,
+                        retry_in_ms = delay.as_millis() as u64,
+                        "Schema event listener disconnected"
+                    );
+                }
+                tokio::time::sleep(delay).await;
+                delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+            }
+        });
+    }
+
+    async fn relay(
+        &self,
+        db: &PgPool,
+        delay: &mut Duration,
+    ) -> std::result::Result<(), sqlx::Error> {
+        let mut listener = PgListener::connect_with(db).await?;
+        listener.listen(CHANNEL).await?;
+        *delay = RECONNECT_DELAY;
+        loop {
+            let notification = listener.recv().await?;
+            self.relay_payload(notification.payload());
+        }
+    }
+
+    fn relay_payload(&self, payload: &str) {
+        match serde_json::from_str::<SchemaEvent>(payload) {
+            // No subscribers is the usual case, not an error
+            Ok(event) => {
+                let _ = self.sender.send(Arc::new(event));
+            }
+            Err(e) => tracing::debug!(error = %e, "Ignoring malformed schema event"),
+        }
+    }
+}
+
+impl Default for EventStream {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Which events a subscriber receives
+#[derive(Debug, Clone, Default)]
+pub struct EventFilter {
+    /// Events of this namespace and the namespaces nested in it
+    pub namespace: Option<String>,
+    /// Events of these types; all types when empty
+    pub event_types: Vec<EventType>,
+}
+
+impl EventFilter {
+    pub fn matches(&self, event: &SchemaEvent) -> bool {
+        if !self.event_types.is_empty() && !self.event_types.contains(&event.event_type) {
+            return false;
+        }
+        let Some(wanted) = &self.namespace else {
+            return true;
+        };
+        let namespace = event
+            .metadata
+            .get(NAMESPACE_KEY)
+            .and_then(|value| value.as_str())
+            .unwrap_or_default();
+        namespace == wanted
+            || namespace
+                .strip_prefix(wanted.as_str())
+                .is_some_and(|rest| rest.starts_with('.'))
+    }
+}
+
+/// What a subscriber is handed next
+#[derive(Debug, Clone)]
+pub enum StreamItem {
+    Event(Arc<SchemaEvent>),
+    /// The subscriber fell behind and this many events were dropped
+    Lagged(u64),
+}
+
+/// One subscriber's view of the stream
+pub struct EventSubscription {
+    receiver: broadcast::Receiver<Arc<SchemaEvent>>,
+    filter: EventFilter,
+}
+
+impl EventSubscription {
+    /// Wait for the next event the filter lets through
+    pub async fn next(&mut self) -> Option<StreamItem> {
+        loop {
+            match self.receiver.recv().await {
+                Ok(event) if self.filter.matches(&event) => return Some(StreamItem::Event(event)),
+                Ok(_) => {}
+                Err(RecvError::Lagged(missed)) => return Some(StreamItem::Lagged(missed)),
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+/// Publishes events on the `schema_events` notification channel
+pub struct PgEventPublisher {
+    db: PgPool,
+}
+
+impl PgEventPublisher {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl EventPublisher for PgEventPublisher {
+    async fn publish(&self, event: SchemaEvent) -> Result<()> {
+        let payload =
+            serde_json::to_string(&event).map_err(|e| Error::SerializationError(e.to_string()))?;
+        sqlx::query("SELECT pg_notify($1, $2)")
+            .bind(CHANNEL)
+            .bind(payload)
+            .execute(&self.db)
+            .await
+            .map_err(|e| Error::EventPublishError(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn publish_batch(&self, events: Vec<SchemaEvent>) -> Result<()> {
+        for event in events {
+            self.publish(event).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use schema_registry_core::events::EventPayload;
+    use schema_registry_core::versioning::SemanticVersion;
+    use std::collections::HashMap;
+    use uuid::Uuid;
+
+    fn event(event_type: EventType, namespace: &str) -> SchemaEvent {
+        SchemaEvent::new(
+            event_type,
+            Uuid::new_v4(),
+            SemanticVersion::new(1, 0, 0),
+            "tester".to_string(),
+            EventPayload::Generic {
+                data: HashMap::new(),
+            },
+        )
+        .with_metadata(NAMESPACE_KEY.to_string(), serde_json::json!(namespace))
+    }
+
+    #[tokio::test]
+    async fn test_subscription_receives_matching_events_only() {
+        let stream = EventStream::new();
+        let mut subscription = stream.subscribe(EventFilter {
+            namespace: Some("com.example".to_string()),
+            event_types: Vec::new(),
+        });
+
+        for namespace in ["com.examples", "org.other", "com.example.billing"] {
+            let payload = serde_json::to_string(&event(EventType::SchemaDeleted, namespace));
+            stream.relay_payload(&payload.unwrap());
+        }
+        stream.relay_payload("not an event");
+
+        let Some(StreamItem::Event(received)) = subscription.next().await else {
+            panic!("expected an event");
+        };
+        assert_eq!(received.event_type, EventType::SchemaDeleted);
+        assert_eq!(
+            received.metadata[NAMESPACE_KEY],
+            serde_json::json!("com.example.billing")
+        );
+    }
+}
@@ -0,0 +1,259 @@
+//! Compatibility exemptions
+//!
+//! Admin endpoints granting and revoking exemptions, and the Postgres store
+//! every replica reads them from.
+
+use super::*;
+use schema_registry_compatibility::{CompatibilityExemption, ExemptionRequest, ExemptionStore};
+
+#[derive(Debug, Deserialize)]
+struct ListExemptionsQuery {
+    #[serde(default)]
+    include_inactive: bool,
+}
+
+pub(crate) async fn grant_exemption(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    BoundedJson(req): BoundedJson<ExemptionRequest>,
+) -> Result<(StatusCode, Json<serde_json::Value>), AppError> {
+    let admin = require_admin(&state, &headers)?;
+    let actor = ChangeActor::from_request(&state, &headers, None, None)?;
+    let namespace = split_subject(&req.subject).0;
+    ensure_unfrozen(&state, &actor, &namespace, "grant_exemption").await?;
+    let exemption = state
+        .exemptions
+        .grant(req, &admin)
+        .await
+        .map_err(|e| match e {
+            CoreError::ValidationError(message) => AppError::InvalidInput(message),
+            e => AppError::Internal(e.to_string()),
+        })?;
+
+    let event = AuditEvent::new(
+        AuditEventType::CompatibilityExemptionGranted,
+        "Compatibility exemption granted".to_string(),
+        AuditResult::Success,
+        String::new(),
+    )
+    .with_user(admin, None)
+    .with_resource("compatibility_exemption".to_string(), exemption.id.to_string())
+    .with_metadata("subject".to_string(), serde_json::json!(exemption.subject))
+    .with_metadata("violation_type".to_string(), serde_json::json!(exemption.violation_type))
+    .with_metadata("reason".to_string(), serde_json::json!(exemption.reason))
+    .with_metadata("expires_at".to_string(), serde_json::json!(exemption.expires_at));
+    state.audit_logger.log(event).await;
+
+    Ok((StatusCode::CREATED, Json(serde_json::json!(exemption))))
+}
+
+pub(crate) async fn list_exemptions(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<ListExemptionsQuery>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    require_admin(&state, &headers)?;
+    let exemptions = state
+        .exemptions
+        .list(query.include_inactive)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    Ok(Json(serde_json::json!({ "exemptions": exemptions })))
+}
+
+pub(crate) async fn revoke_exemption(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let admin = require_admin(&state, &headers)?;
+    let existing = state
+        .exemptions
+        .get(id)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound(format!("Exemption {} not found", id)))?;
+    let actor = ChangeActor::from_request(&state, &headers, None, None)?;
+    let namespace = split_subject(&existing.subject).0;
+    ensure_unfrozen(&state, &actor, &namespace, "revoke_exemption").await?;
+    let exemption = state
+        .exemptions
+        .revoke(id, &admin)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound(format!("Exemption {} not found", id)))?;
+
+    let event = AuditEvent::new(
+        AuditEventType::CompatibilityExemptionRevoked,
+        "Compatibility exemption revoked".to_string(),
+        AuditResult::Success,
+        String::new(),
+    )
+    .with_user(admin, None)
+    .with_resource("compatibility_exemption".to_string(), id.to_string())
+    .with_metadata("subject".to_string(), serde_json::json!(exemption.subject));
+    state.audit_logger.log(event).await;
+
+    Ok(Json(serde_json::json!(exemption)))
+}
+
+/// Exemptions in the `compatibility_exemptions` table
+pub(crate) struct PostgresExemptionStore {
+    pub(crate) db: PgPool,
+}
+
+type ExemptionRow = (
+    Uuid,
+    String,
+    String,
+    String,
+    String,
+    chrono::DateTime<Utc>,
+    chrono::DateTime<Utc>,
+    Option<chrono::DateTime<Utc>>,
+    Option<String>,
+);
+
+const EXEMPTION_COLUMNS: &str =
+    "id, subject, violation_type, reason, granted_by, granted_at, expires_at, revoked_at, revoked_by";
+
+fn exemption_from_row(
+    (
+        id,
+        subject,
+        violation_type,
+        reason,
+        granted_by,
+        granted_at,
+        expires_at,
+        revoked_at,
+        revoked_by,
+    ): ExemptionRow,
+) -> CoreResult<CompatibilityExemption> {
+    Ok(CompatibilityExemption {
+        id,
+        subject,
+        violation_type: serde_json::from_value(serde_json::Value::String(violation_type))?,
+        reason,
+        granted_by,
+        granted_at,
+        expires_at,
+        revoked_at,
+        revoked_by,
+    })
+}
+
+#[async_trait::async_trait]
+impl ExemptionStore for PostgresExemptionStore {
+    async fn insert(&self, exemption: &CompatibilityExemption) -> CoreResult<()> {
+        let violation_type = serde_json::to_value(&exemption.violation_type)?;
+        sqlx::query(
+            r#"
+            INSERT INTO compatibility_exemptions (
+                id, subject, violation_type, reason, granted_by, granted_at, expires_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+        )
+        .bind(exemption.id)
+        .bind(&exemption.subject)
+        .bind(violation_type.as_str())
+        .bind(&exemption.reason)
+        .bind(&exemption.granted_by)
+        .bind(exemption.granted_at)
+        .bind(exemption.expires_at)
+        .execute(&self.db)
+        .await
+        .map_err(|e| CoreError::StorageError(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn revoke(
+        &self,
+        id: Uuid,
+        revoked_by: &str,
+        revoked_at: chrono::DateTime<Utc>,
+    ) -> CoreResult<Option<CompatibilityExemption>> {
+        // The first revocation wins; later ones return it unchanged
+        let row: Option<ExemptionRow> = sqlx::query_as(&format!(
+            r#"
+            UPDATE compatibility_exemptions
+            SET revoked_at = COALESCE(revoked_at, $2),
+                revoked_by = CASE WHEN revoked_at IS NULL THEN $3 ELSE revoked_by END
+            WHERE id = $1
+            RETURNING {EXEMPTION_COLUMNS}
+            "#
+        ))
+        .bind(id)
+        .bind(revoked_at)
+        .bind(revoked_by)
+        .fetch_optional(&self.db)
+        .await
+        .map_err(|e| CoreError::StorageError(e.to_string()))?;
+        row.map(exemption_from_row).transpose()
+    }
+
+    async fn get(&self, id: Uuid) -> CoreResult<Option<CompatibilityExemption>> {
+        let row: Option<ExemptionRow> = sqlx::query_as(&format!(
+            "SELECT {EXEMPTION_COLUMNS} FROM compatibility_exemptions WHERE id = $1"
+        ))
+        .bind(id)
+        .fetch_optional(&self.db)
+        .await
+        .map_err(|e| CoreError::StorageError(e.to_string()))?;
+        row.map(exemption_from_row).transpose()
+    }
+
+    async fn list(
+        &self,
+        subject: Option<&str>,
+        active_at: Option<chrono::DateTime<Utc>>,
+    ) -> CoreResult<Vec<CompatibilityExemption>> {
+        let rows: Vec<ExemptionRow> = sqlx::query_as(&format!(
+            r#"
+            SELECT {EXEMPTION_COLUMNS}
+            FROM compatibility_exemptions
+            WHERE ($1::text IS NULL OR subject = $1)
+              AND ($2::timestamptz IS NULL OR (revoked_at IS NULL AND expires_at > $2))
+            ORDER BY granted_at
+            "#
+        ))
+        .bind(subject)
+        .bind(active_at)
+        .fetch_all(&self.db)
+        .await
+        .map_err(|e| CoreError::StorageError(e.to_string()))?;
+        rows.into_iter().map(exemption_from_row).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use schema_registry_core::types::ViolationType;
+
+    fn row(violation_type: &str) -> ExemptionRow {
+        let now = Utc::now();
+        (
+            Uuid::new_v4(),
+            "orders.Order".to_string(),
+            violation_type.to_string(),
+            "Every producer sends id".to_string(),
+            "alice".to_string(),
+            now,
+            now + chrono::Duration::days(1),
+            None,
+            None,
+        )
+    }
+
+    #[test]
+    fn test_rows_keep_the_violation_type_they_were_stored_with() {
+        let stored = serde_json::to_value(ViolationType::RequiredAdded).unwrap();
+        let exemption = exemption_from_row(row(stored.as_str().unwrap())).unwrap();
+        assert_eq!(exemption.violation_type, ViolationType::RequiredAdded);
+        assert!(exemption.is_active_at(Utc::now()));
+
+        assert!(exemption_from_row(row("NOT_A_VIOLATION")).is_err());
+    }
+}
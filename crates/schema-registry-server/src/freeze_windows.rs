@@ -0,0 +1,141 @@
+//! Change freeze windows
+//!
+//! Admin endpoints listing, saving and removing freeze windows, the Postgres
+//! store every replica reads them from, and the freeze file loader.
+
+use super::*;
+use schema_registry_security::freeze::{ChangeFreezeConfig, FreezeWindow, FreezeWindowStore};
+
+/// Read change freeze windows from a file (YAML, or JSON)
+pub(crate) fn load_change_freeze_config(
+    path: &std::path::Path,
+) -> anyhow::Result<ChangeFreezeConfig> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", path.display(), e))?;
+    serde_yaml::from_str(&content)
+        .map_err(|e| anyhow::anyhow!("Invalid change freeze file {}: {}", path.display(), e))
+}
+
+pub(crate) async fn list_freeze_windows(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<serde_json::Value>, AppError> {
+    require_admin(&state, &headers)?;
+    let now = Utc::now();
+    let windows: Vec<serde_json::Value> = state
+        .freezes
+        .list()
+        .await
+        .map_err(AppError::Frozen)?
+        .into_iter()
+        .map(|window| {
+            let in_force_until = window.in_force_until(now);
+            let mut value = serde_json::json!(window);
+            value["in_force_until"] = serde_json::json!(in_force_until);
+            value
+        })
+        .collect();
+    Ok(Json(serde_json::json!({ "windows": windows })))
+}
+
+/// Add a freeze window, or replace the one with the same name
+pub(crate) async fn put_freeze_window(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(name): Path<String>,
+    BoundedJson(mut window): BoundedJson<FreezeWindow>,
+) -> Result<Json<FreezeWindow>, AppError> {
+    let admin = require_admin(&state, &headers)?;
+    window.name = name;
+    let window = state
+        .freezes
+        .put(window)
+        .await
+        .map_err(AppError::Frozen)?;
+
+    let event = AuditEvent::new(
+        AuditEventType::ConfigurationChanged,
+        "Change freeze window saved".to_string(),
+        AuditResult::Success,
+        String::new(),
+    )
+    .with_user(admin, None)
+    .with_resource("freeze_window".to_string(), window.name.clone())
+    .with_metadata("window".to_string(), serde_json::json!(window));
+    state.audit_logger.log(event).await;
+
+    Ok(Json(window))
+}
+
+pub(crate) async fn delete_freeze_window(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(name): Path<String>,
+) -> Result<StatusCode, AppError> {
+    let admin = require_admin(&state, &headers)?;
+    let removed = state.freezes.remove(&name).await.map_err(AppError::Frozen)?;
+    if removed.is_none() {
+        return Err(AppError::NotFound(format!(
+            "Freeze window '{}' not found",
+            name
+        )));
+    }
+
+    let event = AuditEvent::new(
+        AuditEventType::ConfigurationChanged,
+        "Change freeze window removed".to_string(),
+        AuditResult::Success,
+        String::new(),
+    )
+    .with_user(admin, None)
+    .with_resource("freeze_window".to_string(), name);
+    state.audit_logger.log(event).await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Freeze windows in the `freeze_windows` table, so every replica enforces
+/// those set through the admin API and they outlive a restart
+pub(crate) struct PostgresFreezeWindowStore {
+    pub(crate) db: PgPool,
+}
+
+#[async_trait::async_trait]
+impl FreezeWindowStore for PostgresFreezeWindowStore {
+    async fn list(&self) -> Result<Vec<FreezeWindow>, FreezeError> {
+        let rows: Vec<(sqlx::types::Json<FreezeWindow>,)> =
+            sqlx::query_as("SELECT window_definition FROM freeze_windows ORDER BY name")
+                .fetch_all(&self.db)
+                .await
+                .map_err(|e| FreezeError::Storage(e.to_string()))?;
+        Ok(rows.into_iter().map(|(window,)| window.0).collect())
+    }
+
+    async fn put(&self, window: &FreezeWindow) -> Result<(), FreezeError> {
+        sqlx::query(
+            r#"
+            INSERT INTO freeze_windows (name, window_definition, updated_at)
+            VALUES ($1, $2, NOW())
+            ON CONFLICT (name) DO UPDATE
+            SET window_definition = EXCLUDED.window_definition, updated_at = NOW()
+            "#,
+        )
+        .bind(&window.name)
+        .bind(sqlx::types::Json(window))
+        .execute(&self.db)
+        .await
+        .map_err(|e| FreezeError::Storage(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn remove(&self, name: &str) -> Result<Option<FreezeWindow>, FreezeError> {
+        let row: Option<(sqlx::types::Json<FreezeWindow>,)> = sqlx::query_as(
+            "DELETE FROM freeze_windows WHERE name = $1 RETURNING window_definition",
+        )
+        .bind(name)
+        .fetch_optional(&self.db)
+        .await
+        .map_err(|e| FreezeError::Storage(e.to_string()))?;
+        Ok(row.map(|(window,)| window.0))
+    }
+}
@@ -0,0 +1,405 @@
+//! GraphQL API
+//!
+//! A read-only GraphQL view of subjects, versions and their lineage, with
+//! query cost limits.
+
+use super::*;
+use async_graphql::{
+    ComplexObject, Context, EmptyMutation, EmptySubscription, ErrorExtensions, Json as GraphQLJson,
+    Object, SimpleObject,
+};
+use async_graphql_axum::{GraphQLRequest, GraphQLResponse};
+
+/// Schema of the read-only GraphQL API at `/api/v1/graphql`
+///
+/// Dashboards ask for a schema, its versions, metadata and lineage in one
+/// round trip instead of walking the REST API. Each request is handed the
+/// [`AppState`] and its headers as context data.
+pub(crate) type RegistryGraphQL =
+    async_graphql::Schema<GraphQLQuery, EmptyMutation, EmptySubscription>;
+
+/// Deepest a GraphQL query may nest, so lineage can't be followed without end
+const GRAPHQL_MAX_DEPTH: usize = 12;
+
+/// Most fields one GraphQL query may resolve
+const GRAPHQL_MAX_COMPLEXITY: usize = 2_000;
+
+pub(crate) fn graphql_schema() -> RegistryGraphQL {
+    async_graphql::Schema::build(GraphQLQuery, EmptyMutation, EmptySubscription)
+        .limit_depth(GRAPHQL_MAX_DEPTH)
+        .limit_complexity(GRAPHQL_MAX_COMPLEXITY)
+        .finish()
+}
+
+/// Report an error with the same reason resource conditions use as its code
+fn graphql_error(e: AppError) -> async_graphql::Error {
+    let code = e.reason();
+    async_graphql::Error::new(e.to_string())
+        .extend_with(|_, extensions| extensions.set("code", code))
+}
+
+/// A served schema, `None` when there is none with that ID
+async fn graphql_schema_by_id(
+    state: &AppState,
+    id: Uuid,
+) -> async_graphql::Result<Option<GraphQLSchemaVersion>> {
+    match fetch_schema(state, id).await {
+        Ok(schema) => Ok(Some(GraphQLSchemaVersion(schema))),
+        Err(AppError::NotFound(_)) => Ok(None),
+        Err(e) => Err(graphql_error(e)),
+    }
+}
+
+/// Effective owner of a namespace, inherited from its parents when not set
+fn namespace_owner(state: &AppState, namespace: &str) -> async_graphql::Result<Option<String>> {
+    let settings = state
+        .namespaces
+        .effective(namespace)
+        .map_err(|e| graphql_error(AppError::InvalidInput(e.to_string())))?;
+    Ok(settings.owner.value)
+}
+
+struct GraphQLQuery;
+
+#[Object]
+impl GraphQLQuery {
+    /// A schema version by ID
+    async fn schema(
+        &self,
+        ctx: &Context<'_>,
+        id: Uuid,
+    ) -> async_graphql::Result<Option<GraphQLSchemaVersion>> {
+        let state = ctx.data::<AppState>()?;
+        let started = std::time::Instant::now();
+        let schema = graphql_schema_by_id(state, id).await?;
+        if schema.is_some() {
+            let headers = ctx.data::<HeaderMap>()?;
+            record_usage(state, id, UsageOperation::Read, headers, started.elapsed());
+        }
+        Ok(schema)
+    }
+
+    /// A subject by name, following renames to its current name
+    async fn subject(
+        &self,
+        ctx: &Context<'_>,
+        subject: String,
+    ) -> async_graphql::Result<Option<GraphQLSubject>> {
+        let state = ctx.data::<AppState>()?;
+        let (namespace, name) = split_subject(&subject);
+        let (namespace, name, _) = resolve_subject(state, namespace, name)
+            .await
+            .map_err(graphql_error)?;
+        let exists: Option<(Uuid,)> = sqlx::query_as(
+            "SELECT id FROM schemas WHERE namespace = $1 AND name = $2 AND state <> $3 LIMIT 1",
+        )
+        .bind(&namespace)
+        .bind(&name)
+        .bind(DELETED_STATE)
+        .fetch_optional(&state.db)
+        .await
+        .map_err(|e| graphql_error(e.into()))?;
+        Ok(exists.map(|_| GraphQLSubject { namespace, name }))
+    }
+
+    /// Subjects of a namespace and the namespaces nested in it, by name
+    ///
+    /// `limit` is capped at the configured page size.
+    async fn subjects(
+        &self,
+        ctx: &Context<'_>,
+        namespace: String,
+        #[graphql(default)] offset: u32,
+        limit: Option<u32>,
+    ) -> async_graphql::Result<Vec<GraphQLSubject>> {
+        let state = ctx.data::<AppState>()?;
+        let max_page_size = state.query_limits.max_page_size;
+        let limit = limit.unwrap_or(max_page_size).min(max_page_size);
+        let rows: Vec<(String, String)> = sqlx::query_as(
+            r#"
+            SELECT DISTINCT namespace, name
+            FROM schemas
+            WHERE (namespace = $1 OR namespace LIKE $2) AND state <> $3
+            ORDER BY namespace, name
+            LIMIT $4 OFFSET $5
+            "#,
+        )
+        .bind(&namespace)
+        .bind(format!("{}.%", glob_to_like(&namespace)))
+        .bind(DELETED_STATE)
+        .bind(i64::from(limit))
+        .bind(i64::from(offset))
+        .fetch_all(&state.db)
+        .await
+        .map_err(|e| graphql_error(e.into()))?;
+        Ok(rows
+            .into_iter()
+            .map(|(namespace, name)| GraphQLSubject { namespace, name })
+            .collect())
+    }
+}
+
+/// A subject: the versions of one schema name in a namespace
+struct GraphQLSubject {
+    namespace: String,
+    name: String,
+}
+
+impl GraphQLSubject {
+    /// The subject's served versions, oldest first
+    async fn served_versions(
+        &self,
+        state: &AppState,
+    ) -> async_graphql::Result<Vec<GraphQLSchemaVersion>> {
+        let ids: Vec<(Uuid,)> = sqlx::query_as(
+            r#"
+            SELECT id
+            FROM schemas
+            WHERE namespace = $1 AND name = $2 AND state NOT IN ($3, $4)
+            ORDER BY version_major, version_minor, version_patch
+            "#,
+        )
+        .bind(&self.namespace)
+        .bind(&self.name)
+        .bind(DELETED_STATE)
+        .bind(SchemaState::Quarantined.to_string())
+        .fetch_all(&state.db)
+        .await
+        .map_err(|e| graphql_error(e.into()))?;
+        let mut versions = Vec::with_capacity(ids.len());
+        for (id,) in ids {
+            versions.extend(graphql_schema_by_id(state, id).await?);
+        }
+        Ok(versions)
+    }
+}
+
+#[Object(name = "Subject")]
+impl GraphQLSubject {
+    async fn subject(&self) -> String {
+        subject_of(&self.namespace, &self.name)
+    }
+
+    async fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    async fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Effective owner of the subject's namespace
+    async fn owner(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<String>> {
+        namespace_owner(ctx.data::<AppState>()?, &self.namespace)
+    }
+
+    /// Versions that are neither deleted nor quarantined, oldest first
+    async fn versions(
+        &self,
+        ctx: &Context<'_>,
+    ) -> async_graphql::Result<Vec<GraphQLSchemaVersion>> {
+        self.served_versions(ctx.data::<AppState>()?).await
+    }
+
+    /// Highest served version
+    async fn latest(
+        &self,
+        ctx: &Context<'_>,
+    ) -> async_graphql::Result<Option<GraphQLSchemaVersion>> {
+        Ok(self.served_versions(ctx.data::<AppState>()?).await?.pop())
+    }
+}
+
+/// One version of a schema, as `GET /api/v1/schemas/:id` returns it
+struct GraphQLSchemaVersion(GetSchemaResponse);
+
+#[Object(name = "Schema")]
+impl GraphQLSchemaVersion {
+    async fn id(&self) -> Uuid {
+        self.0.id
+    }
+
+    /// Integer ID Kafka serializers embed in messages
+    async fn global_id(&self) -> Option<i64> {
+        self.0.global_id
+    }
+
+    async fn namespace(&self) -> &str {
+        &self.0.namespace
+    }
+
+    async fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    async fn version(&self) -> &str {
+        &self.0.version
+    }
+
+    async fn format(&self) -> &str {
+        &self.0.format
+    }
+
+    /// The schema document, parsed when it is JSON
+    async fn schema(&self) -> GraphQLJson<&serde_json::Value> {
+        GraphQLJson(&self.0.schema)
+    }
+
+    async fn content(&self) -> &str {
+        &self.0.content
+    }
+
+    async fn state(&self) -> &str {
+        &self.0.state
+    }
+
+    async fn compatibility_mode(&self) -> &str {
+        &self.0.compatibility_mode
+    }
+
+    async fn metadata(&self) -> GraphQLJson<&HashMap<String, serde_json::Value>> {
+        GraphQLJson(&self.0.metadata)
+    }
+
+    async fn created_at(&self) -> &str {
+        &self.0.created_at
+    }
+
+    async fn updated_at(&self) -> &str {
+        &self.0.updated_at
+    }
+
+    /// The subject this is a version of
+    async fn subject(&self) -> GraphQLSubject {
+        GraphQLSubject {
+            namespace: self.0.namespace.clone(),
+            name: self.0.name.clone(),
+        }
+    }
+
+    /// Effective owner of the schema's namespace
+    async fn owner(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<String>> {
+        namespace_owner(ctx.data::<AppState>()?, &self.0.namespace)
+    }
+
+    /// Schemas and entities this schema depends on
+    async fn dependencies(
+        &self,
+        ctx: &Context<'_>,
+    ) -> async_graphql::Result<Vec<GraphQLLineageEdge>> {
+        let state = ctx.data::<AppState>()?;
+        let dependencies = lineage_dependencies(state, self.0.id)
+            .await
+            .map_err(graphql_error)?;
+        Ok(dependencies.into_iter().map(Into::into).collect())
+    }
+
+    /// Schemas that depend on this one
+    async fn dependents(
+        &self,
+        ctx: &Context<'_>,
+    ) -> async_graphql::Result<Vec<GraphQLLineageEdge>> {
+        let state = ctx.data::<AppState>()?;
+        let dependents = lineage_dependents(state, self.0.id)
+            .await
+            .map_err(graphql_error)?;
+        Ok(dependents.into_iter().map(Into::into).collect())
+    }
+
+    /// Applications, pipelines, models and schemas known to read this schema
+    async fn consumers(
+        &self,
+        ctx: &Context<'_>,
+    ) -> async_graphql::Result<Vec<GraphQLConsumer>> {
+        let state = ctx.data::<AppState>()?;
+        let consumers = lineage_consumers(state, self.0.id)
+            .await
+            .map_err(graphql_error)?;
+        Ok(consumers
+            .into_iter()
+            .map(|consumer| GraphQLConsumer {
+                id: consumer.id,
+                name: consumer.name,
+                entity_type: entity_type_name(&consumer.entity_type),
+                relation: consumer.relation.to_string(),
+            })
+            .collect())
+    }
+}
+
+/// `SCREAMING_SNAKE_CASE` name of an entity type, as the REST API spells it
+fn entity_type_name(entity_type: &EntityType) -> String {
+    serde_json::to_value(entity_type)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// One edge of a schema's lineage, to a schema or an external entity
+#[derive(SimpleObject)]
+#[graphql(name = "LineageEdge", complex)]
+struct GraphQLLineageEdge {
+    relation: String,
+    created_at: chrono::DateTime<Utc>,
+    /// ID of the schema at the other end, `null` for an entity
+    schema_id: Option<Uuid>,
+    /// Fully qualified name of the schema, or the entity's name
+    name: String,
+    entity_type: String,
+}
+
+impl From<LineageLink> for GraphQLLineageEdge {
+    fn from(link: LineageLink) -> Self {
+        let (schema_id, name, entity_type) = match link.other {
+            DependencyTarget::Schema(node) => (Some(node.schema_id), node.fqn, EntityType::Schema),
+            DependencyTarget::External(entity) => (None, entity.name, entity.entity_type),
+        };
+        Self {
+            relation: link.relation.to_string(),
+            created_at: link.created_at,
+            schema_id,
+            name,
+            entity_type: entity_type_name(&entity_type),
+        }
+    }
+}
+
+#[ComplexObject]
+impl GraphQLLineageEdge {
+    /// The schema at the other end, `null` for an entity or a schema that is
+    /// no longer registered
+    async fn schema(
+        &self,
+        ctx: &Context<'_>,
+    ) -> async_graphql::Result<Option<GraphQLSchemaVersion>> {
+        match self.schema_id {
+            Some(id) => graphql_schema_by_id(ctx.data::<AppState>()?, id).await,
+            None => Ok(None),
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+#[graphql(name = "Consumer")]
+struct GraphQLConsumer {
+    /// Entity ID, or schema ID for a schema
+    id: String,
+    name: String,
+    entity_type: String,
+    relation: String,
+}
+
+/// Run a GraphQL query, sent as a POST body or GET query parameters
+pub(crate) async fn graphql_query(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    request: GraphQLRequest,
+) -> GraphQLResponse {
+    let request = request.into_inner().data(state.clone()).data(headers);
+    state.graphql.execute(request).await.into()
+}
+
+/// The GraphQL schema in SDL, for client code generators
+pub(crate) async fn graphql_sdl(State(state): State<AppState>) -> String {
+    state.graphql.sdl()
+}
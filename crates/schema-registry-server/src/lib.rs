@@ -0,0 +1,7751 @@
+pub mod audit_store;
+pub mod cache_ttl;
+pub mod change_bus;
+pub mod consumer_webhooks;
+pub mod event_stream;
+pub mod middleware;
+pub mod namespace_store;
+pub mod ownership;
+pub mod resource_store;
+pub mod snapshots;
+pub mod startup;
+pub mod watchdog;
+
+mod confluent_api;
+mod delegation;
+mod exemptions;
+mod freeze_windows;
+mod graphql;
+mod lifecycle;
+mod metrics;
+mod provenance;
+mod public_api;
+mod quarantine;
+mod registration;
+mod server_config;
+
+pub use metrics::{spawn_metrics_server, MetricsAccess};
+pub use server_config::ServerConfig;
+
+use axum::{
+    body::{to_bytes, Body, Bytes},
+    extract::{
+        ws::{Message as WsMessage, WebSocket, WebSocketUpgrade},
+        DefaultBodyLimit, FromRequest, MatchedPath, Path, Query, Request, State,
+    },
+    http::{HeaderMap, HeaderName, HeaderValue, StatusCode},
+    middleware::{self as axum_middleware, Next},
+    response::{
+        sse::{Event as SseEvent, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    routing::{delete, get, patch, post, put},
+    Extension, Json, Router,
+};
+use chrono::Utc;
+use futures::Stream;
+use ipnet::IpNet;
+use redis::aio::ConnectionManager;
+use schema_registry_analytics::{
+    feature_usage, AnalyticsEngine, Anomaly, CapacityHistory, ComplexitySummary, FeatureDimension,
+    FeatureUsageReport, GrowthSample, Operation as UsageOperation, SchemaComplexityEntry,
+    SchemaHealthScore, TopSchemaEntry,
+};
+use schema_registry_compatibility::{
+    history::{
+        CompatibilityCheckRecord, CompatibilityHistory, CompatibilityHistoryQuery,
+        CompatibilityHistoryStore, DEFAULT_HISTORY_LIMIT, MAX_HISTORY_LIMIT,
+    },
+    reverification::{self, ConsumerManifestStore, PinnedDependency},
+    BreakageReportSink, CompatibilityCheckerImpl, CompatibilityTrace, ExemptionRegistry,
+    NamespaceBreakageReport, ReverificationJob, SubjectVersions,
+};
+use schema_registry_core::{
+    bounded_json::{self, JsonLimitError, JsonLimits},
+    canonical_form, canonical_json,
+    codegen::{self, Language},
+    complexity::{ComplexityGrowthThresholds, SchemaComplexity},
+    config_manager_adapter::{ConfigConsumer, ConfigConsumerExt},
+    deadline::{self, Deadline},
+    deprecation,
+    error::{Error as CoreError, Result as CoreResult},
+    events::{EventPayload, EventType, SchemaEvent},
+    field_diff::FieldManifest,
+    fields::FieldSelection,
+    fixtures::{self, LineageFixture},
+    id::IdGenerator,
+    messages::{self, Message, MessageCatalogs},
+    namespace::{
+        EffectiveCompatibility, EffectiveSettings, NamespaceSettings, NamespaceTree, Visibility,
+    },
+    plugin::{InterceptorChain, RegistrationStage, Veto},
+    resource::{
+        Condition, ConditionStatus, SchemaResource, SchemaSpec, WatchError, API_VERSION,
+        CONDITION_READY,
+    },
+    retry::{Classify, ErrorClass, RetryBudget, RetryPolicy},
+    schema::{RegisteredSchema, SchemaMetadata},
+    state::{SchemaLifecycle, SchemaState},
+    subject_config::{SubjectConfigChange, SubjectConfigFile},
+    traits::{
+        CompatibilityChecker, CompatibilityResult, CompatibilityViolation, EventPublisher,
+        SchemaValidator,
+    },
+    types::{CompatibilityMode, SerializationFormat},
+    versioning::SemanticVersion,
+};
+use schema_registry_lineage::{
+    Consumer, DependencyTarget, EntityType, ExternalEntity, RelationType, SchemaNode,
+};
+use schema_registry_observability::SamplingControl;
+use schema_registry_security::{
+    audit::{AuditEvent, AuditEventType, AuditLogger, AuditResult},
+    auth::{JwtManager, TokenRevocationList},
+    directory::{GroupRoleMapper, JsonFileMappingSource, OidcGroupProvider, OidcTokenVerifier},
+    freeze::{
+        ActiveFreeze, ChangeFreezeManager, FreezeCheck, FreezeError, FreezeNotificationSink,
+        TracingFreezeNotificationSink, WebhookFreezeNotificationSink, FREEZE_OVERRIDE_HEADER,
+        FREEZE_OVERRIDE_PERMISSION,
+    },
+    impersonation::{ImpersonationConfig, ImpersonationManager},
+    quarantine::{
+        SecurityNotificationSink, TracingNotificationSink, WebhookNotificationSink,
+        SECURITY_REVIEW_PERMISSION,
+    },
+    siem::{AuditSink, AuditStreamer, HttpBatchFormat, HttpBatchSink, SyslogSink, SyslogTransport},
+    signing::DocumentSigner,
+    spiffe::{SpiffeAuthProvider, SpiffeConfig},
+    token_exchange::{TokenExchangeConfig, TokenExchanger, TokenScope, SCHEMA_WRITE_PERMISSION},
+    QuarantineManager,
+};
+use schema_registry_storage::cache_metrics::{CacheMetrics, CacheTier};
+use schema_registry_storage::gc::{
+    schema_id_from_key, GarbageCollector, GcCandidate, GcConfig, GcReport, GcTarget, ScanPage,
+    SchemaIndex,
+};
+use schema_registry_storage::query_cost::{QueryCostError, QueryLimits};
+use schema_registry_validation::{
+    engine::ValidationEngine as SecurityScanner,
+    format_detection::{self, FormatDetection},
+    instance::streaming::{self, StreamLayout},
+    regex_complexity::RegexPolicy,
+    types::{
+        SchemaFormat, ValidationConfig, ValidationError as RuleFinding, ValidationStep,
+        ValidationWarning as RuleWarning,
+    },
+    wasm_rules::WasmRuleLoader, ValidationEngine,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sqlx::postgres::PgPoolOptions;
+use sqlx::PgPool;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::convert::Infallible;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use subtle::ConstantTimeEq;
+use tower_http::trace::TraceLayer;
+use uuid::Uuid;
+
+use crate::audit_store::PostgresAuditLog;
+use crate::cache_ttl::AdaptiveTtl;
+use crate::change_bus::{ChangeBus, RECHECK_INTERVAL};
+use crate::confluent_api::{
+    confluent_check_compatibility, confluent_delete_config, confluent_get_config,
+    confluent_get_global_config, confluent_get_schema_by_id, confluent_get_version,
+    confluent_get_version_schema, confluent_list_subjects, confluent_list_versions,
+    confluent_lookup, confluent_put_config, confluent_put_global_config, confluent_register,
+};
+use crate::consumer_webhooks::{ConsumerManifest, ConsumerNotifier};
+use crate::delegation::{
+    exchange_token, impersonate, impersonation_context, scoped_token_context, DirectorySignIn,
+    Impersonation, ScopedToken, DEFAULT_GROUP_ROLE_MAPPING_REFRESH_SECS, DEFAULT_OIDC_AUDIENCE,
+    DEFAULT_TOKEN_EXCHANGE_MAX_TTL_SECS,
+};
+use crate::event_stream::{
+    EventFilter, EventStream, EventSubscription, PgEventPublisher, StreamItem, NAMESPACE_KEY,
+};
+use crate::exemptions::{grant_exemption, list_exemptions, revoke_exemption, PostgresExemptionStore};
+use crate::freeze_windows::{
+    delete_freeze_window, list_freeze_windows, load_change_freeze_config, put_freeze_window,
+    PostgresFreezeWindowStore,
+};
+use crate::graphql::{graphql_query, graphql_schema, graphql_sdl, RegistryGraphQL};
+use crate::lifecycle::{
+    archive_schema, change_schema_state, delete_schema, delete_subject, delete_subject_version,
+    get_schema_usage, lifecycle_state, record_usage, settable_next_states, update_schema_metadata,
+    InvalidTransition, SchemaUsageReport, StateChangeResponse, DELETED_STATE,
+};
+use crate::metrics::{
+    CANCELLED_REQUESTS, CHANGE_FREEZE_DECISIONS, CODEGEN_REQUESTS, FEATURE_USAGE,
+    OPERATION_DURATION, PUBLIC_API_REQUESTS, SUBJECT_CONFIG_DRIFT, VALIDATION_SCHEMA_SIZE,
+    VALIDATION_STEP_DURATION,
+};
+use crate::namespace_store::NamespaceSettingsStore;
+use crate::ownership::{
+    OwnershipTransfer, OwnershipTransfers, TransferError, TransferRequest, TransferStatus,
+};
+use crate::provenance::{
+    get_schema_provenance, record_schema_event, replay_audit_event, ExemptionTrail,
+    FreezeOverride, GateOutcome, GateResult, RegistrationTrail, SCHEMA_EVENT_EXEMPTION_APPLIED,
+    SCHEMA_EVENT_REGISTERED, SCHEMA_EVENT_STATE_CHANGED,
+};
+use crate::public_api::{
+    public_api_guard, public_get_schema, public_list_subject_versions, public_search_schemas,
+    spawn_public_rate_limit_cleanup, PublicApi, DEFAULT_PUBLIC_CACHE_TTL_SECS,
+    DEFAULT_PUBLIC_RATE_LIMIT,
+};
+use crate::quarantine::release_quarantined_schema;
+use crate::registration::register_schema;
+use crate::resource_store::PostgresResourceStore;
+use crate::snapshots::{RestoreReport, SnapshotContents, Snapshots};
+use crate::startup::{LazyRedis, StartupPhases, Subsystem, WarmupHints};
+use crate::watchdog::{DependencyWatchdog, HealthProbe, StorageMode};
+
+// ============================================================================
+// Application State
+// ============================================================================
+
+#[derive(Clone)]
+struct AppState {
+    db: PgPool,
+    redis: LazyRedis,
+    validator: Arc<ValidationEngine>,
+    compatibility_checker: Arc<CompatibilityCheckerImpl>,
+    id_generator: Arc<dyn IdGenerator>,
+    security_scanner: Arc<SecurityScanner>,
+    quarantine: Arc<QuarantineManager>,
+    json_limits: JsonLimits,
+    /// Largest request body accepted, in bytes
+    max_request_size: usize,
+    /// Largest schema a registration may carry, in bytes
+    max_schema_size: usize,
+    audit_logger: Arc<AuditLogger>,
+    exemptions: Arc<ExemptionRegistry>,
+    compat_history: Arc<CompatibilityHistory>,
+    namespaces: Arc<NamespaceTree>,
+    /// Writes changes to `namespaces` through to Postgres
+    namespace_store: Arc<NamespaceSettingsStore>,
+    /// Committed subject config file the registry is compared against
+    subject_config_file: Option<Arc<PathBuf>>,
+    /// Deployment environment whose compatibility overrides apply
+    environment: Option<String>,
+    resources: Arc<PostgresResourceStore>,
+    interceptors: Arc<InterceptorChain>,
+    complexity_thresholds: ComplexityGrowthThresholds,
+    /// Compatibility checks and validations at least this slow are logged
+    slow_operation_threshold: Duration,
+    /// Cost limits for search and reporting queries
+    query_limits: QueryLimits,
+    /// Shared by every storage retry, so an outage doesn't multiply the load
+    retry_budget: Arc<RetryBudget>,
+    admin_token: Option<String>,
+    /// `None` unless impersonation is enabled
+    impersonation: Option<Arc<ImpersonationManager>>,
+    /// `None` unless scoped tokens can be exchanged for
+    token_exchange: Option<Arc<TokenExchanger>>,
+    /// `None` unless OIDC tokens can be exchanged for scoped tokens
+    directory: Option<Arc<DirectorySignIn>>,
+    /// `None` unless workloads can authenticate with JWT-SVIDs
+    spiffe: Option<Arc<SpiffeAuthProvider>>,
+    public_api: Arc<PublicApi>,
+    freezes: Arc<ChangeFreezeManager>,
+    /// `None` unless spans are exported with OpenTelemetry
+    trace_sampling: Option<SamplingControl>,
+    /// Schema reads and validations, reviewed before a schema is retired
+    analytics: Arc<AnalyticsEngine>,
+    /// Tells consumers when fields they read change
+    consumer_notifier: Arc<ConsumerNotifier>,
+    /// Checks consumer pins against the next release of each subject
+    reverification: Arc<ReverificationJob>,
+    /// Wakes requests waiting on a version when any replica changes it
+    changes: Arc<ChangeBus>,
+    /// Schema events from every replica, for live event subscribers
+    events: Arc<EventStream>,
+    /// Sends schema events to every replica's subscribers
+    event_publisher: Arc<dyn EventPublisher>,
+    /// Read-only GraphQL API over schemas and their lineage
+    graphql: RegistryGraphQL,
+    /// Switches to cache-bypass mode while Redis is down
+    watchdog: Arc<DependencyWatchdog>,
+    /// Picks how long each schema stays cached
+    cache_ttl: Arc<AdaptiveTtl>,
+    /// Hits, misses and fills of the schema cache
+    cache_metrics: Arc<CacheMetrics>,
+    /// Rows changed by destructive admin operations, kept for undo
+    snapshots: Arc<Snapshots>,
+    /// Namespaces being handed from one team to another
+    ownership_transfers: Arc<OwnershipTransfers>,
+    /// `None` unless a provenance signing key is configured
+    provenance_signer: Option<Arc<DocumentSigner>>,
+    /// Translations of violation messages, picked by `Accept-Language`
+    messages: Arc<MessageCatalogs>,
+}
+
+impl AppState {
+    /// Retry policy for an idempotent storage call
+    fn retry_policy(&self, operation: &'static str) -> RetryPolicy {
+        RetryPolicy::new(operation).with_budget(self.retry_budget.clone())
+    }
+
+    /// Cache connection, `None` while the watchdog bypasses the cache or
+    /// Redis is still connecting
+    fn cache(&self) -> Option<ConnectionManager> {
+        if !self.watchdog.cache_enabled() {
+            return None;
+        }
+        self.redis.connection()
+    }
+}
+
+// ============================================================================
+// Request/Response Models
+// ============================================================================
+
+#[derive(Debug, Deserialize, Serialize)]
+struct RegisterSchemaRequest {
+    // K6 test format
+    subject: String,
+    schema: serde_json::Value,
+    /// Detected from the content when neither this nor `format` is given
+    #[serde(default)]
+    schema_type: String,
+
+    // Optional fields for compatibility with other formats
+    #[serde(default)]
+    namespace: Option<String>,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    version_major: Option<i32>,
+    #[serde(default)]
+    version_minor: Option<i32>,
+    #[serde(default)]
+    version_patch: Option<i32>,
+    #[serde(default)]
+    format: Option<String>,
+    #[serde(default)]
+    content: Option<String>,
+    #[serde(default = "default_state")]
+    state: String,
+    /// Inherited from the namespace settings when not given
+    #[serde(default)]
+    compatibility_mode: Option<CompatibilityMode>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    metadata: HashMap<String, serde_json::Value>,
+}
+
+fn default_state() -> String {
+    "DRAFT".to_string()
+}
+
+#[derive(Debug, Serialize)]
+struct RegisterSchemaResponse {
+    id: Uuid,
+    /// Integer ID Kafka serializers embed in messages
+    global_id: i64,
+    version: String,
+    created_at: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    warnings: Vec<String>,
+}
+
+/// A registration's response, with the version's URL in `Location`
+type Registered = (
+    StatusCode,
+    [(&'static str, String); 1],
+    Json<RegisterSchemaResponse>,
+);
+
+fn registration_response(status: StatusCode, response: RegisterSchemaResponse) -> Registered {
+    let location = format!("/api/v1/schemas/{}", response.id);
+    (status, [("location", location)], Json(response))
+}
+
+/// The version a registration collided with when inserting
+#[derive(Debug, Serialize)]
+struct StoredRegistration {
+    id: Uuid,
+    global_id: i64,
+    subject: String,
+    version: String,
+    created_at: String,
+    /// Whether the stored version has the content the registration sent
+    same_content: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct GetSchemaResponse {
+    id: Uuid,
+    /// Integer ID Kafka serializers embed in messages
+    #[serde(skip_serializing_if = "Option::is_none")]
+    global_id: Option<i64>,
+    namespace: String,
+    name: String,
+    version: String,
+    format: String,
+    #[serde(serialize_with = "canonical_json::value")]
+    schema: serde_json::Value,
+    content: String,
+    state: String,
+    compatibility_mode: String,
+    #[serde(serialize_with = "canonical_json::sorted_value_map")]
+    metadata: HashMap<String, serde_json::Value>,
+    created_at: String,
+    updated_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    complexity: Option<SchemaComplexity>,
+}
+
+/// Fields of [`GetSchemaResponse`] that `?fields=` may select
+const SCHEMA_FIELDS: &[&str] = &[
+    "id",
+    "global_id",
+    "namespace",
+    "name",
+    "version",
+    "format",
+    "schema",
+    "content",
+    "state",
+    "compatibility_mode",
+    "metadata",
+    "created_at",
+    "updated_at",
+    "complexity",
+];
+
+/// Sparse fieldset of a read, as in `?fields=id,version,state,metadata`
+#[derive(Debug, Deserialize)]
+struct FieldsQuery {
+    #[serde(default)]
+    fields: Option<String>,
+}
+
+impl FieldsQuery {
+    /// Respond with the selected fields of `response`, or all of them
+    fn respond<T: Serialize>(&self, response: T, known: &[&str]) -> Result<Response, AppError> {
+        let body = self.serialize(response, known)?;
+        Ok(([("content-type", "application/json")], body).into_response())
+    }
+
+    /// The body [`FieldsQuery::respond`] sends for `response`
+    fn serialize<T: Serialize>(&self, response: T, known: &[&str]) -> Result<Vec<u8>, AppError> {
+        let body = match &self.fields {
+            None => serde_json::to_vec(&response),
+            Some(fields) => {
+                let selection = FieldSelection::parse(fields, known)
+                    .map_err(|e| AppError::InvalidInput(e.to_string()))?;
+                serde_json::to_vec(&selection.apply(serde_json::json!(response)))
+            }
+        };
+        body.map_err(|e| AppError::Internal(format!("Failed to serialize response: {}", e)))
+    }
+}
+
+/// `?raw=true` on a schema read, for the schema file itself
+#[derive(Debug, Default, Deserialize)]
+struct RawQuery {
+    #[serde(default)]
+    raw: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct ValidateResponse {
+    is_valid: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    errors: Vec<String>,
+    /// Deprecated fields the data still uses
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    warnings: Vec<String>,
+    /// `errors` then `warnings`, with their stable codes
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    details: Vec<Message>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CompatibilityCheckRequest {
+    schema_id: Uuid,
+    compared_schema_id: Uuid,
+    /// Mode to check with instead of the subject's effective mode
+    #[serde(default)]
+    mode: Option<CompatibilityMode>,
+    /// Environment whose overrides apply instead of the server's own
+    #[serde(default)]
+    environment: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct CompatibilityCheckResponse {
+    is_compatible: bool,
+    mode: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    violations: Vec<String>,
+    /// `violations` with their stable codes
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    details: Vec<Message>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    exempted_violations: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    exemptions_applied: Vec<Uuid>,
+    explain: ModeExplain,
+}
+
+/// A compatibility check with every rule evaluated to reach it
+#[derive(Debug, Serialize)]
+struct CompatibilityDryRunResponse {
+    #[serde(flatten)]
+    check: CompatibilityCheckResponse,
+    trace: CompatibilityTrace,
+}
+
+/// Which scope provided the mode of a compatibility check
+#[derive(Debug, Serialize)]
+#[serde(tag = "scope", rename_all = "snake_case")]
+enum ModeExplain {
+    /// Named in the request
+    Request,
+    /// Resolved from the namespace settings
+    Settings(EffectiveCompatibility),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SearchSchemasRequest {
+    /// Text matched against names, descriptions and content
+    #[serde(default)]
+    query: String,
+    #[serde(default)]
+    search_mode: Option<String>,
+    #[serde(default)]
+    namespace: Option<String>,
+    /// Namespace glob, e.g. `telemetry.*`
+    #[serde(default)]
+    namespace_pattern: Option<String>,
+    /// Schemas must carry all of these tags
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    state: Option<String>,
+    #[serde(default)]
+    format: Option<String>,
+    #[serde(default)]
+    created_after: Option<chrono::DateTime<Utc>>,
+    #[serde(default)]
+    created_before: Option<chrono::DateTime<Utc>>,
+    #[serde(default)]
+    label_selector: Option<String>,
+    #[serde(default)]
+    limit: Option<u32>,
+    #[serde(default)]
+    offset: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+struct SearchSchemasResponse {
+    results: Vec<SearchResult>,
+    /// Matches across all pages
+    total: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct SearchResult {
+    metadata: SearchResultMetadata,
+    score: f32,
+}
+
+#[derive(Debug, Serialize)]
+struct SearchResultMetadata {
+    schema_id: Uuid,
+    namespace: String,
+    name: String,
+    version: String,
+    format: String,
+    created_at: String,
+    updated_at: String,
+}
+
+#[derive(Debug, Serialize)]
+struct SubjectVersionsResponse {
+    subject: String,
+    aliases: Vec<String>,
+    versions: Vec<SubjectVersion>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    warnings: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct SubjectVersion {
+    id: Uuid,
+    version: String,
+    state: String,
+    created_at: String,
+    /// Compatibility exemptions the version was registered under
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    exemptions: Vec<Uuid>,
+}
+
+/// Most items one page may hold
+const MAX_PAGE_LIMIT: u32 = 1000;
+
+/// A page of a list, as in `?limit=50&offset=100`
+#[derive(Debug, Deserialize)]
+struct PaginationParams {
+    #[serde(default = "default_page_limit")]
+    limit: u32,
+    #[serde(default)]
+    offset: u32,
+}
+
+fn default_page_limit() -> u32 {
+    50
+}
+
+#[derive(Debug, Serialize)]
+struct SchemaVersionsResponse {
+    namespace: String,
+    name: String,
+    versions: Vec<VersionInfo>,
+    /// Versions across all pages
+    total: u64,
+    limit: u32,
+    offset: u32,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    warnings: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct VersionInfo {
+    version: String,
+    schema_id: Uuid,
+    state: String,
+    created_at: String,
+    /// Hash of the version's canonical content
+    checksum: String,
+    /// Compatibility exemptions the version was registered under
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    exemptions: Vec<Uuid>,
+}
+
+#[derive(Debug, Serialize)]
+struct SubjectChangelogResponse {
+    subject: String,
+    /// Oldest first
+    entries: Vec<ChangelogEntry>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    warnings: Vec<String>,
+}
+
+/// A `schema_events` row, with the version it belongs to
+#[derive(Debug, Serialize)]
+struct ChangelogEntry {
+    schema_id: Uuid,
+    version: String,
+    event_type: String,
+    actor: Option<String>,
+    at: chrono::DateTime<Utc>,
+    details: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct ConsumerManifestRequest {
+    webhook_url: String,
+    fields: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ConsumerManifestsResponse {
+    subject: String,
+    consumers: Vec<ConsumerManifest>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RenameSubjectRequest {
+    new_subject: String,
+}
+
+#[derive(Debug, Serialize)]
+struct RenameSubjectResponse {
+    subject: String,
+    alias: String,
+    schemas_moved: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct GcQuery {
+    #[serde(default = "default_gc_dry_run")]
+    dry_run: bool,
+    #[serde(default)]
+    max_deletions: Option<usize>,
+}
+
+fn default_gc_dry_run() -> bool {
+    true
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ResourceMetadataRequest {
+    #[serde(default)]
+    labels: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApplySchemaResourceRequest {
+    #[serde(default)]
+    metadata: ResourceMetadataRequest,
+    spec: SchemaSpec,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ResourceListQuery {
+    #[serde(default)]
+    watch: bool,
+    /// Watch for changes after this version (default: now)
+    #[serde(default)]
+    resource_version: Option<u64>,
+    #[serde(default)]
+    timeout_seconds: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApplySubjectConfigQuery {
+    #[serde(default)]
+    dry_run: bool,
+    /// Also remove settings of subjects the file doesn't mention
+    #[serde(default)]
+    prune: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct SubjectConfigDriftReport {
+    file: String,
+    in_sync: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    changes: Vec<SubjectConfigChange>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CodegenQuery {
+    lang: String,
+}
+
+#[derive(Debug, Serialize)]
+struct HealthResponse {
+    status: String,
+    /// `cache_bypass` while the watchdog has found Redis down
+    mode: StorageMode,
+    components: BTreeMap<String, ComponentHealth>,
+}
+
+#[derive(Debug, Serialize)]
+struct ComponentHealth {
+    status: String,
+    message: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct CapabilitiesResponse {
+    server_version: &'static str,
+    api_versions: Vec<&'static str>,
+    formats: Vec<String>,
+    compatibility_modes: Vec<String>,
+    limits: LimitsInfo,
+    features: BTreeMap<&'static str, bool>,
+}
+
+#[derive(Debug, Serialize)]
+struct LimitsInfo {
+    max_request_bytes: usize,
+    max_schema_bytes: usize,
+    max_json_depth: usize,
+    max_json_nodes: usize,
+    max_string_length: usize,
+}
+
+// ============================================================================
+// Error Handling
+// ============================================================================
+
+/// A request body or schema over its configured size
+#[derive(Debug)]
+struct SizeLimitExceeded {
+    /// What was too large: `request` or `schema`
+    subject: &'static str,
+    limit: usize,
+    /// `None` for a body sent without a `Content-Length` and cut off at the
+    /// limit
+    actual: Option<usize>,
+}
+
+impl SizeLimitExceeded {
+    fn request(limit: usize, actual: Option<usize>) -> Self {
+        Self {
+            subject: "request",
+            limit,
+            actual,
+        }
+    }
+
+    fn schema(limit: usize, actual: usize) -> Self {
+        Self {
+            subject: "schema",
+            limit,
+            actual: Some(actual),
+        }
+    }
+}
+
+enum AppError {
+    Database(sqlx::Error),
+    Redis(redis::RedisError),
+    NotFound(String),
+    InvalidInput(String),
+    Unauthorized(String),
+    /// An authenticated caller acting outside its token's scope
+    Forbidden(String),
+    Conflict(String),
+    Gone(String),
+    Quarantined(String),
+    QuotaExceeded(String),
+    /// A read asking for a representation the resource doesn't have
+    NotAcceptable(String),
+    LimitExceeded(JsonLimitError),
+    /// A request body or schema larger than the server accepts
+    TooLarge(SizeLimitExceeded),
+    Vetoed(Veto),
+    DeadlineExceeded(String),
+    RateLimited(String),
+    /// A change rejected by a change freeze
+    Frozen(FreezeError),
+    /// A search or report rejected by its cost limits, with hints to narrow it
+    QueryTooExpensive(QueryCostError, Vec<String>),
+    /// Archiving or deleting a schema that is still in use, without `force`
+    InUse(Box<SchemaUsageReport>),
+    /// A state change the schema lifecycle doesn't allow
+    InvalidTransition(Box<InvalidTransition>),
+    /// A registration beaten to its version by a concurrent one, e.g. on
+    /// another replica, that stored different content
+    RegistrationConflict(Box<StoredRegistration>),
+    /// A registration breaking compatibility with earlier versions of its
+    /// subject, with no exemption covering the breaking changes
+    Incompatible(Box<CompatibilityResult>),
+    Internal(String),
+}
+
+impl AppError {
+    /// Report a guarded query's rejection along with ways to narrow it
+    fn query_cost(e: QueryCostError, hints: Vec<String>) -> Self {
+        match e {
+            QueryCostError::Database(e) => AppError::Database(e),
+            e => AppError::QueryTooExpensive(e, hints),
+        }
+    }
+
+    /// How a storage failure should be reported, `None` for other errors
+    fn storage_class(&self) -> Option<ErrorClass> {
+        match self {
+            AppError::Database(e) => Some(e.class()),
+            AppError::Redis(e) => Some(e.class()),
+            _ => None,
+        }
+    }
+
+    /// CamelCase reason reported in resource status conditions
+    fn reason(&self) -> &'static str {
+        match self.storage_class() {
+            Some(ErrorClass::Transient) => return "Unavailable",
+            Some(ErrorClass::Conflict) => return "Conflict",
+            _ => {}
+        }
+        match self {
+            AppError::Database(_) | AppError::Redis(_) | AppError::Internal(_) => "InternalError",
+            AppError::NotFound(_) => "NotFound",
+            AppError::InvalidInput(_) => "InvalidSpec",
+            AppError::Unauthorized(_) => "Unauthorized",
+            AppError::Forbidden(_) => "Forbidden",
+            AppError::Conflict(_) => "Conflict",
+            AppError::Gone(_) => "Gone",
+            AppError::Quarantined(_) => "Quarantined",
+            AppError::QuotaExceeded(_) => "QuotaExceeded",
+            AppError::NotAcceptable(_) => "NotAcceptable",
+            AppError::LimitExceeded(_) => "LimitExceeded",
+            AppError::TooLarge(_) => "TooLarge",
+            AppError::Vetoed(_) => "Vetoed",
+            AppError::DeadlineExceeded(_) => "DeadlineExceeded",
+            AppError::RateLimited(_) => "RateLimited",
+            AppError::Frozen(_) => "Frozen",
+            AppError::QueryTooExpensive(..) => "QueryTooExpensive",
+            AppError::InUse(_) => "InUse",
+            AppError::InvalidTransition(_) => "InvalidTransition",
+            AppError::RegistrationConflict(_) => "Conflict",
+            AppError::Incompatible(_) => "Incompatible",
+        }
+    }
+}
+
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::Database(e) => write!(f, "Database error: {}", e),
+            AppError::Redis(e) => write!(f, "Cache error: {}", e),
+            AppError::LimitExceeded(e) => write!(f, "{}", e),
+            AppError::TooLarge(e) => {
+                let subject = match e.subject {
+                    "schema" => "Schema",
+                    _ => "Request body",
+                };
+                match e.actual {
+                    Some(actual) => write!(
+                        f,
+                        "{} is {} bytes, exceeding the limit of {} bytes",
+                        subject, actual, e.limit
+                    ),
+                    None => write!(f, "{} exceeds the limit of {} bytes", subject, e.limit),
+                }
+            }
+            AppError::Vetoed(veto) => write!(f, "{}", veto),
+            AppError::Frozen(e) => write!(f, "{}", e),
+            AppError::QueryTooExpensive(e, _) => write!(f, "{}", e),
+            AppError::InUse(report) => write!(
+                f,
+                "Schema {} was used {} times in the last {} days; pass force=true with a reason to retire it anyway",
+                report.schema_id, report.usage.total_operations, report.usage.days
+            ),
+            AppError::InvalidTransition(e) => write!(
+                f,
+                "Cannot move a schema from {} to {}",
+                e.from, e.to
+            ),
+            AppError::RegistrationConflict(stored) => write!(
+                f,
+                "Version {} of '{}' is already registered with different content",
+                stored.version, stored.subject
+            ),
+            AppError::Incompatible(result) => write!(
+                f,
+                "Schema is incompatible with earlier versions of its subject under {}: {}",
+                result.mode,
+                describe_violations(&result.violations).join("; ")
+            ),
+            AppError::NotFound(msg)
+            | AppError::InvalidInput(msg)
+            | AppError::Unauthorized(msg)
+            | AppError::Forbidden(msg)
+            | AppError::Conflict(msg)
+            | AppError::Gone(msg)
+            | AppError::Quarantined(msg)
+            | AppError::QuotaExceeded(msg)
+            | AppError::NotAcceptable(msg)
+            | AppError::DeadlineExceeded(msg)
+            | AppError::RateLimited(msg)
+            | AppError::Internal(msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            AppError::Database(_) | AppError::Redis(_) => self
+                .storage_class()
+                .and_then(|class| StatusCode::from_u16(class.http_status()).ok())
+                .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            AppError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::InvalidInput(_) => StatusCode::BAD_REQUEST,
+            AppError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            AppError::Forbidden(_) => StatusCode::FORBIDDEN,
+            AppError::Conflict(_) => StatusCode::CONFLICT,
+            AppError::Gone(_) => StatusCode::GONE,
+            AppError::Quarantined(_) => StatusCode::LOCKED,
+            AppError::QuotaExceeded(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            AppError::NotAcceptable(_) => StatusCode::NOT_ACCEPTABLE,
+            AppError::DeadlineExceeded(_) => StatusCode::GATEWAY_TIMEOUT,
+            AppError::RateLimited(_) => StatusCode::TOO_MANY_REQUESTS,
+            AppError::LimitExceeded(e) => {
+                let status = match e {
+                    JsonLimitError::TooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+                    JsonLimitError::Parse(_) => StatusCode::BAD_REQUEST,
+                    _ => StatusCode::UNPROCESSABLE_ENTITY,
+                };
+                let body = Json(serde_json::json!({
+                    "error": e.to_string(),
+                    "code": e.code(),
+                    "limit": e.limit(),
+                }));
+                return (status, body).into_response();
+            }
+            AppError::TooLarge(e) => {
+                let body = Json(serde_json::json!({
+                    "error": self.to_string(),
+                    "code": format!("{}-size-exceeded", e.subject),
+                    "limit": e.limit,
+                    "actual": e.actual,
+                }));
+                return (StatusCode::PAYLOAD_TOO_LARGE, body).into_response();
+            }
+            AppError::Frozen(e) => {
+                let mut body = serde_json::json!({ "error": e.to_string() });
+                match e {
+                    FreezeError::Frozen { freeze, .. } => {
+                        body["code"] = "CHANGE_FREEZE".into();
+                        body["freeze"] = serde_json::json!(freeze);
+                    }
+                    FreezeError::OverrideDenied { freeze, .. } => {
+                        body["code"] = "FREEZE_OVERRIDE_DENIED".into();
+                        body["freeze"] = serde_json::json!(freeze);
+                    }
+                    FreezeError::InvalidWindow(_) => {
+                        return (StatusCode::BAD_REQUEST, Json(body)).into_response();
+                    }
+                    FreezeError::Storage(_) => {
+                        return (StatusCode::INTERNAL_SERVER_ERROR, Json(body)).into_response();
+                    }
+                }
+                return (StatusCode::LOCKED, Json(body)).into_response();
+            }
+            AppError::Vetoed(veto) => {
+                let body = Json(serde_json::json!({
+                    "error": veto.to_string(),
+                    "plugin": veto.plugin,
+                    "stage": veto.stage,
+                    "reason": veto.reason,
+                }));
+                return (StatusCode::UNPROCESSABLE_ENTITY, body).into_response();
+            }
+            AppError::QueryTooExpensive(e, hints) => {
+                let mut body = serde_json::json!({
+                    "error": e.to_string(),
+                    "hints": hints,
+                });
+                match e {
+                    QueryCostError::TooExpensive { estimate, max_cost } => {
+                        body["code"] = "QUERY_TOO_EXPENSIVE".into();
+                        body["estimate"] = serde_json::json!(estimate);
+                        body["max_cost"] = serde_json::json!(max_cost);
+                    }
+                    QueryCostError::TimedOut { timeout } => {
+                        body["code"] = "QUERY_TIMEOUT".into();
+                        body["timeout_ms"] = serde_json::json!(timeout.as_millis() as u64);
+                    }
+                    QueryCostError::PageTooLarge { max, .. } => {
+                        body["code"] = "PAGE_TOO_LARGE".into();
+                        body["max_page_size"] = serde_json::json!(max);
+                    }
+                    QueryCostError::Database(_) => body["code"] = "QUERY_FAILED".into(),
+                }
+                return (StatusCode::UNPROCESSABLE_ENTITY, Json(body)).into_response();
+            }
+            AppError::InUse(report) => {
+                let body = Json(serde_json::json!({
+                    "error": self.to_string(),
+                    "code": "SCHEMA_IN_USE",
+                    "usage": report.usage,
+                    "consumers": report.consumers,
+                }));
+                return (StatusCode::CONFLICT, body).into_response();
+            }
+            AppError::InvalidTransition(e) => {
+                let body = Json(serde_json::json!({
+                    "error": self.to_string(),
+                    "code": "INVALID_STATE_TRANSITION",
+                    "from": e.from,
+                    "to": e.to,
+                    "allowed": e.allowed,
+                }));
+                return (StatusCode::CONFLICT, body).into_response();
+            }
+            AppError::RegistrationConflict(stored) => {
+                let body = Json(serde_json::json!({
+                    "error": self.to_string(),
+                    "code": "REGISTRATION_CONFLICT",
+                    "existing": stored,
+                }));
+                return (StatusCode::CONFLICT, body).into_response();
+            }
+            AppError::Incompatible(result) => {
+                let body = Json(serde_json::json!({
+                    "error": self.to_string(),
+                    "code": "INCOMPATIBLE_SCHEMA",
+                    "mode": result.mode.to_string(),
+                    "violations": result.violations,
+                }));
+                return (StatusCode::CONFLICT, body).into_response();
+            }
+        };
+
+        let body = Json(serde_json::json!({
+            "error": self.to_string(),
+        }));
+
+        (status, body).into_response()
+    }
+}
+
+impl From<sqlx::Error> for AppError {
+    fn from(e: sqlx::Error) -> Self {
+        AppError::Database(e)
+    }
+}
+
+impl From<redis::RedisError> for AppError {
+    fn from(e: redis::RedisError) -> Self {
+        AppError::Redis(e)
+    }
+}
+
+impl From<JsonLimitError> for AppError {
+    fn from(e: JsonLimitError) -> Self {
+        AppError::LimitExceeded(e)
+    }
+}
+
+impl From<TransferError> for AppError {
+    fn from(e: TransferError) -> Self {
+        match e {
+            TransferError::NotFound(msg) => AppError::NotFound(msg),
+            TransferError::Invalid(msg) => AppError::InvalidInput(msg),
+            TransferError::Conflict(msg) => AppError::Conflict(msg),
+            TransferError::Expired(msg) => AppError::Gone(msg),
+            TransferError::Forbidden(msg) => AppError::Forbidden(msg),
+            TransferError::Storage(msg) => AppError::Internal(msg),
+        }
+    }
+}
+
+// ============================================================================
+// Extractors
+// ============================================================================
+
+/// JSON body extractor that enforces the configured depth and size limits
+/// before deserializing, so hostile payloads never reach `serde_json`
+struct BoundedJson<T>(T);
+
+#[async_trait::async_trait]
+impl<T: DeserializeOwned> FromRequest<AppState> for BoundedJson<T> {
+    type Rejection = AppError;
+
+    async fn from_request(req: Request, state: &AppState) -> Result<Self, Self::Rejection> {
+        let body = Bytes::from_request(req, state).await.map_err(|e| {
+            if e.status() == StatusCode::PAYLOAD_TOO_LARGE {
+                AppError::TooLarge(SizeLimitExceeded::request(state.max_request_size, None))
+            } else {
+                AppError::InvalidInput(e.body_text())
+            }
+        })?;
+        Ok(BoundedJson(bounded_json::from_slice(&body, &state.json_limits)?))
+    }
+}
+
+/// Reject a body declared larger than `max_request_size` before reading it
+///
+/// Bodies sent without a `Content-Length` are cut off at the limit by
+/// [`DefaultBodyLimit`]; the plain-text rejection that leaves in handlers
+/// reading the body themselves is replaced by the same structured error.
+async fn enforce_request_size(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let declared = request
+        .headers()
+        .get("content-length")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<usize>().ok());
+    if let Some(actual) = declared.filter(|actual| *actual > state.max_request_size) {
+        let limit = SizeLimitExceeded::request(state.max_request_size, Some(actual));
+        return AppError::TooLarge(limit).into_response();
+    }
+    let response = next.run(request).await;
+    let is_json = response
+        .headers()
+        .get("content-type")
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.contains("json"));
+    if response.status() == StatusCode::PAYLOAD_TOO_LARGE && !is_json {
+        let limit = SizeLimitExceeded::request(state.max_request_size, None);
+        return AppError::TooLarge(limit).into_response();
+    }
+    response
+}
+
+// ============================================================================
+// Idempotency Keys
+// ============================================================================
+
+/// Header a client sets so a registration can be retried safely
+const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// Header marking a response as the stored result of an earlier request
+const IDEMPOTENT_REPLAYED_HEADER: &str = "idempotent-replayed";
+
+const MAX_IDEMPOTENCY_KEY_LEN: usize = 255;
+
+/// Headers of a stored response that describe how it was sent rather than
+/// what it says, so a replay sets them afresh
+const UNREPLAYED_HEADERS: &[&str] = &["connection", "content-length", "transfer-encoding"];
+
+/// How long a key's result is replayed
+const IDEMPOTENCY_KEY_TTL: Duration = Duration::from_secs(86_400);
+
+/// How long a key stays claimed by a request that never finished, e.g. on a
+/// replica that crashed, before a retry may run it again
+const IDEMPOTENCY_CLAIM_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// What an `Idempotency-Key` stands for when a request arrives with it
+enum IdempotencyClaim {
+    /// The key is this request's to run and record
+    Claimed,
+    /// An earlier request with the key is still running
+    InFlight,
+    /// The key was used for a different request
+    Mismatch,
+    /// The earlier request's response: its status, headers and body
+    Completed(StatusCode, Vec<(String, String)>, Vec<u8>),
+}
+
+/// Replay the result of a registration retried with the same `Idempotency-Key`
+///
+/// The first request with a key claims it and stores its response, and a
+/// retry with the same key and body gets that response back, headers
+/// included, marked `Idempotent-Replayed: true`. Keys are per principal and
+/// kept for a day, so a key needs credentials: callers without any would
+/// all share the `anonymous` principal and each other's responses. Only
+/// successful responses are stored; a failed request gives its key up so
+/// the retry runs again.
+async fn idempotent_registration(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(key) = request.headers().get(IDEMPOTENCY_KEY_HEADER) else {
+        return next.run(request).await;
+    };
+    let key = match key.to_str() {
+        Ok(key) if !key.is_empty() && key.len() <= MAX_IDEMPOTENCY_KEY_LEN => key.to_string(),
+        _ => {
+            return AppError::InvalidInput(format!(
+                "Idempotency-Key must be 1 to {} visible ASCII characters",
+                MAX_IDEMPOTENCY_KEY_LEN
+            ))
+            .into_response()
+        }
+    };
+    let actor = ChangeActor::from_request(
+        &state,
+        request.headers(),
+        request.extensions().get::<Impersonation>(),
+        request.extensions().get::<ScopedToken>(),
+    )
+    .and_then(|actor| actor.ensure_authenticated().map(|()| actor));
+    let principal = match actor {
+        Ok(actor) => actor.principal,
+        Err(e) => return e.into_response(),
+    };
+
+    let (parts, body) = request.into_parts();
+    let Ok(body) = to_bytes(body, state.max_request_size).await else {
+        let limit = SizeLimitExceeded::request(state.max_request_size, None);
+        return AppError::TooLarge(limit).into_response();
+    };
+    let request_hash = {
+        use sha2::{Digest, Sha256};
+        let mut hasher = Sha256::new();
+        hasher.update(parts.uri.to_string());
+        hasher.update(b"\n");
+        hasher.update(&body);
+        hex::encode(hasher.finalize())
+    };
+
+    match claim_idempotency_key(&state, &principal, &key, &request_hash).await {
+        Ok(IdempotencyClaim::Claimed) => {}
+        Ok(IdempotencyClaim::InFlight) => {
+            return AppError::Conflict(format!(
+                "A request with Idempotency-Key '{}' is still running; retry once it finishes",
+                key
+            ))
+            .into_response()
+        }
+        Ok(IdempotencyClaim::Mismatch) => {
+            return AppError::InvalidInput(format!(
+                "Idempotency-Key '{}' was already used for a different request",
+                key
+            ))
+            .into_response()
+        }
+        Ok(IdempotencyClaim::Completed(status, headers, body)) => {
+            let mut response = (status, body).into_response();
+            let replayed = response.headers_mut();
+            replayed.remove("content-type");
+            for (name, value) in headers {
+                let name = HeaderName::from_bytes(name.as_bytes());
+                let value = HeaderValue::from_str(&value);
+                if let (Ok(name), Ok(value)) = (name, value) {
+                    replayed.append(name, value);
+                }
+            }
+            replayed.insert(IDEMPOTENT_REPLAYED_HEADER, HeaderValue::from_static("true"));
+            return response;
+        }
+        Err(e) => return e.into_response(),
+    }
+
+    let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+    if !response.status().is_success() {
+        release_idempotency_key(&state, &principal, &key).await;
+        return response;
+    }
+    let (parts, body) = response.into_parts();
+    let body = match to_bytes(body, usize::MAX).await {
+        Ok(body) => body,
+        Err(e) => {
+            release_idempotency_key(&state, &principal, &key).await;
+            return AppError::Internal(format!("Failed to read the response: {}", e))
+                .into_response();
+        }
+    };
+    let headers: Vec<(String, String)> = parts
+        .headers
+        .iter()
+        .filter(|(name, _)| !UNREPLAYED_HEADERS.contains(&name.as_str()))
+        .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+        .collect();
+    let stored = sqlx::query(
+        r#"
+        UPDATE idempotency_keys
+        SET response_status = $3, response_headers = $4, response_body = $5
+        WHERE principal = $1 AND idempotency_key = $2
+        "#,
+    )
+    .bind(&principal)
+    .bind(&key)
+    .bind(parts.status.as_u16() as i16)
+    .bind(sqlx::types::Json(&headers))
+    .bind(body.as_ref())
+    .execute(&state.db)
+    .await;
+    if let Err(e) = stored {
+        // The registration happened; a retry is deduplicated by its content
+        // hash once the claim times out
+        tracing::warn!(error = %e, "Failed to store the result of an idempotent request");
+    }
+    Response::from_parts(parts, Body::from(body))
+}
+
+/// Request hash, response status, headers and body stored for a key
+type IdempotencyKeyRow = (
+    String,
+    Option<i16>,
+    Option<sqlx::types::Json<Vec<(String, String)>>>,
+    Option<Vec<u8>>,
+);
+
+/// Claim `key` for a request, or find out what it already stands for
+///
+/// A key whose result has expired, or whose request never finished, is
+/// claimed afresh.
+async fn claim_idempotency_key(
+    state: &AppState,
+    principal: &str,
+    key: &str,
+    request_hash: &str,
+) -> Result<IdempotencyClaim, AppError> {
+    let claimed: Option<(String,)> = sqlx::query_as(
+        r#"
+        INSERT INTO idempotency_keys (principal, idempotency_key, request_hash)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (principal, idempotency_key) DO UPDATE
+        SET request_hash = EXCLUDED.request_hash,
+            response_status = NULL,
+            response_headers = NULL,
+            response_body = NULL,
+            created_at = NOW()
+        WHERE idempotency_keys.created_at < NOW() - make_interval(secs => $4)
+           OR (idempotency_keys.response_status IS NULL
+               AND idempotency_keys.created_at < NOW() - make_interval(secs => $5))
+        RETURNING request_hash
+        "#,
+    )
+    .bind(principal)
+    .bind(key)
+    .bind(request_hash)
+    .bind(IDEMPOTENCY_KEY_TTL.as_secs_f64())
+    .bind(IDEMPOTENCY_CLAIM_TIMEOUT.as_secs_f64())
+    .fetch_optional(&state.db)
+    .await?;
+    if claimed.is_some() {
+        return Ok(IdempotencyClaim::Claimed);
+    }
+
+    let existing: Option<IdempotencyKeyRow> = sqlx::query_as(
+        r#"
+        SELECT request_hash, response_status, response_headers, response_body
+        FROM idempotency_keys
+        WHERE principal = $1 AND idempotency_key = $2
+        "#,
+    )
+    .bind(principal)
+    .bind(key)
+    .fetch_optional(&state.db)
+    .await?;
+    Ok(match existing {
+        Some((hash, ..)) if hash != request_hash => IdempotencyClaim::Mismatch,
+        Some((_, Some(status), headers, body)) => IdempotencyClaim::Completed(
+            u16::try_from(status)
+                .ok()
+                .and_then(|status| StatusCode::from_u16(status).ok())
+                .unwrap_or(StatusCode::OK),
+            headers.map(|headers| headers.0).unwrap_or_default(),
+            body.unwrap_or_default(),
+        ),
+        // Still running, or given up by a failed request since the claim
+        _ => IdempotencyClaim::InFlight,
+    })
+}
+
+/// Give `key` up so a retry runs again
+async fn release_idempotency_key(state: &AppState, principal: &str, key: &str) {
+    let released =
+        sqlx::query("DELETE FROM idempotency_keys WHERE principal = $1 AND idempotency_key = $2")
+            .bind(principal)
+            .bind(key)
+            .execute(&state.db)
+            .await;
+    if let Err(e) = released {
+        tracing::warn!(error = %e, "Failed to release an idempotency key");
+    }
+}
+
+/// Delete idempotency keys whose results are no longer replayed
+fn spawn_idempotency_key_cleanup(db: PgPool) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(3_600));
+        loop {
+            ticker.tick().await;
+            let deleted = sqlx::query(
+                "DELETE FROM idempotency_keys WHERE created_at < NOW() - make_interval(secs => $1)",
+            )
+            .bind(IDEMPOTENCY_KEY_TTL.as_secs_f64())
+            .execute(&db)
+            .await;
+            match deleted {
+                Ok(done) if done.rows_affected() > 0 => tracing::debug!(
+                    deleted = done.rows_affected(),
+                    "Deleted expired idempotency keys"
+                ),
+                Ok(_) => {}
+                Err(e) => tracing::warn!(error = %e, "Idempotency key cleanup failed"),
+            }
+        }
+    });
+}
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+async fn health_check(State(state): State<AppState>) -> Result<Json<HealthResponse>, AppError> {
+    let mut components = BTreeMap::new();
+
+    // Check PostgreSQL
+    let db_status = match sqlx::query("SELECT 1").fetch_one(&state.db).await {
+        Ok(_) => ComponentHealth {
+            status: "up".to_string(),
+            message: None,
+        },
+        Err(e) => ComponentHealth {
+            status: "down".to_string(),
+            message: Some(e.to_string()),
+        },
+    };
+    components.insert("database".to_string(), db_status);
+
+    // Check Redis
+    let redis_status = {
+        let ping = async {
+            let mut conn = state.redis.get().await?;
+            redis::cmd("PING").query_async::<_, String>(&mut conn).await
+        };
+        match ping.await {
+            Ok(_) => ComponentHealth {
+                status: "up".to_string(),
+                message: None,
+            },
+            Err(e) => ComponentHealth {
+                status: "down".to_string(),
+                message: Some(e.to_string()),
+            },
+        }
+    };
+    components.insert("redis".to_string(), redis_status);
+
+    let mode = state.watchdog.mode();
+    let overall_status =
+        if mode == StorageMode::Normal && components.values().all(|c| c.status == "up") {
+            "healthy"
+        } else {
+            "degraded"
+        };
+
+    Ok(Json(HealthResponse {
+        status: overall_status.to_string(),
+        mode,
+        components,
+    }))
+}
+
+/// Describe what this deployment supports so clients don't hardcode it
+async fn capabilities(State(state): State<AppState>) -> Json<CapabilitiesResponse> {
+    let limits = &state.json_limits;
+    let admin_enabled = state.admin_token.is_some();
+
+    let features = BTreeMap::from([
+        // Off while the watchdog bypasses an unreachable Redis
+        ("redis_cache", state.watchdog.cache_enabled()),
+        ("security_quarantine", true),
+        ("hierarchical_namespaces", true),
+        ("admin_api", admin_enabled),
+        ("compatibility_exemptions", admin_enabled),
+        ("admin_impersonation", admin_enabled && state.impersonation.is_some()),
+        ("token_exchange", state.token_exchange.is_some()),
+        ("schema_resources", admin_enabled),
+        ("registration_plugins", !state.interceptors.is_empty()),
+        ("slow_operation_log", true),
+        ("schema_search", true),
+        ("public_api", true),
+        ("change_freezes", true),
+        (
+            "cross_format_compatibility",
+            state.compatibility_checker.cross_format(),
+        ),
+        (
+            "compatibility_policy",
+            !state.compatibility_checker.policy().is_empty(),
+        ),
+        ("message_catalogs", !state.messages.is_empty()),
+        ("change_feed", true),
+        ("consumer_field_webhooks", true),
+        ("registration_replay", true),
+        ("batch_validation", true),
+        ("confluent_api", true),
+        ("event_stream", true),
+        ("graphql", true),
+        ("idempotency_keys", true),
+    ]);
+
+    Json(CapabilitiesResponse {
+        server_version: env!("CARGO_PKG_VERSION"),
+        api_versions: vec!["v1"],
+        formats: SerializationFormat::ALL.iter().map(|f| f.to_string()).collect(),
+        compatibility_modes: CompatibilityMode::ALL.iter().map(|m| m.to_string()).collect(),
+        limits: LimitsInfo {
+            max_request_bytes: limits.max_bytes.min(state.max_request_size),
+            max_schema_bytes: state.max_schema_size,
+            max_json_depth: limits.max_depth,
+            max_json_nodes: limits.max_nodes,
+            max_string_length: limits.max_string_length,
+        },
+        features,
+    })
+}
+
+async fn get_schema(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+    Query(query): Query<FieldsQuery>,
+    Query(raw): Query<RawQuery>,
+) -> Result<Response, AppError> {
+    let started = std::time::Instant::now();
+    let schema = fetch_schema(&state, id).await?;
+    record_usage(
+        &state,
+        id,
+        UsageOperation::Read,
+        &headers,
+        started.elapsed(),
+    );
+    if wants_raw_schema(&headers, raw.raw, &schema)? {
+        return Ok(raw_schema_response(&headers, &schema));
+    }
+    let body = query.serialize(schema, SCHEMA_FIELDS)?;
+    let etag = schema_etag(&body);
+    if not_modified(&headers, &etag) {
+        return Ok((StatusCode::NOT_MODIFIED, [("etag", etag)]).into_response());
+    }
+    let response_headers = [
+        ("content-type", "application/json".to_string()),
+        ("etag", etag),
+        ("vary", "accept".to_string()),
+    ];
+    Ok((response_headers, body).into_response())
+}
+
+/// A schema by the integer ID Kafka serializers embed in messages, as
+/// `GET /api/v1/schemas/:id` returns it
+async fn get_schema_by_global_id(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(global_id): Path<i64>,
+    query: Query<FieldsQuery>,
+    raw: Query<RawQuery>,
+) -> Result<Response, AppError> {
+    let id = schema_id_by_global_id(&state, global_id).await?;
+    get_schema(State(state), headers, Path(id), query, raw).await
+}
+
+/// UUID of the version with integer ID `global_id`
+async fn schema_id_by_global_id(state: &AppState, global_id: i64) -> Result<Uuid, AppError> {
+    let id: Option<(Uuid,)> = sqlx::query_as("SELECT id FROM schemas WHERE global_id = $1")
+        .bind(global_id)
+        .fetch_optional(&state.db)
+        .await?;
+    id.map(|(id,)| id)
+        .ok_or_else(|| AppError::NotFound(format!("No schema has global ID {}", global_id)))
+}
+
+/// ETag of a schema read: the hash of the body it sends
+///
+/// Anything that changes the body, e.g. the state, metadata, `updated_at` or
+/// the field selection, changes the tag. Metadata is serialized in key order,
+/// so the same schema always has the same body.
+fn schema_etag(body: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+    format!("\"{}\"", hex::encode(Sha256::digest(body)))
+}
+
+/// Whether the request's `If-None-Match` lists `etag` or is `*`
+fn not_modified(headers: &HeaderMap, etag: &str) -> bool {
+    headers
+        .get("if-none-match")
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| {
+            value.split(',').any(|tag| {
+                let tag = tag.trim();
+                tag == "*" || tag.trim_start_matches("W/") == etag
+            })
+        })
+}
+
+/// Media types of schema files, as opposed to the JSON a read returns
+const RAW_SCHEMA_TYPES: &[&str] = &[
+    "application/vnd.schemaregistry.v1+avro",
+    "text/x-protobuf",
+    "application/schema+json",
+];
+
+/// Media type and file extension of a schema file in a stored format
+fn raw_schema_type(format: &str) -> (&'static str, &'static str) {
+    match format {
+        "AVRO" => (RAW_SCHEMA_TYPES[0], "avsc"),
+        "PROTOBUF" => (RAW_SCHEMA_TYPES[1], "proto"),
+        _ => (RAW_SCHEMA_TYPES[2], "json"),
+    }
+}
+
+/// Whether a read should return the schema file rather than its JSON
+///
+/// `?raw=true` or an `Accept` naming the schema's file type asks for the
+/// file. An `Accept` naming only other formats' files can't be met.
+fn wants_raw_schema(
+    headers: &HeaderMap,
+    raw: bool,
+    schema: &GetSchemaResponse,
+) -> Result<bool, AppError> {
+    let accepted: Vec<String> = headers
+        .get_all("accept")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|range| range.split(';').next())
+        .map(|media_type| media_type.trim().to_ascii_lowercase())
+        .filter(|media_type| !media_type.is_empty())
+        .collect();
+    let (content_type, _) = raw_schema_type(&schema.format);
+    if accepted.iter().any(|media_type| media_type == content_type) {
+        return Ok(true);
+    }
+    let only_files = !accepted.is_empty()
+        && accepted
+            .iter()
+            .all(|media_type| RAW_SCHEMA_TYPES.contains(&media_type.as_str()));
+    if only_files {
+        return Err(AppError::NotAcceptable(format!(
+            "Schema {} is {}; it is only available as {} or application/json",
+            schema.id, schema.format, content_type
+        )));
+    }
+    Ok(raw)
+}
+
+/// The schema file as registered, named for download by code generators
+fn raw_schema_response(headers: &HeaderMap, schema: &GetSchemaResponse) -> Response {
+    // The file is the content alone, so its tag is the content's hash
+    let etag = format!(
+        "\"{}\"",
+        RegisteredSchema::calculate_content_hash(&schema.content)
+    );
+    if not_modified(headers, &etag) {
+        return (StatusCode::NOT_MODIFIED, [("etag", etag)]).into_response();
+    }
+    let (content_type, extension) = raw_schema_type(&schema.format);
+    let file_name = format!("{}-{}.{}", schema.name, schema.version, extension);
+    (
+        StatusCode::OK,
+        [
+            ("content-type", content_type.to_string()),
+            ("etag", etag),
+            ("vary", "accept".to_string()),
+            (
+                "content-disposition",
+                content_disposition("attachment", &file_name),
+            ),
+        ],
+        schema.content.clone(),
+    )
+        .into_response()
+}
+
+/// `Content-Disposition` naming a download `file_name`, per RFC 6266
+///
+/// Schema names are the registrant's, so the quoted `filename` keeps only
+/// characters that need no escaping and `filename*` carries the name in
+/// full, percent-encoded as UTF-8.
+fn content_disposition(disposition: &str, file_name: &str) -> String {
+    let fallback: String = file_name
+        .chars()
+        .map(|c| match c {
+            ' ' | '!' | '#'..='[' | ']'..='~' => c,
+            _ => '_',
+        })
+        .collect();
+    let mut encoded = String::with_capacity(file_name.len());
+    for byte in file_name.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' => encoded.push(byte as char),
+            b'!' | b'#' | b'$' | b'&' | b'+' | b'-' | b'.' | b'^' | b'_' | b'`' | b'|' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    format!(
+        "{}; filename=\"{}\"; filename*=UTF-8''{}",
+        disposition, fallback, encoded
+    )
+}
+
+async fn fetch_schema(state: &AppState, id: Uuid) -> Result<GetSchemaResponse, AppError> {
+    tracing::debug!(schema_id = %id, "Fetching schema");
+    state.cache_ttl.record_read(id);
+
+    // Try Redis cache first
+    let cache_key = format!("{}{}", SCHEMA_CACHE_PREFIX, id);
+    let mut cache = state.cache();
+
+    let started = std::time::Instant::now();
+    let cached = match &mut cache {
+        Some(conn) => {
+            let cached = redis::cmd("GET")
+                .arg(&cache_key)
+                .query_async::<_, Option<String>>(conn)
+                .await
+                .ok()
+                .flatten();
+            match cached {
+                Some(_) => state
+                    .cache_metrics
+                    .record_hit(CacheTier::L2, started.elapsed()),
+                None => state
+                    .cache_metrics
+                    .record_miss(CacheTier::L2, started.elapsed()),
+            }
+            cached
+        }
+        None => None,
+    };
+    if let Some(cached) = cached {
+        if let Ok(schema_data) = serde_json::from_str::<serde_json::Value>(&cached) {
+            tracing::debug!(schema_id = %id, "Cache hit");
+            ensure_servable(id, schema_data["state"].as_str().unwrap_or(""))?;
+
+            let version = format!(
+                "{}.{}.{}",
+                schema_data["version_major"].as_i64().unwrap_or(0),
+                schema_data["version_minor"].as_i64().unwrap_or(0),
+                schema_data["version_patch"].as_i64().unwrap_or(0)
+            );
+
+            // Parse content as JSON if it's JSON format
+            let content_str = schema_data["content"]
+                .as_str()
+                .unwrap_or("{}")
+                .to_string();
+            let schema_json = serde_json::from_str(&content_str).unwrap_or(serde_json::json!({}));
+
+            return Ok(GetSchemaResponse {
+                id: schema_data["id"]
+                    .as_str()
+                    .and_then(|s| Uuid::parse_str(s).ok())
+                    .unwrap_or(id),
+                global_id: schema_data["global_id"].as_i64(),
+                namespace: schema_data["namespace"]
+                    .as_str()
+                    .unwrap_or("")
+                    .to_string(),
+                name: schema_data["name"].as_str().unwrap_or("").to_string(),
+                version,
+                format: schema_data["format"].as_str().unwrap_or("").to_string(),
+                schema: schema_json,
+                content: content_str,
+                state: schema_data["state"].as_str().unwrap_or("").to_string(),
+                compatibility_mode: schema_data["compatibility_mode"]
+                    .as_str()
+                    .unwrap_or("")
+                    .to_string(),
+                metadata: serde_json::from_value(schema_data["metadata"].clone())
+                    .unwrap_or_default(),
+                created_at: cached_timestamp(&schema_data["created_at"]),
+                updated_at: cached_timestamp(&schema_data["updated_at"]),
+                complexity: serde_json::from_value(schema_data["complexity"].clone()).ok(),
+            });
+        }
+    }
+
+    tracing::debug!(schema_id = %id, "Cache miss, querying database");
+
+    // Fallback to PostgreSQL
+    let row: Option<(
+        Uuid,
+        i64,
+        String,
+        String,
+        i32,
+        i32,
+        i32,
+        String,
+        String,
+        String,
+        String,
+        Option<serde_json::Value>,
+        chrono::DateTime<Utc>,
+        chrono::DateTime<Utc>,
+        Option<serde_json::Value>,
+    )> = state
+        .retry_policy("schema_read")
+        .retry(|| {
+            // Complexity is read as one object to stay within the columns a
+            // row tuple can hold
+            sqlx::query_as(
+                r#"
+                SELECT id, global_id, namespace, name, version_major, version_minor,
+                       version_patch, format, content, state, compatibility_mode, metadata,
+                       created_at, updated_at,
+                       CASE WHEN size_bytes IS NOT NULL THEN jsonb_build_object(
+                           'size_bytes', size_bytes,
+                           'field_count', COALESCE(field_count, 0),
+                           'max_depth', COALESCE(max_depth, 0),
+                           'constraint_count', COALESCE(constraint_count, 0)
+                       ) END
+                FROM schemas
+                WHERE id = $1
+                LIMIT 1
+                "#,
+            )
+            .bind(id)
+            .fetch_optional(&state.db)
+        })
+        .await?;
+
+    match row {
+        Some((
+            id,
+            global_id,
+            namespace,
+            name,
+            version_major,
+            version_minor,
+            version_patch,
+            format,
+            content,
+            state_str,
+            compat_mode,
+            metadata,
+            created_at,
+            updated_at,
+            complexity,
+        )) => {
+            ensure_servable(id, &state_str)?;
+            let complexity: Option<SchemaComplexity> =
+                complexity.and_then(|complexity| serde_json::from_value(complexity).ok());
+            let version = format!("{}.{}.{}", version_major, version_minor, version_patch);
+
+            // Parse content as JSON
+            let schema_json = serde_json::from_str(&content).unwrap_or(serde_json::json!({}));
+
+            // Update cache
+            let cache_value = serde_json::json!({
+                "id": id.to_string(),
+                "global_id": global_id,
+                "namespace": namespace,
+                "name": name,
+                "version_major": version_major,
+                "version_minor": version_minor,
+                "version_patch": version_patch,
+                "format": format,
+                "content": content,
+                "state": state_str,
+                "compatibility_mode": compat_mode,
+                "metadata": metadata,
+                "complexity": complexity,
+                "created_at": created_at.to_rfc3339(),
+                "updated_at": updated_at.to_rfc3339(),
+            });
+
+            if let Some(conn) = &mut cache {
+                let since_update = (Utc::now() - updated_at).to_std().unwrap_or_default();
+                let started = std::time::Instant::now();
+                let filled: Result<(), _> = redis::cmd("SET")
+                    .arg(&cache_key)
+                    .arg(serde_json::to_string(&cache_value).unwrap())
+                    .arg("EX")
+                    .arg(state.cache_ttl.ttl(id, since_update).as_secs())
+                    .query_async(conn)
+                    .await;
+                if filled.is_ok() {
+                    state
+                        .cache_metrics
+                        .record_fill(CacheTier::L2, started.elapsed());
+                }
+            }
+
+            Ok(GetSchemaResponse {
+                id,
+                global_id: Some(global_id),
+                namespace,
+                name,
+                version,
+                format,
+                schema: schema_json,
+                content,
+                state: state_str,
+                compatibility_mode: compat_mode,
+                metadata: metadata
+                    .and_then(|m| serde_json::from_value(m).ok())
+                    .unwrap_or_default(),
+                created_at: created_at.to_rfc3339(),
+                updated_at: updated_at.to_rfc3339(),
+                complexity,
+            })
+        }
+        None => Err(AppError::NotFound(format!("Schema {} not found", id))),
+    }
+}
+
+/// A timestamp of a cached schema, or now for an entry cached without it
+fn cached_timestamp(value: &serde_json::Value) -> String {
+    value
+        .as_str()
+        .map(str::to_string)
+        .unwrap_or_else(|| Utc::now().to_rfc3339())
+}
+
+/// Default and maximum number of schemas listed by the complexity report
+const DEFAULT_MOST_COMPLEX: i64 = 10;
+const MAX_MOST_COMPLEX: i64 = 100;
+
+#[derive(Debug, Deserialize)]
+struct ComplexityReportQuery {
+    #[serde(default)]
+    limit: Option<i64>,
+}
+
+/// A version's ID, subject, metrics and registration time
+type ComplexityRow = (
+    Uuid,
+    String,
+    String,
+    i64,
+    i32,
+    i32,
+    i32,
+    chrono::DateTime<Utc>,
+);
+
+#[derive(Debug, Serialize)]
+struct ComplexityReport {
+    summary: ComplexitySummary,
+    most_complex: Vec<SchemaComplexityEntry>,
+}
+
+/// Size and complexity aggregated over every live version, and the versions
+/// with the most fields
+///
+/// Versions registered before metrics were recorded are left out.
+async fn complexity_report(
+    State(state): State<AppState>,
+    Query(query): Query<ComplexityReportQuery>,
+) -> Result<Json<ComplexityReport>, AppError> {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_MOST_COMPLEX)
+        .clamp(1, MAX_MOST_COMPLEX);
+
+    let (count, total_size, avg_size, avg_fields, max_fields, max_depth, avg_constraints): (
+        i64,
+        i64,
+        Option<f64>,
+        Option<f64>,
+        Option<i32>,
+        Option<i32>,
+        Option<f64>,
+    ) = sqlx::query_as(
+        r#"
+        SELECT COUNT(*), COALESCE(SUM(size_bytes), 0)::BIGINT,
+               AVG(size_bytes)::FLOAT8, AVG(field_count)::FLOAT8, MAX(field_count),
+               MAX(max_depth), AVG(constraint_count)::FLOAT8
+        FROM schemas
+        WHERE state <> 'DELETED' AND size_bytes IS NOT NULL
+        "#,
+    )
+    .fetch_one(&state.db)
+    .await?;
+    let summary = ComplexitySummary {
+        schema_count: count as usize,
+        total_size_bytes: total_size as u64,
+        avg_size_bytes: avg_size.unwrap_or_default(),
+        avg_field_count: avg_fields.unwrap_or_default(),
+        max_field_count: max_fields.unwrap_or_default() as u32,
+        max_depth: max_depth.unwrap_or_default() as u32,
+        avg_constraint_count: avg_constraints.unwrap_or_default(),
+    };
+
+    let rows: Vec<ComplexityRow> = sqlx::query_as(
+        r#"
+        SELECT id, namespace, name, size_bytes, field_count, max_depth, constraint_count,
+               created_at
+        FROM schemas
+        WHERE state <> 'DELETED' AND size_bytes IS NOT NULL
+        ORDER BY field_count DESC, size_bytes DESC
+        LIMIT $1
+        "#,
+    )
+    .bind(limit)
+    .fetch_all(&state.db)
+    .await?;
+    let most_complex = rows
+        .into_iter()
+        .map(
+            |(id, namespace, name, size_bytes, field_count, max_depth, constraints, created_at)| {
+                SchemaComplexityEntry {
+                    schema_id: id.into(),
+                    subject: subject_of(&namespace, &name),
+                    complexity: SchemaComplexity {
+                        size_bytes: size_bytes as u64,
+                        field_count: field_count as u32,
+                        max_depth: max_depth as u32,
+                        constraint_count: constraints as u32,
+                    },
+                    recorded_at: created_at,
+                }
+            },
+        )
+        .collect();
+
+    Ok(Json(ComplexityReport {
+        summary,
+        most_complex,
+    }))
+}
+
+/// Default and maximum number of schemas listed by `/analytics/top`
+const DEFAULT_TOP_SCHEMAS: usize = 10;
+const MAX_TOP_SCHEMAS: usize = 100;
+
+/// Default and maximum hours scanned for anomalies
+const DEFAULT_ANOMALY_HOURS: i64 = 24;
+const MAX_ANOMALY_HOURS: i64 = 24 * 7;
+
+#[derive(Debug, Deserialize)]
+struct TopSchemasQuery {
+    /// All operations when not given
+    #[serde(default)]
+    operation: Option<UsageOperation>,
+    #[serde(default)]
+    limit: Option<usize>,
+}
+
+/// Most used schemas since the server started, highest first
+async fn top_schemas(
+    State(state): State<AppState>,
+    Query(query): Query<TopSchemasQuery>,
+) -> Json<Vec<TopSchemaEntry>> {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_TOP_SCHEMAS)
+        .clamp(1, MAX_TOP_SCHEMAS);
+    Json(state.analytics.get_top_schemas(query.operation, limit))
+}
+
+/// Health scorecard of a subject's latest readable version
+async fn subject_health(
+    State(state): State<AppState>,
+    Path(subject): Path<String>,
+) -> Result<Json<SchemaHealthScore>, AppError> {
+    let (namespace, name) = split_subject(&subject);
+    let (namespace, name, _) = resolve_subject(&state, namespace, name).await?;
+    let latest: Option<(Uuid,)> = sqlx::query_as(
+        r#"
+        SELECT id FROM schemas
+        WHERE namespace = $1 AND name = $2 AND state NOT IN ('DELETED', 'QUARANTINED')
+        ORDER BY version_major DESC, version_minor DESC, version_patch DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(&namespace)
+    .bind(&name)
+    .fetch_optional(&state.db)
+    .await?;
+    let subject = subject_of(&namespace, &name);
+    let (id,) =
+        latest.ok_or_else(|| AppError::NotFound(format!("Subject {} not found", subject)))?;
+    state
+        .analytics
+        .get_schema_health(&id.into())
+        .map(Json)
+        .ok_or_else(|| AppError::NotFound(format!("No usage recorded for {} yet", subject)))
+}
+
+#[derive(Debug, Deserialize)]
+struct AnomaliesQuery {
+    #[serde(default)]
+    hours: Option<i64>,
+}
+
+/// Error spikes and latency outliers over the last `hours` (24 by default)
+async fn usage_anomalies(
+    State(state): State<AppState>,
+    Query(query): Query<AnomaliesQuery>,
+) -> Result<Json<Vec<Anomaly>>, AppError> {
+    let hours = query
+        .hours
+        .unwrap_or(DEFAULT_ANOMALY_HOURS)
+        .clamp(1, MAX_ANOMALY_HOURS);
+    state
+        .analytics
+        .report_generator()
+        .detect_anomalies(hours)
+        .map(Json)
+        .map_err(|e| AppError::Internal(e.to_string()))
+}
+
+/// Default and maximum days of capacity history
+const DEFAULT_CAPACITY_LOOKBACK_DAYS: i64 = 90;
+const MAX_CAPACITY_LOOKBACK_DAYS: i64 = 730;
+
+#[derive(Debug, Deserialize)]
+struct CapacityHistoryQuery {
+    #[serde(default)]
+    lookback_days: Option<i64>,
+}
+
+/// Daily capacity samples to forecast with `schema-cli analytics forecast`
+///
+/// Schemas per namespace and storage bytes count the stored versions that
+/// aren't deleted, sampled at the end of each day (today: now); requests per
+/// day come from the usage analytics.
+async fn capacity_history(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<CapacityHistoryQuery>,
+) -> Result<Json<CapacityHistory>, AppError> {
+    require_admin(&state, &headers)?;
+    let lookback_days = query
+        .lookback_days
+        .unwrap_or(DEFAULT_CAPACITY_LOOKBACK_DAYS)
+        .clamp(2, MAX_CAPACITY_LOOKBACK_DAYS);
+
+    let mut history = state
+        .analytics
+        .report_generator()
+        .capacity_history(lookback_days)
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let now = Utc::now();
+    let today = now.date_naive().and_time(chrono::NaiveTime::MIN).and_utc();
+    let sample_times: Vec<chrono::DateTime<Utc>> = (0..lookback_days)
+        .rev()
+        .map(|day| (today - chrono::Duration::days(day - 1)).min(now))
+        .collect();
+    let rows: Vec<(chrono::DateTime<Utc>, String, i64, i64)> = sqlx::query_as(
+        r#"
+        SELECT t.sampled_at, s.namespace, COUNT(*), COALESCE(SUM(s.size_bytes), 0)::BIGINT
+        FROM UNNEST($1::TIMESTAMPTZ[]) AS t(sampled_at)
+        JOIN schemas s ON s.created_at <= t.sampled_at
+        WHERE s.state <> 'DELETED'
+        GROUP BY t.sampled_at, s.namespace
+        ORDER BY t.sampled_at, s.namespace
+        "#,
+    )
+    .bind(&sample_times)
+    .fetch_all(&state.db)
+    .await?;
+
+    for (sampled_at, namespace, count, bytes) in rows {
+        history
+            .schemas_per_namespace
+            .entry(namespace)
+            .or_default()
+            .push(GrowthSample::new(sampled_at, count as f64));
+        match history.storage_bytes.last_mut() {
+            Some(sample) if sample.at == sampled_at => sample.value += bytes as f64,
+            _ => history
+                .storage_bytes
+                .push(GrowthSample::new(sampled_at, bytes as f64)),
+        }
+    }
+    history.storage_bytes.retain(|sample| sample.value > 0.0);
+
+    Ok(Json(history))
+}
+
+/// Reject access to schemas that are held for security review or deleted
+fn ensure_servable(id: Uuid, state: &str) -> Result<(), AppError> {
+    if state == DELETED_STATE {
+        return Err(AppError::Gone(format!("Schema {} was deleted", id)));
+    }
+    if state == SchemaState::Quarantined.to_string() {
+        return Err(AppError::Quarantined(format!(
+            "Schema {} is quarantined pending security review",
+            id
+        )));
+    }
+    Ok(())
+}
+
+/// Locale of the caller's `Accept-Language` that messages can be rendered in
+fn negotiate_locale(state: &AppState, headers: &HeaderMap) -> String {
+    let accept_language = headers.get("accept-language").and_then(|v| v.to_str().ok());
+    state.messages.negotiate(accept_language).to_string()
+}
+
+/// Tell the caller which locale the response's messages are in
+fn localized(locale: &str, body: impl IntoResponse) -> Response {
+    let mut response = body.into_response();
+    if let Ok(value) = HeaderValue::from_str(locale) {
+        response.headers_mut().insert("content-language", value);
+    }
+    response
+}
+
+#[derive(Debug, Serialize)]
+struct MessageCodeInfo {
+    code: &'static str,
+    params: &'static [&'static str],
+}
+
+#[derive(Debug, Serialize)]
+struct MessageCatalogsResponse {
+    default_locale: &'static str,
+    codes: Vec<MessageCodeInfo>,
+    /// Codes each loaded catalog translates, by locale
+    locales: BTreeMap<String, Vec<String>>,
+}
+
+/// Message codes and the catalogs translating them, for client and
+/// translator reference
+async fn list_message_catalogs(State(state): State<AppState>) -> Json<MessageCatalogsResponse> {
+    let locales = state
+        .messages
+        .catalogs()
+        .map(|catalog| {
+            (
+                catalog.locale().to_string(),
+                catalog.codes().map(str::to_string).collect(),
+            )
+        })
+        .collect();
+    Json(MessageCatalogsResponse {
+        default_locale: messages::DEFAULT_LOCALE,
+        codes: messages::CODES
+            .iter()
+            .map(|&(code, params)| MessageCodeInfo { code, params })
+            .collect(),
+        locales,
+    })
+}
+
+async fn validate_data(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(schema_id): Path<Uuid>,
+    BoundedJson(data): BoundedJson<serde_json::Value>,
+) -> Result<Response, AppError> {
+    let started = std::time::Instant::now();
+    tracing::debug!(schema_id = %schema_id, "Validating data");
+    let locale = negotiate_locale(&state, &headers);
+
+    let target = ValidationTarget::fetch(&state, schema_id).await?;
+    let response = target.validate(&state, &locale, &data).await?;
+    observe_operation(
+        &state,
+        "validation",
+        schema_id,
+        Some(target.subject.clone()),
+        target.content.len(),
+        None,
+        started.elapsed(),
+    );
+    record_usage(
+        &state,
+        schema_id,
+        UsageOperation::Validate,
+        &headers,
+        started.elapsed(),
+    );
+    Ok(localized(&locale, Json(response)))
+}
+
+/// Most records one batch validation request may carry
+const MAX_BATCH_RECORDS: usize = 10_000;
+
+/// Records read from a batch body ahead of the ones being validated
+const BATCH_READ_AHEAD: usize = 64;
+
+#[derive(Debug, Default, Deserialize)]
+struct BatchValidateQuery {
+    /// Leave valid records out of the results
+    #[serde(default)]
+    only_invalid: bool,
+}
+
+/// How one record of a batch fared
+#[derive(Debug, Serialize)]
+struct BatchRecordResult {
+    /// Position of the record in the batch, from 0
+    index: usize,
+    #[serde(flatten)]
+    result: ValidateResponse,
+}
+
+#[derive(Debug, Default, Serialize)]
+struct BatchValidateSummary {
+    /// Records read from the batch; all of them unless the request
+    /// deadline ran out
+    total: usize,
+    /// Records checked before the request deadline, if any, ran out
+    validated: usize,
+    valid: usize,
+    invalid: usize,
+    /// Errors across all invalid records
+    errors: usize,
+    duration_ms: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct BatchValidateResponse {
+    schema_id: Uuid,
+    /// False when the deadline ran out before every record was checked
+    complete: bool,
+    summary: BatchValidateSummary,
+    results: Vec<BatchRecordResult>,
+}
+
+/// One record of a batch body, or why it couldn't be parsed
+type BatchRecord = Result<serde_json::Value, JsonLimitError>;
+
+/// Read a batch body record by record, sending each to the validating
+/// handler as soon as it is parsed
+///
+/// Runs on the blocking pool. Each record is held to the request limits on
+/// its own, so memory stays proportional to the largest record rather than
+/// the batch. Reading stops once the handler stops receiving.
+fn read_batch(
+    body: impl std::io::Read,
+    ndjson: bool,
+    limits: JsonLimits,
+    max_bytes: usize,
+    records: tokio::sync::mpsc::Sender<BatchRecord>,
+) -> Result<(), AppError> {
+    use std::io::BufRead;
+    use std::ops::ControlFlow;
+
+    let mut body = body.take(max_bytes as u64 + 1);
+    let mut count = 0;
+    let read = {
+        let mut send = |record: BatchRecord| {
+            count += 1;
+            if count > MAX_BATCH_RECORDS || records.blocking_send(record).is_err() {
+                return ControlFlow::Break(());
+            }
+            ControlFlow::Continue(())
+        };
+        if ndjson {
+            let mut lines = std::io::BufReader::new(&mut body);
+            let mut line = Vec::new();
+            loop {
+                line.clear();
+                match lines.read_until(b'\n', &mut line) {
+                    Ok(0) => break Ok(()),
+                    Ok(_) if line.iter().all(u8::is_ascii_whitespace) => continue,
+                    Ok(_) => {
+                        if send(bounded_json::from_slice(&line, &limits)).is_break() {
+                            break Ok(());
+                        }
+                    }
+                    Err(e) => break Err(format!("Failed to read the batch: {}", e)),
+                }
+            }
+        } else {
+            streaming::read_records(
+                &mut body,
+                StreamLayout::Array,
+                |_, record: Box<serde_json::value::RawValue>| {
+                    send(bounded_json::from_slice(record.get().as_bytes(), &limits))
+                },
+            )
+            .map_err(|e| e.to_string())
+        }
+    };
+    if body.limit() == 0 {
+        let limit = SizeLimitExceeded::request(max_bytes, None);
+        return Err(AppError::TooLarge(limit));
+    }
+    read.map_err(AppError::InvalidInput)?;
+    if count > MAX_BATCH_RECORDS {
+        return Err(AppError::InvalidInput(format!(
+            "A batch may hold at most {} records",
+            MAX_BATCH_RECORDS
+        )));
+    }
+    Ok(())
+}
+
+/// Validate many payloads against one schema in a single request
+///
+/// The body is a JSON array of payloads, or newline-delimited JSON with the
+/// `application/x-ndjson` content type. Records are validated as they are
+/// read rather than after the whole body has arrived, each held to the
+/// request limits on its own. A record that isn't valid JSON, or exceeds
+/// the limits, fails on its own rather than failing the batch; an array
+/// that isn't well-formed fails the request. If the request deadline runs
+/// out part way, the records checked so far are returned with `complete`
+/// set to false.
+async fn validate_data_batch(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(schema_id): Path<Uuid>,
+    Query(query): Query<BatchValidateQuery>,
+    body: Body,
+) -> Result<Response, AppError> {
+    use futures::TryStreamExt;
+
+    let started = std::time::Instant::now();
+    let locale = negotiate_locale(&state, &headers);
+    let ndjson = headers
+        .get("content-type")
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| {
+            let media_type = v.split(';').next().unwrap_or_default().trim();
+            media_type.eq_ignore_ascii_case("application/x-ndjson")
+                || media_type.eq_ignore_ascii_case("application/ndjson")
+        });
+    let target = ValidationTarget::fetch(&state, schema_id).await?;
+
+    let body = tokio_util::io::SyncIoBridge::new(tokio_util::io::StreamReader::new(
+        body.into_data_stream().map_err(std::io::Error::other),
+    ));
+    let (sender, mut records) = tokio::sync::mpsc::channel(BATCH_READ_AHEAD);
+    let (limits, max_bytes) = (state.json_limits, state.max_request_size);
+    let reader =
+        tokio::task::spawn_blocking(move || read_batch(body, ndjson, limits, max_bytes, sender));
+
+    let mut summary = BatchValidateSummary::default();
+    let mut results = Vec::new();
+    let mut complete = true;
+    while let Some(record) = records.recv().await {
+        let index = summary.total;
+        summary.total += 1;
+        if Deadline::current().is_some_and(|d| d.is_expired()) {
+            complete = false;
+            break;
+        }
+        let result = match record {
+            Ok(data) => target.validate(&state, &locale, &data).await?,
+            Err(e) => {
+                let error = state.messages.data_mismatch(&locale, None, &e.to_string());
+                ValidateResponse {
+                    is_valid: false,
+                    errors: vec![error.message.clone()],
+                    warnings: Vec::new(),
+                    details: vec![error],
+                }
+            }
+        };
+        summary.validated += 1;
+        if result.is_valid {
+            summary.valid += 1;
+        } else {
+            summary.invalid += 1;
+            summary.errors += result.errors.len();
+        }
+        if !(query.only_invalid && result.is_valid) {
+            results.push(BatchRecordResult { index, result });
+        }
+    }
+    drop(records);
+    reader
+        .await
+        .map_err(|e| AppError::Internal(format!("Batch reader failed: {}", e)))??;
+    if summary.total == 0 {
+        return Err(AppError::InvalidInput(
+            "The batch holds no records".to_string(),
+        ));
+    }
+    tracing::debug!(schema_id = %schema_id, records = summary.total, "Validated batch");
+    if !complete {
+        tracing::warn!(
+            schema_id = %schema_id,
+            validated = summary.validated,
+            total = summary.total,
+            "Request deadline ran out during batch validation"
+        );
+    }
+    summary.duration_ms = started.elapsed().as_millis() as u64;
+
+    observe_operation(
+        &state,
+        "batch_validation",
+        schema_id,
+        Some(target.subject.clone()),
+        target.content.len(),
+        None,
+        started.elapsed(),
+    );
+    record_usage(
+        &state,
+        schema_id,
+        UsageOperation::Validate,
+        &headers,
+        started.elapsed(),
+    );
+    Ok(localized(
+        &locale,
+        Json(BatchValidateResponse {
+            schema_id,
+            complete,
+            summary,
+            results,
+        }),
+    ))
+}
+
+/// A servable schema, loaded to validate data against
+struct ValidationTarget {
+    schema_id: Uuid,
+    subject: String,
+    format: SerializationFormat,
+    content: String,
+    deprecations: Vec<deprecation::FieldDeprecation>,
+}
+
+impl ValidationTarget {
+    async fn fetch(state: &AppState, schema_id: Uuid) -> Result<Self, AppError> {
+        let row: Option<(String, String, String, String, String)> = sqlx::query_as(
+            "SELECT namespace, name, format, content, state FROM schemas WHERE id = $1 LIMIT 1",
+        )
+        .bind(schema_id)
+        .fetch_optional(&state.db)
+        .await?;
+        let (namespace, name, format, content, state_str) =
+            row.ok_or_else(|| AppError::NotFound(format!("Schema {} not found", schema_id)))?;
+        ensure_servable(schema_id, &state_str)?;
+        let format = match format.as_str() {
+            "AVRO" => SerializationFormat::Avro,
+            "PROTOBUF" => SerializationFormat::Protobuf,
+            _ => SerializationFormat::JsonSchema,
+        };
+        Ok(Self {
+            schema_id,
+            subject: subject_of(&namespace, &name),
+            format,
+            deprecations: deprecation::field_deprecations(&content, format),
+            content,
+        })
+    }
+
+    /// Check one payload, with its mismatches and uses of deprecated fields
+    async fn validate(
+        &self,
+        state: &AppState,
+        locale: &str,
+        data: &serde_json::Value,
+    ) -> Result<ValidateResponse, AppError> {
+        let validation = state
+            .validator
+            .validate_instance(self.schema_id, self.format, &self.content, data)
+            .await
+            .map_err(|e| {
+                AppError::InvalidInput(format!(
+                    "Schema {} cannot validate data: {}",
+                    self.schema_id, e
+                ))
+            })?;
+        let errors: Vec<Message> = validation
+            .errors
+            .iter()
+            .map(|e| {
+                let field = e.field_path.as_deref().filter(|path| !path.is_empty());
+                state.messages.data_mismatch(locale, field, &e.message)
+            })
+            .collect();
+        let warnings: Vec<Message> = deprecation::used_in(&self.deprecations, data)
+            .into_iter()
+            .map(|used| state.messages.deprecation(locale, used))
+            .collect();
+
+        let text = |messages: &[Message]| -> Vec<String> {
+            messages.iter().map(|m| m.message.clone()).collect()
+        };
+        Ok(ValidateResponse {
+            is_valid: validation.is_valid,
+            errors: text(&errors),
+            warnings: text(&warnings),
+            details: errors.into_iter().chain(warnings).collect(),
+        })
+    }
+}
+
+async fn check_compatibility(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    impersonation: Option<Extension<Impersonation>>,
+    scoped: Option<Extension<ScopedToken>>,
+    BoundedJson(req): BoundedJson<CompatibilityCheckRequest>,
+) -> Result<Response, AppError> {
+    let started = std::time::Instant::now();
+    let actor = ChangeActor::from_request(
+        &state,
+        &headers,
+        impersonation.as_ref().map(|Extension(i)| i),
+        scoped.as_ref().map(|Extension(s)| s),
+    )?;
+    let locale = negotiate_locale(&state, &headers);
+    tracing::debug!(
+        schema_id = %req.schema_id,
+        compared_schema_id = %req.compared_schema_id,
+        mode = ?req.mode,
+        "Checking compatibility"
+    );
+
+    let pair = compared_pair(&state, &req).await?;
+    let mut result = state
+        .compatibility_checker
+        .check_compatibility(&pair.new_schema, &pair.old_schema, pair.mode)
+        .await
+        .map_err(compatibility_error)?;
+
+    // Breaking changes covered by an admin exemption don't block the check
+    let exemptions_applied = state
+        .exemptions
+        .apply(
+            &pair.subject,
+            &mut result,
+            state.compatibility_checker.min_severity_to_fail(),
+        )
+        .await
+        .map_err(compatibility_error)?;
+    if !exemptions_applied.is_empty() {
+        let mut event = AuditEvent::new(
+            AuditEventType::CompatibilityExemptionApplied,
+            "Breaking changes allowed by compatibility exemption".to_string(),
+            AuditResult::Success,
+            String::new(),
+        )
+        .with_resource("schema".to_string(), req.schema_id.to_string())
+        .with_metadata("subject".to_string(), serde_json::json!(pair.subject))
+        .with_metadata("exemptions".to_string(), serde_json::json!(exemptions_applied))
+        .with_metadata(
+            "violations".to_string(),
+            serde_json::json!(result.exempted_violations),
+        );
+        if let Some(Extension(impersonation)) = &impersonation {
+            event = impersonation.attribute(event);
+        }
+        state.audit_logger.log(event).await;
+    }
+
+    let elapsed = started.elapsed();
+    observe_operation(
+        &state,
+        "compatibility_check",
+        req.schema_id,
+        Some(pair.subject.clone()),
+        pair.new_schema.content.len() + pair.old_schema.content.len(),
+        Some(result.mode.to_string()),
+        elapsed,
+    );
+    let mut record = CompatibilityCheckRecord::new(
+        pair.subject.clone(),
+        (req.schema_id, pair.new_schema.version.clone()),
+        (req.compared_schema_id, pair.old_schema.version.clone()),
+        &result,
+        elapsed,
+    );
+    // The evidence names who authenticated; the client's own ID is kept
+    // beside it, since anyone can send one
+    record = record.with_caller(actor.principal);
+    if let Some(client_id) = headers.get("x-client-id").and_then(|v| v.to_str().ok()) {
+        record = record.with_client_id(client_id);
+    }
+    record_compatibility_check(&state, record, impersonation.as_ref().map(|e| &e.0)).await;
+
+    Ok(localized(
+        &locale,
+        Json(compatibility_response(
+            &state,
+            &locale,
+            &result,
+            exemptions_applied,
+            pair.explain,
+        )),
+    ))
+}
+
+/// Check compatibility like [`check_compatibility`], returning every rule the
+/// checker evaluated
+///
+/// Nothing is recorded: the check bypasses the result cache and isn't written
+/// to the history or the audit log, so contested results can be explained
+/// without affecting them.
+async fn dry_run_compatibility(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    BoundedJson(req): BoundedJson<CompatibilityCheckRequest>,
+) -> Result<Response, AppError> {
+    let locale = negotiate_locale(&state, &headers);
+    tracing::debug!(
+        schema_id = %req.schema_id,
+        compared_schema_id = %req.compared_schema_id,
+        mode = ?req.mode,
+        "Explaining compatibility"
+    );
+
+    let pair = compared_pair(&state, &req).await?;
+    let (mut result, trace) = state
+        .compatibility_checker
+        .explain(
+            &pair.new_schema,
+            std::slice::from_ref(&pair.old_schema),
+            pair.mode,
+        )
+        .await
+        .map_err(compatibility_error)?;
+    let exemptions_applied = state
+        .exemptions
+        .apply(
+            &pair.subject,
+            &mut result,
+            state.compatibility_checker.min_severity_to_fail(),
+        )
+        .await
+        .map_err(compatibility_error)?;
+
+    Ok(localized(
+        &locale,
+        Json(CompatibilityDryRunResponse {
+            check: compatibility_response(
+                &state,
+                &locale,
+                &result,
+                exemptions_applied,
+                pair.explain,
+            ),
+            trace,
+        }),
+    ))
+}
+
+/// Two stored versions of a subject to compare, and the mode to compare them with
+struct ComparedPair {
+    subject: String,
+    new_schema: RegisteredSchema,
+    old_schema: RegisteredSchema,
+    mode: CompatibilityMode,
+    explain: ModeExplain,
+}
+
+/// Load the schemas a compatibility request names and resolve its mode
+async fn compared_pair(
+    state: &AppState,
+    req: &CompatibilityCheckRequest,
+) -> Result<ComparedPair, AppError> {
+    // Fetch both schemas
+    let schema1: Option<(String, String, String, String, i32, i32, i32)> = sqlx::query_as(
+        "SELECT namespace, name, format, content, version_major, version_minor, version_patch FROM schemas WHERE id = $1",
+    )
+    .bind(req.schema_id)
+    .fetch_optional(&state.db)
+    .await?;
+
+    let schema2: Option<(String, String, i32, i32, i32)> = sqlx::query_as(
+        "SELECT format, content, version_major, version_minor, version_patch FROM schemas WHERE id = $1",
+    )
+    .bind(req.compared_schema_id)
+    .fetch_optional(&state.db)
+    .await?;
+
+    let (
+        Some((namespace, name, format1, content1, v1_major, v1_minor, v1_patch)),
+        Some((format2, content2, v2_major, v2_minor, v2_patch)),
+    ) = (schema1, schema2)
+    else {
+        return Err(AppError::NotFound("One or both schemas not found".to_string()));
+    };
+
+    // Without an explicit mode, use the one in effect for the subject
+    // in the requested (or this server's) environment
+    let (mode, explain) = match req.mode {
+        Some(mode) => (mode, ModeExplain::Request),
+        None => {
+            let effective = state
+                .namespaces
+                .effective(&namespace)
+                .map_err(|e| AppError::InvalidInput(e.to_string()))?
+                .compatibility_in(req.environment.as_deref().or(state.environment.as_deref()));
+            (effective.mode, ModeExplain::Settings(effective))
+        }
+    };
+
+    let version = |major: i32, minor: i32, patch: i32| {
+        SemanticVersion::new(major as u32, minor as u32, patch as u32)
+    };
+    let new_schema = comparable_schema(
+        req.schema_id,
+        &namespace,
+        &name,
+        &format1,
+        &content1,
+        version(v1_major, v1_minor, v1_patch),
+    );
+    let old_schema = comparable_schema(
+        req.compared_schema_id,
+        &namespace,
+        &name,
+        &format2,
+        &content2,
+        version(v2_major, v2_minor, v2_patch),
+    );
+    record_feature_use(
+        state,
+        FeatureDimension::Format,
+        &new_schema.format.to_string(),
+    );
+    record_feature_use(
+        state,
+        FeatureDimension::CompatibilityMode,
+        &mode.to_string(),
+    );
+    Ok(ComparedPair {
+        subject: subject_of(&namespace, &name),
+        new_schema,
+        old_schema,
+        mode,
+        explain,
+    })
+}
+
+fn compatibility_error(e: CoreError) -> AppError {
+    match e {
+        CoreError::DeadlineExceeded(message) => AppError::DeadlineExceeded(message),
+        e => AppError::Internal(e.to_string()),
+    }
+}
+
+/// The response to a compatibility check, with violations in `locale`
+fn compatibility_response(
+    state: &AppState,
+    locale: &str,
+    result: &CompatibilityResult,
+    exemptions_applied: Vec<Uuid>,
+    explain: ModeExplain,
+) -> CompatibilityCheckResponse {
+    let localize = |violations: &[CompatibilityViolation]| -> Vec<Message> {
+        violations
+            .iter()
+            .map(|v| state.messages.violation(locale, v))
+            .collect()
+    };
+    let details = localize(&result.violations);
+    CompatibilityCheckResponse {
+        is_compatible: result.is_compatible,
+        mode: result.mode.to_string(),
+        violations: details.iter().map(|d| d.message.clone()).collect(),
+        details,
+        exempted_violations: localize(&result.exempted_violations)
+            .into_iter()
+            .map(|d| d.message)
+            .collect(),
+        exemptions_applied,
+        explain,
+    }
+}
+
+/// A stored version in the shape the compatibility checker compares
+///
+/// Only the fields the checker reads are filled from storage. The content
+/// hash is recomputed, as versions stored before hashes were taken over the
+/// canonical form hold a hash of their raw content.
+fn comparable_schema(
+    id: Uuid,
+    namespace: &str,
+    name: &str,
+    format: &str,
+    content: &str,
+    version: SemanticVersion,
+) -> RegisteredSchema {
+    let now = chrono::Utc::now();
+    let format = match format {
+        "AVRO" => SerializationFormat::Avro,
+        "PROTOBUF" => SerializationFormat::Protobuf,
+        _ => SerializationFormat::JsonSchema,
+    };
+    RegisteredSchema {
+        id,
+        namespace: namespace.to_string(),
+        name: name.to_string(),
+        version,
+        format,
+        content: content.to_string(),
+        content_hash: canonical_form::content_hash(format, content),
+        description: String::new(),
+        compatibility_mode: CompatibilityMode::None,
+        state: SchemaState::Active,
+        metadata: SchemaMetadata {
+            created_at: now,
+            created_by: String::new(),
+            updated_at: now,
+            updated_by: String::new(),
+            activated_at: None,
+            deprecation: None,
+            deletion: None,
+            custom: HashMap::new(),
+        },
+        tags: vec![],
+        examples: vec![],
+        lifecycle: SchemaLifecycle::new(id),
+    }
+}
+
+fn describe_violations(violations: &[CompatibilityViolation]) -> Vec<String> {
+    violations
+        .iter()
+        .map(|v| format!("{}: {}", v.field_path, v.description))
+        .collect()
+}
+
+/// Check `candidate` against `previous` versions of `subject` in `mode`
+///
+/// Violations covered by the subject's active exemptions are moved to
+/// `exempted_violations`; the IDs of the exemptions used are returned with
+/// the result.
+async fn check_with_exemptions(
+    state: &AppState,
+    subject: &str,
+    candidate: &RegisteredSchema,
+    previous: &[RegisteredSchema],
+    mode: CompatibilityMode,
+) -> Result<(CompatibilityResult, Vec<Uuid>), AppError> {
+    let mut result = state
+        .compatibility_checker
+        .check_transitive_compatibility(candidate, previous, mode)
+        .await
+        .map_err(compatibility_error)?;
+    let exemptions_applied = state
+        .exemptions
+        .apply(
+            subject,
+            &mut result,
+            state.compatibility_checker.min_severity_to_fail(),
+        )
+        .await
+        .map_err(compatibility_error)?;
+    Ok((result, exemptions_applied))
+}
+
+/// Persist a check to the history store and the audit log
+///
+/// History failures are logged rather than failing the check itself.
+async fn record_compatibility_check(
+    state: &AppState,
+    record: CompatibilityCheckRecord,
+    impersonation: Option<&Impersonation>,
+) {
+    let mut event = AuditEvent::new(
+        AuditEventType::CompatibilityChecked,
+        "Compatibility checked".to_string(),
+        AuditResult::Success,
+        String::new(),
+    )
+    .with_resource("schema".to_string(), record.schema_id.to_string())
+    .with_metadata("subject".to_string(), serde_json::json!(record.subject))
+    .with_metadata("compared_schema_id".to_string(), serde_json::json!(record.compared_schema_id))
+    .with_metadata("mode".to_string(), serde_json::json!(record.mode))
+    .with_metadata("is_compatible".to_string(), serde_json::json!(record.is_compatible))
+    .with_metadata("violations".to_string(), serde_json::json!(record.violations.len()))
+    .with_metadata("latency_ms".to_string(), serde_json::json!(record.latency_ms));
+    if let Some(caller) = &record.caller {
+        event = event.with_user(caller.clone(), None);
+    }
+    if let Some(client_id) = &record.client_id {
+        event = event.with_metadata("client_id".to_string(), serde_json::json!(client_id));
+    }
+    if let Some(impersonation) = impersonation {
+        event = impersonation.attribute(event);
+    }
+    state.audit_logger.log(event).await;
+
+    if let Err(e) = state.compat_history.record(record).await {
+        tracing::warn!(error = %e, "Failed to record compatibility check history");
+    }
+}
+
+/// Query recorded compatibility checks, newest first
+///
+/// The records name their callers, so reading them needs credentials.
+async fn compatibility_history(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    impersonation: Option<Extension<Impersonation>>,
+    scoped: Option<Extension<ScopedToken>>,
+    Query(query): Query<CompatibilityHistoryQuery>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    ChangeActor::from_request(
+        &state,
+        &headers,
+        impersonation.as_ref().map(|Extension(i)| i),
+        scoped.as_ref().map(|Extension(s)| s),
+    )?
+    .ensure_authenticated()?;
+    let checks = state
+        .compat_history
+        .query(&query)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    Ok(Json(serde_json::json!({ "checks": checks })))
+}
+
+/// Subject name as used at registration time (`namespace.name`, or just `name`)
+fn subject_of(namespace: &str, name: &str) -> String {
+    if namespace == "default" {
+        name.to_string()
+    } else {
+        format!("{}.{}", namespace, name)
+    }
+}
+
+/// Namespaces that are path segments of the API where a namespace could be,
+/// e.g. `/api/v1/schemas/ids/:global_id`, whose routes would hide them
+const RESERVED_NAMESPACES: &[&str] = &["ids"];
+
+/// Reject a new subject in a namespace reserved for the API
+fn ensure_namespace_allowed(namespace: &str) -> Result<(), AppError> {
+    if RESERVED_NAMESPACES.contains(&namespace) {
+        return Err(AppError::InvalidInput(format!(
+            "Namespace '{}' is reserved",
+            namespace
+        )));
+    }
+    Ok(())
+}
+
+/// Parse a subject into namespace and name (format: namespace.name or just name)
+fn split_subject(subject: &str) -> (String, String) {
+    match subject.rfind('.') {
+        Some(dot_pos) => (subject[..dot_pos].to_string(), subject[dot_pos + 1..].to_string()),
+        None => ("default".to_string(), subject.to_string()),
+    }
+}
+
+/// Follow a renamed subject's alias to its current name
+///
+/// Returns a deprecation warning when the given subject is an alias.
+async fn resolve_subject(
+    state: &AppState,
+    namespace: String,
+    name: String,
+) -> Result<(String, String, Option<String>), AppError> {
+    let target: Option<(String, String)> = sqlx::query_as(
+        "SELECT target_namespace, target_name FROM subject_aliases WHERE alias_namespace = $1 AND alias_name = $2",
+    )
+    .bind(&namespace)
+    .bind(&name)
+    .fetch_optional(&state.db)
+    .await?;
+
+    match target {
+        Some((target_namespace, target_name)) => {
+            let alias = subject_of(&namespace, &name);
+            let subject = subject_of(&target_namespace, &target_name);
+            tracing::warn!(alias = %alias, subject = %subject, "Deprecated subject alias used");
+            let warning = format!(
+                "Subject '{}' was renamed to '{}'; the old name is deprecated",
+                alias, subject
+            );
+            Ok((target_namespace, target_name, Some(warning)))
+        }
+        None => Ok((namespace, name, None)),
+    }
+}
+
+// ============================================================================
+// Codegen
+// ============================================================================
+
+/// Generated bindings only depend on content, language and generator, so
+/// they stay cached long after the schema entry itself expires
+const CODEGEN_CACHE_TTL_SECS: u64 = 86_400;
+
+/// Typed bindings for a stored schema
+///
+/// Bindings are cached by subject and content hash, so every version with
+/// the same content shares them. The hash is also the ETag, letting consumers that
+/// regenerate on every build skip unchanged schemas.
+async fn schema_codegen(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<CodegenQuery>,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    let language: Language = query
+        .lang
+        .parse()
+        .map_err(|e: CoreError| AppError::InvalidInput(e.to_string()))?;
+    let schema = fetch_schema(&state, id).await?;
+
+    let content_hash = RegisteredSchema::calculate_content_hash(&schema.content);
+    let etag = format!("\"{}-{}\"", content_hash, language);
+    if not_modified(&headers, &etag) {
+        return Ok((StatusCode::NOT_MODIFIED, [("etag", etag)]).into_response());
+    }
+
+    // The subject names the generated file's header and untitled root types
+    let subject = subject_of(&schema.namespace, &schema.name);
+    let lang = language.to_string();
+    let cache_key = format!(
+        "codegen:v{}:{}:{}:{}",
+        codegen::GENERATOR_VERSION,
+        lang,
+        subject,
+        content_hash
+    );
+    let mut cache = state.cache();
+    let cached = match &mut cache {
+        Some(conn) => redis::cmd("GET")
+            .arg(&cache_key)
+            .query_async::<_, Option<String>>(conn)
+            .await
+            .ok()
+            .flatten(),
+        None => None,
+    };
+
+    let code = match cached {
+        Some(code) => {
+            CODEGEN_REQUESTS.with_label_values(&[lang.as_str(), "hit"]).inc();
+            code
+        }
+        None => {
+            let format = match schema.format.as_str() {
+                "AVRO" => SerializationFormat::Avro,
+                "PROTOBUF" => SerializationFormat::Protobuf,
+                _ => SerializationFormat::JsonSchema,
+            };
+            let code = codegen::generate(&subject, &schema.content, format, language)
+                .map_err(|e| AppError::InvalidInput(format!("Cannot generate {} bindings: {}", language, e)))?;
+
+            if let Some(conn) = &mut cache {
+                let _: Result<(), _> = redis::cmd("SET")
+                    .arg(&cache_key)
+                    .arg(&code)
+                    .arg("EX")
+                    .arg(CODEGEN_CACHE_TTL_SECS)
+                    .query_async(conn)
+                    .await;
+            }
+            CODEGEN_REQUESTS.with_label_values(&[lang.as_str(), "miss"]).inc();
+            code
+        }
+    };
+
+    let file_name = language.file_name(&schema.name);
+    Ok((
+        StatusCode::OK,
+        [
+            ("content-type", "text/plain; charset=utf-8".to_string()),
+            ("etag", etag),
+            (
+                "content-disposition",
+                content_disposition("inline", &file_name),
+            ),
+        ],
+        code,
+    )
+        .into_response())
+}
+
+// ============================================================================
+// Search Handlers
+// ============================================================================
+
+/// Results per page when a search gives no `limit`
+const DEFAULT_SEARCH_LIMIT: u32 = 10;
+
+type SearchRow = (
+    Uuid,
+    String,
+    String,
+    i32,
+    i32,
+    i32,
+    String,
+    chrono::DateTime<Utc>,
+    chrono::DateTime<Utc>,
+    i64,
+);
+
+/// Ways to make a search cheaper, suggested when it is rejected
+fn search_hints(req: &SearchSchemasRequest, max_page_size: u32) -> Vec<String> {
+    let mut hints = Vec::new();
+    if req.namespace.is_none() && req.namespace_pattern.is_none() {
+        hints.push("Filter by namespace or namespace_pattern".to_string());
+    }
+    // Shorter terms can't use the trigram indexes
+    if !req.query.is_empty() && req.query.chars().count() < 3 {
+        hints.push("Use a search term of at least three characters".to_string());
+    }
+    if req.created_after.is_none() && req.created_before.is_none() {
+        hints.push("Narrow the time range with created_after and created_before".to_string());
+    }
+    if req.state.is_none() && req.format.is_none() && req.tags.is_empty() {
+        hints.push("Filter by state, format or tags".to_string());
+    }
+    if req.limit.is_some_and(|limit| limit > DEFAULT_SEARCH_LIMIT) {
+        hints.push(format!(
+            "Request at most {} results per page with limit and page with offset",
+            DEFAULT_SEARCH_LIMIT.min(max_page_size)
+        ));
+    }
+    hints
+}
+
+/// Escape `LIKE` metacharacters, so `value` only matches itself
+fn escape_like(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '\\' | '%' | '_') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Translate a namespace glob into a `LIKE` pattern
+fn glob_to_like(glob: &str) -> String {
+    let mut pattern = String::with_capacity(glob.len());
+    for c in glob.chars() {
+        match c {
+            '\\' | '%' | '_' => {
+                pattern.push('\\');
+                pattern.push(c);
+            }
+            '*' => pattern.push('%'),
+            '?' => pattern.push('_'),
+            c => pattern.push(c),
+        }
+    }
+    pattern
+}
+
+/// Keyword search over registered schemas
+///
+/// The query is cost-checked before it runs; see [`QueryLimits`].
+async fn search_schemas(
+    State(state): State<AppState>,
+    BoundedJson(req): BoundedJson<SearchSchemasRequest>,
+) -> Result<Json<SearchSchemasResponse>, AppError> {
+    Ok(Json(search(&state, &req, None).await?))
+}
+
+/// Run a search, limited to the namespaces `visibility` makes public if given
+///
+/// `visibility` holds the explicit rules of [`NamespaceTree::visibility_rules`].
+async fn search(
+    state: &AppState,
+    req: &SearchSchemasRequest,
+    visibility: Option<&BTreeMap<String, Visibility>>,
+) -> Result<SearchSchemasResponse, AppError> {
+    if req.search_mode.as_deref().is_some_and(|mode| mode != "keyword") {
+        return Err(AppError::InvalidInput(
+            "Only keyword search is supported".to_string(),
+        ));
+    }
+    if req.label_selector.is_some() {
+        return Err(AppError::InvalidInput(
+            "label_selector is not supported".to_string(),
+        ));
+    }
+
+    let limits = &state.query_limits;
+    let hints = search_hints(req, limits.max_page_size);
+    let limit = limits
+        .page_size(req.limit, DEFAULT_SEARCH_LIMIT)
+        .map_err(|e| AppError::query_cost(e, hints.clone()))?;
+    let offset = req.offset.unwrap_or(0);
+    let format = req.format.as_deref().map(|format| match format {
+        "JSON_SCHEMA" => "JSON",
+        other => other,
+    });
+
+    let rows: Vec<SearchRow> = limits
+        .fetch_all(&state.db, |qb| {
+            qb.push(
+                "SELECT id, namespace, name, version_major, version_minor, version_patch, \
+                 format, created_at, updated_at, COUNT(*) OVER () \
+                 FROM schemas WHERE state NOT IN ('DELETED', 'QUARANTINED')",
+            );
+            if !req.query.is_empty() {
+                let pattern = format!("%{}%", escape_like(&req.query));
+                qb.push(" AND (name ILIKE ")
+                    .push_bind(pattern.clone())
+                    .push(" ESCAPE '\\' OR namespace ILIKE ")
+                    .push_bind(pattern.clone())
+                    .push(" ESCAPE '\\' OR description ILIKE ")
+                    .push_bind(pattern.clone())
+                    .push(" ESCAPE '\\' OR content ILIKE ")
+                    .push_bind(pattern)
+                    .push(" ESCAPE '\\')");
+            }
+            if let Some(namespace) = &req.namespace {
+                qb.push(" AND namespace = ").push_bind(namespace.clone());
+            }
+            if let Some(glob) = &req.namespace_pattern {
+                qb.push(" AND namespace LIKE ")
+                    .push_bind(glob_to_like(glob))
+                    .push(" ESCAPE '\\'");
+            }
+            if !req.tags.is_empty() {
+                qb.push(" AND tags @> ").push_bind(req.tags.clone());
+            }
+            if let Some(schema_state) = &req.state {
+                qb.push(" AND state = ").push_bind(schema_state.clone());
+            }
+            if let Some(format) = format {
+                qb.push(" AND format = ").push_bind(format.to_string());
+            }
+            if let Some(after) = req.created_after {
+                qb.push(" AND created_at >= ").push_bind(after);
+            }
+            if let Some(before) = req.created_before {
+                qb.push(" AND created_at < ").push_bind(before);
+            }
+            if let Some(rules) = visibility {
+                let (namespaces, public): (Vec<String>, Vec<bool>) = rules
+                    .iter()
+                    .map(|(namespace, visibility)| (namespace.clone(), *visibility == Visibility::Public))
+                    .unzip();
+                // The nearest namespace with a rule decides; without one it's private
+                qb.push(" AND COALESCE((SELECT vr.is_public FROM UNNEST(")
+                    .push_bind(namespaces)
+                    .push("::text[], ")
+                    .push_bind(public)
+                    .push(
+                        "::bool[]) AS vr(namespace, is_public) \
+                         WHERE schemas.namespace = vr.namespace \
+                         OR LEFT(schemas.namespace, LENGTH(vr.namespace) + 1) = vr.namespace || '.' \
+                         ORDER BY LENGTH(vr.namespace) DESC LIMIT 1), FALSE)",
+                    );
+            }
+            qb.push(
+                " ORDER BY namespace, name, version_major DESC, version_minor DESC, version_patch DESC LIMIT ",
+            )
+            .push_bind(limit as i64)
+            .push(" OFFSET ")
+            .push_bind(offset as i64);
+        })
+        .await
+        .map_err(|e| AppError::query_cost(e, hints))?;
+
+    let total = rows.first().map_or(0, |row| row.9 as u64);
+    let query = req.query.to_lowercase();
+    let results = rows
+        .into_iter()
+        .map(
+            |(id, namespace, name, major, minor, patch, format, created_at, updated_at, _)| {
+                let lower = name.to_lowercase();
+                let score = if query.is_empty() || lower == query {
+                    1.0
+                } else if lower.contains(&query) {
+                    0.8
+                } else {
+                    0.5
+                };
+                SearchResult {
+                    metadata: SearchResultMetadata {
+                        schema_id: id,
+                        namespace,
+                        name,
+                        version: format!("{}.{}.{}", major, minor, patch),
+                        format: if format == "JSON" { "JSON_SCHEMA".to_string() } else { format },
+                        created_at: created_at.to_rfc3339(),
+                        updated_at: updated_at.to_rfc3339(),
+                    },
+                    score,
+                }
+            },
+        )
+        .collect();
+
+    Ok(SearchSchemasResponse { results, total })
+}
+
+// ============================================================================
+// Compatibility Matrix
+// ============================================================================
+
+/// Versions a compatibility matrix covers at most, the latest ones
+const COMPATIBILITY_MATRIX_MAX_VERSIONS: usize = 50;
+
+#[derive(Debug, Deserialize)]
+struct CompatibilityMatrixQuery {
+    /// Cover only the latest `latest` versions
+    #[serde(default)]
+    latest: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+struct CompatibilityMatrixResponse {
+    subject: String,
+    /// Versions in order; rows and columns of `matrix` follow it
+    versions: Vec<String>,
+    /// `matrix[reader][writer]`: whether a consumer on version `reader` can
+    /// read data a producer wrote with version `writer`
+    matrix: Vec<Vec<MatrixCell>>,
+    /// Earlier versions left out to stay within the version limit
+    #[serde(skip_serializing_if = "is_zero")]
+    omitted_versions: usize,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+struct MatrixCell {
+    compatible: bool,
+    violations: usize,
+}
+
+fn is_zero(n: &usize) -> bool {
+    *n == 0
+}
+
+/// Pairwise compatibility of a subject's versions, as readers and writers
+///
+/// Each cell is a `BACKWARD` check of the reader against the writer, whatever
+/// order they were registered in; exemptions don't apply. Versions with the
+/// same canonical content share their results, and the checker's pair cache
+/// keeps the rest for the next request, so only pairs new since the last
+/// one are diffed.
+async fn compatibility_matrix(
+    State(state): State<AppState>,
+    Path(subject): Path<String>,
+    Query(query): Query<CompatibilityMatrixQuery>,
+) -> Result<Json<CompatibilityMatrixResponse>, AppError> {
+    let (namespace, name) = split_subject(&subject);
+    let (namespace, name, _) = resolve_subject(&state, namespace, name).await?;
+    let limit = query
+        .latest
+        .unwrap_or(COMPATIBILITY_MATRIX_MAX_VERSIONS)
+        .clamp(1, COMPATIBILITY_MATRIX_MAX_VERSIONS);
+
+    let (total,): (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM schemas WHERE namespace = $1 AND name = $2")
+            .bind(&namespace)
+            .bind(&name)
+            .fetch_one(&state.db)
+            .await?;
+    let mut rows: Vec<(Uuid, String, String, i32, i32, i32)> = sqlx::query_as(
+        r#"
+        SELECT id, format, content, version_major, version_minor, version_patch
+        FROM schemas
+        WHERE namespace = $1 AND name = $2
+        ORDER BY version_major DESC, version_minor DESC, version_patch DESC
+        LIMIT $3
+        "#,
+    )
+    .bind(&namespace)
+    .bind(&name)
+    .bind(limit as i64)
+    .fetch_all(&state.db)
+    .await?;
+    let subject = subject_of(&namespace, &name);
+    if rows.is_empty() {
+        return Err(AppError::NotFound(format!("Subject {} not found", subject)));
+    }
+    rows.reverse();
+
+    let schemas: Vec<RegisteredSchema> = rows
+        .iter()
+        .map(|(id, format, content, major, minor, patch)| {
+            let version = SemanticVersion::new(*major as u32, *minor as u32, *patch as u32);
+            comparable_schema(*id, &namespace, &name, format, content, version)
+        })
+        .collect();
+    let mut checked: HashMap<(&str, &str), MatrixCell> = HashMap::new();
+    let mut matrix = Vec::with_capacity(schemas.len());
+    for reader in &schemas {
+        let mut row = Vec::with_capacity(schemas.len());
+        for writer in &schemas {
+            let key = (reader.content_hash.as_str(), writer.content_hash.as_str());
+            let cell = match checked.get(&key) {
+                Some(cell) => *cell,
+                None => {
+                    let result = state
+                        .compatibility_checker
+                        .check_compatibility(reader, writer, CompatibilityMode::Backward)
+                        .await
+                        .map_err(compatibility_error)?;
+                    let cell = MatrixCell {
+                        compatible: result.is_compatible,
+                        violations: result.violations.len(),
+                    };
+                    checked.insert(key, cell);
+                    cell
+                }
+            };
+            row.push(cell);
+        }
+        matrix.push(row);
+    }
+
+    Ok(Json(CompatibilityMatrixResponse {
+        subject,
+        versions: schemas.iter().map(|s| s.version.to_string()).collect(),
+        matrix,
+        omitted_versions: (total as usize).saturating_sub(schemas.len()),
+    }))
+}
+
+// ============================================================================
+// Subject Handlers
+// ============================================================================
+
+async fn list_subject_versions(
+    State(state): State<AppState>,
+    Path(subject): Path<String>,
+) -> Result<Json<SubjectVersionsResponse>, AppError> {
+    let (namespace, name) = split_subject(&subject);
+    let (namespace, name, alias_warning) = resolve_subject(&state, namespace, name).await?;
+
+    let rows: Vec<(Uuid, i32, i32, i32, String, chrono::DateTime<Utc>)> = sqlx::query_as(
+        r#"
+        SELECT id, version_major, version_minor, version_patch, state, created_at
+        FROM schemas
+        WHERE namespace = $1 AND name = $2
+        ORDER BY version_major, version_minor, version_patch
+        "#,
+    )
+    .bind(&namespace)
+    .bind(&name)
+    .fetch_all(&state.db)
+    .await?;
+
+    let subject = subject_of(&namespace, &name);
+    if rows.is_empty() {
+        return Err(AppError::NotFound(format!("Subject {} not found", subject)));
+    }
+
+    let aliases: Vec<(String, String)> = sqlx::query_as(
+        "SELECT alias_namespace, alias_name FROM subject_aliases WHERE target_namespace = $1 AND target_name = $2 ORDER BY created_at",
+    )
+    .bind(&namespace)
+    .bind(&name)
+    .fetch_all(&state.db)
+    .await?;
+    let mut exemptions = registration_exemptions(&state, &namespace, &name).await?;
+
+    Ok(Json(SubjectVersionsResponse {
+        subject,
+        aliases: aliases.iter().map(|(ns, nm)| subject_of(ns, nm)).collect(),
+        versions: rows
+            .into_iter()
+            .map(|(id, major, minor, patch, state, created_at)| SubjectVersion {
+                id,
+                version: format!("{}.{}.{}", major, minor, patch),
+                state,
+                created_at: created_at.to_rfc3339(),
+                exemptions: exemptions.remove(&id).unwrap_or_default(),
+            })
+            .collect(),
+        warnings: alias_warning.into_iter().collect(),
+    }))
+}
+
+/// One page of a schema's versions, oldest first
+///
+/// `limit` is capped at [`MAX_PAGE_LIMIT`]; the response reports the limit
+/// applied and the total across pages.
+async fn list_schema_versions(
+    State(state): State<AppState>,
+    Path((namespace, name)): Path<(String, String)>,
+    Query(page): Query<PaginationParams>,
+) -> Result<Json<SchemaVersionsResponse>, AppError> {
+    let (namespace, name, alias_warning) = resolve_subject(&state, namespace, name).await?;
+    let limit = page.limit.clamp(1, MAX_PAGE_LIMIT);
+
+    let (total,): (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM schemas WHERE namespace = $1 AND name = $2")
+            .bind(&namespace)
+            .bind(&name)
+            .fetch_one(&state.db)
+            .await?;
+    if total == 0 {
+        return Err(AppError::NotFound(format!(
+            "Schema {} not found",
+            subject_of(&namespace, &name)
+        )));
+    }
+
+    let rows: Vec<(Uuid, i32, i32, i32, String, chrono::DateTime<Utc>, String)> = sqlx::query_as(
+        r#"
+        SELECT id, version_major, version_minor, version_patch, state, created_at, content_hash
+        FROM schemas
+        WHERE namespace = $1 AND name = $2
+        ORDER BY version_major, version_minor, version_patch
+        LIMIT $3 OFFSET $4
+        "#,
+    )
+    .bind(&namespace)
+    .bind(&name)
+    .bind(limit as i64)
+    .bind(page.offset as i64)
+    .fetch_all(&state.db)
+    .await?;
+    let mut exemptions = registration_exemptions(&state, &namespace, &name).await?;
+
+    Ok(Json(SchemaVersionsResponse {
+        namespace,
+        name,
+        versions: rows
+            .into_iter()
+            .map(
+                |(id, major, minor, patch, state, created_at, checksum)| VersionInfo {
+                    version: format!("{}.{}.{}", major, minor, patch),
+                    schema_id: id,
+                    state,
+                    created_at: created_at.to_rfc3339(),
+                    checksum,
+                    exemptions: exemptions.remove(&id).unwrap_or_default(),
+                },
+            )
+            .collect(),
+        total: total as u64,
+        limit,
+        offset: page.offset,
+        warnings: alias_warning.into_iter().collect(),
+    }))
+}
+
+/// Compatibility exemptions each version of a subject was registered under,
+/// by schema ID
+async fn registration_exemptions(
+    state: &AppState,
+    namespace: &str,
+    name: &str,
+) -> Result<HashMap<Uuid, Vec<Uuid>>, AppError> {
+    let rows: Vec<(Uuid, sqlx::types::Json<ExemptionTrail>)> = sqlx::query_as(
+        r#"
+        SELECT e.schema_id, e.event_data
+        FROM schema_events e
+        JOIN schemas s ON s.id = e.schema_id
+        WHERE s.namespace = $1 AND s.name = $2 AND e.event_type = $3
+        "#,
+    )
+    .bind(namespace)
+    .bind(name)
+    .bind(SCHEMA_EVENT_EXEMPTION_APPLIED)
+    .fetch_all(&state.db)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(|(id, trail)| (id, trail.0.exemptions))
+        .collect())
+}
+
+/// Everything recorded about a subject's versions, oldest first
+///
+/// Registrations, state changes, metadata updates and deletions, along with
+/// the compatibility exemptions breaking versions were registered under.
+async fn get_subject_changelog(
+    State(state): State<AppState>,
+    Path(subject): Path<String>,
+) -> Result<Json<SubjectChangelogResponse>, AppError> {
+    let (namespace, name) = split_subject(&subject);
+    let (namespace, name, alias_warning) = resolve_subject(&state, namespace, name).await?;
+
+    type Row = (
+        Uuid,
+        i32,
+        i32,
+        i32,
+        String,
+        serde_json::Value,
+        chrono::DateTime<Utc>,
+        Option<String>,
+    );
+    // Events written with a registration share its timestamp; the
+    // registration goes first
+    let rows: Vec<Row> = sqlx::query_as(
+        r#"
+        SELECT e.schema_id, s.version_major, s.version_minor, s.version_patch,
+               e.event_type, e.event_data, e.created_at, e.created_by
+        FROM schema_events e
+        JOIN schemas s ON s.id = e.schema_id
+        WHERE s.namespace = $1 AND s.name = $2
+        ORDER BY e.created_at, e.event_type <> $3, e.id
+        "#,
+    )
+    .bind(&namespace)
+    .bind(&name)
+    .bind(SCHEMA_EVENT_REGISTERED)
+    .fetch_all(&state.db)
+    .await?;
+
+    let subject = subject_of(&namespace, &name);
+    if rows.is_empty() {
+        return Err(AppError::NotFound(format!("Subject {} not found", subject)));
+    }
+    Ok(Json(SubjectChangelogResponse {
+        subject,
+        entries: rows
+            .into_iter()
+            .map(
+                |(schema_id, major, minor, patch, event_type, details, at, actor)| ChangelogEntry {
+                    schema_id,
+                    version: format!("{}.{}.{}", major, minor, patch),
+                    event_type,
+                    actor,
+                    at,
+                    details,
+                },
+            )
+            .collect(),
+        warnings: alias_warning.into_iter().collect(),
+    }))
+}
+
+/// The highest version of a schema that is neither deleted nor quarantined
+async fn get_latest_schema_version(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((namespace, name)): Path<(String, String)>,
+    Query(query): Query<FieldsQuery>,
+    Query(raw): Query<RawQuery>,
+) -> Result<Response, AppError> {
+    let started = std::time::Instant::now();
+    let (namespace, name, _) = resolve_subject(&state, namespace, name).await?;
+    let latest: Option<(Uuid,)> = sqlx::query_as(
+        r#"
+        SELECT id
+        FROM schemas
+        WHERE namespace = $1 AND name = $2 AND state NOT IN ('DELETED', 'QUARANTINED')
+        ORDER BY version_major DESC, version_minor DESC, version_patch DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(&namespace)
+    .bind(&name)
+    .fetch_optional(&state.db)
+    .await?;
+    let (id,) = latest.ok_or_else(|| {
+        AppError::NotFound(format!(
+            "Schema {} has no servable versions",
+            subject_of(&namespace, &name)
+        ))
+    })?;
+
+    let schema = fetch_schema(&state, id).await?;
+    record_usage(
+        &state,
+        id,
+        UsageOperation::Read,
+        &headers,
+        started.elapsed(),
+    );
+    if wants_raw_schema(&headers, raw.raw, &schema)? {
+        return Ok(raw_schema_response(&headers, &schema));
+    }
+    query.respond(schema, SCHEMA_FIELDS)
+}
+
+#[derive(Debug, Deserialize)]
+struct SubjectVersionQuery {
+    /// Wait until the version is in this state, e.g. `ACTIVE`
+    #[serde(default)]
+    wait_for_state: Option<SchemaState>,
+    /// How long to wait, e.g. `60s` or `500ms`
+    #[serde(default)]
+    timeout: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct SubjectVersionResponse {
+    id: Uuid,
+    subject: String,
+    version: String,
+    state: String,
+    created_at: String,
+    /// With `wait_for_state`, whether the version was in that state when the
+    /// response was sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reached: Option<bool>,
+}
+
+/// Parse a wait timeout: seconds, optionally suffixed `s`, or `ms` or `m`
+fn parse_wait_timeout(timeout: &str) -> Result<Duration, AppError> {
+    let timeout = timeout.trim();
+    let (digits, unit) = match timeout.find(|c: char| !c.is_ascii_digit()) {
+        Some(at) => timeout.split_at(at),
+        None => (timeout, "s"),
+    };
+    let value: u64 = digits
+        .parse()
+        .map_err(|_| AppError::InvalidInput(format!("Invalid timeout '{}'", timeout)))?;
+    match unit {
+        "ms" => Ok(Duration::from_millis(value)),
+        "s" => Ok(Duration::from_secs(value)),
+        "m" => Ok(Duration::from_secs(value.saturating_mul(60))),
+        _ => Err(AppError::InvalidInput(format!(
+            "Invalid timeout '{}'; use e.g. 60s, 500ms or 2m",
+            timeout
+        ))),
+    }
+}
+
+/// One version of a subject, optionally waiting for it to reach a state
+///
+/// With `?wait_for_state=ACTIVE&timeout=60s` the response is held until the
+/// version is `ACTIVE`, the timeout passes or it reaches a state it can't
+/// leave; `reached` says which. Changes made on any replica wake the request,
+/// through the database's `schema_changes` notifications.
+async fn get_subject_version(
+    State(state): State<AppState>,
+    Path((subject, version)): Path<(String, String)>,
+    Query(query): Query<SubjectVersionQuery>,
+) -> Result<Json<SubjectVersionResponse>, AppError> {
+    let (namespace, name) = split_subject(&subject);
+    let (namespace, name, _) = resolve_subject(&state, namespace, name).await?;
+    let version = version
+        .parse::<SemanticVersion>()
+        .map_err(|e| AppError::InvalidInput(e.to_string()))?;
+    let timeout = match &query.timeout {
+        Some(timeout) => parse_wait_timeout(timeout)?,
+        None => Duration::from_secs(WATCH_TIMEOUT_SECS),
+    }
+    .min(Duration::from_secs(MAX_WATCH_TIMEOUT_SECS));
+    let deadline = tokio::time::Instant::now() + timeout;
+
+    loop {
+        let mut changes = state.changes.subscribe();
+        let row: Option<(Uuid, String, chrono::DateTime<Utc>)> = sqlx::query_as(
+            r#"
+            SELECT id, state, created_at
+            FROM schemas
+            WHERE namespace = $1 AND name = $2
+              AND version_major = $3 AND version_minor = $4 AND version_patch = $5
+            "#,
+        )
+        .bind(&namespace)
+        .bind(&name)
+        .bind(version.major as i32)
+        .bind(version.minor as i32)
+        .bind(version.patch as i32)
+        .fetch_optional(&state.db)
+        .await?;
+        let subject = subject_of(&namespace, &name);
+        let (id, current, created_at) = row.ok_or_else(|| {
+            AppError::NotFound(format!("Version {} of {} not found", version, subject))
+        })?;
+
+        let Some(wanted) = query.wait_for_state else {
+            return Ok(Json(SubjectVersionResponse {
+                id,
+                subject,
+                version: version.to_string(),
+                state: current,
+                created_at: created_at.to_rfc3339(),
+                reached: None,
+            }));
+        };
+        let reached = current == wanted.to_string();
+        let stuck = serde_json::from_value::<SchemaState>(serde_json::json!(current))
+            .is_ok_and(|current| current.is_terminal());
+        let now = tokio::time::Instant::now();
+        if reached || stuck || now >= deadline {
+            return Ok(Json(SubjectVersionResponse {
+                id,
+                subject,
+                version: version.to_string(),
+                state: current,
+                created_at: created_at.to_rfc3339(),
+                reached: Some(reached),
+            }));
+        }
+        changes
+            .changed(id, (deadline - now).min(RECHECK_INTERVAL))
+            .await;
+    }
+}
+
+/// Consumers of a subject and the fields they read
+async fn list_consumer_manifests(
+    State(state): State<AppState>,
+    Path(subject): Path<String>,
+) -> Result<Json<ConsumerManifestsResponse>, AppError> {
+    let (namespace, name) = split_subject(&subject);
+    let (namespace, name, _) = resolve_subject(&state, namespace, name).await?;
+    let consumers = state.consumer_notifier.manifests(&namespace, &name).await?;
+    Ok(Json(ConsumerManifestsResponse {
+        subject: subject_of(&namespace, &name),
+        consumers,
+    }))
+}
+
+/// Declare the fields a consumer reads, and the webhook told when they change
+async fn put_consumer_manifest(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    impersonation: Option<Extension<Impersonation>>,
+    scoped: Option<Extension<ScopedToken>>,
+    Path((subject, consumer)): Path<(String, String)>,
+    BoundedJson(req): BoundedJson<ConsumerManifestRequest>,
+) -> Result<Json<ConsumerManifest>, AppError> {
+    let (namespace, name) = split_subject(&subject);
+    let (namespace, name, _) = resolve_subject(&state, namespace, name).await?;
+    let actor = ChangeActor::from_request(
+        &state,
+        &headers,
+        impersonation.as_ref().map(|Extension(i)| i),
+        scoped.as_ref().map(|Extension(s)| s),
+    )?;
+    ensure_unfrozen(&state, &actor, &namespace, "put_consumer_manifest").await?;
+
+    let fields = FieldManifest::new(req.fields);
+    fields
+        .validate()
+        .map_err(|e| AppError::InvalidInput(e.to_string()))?;
+    match reqwest::Url::parse(&req.webhook_url) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") => {}
+        _ => {
+            return Err(AppError::InvalidInput(format!(
+                "Invalid webhook URL '{}'",
+                req.webhook_url
+            )))
+        }
+    }
+
+    let (updated_at,): (chrono::DateTime<Utc>,) = sqlx::query_as(
+        r#"
+        INSERT INTO consumer_field_manifests (namespace, name, consumer, webhook_url, fields, updated_by)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (namespace, name, consumer) DO UPDATE
+        SET webhook_url = EXCLUDED.webhook_url, fields = EXCLUDED.fields,
+            updated_at = NOW(), updated_by = EXCLUDED.updated_by
+        RETURNING updated_at
+        "#,
+    )
+    .bind(&namespace)
+    .bind(&name)
+    .bind(&consumer)
+    .bind(&req.webhook_url)
+    .bind(fields.fields())
+    .bind(&actor.principal)
+    .fetch_one(&state.db)
+    .await?;
+
+    let event = AuditEvent::new(
+        AuditEventType::ConfigurationChanged,
+        "Consumer field manifest updated".to_string(),
+        AuditResult::Success,
+        String::new(),
+    )
+    .with_user(actor.principal.clone(), None)
+    .with_resource(
+        "consumer_field_manifest".to_string(),
+        format!("{}/{}", subject_of(&namespace, &name), consumer),
+    )
+    .with_metadata("fields".to_string(), serde_json::json!(fields.fields()));
+    state.audit_logger.log(event).await;
+
+    Ok(Json(ConsumerManifest {
+        consumer,
+        webhook_url: req.webhook_url,
+        fields,
+        updated_at,
+        updated_by: Some(actor.principal),
+    }))
+}
+
+async fn delete_consumer_manifest(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    impersonation: Option<Extension<Impersonation>>,
+    scoped: Option<Extension<ScopedToken>>,
+    Path((subject, consumer)): Path<(String, String)>,
+) -> Result<StatusCode, AppError> {
+    let (namespace, name) = split_subject(&subject);
+    let (namespace, name, _) = resolve_subject(&state, namespace, name).await?;
+    let actor = ChangeActor::from_request(
+        &state,
+        &headers,
+        impersonation.as_ref().map(|Extension(i)| i),
+        scoped.as_ref().map(|Extension(s)| s),
+    )?;
+    ensure_unfrozen(&state, &actor, &namespace, "delete_consumer_manifest").await?;
+
+    let deleted = sqlx::query(
+        "DELETE FROM consumer_field_manifests WHERE namespace = $1 AND name = $2 AND consumer = $3",
+    )
+    .bind(&namespace)
+    .bind(&name)
+    .bind(&consumer)
+    .execute(&state.db)
+    .await?;
+    if deleted.rows_affected() == 0 {
+        return Err(AppError::NotFound(format!(
+            "Consumer '{}' of {} not found",
+            consumer, subject
+        )));
+    }
+
+    let event = AuditEvent::new(
+        AuditEventType::ConfigurationChanged,
+        "Consumer field manifest removed".to_string(),
+        AuditResult::Success,
+        String::new(),
+    )
+    .with_user(actor.principal, None)
+    .with_resource(
+        "consumer_field_manifest".to_string(),
+        format!("{}/{}", subject_of(&namespace, &name), consumer),
+    );
+    state.audit_logger.log(event).await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ============================================================================
+// Lineage Edges
+// ============================================================================
+
+/// The registered version a `subject` or `subject@version` reference names;
+/// the latest version when none is given
+async fn resolve_schema_ref(state: &AppState, reference: &str) -> Result<SchemaNode, AppError> {
+    let (subject, version) =
+        fixtures::parse_schema_ref(reference).map_err(|e| AppError::InvalidInput(e.to_string()))?;
+    let (namespace, name) = split_subject(subject);
+    let (namespace, name, _) = resolve_subject(state, namespace, name).await?;
+
+    let row: Option<(Uuid, i32, i32, i32)> = match &version {
+        Some(v) => {
+            sqlx::query_as(
+                r#"
+                SELECT id, version_major, version_minor, version_patch
+                FROM schemas
+                WHERE namespace = $1 AND name = $2
+                  AND version_major = $3 AND version_minor = $4 AND version_patch = $5
+                "#,
+            )
+            .bind(&namespace)
+            .bind(&name)
+            .bind(v.major as i32)
+            .bind(v.minor as i32)
+            .bind(v.patch as i32)
+            .fetch_optional(&state.db)
+            .await?
+        }
+        None => {
+            sqlx::query_as(
+                r#"
+                SELECT id, version_major, version_minor, version_patch
+                FROM schemas
+                WHERE namespace = $1 AND name = $2
+                ORDER BY version_major DESC, version_minor DESC, version_patch DESC
+                LIMIT 1
+                "#,
+            )
+            .bind(&namespace)
+            .bind(&name)
+            .fetch_optional(&state.db)
+            .await?
+        }
+    };
+    let (id, major, minor, patch) =
+        row.ok_or_else(|| AppError::NotFound(format!("Schema {} not found", reference)))?;
+    Ok(SchemaNode::new(
+        id,
+        SemanticVersion::new(major as u32, minor as u32, patch as u32),
+        subject_of(&namespace, &name),
+    ))
+}
+
+/// Record a lineage edge from a registered schema to another schema or to an
+/// application, pipeline or model
+///
+/// Schemas are named by subject, optionally `@version`, as in a fixture
+/// file's `lineage` section; see `schema-cli admin seed`.
+async fn create_lineage_edge(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    BoundedJson(edge): BoundedJson<LineageFixture>,
+) -> Result<(StatusCode, Json<serde_json::Value>), AppError> {
+    let admin = require_admin(&state, &headers)?;
+    let relation: RelationType = match &edge.relation {
+        Some(relation) => serde_json::from_value(serde_json::json!(relation)).map_err(|_| {
+            AppError::InvalidInput(format!("Unknown lineage relation '{}'", relation))
+        })?,
+        None => RelationType::DependsOn,
+    };
+
+    let from = resolve_schema_ref(&state, &edge.from).await?;
+    let actor = ChangeActor::from_request(&state, &headers, None, None)?;
+    let namespace = split_subject(&from.fqn).0;
+    ensure_unfrozen(&state, &actor, &namespace, "create_lineage_edge").await?;
+    let to = match (&edge.to, &edge.entity) {
+        (Some(to), None) => DependencyTarget::Schema(resolve_schema_ref(&state, to).await?),
+        (None, Some(entity)) => {
+            let entity_type: EntityType =
+                serde_json::from_value(serde_json::json!(entity.entity_type)).map_err(|_| {
+                    AppError::InvalidInput(format!("Unknown entity type '{}'", entity.entity_type))
+                })?;
+            DependencyTarget::External(ExternalEntity {
+                id: entity.id.clone(),
+                entity_type,
+                name: entity.name.clone().unwrap_or_else(|| entity.id.clone()),
+                metadata: HashMap::new(),
+            })
+        }
+        _ => {
+            return Err(AppError::InvalidInput(
+                "A lineage edge needs exactly one of to or entity".to_string(),
+            ))
+        }
+    };
+
+    let body = serde_json::json!({
+        "from": from.schema_id,
+        "to": to.id(),
+        "relation": relation,
+    });
+    let (to_schema_id, entity) = match &to {
+        DependencyTarget::Schema(node) => (Some(node.schema_id), None),
+        DependencyTarget::External(entity) => (None, Some(sqlx::types::Json(entity))),
+    };
+    sqlx::query(
+        r#"
+        INSERT INTO lineage_edges (from_schema_id, to_schema_id, entity, relation, created_by)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT DO NOTHING
+        "#,
+    )
+    .bind(from.schema_id)
+    .bind(to_schema_id)
+    .bind(entity)
+    .bind(relation.to_string())
+    .bind(&admin)
+    .execute(&state.db)
+    .await?;
+
+    let event = AuditEvent::new(
+        AuditEventType::ConfigurationChanged,
+        "Lineage edge recorded".to_string(),
+        AuditResult::Success,
+        String::new(),
+    )
+    .with_user(admin, None)
+    .with_resource("lineage_edge".to_string(), edge.from.clone())
+    .with_metadata("edge".to_string(), body.clone());
+    state.audit_logger.log(event).await;
+
+    Ok((StatusCode::CREATED, Json(body)))
+}
+
+/// A stored lineage edge seen from one of its schemas: the relation, when it
+/// was recorded, the entity at the other end, or the ID, namespace, name and
+/// version of the schema there
+type LineageEdgeRow = (
+    String,
+    chrono::DateTime<Utc>,
+    Option<sqlx::types::Json<ExternalEntity>>,
+    Option<Uuid>,
+    Option<String>,
+    Option<String>,
+    Option<i32>,
+    Option<i32>,
+    Option<i32>,
+);
+
+/// One edge of a schema's lineage, with the schema or entity at the other end
+struct LineageLink {
+    relation: RelationType,
+    created_at: chrono::DateTime<Utc>,
+    other: DependencyTarget,
+}
+
+impl LineageLink {
+    /// `None` for an edge whose schema at the other end is gone
+    fn from_row(row: LineageEdgeRow) -> Option<Self> {
+        let (relation, created_at, entity, id, namespace, name, major, minor, patch) = row;
+        let relation = serde_json::from_value(serde_json::json!(relation)).ok()?;
+        let other = match entity {
+            Some(sqlx::types::Json(entity)) => DependencyTarget::External(entity),
+            None => DependencyTarget::Schema(SchemaNode::new(
+                id?,
+                SemanticVersion::new(major? as u32, minor? as u32, patch? as u32),
+                subject_of(&namespace?, &name?),
+            )),
+        };
+        Some(Self {
+            relation,
+            created_at,
+            other,
+        })
+    }
+}
+
+/// What `schema_id` depends on: schemas, and entities such as the
+/// applications using it
+async fn lineage_dependencies(
+    state: &AppState,
+    schema_id: Uuid,
+) -> Result<Vec<LineageLink>, AppError> {
+    let rows: Vec<LineageEdgeRow> = sqlx::query_as(
+        r#"
+        SELECT e.relation, e.created_at, e.entity,
+               s.id, s.namespace, s.name, s.version_major, s.version_minor, s.version_patch
+        FROM lineage_edges e
+        LEFT JOIN schemas s ON s.id = e.to_schema_id
+        WHERE e.from_schema_id = $1
+        ORDER BY e.id
+        "#,
+    )
+    .bind(schema_id)
+    .fetch_all(&state.db)
+    .await?;
+    Ok(rows.into_iter().filter_map(LineageLink::from_row).collect())
+}
+
+/// Schemas with an edge to `schema_id`
+async fn lineage_dependents(
+    state: &AppState,
+    schema_id: Uuid,
+) -> Result<Vec<LineageLink>, AppError> {
+    let rows: Vec<LineageEdgeRow> = sqlx::query_as(
+        r#"
+        SELECT e.relation, e.created_at, NULL::jsonb,
+               s.id, s.namespace, s.name, s.version_major, s.version_minor, s.version_patch
+        FROM lineage_edges e
+        JOIN schemas s ON s.id = e.from_schema_id
+        WHERE e.to_schema_id = $1 AND e.from_schema_id <> $1
+        ORDER BY e.id
+        "#,
+    )
+    .bind(schema_id)
+    .fetch_all(&state.db)
+    .await?;
+    Ok(rows.into_iter().filter_map(LineageLink::from_row).collect())
+}
+
+/// What reads `schema_id`: applications, pipelines and models using it, and
+/// schemas built on it
+async fn lineage_consumers(state: &AppState, schema_id: Uuid) -> Result<Vec<Consumer>, AppError> {
+    let mut consumers = Vec::new();
+    for link in lineage_dependencies(state, schema_id).await? {
+        let reads = link.relation.is_application_relation()
+            || link.relation.is_model_relation()
+            || link.relation == RelationType::ConsumedBy;
+        if let (DependencyTarget::External(entity), true) = (link.other, reads) {
+            consumers.push(Consumer {
+                id: entity.id,
+                name: entity.name,
+                entity_type: entity.entity_type,
+                relation: link.relation,
+            });
+        }
+    }
+    for link in lineage_dependents(state, schema_id).await? {
+        if let DependencyTarget::Schema(node) = link.other {
+            consumers.push(Consumer {
+                id: node.schema_id.to_string(),
+                name: node.fqn,
+                entity_type: EntityType::Schema,
+                relation: link.relation,
+            });
+        }
+    }
+    Ok(consumers)
+}
+
+// ============================================================================
+// Admin Handlers
+// ============================================================================
+
+/// Whether the request carries any bearer token
+fn has_bearer_token(headers: &HeaderMap) -> bool {
+    headers
+        .get("authorization")
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("Bearer "))
+}
+
+/// Compare a presented credential in time independent of where it differs
+fn secrets_match(provided: &str, expected: &str) -> bool {
+    provided.as_bytes().ct_eq(expected.as_bytes()).into()
+}
+
+/// Check the admin bearer token and return the acting admin's name
+fn require_admin(state: &AppState, headers: &HeaderMap) -> Result<String, AppError> {
+    let expected = state
+        .admin_token
+        .as_deref()
+        .ok_or_else(|| AppError::Unauthorized("Admin API is disabled".to_string()))?;
+
+    let provided = headers
+        .get("authorization")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+    if !provided.is_some_and(|provided| secrets_match(provided, expected)) {
+        return Err(AppError::Unauthorized("Invalid admin token".to_string()));
+    }
+
+    Ok(headers
+        .get("x-admin-user")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("admin")
+        .to_string())
+}
+
+/// Rename a subject, keeping the old name as an alias to the same version chain
+///
+/// Schema IDs are unchanged, so lineage edges and cached references stay valid.
+async fn rename_subject(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(subject): Path<String>,
+    BoundedJson(req): BoundedJson<RenameSubjectRequest>,
+) -> Result<Json<RenameSubjectResponse>, AppError> {
+    let admin = require_admin(&state, &headers)?;
+    let (old_namespace, old_name) = split_subject(&subject);
+    let (new_namespace, new_name) = split_subject(&req.new_subject);
+    if new_name.is_empty() || (old_namespace == new_namespace && old_name == new_name) {
+        return Err(AppError::InvalidInput(format!(
+            "Invalid new subject name '{}'",
+            req.new_subject
+        )));
+    }
+    ensure_namespace_allowed(&new_namespace)?;
+    let actor = ChangeActor::from_request(&state, &headers, None, None)?;
+    ensure_unfrozen(&state, &actor, &old_namespace, "rename_subject").await?;
+    if new_namespace != old_namespace {
+        ensure_unfrozen(&state, &actor, &new_namespace, "rename_subject").await?;
+    }
+
+    let mut tx = state.db.begin().await?;
+
+    let (taken,): (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM schemas WHERE namespace = $1 AND name = $2")
+            .bind(&new_namespace)
+            .bind(&new_name)
+            .fetch_one(&mut *tx)
+            .await?;
+    if taken > 0 {
+        return Err(AppError::Conflict(format!(
+            "Subject {} already exists",
+            req.new_subject
+        )));
+    }
+
+    // The new name may only be an alias if it points back at this subject
+    let alias_target: Option<(String, String)> = sqlx::query_as(
+        "SELECT target_namespace, target_name FROM subject_aliases WHERE alias_namespace = $1 AND alias_name = $2",
+    )
+    .bind(&new_namespace)
+    .bind(&new_name)
+    .fetch_optional(&mut *tx)
+    .await?;
+    if let Some((target_namespace, target_name)) = alias_target {
+        if target_namespace != old_namespace || target_name != old_name {
+            return Err(AppError::Conflict(format!(
+                "Subject {} is an alias of {}",
+                req.new_subject,
+                subject_of(&target_namespace, &target_name)
+            )));
+        }
+        sqlx::query("DELETE FROM subject_aliases WHERE alias_namespace = $1 AND alias_name = $2")
+            .bind(&new_namespace)
+            .bind(&new_name)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    let moved: Vec<(Uuid,)> = sqlx::query_as(
+        "UPDATE schemas SET namespace = $1, name = $2 WHERE namespace = $3 AND name = $4 RETURNING id",
+    )
+    .bind(&new_namespace)
+    .bind(&new_name)
+    .bind(&old_namespace)
+    .bind(&old_name)
+    .fetch_all(&mut *tx)
+    .await?;
+    if moved.is_empty() {
+        return Err(AppError::NotFound(format!("Subject {} not found", subject)));
+    }
+
+    // The version counters move too, so numbers of deleted versions aren't
+    // handed out again under the new name
+    for table in ["subject_version_sequences", "confluent_version_sequences"] {
+        sqlx::query(&format!(
+            r#"
+            WITH moved AS (
+                DELETE FROM {table} WHERE namespace = $3 AND name = $4
+                RETURNING last_version
+            )
+            INSERT INTO {table} (namespace, name, last_version)
+            SELECT $1, $2, last_version FROM moved
+            ON CONFLICT (namespace, name) DO UPDATE
+            SET last_version = GREATEST({table}.last_version, EXCLUDED.last_version)
+            "#
+        ))
+        .bind(&new_namespace)
+        .bind(&new_name)
+        .bind(&old_namespace)
+        .bind(&old_name)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    // Keep alias chains one hop long
+    sqlx::query(
+        "UPDATE subject_aliases SET target_namespace = $1, target_name = $2 WHERE target_namespace = $3 AND target_name = $4",
+    )
+    .bind(&new_namespace)
+    .bind(&new_name)
+    .bind(&old_namespace)
+    .bind(&old_name)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO subject_aliases (alias_namespace, alias_name, target_namespace, target_name, created_by)
+        VALUES ($1, $2, $3, $4, $5)
+        "#,
+    )
+    .bind(&old_namespace)
+    .bind(&old_name)
+    .bind(&new_namespace)
+    .bind(&new_name)
+    .bind(&admin)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    // Cached entries still carry the old namespace and name. The rename is
+    // committed either way; if the cache stays unavailable they expire with
+    // their TTL, or are flushed when the watchdog sees it recover.
+    let mut del = redis::cmd("DEL");
+    for (id,) in &moved {
+        del.arg(format!("{}{}", SCHEMA_CACHE_PREFIX, id));
+    }
+    let evicted: Result<(), redis::RedisError> = match state.cache() {
+        Some(conn) => {
+            state
+                .retry_policy("cache_evict")
+                .retry(|| {
+                    let mut conn = conn.clone();
+                    let del = &del;
+                    async move { del.query_async(&mut conn).await }
+                })
+                .await
+        }
+        None => Ok(()),
+    };
+    if let Err(e) = evicted {
+        tracing::warn!(error = %e, "Failed to evict renamed schemas from the cache");
+    }
+
+    let old_subject = subject_of(&old_namespace, &old_name);
+    let new_subject = subject_of(&new_namespace, &new_name);
+    let event = AuditEvent::new(
+        AuditEventType::SchemaUpdated,
+        "Subject renamed".to_string(),
+        AuditResult::Success,
+        String::new(),
+    )
+    .with_user(admin, None)
+    .with_resource("subject".to_string(), new_subject.clone())
+    .with_metadata("alias".to_string(), serde_json::json!(old_subject))
+    .with_metadata("schemas_moved".to_string(), serde_json::json!(moved.len()));
+    state.audit_logger.log(event).await;
+
+    tracing::info!(from = %old_subject, to = %new_subject, schemas = moved.len(), "Subject renamed");
+
+    Ok(Json(RenameSubjectResponse {
+        subject: new_subject,
+        alias: old_subject,
+        schemas_moved: moved.len(),
+    }))
+}
+
+/// Remove cache entries for schemas whose rows no longer exist
+///
+/// Runs as a dry run unless `?dry_run=false` is given.
+async fn run_gc(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<GcQuery>,
+) -> Result<Json<GcReport>, AppError> {
+    let admin = require_admin(&state, &headers)?;
+
+    let mut config = GcConfig {
+        dry_run: query.dry_run,
+        ..Default::default()
+    };
+    if let Some(max) = query.max_deletions {
+        config.max_deletions = max;
+    }
+
+    let report = GarbageCollector::new(Arc::new(PostgresSchemaIndex { db: state.db.clone() }))
+        .with_target(Arc::new(RedisGcTarget { redis: state.redis.clone() }))
+        .with_config(config)
+        .run()
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    tracing::info!(
+        admin = %admin,
+        dry_run = report.dry_run,
+        reclaimed_bytes = report.reclaimed_bytes(),
+        "Garbage collection run by admin"
+    );
+    if !report.dry_run {
+        let event = AuditEvent::new(
+            AuditEventType::ConfigurationChanged,
+            "Orphaned cache entries collected".to_string(),
+            AuditResult::Success,
+            String::new(),
+        )
+        .with_user(admin, None)
+        .with_metadata("targets".to_string(), serde_json::json!(report.targets));
+        state.audit_logger.log(event).await;
+    }
+    Ok(Json(report))
+}
+
+// ============================================================================
+// Garbage Collection
+// ============================================================================
+
+/// Prefix of cached schema keys, versioned so that entries of an older shape,
+/// such as those cached without `global_id`, are never read
+const SCHEMA_CACHE_PREFIX: &str = "schema:v2:";
+
+/// Schema rows in PostgreSQL
+struct PostgresSchemaIndex {
+    db: PgPool,
+}
+
+#[async_trait::async_trait]
+impl SchemaIndex for PostgresSchemaIndex {
+    async fn existing(&self, ids: &[Uuid]) -> CoreResult<HashSet<Uuid>> {
+        let rows: Vec<(Uuid,)> = sqlx::query_as("SELECT id FROM schemas WHERE id = ANY($1)")
+            .bind(ids)
+            .fetch_all(&self.db)
+            .await
+            .map_err(|e| CoreError::StorageError(e.to_string()))?;
+        Ok(rows.into_iter().map(|(id,)| id).collect())
+    }
+}
+
+/// Cached schemas in Redis (`schema:v2:{id}` keys)
+struct RedisGcTarget {
+    redis: LazyRedis,
+}
+
+#[async_trait::async_trait]
+impl GcTarget for RedisGcTarget {
+    fn name(&self) -> &str {
+        "redis"
+    }
+
+    async fn scan(&self, cursor: Option<String>, limit: usize) -> CoreResult<ScanPage> {
+        let mut conn = self
+            .redis
+            .get()
+            .await
+            .map_err(|e| CoreError::StorageError(e.to_string()))?;
+        let (next, keys): (String, Vec<String>) = redis::cmd("SCAN")
+            .arg(cursor.as_deref().unwrap_or("0"))
+            .arg("MATCH")
+            .arg(format!("{}*", SCHEMA_CACHE_PREFIX))
+            .arg("COUNT")
+            .arg(limit)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| CoreError::StorageError(e.to_string()))?;
+
+        let mut candidates = Vec::with_capacity(keys.len());
+        for key in keys {
+            let Some(schema_id) = schema_id_from_key(&key, SCHEMA_CACHE_PREFIX) else {
+                continue;
+            };
+            let size_bytes: Option<u64> = redis::cmd("MEMORY")
+                .arg("USAGE")
+                .arg(&key)
+                .query_async(&mut conn)
+                .await
+                .unwrap_or(None);
+            candidates.push(GcCandidate {
+                key,
+                schema_id,
+                size_bytes: size_bytes.unwrap_or(0),
+            });
+        }
+
+        Ok(ScanPage {
+            candidates,
+            next_cursor: (next != "0").then_some(next),
+        })
+    }
+
+    async fn delete_batch(&self, keys: &[String]) -> CoreResult<()> {
+        let mut conn = self
+            .redis
+            .get()
+            .await
+            .map_err(|e| CoreError::StorageError(e.to_string()))?;
+        let _: () = redis::cmd("DEL")
+            .arg(keys)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| CoreError::StorageError(e.to_string()))?;
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Dependency Watchdog
+// ============================================================================
+
+/// Redis answers PING
+struct RedisProbe {
+    redis: LazyRedis,
+}
+
+#[async_trait::async_trait]
+impl HealthProbe for RedisProbe {
+    fn name(&self) -> &'static str {
+        "redis"
+    }
+
+    fn is_cache(&self) -> bool {
+        true
+    }
+
+    async fn probe(&self) -> Result<(), String> {
+        let mut conn = self.redis.get().await.map_err(|e| e.to_string())?;
+        redis::cmd("PING")
+            .query_async::<_, String>(&mut conn)
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Postgres answers a trivial query
+struct DatabaseProbe {
+    db: PgPool,
+}
+
+#[async_trait::async_trait]
+impl HealthProbe for DatabaseProbe {
+    fn name(&self) -> &'static str {
+        "database"
+    }
+
+    async fn probe(&self) -> Result<(), String> {
+        sqlx::query("SELECT 1")
+            .fetch_one(&self.db)
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Flush the cache each time the watchdog leaves cache-bypass mode
+///
+/// Renames and retirements skip their evictions while the cache is bypassed,
+/// so entries written before the outage may describe schemas that have since
+/// changed.
+fn spawn_cache_flush_on_recovery(watchdog: &DependencyWatchdog, redis: LazyRedis) {
+    let mut modes = watchdog.subscribe();
+    tokio::spawn(async move {
+        while modes.changed().await.is_ok() {
+            if *modes.borrow_and_update() != StorageMode::Normal {
+                continue;
+            }
+            match flush_schema_cache(&redis).await {
+                Ok(keys) => tracing::info!(keys, "Flushed cache entries written before the outage"),
+                Err(e) => {
+                    tracing::warn!(error = %e, "Failed to flush the cache after it recovered")
+                }
+            }
+        }
+    });
+}
+
+/// Delete cached schemas and public search results, returning how many
+async fn flush_schema_cache(redis: &LazyRedis) -> redis::RedisResult<usize> {
+    let mut conn = redis.get().await?;
+    let mut flushed = 0;
+    for pattern in [
+        format!("{}*", SCHEMA_CACHE_PREFIX),
+        "public:search:*".to_string(),
+    ] {
+        let mut cursor = "0".to_string();
+        loop {
+            let (next, keys): (String, Vec<String>) = redis::cmd("SCAN")
+                .arg(&cursor)
+                .arg("MATCH")
+                .arg(&pattern)
+                .arg("COUNT")
+                .arg(1000)
+                .query_async(&mut conn)
+                .await?;
+            if !keys.is_empty() {
+                let _: () = redis::cmd("DEL").arg(&keys).query_async(&mut conn).await?;
+                flushed += keys.len();
+            }
+            if next == "0" {
+                break;
+            }
+            cursor = next;
+        }
+    }
+    Ok(flushed)
+}
+
+// ============================================================================
+// Compatibility History
+// ============================================================================
+
+/// Compatibility check history in the `compatibility_checks` table
+struct PostgresCompatibilityHistory {
+    db: PgPool,
+}
+
+type CompatibilityCheckRow = (
+    Uuid,
+    Option<String>,
+    Option<String>,
+    Uuid,
+    Option<String>,
+    Uuid,
+    Option<String>,
+    String,
+    bool,
+    Option<serde_json::Value>,
+    Option<serde_json::Value>,
+    Option<i64>,
+    chrono::DateTime<Utc>,
+    Option<String>,
+);
+
+#[async_trait::async_trait]
+impl CompatibilityHistoryStore for PostgresCompatibilityHistory {
+    async fn record(&self, record: &CompatibilityCheckRecord) -> CoreResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO compatibility_checks (
+                id, caller, subject, schema_id, schema_version, compared_schema_id,
+                compared_version, compatibility_mode, is_compatible, violations,
+                exempted_violations, latency_ms, checked_at, client_id
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+            "#,
+        )
+        .bind(record.id)
+        .bind(&record.caller)
+        .bind(&record.subject)
+        .bind(record.schema_id)
+        .bind(record.version.to_string())
+        .bind(record.compared_schema_id)
+        .bind(record.compared_version.to_string())
+        .bind(record.mode.to_string())
+        .bind(record.is_compatible)
+        .bind(serde_json::json!(record.violations))
+        .bind(serde_json::json!(record.exempted_violations))
+        .bind(record.latency_ms as i64)
+        .bind(record.checked_at)
+        .bind(&record.client_id)
+        .execute(&self.db)
+        .await
+        .map_err(|e| CoreError::StorageError(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn query(&self, query: &CompatibilityHistoryQuery) -> CoreResult<Vec<CompatibilityCheckRecord>> {
+        let rows: Vec<CompatibilityCheckRow> = sqlx::query_as(
+            r#"
+            SELECT id, caller, subject, schema_id, schema_version, compared_schema_id,
+                   compared_version, compatibility_mode, is_compatible, violations,
+                   exempted_violations, latency_ms, checked_at, client_id
+            FROM compatibility_checks
+            WHERE ($1::text IS NULL OR subject = $1)
+              AND ($2::text IS NULL OR caller = $2)
+              AND ($3::boolean IS NULL OR is_compatible = $3)
+              AND ($4::timestamptz IS NULL OR checked_at >= $4)
+              AND ($5::timestamptz IS NULL OR checked_at < $5)
+            ORDER BY checked_at DESC
+            LIMIT $6
+            "#,
+        )
+        .bind(&query.subject)
+        .bind(&query.caller)
+        .bind(query.is_compatible)
+        .bind(query.since)
+        .bind(query.until)
+        .bind(query.effective_limit() as i64)
+        .fetch_all(&self.db)
+        .await
+        .map_err(|e| CoreError::StorageError(e.to_string()))?;
+
+        // Rows written before history was recorded lack the newer columns
+        let version = |v: Option<String>| {
+            v.and_then(|v| v.parse::<SemanticVersion>().ok())
+                .unwrap_or_else(|| SemanticVersion::new(0, 0, 0))
+        };
+        let violations = |v: Option<serde_json::Value>| {
+            v.and_then(|v| serde_json::from_value(v).ok()).unwrap_or_default()
+        };
+        rows.into_iter()
+            .map(
+                |(
+                    id,
+                    caller,
+                    subject,
+                    schema_id,
+                    schema_version,
+                    compared_schema_id,
+                    compared_version,
+                    mode,
+                    is_compatible,
+                    violations_json,
+                    exempted_json,
+                    latency_ms,
+                    checked_at,
+                    client_id,
+                )| {
+                    Ok(CompatibilityCheckRecord {
+                        id,
+                        caller,
+                        client_id,
+                        subject: subject.unwrap_or_default(),
+                        schema_id,
+                        version: version(schema_version),
+                        compared_schema_id,
+                        compared_version: version(compared_version),
+                        mode: mode.parse()?,
+                        is_compatible,
+                        violations: violations(violations_json),
+                        exempted_violations: violations(exempted_json),
+                        latency_ms: latency_ms.unwrap_or(0) as u64,
+                        checked_at,
+                    })
+                },
+            )
+            .collect()
+    }
+}
+
+// ============================================================================
+// Compatibility Re-verification
+// ============================================================================
+
+/// Default for `REVERIFICATION_INTERVAL_SECS`: nightly
+const DEFAULT_REVERIFICATION_INTERVAL_SECS: u64 = 24 * 60 * 60;
+
+/// Advisory lock held while re-verifying, so one replica runs each round
+const REVERIFICATION_LOCK: i64 = 0x5245_5645_5249_4659;
+
+/// Breakage reports kept per namespace
+const BREAKAGE_REPORT_HISTORY: i64 = 30;
+
+/// Consumer pins in the `consumer_manifests` and `consumer_pins` tables
+struct PostgresManifestStore {
+    db: PgPool,
+}
+
+#[async_trait::async_trait]
+impl ConsumerManifestStore for PostgresManifestStore {
+    async fn list_manifests(&self) -> CoreResult<Vec<reverification::ConsumerManifest>> {
+        load_consumer_pins(&self.db, None)
+            .await
+            .map_err(|e| CoreError::StorageError(e.to_string()))
+    }
+}
+
+/// A consumer and one of its pins, if it has any
+type ConsumerPinRow = (
+    Uuid,
+    String,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+);
+
+/// Every consumer's pins, or only those of `consumer_id`
+async fn load_consumer_pins(
+    db: &PgPool,
+    consumer_id: Option<Uuid>,
+) -> Result<Vec<reverification::ConsumerManifest>, sqlx::Error> {
+    let rows: Vec<ConsumerPinRow> = sqlx::query_as(
+        r#"
+        SELECT m.consumer_id, m.consumer_name, m.owner, p.namespace, p.name, p.pinned_version
+        FROM consumer_manifests m
+        LEFT JOIN consumer_pins p ON p.consumer_id = m.consumer_id
+        WHERE ($1::uuid IS NULL OR m.consumer_id = $1)
+        ORDER BY m.consumer_name, m.consumer_id, p.namespace, p.name
+        "#,
+    )
+    .bind(consumer_id)
+    .fetch_all(db)
+    .await?;
+
+    let mut manifests: Vec<reverification::ConsumerManifest> = Vec::new();
+    for (consumer_id, consumer_name, owner, namespace, name, pinned_version) in rows {
+        if manifests
+            .last()
+            .is_none_or(|m| m.consumer_id != consumer_id)
+        {
+            manifests.push(reverification::ConsumerManifest {
+                consumer_id,
+                consumer_name,
+                owner,
+                dependencies: Vec::new(),
+            });
+        }
+        let (Some(namespace), Some(name), Some(pinned_version)) = (namespace, name, pinned_version)
+        else {
+            continue;
+        };
+        let Ok(pinned_version) = pinned_version.parse() else {
+            continue;
+        };
+        if let Some(manifest) = manifests.last_mut() {
+            manifest.dependencies.push(PinnedDependency {
+                namespace,
+                name,
+                pinned_version,
+            });
+        }
+    }
+    Ok(manifests)
+}
+
+/// Stored versions of a subject, in the lifecycle states re-verification
+/// picks release candidates by
+struct PostgresSubjectVersions {
+    db: PgPool,
+}
+
+#[async_trait::async_trait]
+impl SubjectVersions for PostgresSubjectVersions {
+    async fn versions(&self, namespace: &str, name: &str) -> CoreResult<Vec<RegisteredSchema>> {
+        let rows: Vec<(Uuid, String, String, i32, i32, i32, String)> = sqlx::query_as(
+            r#"
+            SELECT id, format, content, version_major, version_minor, version_patch, state
+            FROM schemas
+            WHERE namespace = $1 AND name = $2
+            "#,
+        )
+        .bind(namespace)
+        .bind(name)
+        .fetch_all(&self.db)
+        .await
+        .map_err(|e| CoreError::StorageError(e.to_string()))?;
+
+        // Deleted versions have no lifecycle state and are never pinned against
+        Ok(rows
+            .into_iter()
+            .filter_map(|(id, format, content, major, minor, patch, state)| {
+                let state = lifecycle_state(&state)?;
+                let version = SemanticVersion::new(major as u32, minor as u32, patch as u32);
+                let mut schema = comparable_schema(id, namespace, name, &format, &content, version);
+                schema.state = state;
+                Some(schema)
+            })
+            .collect())
+    }
+}
+
+/// Breakage reports in the `consumer_breakage_reports` table
+struct PostgresBreakageReports {
+    db: PgPool,
+}
+
+#[async_trait::async_trait]
+impl BreakageReportSink for PostgresBreakageReports {
+    async fn deliver(&self, report: &NamespaceBreakageReport) -> CoreResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO consumer_breakage_reports (namespace, generated_at, report)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (namespace, generated_at) DO UPDATE SET report = EXCLUDED.report
+            "#,
+        )
+        .bind(&report.namespace)
+        .bind(report.generated_at)
+        .bind(serde_json::json!(report))
+        .execute(&self.db)
+        .await
+        .map_err(|e| CoreError::StorageError(e.to_string()))?;
+
+        // Keep the newest reports of the namespace
+        sqlx::query(
+            r#"
+            DELETE FROM consumer_breakage_reports
+            WHERE namespace = $1 AND generated_at <= (
+                SELECT generated_at FROM consumer_breakage_reports
+                WHERE namespace = $1
+                ORDER BY generated_at DESC
+                OFFSET $2 LIMIT 1
+            )
+            "#,
+        )
+        .bind(&report.namespace)
+        .bind(BREAKAGE_REPORT_HISTORY)
+        .execute(&self.db)
+        .await
+        .map_err(|e| CoreError::StorageError(e.to_string()))?;
+        Ok(())
+    }
+}
+
+/// Re-verify on whichever replica takes the lock, every `interval` from now
+fn spawn_reverification(job: Arc<ReverificationJob>, db: PgPool, interval: Duration) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        loop {
+            ticker.tick().await;
+            match run_reverification(&job, &db).await {
+                Ok(Some(_)) => {}
+                Ok(None) => tracing::debug!("Re-verification is running on another replica"),
+                Err(e) => tracing::error!(error = %e, "Compatibility re-verification failed"),
+            }
+        }
+    });
+}
+
+/// Run `job` once, or return `None` when another replica is running it
+async fn run_reverification(
+    job: &ReverificationJob,
+    db: &PgPool,
+) -> Result<Option<Vec<NamespaceBreakageReport>>, AppError> {
+    // Session locks belong to a connection, so hold one for the whole run
+    let mut conn = db.acquire().await?;
+    let (locked,): (bool,) = sqlx::query_as("SELECT pg_try_advisory_lock($1)")
+        .bind(REVERIFICATION_LOCK)
+        .fetch_one(&mut *conn)
+        .await?;
+    if !locked {
+        return Ok(None);
+    }
+    let reports = job.run_once().await;
+    sqlx::query("SELECT pg_advisory_unlock($1)")
+        .bind(REVERIFICATION_LOCK)
+        .execute(&mut *conn)
+        .await?;
+    reports
+        .map(Some)
+        .map_err(|e| AppError::Internal(e.to_string()))
+}
+
+#[derive(Debug, Deserialize)]
+struct ConsumerPinsRequest {
+    consumer_name: String,
+    #[serde(default)]
+    owner: Option<String>,
+    dependencies: Vec<PinnedDependency>,
+}
+
+/// Consumers and the subject versions they are pinned to
+async fn list_consumer_pins(
+    State(state): State<AppState>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let consumers = load_consumer_pins(&state.db, None).await?;
+    Ok(Json(serde_json::json!({ "consumers": consumers })))
+}
+
+async fn get_consumer_pins(
+    State(state): State<AppState>,
+    Path(consumer_id): Path<Uuid>,
+) -> Result<Json<reverification::ConsumerManifest>, AppError> {
+    load_consumer_pins(&state.db, Some(consumer_id))
+        .await?
+        .pop()
+        .map(Json)
+        .ok_or_else(|| AppError::NotFound(format!("Consumer {} not found", consumer_id)))
+}
+
+/// Replace the subject versions a consumer is pinned to
+async fn put_consumer_pins(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(consumer_id): Path<Uuid>,
+    BoundedJson(req): BoundedJson<ConsumerPinsRequest>,
+) -> Result<Json<reverification::ConsumerManifest>, AppError> {
+    let admin = require_admin(&state, &headers)?;
+    if req.consumer_name.trim().is_empty() {
+        return Err(AppError::InvalidInput(
+            "Consumer name must not be empty".to_string(),
+        ));
+    }
+    let mut subjects = HashSet::new();
+    for dependency in &req.dependencies {
+        if !subjects.insert((&dependency.namespace, &dependency.name)) {
+            return Err(AppError::InvalidInput(format!(
+                "{} is pinned more than once",
+                subject_of(&dependency.namespace, &dependency.name)
+            )));
+        }
+    }
+
+    let mut tx = state.db.begin().await?;
+    sqlx::query(
+        r#"
+        INSERT INTO consumer_manifests (consumer_id, consumer_name, owner, updated_by)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (consumer_id) DO UPDATE
+        SET consumer_name = EXCLUDED.consumer_name, owner = EXCLUDED.owner,
+            updated_at = NOW(), updated_by = EXCLUDED.updated_by
+        "#,
+    )
+    .bind(consumer_id)
+    .bind(&req.consumer_name)
+    .bind(&req.owner)
+    .bind(&admin)
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query("DELETE FROM consumer_pins WHERE consumer_id = $1")
+        .bind(consumer_id)
+        .execute(&mut *tx)
+        .await?;
+    for dependency in &req.dependencies {
+        sqlx::query(
+            r#"
+            INSERT INTO consumer_pins (consumer_id, namespace, name, pinned_version)
+            VALUES ($1, $2, $3, $4)
+            "#,
+        )
+        .bind(consumer_id)
+        .bind(&dependency.namespace)
+        .bind(&dependency.name)
+        .bind(dependency.pinned_version.to_string())
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+
+    let event = AuditEvent::new(
+        AuditEventType::ConfigurationChanged,
+        "Consumer pins updated".to_string(),
+        AuditResult::Success,
+        String::new(),
+    )
+    .with_user(admin.clone(), None)
+    .with_resource("consumer_pins".to_string(), consumer_id.to_string())
+    .with_metadata(
+        "dependencies".to_string(),
+        serde_json::json!(req.dependencies),
+    );
+    state.audit_logger.log(event).await;
+
+    Ok(Json(reverification::ConsumerManifest {
+        consumer_id,
+        consumer_name: req.consumer_name,
+        owner: req.owner,
+        dependencies: req.dependencies,
+    }))
+}
+
+async fn delete_consumer_pins(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(consumer_id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    let admin = require_admin(&state, &headers)?;
+    let deleted = sqlx::query("DELETE FROM consumer_manifests WHERE consumer_id = $1")
+        .bind(consumer_id)
+        .execute(&state.db)
+        .await?;
+    if deleted.rows_affected() == 0 {
+        return Err(AppError::NotFound(format!(
+            "Consumer {} not found",
+            consumer_id
+        )));
+    }
+
+    let event = AuditEvent::new(
+        AuditEventType::ConfigurationChanged,
+        "Consumer pins deleted".to_string(),
+        AuditResult::Success,
+        String::new(),
+    )
+    .with_user(admin, None)
+    .with_resource("consumer_pins".to_string(), consumer_id.to_string());
+    state.audit_logger.log(event).await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Latest "who will break at the next release" report for a namespace
+async fn get_breakage_report(
+    State(state): State<AppState>,
+    Path(namespace): Path<String>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let report: Option<(serde_json::Value,)> = sqlx::query_as(
+        r#"
+        SELECT report FROM consumer_breakage_reports
+        WHERE namespace = $1
+        ORDER BY generated_at DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(&namespace)
+    .fetch_optional(&state.db)
+    .await?;
+    report.map(|(report,)| Json(report)).ok_or_else(|| {
+        AppError::NotFound(format!("No breakage report for namespace {}", namespace))
+    })
+}
+
+/// Re-verify every consumer pin now instead of waiting for the next round
+async fn run_reverification_now(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let admin = require_admin(&state, &headers)?;
+    let reports = run_reverification(&state.reverification, &state.db)
+        .await?
+        .ok_or_else(|| AppError::Conflict("Re-verification is already running".to_string()))?;
+    tracing::info!(admin = %admin, namespaces = reports.len(), "Re-verification run by admin");
+    Ok(Json(serde_json::json!({ "reports": reports })))
+}
+
+// ============================================================================
+// Slow Operations
+// ============================================================================
+
+/// Default for `SLOW_OPERATION_THRESHOLD_MS`
+const DEFAULT_SLOW_OPERATION_THRESHOLD_MS: u64 = 500;
+
+/// A compatibility check or validation that exceeded the slow-operation threshold
+#[derive(Debug, Serialize)]
+struct SlowOperation {
+    id: Uuid,
+    /// `compatibility_check` or `validation`
+    operation: String,
+    schema_id: Uuid,
+    subject: Option<String>,
+    schema_size_bytes: u64,
+    /// Compatibility mode, for compatibility checks
+    mode: Option<String>,
+    duration_ms: u64,
+    recorded_at: chrono::DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SlowOperationQuery {
+    #[serde(default)]
+    operation: Option<String>,
+    #[serde(default)]
+    schema_id: Option<Uuid>,
+    #[serde(default)]
+    min_duration_ms: Option<u64>,
+    #[serde(default)]
+    since: Option<chrono::DateTime<Utc>>,
+    #[serde(default)]
+    until: Option<chrono::DateTime<Utc>>,
+    #[serde(default)]
+    limit: Option<usize>,
+}
+
+/// Slow operations of one schema within a query's results
+#[derive(Debug, Serialize)]
+struct SlowSchemaSummary {
+    schema_id: Uuid,
+    subject: Option<String>,
+    count: usize,
+    total_duration_ms: u64,
+    max_duration_ms: u64,
+}
+
+type SlowOperationRow = (
+    Uuid,
+    String,
+    Uuid,
+    Option<String>,
+    i64,
+    Option<String>,
+    i64,
+    chrono::DateTime<Utc>,
+);
+
+/// Observe an operation's duration and log it when it exceeds the threshold
+///
+/// The slow-operation row is written in the background so it neither slows
+/// the request further nor is lost when the request is cancelled.
+fn observe_operation(
+    state: &AppState,
+    operation: &'static str,
+    schema_id: Uuid,
+    subject: Option<String>,
+    schema_size_bytes: usize,
+    mode: Option<String>,
+    elapsed: Duration,
+) {
+    OPERATION_DURATION
+        .with_label_values(&[operation])
+        .observe(elapsed.as_secs_f64());
+    if elapsed < state.slow_operation_threshold {
+        return;
+    }
+
+    tracing::warn!(
+        operation,
+        schema_id = %schema_id,
+        schema_size_bytes,
+        duration_ms = elapsed.as_millis() as u64,
+        "Slow operation"
+    );
+    let db = state.db.clone();
+    tokio::spawn(async move {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO slow_operations (
+                id, operation, schema_id, subject, schema_size_bytes,
+                compatibility_mode, duration_ms, recorded_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(operation)
+        .bind(schema_id)
+        .bind(subject)
+        .bind(schema_size_bytes as i64)
+        .bind(mode)
+        .bind(elapsed.as_millis() as i64)
+        .bind(Utc::now())
+        .execute(&db)
+        .await;
+        if let Err(e) = result {
+            tracing::warn!(error = %e, "Failed to record slow operation");
+        }
+    });
+}
+
+/// Query logged slow operations, slowest first, with a per-schema summary
+async fn slow_operations(
+    State(state): State<AppState>,
+    Query(query): Query<SlowOperationQuery>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_HISTORY_LIMIT)
+        .min(MAX_HISTORY_LIMIT);
+    let hints = vec![
+        "Filter by schema_id or operation".to_string(),
+        "Narrow the time range with since and until".to_string(),
+    ];
+    let rows: Vec<SlowOperationRow> = state
+        .query_limits
+        .fetch_all(&state.db, |qb| {
+            qb.push(
+                "SELECT id, operation, schema_id, subject, schema_size_bytes, \
+                 compatibility_mode, duration_ms, recorded_at \
+                 FROM slow_operations WHERE TRUE",
+            );
+            if let Some(operation) = &query.operation {
+                qb.push(" AND operation = ").push_bind(operation.clone());
+            }
+            if let Some(schema_id) = query.schema_id {
+                qb.push(" AND schema_id = ").push_bind(schema_id);
+            }
+            if let Some(ms) = query.min_duration_ms {
+                qb.push(" AND duration_ms >= ").push_bind(ms as i64);
+            }
+            if let Some(since) = query.since {
+                qb.push(" AND recorded_at >= ").push_bind(since);
+            }
+            if let Some(until) = query.until {
+                qb.push(" AND recorded_at < ").push_bind(until);
+            }
+            qb.push(" ORDER BY duration_ms DESC, recorded_at DESC LIMIT ")
+                .push_bind(limit as i64);
+        })
+        .await
+        .map_err(|e| AppError::query_cost(e, hints))?;
+
+    let operations: Vec<SlowOperation> = rows
+        .into_iter()
+        .map(
+            |(id, operation, schema_id, subject, size, mode, duration_ms, recorded_at)| {
+                SlowOperation {
+                    id,
+                    operation,
+                    schema_id,
+                    subject,
+                    schema_size_bytes: size as u64,
+                    mode,
+                    duration_ms: duration_ms as u64,
+                    recorded_at,
+                }
+            },
+        )
+        .collect();
+
+    let mut by_schema: HashMap<Uuid, SlowSchemaSummary> = HashMap::new();
+    for op in &operations {
+        let summary = by_schema.entry(op.schema_id).or_insert_with(|| SlowSchemaSummary {
+            schema_id: op.schema_id,
+            subject: op.subject.clone(),
+            count: 0,
+            total_duration_ms: 0,
+            max_duration_ms: 0,
+        });
+        summary.count += 1;
+        summary.total_duration_ms += op.duration_ms;
+        summary.max_duration_ms = summary.max_duration_ms.max(op.duration_ms);
+    }
+    let mut schemas: Vec<SlowSchemaSummary> = by_schema.into_values().collect();
+    schemas.sort_by(|a, b| b.total_duration_ms.cmp(&a.total_duration_ms));
+
+    Ok(Json(serde_json::json!({
+        "threshold_ms": state.slow_operation_threshold.as_millis() as u64,
+        "operations": operations,
+        "schemas": schemas,
+    })))
+}
+
+// ============================================================================
+// Namespace Handlers
+// ============================================================================
+
+async fn get_namespace_settings(
+    State(state): State<AppState>,
+    Path(namespace): Path<String>,
+) -> Result<Json<NamespaceSettings>, AppError> {
+    Ok(Json(state.namespaces.get(&namespace).unwrap_or_default()))
+}
+
+async fn put_namespace_settings(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(namespace): Path<String>,
+    BoundedJson(settings): BoundedJson<NamespaceSettings>,
+) -> Result<Json<NamespaceSettings>, AppError> {
+    let admin = require_admin(&state, &headers)?;
+    let actor = ChangeActor::from_request(&state, &headers, None, None)?;
+    ensure_unfrozen(&state, &actor, &namespace, "update_namespace_settings").await?;
+    let write = state.namespace_store.lock().await;
+    state
+        .namespaces
+        .set(&namespace, settings.clone())
+        .map_err(|e| AppError::InvalidInput(e.to_string()))?;
+    write
+        .commit([&namespace], &admin)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let event = AuditEvent::new(
+        AuditEventType::ConfigurationChanged,
+        "Namespace settings updated".to_string(),
+        AuditResult::Success,
+        String::new(),
+    )
+    .with_user(admin, None)
+    .with_resource("namespace".to_string(), namespace)
+    .with_metadata("settings".to_string(), serde_json::json!(settings));
+    state.audit_logger.log(event).await;
+
+    Ok(Json(settings))
+}
+
+async fn delete_namespace_settings(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(namespace): Path<String>,
+) -> Result<StatusCode, AppError> {
+    let admin = require_admin(&state, &headers)?;
+    let actor = ChangeActor::from_request(&state, &headers, None, None)?;
+    ensure_unfrozen(&state, &actor, &namespace, "delete_namespace_settings").await?;
+    let write = state.namespace_store.lock().await;
+    if state.namespaces.remove(&namespace).is_none() {
+        return Err(AppError::NotFound(format!(
+            "Namespace '{}' has no explicit settings",
+            namespace
+        )));
+    }
+    write
+        .commit([&namespace], &admin)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let event = AuditEvent::new(
+        AuditEventType::ConfigurationChanged,
+        "Namespace settings removed".to_string(),
+        AuditResult::Success,
+        String::new(),
+    )
+    .with_user(admin, None)
+    .with_resource("namespace".to_string(), namespace);
+    state.audit_logger.log(event).await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Resolved settings with the namespace each value was inherited from
+async fn get_effective_namespace_settings(
+    State(state): State<AppState>,
+    Path(namespace): Path<String>,
+) -> Result<Json<EffectiveSettings>, AppError> {
+    let effective = state
+        .namespaces
+        .effective(&namespace)
+        .map_err(|e| AppError::InvalidInput(e.to_string()))?;
+    Ok(Json(effective))
+}
+
+// ============================================================================
+// Ownership Transfers
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+struct ListTransfersQuery {
+    namespace: Option<String>,
+    status: Option<TransferStatus>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct TransferDecision {
+    #[serde(default)]
+    note: Option<String>,
+}
+
+fn transfer_event(message: &str, transfer: &OwnershipTransfer, actor: &str) -> AuditEvent {
+    AuditEvent::new(
+        AuditEventType::ConfigurationChanged,
+        message.to_string(),
+        AuditResult::Success,
+        String::new(),
+    )
+    .with_user(actor.to_string(), None)
+    .with_resource("namespace".to_string(), transfer.namespace.clone())
+    .with_metadata("transfer".to_string(), serde_json::json!(transfer))
+}
+
+/// Ask another team to take over a namespace
+async fn request_ownership_transfer(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(namespace): Path<String>,
+    BoundedJson(req): BoundedJson<TransferRequest>,
+) -> Result<(StatusCode, Json<OwnershipTransfer>), AppError> {
+    let admin = require_admin(&state, &headers)?;
+    let current = state
+        .namespaces
+        .effective(&namespace)
+        .map_err(|e| AppError::InvalidInput(e.to_string()))?;
+    let transfer = state
+        .ownership_transfers
+        .request(&current, req, &admin)
+        .await?;
+    let event = transfer_event("Ownership transfer requested", &transfer, &admin);
+    state.audit_logger.log(event).await;
+    Ok((StatusCode::CREATED, Json(transfer)))
+}
+
+async fn list_ownership_transfers(
+    State(state): State<AppState>,
+    Query(query): Query<ListTransfersQuery>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let namespace = query
+        .namespace
+        .as_deref()
+        .map(schema_registry_core::namespace::normalize)
+        .transpose()
+        .map_err(|e| AppError::InvalidInput(e.to_string()))?;
+    let transfers = state
+        .ownership_transfers
+        .list(namespace.as_deref(), query.status)
+        .await?;
+    Ok(Json(serde_json::json!({ "transfers": transfers })))
+}
+
+async fn get_ownership_transfer(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<OwnershipTransfer>, AppError> {
+    state
+        .ownership_transfers
+        .get(id)
+        .await?
+        .map(Json)
+        .ok_or_else(|| AppError::NotFound(format!("Ownership transfer {} not found", id)))
+}
+
+/// Who is deciding a transfer, which must be its receiving owner
+///
+/// The receiving team decides with a token issued to it. The admin token
+/// names no team, so it can't decide; an admin who must act for a team
+/// impersonates it.
+fn transfer_decider(
+    state: &AppState,
+    headers: &HeaderMap,
+    impersonation: Option<&Impersonation>,
+    scoped: Option<&ScopedToken>,
+    transfer: &OwnershipTransfer,
+) -> Result<ChangeActor, AppError> {
+    let actor = ChangeActor::from_request(state, headers, impersonation, scoped)?;
+    actor.ensure_authenticated()?;
+    if impersonation.is_none() && scoped.is_none() {
+        return Err(AppError::Forbidden(format!(
+            "Ownership transfer {} is decided by {} with its own token",
+            transfer.id, transfer.to_owner
+        )));
+    }
+    transfer.ensure_receiving_owner(&actor.principal)?;
+    Ok(actor)
+}
+
+/// The receiving team takes over; owner and notification channel change together
+async fn accept_ownership_transfer(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    impersonation: Option<Extension<Impersonation>>,
+    scoped: Option<Extension<ScopedToken>>,
+    Path(id): Path<Uuid>,
+    decision: Option<BoundedJson<TransferDecision>>,
+) -> Result<Json<OwnershipTransfer>, AppError> {
+    let impersonation = impersonation.as_ref().map(|Extension(i)| i);
+    let pending = state
+        .ownership_transfers
+        .get(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Ownership transfer {} not found", id)))?;
+    let actor = transfer_decider(
+        &state,
+        &headers,
+        impersonation,
+        scoped.as_ref().map(|Extension(s)| s),
+        &pending,
+    )?;
+    ensure_unfrozen(&state, &actor, &pending.namespace, "transfer_ownership").await?;
+
+    let note = decision.and_then(|BoundedJson(decision)| decision.note);
+    let write = state.namespace_store.lock().await;
+    let (transfer, previous) = state
+        .ownership_transfers
+        .accept(id, &actor.principal, note, &state.namespaces, write)
+        .await?;
+    let mut event = transfer_event("Ownership transfer accepted", &transfer, &actor.principal)
+        .with_metadata("previous_settings".to_string(), serde_json::json!(previous));
+    if let Some(impersonation) = impersonation {
+        event = impersonation.attribute(event);
+    }
+    state.audit_logger.log(event).await;
+    Ok(Json(transfer))
+}
+
+async fn reject_ownership_transfer(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    impersonation: Option<Extension<Impersonation>>,
+    scoped: Option<Extension<ScopedToken>>,
+    Path(id): Path<Uuid>,
+    decision: Option<BoundedJson<TransferDecision>>,
+) -> Result<Json<OwnershipTransfer>, AppError> {
+    let impersonation = impersonation.as_ref().map(|Extension(i)| i);
+    let pending = state
+        .ownership_transfers
+        .get(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Ownership transfer {} not found", id)))?;
+    let actor = transfer_decider(
+        &state,
+        &headers,
+        impersonation,
+        scoped.as_ref().map(|Extension(s)| s),
+        &pending,
+    )?;
+    let note = decision.and_then(|BoundedJson(decision)| decision.note);
+    let transfer = state
+        .ownership_transfers
+        .reject(id, &actor.principal, note)
+        .await?;
+    let mut event = transfer_event("Ownership transfer rejected", &transfer, &actor.principal);
+    if let Some(impersonation) = impersonation {
+        event = impersonation.attribute(event);
+    }
+    state.audit_logger.log(event).await;
+    Ok(Json(transfer))
+}
+
+async fn cancel_ownership_transfer(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+    decision: Option<BoundedJson<TransferDecision>>,
+) -> Result<Json<OwnershipTransfer>, AppError> {
+    let admin = require_admin(&state, &headers)?;
+    let note = decision.and_then(|BoundedJson(decision)| decision.note);
+    let transfer = state.ownership_transfers.cancel(id, &admin, note).await?;
+    let event = transfer_event("Ownership transfer cancelled", &transfer, &admin);
+    state.audit_logger.log(event).await;
+    Ok(Json(transfer))
+}
+
+// ============================================================================
+// Change Freezes
+// ============================================================================
+
+/// Principal of changes made without credentials
+const ANONYMOUS_PRINCIPAL: &str = "anonymous";
+
+/// Who is making a change, as far as change freezes and token scopes are
+/// concerned
+///
+/// The admin token holds every permission, `freeze:override` and
+/// `security:review` included.
+/// Callers without credentials change as `anonymous`, but a bearer token no
+/// authenticator recognized is refused rather than ignored.
+struct ChangeActor {
+    principal: String,
+    permissions: Vec<String>,
+    /// Reason given in the `X-Freeze-Override` header
+    override_reason: Option<String>,
+    /// Restrictions of a scoped token
+    scope: Option<TokenScope>,
+}
+
+impl ChangeActor {
+    fn from_request(
+        state: &AppState,
+        headers: &HeaderMap,
+        impersonation: Option<&Impersonation>,
+        scoped: Option<&ScopedToken>,
+    ) -> Result<Self, AppError> {
+        let (principal, permissions) = match (impersonation, scoped) {
+            (Some(impersonation), _) => (
+                impersonation.principal.clone(),
+                impersonation.permissions.clone(),
+            ),
+            (None, Some(scoped)) => (scoped.principal.clone(), scoped.scope.permissions.clone()),
+            (None, None) => match require_admin(state, headers) {
+                Ok(admin) => (
+                    admin,
+                    vec![
+                        FREEZE_OVERRIDE_PERMISSION.to_string(),
+                        SECURITY_REVIEW_PERMISSION.to_string(),
+                    ],
+                ),
+                Err(_) if has_bearer_token(headers) => {
+                    return Err(AppError::Unauthorized(
+                        "Bearer token not recognized".to_string(),
+                    ))
+                }
+                Err(_) => (ANONYMOUS_PRINCIPAL.to_string(), Vec::new()),
+            },
+        };
+        let override_reason = headers
+            .get(FREEZE_OVERRIDE_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        Ok(Self {
+            principal,
+            permissions,
+            override_reason,
+            scope: scoped.map(|scoped| scoped.scope.clone()),
+        })
+    }
+
+    /// Principal of an admin, scoped or impersonated caller; none without
+    /// credentials
+    fn authenticated_principal(&self) -> Option<&str> {
+        (self.principal != ANONYMOUS_PRINCIPAL).then_some(self.principal.as_str())
+    }
+
+    /// Reject changes made without credentials
+    fn ensure_authenticated(&self) -> Result<(), AppError> {
+        if self.authenticated_principal().is_none() {
+            return Err(AppError::Unauthorized(
+                "This change requires credentials".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Reject a change to `namespace` outside the actor's token scope
+    fn ensure_in_scope(&self, namespace: &str) -> Result<(), AppError> {
+        let Some(scope) = &self.scope else {
+            return Ok(());
+        };
+        if !scope.allows_permission(SCHEMA_WRITE_PERMISSION) {
+            return Err(AppError::Forbidden(format!(
+                "Token lacks the {} permission",
+                SCHEMA_WRITE_PERMISSION
+            )));
+        }
+        if !scope.allows_namespace(namespace) {
+            return Err(AppError::Forbidden(format!(
+                "Token is not scoped to namespace '{}'",
+                namespace
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Reject a change to a namespace outside the actor's token scope, or while
+/// a freeze window is in force unless the actor overrides it; returns the
+/// window that was overridden
+async fn ensure_unfrozen(
+    state: &AppState,
+    actor: &ChangeActor,
+    namespace: &str,
+    operation: &str,
+) -> Result<Option<ActiveFreeze>, AppError> {
+    actor.ensure_in_scope(namespace)?;
+    let result = state
+        .freezes
+        .check(FreezeCheck {
+            namespace,
+            operation,
+            principal: &actor.principal,
+            permissions: &actor.permissions,
+            override_reason: actor.override_reason.as_deref(),
+        })
+        .await;
+    let outcome = match &result {
+        Ok(None) => return Ok(None),
+        Ok(Some(_)) => "overridden",
+        Err(FreezeError::OverrideDenied { .. }) => "override_denied",
+        Err(FreezeError::Storage(_)) => return result.map_err(AppError::Frozen),
+        Err(_) => "rejected",
+    };
+    CHANGE_FREEZE_DECISIONS.with_label_values(&[outcome]).inc();
+    result.map_err(AppError::Frozen)
+}
+
+// ============================================================================
+// Trace Sampling
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+struct TraceSamplingRequest {
+    /// New sampling rate of each exporter to change
+    exporters: BTreeMap<String, f64>,
+}
+
+fn trace_sampling(state: &AppState) -> Result<&SamplingControl, AppError> {
+    state.trace_sampling.as_ref().ok_or_else(|| {
+        AppError::NotFound("Traces are not exported; set TRACE_EXPORTERS to enable".to_string())
+    })
+}
+
+async fn get_trace_sampling(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<serde_json::Value>, AppError> {
+    require_admin(&state, &headers)?;
+    let sampling = trace_sampling(&state)?;
+    Ok(Json(serde_json::json!({ "exporters": sampling.rates() })))
+}
+
+/// Change exporters' sampling rates without a restart
+async fn put_trace_sampling(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    BoundedJson(request): BoundedJson<TraceSamplingRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let admin = require_admin(&state, &headers)?;
+    let sampling = trace_sampling(&state)?;
+    let previous = sampling.rates();
+    sampling
+        .update(&request.exporters)
+        .map_err(|e| AppError::InvalidInput(e.to_string()))?;
+    let rates = sampling.rates();
+
+    let event = AuditEvent::new(
+        AuditEventType::ConfigurationChanged,
+        "Trace sampling rates changed".to_string(),
+        AuditResult::Success,
+        String::new(),
+    )
+    .with_user(admin, None)
+    .with_resource("trace_sampling".to_string(), "exporters".to_string())
+    .with_metadata("previous".to_string(), serde_json::json!(previous))
+    .with_metadata("rates".to_string(), serde_json::json!(rates));
+    state.audit_logger.log(event).await;
+
+    Ok(Json(serde_json::json!({ "exporters": rates })))
+}
+
+// ============================================================================
+// Change Feed
+// ============================================================================
+
+/// Changes returned by one change feed request unless asked for fewer
+const DEFAULT_CHANGES_LIMIT: usize = 500;
+/// Most changes one change feed request returns
+const MAX_CHANGES_LIMIT: usize = 5000;
+
+#[derive(Debug, Deserialize)]
+struct ChangesQuery {
+    /// Cursor of the last change already seen
+    #[serde(default)]
+    after: Option<i64>,
+    #[serde(default)]
+    limit: Option<usize>,
+}
+
+/// A schema row that was created, updated or deleted
+#[derive(Debug, Serialize)]
+struct SchemaChange {
+    sequence: i64,
+    schema_id: Uuid,
+    subject: String,
+    change: String,
+    changed_at: chrono::DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+struct ChangesResponse {
+    changes: Vec<SchemaChange>,
+    /// Pass as `after` to get the changes that follow
+    cursor: i64,
+    has_more: bool,
+}
+
+/// Schemas changed since a cursor, oldest first
+///
+/// Without `after` no changes are returned, only the cursor of the latest
+/// one, so a client can start following the feed from now.
+async fn list_changes(
+    State(state): State<AppState>,
+    Query(query): Query<ChangesQuery>,
+) -> Result<Json<ChangesResponse>, AppError> {
+    let Some(after) = query.after else {
+        let (cursor,): (i64,) =
+            sqlx::query_as("SELECT COALESCE(MAX(sequence), 0) FROM schema_changes")
+                .fetch_one(&state.db)
+                .await?;
+        return Ok(Json(ChangesResponse {
+            changes: Vec::new(),
+            cursor,
+            has_more: false,
+        }));
+    };
+
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_CHANGES_LIMIT)
+        .min(MAX_CHANGES_LIMIT);
+    // One extra row tells whether another page follows
+    let mut rows: Vec<(i64, Uuid, String, String, String, chrono::DateTime<Utc>)> = sqlx::query_as(
+        "SELECT sequence, schema_id, namespace, name, change, changed_at FROM schema_changes \
+         WHERE sequence > $1 ORDER BY sequence LIMIT $2",
+    )
+    .bind(after)
+    .bind(limit as i64 + 1)
+    .fetch_all(&state.db)
+    .await?;
+    let has_more = rows.len() > limit;
+    rows.truncate(limit);
+
+    let changes: Vec<SchemaChange> = rows
+        .into_iter()
+        .map(
+            |(sequence, schema_id, namespace, name, change, changed_at)| SchemaChange {
+                sequence,
+                schema_id,
+                subject: subject_of(&namespace, &name),
+                change,
+                changed_at,
+            },
+        )
+        .collect();
+    Ok(Json(ChangesResponse {
+        cursor: changes.last().map_or(after, |change| change.sequence),
+        changes,
+        has_more,
+    }))
+}
+
+// ============================================================================
+// Live Events
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+struct EventStreamQuery {
+    /// Only events of this namespace and the namespaces nested in it
+    #[serde(default)]
+    namespace: Option<String>,
+    /// Comma-separated event types, e.g. `SCHEMA_REGISTERED,SCHEMA_DELETED`
+    #[serde(default)]
+    types: Option<String>,
+}
+
+impl EventStreamQuery {
+    fn filter(&self) -> Result<EventFilter, AppError> {
+        let event_types: Vec<EventType> = self
+            .types
+            .as_deref()
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| {
+                serde_json::from_value(serde_json::json!(name))
+                    .map_err(|_| AppError::InvalidInput(format!("Unknown event type '{}'", name)))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(EventFilter {
+            namespace: self.namespace.clone(),
+            event_types,
+        })
+    }
+}
+
+/// The event announcing a change to one version of a subject
+fn schema_event(
+    event_type: EventType,
+    id: Uuid,
+    namespace: &str,
+    name: &str,
+    version: SemanticVersion,
+    actor: &str,
+    payload: EventPayload,
+) -> SchemaEvent {
+    SchemaEvent::new(event_type, id, version, actor.to_string(), payload)
+        .with_metadata(NAMESPACE_KEY.to_string(), serde_json::json!(namespace))
+        .with_metadata(
+            "subject".to_string(),
+            serde_json::json!(subject_of(namespace, name)),
+        )
+}
+
+/// Tell live event subscribers on every replica about a change, without
+/// holding up the request
+fn announce(state: &AppState, event: SchemaEvent) {
+    let publisher = state.event_publisher.clone();
+    tokio::spawn(async move {
+        let event_type = event.event_type;
+        if let Err(e) = publisher.publish(event).await {
+            tracing::warn!(?event_type, error = %e, "Failed to publish schema event");
+        }
+    });
+}
+
+/// One stream item as JSON: a `SchemaEvent`, or `{"lagged": n}` when the
+/// client fell behind and missed `n` events
+fn stream_message(item: &StreamItem) -> serde_json::Value {
+    match item {
+        StreamItem::Event(event) => serde_json::json!(event.as_ref()),
+        StreamItem::Lagged(missed) => serde_json::json!({ "lagged": missed }),
+    }
+}
+
+/// Schema events as they happen, as server-sent events
+///
+/// Each event is named after its type, e.g. `SCHEMA_REGISTERED`, and carries
+/// the core `SchemaEvent` as JSON; a `lagged` event says how many events a
+/// slow client missed.
+async fn stream_events(
+    State(state): State<AppState>,
+    Query(query): Query<EventStreamQuery>,
+) -> Result<Sse<impl Stream<Item = Result<SseEvent, Infallible>>>, AppError> {
+    let subscription = state.events.subscribe(query.filter()?);
+    let events = futures::stream::unfold(subscription, |mut subscription| async move {
+        let item = subscription.next().await?;
+        let message = stream_message(&item);
+        let event = match &item {
+            StreamItem::Event(event) => SseEvent::default()
+                .id(event.event_id.to_string())
+                .event(message["event_type"].as_str().unwrap_or_default()),
+            StreamItem::Lagged(_) => SseEvent::default().event("lagged"),
+        };
+        Some((Ok(event.data(message.to_string())), subscription))
+    });
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+/// Schema events as they happen, one JSON text message each
+async fn stream_events_ws(
+    State(state): State<AppState>,
+    Query(query): Query<EventStreamQuery>,
+    upgrade: WebSocketUpgrade,
+) -> Result<Response, AppError> {
+    let subscription = state.events.subscribe(query.filter()?);
+    Ok(upgrade.on_upgrade(move |socket| relay_events(socket, subscription)))
+}
+
+async fn relay_events(mut socket: WebSocket, mut subscription: EventSubscription) {
+    loop {
+        tokio::select! {
+            item = subscription.next() => {
+                let Some(item) = item else { break };
+                let text = stream_message(&item).to_string();
+                if socket.send(WsMessage::Text(text)).await.is_err() {
+                    break;
+                }
+            }
+            // Clients only listen; anything but a close is ignored
+            incoming = socket.recv() => match incoming {
+                Some(Ok(WsMessage::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
+
+// ============================================================================
+// Subject Config Files
+// ============================================================================
+
+/// Read a committed subject config file (YAML, or JSON)
+fn load_subject_config(path: &std::path::Path) -> anyhow::Result<SubjectConfigFile> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", path.display(), e))?;
+    let file: SubjectConfigFile = serde_yaml::from_str(&content)
+        .map_err(|e| anyhow::anyhow!("Invalid subject config file {}: {}", path.display(), e))?;
+    Ok(file.normalized()?)
+}
+
+/// Compare the registry with the committed file and update the drift gauge
+fn check_subject_config_drift(
+    namespaces: &NamespaceTree,
+    path: &std::path::Path,
+) -> anyhow::Result<Vec<SubjectConfigChange>> {
+    let committed = load_subject_config(path)?;
+    let changes = committed.diff(&SubjectConfigFile::export(namespaces));
+    SUBJECT_CONFIG_DRIFT.set(changes.len() as i64);
+    Ok(changes)
+}
+
+/// Re-check the committed file every `interval`, logging when drift appears
+///
+/// The file is re-read each time, so a deployment that updates it in place
+/// (a mounted ConfigMap, a `git pull`) is picked up without a restart.
+fn spawn_subject_config_drift_monitor(
+    namespaces: Arc<NamespaceTree>,
+    path: PathBuf,
+    interval: Duration,
+) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        let mut drifted: Vec<String> = Vec::new();
+        loop {
+            ticker.tick().await;
+            match check_subject_config_drift(&namespaces, &path) {
+                Ok(changes) => {
+                    let subjects: Vec<String> =
+                        changes.iter().map(|c| c.subject().to_string()).collect();
+                    if !subjects.is_empty() && subjects != drifted {
+                        tracing::warn!(
+                            file = %path.display(),
+                            subjects = ?subjects,
+                            "Subject settings have drifted from the committed config"
+                        );
+                    }
+                    drifted = subjects;
+                }
+                Err(e) => tracing::warn!(error = %e, "Subject config drift check failed"),
+            }
+        }
+    });
+}
+
+/// Export the explicit settings of every subject and namespace
+async fn export_subject_config(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<SubjectConfigFile>, AppError> {
+    require_admin(&state, &headers)?;
+    Ok(Json(SubjectConfigFile::export(&state.namespaces)))
+}
+
+/// Make the registry match a subject config file
+///
+/// Returns the changes made, or with `?dry_run=true` the changes that would
+/// be made. The settings it overwrites are snapshotted first and can be
+/// restored with the operation ID in `X-Operation-Id`.
+async fn apply_subject_config(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<ApplySubjectConfigQuery>,
+    BoundedJson(file): BoundedJson<SubjectConfigFile>,
+) -> Result<Response, AppError> {
+    let admin = require_admin(&state, &headers)?;
+
+    let desired = file
+        .normalized()
+        .map_err(|e| AppError::InvalidInput(e.to_string()))?;
+    let mut changes = desired.diff(&SubjectConfigFile::export(&state.namespaces));
+    if !query.prune {
+        changes.retain(|change| !matches!(change, SubjectConfigChange::Delete { .. }));
+    }
+
+    let mut operation_id = None;
+    let changes = if query.dry_run || changes.is_empty() {
+        changes
+    } else {
+        let actor = ChangeActor::from_request(&state, &headers, None, None)?;
+        for change in &changes {
+            ensure_unfrozen(&state, &actor, change.subject(), "apply_subject_config").await?;
+        }
+        let contents = SnapshotContents::of_subject_configs(&state.namespaces, &changes);
+        operation_id =
+            Some(snapshot_before(&state, "apply_subject_config", &admin, contents).await?);
+        let write = state.namespace_store.lock().await;
+        let applied = file
+            .apply(&state.namespaces, query.prune)
+            .map_err(|e| AppError::InvalidInput(e.to_string()))?;
+        write
+            .commit(applied.iter().map(|change| change.subject()), &admin)
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+        applied
+    };
+
+    if !query.dry_run && !changes.is_empty() {
+        let event = AuditEvent::new(
+            AuditEventType::ConfigurationChanged,
+            "Subject config file applied".to_string(),
+            AuditResult::Success,
+            String::new(),
+        )
+        .with_user(admin, None)
+        .with_metadata("prune".to_string(), serde_json::json!(query.prune))
+        .with_metadata("changes".to_string(), serde_json::json!(changes))
+        .with_metadata("operation_id".to_string(), serde_json::json!(operation_id));
+        state.audit_logger.log(event).await;
+    }
+
+    Ok(undoable(operation_id, Json(changes)))
+}
+
+/// Differences between the registry and the committed config file
+async fn subject_config_drift(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<SubjectConfigDriftReport>, AppError> {
+    require_admin(&state, &headers)?;
+    let path = state.subject_config_file.as_deref().ok_or_else(|| {
+        AppError::NotFound("No committed subject config file is configured".to_string())
+    })?;
+    let changes = check_subject_config_drift(&state.namespaces, path)
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    Ok(Json(SubjectConfigDriftReport {
+        file: path.display().to_string(),
+        in_sync: changes.is_empty(),
+        changes,
+    }))
+}
+
+// ============================================================================
+// Feature Usage
+// ============================================================================
+
+/// Header clients name their SDK and its version in, e.g. `python/1.4.0`
+const SDK_VERSION_HEADER: &str = "x-sdk-version";
+
+/// SDK version recorded for requests without the header
+const NO_SDK: &str = "none";
+
+/// SDKs whose release lines are counted under their own name
+const KNOWN_SDKS: [&str; 5] = ["go", "java", "python", "rust", "typescript"];
+
+/// Release line an `X-SDK-Version` header names, e.g. `python/1.4` for
+/// `python/1.4.2`
+///
+/// The header is client-supplied, so only known SDKs with a numeric version
+/// get a value of their own, one per major and minor version.
+fn sdk_release(header: &str) -> Option<String> {
+    let (name, version) = header.trim().split_once('/')?;
+    let sdk = KNOWN_SDKS
+        .into_iter()
+        .find(|known| known.eq_ignore_ascii_case(name))?;
+    let mut parts = version.split(['.', '-', '+']);
+    let major: u32 = parts.next()?.parse().ok()?;
+    let minor: u32 = parts.next()?.parse().ok()?;
+    Some(format!("{}/{}.{}", sdk, major, minor))
+}
+
+/// Default and maximum window of the feature usage report
+const DEFAULT_FEATURE_USAGE_DAYS: u32 = 30;
+const MAX_FEATURE_USAGE_DAYS: u32 = 365;
+
+/// Count a use of a feature in analytics and in Prometheus
+fn record_feature_use(state: &AppState, dimension: FeatureDimension, value: &str) {
+    let value = state.analytics.record_feature_use(dimension, value);
+    FEATURE_USAGE
+        .with_label_values(&[dimension.as_str(), &value])
+        .inc();
+}
+
+/// Version segment of an API path, e.g. `v1` for `/api/v1/schemas`
+fn api_version(path: &str) -> Option<&str> {
+    let mut segments = path.trim_start_matches('/').split('/');
+    match segments.next() {
+        Some("api") | Some("public") => segments.next().filter(|v| v.starts_with('v')),
+        _ => None,
+    }
+}
+
+/// Count the API version each request uses and the SDK it was sent by
+///
+/// Health checks and metrics scrapes aren't API requests and aren't counted.
+async fn feature_usage_context(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if let Some(version) = api_version(request.uri().path()) {
+        let sdk = match request.headers().get(SDK_VERSION_HEADER) {
+            Some(header) => header
+                .to_str()
+                .ok()
+                .and_then(sdk_release)
+                .unwrap_or_else(|| feature_usage::OTHER_VALUE.to_string()),
+            None => NO_SDK.to_string(),
+        };
+        record_feature_use(&state, FeatureDimension::ApiVersion, version);
+        record_feature_use(&state, FeatureDimension::SdkVersion, &sdk);
+    }
+    next.run(request).await
+}
+
+#[derive(Debug, Deserialize)]
+struct FeatureUsageQuery {
+    #[serde(default)]
+    days: Option<u32>,
+}
+
+/// Uses of each compatibility mode, format, API version and SDK version
+///
+/// Lists, per dimension, the values used in the last `days` days (30 by
+/// default) and those nobody used, to guide deprecations. Counts are kept
+/// in memory, so they start over when the server restarts. Admin only, as
+/// it shows who calls the registry with what.
+async fn feature_usage_report(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<FeatureUsageQuery>,
+) -> Result<Json<FeatureUsageReport>, AppError> {
+    require_admin(&state, &headers)?;
+    let days = query
+        .days
+        .unwrap_or(DEFAULT_FEATURE_USAGE_DAYS)
+        .clamp(1, MAX_FEATURE_USAGE_DAYS);
+    Ok(Json(state.analytics.get_feature_usage_report(days)))
+}
+
+// ============================================================================
+// Schema Validation Pipeline
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+struct ValidateSchemaRequest {
+    #[serde(default)]
+    schema: serde_json::Value,
+    /// Raw content, e.g. Protobuf source; takes precedence over `schema`
+    #[serde(default)]
+    content: Option<String>,
+    /// `JSON` (the default), `AVRO` or `PROTOBUF`
+    #[serde(default)]
+    format: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ValidateSchemaQuery {
+    /// Include per-step timings and sizes in the response
+    #[serde(default)]
+    debug: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct ValidateSchemaResponse {
+    is_valid: bool,
+    errors: Vec<RuleFinding>,
+    warnings: Vec<RuleWarning>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    quarantine_reasons: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    metrics: Option<PipelineMetrics>,
+}
+
+/// Where the pipeline spent its time, returned with `?debug=true`
+#[derive(Debug, Serialize)]
+struct PipelineMetrics {
+    duration_ms: f64,
+    schema_size_bytes: usize,
+    fields_validated: usize,
+    max_depth: usize,
+    rules_applied: usize,
+    steps: Vec<PipelineStepMetrics>,
+}
+
+#[derive(Debug, Serialize)]
+struct PipelineStepMetrics {
+    step: ValidationStep,
+    duration_ms: f64,
+    errors: usize,
+    warnings: usize,
+}
+
+/// Run schema content through the full validation pipeline without
+/// registering it
+///
+/// Every step's duration is observed in Prometheus; with `?debug=true` the
+/// response also breaks the run down by step, to show which one dominates
+/// for a large schema.
+async fn validate_schema(
+    State(state): State<AppState>,
+    Query(query): Query<ValidateSchemaQuery>,
+    BoundedJson(req): BoundedJson<ValidateSchemaRequest>,
+) -> Result<Json<ValidateSchemaResponse>, AppError> {
+    let content = req
+        .content
+        .unwrap_or_else(|| serde_json::to_string(&req.schema).unwrap_or_else(|_| "{}".to_string()));
+    let format = match req.format.as_deref().map(str::to_uppercase).as_deref() {
+        Some("AVRO") => SchemaFormat::Avro,
+        Some("PROTOBUF") => SchemaFormat::Protobuf,
+        _ => SchemaFormat::JsonSchema,
+    };
+
+    let result = state
+        .security_scanner
+        .validate(&content, format)
+        .await
+        .map_err(|e| AppError::Internal(format!("Validation failed to run: {}", e)))?;
+
+    VALIDATION_SCHEMA_SIZE
+        .with_label_values(&[format.as_str()])
+        .observe(content.len() as f64);
+    for timing in &result.metrics.steps {
+        VALIDATION_STEP_DURATION
+            .with_label_values(&[timing.step.as_str(), format.as_str()])
+            .observe(timing.duration.as_secs_f64());
+    }
+
+    let metrics = query.debug.then(|| PipelineMetrics {
+        duration_ms: result.metrics.duration.as_secs_f64() * 1000.0,
+        schema_size_bytes: content.len(),
+        fields_validated: result.metrics.fields_validated,
+        max_depth: result.metrics.max_recursion_depth,
+        rules_applied: result.metrics.rules_applied,
+        steps: result
+            .metrics
+            .steps
+            .iter()
+            .map(|timing| PipelineStepMetrics {
+                step: timing.step,
+                duration_ms: timing.duration.as_secs_f64() * 1000.0,
+                errors: timing.errors,
+                warnings: timing.warnings,
+            })
+            .collect(),
+    });
+    Ok(Json(ValidateSchemaResponse {
+        is_valid: result.is_valid,
+        errors: result.errors,
+        warnings: result.warnings,
+        quarantine_reasons: result.quarantine_reasons,
+        metrics,
+    }))
+}
+
+// ============================================================================
+// Format Detection
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+struct DetectFormatRequest {
+    #[serde(default)]
+    schema: serde_json::Value,
+    /// Raw content, e.g. Protobuf source; takes precedence over `schema`
+    #[serde(default)]
+    content: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct DetectFormatResponse {
+    /// What registering the content without a `schema_type` would use
+    schema_type: &'static str,
+    #[serde(flatten)]
+    detection: FormatDetection,
+}
+
+/// The `schema_type` registration takes for `format`
+fn schema_type_name(format: SchemaFormat) -> &'static str {
+    match format {
+        SchemaFormat::JsonSchema => "JSON",
+        SchemaFormat::Avro => "AVRO",
+        SchemaFormat::Protobuf => "PROTOBUF",
+    }
+}
+
+fn detect_schema_format(content: &str) -> Result<FormatDetection, AppError> {
+    format_detection::detect(content).map_err(|_| {
+        AppError::InvalidInput(
+            "Could not detect the schema format; set schema_type to JSON, AVRO or PROTOBUF"
+                .to_string(),
+        )
+    })
+}
+
+/// Detect whether content is JSON Schema, Avro, Protobuf or an OpenAPI
+/// component, as registration does when `schema_type` is omitted
+async fn detect_format(
+    BoundedJson(req): BoundedJson<DetectFormatRequest>,
+) -> Result<Json<DetectFormatResponse>, AppError> {
+    let content = req
+        .content
+        .unwrap_or_else(|| serde_json::to_string(&req.schema).unwrap_or_else(|_| "{}".to_string()));
+    let detection = detect_schema_format(&content)?;
+    Ok(Json(DetectFormatResponse {
+        schema_type: schema_type_name(detection.format.schema_format()),
+        detection,
+    }))
+}
+
+// ============================================================================
+// Operation Snapshots
+// ============================================================================
+
+/// Response header carrying the ID that undoes a destructive operation
+const OPERATION_ID_HEADER: &str = "x-operation-id";
+
+/// Tell the caller which operation ID undoes what the response did
+fn undoable(operation_id: Option<Uuid>, body: impl IntoResponse) -> Response {
+    let mut response = body.into_response();
+    if let Some(operation_id) = operation_id {
+        if let Ok(value) = HeaderValue::from_str(&operation_id.to_string()) {
+            response.headers_mut().insert(OPERATION_ID_HEADER, value);
+        }
+    }
+    response
+}
+
+/// Snapshot what `operation` is about to change, refusing it if the
+/// snapshot can't be stored
+async fn snapshot_before(
+    state: &AppState,
+    operation: &str,
+    actor: &str,
+    contents: SnapshotContents,
+) -> Result<Uuid, AppError> {
+    let operation_id = state
+        .snapshots
+        .take(operation, actor, contents)
+        .await
+        .map_err(|e| {
+            tracing::error!(operation, error = %e, "Failed to snapshot before a destructive operation");
+            AppError::Internal(format!(
+                "Could not snapshot the rows {} would change, so it was not run: {}",
+                operation, e
+            ))
+        })?;
+    tracing::info!(operation, %operation_id, store = state.snapshots.store_name(), "Snapshot taken");
+    Ok(operation_id)
+}
+
+#[derive(Debug, Serialize)]
+struct UndoResponse {
+    /// The operation that was undone
+    operation_id: Uuid,
+    operation: String,
+    restored: RestoreReport,
+}
+
+/// Put back the rows an operation changed, from its snapshot
+///
+/// Settings the restore overwrites are snapshotted in turn, so an undo can
+/// itself be undone with the ID in `X-Operation-Id`. A deleted schema is only
+/// restored while no schema with its ID exists.
+async fn undo_operation(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(operation_id): Path<Uuid>,
+) -> Result<Response, AppError> {
+    let admin = require_admin(&state, &headers)?;
+    let mut snapshot = state
+        .snapshots
+        .get(operation_id)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+        .ok_or_else(|| {
+            AppError::NotFound(format!(
+                "No snapshot of operation {}; it may have been pruned",
+                operation_id
+            ))
+        })?;
+    if snapshot.expires_at < Utc::now() {
+        return Err(AppError::Gone(format!(
+            "The snapshot of operation {} expired at {}",
+            operation_id, snapshot.expires_at
+        )));
+    }
+    if let Some(undone_at) = snapshot.undone_at {
+        return Err(AppError::Conflict(format!(
+            "Operation {} was already undone at {}",
+            operation_id, undone_at
+        )));
+    }
+
+    let actor = ChangeActor::from_request(&state, &headers, None, None)?;
+    match &snapshot.contents {
+        SnapshotContents::Schemas { schemas, .. } => {
+            let ids: Vec<Uuid> = schemas
+                .iter()
+                .filter_map(|row| row.get("id")?.as_str()?.parse().ok())
+                .collect();
+            let existing: Vec<(Uuid,)> =
+                sqlx::query_as("SELECT id FROM schemas WHERE id = ANY($1)")
+                    .bind(&ids)
+                    .fetch_all(&state.db)
+                    .await?;
+            if let Some((id,)) = existing.first() {
+                return Err(AppError::Conflict(format!(
+                    "Schema {} exists again; nothing was restored",
+                    id
+                )));
+            }
+            for namespace in schemas
+                .iter()
+                .filter_map(|row| row.get("namespace")?.as_str())
+            {
+                ensure_unfrozen(&state, &actor, namespace, "undo").await?;
+            }
+        }
+        SnapshotContents::SubjectConfigs { subjects } => {
+            for subject in subjects.keys() {
+                ensure_unfrozen(&state, &actor, subject, "undo").await?;
+            }
+        }
+    }
+
+    let undo_id = match snapshot.contents.overwritten_by_restore(&state.namespaces) {
+        Some(contents) => Some(snapshot_before(&state, "undo", &admin, contents).await?),
+        None => None,
+    };
+    let write = state.namespace_store.lock().await;
+    let restored = snapshot
+        .contents
+        .restore(&state.db, &state.namespaces)
+        .await
+        .map_err(|e| match e.downcast::<sqlx::Error>() {
+            Ok(e) => AppError::Database(e),
+            Err(e) => AppError::Internal(e.to_string()),
+        })?;
+    if let SnapshotContents::SubjectConfigs { subjects } = &snapshot.contents {
+        write
+            .commit(subjects.keys(), &admin)
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+    }
+    if let Err(e) = state.snapshots.mark_undone(&mut snapshot, &admin).await {
+        tracing::warn!(%operation_id, error = %e, "Failed to mark the snapshot as restored");
+    }
+
+    let event = AuditEvent::new(
+        AuditEventType::BackupRestored,
+        "Operation undone".to_string(),
+        AuditResult::Success,
+        String::new(),
+    )
+    .with_user(admin, None)
+    .with_resource("operation".to_string(), operation_id.to_string())
+    .with_metadata(
+        "operation".to_string(),
+        serde_json::json!(snapshot.operation),
+    )
+    .with_metadata("restored".to_string(), serde_json::json!(restored))
+    .with_metadata("undo_operation_id".to_string(), serde_json::json!(undo_id));
+    state.audit_logger.log(event).await;
+
+    tracing::info!(%operation_id, operation = %snapshot.operation, "Operation undone");
+    Ok(undoable(
+        undo_id,
+        Json(UndoResponse {
+            operation_id,
+            operation: snapshot.operation,
+            restored,
+        }),
+    ))
+}
+
+// ============================================================================
+// Request Deadlines
+// ============================================================================
+
+/// Counts the request as abandoned by the client unless it completes
+struct InFlight {
+    route: String,
+    completed: bool,
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        if !self.completed {
+            CANCELLED_REQUESTS
+                .with_label_values(&[&self.route, "client_disconnect"])
+                .inc();
+        }
+    }
+}
+
+/// The client's deadline from `grpc-timeout` or `X-Request-Timeout`,
+/// whichever is earlier
+fn request_deadline(headers: &HeaderMap) -> Option<Deadline> {
+    let header = |name: &str, parse: fn(&str) -> Option<Duration>| {
+        headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .and_then(parse)
+            .map(Deadline::after)
+    };
+    Deadline::earliest(
+        header(deadline::GRPC_TIMEOUT_HEADER, deadline::parse_grpc_timeout),
+        header(deadline::REQUEST_TIMEOUT_HEADER, deadline::parse_request_timeout),
+    )
+}
+
+/// Run the request within the client's deadline
+///
+/// Handlers see the deadline through `Deadline::current`. When it passes, or
+/// the client disconnects, the handler future is dropped and the abandoned
+/// work is counted.
+async fn deadline_context(request: Request, next: Next) -> Response {
+    let mut in_flight = InFlight {
+        route: request
+            .extensions()
+            .get::<MatchedPath>()
+            .map(|p| p.as_str().to_string())
+            .unwrap_or_else(|| "unmatched".to_string()),
+        completed: false,
+    };
+
+    let response = match request_deadline(request.headers()) {
+        Some(deadline) => match deadline.run(next.run(request)).await {
+            Ok(response) => response,
+            Err(_) => {
+                AppError::DeadlineExceeded("Request deadline exceeded".to_string()).into_response()
+            }
+        },
+        None => next.run(request).await,
+    };
+
+    in_flight.completed = true;
+    if response.status() == StatusCode::GATEWAY_TIMEOUT {
+        CANCELLED_REQUESTS
+            .with_label_values(&[&in_flight.route, "deadline_exceeded"])
+            .inc();
+    }
+    response
+}
+
+// ============================================================================
+// Schema Resource Handlers
+// ============================================================================
+
+/// Default and maximum time a watch request waits for changes
+const WATCH_TIMEOUT_SECS: u64 = 30;
+const MAX_WATCH_TIMEOUT_SECS: u64 = 300;
+
+/// Create or update a schema resource and reconcile it
+///
+/// Specs that are already reconciled and ready are not registered again, so
+/// operators can re-apply on every resync.
+async fn apply_schema_resource(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(name): Path<String>,
+    BoundedJson(req): BoundedJson<ApplySchemaResourceRequest>,
+) -> Result<(StatusCode, Json<SchemaResource>), AppError> {
+    let admin = require_admin(&state, &headers)?;
+    let actor = ChangeActor::from_request(&state, &headers, None, None)?;
+    let namespace = split_subject(&req.spec.subject).0;
+    ensure_unfrozen(&state, &actor, &namespace, "apply_schema_resource").await?;
+    let existed = state
+        .resources
+        .get(&name)
+        .await
+        .map_err(resource_store_error)?
+        .is_some();
+    let resource = state
+        .resources
+        .apply(&name, req.spec, req.metadata.labels)
+        .await
+        .map_err(resource_store_error)?;
+
+    let resource = if resource.is_ready() {
+        resource
+    } else {
+        let event = AuditEvent::new(
+            AuditEventType::ConfigurationChanged,
+            "Schema resource applied".to_string(),
+            AuditResult::Success,
+            String::new(),
+        )
+        .with_user(admin, None)
+        .with_resource("schema_resource".to_string(), name)
+        .with_metadata("subject".to_string(), serde_json::json!(resource.spec.subject))
+        .with_metadata(
+            "generation".to_string(),
+            serde_json::json!(resource.metadata.generation),
+        );
+        state.audit_logger.log(event).await;
+
+        reconcile_schema_resource(&state, &headers, resource).await?
+    };
+
+    let status = if existed { StatusCode::OK } else { StatusCode::CREATED };
+    Ok((status, Json(resource)))
+}
+
+/// Register the resource's spec and record the outcome in its status
+async fn reconcile_schema_resource(
+    state: &AppState,
+    headers: &HeaderMap,
+    resource: SchemaResource,
+) -> Result<SchemaResource, AppError> {
+    let name = resource.metadata.name.clone();
+    let generation = resource.metadata.generation;
+    let mut status = resource.status.clone();
+    status.observed_generation = generation;
+
+    let condition = match register_from_spec(state, headers, &resource.spec).await {
+        Ok(registered) => {
+            let mut message = format!(
+                "Registered {} version {}",
+                resource.spec.subject, registered.version
+            );
+            for warning in &registered.warnings {
+                message.push_str("; ");
+                message.push_str(warning);
+            }
+            status.schema_id = Some(registered.id);
+            status.registered_version = Some(registered.version);
+            Condition::new(
+                CONDITION_READY,
+                ConditionStatus::True,
+                "Registered",
+                message,
+                generation,
+            )
+        }
+        Err(e) => {
+            tracing::warn!(resource = %name, error = %e, "Schema resource reconciliation failed");
+            Condition::new(
+                CONDITION_READY,
+                ConditionStatus::False,
+                e.reason(),
+                e.to_string(),
+                generation,
+            )
+        }
+    };
+    status.set_condition(condition);
+
+    // A newer generation applied meanwhile is reconciled by its own request
+    let updated = match state
+        .resources
+        .update_status(&name, generation, status)
+        .await
+        .map_err(resource_store_error)?
+    {
+        Some(updated) => Some(updated),
+        None => state
+            .resources
+            .get(&name)
+            .await
+            .map_err(resource_store_error)?,
+    };
+    Ok(updated.unwrap_or(resource))
+}
+
+/// The response for a failed resource store operation
+fn resource_store_error(e: anyhow::Error) -> AppError {
+    if let Some(e) = e.downcast_ref::<WatchError>() {
+        AppError::Gone(e.to_string())
+    } else if let Some(e) = e.downcast_ref::<CoreError>() {
+        AppError::InvalidInput(e.to_string())
+    } else {
+        AppError::Internal(e.to_string())
+    }
+}
+
+/// Register a spec on behalf of the request that applied it
+async fn register_from_spec(
+    state: &AppState,
+    headers: &HeaderMap,
+    spec: &SchemaSpec,
+) -> Result<RegisterSchemaResponse, AppError> {
+    let version = spec
+        .version
+        .parse::<SemanticVersion>()
+        .map_err(|e| AppError::InvalidInput(e.to_string()))?;
+    let req = RegisterSchemaRequest {
+        subject: spec.subject.clone(),
+        schema: spec.schema.clone(),
+        schema_type: spec.schema_type.clone(),
+        namespace: None,
+        name: None,
+        version_major: Some(version.major as i32),
+        version_minor: Some(version.minor as i32),
+        version_patch: Some(version.patch as i32),
+        format: None,
+        content: None,
+        state: default_state(),
+        compatibility_mode: spec.compatibility_mode,
+        description: spec.description.clone(),
+        tags: spec.tags.clone(),
+        metadata: HashMap::new(),
+    };
+    let (_, _, Json(registered)) = register_schema(
+        State(state.clone()),
+        headers.clone(),
+        None,
+        None,
+        BoundedJson(req),
+    )
+    .await?;
+    Ok(registered)
+}
+
+async fn get_schema_resource(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<SchemaResource>, AppError> {
+    state
+        .resources
+        .get(&name)
+        .await
+        .map_err(resource_store_error)?
+        .map(Json)
+        .ok_or_else(|| AppError::NotFound(format!("Schema resource '{}' not found", name)))
+}
+
+/// List schema resources, or with `?watch=true` wait for changes
+///
+/// A watch returns the events after `resourceVersion` as soon as there are
+/// any, or an empty list after `timeoutSeconds`. `410 Gone` means the version
+/// is no longer retained and the client must list again.
+async fn list_schema_resources(
+    State(state): State<AppState>,
+    Query(query): Query<ResourceListQuery>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let (items, current) = state.resources.list().await.map_err(resource_store_error)?;
+    if !query.watch {
+        return Ok(Json(serde_json::json!({
+            "apiVersion": API_VERSION,
+            "kind": "SchemaList",
+            "metadata": { "resourceVersion": current },
+            "items": items,
+        })));
+    }
+
+    let since = query.resource_version.unwrap_or(current);
+    let timeout = query
+        .timeout_seconds
+        .unwrap_or(WATCH_TIMEOUT_SECS)
+        .min(MAX_WATCH_TIMEOUT_SECS);
+    let events = state
+        .resources
+        .watch(since, Duration::from_secs(timeout))
+        .await
+        .map_err(resource_store_error)?;
+    let resource_version = events
+        .last()
+        .map(|e| e.object.metadata.resource_version)
+        .unwrap_or(since);
+
+    Ok(Json(serde_json::json!({
+        "metadata": { "resourceVersion": resource_version },
+        "events": events,
+    })))
+}
+
+/// Stop managing a schema declaratively; registered versions are kept
+async fn delete_schema_resource(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(name): Path<String>,
+) -> Result<StatusCode, AppError> {
+    let admin = require_admin(&state, &headers)?;
+    let actor = ChangeActor::from_request(&state, &headers, None, None)?;
+    let existing = state
+        .resources
+        .get(&name)
+        .await
+        .map_err(resource_store_error)?
+        .ok_or_else(|| AppError::NotFound(format!("Schema resource '{}' not found", name)))?;
+    let namespace = split_subject(&existing.spec.subject).0;
+    ensure_unfrozen(&state, &actor, &namespace, "delete_schema_resource").await?;
+    let resource = state
+        .resources
+        .delete(&name)
+        .await
+        .map_err(resource_store_error)?
+        .ok_or_else(|| AppError::NotFound(format!("Schema resource '{}' not found", name)))?;
+
+    let event = AuditEvent::new(
+        AuditEventType::ConfigurationChanged,
+        "Schema resource deleted".to_string(),
+        AuditResult::Success,
+        String::new(),
+    )
+    .with_user(admin, None)
+    .with_resource("schema_resource".to_string(), name)
+    .with_metadata("subject".to_string(), serde_json::json!(resource.spec.subject));
+    state.audit_logger.log(event).await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ============================================================================
+// Server Setup
+// ============================================================================
+
+/// Database migrations of the server, applied by [`build_app`]
+pub static MIGRATOR: sqlx::migrate::Migrator = sqlx::migrate!("./migrations");
+
+/// Compiled-in registration plugins
+///
+/// Deployments add their interceptors here, in the order they should run.
+fn registration_interceptors() -> InterceptorChain {
+    InterceptorChain::new()
+}
+
+/// SIEM sinks configured through `AUDIT_SYSLOG_*` and `AUDIT_HTTP_*`
+fn audit_sinks_from_env() -> anyhow::Result<Vec<Arc<dyn AuditSink>>> {
+    let mut sinks: Vec<Arc<dyn AuditSink>> = Vec::new();
+
+    if let Ok(addr) = std::env::var("AUDIT_SYSLOG_ADDR") {
+        let transport = match std::env::var("AUDIT_SYSLOG_TRANSPORT").as_deref() {
+            Ok("tcp") => SyslogTransport::Tcp,
+            Ok("udp") | Err(_) => SyslogTransport::Udp,
+            Ok(other) => anyhow::bail!("unknown AUDIT_SYSLOG_TRANSPORT: {}", other),
+        };
+        sinks.push(Arc::new(SyslogSink::new(addr, transport)));
+    }
+
+    if let Ok(url) = std::env::var("AUDIT_HTTP_URL") {
+        let format = match std::env::var("AUDIT_HTTP_FORMAT").as_deref() {
+            Ok("json") | Err(_) => HttpBatchFormat::JsonArray,
+            Ok("splunk") => HttpBatchFormat::SplunkHec,
+            Ok("elastic") => HttpBatchFormat::ElasticBulk,
+            Ok(other) => anyhow::bail!("unknown AUDIT_HTTP_FORMAT: {}", other),
+        };
+        let mut sink = HttpBatchSink::new(url, format);
+        if let Ok(auth) = std::env::var("AUDIT_HTTP_AUTHORIZATION") {
+            sink = sink.with_header("Authorization", auth);
+        }
+        sinks.push(Arc::new(sink));
+    }
+
+    Ok(sinks)
+}
+
+/// Connect to Postgres, run migrations and build the API router
+///
+/// Redis is connected on first use, or in the background straight away if
+/// `config.warmup` names the cache; see [`startup`].
+pub async fn build_app(config: ServerConfig) -> anyhow::Result<Router> {
+    let mut phases = StartupPhases::new();
+    tracing::info!("Database URL: {}", config.database_url);
+    tracing::info!("Redis URL: {}", config.redis_url);
+    tracing::info!("Schema ID strategy: {:?}", config.id_strategy);
+    tracing::info!("Slow operation threshold: {:?}", config.slow_operation_threshold);
+
+    // Library crates record through the `metrics` facade; export them with ours
+    if !schema_registry_observability::recorder::install() {
+        tracing::debug!("A metrics recorder is already installed");
+    }
+
+    // Create PostgreSQL connection pool
+    tracing::info!("Connecting to PostgreSQL...");
+    let db = phases
+        .time(
+            "postgres",
+            PgPoolOptions::new()
+                .max_connections(50)
+                .acquire_timeout(Duration::from_secs(5))
+                .connect(&config.database_url),
+        )
+        .await?;
+
+    tracing::info!("PostgreSQL connection pool created");
+
+    // Run migrations
+    tracing::info!("Running database migrations...");
+    phases.time("migrations", MIGRATOR.run(&db)).await?;
+    tracing::info!("Migrations completed");
+
+    // Redis is only opened once something needs it
+    let redis = LazyRedis::new(redis::Client::open(config.redis_url)?);
+
+    // Create validation engine and compatibility checker
+    let scanner_config = ValidationConfig::default()
+        .with_max_size(config.max_schema_size)
+        .with_llm_settings(config.llm_validation.clone())
+        .with_regex_policy(RegexPolicy::from(&config.performance_thresholds))
+        .with_security_settings(&config.security_validation)
+        .map_err(|e| anyhow::anyhow!("Invalid security pattern: {}", e))?;
+    let (validator, compatibility_checker, mut security_scanner) =
+        phases.time_sync("engines", || {
+            let mut compatibility_checker = CompatibilityCheckerImpl::new()
+                .with_policy(config.compatibility_policy)
+                .with_min_severity_to_fail(config.min_severity_to_fail);
+            if config.cross_format_compatibility {
+                compatibility_checker = compatibility_checker.with_cross_format();
+            }
+            (
+                Arc::new(ValidationEngine::new()),
+                Arc::new(compatibility_checker),
+                SecurityScanner::with_config(scanner_config),
+            )
+        });
+    if let Some(dir) = &config.validation_rules_dir {
+        let rules = phases.time_sync("validation_rules", || {
+            WasmRuleLoader::new().and_then(|loader| loader.load_dir(dir))
+        })?;
+        for rule in rules {
+            security_scanner.add_rule(Arc::new(rule));
+        }
+        tracing::info!(
+            dir = %dir.display(),
+            rules = ?security_scanner.rule_names(),
+            "Loaded validation rules"
+        );
+    }
+    let security_scanner = Arc::new(security_scanner);
+    let id_generator = config.id_strategy.build()?;
+    let audit_logger = Arc::new(AuditLogger::new());
+    let exemptions = Arc::new(ExemptionRegistry::with_store(Arc::new(
+        PostgresExemptionStore { db: db.clone() },
+    )));
+    let security_sink: Arc<dyn SecurityNotificationSink> =
+        match config.security_notification_url.clone() {
+            Some(url) => Arc::new(WebhookNotificationSink::new(url)),
+            None => Arc::new(TracingNotificationSink),
+        };
+    let quarantine = Arc::new(QuarantineManager::new(audit_logger.clone(), security_sink));
+    if config.admin_token.is_none() {
+        tracing::warn!("ADMIN_API_TOKEN not set, admin endpoints are disabled");
+    }
+    let impersonation = match config.impersonation_signing_key {
+        Some(key) => {
+            anyhow::ensure!(key.len() >= 32, "Impersonation signing key must be at least 32 bytes");
+            let jwt = JwtManager::new_hs256(key.as_bytes(), Arc::new(TokenRevocationList::new()));
+            let impersonation_config = ImpersonationConfig {
+                enabled: true,
+                ..Default::default()
+            };
+            tracing::warn!("Admin impersonation is enabled");
+            Some(Arc::new(ImpersonationManager::new(
+                impersonation_config,
+                Arc::new(jwt),
+                audit_logger.clone(),
+            )))
+        }
+        None => None,
+    };
+    let token_exchange = match config.token_exchange_signing_key {
+        Some(key) => {
+            anyhow::ensure!(key.len() >= 32, "Token exchange signing key must be at least 32 bytes");
+            let jwt = JwtManager::new_hs256(key.as_bytes(), Arc::new(TokenRevocationList::new()));
+            let exchange_config = TokenExchangeConfig {
+                enabled: true,
+                max_ttl: config.token_exchange_max_ttl,
+                default_ttl: TokenExchangeConfig::default()
+                    .default_ttl
+                    .min(config.token_exchange_max_ttl),
+            };
+            tracing::info!(
+                max_ttl_secs = exchange_config.max_ttl.as_secs(),
+                "Scoped token exchange is enabled"
+            );
+            Some(Arc::new(TokenExchanger::new(
+                exchange_config,
+                Arc::new(jwt),
+                audit_logger.clone(),
+            )))
+        }
+        None => None,
+    };
+    let directory = match (config.oidc_public_key, config.group_role_mapping_file) {
+        (Some(key), Some(mapping_file)) => {
+            let issuer = config.oidc_issuer.as_deref().ok_or_else(|| {
+                anyhow::anyhow!("An OIDC issuer is required for directory sign-in")
+            })?;
+            let verifier = OidcTokenVerifier::rs256(key.as_bytes(), issuer, &config.oidc_audience)?;
+            let mapper = Arc::new(
+                GroupRoleMapper::new(Arc::new(JsonFileMappingSource::new(mapping_file)))
+                    .with_audit_logger(audit_logger.clone()),
+            );
+            mapper.refresh().await?;
+            mapper
+                .clone()
+                .spawn_refresh(config.group_role_mapping_refresh);
+            tracing::info!(issuer, "Directory sign-in is enabled");
+            Some(Arc::new(DirectorySignIn {
+                verifier,
+                provider: OidcGroupProvider::new(mapper)
+                    .with_groups_claim(config.oidc_groups_claim),
+            }))
+        }
+        (None, None) => None,
+        _ => anyhow::bail!("The OIDC public key and group role mapping file must be set together"),
+    };
+    let spiffe = match config.spiffe_config_file {
+        Some(path) => {
+            let spiffe_config: SpiffeConfig =
+                serde_json::from_str(&tokio::fs::read_to_string(&path).await?)?;
+            let provider = SpiffeAuthProvider::from_config(spiffe_config)?
+                .with_audit_logger(audit_logger.clone());
+            tracing::info!(
+                path = %path.display(),
+                "Workload authentication with JWT-SVIDs is enabled"
+            );
+            Some(Arc::new(provider))
+        }
+        None => None,
+    };
+    let provenance_signer = match config.provenance_signing_key {
+        Some(key) => {
+            let signer = DocumentSigner::new_hs256(config.provenance_key_id, key.as_bytes())?;
+            tracing::info!(key_id = signer.key_id(), "Provenance exports are signed");
+            Some(Arc::new(signer))
+        }
+        None => None,
+    };
+    if !config.audit_sinks.is_empty() {
+        let audit_log = Arc::new(PostgresAuditLog::new(db.clone()));
+        Arc::new(AuditStreamer::new(audit_logger.clone(), audit_log.clone())).spawn();
+        for sink in config.audit_sinks {
+            tracing::info!("Streaming audit events to {}", sink.name());
+            Arc::new(AuditStreamer::new(audit_log.clone(), sink).with_ledger(audit_log.clone()))
+                .spawn();
+        }
+    }
+
+    // Stored settings are loaded first so the committed file only adds to them
+    let namespaces = Arc::new(NamespaceTree::default());
+    let namespace_store = Arc::new(NamespaceSettingsStore::new(db.clone(), namespaces.clone()));
+    let stored = phases
+        .time("namespace_settings", namespace_store.load())
+        .await?;
+    tracing::info!(namespaces = stored, "Loaded namespace settings");
+    if let Some(path) = &config.subject_config_file {
+        let write = namespace_store.lock().await;
+        let applied = phases.time_sync("subject_config", || {
+            load_subject_config(path)?.apply(&namespaces, false)
+        })?;
+        write
+            .commit(
+                applied.iter().map(|change| change.subject()),
+                "subject_config_file",
+            )
+            .await?;
+        tracing::info!(file = %path.display(), subjects = applied.len(), "Applied subject config file");
+        spawn_subject_config_drift_monitor(
+            namespaces.clone(),
+            path.clone(),
+            config.subject_config_drift_interval,
+        );
+    }
+
+    let public_api = Arc::new(PublicApi::new(
+        config.public_api_tokens,
+        config.public_trusted_proxies,
+        config.public_rate_limit,
+        config.public_cache_ttl,
+    )?);
+    tracing::info!(
+        keys = public_api.tokens.len(),
+        rate_limit = config.public_rate_limit,
+        "Public API serves namespaces marked public"
+    );
+    spawn_public_rate_limit_cleanup(public_api.clone());
+
+    let freeze_sink: Arc<dyn FreezeNotificationSink> = match config.freeze_notification_url {
+        Some(url) => Arc::new(WebhookFreezeNotificationSink::new(url)),
+        None => Arc::new(TracingFreezeNotificationSink),
+    };
+    let freezes = Arc::new(
+        ChangeFreezeManager::new(config.environment.clone(), audit_logger.clone(), freeze_sink)
+            .with_store(Arc::new(PostgresFreezeWindowStore { db: db.clone() })),
+    );
+    if let Some(path) = &config.change_freeze_file {
+        let freeze_config = load_change_freeze_config(path)?;
+        let windows = freeze_config.windows.len();
+        phases.time("change_freezes", freezes.load(freeze_config)).await?;
+        tracing::info!(file = %path.display(), windows, "Loaded change freeze windows");
+    }
+
+    let analytics = Arc::new(AnalyticsEngine::new());
+    phases.time("analytics", analytics.start()).await?;
+    analytics.set_known_feature_values(
+        FeatureDimension::CompatibilityMode,
+        CompatibilityMode::ALL.iter().map(|m| m.to_string()),
+    );
+    analytics.set_known_feature_values(
+        FeatureDimension::Format,
+        SerializationFormat::ALL.iter().map(|f| f.to_string()),
+    );
+    analytics.set_known_feature_values(FeatureDimension::ApiVersion, ["v1"]);
+    analytics.set_known_feature_values(FeatureDimension::SdkVersion, [NO_SDK]);
+    let compat_history = Arc::new(
+        CompatibilityHistory::new(Arc::new(PostgresCompatibilityHistory { db: db.clone() }))
+            .with_observer(analytics.clone()),
+    );
+
+    let probes: Vec<Arc<dyn HealthProbe>> = vec![
+        Arc::new(DatabaseProbe { db: db.clone() }),
+        Arc::new(RedisProbe {
+            redis: redis.clone(),
+        }),
+    ];
+    let watchdog = Arc::new(DependencyWatchdog::new(
+        config.watchdog,
+        probes,
+        audit_logger.clone(),
+    ));
+    spawn_cache_flush_on_recovery(&watchdog, redis.clone());
+    watchdog.spawn();
+
+    let consumer_notifier = Arc::new(ConsumerNotifier::new(db.clone()));
+    let changes = Arc::new(ChangeBus::new());
+    changes.clone().spawn_listener(db.clone());
+    let events = Arc::new(EventStream::new());
+    events.clone().spawn_listener(db.clone());
+    namespace_store.clone().spawn_listener();
+    spawn_idempotency_key_cleanup(db.clone());
+    let event_publisher: Arc<dyn EventPublisher> = Arc::new(PgEventPublisher::new(db.clone()));
+    let reverification = Arc::new(
+        ReverificationJob::new(
+            Arc::new(PostgresSubjectVersions { db: db.clone() }),
+            compatibility_checker.clone(),
+            Arc::new(PostgresManifestStore { db: db.clone() }),
+        )
+        .with_publisher(event_publisher.clone())
+        .with_sink(Arc::new(PostgresBreakageReports { db: db.clone() })),
+    );
+    spawn_reverification(
+        reverification.clone(),
+        db.clone(),
+        config.reverification_interval,
+    );
+
+    let snapshots = Arc::new(
+        phases
+            .time("snapshots", Snapshots::from_config(&config.snapshots))
+            .await,
+    );
+    if config.snapshots.s3_bucket.is_none() {
+        tracing::warn!("SNAPSHOT_S3_BUCKET not set, operation snapshots are lost on restart");
+    }
+    tracing::info!(
+        store = snapshots.store_name(),
+        retention_days = snapshots.retention().as_secs() / 86_400,
+        "Destructive admin operations are snapshotted"
+    );
+    snapshots.clone().spawn_pruning();
+
+    let ownership_transfers = Arc::new(OwnershipTransfers::new(db.clone()));
+    let expiry_audit = audit_logger.clone();
+    ownership_transfers.clone().spawn_expiry(move |transfer| {
+        let audit_logger = expiry_audit.clone();
+        tokio::spawn(async move {
+            let event = transfer_event("Ownership transfer expired", &transfer, "system");
+            audit_logger.log(event).await;
+        });
+    });
+
+    // Create application state
+    let state = AppState {
+        db,
+        redis,
+        validator,
+        compatibility_checker,
+        id_generator,
+        security_scanner,
+        quarantine,
+        json_limits: JsonLimits::default(),
+        max_request_size: config.max_request_size,
+        max_schema_size: config.max_schema_size,
+        audit_logger,
+        exemptions,
+        compat_history,
+        namespaces,
+        namespace_store,
+        subject_config_file: config.subject_config_file.map(Arc::new),
+        environment: config.environment,
+        resources: Arc::new(PostgresResourceStore::new(db.clone())),
+        interceptors: Arc::new(registration_interceptors()),
+        complexity_thresholds: ComplexityGrowthThresholds::default(),
+        slow_operation_threshold: config.slow_operation_threshold,
+        query_limits: config.query_limits,
+        retry_budget: Arc::new(RetryBudget::default()),
+        admin_token: config.admin_token,
+        impersonation,
+        token_exchange,
+        directory,
+        spiffe,
+        public_api,
+        freezes,
+        trace_sampling: config.trace_sampling,
+        analytics,
+        consumer_notifier,
+        reverification,
+        changes,
+        events,
+        event_publisher,
+        graphql: graphql_schema(),
+        watchdog,
+        cache_ttl: Arc::new(AdaptiveTtl::new(config.cache_ttl)),
+        cache_metrics: Arc::new(CacheMetrics::new()),
+        snapshots,
+        ownership_transfers,
+        provenance_signer,
+        messages: Arc::new(config.message_catalogs),
+    };
+    spawn_warmup(&state, &config.warmup);
+
+    // Read-only routes for partners, limited to public namespaces
+    let public_router = Router::new()
+        .route("/public/v1/schemas/search", post(public_search_schemas))
+        .route("/public/v1/schemas/:id", get(public_get_schema))
+        .route(
+            "/public/v1/subjects/:subject/versions",
+            get(public_list_subject_versions),
+        )
+        .route_layer(axum_middleware::from_fn_with_state(
+            state.clone(),
+            public_api_guard,
+        ));
+
+    // Confluent Schema Registry paths, for Kafka serializers and tooling
+    let confluent_router = Router::new()
+        .route("/subjects", get(confluent_list_subjects))
+        .route("/subjects/:subject", post(confluent_lookup))
+        .route(
+            "/subjects/:subject/versions",
+            get(confluent_list_versions).post(confluent_register),
+        )
+        .route(
+            "/subjects/:subject/versions/:version",
+            get(confluent_get_version),
+        )
+        .route(
+            "/subjects/:subject/versions/:version/schema",
+            get(confluent_get_version_schema),
+        )
+        .route("/schemas/ids/:id", get(confluent_get_schema_by_id))
+        .route(
+            "/config",
+            get(confluent_get_global_config).put(confluent_put_global_config),
+        )
+        .route(
+            "/config/:subject",
+            get(confluent_get_config)
+                .put(confluent_put_config)
+                .delete(confluent_delete_config),
+        )
+        .route(
+            "/compatibility/subjects/:subject/versions/:version",
+            post(confluent_check_compatibility),
+        );
+
+    // Build API router
+    let api_router = Router::new()
+        .route(
+            "/api/v1/schemas",
+            post(register_schema).route_layer(axum_middleware::from_fn_with_state(
+                state.clone(),
+                idempotent_registration,
+            )),
+        )
+        .route("/api/v1/schemas/search", post(search_schemas))
+        .route("/api/v1/schemas/validate", post(validate_schema))
+        .route("/api/v1/schemas/detect-format", post(detect_format))
+        .route("/api/v1/schemas/:id", get(get_schema).delete(delete_schema))
+        .route(
+            "/api/v1/schemas/ids/:global_id",
+            get(get_schema_by_global_id),
+        )
+        .route("/api/v1/schemas/:id/usage", get(get_schema_usage))
+        // The namespace takes the `:id` slot, as the router allows one
+        // parameter name per position
+        .route(
+            "/api/v1/schemas/:id/:name/versions",
+            get(list_schema_versions),
+        )
+        .route(
+            "/api/v1/schemas/:id/:name/versions/latest",
+            get(get_latest_schema_version),
+        )
+        .route("/api/v1/schemas/:id/metadata", patch(update_schema_metadata))
+        .route("/api/v1/schemas/:id/archive", post(archive_schema))
+        .route("/api/v1/schemas/:id/state", post(change_schema_state))
+        .route(
+            "/api/v1/schemas/:id/quarantine/release",
+            post(release_quarantined_schema),
+        )
+        .route("/api/v1/schemas/:id/codegen", get(schema_codegen))
+        .route(
+            "/api/v1/schemas/:id/versions/:version/provenance",
+            get(get_schema_provenance),
+        )
+        .route("/api/v1/validate/:id", post(validate_data))
+        .route("/api/v1/validate/:id/batch", post(validate_data_batch))
+        .route("/api/v1/compatibility/check", post(check_compatibility))
+        .route("/api/v1/compatibility/dry-run", post(dry_run_compatibility))
+        .route("/api/v1/compatibility/history", get(compatibility_history))
+        .route("/api/v1/changes", get(list_changes))
+        .route("/api/v1/events/stream", get(stream_events))
+        .route("/api/v1/events/ws", get(stream_events_ws))
+        .route("/api/v1/graphql", get(graphql_query).post(graphql_query))
+        .route("/api/v1/graphql/schema", get(graphql_sdl))
+        .route("/api/v1/operations/slow", get(slow_operations))
+        .route("/api/v1/analytics/features", get(feature_usage_report))
+        .route("/api/v1/analytics/complexity", get(complexity_report))
+        .route("/api/v1/analytics/top", get(top_schemas))
+        .route(
+            "/api/v1/analytics/subjects/:subject/health",
+            get(subject_health),
+        )
+        .route("/api/v1/analytics/anomalies", get(usage_anomalies))
+        .route(
+            "/api/v1/admin/compatibility/exemptions",
+            post(grant_exemption).get(list_exemptions),
+        )
+        .route(
+            "/api/v1/admin/compatibility/exemptions/:id",
+            delete(revoke_exemption),
+        )
+        .route("/api/v1/subjects/:subject", delete(delete_subject))
+        .route("/api/v1/subjects/:subject/versions", get(list_subject_versions))
+        .route("/api/v1/subjects/:subject/changelog", get(get_subject_changelog))
+        .route(
+            "/api/v1/subjects/:subject/versions/:version",
+            get(get_subject_version).delete(delete_subject_version),
+        )
+        .route(
+            "/api/v1/subjects/:subject/compatibility-matrix",
+            get(compatibility_matrix),
+        )
+        .route(
+            "/api/v1/subjects/:subject/consumers",
+            get(list_consumer_manifests),
+        )
+        .route(
+            "/api/v1/subjects/:subject/consumers/:consumer",
+            put(put_consumer_manifest).delete(delete_consumer_manifest),
+        )
+        .route("/api/v1/consumers", get(list_consumer_pins))
+        .route(
+            "/api/v1/consumers/:consumer_id",
+            get(get_consumer_pins)
+                .put(put_consumer_pins)
+                .delete(delete_consumer_pins),
+        )
+        .route(
+            "/api/v1/namespaces/:namespace/breakage-report",
+            get(get_breakage_report),
+        )
+        .route(
+            "/api/v1/admin/reverification/run",
+            post(run_reverification_now),
+        )
+        .route("/api/v1/lineage/edges", post(create_lineage_edge))
+        .route("/api/v1/admin/capacity/history", get(capacity_history))
+        .route("/api/v1/admin/gc", post(run_gc))
+        .route(
+            "/api/v1/admin/operations/:operation_id/undo",
+            post(undo_operation),
+        )
+        .route("/api/v1/admin/impersonate", post(impersonate))
+        .route(
+            "/api/v1/admin/audit/:event_id/replay",
+            post(replay_audit_event),
+        )
+        .route("/api/v1/auth/token", post(exchange_token))
+        .route(
+            "/api/v1/admin/subjects/:subject/rename",
+            post(rename_subject),
+        )
+        .route("/api/v1/admin/freezes", get(list_freeze_windows))
+        .route(
+            "/api/v1/admin/freezes/:name",
+            put(put_freeze_window).delete(delete_freeze_window),
+        )
+        .route(
+            "/api/v1/admin/tracing/sampling",
+            get(get_trace_sampling).put(put_trace_sampling),
+        )
+        .route(
+            "/api/v1/namespaces/:namespace/settings",
+            get(get_namespace_settings)
+                .put(put_namespace_settings)
+                .delete(delete_namespace_settings),
+        )
+        .route(
+            "/api/v1/namespaces/:namespace/effective-settings",
+            get(get_effective_namespace_settings),
+        )
+        .route(
+            "/api/v1/namespaces/:namespace/ownership-transfers",
+            post(request_ownership_transfer),
+        )
+        .route("/api/v1/ownership-transfers", get(list_ownership_transfers))
+        .route(
+            "/api/v1/ownership-transfers/:id",
+            get(get_ownership_transfer),
+        )
+        .route(
+            "/api/v1/ownership-transfers/:id/accept",
+            post(accept_ownership_transfer),
+        )
+        .route(
+            "/api/v1/ownership-transfers/:id/reject",
+            post(reject_ownership_transfer),
+        )
+        .route(
+            "/api/v1/ownership-transfers/:id/cancel",
+            post(cancel_ownership_transfer),
+        )
+        .route("/api/v1/config/subjects", get(export_subject_config))
+        .route("/api/v1/config/subjects/drift", get(subject_config_drift))
+        .route("/api/v1/admin/config/subjects", post(apply_subject_config))
+        .route("/api/v1/resources/schemas", get(list_schema_resources))
+        .route(
+            "/api/v1/resources/schemas/:name",
+            get(get_schema_resource)
+                .put(apply_schema_resource)
+                .delete(delete_schema_resource),
+        )
+        .route("/api/v1/capabilities", get(capabilities))
+        .route("/api/v1/messages", get(list_message_catalogs))
+        .route("/health", get(health_check))
+        .merge(public_router)
+        .merge(confluent_router)
+        .layer(axum_middleware::from_fn_with_state(
+            state.clone(),
+            feature_usage_context,
+        ))
+        .layer(axum_middleware::from_fn_with_state(
+            state.clone(),
+            impersonation_context,
+        ))
+        .layer(axum_middleware::from_fn_with_state(
+            state.clone(),
+            scoped_token_context,
+        ))
+        .layer(axum_middleware::from_fn(deadline_context))
+        .layer(axum_middleware::from_fn_with_state(
+            state.clone(),
+            enforce_request_size,
+        ))
+        .layer(DefaultBodyLimit::max(config.max_request_size))
+        .with_state(state)
+        .layer(TraceLayer::new_for_http());
+
+    phases.finish();
+
+    Ok(api_router)
+}
+
+/// Most schemas compiled ahead of use by the `compiled_schemas` warmup
+const WARMUP_SCHEMA_LIMIT: i64 = 500;
+
+/// Start the subsystems named by `hints` in the background
+fn spawn_warmup(state: &AppState, hints: &WarmupHints) {
+    if hints.includes(Subsystem::Cache) {
+        state.redis.connect_in_background();
+    }
+    if hints.includes(Subsystem::CompiledSchemas) {
+        let state = state.clone();
+        tokio::spawn(async move {
+            let started = std::time::Instant::now();
+            match warm_compiled_schemas(&state).await {
+                Ok(compiled) => tracing::info!(
+                    compiled,
+                    elapsed_ms = started.elapsed().as_millis() as u64,
+                    "Compiled schemas ahead of first use"
+                ),
+                Err(e) => tracing::warn!(error = %e, "Failed to warm the compiled-schema cache"),
+            }
+        });
+    }
+}
+
+/// Compile the most recently registered active schemas, returning how many
+/// compiled
+async fn warm_compiled_schemas(state: &AppState) -> Result<usize, AppError> {
+    let rows: Vec<(Uuid, String, String)> = sqlx::query_as(
+        "SELECT id, format, content FROM schemas WHERE state = 'ACTIVE' ORDER BY created_at DESC LIMIT $1",
+    )
+    .bind(WARMUP_SCHEMA_LIMIT)
+    .fetch_all(&state.db)
+    .await?;
+    let mut compiled = 0;
+    for (id, format, content) in rows {
+        let format = match format.as_str() {
+            "AVRO" => SerializationFormat::Avro,
+            "PROTOBUF" => SerializationFormat::Protobuf,
+            _ => SerializationFormat::JsonSchema,
+        };
+        // A schema that can't compile fails again, visibly, when used
+        if state
+            .validator
+            .precompile_instance(id, format, &content)
+            .await
+            .is_ok()
+        {
+            compiled += 1;
+        }
+    }
+    Ok(compiled)
+}
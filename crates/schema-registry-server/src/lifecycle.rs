@@ -0,0 +1,975 @@
+//! Schema lifecycle
+//!
+//! Usage reports ahead of retirement, archiving and deleting versions and
+//! subjects, lifecycle state changes and metadata updates.
+
+use super::*;
+use schema_registry_analytics::{SchemaUsageEvent, UsageHeatReport};
+use schema_registry_core::metadata_patch::{Documentation, MetadataPatch};
+
+use crate::provenance::{SCHEMA_EVENT_ARCHIVED, SCHEMA_EVENT_DELETED, SCHEMA_EVENT_METADATA_UPDATED};
+
+/// Days of usage reviewed before a schema is archived or deleted
+const RETIREMENT_USAGE_DAYS: i64 = 30;
+
+/// Record a read or validation of a schema, so retiring it shows who still
+/// uses it
+pub(crate) fn record_usage(
+    state: &AppState,
+    schema_id: Uuid,
+    operation: UsageOperation,
+    headers: &HeaderMap,
+    elapsed: Duration,
+) {
+    let client = headers
+        .get("x-client-id")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("unknown");
+    state.analytics.try_record_event(SchemaUsageEvent::new(
+        schema_id,
+        operation,
+        client.to_string(),
+        String::new(),
+        elapsed.as_millis() as u64,
+        true,
+    ));
+}
+
+/// Who used a schema recently, and what is known to read it
+#[derive(Debug, Serialize)]
+pub(crate) struct SchemaUsageReport {
+    pub(crate) schema_id: Uuid,
+    pub(crate) usage: UsageHeatReport,
+    pub(crate) consumers: Vec<Consumer>,
+}
+
+async fn usage_report(state: &AppState, schema_id: Uuid) -> Result<SchemaUsageReport, AppError> {
+    let usage = state
+        .analytics
+        .get_usage_heat(&schema_id.into(), RETIREMENT_USAGE_DAYS)
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    let consumers = lineage_consumers(state, schema_id).await?;
+    Ok(SchemaUsageReport {
+        schema_id,
+        usage,
+        consumers,
+    })
+}
+
+pub(crate) async fn get_schema_usage(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<SchemaUsageReport>, AppError> {
+    let exists: Option<(Uuid,)> = sqlx::query_as("SELECT id FROM schemas WHERE id = $1")
+        .bind(id)
+        .fetch_optional(&state.db)
+        .await?;
+    if exists.is_none() {
+        return Err(AppError::NotFound(format!("Schema {} not found", id)));
+    }
+    Ok(Json(usage_report(&state, id).await?))
+}
+
+#[derive(Debug, Deserialize)]
+struct RetirementQuery {
+    /// Retire the schema even though it was used recently
+    #[serde(default)]
+    force: bool,
+    /// Why a schema in use is retired anyway, recorded in the audit log
+    reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Retirement {
+    Archive,
+    Delete,
+}
+
+impl Retirement {
+    fn operation(self) -> &'static str {
+        match self {
+            Retirement::Archive => "archive_schema",
+            Retirement::Delete => "delete_schema",
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct RetirementResponse {
+    subject: String,
+    state: String,
+    /// Whether the schema was retired although it was in use
+    forced: bool,
+    #[serde(flatten)]
+    report: SchemaUsageReport,
+}
+
+/// Archive a schema, unless it was used in the last 30 days
+pub(crate) async fn archive_schema(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+    Query(query): Query<RetirementQuery>,
+) -> Result<Response, AppError> {
+    retire_schema(&state, &headers, id, query, Retirement::Archive).await
+}
+
+/// Delete a schema, unless it was used in the last 30 days
+pub(crate) async fn delete_schema(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+    Query(query): Query<RetirementQuery>,
+) -> Result<Response, AppError> {
+    retire_schema(&state, &headers, id, query, Retirement::Delete).await
+}
+
+/// Archive or delete a schema
+///
+/// A schema used within [`RETIREMENT_USAGE_DAYS`] is only retired with
+/// `force` and a reason; the refusal carries the usage so the caller can see
+/// who would break. Deletes are snapshotted first and can be undone.
+async fn retire_schema(
+    state: &AppState,
+    headers: &HeaderMap,
+    id: Uuid,
+    query: RetirementQuery,
+    retirement: Retirement,
+) -> Result<Response, AppError> {
+    let admin = require_admin(state, headers)?;
+    let reason = query
+        .reason
+        .map(|r| r.trim().to_string())
+        .filter(|r| !r.is_empty());
+    if query.force && reason.is_none() {
+        return Err(AppError::InvalidInput(
+            "A reason is required to force a schema's retirement".to_string(),
+        ));
+    }
+
+    let row: Option<(String, String, String, i32, i32, i32)> = sqlx::query_as(
+        "SELECT namespace, name, state, version_major, version_minor, version_patch FROM schemas WHERE id = $1",
+    )
+    .bind(id)
+    .fetch_optional(&state.db)
+    .await?;
+    let (namespace, name, current, major, minor, patch) =
+        row.ok_or_else(|| AppError::NotFound(format!("Schema {} not found", id)))?;
+    let archived = SchemaState::Archived.to_string();
+    if retirement == Retirement::Archive && current == archived {
+        return Err(AppError::Conflict(format!(
+            "Schema {} is already archived",
+            id
+        )));
+    }
+    let actor = ChangeActor::from_request(state, headers, None, None)?;
+    ensure_unfrozen(state, &actor, &namespace, retirement.operation()).await?;
+
+    let report = usage_report(state, id).await?;
+    let forced = report.usage.in_use();
+    if forced && !query.force {
+        return Err(AppError::InUse(Box::new(report)));
+    }
+
+    let mut operation_id = None;
+    let (new_state, event_type, message) = match retirement {
+        Retirement::Archive => {
+            let mut tx = state.db.begin().await?;
+            sqlx::query("UPDATE schemas SET state = $1 WHERE id = $2")
+                .bind(&archived)
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+            let details = serde_json::json!({
+                "previous_state": current,
+                "forced": forced,
+                "reason": reason,
+            });
+            record_schema_event(&mut *tx, id, SCHEMA_EVENT_ARCHIVED, &details, &admin).await?;
+            tx.commit().await?;
+            (archived, AuditEventType::SchemaUpdated, "Schema archived")
+        }
+        Retirement::Delete => {
+            let contents = SnapshotContents::of_schema(&state.db, id).await?;
+            operation_id = Some(snapshot_before(state, "delete_schema", &admin, contents).await?);
+            sqlx::query("DELETE FROM schemas WHERE id = $1")
+                .bind(id)
+                .execute(&state.db)
+                .await?;
+            (
+                "DELETED".to_string(),
+                AuditEventType::SchemaDeleted,
+                "Schema deleted",
+            )
+        }
+    };
+
+    let evicted: Result<(), redis::RedisError> = match state.cache() {
+        Some(conn) => {
+            state
+                .retry_policy("cache_evict")
+                .retry(|| {
+                    let mut conn = conn.clone();
+                    async move {
+                        redis::cmd("DEL")
+                            .arg(format!("{}{}", SCHEMA_CACHE_PREFIX, id))
+                            .query_async(&mut conn)
+                            .await
+                    }
+                })
+                .await
+        }
+        None => Ok(()),
+    };
+    if let Err(e) = evicted {
+        tracing::warn!(schema_id = %id, error = %e, "Failed to evict retired schema from the cache");
+    }
+
+    let retired = match retirement {
+        Retirement::Archive => EventType::SchemaArchived,
+        Retirement::Delete => EventType::SchemaDeleted,
+    };
+    let details = HashMap::from([
+        ("previous_state".to_string(), serde_json::json!(current)),
+        ("forced".to_string(), serde_json::json!(forced)),
+    ]);
+    announce(
+        state,
+        schema_event(
+            retired,
+            id,
+            &namespace,
+            &name,
+            SemanticVersion::new(major as u32, minor as u32, patch as u32),
+            &admin,
+            EventPayload::Generic { data: details },
+        ),
+    );
+
+    let subject = subject_of(&namespace, &name);
+    let mut event = AuditEvent::new(
+        event_type,
+        message.to_string(),
+        AuditResult::Success,
+        String::new(),
+    )
+    .with_user(admin, None)
+    .with_resource("schema".to_string(), id.to_string())
+    .with_metadata("subject".to_string(), serde_json::json!(subject))
+    .with_metadata(
+        "operations_last_30_days".to_string(),
+        serde_json::json!(report.usage.total_operations),
+    )
+    .with_metadata("forced".to_string(), serde_json::json!(forced));
+    if let Some(operation_id) = operation_id {
+        event = event.with_metadata("operation_id".to_string(), serde_json::json!(operation_id));
+    }
+    if forced {
+        event = event
+            .with_metadata("reason".to_string(), serde_json::json!(reason))
+            .with_metadata(
+                "clients".to_string(),
+                serde_json::json!(report.usage.clients),
+            );
+    }
+    state.audit_logger.log(event).await;
+
+    tracing::info!(schema_id = %id, subject = %subject, state = %new_state, forced, "Schema retired");
+
+    Ok(undoable(
+        operation_id,
+        Json(RetirementResponse {
+            subject,
+            state: new_state,
+            forced,
+            report,
+        }),
+    ))
+}
+
+/// State of a soft-deleted version, whose row is kept as a tombstone
+pub(crate) const DELETED_STATE: &str = "DELETED";
+
+#[derive(Debug, Deserialize)]
+struct DeletionQuery {
+    /// Remove the rows rather than leave tombstones
+    #[serde(default)]
+    permanent: bool,
+    /// Delete the versions even though one was used recently
+    #[serde(default)]
+    force: bool,
+    /// Why the versions were deleted, kept with the tombstone and audited;
+    /// required with `force`
+    reason: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct DeletedVersion {
+    id: Uuid,
+    version: String,
+}
+
+#[derive(Debug, Serialize)]
+struct DeletionResponse {
+    subject: String,
+    /// Whether the rows were removed rather than tombstoned
+    permanent: bool,
+    /// Whether the versions were deleted although one was in use
+    forced: bool,
+    deleted: Vec<DeletedVersion>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    warnings: Vec<String>,
+}
+
+/// Delete one version of a subject
+pub(crate) async fn delete_subject_version(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((subject, version)): Path<(String, String)>,
+    Query(query): Query<DeletionQuery>,
+) -> Result<Response, AppError> {
+    let version = version
+        .parse::<SemanticVersion>()
+        .map_err(|e| AppError::InvalidInput(e.to_string()))?;
+    delete_versions(&state, &headers, &subject, Some(version), query).await
+}
+
+/// Delete every version of a subject
+pub(crate) async fn delete_subject(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(subject): Path<String>,
+    Query(query): Query<DeletionQuery>,
+) -> Result<Response, AppError> {
+    delete_versions(&state, &headers, &subject, None, query).await
+}
+
+/// Tombstone one version of a subject, or all of them
+///
+/// Tombstoned rows stay, so their version numbers are never reused: the
+/// state becomes `DELETED`, `metadata.deletion` records who deleted them,
+/// when and why, and reads answer `410 Gone`. With `permanent` the rows are
+/// removed instead, tombstoned or not, and snapshotted first so it can be
+/// undone. Either way it needs an admin token, and as with
+/// [`retire_schema`], versions used within [`RETIREMENT_USAGE_DAYS`] are
+/// only deleted with `force` and a reason.
+async fn delete_versions(
+    state: &AppState,
+    headers: &HeaderMap,
+    subject: &str,
+    version: Option<SemanticVersion>,
+    query: DeletionQuery,
+) -> Result<Response, AppError> {
+    let principal = require_admin(state, headers)?;
+    let reason = query
+        .reason
+        .map(|r| r.trim().to_string())
+        .filter(|r| !r.is_empty());
+    if query.force && reason.is_none() {
+        return Err(AppError::InvalidInput(
+            "A reason is required to force a deletion".to_string(),
+        ));
+    }
+    let (namespace, name) = split_subject(subject);
+    let (namespace, name, alias_warning) = resolve_subject(state, namespace, name).await?;
+    let subject = subject_of(&namespace, &name);
+    let actor = ChangeActor::from_request(state, headers, None, None)?;
+    let operation = match version {
+        Some(_) => "delete_version",
+        None => "delete_subject",
+    };
+    ensure_unfrozen(state, &actor, &namespace, operation).await?;
+
+    let rows: Vec<(Uuid, i32, i32, i32, String)> = sqlx::query_as(
+        r#"
+        SELECT id, version_major, version_minor, version_patch, state
+        FROM schemas
+        WHERE namespace = $1 AND name = $2
+          AND ($3::INT IS NULL
+               OR (version_major = $3 AND version_minor = $4 AND version_patch = $5))
+        ORDER BY version_major, version_minor, version_patch
+        "#,
+    )
+    .bind(&namespace)
+    .bind(&name)
+    .bind(version.as_ref().map(|v| v.major as i32))
+    .bind(version.as_ref().map(|v| v.minor as i32))
+    .bind(version.as_ref().map(|v| v.patch as i32))
+    .fetch_all(&state.db)
+    .await?;
+    if rows.is_empty() {
+        return Err(AppError::NotFound(match &version {
+            Some(version) => format!("Version {} of {} not found", version, subject),
+            None => format!("Subject {} not found", subject),
+        }));
+    }
+    let targets: Vec<_> = rows
+        .into_iter()
+        .filter(|(.., current)| query.permanent || current != DELETED_STATE)
+        .collect();
+    if targets.is_empty() {
+        return Err(AppError::Conflict(match &version {
+            Some(version) => format!("Version {} of {} is already deleted", version, subject),
+            None => format!("Every version of {} is already deleted", subject),
+        }));
+    }
+    let ids: Vec<Uuid> = targets.iter().map(|(id, ..)| *id).collect();
+
+    let mut forced = false;
+    for id in &ids {
+        let report = usage_report(state, *id).await?;
+        if report.usage.in_use() {
+            if !query.force {
+                return Err(AppError::InUse(Box::new(report)));
+            }
+            forced = true;
+        }
+    }
+
+    let mut operation_id = None;
+    if query.permanent {
+        let contents = SnapshotContents::of_schemas(&state.db, &ids).await?;
+        operation_id = Some(snapshot_before(state, operation, &principal, contents).await?);
+        sqlx::query("DELETE FROM schemas WHERE id = ANY($1)")
+            .bind(&ids)
+            .execute(&state.db)
+            .await?;
+    } else {
+        let deletion = serde_json::json!({
+            "deleted_by": principal,
+            "deleted_at": Utc::now(),
+            "reason": reason,
+            "forced": forced,
+        });
+        let mut tx = state.db.begin().await?;
+        // The previous state is read from each row before the update sets it
+        sqlx::query(
+            r#"
+            UPDATE schemas
+            SET state = $1,
+                metadata = jsonb_set(
+                    COALESCE(metadata, '{}'::jsonb),
+                    '{deletion}',
+                    $2::jsonb || jsonb_build_object('previous_state', state)
+                )
+            WHERE id = ANY($3)
+            "#,
+        )
+        .bind(DELETED_STATE)
+        .bind(sqlx::types::Json(&deletion))
+        .bind(&ids)
+        .execute(&mut *tx)
+        .await?;
+        for (id, .., previous_state) in &targets {
+            let details = serde_json::json!({
+                "previous_state": previous_state,
+                "forced": forced,
+                "reason": reason,
+            });
+            record_schema_event(&mut *tx, *id, SCHEMA_EVENT_DELETED, &details, &principal).await?;
+        }
+        tx.commit().await?;
+    }
+
+    let mut del = redis::cmd("DEL");
+    for id in &ids {
+        del.arg(format!("{}{}", SCHEMA_CACHE_PREFIX, id));
+        state.validator.invalidate_instance(*id).await;
+        if query.permanent {
+            state.quarantine.forget(&id.to_string()).await;
+        }
+    }
+    let evicted: Result<(), redis::RedisError> = match state.cache() {
+        Some(conn) => {
+            state
+                .retry_policy("cache_evict")
+                .retry(|| {
+                    let mut conn = conn.clone();
+                    let del = &del;
+                    async move { del.query_async(&mut conn).await }
+                })
+                .await
+        }
+        None => Ok(()),
+    };
+    if let Err(e) = evicted {
+        tracing::warn!(subject = %subject, error = %e, "Failed to evict deleted schemas from the cache");
+    }
+
+    for (id, major, minor, patch, previous_state) in &targets {
+        let details = HashMap::from([
+            (
+                "previous_state".to_string(),
+                serde_json::json!(previous_state),
+            ),
+            ("permanent".to_string(), serde_json::json!(query.permanent)),
+            ("forced".to_string(), serde_json::json!(forced)),
+            ("reason".to_string(), serde_json::json!(reason)),
+        ]);
+        announce(
+            state,
+            schema_event(
+                EventType::SchemaDeleted,
+                *id,
+                &namespace,
+                &name,
+                SemanticVersion::new(*major as u32, *minor as u32, *patch as u32),
+                &principal,
+                EventPayload::Generic { data: details },
+            ),
+        );
+    }
+
+    let deleted: Vec<DeletedVersion> = targets
+        .into_iter()
+        .map(|(id, major, minor, patch, _)| DeletedVersion {
+            id,
+            version: format!("{}.{}.{}", major, minor, patch),
+        })
+        .collect();
+    let message = if query.permanent {
+        "Schema versions permanently deleted"
+    } else {
+        "Schema versions deleted"
+    };
+    let mut event = AuditEvent::new(
+        AuditEventType::SchemaDeleted,
+        message.to_string(),
+        AuditResult::Success,
+        String::new(),
+    )
+    .with_user(principal, None)
+    .with_resource("subject".to_string(), subject.clone())
+    .with_metadata(
+        "versions".to_string(),
+        serde_json::json!(deleted.iter().map(|d| &d.version).collect::<Vec<_>>()),
+    )
+    .with_metadata("permanent".to_string(), serde_json::json!(query.permanent))
+    .with_metadata("forced".to_string(), serde_json::json!(forced))
+    .with_metadata("reason".to_string(), serde_json::json!(reason));
+    if let Some(operation_id) = operation_id {
+        event = event.with_metadata("operation_id".to_string(), serde_json::json!(operation_id));
+    }
+    state.audit_logger.log(event).await;
+
+    tracing::info!(
+        subject = %subject,
+        versions = deleted.len(),
+        permanent = query.permanent,
+        forced,
+        "Schema versions deleted"
+    );
+
+    Ok(undoable(
+        operation_id,
+        Json(DeletionResponse {
+            subject,
+            permanent: query.permanent,
+            forced,
+            deleted,
+            warnings: alias_warning.into_iter().collect(),
+        }),
+    ))
+}
+
+/// States a version can be moved to through the state endpoint
+///
+/// The validation and compatibility states are passed through during
+/// registration and quarantine is left through security review, so neither
+/// is set by hand.
+const SETTABLE_STATES: [SchemaState; 3] = [
+    SchemaState::Active,
+    SchemaState::Deprecated,
+    SchemaState::Archived,
+];
+
+/// Where a stored version is in the lifecycle
+///
+/// Registration validates and checks compatibility before it stores a
+/// version, so a stored `DRAFT` has passed both: it is what the lifecycle
+/// calls `Registered`, ready to be activated.
+pub(crate) fn lifecycle_state(stored: &str) -> Option<SchemaState> {
+    if stored == SchemaState::Draft.to_string() {
+        return Some(SchemaState::Registered);
+    }
+    serde_json::from_value(serde_json::json!(stored)).ok()
+}
+
+/// States a version in `current` can be moved to through the state endpoint
+pub(crate) fn settable_next_states(current: SchemaState) -> Vec<SchemaState> {
+    current
+        .next_states()
+        .into_iter()
+        .filter(|next| SETTABLE_STATES.contains(next))
+        .collect()
+}
+
+#[derive(Debug, Deserialize)]
+struct StateChangeRequest {
+    state: SchemaState,
+    /// Why the state changed, kept with the transition and audited
+    reason: Option<String>,
+    /// Archive the schema even though it was used recently
+    #[serde(default)]
+    force: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct StateChangeResponse {
+    pub(crate) id: Uuid,
+    pub(crate) subject: String,
+    pub(crate) previous_state: String,
+    pub(crate) state: String,
+    /// States the version can be moved to from here
+    pub(crate) next_states: Vec<SchemaState>,
+}
+
+/// A state change the lifecycle doesn't allow
+#[derive(Debug, Serialize)]
+pub(crate) struct InvalidTransition {
+    pub(crate) from: String,
+    pub(crate) to: SchemaState,
+    /// States the version can be moved to instead
+    pub(crate) allowed: Vec<SchemaState>,
+}
+
+/// Whether moving a version to `state` retires it, as the archive endpoint does
+fn retires(state: SchemaState) -> bool {
+    matches!(state, SchemaState::Deprecated | SchemaState::Archived)
+}
+
+/// Move a version along its lifecycle, e.g. `DRAFT` to `ACTIVE`
+///
+/// The move is checked against [`SchemaLifecycle`]; one it doesn't allow is
+/// refused with `409` and the states the version can move to. As with the
+/// archive endpoint, deprecating or archiving needs the admin token, and
+/// doing so to a schema used within [`RETIREMENT_USAGE_DAYS`] needs `force`
+/// and a reason.
+pub(crate) async fn change_schema_state(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    impersonation: Option<Extension<Impersonation>>,
+    scoped: Option<Extension<ScopedToken>>,
+    Path(id): Path<Uuid>,
+    BoundedJson(req): BoundedJson<StateChangeRequest>,
+) -> Result<Json<StateChangeResponse>, AppError> {
+    let admin = if retires(req.state) {
+        Some(require_admin(&state, &headers)?)
+    } else {
+        None
+    };
+    let actor = ChangeActor::from_request(
+        &state,
+        &headers,
+        impersonation.as_ref().map(|Extension(i)| i),
+        scoped.as_ref().map(|Extension(s)| s),
+    )?;
+    let principal = admin.unwrap_or_else(|| actor.principal.clone());
+    let reason = req
+        .reason
+        .map(|r| r.trim().to_string())
+        .filter(|r| !r.is_empty());
+    if req.force && reason.is_none() {
+        return Err(AppError::InvalidInput(
+            "A reason is required to force a schema's retirement".to_string(),
+        ));
+    }
+
+    let mut tx = state.db.begin().await?;
+    let row: Option<(String, String, String, i32, i32, i32)> = sqlx::query_as(
+        "SELECT namespace, name, state, version_major, version_minor, version_patch FROM schemas WHERE id = $1 FOR UPDATE",
+    )
+    .bind(id)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let (namespace, name, stored, major, minor, patch) =
+        row.ok_or_else(|| AppError::NotFound(format!("Schema {} not found", id)))?;
+    ensure_servable(id, &stored)?;
+    ensure_unfrozen(&state, &actor, &namespace, "change_state").await?;
+    let current = lifecycle_state(&stored).ok_or_else(|| {
+        AppError::Internal(format!("Schema {} has unknown state '{}'", id, stored))
+    })?;
+
+    let subject = subject_of(&namespace, &name);
+    let target = req.state.to_string();
+    if stored == target {
+        return Ok(Json(StateChangeResponse {
+            id,
+            subject,
+            previous_state: stored,
+            state: target,
+            next_states: settable_next_states(current),
+        }));
+    }
+    let mut lifecycle = SchemaLifecycle::new(id);
+    lifecycle.current_state = current;
+    let moved = SETTABLE_STATES.contains(&req.state)
+        && lifecycle
+            .transition(req.state, "state_endpoint".to_string(), principal.clone())
+            .is_ok();
+    if !moved {
+        return Err(AppError::InvalidTransition(Box::new(InvalidTransition {
+            from: stored,
+            to: req.state,
+            allowed: settable_next_states(current),
+        })));
+    }
+    let mut transition = lifecycle
+        .state_history
+        .pop()
+        .expect("a successful transition is recorded");
+    if let Some(reason) = &reason {
+        transition = transition.with_reason(reason.clone());
+    }
+
+    let mut forced = false;
+    if retires(req.state) {
+        let report = usage_report(&state, id).await?;
+        if report.usage.in_use() {
+            if !req.force {
+                return Err(AppError::InUse(Box::new(report)));
+            }
+            forced = true;
+            transition = transition.with_metadata(
+                "forced_with_operations".to_string(),
+                serde_json::json!(report.usage.total_operations),
+            );
+        }
+    }
+
+    sqlx::query("UPDATE schemas SET state = $1 WHERE id = $2")
+        .bind(&target)
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+    record_schema_event(
+        &mut *tx,
+        id,
+        SCHEMA_EVENT_STATE_CHANGED,
+        &transition,
+        &principal,
+    )
+    .await?;
+    tx.commit().await?;
+
+    // The cached copy carries the old state
+    let evicted: Result<(), redis::RedisError> = match state.cache() {
+        Some(conn) => {
+            state
+                .retry_policy("cache_evict")
+                .retry(|| {
+                    let mut conn = conn.clone();
+                    async move {
+                        redis::cmd("DEL")
+                            .arg(format!("{}{}", SCHEMA_CACHE_PREFIX, id))
+                            .query_async(&mut conn)
+                            .await
+                    }
+                })
+                .await
+        }
+        None => Ok(()),
+    };
+    if let Err(e) = evicted {
+        tracing::warn!(schema_id = %id, error = %e, "Failed to evict schema from the cache after a state change");
+    }
+
+    let changed = match req.state {
+        SchemaState::Active => EventType::SchemaActivated,
+        SchemaState::Deprecated => EventType::SchemaDeprecated,
+        _ => EventType::SchemaArchived,
+    };
+    let details = HashMap::from([
+        ("previous_state".to_string(), serde_json::json!(stored)),
+        ("reason".to_string(), serde_json::json!(reason)),
+        ("forced".to_string(), serde_json::json!(forced)),
+    ]);
+    announce(
+        &state,
+        schema_event(
+            changed,
+            id,
+            &namespace,
+            &name,
+            SemanticVersion::new(major as u32, minor as u32, patch as u32),
+            &principal,
+            EventPayload::Generic { data: details },
+        ),
+    );
+
+    let mut event = AuditEvent::new(
+        AuditEventType::SchemaUpdated,
+        "Schema state changed".to_string(),
+        AuditResult::Success,
+        String::new(),
+    )
+    .with_user(principal, None)
+    .with_resource("schema".to_string(), id.to_string())
+    .with_metadata("subject".to_string(), serde_json::json!(subject))
+    .with_metadata("from".to_string(), serde_json::json!(stored))
+    .with_metadata("to".to_string(), serde_json::json!(target))
+    .with_metadata("reason".to_string(), serde_json::json!(reason))
+    .with_metadata("forced".to_string(), serde_json::json!(forced));
+    if let Some(Extension(impersonation)) = &impersonation {
+        event = impersonation.attribute(event);
+    }
+    state.audit_logger.log(event).await;
+
+    tracing::info!(schema_id = %id, subject = %subject, from = %stored, to = %target, "Schema state changed");
+
+    Ok(Json(StateChangeResponse {
+        id,
+        subject,
+        previous_state: stored,
+        state: target,
+        next_states: settable_next_states(req.state),
+    }))
+}
+
+#[derive(Debug, Serialize)]
+struct MetadataUpdateResponse {
+    id: Uuid,
+    subject: String,
+    /// Unchanged by metadata updates
+    version: String,
+    /// Whether the patch changed anything
+    updated: bool,
+    #[serde(flatten)]
+    documentation: Documentation,
+}
+
+/// Update a version's description, tags and custom metadata in place
+///
+/// The content, its hash and the version stay as they are, so documentation
+/// fixes don't register a new version or trigger compatibility checks.
+pub(crate) async fn update_schema_metadata(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    impersonation: Option<Extension<Impersonation>>,
+    scoped: Option<Extension<ScopedToken>>,
+    Path(id): Path<Uuid>,
+    BoundedJson(patch): BoundedJson<MetadataPatch>,
+) -> Result<Json<MetadataUpdateResponse>, AppError> {
+    if patch.is_empty() {
+        return Err(AppError::InvalidInput(
+            "Name at least one of description, tags or metadata to update".to_string(),
+        ));
+    }
+    let actor = ChangeActor::from_request(
+        &state,
+        &headers,
+        impersonation.as_ref().map(|Extension(i)| i),
+        scoped.as_ref().map(|Extension(s)| s),
+    )?;
+    actor.ensure_authenticated()?;
+
+    let mut tx = state.db.begin().await?;
+    let row: Option<(
+        String,
+        String,
+        i32,
+        i32,
+        i32,
+        Option<String>,
+        Option<Vec<String>>,
+        Option<sqlx::types::Json<HashMap<String, serde_json::Value>>>,
+    )> = sqlx::query_as(
+        "SELECT namespace, name, version_major, version_minor, version_patch, description, tags, metadata FROM schemas WHERE id = $1 FOR UPDATE",
+    )
+    .bind(id)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let (namespace, name, major, minor, patch_version, description, tags, metadata) =
+        row.ok_or_else(|| AppError::NotFound(format!("Schema {} not found", id)))?;
+    ensure_unfrozen(&state, &actor, &namespace, "update_schema_metadata").await?;
+    let mut documentation = Documentation {
+        description,
+        tags: tags.unwrap_or_default(),
+        metadata: metadata.map(|m| m.0).unwrap_or_default(),
+    };
+    let previous = documentation.clone();
+    let updated = patch
+        .apply(&mut documentation)
+        .map_err(|e| AppError::InvalidInput(e.to_string()))?;
+
+    let subject = subject_of(&namespace, &name);
+    let version = format!("{}.{}.{}", major, minor, patch_version);
+    if !updated {
+        return Ok(Json(MetadataUpdateResponse {
+            id,
+            subject,
+            version,
+            updated,
+            documentation,
+        }));
+    }
+
+    sqlx::query("UPDATE schemas SET description = $1, tags = $2, metadata = $3 WHERE id = $4")
+        .bind(documentation.description.as_deref())
+        .bind(&documentation.tags)
+        .bind(sqlx::types::Json(&documentation.metadata))
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+    let details = serde_json::json!({
+        "previous": previous,
+        "patch": patch,
+    });
+    record_schema_event(
+        &mut *tx,
+        id,
+        SCHEMA_EVENT_METADATA_UPDATED,
+        &details,
+        &actor.principal,
+    )
+    .await?;
+    tx.commit().await?;
+
+    // The cached copy carries the old metadata
+    let evicted: Result<(), redis::RedisError> = match state.cache() {
+        Some(conn) => {
+            state
+                .retry_policy("cache_evict")
+                .retry(|| {
+                    let mut conn = conn.clone();
+                    async move {
+                        redis::cmd("DEL")
+                            .arg(format!("{}{}", SCHEMA_CACHE_PREFIX, id))
+                            .query_async(&mut conn)
+                            .await
+                    }
+                })
+                .await
+        }
+        None => Ok(()),
+    };
+    if let Err(e) = evicted {
+        tracing::warn!(schema_id = %id, error = %e, "Failed to evict updated schema from the cache");
+    }
+
+    let mut event = AuditEvent::new(
+        AuditEventType::SchemaUpdated,
+        "Schema metadata updated".to_string(),
+        AuditResult::Success,
+        String::new(),
+    )
+    .with_user(actor.principal.clone(), None)
+    .with_resource("schema".to_string(), id.to_string())
+    .with_metadata("subject".to_string(), serde_json::json!(subject))
+    .with_metadata("version".to_string(), serde_json::json!(version))
+    .with_metadata("patch".to_string(), serde_json::json!(patch));
+    if let Some(Extension(impersonation)) = &impersonation {
+        event = impersonation.attribute(event);
+    }
+    state.audit_logger.log(event).await;
+
+    tracing::info!(schema_id = %id, subject = %subject, "Schema metadata updated");
+
+    Ok(Json(MetadataUpdateResponse {
+        id,
+        subject,
+        version,
+        updated,
+        documentation,
+    }))
+}
@@ -0,0 +1,330 @@
+//! Prometheus metrics
+//!
+//! The metrics the server exports beyond the shared observability ones,
+//! and the endpoint serving them.
+
+use super::*;
+use axum::extract::ConnectInfo;
+use axum_server::tls_rustls::RustlsConfig;
+use base64::{prelude::BASE64_STANDARD, Engine};
+use prometheus::{Encoder, HistogramVec, IntCounterVec, IntGauge, TextEncoder};
+use schema_registry_observability::LabelScrubber;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::LazyLock;
+
+pub(crate) static REGISTRATION_CONFLICTS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    prometheus::register_int_counter_vec!(
+        "schema_registry_registration_conflicts_total",
+        "Registrations that lost an insert race, by whether the winner had the same content",
+        &["outcome"]
+    )
+    .expect("registration conflict metric registers once")
+});
+
+/// Bindings generated, by language and whether they came from the cache
+pub(crate) static CODEGEN_REQUESTS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    prometheus::register_int_counter_vec!(
+        "schema_registry_codegen_requests_total",
+        "Typed bindings served by language and cache result",
+        &["lang", "cache"]
+    )
+    .expect("codegen requests metric registers once")
+});
+
+/// Duration of compatibility checks and validations
+///
+/// Buckets run from 1ms to 30s so the series renders as a latency heatmap.
+pub(crate) static OPERATION_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    prometheus::register_histogram_vec!(
+        "schema_registry_operation_duration_seconds",
+        "Duration of compatibility checks and validations",
+        &["operation"],
+        vec![0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0]
+    )
+    .expect("operation duration metric registers once")
+});
+
+/// Changes that ran into a change freeze, by outcome
+pub(crate) static CHANGE_FREEZE_DECISIONS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    prometheus::register_int_counter_vec!(
+        "schema_registry_change_freeze_decisions_total",
+        "Changes rejected or overridden while a change freeze was in force",
+        &["outcome"]
+    )
+    .expect("change freeze decisions metric registers once")
+});
+
+/// Requests to the public API by outcome
+pub(crate) static PUBLIC_API_REQUESTS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    prometheus::register_int_counter_vec!(
+        "schema_registry_public_api_requests_total",
+        "Requests to the public read-only API by outcome",
+        &["outcome"]
+    )
+    .expect("public API requests metric registers once")
+});
+
+/// Subjects whose registry settings differ from the committed config file
+pub(crate) static SUBJECT_CONFIG_DRIFT: LazyLock<IntGauge> = LazyLock::new(|| {
+    prometheus::register_int_gauge!(
+        "schema_registry_subject_config_drift",
+        "Subjects whose settings differ from the committed subject config file"
+    )
+    .expect("subject config drift metric registers once")
+});
+
+/// Uses of compatibility modes, formats, and API and SDK versions
+pub(crate) static FEATURE_USAGE: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    prometheus::register_int_counter_vec!(
+        "schema_registry_feature_usage_total",
+        "Uses of compatibility modes, formats, and API and SDK versions",
+        &["dimension", "value"]
+    )
+    .expect("feature usage metric registers once")
+});
+
+/// Duration of each step of the schema validation pipeline
+pub(crate) static VALIDATION_STEP_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    prometheus::register_histogram_vec!(
+        "schema_registry_validation_step_duration_seconds",
+        "Duration of each step of the schema validation pipeline",
+        &["step", "format"],
+        vec![0.0001, 0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5]
+    )
+    .expect("validation step duration metric registers once")
+});
+
+/// Size of schemas run through the validation pipeline
+pub(crate) static VALIDATION_SCHEMA_SIZE: LazyLock<HistogramVec> = LazyLock::new(|| {
+    prometheus::register_histogram_vec!(
+        "schema_registry_validation_schema_size_bytes",
+        "Size of schemas run through the validation pipeline",
+        &["format"],
+        vec![256.0, 1024.0, 4096.0, 16384.0, 65536.0, 262144.0, 1048576.0, 4194304.0]
+    )
+    .expect("validation schema size metric registers once")
+});
+
+/// Requests abandoned before completion, by route and reason
+pub(crate) static CANCELLED_REQUESTS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    prometheus::register_int_counter_vec!(
+        "schema_registry_requests_cancelled_total",
+        "Requests abandoned before completion by route and reason",
+        &["route", "reason"]
+    )
+    .expect("cancelled requests metric registers once")
+});
+
+/// Credentials a scraper must present on the metrics port
+#[derive(Debug, Clone)]
+enum MetricsAuth {
+    Bearer(String),
+    /// Base64 of `user:password`, as sent in an `Authorization: Basic` header
+    Basic(String),
+}
+
+/// Who may scrape the metrics port, and what they see
+#[derive(Debug, Clone, Default)]
+pub struct MetricsAccess {
+    auth: Option<MetricsAuth>,
+    /// Client networks allowed to connect; empty allows every address
+    allowed_networks: Vec<IpNet>,
+    scrubber: Option<LabelScrubber>,
+}
+
+impl MetricsAccess {
+    /// Access rules from the `METRICS_*` environment variables
+    pub fn from_env() -> anyhow::Result<Self> {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+
+        let auth = match (var("METRICS_BEARER_TOKEN"), var("METRICS_BASIC_AUTH")) {
+            (Some(_), Some(_)) => anyhow::bail!(
+                "Set only one of METRICS_BEARER_TOKEN and METRICS_BASIC_AUTH"
+            ),
+            (Some(token), None) => Some(MetricsAuth::Bearer(token)),
+            (None, Some(credentials)) => {
+                if !credentials.contains(':') {
+                    anyhow::bail!("METRICS_BASIC_AUTH must be of the form user:password");
+                }
+                Some(MetricsAuth::Basic(BASE64_STANDARD.encode(credentials)))
+            }
+            (None, None) => None,
+        };
+
+        let allowed_networks = match var("METRICS_ALLOWED_NETWORKS") {
+            Some(list) => list
+                .split(',')
+                .map(str::trim)
+                .filter(|entry| !entry.is_empty())
+                .map(|entry| {
+                    entry
+                        .parse::<IpNet>()
+                        .or_else(|_| entry.parse::<IpAddr>().map(IpNet::from))
+                        .map_err(|_| anyhow::anyhow!("Invalid network in METRICS_ALLOWED_NETWORKS: {}", entry))
+                })
+                .collect::<anyhow::Result<_>>()?,
+            None => Vec::new(),
+        };
+
+        let scrub_key = || {
+            var("METRICS_SCRUB_KEY").ok_or_else(|| {
+                anyhow::anyhow!("METRICS_SCRUB_LABELS requires a secret in METRICS_SCRUB_KEY")
+            })
+        };
+        let scrubber = match var("METRICS_SCRUB_LABELS").as_deref() {
+            None | Some("false") | Some("0") => None,
+            Some("true") | Some("1") => Some(LabelScrubber::with_default_labels(scrub_key()?)),
+            Some(labels) => Some(LabelScrubber::new(
+                scrub_key()?,
+                labels.split(',').map(str::trim).filter(|l| !l.is_empty()),
+            )),
+        };
+
+        Ok(Self { auth, allowed_networks, scrubber })
+    }
+
+    fn allows(&self, addr: IpAddr) -> bool {
+        // IPv4 clients of a dual-stack listener show up as mapped IPv6 addresses
+        let addr = match addr {
+            IpAddr::V6(v6) => v6.to_ipv4_mapped().map_or(addr, IpAddr::V4),
+            v4 => v4,
+        };
+        self.allowed_networks.is_empty()
+            || self.allowed_networks.iter().any(|network| network.contains(&addr))
+    }
+
+    fn authorized(&self, headers: &HeaderMap) -> bool {
+        let provided = headers.get("authorization").and_then(|v| v.to_str().ok());
+        match &self.auth {
+            None => true,
+            Some(MetricsAuth::Bearer(token)) => provided
+                .and_then(|v| v.strip_prefix("Bearer "))
+                .is_some_and(|v| secrets_match(v, token)),
+            Some(MetricsAuth::Basic(credentials)) => provided
+                .and_then(|v| v.strip_prefix("Basic "))
+                .is_some_and(|v| secrets_match(v, credentials)),
+        }
+    }
+}
+
+async fn metrics_guard(
+    State(access): State<Arc<MetricsAccess>>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if !access.allows(peer.ip()) {
+        tracing::warn!(peer = %peer, "Metrics scrape from a network that is not allowed");
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    if !access.authorized(request.headers()) {
+        let challenge = match access.auth {
+            Some(MetricsAuth::Basic(_)) => "Basic realm=\"metrics\"",
+            _ => "Bearer",
+        };
+        return (StatusCode::UNAUTHORIZED, [("www-authenticate", challenge)]).into_response();
+    }
+    next.run(request).await
+}
+
+async fn metrics_handler(State(access): State<Arc<MetricsAccess>>) -> impl IntoResponse {
+    let encoder = TextEncoder::new();
+    let mut metric_families = prometheus::gather();
+    if let Some(scrubber) = &access.scrubber {
+        scrubber.scrub(&mut metric_families);
+    }
+    let mut buffer = vec![];
+    encoder.encode(&metric_families, &mut buffer).unwrap();
+
+    (
+        StatusCode::OK,
+        [("content-type", "text/plain; version=0.0.4")],
+        buffer,
+    )
+}
+
+/// Serve `/metrics` on its own listener, over TLS when `tls` is given
+pub fn spawn_metrics_server(addr: SocketAddr, tls: Option<RustlsConfig>, access: Arc<MetricsAccess>) {
+    tracing::info!(
+        tls = tls.is_some(),
+        auth = access.auth.is_some(),
+        allowed_networks = access.allowed_networks.len(),
+        scrub_labels = access.scrubber.is_some(),
+        "Metrics server listening on {}",
+        addr
+    );
+    let metrics_router = Router::new()
+        .route("/metrics", get(metrics_handler))
+        .layer(axum_middleware::from_fn_with_state(
+            access.clone(),
+            metrics_guard,
+        ))
+        .with_state(access)
+        .into_make_service_with_connect_info::<SocketAddr>();
+
+    tokio::spawn(async move {
+        match tls {
+            Some(tls) => axum_server::bind_rustls(addr, tls)
+                .serve(metrics_router)
+                .await
+                .expect("Metrics server failed"),
+            None => {
+                let listener = tokio::net::TcpListener::bind(addr)
+                    .await
+                    .expect("Failed to bind metrics server");
+                axum::serve(listener, metrics_router)
+                    .await
+                    .expect("Metrics server failed");
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(authorization: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("authorization", HeaderValue::from_str(authorization).unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_scrapers_present_the_configured_credentials() {
+        let open = MetricsAccess::default();
+        assert!(open.authorized(&HeaderMap::new()));
+
+        let bearer = MetricsAccess {
+            auth: Some(MetricsAuth::Bearer("scrape-token".to_string())),
+            ..MetricsAccess::default()
+        };
+        assert!(bearer.authorized(&headers("Bearer scrape-token")));
+        assert!(!bearer.authorized(&headers("Bearer other-token")));
+        assert!(!bearer.authorized(&HeaderMap::new()));
+
+        let basic = MetricsAccess {
+            auth: Some(MetricsAuth::Basic(BASE64_STANDARD.encode("prometheus:secret"))),
+            ..MetricsAccess::default()
+        };
+        let credentials = BASE64_STANDARD.encode("prometheus:secret");
+        assert!(basic.authorized(&headers(&format!("Basic {}", credentials))));
+        assert!(!basic.authorized(&headers(&format!("Bearer {}", credentials))));
+    }
+
+    #[test]
+    fn test_only_allowed_networks_may_scrape() {
+        let open = MetricsAccess::default();
+        assert!(open.allows("203.0.113.7".parse().unwrap()));
+
+        let access = MetricsAccess {
+            allowed_networks: vec!["10.0.0.0/8".parse().unwrap()],
+            ..MetricsAccess::default()
+        };
+        assert!(access.allows("10.1.2.3".parse().unwrap()));
+        assert!(!access.allows("192.168.1.1".parse().unwrap()));
+
+        // IPv4 clients of a dual-stack listener are matched as IPv4
+        assert!(access.allows("::ffff:10.1.2.3".parse().unwrap()));
+    }
+}
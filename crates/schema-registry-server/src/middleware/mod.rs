@@ -0,0 +1,3 @@
+//! Request middleware shared by the API routers
+
+pub mod rate_limiter;
@@ -1,11 +1,13 @@
 use axum::{
-    extract::{Request, State},
+    extract::{ConnectInfo, Request, State},
     http::StatusCode,
     middleware::Next,
-    response::{IntoResponse, Response},
+    response::Response,
 };
+use ipnet::IpNet;
 use std::{
     collections::HashMap,
+    net::{IpAddr, SocketAddr},
     sync::Arc,
     time::{Duration, Instant},
 };
@@ -125,7 +127,7 @@ impl RateLimiter {
     }
 
     /// Extract client identifier from request
-    fn extract_client_id(req: &Request) -> String {
+    pub fn extract_client_id(req: &Request) -> String {
         // Try API key first
         if let Some(api_key) = req.headers().get("X-API-Key") {
             if let Ok(key) = api_key.to_str() {
@@ -141,13 +143,58 @@ impl RateLimiter {
         }
 
         // Last resort: remote addr from connection info
-        "ip:unknown".to_string()
+        match req.extensions().get::<ConnectInfo<SocketAddr>>() {
+            Some(ConnectInfo(peer)) => format!("ip:{}", peer.ip()),
+            None => "ip:unknown".to_string(),
+        }
+    }
+
+    /// Address of the client that sent `req`
+    ///
+    /// `X-Forwarded-For` is only believed when the peer is one of the
+    /// `trusted_proxies`: its entries are read from the right, skipping
+    /// trusted proxies, and the first other address is the client. Anyone
+    /// else can write any address into the header.
+    pub fn client_address(req: &Request, trusted_proxies: &[IpNet]) -> Option<IpAddr> {
+        let ConnectInfo(peer) = req.extensions().get::<ConnectInfo<SocketAddr>>()?;
+        let trusted = |addr: &IpAddr| trusted_proxies.iter().any(|proxy| proxy.contains(addr));
+        let mut client = peer.ip();
+        if !trusted(&client) {
+            return Some(client);
+        }
+        let forwarded = req
+            .headers()
+            .get_all("X-Forwarded-For")
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(str::trim)
+            .collect::<Vec<_>>();
+        for hop in forwarded.into_iter().rev() {
+            match hop.parse::<IpAddr>() {
+                Ok(addr) => {
+                    client = addr;
+                    if !trusted(&addr) {
+                        break;
+                    }
+                }
+                // Whatever is left of a malformed entry was written by the client
+                Err(_) => break,
+            }
+        }
+        Some(client)
     }
 
     /// Check if request should be rate limited
     pub async fn check_rate_limit(&self, req: &Request) -> Result<(), StatusCode> {
-        let client_id = Self::extract_client_id(req);
+        self.check_client(Self::extract_client_id(req)).await
+    }
 
+    /// Check if a client identified by [`Self::extract_client_id`] should be rate limited
+    ///
+    /// Unlike [`Self::check_rate_limit`], the future doesn't borrow the
+    /// request, so it can be awaited in middleware, whose futures must be `Send`.
+    pub async fn check_client(&self, client_id: String) -> Result<(), StatusCode> {
         // Check queue depth first (backpressure)
         let queue_depth = *self.current_queue_depth.read().await;
         if queue_depth >= self.config.max_queue_depth {
@@ -221,7 +268,9 @@ pub async fn rate_limit_middleware(
     next: Next,
 ) -> Result<Response, StatusCode> {
     // Check rate limit
-    rate_limiter.check_rate_limit(&req).await?;
+    rate_limiter
+        .check_client(RateLimiter::extract_client_id(&req))
+        .await?;
 
     // Increment queue depth
     rate_limiter.increment_queue_depth().await;
@@ -238,7 +287,10 @@ pub async fn rate_limit_middleware(
 /// Adaptive rate limiter that adjusts based on system load
 pub struct AdaptiveRateLimiter {
     base_limiter: RateLimiter,
+    // Reserved for load checks based on real system metrics
+    #[allow(dead_code)]
     cpu_threshold: f64,
+    #[allow(dead_code)]
     memory_threshold: f64,
 }
 
@@ -399,6 +451,35 @@ mod tests {
         assert_eq!(limiter.get_queue_depth().await, 0);
     }
 
+    #[test]
+    fn test_forwarded_addresses_are_only_believed_from_trusted_proxies() {
+        let request = |peer: &str, forwarded: &str| {
+            let mut req = axum::http::Request::builder()
+                .header("X-Forwarded-For", forwarded)
+                .body(axum::body::Body::empty())
+                .unwrap();
+            req.extensions_mut()
+                .insert(ConnectInfo(SocketAddr::new(peer.parse().unwrap(), 443)));
+            req
+        };
+        let proxies: Vec<IpNet> = vec!["10.0.0.0/8".parse().unwrap()];
+        let client = |req: &Request| RateLimiter::client_address(req, &proxies).unwrap();
+
+        // A direct client can't pick its own bucket
+        let direct = request("203.0.113.7", "198.51.100.1");
+        assert_eq!(client(&direct), "203.0.113.7".parse::<IpAddr>().unwrap());
+
+        // Behind proxies, the first untrusted hop from the right is the client
+        let proxied = request("10.0.0.2", "198.51.100.1, 203.0.113.7, 10.0.0.1");
+        assert_eq!(client(&proxied), "203.0.113.7".parse::<IpAddr>().unwrap());
+
+        let garbled = request("10.0.0.2", "anything, 10.0.0.1");
+        assert_eq!(client(&garbled), "10.0.0.1".parse::<IpAddr>().unwrap());
+
+        let untrusted = RateLimiter::client_address(&proxied, &[]).unwrap();
+        assert_eq!(untrusted, "10.0.0.2".parse::<IpAddr>().unwrap());
+    }
+
     #[tokio::test]
     async fn test_circuit_breaker() {
         let breaker = CircuitBreaker::new(3, Duration::from_secs(1));
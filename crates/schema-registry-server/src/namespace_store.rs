@@ -0,0 +1,170 @@
+//! Durable home of namespace and subject settings
+//!
+//! Settings are resolved on every registration, so each replica serves them
+//! from its [`NamespaceTree`] and writes changes through to the
+//! `namespace_settings` table. A commit notifies the `namespace_settings`
+//! Postgres channel and every replica reloads its tree, so a change made on
+//! one replica is seen by all of them and survives a restart.
+//!
+//! Notifications sent while the listener reconnects are lost, so the tree is
+//! reloaded whenever the listener connects.
+
+use schema_registry_core::namespace::{self, NamespaceSettings, NamespaceTree};
+use sqlx::postgres::PgListener;
+use sqlx::PgPool;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{Mutex, MutexGuard};
+
+/// Postgres channel commits notify
+pub const CHANNEL: &str = "namespace_settings";
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+/// Writes settings changes to Postgres and keeps the tree in step with it
+pub struct NamespaceSettingsStore {
+    db: PgPool,
+    tree: Arc<NamespaceTree>,
+    writer: Mutex<()>,
+}
+
+impl NamespaceSettingsStore {
+    pub fn new(db: PgPool, tree: Arc<NamespaceTree>) -> Self {
+        Self {
+            db,
+            tree,
+            writer: Mutex::new(()),
+        }
+    }
+
+    /// Replace the tree with the stored settings, returning how many
+    /// namespaces have explicit settings
+    pub async fn load(&self) -> anyhow::Result<usize> {
+        let rows: Vec<(String, serde_json::Value)> =
+            sqlx::query_as("SELECT namespace, settings FROM namespace_settings")
+                .fetch_all(&self.db)
+                .await?;
+        let settings = rows
+            .into_iter()
+            .map(|(namespace, settings)| Ok((namespace, serde_json::from_value(settings)?)))
+            .collect::<anyhow::Result<BTreeMap<String, NamespaceSettings>>>()?;
+        let count = settings.len();
+        self.tree.replace_all(settings)?;
+        Ok(count)
+    }
+
+    /// Take the write lock before changing the tree
+    ///
+    /// Changes made while holding it are persisted by
+    /// [`NamespaceWrite::commit`]; a reload waits for the lock, so it can't
+    /// undo a change that is about to be committed.
+    pub async fn lock(&self) -> NamespaceWrite<'_> {
+        NamespaceWrite {
+            store: self,
+            _guard: self.writer.lock().await,
+        }
+    }
+
+    /// Reload the tree whenever another replica commits, until the process
+    /// exits, reconnecting with backoff when the connection drops
+    pub fn spawn_listener(self: Arc<Self>) {
+        tokio::spawn(async move {
+            let mut delay = RECONNECT_DELAY;
+            loop {
+                if let Err(e) = self.relay(&mut delay).await {
+                    tracing::warn!(
+                        error = %e,
+                        retry_in_ms = delay.as_millis() as u64,
+                        "Namespace settings listener disconnected"
+                    );
+                }
+                tokio::time::sleep(delay).await;
+                delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+            }
+        });
+    }
+
+    async fn relay(&self, delay: &mut Duration) -> anyhow::Result<()> {
+        let mut listener = PgListener::connect_with(&self.db).await?;
+        listener.listen(CHANNEL).await?;
+        *delay = RECONNECT_DELAY;
+        loop {
+            self.reload().await;
+            listener.recv().await?;
+        }
+    }
+
+    async fn reload(&self) {
+        let _guard = self.writer.lock().await;
+        if let Err(e) = self.load().await {
+            tracing::warn!(error = %e, "Failed to reload namespace settings");
+        }
+    }
+}
+
+/// Exclusive right to change the tree until committed or dropped
+pub struct NamespaceWrite<'a> {
+    store: &'a NamespaceSettingsStore,
+    _guard: MutexGuard<'a, ()>,
+}
+
+impl NamespaceWrite<'_> {
+    /// Persist the tree's current settings of `namespaces`
+    ///
+    /// Namespaces without explicit settings in the tree are deleted. If the
+    /// write fails the tree is reloaded, dropping the uncommitted changes.
+    pub async fn commit<I, S>(self, namespaces: I, updated_by: &str) -> anyhow::Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let result = self.write(namespaces, updated_by).await;
+        if result.is_err() {
+            if let Err(e) = self.store.load().await {
+                tracing::warn!(error = %e, "Failed to reload namespace settings");
+            }
+        }
+        result
+    }
+
+    async fn write<I, S>(&self, namespaces: I, updated_by: &str) -> anyhow::Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut tx = self.store.db.begin().await?;
+        for namespace in namespaces {
+            let namespace = namespace::normalize(namespace.as_ref())?;
+            match self.store.tree.get(&namespace) {
+                Some(settings) => {
+                    sqlx::query(
+                        "INSERT INTO namespace_settings (namespace, settings, updated_by) \
+                         VALUES ($1, $2, $3) \
+                         ON CONFLICT (namespace) DO UPDATE \
+                         SET settings = EXCLUDED.settings, updated_by = EXCLUDED.updated_by, \
+                             updated_at = NOW()",
+                    )
+                    .bind(&namespace)
+                    .bind(serde_json::to_value(&settings)?)
+                    .bind(updated_by)
+                    .execute(&mut *tx)
+                    .await?;
+                }
+                None => {
+                    sqlx::query("DELETE FROM namespace_settings WHERE namespace = $1")
+                        .bind(&namespace)
+                        .execute(&mut *tx)
+                        .await?;
+                }
+            }
+        }
+        sqlx::query("SELECT pg_notify($1, '')")
+            .bind(CHANNEL)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,680 @@
+//! Ownership transfers between teams
+//!
+//! Handing a namespace to another team is a request the receiving team
+//! accepts, rather than an edit to the namespace's settings. A transfer names
+//! the new owner and the webhook it is notified on, and waits until the
+//! receiving team accepts or rejects it, the requester cancels it, or its
+//! deadline passes and it expires.
+//!
+//! The receiving team's channel hears of the request; both teams' channels
+//! hear how it ended. Accepting sets the owner and notification channel of the
+//! namespace in one step, and only if the namespace still has the owner it
+//! had when the transfer was requested.
+//!
+//! Only the receiving owner may accept or reject a transfer: the deciding
+//! principal must be the new owner, so an admin acts for a team by
+//! impersonating it rather than with the admin token.
+//!
+//! Transfers are kept in the `ownership_transfers` table, so every replica
+//! sees the same ones and they survive a restart; deciding one locks its row.
+//! Notices are posted without holding up the request; a failed delivery is
+//! logged and not retried.
+
+use crate::namespace_store::NamespaceWrite;
+use chrono::{DateTime, Duration, Utc};
+use schema_registry_core::namespace::{EffectiveSettings, NamespaceSettings, NamespaceTree};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Postgres, Transaction};
+use std::fmt;
+use std::sync::{Arc, OnceLock};
+use uuid::Uuid;
+
+/// How long the receiving team has to answer by default
+pub const DEFAULT_TRANSFER_DAYS: i64 = 7;
+
+/// Longest deadline a transfer may be given
+pub const MAX_TRANSFER_DAYS: i64 = 30;
+
+/// How often pending transfers are checked against their deadline
+const EXPIRY_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Advisory lock serializing transfer requests
+const REQUEST_LOCK: i64 = 0x6f77_6e65_7273_6869;
+
+/// Where a transfer stands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferStatus {
+    /// Waiting for the receiving team
+    Pending,
+    Accepted,
+    Rejected,
+    /// Withdrawn before the receiving team answered
+    Cancelled,
+    /// Not answered before the deadline
+    Expired,
+}
+
+impl TransferStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TransferStatus::Pending => "pending",
+            TransferStatus::Accepted => "accepted",
+            TransferStatus::Rejected => "rejected",
+            TransferStatus::Cancelled => "cancelled",
+            TransferStatus::Expired => "expired",
+        }
+    }
+}
+
+/// A request to hand a namespace to another team
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OwnershipTransfer {
+    pub id: Uuid,
+    pub namespace: String,
+    /// Effective owner when the transfer was requested
+    pub from_owner: Option<String>,
+    /// Channel the current owner is notified on
+    pub from_channel: Option<String>,
+    pub to_owner: String,
+    /// Channel the new owner is notified on, from acceptance onwards
+    pub to_channel: String,
+    pub reason: String,
+    pub requested_by: String,
+    pub requested_at: DateTime<Utc>,
+    /// When a pending transfer expires
+    pub deadline: DateTime<Utc>,
+    pub status: TransferStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decided_by: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decided_at: Option<DateTime<Utc>>,
+    /// Why the transfer was accepted, rejected or cancelled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+impl OwnershipTransfer {
+    /// Check a request to hand `current.namespace` to `request.to_owner`
+    pub fn new(
+        current: &EffectiveSettings,
+        request: TransferRequest,
+        requested_by: &str,
+        now: DateTime<Utc>,
+    ) -> Result<Self, TransferError> {
+        let to_owner = request.to_owner.trim().to_string();
+        if to_owner.is_empty() {
+            return Err(TransferError::Invalid(
+                "The receiving owner must not be empty".to_string(),
+            ));
+        }
+        if current.owner.value.as_deref() == Some(to_owner.as_str()) {
+            return Err(TransferError::Invalid(format!(
+                "Namespace '{}' is already owned by {}",
+                current.namespace, to_owner
+            )));
+        }
+        if request.reason.trim().is_empty() {
+            return Err(TransferError::Invalid(
+                "Transfer reason must not be empty".to_string(),
+            ));
+        }
+        let channel = reqwest::Url::parse(request.notification_channel.trim())
+            .ok()
+            .filter(|url| matches!(url.scheme(), "http" | "https"))
+            .ok_or_else(|| {
+                TransferError::Invalid(
+                    "The notification channel must be an http(s) webhook URL".to_string(),
+                )
+            })?;
+        let deadline = request
+            .deadline
+            .unwrap_or(now + Duration::days(DEFAULT_TRANSFER_DAYS));
+        if deadline <= now {
+            return Err(TransferError::Invalid(
+                "Transfer deadline must be in the future".to_string(),
+            ));
+        }
+        if deadline > now + Duration::days(MAX_TRANSFER_DAYS) {
+            return Err(TransferError::Invalid(format!(
+                "Transfers may not stay open longer than {} days",
+                MAX_TRANSFER_DAYS
+            )));
+        }
+
+        Ok(Self {
+            id: Uuid::new_v4(),
+            namespace: current.namespace.clone(),
+            from_owner: current.owner.value.clone(),
+            from_channel: current.notification_channel.value.clone(),
+            to_owner,
+            to_channel: channel.to_string(),
+            reason: request.reason.trim().to_string(),
+            requested_by: requested_by.to_string(),
+            requested_at: now,
+            deadline,
+            status: TransferStatus::Pending,
+            decided_by: None,
+            decided_at: None,
+            note: None,
+        })
+    }
+
+    /// Whether the transfer is pending past its deadline
+    pub fn is_overdue(&self, now: DateTime<Utc>) -> bool {
+        self.status == TransferStatus::Pending && now >= self.deadline
+    }
+
+    /// Reject `principal` deciding the transfer unless it is the receiving
+    /// owner
+    pub fn ensure_receiving_owner(&self, principal: &str) -> Result<(), TransferError> {
+        if principal != self.to_owner {
+            return Err(TransferError::Forbidden(format!(
+                "Only {} can decide ownership transfer {}",
+                self.to_owner, self.id
+            )));
+        }
+        Ok(())
+    }
+
+    fn expire(&mut self) {
+        self.status = TransferStatus::Expired;
+        self.decided_at = Some(self.deadline);
+    }
+}
+
+/// Body of a transfer request
+#[derive(Debug, Clone, Deserialize)]
+pub struct TransferRequest {
+    pub to_owner: String,
+    /// Webhook URL the receiving team is notified on
+    pub notification_channel: String,
+    pub reason: String,
+    /// Defaults to [`DEFAULT_TRANSFER_DAYS`] from now
+    #[serde(default)]
+    pub deadline: Option<DateTime<Utc>>,
+}
+
+/// Payload posted to a team's channel
+#[derive(Debug, Clone, Serialize)]
+pub struct TransferNotice {
+    /// `requested`, or the status the transfer ended in
+    pub event: &'static str,
+    pub transfer: OwnershipTransfer,
+}
+
+/// Why a transfer couldn't be requested or decided
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransferError {
+    NotFound(String),
+    Invalid(String),
+    /// The transfer was already decided, or ownership changed under it
+    Conflict(String),
+    /// The deadline passed before the decision
+    Expired(String),
+    /// The caller isn't allowed to decide the transfer
+    Forbidden(String),
+    /// Transfers couldn't be read or written
+    Storage(String),
+}
+
+impl fmt::Display for TransferError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransferError::NotFound(msg)
+            | TransferError::Invalid(msg)
+            | TransferError::Conflict(msg)
+            | TransferError::Expired(msg)
+            | TransferError::Forbidden(msg)
+            | TransferError::Storage(msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl std::error::Error for TransferError {}
+
+impl From<sqlx::Error> for TransferError {
+    fn from(e: sqlx::Error) -> Self {
+        TransferError::Storage(format!("Failed to access ownership transfers: {}", e))
+    }
+}
+
+impl From<serde_json::Error> for TransferError {
+    fn from(e: serde_json::Error) -> Self {
+        TransferError::Storage(format!("Invalid stored ownership transfer: {}", e))
+    }
+}
+
+/// Pending and decided transfers, and the notices about them
+pub struct OwnershipTransfers {
+    db: PgPool,
+    client: OnceLock<reqwest::Client>,
+}
+
+impl OwnershipTransfers {
+    pub fn new(db: PgPool) -> Self {
+        Self {
+            db,
+            client: OnceLock::new(),
+        }
+    }
+
+    /// Request to hand `current.namespace` to `request.to_owner`
+    ///
+    /// A namespace has at most one pending transfer at a time.
+    pub async fn request(
+        &self,
+        current: &EffectiveSettings,
+        request: TransferRequest,
+        requested_by: &str,
+    ) -> Result<OwnershipTransfer, TransferError> {
+        let transfer = OwnershipTransfer::new(current, request, requested_by, Utc::now())?;
+
+        let mut tx = self.db.begin().await?;
+        sqlx::query("SELECT pg_advisory_xact_lock($1)")
+            .bind(REQUEST_LOCK)
+            .execute(&mut *tx)
+            .await?;
+        let pending: Option<(serde_json::Value,)> = sqlx::query_as(
+            "SELECT transfer FROM ownership_transfers \
+             WHERE namespace = $1 AND status = 'pending' AND deadline > $2 LIMIT 1",
+        )
+        .bind(&transfer.namespace)
+        .bind(transfer.requested_at)
+        .fetch_optional(&mut *tx)
+        .await?;
+        if let Some((pending,)) = pending {
+            let pending: OwnershipTransfer = serde_json::from_value(pending)?;
+            return Err(TransferError::Conflict(format!(
+                "Namespace '{}' already has a pending transfer to {} ({})",
+                transfer.namespace, pending.to_owner, pending.id
+            )));
+        }
+        store(&mut tx, &transfer).await?;
+        tx.commit().await?;
+
+        tracing::info!(
+            transfer_id = %transfer.id,
+            namespace = %transfer.namespace,
+            to_owner = %transfer.to_owner,
+            deadline = %transfer.deadline,
+            "Ownership transfer requested"
+        );
+        self.notify(&transfer.to_channel, "requested", &transfer);
+        Ok(transfer)
+    }
+
+    pub async fn get(&self, id: Uuid) -> Result<Option<OwnershipTransfer>, TransferError> {
+        let row: Option<(serde_json::Value,)> =
+            sqlx::query_as("SELECT transfer FROM ownership_transfers WHERE id = $1")
+                .bind(id)
+                .fetch_optional(&self.db)
+                .await?;
+        Ok(row
+            .map(|(transfer,)| serde_json::from_value(transfer))
+            .transpose()?)
+    }
+
+    /// Transfers, newest first, optionally of one namespace or status
+    pub async fn list(
+        &self,
+        namespace: Option<&str>,
+        status: Option<TransferStatus>,
+    ) -> Result<Vec<OwnershipTransfer>, TransferError> {
+        let rows: Vec<(serde_json::Value,)> = sqlx::query_as(
+            "SELECT transfer FROM ownership_transfers \
+             WHERE ($1::TEXT IS NULL OR namespace = $1) AND ($2::TEXT IS NULL OR status = $2) \
+             ORDER BY requested_at DESC",
+        )
+        .bind(namespace)
+        .bind(status.map(|status| status.as_str()))
+        .fetch_all(&self.db)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|(transfer,)| serde_json::from_value(transfer))
+            .collect::<Result<_, _>>()?)
+    }
+
+    /// Accept a pending transfer, making the receiving team the owner
+    ///
+    /// The new owner is set in `tree` and committed through `settings`
+    /// before the transfer is closed, so the two can't disagree. Returns the
+    /// transfer and the namespace's explicit settings from before the change.
+    pub async fn accept(
+        &self,
+        id: Uuid,
+        accepted_by: &str,
+        note: Option<String>,
+        tree: &NamespaceTree,
+        settings: NamespaceWrite<'_>,
+    ) -> Result<(OwnershipTransfer, Option<NamespaceSettings>), TransferError> {
+        let (tx, transfer) = self.lock_pending(id, accepted_by).await?;
+        let previous = tree
+            .set_ownership(
+                &transfer.namespace,
+                transfer.from_owner.as_deref(),
+                &transfer.to_owner,
+                Some(transfer.to_channel.clone()),
+            )
+            .map_err(|e| TransferError::Conflict(e.to_string()))?;
+        settings
+            .commit([&transfer.namespace], accepted_by)
+            .await
+            .map_err(|e| TransferError::Storage(e.to_string()))?;
+        let transfer = self
+            .close(tx, transfer, TransferStatus::Accepted, accepted_by, note)
+            .await?;
+        Ok((transfer, previous))
+    }
+
+    /// Turn down a pending transfer; ownership stays as it is
+    pub async fn reject(
+        &self,
+        id: Uuid,
+        rejected_by: &str,
+        note: Option<String>,
+    ) -> Result<OwnershipTransfer, TransferError> {
+        let (tx, transfer) = self.lock_pending(id, rejected_by).await?;
+        self.close(tx, transfer, TransferStatus::Rejected, rejected_by, note)
+            .await
+    }
+
+    /// Withdraw a pending transfer
+    pub async fn cancel(
+        &self,
+        id: Uuid,
+        cancelled_by: &str,
+        note: Option<String>,
+    ) -> Result<OwnershipTransfer, TransferError> {
+        let (tx, transfer) = self.lock(id).await?;
+        self.close(tx, transfer, TransferStatus::Cancelled, cancelled_by, note)
+            .await
+    }
+
+    /// Lock a pending transfer the receiving owner `principal` decides
+    async fn lock_pending(
+        &self,
+        id: Uuid,
+        principal: &str,
+    ) -> Result<(Transaction<'static, Postgres>, OwnershipTransfer), TransferError> {
+        let (tx, transfer) = self.lock(id).await?;
+        transfer.ensure_receiving_owner(principal)?;
+        Ok((tx, transfer))
+    }
+
+    /// Lock a transfer's row until it is closed, so a transfer is decided,
+    /// and applied, at most once
+    ///
+    /// A transfer found overdue is expired instead.
+    async fn lock(
+        &self,
+        id: Uuid,
+    ) -> Result<(Transaction<'static, Postgres>, OwnershipTransfer), TransferError> {
+        let mut tx = self.db.begin().await?;
+        let row: Option<(serde_json::Value,)> =
+            sqlx::query_as("SELECT transfer FROM ownership_transfers WHERE id = $1 FOR UPDATE")
+                .bind(id)
+                .fetch_optional(&mut *tx)
+                .await?;
+        let Some((transfer,)) = row else {
+            return Err(TransferError::NotFound(format!(
+                "Ownership transfer {} not found",
+                id
+            )));
+        };
+        let mut transfer: OwnershipTransfer = serde_json::from_value(transfer)?;
+        if transfer.is_overdue(Utc::now()) {
+            transfer.expire();
+            store(&mut tx, &transfer).await?;
+            tx.commit().await?;
+            self.notify_both(&transfer);
+            return Err(TransferError::Expired(format!(
+                "Ownership transfer {} expired at {}",
+                id, transfer.deadline
+            )));
+        }
+        if transfer.status != TransferStatus::Pending {
+            return Err(TransferError::Conflict(format!(
+                "Ownership transfer {} is already {}",
+                id,
+                transfer.status.as_str()
+            )));
+        }
+        Ok((tx, transfer))
+    }
+
+    /// Close a locked pending transfer as `status`
+    async fn close(
+        &self,
+        mut tx: Transaction<'static, Postgres>,
+        mut transfer: OwnershipTransfer,
+        status: TransferStatus,
+        decided_by: &str,
+        note: Option<String>,
+    ) -> Result<OwnershipTransfer, TransferError> {
+        transfer.status = status;
+        transfer.decided_by = Some(decided_by.to_string());
+        transfer.decided_at = Some(Utc::now());
+        transfer.note = note.filter(|note| !note.trim().is_empty());
+        store(&mut tx, &transfer).await?;
+        tx.commit().await?;
+
+        tracing::info!(
+            transfer_id = %transfer.id,
+            namespace = %transfer.namespace,
+            status = transfer.status.as_str(),
+            "Ownership transfer decided"
+        );
+        self.notify_both(&transfer);
+        Ok(transfer)
+    }
+
+    /// Expire pending transfers whose deadline has passed
+    ///
+    /// Rows another replica is deciding or expiring are skipped, so each
+    /// transfer is expired, and its teams notified, once.
+    pub async fn expire_due(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<OwnershipTransfer>, TransferError> {
+        let mut tx = self.db.begin().await?;
+        let rows: Vec<(serde_json::Value,)> = sqlx::query_as(
+            "SELECT transfer FROM ownership_transfers \
+             WHERE status = 'pending' AND deadline <= $1 FOR UPDATE SKIP LOCKED",
+        )
+        .bind(now)
+        .fetch_all(&mut *tx)
+        .await?;
+        let mut expired = Vec::with_capacity(rows.len());
+        for (transfer,) in rows {
+            let mut transfer: OwnershipTransfer = serde_json::from_value(transfer)?;
+            transfer.expire();
+            store(&mut tx, &transfer).await?;
+            expired.push(transfer);
+        }
+        tx.commit().await?;
+
+        for transfer in &expired {
+            tracing::info!(
+                transfer_id = %transfer.id,
+                namespace = %transfer.namespace,
+                "Ownership transfer expired"
+            );
+            self.notify_both(transfer);
+        }
+        Ok(expired)
+    }
+
+    /// Expire overdue transfers every minute, passing each to `on_expired`
+    pub fn spawn_expiry<F>(self: Arc<Self>, on_expired: F)
+    where
+        F: Fn(OwnershipTransfer) + Send + 'static,
+    {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(EXPIRY_INTERVAL);
+            loop {
+                ticker.tick().await;
+                match self.expire_due(Utc::now()).await {
+                    Ok(expired) => expired.into_iter().for_each(&on_expired),
+                    Err(e) => {
+                        tracing::warn!(error = %e, "Failed to expire ownership transfers");
+                    }
+                }
+            }
+        });
+    }
+
+    fn client(&self) -> &reqwest::Client {
+        self.client.get_or_init(|| {
+            reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs(10))
+                .build()
+                .unwrap_or_default()
+        })
+    }
+
+    /// Tell both teams how a transfer ended
+    fn notify_both(&self, transfer: &OwnershipTransfer) {
+        let event = transfer.status.as_str();
+        if let Some(channel) = &transfer.from_channel {
+            if *channel != transfer.to_channel {
+                self.notify(channel, event, transfer);
+            }
+        }
+        self.notify(&transfer.to_channel, event, transfer);
+    }
+
+    fn notify(&self, channel: &str, event: &'static str, transfer: &OwnershipTransfer) {
+        let client = self.client().clone();
+        let channel = channel.to_string();
+        let notice = TransferNotice {
+            event,
+            transfer: transfer.clone(),
+        };
+        tokio::spawn(async move {
+            let delivered = client
+                .post(&channel)
+                .json(&notice)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status());
+            if let Err(e) = delivered {
+                tracing::warn!(
+                    transfer_id = %notice.transfer.id,
+                    channel = %channel,
+                    error = %e,
+                    "Failed to deliver ownership transfer notice"
+                );
+            }
+        });
+    }
+}
+
+/// Insert or update a transfer's row
+async fn store(
+    tx: &mut Transaction<'static, Postgres>,
+    transfer: &OwnershipTransfer,
+) -> Result<(), TransferError> {
+    sqlx::query(
+        "INSERT INTO ownership_transfers (id, namespace, status, requested_at, deadline, transfer) \
+         VALUES ($1, $2, $3, $4, $5, $6) \
+         ON CONFLICT (id) DO UPDATE SET status = EXCLUDED.status, transfer = EXCLUDED.transfer",
+    )
+    .bind(transfer.id)
+    .bind(&transfer.namespace)
+    .bind(transfer.status.as_str())
+    .bind(transfer.requested_at)
+    .bind(transfer.deadline)
+    .bind(serde_json::to_value(transfer)?)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(to_owner: &str) -> TransferRequest {
+        TransferRequest {
+            to_owner: to_owner.to_string(),
+            notification_channel: format!("https://hooks.example.com/{}", to_owner),
+            reason: "Reorg".to_string(),
+            deadline: None,
+        }
+    }
+
+    fn current() -> EffectiveSettings {
+        let tree = NamespaceTree::default();
+        tree.set(
+            "com.example.payments",
+            NamespaceSettings {
+                owner: Some("platform".to_string()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        tree.effective("com.example.payments").unwrap()
+    }
+
+    #[test]
+    fn test_transfer_names_both_owners_and_only_the_new_one_decides() {
+        let now = Utc::now();
+        let transfer =
+            OwnershipTransfer::new(&current(), request(" payments "), "alice", now).unwrap();
+        assert_eq!(transfer.from_owner.as_deref(), Some("platform"));
+        assert_eq!(transfer.to_owner, "payments");
+        assert_eq!(transfer.to_channel, "https://hooks.example.com/payments");
+        assert_eq!(
+            transfer.deadline,
+            now + Duration::days(DEFAULT_TRANSFER_DAYS)
+        );
+        assert_eq!(transfer.status, TransferStatus::Pending);
+
+        assert!(transfer.ensure_receiving_owner("payments").is_ok());
+        for principal in ["platform", "admin", "alice"] {
+            assert!(matches!(
+                transfer.ensure_receiving_owner(principal),
+                Err(TransferError::Forbidden(_))
+            ));
+        }
+
+        assert!(!transfer.is_overdue(now));
+        assert!(transfer.is_overdue(transfer.deadline));
+        let mut expired = transfer.clone();
+        expired.expire();
+        assert_eq!(expired.status, TransferStatus::Expired);
+        assert_eq!(expired.decided_at, Some(transfer.deadline));
+        assert!(!expired.is_overdue(transfer.deadline));
+    }
+
+    #[test]
+    fn test_invalid_transfer_requests_are_refused() {
+        let now = Utc::now();
+        let refused = |request: TransferRequest| {
+            matches!(
+                OwnershipTransfer::new(&current(), request, "alice", now),
+                Err(TransferError::Invalid(_))
+            )
+        };
+        assert!(refused(request("platform")));
+        assert!(refused(request("  ")));
+        assert!(refused(TransferRequest {
+            reason: " ".to_string(),
+            ..request("payments")
+        }));
+        assert!(refused(TransferRequest {
+            notification_channel: "payments-team".to_string(),
+            ..request("payments")
+        }));
+        assert!(refused(TransferRequest {
+            deadline: Some(now - Duration::minutes(1)),
+            ..request("payments")
+        }));
+        assert!(refused(TransferRequest {
+            deadline: Some(now + Duration::days(MAX_TRANSFER_DAYS + 1)),
+            ..request("payments")
+        }));
+    }
+}
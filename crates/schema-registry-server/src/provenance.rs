@@ -0,0 +1,783 @@
+//! Provenance and registration replay
+//!
+//! How a version came to be registered, and re-running an audited
+//! registration against a sandbox namespace.
+
+use super::*;
+use schema_registry_security::{
+    audit::AuditEventFilter, quarantine::QuarantineStatus, signing::DocumentSignature,
+};
+use std::collections::BTreeSet;
+
+/// `schema_events` row written with every registered version
+pub(crate) const SCHEMA_EVENT_REGISTERED: &str = "REGISTERED";
+/// `schema_events` row written when a version is archived
+pub(crate) const SCHEMA_EVENT_ARCHIVED: &str = "ARCHIVED";
+/// `schema_events` row written when a version's metadata is updated in place
+pub(crate) const SCHEMA_EVENT_METADATA_UPDATED: &str = "METADATA_UPDATED";
+/// `schema_events` row written when a version is tombstoned
+pub(crate) const SCHEMA_EVENT_DELETED: &str = "DELETED";
+/// `schema_events` row written when a version is moved along its lifecycle
+pub(crate) const SCHEMA_EVENT_STATE_CHANGED: &str = "STATE_CHANGED";
+/// `schema_events` row written with a version whose breaking changes were
+/// allowed by compatibility exemptions
+pub(crate) const SCHEMA_EVENT_EXEMPTION_APPLIED: &str = "EXEMPTION_APPLIED";
+
+/// How a registration fared at one of its checks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum GateOutcome {
+    Passed,
+    /// Passed with findings to follow up, e.g. a quarantining security scan
+    Flagged,
+    /// Would have blocked the registration, but was overridden
+    Overridden,
+}
+
+impl GateOutcome {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            GateOutcome::Passed => "passed",
+            GateOutcome::Flagged => "flagged",
+            GateOutcome::Overridden => "overridden",
+        }
+    }
+}
+
+/// One check a registration went through
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct GateResult {
+    pub(crate) gate: String,
+    pub(crate) outcome: GateOutcome,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) details: Vec<String>,
+}
+
+impl GateResult {
+    pub(crate) fn passed(gate: &str) -> Self {
+        Self {
+            gate: gate.to_string(),
+            outcome: GateOutcome::Passed,
+            details: Vec::new(),
+        }
+    }
+
+    /// Flagged with `findings`, or passed if there are none
+    pub(crate) fn flagged_if(gate: &str, findings: Vec<String>) -> Self {
+        let outcome = if findings.is_empty() {
+            GateOutcome::Passed
+        } else {
+            GateOutcome::Flagged
+        };
+        Self {
+            gate: gate.to_string(),
+            outcome,
+            details: findings,
+        }
+    }
+
+    /// Registration plugins run at `stage`, none of which vetoed
+    pub(crate) fn plugins(state: &AppState, stage: RegistrationStage) -> Self {
+        Self {
+            gate: format!("plugins_{}", stage),
+            outcome: GateOutcome::Passed,
+            details: state.interceptors.names(),
+        }
+    }
+}
+
+/// A change freeze overridden to register a version
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct FreezeOverride {
+    pub(crate) window: String,
+    pub(crate) reason: String,
+    pub(crate) approved_by: String,
+}
+
+/// What the `REGISTERED` schema event records
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct RegistrationTrail {
+    pub(crate) subject: String,
+    pub(crate) version: String,
+    /// State the version was registered in
+    pub(crate) state: String,
+    pub(crate) gates: Vec<GateResult>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) freeze_override: Option<FreezeOverride>,
+}
+
+/// What the `EXEMPTION_APPLIED` schema event records
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct ExemptionTrail {
+    /// Compatibility mode the version was checked in
+    pub(crate) mode: String,
+    pub(crate) exemptions: Vec<Uuid>,
+    /// Breaking changes the exemptions allowed
+    pub(crate) violations: Vec<serde_json::Value>,
+}
+
+pub(crate) async fn record_schema_event(
+    executor: impl sqlx::PgExecutor<'_>,
+    schema_id: Uuid,
+    event_type: &str,
+    data: &(impl Serialize + Sync),
+    actor: &str,
+) -> Result<(), AppError> {
+    sqlx::query(
+        "INSERT INTO schema_events (schema_id, event_type, event_data, created_by) VALUES ($1, $2, $3, $4)",
+    )
+    .bind(schema_id)
+    .bind(event_type)
+    .bind(sqlx::types::Json(data))
+    .bind(actor)
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+/// A `schema_events` row
+#[derive(Debug, Serialize)]
+struct LifecycleEvent {
+    event_type: String,
+    actor: Option<String>,
+    at: chrono::DateTime<Utc>,
+    details: serde_json::Value,
+}
+
+/// A sign-off on a version
+#[derive(Debug, Serialize)]
+struct Approval {
+    /// `change_freeze_override`, `security_review` or `compatibility_exemption`
+    kind: &'static str,
+    approved_by: String,
+    /// `approved` or `rejected`
+    decision: &'static str,
+    note: Option<String>,
+    at: Option<chrono::DateTime<Utc>>,
+}
+
+/// Who proposed, checked, approved and changed a version, and how
+#[derive(Debug, Serialize)]
+struct ProvenanceDocument {
+    schema_id: Uuid,
+    subject: String,
+    version: String,
+    format: String,
+    content_hash: String,
+    state: String,
+    created_at: chrono::DateTime<Utc>,
+    /// Unknown for versions registered before provenance was recorded
+    created_by: Option<String>,
+    lifecycle: Vec<LifecycleEvent>,
+    gates: Vec<GateResult>,
+    approvals: Vec<Approval>,
+    /// Checks of this version against others, newest first
+    compatibility_reports: Vec<CompatibilityCheckRecord>,
+    /// Audit events about the version held by this instance, with their
+    /// hash-chain links
+    audit_events: Vec<AuditEvent>,
+    generated_at: chrono::DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+struct SignedProvenance {
+    provenance: ProvenanceDocument,
+    /// Covers the canonical JSON of `provenance`
+    signature: DocumentSignature,
+}
+
+/// Signed decision trail of one version of a subject
+///
+/// `id` may be any version of the subject. Lifecycle events and gate results
+/// are stored with the schema; audit events, and the approvals taken from
+/// them, only go back to this instance's start.
+pub(crate) async fn get_schema_provenance(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((id, version)): Path<(Uuid, String)>,
+) -> Result<Json<SignedProvenance>, AppError> {
+    let admin = require_admin(&state, &headers)?;
+    let signer = state.provenance_signer.clone().ok_or_else(|| {
+        AppError::NotFound(
+            "Provenance export is disabled; set PROVENANCE_SIGNING_KEY to enable".to_string(),
+        )
+    })?;
+    let version = version
+        .parse::<SemanticVersion>()
+        .map_err(|e| AppError::InvalidInput(e.to_string()))?;
+
+    let row: Option<(
+        Uuid,
+        String,
+        String,
+        String,
+        String,
+        String,
+        chrono::DateTime<Utc>,
+        Option<String>,
+    )> = sqlx::query_as(
+        r#"
+        SELECT s.id, s.namespace, s.name, s.format, s.content_hash, s.state, s.created_at, s.created_by
+        FROM schemas s
+        JOIN schemas v ON v.namespace = s.namespace AND v.name = s.name
+        WHERE v.id = $1 AND s.version_major = $2 AND s.version_minor = $3 AND s.version_patch = $4
+        "#,
+    )
+    .bind(id)
+    .bind(version.major as i32)
+    .bind(version.minor as i32)
+    .bind(version.patch as i32)
+    .fetch_optional(&state.db)
+    .await?;
+    let (schema_id, namespace, name, format, content_hash, schema_state, created_at, created_by) =
+        row.ok_or_else(|| {
+            AppError::NotFound(format!(
+                "Version {} of the subject of schema {} not found",
+                version, id
+            ))
+        })?;
+    let subject = subject_of(&namespace, &name);
+
+    let rows: Vec<(String, serde_json::Value, chrono::DateTime<Utc>, Option<String>)> =
+        sqlx::query_as(
+            "SELECT event_type, event_data, created_at, created_by FROM schema_events WHERE schema_id = $1 ORDER BY created_at, id",
+        )
+        .bind(schema_id)
+        .fetch_all(&state.db)
+        .await?;
+    let lifecycle: Vec<LifecycleEvent> = rows
+        .into_iter()
+        .map(|(event_type, details, at, actor)| LifecycleEvent {
+            event_type,
+            actor,
+            at,
+            details,
+        })
+        .collect();
+
+    let registration = lifecycle
+        .iter()
+        .find(|e| e.event_type == SCHEMA_EVENT_REGISTERED)
+        .and_then(|e| {
+            let trail = serde_json::from_value::<RegistrationTrail>(e.details.clone()).ok()?;
+            Some((trail, e.at))
+        });
+    let mut approvals = Vec::new();
+    let gates = match registration {
+        Some((trail, registered_at)) => {
+            if let Some(freeze) = trail.freeze_override {
+                approvals.push(Approval {
+                    kind: "change_freeze_override",
+                    approved_by: freeze.approved_by,
+                    decision: "approved",
+                    note: Some(format!("{}: {}", freeze.window, freeze.reason)),
+                    at: Some(registered_at),
+                });
+            }
+            trail.gates
+        }
+        None => Vec::new(),
+    };
+
+    if let Some(record) = state.quarantine.get(&schema_id.to_string()).await {
+        let decision = match record.status {
+            QuarantineStatus::Cleared => Some("approved"),
+            QuarantineStatus::Rejected => Some("rejected"),
+            QuarantineStatus::Pending => None,
+        };
+        if let (Some(decision), Some(reviewer)) = (decision, record.reviewed_by) {
+            approvals.push(Approval {
+                kind: "security_review",
+                approved_by: reviewer,
+                decision,
+                note: record.review_note,
+                at: record
+                    .reviewed_at
+                    .and_then(|t| chrono::DateTime::from_timestamp(t as i64, 0)),
+            });
+        }
+    }
+
+    let audit_events = state
+        .audit_logger
+        .get_events(AuditEventFilter {
+            resource_id: Some(schema_id.to_string()),
+            ..Default::default()
+        })
+        .await;
+    // Exemptions used to register the version are stored with it; ones
+    // applied to checks of it are only in the audit log
+    let exemption_ids: BTreeSet<Uuid> = lifecycle
+        .iter()
+        .filter(|e| e.event_type == SCHEMA_EVENT_EXEMPTION_APPLIED)
+        .filter_map(|e| e.details.get("exemptions"))
+        .chain(
+            audit_events
+                .iter()
+                .filter(|e| e.event_type == AuditEventType::CompatibilityExemptionApplied)
+                .filter_map(|e| e.metadata.get("exemptions")),
+        )
+        .filter_map(|ids| serde_json::from_value::<Vec<Uuid>>(ids.clone()).ok())
+        .flatten()
+        .collect();
+    for exemption_id in exemption_ids {
+        let exemption = state
+            .exemptions
+            .get(exemption_id)
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+        if let Some(exemption) = exemption {
+            approvals.push(Approval {
+                kind: "compatibility_exemption",
+                approved_by: exemption.granted_by,
+                decision: "approved",
+                note: Some(exemption.reason),
+                at: Some(exemption.granted_at),
+            });
+        }
+    }
+
+    let compatibility_reports = state
+        .compat_history
+        .query(&CompatibilityHistoryQuery {
+            subject: Some(subject.clone()),
+            limit: Some(MAX_HISTORY_LIMIT),
+            ..Default::default()
+        })
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+        .into_iter()
+        .filter(|r| r.schema_id == schema_id)
+        .collect();
+
+    let provenance = ProvenanceDocument {
+        schema_id,
+        subject,
+        version: version.to_string(),
+        format,
+        content_hash,
+        state: schema_state,
+        created_at,
+        created_by,
+        lifecycle,
+        gates,
+        approvals,
+        compatibility_reports,
+        audit_events,
+        generated_at: Utc::now(),
+    };
+    let signature = signer
+        .sign(&provenance)
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    tracing::info!(
+        schema_id = %schema_id,
+        admin = %admin,
+        key_id = %signature.key_id,
+        "Exported schema provenance"
+    );
+
+    Ok(Json(SignedProvenance {
+        provenance,
+        signature,
+    }))
+}
+
+/// Namespace replays are nested under unless the request names another
+const DEFAULT_REPLAY_NAMESPACE: &str = "replay";
+/// Metadata key marking the versions a replay wrote, with the event replayed
+const REPLAY_METADATA_KEY: &str = "replay_of";
+
+#[derive(Debug, Default, Deserialize)]
+struct ReplayRequest {
+    #[serde(default)]
+    sandbox_namespace: Option<String>,
+    /// Leave the sandbox subject in place to inspect it
+    #[serde(default)]
+    keep: bool,
+}
+
+/// How one run of a registration ended
+#[derive(Debug, Default, Serialize)]
+struct ReplayOutcome {
+    /// Status the registration answered with
+    status: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    schema_id: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    state: Option<String>,
+    /// `None` when the version's decision trail is gone
+    #[serde(skip_serializing_if = "Option::is_none")]
+    gates: Option<Vec<GateResult>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ReplayReport {
+    event_id: String,
+    subject: String,
+    sandbox_subject: String,
+    /// Versions the subject had before the original registration, copied
+    /// into the sandbox first
+    versions_copied: u64,
+    original: ReplayOutcome,
+    replay: ReplayOutcome,
+    /// Where the replay departed from the original, empty if it didn't
+    differences: Vec<String>,
+    kept: bool,
+}
+
+/// Replay an audited registration against a sandbox namespace
+///
+/// The request recorded with the audit event is registered again as
+/// `<sandbox>.<subject>`, on top of copies of the versions the subject had
+/// when it first ran, and the two runs are compared gate by gate. The
+/// sandbox's namespace settings and freeze windows apply rather than the
+/// original's, and secrets redacted from the recorded request stay redacted.
+/// The sandbox subject is removed afterwards unless `keep` is set.
+pub(crate) async fn replay_audit_event(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(event_id): Path<String>,
+    BoundedJson(req): BoundedJson<ReplayRequest>,
+) -> Result<Json<ReplayReport>, AppError> {
+    let admin = require_admin(&state, &headers)?;
+    let event = state
+        .audit_logger
+        .find(&event_id)
+        .await
+        .ok_or_else(|| AppError::NotFound(format!("Audit event {} not found", event_id)))?;
+    if event.event_type != AuditEventType::SchemaRegistered {
+        return Err(AppError::InvalidInput(format!(
+            "Audit event {} records {:?}; only schema registrations can be replayed",
+            event_id, event.event_type
+        )));
+    }
+    let payload = event.request_payload().cloned().ok_or_else(|| {
+        AppError::InvalidInput(format!(
+            "Audit event {} recorded no request to replay",
+            event_id
+        ))
+    })?;
+    let mut request: RegisterSchemaRequest = serde_json::from_value(payload).map_err(|e| {
+        AppError::InvalidInput(format!(
+            "Audit event {} recorded an unreadable request: {}",
+            event_id, e
+        ))
+    })?;
+
+    let sandbox = req
+        .sandbox_namespace
+        .as_deref()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .unwrap_or(DEFAULT_REPLAY_NAMESPACE);
+    if sandbox.split('.').any(str::is_empty) {
+        return Err(AppError::InvalidInput(format!(
+            "Invalid sandbox namespace '{}'",
+            sandbox
+        )));
+    }
+
+    // The original version tells where the subject lived and what it
+    // inherited, if it still exists
+    let original_id = event
+        .resource_id
+        .as_deref()
+        .and_then(|id| id.parse::<Uuid>().ok());
+    let original_row: Option<(String, String, String, chrono::DateTime<Utc>)> =
+        match original_id {
+            Some(id) => sqlx::query_as(
+                "SELECT namespace, name, compatibility_mode, created_at FROM schemas WHERE id = $1",
+            )
+            .bind(id)
+            .fetch_optional(&state.db)
+            .await?,
+            None => None,
+        };
+    let (namespace, name, registered_before) = match &original_row {
+        Some((namespace, name, mode, created_at)) => {
+            if request.compatibility_mode.is_none() {
+                request.compatibility_mode = mode.parse().ok();
+            }
+            (namespace.clone(), name.clone(), *created_at)
+        }
+        None => {
+            let (namespace, name) = split_subject(&request.subject);
+            let audited_at = chrono::DateTime::from_timestamp(event.timestamp as i64, 0)
+                .unwrap_or_else(Utc::now);
+            (namespace, name, audited_at)
+        }
+    };
+    let subject = subject_of(&namespace, &name);
+    let sandbox_namespace = format!("{}.{}", sandbox, namespace);
+    let sandbox_subject = subject_of(&sandbox_namespace, &name);
+
+    clear_replay_sandbox(&state, &sandbox_namespace, &name).await?;
+    let versions_copied = sqlx::query(
+        r#"
+        INSERT INTO schemas (
+            id, namespace, name, version_major, version_minor, version_patch,
+            format, content, content_hash, state, compatibility_mode,
+            created_at, updated_at, created_by, description, metadata, tags,
+            size_bytes, field_count, max_depth, constraint_count
+        )
+        SELECT
+            gen_random_uuid(), $1, name, version_major, version_minor, version_patch,
+            format, content, content_hash, state, compatibility_mode,
+            created_at, updated_at, created_by, description,
+            COALESCE(metadata, '{}'::jsonb) || $2, tags,
+            size_bytes, field_count, max_depth, constraint_count
+        FROM schemas
+        WHERE namespace = $3 AND name = $4 AND created_at < $5 AND id IS DISTINCT FROM $6
+        "#,
+    )
+    .bind(&sandbox_namespace)
+    .bind(serde_json::json!({ REPLAY_METADATA_KEY: event_id }))
+    .bind(&namespace)
+    .bind(&name)
+    .bind(registered_before)
+    .bind(original_id)
+    .execute(&state.db)
+    .await?
+    .rows_affected();
+
+    let original = ReplayOutcome {
+        status: StatusCode::CREATED.as_u16(),
+        schema_id: original_id,
+        version: event
+            .metadata
+            .get("version")
+            .and_then(|v| v.as_str())
+            .map(str::to_string),
+        state: event
+            .metadata
+            .get("state")
+            .and_then(|v| v.as_str())
+            .map(str::to_string),
+        gates: match original_id {
+            Some(id) => registration_trail(&state, id).await?.map(|t| t.gates),
+            None => None,
+        },
+        error: None,
+    };
+
+    request.subject = sandbox_subject.clone();
+    request
+        .metadata
+        .insert(REPLAY_METADATA_KEY.to_string(), serde_json::json!(event_id));
+    let replay = match register_schema(
+        State(state.clone()),
+        headers,
+        None,
+        None,
+        BoundedJson(request),
+    )
+    .await
+    {
+        Ok((status, _, Json(response))) => {
+            let trail = registration_trail(&state, response.id).await?;
+            ReplayOutcome {
+                status: status.as_u16(),
+                schema_id: Some(response.id),
+                version: Some(response.version),
+                state: trail.as_ref().map(|t| t.state.clone()),
+                gates: trail.map(|t| t.gates),
+                error: None,
+            }
+        }
+        Err(e) => {
+            let error = e.to_string();
+            ReplayOutcome {
+                status: e.into_response().status().as_u16(),
+                error: Some(error),
+                ..Default::default()
+            }
+        }
+    };
+    let differences = replay_differences(&original, &replay);
+
+    if !req.keep {
+        clear_replay_sandbox(&state, &sandbox_namespace, &name).await?;
+    }
+
+    let event = AuditEvent::new(
+        AuditEventType::RegistrationReplayed,
+        "Registration replayed".to_string(),
+        AuditResult::Success,
+        String::new(),
+    )
+    .with_user(admin.clone(), None)
+    .with_resource("audit_event".to_string(), event_id.clone())
+    .with_metadata("subject".to_string(), serde_json::json!(subject))
+    .with_metadata(
+        "sandbox_subject".to_string(),
+        serde_json::json!(sandbox_subject),
+    )
+    .with_metadata(
+        "differences".to_string(),
+        serde_json::json!(differences.len()),
+    );
+    state.audit_logger.log(event).await;
+
+    tracing::info!(
+        event_id = %event_id,
+        admin = %admin,
+        sandbox = %sandbox_subject,
+        differences = differences.len(),
+        "Replayed registration"
+    );
+
+    Ok(Json(ReplayReport {
+        event_id,
+        subject,
+        sandbox_subject,
+        versions_copied,
+        original,
+        replay,
+        differences,
+        kept: req.keep,
+    }))
+}
+
+/// The `REGISTERED` trail of a version
+async fn registration_trail(
+    state: &AppState,
+    schema_id: Uuid,
+) -> Result<Option<RegistrationTrail>, AppError> {
+    let row: Option<(serde_json::Value,)> = sqlx::query_as(
+        "SELECT event_data FROM schema_events WHERE schema_id = $1 AND event_type = $2 ORDER BY created_at, id LIMIT 1",
+    )
+    .bind(schema_id)
+    .bind(SCHEMA_EVENT_REGISTERED)
+    .fetch_optional(&state.db)
+    .await?;
+    Ok(row.and_then(|(data,)| serde_json::from_value(data).ok()))
+}
+
+/// Remove a sandbox subject left by an earlier replay
+///
+/// Refuses to touch a subject holding any version a replay didn't write, so
+/// a sandbox namespace that collides with real subjects loses nothing.
+async fn clear_replay_sandbox(
+    state: &AppState,
+    namespace: &str,
+    name: &str,
+) -> Result<(), AppError> {
+    let mut tx = state.db.begin().await?;
+    let (foreign,): (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM schemas WHERE namespace = $1 AND name = $2 AND NOT (COALESCE(metadata, '{}'::jsonb) ? $3)",
+    )
+    .bind(namespace)
+    .bind(name)
+    .bind(REPLAY_METADATA_KEY)
+    .fetch_one(&mut *tx)
+    .await?;
+    if foreign > 0 {
+        return Err(AppError::Conflict(format!(
+            "Subject {} holds versions not written by a replay; choose another sandbox namespace",
+            subject_of(namespace, name)
+        )));
+    }
+    let removed: Vec<(Uuid,)> =
+        sqlx::query_as("DELETE FROM schemas WHERE namespace = $1 AND name = $2 RETURNING id")
+            .bind(namespace)
+            .bind(name)
+            .fetch_all(&mut *tx)
+            .await?;
+    // Numbering starts over with the copies of the next replay
+    sqlx::query("DELETE FROM subject_version_sequences WHERE namespace = $1 AND name = $2")
+        .bind(namespace)
+        .bind(name)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    if removed.is_empty() {
+        return Ok(());
+    }
+
+    let mut del = redis::cmd("DEL");
+    for (id,) in &removed {
+        del.arg(format!("{}{}", SCHEMA_CACHE_PREFIX, id));
+        state.quarantine.forget(&id.to_string()).await;
+        state.validator.invalidate_instance(*id).await;
+    }
+    let evicted: Result<(), redis::RedisError> = match state.cache() {
+        Some(conn) => {
+            state
+                .retry_policy("cache_evict")
+                .retry(|| {
+                    let mut conn = conn.clone();
+                    let del = &del;
+                    async move { del.query_async(&mut conn).await }
+                })
+                .await
+        }
+        None => Ok(()),
+    };
+    if let Err(e) = evicted {
+        tracing::warn!(error = %e, "Failed to evict replayed schemas from the cache");
+    }
+    Ok(())
+}
+
+/// Where a replay departed from the original run, in plain words
+fn replay_differences(original: &ReplayOutcome, replay: &ReplayOutcome) -> Vec<String> {
+    if let Some(error) = &replay.error {
+        return vec![format!(
+            "Registered originally, rejected on replay with {}: {}",
+            replay.status, error
+        )];
+    }
+
+    let mut differences = Vec::new();
+    if original.status != replay.status {
+        differences.push(format!(
+            "Answered {} originally, {} on replay",
+            original.status, replay.status
+        ));
+    }
+    for (what, before, after) in [
+        ("Version", &original.version, &replay.version),
+        ("State", &original.state, &replay.state),
+    ] {
+        if let (Some(before), Some(after)) = (before, after) {
+            if before != after {
+                differences.push(format!(
+                    "{} was {} originally, {} on replay",
+                    what, before, after
+                ));
+            }
+        }
+    }
+
+    let (Some(before), Some(after)) = (&original.gates, &replay.gates) else {
+        return differences;
+    };
+    for gate in before {
+        match after.iter().find(|g| g.gate == gate.gate) {
+            None => differences.push(format!("Gate '{}' did not run on replay", gate.gate)),
+            Some(replayed) if replayed.outcome != gate.outcome => differences.push(format!(
+                "Gate '{}' {} originally, {} on replay",
+                gate.gate,
+                gate.outcome.as_str(),
+                replayed.outcome.as_str()
+            )),
+            Some(replayed) if replayed.details != gate.details => differences.push(format!(
+                "Gate '{}' reported [{}] originally, [{}] on replay",
+                gate.gate,
+                gate.details.join("; "),
+                replayed.details.join("; ")
+            )),
+            Some(_) => {}
+        }
+    }
+    for gate in after {
+        if !before.iter().any(|g| g.gate == gate.gate) {
+            differences.push(format!("Gate '{}' only ran on replay", gate.gate));
+        }
+    }
+    differences
+}
@@ -0,0 +1,217 @@
+//! Public API
+//!
+//! Unauthenticated or API-key reads of the namespaces marked public, rate
+//! limited per client and cached.
+
+use super::*;
+use crate::middleware::rate_limiter::{RateLimitConfig, RateLimiter};
+
+/// Requests per minute one client may make when no limit is configured
+pub(crate) const DEFAULT_PUBLIC_RATE_LIMIT: usize = 60;
+
+/// Seconds public responses may be cached when no TTL is configured
+pub(crate) const DEFAULT_PUBLIC_CACHE_TTL_SECS: u64 = 60;
+
+const PUBLIC_RATE_LIMIT_WINDOW: Duration = Duration::from_secs(60);
+
+/// The read-only API that serves public namespaces to external partners
+pub(crate) struct PublicApi {
+    /// Keys partners send as `X-API-Key`; empty lets anyone read
+    pub(crate) tokens: HashSet<String>,
+    /// Proxies whose `X-Forwarded-For` names the client
+    pub(crate) trusted_proxies: Vec<IpNet>,
+    pub(crate) limiter: RateLimiter,
+    /// How long the registry and HTTP caches keep public responses
+    pub(crate) cache_ttl: Duration,
+}
+
+impl PublicApi {
+    pub(crate) fn new(
+        tokens: Vec<String>,
+        trusted_proxies: Vec<IpNet>,
+        rate_limit: usize,
+        cache_ttl: Duration,
+    ) -> anyhow::Result<Self> {
+        anyhow::ensure!(rate_limit > 0, "The public API rate limit must be at least 1");
+        Ok(Self {
+            tokens: tokens.into_iter().filter(|token| !token.is_empty()).collect(),
+            trusted_proxies,
+            limiter: RateLimiter::new(RateLimitConfig {
+                max_requests: rate_limit,
+                window_duration: PUBLIC_RATE_LIMIT_WINDOW,
+                adaptive: false,
+                burst_size: rate_limit,
+                ..Default::default()
+            }),
+            cache_ttl,
+        })
+    }
+}
+
+/// Forget clients that have been quiet for a while
+pub(crate) fn spawn_public_rate_limit_cleanup(public_api: Arc<PublicApi>) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(PUBLIC_RATE_LIMIT_WINDOW);
+        loop {
+            ticker.tick().await;
+            public_api.limiter.cleanup_old_states().await;
+        }
+    });
+}
+
+/// Check the partner key and rate limit, and mark responses cacheable
+///
+/// Clients are limited by API key when keys are required, and otherwise by
+/// their address, read from `X-Forwarded-For` only when a trusted proxy
+/// connected.
+pub(crate) async fn public_api_guard(
+    State(state): State<AppState>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let public_api = &state.public_api;
+    let rejected = |outcome: &str, error: AppError| {
+        PUBLIC_API_REQUESTS.with_label_values(&[outcome]).inc();
+        error.into_response()
+    };
+
+    let client = if public_api.tokens.is_empty() {
+        // Keys mean nothing here, so they mustn't buy a fresh rate limit
+        request.headers_mut().remove("x-api-key");
+        match RateLimiter::client_address(&request, &public_api.trusted_proxies) {
+            Some(addr) => format!("ip:{}", addr),
+            None => "ip:unknown".to_string(),
+        }
+    } else {
+        let key = request.headers().get("x-api-key").and_then(|v| v.to_str().ok());
+        match key.filter(|key| public_api.tokens.contains(*key)) {
+            Some(key) => format!("api_key:{}", key),
+            None => {
+                return rejected(
+                    "unauthorized",
+                    AppError::Unauthorized("A valid X-API-Key is required".to_string()),
+                );
+            }
+        }
+    };
+    if public_api.limiter.check_client(client).await.is_err() {
+        return rejected("rate_limited", AppError::RateLimited("Rate limit exceeded".to_string()));
+    }
+
+    let mut response = next.run(request).await;
+    let outcome = match response.status() {
+        status if status.is_success() => "ok",
+        StatusCode::NOT_FOUND => "not_found",
+        _ => "error",
+    };
+    PUBLIC_API_REQUESTS.with_label_values(&[outcome]).inc();
+
+    if response.status().is_success() {
+        // Shared caches must not hand responses to callers without a key
+        let scope = if public_api.tokens.is_empty() { "public" } else { "private" };
+        let cache_control = format!("{}, max-age={}", scope, public_api.cache_ttl.as_secs());
+        if let Ok(value) = HeaderValue::from_str(&cache_control) {
+            response.headers_mut().insert("cache-control", value);
+        }
+    }
+    response
+}
+
+/// A schema in a public namespace
+///
+/// Private, deleted and quarantined schemas all look like missing ones.
+pub(crate) async fn public_get_schema(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<FieldsQuery>,
+) -> Result<Response, AppError> {
+    let not_found = || AppError::NotFound(format!("Schema {} not found", id));
+    let schema = fetch_schema(&state, id).await.map_err(|e| match e {
+        AppError::Gone(_) | AppError::Quarantined(_) => not_found(),
+        e => e,
+    })?;
+    if !state.namespaces.is_public(&schema.namespace) {
+        return Err(not_found());
+    }
+    query.respond(schema, SCHEMA_FIELDS)
+}
+
+/// Live versions of a subject in a public namespace
+pub(crate) async fn public_list_subject_versions(
+    State(state): State<AppState>,
+    Path(subject): Path<String>,
+) -> Result<Json<SubjectVersionsResponse>, AppError> {
+    let not_found = || AppError::NotFound(format!("Subject {} not found", subject));
+    if !state.namespaces.is_public(&split_subject(&subject).0) {
+        return Err(not_found());
+    }
+    let Json(mut response) = list_subject_versions(State(state.clone()), Path(subject.clone())).await?;
+
+    // The subject may be an alias of one in a private namespace
+    if !state.namespaces.is_public(&split_subject(&response.subject).0) {
+        return Err(not_found());
+    }
+    response
+        .aliases
+        .retain(|alias| state.namespaces.is_public(&split_subject(alias).0));
+    response
+        .versions
+        .retain(|version| !matches!(version.state.as_str(), "DELETED" | "QUARANTINED"));
+    if response.versions.is_empty() {
+        return Err(not_found());
+    }
+    Ok(Json(response))
+}
+
+/// Keyword search over public namespaces
+///
+/// Results are cached for the public cache TTL, keyed by the request and
+/// the visibility rules, so making a namespace private takes effect at once.
+pub(crate) async fn public_search_schemas(
+    State(state): State<AppState>,
+    BoundedJson(req): BoundedJson<SearchSchemasRequest>,
+) -> Result<Response, AppError> {
+    let rules = state.namespaces.visibility_rules();
+    if !rules.values().any(|visibility| *visibility == Visibility::Public) {
+        return Ok(Json(SearchSchemasResponse { results: Vec::new(), total: 0 }).into_response());
+    }
+
+    use sha2::{Digest, Sha256};
+    let cache_ttl = state.public_api.cache_ttl.as_secs();
+    let fingerprint = serde_json::to_vec(&(&rules, &req)).map_err(|e| AppError::Internal(e.to_string()))?;
+    let cache_key = format!("public:search:{}", hex::encode(Sha256::digest(&fingerprint)));
+    let mut cache = state.cache().filter(|_| cache_ttl > 0);
+    if let Some(conn) = &mut cache {
+        if let Ok(Some(cached)) = redis::cmd("GET")
+            .arg(&cache_key)
+            .query_async::<_, Option<String>>(conn)
+            .await
+        {
+            return Ok(([("content-type", "application/json")], cached).into_response());
+        }
+    }
+
+    let mut response = search(&state, &req, Some(&rules)).await?;
+    // The SQL filter already applies the rules; this guards against it drifting
+    let before = response.results.len();
+    response
+        .results
+        .retain(|result| state.namespaces.is_public(&result.metadata.namespace));
+    let leaked = before - response.results.len();
+    if leaked > 0 {
+        tracing::error!(count = leaked, "Public search returned schemas from private namespaces");
+        response.total = response.total.saturating_sub(leaked as u64);
+    }
+
+    let body = serde_json::to_string(&response).map_err(|e| AppError::Internal(e.to_string()))?;
+    if let Some(conn) = &mut cache {
+        let _: Result<(), _> = redis::cmd("SET")
+            .arg(&cache_key)
+            .arg(&body)
+            .arg("EX")
+            .arg(cache_ttl)
+            .query_async(conn)
+            .await;
+    }
+    Ok(([("content-type", "application/json")], body).into_response())
+}
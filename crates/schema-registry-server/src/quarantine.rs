@@ -0,0 +1,124 @@
+//! Security review of quarantined versions
+
+use super::*;
+use schema_registry_security::quarantine::QuarantineError;
+
+#[derive(Debug, Deserialize)]
+struct QuarantineReleaseRequest {
+    /// What the review found, kept with the release and audited
+    note: Option<String>,
+}
+
+/// Release a quarantined version once security review finds it safe
+///
+/// Needs the `security:review` permission: the admin token, or a scoped or
+/// impersonation token granting it. The version returns to `DRAFT`, as if
+/// its registration had passed the security scan, and the release is
+/// audited with the reviewer and note.
+pub(crate) async fn release_quarantined_schema(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    impersonation: Option<Extension<Impersonation>>,
+    scoped: Option<Extension<ScopedToken>>,
+    Path(id): Path<Uuid>,
+    BoundedJson(req): BoundedJson<QuarantineReleaseRequest>,
+) -> Result<Json<StateChangeResponse>, AppError> {
+    let actor = ChangeActor::from_request(
+        &state,
+        &headers,
+        impersonation.as_ref().map(|Extension(i)| i),
+        scoped.as_ref().map(|Extension(s)| s),
+    )?;
+    actor.ensure_authenticated()?;
+    state
+        .quarantine
+        .authorize(&id.to_string(), &actor.principal, &actor.permissions)
+        .await
+        .map_err(|e| AppError::Forbidden(e.to_string()))?;
+    let note = req
+        .note
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty());
+
+    let mut tx = state.db.begin().await?;
+    let row: Option<(String, String, String, chrono::DateTime<Utc>)> = sqlx::query_as(
+        "SELECT namespace, name, state, created_at FROM schemas WHERE id = $1 FOR UPDATE",
+    )
+    .bind(id)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let (namespace, name, stored, created_at) =
+        row.ok_or_else(|| AppError::NotFound(format!("Schema {} not found", id)))?;
+    let quarantined = SchemaState::Quarantined.to_string();
+    if stored != quarantined {
+        return Err(AppError::Conflict(format!(
+            "Schema {} is not quarantined",
+            id
+        )));
+    }
+    ensure_unfrozen(&state, &actor, &namespace, "release_quarantine").await?;
+
+    let reviewer = actor.principal.clone();
+    let subject = subject_of(&namespace, &name);
+    let released = SchemaState::Draft.to_string();
+    sqlx::query("UPDATE schemas SET state = $1 WHERE id = $2")
+        .bind(&released)
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+    let details = serde_json::json!({
+        "previous_state": stored,
+        "state": released,
+        "note": note,
+    });
+    record_schema_event(&mut *tx, id, SCHEMA_EVENT_STATE_CHANGED, &details, &reviewer).await?;
+    tx.commit().await?;
+
+    // Audits the release and tells the security team
+    state
+        .quarantine
+        .release(
+            &id.to_string(),
+            &subject,
+            u64::try_from(created_at.timestamp()).unwrap_or_default(),
+            &reviewer,
+            &actor.permissions,
+            note,
+        )
+        .await
+        .map_err(|e| match e {
+            QuarantineError::MissingPermission(_) => AppError::Forbidden(e.to_string()),
+            _ => AppError::Conflict(e.to_string()),
+        })?;
+
+    let evicted: Result<(), redis::RedisError> = match state.cache() {
+        Some(conn) => {
+            state
+                .retry_policy("cache_evict")
+                .retry(|| {
+                    let mut conn = conn.clone();
+                    async move {
+                        redis::cmd("DEL")
+                            .arg(format!("{}{}", SCHEMA_CACHE_PREFIX, id))
+                            .query_async(&mut conn)
+                            .await
+                    }
+                })
+                .await
+        }
+        None => Ok(()),
+    };
+    if let Err(e) = evicted {
+        tracing::warn!(schema_id = %id, error = %e, "Failed to evict schema from the cache after its release");
+    }
+
+    tracing::info!(schema_id = %id, subject = %subject, reviewer = %reviewer, "Schema released from quarantine");
+
+    Ok(Json(StateChangeResponse {
+        id,
+        subject,
+        previous_state: stored,
+        state: released,
+        next_states: settable_next_states(SchemaState::Registered),
+    }))
+}
@@ -0,0 +1,1007 @@
+//! Schema registration
+//!
+//! The gates a new version goes through before it is stored, and the
+//! commit that stores, caches, audits and announces it.
+
+use super::*;
+use chrono::SubsecRound;
+use schema_registry_core::plugin::RegistrationContext;
+use schema_registry_core::schema::SchemaInput;
+use schema_registry_core::subject_config::NORMALIZE_POLICY;
+use schema_registry_storage::postgres::PostgresStorage;
+use schema_registry_validation::types::Severity as RuleSeverity;
+
+use crate::confluent_api::subject_compatibility;
+use crate::consumer_webhooks::RegisteredVersion;
+use crate::metrics::REGISTRATION_CONFLICTS;
+
+/// Find the row a registration's insert collided with: the version it asked
+/// for, or else the version that already has its content
+async fn stored_registration(
+    state: &AppState,
+    namespace: &str,
+    name: &str,
+    (major, minor, patch): (i32, i32, i32),
+    content_hash: &str,
+) -> Result<Option<StoredRegistration>, AppError> {
+    let row: Option<(Uuid, i64, i32, i32, i32, String, chrono::DateTime<Utc>)> = sqlx::query_as(
+        r#"
+        SELECT id, global_id, version_major, version_minor, version_patch, content_hash,
+               created_at
+        FROM schemas
+        WHERE namespace = $1 AND name = $2
+          AND ((version_major = $3 AND version_minor = $4 AND version_patch = $5)
+               OR (content_hash = $6 AND state <> 'DELETED'))
+        ORDER BY (version_major = $3 AND version_minor = $4 AND version_patch = $5) DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(namespace)
+    .bind(name)
+    .bind(major)
+    .bind(minor)
+    .bind(patch)
+    .bind(content_hash)
+    .fetch_optional(&state.db)
+    .await?;
+    Ok(row.map(
+        |(id, global_id, major, minor, patch, hash, created_at)| StoredRegistration {
+            id,
+            global_id,
+            subject: subject_of(namespace, name),
+            version: format!("{}.{}.{}", major, minor, patch),
+            created_at: created_at.to_rfc3339(),
+            same_content: hash.trim_end() == content_hash,
+        },
+    ))
+}
+
+pub(crate) async fn register_schema(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    impersonation: Option<Extension<Impersonation>>,
+    scoped: Option<Extension<ScopedToken>>,
+    BoundedJson(mut req): BoundedJson<RegisterSchemaRequest>,
+) -> Result<Registered, AppError> {
+    // Kept for the audit trail, so the registration can be replayed later
+    let request_payload = serde_json::to_value(&req).unwrap_or_default();
+    let (namespace, name) = split_subject(&req.subject);
+    let (namespace, name, alias_warning) = resolve_subject(&state, namespace, name).await?;
+    ensure_namespace_allowed(&namespace)?;
+    let actor = ChangeActor::from_request(
+        &state,
+        &headers,
+        impersonation.as_ref().map(|Extension(i)| i),
+        scoped.as_ref().map(|Extension(s)| s),
+    )?;
+    let freeze = ensure_unfrozen(&state, &actor, &namespace, "register_schema").await?;
+    let mut warnings: Vec<String> = alias_warning.into_iter().collect();
+    let mut gates = vec![match &freeze {
+        None => GateResult::passed("change_freeze"),
+        Some(freeze) => GateResult {
+            gate: "change_freeze".to_string(),
+            outcome: GateOutcome::Overridden,
+            details: vec![format!("Freeze window '{}' overridden", freeze.window)],
+        },
+    }];
+
+    // Convert schema to content string
+    let content = req.content.clone().unwrap_or_else(|| {
+        serde_json::to_string(&req.schema).unwrap_or_else(|_| "{}".to_string())
+    });
+    if content.len() > state.max_schema_size {
+        let limit = SizeLimitExceeded::schema(state.max_schema_size, content.len());
+        return Err(AppError::TooLarge(limit));
+    }
+
+    // Normalize format/schema_type, detecting it when neither is given
+    let format = match req.format.clone() {
+        Some(format) => format,
+        None if req.schema_type.is_empty() => {
+            let detection = detect_schema_format(&content)?;
+            warnings.push(format!(
+                "schema_type not given; detected {} (confidence {:.2})",
+                detection.format.as_str(),
+                detection.confidence
+            ));
+            schema_type_name(detection.format.schema_format()).to_string()
+        }
+        None => match req.schema_type.to_uppercase().as_str() {
+            "JSON" => "JSON".to_string(),
+            "AVRO" => "AVRO".to_string(),
+            "PROTOBUF" => "PROTOBUF".to_string(),
+            _ => "JSON".to_string(),
+        },
+    };
+
+    // Raw content bypasses the body extractor's limits, so check it separately
+    if format != "PROTOBUF" {
+        bounded_json::check(content.as_bytes(), &state.json_limits)?;
+    }
+
+    let serialization_format = match format.as_str() {
+        "AVRO" => SerializationFormat::Avro,
+        "PROTOBUF" => SerializationFormat::Protobuf,
+        _ => SerializationFormat::JsonSchema,
+    };
+
+    // Hash the canonical form, so reordered keys or reformatted content
+    // match the version already registered
+    let content_hash = canonical_form::content_hash(serialization_format, &content);
+
+    // Without an explicit version, take the subject's next major version. The
+    // checks below run against the number it would get now; the commit task
+    // allocates it on the inserting transaction, so the subject's sequence
+    // row is locked only while the schema is stored, not during the scans,
+    // and checks the version again if another registration took the number.
+    let auto_version =
+        req.version_major.is_none() && req.version_minor.is_none() && req.version_patch.is_none();
+    let (version_major, version_minor, version_patch) = if auto_version {
+        let mut conn = state.db.acquire().await?;
+        let major = PostgresStorage::next_version(&mut conn, &namespace, &name)
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+        (major as i32, 0, 0)
+    } else {
+        (
+            req.version_major.unwrap_or(1),
+            req.version_minor.unwrap_or(0),
+            req.version_patch.unwrap_or(0),
+        )
+    };
+
+    tracing::info!(
+        subject = %req.subject,
+        namespace = %namespace,
+        name = %name,
+        version = %format!("{}.{}.{}", version_major, version_minor, version_patch),
+        "Registering schema"
+    );
+
+    // Check if the schema already exists. Auto-versioned content is matched by
+    // hash, skipping tombstones so deleted content registers as a new version;
+    // a concurrent registration of the same content is caught by the insert.
+    // An explicit version is matched by number, and is only the same
+    // registration if its content is too.
+    let existing: Option<(Uuid, i64, i32, i32, i32, String, String, chrono::DateTime<Utc>)> =
+        if auto_version {
+            sqlx::query_as(
+                "SELECT id, global_id, version_major, version_minor, version_patch, state, content_hash, created_at FROM schemas WHERE namespace = $1 AND name = $2 AND content_hash = $3 AND state <> 'DELETED'"
+            )
+            .bind(&namespace)
+            .bind(&name)
+            .bind(&content_hash)
+            .fetch_optional(&state.db)
+            .await?
+        } else {
+            sqlx::query_as(
+                "SELECT id, global_id, version_major, version_minor, version_patch, state, content_hash, created_at FROM schemas WHERE namespace = $1 AND name = $2 AND version_major = $3 AND version_minor = $4 AND version_patch = $5"
+            )
+            .bind(&namespace)
+            .bind(&name)
+            .bind(version_major)
+            .bind(version_minor)
+            .bind(version_patch)
+            .fetch_optional(&state.db)
+            .await?
+        };
+
+    if let Some((existing_id, global_id, major, minor, patch, existing_state, hash, created_at)) =
+        existing
+    {
+        let version = format!("{}.{}.{}", major, minor, patch);
+        // Deleted version numbers are not reused
+        if existing_state == DELETED_STATE {
+            return Err(AppError::Conflict(format!(
+                "Version {} of {} was deleted",
+                version, req.subject
+            )));
+        }
+        // The same answer a registration gets when it loses the insert race
+        // for this version
+        if hash.trim_end() != content_hash {
+            return Err(AppError::RegistrationConflict(Box::new(StoredRegistration {
+                id: existing_id,
+                global_id,
+                subject: subject_of(&namespace, &name),
+                version,
+                created_at: created_at.to_rfc3339(),
+                same_content: false,
+            })));
+        }
+        return Ok(registration_response(
+            StatusCode::OK,
+            RegisterSchemaResponse {
+                id: existing_id,
+                global_id,
+                version,
+                created_at: created_at.to_rfc3339(),
+                warnings,
+            },
+        ));
+    }
+
+    // Apply settings inherited through the namespace hierarchy
+    let settings = state
+        .namespaces
+        .effective(&namespace)
+        .map_err(|e| AppError::InvalidInput(e.to_string()))?;
+    let compatibility_mode = req
+        .compatibility_mode
+        .unwrap_or_else(|| settings.compatibility_in(state.environment.as_deref()).mode);
+
+    // Namespaces with the `normalize` policy store the canonical form
+    let normalize = settings
+        .policies
+        .get(NORMALIZE_POLICY)
+        .is_some_and(|policy| policy.value == serde_json::Value::Bool(true));
+    let content = if normalize {
+        canonical_form::canonicalize(serialization_format, &content)
+            .map_err(|e| AppError::InvalidInput(e.to_string()))?
+    } else {
+        content
+    };
+    record_feature_use(
+        &state,
+        FeatureDimension::Format,
+        &serialization_format.to_string(),
+    );
+    record_feature_use(
+        &state,
+        FeatureDimension::CompatibilityMode,
+        &compatibility_mode.to_string(),
+    );
+
+    // Plugins may add metadata and tags or veto the registration
+    let mut plugin_input = SchemaInput {
+        name: name.clone(),
+        namespace: namespace.clone(),
+        format: serialization_format,
+        content: content.clone(),
+        description: req.description.clone().unwrap_or_default(),
+        compatibility_mode,
+        auto_activate: false,
+        version: Some(SemanticVersion::new(
+            version_major as u32,
+            version_minor as u32,
+            version_patch as u32,
+        )),
+        metadata: std::mem::take(&mut req.metadata),
+        tags: std::mem::take(&mut req.tags),
+        examples: Vec::new(),
+    };
+    let mut plugin_ctx = RegistrationContext::new(req.subject.clone());
+    plugin_ctx.principal = actor.authenticated_principal().map(str::to_string);
+    state
+        .interceptors
+        .run_pre(RegistrationStage::PreValidate, &mut plugin_input, &plugin_ctx)
+        .await
+        .map_err(AppError::Vetoed)?;
+    gates.push(GateResult::plugins(&state, RegistrationStage::PreValidate));
+
+    // Structural errors, such as a JSON Schema breaking its meta-schema
+    let validation = state
+        .validator
+        .validate_content(&content, serialization_format)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    if !validation.is_valid {
+        let errors: Vec<String> = validation
+            .errors
+            .iter()
+            .map(|e| match e.field_path.as_deref() {
+                Some(path) if !path.is_empty() => format!("{}: {}", path, e.message),
+                _ => e.message.clone(),
+            })
+            .collect();
+        return Err(AppError::InvalidInput(format!(
+            "Invalid schema: {}",
+            errors.join("; ")
+        )));
+    }
+    gates.push(GateResult::passed("validation"));
+
+    // Org rules loaded from VALIDATION_RULES_DIR; error findings block
+    // registration, the rest are returned as warnings
+    let scan_format = match format.as_str() {
+        "AVRO" => SchemaFormat::Avro,
+        "PROTOBUF" => SchemaFormat::Protobuf,
+        _ => SchemaFormat::JsonSchema,
+    };
+    let findings = state
+        .security_scanner
+        .check_custom_rules(&content, scan_format);
+    let describe = |finding: &RuleFinding| match &finding.location {
+        Some(location) => format!("{} at {}: {}", finding.rule, location, finding.message),
+        None => format!("{}: {}", finding.rule, finding.message),
+    };
+    let (blocking, advisory): (Vec<&RuleFinding>, Vec<&RuleFinding>) = findings
+        .iter()
+        .partition(|finding| finding.severity == RuleSeverity::Error);
+    if !blocking.is_empty() {
+        let messages: Vec<String> = blocking.into_iter().map(describe).collect();
+        return Err(AppError::InvalidInput(format!(
+            "Schema violates validation rules: {}",
+            messages.join("; ")
+        )));
+    }
+    let advisory: Vec<String> = advisory.into_iter().map(describe).collect();
+    gates.push(GateResult::flagged_if("custom_rules", advisory.clone()));
+    warnings.extend(advisory);
+
+    // How well a model can use the schema; advisory only
+    let lint: Vec<String> = state
+        .security_scanner
+        .lint_llm(&content, scan_format)
+        .into_iter()
+        .map(|warning| match warning.location {
+            Some(location) => format!("{} at {}: {}", warning.rule, location, warning.message),
+            None => format!("{}: {}", warning.rule, warning.message),
+        })
+        .collect();
+    gates.push(GateResult::flagged_if("llm_lint", lint.clone()));
+    warnings.extend(lint);
+
+    // The quota and compatibility checks run again once the commit holds the
+    // subject, against whatever registrations landed in the meantime; these
+    // runs fail fast before the scans below
+    let quotas = Quotas {
+        max_schemas: settings.max_schemas.value,
+        max_versions: settings.max_versions_per_schema.value,
+    };
+    quotas
+        .enforce(&mut *state.db.acquire().await?, &namespace, &name)
+        .await?;
+    gates.push(GateResult::passed("quota"));
+
+    // Breaking changes against the subject's earlier versions block the
+    // registration, unless an active exemption for the subject covers them.
+    // The subject's level applies, whatever mode the request stores.
+    let gate_mode = subject_compatibility(&state, &namespace, &name)?;
+    let candidate_version = SemanticVersion::new(
+        version_major as u32,
+        version_minor as u32,
+        version_patch as u32,
+    );
+    let previous =
+        compatibility_baseline(&state, (&namespace, &name), &candidate_version, gate_mode).await?;
+    let checked_against: Vec<Uuid> = previous.iter().map(|schema| schema.id).collect();
+    let (gate, mut exemption) = compatibility_gate(
+        &state,
+        (&namespace, &name),
+        (&format, &content),
+        candidate_version,
+        gate_mode,
+        &previous,
+    )
+    .await?;
+    gates.push(gate);
+
+    // Insert new schema
+    let id = state.id_generator.generate();
+    // At the precision Postgres stores, so a cached read and a database read
+    // return the same timestamps
+    let now = Utc::now().trunc_subsecs(6);
+
+    // Blocking pattern rules and rejected regexes fail registration; schemas
+    // with other suspicious findings are stored but held for review
+    let scan = state.security_scanner.scan_security(&content, scan_format).await;
+    if scan.has_errors() {
+        let errors: Vec<String> = scan.errors.iter().map(describe).collect();
+        return Err(AppError::InvalidInput(format!(
+            "Schema failed security validation: {}",
+            errors.join("; ")
+        )));
+    }
+    let scan_warnings: Vec<String> = scan
+        .warnings
+        .iter()
+        .map(|warning| match &warning.location {
+            Some(location) => format!("{} at {}: {}", warning.rule, location, warning.message),
+            None => format!("{}: {}", warning.rule, warning.message),
+        })
+        .collect();
+    warnings.extend(scan_warnings);
+
+    let complexity = SchemaComplexity::measure(&content, serialization_format);
+    let alerts = complexity_alerts(
+        &state,
+        &namespace,
+        &name,
+        (version_major, version_minor, version_patch),
+        &complexity,
+    )
+    .await?;
+    gates.push(GateResult::flagged_if("complexity", alerts.clone()));
+    warnings.extend(alerts);
+    gates.push(GateResult::flagged_if(
+        "security_scan",
+        scan.quarantine_reasons.clone(),
+    ));
+    // The quarantine is recorded, audited and announced to the security team
+    // once the version is stored, so a registration that fails from here on
+    // leaves nothing to review
+    let quarantine_reasons = scan
+        .requires_quarantine()
+        .then_some(scan.quarantine_reasons);
+    let schema_state = if quarantine_reasons.is_some() {
+        SchemaState::Quarantined.to_string()
+    } else {
+        req.state.clone()
+    };
+
+    plugin_ctx.schema_id = Some(id);
+    state
+        .interceptors
+        .run_pre(RegistrationStage::PreCommit, &mut plugin_input, &plugin_ctx)
+        .await
+        .map_err(AppError::Vetoed)?;
+    gates.push(GateResult::plugins(&state, RegistrationStage::PreCommit));
+
+    // Don't start writing for a client that has run out of time. Once started,
+    // the commit runs in its own task so a cancelled request can't stop it
+    // between the database and the cache.
+    if Deadline::current().is_some_and(|d| d.is_expired()) {
+        return Err(AppError::DeadlineExceeded(
+            "Request deadline exceeded before the schema was stored".to_string(),
+        ));
+    }
+    let description = req.description.take();
+    let registered_by = actor.principal.clone();
+    let mut registered = RegistrationTrail {
+        subject: req.subject.clone(),
+        version: format!("{}.{}.{}", version_major, version_minor, version_patch),
+        state: schema_state.clone(),
+        gates,
+        freeze_override: freeze.map(|freeze| FreezeOverride {
+            window: freeze.window,
+            reason: actor.override_reason.clone().unwrap_or_default(),
+            approved_by: actor.principal.clone(),
+        }),
+    };
+    let commit = {
+        let state = state.clone();
+        tokio::spawn(async move {
+            let mut tx = state.db.begin().await?;
+            // Registrations of one subject commit one at a time, so the
+            // checks below see every version stored before this one. A
+            // namespace quota counts the other subjects too, so it holds the
+            // namespace as well, always before the subject.
+            let mut locks = vec![format!("register:{}", subject_of(&namespace, &name))];
+            if quotas.max_schemas.is_some() {
+                locks.insert(0, format!("register-namespace:{}", namespace));
+            }
+            for lock in locks {
+                sqlx::query("SELECT pg_advisory_xact_lock(hashtextextended($1, 0))")
+                    .bind(lock)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+            let mut version_major = version_major;
+            if auto_version {
+                let major = PostgresStorage::allocate_version(&mut tx, &namespace, &name)
+                    .await
+                    .map_err(|e| AppError::Internal(e.to_string()))?
+                    as i32;
+                // Another registration of the subject took the number the
+                // checks ran against
+                if major != version_major {
+                    version_major = major;
+                    registered.version = format!("{}.0.0", major);
+                    plugin_input.version = Some(SemanticVersion::new(major as u32, 0, 0));
+                }
+            }
+            quotas.enforce(&mut tx, &namespace, &name).await?;
+            // Check again against the versions that landed since the gate ran
+            let version = SemanticVersion::new(
+                version_major as u32,
+                version_minor as u32,
+                version_patch as u32,
+            );
+            let previous =
+                compatibility_baseline(&state, (&namespace, &name), &version, gate_mode).await?;
+            if !previous.iter().map(|schema| schema.id).eq(checked_against) {
+                let (gate, trail) = compatibility_gate(
+                    &state,
+                    (&namespace, &name),
+                    (&format, &content),
+                    version,
+                    gate_mode,
+                    &previous,
+                )
+                .await?;
+                if let Some(checked) = registered
+                    .gates
+                    .iter_mut()
+                    .find(|checked| checked.gate == gate.gate)
+                {
+                    *checked = gate;
+                }
+                exemption = trail;
+            }
+            let insert = sqlx::query_as::<_, (i64,)>(
+                r#"
+                INSERT INTO schemas (
+                    id, namespace, name, version_major, version_minor, version_patch,
+                    format, content, content_hash, state, compatibility_mode,
+                    created_at, updated_at, created_by, description, metadata, tags,
+                    size_bytes, field_count, max_depth, constraint_count
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21)
+                RETURNING global_id
+                "#,
+            )
+            .bind(id)
+            .bind(&namespace)
+            .bind(&name)
+            .bind(version_major)
+            .bind(version_minor)
+            .bind(version_patch)
+            .bind(&format)
+            .bind(&content)
+            .bind(&content_hash)
+            .bind(&schema_state)
+            .bind(compatibility_mode.as_str())
+            .bind(now)
+            .bind(now)
+            .bind(&registered_by)
+            .bind(description.as_deref())
+            .bind(serde_json::to_value(&plugin_input.metadata).unwrap())
+            .bind(&plugin_input.tags)
+            .bind(complexity.size_bytes as i64)
+            .bind(complexity.field_count as i32)
+            .bind(complexity.max_depth as i32)
+            .bind(complexity.constraint_count as i32);
+            // The decision trail is written with the schema, so a stored
+            // version always has its provenance
+            let (global_id,) = match insert.fetch_one(&mut *tx).await {
+                Ok(row) => row,
+                Err(e) => {
+                    // Another registration, e.g. on another replica, stored this
+                    // version or content after the check above
+                    if e.class() == ErrorClass::Conflict {
+                        drop(tx);
+                        let stored = stored_registration(
+                            &state,
+                            &namespace,
+                            &name,
+                            (version_major, version_minor, version_patch),
+                            &content_hash,
+                        )
+                        .await?;
+                        if let Some(stored) = stored {
+                            return Err(AppError::RegistrationConflict(Box::new(stored)));
+                        }
+                    }
+                    return Err(e.into());
+                }
+            };
+            record_schema_event(
+                &mut *tx,
+                id,
+                SCHEMA_EVENT_REGISTERED,
+                &registered,
+                &registered_by,
+            )
+            .await?;
+            if let Some(exemption) = &exemption {
+                record_schema_event(
+                    &mut *tx,
+                    id,
+                    SCHEMA_EVENT_EXEMPTION_APPLIED,
+                    exemption,
+                    &registered_by,
+                )
+                .await?;
+            }
+            tx.commit().await?;
+            if let Some(reasons) = quarantine_reasons {
+                tracing::warn!(
+                    schema_id = %id,
+                    reasons = ?reasons,
+                    "Schema quarantined pending security review"
+                );
+                state
+                    .quarantine
+                    .quarantine(
+                        id.to_string(),
+                        registered.subject.clone(),
+                        reasons,
+                        "registration".to_string(),
+                    )
+                    .await;
+            }
+            announce(
+                &state,
+                schema_event(
+                    EventType::SchemaRegistered,
+                    id,
+                    &namespace,
+                    &name,
+                    SemanticVersion::new(
+                        version_major as u32,
+                        version_minor as u32,
+                        version_patch as u32,
+                    ),
+                    &registered_by,
+                    EventPayload::SchemaRegistered {
+                        schema_name: name.clone(),
+                        namespace: namespace.clone(),
+                        validation_result: None,
+                        compatibility_result: None,
+                    },
+                )
+                .with_metadata("state".to_string(), serde_json::json!(schema_state)),
+            );
+
+            // Cache in Redis with 1-hour TTL
+            let cache_key = format!("{}{}", SCHEMA_CACHE_PREFIX, id);
+            let cache_value = serde_json::json!({
+                "id": id,
+                "global_id": global_id,
+                "namespace": namespace,
+                "name": name,
+                "version_major": version_major,
+                "version_minor": version_minor,
+                "version_patch": version_patch,
+                "format": format,
+                "content": content,
+                "state": schema_state,
+                "compatibility_mode": compatibility_mode,
+                "metadata": plugin_input.metadata,
+                "complexity": complexity,
+                "created_at": now.to_rfc3339(),
+                "updated_at": now.to_rfc3339(),
+            });
+
+            // The schema is stored once Postgres has it; a cache that stays
+            // unavailable only costs the first read a cache miss
+            let mut set = redis::cmd("SET");
+            set.arg(&cache_key)
+                .arg(serde_json::to_string(&cache_value).unwrap())
+                .arg("EX")
+                .arg(state.cache_ttl.ttl(id, Duration::ZERO).as_secs());
+            let cached: Result<(), redis::RedisError> = match state.cache() {
+                Some(conn) => {
+                    state
+                        .retry_policy("cache_write")
+                        .retry(|| {
+                            let mut conn = conn.clone();
+                            let set = &set;
+                            async move { set.query_async(&mut conn).await }
+                        })
+                        .await
+                }
+                None => Ok(()),
+            };
+            if let Err(e) = cached {
+                tracing::warn!(schema_id = %id, error = %e, "Failed to cache registered schema");
+            }
+
+            let mut event = AuditEvent::new(
+                AuditEventType::SchemaRegistered,
+                "Schema registered".to_string(),
+                AuditResult::Success,
+                String::new(),
+            )
+            .with_user(registered_by.clone(), None)
+            .with_resource("schema".to_string(), id.to_string())
+            .with_metadata("subject".to_string(), serde_json::json!(registered.subject))
+            .with_metadata("version".to_string(), serde_json::json!(registered.version))
+            .with_metadata("state".to_string(), serde_json::json!(registered.state))
+            .with_request_payload(request_payload);
+            if let Some(Extension(impersonation)) = &impersonation {
+                event = impersonation.attribute(event);
+            }
+            state.audit_logger.log(event).await;
+
+            if let Some(exemption) = exemption {
+                let mut event = AuditEvent::new(
+                    AuditEventType::CompatibilityExemptionApplied,
+                    "Breaking changes registered under compatibility exemption".to_string(),
+                    AuditResult::Success,
+                    String::new(),
+                )
+                .with_user(registered_by.clone(), None)
+                .with_resource("schema".to_string(), id.to_string())
+                .with_metadata("subject".to_string(), serde_json::json!(registered.subject))
+                .with_metadata(
+                    "exemptions".to_string(),
+                    serde_json::json!(exemption.exemptions),
+                )
+                .with_metadata(
+                    "violations".to_string(),
+                    serde_json::json!(exemption.violations),
+                );
+                if let Some(Extension(impersonation)) = &impersonation {
+                    event = impersonation.attribute(event);
+                }
+                state.audit_logger.log(event).await;
+                announce(
+                    &state,
+                    schema_event(
+                        EventType::CompatibilityExemptionApplied,
+                        id,
+                        &namespace,
+                        &name,
+                        SemanticVersion::new(
+                            version_major as u32,
+                            version_minor as u32,
+                            version_patch as u32,
+                        ),
+                        &registered_by,
+                        EventPayload::CompatibilityExemptionApplied {
+                            exemption_ids: exemption.exemptions,
+                            violations: exemption.violations,
+                        },
+                    )
+                    .with_metadata("mode".to_string(), serde_json::json!(exemption.mode)),
+                );
+            }
+
+            tracing::info!(schema_id = %id, "Schema registered successfully");
+            state.interceptors.run_post_commit(&plugin_input, &plugin_ctx).await;
+
+            // Consumers hear about changes to their fields without the client
+            // waiting on their webhooks
+            let notifier = state.consumer_notifier.clone();
+            let registered_version = RegisteredVersion {
+                schema_id: id,
+                subject: subject_of(&namespace, &name),
+                namespace,
+                name,
+                version: (version_major, version_minor, version_patch),
+                format: serialization_format,
+                content,
+            };
+            tokio::spawn(async move { notifier.notify(registered_version).await });
+            Ok::<_, AppError>((global_id, registered.version))
+        })
+    };
+    let committed = commit
+        .await
+        .map_err(|e| AppError::Internal(format!("Schema commit failed: {}", e)))?;
+    let (global_id, version) = match committed {
+        Ok(committed) => committed,
+        // Nothing was cached for the losing insert, so the winner's entry
+        // stands. Resending the winner's content succeeds, as it would have
+        // without the race.
+        Err(AppError::RegistrationConflict(stored)) => {
+            let outcome = if stored.same_content {
+                "idempotent"
+            } else {
+                "rejected"
+            };
+            REGISTRATION_CONFLICTS.with_label_values(&[outcome]).inc();
+            tracing::info!(
+                subject = %stored.subject,
+                version = %stored.version,
+                schema_id = %stored.id,
+                outcome,
+                "Registration lost an insert race"
+            );
+            if !stored.same_content {
+                return Err(AppError::RegistrationConflict(stored));
+            }
+            warnings.push(format!(
+                "Version {} with this content was registered concurrently",
+                stored.version
+            ));
+            return Ok(registration_response(
+                StatusCode::OK,
+                RegisterSchemaResponse {
+                    id: stored.id,
+                    global_id: stored.global_id,
+                    version: stored.version,
+                    created_at: stored.created_at,
+                    warnings,
+                },
+            ));
+        }
+        Err(e) => return Err(e),
+    };
+
+    Ok(registration_response(
+        StatusCode::CREATED,
+        RegisterSchemaResponse {
+            id,
+            global_id,
+            version,
+            created_at: now.to_rfc3339(),
+            warnings,
+        },
+    ))
+}
+
+/// Limits on a namespace's schemas and a schema's versions
+#[derive(Debug, Clone, Copy)]
+struct Quotas {
+    max_schemas: Option<u64>,
+    max_versions: Option<u64>,
+}
+
+impl Quotas {
+    /// Fail if one more version of `namespace.name` would exceed a quota
+    ///
+    /// Quotas count live schemas; new versions of a schema already in the
+    /// namespace don't count against `max_schemas`.
+    async fn enforce(
+        &self,
+        conn: &mut sqlx::PgConnection,
+        namespace: &str,
+        name: &str,
+    ) -> Result<(), AppError> {
+        if let Some(max) = self.max_schemas {
+            let (count,): (i64,) = sqlx::query_as(
+                "SELECT COUNT(DISTINCT name) FROM schemas WHERE namespace = $1 AND name <> $2 AND state <> 'DELETED'",
+            )
+            .bind(namespace)
+            .bind(name)
+            .fetch_one(&mut *conn)
+            .await?;
+            if count as u64 >= max {
+                return Err(AppError::QuotaExceeded(format!(
+                    "Namespace '{}' has reached its quota of {} schemas",
+                    namespace, max
+                )));
+            }
+        }
+        if let Some(max) = self.max_versions {
+            let (count,): (i64,) = sqlx::query_as(
+                "SELECT COUNT(*) FROM schemas WHERE namespace = $1 AND name = $2 AND state <> 'DELETED'",
+            )
+            .bind(namespace)
+            .bind(name)
+            .fetch_one(&mut *conn)
+            .await?;
+            if count as u64 >= max {
+                return Err(AppError::QuotaExceeded(format!(
+                    "Schema '{}' has reached its quota of {} versions",
+                    subject_of(namespace, name),
+                    max
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Check a new version against `previous` versions of its subject
+///
+/// Returns the gate's result, and the trail of the exemptions that let
+/// breaking changes through, if any did.
+async fn compatibility_gate(
+    state: &AppState,
+    (namespace, name): (&str, &str),
+    (format, content): (&str, &str),
+    version: SemanticVersion,
+    mode: CompatibilityMode,
+    previous: &[RegisteredSchema],
+) -> Result<(GateResult, Option<ExemptionTrail>), AppError> {
+    if previous.is_empty() {
+        return Ok((GateResult::passed("compatibility"), None));
+    }
+    let candidate = comparable_schema(Uuid::nil(), namespace, name, format, content, version);
+    let (result, exemptions_applied) =
+        check_with_exemptions(state, &subject_of(namespace, name), &candidate, previous, mode)
+            .await?;
+    if !result.is_compatible {
+        return Err(AppError::Incompatible(Box::new(result)));
+    }
+    // Violations below the failure threshold don't block, but are kept
+    let findings = describe_violations(&result.violations);
+    if exemptions_applied.is_empty() {
+        return Ok((GateResult::flagged_if("compatibility", findings), None));
+    }
+    let gate = GateResult {
+        gate: "compatibility".to_string(),
+        outcome: GateOutcome::Overridden,
+        details: describe_violations(&result.exempted_violations)
+            .into_iter()
+            .chain(findings)
+            .collect(),
+    };
+    let trail = ExemptionTrail {
+        mode: mode.to_string(),
+        exemptions: exemptions_applied,
+        violations: result
+            .exempted_violations
+            .iter()
+            .map(|v| serde_json::json!(v))
+            .collect(),
+    };
+    Ok((gate, Some(trail)))
+}
+
+/// Compare a new version's complexity with the previous version of the subject
+async fn complexity_alerts(
+    state: &AppState,
+    namespace: &str,
+    name: &str,
+    (major, minor, patch): (i32, i32, i32),
+    complexity: &SchemaComplexity,
+) -> Result<Vec<String>, AppError> {
+    let previous: Option<(i64, i32, i32, i32)> = sqlx::query_as(
+        r#"
+        SELECT size_bytes, field_count, max_depth, constraint_count
+        FROM schemas
+        WHERE namespace = $1 AND name = $2 AND size_bytes IS NOT NULL
+          AND (version_major, version_minor, version_patch) < ($3, $4, $5)
+        ORDER BY version_major DESC, version_minor DESC, version_patch DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(namespace)
+    .bind(name)
+    .bind(major)
+    .bind(minor)
+    .bind(patch)
+    .fetch_optional(&state.db)
+    .await?;
+
+    let Some((size_bytes, field_count, max_depth, constraint_count)) = previous else {
+        return Ok(Vec::new());
+    };
+    let previous = SchemaComplexity {
+        size_bytes: size_bytes as u64,
+        field_count: field_count as u32,
+        max_depth: max_depth as u32,
+        constraint_count: constraint_count as u32,
+    };
+
+    let subject = subject_of(namespace, name);
+    Ok(state
+        .complexity_thresholds
+        .check(&previous, complexity)
+        .into_iter()
+        .map(|alert| {
+            tracing::warn!(
+                subject = %subject,
+                metric = %alert.metric,
+                previous = alert.previous,
+                current = alert.current,
+                "Schema complexity grew beyond threshold"
+            );
+            format!("Complexity alert for {}: {}", subject, alert.message)
+        })
+        .collect())
+}
+
+/// Versions of a subject a new `version` has to stay compatible with
+///
+/// Servable versions below `version`, oldest first: all of them in a
+/// transitive mode, otherwise only the latest. None in `NONE` mode.
+async fn compatibility_baseline(
+    state: &AppState,
+    (namespace, name): (&str, &str),
+    version: &SemanticVersion,
+    mode: CompatibilityMode,
+) -> Result<Vec<RegisteredSchema>, AppError> {
+    if mode == CompatibilityMode::None {
+        return Ok(Vec::new());
+    }
+    let rows: Vec<(Uuid, i32, i32, i32, String, String)> = sqlx::query_as(
+        r#"
+        SELECT id, version_major, version_minor, version_patch, format, content
+        FROM schemas
+        WHERE namespace = $1 AND name = $2 AND state NOT IN ('DELETED', 'QUARANTINED')
+        ORDER BY version_major, version_minor, version_patch
+        "#,
+    )
+    .bind(namespace)
+    .bind(name)
+    .fetch_all(&state.db)
+    .await?;
+    let mut previous: Vec<RegisteredSchema> = rows
+        .into_iter()
+        .map(|(id, major, minor, patch, format, content)| {
+            let version = SemanticVersion::new(major as u32, minor as u32, patch as u32);
+            comparable_schema(id, namespace, name, &format, &content, version)
+        })
+        .filter(|schema| schema.version < *version)
+        .collect();
+    if !mode.is_transitive() {
+        previous = previous.split_off(previous.len().saturating_sub(1));
+    }
+    Ok(previous)
+}
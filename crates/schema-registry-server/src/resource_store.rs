@@ -0,0 +1,254 @@
+//! Schema resources kept in Postgres
+//!
+//! Resources live in `schema_resources` and every change is appended to
+//! `schema_resource_events` under the next `resourceVersion`, so each replica
+//! lists and watches the same objects and a watch survives a restart. Writes
+//! take a transaction-scoped advisory lock, so versions commit in order and a
+//! watcher that has seen a version has seen every change before it.
+
+use schema_registry_core::resource::{
+    SchemaResource, SchemaResourceStatus, SchemaSpec, WatchError, WatchEvent, WatchEventType,
+    DEFAULT_WATCH_HISTORY,
+};
+use sqlx::{PgPool, Postgres, Transaction};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Advisory lock serializing resource writes
+const WRITE_LOCK: i64 = 0x7363_6865_6d61_7273;
+
+/// How often a watch checks for new events
+const WATCH_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Declarative schema resources shared by every replica
+pub struct PostgresResourceStore {
+    db: PgPool,
+    /// Number of events retained for watchers
+    history: u64,
+}
+
+impl PostgresResourceStore {
+    pub fn new(db: PgPool) -> Self {
+        Self {
+            db,
+            history: DEFAULT_WATCH_HISTORY as u64,
+        }
+    }
+
+    /// Create or update the desired state of a resource
+    ///
+    /// See [`SchemaResource::reapplied`] for when the generation increases.
+    pub async fn apply(
+        &self,
+        name: &str,
+        spec: SchemaSpec,
+        labels: BTreeMap<String, String>,
+    ) -> anyhow::Result<SchemaResource> {
+        let mut tx = self.write().await?;
+        let (event_type, resource) = match fetch(&mut tx, name).await? {
+            Some(existing) => match existing.reapplied(spec, labels) {
+                Some(resource) => (WatchEventType::Modified, resource),
+                None => return Ok(existing),
+            },
+            None => (
+                WatchEventType::Added,
+                SchemaResource::new(name, spec, labels)?,
+            ),
+        };
+        let resource = self.commit(tx, event_type, resource).await?;
+        Ok(resource)
+    }
+
+    /// Record the outcome of reconciling `generation` of a resource
+    ///
+    /// Returns `None` without changing anything when the resource was deleted
+    /// or its spec changed since `generation`.
+    pub async fn update_status(
+        &self,
+        name: &str,
+        generation: u64,
+        status: SchemaResourceStatus,
+    ) -> anyhow::Result<Option<SchemaResource>> {
+        let mut tx = self.write().await?;
+        let Some(mut resource) = fetch(&mut tx, name).await? else {
+            return Ok(None);
+        };
+        if resource.metadata.generation != generation {
+            return Ok(None);
+        }
+        resource.status = status;
+        let resource = self.commit(tx, WatchEventType::Modified, resource).await?;
+        Ok(Some(resource))
+    }
+
+    pub async fn get(&self, name: &str) -> anyhow::Result<Option<SchemaResource>> {
+        let row: Option<(serde_json::Value,)> =
+            sqlx::query_as("SELECT resource FROM schema_resources WHERE name = $1")
+                .bind(name)
+                .fetch_optional(&self.db)
+                .await?;
+        row.map(|(resource,)| Ok(serde_json::from_value(resource)?))
+            .transpose()
+    }
+
+    /// All resources and the current resource version
+    pub async fn list(&self) -> anyhow::Result<(Vec<SchemaResource>, u64)> {
+        let mut tx = self.db.begin().await?;
+        sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ")
+            .execute(&mut *tx)
+            .await?;
+        let rows: Vec<(serde_json::Value,)> =
+            sqlx::query_as("SELECT resource FROM schema_resources ORDER BY name")
+                .fetch_all(&mut *tx)
+                .await?;
+        let (version,): (i64,) =
+            sqlx::query_as("SELECT COALESCE(MAX(resource_version), 0) FROM schema_resource_events")
+                .fetch_one(&mut *tx)
+                .await?;
+        tx.commit().await?;
+        let resources = rows
+            .into_iter()
+            .map(|(resource,)| serde_json::from_value(resource))
+            .collect::<Result<_, _>>()?;
+        Ok((resources, version as u64))
+    }
+
+    pub async fn delete(&self, name: &str) -> anyhow::Result<Option<SchemaResource>> {
+        let mut tx = self.write().await?;
+        let Some(resource) = fetch(&mut tx, name).await? else {
+            return Ok(None);
+        };
+        let resource = self.commit(tx, WatchEventType::Deleted, resource).await?;
+        Ok(Some(resource))
+    }
+
+    /// Events after `resource_version`, oldest first
+    ///
+    /// Fails with [`WatchError::Expired`] when the version is older than the
+    /// retained history.
+    pub async fn events_since(&self, resource_version: u64) -> anyhow::Result<Vec<WatchEvent>> {
+        let mut tx = self.db.begin().await?;
+        sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ")
+            .execute(&mut *tx)
+            .await?;
+        let (oldest,): (Option<i64>,) =
+            sqlx::query_as("SELECT MIN(resource_version) FROM schema_resource_events")
+                .fetch_one(&mut *tx)
+                .await?;
+        if let Some(oldest) = oldest.map(|v| v as u64) {
+            // Versions up to `oldest - 1` are still covered by the retained history
+            if resource_version + 1 < oldest {
+                return Err(WatchError::Expired {
+                    requested: resource_version,
+                    oldest,
+                }
+                .into());
+            }
+        }
+        let rows: Vec<(serde_json::Value,)> = sqlx::query_as(
+            "SELECT event FROM schema_resource_events \
+             WHERE resource_version > $1 ORDER BY resource_version",
+        )
+        .bind(resource_version as i64)
+        .fetch_all(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        rows.into_iter()
+            .map(|(event,)| Ok(serde_json::from_value(event)?))
+            .collect()
+    }
+
+    /// Wait up to `timeout` for events after `resource_version`
+    ///
+    /// Returns an empty list when nothing changed before the timeout.
+    pub async fn watch(
+        &self,
+        resource_version: u64,
+        timeout: Duration,
+    ) -> anyhow::Result<Vec<WatchEvent>> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let events = self.events_since(resource_version).await?;
+            if !events.is_empty() || tokio::time::Instant::now() >= deadline {
+                return Ok(events);
+            }
+            tokio::time::sleep_until(
+                deadline.min(tokio::time::Instant::now() + WATCH_POLL_INTERVAL),
+            )
+            .await;
+        }
+    }
+
+    /// Begin a write, waiting for earlier writes to commit
+    async fn write(&self) -> anyhow::Result<Transaction<'static, Postgres>> {
+        let mut tx = self.db.begin().await?;
+        sqlx::query("SELECT pg_advisory_xact_lock($1)")
+            .bind(WRITE_LOCK)
+            .execute(&mut *tx)
+            .await?;
+        Ok(tx)
+    }
+
+    /// Store `resource` under the next resource version and record the event
+    async fn commit(
+        &self,
+        mut tx: Transaction<'static, Postgres>,
+        event_type: WatchEventType,
+        mut resource: SchemaResource,
+    ) -> anyhow::Result<SchemaResource> {
+        let (version,): (i64,) = sqlx::query_as(
+            "SELECT COALESCE(MAX(resource_version), 0) + 1 FROM schema_resource_events",
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        resource.metadata.resource_version = version as u64;
+
+        match event_type {
+            WatchEventType::Deleted => {
+                sqlx::query("DELETE FROM schema_resources WHERE name = $1")
+                    .bind(&resource.metadata.name)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+            _ => {
+                sqlx::query(
+                    "INSERT INTO schema_resources (name, resource) VALUES ($1, $2) \
+                     ON CONFLICT (name) DO UPDATE SET resource = EXCLUDED.resource",
+                )
+                .bind(&resource.metadata.name)
+                .bind(serde_json::to_value(&resource)?)
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+
+        let event = WatchEvent {
+            event_type,
+            object: resource.clone(),
+        };
+        sqlx::query("INSERT INTO schema_resource_events (resource_version, event) VALUES ($1, $2)")
+            .bind(version)
+            .bind(serde_json::to_value(&event)?)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM schema_resource_events WHERE resource_version <= $1")
+            .bind(version - self.history as i64)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(resource)
+    }
+}
+
+async fn fetch(
+    tx: &mut Transaction<'static, Postgres>,
+    name: &str,
+) -> anyhow::Result<Option<SchemaResource>> {
+    let row: Option<(serde_json::Value,)> =
+        sqlx::query_as("SELECT resource FROM schema_resources WHERE name = $1")
+            .bind(name)
+            .fetch_optional(&mut **tx)
+            .await?;
+    row.map(|(resource,)| Ok(serde_json::from_value(resource)?))
+        .transpose()
+}
@@ -0,0 +1,569 @@
+//! Snapshots taken before destructive admin operations
+//!
+//! Hard deletes and subject config applies are hard to take back once a
+//! mistyped ID or the wrong file has gone through. Before either runs, the
+//! rows it is about to change are copied into a snapshot under a fresh
+//! operation ID, and `POST /api/v1/admin/operations/{id}/undo` puts them back.
+//! An operation whose snapshot can't be stored is refused.
+//!
+//! A snapshot holds:
+//!
+//! - for a hard delete, the schema row with its dependency edges (both
+//!   directions) and its events, which the delete cascades to
+//! - for a config apply, the previous settings of each subject it touches,
+//!   `None` for subjects it creates
+//!
+//! Snapshots are written to S3 when a bucket is configured, and otherwise
+//! kept in memory, where a restart loses them. Either way they are pruned
+//! once their retention window has passed.
+
+use anyhow::Context;
+use async_trait::async_trait;
+use aws_config::BehaviorVersion;
+use aws_sdk_s3::error::DisplayErrorContext;
+use aws_sdk_s3::operation::get_object::GetObjectError;
+use aws_sdk_s3::primitives::ByteStream;
+use aws_sdk_s3::Client as S3Client;
+use chrono::{DateTime, Utc};
+use schema_registry_core::namespace::NamespaceTree;
+use schema_registry_core::subject_config::{SubjectConfig, SubjectConfigChange};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use uuid::Uuid;
+
+/// How often expired snapshots are pruned
+const PRUNE_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// Where snapshots are kept, and for how long
+#[derive(Debug, Clone)]
+pub struct SnapshotConfig {
+    /// S3 bucket for snapshots; they are kept in memory without one
+    pub s3_bucket: Option<String>,
+    /// Key prefix of snapshot objects in the bucket
+    pub s3_prefix: String,
+    /// How long an operation can be undone
+    pub retention: Duration,
+}
+
+impl Default for SnapshotConfig {
+    fn default() -> Self {
+        Self {
+            s3_bucket: None,
+            s3_prefix: "snapshots/".to_string(),
+            retention: Duration::from_secs(7 * 86_400),
+        }
+    }
+}
+
+/// Rows a destructive operation changed, as they were before it ran
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub operation_id: Uuid,
+    /// e.g. `delete_schema`
+    pub operation: String,
+    pub actor: String,
+    pub taken_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub contents: SnapshotContents,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub undone_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub undone_by: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SnapshotContents {
+    /// Rows of `schemas`, `schema_dependencies` and `schema_events`, each a
+    /// JSON object of column values
+    Schemas {
+        schemas: Vec<serde_json::Value>,
+        dependencies: Vec<serde_json::Value>,
+        events: Vec<serde_json::Value>,
+    },
+    /// Settings of each subject before the operation, `None` if it had none
+    SubjectConfigs {
+        subjects: BTreeMap<String, Option<SubjectConfig>>,
+    },
+}
+
+impl SnapshotContents {
+    /// The schema `id` as stored, with everything deleting it removes
+    pub async fn of_schema(db: &PgPool, id: Uuid) -> sqlx::Result<Self> {
+        Self::of_schemas(db, &[id]).await
+    }
+
+    /// The schemas `ids` as stored, with everything deleting them removes
+    pub async fn of_schemas(db: &PgPool, ids: &[Uuid]) -> sqlx::Result<Self> {
+        let rows = |sql: &'static str| async move {
+            sqlx::query_scalar::<_, serde_json::Value>(sql)
+                .bind(ids)
+                .fetch_all(db)
+                .await
+        };
+        Ok(SnapshotContents::Schemas {
+            schemas: rows("SELECT to_jsonb(s) FROM schemas s WHERE id = ANY($1)").await?,
+            dependencies: rows(
+                "SELECT to_jsonb(d) FROM schema_dependencies d \
+                 WHERE schema_id = ANY($1) OR depends_on_schema_id = ANY($1)",
+            )
+            .await?,
+            events: rows("SELECT to_jsonb(e) FROM schema_events e WHERE schema_id = ANY($1)")
+                .await?,
+        })
+    }
+
+    /// The current settings of every subject `changes` touch
+    pub fn of_subject_configs(tree: &NamespaceTree, changes: &[SubjectConfigChange]) -> Self {
+        SnapshotContents::SubjectConfigs {
+            subjects: changes
+                .iter()
+                .map(|change| {
+                    let subject = change.subject().to_string();
+                    let current = tree.get(&subject).map(SubjectConfig::from);
+                    (subject, current)
+                })
+                .collect(),
+        }
+    }
+
+    /// The state restoring this snapshot would overwrite, if it overwrites
+    /// anything
+    ///
+    /// Restoring schema rows only inserts, so it has nothing to snapshot.
+    pub fn overwritten_by_restore(&self, tree: &NamespaceTree) -> Option<Self> {
+        match self {
+            SnapshotContents::Schemas { .. } => None,
+            SnapshotContents::SubjectConfigs { subjects } => {
+                Some(SnapshotContents::SubjectConfigs {
+                    subjects: subjects
+                        .keys()
+                        .map(|subject| {
+                            (subject.clone(), tree.get(subject).map(SubjectConfig::from))
+                        })
+                        .collect(),
+                })
+            }
+        }
+    }
+
+    /// Put the snapshotted rows back
+    ///
+    /// Schema rows are restored in one transaction and never replace a row
+    /// with the same ID. Dependency edges to schemas that no longer exist
+    /// are skipped.
+    pub async fn restore(
+        &self,
+        db: &PgPool,
+        tree: &NamespaceTree,
+    ) -> anyhow::Result<RestoreReport> {
+        let mut report = RestoreReport::default();
+        match self {
+            SnapshotContents::Schemas {
+                schemas,
+                dependencies,
+                events,
+            } => {
+                let mut tx = db.begin().await?;
+                report.schemas = sqlx::query(
+                    "INSERT INTO schemas \
+                     SELECT * FROM jsonb_populate_recordset(NULL::schemas, $1)",
+                )
+                .bind(serde_json::Value::from(schemas.clone()))
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+                report.dependencies = sqlx::query(
+                    "INSERT INTO schema_dependencies \
+                     SELECT r.* FROM jsonb_populate_recordset(NULL::schema_dependencies, $1) r \
+                     WHERE EXISTS (SELECT 1 FROM schemas WHERE id = r.schema_id) \
+                       AND EXISTS (SELECT 1 FROM schemas WHERE id = r.depends_on_schema_id) \
+                     ON CONFLICT DO NOTHING",
+                )
+                .bind(serde_json::Value::from(dependencies.clone()))
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+                report.skipped_dependencies =
+                    (dependencies.len() as u64).saturating_sub(report.dependencies);
+                report.events = sqlx::query(
+                    "INSERT INTO schema_events \
+                     SELECT * FROM jsonb_populate_recordset(NULL::schema_events, $1) \
+                     ON CONFLICT DO NOTHING",
+                )
+                .bind(serde_json::Value::from(events.clone()))
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+                tx.commit().await?;
+            }
+            SnapshotContents::SubjectConfigs { subjects } => {
+                report.subjects = restore_subject_configs(subjects, tree)?;
+            }
+        }
+        Ok(report)
+    }
+}
+
+/// Give each subject its snapshotted settings, returning how many
+fn restore_subject_configs(
+    subjects: &BTreeMap<String, Option<SubjectConfig>>,
+    tree: &NamespaceTree,
+) -> anyhow::Result<u64> {
+    for (subject, config) in subjects {
+        match config {
+            Some(config) => tree.set(subject, config.clone().into())?,
+            None => {
+                tree.remove(subject);
+            }
+        }
+    }
+    Ok(subjects.len() as u64)
+}
+
+/// What an undo put back
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RestoreReport {
+    pub schemas: u64,
+    pub dependencies: u64,
+    /// Edges left out because the schema at their other end is gone
+    pub skipped_dependencies: u64,
+    pub events: u64,
+    pub subjects: u64,
+}
+
+/// Durable home of snapshots
+#[async_trait]
+pub trait SnapshotStore: Send + Sync {
+    fn name(&self) -> &str;
+
+    async fn put(&self, snapshot: &Snapshot) -> anyhow::Result<()>;
+
+    async fn get(&self, operation_id: Uuid) -> anyhow::Result<Option<Snapshot>>;
+
+    /// Delete snapshots taken before `cutoff`, returning how many
+    async fn prune(&self, cutoff: DateTime<Utc>) -> anyhow::Result<usize>;
+}
+
+/// Snapshots held in memory, lost on restart
+#[derive(Debug, Default)]
+pub struct MemorySnapshotStore {
+    snapshots: Mutex<HashMap<Uuid, Snapshot>>,
+}
+
+#[async_trait]
+impl SnapshotStore for MemorySnapshotStore {
+    fn name(&self) -> &str {
+        "memory"
+    }
+
+    async fn put(&self, snapshot: &Snapshot) -> anyhow::Result<()> {
+        self.snapshots
+            .lock()
+            .unwrap()
+            .insert(snapshot.operation_id, snapshot.clone());
+        Ok(())
+    }
+
+    async fn get(&self, operation_id: Uuid) -> anyhow::Result<Option<Snapshot>> {
+        Ok(self.snapshots.lock().unwrap().get(&operation_id).cloned())
+    }
+
+    async fn prune(&self, cutoff: DateTime<Utc>) -> anyhow::Result<usize> {
+        let mut snapshots = self.snapshots.lock().unwrap();
+        let before = snapshots.len();
+        snapshots.retain(|_, snapshot| snapshot.taken_at >= cutoff);
+        Ok(before - snapshots.len())
+    }
+}
+
+/// Snapshots stored as `{prefix}{operation_id}.json` objects
+pub struct S3SnapshotStore {
+    client: S3Client,
+    bucket: String,
+    prefix: String,
+}
+
+impl S3SnapshotStore {
+    /// Connect with credentials and region from the usual AWS sources
+    pub async fn new(bucket: String, prefix: String) -> Self {
+        let config = aws_config::defaults(BehaviorVersion::latest()).load().await;
+        Self {
+            client: S3Client::new(&config),
+            bucket,
+            prefix,
+        }
+    }
+
+    fn key(&self, operation_id: Uuid) -> String {
+        format!("{}{}.json", self.prefix, operation_id)
+    }
+}
+
+#[async_trait]
+impl SnapshotStore for S3SnapshotStore {
+    fn name(&self) -> &str {
+        "s3"
+    }
+
+    async fn put(&self, snapshot: &Snapshot) -> anyhow::Result<()> {
+        let key = self.key(snapshot.operation_id);
+        self.client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(&key)
+            .body(ByteStream::from(serde_json::to_vec(snapshot)?))
+            .content_type("application/json")
+            .send()
+            .await
+            .map_err(|e| {
+                anyhow::anyhow!(
+                    "Failed to write s3://{}/{}: {}",
+                    self.bucket,
+                    key,
+                    DisplayErrorContext(e)
+                )
+            })?;
+        Ok(())
+    }
+
+    async fn get(&self, operation_id: Uuid) -> anyhow::Result<Option<Snapshot>> {
+        let key = self.key(operation_id);
+        let output = match self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(&key)
+            .send()
+            .await
+        {
+            Ok(output) => output,
+            Err(e) => {
+                return match e.into_service_error() {
+                    GetObjectError::NoSuchKey(_) => Ok(None),
+                    e => Err(anyhow::anyhow!(
+                        "Failed to read s3://{}/{}: {}",
+                        self.bucket,
+                        key,
+                        DisplayErrorContext(e)
+                    )),
+                }
+            }
+        };
+        let body = output.body.collect().await?.into_bytes();
+        let snapshot = serde_json::from_slice(&body)
+            .with_context(|| format!("s3://{}/{} is not a snapshot", self.bucket, key))?;
+        Ok(Some(snapshot))
+    }
+
+    async fn prune(&self, cutoff: DateTime<Utc>) -> anyhow::Result<usize> {
+        let mut pruned = 0;
+        let mut continuation_token = None;
+        loop {
+            let output = self
+                .client
+                .list_objects_v2()
+                .bucket(&self.bucket)
+                .prefix(&self.prefix)
+                .set_continuation_token(continuation_token)
+                .send()
+                .await
+                .map_err(|e| {
+                    anyhow::anyhow!(
+                        "Failed to list s3://{}/{}: {}",
+                        self.bucket,
+                        self.prefix,
+                        DisplayErrorContext(e)
+                    )
+                })?;
+
+            for object in output.contents() {
+                let expired = object
+                    .last_modified()
+                    .is_some_and(|modified| modified.secs() < cutoff.timestamp());
+                let Some(key) = object.key().filter(|_| expired) else {
+                    continue;
+                };
+                self.client
+                    .delete_object()
+                    .bucket(&self.bucket)
+                    .key(key)
+                    .send()
+                    .await
+                    .map_err(|e| {
+                        anyhow::anyhow!(
+                            "Failed to delete s3://{}/{}: {}",
+                            self.bucket,
+                            key,
+                            DisplayErrorContext(e)
+                        )
+                    })?;
+                pruned += 1;
+            }
+            if !output.is_truncated().unwrap_or(false) {
+                return Ok(pruned);
+            }
+            continuation_token = output.next_continuation_token().map(str::to_string);
+        }
+    }
+}
+
+/// Takes snapshots and looks them up for undo
+pub struct Snapshots {
+    store: Arc<dyn SnapshotStore>,
+    retention: Duration,
+}
+
+impl Snapshots {
+    pub fn new(store: Arc<dyn SnapshotStore>, retention: Duration) -> Self {
+        Self { store, retention }
+    }
+
+    /// S3 when `config` names a bucket, memory otherwise
+    pub async fn from_config(config: &SnapshotConfig) -> Self {
+        let store: Arc<dyn SnapshotStore> = match &config.s3_bucket {
+            Some(bucket) => {
+                Arc::new(S3SnapshotStore::new(bucket.clone(), config.s3_prefix.clone()).await)
+            }
+            None => Arc::new(MemorySnapshotStore::default()),
+        };
+        Self::new(store, config.retention)
+    }
+
+    pub fn store_name(&self) -> &str {
+        self.store.name()
+    }
+
+    pub fn retention(&self) -> Duration {
+        self.retention
+    }
+
+    /// Store `contents` as the state before `operation`, returning the
+    /// operation ID that undoes it
+    pub async fn take(
+        &self,
+        operation: &str,
+        actor: &str,
+        contents: SnapshotContents,
+    ) -> anyhow::Result<Uuid> {
+        let taken_at = Utc::now();
+        let snapshot = Snapshot {
+            operation_id: Uuid::new_v4(),
+            operation: operation.to_string(),
+            actor: actor.to_string(),
+            taken_at,
+            expires_at: taken_at + chrono::Duration::from_std(self.retention)?,
+            contents,
+            undone_at: None,
+            undone_by: None,
+        };
+        self.store.put(&snapshot).await?;
+        Ok(snapshot.operation_id)
+    }
+
+    pub async fn get(&self, operation_id: Uuid) -> anyhow::Result<Option<Snapshot>> {
+        self.store.get(operation_id).await
+    }
+
+    /// Record that `snapshot` was restored, so it isn't restored twice
+    pub async fn mark_undone(&self, snapshot: &mut Snapshot, actor: &str) -> anyhow::Result<()> {
+        snapshot.undone_at = Some(Utc::now());
+        snapshot.undone_by = Some(actor.to_string());
+        self.store.put(snapshot).await
+    }
+
+    /// Prune expired snapshots every hour
+    pub fn spawn_pruning(self: Arc<Self>) {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(PRUNE_INTERVAL);
+            loop {
+                ticker.tick().await;
+                let Ok(retention) = chrono::Duration::from_std(self.retention) else {
+                    return;
+                };
+                match self.store.prune(Utc::now() - retention).await {
+                    Ok(0) => {}
+                    Ok(pruned) => tracing::info!(pruned, "Pruned expired operation snapshots"),
+                    Err(e) => tracing::warn!(error = %e, "Failed to prune operation snapshots"),
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use schema_registry_core::subject_config::SubjectConfigFile;
+
+    fn config(yaml: &str) -> SubjectConfigFile {
+        serde_yaml::from_str::<SubjectConfigFile>(yaml)
+            .unwrap()
+            .normalized()
+            .unwrap()
+    }
+
+    fn restore(contents: &SnapshotContents, tree: &NamespaceTree) {
+        let SnapshotContents::SubjectConfigs { subjects } = contents else {
+            panic!("expected subject configs");
+        };
+        restore_subject_configs(subjects, tree).unwrap();
+    }
+
+    #[test]
+    fn test_undo_restores_overwritten_subject_configs() {
+        let tree = NamespaceTree::default();
+        config("subjects:\n  com.acme.orders:\n    compatibilityLevel: FULL\n")
+            .apply(&tree, false)
+            .unwrap();
+        let desired = config("subjects:\n  com.acme.payments:\n    compatibilityLevel: NONE\n");
+        let changes = desired.diff(&SubjectConfigFile::export(&tree));
+        let contents = SnapshotContents::of_subject_configs(&tree, &changes);
+        desired.apply(&tree, true).unwrap();
+        assert!(tree.get("com.acme.orders").is_none());
+
+        let overwritten = contents.overwritten_by_restore(&tree).unwrap();
+        restore(&contents, &tree);
+        assert!(tree.get("com.acme.orders").is_some());
+        assert!(tree.get("com.acme.payments").is_none());
+
+        // Undoing the undo brings the applied file back
+        restore(&overwritten, &tree);
+        assert_eq!(SubjectConfigFile::export(&tree), desired);
+    }
+
+    #[tokio::test]
+    async fn test_snapshots_expire() {
+        let store = Arc::new(MemorySnapshotStore::default());
+        let snapshots = Snapshots::new(store.clone(), Duration::from_secs(3600));
+        let contents = SnapshotContents::SubjectConfigs {
+            subjects: BTreeMap::new(),
+        };
+        let id = snapshots
+            .take("apply_subject_config", "admin", contents)
+            .await
+            .unwrap();
+        let mut snapshot = snapshots.get(id).await.unwrap().unwrap();
+        assert_eq!(
+            snapshot.expires_at - snapshot.taken_at,
+            chrono::Duration::hours(1)
+        );
+
+        snapshots.mark_undone(&mut snapshot, "admin").await.unwrap();
+        assert!(snapshots
+            .get(id)
+            .await
+            .unwrap()
+            .unwrap()
+            .undone_at
+            .is_some());
+
+        assert_eq!(store.prune(snapshot.taken_at).await.unwrap(), 0);
+        let later = snapshot.taken_at + chrono::Duration::seconds(1);
+        assert_eq!(store.prune(later).await.unwrap(), 1);
+        assert!(snapshots.get(id).await.unwrap().is_none());
+    }
+}
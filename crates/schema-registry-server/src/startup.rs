@@ -0,0 +1,275 @@
+//! Startup phases, warmup hints and the lazily opened cache connection
+//!
+//! Only Postgres must be reachable before the server can answer requests.
+//! The Redis connection is opened on first use, and the compiled-schema
+//! cache fills as payloads are validated, so a cold start waits on the
+//! database alone. Subsystems named in `STARTUP_WARMUP` start in the
+//! background as soon as the router is built, to be ready by the time traffic
+//! arrives without holding up startup.
+//!
+//! Each startup phase is timed. The breakdown is logged once the router is
+//! built and exported as `schema_registry_startup_phase_seconds`.
+
+use prometheus::GaugeVec;
+use redis::aio::ConnectionManager;
+use std::collections::BTreeSet;
+use std::future::Future;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, LazyLock};
+use std::time::{Duration, Instant};
+use tokio::sync::OnceCell;
+
+/// Duration of each startup phase of this instance
+static STARTUP_PHASE_SECONDS: LazyLock<GaugeVec> = LazyLock::new(|| {
+    prometheus::register_gauge_vec!(
+        "schema_registry_startup_phase_seconds",
+        "Duration of each phase of the last server startup",
+        &["phase"]
+    )
+    .expect("startup phase metric registers once")
+});
+
+/// A subsystem that can be started ahead of its first use
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Subsystem {
+    /// The Redis connection
+    Cache,
+    /// Compiled forms of the most recently registered active schemas
+    CompiledSchemas,
+}
+
+impl Subsystem {
+    pub const ALL: [Subsystem; 2] = [Subsystem::Cache, Subsystem::CompiledSchemas];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Subsystem::Cache => "cache",
+            Subsystem::CompiledSchemas => "compiled_schemas",
+        }
+    }
+}
+
+impl FromStr for Subsystem {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        Subsystem::ALL
+            .into_iter()
+            .find(|subsystem| subsystem.as_str() == s)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "unknown warmup subsystem '{}'; expected one of cache, compiled_schemas",
+                    s
+                )
+            })
+    }
+}
+
+/// Subsystems to start in the background at startup rather than on first use
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WarmupHints {
+    subsystems: BTreeSet<Subsystem>,
+}
+
+impl WarmupHints {
+    pub fn all() -> Self {
+        Self {
+            subsystems: Subsystem::ALL.into_iter().collect(),
+        }
+    }
+
+    pub fn includes(&self, subsystem: Subsystem) -> bool {
+        self.subsystems.contains(&subsystem)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.subsystems.is_empty()
+    }
+}
+
+impl FromStr for WarmupHints {
+    type Err = anyhow::Error;
+
+    /// A comma-separated list of subsystems, `all`, or `none`
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let mut hints = Self::default();
+        for name in s.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+            match name {
+                "all" => return Ok(Self::all()),
+                "none" => {}
+                name => {
+                    hints.subsystems.insert(name.parse()?);
+                }
+            }
+        }
+        Ok(hints)
+    }
+}
+
+/// How long each phase of startup took, in the order they ran
+#[derive(Debug)]
+pub struct StartupPhases {
+    started: Instant,
+    phases: Vec<(&'static str, Duration)>,
+}
+
+impl StartupPhases {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            phases: Vec::new(),
+        }
+    }
+
+    /// Run `phase` to completion, recording how long it took
+    pub async fn time<T>(&mut self, phase: &'static str, future: impl Future<Output = T>) -> T {
+        let started = Instant::now();
+        let output = future.await;
+        self.phases.push((phase, started.elapsed()));
+        output
+    }
+
+    /// Run a synchronous `phase`, recording how long it took
+    pub fn time_sync<T>(&mut self, phase: &'static str, f: impl FnOnce() -> T) -> T {
+        let started = Instant::now();
+        let output = f();
+        self.phases.push((phase, started.elapsed()));
+        output
+    }
+
+    pub fn phases(&self) -> &[(&'static str, Duration)] {
+        &self.phases
+    }
+
+    /// `phase=12ms` for each phase, in order
+    pub fn breakdown(&self) -> String {
+        self.phases
+            .iter()
+            .map(|(phase, elapsed)| format!("{}={}ms", phase, elapsed.as_millis()))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Log the breakdown and export it as metrics
+    pub fn finish(&self) {
+        for (phase, elapsed) in &self.phases {
+            STARTUP_PHASE_SECONDS
+                .with_label_values(&[phase])
+                .set(elapsed.as_secs_f64());
+        }
+        let total = self.started.elapsed();
+        STARTUP_PHASE_SECONDS
+            .with_label_values(&["total"])
+            .set(total.as_secs_f64());
+        tracing::info!(
+            total_ms = total.as_millis() as u64,
+            phases = %self.breakdown(),
+            "Startup complete"
+        );
+    }
+}
+
+impl Default for StartupPhases {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A Redis connection opened on first use
+///
+/// [`LazyRedis::get`] waits for the connection; [`LazyRedis::connection`]
+/// never does, starting to connect in the background instead, so requests
+/// served before Redis answers simply miss the cache.
+#[derive(Clone)]
+pub(crate) struct LazyRedis {
+    client: redis::Client,
+    connection: Arc<OnceCell<ConnectionManager>>,
+    connecting: Arc<AtomicBool>,
+}
+
+impl LazyRedis {
+    pub(crate) fn new(client: redis::Client) -> Self {
+        Self {
+            client,
+            connection: Arc::new(OnceCell::new()),
+            connecting: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// The connection, opened now if it isn't yet
+    ///
+    /// A failed attempt is not remembered; the next call tries again.
+    pub(crate) async fn get(&self) -> redis::RedisResult<ConnectionManager> {
+        self.connection
+            .get_or_try_init(|| async {
+                let started = Instant::now();
+                let connection = ConnectionManager::new(self.client.clone()).await?;
+                tracing::info!(
+                    elapsed_ms = started.elapsed().as_millis() as u64,
+                    "Redis connection established"
+                );
+                Ok(connection)
+            })
+            .await
+            .cloned()
+    }
+
+    /// The connection if it is open, otherwise `None` while it opens in the
+    /// background
+    pub(crate) fn connection(&self) -> Option<ConnectionManager> {
+        if let Some(connection) = self.connection.get() {
+            return Some(connection.clone());
+        }
+        self.connect_in_background();
+        None
+    }
+
+    /// Start opening the connection unless an attempt is under way
+    pub(crate) fn connect_in_background(&self) {
+        if self.connection.initialized() || self.connecting.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        let redis = self.clone();
+        tokio::spawn(async move {
+            if let Err(e) = redis.get().await {
+                tracing::warn!(
+                    error = %e,
+                    "Failed to connect to Redis; the cache is skipped until it answers"
+                );
+            }
+            redis.connecting.store(false, Ordering::Release);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_warmup_hints_parse() {
+        let hints: WarmupHints = "cache, compiled_schemas".parse().unwrap();
+        assert_eq!(hints, WarmupHints::all());
+        assert_eq!("all".parse::<WarmupHints>().unwrap(), WarmupHints::all());
+        assert!("none".parse::<WarmupHints>().unwrap().is_empty());
+        assert!("".parse::<WarmupHints>().unwrap().is_empty());
+
+        let hints: WarmupHints = "cache".parse().unwrap();
+        assert!(hints.includes(Subsystem::Cache));
+        assert!(!hints.includes(Subsystem::CompiledSchemas));
+        assert!("s3".parse::<WarmupHints>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_phases_are_recorded_in_order() {
+        let mut phases = StartupPhases::new();
+        phases.time_sync("config", || ());
+        let answer = phases.time("postgres", async { 42 }).await;
+        assert_eq!(answer, 42);
+
+        let names: Vec<&str> = phases.phases().iter().map(|(phase, _)| *phase).collect();
+        assert_eq!(names, ["config", "postgres"]);
+        assert!(phases.breakdown().starts_with("config="));
+    }
+}
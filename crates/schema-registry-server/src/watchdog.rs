@@ -0,0 +1,376 @@
+//! Dependency health watchdog
+//!
+//! Probes the backing stores on an interval and switches the server into
+//! cache-bypass mode while the cache is down. A dependency is marked down
+//! after `failure_threshold` consecutive failed probes and up again after
+//! `recovery_threshold` consecutive successful ones, so a single slow probe
+//! doesn't flip the mode back and forth.
+//!
+//! In cache-bypass mode reads go straight to Postgres and cache writes and
+//! evictions are skipped, instead of every request waiting out connection
+//! timeouts and retries. Each switch is logged, audited, and published to
+//! [`DependencyWatchdog::subscribe`] receivers. The current mode is reported by
+//! `/health` and the `schema_registry_cache_bypass` gauge.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use prometheus::{IntCounterVec, IntGauge, IntGaugeVec};
+use schema_registry_security::audit::{AuditEvent, AuditEventType, AuditLogger, AuditResult};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::Duration;
+use tokio::sync::watch;
+
+/// 1 while the server bypasses the cache
+static CACHE_BYPASS: LazyLock<IntGauge> = LazyLock::new(|| {
+    prometheus::register_int_gauge!(
+        "schema_registry_cache_bypass",
+        "1 while the cache is down and requests bypass it"
+    )
+    .expect("cache bypass metric registers once")
+});
+
+/// Whether each dependency is considered up
+static DEPENDENCY_UP: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    prometheus::register_int_gauge_vec!(
+        "schema_registry_dependency_up",
+        "1 while the watchdog considers the dependency up",
+        &["dependency"]
+    )
+    .expect("dependency up metric registers once")
+});
+
+/// Switches between storage modes, by the mode switched to
+static MODE_TRANSITIONS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    prometheus::register_int_counter_vec!(
+        "schema_registry_storage_mode_transitions_total",
+        "Switches between normal and cache-bypass mode",
+        &["mode"]
+    )
+    .expect("storage mode transitions metric registers once")
+});
+
+/// How requests use the backing stores
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageMode {
+    /// Reads are served from the cache when possible
+    Normal,
+    /// The cache is down; reads go to Postgres and cache updates are skipped
+    CacheBypass,
+}
+
+impl StorageMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StorageMode::Normal => "normal",
+            StorageMode::CacheBypass => "cache_bypass",
+        }
+    }
+}
+
+/// Probe thresholds and timing
+#[derive(Debug, Clone)]
+pub struct WatchdogConfig {
+    /// Time between probe rounds
+    pub interval: Duration,
+    /// A probe taking longer than this fails
+    pub probe_timeout: Duration,
+    /// Consecutive failures before a dependency is marked down
+    pub failure_threshold: u32,
+    /// Consecutive successes before a down dependency is marked up again
+    pub recovery_threshold: u32,
+}
+
+impl Default for WatchdogConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(5),
+            probe_timeout: Duration::from_secs(2),
+            failure_threshold: 3,
+            recovery_threshold: 2,
+        }
+    }
+}
+
+/// A backing store the watchdog checks
+#[async_trait]
+pub trait HealthProbe: Send + Sync {
+    /// Name in `/health` and the `dependency` metric label
+    fn name(&self) -> &'static str;
+
+    /// Whether requests bypass this dependency while it is down
+    fn is_cache(&self) -> bool {
+        false
+    }
+
+    async fn probe(&self) -> Result<(), String>;
+}
+
+/// What the watchdog last concluded about a dependency
+#[derive(Debug, Clone, Serialize)]
+pub struct DependencyStatus {
+    pub up: bool,
+    pub consecutive_failures: u32,
+    /// Error of the most recent failed probe, cleared once the dependency is up
+    pub last_error: Option<String>,
+    /// When `up` last changed
+    pub since: DateTime<Utc>,
+}
+
+struct Tracked {
+    status: DependencyStatus,
+    consecutive_successes: u32,
+}
+
+/// Tracks dependency health and derives the storage mode from it
+pub struct DependencyWatchdog {
+    config: WatchdogConfig,
+    probes: Vec<Arc<dyn HealthProbe>>,
+    tracked: Mutex<BTreeMap<&'static str, Tracked>>,
+    mode: watch::Sender<StorageMode>,
+    audit_logger: Arc<AuditLogger>,
+}
+
+impl DependencyWatchdog {
+    /// Every dependency starts up, since the server connected to it at startup
+    pub fn new(
+        config: WatchdogConfig,
+        probes: Vec<Arc<dyn HealthProbe>>,
+        audit_logger: Arc<AuditLogger>,
+    ) -> Self {
+        let tracked = probes
+            .iter()
+            .map(|probe| {
+                DEPENDENCY_UP.with_label_values(&[probe.name()]).set(1);
+                let status = DependencyStatus {
+                    up: true,
+                    consecutive_failures: 0,
+                    last_error: None,
+                    since: Utc::now(),
+                };
+                (
+                    probe.name(),
+                    Tracked {
+                        status,
+                        consecutive_successes: 0,
+                    },
+                )
+            })
+            .collect();
+        CACHE_BYPASS.set(0);
+
+        Self {
+            config,
+            probes,
+            tracked: Mutex::new(tracked),
+            mode: watch::channel(StorageMode::Normal).0,
+            audit_logger,
+        }
+    }
+
+    pub fn mode(&self) -> StorageMode {
+        *self.mode.borrow()
+    }
+
+    /// Whether requests should read and update the cache
+    pub fn cache_enabled(&self) -> bool {
+        self.mode() == StorageMode::Normal
+    }
+
+    /// Receive every mode switch from now on
+    pub fn subscribe(&self) -> watch::Receiver<StorageMode> {
+        self.mode.subscribe()
+    }
+
+    /// Last known status of every dependency, by name
+    pub fn statuses(&self) -> BTreeMap<&'static str, DependencyStatus> {
+        self.tracked
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(name, tracked)| (*name, tracked.status.clone()))
+            .collect()
+    }
+
+    /// Probe every dependency once and switch modes if needed
+    pub async fn check(&self) {
+        for probe in &self.probes {
+            let result = match tokio::time::timeout(self.config.probe_timeout, probe.probe()).await
+            {
+                Ok(result) => result,
+                Err(_) => Err(format!(
+                    "No response within {:?}",
+                    self.config.probe_timeout
+                )),
+            };
+            self.record(probe.name(), result);
+        }
+
+        let cache_down = self
+            .probes
+            .iter()
+            .any(|probe| probe.is_cache() && !self.tracked.lock().unwrap()[probe.name()].status.up);
+        let mode = if cache_down {
+            StorageMode::CacheBypass
+        } else {
+            StorageMode::Normal
+        };
+        if mode != self.mode() {
+            self.switch(mode).await;
+        }
+    }
+
+    /// Probe every `interval` until the process exits
+    pub fn spawn(self: &Arc<Self>) {
+        let watchdog = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(watchdog.config.interval);
+            loop {
+                ticker.tick().await;
+                watchdog.check().await;
+            }
+        });
+    }
+
+    fn record(&self, name: &'static str, result: Result<(), String>) {
+        let mut tracked = self.tracked.lock().unwrap();
+        let Some(tracked) = tracked.get_mut(name) else {
+            return;
+        };
+        let status = &mut tracked.status;
+        match result {
+            Ok(()) => {
+                tracked.consecutive_successes += 1;
+                status.consecutive_failures = 0;
+                if !status.up && tracked.consecutive_successes >= self.config.recovery_threshold {
+                    status.up = true;
+                    status.last_error = None;
+                    status.since = Utc::now();
+                    DEPENDENCY_UP.with_label_values(&[name]).set(1);
+                    tracing::info!(dependency = name, "Dependency is back up");
+                }
+            }
+            Err(error) => {
+                tracked.consecutive_successes = 0;
+                status.consecutive_failures += 1;
+                if status.up && status.consecutive_failures >= self.config.failure_threshold {
+                    status.up = false;
+                    status.since = Utc::now();
+                    DEPENDENCY_UP.with_label_values(&[name]).set(0);
+                    tracing::error!(dependency = name, error = %error, "Dependency is down");
+                }
+                status.last_error = Some(error);
+            }
+        }
+    }
+
+    async fn switch(&self, mode: StorageMode) {
+        let previous = self.mode.send_replace(mode);
+        CACHE_BYPASS.set((mode == StorageMode::CacheBypass) as i64);
+        MODE_TRANSITIONS.with_label_values(&[mode.as_str()]).inc();
+
+        let (event_type, action) = match mode {
+            StorageMode::CacheBypass => {
+                tracing::warn!("Cache is down, bypassing it until it recovers");
+                (AuditEventType::DegradedModeEntered, "Cache bypass enabled")
+            }
+            StorageMode::Normal => {
+                tracing::info!("Cache recovered, leaving cache-bypass mode");
+                (AuditEventType::DegradedModeExited, "Cache bypass disabled")
+            }
+        };
+        let mut event = AuditEvent::new(
+            event_type,
+            action.to_string(),
+            AuditResult::Success,
+            String::new(),
+        )
+        .with_metadata("from".to_string(), serde_json::json!(previous.as_str()))
+        .with_metadata("to".to_string(), serde_json::json!(mode.as_str()));
+        for (name, status) in self.statuses() {
+            if let Some(error) = status.last_error {
+                event = event.with_metadata(format!("{}_error", name), serde_json::json!(error));
+            }
+        }
+        self.audit_logger.log(event).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use schema_registry_security::audit::AuditEventFilter;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    struct FakeCache {
+        up: AtomicBool,
+    }
+
+    #[async_trait]
+    impl HealthProbe for FakeCache {
+        fn name(&self) -> &'static str {
+            "redis"
+        }
+
+        fn is_cache(&self) -> bool {
+            true
+        }
+
+        async fn probe(&self) -> Result<(), String> {
+            if self.up.load(Ordering::Relaxed) {
+                Ok(())
+            } else {
+                Err("Connection refused".to_string())
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cache_outage_switches_to_bypass_and_back() {
+        let cache = Arc::new(FakeCache {
+            up: AtomicBool::new(true),
+        });
+        let audit_logger = Arc::new(AuditLogger::new());
+        let config = WatchdogConfig {
+            failure_threshold: 2,
+            recovery_threshold: 2,
+            ..WatchdogConfig::default()
+        };
+        let watchdog = DependencyWatchdog::new(config, vec![cache.clone()], audit_logger.clone());
+        let mut modes = watchdog.subscribe();
+
+        cache.up.store(false, Ordering::Relaxed);
+        watchdog.check().await;
+        assert!(
+            watchdog.cache_enabled(),
+            "one failure is below the threshold"
+        );
+
+        watchdog.check().await;
+        assert_eq!(watchdog.mode(), StorageMode::CacheBypass);
+        assert!(modes.has_changed().unwrap());
+        assert_eq!(*modes.borrow_and_update(), StorageMode::CacheBypass);
+        let status = &watchdog.statuses()["redis"];
+        assert!(!status.up);
+        assert_eq!(status.last_error.as_deref(), Some("Connection refused"));
+
+        cache.up.store(true, Ordering::Relaxed);
+        watchdog.check().await;
+        assert_eq!(watchdog.mode(), StorageMode::CacheBypass);
+        watchdog.check().await;
+        assert_eq!(watchdog.mode(), StorageMode::Normal);
+        assert!(watchdog.statuses()["redis"].last_error.is_none());
+
+        let events = audit_logger.get_events(AuditEventFilter::default()).await;
+        let types: Vec<_> = events.iter().map(|e| e.event_type).collect();
+        assert_eq!(
+            types,
+            [
+                AuditEventType::DegradedModeEntered,
+                AuditEventType::DegradedModeExited
+            ]
+        );
+    }
+}
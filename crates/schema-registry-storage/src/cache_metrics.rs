@@ -0,0 +1,201 @@
+//! Cache effectiveness metrics per storage tier
+//!
+//! [`CacheMetrics`] counts lookups, fills and evictions for each tier of
+//! [`MultiTierStorage`](crate::MultiTierStorage), or of the server's schema
+//! cache, and publishes them through the `metrics` facade under the names the
+//! dashboards and alert rules in `deployments/monitoring` use:
+//!
+//! - `schema_registry_cache_operations_total{operation, tier, result}`
+//! - `schema_registry_cache_lookup_duration_seconds{tier, result}`
+//! - `schema_registry_cache_fill_duration_seconds{tier}`
+//! - `schema_registry_cache_evictions_total{tier, reason}`
+//! - `schema_registry_cache_hit_rate{tier}` - derived hit ratio since start
+//!
+//! Only the in-process L1 cache reports evictions; Redis evicts silently and
+//! those entries show up as L2 misses.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Storage tier, from fastest to slowest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CacheTier {
+    /// In-process cache
+    L1,
+    /// Redis
+    L2,
+    /// Primary store
+    Postgres,
+    /// Archive
+    S3,
+}
+
+impl CacheTier {
+    pub const ALL: [CacheTier; 4] = [
+        CacheTier::L1,
+        CacheTier::L2,
+        CacheTier::Postgres,
+        CacheTier::S3,
+    ];
+
+    /// Value of the `tier` label
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CacheTier::L1 => "L1",
+            CacheTier::L2 => "L2",
+            CacheTier::Postgres => "postgres",
+            CacheTier::S3 => "s3",
+        }
+    }
+
+    fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+#[derive(Default)]
+struct TierCounters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    fills: AtomicU64,
+    evictions: AtomicU64,
+}
+
+/// Point-in-time counts for one tier
+#[derive(Debug, Clone, PartialEq)]
+pub struct TierStats {
+    pub tier: CacheTier,
+    pub hits: u64,
+    pub misses: u64,
+    pub fills: u64,
+    pub evictions: u64,
+}
+
+impl TierStats {
+    /// Share of lookups answered by this tier, `None` before the first lookup
+    pub fn hit_ratio(&self) -> Option<f64> {
+        let lookups = self.hits + self.misses;
+        (lookups > 0).then(|| self.hits as f64 / lookups as f64)
+    }
+}
+
+/// Lookup, fill and eviction counters for every tier
+#[derive(Default)]
+pub struct CacheMetrics {
+    tiers: [TierCounters; 4],
+}
+
+impl CacheMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A lookup answered by `tier`
+    pub fn record_hit(&self, tier: CacheTier, latency: Duration) {
+        self.tiers[tier.index()].hits.fetch_add(1, Ordering::Relaxed);
+        self.record_lookup(tier, "hit", latency);
+    }
+
+    /// A lookup `tier` couldn't answer
+    pub fn record_miss(&self, tier: CacheTier, latency: Duration) {
+        self.tiers[tier.index()].misses.fetch_add(1, Ordering::Relaxed);
+        self.record_lookup(tier, "miss", latency);
+    }
+
+    /// A cache tier populated after a miss
+    pub fn record_fill(&self, tier: CacheTier, latency: Duration) {
+        self.tiers[tier.index()].fills.fetch_add(1, Ordering::Relaxed);
+        metrics::counter!(
+            "schema_registry_cache_operations_total",
+            "operation" => "fill",
+            "tier" => tier.as_str(),
+            "result" => "ok"
+        )
+        .increment(1);
+        metrics::histogram!("schema_registry_cache_fill_duration_seconds", "tier" => tier.as_str())
+            .record(latency.as_secs_f64());
+    }
+
+    /// An entry dropped by `tier` to make room (`size`) or because it expired
+    pub fn record_eviction(&self, tier: CacheTier, reason: &'static str) {
+        self.tiers[tier.index()].evictions.fetch_add(1, Ordering::Relaxed);
+        metrics::counter!(
+            "schema_registry_cache_evictions_total",
+            "tier" => tier.as_str(),
+            "reason" => reason
+        )
+        .increment(1);
+    }
+
+    pub fn stats(&self, tier: CacheTier) -> TierStats {
+        let counters = &self.tiers[tier.index()];
+        TierStats {
+            tier,
+            hits: counters.hits.load(Ordering::Relaxed),
+            misses: counters.misses.load(Ordering::Relaxed),
+            fills: counters.fills.load(Ordering::Relaxed),
+            evictions: counters.evictions.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Stats for every tier, fastest first
+    pub fn snapshot(&self) -> Vec<TierStats> {
+        CacheTier::ALL.iter().map(|tier| self.stats(*tier)).collect()
+    }
+
+    /// Share of all lookups answered by L1 or L2
+    ///
+    /// Every lookup starts at L1, so L1's lookups are the total.
+    pub fn cached_hit_ratio(&self) -> Option<f64> {
+        let l1 = self.stats(CacheTier::L1);
+        let l2 = self.stats(CacheTier::L2);
+        let lookups = l1.hits + l1.misses;
+        (lookups > 0).then(|| (l1.hits + l2.hits) as f64 / lookups as f64)
+    }
+
+    fn record_lookup(&self, tier: CacheTier, result: &'static str, latency: Duration) {
+        metrics::counter!(
+            "schema_registry_cache_operations_total",
+            "operation" => "get",
+            "tier" => tier.as_str(),
+            "result" => result
+        )
+        .increment(1);
+        metrics::histogram!(
+            "schema_registry_cache_lookup_duration_seconds",
+            "tier" => tier.as_str(),
+            "result" => result
+        )
+        .record(latency.as_secs_f64());
+        if let Some(ratio) = self.stats(tier).hit_ratio() {
+            metrics::gauge!("schema_registry_cache_hit_rate", "tier" => tier.as_str()).set(ratio);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hit_ratios() {
+        let metrics = CacheMetrics::new();
+        assert_eq!(metrics.cached_hit_ratio(), None);
+
+        let fast = Duration::from_micros(50);
+        for _ in 0..3 {
+            metrics.record_hit(CacheTier::L1, fast);
+        }
+        metrics.record_miss(CacheTier::L1, fast);
+        metrics.record_hit(CacheTier::L2, Duration::from_micros(400));
+        metrics.record_fill(CacheTier::L1, fast);
+        metrics.record_eviction(CacheTier::L1, "size");
+
+        let l1 = metrics.stats(CacheTier::L1);
+        assert_eq!((l1.hits, l1.misses, l1.fills, l1.evictions), (3, 1, 1, 1));
+        assert_eq!(l1.hit_ratio(), Some(0.75));
+        assert_eq!(metrics.cached_hit_ratio(), Some(1.0));
+        assert_eq!(metrics.stats(CacheTier::Postgres).hit_ratio(), None);
+        assert_eq!(metrics.snapshot().len(), 4);
+    }
+}
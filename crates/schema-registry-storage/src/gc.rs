@@ -0,0 +1,356 @@
+//! Garbage collection of orphaned cache keys and archive objects
+//!
+//! Cache and archive tiers are keyed by schema ID. When a schema row is
+//! deleted from the primary store without the other tiers being cleaned up,
+//! their entries linger forever. The [`GarbageCollector`] scans each
+//! [`GcTarget`], asks the [`SchemaIndex`] which IDs still exist, and deletes
+//! (or, in dry-run mode, only reports) the rest.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use schema_registry_core::error::Result;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Maximum number of orphaned keys listed per target in a report
+const MAX_REPORTED_KEYS: usize = 100;
+
+/// A cache key or archive object belonging to a schema
+#[derive(Debug, Clone)]
+pub struct GcCandidate {
+    /// Key or object name
+    pub key: String,
+    /// Schema the entry belongs to
+    pub schema_id: Uuid,
+    /// Size of the entry in bytes, if known
+    pub size_bytes: u64,
+}
+
+/// One page of a target scan
+#[derive(Debug, Clone, Default)]
+pub struct ScanPage {
+    pub candidates: Vec<GcCandidate>,
+    /// Cursor for the next page, `None` when the scan is complete
+    pub next_cursor: Option<String>,
+}
+
+/// A storage tier that can be scanned for orphaned entries
+#[async_trait]
+pub trait GcTarget: Send + Sync {
+    /// Name used in reports and metrics
+    fn name(&self) -> &str;
+
+    /// Scan up to `limit` entries starting at `cursor`
+    async fn scan(&self, cursor: Option<String>, limit: usize) -> Result<ScanPage>;
+
+    /// Delete the given keys
+    async fn delete_batch(&self, keys: &[String]) -> Result<()>;
+}
+
+/// Source of truth for which schemas still exist
+#[async_trait]
+pub trait SchemaIndex: Send + Sync {
+    /// Return the subset of `ids` that still have schema rows
+    async fn existing(&self, ids: &[Uuid]) -> Result<HashSet<Uuid>>;
+}
+
+/// Extract the schema ID from a key such as `schema:<uuid>`
+pub fn schema_id_from_key(key: &str, prefix: &str) -> Option<Uuid> {
+    key.strip_prefix(prefix).and_then(|id| Uuid::parse_str(id).ok())
+}
+
+/// Garbage collection settings
+#[derive(Debug, Clone)]
+pub struct GcConfig {
+    /// Only report orphans without deleting them
+    pub dry_run: bool,
+    /// Entries requested per scan page
+    pub scan_batch_size: usize,
+    /// Keys deleted per delete call
+    pub delete_batch_size: usize,
+    /// Maximum entries deleted per target in one run
+    pub max_deletions: usize,
+}
+
+impl Default for GcConfig {
+    fn default() -> Self {
+        Self {
+            dry_run: true,
+            scan_batch_size: 500,
+            delete_batch_size: 100,
+            max_deletions: 10_000,
+        }
+    }
+}
+
+/// Result of collecting one target
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GcTargetReport {
+    pub target: String,
+    pub scanned: usize,
+    pub orphaned: usize,
+    pub orphaned_bytes: u64,
+    pub deleted: usize,
+    pub reclaimed_bytes: u64,
+    /// Sample of orphaned keys, capped at 100
+    pub orphan_keys: Vec<String>,
+    /// Whether the deletion limit stopped cleanup before all orphans were removed
+    pub limit_reached: bool,
+}
+
+/// Result of a garbage collection run
+#[derive(Debug, Clone, Serialize)]
+pub struct GcReport {
+    pub dry_run: bool,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub targets: Vec<GcTargetReport>,
+}
+
+impl GcReport {
+    /// Total bytes reclaimed across all targets
+    pub fn reclaimed_bytes(&self) -> u64 {
+        self.targets.iter().map(|t| t.reclaimed_bytes).sum()
+    }
+}
+
+/// Removes entries whose schema no longer exists
+pub struct GarbageCollector {
+    index: Arc<dyn SchemaIndex>,
+    targets: Vec<Arc<dyn GcTarget>>,
+    config: GcConfig,
+}
+
+impl GarbageCollector {
+    pub fn new(index: Arc<dyn SchemaIndex>) -> Self {
+        Self {
+            index,
+            targets: Vec::new(),
+            config: GcConfig::default(),
+        }
+    }
+
+    /// Add a storage tier to collect
+    pub fn with_target(mut self, target: Arc<dyn GcTarget>) -> Self {
+        self.targets.push(target);
+        self
+    }
+
+    /// Set the collection settings
+    pub fn with_config(mut self, config: GcConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Collect every target once
+    pub async fn run(&self) -> Result<GcReport> {
+        let started_at = Utc::now();
+        let mut targets = Vec::with_capacity(self.targets.len());
+        for target in &self.targets {
+            targets.push(self.collect(target.as_ref()).await?);
+        }
+
+        let report = GcReport {
+            dry_run: self.config.dry_run,
+            started_at,
+            finished_at: Utc::now(),
+            targets,
+        };
+        tracing::info!(
+            dry_run = report.dry_run,
+            orphaned = report.targets.iter().map(|t| t.orphaned).sum::<usize>(),
+            reclaimed_bytes = report.reclaimed_bytes(),
+            "Storage garbage collection completed"
+        );
+        Ok(report)
+    }
+
+    async fn collect(&self, target: &dyn GcTarget) -> Result<GcTargetReport> {
+        let mut report = GcTargetReport {
+            target: target.name().to_string(),
+            ..Default::default()
+        };
+        let mut cursor = None;
+
+        loop {
+            let page = target.scan(cursor, self.config.scan_batch_size.max(1)).await?;
+            report.scanned += page.candidates.len();
+
+            let ids: Vec<Uuid> = page.candidates.iter().map(|c| c.schema_id).collect();
+            let existing = if ids.is_empty() {
+                HashSet::new()
+            } else {
+                self.index.existing(&ids).await?
+            };
+            let orphans: Vec<&GcCandidate> = page
+                .candidates
+                .iter()
+                .filter(|c| !existing.contains(&c.schema_id))
+                .collect();
+
+            report.orphaned += orphans.len();
+            for orphan in &orphans {
+                report.orphaned_bytes += orphan.size_bytes;
+                if report.orphan_keys.len() < MAX_REPORTED_KEYS {
+                    report.orphan_keys.push(orphan.key.clone());
+                }
+            }
+
+            if !self.config.dry_run {
+                self.delete_orphans(target, &orphans, &mut report).await?;
+            }
+
+            cursor = page.next_cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+
+        metrics::counter!("schema_registry.gc.orphans_found", "target" => report.target.clone())
+            .increment(report.orphaned as u64);
+        metrics::counter!("schema_registry.gc.deleted", "target" => report.target.clone())
+            .increment(report.deleted as u64);
+        metrics::counter!("schema_registry.gc.reclaimed_bytes", "target" => report.target.clone())
+            .increment(report.reclaimed_bytes);
+
+        Ok(report)
+    }
+
+    async fn delete_orphans(
+        &self,
+        target: &dyn GcTarget,
+        orphans: &[&GcCandidate],
+        report: &mut GcTargetReport,
+    ) -> Result<()> {
+        let remaining = self.config.max_deletions.saturating_sub(report.deleted);
+        if orphans.len() > remaining {
+            report.limit_reached = true;
+        }
+
+        for chunk in orphans[..orphans.len().min(remaining)].chunks(self.config.delete_batch_size.max(1)) {
+            let keys: Vec<String> = chunk.iter().map(|c| c.key.clone()).collect();
+            target.delete_batch(&keys).await?;
+            report.deleted += chunk.len();
+            report.reclaimed_bytes += chunk.iter().map(|c| c.size_bytes).sum::<u64>();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parking_lot::Mutex;
+
+    struct MemoryTarget {
+        entries: Mutex<Vec<GcCandidate>>,
+    }
+
+    impl MemoryTarget {
+        fn new(ids: &[Uuid]) -> Self {
+            Self {
+                entries: Mutex::new(
+                    ids.iter()
+                        .map(|id| GcCandidate {
+                            key: format!("schema:{}", id),
+                            schema_id: *id,
+                            size_bytes: 10,
+                        })
+                        .collect(),
+                ),
+            }
+        }
+
+        fn len(&self) -> usize {
+            self.entries.lock().len()
+        }
+    }
+
+    #[async_trait]
+    impl GcTarget for MemoryTarget {
+        fn name(&self) -> &str {
+            "memory"
+        }
+
+        async fn scan(&self, cursor: Option<String>, limit: usize) -> Result<ScanPage> {
+            let entries = self.entries.lock();
+            let start: usize = cursor.map(|c| c.parse().unwrap()).unwrap_or(0);
+            let end = (start + limit).min(entries.len());
+            Ok(ScanPage {
+                candidates: entries[start..end].to_vec(),
+                next_cursor: (end < entries.len()).then(|| end.to_string()),
+            })
+        }
+
+        async fn delete_batch(&self, keys: &[String]) -> Result<()> {
+            self.entries.lock().retain(|c| !keys.contains(&c.key));
+            Ok(())
+        }
+    }
+
+    struct FixedIndex(HashSet<Uuid>);
+
+    #[async_trait]
+    impl SchemaIndex for FixedIndex {
+        async fn existing(&self, ids: &[Uuid]) -> Result<HashSet<Uuid>> {
+            Ok(ids.iter().filter(|id| self.0.contains(id)).copied().collect())
+        }
+    }
+
+    fn setup() -> (Arc<MemoryTarget>, Arc<FixedIndex>) {
+        let live: Vec<Uuid> = (0..3).map(|_| Uuid::new_v4()).collect();
+        let dead: Vec<Uuid> = (0..5).map(|_| Uuid::new_v4()).collect();
+        let all: Vec<Uuid> = live.iter().chain(dead.iter()).copied().collect();
+        (
+            Arc::new(MemoryTarget::new(&all)),
+            Arc::new(FixedIndex(live.into_iter().collect())),
+        )
+    }
+
+    #[test]
+    fn test_schema_id_from_key() {
+        let id = Uuid::new_v4();
+        assert_eq!(schema_id_from_key(&format!("schema:{}", id), "schema:"), Some(id));
+        assert_eq!(schema_id_from_key("schema:not-a-uuid", "schema:"), None);
+        assert_eq!(schema_id_from_key(&id.to_string(), "schema:"), None);
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_reports_without_deleting() {
+        let (target, index) = setup();
+        let gc = GarbageCollector::new(index)
+            .with_target(target.clone())
+            .with_config(GcConfig { scan_batch_size: 3, ..Default::default() });
+
+        let report = gc.run().await.unwrap();
+        let t = &report.targets[0];
+        assert!(report.dry_run);
+        assert_eq!(t.scanned, 8);
+        assert_eq!(t.orphaned, 5);
+        assert_eq!(t.orphaned_bytes, 50);
+        assert_eq!(t.deleted, 0);
+        assert_eq!(target.len(), 8);
+    }
+
+    #[tokio::test]
+    async fn test_deletion_respects_limit() {
+        let (target, index) = setup();
+        let gc = GarbageCollector::new(index)
+            .with_target(target.clone())
+            .with_config(GcConfig {
+                dry_run: false,
+                scan_batch_size: 100,
+                delete_batch_size: 2,
+                max_deletions: 4,
+            });
+
+        let report = gc.run().await.unwrap();
+        let t = &report.targets[0];
+        assert_eq!(t.deleted, 4);
+        assert_eq!(t.reclaimed_bytes, 40);
+        assert!(t.limit_reached);
+        assert_eq!(target.len(), 4);
+    }
+}
@@ -3,15 +3,32 @@
 //! Storage abstraction layer for PostgreSQL, Redis, and S3.
 //! Implements the SchemaStorage trait from schema-registry-core.
 
+pub mod cache_metrics;
 pub mod cache_warmer;
+pub mod gc;
 pub mod postgres;
+pub mod query_cost;
 pub mod redis_cache;
 pub mod s3;
 
 use async_trait::async_trait;
+use cache_metrics::{CacheMetrics, CacheTier};
+use moka::future::Cache;
+use moka::notification::RemovalCause;
 use schema_registry_core::{error::Result, schema::RegisteredSchema, traits::SchemaStorage, versioning::SemanticVersion};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use uuid::Uuid;
 
+/// Entries kept in the in-process L1 cache
+pub const L1_CAPACITY: u64 = 10_000;
+
+/// How long an entry stays in L1 before it is re-read from L2
+pub const L1_TTL: Duration = Duration::from_secs(300);
+
+/// L1 key; `None` is the latest version
+type L1Key = (Uuid, Option<SemanticVersion>);
+
 /// Storage backend configuration
 #[derive(Debug, Clone)]
 pub enum StorageConfig {
@@ -29,28 +46,96 @@ pub enum StorageConfig {
         bucket: String,
         region: String,
     },
+    /// S3 with replica buckets in other regions
+    S3Replicated(s3::ReplicationConfig),
 }
 
 /// Multi-tier storage implementation
 pub struct MultiTierStorage {
+    // In-process cache (L1)
+    l1: Cache<L1Key, RegisteredSchema>,
     // Primary storage (PostgreSQL)
     postgres: postgres::PostgresStorage,
     // Cache layer (Redis)
     cache: redis_cache::RedisCache,
     // Archive storage (S3)
-    #[allow(dead_code)]
     s3: s3::S3Storage,
+    metrics: Arc<CacheMetrics>,
 }
 
 impl MultiTierStorage {
     /// Create a new multi-tier storage instance
     pub async fn new(postgres_config: StorageConfig, redis_config: StorageConfig, s3_config: StorageConfig) -> Result<Self> {
+        let metrics = Arc::new(CacheMetrics::new());
+        let evictions = metrics.clone();
+        let l1 = Cache::builder()
+            .max_capacity(L1_CAPACITY)
+            .time_to_live(L1_TTL)
+            .eviction_listener(move |_key, _value, cause| {
+                let reason = match cause {
+                    RemovalCause::Size => "size",
+                    RemovalCause::Expired => "expired",
+                    RemovalCause::Explicit | RemovalCause::Replaced => return,
+                };
+                evictions.record_eviction(CacheTier::L1, reason);
+            })
+            .build();
+
         Ok(Self {
+            l1,
             postgres: postgres::PostgresStorage::new(postgres_config).await?,
             cache: redis_cache::RedisCache::new(redis_config).await?,
             s3: s3::S3Storage::new(s3_config).await?,
+            metrics,
         })
     }
+
+    /// Hit, miss, fill and eviction counts per tier
+    pub fn cache_metrics(&self) -> Arc<CacheMetrics> {
+        self.metrics.clone()
+    }
+
+    /// Verification state of each S3 replica region
+    pub fn s3_replication_status(&self) -> Vec<s3::ReplicationStatus> {
+        self.s3.replication_status()
+    }
+
+    /// Drop the L1 entries a write to this version makes stale
+    async fn invalidate_l1(&self, id: Uuid, version: &SemanticVersion) {
+        self.l1.invalidate(&(id, Some(version.clone()))).await;
+        self.l1.invalidate(&(id, None)).await;
+    }
+
+    /// Read through Postgres, falling back to the S3 archive for schemas no
+    /// longer in the primary store
+    async fn retrieve_from_storage(
+        &self,
+        id: Uuid,
+        version: Option<SemanticVersion>,
+    ) -> Result<RegisteredSchema> {
+        let started = Instant::now();
+        let error = match self.postgres.retrieve(id, version.clone()).await {
+            Ok(schema) => {
+                self.metrics.record_hit(CacheTier::Postgres, started.elapsed());
+                return Ok(schema);
+            }
+            Err(e) if e.is_not_found() => e,
+            Err(e) => return Err(e),
+        };
+        self.metrics.record_miss(CacheTier::Postgres, started.elapsed());
+
+        let started = Instant::now();
+        match self.s3.retrieve(id, version).await {
+            Ok(schema) => {
+                self.metrics.record_hit(CacheTier::S3, started.elapsed());
+                Ok(schema)
+            }
+            Err(_) => {
+                self.metrics.record_miss(CacheTier::S3, started.elapsed());
+                Err(error)
+            }
+        }
+    }
 }
 
 #[async_trait]
@@ -58,20 +143,45 @@ impl SchemaStorage for MultiTierStorage {
     async fn store(&self, schema: RegisteredSchema) -> Result<()> {
         // Store in PostgreSQL (primary)
         self.postgres.store(schema.clone()).await?;
+        self.invalidate_l1(schema.id, &schema.version).await;
         // Update cache
         self.cache.store(schema).await?;
         Ok(())
     }
 
     async fn retrieve(&self, id: Uuid, version: Option<SemanticVersion>) -> Result<RegisteredSchema> {
-        // Try cache first
-        if let Ok(schema) = self.cache.retrieve(id, version.clone()).await {
+        let key = (id, version.clone());
+
+        // Try the in-process cache, then Redis
+        let started = Instant::now();
+        if let Some(schema) = self.l1.get(&key).await {
+            self.metrics.record_hit(CacheTier::L1, started.elapsed());
             return Ok(schema);
         }
-        // Fallback to PostgreSQL
-        let schema = self.postgres.retrieve(id, version).await?;
-        // Update cache
-        let _ = self.cache.store(schema.clone()).await;
+        self.metrics.record_miss(CacheTier::L1, started.elapsed());
+
+        let started = Instant::now();
+        let schema = match self.cache.retrieve(id, version.clone()).await {
+            Ok(schema) => {
+                self.metrics.record_hit(CacheTier::L2, started.elapsed());
+                schema
+            }
+            Err(_) => {
+                self.metrics.record_miss(CacheTier::L2, started.elapsed());
+                let schema = self.retrieve_from_storage(id, version).await?;
+
+                // Update cache
+                let started = Instant::now();
+                if self.cache.store(schema.clone()).await.is_ok() {
+                    self.metrics.record_fill(CacheTier::L2, started.elapsed());
+                }
+                schema
+            }
+        };
+
+        let started = Instant::now();
+        self.l1.insert(key, schema.clone()).await;
+        self.metrics.record_fill(CacheTier::L1, started.elapsed());
         Ok(schema)
     }
 
@@ -81,13 +191,21 @@ impl SchemaStorage for MultiTierStorage {
 
     async fn update(&self, schema: RegisteredSchema) -> Result<()> {
         self.postgres.update(schema.clone()).await?;
+        self.invalidate_l1(schema.id, &schema.version).await;
         self.cache.store(schema).await?;
         Ok(())
     }
 
     async fn delete(&self, id: Uuid, version: SemanticVersion) -> Result<()> {
-        self.postgres.delete(id, version).await?;
-        // Invalidate cache
+        self.postgres.delete(id, version.clone()).await?;
+        self.invalidate_l1(id, &version).await;
+        // Entries left behind on failure are picked up by the garbage collector
+        if let Err(e) = self.cache.delete(id, version.clone()).await {
+            tracing::warn!(schema_id = %id, error = %e, "Failed to invalidate cache entry");
+        }
+        if let Err(e) = self.s3.delete(id, version).await {
+            tracing::warn!(schema_id = %id, error = %e, "Failed to delete archived schema");
+        }
         Ok(())
     }
 
@@ -104,6 +222,32 @@ impl SchemaStorage for MultiTierStorage {
 mod tests {
     use super::*;
 
+    #[tokio::test]
+    async fn test_retrieve_records_tier_misses() {
+        let storage = MultiTierStorage::new(
+            StorageConfig::Postgres {
+                connection_string: "postgresql://localhost/test".to_string(),
+                max_connections: 1,
+            },
+            StorageConfig::Redis {
+                url: "redis://localhost:6379".to_string(),
+            },
+            StorageConfig::S3 {
+                bucket: "bucket".to_string(),
+                region: "us-east-1".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+
+        assert!(storage.retrieve(Uuid::new_v4(), None).await.is_err());
+
+        let metrics = storage.cache_metrics();
+        assert_eq!(metrics.stats(CacheTier::L1).misses, 1);
+        assert_eq!(metrics.stats(CacheTier::L2).misses, 1);
+        assert_eq!(metrics.cached_hit_ratio(), Some(0.0));
+    }
+
     #[test]
     fn test_storage_config_postgres_creation() {
         let config = StorageConfig::Postgres {
@@ -2,6 +2,7 @@
 
 use async_trait::async_trait;
 use schema_registry_core::{error::{Error, Result}, schema::RegisteredSchema, traits::SchemaStorage, versioning::SemanticVersion};
+use sqlx::PgConnection;
 use uuid::Uuid;
 
 use crate::StorageConfig;
@@ -15,6 +16,76 @@ impl PostgresStorage {
     pub async fn new(_config: StorageConfig) -> Result<Self> {
         Ok(Self {})
     }
+
+    /// Allocate the next auto-assigned version number of a subject
+    ///
+    /// Call this on the transaction that inserts the new version. Bumping the
+    /// subject's `subject_version_sequences` row locks it until the
+    /// transaction ends, so concurrent registrations of the same subject queue
+    /// up behind each other instead of racing to the same number. If the
+    /// transaction rolls back, so does the bump, which keeps the sequence
+    /// gapless. The number never goes below the highest major version already
+    /// stored, so explicitly versioned registrations are not collided with.
+    pub async fn allocate_version(conn: &mut PgConnection, namespace: &str, name: &str) -> Result<u32> {
+        let (version,): (i32,) = sqlx::query_as(
+            r#"
+            INSERT INTO subject_version_sequences (namespace, name, last_version)
+            VALUES (
+                $1, $2,
+                1 + COALESCE(
+                    (SELECT MAX(version_major) FROM schemas WHERE namespace = $1 AND name = $2),
+                    0
+                )
+            )
+            ON CONFLICT (namespace, name) DO UPDATE
+            SET last_version = GREATEST(
+                    subject_version_sequences.last_version,
+                    EXCLUDED.last_version - 1
+                ) + 1,
+                updated_at = NOW()
+            RETURNING last_version
+            "#,
+        )
+        .bind(namespace)
+        .bind(name)
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|e| Error::StorageError(format!("Failed to allocate version: {}", e)))?;
+
+        u32::try_from(version)
+            .map_err(|_| Error::StorageError(format!("Invalid version sequence value {}", version)))
+    }
+
+    /// The number [`allocate_version`](Self::allocate_version) would return
+    /// now, without taking it or locking anything
+    ///
+    /// A concurrent registration may take it first, so only use it for checks
+    /// that run before the version is allocated.
+    pub async fn next_version(conn: &mut PgConnection, namespace: &str, name: &str) -> Result<u32> {
+        let (version,): (i32,) = sqlx::query_as(
+            r#"
+            SELECT 1 + GREATEST(
+                COALESCE(
+                    (SELECT last_version FROM subject_version_sequences
+                     WHERE namespace = $1 AND name = $2),
+                    0
+                ),
+                COALESCE(
+                    (SELECT MAX(version_major) FROM schemas WHERE namespace = $1 AND name = $2),
+                    0
+                )
+            )
+            "#,
+        )
+        .bind(namespace)
+        .bind(name)
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|e| Error::StorageError(format!("Failed to read version sequence: {}", e)))?;
+
+        u32::try_from(version)
+            .map_err(|_| Error::StorageError(format!("Invalid version sequence value {}", version)))
+    }
 }
 
 #[async_trait]
@@ -240,4 +311,76 @@ mod tests {
             assert_ne!(c1, c2);
         }
     }
+
+    /// Registers `count` versions of one subject concurrently, each allocating
+    /// its number and inserting in one transaction
+    ///
+    /// Needs a database with the server migrations applied; run with
+    /// `DATABASE_URL=postgres://... cargo test -p schema-registry-storage -- --ignored`
+    #[tokio::test]
+    #[ignore]
+    async fn test_allocate_version_is_gapless_under_concurrency() {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let pool = sqlx::postgres::PgPoolOptions::new()
+            .max_connections(16)
+            .connect(&url)
+            .await
+            .unwrap();
+        let namespace = format!("test_{}", Uuid::new_v4().simple());
+        let count = 64;
+
+        let tasks: Vec<_> = (0..count)
+            .map(|i| {
+                let pool = pool.clone();
+                let namespace = namespace.clone();
+                tokio::spawn(async move {
+                    let mut tx = pool.begin().await.unwrap();
+                    let version = PostgresStorage::allocate_version(&mut tx, &namespace, "user")
+                        .await
+                        .unwrap();
+                    // Every fourth registration fails after allocating
+                    if i % 4 == 3 {
+                        tx.rollback().await.unwrap();
+                        return;
+                    }
+                    sqlx::query(
+                        "INSERT INTO schemas (namespace, name, version_major, version_minor, version_patch, format, content, content_hash) \
+                         VALUES ($1, 'user', $2, 0, 0, 'JSON', '{}', $3)",
+                    )
+                    .bind(&namespace)
+                    .bind(version as i32)
+                    .bind(format!("{:064x}", Uuid::new_v4().as_u128()))
+                    .execute(&mut *tx)
+                    .await
+                    .unwrap();
+                    tx.commit().await.unwrap();
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        let versions: Vec<(i32,)> = sqlx::query_as(
+            "SELECT version_major FROM schemas WHERE namespace = $1 ORDER BY version_major",
+        )
+        .bind(&namespace)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        let versions: Vec<i32> = versions.into_iter().map(|(v,)| v).collect();
+        let committed = count - count / 4;
+        assert_eq!(versions, (1..=committed).collect::<Vec<i32>>());
+
+        sqlx::query("DELETE FROM schemas WHERE namespace = $1")
+            .bind(&namespace)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM subject_version_sequences WHERE namespace = $1")
+            .bind(&namespace)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
 }
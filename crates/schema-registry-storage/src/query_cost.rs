@@ -0,0 +1,217 @@
+//! Cost limits for user-shaped queries
+//!
+//! Search and reporting endpoints build their SQL from request filters, and a
+//! request with few filters (or a leading `%` wildcard) can make PostgreSQL
+//! scan a whole table. [`QueryLimits::fetch_all`] guards such a query in
+//! three ways:
+//!
+//! - the planner's cost estimate is checked first, and the query is rejected
+//!   without running if it exceeds [`QueryLimits::max_cost`]
+//! - the query runs under a `statement_timeout`, in case the estimate was
+//!   optimistic
+//! - [`QueryLimits::page_size`] caps how many rows one request may ask for
+//!
+//! Rejections carry the estimate so callers can tell users which filters to
+//! add.
+
+use serde::Serialize;
+use sqlx::postgres::PgRow;
+use sqlx::{FromRow, PgPool, Postgres, QueryBuilder};
+use std::time::Duration;
+
+/// SQLSTATE of a statement cancelled by `statement_timeout`
+const QUERY_CANCELED: &str = "57014";
+
+/// Limits applied to guarded queries
+#[derive(Debug, Clone)]
+pub struct QueryLimits {
+    /// Longest a guarded query may run
+    pub statement_timeout: Duration,
+    /// Highest planner cost (in PostgreSQL cost units) a query may have
+    pub max_cost: f64,
+    /// Most rows one page may hold
+    pub max_page_size: u32,
+}
+
+impl Default for QueryLimits {
+    fn default() -> Self {
+        Self {
+            statement_timeout: Duration::from_secs(5),
+            max_cost: 100_000.0,
+            max_page_size: 100,
+        }
+    }
+}
+
+/// What the planner expects a query to cost
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct QueryEstimate {
+    /// Planner cost of the whole query
+    pub total_cost: f64,
+    /// Rows the query is expected to return before `LIMIT`
+    pub rows: f64,
+    /// Tables the plan reads in full
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub sequential_scans: Vec<String>,
+}
+
+/// Why a guarded query was not answered
+#[derive(Debug, thiserror::Error)]
+pub enum QueryCostError {
+    #[error("Query too expensive: estimated cost {:.0} exceeds the limit of {max_cost:.0}", estimate.total_cost)]
+    TooExpensive { estimate: QueryEstimate, max_cost: f64 },
+
+    #[error("Query too expensive: cancelled after {}ms", timeout.as_millis())]
+    TimedOut { timeout: Duration },
+
+    #[error("Page size {requested} exceeds the maximum of {max}")]
+    PageTooLarge { requested: u32, max: u32 },
+
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+impl QueryLimits {
+    /// Rows to fetch for a page, `default` when the request gives no limit
+    pub fn page_size(&self, requested: Option<u32>, default: u32) -> Result<u32, QueryCostError> {
+        match requested {
+            Some(requested) if requested > self.max_page_size => Err(QueryCostError::PageTooLarge {
+                requested,
+                max: self.max_page_size,
+            }),
+            Some(requested) => Ok(requested.max(1)),
+            None => Ok(default.min(self.max_page_size)),
+        }
+    }
+
+    /// Check the cost of the query written by `build`, then run it under the
+    /// statement timeout
+    ///
+    /// `build` is called twice, once behind `EXPLAIN`, so it must push the
+    /// same SQL and binds each time.
+    pub async fn fetch_all<'q, T, F>(&self, pool: &PgPool, build: F) -> Result<Vec<T>, QueryCostError>
+    where
+        T: for<'r> FromRow<'r, PgRow> + Send + Unpin,
+        F: Fn(&mut QueryBuilder<'q, Postgres>),
+    {
+        let mut tx = pool.begin().await?;
+        // SET doesn't take bind parameters; the value is a plain integer
+        sqlx::query(&format!(
+            "SET LOCAL statement_timeout = {}",
+            self.statement_timeout.as_millis()
+        ))
+        .execute(&mut *tx)
+        .await?;
+
+        let mut explain = QueryBuilder::new("EXPLAIN (FORMAT JSON) ");
+        build(&mut explain);
+        let plan: serde_json::Value = explain
+            .build_query_scalar()
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| self.map_timeout(e))?;
+        let estimate = estimate_from_plan(&plan);
+        if estimate.total_cost > self.max_cost {
+            return Err(QueryCostError::TooExpensive { estimate, max_cost: self.max_cost });
+        }
+
+        let mut query = QueryBuilder::new("");
+        build(&mut query);
+        let rows = query
+            .build_query_as()
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(|e| self.map_timeout(e))?;
+        tx.commit().await?;
+        Ok(rows)
+    }
+
+    fn map_timeout(&self, e: sqlx::Error) -> QueryCostError {
+        let cancelled = e
+            .as_database_error()
+            .and_then(|db| db.code())
+            .is_some_and(|code| code == QUERY_CANCELED);
+        if cancelled {
+            QueryCostError::TimedOut { timeout: self.statement_timeout }
+        } else {
+            QueryCostError::Database(e)
+        }
+    }
+}
+
+/// Read the estimate from `EXPLAIN (FORMAT JSON)` output
+pub fn estimate_from_plan(explain: &serde_json::Value) -> QueryEstimate {
+    fn collect_seq_scans(node: &serde_json::Value, out: &mut Vec<String>) {
+        if node["Node Type"] == "Seq Scan" {
+            if let Some(relation) = node["Relation Name"].as_str() {
+                if !out.iter().any(|r| r == relation) {
+                    out.push(relation.to_string());
+                }
+            }
+        }
+        if let Some(children) = node["Plans"].as_array() {
+            for child in children {
+                collect_seq_scans(child, out);
+            }
+        }
+    }
+
+    let root = &explain[0]["Plan"];
+    // Judge the query below any LIMIT. The planner scales a limited plan's
+    // cost down on the assumption that matches come early, which is exactly
+    // what a wildcard search over a large table can't promise.
+    let unlimited = if root["Node Type"] == "Limit" { &root["Plans"][0] } else { root };
+
+    let mut sequential_scans = Vec::new();
+    collect_seq_scans(root, &mut sequential_scans);
+    QueryEstimate {
+        total_cost: unlimited["Total Cost"].as_f64().unwrap_or(0.0),
+        rows: unlimited["Plan Rows"].as_f64().unwrap_or(0.0),
+        sequential_scans,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_page_size_is_capped() {
+        let limits = QueryLimits::default();
+        assert_eq!(limits.page_size(None, 10).unwrap(), 10);
+        assert_eq!(limits.page_size(Some(100), 10).unwrap(), 100);
+        assert_eq!(limits.page_size(Some(0), 10).unwrap(), 1);
+        assert!(matches!(
+            limits.page_size(Some(101), 10),
+            Err(QueryCostError::PageTooLarge { requested: 101, max: 100 })
+        ));
+    }
+
+    #[test]
+    fn test_estimate_looks_below_limit() {
+        let explain = json!([{
+            "Plan": {
+                "Node Type": "Limit",
+                "Total Cost": 12.5,
+                "Plan Rows": 10,
+                "Plans": [{
+                    "Node Type": "Sort",
+                    "Total Cost": 48210.0,
+                    "Plan Rows": 95000,
+                    "Plans": [{
+                        "Node Type": "Seq Scan",
+                        "Relation Name": "schemas",
+                        "Total Cost": 30100.0,
+                        "Plan Rows": 95000
+                    }]
+                }]
+            }
+        }]);
+
+        let estimate = estimate_from_plan(&explain);
+        assert_eq!(estimate.total_cost, 48210.0);
+        assert_eq!(estimate.rows, 95000.0);
+        assert_eq!(estimate.sequential_scans, vec!["schemas".to_string()]);
+    }
+}
@@ -1,44 +1,580 @@
 //! S3 storage for schema archives
+//!
+//! Archived schemas are written to a primary bucket, and S3 replication copies
+//! them to replica buckets in other regions. After each write a background task
+//! polls every replica until it holds an object with the same SHA-256
+//! checksum, and records how long that took. Reads go to the primary first and
+//! fail over to the replicas, in configured order, when a region errors.
+//!
+//! Replication health is published for the DR runbooks:
+//!
+//! - `schema_registry_s3_replication_lag_seconds{region}` - write to verified replica copy
+//! - `schema_registry_s3_replication_verifications_total{region, result}` - `verified`, `timeout` or `error`
+//! - `schema_registry_s3_replication_pending{region}` - writes not yet seen in the replica
+//! - `schema_registry_s3_read_failovers_total{region}` - reads answered by a replica
+//!
+//! Lag is measured at poll granularity, so it overstates the real lag by up to
+//! one verification interval.
 
 use async_trait::async_trait;
+use aws_config::BehaviorVersion;
+use aws_sdk_s3::error::DisplayErrorContext;
+use aws_sdk_s3::operation::{get_object::GetObjectError, head_object::HeadObjectError};
+use aws_sdk_s3::primitives::ByteStream;
+use aws_sdk_s3::Client as S3Client;
 use schema_registry_core::{error::{Error, Result}, schema::RegisteredSchema, traits::SchemaStorage, versioning::SemanticVersion};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use uuid::Uuid;
 
 use crate::StorageConfig;
 
+/// Object metadata key holding the hex SHA-256 of the body
+const CHECKSUM_METADATA: &str = "sha256";
+
+/// Prefix of every archived schema object
+const OBJECT_PREFIX: &str = "schemas/";
+
+/// Default time between checks of a replica that doesn't have a write yet
+pub const DEFAULT_VERIFY_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Default time a replica may lag before a write counts as not replicated;
+/// matches the S3 Replication Time Control target
+pub const DEFAULT_VERIFY_TIMEOUT: Duration = Duration::from_secs(15 * 60);
+
+/// A bucket in one region
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct S3Region {
+    pub region: String,
+    pub bucket: String,
+}
+
+/// Primary and replica buckets of a replicated archive
+#[derive(Debug, Clone)]
+pub struct ReplicationConfig {
+    /// Bucket every write goes to
+    pub primary: S3Region,
+    /// Buckets S3 replicates the primary into, in read failover order
+    pub replicas: Vec<S3Region>,
+    /// Time between checks of a replica that doesn't have a write yet
+    pub verify_interval: Duration,
+    /// How long a replica may lag before the write counts as not replicated
+    pub verify_timeout: Duration,
+}
+
+impl ReplicationConfig {
+    pub fn new(primary: S3Region, replicas: Vec<S3Region>) -> Self {
+        Self {
+            primary,
+            replicas,
+            verify_interval: DEFAULT_VERIFY_INTERVAL,
+            verify_timeout: DEFAULT_VERIFY_TIMEOUT,
+        }
+    }
+}
+
+/// One region's bucket, as used by [`S3Storage`]
+#[async_trait]
+pub trait ObjectStore: Send + Sync {
+    /// Value of the `region` label
+    fn region(&self) -> &str;
+
+    /// Write `body`, recording `checksum` with the object
+    async fn put(&self, key: &str, body: Vec<u8>, checksum: &str) -> Result<()>;
+
+    /// Object body, `None` if the key doesn't exist
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>>;
+
+    /// Checksum recorded when the object was written, `None` if the key
+    /// doesn't exist
+    async fn checksum(&self, key: &str) -> Result<Option<String>>;
+
+    /// Keys starting with `prefix`
+    async fn list(&self, prefix: &str) -> Result<Vec<String>>;
+
+    async fn delete(&self, key: &str) -> Result<()>;
+}
+
+/// [`ObjectStore`] backed by an S3 bucket
+pub struct S3Bucket {
+    client: S3Client,
+    region: String,
+    bucket: String,
+}
+
+impl S3Bucket {
+    /// Client for `location`, with credentials from the default AWS provider
+    /// chain
+    pub async fn connect(location: &S3Region) -> Self {
+        let config = aws_config::defaults(BehaviorVersion::latest())
+            .region(aws_config::Region::new(location.region.clone()))
+            .load()
+            .await;
+
+        Self {
+            client: S3Client::new(&config),
+            region: location.region.clone(),
+            bucket: location.bucket.clone(),
+        }
+    }
+
+    fn error(&self, action: &str, key: &str, e: impl std::error::Error) -> Error {
+        Error::StorageError(format!(
+            "Failed to {} s3://{}/{} in {}: {}",
+            action,
+            self.bucket,
+            key,
+            self.region,
+            DisplayErrorContext(e)
+        ))
+    }
+}
+
+#[async_trait]
+impl ObjectStore for S3Bucket {
+    fn region(&self) -> &str {
+        &self.region
+    }
+
+    async fn put(&self, key: &str, body: Vec<u8>, checksum: &str) -> Result<()> {
+        self.client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .body(ByteStream::from(body))
+            .content_type("application/json")
+            .metadata(CHECKSUM_METADATA, checksum)
+            .send()
+            .await
+            .map_err(|e| self.error("write", key, e))?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let output = match self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .send()
+            .await
+        {
+            Ok(output) => output,
+            Err(e) => {
+                return match e.into_service_error() {
+                    GetObjectError::NoSuchKey(_) => Ok(None),
+                    e => Err(self.error("read", key, e)),
+                }
+            }
+        };
+        let body = output
+            .body
+            .collect()
+            .await
+            .map_err(|e| self.error("read", key, e))?;
+        Ok(Some(body.to_vec()))
+    }
+
+    async fn checksum(&self, key: &str) -> Result<Option<String>> {
+        match self
+            .client
+            .head_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .send()
+            .await
+        {
+            Ok(output) => Ok(Some(
+                output
+                    .metadata()
+                    .and_then(|metadata| metadata.get(CHECKSUM_METADATA))
+                    .cloned()
+                    .unwrap_or_default(),
+            )),
+            Err(e) => match e.into_service_error() {
+                HeadObjectError::NotFound(_) => Ok(None),
+                e => Err(self.error("inspect", key, e)),
+            },
+        }
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<String>> {
+        let mut keys = Vec::new();
+        let mut continuation_token = None;
+        loop {
+            let output = self
+                .client
+                .list_objects_v2()
+                .bucket(&self.bucket)
+                .prefix(prefix)
+                .set_continuation_token(continuation_token)
+                .send()
+                .await
+                .map_err(|e| self.error("list", prefix, e))?;
+
+            keys.extend(
+                output
+                    .contents()
+                    .iter()
+                    .filter_map(|object| object.key().map(str::to_string)),
+            );
+            if !output.is_truncated().unwrap_or(false) {
+                return Ok(keys);
+            }
+            continuation_token = output.next_continuation_token().map(str::to_string);
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        self.client
+            .delete_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .send()
+            .await
+            .map_err(|e| self.error("delete", key, e))?;
+        Ok(())
+    }
+}
+
+type RegionFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T>> + Send + 'a>>;
+
+/// Outcome of waiting for a replica to receive a write
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Verification {
+    Verified,
+    TimedOut,
+    Failed,
+}
+
+impl Verification {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Verification::Verified => "verified",
+            Verification::TimedOut => "timeout",
+            Verification::Failed => "error",
+        }
+    }
+}
+
+#[derive(Default)]
+struct RegionCounters {
+    pending: AtomicU64,
+    verified: AtomicU64,
+    failed: AtomicU64,
+    /// Lag of the last verified write in milliseconds, plus one so 0 means none
+    last_lag_ms: AtomicU64,
+}
+
+/// Point-in-time replication state of one replica
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplicationStatus {
+    pub region: String,
+    /// Writes whose copy hasn't been verified yet
+    pub pending: u64,
+    pub verified: u64,
+    /// Writes that timed out or whose last check errored
+    pub failed: u64,
+    /// Lag of the most recently verified write
+    pub last_lag: Option<Duration>,
+}
+
+/// Verification counters per replica region
+struct ReplicationMetrics {
+    regions: HashMap<String, RegionCounters>,
+}
+
+impl ReplicationMetrics {
+    fn new(replicas: &[Arc<dyn ObjectStore>]) -> Self {
+        Self {
+            regions: replicas
+                .iter()
+                .map(|replica| (replica.region().to_string(), RegionCounters::default()))
+                .collect(),
+        }
+    }
+
+    fn record_pending(&self, region: &str) {
+        let pending = self.regions[region].pending.fetch_add(1, Ordering::Relaxed) + 1;
+        metrics::gauge!("schema_registry_s3_replication_pending", "region" => region.to_string())
+            .set(pending as f64);
+    }
+
+    fn record_outcome(&self, region: &str, outcome: Verification, lag: Duration) {
+        let counters = &self.regions[region];
+        let pending = counters.pending.fetch_sub(1, Ordering::Relaxed) - 1;
+        metrics::gauge!("schema_registry_s3_replication_pending", "region" => region.to_string())
+            .set(pending as f64);
+        metrics::counter!(
+            "schema_registry_s3_replication_verifications_total",
+            "region" => region.to_string(),
+            "result" => outcome.as_str()
+        )
+        .increment(1);
+
+        if outcome == Verification::Verified {
+            counters.verified.fetch_add(1, Ordering::Relaxed);
+            counters
+                .last_lag_ms
+                .store(lag.as_millis() as u64 + 1, Ordering::Relaxed);
+            metrics::histogram!("schema_registry_s3_replication_lag_seconds", "region" => region.to_string())
+                .record(lag.as_secs_f64());
+        } else {
+            counters.failed.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn status(&self, region: &str) -> ReplicationStatus {
+        let counters = &self.regions[region];
+        let last_lag_ms = counters.last_lag_ms.load(Ordering::Relaxed);
+        ReplicationStatus {
+            region: region.to_string(),
+            pending: counters.pending.load(Ordering::Relaxed),
+            verified: counters.verified.load(Ordering::Relaxed),
+            failed: counters.failed.load(Ordering::Relaxed),
+            last_lag: (last_lag_ms > 0).then(|| Duration::from_millis(last_lag_ms - 1)),
+        }
+    }
+}
+
 /// S3 storage backend
-pub struct S3Storage {}
+///
+/// Writes go to the primary region only; replicas are read from when an
+/// earlier region fails, and checked after each write.
+pub struct S3Storage {
+    primary: Arc<dyn ObjectStore>,
+    replicas: Vec<Arc<dyn ObjectStore>>,
+    verify_interval: Duration,
+    verify_timeout: Duration,
+    replication: Arc<ReplicationMetrics>,
+}
 
 impl S3Storage {
-    pub async fn new(_config: StorageConfig) -> Result<Self> {
-        Ok(Self {})
+    /// Connect to the bucket of an `S3` config, or to every region of an
+    /// `S3Replicated` one
+    pub async fn new(config: StorageConfig) -> Result<Self> {
+        let replication = match config {
+            StorageConfig::S3 { bucket, region } => {
+                ReplicationConfig::new(S3Region { region, bucket }, Vec::new())
+            }
+            StorageConfig::S3Replicated(replication) => replication,
+            _ => {
+                return Err(Error::ConfigError(
+                    "S3 storage requires an S3 storage configuration".to_string(),
+                ))
+            }
+        };
+
+        let primary = Arc::new(S3Bucket::connect(&replication.primary).await);
+        let mut replicas: Vec<Arc<dyn ObjectStore>> = Vec::new();
+        for replica in &replication.replicas {
+            replicas.push(Arc::new(S3Bucket::connect(replica).await));
+        }
+
+        Ok(Self::with_stores(primary, replicas)
+            .with_verification(replication.verify_interval, replication.verify_timeout))
+    }
+
+    /// Storage over already connected regions, e.g. S3-compatible stores
+    pub fn with_stores(primary: Arc<dyn ObjectStore>, replicas: Vec<Arc<dyn ObjectStore>>) -> Self {
+        Self {
+            replication: Arc::new(ReplicationMetrics::new(&replicas)),
+            primary,
+            replicas,
+            verify_interval: DEFAULT_VERIFY_INTERVAL,
+            verify_timeout: DEFAULT_VERIFY_TIMEOUT,
+        }
+    }
+
+    /// Set how often replicas are checked after a write, and for how long
+    pub fn with_verification(mut self, interval: Duration, timeout: Duration) -> Self {
+        self.verify_interval = interval;
+        self.verify_timeout = timeout;
+        self
     }
+
+    /// Verification state of every replica, in failover order
+    pub fn replication_status(&self) -> Vec<ReplicationStatus> {
+        self.replicas
+            .iter()
+            .map(|replica| self.replication.status(replica.region()))
+            .collect()
+    }
+
+    /// Run `op` against the primary, then each replica, until one succeeds
+    async fn read<'a, T>(
+        &'a self,
+        op: impl Fn(&'a dyn ObjectStore) -> RegionFuture<'a, T> + Sync,
+    ) -> Result<T> {
+        let mut error = None;
+        for (i, store) in std::iter::once(&self.primary)
+            .chain(&self.replicas)
+            .enumerate()
+        {
+            match op(store.as_ref()).await {
+                Ok(value) => {
+                    if i > 0 {
+                        metrics::counter!(
+                            "schema_registry_s3_read_failovers_total",
+                            "region" => store.region().to_string()
+                        )
+                        .increment(1);
+                    }
+                    return Ok(value);
+                }
+                Err(e) => {
+                    tracing::warn!(region = store.region(), error = %e, "S3 read failed");
+                    error = Some(e);
+                }
+            }
+        }
+        Err(error.expect("the primary region is always tried"))
+    }
+
+    /// Check every replica in the background until it holds `checksum` at `key`
+    fn verify_replication(&self, key: String, checksum: String) {
+        let written = Instant::now();
+        for replica in &self.replicas {
+            let replica = replica.clone();
+            let metrics = self.replication.clone();
+            let (key, checksum) = (key.clone(), checksum.clone());
+            let (interval, timeout) = (self.verify_interval, self.verify_timeout);
+
+            metrics.record_pending(replica.region());
+            tokio::spawn(async move {
+                let outcome = await_replica(
+                    replica.as_ref(),
+                    &key,
+                    &checksum,
+                    written,
+                    interval,
+                    timeout,
+                )
+                .await;
+                if outcome != Verification::Verified {
+                    tracing::warn!(
+                        region = replica.region(),
+                        key = %key,
+                        result = outcome.as_str(),
+                        "Replica did not receive archived schema"
+                    );
+                }
+                metrics.record_outcome(replica.region(), outcome, written.elapsed());
+            });
+        }
+    }
+
+    async fn latest_version(&self, id: Uuid) -> Result<Option<SemanticVersion>> {
+        Ok(self.list_versions(id).await?.pop())
+    }
+}
+
+/// Poll `replica` until its copy of `key` matches `checksum` or `timeout`
+/// passes since the write
+async fn await_replica(
+    replica: &dyn ObjectStore,
+    key: &str,
+    checksum: &str,
+    written: Instant,
+    interval: Duration,
+    timeout: Duration,
+) -> Verification {
+    loop {
+        // A missing key or a different checksum means the copy hasn't arrived
+        let outcome = match replica.checksum(key).await {
+            Ok(Some(copy)) if copy == checksum => return Verification::Verified,
+            Ok(_) => Verification::TimedOut,
+            Err(e) => {
+                tracing::debug!(region = replica.region(), error = %e, "Replica check failed");
+                Verification::Failed
+            }
+        };
+        if written.elapsed() + interval > timeout {
+            return outcome;
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+fn object_prefix(id: Uuid) -> String {
+    format!("{}{}/", OBJECT_PREFIX, id)
+}
+
+fn object_key(id: Uuid, version: &SemanticVersion) -> String {
+    format!("{}{}.json", object_prefix(id), version)
 }
 
 #[async_trait]
 impl SchemaStorage for S3Storage {
-    async fn store(&self, _schema: RegisteredSchema) -> Result<()> {
+    async fn store(&self, schema: RegisteredSchema) -> Result<()> {
+        let key = object_key(schema.id, &schema.version);
+        let body =
+            serde_json::to_vec(&schema).map_err(|e| Error::SerializationError(e.to_string()))?;
+        let checksum = hex::encode(Sha256::digest(&body));
+
+        self.primary.put(&key, body, &checksum).await?;
+        self.verify_replication(key, checksum);
         Ok(())
     }
 
-    async fn retrieve(&self, _id: Uuid, _version: Option<SemanticVersion>) -> Result<RegisteredSchema> {
-        Err(Error::InternalError("Not implemented".to_string()))
+    async fn retrieve(&self, id: Uuid, version: Option<SemanticVersion>) -> Result<RegisteredSchema> {
+        let version = match version {
+            Some(version) => version,
+            None => self
+                .latest_version(id)
+                .await?
+                .ok_or_else(|| Error::SchemaNotFound(format!("Schema {} is not archived", id)))?,
+        };
+
+        let key = object_key(id, &version);
+        let body = self.read(|store| store.get(&key)).await?.ok_or_else(|| {
+            Error::SchemaNotFound(format!("Schema {} version {} is not archived", id, version))
+        })?;
+        serde_json::from_slice(&body).map_err(|e| Error::SerializationError(e.to_string()))
     }
 
     async fn retrieve_by_hash(&self, _content_hash: &str) -> Result<Option<RegisteredSchema>> {
+        // The archive is keyed by schema ID only
         Ok(None)
     }
 
-    async fn update(&self, _schema: RegisteredSchema) -> Result<()> {
-        Ok(())
+    async fn update(&self, schema: RegisteredSchema) -> Result<()> {
+        self.store(schema).await
     }
 
-    async fn delete(&self, _id: Uuid, _version: SemanticVersion) -> Result<()> {
+    async fn delete(&self, id: Uuid, version: SemanticVersion) -> Result<()> {
+        let key = object_key(id, &version);
+        self.primary.delete(&key).await?;
+        // Replication doesn't carry deletes by default; a copy left behind
+        // would be served on failover, so remove it directly
+        for replica in &self.replicas {
+            if let Err(e) = replica.delete(&key).await {
+                tracing::warn!(region = replica.region(), key = %key, error = %e, "Failed to delete replica copy");
+            }
+        }
         Ok(())
     }
 
-    async fn list_versions(&self, _id: Uuid) -> Result<Vec<SemanticVersion>> {
-        Ok(vec![])
+    async fn list_versions(&self, id: Uuid) -> Result<Vec<SemanticVersion>> {
+        let prefix = object_prefix(id);
+        let keys = self.read(|store| store.list(&prefix)).await?;
+
+        let mut versions: Vec<SemanticVersion> = keys
+            .iter()
+            .filter_map(|key| {
+                key.strip_prefix(&prefix)?
+                    .strip_suffix(".json")?
+                    .parse()
+                    .ok()
+            })
+            .collect();
+        versions.sort();
+        Ok(versions)
     }
 
     async fn find_by_name(&self, _namespace: &str, _name: &str) -> Result<Vec<RegisteredSchema>> {
@@ -49,38 +585,103 @@ impl SchemaStorage for S3Storage {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use parking_lot::Mutex;
     use schema_registry_core::{
         schema::SchemaMetadata,
         types::SerializationFormat,
         CompatibilityMode, SchemaState, SemanticVersion, RegisteredSchema, SchemaLifecycle,
     };
+    use std::sync::atomic::AtomicBool;
     use uuid::Uuid;
 
-    #[tokio::test]
-    async fn test_s3_storage_creation() {
-        let config = StorageConfig::S3 {
-            bucket: "test-bucket".to_string(),
-            region: "us-east-1".to_string(),
-        };
+    /// In-memory bucket; `down` makes every call fail like an unreachable region
+    struct MemoryStore {
+        region: String,
+        objects: Mutex<HashMap<String, (Vec<u8>, String)>>,
+        down: AtomicBool,
+    }
 
-        let storage = S3Storage::new(config).await;
-        assert!(storage.is_ok());
+    impl MemoryStore {
+        fn new(region: &str) -> Arc<Self> {
+            Arc::new(Self {
+                region: region.to_string(),
+                objects: Mutex::new(HashMap::new()),
+                down: AtomicBool::new(false),
+            })
+        }
+
+        fn check(&self) -> Result<()> {
+            if self.down.load(Ordering::Relaxed) {
+                return Err(Error::StorageError(format!(
+                    "{} is unreachable",
+                    self.region
+                )));
+            }
+            Ok(())
+        }
+
+        /// Copy every object to `replica`, as S3 replication would
+        fn replicate_to(&self, replica: &MemoryStore) {
+            replica.objects.lock().extend(self.objects.lock().clone());
+        }
     }
 
-    #[tokio::test]
-    async fn test_s3_storage_store_succeeds() {
-        let config = StorageConfig::S3 {
-            bucket: "test-bucket".to_string(),
-            region: "us-east-1".to_string(),
-        };
+    #[async_trait]
+    impl ObjectStore for MemoryStore {
+        fn region(&self) -> &str {
+            &self.region
+        }
 
-        let storage = S3Storage::new(config).await.unwrap();
-        let id = Uuid::new_v4();
-        let schema = RegisteredSchema {
+        async fn put(&self, key: &str, body: Vec<u8>, checksum: &str) -> Result<()> {
+            self.check()?;
+            self.objects
+                .lock()
+                .insert(key.to_string(), (body, checksum.to_string()));
+            Ok(())
+        }
+
+        async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+            self.check()?;
+            Ok(self.objects.lock().get(key).map(|(body, _)| body.clone()))
+        }
+
+        async fn checksum(&self, key: &str) -> Result<Option<String>> {
+            self.check()?;
+            Ok(self
+                .objects
+                .lock()
+                .get(key)
+                .map(|(_, checksum)| checksum.clone()))
+        }
+
+        async fn list(&self, prefix: &str) -> Result<Vec<String>> {
+            self.check()?;
+            Ok(self
+                .objects
+                .lock()
+                .keys()
+                .filter(|key| key.starts_with(prefix))
+                .cloned()
+                .collect())
+        }
+
+        async fn delete(&self, key: &str) -> Result<()> {
+            self.check()?;
+            self.objects.lock().remove(key);
+            Ok(())
+        }
+    }
+
+    fn memory_storage() -> S3Storage {
+        S3Storage::with_stores(MemoryStore::new("us-east-1"), Vec::new())
+    }
+
+    fn test_schema(id: Uuid, version: SemanticVersion) -> RegisteredSchema {
+        RegisteredSchema {
             id,
             namespace: "test".to_string(),
             name: "schema".to_string(),
-            version: SemanticVersion::new(1, 0, 0),
+            version,
             format: SerializationFormat::JsonSchema,
             content: "{}".to_string(),
             content_hash: "abc123".to_string(),
@@ -100,31 +701,50 @@ mod tests {
             tags: vec![],
             examples: vec![],
             lifecycle: SchemaLifecycle::new(id),
-        };
+        }
+    }
 
-        assert!(storage.store(schema).await.is_ok());
+    /// Wait for the background verification of every replica to finish
+    async fn settled(storage: &S3Storage) -> Vec<ReplicationStatus> {
+        for _ in 0..200 {
+            let status = storage.replication_status();
+            if status.iter().all(|region| region.pending == 0) {
+                return status;
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        panic!("replication verification did not finish");
     }
 
     #[tokio::test]
-    async fn test_s3_storage_retrieve_not_implemented() {
+    async fn test_s3_storage_creation() {
         let config = StorageConfig::S3 {
             bucket: "test-bucket".to_string(),
             region: "us-east-1".to_string(),
         };
 
-        let storage = S3Storage::new(config).await.unwrap();
+        let storage = S3Storage::new(config).await;
+        assert!(storage.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_s3_storage_store_succeeds() {
+        let storage = memory_storage();
+        let schema = test_schema(Uuid::new_v4(), SemanticVersion::new(1, 0, 0));
+
+        assert!(storage.store(schema).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_s3_storage_retrieve_not_implemented() {
+        let storage = memory_storage();
         let result = storage.retrieve(Uuid::new_v4(), None).await;
         assert!(result.is_err());
     }
 
     #[tokio::test]
     async fn test_s3_storage_retrieve_by_hash() {
-        let config = StorageConfig::S3 {
-            bucket: "test-bucket".to_string(),
-            region: "us-east-1".to_string(),
-        };
-
-        let storage = S3Storage::new(config).await.unwrap();
+        let storage = memory_storage();
         let result = storage.retrieve_by_hash("test_hash").await;
         assert!(result.is_ok());
         assert!(result.unwrap().is_none());
@@ -132,12 +752,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_s3_storage_delete_succeeds() {
-        let config = StorageConfig::S3 {
-            bucket: "test-bucket".to_string(),
-            region: "us-east-1".to_string(),
-        };
-
-        let storage = S3Storage::new(config).await.unwrap();
+        let storage = memory_storage();
         let result = storage
             .delete(Uuid::new_v4(), SemanticVersion::new(1, 0, 0))
             .await;
@@ -146,12 +761,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_s3_storage_list_versions_empty() {
-        let config = StorageConfig::S3 {
-            bucket: "test-bucket".to_string(),
-            region: "us-east-1".to_string(),
-        };
-
-        let storage = S3Storage::new(config).await.unwrap();
+        let storage = memory_storage();
         let result = storage.list_versions(Uuid::new_v4()).await;
         assert!(result.is_ok());
         assert_eq!(result.unwrap().len(), 0);
@@ -159,14 +769,87 @@ mod tests {
 
     #[tokio::test]
     async fn test_s3_storage_find_by_name_empty() {
-        let config = StorageConfig::S3 {
-            bucket: "test-bucket".to_string(),
-            region: "us-east-1".to_string(),
-        };
-
-        let storage = S3Storage::new(config).await.unwrap();
+        let storage = memory_storage();
         let result = storage.find_by_name("test", "schema").await;
         assert!(result.is_ok());
         assert_eq!(result.unwrap().len(), 0);
     }
+
+    #[tokio::test]
+    async fn test_retrieve_returns_latest_archived_version() {
+        let storage = memory_storage();
+        let id = Uuid::new_v4();
+        for version in [
+            SemanticVersion::new(1, 2, 0),
+            SemanticVersion::new(1, 10, 0),
+        ] {
+            storage.store(test_schema(id, version)).await.unwrap();
+        }
+
+        let latest = storage.retrieve(id, None).await.unwrap();
+        assert_eq!(latest.version, SemanticVersion::new(1, 10, 0));
+        assert_eq!(storage.list_versions(id).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_replica_copy_is_verified_by_checksum() {
+        let primary = MemoryStore::new("us-east-1");
+        let replica = MemoryStore::new("eu-west-1");
+        let storage = S3Storage::with_stores(primary.clone(), vec![replica.clone()])
+            .with_verification(Duration::from_millis(5), Duration::from_secs(5));
+
+        storage
+            .store(test_schema(Uuid::new_v4(), SemanticVersion::new(1, 0, 0)))
+            .await
+            .unwrap();
+        assert_eq!(storage.replication_status()[0].pending, 1);
+        primary.replicate_to(&replica);
+
+        let status = settled(&storage).await;
+        assert_eq!(status[0].region, "eu-west-1");
+        assert_eq!((status[0].verified, status[0].failed), (1, 0));
+        assert!(status[0].last_lag.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_stale_replica_copy_times_out() {
+        let primary = MemoryStore::new("us-east-1");
+        let replica = MemoryStore::new("eu-west-1");
+        let storage = S3Storage::with_stores(primary.clone(), vec![replica.clone()])
+            .with_verification(Duration::from_millis(5), Duration::from_millis(30));
+        let id = Uuid::new_v4();
+
+        let mut schema = test_schema(id, SemanticVersion::new(1, 0, 0));
+        storage.store(schema.clone()).await.unwrap();
+        primary.replicate_to(&replica);
+        settled(&storage).await;
+
+        // The replica still holds the first write, so the checksum never matches
+        schema.description = "changed".to_string();
+        storage.update(schema).await.unwrap();
+
+        let status = settled(&storage).await;
+        assert_eq!((status[0].verified, status[0].failed), (1, 1));
+    }
+
+    #[tokio::test]
+    async fn test_reads_fail_over_to_replica() {
+        let primary = MemoryStore::new("us-east-1");
+        let replica = MemoryStore::new("eu-west-1");
+        let storage = S3Storage::with_stores(primary.clone(), vec![replica.clone()]);
+        let id = Uuid::new_v4();
+
+        storage
+            .store(test_schema(id, SemanticVersion::new(1, 0, 0)))
+            .await
+            .unwrap();
+        primary.replicate_to(&replica);
+        primary.down.store(true, Ordering::Relaxed);
+
+        let schema = storage.retrieve(id, None).await.unwrap();
+        assert_eq!(schema.id, id);
+
+        replica.down.store(true, Ordering::Relaxed);
+        assert!(storage.retrieve(id, None).await.is_err());
+    }
 }
@@ -1,6 +1,7 @@
 //! Test environment setup with testcontainers
 
 use anyhow::Result;
+use schema_registry_server::ServerConfig;
 use std::sync::Arc;
 use testcontainers::*;
 use testcontainers::runners::AsyncRunner;
@@ -8,6 +9,8 @@ use testcontainers_modules::{postgres::Postgres, redis::Redis, localstack::Local
 use tokio::sync::Mutex;
 use uuid::Uuid;
 
+use crate::server::TestServer;
+
 /// Comprehensive test environment with all required services
 pub struct TestEnvironment {
     pub postgres_container: Arc<Mutex<ContainerAsync<Postgres>>>,
@@ -268,6 +271,36 @@ impl TestEnvironment {
         Ok(())
     }
 
+    /// Start a registry server against this environment
+    pub async fn start_server(&self) -> Result<TestServer> {
+        self.start_server_with(|_| {}).await
+    }
+
+    /// Start a registry server, adjusting its settings first
+    ///
+    /// Each server gets a database of its own in the PostgreSQL container, so
+    /// the server's migrations don't collide with the tables created for
+    /// [`TestEnvironment::db_pool`] or with other servers.
+    pub async fn start_server_with(
+        &self,
+        configure: impl FnOnce(&mut ServerConfig),
+    ) -> Result<TestServer> {
+        let database = format!("registry_{}", Uuid::new_v4().simple());
+        sqlx::query(&format!("CREATE DATABASE {}", database))
+            .execute(self.db_pool())
+            .await?;
+        let (base_url, _) = self
+            .database_url
+            .rsplit_once('/')
+            .expect("database URL has a database name");
+        let database_url = format!("{}/{}", base_url, database);
+        tracing::info!("Created database {} for a test server", database);
+
+        let mut config = ServerConfig::new(database_url, self.redis_url.clone());
+        configure(&mut config);
+        TestServer::start(config).await
+    }
+
     /// Get database pool
     pub fn db_pool(&self) -> &sqlx::PgPool {
         self.db_pool.as_ref().expect("Database pool not initialized")
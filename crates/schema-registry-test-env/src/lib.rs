@@ -0,0 +1,28 @@
+//! # Schema Registry Test Environment
+//!
+//! Integration test harness: [`TestEnvironment`] starts PostgreSQL, Redis and
+//! LocalStack (S3) containers, and [`TestServer`] runs the registry server
+//! in-process against them.
+//!
+//! ```no_run
+//! use schema_registry_test_env::TestEnvironment;
+//!
+//! # async fn example() -> anyhow::Result<()> {
+//! let env = TestEnvironment::new().await?;
+//! let server = env
+//!     .start_server_with(|config| config.admin_token = Some("admin".to_string()))
+//!     .await?;
+//!
+//! let health = server.get("/health").await?;
+//! assert!(health.status().is_success());
+//! # Ok(())
+//! # }
+//! ```
+
+mod environment;
+mod server;
+
+pub use environment::TestEnvironment;
+pub use schema_registry_core::fixtures::Fixtures;
+pub use schema_registry_server::ServerConfig;
+pub use server::TestServer;
@@ -0,0 +1,162 @@
+//! The registry server running in-process
+
+use anyhow::{Context, Result};
+use schema_registry_core::fixtures::Fixtures;
+use schema_registry_server::{build_app, ServerConfig};
+use serde::Serialize;
+use std::net::SocketAddr;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+
+/// A registry server listening on a random local port
+///
+/// The server stops when this is dropped.
+pub struct TestServer {
+    addr: SocketAddr,
+    database_url: String,
+    admin_token: Option<String>,
+    client: reqwest::Client,
+    shutdown: Option<oneshot::Sender<()>>,
+    task: JoinHandle<()>,
+}
+
+impl TestServer {
+    /// Build the server from `config` and start serving
+    ///
+    /// The server runs its migrations on `config.database_url` first.
+    pub async fn start(config: ServerConfig) -> Result<Self> {
+        let database_url = config.database_url.clone();
+        let admin_token = config.admin_token.clone();
+        let app = build_app(config).await.context("Failed to build the registry server")?;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let (shutdown, stopped) = oneshot::channel::<()>();
+        let task = tokio::spawn(async move {
+            let result = axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+                .with_graceful_shutdown(async {
+                    let _ = stopped.await;
+                })
+                .await;
+            if let Err(e) = result {
+                tracing::error!(error = %e, "Test server failed");
+            }
+        });
+        tracing::info!("Test server listening on {}", addr);
+
+        Ok(Self {
+            addr,
+            database_url,
+            admin_token,
+            client: reqwest::Client::new(),
+            shutdown: Some(shutdown),
+            task,
+        })
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// e.g. `http://127.0.0.1:40123`
+    pub fn base_url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Absolute URL of an API path such as `/api/v1/schemas`
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url(), path)
+    }
+
+    /// The database this server was migrated into
+    pub fn database_url(&self) -> &str {
+        &self.database_url
+    }
+
+    /// HTTP client for requests the helpers below don't cover
+    pub fn client(&self) -> &reqwest::Client {
+        &self.client
+    }
+
+    pub async fn get(&self, path: &str) -> Result<reqwest::Response> {
+        Ok(self.client.get(self.url(path)).send().await?)
+    }
+
+    pub async fn post_json<T: Serialize + ?Sized>(&self, path: &str, body: &T) -> Result<reqwest::Response> {
+        Ok(self.client.post(self.url(path)).json(body).send().await?)
+    }
+
+    /// Register a schema under `subject`, returning the response body
+    ///
+    /// Fails unless the server answers with a success status.
+    pub async fn register_schema(
+        &self,
+        subject: &str,
+        schema_type: &str,
+        schema: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        let body = serde_json::json!({
+            "subject": subject,
+            "schema_type": schema_type,
+            "schema": schema,
+        });
+        let response = self.post_json("/api/v1/schemas", &body).await?;
+        let status = response.status();
+        let body: serde_json::Value = response.json().await?;
+        anyhow::ensure!(status.is_success(), "Registering {} returned {}: {}", subject, status, body);
+        Ok(body)
+    }
+
+    /// Create everything a fixture file describes, e.g. a bug report's setup
+    ///
+    /// Namespace settings and lineage edges are admin requests, so fixtures
+    /// with either need a server started with an admin token.
+    pub async fn seed(&self, fixtures: &Fixtures) -> Result<()> {
+        for request in fixtures.requests()? {
+            let mut builder = match request.method {
+                "PUT" => self.client.put(self.url(&request.path)),
+                _ => self.client.post(self.url(&request.path)),
+            };
+            if let Some(token) = &self.admin_token {
+                builder = builder.bearer_auth(token);
+            }
+            let response = builder.json(&request.body).send().await?;
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            anyhow::ensure!(
+                status.is_success(),
+                "Seeding {} returned {}: {}",
+                request.description,
+                status,
+                body
+            );
+        }
+        Ok(())
+    }
+
+    /// Seed from a fixture file (YAML); see [`TestServer::seed`]
+    pub async fn seed_file(&self, path: impl AsRef<std::path::Path>) -> Result<()> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read fixtures {}", path.display()))?;
+        let fixtures: Fixtures = serde_yaml::from_str(&content)
+            .with_context(|| format!("Invalid fixtures in {}", path.display()))?;
+        self.seed(&fixtures).await
+    }
+
+    /// Stop accepting requests and wait for in-flight ones to finish
+    pub async fn shutdown(mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+        let _ = (&mut self.task).await;
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+    }
+}
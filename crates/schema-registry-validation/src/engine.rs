@@ -9,10 +9,15 @@
 //! 6. Performance validation (complexity limits)
 //! 7. Custom rule validation (extensible rules)
 
+use crate::llm_lint;
+use crate::proto_parser::{ProtoParseErrorKind, ProtoParser, ProtoSchema};
 use crate::types::{
-    SchemaFormat, ValidationConfig, ValidationError, ValidationResult, ValidationWarning, Severity,
+    SchemaFormat, StepTiming, ValidationConfig, ValidationError, ValidationResult, ValidationStep,
+    ValidationWarning, Severity,
 };
 use anyhow::Result;
+use prost_reflect::Cardinality;
+use schema_registry_core::bounded_json;
 use std::sync::Arc;
 use std::time::Instant;
 
@@ -34,6 +39,8 @@ pub struct ValidationEngine {
     config: ValidationConfig,
     /// Custom validation rules
     custom_rules: Vec<Arc<dyn ValidationRule>>,
+    /// Compiles Protobuf schemas, with the imports they may use
+    proto_parser: ProtoParser,
 }
 
 impl ValidationEngine {
@@ -42,6 +49,7 @@ impl ValidationEngine {
         Self {
             config: ValidationConfig::default(),
             custom_rules: Vec::new(),
+            proto_parser: ProtoParser::new(),
         }
     }
 
@@ -50,14 +58,49 @@ impl ValidationEngine {
         Self {
             config,
             custom_rules: Vec::new(),
+            proto_parser: ProtoParser::new(),
         }
     }
 
+    /// Compiles Protobuf schemas with `parser`, e.g. one supplied with the
+    /// files schemas import
+    pub fn with_proto_parser(mut self, parser: ProtoParser) -> Self {
+        self.proto_parser = parser;
+        self
+    }
+
     /// Adds a custom validation rule
     pub fn add_rule(&mut self, rule: Arc<dyn ValidationRule>) {
         self.custom_rules.push(rule);
     }
 
+    /// Names of the custom rules, in the order they run
+    pub fn rule_names(&self) -> Vec<String> {
+        self.custom_rules
+            .iter()
+            .map(|rule| rule.name().to_string())
+            .collect()
+    }
+
+    /// Runs only the custom rules, e.g. to enforce org rules on registration
+    ///
+    /// Unlike step 7 of [`ValidationEngine::validate`], each finding keeps the
+    /// severity its rule gave it. A rule that fails to run is reported as an
+    /// error.
+    pub fn check_custom_rules(&self, schema: &str, format: SchemaFormat) -> Vec<ValidationError> {
+        let mut findings = Vec::new();
+        for rule in &self.custom_rules {
+            match rule.validate(schema, format) {
+                Ok(errors) => findings.extend(errors),
+                Err(e) => findings.push(ValidationError::new(
+                    rule.name(),
+                    format!("Rule execution failed: {}", e),
+                )),
+            }
+        }
+        findings
+    }
+
     /// Validates a schema using the 7-step pipeline
     pub async fn validate(&self, schema: &str, format: SchemaFormat) -> Result<ValidationResult> {
         let start = Instant::now();
@@ -80,31 +123,43 @@ impl ValidationEngine {
         }
         result.metrics.schema_size_bytes = schema_size;
 
-        // Step 1: Structural validation
-        if let Err(errors) = self.validate_structure(schema, format).await {
-            result.merge(errors);
-            if self.config.fail_fast && result.has_errors() {
+        // Reject pathological JSON before any step builds a value tree from it
+        if matches!(format, SchemaFormat::JsonSchema | SchemaFormat::Avro) {
+            if let Err(e) = bounded_json::check(schema.as_bytes(), &self.config.json_limits) {
+                result.add_error(
+                    ValidationError::new(e.code(), e.to_string())
+                        .with_suggestion("Reduce nesting depth or split the schema into smaller schemas"),
+                );
                 result.metrics.duration = start.elapsed();
                 return Ok(result);
             }
         }
 
+        // Step 1: Structural validation
+        let started = Instant::now();
+        let (Ok(step) | Err(step)) = self.validate_structure(schema, format).await;
+        record_step(&mut result, ValidationStep::Structural, started, step);
+        if self.config.fail_fast && result.has_errors() {
+            result.metrics.duration = start.elapsed();
+            return Ok(result);
+        }
+
         // Step 2: Type validation
-        if let Err(errors) = self.validate_types(schema, format).await {
-            result.merge(errors);
-            if self.config.fail_fast && result.has_errors() {
-                result.metrics.duration = start.elapsed();
-                return Ok(result);
-            }
+        let started = Instant::now();
+        let (Ok(step) | Err(step)) = self.validate_types(schema, format).await;
+        record_step(&mut result, ValidationStep::Types, started, step);
+        if self.config.fail_fast && result.has_errors() {
+            result.metrics.duration = start.elapsed();
+            return Ok(result);
         }
 
         // Step 3: Semantic validation
-        if let Err(errors) = self.validate_semantics(schema, format).await {
-            result.merge(errors);
-            if self.config.fail_fast && result.has_errors() {
-                result.metrics.duration = start.elapsed();
-                return Ok(result);
-            }
+        let started = Instant::now();
+        let (Ok(step) | Err(step)) = self.validate_semantics(schema, format).await;
+        record_step(&mut result, ValidationStep::Semantics, started, step);
+        if self.config.fail_fast && result.has_errors() {
+            result.metrics.duration = start.elapsed();
+            return Ok(result);
         }
 
         // Step 4: Compatibility validation (skipped if no previous version)
@@ -112,47 +167,49 @@ impl ValidationEngine {
 
         // Step 5: Security validation
         if self.config.security_validation {
-            if let Err(errors) = self.validate_security(schema, format).await {
-                result.merge(errors);
-                if self.config.fail_fast && result.has_errors() {
-                    result.metrics.duration = start.elapsed();
-                    return Ok(result);
-                }
+            let started = Instant::now();
+            let (Ok(step) | Err(step)) = self.validate_security(schema, format).await;
+            record_step(&mut result, ValidationStep::Security, started, step);
+            if self.config.fail_fast && result.has_errors() {
+                result.metrics.duration = start.elapsed();
+                return Ok(result);
             }
         }
 
         // Step 6: Performance validation
         if self.config.performance_validation {
-            if let Err(errors) = self.validate_performance(schema, format).await {
-                result.merge(errors);
-                if self.config.fail_fast && result.has_errors() {
-                    result.metrics.duration = start.elapsed();
-                    return Ok(result);
-                }
+            let started = Instant::now();
+            let (Ok(step) | Err(step)) = self.validate_performance(schema, format).await;
+            record_step(&mut result, ValidationStep::Performance, started, step);
+            if self.config.fail_fast && result.has_errors() {
+                result.metrics.duration = start.elapsed();
+                return Ok(result);
             }
         }
 
         // Step 7: Custom rules validation
+        let started = Instant::now();
+        let mut step = ValidationResult::success(format);
         for rule in &self.custom_rules {
             match rule.validate(schema, format) {
                 Ok(errors) => {
                     for error in errors {
-                        result.add_error(error);
+                        step.add_error(error);
                     }
-                    result.metrics.rules_applied += 1;
+                    step.metrics.rules_applied += 1;
 
-                    if self.config.fail_fast && result.has_errors() {
-                        result.metrics.duration = start.elapsed();
-                        return Ok(result);
+                    if self.config.fail_fast && step.has_errors() {
+                        break;
                     }
                 }
                 Err(e) => {
-                    result.add_error(
+                    step.add_error(
                         ValidationError::new(rule.name(), format!("Rule execution failed: {}", e)),
                     );
                 }
             }
         }
+        record_step(&mut result, ValidationStep::CustomRules, started, step);
 
         // Filter warnings if not requested
         if !self.config.include_warnings {
@@ -163,6 +220,24 @@ impl ValidationEngine {
         Ok(result)
     }
 
+    /// Runs only the LLM-friendliness lint, e.g. to warn on registration
+    pub fn lint_llm(&self, schema: &str, format: SchemaFormat) -> Vec<ValidationWarning> {
+        if !self.config.llm_validation {
+            return Vec::new();
+        }
+        llm_lint::lint(schema, format, &self.config.llm)
+    }
+
+    /// Runs only the security step, e.g. to decide whether to quarantine a schema
+    pub async fn scan_security(&self, schema: &str, format: SchemaFormat) -> ValidationResult {
+        if !self.config.security_validation {
+            return ValidationResult::success(format);
+        }
+        match self.validate_security(schema, format).await {
+            Ok(result) | Err(result) => result,
+        }
+    }
+
     /// Step 1: Validates the structural integrity of the schema
     async fn validate_structure(
         &self,
@@ -195,18 +270,41 @@ impl ValidationEngine {
                     );
                 }
             }
-            SchemaFormat::Protobuf => {
-                // Basic syntax check for protobuf
-                if !schema.contains("message") && !schema.contains("enum") {
+            SchemaFormat::Protobuf => match self.proto_parser.parse(schema) {
+                Ok(proto) => {
+                    if proto.messages().is_empty() && proto.enums().is_empty() {
+                        result.add_error(
+                            ValidationError::new(
+                                "structural-validity",
+                                "Protobuf schema must contain at least one message or enum definition",
+                            )
+                            .with_suggestion("Add a message or enum definition"),
+                        );
+                    }
+                }
+                Err(e) if e.kind == ProtoParseErrorKind::Syntax => {
                     result.add_error(
                         ValidationError::new(
                             "structural-validity",
-                            "Protobuf schema must contain at least one message or enum definition",
+                            format!("Invalid Protobuf schema: {}", e),
                         )
-                        .with_suggestion("Add a message or enum definition"),
+                        .with_suggestion("Check Protobuf schema syntax"),
                     );
                 }
-            }
+                Err(e) if e.kind == ProtoParseErrorKind::Import => {
+                    result.add_error(
+                        ValidationError::new(
+                            "protobuf-import",
+                            format!("Unresolved import(s): {}", e.missing_imports.join(", ")),
+                        )
+                        .with_suggestion(
+                            "Import only well-known types or files the registry was given",
+                        ),
+                    );
+                }
+                // Type errors are reported by step 2
+                Err(_) => {}
+            },
         }
 
         if result.has_errors() {
@@ -237,7 +335,6 @@ impl ValidationEngine {
                 }
             }
             SchemaFormat::Protobuf => {
-                // Type validation for protobuf
                 self.validate_protobuf_types(schema, &mut result);
             }
         }
@@ -271,8 +368,16 @@ impl ValidationEngine {
                 }
             }
             SchemaFormat::Protobuf => {
-                // Semantic validation for protobuf
-                self.validate_protobuf_semantics(schema, &mut result);
+                if let Ok(proto) = self.proto_parser.parse(schema) {
+                    self.validate_protobuf_semantics(&proto, &mut result);
+                }
+            }
+        }
+
+        // LLM-friendliness lint
+        if self.config.llm_validation {
+            for warning in llm_lint::lint(schema, format, &self.config.llm) {
+                result.add_warning(warning);
             }
         }
 
@@ -292,25 +397,36 @@ impl ValidationEngine {
         let mut result = ValidationResult::success(format);
         result.metrics.rules_applied += 1;
 
-        // Check for suspicious patterns
-        let suspicious_patterns = [
-            ("eval", "Contains potentially dangerous eval keyword"),
-            ("exec", "Contains potentially dangerous exec keyword"),
-            ("__proto__", "Contains prototype pollution pattern"),
-            ("constructor", "Contains constructor access pattern"),
-        ];
-
-        for (pattern, message) in &suspicious_patterns {
-            if schema.to_lowercase().contains(pattern) {
+        // Check for the configured suspicious patterns
+        for rule in self.config.security_patterns.matches(schema) {
+            if rule.blocking {
+                result.add_error(
+                    ValidationError::new("security-check", rule.message.clone())
+                        .with_context("pattern", rule.name.clone())
+                        .with_suggestion("Remove the flagged content from the schema"),
+                );
+            } else {
                 result.add_warning(
-                    ValidationWarning::new("security-check", *message)
+                    ValidationWarning::new("security-check", rule.message.clone())
                         .with_suggestion("Review schema for security implications"),
                 );
+                if rule.quarantine && self.config.quarantine_on_security_findings {
+                    result.add_quarantine_reason(rule.message.clone());
+                }
+            }
+        }
+
+        // A catastrophic `pattern` lets one schema stall every consumer that
+        // validates against it
+        if format == SchemaFormat::JsonSchema {
+            if let Ok(json) = serde_json::from_str::<serde_json::Value>(schema) {
+                self.validate_json_regexes(&json, "$", &mut result);
             }
         }
 
         // Check schema complexity (potential DoS)
         let nesting_level = self.calculate_nesting_depth(schema, format);
+        result.metrics.max_recursion_depth = nesting_level;
         if nesting_level > self.config.max_recursion_depth {
             result.add_error(
                 ValidationError::new(
@@ -332,26 +448,16 @@ impl ValidationEngine {
     }
 
     /// Step 6: Validates performance constraints
+    ///
+    /// Regex constraints are checked for ReDoS risks in step 5.
     async fn validate_performance(
         &self,
-        schema: &str,
+        _schema: &str,
         format: SchemaFormat,
     ) -> Result<ValidationResult, ValidationResult> {
         let mut result = ValidationResult::success(format);
         result.metrics.rules_applied += 1;
 
-        // Check for performance issues
-        match format {
-            SchemaFormat::JsonSchema => {
-                if let Ok(json) = serde_json::from_str::<serde_json::Value>(schema) {
-                    self.validate_json_schema_performance(&json, &mut result);
-                }
-            }
-            _ => {
-                // Basic performance checks for other formats
-            }
-        }
-
         if result.has_errors() {
             Err(result)
         } else {
@@ -397,25 +503,27 @@ impl ValidationEngine {
     }
 
     fn validate_protobuf_types(&self, schema: &str, result: &mut ValidationResult) {
-        // Check for valid protobuf types
-        let valid_types = [
-            "double", "float", "int32", "int64", "uint32", "uint64", "sint32", "sint64",
-            "fixed32", "fixed64", "sfixed32", "sfixed64", "bool", "string", "bytes",
-        ];
-
-        // Count field definitions
-        let field_count = schema.matches("=").count();
-        result.metrics.fields_validated = field_count;
-
-        // Basic validation - this is simplified
-        // In production, use a proper protobuf parser
-        if !schema.contains("message") && !schema.contains("enum") {
-            result.add_warning(
-                ValidationWarning::new(
-                    "type-validation",
-                    "No message or enum definitions found",
-                ),
-            );
+        // Compiling resolves every type reference and checks field numbers
+        // and names; syntax and import errors are step 1's
+        match self.proto_parser.parse(schema) {
+            Ok(proto) => {
+                result.metrics.fields_validated = proto.field_count();
+                if proto.messages().is_empty() && proto.enums().is_empty() {
+                    result.add_warning(
+                        ValidationWarning::new(
+                            "type-validation",
+                            "No message or enum definitions found",
+                        ),
+                    );
+                }
+            }
+            Err(e) if e.kind == ProtoParseErrorKind::Invalid => {
+                result.add_error(
+                    ValidationError::new("type-validation", e.to_string())
+                        .with_suggestion("Check type references, field numbers and names"),
+                );
+            }
+            Err(_) => {}
         }
     }
 
@@ -445,51 +553,6 @@ impl ValidationEngine {
                 }
             }
         }
-
-        // LLM-specific validation
-        if self.config.llm_validation {
-            self.validate_llm_specific(json, result);
-        }
-    }
-
-    fn validate_llm_specific(&self, json: &serde_json::Value, result: &mut ValidationResult) {
-        // Check for description
-        if json.get("description").is_none() || json.get("description").and_then(|d| d.as_str()).map(|s| s.is_empty()).unwrap_or(true) {
-            result.add_warning(
-                ValidationWarning::new(
-                    "llm-validation",
-                    "Schema lacks description for LLM understanding",
-                )
-                .with_suggestion("Add a 'description' field to help LLMs understand the schema"),
-            );
-        }
-
-        // Check properties for descriptions
-        if let Some(properties) = json.get("properties").and_then(|p| p.as_object()) {
-            for (name, prop) in properties {
-                if prop.get("description").is_none() {
-                    result.add_warning(
-                        ValidationWarning::new(
-                            "llm-validation",
-                            format!("Field '{}' lacks description for LLM context", name),
-                        )
-                        .with_location(format!("$.properties.{}", name))
-                        .with_suggestion("Add a 'description' field to improve LLM understanding"),
-                    );
-                }
-
-                // Check for examples
-                if prop.get("examples").is_none() && prop.get("example").is_none() {
-                    result.add_warning(
-                        ValidationWarning::new(
-                            "llm-validation",
-                            format!("Field '{}' lacks examples for LLM guidance", name),
-                        )
-                        .with_location(format!("$.properties.{}", name)),
-                    );
-                }
-            }
-        }
     }
 
     fn validate_avro_semantics(&self, _schema: &apache_avro::Schema, _result: &mut ValidationResult) {
@@ -497,39 +560,76 @@ impl ValidationEngine {
         // Add custom semantic checks here
     }
 
-    fn validate_protobuf_semantics(&self, schema: &str, result: &mut ValidationResult) {
-        // Check for field numbers
-        if schema.contains("message") {
-            // Ensure field numbers are unique
-            // This is a simplified check
-            let field_numbers: Vec<&str> = schema.split('=').skip(1).collect();
-            if field_numbers.len() > 1 {
-                // Check for duplicates would go here
+    fn validate_protobuf_semantics(&self, proto: &ProtoSchema, result: &mut ValidationResult) {
+        for message in proto.messages() {
+            if message.fields().next().is_none() {
+                result.add_warning(
+                    ValidationWarning::new(
+                        "semantic-validation",
+                        format!("Message '{}' has no fields", message.full_name()),
+                    )
+                    .with_location(message.full_name()),
+                );
+            }
+            // proto2 `required` can never be dropped without breaking readers
+            for field in message
+                .fields()
+                .filter(|f| f.cardinality() == Cardinality::Required)
+            {
+                result.add_warning(
+                    ValidationWarning::new(
+                        "semantic-validation",
+                        format!(
+                            "Field '{}' is required; it can never be removed compatibly",
+                            field.full_name()
+                        ),
+                    )
+                    .with_location(field.full_name())
+                    .with_suggestion("Make the field optional and validate it in the application"),
+                );
             }
         }
     }
 
-    fn validate_json_schema_performance(
-        &self,
-        json: &serde_json::Value,
-        result: &mut ValidationResult,
-    ) {
-        // Check for overly complex patterns
-        if let Some(properties) = json.get("properties").and_then(|p| p.as_object()) {
-            for (name, prop) in properties {
-                if let Some(pattern) = prop.get("pattern").and_then(|p| p.as_str()) {
-                    // Check pattern complexity
-                    if pattern.len() > 500 {
-                        result.add_warning(
-                            ValidationWarning::new(
-                                "performance-validation",
-                                format!("Complex regex pattern for field '{}' may impact performance", name),
-                            )
-                            .with_location(format!("$.properties.{}.pattern", name))
-                            .with_suggestion("Simplify the regex pattern"),
-                        );
+    /// Recursively applies the regex policy to `pattern` and `patternProperties`
+    fn validate_json_regexes(&self, json: &serde_json::Value, path: &str, result: &mut ValidationResult) {
+        match json {
+            serde_json::Value::Object(map) => {
+                if let Some(pattern) = map.get("pattern").and_then(|p| p.as_str()) {
+                    self.check_regex(pattern, &format!("{}.pattern", path), result);
+                }
+                if let Some(patterns) = map.get("patternProperties").and_then(|p| p.as_object()) {
+                    for pattern in patterns.keys() {
+                        self.check_regex(pattern, &format!("{}.patternProperties", path), result);
                     }
                 }
+                for (key, value) in map {
+                    self.validate_json_regexes(value, &format!("{}.{}", path, key), result);
+                }
+            }
+            serde_json::Value::Array(items) => {
+                for (index, value) in items.iter().enumerate() {
+                    self.validate_json_regexes(value, &format!("{}[{}]", path, index), result);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn check_regex(&self, pattern: &str, location: &str, result: &mut ValidationResult) {
+        for issue in self.config.regex_policy.check(pattern) {
+            if issue.blocking {
+                result.add_error(
+                    ValidationError::new("regex-complexity", issue.message)
+                        .with_location(location)
+                        .with_suggestion("Simplify the regex pattern or avoid nested quantifiers"),
+                );
+            } else {
+                result.add_warning(
+                    ValidationWarning::new("regex-complexity", issue.message)
+                        .with_location(location)
+                        .with_suggestion("Simplify the regex pattern or avoid nested quantifiers"),
+                );
             }
         }
     }
@@ -543,12 +643,11 @@ impl ValidationEngine {
                     0
                 }
             }
-            SchemaFormat::Protobuf => {
-                // Count message nesting
-                let open_braces = schema.matches('{').count();
-                let close_braces = schema.matches('}').count();
-                open_braces.min(close_braces)
-            }
+            SchemaFormat::Protobuf => self
+                .proto_parser
+                .parse(schema)
+                .map(|proto| proto.nesting_depth())
+                .unwrap_or(0),
         }
     }
 
@@ -581,9 +680,29 @@ impl Default for ValidationEngine {
     }
 }
 
+/// Merges a step's outcome into `result`, recording how long it took
+fn record_step(
+    result: &mut ValidationResult,
+    step: ValidationStep,
+    started: Instant,
+    outcome: ValidationResult,
+) {
+    result.metrics.steps.push(StepTiming {
+        step,
+        duration: started.elapsed(),
+        errors: outcome.errors.len(),
+        warnings: outcome.warnings.len(),
+    });
+    result.merge(outcome);
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::regex_complexity::{RegexEnforcement, RegexPolicy};
+    use schema_registry_core::config_manager_adapter::{
+        SecurityPatternRule, SecurityValidationSettings,
+    };
 
     #[tokio::test]
     async fn test_validate_valid_json_schema() {
@@ -627,6 +746,70 @@ mod tests {
         assert!(result.errors.iter().any(|e| e.rule == "schema-size"));
     }
 
+    #[tokio::test]
+    async fn test_validate_rejects_deeply_nested_json() {
+        let engine = ValidationEngine::new();
+        let depth = engine.config.json_limits.max_depth + 1;
+        let schema = format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+
+        let result = engine.validate(&schema, SchemaFormat::JsonSchema).await.unwrap();
+        assert!(!result.is_valid);
+        assert!(result.errors.iter().any(|e| e.rule == "json-depth-exceeded"));
+    }
+
+    #[tokio::test]
+    async fn test_catastrophic_regex_enforcement() {
+        let schema = r#"{"type": "object", "properties": {"code": {"type": "string", "pattern": "^(a+)+$"}}}"#;
+
+        let result = ValidationEngine::new().validate(schema, SchemaFormat::JsonSchema).await.unwrap();
+        assert!(result.is_valid);
+        assert!(result.warnings.iter().any(|w| w.rule == "regex-complexity"));
+
+        let policy = RegexPolicy {
+            enforcement: RegexEnforcement::Reject,
+            ..RegexPolicy::default()
+        };
+        let engine = ValidationEngine::with_config(ValidationConfig::default().with_regex_policy(policy));
+        let result = engine.validate(schema, SchemaFormat::JsonSchema).await.unwrap();
+        assert!(!result.is_valid);
+        assert!(result
+            .errors
+            .iter()
+            .any(|e| e.location.as_deref() == Some("$.properties.code.pattern")));
+    }
+
+    #[tokio::test]
+    async fn test_validate_records_step_timings() {
+        let engine = ValidationEngine::new();
+        let schema = r#"{"type": "object", "properties": {"a": {"type": "object", "properties": {"b": {"type": "string"}}}}}"#;
+
+        let result = engine.validate(schema, SchemaFormat::JsonSchema).await.unwrap();
+        let steps: Vec<_> = result.metrics.steps.iter().map(|t| t.step).collect();
+        assert_eq!(
+            steps,
+            vec![
+                ValidationStep::Structural,
+                ValidationStep::Types,
+                ValidationStep::Semantics,
+                ValidationStep::Security,
+                ValidationStep::Performance,
+                ValidationStep::CustomRules,
+            ]
+        );
+        assert_eq!(result.metrics.schema_size_bytes, schema.len());
+        assert!(result.metrics.max_recursion_depth > 0);
+        let semantics = &result.metrics.steps[2];
+        assert_eq!(semantics.errors, 0);
+
+        let engine = ValidationEngine::with_config(ValidationConfig::default().with_fail_fast(true));
+        let result = engine
+            .validate("syntax = \"proto3\"; message {", SchemaFormat::Protobuf)
+            .await
+            .unwrap();
+        assert_eq!(result.metrics.steps.len(), 1);
+        assert_eq!(result.metrics.steps[0].errors, result.error_count());
+    }
+
     #[tokio::test]
     async fn test_llm_validation_warnings() {
         let engine = ValidationEngine::new();
@@ -642,6 +825,89 @@ mod tests {
         assert!(result.warning_count() > 0);
     }
 
+    #[tokio::test]
+    async fn test_security_findings_quarantine() {
+        let engine = ValidationEngine::new();
+        let schema = r#"{"type": "object", "properties": {"__proto__": {"type": "string"}}}"#;
+
+        let result = engine.validate(schema, SchemaFormat::JsonSchema).await.unwrap();
+        assert!(result.is_valid);
+        assert!(result.requires_quarantine());
+
+        let engine = ValidationEngine::with_config(ValidationConfig::default().with_quarantine(false));
+        let result = engine.scan_security(schema, SchemaFormat::JsonSchema).await;
+        assert!(result.has_warnings());
+        assert!(!result.requires_quarantine());
+    }
+
+    #[tokio::test]
+    async fn test_configured_security_patterns_and_redos_scan() {
+        let mut settings = SecurityValidationSettings::default();
+        settings.patterns = vec![SecurityPatternRule {
+            name: "script".to_string(),
+            pattern: r"<script\b".to_string(),
+            message: "Contains a script tag".to_string(),
+            case_sensitive: false,
+            blocking: true,
+            quarantine: true,
+        }];
+        settings.regex_enforcement = RegexEnforcement::Reject;
+        let config = ValidationConfig::default()
+            .with_security_settings(&settings)
+            .unwrap();
+        let engine = ValidationEngine::with_config(config);
+
+        // The defaults no longer apply once patterns are configured
+        let schema = r#"{"type": "object", "properties": {"__proto__": {"type": "string"}}}"#;
+        let result = engine.scan_security(schema, SchemaFormat::JsonSchema).await;
+        assert!(!result.has_warnings());
+
+        let schema = r#"{"type": "object", "description": "<SCRIPT>alert(1)</script>"}"#;
+        let result = engine.scan_security(schema, SchemaFormat::JsonSchema).await;
+        assert!(result.has_errors());
+
+        let schema = r#"{"type": "string", "pattern": "^(a+)+$"}"#;
+        let result = engine.scan_security(schema, SchemaFormat::JsonSchema).await;
+        assert!(result
+            .errors
+            .iter()
+            .any(|e| e.rule == "regex-complexity" && e.location.as_deref() == Some("$.pattern")));
+    }
+
+    #[tokio::test]
+    async fn test_protobuf_is_validated_on_descriptors() {
+        let engine = ValidationEngine::new();
+        let schema = r#"
+syntax = "proto2";
+package billing;
+
+import "google/protobuf/timestamp.proto";
+
+message Invoice {
+  required string id = 1;
+  optional google.protobuf.Timestamp issued_at = 2;
+}
+"#;
+        let result = engine.validate(schema, SchemaFormat::Protobuf).await.unwrap();
+        assert!(result.is_valid);
+        assert_eq!(result.metrics.fields_validated, 2);
+        assert!(result
+            .warnings
+            .iter()
+            .any(|w| w.location.as_deref() == Some("billing.Invoice.id")));
+
+        let schema = "syntax = \"proto3\"; message Invoice { Customer customer = 1; }";
+        let result = engine.validate(schema, SchemaFormat::Protobuf).await.unwrap();
+        assert!(result.errors.iter().any(|e| e.rule == "type-validation"));
+
+        // Mentioning "message" no longer passes as a schema
+        let result = engine
+            .validate("// message Invoice", SchemaFormat::Protobuf)
+            .await
+            .unwrap();
+        assert!(result.errors.iter().any(|e| e.rule == "structural-validity"));
+    }
+
     #[tokio::test]
     async fn test_fail_fast_mode() {
         let config = ValidationConfig::default().with_fail_fast(true);
@@ -1,125 +1,303 @@
 //! Schema format detection
 //!
-//! Automatically detects whether a schema is JSON Schema, Apache Avro, or Protocol Buffers
-//! based on content analysis.
+//! Automatically detects whether a schema is JSON Schema, Apache Avro, Protocol Buffers
+//! or an OpenAPI component based on content analysis.
+//!
+//! Each format is scored by the evidence found for it (a `syntax` declaration, an
+//! Avro `fields` list, a `$schema` URI, ...). A format's confidence is the summed
+//! weight of its evidence, capped at 1.0, and the best-supported format wins. JSON
+//! content with no recognizable evidence falls back to JSON Schema at low
+//! confidence, since any JSON object is a valid (if permissive) JSON Schema.
 
 use crate::types::SchemaFormat;
 use anyhow::{anyhow, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::sync::LazyLock;
+
+/// Confidence given to JSON content that matched no format's evidence
+const FALLBACK_CONFIDENCE: f64 = 0.3;
+
+static PROTO_SYNTAX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(?m)^\s*syntax\s*=\s*"proto[23]"\s*;"#).unwrap());
+static PROTO_PACKAGE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\bpackage\s+[\w.]+\s*;").unwrap());
+static PROTO_BLOCK: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b(message|enum|service)\s+\w+\s*\{").unwrap());
+static PROTO_FIELD: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b\w+\s*=\s*\d+\s*(\[[^\]]*\])?\s*;").unwrap());
+
+/// A format content can be detected as
+///
+/// OpenAPI components are told apart from plain JSON Schema so callers can
+/// report them, but they register as JSON Schema; see [`DetectedFormat::schema_format`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum DetectedFormat {
+    JsonSchema,
+    Avro,
+    Protobuf,
+    /// An OpenAPI document's `components.schemas`, or a single schema object
+    /// using OpenAPI-only keywords such as `nullable` or `discriminator`
+    OpenapiComponent,
+}
 
-/// Detects the format of a schema from its content
-pub fn detect_format(content: &str) -> Result<SchemaFormat> {
-    // Try to detect based on content patterns
-    if is_protobuf(content) {
-        return Ok(SchemaFormat::Protobuf);
+impl DetectedFormat {
+    /// The format the content is validated and registered as
+    pub fn schema_format(&self) -> SchemaFormat {
+        match self {
+            DetectedFormat::JsonSchema | DetectedFormat::OpenapiComponent => {
+                SchemaFormat::JsonSchema
+            }
+            DetectedFormat::Avro => SchemaFormat::Avro,
+            DetectedFormat::Protobuf => SchemaFormat::Protobuf,
+        }
     }
 
-    // Try to parse as JSON
-    if let Ok(json) = serde_json::from_str::<Value>(content) {
-        if is_avro_json(&json) {
-            return Ok(SchemaFormat::Avro);
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DetectedFormat::JsonSchema => "json-schema",
+            DetectedFormat::Avro => "avro",
+            DetectedFormat::Protobuf => "protobuf",
+            DetectedFormat::OpenapiComponent => "openapi-component",
         }
-        if is_json_schema(&json) {
-            return Ok(SchemaFormat::JsonSchema);
-        }
-
-        // Default to JSON Schema if it's valid JSON but we can't determine
-        return Ok(SchemaFormat::JsonSchema);
     }
-
-    Err(anyhow!("Unable to detect schema format"))
 }
 
-/// Checks if JSON content is an Avro schema
-fn is_avro_json(json: &Value) -> bool {
-    // Avro schemas have a "type" field that can be:
-    // - A primitive type: "null", "boolean", "int", "long", "float", "double", "bytes", "string"
-    // - A complex type: "record", "enum", "array", "map", "fixed", "union"
-
-    if let Some(type_field) = json.get("type") {
-        if let Some(type_str) = type_field.as_str() {
-            // Check for Avro-specific types
-            match type_str {
-                "record" | "enum" | "fixed" => return true,
-                "null" | "boolean" | "int" | "long" | "float" | "double"
-                | "bytes" | "string" => {
-                    // Could be Avro or JSON Schema, check for Avro-specific fields
-                    return json.get("namespace").is_some()
-                        || json.get("fields").is_some()
-                        || json.get("symbols").is_some();
-                }
-                _ => {}
-            }
-        }
-    }
+/// A format that some evidence pointed to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FormatCandidate {
+    pub format: DetectedFormat,
+    /// Between 0.0 and 1.0
+    pub confidence: f64,
+    /// The evidence found, e.g. `"avro record type"`
+    pub signals: Vec<String>,
+}
 
-    // Check for array type (Avro union)
-    if json.is_array() {
-        return true;
-    }
+/// The outcome of [`detect`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FormatDetection {
+    pub format: DetectedFormat,
+    pub confidence: f64,
+    pub signals: Vec<String>,
+    /// Every format with some evidence, best first; the first is the detected one
+    pub candidates: Vec<FormatCandidate>,
+}
 
-    false
+/// Evidence collected for one format
+struct Evidence {
+    format: DetectedFormat,
+    score: f64,
+    signals: Vec<String>,
 }
 
-/// Checks if JSON content is a JSON Schema
-fn is_json_schema(json: &Value) -> bool {
-    // JSON Schema indicators
-    if json.get("$schema").is_some() {
-        return true;
+impl Evidence {
+    fn new(format: DetectedFormat) -> Self {
+        Self {
+            format,
+            score: 0.0,
+            signals: Vec::new(),
+        }
     }
 
-    if json.get("properties").is_some() {
-        return true;
+    fn add(&mut self, present: bool, weight: f64, signal: &str) {
+        if present {
+            self.score += weight;
+            self.signals.push(signal.to_string());
+        }
     }
 
-    if json.get("definitions").is_some() || json.get("$defs").is_some() {
-        return true;
+    fn into_candidate(self) -> FormatCandidate {
+        FormatCandidate {
+            format: self.format,
+            confidence: self.score.min(1.0),
+            signals: self.signals,
+        }
     }
+}
 
-    // Check for JSON Schema specific keywords
-    let json_schema_keywords = [
-        "allOf", "anyOf", "oneOf", "not",
-        "items", "additionalProperties",
-        "required", "minLength", "maxLength",
-        "minimum", "maximum", "pattern",
-        "enum", "const", "format",
-    ];
-
-    for keyword in &json_schema_keywords {
-        if json.get(keyword).is_some() {
-            return true;
+/// Detects the format of a schema from its content, with a confidence score
+///
+/// Fails when the content is neither JSON nor recognizable as Protobuf.
+pub fn detect(content: &str) -> Result<FormatDetection> {
+    let mut evidence = match serde_json::from_str::<Value>(content) {
+        Ok(json) => vec![
+            avro_evidence(&json),
+            json_schema_evidence(&json),
+            openapi_evidence(&json),
+        ],
+        Err(_) => vec![protobuf_evidence(content)],
+    };
+    evidence.retain(|e| e.score > 0.0);
+    // Stable, so ties keep the order above
+    evidence.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+    let mut candidates: Vec<FormatCandidate> =
+        evidence.into_iter().map(Evidence::into_candidate).collect();
+    if candidates.is_empty() {
+        if serde_json::from_str::<Value>(content).is_err() {
+            return Err(anyhow!("Unable to detect schema format"));
         }
+        candidates.push(FormatCandidate {
+            format: DetectedFormat::JsonSchema,
+            confidence: FALLBACK_CONFIDENCE,
+            signals: vec!["valid json".to_string()],
+        });
     }
 
-    false
+    let best = &candidates[0];
+    Ok(FormatDetection {
+        format: best.format,
+        confidence: best.confidence,
+        signals: best.signals.clone(),
+        candidates,
+    })
 }
 
-/// Checks if content is Protocol Buffers
-fn is_protobuf(content: &str) -> bool {
-    // Protobuf files typically contain:
-    // - syntax declaration: syntax = "proto3";
-    // - message definitions
-    // - package declarations
-
-    let content_lower = content.to_lowercase();
+/// Detects the format of a schema from its content
+pub fn detect_format(content: &str) -> Result<SchemaFormat> {
+    detect(content).map(|detection| detection.format.schema_format())
+}
 
-    // Check for syntax declaration
-    if content_lower.contains("syntax") &&
-       (content_lower.contains("proto2") || content_lower.contains("proto3")) {
-        return true;
-    }
+fn avro_evidence(json: &Value) -> Evidence {
+    let mut evidence = Evidence::new(DetectedFormat::Avro);
+    // A top-level array is a union, e.g. ["null", "string"]
+    evidence.add(json.is_array(), 0.8, "avro union");
+
+    let type_name = json.get("type").and_then(Value::as_str);
+    evidence.add(
+        matches!(type_name, Some("record" | "enum" | "fixed")),
+        0.6,
+        "avro named type",
+    );
+    evidence.add(
+        matches!(
+            type_name,
+            Some("int" | "long" | "float" | "double" | "bytes")
+        ),
+        0.4,
+        "avro primitive type",
+    );
+    evidence.add(
+        json.get("fields").is_some_and(Value::is_array),
+        0.3,
+        "avro fields",
+    );
+    evidence.add(
+        json.get("symbols").is_some_and(Value::is_array),
+        0.3,
+        "avro symbols",
+    );
+    evidence.add(
+        json.get("namespace").is_some_and(Value::is_string),
+        0.1,
+        "avro namespace",
+    );
+    evidence.add(
+        json.get("logicalType").is_some() || json.get("aliases").is_some(),
+        0.2,
+        "avro logical type or aliases",
+    );
+    evidence
+}
 
-    // Check for message keyword
-    if content_lower.contains("message") && content_lower.contains("{") {
-        return true;
-    }
+fn json_schema_evidence(json: &Value) -> Evidence {
+    let mut evidence = Evidence::new(DetectedFormat::JsonSchema);
+    let dialect = json.get("$schema").and_then(Value::as_str);
+    evidence.add(
+        dialect.is_some_and(|uri| uri.contains("json-schema.org")),
+        0.9,
+        "json schema dialect",
+    );
+    evidence.add(
+        json.get("properties").is_some_and(Value::is_object),
+        0.4,
+        "json schema properties",
+    );
+    evidence.add(
+        json.get("definitions").is_some() || json.get("$defs").is_some(),
+        0.3,
+        "json schema definitions",
+    );
+    evidence.add(
+        json.get("$id").is_some() || json.get("$ref").is_some(),
+        0.2,
+        "json schema $id or $ref",
+    );
+    evidence.add(
+        matches!(
+            json.get("type").and_then(Value::as_str),
+            Some("object" | "array" | "number" | "integer")
+        ),
+        0.3,
+        "json schema type",
+    );
+
+    // Keywords Avro never uses
+    let keywords = [
+        "allOf",
+        "anyOf",
+        "oneOf",
+        "not",
+        "items",
+        "additionalProperties",
+        "required",
+        "minLength",
+        "maxLength",
+        "minimum",
+        "maximum",
+        "pattern",
+        "const",
+        "format",
+    ];
+    let found = keywords.iter().filter(|k| json.get(**k).is_some()).count();
+    evidence.add(
+        found > 0,
+        (0.1 * found as f64).min(0.4),
+        "json schema keywords",
+    );
+    evidence
+}
 
-    // Check for package declaration
-    if content_lower.contains("package") && content_lower.contains(";") {
-        return true;
-    }
+fn openapi_evidence(json: &Value) -> Evidence {
+    let mut evidence = Evidence::new(DetectedFormat::OpenapiComponent);
+    evidence.add(
+        json.get("openapi").is_some_and(Value::is_string),
+        0.6,
+        "openapi version",
+    );
+    evidence.add(
+        json.pointer("/components/schemas")
+            .is_some_and(Value::is_object),
+        0.6,
+        "openapi component schemas",
+    );
+
+    // A single component schema only stands out by the keywords OpenAPI adds
+    let keywords = [
+        "nullable",
+        "discriminator",
+        "xml",
+        "externalDocs",
+        "example",
+    ];
+    let found = keywords.iter().filter(|k| json.get(**k).is_some()).count();
+    evidence.add(found > 0, 0.5 * found as f64, "openapi schema keywords");
+    evidence
+}
 
-    false
+fn protobuf_evidence(content: &str) -> Evidence {
+    let mut evidence = Evidence::new(DetectedFormat::Protobuf);
+    evidence.add(
+        PROTO_SYNTAX.is_match(content),
+        0.7,
+        "protobuf syntax declaration",
+    );
+    evidence.add(PROTO_BLOCK.is_match(content), 0.4, "protobuf definitions");
+    evidence.add(PROTO_PACKAGE.is_match(content), 0.2, "protobuf package");
+    evidence.add(PROTO_FIELD.is_match(content), 0.2, "protobuf field numbers");
+    evidence
 }
 
 /// Validates that the schema content matches the specified format
@@ -257,4 +435,43 @@ message User {
         let result = detect_format(schema);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_detect_scores_confidence() {
+        let detection = detect(r#"{"type": "record", "name": "User", "fields": []}"#).unwrap();
+        assert_eq!(detection.format, DetectedFormat::Avro);
+        assert!((detection.confidence - 0.9).abs() < 1e-9);
+        assert_eq!(detection.signals, vec!["avro named type", "avro fields"]);
+
+        // Nothing recognizable: plain JSON falls back to JSON Schema
+        let detection = detect(r#"{"title": "Anything"}"#).unwrap();
+        assert_eq!(detection.format, DetectedFormat::JsonSchema);
+        assert_eq!(detection.confidence, FALLBACK_CONFIDENCE);
+
+        // A JSON document mentioning "message" isn't Protobuf
+        let detection = detect(r#"{"properties": {"message": {"type": "string"}}}"#).unwrap();
+        assert_eq!(detection.format, DetectedFormat::JsonSchema);
+    }
+
+    #[test]
+    fn test_detect_openapi_component() {
+        let document = r#"{
+            "openapi": "3.0.3",
+            "components": {"schemas": {"Pet": {"type": "object"}}}
+        }"#;
+        let detection = detect(document).unwrap();
+        assert_eq!(detection.format, DetectedFormat::OpenapiComponent);
+        assert_eq!(detection.confidence, 1.0);
+        assert_eq!(detect_format(document).unwrap(), SchemaFormat::JsonSchema);
+
+        let component = r#"{
+            "type": "object",
+            "properties": {"name": {"type": "string"}},
+            "nullable": true,
+            "discriminator": {"propertyName": "kind"}
+        }"#;
+        let detection = detect(component).unwrap();
+        assert_eq!(detection.format, DetectedFormat::OpenapiComponent);
+        assert_eq!(detection.candidates[1].format, DetectedFormat::JsonSchema);
+    }
 }
@@ -0,0 +1,305 @@
+//! Avro data in its JSON form
+
+use super::{child_pointer, json_type, InstanceError};
+use apache_avro::Schema;
+use schema_registry_core::error::{Error, Result};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// A parsed Avro schema, with its named types for resolving references
+pub(super) struct AvroInstanceSchema {
+    root: Schema,
+    named: HashMap<String, Schema>,
+}
+
+impl AvroInstanceSchema {
+    pub(super) fn compile(content: &str) -> Result<Self> {
+        let root = Schema::parse_str(content)
+            .map_err(|e| Error::ParseError(format!("Avro schema cannot be parsed: {}", e)))?;
+        let mut named = HashMap::new();
+        collect_named(&root, &mut named);
+        Ok(Self { root, named })
+    }
+
+    pub(super) fn validate(&self, payload: &Value, errors: &mut Vec<InstanceError>) {
+        self.check(&self.root, payload, "", errors);
+    }
+
+    fn check(
+        &self,
+        schema: &Schema,
+        value: &Value,
+        pointer: &str,
+        errors: &mut Vec<InstanceError>,
+    ) {
+        let mismatch = |expected: &str, errors: &mut Vec<InstanceError>| {
+            errors.push(InstanceError {
+                pointer: pointer.to_string(),
+                message: format!("Expected {}, found {}", expected, json_type(value)),
+                code: "avro-type-mismatch",
+            });
+        };
+        match schema {
+            Schema::Null if !value.is_null() => mismatch("null", errors),
+            Schema::Boolean if !value.is_boolean() => mismatch("boolean", errors),
+            Schema::Int | Schema::Date | Schema::TimeMillis => {
+                let fits = value.as_i64().is_some_and(|n| i32::try_from(n).is_ok());
+                if !fits {
+                    mismatch("32-bit integer", errors);
+                }
+            }
+            Schema::Long
+            | Schema::TimeMicros
+            | Schema::TimestampMillis
+            | Schema::TimestampMicros
+            | Schema::LocalTimestampMillis
+            | Schema::LocalTimestampMicros
+                if value.as_i64().is_none() =>
+            {
+                mismatch("64-bit integer", errors)
+            }
+            Schema::Float | Schema::Double if !value.is_number() => mismatch("number", errors),
+            Schema::String | Schema::Uuid if !value.is_string() => mismatch("string", errors),
+            // Bytes are written as strings of code points 0-255
+            Schema::Bytes | Schema::Duration if !value.is_string() => {
+                mismatch("string of bytes", errors)
+            }
+            Schema::Decimal(_) if !(value.is_string() || value.is_number()) => {
+                mismatch("decimal", errors)
+            }
+            Schema::Fixed(fixed) => match value.as_str() {
+                Some(bytes) if bytes.chars().count() == fixed.size => {}
+                Some(_) => errors.push(InstanceError {
+                    pointer: pointer.to_string(),
+                    message: format!("Expected exactly {} bytes", fixed.size),
+                    code: "avro-fixed-size",
+                }),
+                None => mismatch("string of bytes", errors),
+            },
+            Schema::Enum(enum_schema) => match value.as_str() {
+                Some(symbol) if enum_schema.symbols.iter().any(|s| s == symbol) => {}
+                Some(symbol) => errors.push(InstanceError {
+                    pointer: pointer.to_string(),
+                    message: format!(
+                        "'{}' is not a symbol of {}",
+                        symbol,
+                        enum_schema.name.fullname(None)
+                    ),
+                    code: "avro-enum-symbol",
+                }),
+                None => mismatch("enum symbol", errors),
+            },
+            Schema::Array(items) => match value.as_array() {
+                Some(values) => {
+                    for (i, item) in values.iter().enumerate() {
+                        self.check(items, item, &child_pointer(pointer, &i.to_string()), errors);
+                    }
+                }
+                None => mismatch("array", errors),
+            },
+            Schema::Map(values) => match value.as_object() {
+                Some(entries) => {
+                    for (key, entry) in entries {
+                        self.check(values, entry, &child_pointer(pointer, key), errors);
+                    }
+                }
+                None => mismatch("map", errors),
+            },
+            Schema::Record(record) => {
+                let Some(object) = value.as_object() else {
+                    return mismatch(&format!("record {}", record.name.fullname(None)), errors);
+                };
+                for field in &record.fields {
+                    let field_pointer = child_pointer(pointer, &field.name);
+                    match object.get(&field.name) {
+                        Some(field_value) => {
+                            self.check(&field.schema, field_value, &field_pointer, errors)
+                        }
+                        None if field.default.is_some() => {}
+                        None => errors.push(InstanceError {
+                            pointer: field_pointer,
+                            message: format!(
+                                "Missing field '{}', which has no default",
+                                field.name
+                            ),
+                            code: "avro-missing-field",
+                        }),
+                    }
+                }
+                for key in object.keys() {
+                    if !record.fields.iter().any(|field| &field.name == key) {
+                        errors.push(InstanceError {
+                            pointer: child_pointer(pointer, key),
+                            message: format!(
+                                "Field '{}' is not declared by {}",
+                                key,
+                                record.name.fullname(None)
+                            ),
+                            code: "avro-unknown-field",
+                        });
+                    }
+                }
+            }
+            Schema::Union(union) => self.check_union(union.variants(), value, pointer, errors),
+            Schema::Ref { name } => match self.named.get(&name.fullname(None)) {
+                Some(named) => self.check(named, value, pointer, errors),
+                None => errors.push(InstanceError {
+                    pointer: pointer.to_string(),
+                    message: format!("Unknown type {}", name.fullname(None)),
+                    code: "avro-unknown-type",
+                }),
+            },
+            _ => {}
+        }
+    }
+
+    /// A union value matches one branch as it is, or is wrapped in an object
+    /// naming the branch it matches
+    fn check_union(
+        &self,
+        branches: &[Schema],
+        value: &Value,
+        pointer: &str,
+        errors: &mut Vec<InstanceError>,
+    ) {
+        let matches = |branch: &Schema, value: &Value| {
+            let mut branch_errors = Vec::new();
+            self.check(branch, value, pointer, &mut branch_errors);
+            branch_errors
+        };
+        if branches
+            .iter()
+            .any(|branch| matches(branch, value).is_empty())
+        {
+            return;
+        }
+
+        if let Some((name, wrapped)) = value
+            .as_object()
+            .filter(|object| object.len() == 1)
+            .and_then(|object| object.iter().next())
+        {
+            if let Some(branch) = branches.iter().find(|branch| branch_name(branch) == *name) {
+                let wrapped_pointer = child_pointer(pointer, name);
+                let mut branch_errors = Vec::new();
+                self.check(branch, wrapped, &wrapped_pointer, &mut branch_errors);
+                errors.extend(branch_errors);
+                return;
+            }
+        }
+
+        errors.push(InstanceError {
+            pointer: pointer.to_string(),
+            message: format!(
+                "Expected one of {}, found {}",
+                branches
+                    .iter()
+                    .map(branch_name)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                json_type(value)
+            ),
+            code: "avro-union",
+        });
+    }
+}
+
+/// Name of a union branch, as used to wrap its values
+fn branch_name(schema: &Schema) -> String {
+    match schema {
+        Schema::Record(record) => record.name.fullname(None),
+        Schema::Enum(enum_schema) => enum_schema.name.fullname(None),
+        Schema::Fixed(fixed) => fixed.name.fullname(None),
+        Schema::Ref { name } => name.fullname(None),
+        Schema::Array(_) => "array".to_string(),
+        Schema::Map(_) => "map".to_string(),
+        Schema::Null => "null".to_string(),
+        Schema::Boolean => "boolean".to_string(),
+        Schema::Int | Schema::Date | Schema::TimeMillis => "int".to_string(),
+        Schema::Float => "float".to_string(),
+        Schema::Double => "double".to_string(),
+        Schema::Bytes | Schema::Decimal(_) => "bytes".to_string(),
+        Schema::String | Schema::Uuid => "string".to_string(),
+        _ => "long".to_string(),
+    }
+}
+
+/// Named types declared anywhere in `schema`, by full name
+fn collect_named(schema: &Schema, named: &mut HashMap<String, Schema>) {
+    match schema {
+        Schema::Record(record) => {
+            named.insert(record.name.fullname(None), schema.clone());
+            for field in &record.fields {
+                collect_named(&field.schema, named);
+            }
+        }
+        Schema::Enum(enum_schema) => {
+            named.insert(enum_schema.name.fullname(None), schema.clone());
+        }
+        Schema::Fixed(fixed) => {
+            named.insert(fixed.name.fullname(None), schema.clone());
+        }
+        Schema::Array(inner) | Schema::Map(inner) => collect_named(inner, named),
+        Schema::Union(union) => {
+            for variant in union.variants() {
+                collect_named(variant, named);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn errors(schema: &str, payload: Value) -> Vec<(String, &'static str)> {
+        let schema = AvroInstanceSchema::compile(schema).unwrap();
+        let mut errors = Vec::new();
+        schema.validate(&payload, &mut errors);
+        errors.into_iter().map(|e| (e.pointer, e.code)).collect()
+    }
+
+    const ORDER: &str = r#"{
+        "type": "record", "name": "Order", "namespace": "com.shop",
+        "fields": [
+            {"name": "id", "type": "long"},
+            {"name": "status", "type": {"type": "enum", "name": "Status", "symbols": ["OPEN", "PAID"]}},
+            {"name": "note", "type": ["null", "string"], "default": null},
+            {"name": "next", "type": ["null", "Order"], "default": null}
+        ]
+    }"#;
+
+    #[test]
+    fn test_records_are_checked_field_by_field() {
+        assert!(errors(ORDER, json!({"id": 1, "status": "OPEN"})).is_empty());
+        assert!(errors(
+            ORDER,
+            json!({"id": 1, "status": "PAID", "note": {"string": "gift"}, "next": {"id": 2, "status": "OPEN"}})
+        )
+        .is_empty());
+
+        assert_eq!(
+            errors(
+                ORDER,
+                json!({"id": "1", "status": "SHIPPED", "extra": true, "next": {"status": "OPEN"}})
+            ),
+            [
+                ("/id".to_string(), "avro-type-mismatch"),
+                ("/status".to_string(), "avro-enum-symbol"),
+                ("/next".to_string(), "avro-union"),
+                ("/extra".to_string(), "avro-unknown-field"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_int_range_is_enforced() {
+        assert!(errors(r#""int""#, json!(2_147_483_647)).is_empty());
+        assert_eq!(
+            errors(r#""int""#, json!(2_147_483_648u64)),
+            [(String::new(), "avro-type-mismatch")]
+        );
+    }
+}
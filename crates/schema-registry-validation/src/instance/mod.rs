@@ -0,0 +1,306 @@
+//! Data-instance validation
+//!
+//! Checks a JSON payload against a registered schema and reports every
+//! mismatch with the JSON pointer of the offending value:
+//!
+//! - JSON Schema: the draft the schema declares with `$schema`, draft-07
+//!   when it declares none
+//! - Avro: the JSON form of the data; a union value may be given bare or
+//!   wrapped in an object naming its branch, as in `{"string": "a"}`
+//! - Protobuf: the proto3 JSON mapping of the first message the file
+//!   declares; fields may be named as declared or in lowerCamelCase
+//!
+//! Compiling a schema costs far more than checking a payload, so compiled
+//! schemas are cached by schema ID. An entry is recompiled if the content it
+//! was compiled from changes. Documents too large to parse whole are checked
+//! record by record; see [`streaming`].
+
+mod avro;
+mod protobuf;
+pub mod streaming;
+
+use jsonschema::{Draft, JSONSchema};
+use moka::future::Cache;
+use schema_registry_core::error::{Error, Result};
+use schema_registry_core::traits::{ValidationError, ValidationResult};
+use schema_registry_core::types::SerializationFormat;
+use serde_json::Value;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Compiled schemas kept by default
+pub const DEFAULT_INSTANCE_CACHE_CAPACITY: u64 = 1_000;
+
+enum CompiledSchema {
+    JsonSchema(JSONSchema),
+    Avro(avro::AvroInstanceSchema),
+    Protobuf(protobuf::ProtoInstanceSchema),
+}
+
+impl CompiledSchema {
+    /// Add an error to `errors` for every mismatch in `payload`
+    fn check(&self, payload: &Value, errors: &mut Vec<InstanceError>) {
+        match self {
+            CompiledSchema::JsonSchema(schema) => {
+                if let Err(violations) = schema.validate(payload) {
+                    errors.extend(violations.map(|e| InstanceError {
+                        pointer: e.instance_path.to_string(),
+                        message: e.to_string(),
+                        code: "instance-validation",
+                    }));
+                }
+            }
+            CompiledSchema::Avro(schema) => schema.validate(payload, errors),
+            CompiledSchema::Protobuf(schema) => schema.validate(payload, errors),
+        }
+    }
+}
+
+struct CacheEntry {
+    /// Hash of the content the schema was compiled from
+    fingerprint: u64,
+    compiled: CompiledSchema,
+}
+
+/// Validates payloads against compiled schemas, least recently used
+/// schemas evicted first
+pub struct InstanceValidator {
+    compiled: Cache<Uuid, Arc<CacheEntry>>,
+}
+
+impl InstanceValidator {
+    pub fn new(capacity: u64) -> Self {
+        Self {
+            compiled: Cache::new(capacity),
+        }
+    }
+
+    /// Check `payload` against the schema `schema_id` with `content`
+    ///
+    /// Fails only if the schema itself can't be compiled; a payload that
+    /// doesn't match comes back as an invalid result, one error per
+    /// mismatch.
+    pub async fn validate_instance(
+        &self,
+        schema_id: Uuid,
+        format: SerializationFormat,
+        content: &str,
+        payload: &Value,
+    ) -> Result<ValidationResult> {
+        let entry = self.compiled_schema(schema_id, format, content).await?;
+        let mut errors = Vec::new();
+        entry.compiled.check(payload, &mut errors);
+        Ok(ValidationResult {
+            is_valid: errors.is_empty(),
+            errors: errors.into_iter().map(ValidationError::from).collect(),
+            warnings: Vec::new(),
+            metadata: std::collections::HashMap::new(),
+        })
+    }
+
+    /// Compile and cache `schema_id` without checking a payload, e.g. to
+    /// warm the cache at startup
+    pub async fn precompile(
+        &self,
+        schema_id: Uuid,
+        format: SerializationFormat,
+        content: &str,
+    ) -> Result<()> {
+        self.compiled_schema(schema_id, format, content).await?;
+        Ok(())
+    }
+
+    /// Drop the compiled form of `schema_id`, e.g. once it's deleted
+    pub async fn invalidate(&self, schema_id: Uuid) {
+        self.compiled.invalidate(&schema_id).await;
+    }
+
+    async fn compiled_schema(
+        &self,
+        schema_id: Uuid,
+        format: SerializationFormat,
+        content: &str,
+    ) -> Result<Arc<CacheEntry>> {
+        let fingerprint = fingerprint(format, content);
+        if let Some(entry) = self.compiled.get(&schema_id).await {
+            if entry.fingerprint == fingerprint {
+                return Ok(entry);
+            }
+        }
+        let entry = Arc::new(CacheEntry {
+            fingerprint,
+            compiled: compile(format, content)?,
+        });
+        self.compiled.insert(schema_id, entry.clone()).await;
+        Ok(entry)
+    }
+}
+
+impl Default for InstanceValidator {
+    fn default() -> Self {
+        Self::new(DEFAULT_INSTANCE_CACHE_CAPACITY)
+    }
+}
+
+fn fingerprint(format: SerializationFormat, content: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    format.to_string().hash(&mut hasher);
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn compile(format: SerializationFormat, content: &str) -> Result<CompiledSchema> {
+    match format {
+        SerializationFormat::JsonSchema => {
+            let schema: Value = serde_json::from_str(content)
+                .map_err(|e| Error::ParseError(format!("Schema is not valid JSON: {}", e)))?;
+            let draft = crate::validators::json_schema::declared_draft(&schema, Draft::Draft7)
+                .map_err(|uri| Error::ParseError(format!("Unsupported meta-schema '{}'", uri)))?;
+            JSONSchema::options()
+                .with_draft(draft)
+                .compile(&schema)
+                .map(CompiledSchema::JsonSchema)
+                .map_err(|e| Error::ParseError(format!("Schema cannot be compiled: {}", e)))
+        }
+        SerializationFormat::Avro => {
+            avro::AvroInstanceSchema::compile(content).map(CompiledSchema::Avro)
+        }
+        SerializationFormat::Protobuf => {
+            protobuf::ProtoInstanceSchema::compile(content).map(CompiledSchema::Protobuf)
+        }
+    }
+}
+
+/// A value that doesn't match its schema
+struct InstanceError {
+    /// JSON pointer to the value, `""` for the payload itself
+    pointer: String,
+    message: String,
+    code: &'static str,
+}
+
+impl From<InstanceError> for ValidationError {
+    fn from(error: InstanceError) -> Self {
+        ValidationError {
+            message: error.message,
+            field_path: Some(error.pointer),
+            code: error.code.to_string(),
+        }
+    }
+}
+
+/// Pointer to `key` within the value at `pointer`
+fn child_pointer(pointer: &str, key: &str) -> String {
+    format!("{}/{}", pointer, key.replace('~', "~0").replace('/', "~1"))
+}
+
+/// Name of a JSON value's type, for error messages
+fn json_type(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn pointers(result: &ValidationResult) -> Vec<&str> {
+        result
+            .errors
+            .iter()
+            .filter_map(|e| e.field_path.as_deref())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_json_schema_reports_every_mismatch() {
+        let validator = InstanceValidator::default();
+        let schema = r#"{
+            "type": "object",
+            "required": ["id"],
+            "properties": {
+                "id": {"type": "string"},
+                "lines": {"type": "array", "items": {"type": "object", "properties": {"qty": {"type": "integer", "minimum": 1}}}}
+            }
+        }"#;
+        let id = Uuid::new_v4();
+
+        let result = validator
+            .validate_instance(
+                id,
+                SerializationFormat::JsonSchema,
+                schema,
+                &json!({"id": "a", "lines": [{"qty": 2}]}),
+            )
+            .await
+            .unwrap();
+        assert!(result.is_valid);
+
+        let result = validator
+            .validate_instance(
+                id,
+                SerializationFormat::JsonSchema,
+                schema,
+                &json!({"lines": [{"qty": 0}]}),
+            )
+            .await
+            .unwrap();
+        assert!(!result.is_valid);
+        let mut found = pointers(&result);
+        found.sort();
+        assert_eq!(found, ["", "/lines/0/qty"]);
+    }
+
+    #[tokio::test]
+    async fn test_changed_content_is_recompiled() {
+        let validator = InstanceValidator::default();
+        let id = Uuid::new_v4();
+        let payload = json!("text");
+
+        let result = validator
+            .validate_instance(
+                id,
+                SerializationFormat::JsonSchema,
+                r#"{"type": "string"}"#,
+                &payload,
+            )
+            .await
+            .unwrap();
+        assert!(result.is_valid);
+
+        let result = validator
+            .validate_instance(
+                id,
+                SerializationFormat::JsonSchema,
+                r#"{"type": "integer"}"#,
+                &payload,
+            )
+            .await
+            .unwrap();
+        assert!(!result.is_valid);
+    }
+
+    #[tokio::test]
+    async fn test_uncompilable_schemas_fail() {
+        let validator = InstanceValidator::default();
+        let result = validator
+            .validate_instance(
+                Uuid::new_v4(),
+                SerializationFormat::Avro,
+                "{not avro",
+                &json!({}),
+            )
+            .await;
+        assert!(result.is_err());
+    }
+}
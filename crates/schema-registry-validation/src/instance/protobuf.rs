@@ -0,0 +1,303 @@
+//! Protobuf data in its proto3 JSON mapping
+//!
+//! Payloads are checked against the descriptors [`ProtoParser`] compiles the
+//! `.proto` file into, so imports and nested type references resolve as
+//! protoc resolves them. Messages are walked field by field to report every
+//! mismatch with its pointer; the `google.protobuf` well-known types, whose
+//! JSON forms are special (timestamps as RFC 3339 strings, wrappers as bare
+//! values, ...), are checked by deserializing them as a [`DynamicMessage`].
+
+use super::{child_pointer, json_type, InstanceError};
+use crate::proto_parser::ProtoParser;
+use prost_reflect::{DynamicMessage, Kind, MessageDescriptor};
+use schema_registry_core::error::{Error, Result};
+use serde_json::Value;
+
+/// The first message of a `.proto` file, compiled
+pub(super) struct ProtoInstanceSchema {
+    root: MessageDescriptor,
+}
+
+impl ProtoInstanceSchema {
+    pub(super) fn compile(content: &str) -> Result<Self> {
+        let schema = ProtoParser::new()
+            .parse(content)
+            .map_err(|e| Error::ParseError(format!("Invalid Protobuf schema: {}", e)))?;
+        let root =
+            schema.file().messages().next().ok_or_else(|| {
+                Error::ParseError("Protobuf schema declares no message".to_string())
+            })?;
+        Ok(Self { root })
+    }
+
+    pub(super) fn validate(&self, payload: &Value, errors: &mut Vec<InstanceError>) {
+        check_message(&self.root, payload, "", errors);
+    }
+}
+
+fn check_message(
+    message: &MessageDescriptor,
+    value: &Value,
+    pointer: &str,
+    errors: &mut Vec<InstanceError>,
+) {
+    if message.package_name() == "google.protobuf" {
+        if let Err(e) = DynamicMessage::deserialize(message.clone(), value) {
+            errors.push(InstanceError {
+                pointer: pointer.to_string(),
+                message: format!("Invalid {}: {}", message.full_name(), e),
+                code: "protobuf-type-mismatch",
+            });
+        }
+        return;
+    }
+    let Some(object) = value.as_object() else {
+        errors.push(mismatch(
+            pointer,
+            &format!("message {}", message.full_name()),
+            value,
+        ));
+        return;
+    };
+    for (key, field_value) in object {
+        let field_pointer = child_pointer(pointer, key);
+        let Some(field) = message
+            .get_field_by_name(key)
+            .or_else(|| message.get_field_by_json_name(key))
+        else {
+            errors.push(InstanceError {
+                pointer: field_pointer,
+                message: format!("Field '{}' is not declared by {}", key, message.full_name()),
+                code: "protobuf-unknown-field",
+            });
+            continue;
+        };
+        // null is the field's default value
+        if field_value.is_null() {
+            continue;
+        }
+        if let (true, Kind::Message(entry)) = (field.is_map(), field.kind()) {
+            let Some(entries) = field_value.as_object() else {
+                errors.push(mismatch(&field_pointer, "map", field_value));
+                continue;
+            };
+            let key_kind = entry.map_entry_key_field().kind();
+            let value_kind = entry.map_entry_value_field().kind();
+            for (entry_key, entry) in entries {
+                let entry_pointer = child_pointer(&field_pointer, entry_key);
+                if !map_key_fits(&key_kind, entry_key) {
+                    errors.push(InstanceError {
+                        pointer: entry_pointer.clone(),
+                        message: format!(
+                            "Map key '{}' is not a valid {}",
+                            entry_key,
+                            kind_name(&key_kind)
+                        ),
+                        code: "protobuf-type-mismatch",
+                    });
+                }
+                check_value(&value_kind, entry, &entry_pointer, errors);
+            }
+        } else if field.is_list() {
+            let Some(items) = field_value.as_array() else {
+                errors.push(mismatch(&field_pointer, "array", field_value));
+                continue;
+            };
+            for (i, item) in items.iter().enumerate() {
+                let item_pointer = child_pointer(&field_pointer, &i.to_string());
+                check_value(&field.kind(), item, &item_pointer, errors);
+            }
+        } else {
+            check_value(&field.kind(), field_value, &field_pointer, errors);
+        }
+    }
+}
+
+fn check_value(kind: &Kind, value: &Value, pointer: &str, errors: &mut Vec<InstanceError>) {
+    let fits = match kind {
+        Kind::Int32 | Kind::Sint32 | Kind::Sfixed32 => {
+            integer_fits(value, i32::MIN as i128, i32::MAX as i128)
+        }
+        Kind::Uint32 | Kind::Fixed32 => integer_fits(value, 0, u32::MAX as i128),
+        Kind::Int64 | Kind::Sint64 | Kind::Sfixed64 => {
+            integer_fits(value, i64::MIN as i128, i64::MAX as i128)
+        }
+        Kind::Uint64 | Kind::Fixed64 => integer_fits(value, 0, u64::MAX as i128),
+        Kind::Float | Kind::Double => {
+            value.is_number() || matches!(value.as_str(), Some("NaN" | "Infinity" | "-Infinity"))
+        }
+        Kind::Bool => value.is_boolean(),
+        Kind::String | Kind::Bytes => value.is_string(),
+        Kind::Message(message) => return check_message(message, value, pointer, errors),
+        Kind::Enum(enumeration) => match value {
+            Value::String(symbol) if enumeration.get_value_by_name(symbol).is_some() => true,
+            Value::String(symbol) => {
+                errors.push(InstanceError {
+                    pointer: pointer.to_string(),
+                    message: format!(
+                        "'{}' is not a value of {}",
+                        symbol,
+                        enumeration.full_name()
+                    ),
+                    code: "protobuf-enum-value",
+                });
+                return;
+            }
+            value => integer_fits(value, i32::MIN as i128, i32::MAX as i128),
+        },
+    };
+    if !fits {
+        errors.push(mismatch(pointer, &kind_name(kind), value));
+    }
+}
+
+/// Name of a field type as the `.proto` file spells it
+fn kind_name(kind: &Kind) -> String {
+    match kind {
+        Kind::Double => "double".to_string(),
+        Kind::Float => "float".to_string(),
+        Kind::Int32 => "int32".to_string(),
+        Kind::Int64 => "int64".to_string(),
+        Kind::Uint32 => "uint32".to_string(),
+        Kind::Uint64 => "uint64".to_string(),
+        Kind::Sint32 => "sint32".to_string(),
+        Kind::Sint64 => "sint64".to_string(),
+        Kind::Fixed32 => "fixed32".to_string(),
+        Kind::Fixed64 => "fixed64".to_string(),
+        Kind::Sfixed32 => "sfixed32".to_string(),
+        Kind::Sfixed64 => "sfixed64".to_string(),
+        Kind::Bool => "bool".to_string(),
+        Kind::String => "string".to_string(),
+        Kind::Bytes => "bytes".to_string(),
+        Kind::Message(message) => message.full_name().to_string(),
+        Kind::Enum(enumeration) => enumeration.full_name().to_string(),
+    }
+}
+
+fn mismatch(pointer: &str, expected: &str, value: &Value) -> InstanceError {
+    InstanceError {
+        pointer: pointer.to_string(),
+        message: format!("Expected {}, found {}", expected, json_type(value)),
+        code: "protobuf-type-mismatch",
+    }
+}
+
+/// Integers may be written as numbers or, for 64-bit values, as strings
+fn integer_fits(value: &Value, min: i128, max: i128) -> bool {
+    let n = match value {
+        Value::Number(n) => n
+            .as_i64()
+            .map(i128::from)
+            .or_else(|| n.as_u64().map(i128::from))
+            .or_else(|| n.as_f64().filter(|f| f.fract() == 0.0).map(|f| f as i128)),
+        Value::String(s) => s.parse::<i128>().ok(),
+        _ => None,
+    };
+    n.is_some_and(|n| (min..=max).contains(&n))
+}
+
+/// Map keys are always JSON strings, holding a value of the key type
+fn map_key_fits(kind: &Kind, key: &str) -> bool {
+    match kind {
+        Kind::Bool => matches!(key, "true" | "false"),
+        // Integer keys are in range as strings are for 64-bit values
+        kind => {
+            let mut errors = Vec::new();
+            check_value(kind, &Value::String(key.to_string()), "", &mut errors);
+            errors.is_empty()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const ORDER: &str = r#"
+        syntax = "proto3";
+        package shop.v1;
+
+        import "google/protobuf/timestamp.proto";
+
+        // An order
+        message Order {
+            string order_id = 1;
+            Status status = 2;
+            repeated Line lines = 3;
+            map<string, int64> counters = 4;
+            google.protobuf.Timestamp placed_at = 5;
+            oneof payment {
+                string card_token = 6;
+                string voucher = 7;
+            }
+
+            message Line {
+                string sku = 1;
+                uint32 qty = 2 [deprecated = true];
+            }
+        }
+
+        enum Status {
+            STATUS_UNSPECIFIED = 0;
+            OPEN = 1;
+        }
+    "#;
+
+    fn errors(payload: Value) -> Vec<(String, &'static str)> {
+        let schema = ProtoInstanceSchema::compile(ORDER).unwrap();
+        let mut errors = Vec::new();
+        schema.validate(&payload, &mut errors);
+        errors.into_iter().map(|e| (e.pointer, e.code)).collect()
+    }
+
+    #[test]
+    fn test_first_message_is_checked_in_json_mapping() {
+        assert!(errors(json!({
+            "orderId": "o-1",
+            "status": "OPEN",
+            "lines": [{"sku": "a", "qty": 2}],
+            "counters": {"views": "12"},
+            "placed_at": "2025-01-15T10:30:00Z",
+            "voucher": "SPRING",
+        }))
+        .is_empty());
+
+        assert_eq!(
+            errors(json!({
+                "status": "CLOSED",
+                "lines": [{"sku": 3, "qty": -1}],
+                "shipping": "express",
+            })),
+            [
+                ("/lines/0/qty".to_string(), "protobuf-type-mismatch"),
+                ("/lines/0/sku".to_string(), "protobuf-type-mismatch"),
+                ("/shipping".to_string(), "protobuf-unknown-field"),
+                ("/status".to_string(), "protobuf-enum-value"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_well_known_types_use_their_json_forms() {
+        assert_eq!(
+            errors(json!({
+                "placed_at": "yesterday",
+                "counters": {"views": 1, "clicks": "many"},
+            })),
+            [
+                ("/counters/clicks".to_string(), "protobuf-type-mismatch"),
+                ("/placed_at".to_string(), "protobuf-type-mismatch"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_files_without_messages_or_with_unresolved_imports_are_refused() {
+        assert!(ProtoInstanceSchema::compile("syntax = \"proto3\";").is_err());
+        assert!(ProtoInstanceSchema::compile(
+            "syntax = \"proto3\"; import \"shop/common.proto\"; message A { shop.Money m = 1; }"
+        )
+        .is_err());
+    }
+}
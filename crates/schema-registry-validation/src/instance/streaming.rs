@@ -0,0 +1,286 @@
+//! Validation of documents too large to hold in memory
+//!
+//! Documents handed over for RAG ingestion run to hundreds of megabytes,
+//! nearly all of it a list of records such as chunks or passages. Parsing one
+//! into a single `Value` takes several times its size in memory, so the
+//! document is read record by record instead: each record is parsed, checked
+//! against the schema and dropped before the next one is read. Memory stays
+//! proportional to the largest record, not the document.
+//!
+//! Reading blocks, so async callers should run it on the blocking pool.
+
+use super::{compile, CompiledSchema, InstanceError};
+use schema_registry_core::error::{Error, Result};
+use schema_registry_core::traits::ValidationError;
+use schema_registry_core::types::SerializationFormat;
+use serde::de::{self, DeserializeOwned, SeqAccess, Visitor};
+use serde_json::Value;
+use std::fmt;
+use std::io::{self, BufReader, Read};
+use std::marker::PhantomData;
+use std::ops::ControlFlow;
+
+/// Errors kept by default; later ones are only counted
+pub const DEFAULT_MAX_ERRORS: usize = 1_000;
+
+/// How records are laid out in a document
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StreamLayout {
+    /// One top-level array whose elements are the records
+    #[default]
+    Array,
+    /// Records one after another, separated by whitespace, as in JSON Lines
+    Lines,
+}
+
+/// What checking a document found
+#[derive(Debug, Clone, Default)]
+pub struct StreamReport {
+    pub records: u64,
+    pub invalid_records: u64,
+    pub bytes_read: u64,
+    /// Mismatches, each with the JSON pointer of the offending value
+    /// prefixed by its record's index, e.g. `/41/metadata/source`
+    pub errors: Vec<ValidationError>,
+    /// Whether errors past the limit were left out of `errors`
+    pub truncated: bool,
+}
+
+impl StreamReport {
+    pub fn is_valid(&self) -> bool {
+        self.invalid_records == 0
+    }
+
+    fn record(&mut self, index: u64, found: Vec<InstanceError>, max_errors: usize) {
+        self.records += 1;
+        if found.is_empty() {
+            return;
+        }
+        self.invalid_records += 1;
+        for error in found {
+            if self.errors.len() >= max_errors {
+                self.truncated = true;
+                break;
+            }
+            let mut error = ValidationError::from(error);
+            error.field_path = error
+                .field_path
+                .map(|pointer| format!("/{}{}", index, pointer));
+            self.errors.push(error);
+        }
+    }
+}
+
+/// Checks every record of a document against one schema
+pub struct StreamingValidator {
+    schema: CompiledSchema,
+    layout: StreamLayout,
+    max_errors: usize,
+}
+
+impl StreamingValidator {
+    /// Compile the schema records are checked against
+    pub fn new(format: SerializationFormat, content: &str) -> Result<Self> {
+        Ok(Self {
+            schema: compile(format, content)?,
+            layout: StreamLayout::default(),
+            max_errors: DEFAULT_MAX_ERRORS,
+        })
+    }
+
+    pub fn with_layout(mut self, layout: StreamLayout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    /// Keep at most `max_errors` errors in the report
+    pub fn with_max_errors(mut self, max_errors: usize) -> Self {
+        self.max_errors = max_errors;
+        self
+    }
+
+    /// Read a document from `reader` to its end, checking each record
+    ///
+    /// Fails if the document isn't well-formed JSON in the expected layout,
+    /// naming the line and column; records that don't match the schema are
+    /// reported, not failures.
+    pub fn validate_reader<R: Read>(&self, reader: R) -> Result<StreamReport> {
+        let mut report = StreamReport::default();
+        let mut bytes_read = 0;
+        {
+            let reader = CountingReader {
+                inner: reader,
+                count: &mut bytes_read,
+            };
+            read_records(reader, self.layout, |index, record: Value| {
+                let mut found = Vec::new();
+                self.schema.check(&record, &mut found);
+                report.record(index, found, self.max_errors);
+                ControlFlow::Continue(())
+            })?;
+        }
+        report.bytes_read = bytes_read;
+        Ok(report)
+    }
+}
+
+/// Hand each record of a document to `each` as soon as it is parsed
+///
+/// Records are parsed as `T`, so callers wanting to parse them on their own
+/// terms can take them as `Box<RawValue>`. Returning `ControlFlow::Break`
+/// stops reading, leaving the rest of the document unread and unchecked.
+/// Fails if the document read so far isn't well-formed JSON in the layout.
+pub fn read_records<R, T, F>(reader: R, layout: StreamLayout, mut each: F) -> Result<()>
+where
+    R: Read,
+    T: DeserializeOwned,
+    F: FnMut(u64, T) -> ControlFlow<()>,
+{
+    let mut stream = serde_json::Deserializer::from_reader(BufReader::new(reader));
+    match layout {
+        StreamLayout::Array => {
+            let mut stopped = false;
+            let records = RecordVisitor {
+                each: &mut each,
+                stopped: &mut stopped,
+                record: PhantomData,
+            };
+            let read = de::Deserializer::deserialize_seq(&mut stream, records);
+            if stopped {
+                return Ok(());
+            }
+            read.and_then(|()| stream.end()).map_err(malformed)
+        }
+        StreamLayout::Lines => {
+            for (index, record) in stream.into_iter::<T>().enumerate() {
+                if each(index as u64, record.map_err(malformed)?).is_break() {
+                    break;
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+fn malformed(e: serde_json::Error) -> Error {
+    Error::ParseError(format!("Document is not well-formed: {}", e))
+}
+
+/// Hands each element of an array to `each` as soon as it is parsed
+struct RecordVisitor<'a, T, F> {
+    each: &'a mut F,
+    stopped: &'a mut bool,
+    record: PhantomData<T>,
+}
+
+impl<'de, T: DeserializeOwned, F: FnMut(u64, T) -> ControlFlow<()>> Visitor<'de>
+    for RecordVisitor<'_, T, F>
+{
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an array of records")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<(), A::Error> {
+        let mut index = 0;
+        while let Some(record) = seq.next_element::<T>()? {
+            if (self.each)(index, record).is_break() {
+                *self.stopped = true;
+                return Err(de::Error::custom("stopped reading records"));
+            }
+            index += 1;
+        }
+        Ok(())
+    }
+}
+
+struct CountingReader<'a, R> {
+    inner: R,
+    count: &'a mut u64,
+}
+
+impl<R: Read> Read for CountingReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        *self.count += read as u64;
+        Ok(read)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHUNK_SCHEMA: &str = r#"{
+        "type": "object",
+        "required": ["id", "text"],
+        "properties": {
+            "id": {"type": "string"},
+            "text": {"type": "string", "minLength": 1}
+        }
+    }"#;
+
+    #[test]
+    fn test_array_records_are_checked_one_by_one() {
+        let validator =
+            StreamingValidator::new(SerializationFormat::JsonSchema, CHUNK_SCHEMA).unwrap();
+        let document = r#"[
+            {"id": "a", "text": "first"},
+            {"id": "b", "text": ""},
+            {"id": "c", "text": "third"}
+        ]"#;
+        let report = validator.validate_reader(document.as_bytes()).unwrap();
+        assert_eq!(report.records, 3);
+        assert_eq!(report.invalid_records, 1);
+        assert_eq!(report.bytes_read, document.len() as u64);
+        assert_eq!(report.errors[0].field_path.as_deref(), Some("/1/text"));
+
+        let error = validator
+            .validate_reader(r#"[{"id": "a", "text": "x"}, {"id": "#.as_bytes())
+            .unwrap_err();
+        assert!(error.to_string().contains("not well-formed"));
+    }
+
+    #[test]
+    fn test_lines_layout_caps_errors() {
+        let validator = StreamingValidator::new(SerializationFormat::JsonSchema, CHUNK_SCHEMA)
+            .unwrap()
+            .with_layout(StreamLayout::Lines)
+            .with_max_errors(1);
+        let document =
+            "{\"id\": \"a\", \"text\": \"ok\"}\n{\"text\": \"\"}\n{\"id\": 3, \"text\": \"x\"}\n";
+        let report = validator.validate_reader(document.as_bytes()).unwrap();
+        assert_eq!(report.records, 3);
+        assert_eq!(report.invalid_records, 2);
+        assert!(!report.is_valid());
+        assert_eq!(report.errors.len(), 1);
+        assert!(report.truncated);
+    }
+
+    #[test]
+    fn test_read_records_stops_when_asked() {
+        let document = r#"[{"id": "a"}, {"id": "b"}, {"id": "c"}, oops"#;
+        let mut seen = Vec::new();
+        read_records(
+            document.as_bytes(),
+            StreamLayout::Array,
+            |index, record: Value| {
+                seen.push((index, record["id"].clone()));
+                if index == 1 {
+                    ControlFlow::Break(())
+                } else {
+                    ControlFlow::Continue(())
+                }
+            },
+        )
+        .unwrap();
+        assert_eq!(seen, vec![(0, Value::from("a")), (1, Value::from("b"))]);
+
+        let error = read_records(document.as_bytes(), StreamLayout::Array, |_, _: Value| {
+            ControlFlow::Continue(())
+        })
+        .unwrap_err();
+        assert!(error.to_string().contains("not well-formed"));
+    }
+}
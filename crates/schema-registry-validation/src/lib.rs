@@ -1,19 +1,67 @@
 //! Schema validation engine
 use async_trait::async_trait;
-use schema_registry_core::{error::Result, schema::SchemaInput, traits::{SchemaValidator, ValidationResult}, types::SerializationFormat};
+use schema_registry_core::{error::Result, schema::SchemaInput, traits::{SchemaValidator, ValidationError, ValidationResult}, types::SerializationFormat};
+use uuid::Uuid;
 
 pub mod engine;
 pub mod format_detection;
+pub mod instance;
+pub mod llm_lint;
+pub mod proto_parser;
+pub mod regex_complexity;
+pub mod security_patterns;
 pub mod types;
 pub mod validators;
+#[cfg(feature = "wasm")]
+pub mod wasm_rules;
 
 // Config Manager integration for policy-based validation (Phase 2B)
 pub mod config_integration;
 
-pub struct ValidationEngine {}
+pub use format_detection::{detect, detect_format, DetectedFormat, FormatCandidate, FormatDetection};
+
+pub struct ValidationEngine {
+    instances: instance::InstanceValidator,
+}
 
 impl ValidationEngine {
-    pub fn new() -> Self { Self {} }
+    pub fn new() -> Self {
+        Self {
+            instances: instance::InstanceValidator::default(),
+        }
+    }
+
+    /// Check a data payload against the registered schema `schema_id`
+    ///
+    /// Each mismatch is an error whose `field_path` is the JSON pointer of
+    /// the offending value. The compiled schema is cached for the next
+    /// payload; see [`instance`].
+    pub async fn validate_instance(
+        &self,
+        schema_id: Uuid,
+        format: SerializationFormat,
+        content: &str,
+        payload: &serde_json::Value,
+    ) -> Result<ValidationResult> {
+        self.instances
+            .validate_instance(schema_id, format, content, payload)
+            .await
+    }
+
+    /// Compile `schema_id` ahead of its first payload
+    pub async fn precompile_instance(
+        &self,
+        schema_id: Uuid,
+        format: SerializationFormat,
+        content: &str,
+    ) -> Result<()> {
+        self.instances.precompile(schema_id, format, content).await
+    }
+
+    /// Drop the compiled form of `schema_id`, e.g. once it's deleted
+    pub async fn invalidate_instance(&self, schema_id: Uuid) {
+        self.instances.invalidate(schema_id).await
+    }
 }
 
 impl Default for ValidationEngine {
@@ -26,16 +74,62 @@ impl SchemaValidator for ValidationEngine {
         self.validate_content(&input.content, input.format).await
     }
 
-    async fn validate_content(&self, _content: &str, _format: SerializationFormat) -> Result<ValidationResult> {
+    /// JSON Schema documents are checked against the meta-schema they
+    /// declare with `$schema` (draft-07 when they declare none); each error's
+    /// `field_path` is a JSON pointer into the document. Protobuf schemas are
+    /// compiled into descriptors; see [`proto_parser`].
+    async fn validate_content(&self, content: &str, format: SerializationFormat) -> Result<ValidationResult> {
+        let errors = match format {
+            SerializationFormat::JsonSchema => json_schema_errors(content),
+            SerializationFormat::Protobuf => protobuf_errors(content),
+            SerializationFormat::Avro => Vec::new(),
+        };
         Ok(ValidationResult {
-            is_valid: true,
-            errors: Vec::new(),
+            is_valid: errors.is_empty(),
+            errors,
             warnings: Vec::new(),
             metadata: std::collections::HashMap::new(),
         })
     }
 }
 
+fn json_schema_errors(content: &str) -> Vec<ValidationError> {
+    let schema: serde_json::Value = match serde_json::from_str(content) {
+        Ok(schema) => schema,
+        Err(e) => {
+            return vec![ValidationError {
+                message: format!("Schema is not valid JSON: {}", e),
+                field_path: None,
+                code: "json-schema-parse".to_string(),
+            }];
+        }
+    };
+    validators::json_schema::meta_schema_violations(&schema, jsonschema::Draft::Draft7)
+        .into_iter()
+        .map(|violation| ValidationError {
+            message: violation.message,
+            field_path: Some(violation.pointer),
+            code: "json-schema-meta-schema".to_string(),
+        })
+        .collect()
+}
+
+fn protobuf_errors(content: &str) -> Vec<ValidationError> {
+    match proto_parser::ProtoParser::new().parse(content) {
+        Ok(_) => Vec::new(),
+        Err(e) => vec![ValidationError {
+            message: e.message,
+            field_path: None,
+            code: match e.kind {
+                proto_parser::ProtoParseErrorKind::Syntax => "protobuf-parse",
+                proto_parser::ProtoParseErrorKind::Import => "protobuf-import",
+                proto_parser::ProtoParseErrorKind::Invalid => "protobuf-invalid",
+            }
+            .to_string(),
+        }],
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -214,6 +308,58 @@ mod tests {
         assert!(proto_result.is_ok());
     }
 
+    #[tokio::test]
+    async fn test_validate_content_reports_meta_schema_errors() {
+        let engine = ValidationEngine::new();
+        let content = r#"{
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "type": "object",
+            "properties": {"id": {"type": "strin"}},
+            "required": "id"
+        }"#;
+        let result = engine
+            .validate_content(content, SerializationFormat::JsonSchema)
+            .await
+            .unwrap();
+        assert!(!result.is_valid);
+        let paths: Vec<_> = result
+            .errors
+            .iter()
+            .filter_map(|e| e.field_path.as_deref())
+            .collect();
+        assert!(paths.contains(&"/properties/id/type"));
+        assert!(paths.contains(&"/required"));
+
+        let result = engine
+            .validate_content("{not json", SerializationFormat::JsonSchema)
+            .await
+            .unwrap();
+        assert_eq!(result.errors[0].code, "json-schema-parse");
+    }
+
+    #[tokio::test]
+    async fn test_validate_content_compiles_protobuf() {
+        let engine = ValidationEngine::new();
+        let result = engine
+            .validate_content(
+                "syntax = \"proto3\"; message User { Profile profile = 1; }",
+                SerializationFormat::Protobuf,
+            )
+            .await
+            .unwrap();
+        assert!(!result.is_valid);
+        assert_eq!(result.errors[0].code, "protobuf-invalid");
+
+        let result = engine
+            .validate_content(
+                "syntax = \"proto3\"; import \"other.proto\"; message User {}",
+                SerializationFormat::Protobuf,
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.errors[0].code, "protobuf-import");
+    }
+
     #[test]
     fn test_engine_can_be_cloned_via_new() {
         let engine1 = ValidationEngine::new();
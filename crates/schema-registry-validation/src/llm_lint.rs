@@ -0,0 +1,373 @@
+//! LLM-friendliness lint
+//!
+//! Schemas are increasingly read by models as well as code: they end up in
+//! prompts, tool definitions and structured-output requests. This pass
+//! checks what helps a model use a schema correctly, per the
+//! [`LlmValidationSettings`] policy, and reports each finding as a warning:
+//!
+//! - `llm-description`: the schema or a field has no description (JSON
+//!   Schema `description`, Avro `doc`, a Protobuf `//` comment above the
+//!   declaration), when descriptions are required
+//! - `llm-description-length`: a description shorter than the policy minimum
+//! - `llm-examples`: a JSON Schema leaf property without `examples`, when
+//!   examples are required
+//! - `llm-field-name`: a field name a model is likely to misread, such as
+//!   `x`, `tmp` or `field2`
+//! - `llm-token-estimate`: the schema is estimated to take more prompt
+//!   tokens than the policy allows
+//!
+//! Field locations are dotted paths from the root, with `[]` marking the
+//! items of an array, as in `$.lines[].sku`.
+
+use crate::types::{SchemaFormat, ValidationWarning};
+use regex::Regex;
+use schema_registry_core::config_manager_adapter::LlmValidationSettings;
+use schema_registry_core::deprecation;
+use schema_registry_core::types::SerializationFormat;
+use serde_json::Value;
+use std::sync::LazyLock;
+
+/// Characters per token assumed by [`estimate_tokens`]
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Names that say nothing about what a field holds
+const OPAQUE_NAMES: &[&str] = &[
+    "data", "val", "value", "values", "field", "obj", "object", "item", "tmp", "temp", "misc",
+    "info", "stuff", "thing", "foo", "bar", "baz",
+];
+
+/// `field2`, `col10`: a generic word numbered instead of named
+static NUMBERED_NAME: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)^(field|col|column|attr|param|prop|var|val|value|item|data|f|c|x)_?\d+$")
+        .expect("numbered name regex is valid")
+});
+
+/// A Protobuf field declaration, capturing the field name
+static PROTO_FIELD: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^\s*(?:repeated\s+|optional\s+|required\s+)?(?:map<[^>]*>|[\w.]+)\s+(\w+)\s*=\s*\d+",
+    )
+    .expect("proto field regex is valid")
+});
+
+/// A Protobuf message declaration, capturing the message name
+static PROTO_MESSAGE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*message\s+(\w+)").expect("proto message regex is valid"));
+
+/// Rough number of tokens `schema` takes up in a prompt
+///
+/// Whitespace runs count as a single character and the rest is divided by
+/// four, about what common BPE tokenizers average over JSON and code.
+pub fn estimate_tokens(schema: &str) -> usize {
+    let mut chars: usize = 0;
+    let mut in_whitespace = false;
+    for c in schema.trim().chars() {
+        if c.is_whitespace() {
+            if !in_whitespace {
+                chars += 1;
+            }
+            in_whitespace = true;
+        } else {
+            chars += 1;
+            in_whitespace = false;
+        }
+    }
+    chars.div_ceil(CHARS_PER_TOKEN)
+}
+
+/// Lint `schema` against `settings`; nothing is reported when they are
+/// disabled or the schema can't be parsed
+pub fn lint(
+    schema: &str,
+    format: SchemaFormat,
+    settings: &LlmValidationSettings,
+) -> Vec<ValidationWarning> {
+    let mut lint = Lint {
+        settings,
+        warnings: Vec::new(),
+    };
+    if !settings.enabled {
+        return lint.warnings;
+    }
+
+    match format {
+        SchemaFormat::JsonSchema => {
+            if let Ok(json) = serde_json::from_str::<Value>(schema) {
+                lint.description("$", "The schema", json.get("description"));
+                lint.json_schema_properties(&json, "");
+            }
+        }
+        SchemaFormat::Avro => {
+            if let Ok(json) = serde_json::from_str::<Value>(schema) {
+                lint.description("$", "The schema", json.get("doc"));
+                for field in deprecation::fields(schema, SerializationFormat::Avro) {
+                    let location = format!("$.{}", field.path);
+                    let name = leaf_name(&field.path);
+                    lint.field_name(&location, name);
+                    let description = field.description.map(Value::String);
+                    lint.description(
+                        &location,
+                        &format!("Field '{}'", name),
+                        description.as_ref(),
+                    );
+                }
+            }
+        }
+        SchemaFormat::Protobuf => lint.protobuf(schema),
+    }
+
+    if let Some(max) = settings.max_token_estimate {
+        let tokens = estimate_tokens(schema);
+        if tokens > max {
+            lint.warnings.push(
+                ValidationWarning::new(
+                    "llm-token-estimate",
+                    format!(
+                        "Schema is estimated at {} tokens, over the limit of {}",
+                        tokens, max
+                    ),
+                )
+                .with_location("$")
+                .with_suggestion(
+                    "Split the schema or move shared definitions into referenced schemas",
+                ),
+            );
+        }
+    }
+    lint.warnings
+}
+
+struct Lint<'a> {
+    settings: &'a LlmValidationSettings,
+    warnings: Vec<ValidationWarning>,
+}
+
+impl Lint<'_> {
+    /// Check the description of `what` at `location`
+    fn description(&mut self, location: &str, what: &str, description: Option<&Value>) {
+        let text = description.and_then(Value::as_str).map(str::trim);
+        match text {
+            None | Some("") => {
+                if self.settings.require_descriptions {
+                    self.warnings.push(
+                        ValidationWarning::new(
+                            "llm-description",
+                            format!("{} has no description", what),
+                        )
+                        .with_location(location)
+                        .with_suggestion("Describe what it holds and how it is used"),
+                    );
+                }
+            }
+            Some(text) if text.chars().count() < self.settings.min_description_length => {
+                self.warnings.push(
+                    ValidationWarning::new(
+                        "llm-description-length",
+                        format!(
+                            "{} has a description shorter than {} characters",
+                            what, self.settings.min_description_length
+                        ),
+                    )
+                    .with_location(location),
+                );
+            }
+            Some(_) => {}
+        }
+    }
+
+    fn field_name(&mut self, location: &str, name: &str) {
+        if !self.settings.validate_field_names {
+            return;
+        }
+        let problem = if name.chars().count() < 2 {
+            Some("is too short to say what it holds")
+        } else if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            Some("contains characters other than letters, digits and underscores")
+        } else if OPAQUE_NAMES.contains(&name.to_ascii_lowercase().as_str()) {
+            Some("is too generic to say what it holds")
+        } else if NUMBERED_NAME.is_match(name) {
+            Some("is numbered rather than named")
+        } else {
+            None
+        };
+        if let Some(problem) = problem {
+            self.warnings.push(
+                ValidationWarning::new(
+                    "llm-field-name",
+                    format!("Field name '{}' {}", name, problem),
+                )
+                .with_location(location)
+                .with_suggestion("Use a descriptive snake_case or camelCase name"),
+            );
+        }
+    }
+
+    fn json_schema_properties(&mut self, node: &Value, prefix: &str) {
+        if let Some(properties) = node.get("properties").and_then(Value::as_object) {
+            for (name, property) in properties {
+                let path = if prefix.is_empty() {
+                    name.clone()
+                } else {
+                    format!("{}.{}", prefix, name)
+                };
+                let location = format!("$.{}", path);
+                self.field_name(&location, name);
+                self.description(
+                    &location,
+                    &format!("Field '{}'", name),
+                    property.get("description"),
+                );
+
+                let is_leaf =
+                    property.get("properties").is_none() && property.get("items").is_none();
+                let has_examples = ["examples", "example", "const", "enum"]
+                    .iter()
+                    .any(|key| property.get(key).is_some());
+                if self.settings.require_examples && is_leaf && !has_examples {
+                    self.warnings.push(
+                        ValidationWarning::new(
+                            "llm-examples",
+                            format!("Field '{}' has no examples", name),
+                        )
+                        .with_location(&location)
+                        .with_suggestion("Add 'examples' with one or two realistic values"),
+                    );
+                }
+                self.json_schema_properties(property, &path);
+            }
+        }
+        if let Some(items) = node.get("items").filter(|items| items.is_object()) {
+            self.json_schema_properties(items, &format!("{}[]", prefix));
+        }
+    }
+
+    /// Messages and fields, each described by the `//` comment above it
+    fn protobuf(&mut self, schema: &str) {
+        let mut commented = false;
+        let mut message: Option<String> = None;
+        for line in schema.lines() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                commented = false;
+                continue;
+            }
+            if trimmed.starts_with("//") || trimmed.ends_with("*/") {
+                commented = true;
+                continue;
+            }
+
+            if let Some(name) = PROTO_MESSAGE.captures(line).map(|c| c[1].to_string()) {
+                if !commented {
+                    self.description(&format!("$.{}", name), &format!("Message '{}'", name), None);
+                }
+                message = Some(name);
+            } else if let Some(name) = PROTO_FIELD.captures(line).map(|c| c[1].to_string()) {
+                let location = match &message {
+                    Some(message) => format!("$.{}.{}", message, name),
+                    None => format!("$.{}", name),
+                };
+                self.field_name(&location, &name);
+                // A trailing comment describes the field as well
+                if !commented && !trimmed.contains("//") {
+                    self.description(&location, &format!("Field '{}'", name), None);
+                }
+            }
+            commented = false;
+        }
+    }
+}
+
+/// Last segment of a dotted field path, without array markers
+fn leaf_name(path: &str) -> &str {
+    let name = path.rsplit('.').next().unwrap_or(path);
+    name.strip_suffix("[]").unwrap_or(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strict() -> LlmValidationSettings {
+        LlmValidationSettings {
+            require_descriptions: true,
+            require_examples: true,
+            max_token_estimate: Some(1_000),
+            ..LlmValidationSettings::default()
+        }
+    }
+
+    fn findings(warnings: &[ValidationWarning]) -> Vec<(&str, &str)> {
+        warnings
+            .iter()
+            .map(|w| (w.rule.as_str(), w.location.as_deref().unwrap_or("")))
+            .collect()
+    }
+
+    #[test]
+    fn test_json_schema_findings_follow_the_policy() {
+        let schema = r#"{
+            "description": "An order placed in the web shop",
+            "properties": {
+                "order_id": {"type": "string", "description": "Shop-wide order number", "examples": ["A-1001"]},
+                "tmp": {"type": "string", "description": "Scratch"},
+                "lines": {"type": "array", "description": "Products ordered, one per line", "items": {
+                    "properties": {"field2": {"type": "integer", "description": "Quantity ordered", "examples": [1]}}
+                }}
+            }
+        }"#;
+
+        let warnings = lint(schema, SchemaFormat::JsonSchema, &strict());
+        assert_eq!(
+            findings(&warnings),
+            [
+                ("llm-field-name", "$.lines[].field2"),
+                ("llm-field-name", "$.tmp"),
+                ("llm-description-length", "$.tmp"),
+                ("llm-examples", "$.tmp"),
+            ]
+        );
+
+        // The default policy doesn't require descriptions or examples
+        let warnings = lint(
+            r#"{"properties": {"id": {"type": "string"}}}"#,
+            SchemaFormat::JsonSchema,
+            &LlmValidationSettings::default(),
+        );
+        assert!(warnings.is_empty());
+
+        let disabled = LlmValidationSettings {
+            enabled: false,
+            ..strict()
+        };
+        assert!(lint(schema, SchemaFormat::JsonSchema, &disabled).is_empty());
+    }
+
+    #[test]
+    fn test_avro_and_protobuf_descriptions() {
+        let avro = r#"{"type": "record", "name": "User", "doc": "A registered shop customer",
+            "fields": [{"name": "email", "type": "string"}]}"#;
+        let warnings = lint(avro, SchemaFormat::Avro, &strict());
+        assert_eq!(findings(&warnings), [("llm-description", "$.email")]);
+
+        let proto = "syntax = \"proto3\";\n\n// A registered shop customer\nmessage User {\n  // Login address, unique per customer\n  string email = 1;\n  int64 id = 2; // Shop-wide customer number\n  string x = 3;\n}\n";
+        let warnings = lint(proto, SchemaFormat::Protobuf, &strict());
+        assert_eq!(
+            findings(&warnings),
+            [
+                ("llm-field-name", "$.User.x"),
+                ("llm-description", "$.User.x")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_token_estimate() {
+        assert_eq!(estimate_tokens("{\n    \"a\":    1\n}"), 3);
+        let settings = LlmValidationSettings {
+            max_token_estimate: Some(2),
+            ..LlmValidationSettings::default()
+        };
+        let warnings = lint(r#"{"type": "string"}"#, SchemaFormat::JsonSchema, &settings);
+        assert_eq!(findings(&warnings), [("llm-token-estimate", "$")]);
+    }
+}
@@ -0,0 +1,282 @@
+//! Protobuf schemas parsed into real descriptors
+//!
+//! A schema's `.proto` source is compiled with [`protox`] into a
+//! [`prost_reflect::DescriptorPool`], the same descriptor set `protoc` would
+//! produce. Compiling checks the syntax, resolves every import and type
+//! reference, and enforces protoc's rules (unique field numbers, reserved
+//! ranges, proto3 enums starting at zero, ...). Later validation steps then
+//! work on the descriptors instead of pattern-matching the source.
+//!
+//! Imports of the well-known types (`google/protobuf/*.proto`) always
+//! resolve. Other imports, such as a schema this one references, resolve only
+//! when their source is supplied with [`ProtoParser::with_import`].
+
+use prost_reflect::{DescriptorPool, EnumDescriptor, FileDescriptor, MessageDescriptor};
+use prost_types::FileDescriptorSet;
+use protox::file::{File, FileResolver, GoogleFileResolver};
+use protox::Compiler;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+/// Name the schema being parsed is compiled under
+pub const SCHEMA_FILE_NAME: &str = "schema.proto";
+
+/// Why a schema failed to compile
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProtoParseErrorKind {
+    /// The source isn't valid `.proto` syntax
+    Syntax,
+    /// An imported file wasn't supplied
+    Import,
+    /// Unresolved types, duplicate names or numbers, or another rule broken
+    Invalid,
+}
+
+/// A schema that failed to compile
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProtoParseError {
+    pub kind: ProtoParseErrorKind,
+    pub message: String,
+    /// Imports that couldn't be resolved
+    pub missing_imports: Vec<String>,
+}
+
+impl fmt::Display for ProtoParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for ProtoParseError {}
+
+/// Compiles protobuf schemas into descriptors
+#[derive(Debug, Clone, Default)]
+pub struct ProtoParser {
+    imports: HashMap<String, String>,
+}
+
+impl ProtoParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Supply the source of a file schemas may import as `name`
+    pub fn with_import(mut self, name: impl Into<String>, source: impl Into<String>) -> Self {
+        self.imports.insert(name.into(), source.into());
+        self
+    }
+
+    /// Compile `content` along with everything it imports
+    pub fn parse(&self, content: &str) -> Result<ProtoSchema, ProtoParseError> {
+        if let Err(e) = File::from_source(SCHEMA_FILE_NAME, content) {
+            return Err(ProtoParseError {
+                kind: ProtoParseErrorKind::Syntax,
+                message: e.to_string(),
+                missing_imports: Vec::new(),
+            });
+        }
+
+        let missing = Arc::new(Mutex::new(BTreeSet::new()));
+        let resolver = SourceResolver {
+            schema: content.to_string(),
+            imports: self.imports.clone(),
+            google: GoogleFileResolver::new(),
+            missing: missing.clone(),
+        };
+        let mut compiler = Compiler::with_file_resolver(resolver);
+        compiler.include_imports(true);
+        if let Err(e) = compiler.open_file(SCHEMA_FILE_NAME) {
+            let missing_imports: Vec<String> = missing
+                .lock()
+                .map(|missing| missing.iter().cloned().collect())
+                .unwrap_or_default();
+            let kind = if missing_imports.is_empty() {
+                ProtoParseErrorKind::Invalid
+            } else {
+                ProtoParseErrorKind::Import
+            };
+            return Err(ProtoParseError {
+                kind,
+                message: e.to_string(),
+                missing_imports,
+            });
+        }
+
+        let pool = compiler.descriptor_pool();
+        let file = pool
+            .get_file_by_name(SCHEMA_FILE_NAME)
+            .ok_or_else(|| ProtoParseError {
+                kind: ProtoParseErrorKind::Invalid,
+                message: "Compiled descriptor set is missing the schema file".to_string(),
+                missing_imports: Vec::new(),
+            })?;
+        Ok(ProtoSchema { pool, file })
+    }
+}
+
+/// A compiled protobuf schema
+#[derive(Debug, Clone)]
+pub struct ProtoSchema {
+    pool: DescriptorPool,
+    file: FileDescriptor,
+}
+
+impl ProtoSchema {
+    /// The schema file itself
+    pub fn file(&self) -> &FileDescriptor {
+        &self.file
+    }
+
+    /// The schema file and everything it imports
+    pub fn pool(&self) -> &DescriptorPool {
+        &self.pool
+    }
+
+    /// Descriptor set of the schema file and its imports, dependencies first
+    pub fn descriptor_set(&self) -> FileDescriptorSet {
+        FileDescriptorSet {
+            file: self.pool.file_descriptor_protos().cloned().collect(),
+        }
+    }
+
+    /// Every message the schema defines, nested ones included; map entries
+    /// are left out
+    pub fn messages(&self) -> Vec<MessageDescriptor> {
+        let mut messages = Vec::new();
+        let mut pending: Vec<MessageDescriptor> = self.file.messages().collect();
+        while let Some(message) = pending.pop() {
+            pending.extend(message.child_messages());
+            if !message.is_map_entry() {
+                messages.push(message);
+            }
+        }
+        messages.sort_by(|a, b| a.full_name().cmp(b.full_name()));
+        messages
+    }
+
+    /// Every enum the schema defines, nested ones included
+    pub fn enums(&self) -> Vec<EnumDescriptor> {
+        let mut enums: Vec<EnumDescriptor> = self.file.enums().collect();
+        for message in self.messages() {
+            enums.extend(message.child_enums());
+        }
+        enums.sort_by(|a, b| a.full_name().cmp(b.full_name()));
+        enums
+    }
+
+    /// Fields across all messages
+    pub fn field_count(&self) -> usize {
+        self.messages().iter().map(|m| m.fields().len()).sum()
+    }
+
+    /// Deepest message nesting; a top-level message is depth 1
+    pub fn nesting_depth(&self) -> usize {
+        fn depth(message: &MessageDescriptor) -> usize {
+            1 + message
+                .child_messages()
+                .filter(|child| !child.is_map_entry())
+                .map(|child| depth(&child))
+                .max()
+                .unwrap_or(0)
+        }
+        self.file.messages().map(|m| depth(&m)).max().unwrap_or(0)
+    }
+}
+
+/// Resolves the schema, supplied imports and the well-known types, noting
+/// every other file the compiler asks for
+struct SourceResolver {
+    schema: String,
+    imports: HashMap<String, String>,
+    google: GoogleFileResolver,
+    missing: Arc<Mutex<BTreeSet<String>>>,
+}
+
+impl FileResolver for SourceResolver {
+    fn open_file(&self, name: &str) -> Result<File, protox::Error> {
+        if name == SCHEMA_FILE_NAME {
+            return File::from_source(name, &self.schema);
+        }
+        if let Some(source) = self.imports.get(name) {
+            return File::from_source(name, source);
+        }
+        if name.starts_with("google/protobuf/") {
+            return self.google.open_file(name);
+        }
+        if let Ok(mut missing) = self.missing.lock() {
+            missing.insert(name.to_string());
+        }
+        Err(protox::Error::file_not_found(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORDER: &str = r#"
+syntax = "proto3";
+package shop;
+
+import "google/protobuf/timestamp.proto";
+
+message Order {
+  message Line {
+    string sku = 1;
+    uint32 quantity = 2;
+  }
+  enum Status {
+    STATUS_UNSPECIFIED = 0;
+    STATUS_PAID = 1;
+  }
+  string id = 1;
+  repeated Line lines = 2;
+  map<string, string> labels = 3;
+  Status status = 4;
+  google.protobuf.Timestamp placed_at = 5;
+}
+"#;
+
+    #[test]
+    fn test_parse_builds_descriptors() {
+        let schema = ProtoParser::new().parse(ORDER).unwrap();
+        let names: Vec<_> = schema
+            .messages()
+            .iter()
+            .map(|m| m.full_name().to_string())
+            .collect();
+        assert_eq!(names, vec!["shop.Order", "shop.Order.Line"]);
+        assert_eq!(schema.enums()[0].full_name(), "shop.Order.Status");
+        assert_eq!(schema.field_count(), 7);
+        assert_eq!(schema.nesting_depth(), 2);
+        assert!(schema
+            .descriptor_set()
+            .file
+            .iter()
+            .any(|f| f.name() == "google/protobuf/timestamp.proto"));
+    }
+
+    #[test]
+    fn test_parse_errors_are_classified() {
+        let parser = ProtoParser::new();
+
+        let err = parser.parse("syntax = \"proto3\"; message {").unwrap_err();
+        assert_eq!(err.kind, ProtoParseErrorKind::Syntax);
+
+        let importing = "syntax = \"proto3\"; import \"common/money.proto\"; message Price { common.Money amount = 1; }";
+        let err = parser.parse(importing).unwrap_err();
+        assert_eq!(err.kind, ProtoParseErrorKind::Import);
+        assert_eq!(err.missing_imports, vec!["common/money.proto"]);
+        let supplied = ProtoParser::new().with_import(
+            "common/money.proto",
+            "syntax = \"proto3\"; package common; message Money { int64 cents = 1; }",
+        );
+        assert!(supplied.parse(importing).is_ok());
+
+        let err = parser
+            .parse("syntax = \"proto3\"; message A { Missing b = 1; string c = 1; }")
+            .unwrap_err();
+        assert_eq!(err.kind, ProtoParseErrorKind::Invalid);
+    }
+}
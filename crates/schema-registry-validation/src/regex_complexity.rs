@@ -0,0 +1,439 @@
+//! Static complexity analysis for regex constraints
+//!
+//! JSON Schema `pattern` and `patternProperties` regexes are evaluated by
+//! downstream validators, most of which use backtracking engines. A pattern
+//! such as `(a+)+$` takes exponential time on a crafted input, so a single
+//! schema can take down every consumer that validates against it.
+//!
+//! [`analyze`] scans a pattern without compiling it and reports the constructs
+//! known to cause catastrophic backtracking along with a complexity score.
+//! [`RegexPolicy`] turns that analysis into warnings or errors depending on the
+//! configured [`RegexEnforcement`].
+
+use schema_registry_core::config_manager_adapter::PerformanceThresholds;
+pub use schema_registry_core::config_manager_adapter::RegexEnforcement;
+use serde::{Deserialize, Serialize};
+
+/// Repetition bound above which a counted quantifier is flagged
+const LARGE_REPETITION: usize = 1000;
+
+/// A potentially dangerous construct found in a pattern
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RegexFinding {
+    /// Unbounded quantifier applied to a group that already repeats, e.g. `(a+)+`
+    NestedQuantifier { offset: usize },
+    /// Unbounded repetition of alternatives that can match the same text, e.g. `(a|ab)*`
+    OverlappingAlternation { offset: usize },
+    /// Backreference such as `\1` or `\k<name>`
+    Backreference { offset: usize },
+    /// Lookahead or lookbehind assertion
+    Lookaround { offset: usize },
+    /// Counted repetition with a very large bound, e.g. `a{1,100000}`
+    LargeRepetition { offset: usize, bound: usize },
+}
+
+impl RegexFinding {
+    /// Whether the construct can cause exponential backtracking
+    pub fn is_catastrophic(&self) -> bool {
+        matches!(
+            self,
+            RegexFinding::NestedQuantifier { .. } | RegexFinding::OverlappingAlternation { .. }
+        )
+    }
+
+    /// Whether the construct is supported by linear-time (RE2-style) engines
+    pub fn is_re2_compatible(&self) -> bool {
+        !matches!(
+            self,
+            RegexFinding::Backreference { .. } | RegexFinding::Lookaround { .. }
+        )
+    }
+
+    /// Human readable description
+    pub fn describe(&self) -> String {
+        match self {
+            RegexFinding::NestedQuantifier { offset } => {
+                format!("nested quantifier at offset {} can backtrack exponentially", offset)
+            }
+            RegexFinding::OverlappingAlternation { offset } => format!(
+                "repeated alternation with overlapping branches at offset {} can backtrack exponentially",
+                offset
+            ),
+            RegexFinding::Backreference { offset } => {
+                format!("backreference at offset {} is not supported by linear-time engines", offset)
+            }
+            RegexFinding::Lookaround { offset } => {
+                format!("lookaround at offset {} is not supported by linear-time engines", offset)
+            }
+            RegexFinding::LargeRepetition { offset, bound } => {
+                format!("repetition bound {} at offset {} is very large", bound, offset)
+            }
+        }
+    }
+}
+
+/// Result of analyzing a single pattern
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegexAnalysis {
+    /// Complexity score, compared against `max_regex_complexity`
+    pub score: usize,
+    /// Dangerous constructs found in the pattern
+    pub findings: Vec<RegexFinding>,
+}
+
+impl RegexAnalysis {
+    /// Whether any finding can cause catastrophic backtracking
+    pub fn is_catastrophic(&self) -> bool {
+        self.findings.iter().any(|f| f.is_catastrophic())
+    }
+}
+
+/// A single issue reported by [`RegexPolicy::check`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegexIssue {
+    /// Description of the issue
+    pub message: String,
+    /// Whether the issue should fail validation
+    pub blocking: bool,
+}
+
+/// Regex complexity policy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegexPolicy {
+    /// Maximum allowed complexity score
+    pub max_complexity: usize,
+    /// Enforcement mode
+    pub enforcement: RegexEnforcement,
+}
+
+impl Default for RegexPolicy {
+    fn default() -> Self {
+        Self::from(&PerformanceThresholds::default())
+    }
+}
+
+impl From<&PerformanceThresholds> for RegexPolicy {
+    fn from(thresholds: &PerformanceThresholds) -> Self {
+        Self {
+            max_complexity: thresholds.max_regex_complexity,
+            enforcement: RegexEnforcement::default(),
+        }
+    }
+}
+
+impl RegexPolicy {
+    /// Analyze a pattern and report issues according to the enforcement mode
+    pub fn check(&self, pattern: &str) -> Vec<RegexIssue> {
+        let analysis = analyze(pattern);
+        let mut issues = Vec::new();
+
+        if analysis.score > self.max_complexity {
+            issues.push(RegexIssue {
+                message: format!(
+                    "Regex complexity score {} exceeds maximum {}",
+                    analysis.score, self.max_complexity
+                ),
+                blocking: self.enforcement != RegexEnforcement::Warn,
+            });
+        }
+
+        for finding in &analysis.findings {
+            let blocking = match self.enforcement {
+                RegexEnforcement::Warn => false,
+                RegexEnforcement::Reject => finding.is_catastrophic(),
+                RegexEnforcement::SafeSubset => true,
+            };
+            issues.push(RegexIssue {
+                message: format!("Regex {}", finding.describe()),
+                blocking,
+            });
+        }
+
+        // The regex crate only implements the linear-time subset
+        if self.enforcement == RegexEnforcement::SafeSubset
+            && analysis.findings.iter().all(|f| f.is_re2_compatible())
+        {
+            if let Err(e) = regex::Regex::new(pattern) {
+                issues.push(RegexIssue {
+                    message: format!("Regex is outside the safe subset: {}", e),
+                    blocking: true,
+                });
+            }
+        }
+
+        issues
+    }
+}
+
+/// A group being scanned, or the top level of the pattern
+#[derive(Default)]
+struct Group {
+    start: usize,
+    branches: Vec<String>,
+    current: String,
+    repeats: bool,
+}
+
+/// The most recent atom, which a following quantifier applies to
+enum Atom {
+    None,
+    Simple,
+    Group { repeats: bool, overlapping: bool },
+}
+
+/// Statically analyze a pattern
+pub fn analyze(pattern: &str) -> RegexAnalysis {
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut findings = Vec::new();
+    let mut score = 0usize;
+    let mut stack = vec![Group::default()];
+    let mut last = Atom::None;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+
+        match c {
+            '\\' => {
+                let next = chars.get(i + 1).copied();
+                match next {
+                    Some('1'..='9') => {
+                        findings.push(RegexFinding::Backreference { offset: i });
+                        score += 25;
+                    }
+                    Some('k') if chars.get(i + 2) == Some(&'<') => {
+                        findings.push(RegexFinding::Backreference { offset: i });
+                        score += 25;
+                    }
+                    _ => score += 1,
+                }
+                i += 2;
+                last = Atom::Simple;
+            }
+            '[' => {
+                i += 1;
+                if chars.get(i) == Some(&'^') {
+                    i += 1;
+                }
+                if chars.get(i) == Some(&']') {
+                    i += 1;
+                }
+                while i < chars.len() && chars[i] != ']' {
+                    i += if chars[i] == '\\' { 2 } else { 1 };
+                }
+                i += 1;
+                score += 1;
+                last = Atom::Simple;
+            }
+            '(' => {
+                let rest: String = chars[i..chars.len().min(i + 4)].iter().collect();
+                if rest.starts_with("(?=")
+                    || rest.starts_with("(?!")
+                    || rest.starts_with("(?<=")
+                    || rest.starts_with("(?<!")
+                {
+                    findings.push(RegexFinding::Lookaround { offset: i });
+                    score += 10;
+                }
+                stack.push(Group {
+                    start: i,
+                    ..Group::default()
+                });
+                i += 1;
+                last = Atom::None;
+                continue;
+            }
+            ')' if stack.len() > 1 => {
+                let mut group = stack.pop().unwrap();
+                let current = std::mem::take(&mut group.current);
+                group.branches.push(current);
+                let overlapping = branches_overlap(&group.branches);
+
+                let parent = stack.last_mut().unwrap();
+                parent.repeats |= group.repeats;
+                i += 1;
+                parent.current.extend(&chars[group.start..i]);
+                last = Atom::Group {
+                    repeats: group.repeats,
+                    overlapping,
+                };
+                continue;
+            }
+            '|' => {
+                let group = stack.last_mut().unwrap();
+                let current = std::mem::take(&mut group.current);
+                group.branches.push(current);
+                score += 1;
+                i += 1;
+                last = Atom::None;
+                continue;
+            }
+            '*' | '+' | '?' | '{' => {
+                let (unbounded, bound, len) = match c {
+                    '*' | '+' => (true, None, 1),
+                    '?' => (false, None, 1),
+                    _ => match parse_counted(&chars[i..]) {
+                        Some((max, len)) => (max.is_none(), max, len),
+                        None => {
+                            // A literal brace
+                            score += 1;
+                            i += 1;
+                            last = Atom::Simple;
+                            continue;
+                        }
+                    },
+                };
+                i += len;
+                // Lazy and possessive modifiers don't change the analysis
+                if matches!(chars.get(i), Some('?') | Some('+')) {
+                    i += 1;
+                }
+
+                score += if unbounded { 3 } else { 1 };
+                if let Some(bound) = bound {
+                    score += bound.min(LARGE_REPETITION) / 100;
+                    if bound > LARGE_REPETITION {
+                        findings.push(RegexFinding::LargeRepetition { offset: start, bound });
+                    }
+                }
+
+                if unbounded {
+                    if let Atom::Group { repeats, overlapping } = last {
+                        if repeats {
+                            findings.push(RegexFinding::NestedQuantifier { offset: start });
+                            score += 50;
+                        } else if overlapping {
+                            findings.push(RegexFinding::OverlappingAlternation { offset: start });
+                            score += 40;
+                        }
+                    }
+                    stack.last_mut().unwrap().repeats = true;
+                }
+
+                stack.last_mut().unwrap().current.extend(&chars[start..i]);
+                last = Atom::None;
+                continue;
+            }
+            _ => {
+                score += 1;
+                i += 1;
+                last = Atom::Simple;
+            }
+        }
+
+        let end = i.min(chars.len());
+        stack.last_mut().unwrap().current.extend(&chars[start..end]);
+    }
+
+    RegexAnalysis { score, findings }
+}
+
+/// Parse `{n}`, `{n,}` or `{n,m}`, returning the upper bound and length consumed
+fn parse_counted(chars: &[char]) -> Option<(Option<usize>, usize)> {
+    let close = chars.iter().position(|&c| c == '}')?;
+    let body: String = chars[1..close].iter().collect();
+    let (min, max) = match body.split_once(',') {
+        Some((min, "")) => (min, None),
+        Some((min, max)) => (min, Some(max.trim().parse::<usize>().ok()?)),
+        None => (body.as_str(), Some(body.trim().parse::<usize>().ok()?)),
+    };
+    min.trim().parse::<usize>().ok()?;
+    Some((max, close + 1))
+}
+
+/// Heuristic: branches overlap if one can match a prefix of what another matches
+fn branches_overlap(branches: &[String]) -> bool {
+    if branches.len() < 2 {
+        return false;
+    }
+    for (i, a) in branches.iter().enumerate() {
+        for b in &branches[i + 1..] {
+            if a.is_empty()
+                || b.is_empty()
+                || a.starts_with('.')
+                || b.starts_with('.')
+                || a.starts_with(b.as_str())
+                || b.starts_with(a.as_str())
+            {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simple_patterns_are_safe() {
+        for pattern in [
+            "^[a-z]+$",
+            r"^[a-zA-Z0-9._%+-]+@[a-zA-Z0-9.-]+\.[a-zA-Z]{2,}$",
+            r"^\d{3}-\d{4}$",
+            "^(foo|bar)+$",
+        ] {
+            let analysis = analyze(pattern);
+            assert!(analysis.findings.is_empty(), "{}: {:?}", pattern, analysis.findings);
+            assert!(analysis.score < 100, "{}: {}", pattern, analysis.score);
+        }
+    }
+
+    #[test]
+    fn test_detects_catastrophic_patterns() {
+        assert!(matches!(
+            analyze("^(a+)+$").findings.as_slice(),
+            [RegexFinding::NestedQuantifier { offset: 5 }]
+        ));
+        assert!(analyze("((ab)*c)*").is_catastrophic());
+        assert!(analyze("^(a|ab)*$").is_catastrophic());
+        assert!(analyze("(.*|x)+").is_catastrophic());
+        assert!(!analyze("(a+)?").is_catastrophic());
+    }
+
+    #[test]
+    fn test_detects_non_re2_constructs() {
+        let analysis = analyze(r"(a)\1(?=b)");
+        assert!(analysis.findings.contains(&RegexFinding::Backreference { offset: 3 }));
+        assert!(analysis.findings.contains(&RegexFinding::Lookaround { offset: 5 }));
+        assert!(analysis.findings.iter().all(|f| !f.is_catastrophic()));
+
+        assert!(matches!(
+            analyze("a{1,5000}").findings.as_slice(),
+            [RegexFinding::LargeRepetition { bound: 5000, .. }]
+        ));
+    }
+
+    #[test]
+    fn test_enforcement_modes() {
+        let warn = RegexPolicy::default();
+        assert!(warn.check("(a+)+").iter().all(|i| !i.blocking));
+
+        let reject = RegexPolicy {
+            enforcement: RegexEnforcement::Reject,
+            ..RegexPolicy::default()
+        };
+        assert!(reject.check("(a+)+").iter().any(|i| i.blocking));
+        assert!(reject.check(r"(a)\1").iter().all(|i| !i.blocking));
+
+        let safe = RegexPolicy {
+            enforcement: RegexEnforcement::SafeSubset,
+            ..RegexPolicy::default()
+        };
+        assert!(safe.check(r"(a)\1").iter().any(|i| i.blocking));
+        assert!(safe.check("^[a-z]+$").is_empty());
+    }
+
+    #[test]
+    fn test_complexity_threshold() {
+        let policy = RegexPolicy {
+            max_complexity: 5,
+            enforcement: RegexEnforcement::Reject,
+        };
+        let issues = policy.check("^[a-z]+[0-9]+[A-Z]+$");
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].blocking);
+    }
+}
@@ -0,0 +1,106 @@
+//! Suspicious content patterns searched for by the security step
+//!
+//! Which patterns count as suspicious varies between organizations, so the
+//! list comes from `ValidationSettingsConfig::security` rather than the
+//! engine. Each [`SecurityPatternRule`] is compiled once into a regex; the
+//! defaults reproduce the engine's original `eval`, `exec`, `__proto__` and
+//! `constructor` checks. Rule patterns run on the `regex` crate, which
+//! matches in linear time, so a configured rule can't itself be a ReDoS risk.
+
+use regex::{Regex, RegexBuilder};
+use schema_registry_core::config_manager_adapter::{
+    SecurityPatternRule, SecurityValidationSettings,
+};
+
+/// A rule along with its compiled pattern
+#[derive(Debug, Clone)]
+struct CompiledRule {
+    rule: SecurityPatternRule,
+    regex: Regex,
+}
+
+/// Compiled security pattern rules
+#[derive(Debug, Clone)]
+pub struct SecurityPatterns {
+    rules: Vec<CompiledRule>,
+}
+
+impl SecurityPatterns {
+    /// Compile `rules`, failing on the first invalid pattern
+    pub fn compile(rules: &[SecurityPatternRule]) -> Result<Self, regex::Error> {
+        let rules = rules
+            .iter()
+            .map(|rule| {
+                let regex = RegexBuilder::new(&rule.pattern)
+                    .case_insensitive(!rule.case_sensitive)
+                    .build()?;
+                Ok(CompiledRule {
+                    rule: rule.clone(),
+                    regex,
+                })
+            })
+            .collect::<Result<Vec<_>, regex::Error>>()?;
+        Ok(Self { rules })
+    }
+
+    /// Rules whose pattern occurs in `schema`, in configured order
+    pub fn matches<'a>(&'a self, schema: &'a str) -> impl Iterator<Item = &'a SecurityPatternRule> {
+        self.rules
+            .iter()
+            .filter(move |compiled| compiled.regex.is_match(schema))
+            .map(|compiled| &compiled.rule)
+    }
+
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+}
+
+impl Default for SecurityPatterns {
+    fn default() -> Self {
+        Self::compile(&SecurityValidationSettings::default().patterns)
+            .expect("default security patterns are valid")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(name: &str, pattern: &str) -> SecurityPatternRule {
+        SecurityPatternRule {
+            name: name.to_string(),
+            pattern: pattern.to_string(),
+            message: format!("Contains {}", name),
+            case_sensitive: false,
+            blocking: false,
+            quarantine: true,
+        }
+    }
+
+    #[test]
+    fn test_defaults_match_original_keywords() {
+        let patterns = SecurityPatterns::default();
+        assert_eq!(patterns.len(), 4);
+        let names: Vec<_> = patterns
+            .matches(r#"{"properties": {"__PROTO__": {}, "onEval": {}}}"#)
+            .map(|r| r.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["eval", "proto"]);
+    }
+
+    #[test]
+    fn test_compile_respects_case_and_rejects_invalid_patterns() {
+        let mut script = rule("script", r"<script\b");
+        script.case_sensitive = true;
+        let patterns = SecurityPatterns::compile(&[script]).unwrap();
+        assert_eq!(patterns.matches("<script src=x>").count(), 1);
+        assert_eq!(patterns.matches("<SCRIPT src=x>").count(), 0);
+
+        assert!(SecurityPatterns::compile(&[rule("broken", "(unclosed")]).is_err());
+    }
+}
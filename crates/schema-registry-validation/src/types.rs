@@ -3,6 +3,12 @@
 //! This module defines the foundational types for schema validation,
 //! including validation results, errors, warnings, and metrics.
 
+use crate::regex_complexity::RegexPolicy;
+use crate::security_patterns::SecurityPatterns;
+use schema_registry_core::bounded_json::JsonLimits;
+use schema_registry_core::config_manager_adapter::{
+    LlmValidationSettings, SecurityValidationSettings,
+};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::time::Duration;
@@ -139,6 +145,47 @@ impl ValidationWarning {
     }
 }
 
+/// A step of the validation pipeline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidationStep {
+    Structural,
+    Types,
+    Semantics,
+    Security,
+    Performance,
+    CustomRules,
+}
+
+impl ValidationStep {
+    /// Returns the step's name, as used in metric labels
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ValidationStep::Structural => "structural",
+            ValidationStep::Types => "types",
+            ValidationStep::Semantics => "semantics",
+            ValidationStep::Security => "security",
+            ValidationStep::Performance => "performance",
+            ValidationStep::CustomRules => "custom_rules",
+        }
+    }
+}
+
+impl std::fmt::Display for ValidationStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Time spent in one pipeline step and what it found
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StepTiming {
+    pub step: ValidationStep,
+    pub duration: Duration,
+    pub errors: usize,
+    pub warnings: usize,
+}
+
 /// Metrics collected during validation
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ValidationMetrics {
@@ -154,6 +201,9 @@ pub struct ValidationMetrics {
     pub max_recursion_depth: usize,
     /// Custom metrics
     pub custom: HashMap<String, String>,
+    /// Steps that ran, in pipeline order
+    #[serde(default)]
+    pub steps: Vec<StepTiming>,
 }
 
 impl ValidationMetrics {
@@ -172,6 +222,14 @@ impl ValidationMetrics {
     pub fn add_metric(&mut self, key: impl Into<String>, value: impl Into<String>) {
         self.custom.insert(key.into(), value.into());
     }
+
+    /// Time spent in `step`, if it ran
+    pub fn step_duration(&self, step: ValidationStep) -> Option<Duration> {
+        self.steps
+            .iter()
+            .find(|timing| timing.step == step)
+            .map(|timing| timing.duration)
+    }
 }
 
 /// Result of schema validation
@@ -187,6 +245,9 @@ pub struct ValidationResult {
     pub metrics: ValidationMetrics,
     /// The format that was validated
     pub format: SchemaFormat,
+    /// Security findings that require the schema to be quarantined
+    #[serde(default)]
+    pub quarantine_reasons: Vec<String>,
 }
 
 impl ValidationResult {
@@ -198,6 +259,7 @@ impl ValidationResult {
             warnings: Vec::new(),
             metrics: ValidationMetrics::new(),
             format,
+            quarantine_reasons: Vec::new(),
         }
     }
 
@@ -209,6 +271,7 @@ impl ValidationResult {
             warnings: Vec::new(),
             metrics: ValidationMetrics::new(),
             format,
+            quarantine_reasons: Vec::new(),
         }
     }
 
@@ -223,13 +286,28 @@ impl ValidationResult {
         self.warnings.push(warning);
     }
 
+    /// Flags the schema for quarantine without failing validation
+    pub fn add_quarantine_reason(&mut self, reason: impl Into<String>) {
+        self.quarantine_reasons.push(reason.into());
+    }
+
+    /// Returns true if the schema must be quarantined pending security review
+    pub fn requires_quarantine(&self) -> bool {
+        !self.quarantine_reasons.is_empty()
+    }
+
     /// Merges another validation result into this one
     pub fn merge(&mut self, other: ValidationResult) {
         self.is_valid = self.is_valid && other.is_valid;
         self.errors.extend(other.errors);
         self.warnings.extend(other.warnings);
+        self.quarantine_reasons.extend(other.quarantine_reasons);
         self.metrics.rules_applied += other.metrics.rules_applied;
         self.metrics.fields_validated += other.metrics.fields_validated;
+        self.metrics.max_recursion_depth = self
+            .metrics
+            .max_recursion_depth
+            .max(other.metrics.max_recursion_depth);
     }
 
     /// Returns true if there are any errors
@@ -266,10 +344,20 @@ pub struct ValidationConfig {
     pub max_recursion_depth: usize,
     /// Enable LLM-specific validation rules
     pub llm_validation: bool,
+    /// What the LLM-friendliness lint checks; see [`crate::llm_lint`]
+    pub llm: LlmValidationSettings,
     /// Enable security validation
     pub security_validation: bool,
     /// Enable performance validation
     pub performance_validation: bool,
+    /// Quarantine schemas with suspicious security findings instead of only warning
+    pub quarantine_on_security_findings: bool,
+    /// Suspicious content patterns checked by the security step
+    pub security_patterns: SecurityPatterns,
+    /// Limits enforced before JSON-based schemas are parsed
+    pub json_limits: JsonLimits,
+    /// Complexity policy for `pattern` and `patternProperties` regexes
+    pub regex_policy: RegexPolicy,
 }
 
 impl Default for ValidationConfig {
@@ -280,8 +368,18 @@ impl Default for ValidationConfig {
             max_schema_size: 1024 * 1024, // 1MB
             max_recursion_depth: 100,
             llm_validation: true,
+            // The engine has always flagged missing descriptions and examples
+            llm: LlmValidationSettings {
+                require_descriptions: true,
+                require_examples: true,
+                ..LlmValidationSettings::default()
+            },
             security_validation: true,
             performance_validation: true,
+            quarantine_on_security_findings: true,
+            security_patterns: SecurityPatterns::default(),
+            json_limits: JsonLimits::default(),
+            regex_policy: RegexPolicy::default(),
         }
     }
 }
@@ -309,6 +407,44 @@ impl ValidationConfig {
         self.max_schema_size = max_size;
         self
     }
+
+    /// Sets the limits enforced before parsing JSON-based schemas
+    pub fn with_json_limits(mut self, limits: JsonLimits) -> Self {
+        self.json_limits = limits;
+        self
+    }
+
+    /// Sets the complexity policy for regex constraints
+    pub fn with_regex_policy(mut self, policy: RegexPolicy) -> Self {
+        self.regex_policy = policy;
+        self
+    }
+
+    /// Sets the LLM-friendliness policy, e.g. as loaded from Config Manager
+    pub fn with_llm_settings(mut self, settings: LlmValidationSettings) -> Self {
+        self.llm_validation = settings.enabled;
+        self.llm = settings;
+        self
+    }
+
+    /// Enables or disables quarantine on security findings
+    pub fn with_quarantine(mut self, quarantine: bool) -> Self {
+        self.quarantine_on_security_findings = quarantine;
+        self
+    }
+
+    /// Sets the security patterns and regex enforcement, e.g. as loaded from
+    /// Config Manager; fails if a pattern isn't a valid regex
+    pub fn with_security_settings(
+        mut self,
+        settings: &SecurityValidationSettings,
+    ) -> Result<Self, regex::Error> {
+        self.security_patterns = SecurityPatterns::compile(&settings.patterns)?;
+        self.security_validation = settings.enabled;
+        self.quarantine_on_security_findings = settings.quarantine_on_findings;
+        self.regex_policy.enforcement = settings.regex_enforcement;
+        Ok(self)
+    }
 }
 
 #[cfg(test)]
@@ -348,6 +484,19 @@ mod tests {
         assert_eq!(result.error_count(), 1);
     }
 
+    #[test]
+    fn test_validation_result_quarantine() {
+        let mut result = ValidationResult::success(SchemaFormat::JsonSchema);
+        assert!(!result.requires_quarantine());
+
+        let mut flagged = ValidationResult::success(SchemaFormat::JsonSchema);
+        flagged.add_quarantine_reason("Contains prototype pollution pattern");
+
+        result.merge(flagged);
+        assert!(result.requires_quarantine());
+        assert!(result.is_valid);
+    }
+
     #[test]
     fn test_validation_result_merge() {
         let mut result1 = ValidationResult::success(SchemaFormat::JsonSchema);
@@ -538,6 +687,7 @@ mod tests {
     fn test_validation_config_security_validation() {
         let config = ValidationConfig::default();
         assert!(config.security_validation);
+        assert_eq!(config.security_patterns.len(), 4);
     }
 
     #[test]
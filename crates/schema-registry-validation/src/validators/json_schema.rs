@@ -6,8 +6,117 @@
 use crate::types::{ValidationError, ValidationResult, ValidationWarning, SchemaFormat};
 use anyhow::Result;
 use jsonschema::{Draft, JSONSchema};
+use once_cell::sync::Lazy;
 use serde_json::Value;
 
+/// Meta-schemas a schema can declare with `$schema`, by URI without its
+/// scheme and fragment
+const META_SCHEMAS: [(&str, Draft, &str); 5] = [
+    ("json-schema.org/draft-04/schema", Draft::Draft4, "http://json-schema.org/draft-04/schema#"),
+    ("json-schema.org/draft-06/schema", Draft::Draft6, "http://json-schema.org/draft-06/schema#"),
+    ("json-schema.org/draft-07/schema", Draft::Draft7, "http://json-schema.org/draft-07/schema#"),
+    (
+        "json-schema.org/draft/2019-09/schema",
+        Draft::Draft201909,
+        "https://json-schema.org/draft/2019-09/schema",
+    ),
+    (
+        "json-schema.org/draft/2020-12/schema",
+        Draft::Draft202012,
+        "https://json-schema.org/draft/2020-12/schema",
+    ),
+];
+
+/// A structural error in a schema document, found against its meta-schema
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetaSchemaViolation {
+    /// JSON pointer to the offending value, `""` for the document itself
+    pub pointer: String,
+    pub message: String,
+}
+
+/// The draft a schema declares with `$schema`, or `default` if it declares none
+///
+/// Fails with the declared URI if it names no supported draft.
+pub fn declared_draft(schema: &Value, default: Draft) -> std::result::Result<Draft, String> {
+    let Some(uri) = schema.get("$schema").and_then(Value::as_str) else {
+        return Ok(default);
+    };
+    let bare = uri.trim_end_matches('#');
+    let bare = bare
+        .strip_prefix("https://")
+        .or_else(|| bare.strip_prefix("http://"))
+        .unwrap_or(bare);
+    META_SCHEMAS
+        .iter()
+        .find(|(known, _, _)| *known == bare)
+        .map(|(_, draft, _)| *draft)
+        .ok_or_else(|| uri.to_string())
+}
+
+/// Validate a schema document against the meta-schema of the draft it
+/// declares, `default` when it declares none
+///
+/// Every violation is reported, each at the JSON pointer of the offending
+/// value.
+pub fn meta_schema_violations(schema: &Value, default: Draft) -> Vec<MetaSchemaViolation> {
+    let draft = match declared_draft(schema, default) {
+        Ok(draft) => draft,
+        Err(uri) => {
+            return vec![MetaSchemaViolation {
+                pointer: "/$schema".to_string(),
+                message: format!(
+                    "Unsupported meta-schema '{}'; declare draft-07, 2019-09 or 2020-12",
+                    uri
+                ),
+            }];
+        }
+    };
+    let Some(validator) = meta_schema_validator(draft) else {
+        // Compiling a schema checks it against its meta-schema too, though
+        // only up to the first violation
+        return match JSONSchema::options().with_draft(draft).compile(schema) {
+            Ok(_) => Vec::new(),
+            Err(e) => vec![MetaSchemaViolation {
+                pointer: e.instance_path.to_string(),
+                message: e.to_string(),
+            }],
+        };
+    };
+    match validator.validate(schema) {
+        Ok(()) => Vec::new(),
+        Err(errors) => errors
+            .map(|e| MetaSchemaViolation {
+                pointer: e.instance_path.to_string(),
+                message: e.to_string(),
+            })
+            .collect(),
+    }
+}
+
+/// The compiled meta-schema of `draft`, shared by every validation
+fn meta_schema_validator(draft: Draft) -> Option<&'static JSONSchema> {
+    static COMPILED: Lazy<Vec<(Draft, JSONSchema)>> = Lazy::new(|| {
+        META_SCHEMAS
+            .iter()
+            .filter_map(|(_, draft, uri)| {
+                let reference = serde_json::json!({ "$ref": uri });
+                match JSONSchema::options().with_draft(*draft).compile(&reference) {
+                    Ok(compiled) => Some((*draft, compiled)),
+                    Err(e) => {
+                        tracing::warn!(meta_schema = uri, error = %e, "Failed to compile meta-schema");
+                        None
+                    }
+                }
+            })
+            .collect()
+    });
+    COMPILED
+        .iter()
+        .find(|(compiled, _)| *compiled == draft)
+        .map(|(_, validator)| validator)
+}
+
 /// JSON Schema validator
 pub struct JsonSchemaValidator {
     /// The JSON Schema draft version to use
@@ -54,21 +163,12 @@ impl JsonSchemaValidator {
             }
         };
 
-        // Validate against meta-schema
-        match self.validate_against_metaschema(&schema_value) {
-            Ok(errors) => {
-                for error in errors {
-                    result.add_error(error);
-                }
-            }
-            Err(e) => {
-                result.add_error(
-                    ValidationError::new(
-                        "json-schema-validation",
-                        format!("Meta-schema validation error: {}", e),
-                    ),
-                );
-            }
+        // Validate against the declared meta-schema
+        for violation in meta_schema_violations(&schema_value, self.draft) {
+            result.add_error(
+                ValidationError::new("json-schema-meta-schema", violation.message)
+                    .with_location(violation.pointer),
+            );
         }
 
         // Additional JSON Schema specific validations
@@ -135,75 +235,6 @@ impl JsonSchemaValidator {
         Ok(result)
     }
 
-    /// Validates the schema against its meta-schema
-    fn validate_against_metaschema(&self, schema: &Value) -> Result<Vec<ValidationError>> {
-        let mut errors = Vec::new();
-
-        // For now, we skip meta-schema validation due to lifetime constraints
-        // In production, we would use embedded static meta-schemas
-        // This validation is primarily structural and semantic
-
-        // Basic validation: ensure it's a valid JSON object
-        if !schema.is_object() {
-            errors.push(
-                ValidationError::new(
-                    "json-schema-structure",
-                    "Schema must be a JSON object",
-                )
-                .with_suggestion("Ensure the schema is a valid JSON object"),
-            );
-        }
-
-        Ok(errors)
-    }
-
-    /// Gets the meta-schema for validation
-    fn get_metaschema(&self, schema: &Value) -> Result<Value> {
-        // Check if schema specifies a $schema
-        if let Some(schema_uri) = schema.get("$schema").and_then(|s| s.as_str()) {
-            // Use the specified meta-schema
-            // In production, you would fetch this or have it embedded
-            return self.get_metaschema_by_draft();
-        }
-
-        // Use default meta-schema for the configured draft
-        self.get_metaschema_by_draft()
-    }
-
-    /// Gets the meta-schema for the configured draft
-    fn get_metaschema_by_draft(&self) -> Result<Value> {
-        // For now, return a minimal meta-schema
-        // In production, embed the full meta-schemas
-        let metaschema = match self.draft {
-            Draft::Draft7 => {
-                serde_json::json!({
-                    "$schema": "http://json-schema.org/draft-07/schema#",
-                    "type": "object"
-                })
-            }
-            Draft::Draft6 => {
-                serde_json::json!({
-                    "$schema": "http://json-schema.org/draft-06/schema#",
-                    "type": "object"
-                })
-            }
-            Draft::Draft4 => {
-                serde_json::json!({
-                    "$schema": "http://json-schema.org/draft-04/schema#",
-                    "type": "object"
-                })
-            }
-            _ => {
-                serde_json::json!({
-                    "$schema": "http://json-schema.org/draft-07/schema#",
-                    "type": "object"
-                })
-            }
-        };
-
-        Ok(metaschema)
-    }
-
     /// Validates JSON Schema specific properties
     fn validate_schema_properties(&self, schema: &Value, result: &mut ValidationResult) {
         // Check for $id
@@ -378,6 +409,47 @@ mod tests {
         assert!(!result.is_valid);
     }
 
+    #[test]
+    fn test_meta_schema_violations_have_pointers() {
+        let schema = serde_json::json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "type": "object",
+            "properties": {
+                "id": {"type": "strin"},
+                "tags": {"type": "array", "minItems": -1}
+            }
+        });
+        let violations = meta_schema_violations(&schema, Draft::Draft7);
+        let pointers: Vec<&str> = violations.iter().map(|v| v.pointer.as_str()).collect();
+        assert!(pointers.contains(&"/properties/id/type"));
+        assert!(pointers.contains(&"/properties/tags/minItems"));
+
+        let validator = JsonSchemaValidator::new_draft_7();
+        let result = validator.validate(&schema.to_string()).unwrap();
+        assert!(result
+            .errors
+            .iter()
+            .any(|e| e.rule == "json-schema-meta-schema"
+                && e.location.as_deref() == Some("/properties/id/type")));
+    }
+
+    #[test]
+    fn test_declared_draft() {
+        let draft = |uri: &str| declared_draft(&serde_json::json!({ "$schema": uri }), Draft::Draft7);
+        assert_eq!(draft("http://json-schema.org/draft-07/schema#").unwrap(), Draft::Draft7);
+        assert_eq!(draft("https://json-schema.org/draft-07/schema").unwrap(), Draft::Draft7);
+        assert_eq!(draft("https://json-schema.org/draft/2019-09/schema").unwrap(), Draft::Draft201909);
+        assert_eq!(draft("https://json-schema.org/draft/2020-12/schema").unwrap(), Draft::Draft202012);
+        assert!(draft("https://example.com/my-dialect").is_err());
+        assert_eq!(declared_draft(&serde_json::json!({}), Draft::Draft202012).unwrap(), Draft::Draft202012);
+
+        let violations = meta_schema_violations(
+            &serde_json::json!({ "$schema": "https://example.com/my-dialect" }),
+            Draft::Draft7,
+        );
+        assert_eq!(violations[0].pointer, "/$schema");
+    }
+
     #[test]
     fn test_missing_type_warning() {
         let validator = JsonSchemaValidator::new_draft_7();
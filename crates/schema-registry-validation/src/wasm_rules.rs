@@ -0,0 +1,305 @@
+//! Custom validation rules compiled to WebAssembly
+//!
+//! Platform teams can ship org rules, such as naming conventions or PII
+//! markers, as WASM modules instead of patching the registry. Each module
+//! becomes a [`ValidationRule`] and runs in a sandbox: it gets no imports, so
+//! it can't reach the filesystem, the network or the clock, and every call is
+//! bounded in fuel (roughly, instructions executed) and memory.
+//!
+//! A rule module exports:
+//!
+//! - `memory`: its linear memory
+//! - `alloc(len: i32) -> i32`: space for `len` bytes of input
+//! - `validate(ptr: i32, len: i32) -> i64`: check the input at `ptr`, and
+//!   return where its findings are, the pointer in the high 32 bits and the
+//!   length in the low 32 bits
+//!
+//! The input is a JSON object `{"schema": "<content>", "format": "avro"}`,
+//! `format` being one of `json-schema`, `avro` or `protobuf`. The findings
+//! are a JSON array of `{"message", "location"?, "suggestion"?, "severity"?}`
+//! objects; an empty array means the schema passes. `severity` is `info`,
+//! `warning` or `error` (the default).
+
+use crate::engine::ValidationRule;
+use crate::types::{SchemaFormat, Severity, ValidationError};
+use anyhow::{anyhow, bail, Context, Result};
+use serde::Deserialize;
+use std::path::Path;
+use wasmtime::{Config, Engine, Linker, Module, Store, StoreLimitsBuilder};
+
+/// Fuel one call may burn by default
+pub const DEFAULT_FUEL: u64 = 10_000_000;
+
+/// Linear memory one rule may grow to by default
+pub const DEFAULT_MEMORY_LIMIT: usize = 16 * 1024 * 1024;
+
+/// Largest findings document a rule may return
+const MAX_OUTPUT_BYTES: usize = 1024 * 1024;
+
+/// Compiles rule modules, sharing one engine between them
+#[derive(Clone)]
+pub struct WasmRuleLoader {
+    engine: Engine,
+    fuel: u64,
+    memory_limit: usize,
+}
+
+impl WasmRuleLoader {
+    pub fn new() -> Result<Self> {
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        Ok(Self {
+            engine: Engine::new(&config)?,
+            fuel: DEFAULT_FUEL,
+            memory_limit: DEFAULT_MEMORY_LIMIT,
+        })
+    }
+
+    /// Fuel each call of the rules loaded from now on may burn
+    pub fn with_fuel(mut self, fuel: u64) -> Self {
+        self.fuel = fuel;
+        self
+    }
+
+    /// Linear memory each rule loaded from now on may grow to, in bytes
+    pub fn with_memory_limit(mut self, bytes: usize) -> Self {
+        self.memory_limit = bytes;
+        self
+    }
+
+    /// Compile the module `bytes` (binary or text format) as the rule `name`
+    pub fn load(&self, name: impl Into<String>, bytes: impl AsRef<[u8]>) -> Result<WasmRule> {
+        let name = name.into();
+        let module = Module::new(&self.engine, bytes)
+            .with_context(|| format!("Rule '{}' is not a valid WASM module", name))?;
+        if let Some(import) = module.imports().next() {
+            bail!(
+                "Rule '{}' imports {}::{}; rules run without host access",
+                name,
+                import.module(),
+                import.name()
+            );
+        }
+        for export in ["memory", "alloc", "validate"] {
+            if module.get_export(export).is_none() {
+                bail!("Rule '{}' doesn't export '{}'", name, export);
+            }
+        }
+        Ok(WasmRule {
+            name,
+            engine: self.engine.clone(),
+            module,
+            fuel: self.fuel,
+            memory_limit: self.memory_limit,
+        })
+    }
+
+    /// Load every `*.wasm` file in `dir`, in file name order, each named after
+    /// its file stem
+    pub fn load_dir(&self, dir: &Path) -> Result<Vec<WasmRule>> {
+        let mut paths = Vec::new();
+        for entry in std::fs::read_dir(dir)
+            .with_context(|| format!("Cannot read rule directory {}", dir.display()))?
+        {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "wasm") {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+
+        paths
+            .iter()
+            .map(|path| {
+                let name = path
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                let bytes = std::fs::read(path)
+                    .with_context(|| format!("Cannot read rule {}", path.display()))?;
+                self.load(name, bytes)
+            })
+            .collect()
+    }
+}
+
+/// A validation rule running a WASM module in a sandbox
+pub struct WasmRule {
+    name: String,
+    engine: Engine,
+    module: Module,
+    fuel: u64,
+    memory_limit: usize,
+}
+
+impl WasmRule {
+    /// Run the module on `input`, returning the findings document
+    fn call(&self, input: &[u8]) -> Result<Vec<u8>> {
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(self.memory_limit)
+            .instances(1)
+            .build();
+        let mut store = Store::new(&self.engine, limits);
+        store.limiter(|limits| limits);
+        store.set_fuel(self.fuel)?;
+
+        let instance = Linker::new(&self.engine).instantiate(&mut store, &self.module)?;
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or_else(|| anyhow!("'memory' is not a memory"))?;
+        let alloc = instance.get_typed_func::<i32, i32>(&mut store, "alloc")?;
+        let validate = instance.get_typed_func::<(i32, i32), i64>(&mut store, "validate")?;
+
+        let len = i32::try_from(input.len()).context("Schema is too large for a WASM rule")?;
+        let ptr = alloc.call(&mut store, len)?;
+        memory
+            .write(&mut store, ptr as u32 as usize, input)
+            .context("'alloc' returned space outside memory")?;
+
+        let packed = validate.call(&mut store, (ptr, len))? as u64;
+        let (out_ptr, out_len) = ((packed >> 32) as usize, (packed & 0xffff_ffff) as usize);
+        if out_len > MAX_OUTPUT_BYTES {
+            bail!(
+                "Findings are {} bytes, over the limit of {}",
+                out_len,
+                MAX_OUTPUT_BYTES
+            );
+        }
+        let mut output = vec![0; out_len];
+        memory
+            .read(&store, out_ptr, &mut output)
+            .context("Findings lie outside memory")?;
+        Ok(output)
+    }
+}
+
+impl ValidationRule for WasmRule {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn validate(&self, schema: &str, format: SchemaFormat) -> Result<Vec<ValidationError>> {
+        let input = serde_json::to_vec(&serde_json::json!({
+            "schema": schema,
+            "format": format.as_str(),
+        }))?;
+        let output = self.call(&input)?;
+        let findings: Vec<Finding> =
+            serde_json::from_slice(&output).context("Findings are not a JSON array of findings")?;
+
+        Ok(findings
+            .into_iter()
+            .map(|finding| {
+                let mut error = ValidationError::new(self.name.clone(), finding.message);
+                error.severity = finding.severity.map_or(self.severity(), Severity::from);
+                error.location = finding.location;
+                error.suggestion = finding.suggestion;
+                error
+            })
+            .collect())
+    }
+}
+
+/// One finding as a rule module reports it
+#[derive(Deserialize)]
+struct Finding {
+    message: String,
+    #[serde(default)]
+    location: Option<String>,
+    #[serde(default)]
+    suggestion: Option<String>,
+    #[serde(default)]
+    severity: Option<FindingSeverity>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum FindingSeverity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl From<FindingSeverity> for Severity {
+    fn from(severity: FindingSeverity) -> Self {
+        match severity {
+            FindingSeverity::Info => Severity::Info,
+            FindingSeverity::Warning => Severity::Warning,
+            FindingSeverity::Error => Severity::Error,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Flags every schema, returning its findings from a data segment
+    const PII_RULE: &str = r#"(module
+        (memory (export "memory") 1)
+        (global $next (mut i32) (i32.const 1024))
+        (func (export "alloc") (param $len i32) (result i32)
+            (local $ptr i32)
+            global.get $next
+            local.set $ptr
+            global.get $next
+            local.get $len
+            i32.add
+            global.set $next
+            local.get $ptr)
+        (data (i32.const 0) "[{\"message\":\"Field names must not mention ssn\",\"location\":\"$.ssn\",\"severity\":\"warning\"}]")
+        (func (export "validate") (param i32 i32) (result i64)
+            i64.const 88))"#;
+
+    #[test]
+    fn test_findings_become_validation_errors() {
+        let rule = WasmRuleLoader::new()
+            .unwrap()
+            .load("pii", PII_RULE)
+            .unwrap();
+        let errors = rule
+            .validate(r#"{"properties": {"ssn": {}}}"#, SchemaFormat::JsonSchema)
+            .unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].rule, "pii");
+        assert_eq!(errors[0].severity, Severity::Warning);
+        assert_eq!(errors[0].location.as_deref(), Some("$.ssn"));
+    }
+
+    #[test]
+    fn test_runaway_rules_run_out_of_fuel() {
+        let rule = WasmRuleLoader::new()
+            .unwrap()
+            .with_fuel(10_000)
+            .load(
+                "spin",
+                r#"(module
+                    (memory (export "memory") 1)
+                    (func (export "alloc") (param i32) (result i32) i32.const 0)
+                    (func (export "validate") (param i32 i32) (result i64)
+                        (loop $spin br $spin)
+                        i64.const 0))"#,
+            )
+            .unwrap();
+        assert!(rule.validate("{}", SchemaFormat::JsonSchema).is_err());
+    }
+
+    #[test]
+    fn test_rules_may_not_import_host_functions() {
+        let loader = WasmRuleLoader::new().unwrap();
+        let result = loader.load(
+            "leaky",
+            r#"(module
+                (import "wasi_snapshot_preview1" "fd_write" (func (param i32 i32 i32 i32) (result i32)))
+                (memory (export "memory") 1)
+                (func (export "alloc") (param i32) (result i32) i32.const 0)
+                (func (export "validate") (param i32 i32) (result i64) i64.const 0))"#,
+        );
+        assert!(result.is_err());
+        assert!(loader.load("empty", "(module)").is_err());
+    }
+}
@@ -14,6 +14,10 @@ import (
 	"time"
 )
 
+// SDKVersion is the version of this SDK, sent to the registry as "go/<version>"
+// in the X-SDK-Version header so it can report which SDK releases are in use.
+const SDKVersion = "1.0.0"
+
 // Client is a production-ready, thread-safe client for the LLM Schema Registry.
 // It supports context-based cancellation, automatic retries, and in-memory caching.
 type Client struct {
@@ -410,6 +414,7 @@ func (c *Client) doRequest(ctx context.Context, method, path string, body []byte
 	// Set headers
 	req.Header.Set("Content-Type", "application/json")
 	req.Header.Set("Accept", "application/json")
+	req.Header.Set("X-SDK-Version", "go/"+SDKVersion)
 	if c.apiKey != "" {
 		req.Header.Set("Authorization", fmt.Sprintf("Bearer %s", c.apiKey))
 	}
@@ -74,6 +74,12 @@ public final class SchemaRegistryClient implements Closeable {
     private static final Logger logger = LoggerFactory.getLogger(SchemaRegistryClient.class);
     private static final MediaType JSON = MediaType.get("application/json; charset=utf-8");
 
+    /**
+     * Version of this SDK, sent as {@code java/<version>} in the {@code X-SDK-Version}
+     * header so the registry can report which SDK releases are in use.
+     */
+    public static final String SDK_VERSION = "1.0.0";
+
     private final String baseUrl;
     private final OkHttpClient httpClient;
     private final ObjectMapper objectMapper;
@@ -100,7 +106,10 @@ public final class SchemaRegistryClient implements Closeable {
                 .connectTimeout(builder.timeout)
                 .readTimeout(builder.timeout)
                 .writeTimeout(builder.timeout)
-                .retryOnConnectionFailure(true);
+                .retryOnConnectionFailure(true)
+                .addInterceptor(chain -> chain.proceed(chain.request().newBuilder()
+                        .header("X-SDK-Version", "java/" + SDK_VERSION)
+                        .build()));
 
         // Add authentication if API key is provided
         if (builder.apiKey != null) {
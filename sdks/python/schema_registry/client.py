@@ -15,6 +15,7 @@ from tenacity import (
     wait_exponential,
 )
 
+from . import __version__
 from .exceptions import (
     AuthenticationError,
     AuthorizationError,
@@ -95,7 +96,11 @@ class SchemaRegistryClient:
         self._cache: TTLCache = TTLCache(maxsize=cache_maxsize, ttl=cache_ttl)
 
         # HTTP client
-        headers = {"Content-Type": "application/json"}
+        headers = {
+            "Content-Type": "application/json",
+            # Lets the registry report which SDK releases are in use
+            "X-SDK-Version": f"python/{__version__}",
+        }
         if api_key:
             headers["Authorization"] = f"Bearer {api_key}"
 
@@ -0,0 +1,457 @@
+//! Derive macro for `llm_schema_registry_sdk::bootstrap::RegistrySchema`.
+//!
+//! Use it through the SDK's `derive` feature rather than depending on this
+//! crate directly; the generated code refers to `::llm_schema_registry_sdk`.
+//!
+//! The macro reads the same serde attributes that shape the serialized form
+//! (`rename`, `rename_all`, `skip`, `skip_serializing`, `skip_serializing_if`,
+//! `default` and `deny_unknown_fields`), so the generated JSON Schema describes
+//! what the type actually puts on the wire. Attributes that change the shape in
+//! ways a schema can't be derived from (`flatten`, `with`, `tag`, ...) are
+//! rejected at compile time instead of producing a schema that doesn't match.
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::meta::ParseNestedMeta;
+use syn::{
+    parse_macro_input, parse_quote, Attribute, Data, DeriveInput, Expr, Fields, LitStr, Token,
+};
+
+/// Derives `ToJsonSchema` and, with `#[registry(...)]`, `RegistrySchema`.
+///
+/// ```ignore
+/// #[derive(Serialize, Deserialize, RegistrySchema)]
+/// #[registry(namespace = "telemetry", name = "InferenceEvent", version = "1.2.0")]
+/// #[serde(rename_all = "camelCase")]
+/// struct InferenceEvent {
+///     /// Model that served the request
+///     model_id: String,
+///     latency_ms: Option<u64>,
+/// }
+/// ```
+///
+/// `name` defaults to the type's (serde) name. Types without a `registry`
+/// attribute only implement `ToJsonSchema`, so they can be used as fields of
+/// registered types.
+#[proc_macro_derive(RegistrySchema, attributes(registry))]
+pub fn derive_registry_schema(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let container = Container::from_attrs(&input.attrs)?;
+    let description = option_lit(docs(&input.attrs).as_deref());
+
+    let body = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => {
+                let mut properties = Vec::new();
+                for field in &fields.named {
+                    let attrs = FieldAttrs::from_attrs(&field.attrs)?;
+                    if attrs.skip {
+                        continue;
+                    }
+                    let ident = field.ident.as_ref().expect("named field");
+                    let name = attrs.rename.unwrap_or_else(|| {
+                        container
+                            .rename_all
+                            .apply_to_field(&unraw(&ident.to_string()))
+                    });
+                    let ty = &field.ty;
+                    let may_omit = container.default || attrs.may_omit;
+                    let field_description = option_lit(docs(&field.attrs).as_deref());
+                    let deprecated = field
+                        .attrs
+                        .iter()
+                        .any(|attr| attr.path().is_ident("deprecated"));
+                    properties.push(quote! {
+                        .field(
+                            #name,
+                            <#ty as ::llm_schema_registry_sdk::bootstrap::ToJsonSchema>::json_schema(),
+                            #may_omit || <#ty as ::llm_schema_registry_sdk::bootstrap::ToJsonSchema>::optional(),
+                            #field_description,
+                            #deprecated,
+                        )
+                    });
+                }
+                let deny_unknown_fields = container.deny_unknown_fields;
+                quote! {
+                    ::llm_schema_registry_sdk::bootstrap::__private::Object::new()
+                        #(#properties)*
+                        .finish(#description, #deny_unknown_fields)
+                }
+            }
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                let ty = &fields.unnamed[0].ty;
+                quote! {
+                    <#ty as ::llm_schema_registry_sdk::bootstrap::ToJsonSchema>::json_schema()
+                }
+            }
+            Fields::Unnamed(_) => {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "RegistrySchema supports structs with named fields and newtype structs",
+                ))
+            }
+            Fields::Unit => quote! {
+                ::llm_schema_registry_sdk::bootstrap::__private::null()
+            },
+        },
+        Data::Enum(data) => {
+            if container.tagged {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "RegistrySchema doesn't support tagged or untagged enums",
+                ));
+            }
+            let mut variants = Vec::new();
+            for variant in &data.variants {
+                if !matches!(variant.fields, Fields::Unit) {
+                    return Err(syn::Error::new_spanned(
+                        variant,
+                        "RegistrySchema only supports enums with unit variants",
+                    ));
+                }
+                let attrs = FieldAttrs::from_attrs(&variant.attrs)?;
+                if attrs.skip {
+                    continue;
+                }
+                variants.push(attrs.rename.unwrap_or_else(|| {
+                    container
+                        .rename_all
+                        .apply_to_variant(&unraw(&variant.ident.to_string()))
+                }));
+            }
+            quote! {
+                ::llm_schema_registry_sdk::bootstrap::__private::string_enum(&[#(#variants),*], #description)
+            }
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "RegistrySchema can't be derived for unions",
+            ))
+        }
+    };
+
+    let ident = &input.ident;
+    let mut generics = input.generics.clone();
+    for param in generics.type_params_mut() {
+        param.bounds.push(parse_quote!(
+            ::llm_schema_registry_sdk::bootstrap::ToJsonSchema
+        ));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let registry = match container.registry {
+        Some(registry) => {
+            let namespace = registry.namespace.ok_or_else(|| {
+                syn::Error::new(registry.span, "#[registry(...)] needs a `namespace`")
+            })?;
+            let version = registry.version.ok_or_else(|| {
+                syn::Error::new(registry.span, "#[registry(...)] needs a `version`")
+            })?;
+            let name = registry
+                .name
+                .or(container.rename)
+                .unwrap_or_else(|| unraw(&ident.to_string()));
+            quote! {
+                impl #impl_generics ::llm_schema_registry_sdk::bootstrap::RegistrySchema for #ident #ty_generics #where_clause {
+                    const NAMESPACE: &'static str = #namespace;
+                    const NAME: &'static str = #name;
+                    const VERSION: &'static str = #version;
+                }
+            }
+        }
+        None => TokenStream2::new(),
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::llm_schema_registry_sdk::bootstrap::ToJsonSchema for #ident #ty_generics #where_clause {
+            fn json_schema() -> ::llm_schema_registry_sdk::bootstrap::__private::Value {
+                #body
+            }
+        }
+
+        #registry
+    })
+}
+
+/// The `#[registry(...)]` attribute
+struct Registry {
+    span: Span,
+    namespace: Option<String>,
+    name: Option<String>,
+    version: Option<String>,
+}
+
+/// Container-level attributes
+#[derive(Default)]
+struct Container {
+    registry: Option<Registry>,
+    rename: Option<String>,
+    rename_all: RenameRule,
+    deny_unknown_fields: bool,
+    default: bool,
+    tagged: bool,
+}
+
+impl Container {
+    fn from_attrs(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut container = Self::default();
+        for attr in attrs {
+            if attr.path().is_ident("registry") {
+                let mut registry = Registry {
+                    span: Span::call_site(),
+                    namespace: None,
+                    name: None,
+                    version: None,
+                };
+                attr.parse_nested_meta(|meta| {
+                    let value = meta.value()?.parse::<LitStr>()?;
+                    registry.span = value.span();
+                    let value = value.value();
+                    if meta.path.is_ident("namespace") {
+                        registry.namespace = Some(value);
+                    } else if meta.path.is_ident("name") {
+                        registry.name = Some(value);
+                    } else if meta.path.is_ident("version") {
+                        registry.version = Some(value);
+                    } else {
+                        return Err(meta.error("expected `namespace`, `name` or `version`"));
+                    }
+                    Ok(())
+                })?;
+                container.registry = Some(registry);
+            } else if attr.path().is_ident("serde") {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("rename") {
+                        container.rename = serialized_name(&meta)?;
+                    } else if meta.path.is_ident("rename_all") {
+                        if let Some(rule) = serialized_name(&meta)? {
+                            container.rename_all = RenameRule::parse(&rule).ok_or_else(|| {
+                                meta.error(format!("unknown rename rule `{rule}`"))
+                            })?;
+                        }
+                    } else if meta.path.is_ident("deny_unknown_fields") {
+                        container.deny_unknown_fields = true;
+                    } else if meta.path.is_ident("default") {
+                        container.default = true;
+                        skip_value(&meta)?;
+                    } else if meta.path.is_ident("tag")
+                        || meta.path.is_ident("content")
+                        || meta.path.is_ident("untagged")
+                    {
+                        container.tagged = true;
+                        skip_value(&meta)?;
+                    } else if meta.path.is_ident("transparent")
+                        || meta.path.is_ident("from")
+                        || meta.path.is_ident("try_from")
+                        || meta.path.is_ident("into")
+                        || meta.path.is_ident("remote")
+                    {
+                        return Err(meta.error(
+                            "RegistrySchema can't describe types serialized through another type",
+                        ));
+                    } else {
+                        skip_value(&meta)?;
+                    }
+                    Ok(())
+                })?;
+            }
+        }
+        Ok(container)
+    }
+}
+
+/// Field- and variant-level serde attributes
+#[derive(Default)]
+struct FieldAttrs {
+    rename: Option<String>,
+    skip: bool,
+    may_omit: bool,
+}
+
+impl FieldAttrs {
+    fn from_attrs(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut field = Self::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    field.rename = serialized_name(&meta)?;
+                } else if meta.path.is_ident("skip") || meta.path.is_ident("skip_serializing") {
+                    field.skip = true;
+                } else if meta.path.is_ident("default") || meta.path.is_ident("skip_serializing_if")
+                {
+                    field.may_omit = true;
+                    skip_value(&meta)?;
+                } else if meta.path.is_ident("flatten")
+                    || meta.path.is_ident("with")
+                    || meta.path.is_ident("serialize_with")
+                    || meta.path.is_ident("other")
+                    || meta.path.is_ident("untagged")
+                {
+                    return Err(meta.error(
+                        "RegistrySchema can't describe fields with a custom serialized shape",
+                    ));
+                } else {
+                    skip_value(&meta)?;
+                }
+                Ok(())
+            })?;
+        }
+        Ok(field)
+    }
+}
+
+/// `rename = "x"` or the serialize half of `rename(serialize = "x", ...)`
+fn serialized_name(meta: &ParseNestedMeta) -> syn::Result<Option<String>> {
+    if meta.input.peek(Token![=]) {
+        return Ok(Some(meta.value()?.parse::<LitStr>()?.value()));
+    }
+    let mut name = None;
+    meta.parse_nested_meta(|nested| {
+        if nested.path.is_ident("serialize") {
+            name = Some(nested.value()?.parse::<LitStr>()?.value());
+        } else {
+            skip_value(&nested)?;
+        }
+        Ok(())
+    })?;
+    Ok(name)
+}
+
+/// Consume the value of a serde attribute that doesn't affect the schema
+fn skip_value(meta: &ParseNestedMeta) -> syn::Result<()> {
+    if meta.input.peek(Token![=]) {
+        meta.value()?.parse::<Expr>()?;
+    } else if meta.input.peek(syn::token::Paren) {
+        meta.parse_nested_meta(|nested| skip_value(&nested))?;
+    }
+    Ok(())
+}
+
+/// Doc comment text, used as the schema `description`
+fn docs(attrs: &[Attribute]) -> Option<String> {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            syn::Meta::NameValue(nv) => match &nv.value {
+                Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(text),
+                    ..
+                }) => Some(text.value().trim().to_string()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect();
+    let text = lines.join("\n").trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+fn option_lit(text: Option<&str>) -> TokenStream2 {
+    match text {
+        Some(text) => quote!(::std::option::Option::Some(#text)),
+        None => quote!(::std::option::Option::None),
+    }
+}
+
+fn unraw(ident: &str) -> String {
+    ident.trim_start_matches("r#").to_string()
+}
+
+/// serde's `rename_all` rules
+#[derive(Default, Clone, Copy)]
+enum RenameRule {
+    #[default]
+    None,
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+}
+
+impl RenameRule {
+    fn parse(rule: &str) -> Option<Self> {
+        Some(match rule {
+            "lowercase" => Self::Lower,
+            "UPPERCASE" => Self::Upper,
+            "PascalCase" => Self::Pascal,
+            "camelCase" => Self::Camel,
+            "snake_case" => Self::Snake,
+            "SCREAMING_SNAKE_CASE" => Self::ScreamingSnake,
+            "kebab-case" => Self::Kebab,
+            "SCREAMING-KEBAB-CASE" => Self::ScreamingKebab,
+            _ => return None,
+        })
+    }
+
+    /// Rename a snake_case field
+    fn apply_to_field(self, field: &str) -> String {
+        match self {
+            Self::None | Self::Lower | Self::Snake => field.to_string(),
+            Self::Upper | Self::ScreamingSnake => field.to_ascii_uppercase(),
+            Self::Pascal => field
+                .split('_')
+                .map(|word| {
+                    let mut chars = word.chars();
+                    chars
+                        .next()
+                        .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                        .unwrap_or_default()
+                })
+                .collect(),
+            Self::Camel => {
+                let pascal = Self::Pascal.apply_to_field(field);
+                let mut chars = pascal.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_ascii_lowercase().to_string() + chars.as_str())
+                    .unwrap_or_default()
+            }
+            Self::Kebab => field.replace('_', "-"),
+            Self::ScreamingKebab => field.to_ascii_uppercase().replace('_', "-"),
+        }
+    }
+
+    /// Rename a PascalCase variant
+    fn apply_to_variant(self, variant: &str) -> String {
+        match self {
+            Self::None | Self::Pascal => variant.to_string(),
+            Self::Lower => variant.to_ascii_lowercase(),
+            Self::Upper => variant.to_ascii_uppercase(),
+            Self::Camel => {
+                let mut chars = variant.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_ascii_lowercase().to_string() + chars.as_str())
+                    .unwrap_or_default()
+            }
+            Self::Snake => {
+                let mut snake = String::new();
+                for (i, ch) in variant.char_indices() {
+                    if i > 0 && ch.is_uppercase() {
+                        snake.push('_');
+                    }
+                    snake.push(ch.to_ascii_lowercase());
+                }
+                snake
+            }
+            Self::ScreamingSnake => Self::Snake.apply_to_variant(variant).to_ascii_uppercase(),
+            Self::Kebab => Self::Snake.apply_to_variant(variant).replace('_', "-"),
+            Self::ScreamingKebab => Self::ScreamingSnake
+                .apply_to_variant(variant)
+                .replace('_', "-"),
+        }
+    }
+}
@@ -0,0 +1,113 @@
+//! The registry operations as a trait.
+//!
+//! Code that talks to the registry can depend on [`SchemaRegistryApi`]
+//! instead of [`SchemaRegistryClient`], and be unit-tested against
+//! [`MockSchemaRegistryClient`](crate::mock::MockSchemaRegistryClient):
+//!
+//! ```
+//! use llm_schema_registry_sdk::{Result, SchemaRegistryApi};
+//!
+//! async fn is_valid_event(api: &dyn SchemaRegistryApi, schema_id: &str, event: &str) -> Result<bool> {
+//!     Ok(api.validate_data(schema_id, event).await?.is_valid())
+//! }
+//! ```
+
+use crate::client::SchemaRegistryClient;
+use crate::errors::Result;
+use crate::models::{
+    CompatibilityMode, CompatibilityResult, GetSchemaResponse, HealthCheckResponse,
+    ListVersionsResponse, RegisterSchemaResponse, Schema, SearchQuery, SearchResponse,
+    ValidateResponse,
+};
+use async_trait::async_trait;
+
+/// Schema registry operations, implemented by the real and the mock client.
+///
+/// See the inherent methods of [`SchemaRegistryClient`] for details of each
+/// operation.
+#[async_trait]
+pub trait SchemaRegistryApi: Send + Sync {
+    /// Registers a new schema or retrieves an existing one.
+    async fn register_schema(&self, schema: Schema) -> Result<RegisterSchemaResponse>;
+
+    /// Retrieves a schema by its ID.
+    async fn get_schema(&self, schema_id: &str) -> Result<GetSchemaResponse>;
+
+    /// Retrieves a schema by namespace, name, and version.
+    async fn get_schema_by_version(
+        &self,
+        namespace: &str,
+        name: &str,
+        version: &str,
+    ) -> Result<GetSchemaResponse>;
+
+    /// Validates data against a schema.
+    async fn validate_data(&self, schema_id: &str, data: &str) -> Result<ValidateResponse>;
+
+    /// Checks compatibility between a new schema and existing versions.
+    async fn check_compatibility(
+        &self,
+        schema: Schema,
+        mode: CompatibilityMode,
+    ) -> Result<CompatibilityResult>;
+
+    /// Lists all versions of a schema.
+    async fn list_versions(&self, namespace: &str, name: &str) -> Result<ListVersionsResponse>;
+
+    /// Searches for schemas matching a query.
+    async fn search_schemas(&self, query: SearchQuery) -> Result<SearchResponse>;
+
+    /// Deletes a schema by ID.
+    async fn delete_schema(&self, schema_id: &str) -> Result<()>;
+
+    /// Performs a health check on the Schema Registry service.
+    async fn health_check(&self) -> Result<HealthCheckResponse>;
+}
+
+#[async_trait]
+impl SchemaRegistryApi for SchemaRegistryClient {
+    async fn register_schema(&self, schema: Schema) -> Result<RegisterSchemaResponse> {
+        SchemaRegistryClient::register_schema(self, schema).await
+    }
+
+    async fn get_schema(&self, schema_id: &str) -> Result<GetSchemaResponse> {
+        SchemaRegistryClient::get_schema(self, schema_id).await
+    }
+
+    async fn get_schema_by_version(
+        &self,
+        namespace: &str,
+        name: &str,
+        version: &str,
+    ) -> Result<GetSchemaResponse> {
+        SchemaRegistryClient::get_schema_by_version(self, namespace, name, version).await
+    }
+
+    async fn validate_data(&self, schema_id: &str, data: &str) -> Result<ValidateResponse> {
+        SchemaRegistryClient::validate_data(self, schema_id, data).await
+    }
+
+    async fn check_compatibility(
+        &self,
+        schema: Schema,
+        mode: CompatibilityMode,
+    ) -> Result<CompatibilityResult> {
+        SchemaRegistryClient::check_compatibility(self, schema, mode).await
+    }
+
+    async fn list_versions(&self, namespace: &str, name: &str) -> Result<ListVersionsResponse> {
+        SchemaRegistryClient::list_versions(self, namespace, name).await
+    }
+
+    async fn search_schemas(&self, query: SearchQuery) -> Result<SearchResponse> {
+        SchemaRegistryClient::search_schemas(self, query).await
+    }
+
+    async fn delete_schema(&self, schema_id: &str) -> Result<()> {
+        SchemaRegistryClient::delete_schema(self, schema_id).await
+    }
+
+    async fn health_check(&self) -> Result<HealthCheckResponse> {
+        SchemaRegistryClient::health_check(self).await
+    }
+}
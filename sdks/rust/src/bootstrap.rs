@@ -0,0 +1,576 @@
+//! Schemas generated from Rust types.
+//!
+//! With the `derive` feature, `#[derive(RegistrySchema)]` generates a JSON
+//! Schema from a serde-annotated struct or unit enum. A service can then
+//! register that schema, or check at startup that the registry still holds
+//! exactly what it was compiled against and refuse to start otherwise:
+//!
+//! ```ignore
+//! use llm_schema_registry_sdk::{bootstrap, RegistrySchema, SchemaRegistryClient};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize, RegistrySchema)]
+//! #[registry(namespace = "telemetry", name = "InferenceEvent", version = "1.2.0")]
+//! #[serde(rename_all = "camelCase")]
+//! struct InferenceEvent {
+//!     /// Model that served the request
+//!     model_id: String,
+//!     latency_ms: Option<u64>,
+//! }
+//!
+//! # async fn start(client: &SchemaRegistryClient) -> llm_schema_registry_sdk::Result<()> {
+//! // Fails with `SchemaRegistryError::SchemaDrift` if the types and registry disagree
+//! bootstrap::verify::<InferenceEvent>(client).await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Schemas are compared by meaning, not text: key order, the order of
+//! `required` and `enum` and annotations such as `description` and `title`
+//! don't count as drift.
+
+use crate::api::SchemaRegistryApi;
+use crate::errors::{Result, SchemaRegistryError};
+use crate::models::{RegisterSchemaResponse, Schema, SchemaFormat};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// JSON Schema dialect declared by generated schemas.
+pub const DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Keywords that document a schema without changing what it accepts.
+const ANNOTATIONS: &[&str] = &["$schema", "$comment", "title", "description", "examples"];
+
+/// Keywords whose value maps names to schemas rather than being a schema.
+const SCHEMA_MAPS: &[&str] = &["properties", "patternProperties", "$defs", "definitions"];
+
+/// A type that can describe its serialized form as a JSON Schema.
+///
+/// Implemented for primitives, strings, collections, maps, `Option`,
+/// `serde_json::Value` and chrono dates; derive it for your own types.
+pub trait ToJsonSchema {
+    /// The JSON Schema of this type's serialized form.
+    fn json_schema() -> Value;
+
+    /// Whether a struct field of this type may be left out.
+    #[must_use]
+    fn optional() -> bool {
+        false
+    }
+}
+
+/// A type registered under a fixed subject and version.
+///
+/// Usually derived, with the subject in `#[registry(namespace = "...",
+/// name = "...", version = "...")]`.
+pub trait RegistrySchema: ToJsonSchema {
+    /// Schema namespace.
+    const NAMESPACE: &'static str;
+    /// Schema name.
+    const NAME: &'static str;
+    /// Schema version.
+    const VERSION: &'static str;
+
+    /// The JSON Schema document registered for this type.
+    #[must_use]
+    fn content() -> Value {
+        let mut schema = Self::json_schema();
+        if let Some(object) = schema.as_object_mut() {
+            object.insert("$schema".to_string(), Value::from(DIALECT));
+            object.insert("title".to_string(), Value::from(Self::NAME));
+        }
+        schema
+    }
+
+    /// The schema to register for this type.
+    #[must_use]
+    fn schema() -> Schema {
+        Schema::new(
+            Self::NAMESPACE,
+            Self::NAME,
+            Self::VERSION,
+            SchemaFormat::JsonSchema,
+            Self::content().to_string(),
+        )
+    }
+}
+
+fn subject<T: RegistrySchema>() -> String {
+    format!("{}.{}@{}", T::NAMESPACE, T::NAME, T::VERSION)
+}
+
+/// Registers `T`'s schema.
+///
+/// If the version is already registered, it is verified instead, so a
+/// service started with changed types but an unchanged version fails rather
+/// than silently using the old schema.
+pub async fn register<T: RegistrySchema>(
+    api: &(impl SchemaRegistryApi + ?Sized),
+) -> Result<RegisterSchemaResponse> {
+    let response = api.register_schema(T::schema()).await?;
+    if !response.created {
+        verify::<T>(api).await?;
+    }
+    Ok(response)
+}
+
+/// Checks that the registry holds exactly `T`'s schema.
+///
+/// Returns [`SchemaRegistryError::SchemaNotFound`] if the version isn't
+/// registered and [`SchemaRegistryError::SchemaDrift`] if it differs.
+pub async fn verify<T: RegistrySchema>(api: &(impl SchemaRegistryApi + ?Sized)) -> Result<()> {
+    let registered = api
+        .get_schema_by_version(T::NAMESPACE, T::NAME, T::VERSION)
+        .await?;
+    let drift = |differences| SchemaRegistryError::SchemaDrift {
+        subject: subject::<T>(),
+        differences,
+    };
+
+    if registered.metadata.format != SchemaFormat::JsonSchema {
+        return Err(drift(vec![format!(
+            "registered as {:?}, not JSON Schema",
+            registered.metadata.format
+        )]));
+    }
+    let actual: Value = serde_json::from_str(&registered.content)
+        .map_err(|e| drift(vec![format!("registered content is not JSON: {e}")]))?;
+
+    let differences = differences(&T::content(), &actual);
+    if differences.is_empty() {
+        Ok(())
+    } else {
+        Err(drift(differences))
+    }
+}
+
+/// Where two JSON Schemas differ, as JSON Pointer paths.
+///
+/// `expected` is the compiled type's schema and `actual` the registry's.
+#[must_use]
+pub fn differences(expected: &Value, actual: &Value) -> Vec<String> {
+    let mut out = Vec::new();
+    diff_at("", expected, actual, true, &mut out);
+    out
+}
+
+fn diff_at(path: &str, expected: &Value, actual: &Value, is_schema: bool, out: &mut Vec<String>) {
+    match (expected, actual) {
+        (Value::Object(expected), Value::Object(actual)) => {
+            let counts = |key: &String| !(is_schema && ANNOTATIONS.contains(&key.as_str()));
+            let child_is_schema = |key: &String| !is_schema || !SCHEMA_MAPS.contains(&key.as_str());
+            for (key, value) in expected.iter().filter(|(key, _)| counts(key)) {
+                let child = format!("{path}/{}", escape(key));
+                match actual.get(key) {
+                    Some(other) => diff_at(&child, value, other, child_is_schema(key), out),
+                    None => out.push(format!("{child}: only in the compiled type")),
+                }
+            }
+            for key in actual.keys().filter(|key| counts(key)) {
+                if !expected.contains_key(key) {
+                    out.push(format!("{path}/{}: only in the registry", escape(key)));
+                }
+            }
+        }
+        (Value::Array(e), Value::Array(a)) if is_scalars(e) && is_scalars(a) => {
+            let as_set = |values: &[Value]| -> BTreeSet<String> {
+                values.iter().map(Value::to_string).collect()
+            };
+            if as_set(e) != as_set(a) {
+                out.push(mismatch(path, expected, actual));
+            }
+        }
+        (Value::Array(e), Value::Array(a)) if e.len() == a.len() => {
+            for (i, (value, other)) in e.iter().zip(a).enumerate() {
+                diff_at(&format!("{path}/{i}"), value, other, true, out);
+            }
+        }
+        _ if expected != actual => out.push(mismatch(path, expected, actual)),
+        _ => {}
+    }
+}
+
+fn is_scalars(values: &[Value]) -> bool {
+    values
+        .iter()
+        .all(|value| !value.is_array() && !value.is_object())
+}
+
+fn mismatch(path: &str, expected: &Value, actual: &Value) -> String {
+    let path = if path.is_empty() { "/" } else { path };
+    format!("{path}: {expected} in the compiled type, {actual} in the registry")
+}
+
+fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// Allow `null` in addition to what `schema` accepts.
+fn nullable(mut schema: Value) -> Value {
+    if let Some(object) = schema.as_object_mut() {
+        if object.is_empty() {
+            return schema;
+        }
+        let types = match object.get("type") {
+            Some(Value::String(ty)) => Some(vec![Value::from(ty.as_str())]),
+            Some(Value::Array(types)) => Some(types.clone()),
+            _ => None,
+        };
+        if let Some(mut types) = types {
+            let null = Value::from("null");
+            if !types.contains(&null) {
+                types.push(null.clone());
+            }
+            if let Some(Value::Array(values)) = object.get_mut("enum") {
+                if !values.contains(&Value::Null) {
+                    values.push(Value::Null);
+                }
+            }
+            object.insert("type".to_string(), Value::Array(types));
+            return schema;
+        }
+    }
+    json!({"anyOf": [schema, {"type": "null"}]})
+}
+
+macro_rules! impl_to_json_schema {
+    ($($ty:ty => $schema:tt),* $(,)?) => {
+        $(
+            impl ToJsonSchema for $ty {
+                fn json_schema() -> Value {
+                    json!($schema)
+                }
+            }
+        )*
+    };
+}
+
+impl_to_json_schema! {
+    bool => {"type": "boolean"},
+    i8 => {"type": "integer"},
+    i16 => {"type": "integer"},
+    i32 => {"type": "integer"},
+    i64 => {"type": "integer"},
+    i128 => {"type": "integer"},
+    isize => {"type": "integer"},
+    u8 => {"type": "integer", "minimum": 0},
+    u16 => {"type": "integer", "minimum": 0},
+    u32 => {"type": "integer", "minimum": 0},
+    u64 => {"type": "integer", "minimum": 0},
+    u128 => {"type": "integer", "minimum": 0},
+    usize => {"type": "integer", "minimum": 0},
+    f32 => {"type": "number"},
+    f64 => {"type": "number"},
+    char => {"type": "string", "minLength": 1, "maxLength": 1},
+    str => {"type": "string"},
+    String => {"type": "string"},
+    () => {"type": "null"},
+    Value => {},
+    chrono::NaiveDate => {"type": "string", "format": "date"},
+}
+
+impl<Tz: chrono::TimeZone> ToJsonSchema for chrono::DateTime<Tz> {
+    fn json_schema() -> Value {
+        json!({"type": "string", "format": "date-time"})
+    }
+}
+
+impl<T: ToJsonSchema> ToJsonSchema for Option<T> {
+    fn json_schema() -> Value {
+        nullable(T::json_schema())
+    }
+
+    fn optional() -> bool {
+        true
+    }
+}
+
+macro_rules! impl_to_json_schema_wrapper {
+    ($($wrapper:ident),*) => {
+        $(
+            impl<T: ToJsonSchema + ?Sized> ToJsonSchema for $wrapper<T> {
+                fn json_schema() -> Value {
+                    T::json_schema()
+                }
+
+                fn optional() -> bool {
+                    T::optional()
+                }
+            }
+        )*
+    };
+}
+
+impl_to_json_schema_wrapper!(Box, Rc, Arc);
+
+macro_rules! impl_to_json_schema_seq {
+    ($($seq:ident),*) => {
+        $(
+            impl<T: ToJsonSchema> ToJsonSchema for $seq<T> {
+                fn json_schema() -> Value {
+                    json!({"type": "array", "items": T::json_schema()})
+                }
+            }
+        )*
+    };
+}
+
+impl_to_json_schema_seq!(Vec, VecDeque);
+
+impl<T: ToJsonSchema> ToJsonSchema for [T] {
+    fn json_schema() -> Value {
+        json!({"type": "array", "items": T::json_schema()})
+    }
+}
+
+impl<T: ToJsonSchema, const N: usize> ToJsonSchema for [T; N] {
+    fn json_schema() -> Value {
+        json!({"type": "array", "items": T::json_schema(), "minItems": N, "maxItems": N})
+    }
+}
+
+impl<T: ToJsonSchema, S> ToJsonSchema for HashSet<T, S> {
+    fn json_schema() -> Value {
+        json!({"type": "array", "items": T::json_schema(), "uniqueItems": true})
+    }
+}
+
+impl<T: ToJsonSchema> ToJsonSchema for BTreeSet<T> {
+    fn json_schema() -> Value {
+        json!({"type": "array", "items": T::json_schema(), "uniqueItems": true})
+    }
+}
+
+impl<K, V: ToJsonSchema, S> ToJsonSchema for HashMap<K, V, S> {
+    fn json_schema() -> Value {
+        json!({"type": "object", "additionalProperties": V::json_schema()})
+    }
+}
+
+impl<K, V: ToJsonSchema> ToJsonSchema for BTreeMap<K, V> {
+    fn json_schema() -> Value {
+        json!({"type": "object", "additionalProperties": V::json_schema()})
+    }
+}
+
+/// Support code for the derive macro; not part of the public API.
+#[doc(hidden)]
+pub mod __private {
+    pub use serde_json::Value;
+    use serde_json::{json, Map};
+
+    /// An object schema under construction
+    #[derive(Default)]
+    pub struct Object {
+        properties: Map<String, Value>,
+        required: Vec<Value>,
+    }
+
+    impl Object {
+        #[must_use]
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        #[must_use]
+        pub fn field(
+            mut self,
+            name: &str,
+            mut schema: Value,
+            optional: bool,
+            description: Option<&str>,
+            deprecated: bool,
+        ) -> Self {
+            if let Some(object) = schema.as_object_mut() {
+                if let Some(description) = description {
+                    object.insert("description".to_string(), Value::from(description));
+                }
+                if deprecated {
+                    object.insert("deprecated".to_string(), Value::Bool(true));
+                }
+            }
+            self.properties.insert(name.to_string(), schema);
+            if !optional {
+                self.required.push(Value::from(name));
+            }
+            self
+        }
+
+        #[must_use]
+        pub fn finish(self, description: Option<&str>, deny_unknown_fields: bool) -> Value {
+            let mut schema = json!({"type": "object", "properties": self.properties});
+            if !self.required.is_empty() {
+                schema["required"] = Value::Array(self.required);
+            }
+            if deny_unknown_fields {
+                schema["additionalProperties"] = Value::Bool(false);
+            }
+            if let Some(description) = description {
+                schema["description"] = Value::from(description);
+            }
+            schema
+        }
+    }
+
+    #[must_use]
+    pub fn string_enum(values: &[&str], description: Option<&str>) -> Value {
+        let mut schema = json!({"type": "string", "enum": values});
+        if let Some(description) = description {
+            schema["description"] = Value::from(description);
+        }
+        schema
+    }
+
+    #[must_use]
+    pub fn null() -> Value {
+        json!({"type": "null"})
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockSchemaRegistryClient;
+    use llm_schema_registry_sdk_derive::RegistrySchema;
+    use serde::Serialize;
+
+    #[derive(Serialize, RegistrySchema)]
+    #[serde(rename_all = "snake_case")]
+    #[allow(dead_code)]
+    enum Provider {
+        OpenAi,
+        Anthropic,
+        #[serde(rename = "self-hosted")]
+        SelfHosted,
+    }
+
+    #[derive(Serialize, RegistrySchema)]
+    #[allow(dead_code)]
+    struct Usage {
+        prompt_tokens: u32,
+        completion_tokens: u32,
+    }
+
+    /// One model invocation
+    #[derive(Serialize, RegistrySchema)]
+    #[registry(namespace = "telemetry", version = "1.0.0")]
+    #[serde(rename_all = "camelCase", deny_unknown_fields)]
+    #[allow(dead_code)]
+    struct InferenceEvent {
+        /// Model that served the request
+        model_id: String,
+        provider: Provider,
+        latency_ms: Option<u64>,
+        usage: Usage,
+        #[serde(rename = "tags", default)]
+        labels: Vec<String>,
+        #[serde(skip)]
+        cached: bool,
+    }
+
+    #[derive(Serialize, RegistrySchema)]
+    #[registry(namespace = "telemetry", name = "InferenceEvent", version = "1.0.0")]
+    #[allow(dead_code)]
+    struct OldInferenceEvent {
+        model_id: String,
+        latency_ms: i64,
+    }
+
+    #[test]
+    fn test_derived_schema_follows_serde() {
+        let schema = InferenceEvent::content();
+
+        assert_eq!(InferenceEvent::NAME, "InferenceEvent");
+        assert_eq!(schema["title"], "InferenceEvent");
+        assert_eq!(schema["description"], "One model invocation");
+        assert_eq!(schema["additionalProperties"], false);
+        assert_eq!(schema["required"], json!(["modelId", "provider", "usage"]));
+        assert_eq!(
+            schema["properties"]["modelId"],
+            json!({"type": "string", "description": "Model that served the request"})
+        );
+        assert_eq!(
+            schema["properties"]["provider"]["enum"],
+            json!(["open_ai", "anthropic", "self-hosted"])
+        );
+        assert_eq!(
+            schema["properties"]["latencyMs"]["type"],
+            json!(["integer", "null"])
+        );
+        assert_eq!(
+            schema["properties"]["usage"]["required"],
+            json!(["prompt_tokens", "completion_tokens"])
+        );
+        assert_eq!(schema["properties"]["tags"]["items"]["type"], "string");
+        assert!(schema["properties"].get("cached").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_register_and_verify_fail_fast_on_drift() {
+        let mock = MockSchemaRegistryClient::new();
+        assert!(matches!(
+            verify::<InferenceEvent>(&mock).await,
+            Err(SchemaRegistryError::SchemaNotFound(_))
+        ));
+
+        assert!(register::<InferenceEvent>(&mock).await.unwrap().created);
+        verify::<InferenceEvent>(&mock).await.unwrap();
+        assert!(!register::<InferenceEvent>(&mock).await.unwrap().created);
+
+        // Same subject and version, different types
+        match register::<OldInferenceEvent>(&mock).await {
+            Err(SchemaRegistryError::SchemaDrift {
+                subject,
+                differences,
+            }) => {
+                assert_eq!(subject, "telemetry.InferenceEvent@1.0.0");
+                assert!(
+                    differences.contains(&"/properties/provider: only in the registry".to_string())
+                );
+                assert!(differences
+                    .contains(&"/properties/latencyMs: only in the registry".to_string()));
+                assert!(differences
+                    .contains(&"/properties/latency_ms: only in the compiled type".to_string()));
+            }
+            other => panic!("expected drift, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_differences_ignore_order_and_annotations() {
+        let expected = json!({
+            "title": "A",
+            "type": "object",
+            "properties": {
+                "description": {"type": "string", "description": "Free text"},
+                "kind": {"enum": ["a", "b"]}
+            },
+            "required": ["description", "kind"]
+        });
+        let reordered = json!({
+            "required": ["kind", "description"],
+            "properties": {
+                "kind": {"enum": ["b", "a"]},
+                "description": {"type": "string"}
+            },
+            "type": "object"
+        });
+        assert!(differences(&expected, &reordered).is_empty());
+
+        let changed = json!({
+            "type": "object",
+            "properties": {"kind": {"enum": ["a", "b"]}},
+            "required": ["kind"]
+        });
+        assert_eq!(
+            differences(&expected, &changed),
+            vec![
+                "/properties/description: only in the compiled type".to_string(),
+                r#"/required: ["description","kind"] in the compiled type, ["kind"] in the registry"#
+                    .to_string(),
+            ]
+        );
+    }
+}
@@ -2,11 +2,20 @@
 //!
 //! This module provides a zero-cost abstraction over moka's async cache with TTL support
 //! and automatic eviction. The cache is thread-safe and optimized for concurrent access.
-
-use crate::models::GetSchemaResponse;
+//!
+//! With a [`ChangeFeedConfig`], entries don't expire after a TTL. Instead the
+//! client follows the registry's change feed and invalidates exactly the
+//! schemas that changed, see
+//! [`SchemaRegistryClient::spawn_cache_sync`](crate::SchemaRegistryClient::spawn_cache_sync).
+//! An entry is only as fresh as the last successful sync, so while the feed
+//! can't be read entries grow stale, and are refetched once they are staler
+//! than [`ChangeFeedConfig::max_staleness`].
+
+use crate::models::{GetSchemaResponse, SchemaChange};
 use moka::future::Cache;
-use std::sync::Arc;
-use std::time::Duration;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 /// Default cache TTL (5 minutes)
 const DEFAULT_TTL_SECS: u64 = 300;
@@ -14,13 +23,24 @@ const DEFAULT_TTL_SECS: u64 = 300;
 /// Default maximum cache entries
 const DEFAULT_MAX_CAPACITY: u64 = 1000;
 
+/// Default change feed poll interval (5 seconds)
+const DEFAULT_POLL_INTERVAL_SECS: u64 = 5;
+
+/// Default number of changes requested per page
+const DEFAULT_CHANGES_PAGE_SIZE: u32 = 500;
+
+/// Default staleness after which an entry is refetched (5 minutes)
+const DEFAULT_MAX_STALENESS_SECS: u64 = 300;
+
 /// Configuration for the schema cache.
 #[derive(Debug, Clone)]
 pub struct CacheConfig {
-    /// Time-to-live for cache entries
+    /// Time-to-live for cache entries, unused when following the change feed
     pub ttl: Duration,
     /// Maximum number of entries
     pub max_capacity: u64,
+    /// Keep entries until the change feed reports them changed
+    pub change_feed: Option<ChangeFeedConfig>,
 }
 
 impl Default for CacheConfig {
@@ -28,6 +48,7 @@ impl Default for CacheConfig {
         Self {
             ttl: Duration::from_secs(DEFAULT_TTL_SECS),
             max_capacity: DEFAULT_MAX_CAPACITY,
+            change_feed: None,
         }
     }
 }
@@ -38,6 +59,7 @@ impl CacheConfig {
         Self {
             ttl: Duration::from_secs(ttl_secs),
             max_capacity,
+            change_feed: None,
         }
     }
 
@@ -52,6 +74,73 @@ impl CacheConfig {
         self.max_capacity = max_capacity;
         self
     }
+
+    /// Invalidates entries from the change feed instead of expiring them.
+    #[must_use]
+    pub fn with_change_feed(mut self, change_feed: ChangeFeedConfig) -> Self {
+        self.change_feed = Some(change_feed);
+        self
+    }
+}
+
+/// How the cache follows the registry's change feed.
+#[derive(Debug, Clone)]
+pub struct ChangeFeedConfig {
+    /// Time between reads of the feed
+    pub poll_interval: Duration,
+    /// Changes requested per page
+    pub page_size: u32,
+    /// Entries staler than this are refetched instead of served
+    pub max_staleness: Duration,
+}
+
+impl Default for ChangeFeedConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_secs(DEFAULT_POLL_INTERVAL_SECS),
+            page_size: DEFAULT_CHANGES_PAGE_SIZE,
+            max_staleness: Duration::from_secs(DEFAULT_MAX_STALENESS_SECS),
+        }
+    }
+}
+
+impl ChangeFeedConfig {
+    /// Sets the poll interval.
+    #[must_use]
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Sets the staleness after which entries are refetched.
+    #[must_use]
+    pub fn with_max_staleness(mut self, max_staleness: Duration) -> Self {
+        self.max_staleness = max_staleness;
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    response: GetSchemaResponse,
+    /// When the request that fetched the response was sent
+    fetched_at: Instant,
+}
+
+/// Where the cache is in the change feed
+#[derive(Debug, Default)]
+struct FeedPosition {
+    cursor: Option<i64>,
+    /// When the last successful sync started reading the feed
+    synced_at: Option<Instant>,
+}
+
+/// Taken before fetching a schema, so a fetch that raced with an
+/// invalidation isn't cached.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct FetchTicket {
+    generation: u64,
+    started: Instant,
 }
 
 /// Thread-safe async cache for schema responses.
@@ -60,7 +149,11 @@ impl CacheConfig {
 /// It uses zero-cost abstractions and is optimized for high-throughput scenarios.
 #[derive(Clone)]
 pub struct SchemaCache {
-    cache: Arc<Cache<String, GetSchemaResponse>>,
+    cache: Arc<Cache<String, Entry>>,
+    /// Bumped by every invalidation
+    generation: Arc<AtomicU64>,
+    feed: Arc<Mutex<FeedPosition>>,
+    max_staleness: Option<Duration>,
 }
 
 impl SchemaCache {
@@ -79,13 +172,16 @@ impl SchemaCache {
     /// let cache = SchemaCache::new(config);
     /// ```
     pub fn new(config: CacheConfig) -> Self {
-        let cache = Cache::builder()
-            .max_capacity(config.max_capacity)
-            .time_to_live(config.ttl)
-            .build();
+        let mut builder = Cache::builder().max_capacity(config.max_capacity);
+        if config.change_feed.is_none() {
+            builder = builder.time_to_live(config.ttl);
+        }
 
         Self {
-            cache: Arc::new(cache),
+            cache: Arc::new(builder.build()),
+            generation: Arc::new(AtomicU64::new(0)),
+            feed: Arc::new(Mutex::new(FeedPosition::default())),
+            max_staleness: config.change_feed.map(|feed| feed.max_staleness),
         }
     }
 
@@ -96,7 +192,8 @@ impl SchemaCache {
 
     /// Gets a schema from the cache.
     ///
-    /// Returns `None` if the schema is not in the cache or has expired.
+    /// Returns `None` if the schema is not in the cache, has expired, or is
+    /// staler than the change feed's `max_staleness`.
     ///
     /// # Examples
     ///
@@ -110,7 +207,11 @@ impl SchemaCache {
     /// # }
     /// ```
     pub async fn get(&self, key: &str) -> Option<GetSchemaResponse> {
-        self.cache.get(key).await
+        let entry = self.cache.get(key).await?;
+        match self.max_staleness {
+            Some(max) if self.staleness_of(&entry) > max => None,
+            _ => Some(entry.response),
+        }
     }
 
     /// Inserts a schema into the cache.
@@ -125,6 +226,7 @@ impl SchemaCache {
     /// # let response = GetSchemaResponse {
     /// #     metadata: SchemaMetadata {
     /// #         schema_id: "123".to_string(),
+    /// #         global_id: None,
     /// #         namespace: "test".to_string(),
     /// #         name: "Schema".to_string(),
     /// #         version: "1.0.0".to_string(),
@@ -139,7 +241,38 @@ impl SchemaCache {
     /// # }
     /// ```
     pub async fn insert(&self, key: impl Into<String>, value: GetSchemaResponse) {
-        self.cache.insert(key.into(), value).await;
+        let entry = Entry {
+            response: value,
+            fetched_at: Instant::now(),
+        };
+        self.cache.insert(key.into(), entry).await;
+    }
+
+    pub(crate) fn fetch_ticket(&self) -> FetchTicket {
+        FetchTicket {
+            generation: self.generation.load(Ordering::SeqCst),
+            started: Instant::now(),
+        }
+    }
+
+    /// Inserts a schema fetched with `ticket`, unless something was
+    /// invalidated since, in which case the response may predate the change.
+    pub(crate) async fn insert_fetched(
+        &self,
+        key: &str,
+        value: GetSchemaResponse,
+        ticket: FetchTicket,
+    ) {
+        let entry = Entry {
+            response: value,
+            fetched_at: ticket.started,
+        };
+        self.cache.insert(key.to_string(), entry).await;
+        // Invalidations bump the generation first, so either this sees the
+        // bump or the invalidation runs after the insert
+        if self.generation.load(Ordering::SeqCst) != ticket.generation {
+            self.cache.invalidate(key).await;
+        }
     }
 
     /// Invalidates (removes) a schema from the cache.
@@ -154,6 +287,7 @@ impl SchemaCache {
     /// # }
     /// ```
     pub async fn invalidate(&self, key: &str) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
         self.cache.invalidate(key).await;
     }
 
@@ -169,9 +303,62 @@ impl SchemaCache {
     /// # }
     /// ```
     pub async fn invalidate_all(&self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
         self.cache.invalidate_all();
     }
 
+    /// How long ago a cached schema was last known to be current.
+    ///
+    /// That's when it was fetched, or when the last successful change feed
+    /// sync started if that's later. Returns `None` if the schema isn't cached.
+    pub async fn staleness(&self, key: &str) -> Option<Duration> {
+        let entry = self.cache.get(key).await?;
+        Some(self.staleness_of(&entry))
+    }
+
+    /// Staleness of every cached schema, e.g. to export as a gauge per entry.
+    #[must_use]
+    pub fn staleness_by_key(&self) -> Vec<(String, Duration)> {
+        self.cache
+            .iter()
+            .map(|(key, entry)| (key.to_string(), self.staleness_of(&entry)))
+            .collect()
+    }
+
+    fn staleness_of(&self, entry: &Entry) -> Duration {
+        let synced_at = self.feed.lock().unwrap().synced_at;
+        let current_at = synced_at.map_or(entry.fetched_at, |synced| synced.max(entry.fetched_at));
+        current_at.elapsed()
+    }
+
+    /// Cursor of the last change applied, `None` until the feed is followed
+    pub(crate) fn feed_cursor(&self) -> Option<i64> {
+        self.feed.lock().unwrap().cursor
+    }
+
+    /// Starts following the feed at `cursor`
+    ///
+    /// Entries cached before then may have missed changes, so they're dropped.
+    pub(crate) async fn follow_feed_from(&self, cursor: i64, started: Instant) {
+        self.invalidate_all().await;
+        let mut feed = self.feed.lock().unwrap();
+        feed.cursor = Some(cursor);
+        feed.synced_at = Some(started);
+    }
+
+    /// Invalidates the changed schemas and advances the cursor past them
+    pub(crate) async fn apply_changes(&self, changes: &[SchemaChange], cursor: i64) {
+        for change in changes {
+            self.invalidate(&change.schema_id).await;
+        }
+        self.feed.lock().unwrap().cursor = Some(cursor);
+    }
+
+    /// Records a sync that read the feed to its end, starting at `started`
+    pub(crate) fn mark_synced(&self, started: Instant) {
+        self.feed.lock().unwrap().synced_at = Some(started);
+    }
+
     /// Returns the current number of entries in the cache.
     pub fn entry_count(&self) -> u64 {
         self.cache.entry_count()
@@ -211,6 +398,7 @@ mod tests {
         GetSchemaResponse {
             metadata: SchemaMetadata {
                 schema_id: id.to_string(),
+                global_id: None,
                 namespace: "test".to_string(),
                 name: "TestSchema".to_string(),
                 version: "1.0.0".to_string(),
@@ -315,6 +503,49 @@ mod tests {
         assert!(cache.entry_count() <= 2);
     }
 
+    #[tokio::test]
+    async fn test_followed_cache_refetches_entries_past_max_staleness() {
+        let feed = ChangeFeedConfig::default().with_max_staleness(Duration::from_millis(100));
+        let cache = SchemaCache::new(CacheConfig::new(1, 100).with_change_feed(feed));
+
+        cache.insert("fresh", create_test_response("fresh")).await;
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        cache.run_pending_tasks().await;
+        assert!(
+            cache.get("fresh").await.is_none(),
+            "stale entries are not served"
+        );
+        assert!(cache.staleness("fresh").await.unwrap() >= Duration::from_millis(150));
+
+        // A sync vouches for every entry as of when it started
+        cache.follow_feed_from(7, Instant::now()).await;
+        cache.insert("synced", create_test_response("synced")).await;
+        cache.mark_synced(Instant::now());
+        assert!(cache.get("synced").await.is_some());
+        assert!(cache
+            .staleness_by_key()
+            .iter()
+            .all(|(_, staleness)| *staleness < Duration::from_millis(100)));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_racing_an_invalidation_is_not_cached() {
+        let cache = SchemaCache::with_defaults();
+
+        let ticket = cache.fetch_ticket();
+        cache.invalidate("schema-1").await;
+        cache
+            .insert_fetched("schema-1", create_test_response("schema-1"), ticket)
+            .await;
+        assert!(cache.get("schema-1").await.is_none());
+
+        let ticket = cache.fetch_ticket();
+        cache
+            .insert_fetched("schema-1", create_test_response("schema-1"), ticket)
+            .await;
+        assert!(cache.get("schema-1").await.is_some());
+    }
+
     #[test]
     fn test_cache_config_builder() {
         let config = CacheConfig::default()
@@ -4,15 +4,27 @@
 //! Schema Registry API. The client uses tokio for async operations and reqwest for
 //! HTTP communication, providing zero-cost abstractions and high performance.
 
-use crate::cache::{CacheConfig, SchemaCache};
+use crate::cache::{CacheConfig, ChangeFeedConfig, SchemaCache};
 use crate::errors::{Result, SchemaRegistryError};
+use crate::middleware::{insert_header, ClientMiddleware, RequestHeaders, TokenSource};
 use crate::models::*;
+use crate::search::MAX_PAGE_SIZE;
+use futures::stream::{self, Stream, TryStreamExt};
 use reqwest::{Client, StatusCode};
-use std::time::Duration;
+use schema_registry_core::retry::{ErrorClass, RetryPolicy, RetryRule};
+use serde::Deserialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
 use tokio::time::sleep;
 use tracing::{debug, info, warn};
 use url::Url;
 
+/// SDK name and version sent in the `X-SDK-Version` header, so the registry
+/// can report which SDK releases are in use
+const SDK_VERSION: &str = concat!("rust/", env!("CARGO_PKG_VERSION"));
+
 /// Default timeout for HTTP requests (30 seconds)
 const DEFAULT_TIMEOUT_SECS: u64 = 30;
 
@@ -22,6 +34,15 @@ const DEFAULT_MAX_RETRIES: u32 = 3;
 /// Default initial retry delay (500ms)
 const DEFAULT_INITIAL_RETRY_DELAY_MS: u64 = 500;
 
+/// Upper bound for the retry delay (30 seconds)
+const MAX_RETRY_DELAY_SECS: u64 = 30;
+
+/// Versions requested per page when listing a schema's versions
+const VERSIONS_PAGE_SIZE: u32 = 1000;
+
+/// Longest the registry is asked to hold one wait request (60 seconds)
+pub const MAX_WAIT_POLL: Duration = Duration::from_secs(60);
+
 /// Configuration for the Schema Registry client.
 #[derive(Debug, Clone)]
 pub struct ClientConfig {
@@ -31,12 +52,14 @@ pub struct ClientConfig {
     pub api_key: Option<String>,
     /// Request timeout
     pub timeout: Duration,
-    /// Maximum number of retry attempts
+    /// Maximum number of attempts, including the first
     pub max_retries: u32,
-    /// Initial retry delay (exponential backoff)
+    /// Initial retry delay (jittered exponential backoff)
     pub initial_retry_delay: Duration,
     /// Cache configuration
     pub cache_config: CacheConfig,
+    /// Headers sent with every request, e.g. a fixed tenant id
+    pub default_headers: Vec<(String, String)>,
 }
 
 impl ClientConfig {
@@ -57,6 +80,7 @@ impl ClientConfig {
             max_retries: DEFAULT_MAX_RETRIES,
             initial_retry_delay: Duration::from_millis(DEFAULT_INITIAL_RETRY_DELAY_MS),
             cache_config: CacheConfig::default(),
+            default_headers: Vec::new(),
         }
     }
 
@@ -89,6 +113,30 @@ impl ClientConfig {
         self.cache_config = cache_config;
         self
     }
+
+    /// Adds a header sent with every request.
+    #[must_use]
+    pub fn with_default_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.default_headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Backoff between attempts of a failed request
+    fn retry_rule(&self) -> RetryRule {
+        RetryRule::backoff(
+            self.max_retries,
+            self.initial_retry_delay,
+            Duration::from_secs(MAX_RETRY_DELAY_SECS),
+        )
+    }
+
+    /// The registry's shared retry policy, applied to transient failures and
+    /// rate limiting
+    fn retry_policy(&self) -> RetryPolicy {
+        RetryPolicy::new("sdk_request")
+            .with_rule(ErrorClass::Transient, self.retry_rule())
+            .with_rule(ErrorClass::RateLimited, self.retry_rule())
+    }
 }
 
 /// The main Schema Registry client.
@@ -127,6 +175,9 @@ pub struct SchemaRegistryClient {
     config: ClientConfig,
     http_client: Client,
     cache: SchemaCache,
+    retry_policy: RetryPolicy,
+    token_source: Option<Arc<dyn TokenSource>>,
+    middleware: Vec<Arc<dyn ClientMiddleware>>,
 }
 
 impl SchemaRegistryClient {
@@ -147,11 +198,15 @@ impl SchemaRegistryClient {
             .map_err(|e| SchemaRegistryError::ConfigError(format!("Failed to build HTTP client: {}", e)))?;
 
         let cache = SchemaCache::new(config.cache_config.clone());
+        let retry_policy = config.retry_policy();
 
         Ok(Self {
             config,
             http_client,
             cache,
+            retry_policy,
+            token_source: None,
+            middleware: Vec::new(),
         })
     }
 
@@ -185,9 +240,7 @@ impl SchemaRegistryClient {
 
         let response = self
             .retry_request(|| async {
-                self.add_auth_header(self.http_client.post(&url).json(&schema))
-                    .send()
-                    .await
+                self.send(self.http_client.post(&url).json(&schema)).await
             })
             .await?;
 
@@ -222,19 +275,20 @@ impl SchemaRegistryClient {
         debug!("Cache miss for schema ID: {}", schema_id);
 
         let url = self.build_url(&format!("/api/v1/schemas/{}", schema_id))?;
+        let ticket = self.cache.fetch_ticket();
 
         let response = self
             .retry_request(|| async {
-                self.add_auth_header(self.http_client.get(&url))
-                    .send()
-                    .await
+                self.send(self.http_client.get(&url)).await
             })
             .await?;
 
         let result: GetSchemaResponse = response.json().await?;
 
         // Cache the result
-        self.cache.insert(schema_id, result.clone()).await;
+        self.cache
+            .insert_fetched(schema_id, result.clone(), ticket)
+            .await;
 
         Ok(result)
     }
@@ -261,19 +315,56 @@ impl SchemaRegistryClient {
             "/api/v1/schemas/{}/{}/versions/{}",
             namespace, name, version
         ))?;
+        let ticket = self.cache.fetch_ticket();
 
         let response = self
             .retry_request(|| async {
-                self.add_auth_header(self.http_client.get(&url))
-                    .send()
-                    .await
+                self.send(self.http_client.get(&url)).await
             })
             .await?;
 
         let result: GetSchemaResponse = response.json().await?;
 
         // Cache the result by schema_id
-        self.cache.insert(&result.metadata.schema_id, result.clone()).await;
+        self.cache
+            .insert_fetched(&result.metadata.schema_id, result.clone(), ticket)
+            .await;
+
+        Ok(result)
+    }
+
+    /// Retrieves a schema by the integer ID Kafka serializers embed in messages.
+    ///
+    /// Messages in the standard wire format start with a zero magic byte and
+    /// the schema's 4-byte big-endian global ID.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use llm_schema_registry_sdk::SchemaRegistryClient;
+    /// # async fn example(client: SchemaRegistryClient, message: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+    /// let global_id = i32::from_be_bytes(message[1..5].try_into()?);
+    /// let schema = client.get_schema_by_global_id(global_id.into()).await?;
+    /// println!("Message written with: {}", schema.metadata.schema_id);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_schema_by_global_id(&self, global_id: i64) -> Result<GetSchemaResponse> {
+        let url = self.build_url(&format!("/api/v1/schemas/ids/{global_id}"))?;
+        let ticket = self.cache.fetch_ticket();
+
+        let response = self
+            .retry_request(|| async {
+                self.send(self.http_client.get(&url)).await
+            })
+            .await?;
+
+        let result: GetSchemaResponse = response.json().await?;
+
+        // Cache the result by schema_id
+        self.cache
+            .insert_fetched(&result.metadata.schema_id, result.clone(), ticket)
+            .await;
 
         Ok(result)
     }
@@ -305,9 +396,7 @@ impl SchemaRegistryClient {
 
         let response = self
             .retry_request(|| async {
-                self.add_auth_header(self.http_client.post(&url).json(&payload))
-                    .send()
-                    .await
+                self.send(self.http_client.post(&url).json(&payload)).await
             })
             .await?;
 
@@ -352,9 +441,7 @@ impl SchemaRegistryClient {
 
         let response = self
             .retry_request(|| async {
-                self.add_auth_header(self.http_client.post(&url).json(&request))
-                    .send()
-                    .await
+                self.send(self.http_client.post(&url).json(&request)).await
             })
             .await?;
 
@@ -365,6 +452,9 @@ impl SchemaRegistryClient {
 
     /// Lists all versions of a schema.
     ///
+    /// The registry returns versions a page at a time; this follows the
+    /// pages and returns them all.
+    ///
     /// # Examples
     ///
     /// ```no_run
@@ -378,19 +468,159 @@ impl SchemaRegistryClient {
     /// # }
     /// ```
     pub async fn list_versions(&self, namespace: &str, name: &str) -> Result<ListVersionsResponse> {
-        let url = self.build_url(&format!("/api/v1/schemas/{}/{}/versions", namespace, name))?;
+        let base = self.build_url(&format!("/api/v1/schemas/{namespace}/{name}/versions"))?;
+        let mut versions = Vec::new();
+
+        loop {
+            let mut url = Url::parse(&base)?;
+            url.query_pairs_mut()
+                .append_pair("limit", &VERSIONS_PAGE_SIZE.to_string())
+                .append_pair("offset", &versions.len().to_string());
+            let url = url.to_string();
+
+            let response = self
+                .retry_request(|| async {
+                    self.send(self.http_client.get(&url)).await
+                })
+                .await?;
+
+            let page: VersionsPage = response.json().await?;
+            let received = page.versions.len();
+            versions.extend(page.versions);
+            // Registries without paging send every version and no total
+            let done = page
+                .total
+                .map_or(true, |total| received == 0 || versions.len() as u64 >= total);
+            if done {
+                return Ok(ListVersionsResponse {
+                    namespace: page.namespace,
+                    name: page.name,
+                    versions,
+                });
+            }
+        }
+    }
+
+    /// Gets one version of a subject and the state it is in.
+    pub async fn get_subject_version(
+        &self,
+        subject: &str,
+        version: &str,
+    ) -> Result<SubjectVersionStatus> {
+        let url = self.build_url(&format!("/api/v1/subjects/{subject}/versions/{version}"))?;
 
         let response = self
             .retry_request(|| async {
-                self.add_auth_header(self.http_client.get(&url))
-                    .send()
-                    .await
+                self.send(self.http_client.get(&url)).await
             })
             .await?;
 
-        let result: ListVersionsResponse = response.json().await?;
+        Ok(response.json().await?)
+    }
 
-        Ok(result)
+    /// Waits for a version of a subject to become `ACTIVE`.
+    ///
+    /// See [`wait_for_state`](Self::wait_for_state).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use llm_schema_registry_sdk::SchemaRegistryClient;
+    /// # use std::time::Duration;
+    /// # async fn example(client: SchemaRegistryClient) -> Result<(), Box<dyn std::error::Error>> {
+    /// client
+    ///     .wait_for_active("telemetry.InferenceEvent", "1.1.0", Duration::from_secs(300))
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn wait_for_active(
+        &self,
+        subject: &str,
+        version: &str,
+        timeout: Duration,
+    ) -> Result<SubjectVersionStatus> {
+        self.wait_for_state(subject, version, SchemaState::Active, timeout)
+            .await
+    }
+
+    /// Waits for a version of a subject to reach `state`.
+    ///
+    /// The registry holds each request until the version changes, so this
+    /// makes one request per [`MAX_WAIT_POLL`] rather than polling. Failed
+    /// requests are retried with exponential backoff until `timeout`.
+    ///
+    /// Fails with [`SchemaRegistryError::TimeoutError`] once `timeout` passes
+    /// and with [`SchemaRegistryError::StateUnreachable`] if the version is
+    /// archived or abandoned instead.
+    pub async fn wait_for_state(
+        &self,
+        subject: &str,
+        version: &str,
+        state: SchemaState,
+        timeout: Duration,
+    ) -> Result<SubjectVersionStatus> {
+        let wanted = serde_json::to_value(state)?
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+        let path = format!("/api/v1/subjects/{subject}/versions/{version}");
+        let deadline = Instant::now() + timeout;
+        let backoff = RetryRule::backoff(u32::MAX, self.config.initial_retry_delay, MAX_WAIT_POLL);
+        let mut retry = 1;
+
+        loop {
+            let poll = deadline
+                .saturating_duration_since(Instant::now())
+                .min(MAX_WAIT_POLL);
+            let mut url = Url::parse(&self.build_url(&path)?)?;
+            url.query_pairs_mut()
+                .append_pair("wait_for_state", &wanted)
+                .append_pair("timeout", &format!("{}ms", poll.as_millis()));
+            // The registry answers after `poll`; give the answer time to arrive
+            let request = self
+                .http_client
+                .get(url.as_str())
+                .timeout(poll + self.config.timeout);
+
+            let result = match self.send(request).await {
+                Ok(response) if response.status().is_success() => {
+                    response.json::<SubjectVersionStatus>().await.map_err(Into::into)
+                }
+                Ok(response) => Err(self.handle_error_response(response).await),
+                Err(error) => Err(error),
+            };
+            match result {
+                Ok(status) if status.reached == Some(true) => return Ok(status),
+                Ok(status) if status.is_terminal() => {
+                    return Err(SchemaRegistryError::StateUnreachable {
+                        subject: format!("{}@{}", status.subject, status.version),
+                        state: status.state,
+                        wanted,
+                    });
+                }
+                Ok(status) => {
+                    debug!("{subject}@{version} is still {}", status.state);
+                    retry = 1;
+                }
+                Err(error) if error.is_retryable() => {
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    let delay = backoff.delay(retry).unwrap_or(MAX_WAIT_POLL);
+                    warn!(
+                        "Waiting for {subject}@{version} failed: {error}. Retrying in {delay:?}..."
+                    );
+                    sleep(delay.min(remaining)).await;
+                    retry += 1;
+                }
+                Err(error) => return Err(error),
+            }
+
+            if Instant::now() >= deadline {
+                return Err(SchemaRegistryError::TimeoutError(format!(
+                    "{subject}@{version} did not become {wanted} within {timeout:?}"
+                )));
+            }
+        }
     }
 
     /// Searches for schemas matching a query.
@@ -414,9 +644,7 @@ impl SchemaRegistryClient {
 
         let response = self
             .retry_request(|| async {
-                self.add_auth_header(self.http_client.post(&url).json(&query))
-                    .send()
-                    .await
+                self.send(self.http_client.post(&url).json(&query)).await
             })
             .await?;
 
@@ -425,6 +653,53 @@ impl SchemaRegistryClient {
         Ok(result)
     }
 
+    /// Streams every schema matching a query, fetching pages as needed.
+    ///
+    /// Pages hold `query.limit` results, or [`MAX_PAGE_SIZE`] when unset, and
+    /// start at `query.offset`. The stream ends after the last page or the
+    /// first error.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use llm_schema_registry_sdk::{SchemaRegistryClient, SchemaState, SearchQuery};
+    /// use futures::TryStreamExt;
+    ///
+    /// # async fn example(client: SchemaRegistryClient) -> Result<(), Box<dyn std::error::Error>> {
+    /// let query = SearchQuery::builder()
+    ///     .namespace_glob("telemetry.*")
+    ///     .state(SchemaState::Deprecated)
+    ///     .build();
+    ///
+    /// let mut results = std::pin::pin!(client.search_all(query));
+    /// while let Some(result) = results.try_next().await? {
+    ///     println!("{}.{}", result.metadata.namespace, result.metadata.name);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn search_all(&self, query: SearchQuery) -> impl Stream<Item = Result<SearchResult>> + '_ {
+        let page_size = query.limit.unwrap_or(MAX_PAGE_SIZE);
+        let offset = query.offset.unwrap_or(0);
+
+        stream::try_unfold(Some((query, offset)), move |next| async move {
+            let Some((mut query, offset)) = next else {
+                return Ok::<_, SchemaRegistryError>(None);
+            };
+            query.limit = Some(page_size);
+            query.offset = Some(offset);
+
+            let page = self.search_schemas(query.clone()).await?;
+            let fetched = u32::try_from(page.results.len()).unwrap_or(u32::MAX);
+            let next_offset = offset.saturating_add(fetched);
+            debug!("Fetched {} search results at offset {}", fetched, offset);
+
+            let next = (fetched > 0 && next_offset < page.total).then_some((query, next_offset));
+            Ok(Some((stream::iter(page.results.into_iter().map(Ok)), next)))
+        })
+        .try_flatten()
+    }
+
     /// Deletes a schema by ID.
     ///
     /// # Examples
@@ -441,9 +716,7 @@ impl SchemaRegistryClient {
         let url = self.build_url(&format!("/api/v1/schemas/{}", schema_id))?;
 
         self.retry_request(|| async {
-            self.add_auth_header(self.http_client.delete(&url))
-                .send()
-                .await
+            self.send(self.http_client.delete(&url)).await
         })
         .await?;
 
@@ -470,7 +743,7 @@ impl SchemaRegistryClient {
     pub async fn health_check(&self) -> Result<HealthCheckResponse> {
         let url = self.build_url("/health")?;
 
-        let response = self.add_auth_header(self.http_client.get(&url)).send().await?;
+        let response = self.send(self.http_client.get(&url)).await?;
 
         let result: HealthCheckResponse = response.json().await?;
 
@@ -482,6 +755,132 @@ impl SchemaRegistryClient {
         self.cache.invalidate_all().await;
     }
 
+    /// How long ago each cached schema was last known to be current, by
+    /// schema ID.
+    #[must_use]
+    pub fn cache_staleness(&self) -> Vec<(String, Duration)> {
+        self.cache.staleness_by_key()
+    }
+
+    /// Reads one page of the registry's change feed.
+    ///
+    /// Without `after`, no changes are returned, only the cursor of the
+    /// latest one to follow the feed from.
+    pub async fn list_changes(&self, after: Option<i64>, limit: u32) -> Result<ChangesResponse> {
+        let mut url = Url::parse(&self.build_url("/api/v1/changes")?)?;
+        if let Some(after) = after {
+            url.query_pairs_mut()
+                .append_pair("after", &after.to_string())
+                .append_pair("limit", &limit.to_string());
+        }
+        let url = url.to_string();
+
+        let response = self
+            .retry_request(|| async {
+                self.send(self.http_client.get(&url)).await
+            })
+            .await?;
+
+        Ok(response.json().await?)
+    }
+
+    /// Invalidates the cached schemas that changed since the last sync.
+    ///
+    /// The first sync only starts following the feed, dropping whatever was
+    /// cached before. Returns the number of changes applied.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use llm_schema_registry_sdk::SchemaRegistryClient;
+    /// # async fn example(client: SchemaRegistryClient) -> Result<(), Box<dyn std::error::Error>> {
+    /// let applied = client.sync_cache().await?;
+    /// println!("{} schemas changed", applied);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn sync_cache(&self) -> Result<usize> {
+        let page_size = self
+            .config
+            .cache_config
+            .change_feed
+            .as_ref()
+            .map_or(ChangeFeedConfig::default().page_size, |feed| feed.page_size);
+        let started = Instant::now();
+
+        let Some(mut cursor) = self.cache.feed_cursor() else {
+            let latest = self.list_changes(None, page_size).await?;
+            self.cache.follow_feed_from(latest.cursor, started).await;
+            debug!("Following the change feed from {}", latest.cursor);
+            return Ok(0);
+        };
+
+        let mut applied = 0;
+        loop {
+            let page = self.list_changes(Some(cursor), page_size).await?;
+            self.cache.apply_changes(&page.changes, page.cursor).await;
+            applied += page.changes.len();
+            cursor = page.cursor;
+            if !page.has_more {
+                break;
+            }
+        }
+        self.cache.mark_synced(started);
+        debug!("Applied {} changes from the change feed", applied);
+
+        Ok(applied)
+    }
+
+    /// Syncs the cache with the change feed every
+    /// [`poll_interval`](ChangeFeedConfig::poll_interval) until the returned
+    /// task is aborted.
+    ///
+    /// Requires a cache configured [`with_change_feed`](CacheConfig::with_change_feed).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use llm_schema_registry_sdk::{cache::ChangeFeedConfig, CacheConfig, SchemaRegistryClient};
+    /// # use std::sync::Arc;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Arc::new(
+    ///     SchemaRegistryClient::builder()
+    ///         .base_url("http://localhost:8080")
+    ///         .cache_config(CacheConfig::default().with_change_feed(ChangeFeedConfig::default()))
+    ///         .build()?,
+    /// );
+    /// let sync = client.spawn_cache_sync()?;
+    /// // ...
+    /// sync.abort();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn spawn_cache_sync(self: &Arc<Self>) -> Result<JoinHandle<()>> {
+        let feed = self
+            .config
+            .cache_config
+            .change_feed
+            .clone()
+            .ok_or_else(|| {
+                SchemaRegistryError::ConfigError(
+                    "Cache sync requires a cache configured with a change feed".to_string(),
+                )
+            })?;
+        let client = Arc::clone(self);
+
+        Ok(tokio::spawn(async move {
+            loop {
+                if let Err(error) = client.sync_cache().await {
+                    warn!(
+                        "Change feed sync failed, cached schemas are growing stale: {}",
+                        error
+                    );
+                }
+                sleep(feed.poll_interval).await;
+            }
+        }))
+    }
+
     // Private helper methods
 
     fn build_url(&self, path: &str) -> Result<String> {
@@ -490,63 +889,71 @@ impl SchemaRegistryClient {
         Ok(url.to_string())
     }
 
-    fn add_auth_header(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
-        if let Some(ref api_key) = self.config.api_key {
-            request.header("Authorization", format!("Bearer {}", api_key))
-        } else {
-            request
+    /// Sends one request attempt with auth, headers and middleware applied
+    async fn send(&self, request: reqwest::RequestBuilder) -> Result<reqwest::Response> {
+        let mut request = request.build()?;
+        insert_header(&mut request, "X-SDK-Version", SDK_VERSION)?;
+
+        if let Some(ref token_source) = self.token_source {
+            let token = token_source.token().await?;
+            insert_header(&mut request, "Authorization", &format!("Bearer {token}"))?;
+        } else if let Some(ref api_key) = self.config.api_key {
+            insert_header(&mut request, "Authorization", &format!("Bearer {api_key}"))?;
+        }
+        for (name, value) in &self.config.default_headers {
+            insert_header(&mut request, name, value)?;
+        }
+        if let Some(scoped) = RequestHeaders::current() {
+            for (name, value) in scoped.iter() {
+                insert_header(&mut request, name, value)?;
+            }
+        }
+        for middleware in &self.middleware {
+            middleware.on_request(&mut request).await?;
         }
+
+        let response = self.http_client.execute(request).await?;
+
+        if response.status() == StatusCode::UNAUTHORIZED {
+            if let Some(ref token_source) = self.token_source {
+                token_source.invalidate().await;
+            }
+        }
+        for middleware in &self.middleware {
+            middleware.on_response(&response).await?;
+        }
+
+        Ok(response)
     }
 
+    /// Sends a request, repeating it as the retry policy allows
     async fn retry_request<F, Fut>(&self, request_fn: F) -> Result<reqwest::Response>
     where
         F: Fn() -> Fut,
-        Fut: std::future::Future<Output = std::result::Result<reqwest::Response, reqwest::Error>>,
+        Fut: std::future::Future<Output = Result<reqwest::Response>>,
     {
-        let mut attempts = 0;
-        let mut delay = self.config.initial_retry_delay;
-
-        loop {
-            attempts += 1;
-
-            let request = request_fn().await;
-
-            match request {
-                Ok(response) => {
-                    let status = response.status();
-
-                    if status.is_success() {
-                        return Ok(response);
-                    }
-
-                    let error = self.handle_error_response(response).await;
-
-                    if attempts >= self.config.max_retries || !error.is_retryable() {
-                        return Err(error);
-                    }
-
-                    warn!(
-                        "Request failed (attempt {}/{}): {}. Retrying in {:?}...",
-                        attempts, self.config.max_retries, error, delay
-                    );
+        let token_refreshed = AtomicBool::new(false);
+
+        self.retry_policy
+            .retry(|| async {
+                let mut response = request_fn().await?;
+
+                // A rejected token was discarded by `send`; retry once with a fresh one
+                if response.status() == StatusCode::UNAUTHORIZED
+                    && self.token_source.is_some()
+                    && !token_refreshed.swap(true, Ordering::Relaxed)
+                {
+                    debug!("Token rejected, retrying with a refreshed token");
+                    response = request_fn().await?;
                 }
-                Err(e) => {
-                    let error: SchemaRegistryError = e.into();
-
-                    if attempts >= self.config.max_retries || !error.is_retryable() {
-                        return Err(error);
-                    }
 
-                    warn!(
-                        "Request failed (attempt {}/{}): {}. Retrying in {:?}...",
-                        attempts, self.config.max_retries, error, delay
-                    );
+                if response.status().is_success() {
+                    Ok(response)
+                } else {
+                    Err(self.handle_error_response(response).await)
                 }
-            }
-
-            sleep(delay).await;
-            delay *= 2; // Exponential backoff
-        }
+            })
+            .await
     }
 
     async fn handle_error_response(&self, response: reqwest::Response) -> SchemaRegistryError {
@@ -574,10 +981,23 @@ impl SchemaRegistryClient {
     }
 }
 
+/// One page of a schema's versions, as the registry sends it
+#[derive(Deserialize)]
+struct VersionsPage {
+    namespace: String,
+    name: String,
+    versions: Vec<SchemaVersion>,
+    /// Versions across all pages
+    #[serde(default)]
+    total: Option<u64>,
+}
+
 /// Builder for creating a SchemaRegistryClient.
 #[derive(Default)]
 pub struct ClientBuilder {
     config: Option<ClientConfig>,
+    token_source: Option<Arc<dyn TokenSource>>,
+    middleware: Vec<Arc<dyn ClientMiddleware>>,
 }
 
 impl ClientBuilder {
@@ -619,13 +1039,39 @@ impl ClientBuilder {
         self
     }
 
+    /// Adds a header sent with every request.
+    #[must_use]
+    pub fn default_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        if let Some(ref mut config) = self.config {
+            config.default_headers.push((name.into(), value.into()));
+        }
+        self
+    }
+
+    /// Authenticates with tokens from `token_source` instead of the API key.
+    #[must_use]
+    pub fn token_source(mut self, token_source: impl TokenSource + 'static) -> Self {
+        self.token_source = Some(Arc::new(token_source));
+        self
+    }
+
+    /// Adds a middleware; middleware runs in the order it was added.
+    #[must_use]
+    pub fn middleware(mut self, middleware: impl ClientMiddleware + 'static) -> Self {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
     /// Builds the SchemaRegistryClient.
     pub fn build(self) -> Result<SchemaRegistryClient> {
         let config = self
             .config
             .ok_or_else(|| SchemaRegistryError::ConfigError("Base URL is required".to_string()))?;
 
-        SchemaRegistryClient::new(config)
+        let mut client = SchemaRegistryClient::new(config)?;
+        client.token_source = self.token_source;
+        client.middleware = self.middleware;
+        Ok(client)
     }
 }
 
@@ -668,6 +1114,203 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_search_all_follows_pages() {
+        use wiremock::matchers::{body_partial_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        let result = |name: &str| {
+            serde_json::json!({
+                "metadata": {
+                    "schema_id": name,
+                    "namespace": "telemetry",
+                    "name": name,
+                    "version": "1.0.0",
+                    "format": "JSON_SCHEMA"
+                },
+                "score": 1.0
+            })
+        };
+        for (offset, names) in [(0, vec!["a", "b"]), (2, vec!["c"])] {
+            let results: Vec<_> = names.into_iter().map(result).collect();
+            Mock::given(method("POST"))
+                .and(path("/api/v1/schemas/search"))
+                .and(body_partial_json(serde_json::json!({"offset": offset, "limit": 2})))
+                .respond_with(
+                    ResponseTemplate::new(200)
+                        .set_body_json(serde_json::json!({"results": results, "total": 3})),
+                )
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+
+        let client = SchemaRegistryClient::builder()
+            .base_url(server.uri())
+            .build()
+            .unwrap();
+        let query = SearchQuery::builder().namespace("telemetry").page_size(2).build();
+        let names: Vec<String> = client
+            .search_all(query)
+            .map_ok(|r| r.metadata.name)
+            .try_collect()
+            .await
+            .unwrap();
+
+        assert_eq!(names, vec!["a", "b", "c"]);
+    }
+
+    #[tokio::test]
+    async fn test_list_versions_follows_pages() {
+        use wiremock::matchers::{method, path, query_param};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        let version = |v: &str| {
+            serde_json::json!({
+                "version": v,
+                "schema_id": format!("id-{v}"),
+                "created_at": "2026-01-01T00:00:00Z",
+                "state": "ACTIVE",
+                "checksum": "abc"
+            })
+        };
+        for (offset, versions) in [("0", vec!["1.0.0", "2.0.0"]), ("2", vec!["3.0.0"])] {
+            let versions: Vec<_> = versions.into_iter().map(version).collect();
+            Mock::given(method("GET"))
+                .and(path("/api/v1/schemas/telemetry/InferenceEvent/versions"))
+                .and(query_param("offset", offset))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "namespace": "telemetry",
+                    "name": "InferenceEvent",
+                    "versions": versions,
+                    "total": 3
+                })))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+
+        let client = SchemaRegistryClient::builder()
+            .base_url(server.uri())
+            .build()
+            .unwrap();
+        let listed = client.list_versions("telemetry", "InferenceEvent").await.unwrap();
+        let versions: Vec<_> = listed.versions.iter().map(|v| v.version.as_str()).collect();
+
+        assert_eq!(versions, vec!["1.0.0", "2.0.0", "3.0.0"]);
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_active_retries_until_reached() {
+        use wiremock::matchers::{method, path, query_param};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        let status = |state: &str, reached: bool| {
+            serde_json::json!({
+                "id": "00000000-0000-0000-0000-000000000001",
+                "subject": "telemetry.InferenceEvent",
+                "version": "1.1.0",
+                "state": state,
+                "created_at": "2026-01-01T00:00:00Z",
+                "reached": reached
+            })
+        };
+        let responses = [
+            ResponseTemplate::new(503),
+            ResponseTemplate::new(200).set_body_json(status("REGISTERED", false)),
+            ResponseTemplate::new(200).set_body_json(status("ACTIVE", true)),
+        ];
+        for response in responses {
+            Mock::given(method("GET"))
+                .and(path("/api/v1/subjects/telemetry.InferenceEvent/versions/1.1.0"))
+                .and(query_param("wait_for_state", "ACTIVE"))
+                .respond_with(response)
+                .up_to_n_times(1)
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+
+        let config = ClientConfig::new(server.uri())
+            .with_initial_retry_delay(Duration::from_millis(10));
+        let client = SchemaRegistryClient::new(config).unwrap();
+        let status = client
+            .wait_for_active("telemetry.InferenceEvent", "1.1.0", Duration::from_secs(10))
+            .await
+            .unwrap();
+
+        assert_eq!(status.state, "ACTIVE");
+    }
+
+    #[tokio::test]
+    async fn test_sync_cache_invalidates_only_changed_schemas() {
+        use wiremock::matchers::{method, path, query_param, query_param_is_missing};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        let changes = |changes: serde_json::Value, cursor: i64| {
+            ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "changes": changes,
+                "cursor": cursor,
+                "has_more": false
+            }))
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/v1/changes"))
+            .and(query_param_is_missing("after"))
+            .respond_with(changes(serde_json::json!([]), 7))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/changes"))
+            .and(query_param("after", "7"))
+            .respond_with(changes(
+                serde_json::json!([{
+                    "sequence": 8,
+                    "schema_id": "a",
+                    "subject": "telemetry.a",
+                    "change": "UPDATED",
+                    "changed_at": "2025-01-01T00:00:00Z"
+                }]),
+                8,
+            ))
+            .expect(1)
+            .mount(&server)
+            .await;
+        for (id, fetches) in [("a", 2), ("b", 1)] {
+            Mock::given(method("GET"))
+                .and(path(format!("/api/v1/schemas/{id}")))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "schema_id": id,
+                    "namespace": "telemetry",
+                    "name": id,
+                    "version": "1.0.0",
+                    "format": "JSON_SCHEMA",
+                    "content": "{}"
+                })))
+                .expect(fetches)
+                .mount(&server)
+                .await;
+        }
+
+        let client = SchemaRegistryClient::builder()
+            .base_url(server.uri())
+            .cache_config(CacheConfig::default().with_change_feed(ChangeFeedConfig::default()))
+            .build()
+            .unwrap();
+        assert_eq!(client.sync_cache().await.unwrap(), 0);
+        client.get_schema("a").await.unwrap();
+        client.get_schema("b").await.unwrap();
+
+        assert_eq!(client.sync_cache().await.unwrap(), 1);
+        client.get_schema("a").await.unwrap();
+        client.get_schema("b").await.unwrap();
+    }
+
     #[test]
     fn test_client_invalid_base_url() {
         let result = SchemaRegistryClient::builder()
@@ -3,6 +3,7 @@
 //! This module provides comprehensive error handling with strongly-typed error variants
 //! for all possible failure modes when interacting with the Schema Registry API.
 
+use schema_registry_core::retry::{Classify, ErrorClass};
 use thiserror::Error;
 
 /// Result type alias for SDK operations.
@@ -71,6 +72,26 @@ pub enum SchemaRegistryError {
     #[error("Cache error: {0}")]
     CacheError(String),
 
+    /// A registered schema differs from the type compiled into the service.
+    #[error("Schema drift for {subject}: {}", differences.join("; "))]
+    SchemaDrift {
+        /// Subject and version, e.g. `telemetry.InferenceEvent@1.0.0`
+        subject: String,
+        /// Where the registered schema differs
+        differences: Vec<String>,
+    },
+
+    /// A version reached a state it can't leave while being waited on.
+    #[error("{subject} is {state} and can no longer become {wanted}")]
+    StateUnreachable {
+        /// Subject and version, e.g. `telemetry.InferenceEvent@1.0.0`
+        subject: String,
+        /// State the version ended up in
+        state: String,
+        /// State that was waited for
+        wanted: String,
+    },
+
     /// Generic error for unexpected conditions.
     #[error("Internal error: {0}")]
     InternalError(String),
@@ -81,13 +102,7 @@ impl SchemaRegistryError {
     ///
     /// Retryable errors include network timeouts, rate limits, and certain server errors.
     pub fn is_retryable(&self) -> bool {
-        match self {
-            SchemaRegistryError::TimeoutError(_)
-            | SchemaRegistryError::RateLimitError(_)
-            | SchemaRegistryError::HttpError(_) => true,
-            SchemaRegistryError::ServerError { status, .. } => *status >= 500 && *status < 600,
-            _ => false,
-        }
+        self.class().is_retryable()
     }
 
     /// Returns true if this error is a client error (4xx status codes).
@@ -111,6 +126,22 @@ impl SchemaRegistryError {
     }
 }
 
+impl Classify for SchemaRegistryError {
+    fn class(&self) -> ErrorClass {
+        match self {
+            SchemaRegistryError::TimeoutError(_) | SchemaRegistryError::HttpError(_) => {
+                ErrorClass::Transient
+            }
+            SchemaRegistryError::RateLimitError(_) => ErrorClass::RateLimited,
+            SchemaRegistryError::IncompatibleSchema(_) => ErrorClass::Conflict,
+            SchemaRegistryError::ServerError { status, .. } => {
+                ErrorClass::from_http_status(*status)
+            }
+            _ => ErrorClass::Permanent,
+        }
+    }
+}
+
 // Conversions from external error types
 
 impl From<reqwest::Error> for SchemaRegistryError {
@@ -193,6 +224,24 @@ mod tests {
             .is_retryable());
     }
 
+    #[test]
+    fn test_error_class() {
+        let server_error = |status| SchemaRegistryError::ServerError {
+            status,
+            message: String::new(),
+        };
+        assert_eq!(server_error(502).class(), ErrorClass::Transient);
+        assert_eq!(server_error(501).class(), ErrorClass::Permanent);
+        assert_eq!(
+            SchemaRegistryError::RateLimitError("slow down".to_string()).class(),
+            ErrorClass::RateLimited
+        );
+        assert_eq!(
+            SchemaRegistryError::IncompatibleSchema("conflict".to_string()).class(),
+            ErrorClass::Conflict
+        );
+    }
+
     #[test]
     fn test_is_client_error() {
         assert!(SchemaRegistryError::SchemaNotFound("not found".to_string()).is_client_error());
@@ -57,9 +57,13 @@
 //! The SDK is organized into the following modules:
 //!
 //! - [`client`]: Main client implementation with retry logic and error handling
+//! - [`api`]: The [`SchemaRegistryApi`] trait, for code that should also run against a mock
+//! - [`mock`]: In-memory [`MockSchemaRegistryClient`] for unit tests
 //! - [`models`]: Data models for schemas, responses, and requests
 //! - [`errors`]: Comprehensive error types with detailed context
 //! - [`cache`]: Async caching implementation for performance optimization
+//! - [`search`]: Typed search query builder
+//! - [`middleware`]: Request middleware, OAuth 2.0 token refresh and scoped headers
 //!
 //! ## Performance
 //!
@@ -161,20 +165,39 @@
 #![allow(clippy::missing_errors_doc)]
 #![allow(clippy::missing_panics_doc)]
 
+pub mod api;
+pub mod bootstrap;
 pub mod cache;
 pub mod client;
 pub mod errors;
+pub mod middleware;
+pub mod mock;
 pub mod models;
+pub mod search;
 
 // Re-export commonly used types for convenience
-pub use cache::{CacheConfig, SchemaCache};
+pub use api::SchemaRegistryApi;
+pub use bootstrap::{RegistrySchema, ToJsonSchema};
+pub use cache::{CacheConfig, ChangeFeedConfig, SchemaCache};
 pub use client::{ClientBuilder, ClientConfig, SchemaRegistryClient};
 pub use errors::{Result, SchemaRegistryError};
+pub use mock::MockSchemaRegistryClient;
+pub use middleware::{ClientMiddleware, OAuth2ClientCredentials, RequestHeaders, TokenSource};
 pub use models::{
-    CheckCompatibilityRequest, CompatibilityMode, CompatibilityResult, GetSchemaResponse,
-    HealthCheckResponse, ListVersionsResponse, RegisterSchemaResponse, Schema, SchemaFormat,
-    SchemaMetadata, SchemaVersion, SearchQuery, SearchResponse, SearchResult, ValidateResponse,
+    ChangeKind, ChangesResponse, CheckCompatibilityRequest, CompatibilityMode, CompatibilityResult,
+    GetSchemaResponse, HealthCheckResponse, ListVersionsResponse, RegisterSchemaResponse, Schema,
+    SchemaChange, SchemaFormat, SchemaMetadata, SchemaState, SchemaVersion, SearchQuery,
+    SearchResponse, SearchResult, SubjectVersionStatus, ValidateResponse,
 };
+pub use search::SearchQueryBuilder;
+
+/// Derives [`ToJsonSchema`] and, with `#[registry(...)]`, [`RegistrySchema`].
+#[cfg(feature = "derive")]
+pub use llm_schema_registry_sdk_derive::RegistrySchema;
+
+// Lets the derive's `::llm_schema_registry_sdk` paths resolve in this crate's tests
+#[cfg(test)]
+extern crate self as llm_schema_registry_sdk;
 
 /// Prelude module for convenient imports.
 ///
@@ -184,6 +207,8 @@ pub use models::{
 /// use llm_schema_registry_sdk::prelude::*;
 /// ```
 pub mod prelude {
+    pub use crate::api::SchemaRegistryApi;
+    pub use crate::bootstrap::RegistrySchema;
     pub use crate::cache::{CacheConfig, SchemaCache};
     pub use crate::client::{ClientBuilder, ClientConfig, SchemaRegistryClient};
     pub use crate::errors::{Result, SchemaRegistryError};
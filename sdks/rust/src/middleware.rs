@@ -0,0 +1,408 @@
+//! Request interception, token-based auth and per-request headers.
+//!
+//! - [`ClientMiddleware`] sees every request attempt before it is sent and
+//!   every response before the client handles it.
+//! - [`TokenSource`] supplies the bearer token for each request, replacing a
+//!   static API key. [`OAuth2ClientCredentials`] fetches tokens with the
+//!   OAuth 2.0 client-credentials grant and refreshes them before they expire.
+//! - [`RequestHeaders`] adds headers, such as a tenant or correlation id, to
+//!   every request made within a scope.
+//!
+//! ```no_run
+//! use llm_schema_registry_sdk::middleware::{OAuth2ClientCredentials, RequestHeaders};
+//! use llm_schema_registry_sdk::SchemaRegistryClient;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = SchemaRegistryClient::builder()
+//!     .base_url("http://localhost:8080")
+//!     .token_source(
+//!         OAuth2ClientCredentials::new("https://auth.example.com/oauth/token", "svc-id", "secret")
+//!             .with_scope("schemas:read"),
+//!     )
+//!     .build()?;
+//!
+//! let schema = RequestHeaders::new()
+//!     .tenant_id("acme")
+//!     .correlation_id("req-7f3a")
+//!     .scope(client.get_schema("schema-id-123"))
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::errors::{Result, SchemaRegistryError};
+use async_trait::async_trait;
+use reqwest::header::{HeaderName, HeaderValue};
+use reqwest::{Client, Request, Response};
+use serde::Deserialize;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// Header carrying the tenant a request acts for.
+pub const TENANT_ID_HEADER: &str = "x-tenant-id";
+
+/// Header tying a request to the caller's trace or log context.
+pub const CORRELATION_ID_HEADER: &str = "x-correlation-id";
+
+/// Token lifetime assumed when the token endpoint doesn't report one.
+const DEFAULT_TOKEN_LIFETIME: Duration = Duration::from_secs(3600);
+
+/// Hooks run around every request attempt, including retries.
+///
+/// Middleware runs in the order it was added to the
+/// [`ClientBuilder`](crate::ClientBuilder), after authentication and scoped
+/// headers have been applied. Returning an error aborts the request with that
+/// error.
+#[async_trait]
+pub trait ClientMiddleware: Send + Sync {
+    /// Called before a request attempt is sent.
+    async fn on_request(&self, request: &mut Request) -> Result<()> {
+        let _ = request;
+        Ok(())
+    }
+
+    /// Called with every response, successful or not.
+    async fn on_response(&self, response: &Response) -> Result<()> {
+        let _ = response;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<T: ClientMiddleware + ?Sized> ClientMiddleware for Arc<T> {
+    async fn on_request(&self, request: &mut Request) -> Result<()> {
+        (**self).on_request(request).await
+    }
+
+    async fn on_response(&self, response: &Response) -> Result<()> {
+        (**self).on_response(response).await
+    }
+}
+
+/// Supplies bearer tokens for requests.
+#[async_trait]
+pub trait TokenSource: Send + Sync {
+    /// A token valid for the next request.
+    async fn token(&self) -> Result<String>;
+
+    /// Discards the current token after the server rejected it.
+    async fn invalidate(&self) {}
+}
+
+#[async_trait]
+impl<T: TokenSource + ?Sized> TokenSource for Arc<T> {
+    async fn token(&self) -> Result<String> {
+        (**self).token().await
+    }
+
+    async fn invalidate(&self) {
+        (**self).invalidate().await;
+    }
+}
+
+/// OAuth 2.0 client-credentials token source.
+///
+/// Tokens are cached and refreshed once they are within the refresh margin
+/// (60 seconds by default) of expiring, or after the registry rejects one.
+/// Concurrent requests share a single refresh.
+pub struct OAuth2ClientCredentials {
+    token_url: String,
+    client_id: String,
+    client_secret: String,
+    scopes: Vec<String>,
+    refresh_margin: Duration,
+    http_client: Client,
+    cached: Mutex<Option<CachedToken>>,
+}
+
+struct CachedToken {
+    access_token: String,
+    expires_at: Instant,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default)]
+    expires_in: Option<u64>,
+}
+
+impl OAuth2ClientCredentials {
+    /// Creates a token source for the given token endpoint and client.
+    pub fn new(
+        token_url: impl Into<String>,
+        client_id: impl Into<String>,
+        client_secret: impl Into<String>,
+    ) -> Self {
+        Self {
+            token_url: token_url.into(),
+            client_id: client_id.into(),
+            client_secret: client_secret.into(),
+            scopes: Vec::new(),
+            refresh_margin: Duration::from_secs(60),
+            http_client: Client::new(),
+            cached: Mutex::new(None),
+        }
+    }
+
+    /// Requests `scope` in addition to any scopes already added.
+    #[must_use]
+    pub fn with_scope(mut self, scope: impl Into<String>) -> Self {
+        self.scopes.push(scope.into());
+        self
+    }
+
+    /// Sets how long before expiry a token is refreshed.
+    #[must_use]
+    pub fn with_refresh_margin(mut self, margin: Duration) -> Self {
+        self.refresh_margin = margin;
+        self
+    }
+
+    async fn fetch(&self) -> Result<CachedToken> {
+        let mut form = vec![("grant_type", "client_credentials".to_string())];
+        if !self.scopes.is_empty() {
+            form.push(("scope", self.scopes.join(" ")));
+        }
+
+        let response = self
+            .http_client
+            .post(&self.token_url)
+            .basic_auth(&self.client_id, Some(&self.client_secret))
+            .form(&form)
+            .send()
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(SchemaRegistryError::AuthenticationError(format!(
+                "token endpoint returned {status}: {body}"
+            )));
+        }
+
+        let token: TokenResponse = response.json().await?;
+        let lifetime = token
+            .expires_in
+            .map_or(DEFAULT_TOKEN_LIFETIME, Duration::from_secs);
+        Ok(CachedToken {
+            access_token: token.access_token,
+            expires_at: Instant::now() + lifetime,
+        })
+    }
+}
+
+#[async_trait]
+impl TokenSource for OAuth2ClientCredentials {
+    async fn token(&self) -> Result<String> {
+        let mut cached = self.cached.lock().await;
+        if let Some(token) = cached.as_ref() {
+            if Instant::now() + self.refresh_margin < token.expires_at {
+                return Ok(token.access_token.clone());
+            }
+        }
+
+        tracing::debug!("Fetching OAuth2 token from {}", self.token_url);
+        let token = self.fetch().await?;
+        let access_token = token.access_token.clone();
+        *cached = Some(token);
+        Ok(access_token)
+    }
+
+    async fn invalidate(&self) {
+        *self.cached.lock().await = None;
+    }
+}
+
+tokio::task_local! {
+    static SCOPED_HEADERS: RequestHeaders;
+}
+
+/// Headers added to every request made within [`RequestHeaders::scope`].
+///
+/// Scoped headers are applied after the client's default headers, so they
+/// replace defaults of the same name.
+#[derive(Debug, Clone, Default)]
+#[must_use]
+pub struct RequestHeaders {
+    headers: Vec<(String, String)>,
+}
+
+impl RequestHeaders {
+    /// Creates an empty header set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a header.
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Sets the [`TENANT_ID_HEADER`].
+    pub fn tenant_id(self, tenant_id: impl Into<String>) -> Self {
+        self.header(TENANT_ID_HEADER, tenant_id)
+    }
+
+    /// Sets the [`CORRELATION_ID_HEADER`].
+    pub fn correlation_id(self, correlation_id: impl Into<String>) -> Self {
+        self.header(CORRELATION_ID_HEADER, correlation_id)
+    }
+
+    /// Runs `future` with these headers added to its requests.
+    pub async fn scope<F: Future>(self, future: F) -> F::Output {
+        SCOPED_HEADERS.scope(self, future).await
+    }
+
+    /// Headers of the innermost enclosing scope.
+    pub(crate) fn current() -> Option<Self> {
+        SCOPED_HEADERS.try_with(Clone::clone).ok()
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.headers.iter().map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+}
+
+/// Sets a header on `request`, replacing any existing value.
+pub(crate) fn insert_header(request: &mut Request, name: &str, value: &str) -> Result<()> {
+    let name = HeaderName::from_bytes(name.as_bytes())
+        .map_err(|e| SchemaRegistryError::ConfigError(format!("Invalid header name {name:?}: {e}")))?;
+    let value = HeaderValue::from_str(value)
+        .map_err(|e| SchemaRegistryError::ConfigError(format!("Invalid value for header {name}: {e}")))?;
+    request.headers_mut().insert(name, value);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SchemaRegistryClient;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use wiremock::matchers::{body_string_contains, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn schema_body() -> serde_json::Value {
+        serde_json::json!({
+            "schema_id": "s1",
+            "namespace": "telemetry",
+            "name": "InferenceEvent",
+            "version": "1.0.0",
+            "format": "JSON_SCHEMA",
+            "content": "{}"
+        })
+    }
+
+    #[derive(Default)]
+    struct CountingMiddleware {
+        requests: AtomicUsize,
+        responses: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl ClientMiddleware for CountingMiddleware {
+        async fn on_request(&self, request: &mut Request) -> Result<()> {
+            self.requests.fetch_add(1, Ordering::SeqCst);
+            insert_header(request, "x-sdk-test", "1")
+        }
+
+        async fn on_response(&self, _response: &Response) -> Result<()> {
+            self.responses.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_oauth2_token_is_cached_and_headers_scoped() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/oauth/token"))
+            .and(body_string_contains("grant_type=client_credentials"))
+            .and(body_string_contains("scope=schemas%3Aread"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "access_token": "token-1",
+                "token_type": "Bearer",
+                "expires_in": 3600
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        for id in ["s1", "s2"] {
+            Mock::given(method("GET"))
+                .and(path(format!("/api/v1/schemas/{id}")))
+                .and(header("authorization", "Bearer token-1"))
+                .and(header(TENANT_ID_HEADER, "acme"))
+                .and(header("x-sdk-test", "1"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(schema_body()))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+
+        let middleware = Arc::new(CountingMiddleware::default());
+        let client = SchemaRegistryClient::builder()
+            .base_url(server.uri())
+            .token_source(
+                OAuth2ClientCredentials::new(format!("{}/oauth/token", server.uri()), "id", "secret")
+                    .with_scope("schemas:read"),
+            )
+            .middleware(middleware.clone())
+            .build()
+            .unwrap();
+
+        RequestHeaders::new()
+            .tenant_id("acme")
+            .scope(async {
+                client.get_schema("s1").await.unwrap();
+                client.get_schema("s2").await.unwrap();
+            })
+            .await;
+
+        assert_eq!(middleware.requests.load(Ordering::SeqCst), 2);
+        assert_eq!(middleware.responses.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_rejected_token_is_refreshed() {
+        let server = MockServer::start().await;
+        let issued = Arc::new(AtomicUsize::new(0));
+        let counter = issued.clone();
+        Mock::given(method("POST"))
+            .and(path("/oauth/token"))
+            .respond_with(move |_: &wiremock::Request| {
+                let n = counter.fetch_add(1, Ordering::SeqCst) + 1;
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "access_token": format!("token-{n}") }))
+            })
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/schemas/s1"))
+            .and(header("authorization", "Bearer token-1"))
+            .respond_with(ResponseTemplate::new(401))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/schemas/s1"))
+            .and(header("authorization", "Bearer token-2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(schema_body()))
+            .mount(&server)
+            .await;
+
+        let client = SchemaRegistryClient::builder()
+            .base_url(server.uri())
+            .token_source(OAuth2ClientCredentials::new(
+                format!("{}/oauth/token", server.uri()),
+                "id",
+                "secret",
+            ))
+            .build()
+            .unwrap();
+
+        let schema = client.get_schema("s1").await.unwrap();
+        assert_eq!(schema.metadata.schema_id, "s1");
+        assert_eq!(issued.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,522 @@
+//! In-memory [`SchemaRegistryApi`] for unit tests.
+//!
+//! [`MockSchemaRegistryClient`] serves canned schemas, records every call,
+//! and lets tests override any operation's response and assert how often it
+//! was called - no HTTP server required.
+//!
+//! ```
+//! use llm_schema_registry_sdk::mock::{Method, MockSchemaRegistryClient};
+//! use llm_schema_registry_sdk::{SchemaFormat, SchemaRegistryApi, SchemaRegistryError, ValidateResponse};
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let mock = MockSchemaRegistryClient::new()
+//!     .with_schema("s1", "telemetry", "InferenceEvent", "1.0.0", SchemaFormat::JsonSchema, "{}")
+//!     .on_validate_data(|_schema_id, data| {
+//!         Ok(ValidateResponse {
+//!             is_valid: data.contains("model"),
+//!             errors: None,
+//!         })
+//!     })
+//!     .expect(Method::ValidateData, 1);
+//!
+//! assert!(mock.validate_data("s1", r#"{"model": "gpt-4"}"#).await.unwrap().is_valid());
+//! assert!(matches!(
+//!     mock.get_schema("missing").await,
+//!     Err(SchemaRegistryError::SchemaNotFound(_))
+//! ));
+//! mock.verify();
+//! # }
+//! ```
+
+use crate::api::SchemaRegistryApi;
+use crate::errors::{Result, SchemaRegistryError};
+use crate::models::{
+    CompatibilityMode, CompatibilityResult, GetSchemaResponse, HealthCheckResponse,
+    ListVersionsResponse, RegisterSchemaResponse, Schema, SchemaFormat, SchemaMetadata,
+    SchemaVersion, SearchQuery, SearchResponse, SearchResult, ValidateResponse,
+};
+use async_trait::async_trait;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// A [`SchemaRegistryApi`] operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Method {
+    /// [`SchemaRegistryApi::register_schema`]
+    RegisterSchema,
+    /// [`SchemaRegistryApi::get_schema`]
+    GetSchema,
+    /// [`SchemaRegistryApi::get_schema_by_version`]
+    GetSchemaByVersion,
+    /// [`SchemaRegistryApi::validate_data`]
+    ValidateData,
+    /// [`SchemaRegistryApi::check_compatibility`]
+    CheckCompatibility,
+    /// [`SchemaRegistryApi::list_versions`]
+    ListVersions,
+    /// [`SchemaRegistryApi::search_schemas`]
+    SearchSchemas,
+    /// [`SchemaRegistryApi::delete_schema`]
+    DeleteSchema,
+    /// [`SchemaRegistryApi::health_check`]
+    HealthCheck,
+}
+
+/// A call made to the mock.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockCall {
+    /// Operation called
+    pub method: Method,
+    /// Schema ID, `namespace.name[@version]` or search text, whichever
+    /// identifies the call's target
+    pub target: String,
+}
+
+type Responder<A, T> = Box<dyn Fn(A) -> Result<T> + Send + Sync>;
+type ValidateResponder = Box<dyn Fn(&str, &str) -> Result<ValidateResponse> + Send + Sync>;
+
+#[derive(Default)]
+struct Responders {
+    register_schema: Option<Responder<Schema, RegisterSchemaResponse>>,
+    validate_data: Option<ValidateResponder>,
+    check_compatibility: Option<Responder<(Schema, CompatibilityMode), CompatibilityResult>>,
+    search_schemas: Option<Responder<SearchQuery, SearchResponse>>,
+    failures: HashMap<Method, Box<dyn Fn() -> SchemaRegistryError + Send + Sync>>,
+}
+
+/// Programmable in-memory stand-in for [`SchemaRegistryClient`](crate::SchemaRegistryClient).
+///
+/// Without overrides the mock behaves like a small registry: registered and
+/// canned schemas can be fetched, listed, searched and deleted, data that
+/// parses as JSON is valid, and every schema is compatible.
+#[derive(Default)]
+pub struct MockSchemaRegistryClient {
+    schemas: Mutex<BTreeMap<String, GetSchemaResponse>>,
+    responders: Responders,
+    expectations: HashMap<Method, usize>,
+    calls: Mutex<Vec<MockCall>>,
+    registered: AtomicUsize,
+}
+
+impl MockSchemaRegistryClient {
+    /// Creates an empty mock.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a canned schema.
+    #[must_use]
+    pub fn with_schema(
+        self,
+        schema_id: impl Into<String>,
+        namespace: impl Into<String>,
+        name: impl Into<String>,
+        version: impl Into<String>,
+        format: SchemaFormat,
+        content: impl Into<String>,
+    ) -> Self {
+        let schema_id = schema_id.into();
+        let response = GetSchemaResponse {
+            metadata: SchemaMetadata {
+                schema_id: schema_id.clone(),
+                global_id: None,
+                namespace: namespace.into(),
+                name: name.into(),
+                version: version.into(),
+                format,
+                created_at: None,
+                updated_at: None,
+                tags: None,
+            },
+            content: content.into(),
+        };
+        self.lock_schemas().insert(schema_id, response);
+        self
+    }
+
+    /// Overrides [`SchemaRegistryApi::register_schema`].
+    #[must_use]
+    pub fn on_register_schema<F>(mut self, f: F) -> Self
+    where
+        F: Fn(Schema) -> Result<RegisterSchemaResponse> + Send + Sync + 'static,
+    {
+        self.responders.register_schema = Some(Box::new(f));
+        self
+    }
+
+    /// Overrides [`SchemaRegistryApi::validate_data`].
+    #[must_use]
+    pub fn on_validate_data<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&str, &str) -> Result<ValidateResponse> + Send + Sync + 'static,
+    {
+        self.responders.validate_data = Some(Box::new(f));
+        self
+    }
+
+    /// Overrides [`SchemaRegistryApi::check_compatibility`].
+    #[must_use]
+    pub fn on_check_compatibility<F>(mut self, f: F) -> Self
+    where
+        F: Fn(Schema, CompatibilityMode) -> Result<CompatibilityResult> + Send + Sync + 'static,
+    {
+        self.responders.check_compatibility = Some(Box::new(move |(schema, mode)| f(schema, mode)));
+        self
+    }
+
+    /// Overrides [`SchemaRegistryApi::search_schemas`].
+    #[must_use]
+    pub fn on_search_schemas<F>(mut self, f: F) -> Self
+    where
+        F: Fn(SearchQuery) -> Result<SearchResponse> + Send + Sync + 'static,
+    {
+        self.responders.search_schemas = Some(Box::new(f));
+        self
+    }
+
+    /// Makes every call to `method` fail with the error `f` returns.
+    #[must_use]
+    pub fn fail<F>(mut self, method: Method, f: F) -> Self
+    where
+        F: Fn() -> SchemaRegistryError + Send + Sync + 'static,
+    {
+        self.responders.failures.insert(method, Box::new(f));
+        self
+    }
+
+    /// Expects `method` to be called exactly `times` times; see [`verify`](Self::verify).
+    #[must_use]
+    pub fn expect(mut self, method: Method, times: usize) -> Self {
+        self.expectations.insert(method, times);
+        self
+    }
+
+    /// Every call made so far, oldest first.
+    pub fn calls(&self) -> Vec<MockCall> {
+        self.calls.lock().expect("mock calls lock").clone()
+    }
+
+    /// Number of calls made to `method`.
+    pub fn call_count(&self, method: Method) -> usize {
+        self.calls
+            .lock()
+            .expect("mock calls lock")
+            .iter()
+            .filter(|call| call.method == method)
+            .count()
+    }
+
+    /// Panics unless every expected method was called the expected number of times.
+    pub fn verify(&self) {
+        let mut unmet: Vec<String> = self
+            .expectations
+            .iter()
+            .filter_map(|(method, expected)| {
+                let actual = self.call_count(*method);
+                (actual != *expected)
+                    .then(|| format!("{method:?}: expected {expected} call(s), got {actual}"))
+            })
+            .collect();
+        unmet.sort();
+        assert!(unmet.is_empty(), "unmet mock expectations: {}", unmet.join("; "));
+    }
+
+    fn record(&self, method: Method, target: impl Into<String>) -> Result<()> {
+        self.calls.lock().expect("mock calls lock").push(MockCall {
+            method,
+            target: target.into(),
+        });
+        match self.responders.failures.get(&method) {
+            Some(failure) => Err(failure()),
+            None => Ok(()),
+        }
+    }
+
+    fn lock_schemas(&self) -> std::sync::MutexGuard<'_, BTreeMap<String, GetSchemaResponse>> {
+        self.schemas.lock().expect("mock schemas lock")
+    }
+
+    fn find(&self, namespace: &str, name: &str, version: Option<&str>) -> Vec<GetSchemaResponse> {
+        self.lock_schemas()
+            .values()
+            .filter(|s| {
+                s.metadata.namespace == namespace
+                    && s.metadata.name == name
+                    && version.map_or(true, |v| s.metadata.version == v)
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+fn not_found(target: &str) -> SchemaRegistryError {
+    SchemaRegistryError::SchemaNotFound(target.to_string())
+}
+
+#[async_trait]
+impl SchemaRegistryApi for MockSchemaRegistryClient {
+    async fn register_schema(&self, schema: Schema) -> Result<RegisterSchemaResponse> {
+        self.record(
+            Method::RegisterSchema,
+            format!("{}@{}", schema.full_name(), schema.version),
+        )?;
+        if let Some(f) = &self.responders.register_schema {
+            return f(schema);
+        }
+
+        if let Some(existing) = self
+            .find(&schema.namespace, &schema.name, Some(&schema.version))
+            .into_iter()
+            .next()
+        {
+            return Ok(RegisterSchemaResponse {
+                schema_id: existing.metadata.schema_id,
+                namespace: schema.namespace,
+                name: schema.name,
+                version: schema.version,
+                created: false,
+            });
+        }
+
+        let global_id = self.registered.fetch_add(1, Ordering::SeqCst) + 1;
+        let schema_id = format!("mock-{global_id}");
+        self.lock_schemas().insert(
+            schema_id.clone(),
+            GetSchemaResponse {
+                metadata: SchemaMetadata {
+                    schema_id: schema_id.clone(),
+                    global_id: i64::try_from(global_id).ok(),
+                    namespace: schema.namespace.clone(),
+                    name: schema.name.clone(),
+                    version: schema.version.clone(),
+                    format: schema.format,
+                    created_at: None,
+                    updated_at: None,
+                    tags: schema.metadata,
+                },
+                content: schema.content,
+            },
+        );
+        Ok(RegisterSchemaResponse {
+            schema_id,
+            namespace: schema.namespace,
+            name: schema.name,
+            version: schema.version,
+            created: true,
+        })
+    }
+
+    async fn get_schema(&self, schema_id: &str) -> Result<GetSchemaResponse> {
+        self.record(Method::GetSchema, schema_id)?;
+        self.lock_schemas()
+            .get(schema_id)
+            .cloned()
+            .ok_or_else(|| not_found(schema_id))
+    }
+
+    async fn get_schema_by_version(
+        &self,
+        namespace: &str,
+        name: &str,
+        version: &str,
+    ) -> Result<GetSchemaResponse> {
+        let target = format!("{namespace}.{name}@{version}");
+        self.record(Method::GetSchemaByVersion, target.clone())?;
+        self.find(namespace, name, Some(version))
+            .into_iter()
+            .next()
+            .ok_or_else(|| not_found(&target))
+    }
+
+    async fn validate_data(&self, schema_id: &str, data: &str) -> Result<ValidateResponse> {
+        self.record(Method::ValidateData, schema_id)?;
+        if let Some(f) = &self.responders.validate_data {
+            return f(schema_id, data);
+        }
+
+        if !self.lock_schemas().contains_key(schema_id) {
+            return Err(not_found(schema_id));
+        }
+        Ok(match serde_json::from_str::<serde_json::Value>(data) {
+            Ok(_) => ValidateResponse {
+                is_valid: true,
+                errors: None,
+            },
+            Err(e) => ValidateResponse {
+                is_valid: false,
+                errors: Some(vec![e.to_string()]),
+            },
+        })
+    }
+
+    async fn check_compatibility(
+        &self,
+        schema: Schema,
+        mode: CompatibilityMode,
+    ) -> Result<CompatibilityResult> {
+        self.record(
+            Method::CheckCompatibility,
+            format!("{}@{}", schema.full_name(), schema.version),
+        )?;
+        if let Some(f) = &self.responders.check_compatibility {
+            return f((schema, mode));
+        }
+
+        Ok(CompatibilityResult {
+            is_compatible: true,
+            mode,
+            details: None,
+        })
+    }
+
+    async fn list_versions(&self, namespace: &str, name: &str) -> Result<ListVersionsResponse> {
+        self.record(Method::ListVersions, format!("{namespace}.{name}"))?;
+        let versions = self
+            .find(namespace, name, None)
+            .into_iter()
+            .map(|s| SchemaVersion {
+                version: s.metadata.version,
+                schema_id: s.metadata.schema_id,
+                created_at: s.metadata.created_at.unwrap_or_default(),
+            })
+            .collect();
+        Ok(ListVersionsResponse {
+            namespace: namespace.to_string(),
+            name: name.to_string(),
+            versions,
+        })
+    }
+
+    async fn search_schemas(&self, query: SearchQuery) -> Result<SearchResponse> {
+        self.record(Method::SearchSchemas, query.query.clone())?;
+        if let Some(f) = &self.responders.search_schemas {
+            return f(query);
+        }
+
+        let text = query.query.to_lowercase();
+        let matches: Vec<SearchResult> = self
+            .lock_schemas()
+            .values()
+            .filter(|s| {
+                let full_name = format!("{}.{}", s.metadata.namespace, s.metadata.name);
+                full_name.to_lowercase().contains(&text)
+                    && query.namespace.as_ref().map_or(true, |ns| &s.metadata.namespace == ns)
+                    && query.format.map_or(true, |f| s.metadata.format == f)
+            })
+            .map(|s| SearchResult {
+                metadata: s.metadata.clone(),
+                score: 1.0,
+            })
+            .collect();
+
+        let total = u32::try_from(matches.len()).unwrap_or(u32::MAX);
+        let offset = query.offset.unwrap_or(0) as usize;
+        let limit = query.limit.unwrap_or(10) as usize;
+        Ok(SearchResponse {
+            results: matches.into_iter().skip(offset).take(limit).collect(),
+            total,
+        })
+    }
+
+    async fn delete_schema(&self, schema_id: &str) -> Result<()> {
+        self.record(Method::DeleteSchema, schema_id)?;
+        self.lock_schemas()
+            .remove(schema_id)
+            .map(|_| ())
+            .ok_or_else(|| not_found(schema_id))
+    }
+
+    async fn health_check(&self) -> Result<HealthCheckResponse> {
+        self.record(Method::HealthCheck, "")?;
+        Ok(HealthCheckResponse {
+            status: "healthy".to_string(),
+            version: None,
+            info: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    /// Downstream code written against the trait
+    async fn register_if_compatible(api: &dyn SchemaRegistryApi, schema: Schema) -> Result<Option<String>> {
+        let result = api
+            .check_compatibility(schema.clone(), CompatibilityMode::Backward)
+            .await?;
+        if !result.is_compatible() {
+            return Ok(None);
+        }
+        Ok(Some(api.register_schema(schema).await?.schema_id))
+    }
+
+    fn event(version: &str) -> Schema {
+        Schema::new("telemetry", "InferenceEvent", version, SchemaFormat::JsonSchema, "{}")
+    }
+
+    #[tokio::test]
+    async fn test_default_behaviour_acts_like_a_registry() {
+        let mock = MockSchemaRegistryClient::new()
+            .with_schema("s1", "telemetry", "InferenceEvent", "1.0.0", SchemaFormat::JsonSchema, "{}")
+            .expect(Method::RegisterSchema, 2);
+
+        let id = register_if_compatible(&mock, event("2.0.0")).await.unwrap().unwrap();
+        let again = mock.register_schema(event("2.0.0")).await.unwrap();
+        assert_eq!(again.schema_id, id);
+        assert!(!again.created);
+
+        let versions = mock.list_versions("telemetry", "InferenceEvent").await.unwrap();
+        assert_eq!(versions.versions.len(), 2);
+        assert_eq!(mock.search_schemas(SearchQuery::new("inference")).await.unwrap().total, 2);
+        assert!(!mock.validate_data("s1", "not json").await.unwrap().is_valid());
+
+        mock.delete_schema(&id).await.unwrap();
+        assert!(mock.get_schema(&id).await.is_err());
+        mock.verify();
+    }
+
+    #[tokio::test]
+    async fn test_programmed_responses_and_failures() {
+        let mock = Arc::new(
+            MockSchemaRegistryClient::new()
+                .on_check_compatibility(|_, mode| {
+                    Ok(CompatibilityResult {
+                        is_compatible: false,
+                        mode,
+                        details: Some(vec!["field removed".to_string()]),
+                    })
+                })
+                .fail(Method::HealthCheck, || {
+                    SchemaRegistryError::ServerError {
+                        status: 503,
+                        message: "unavailable".to_string(),
+                    }
+                }),
+        );
+        let api: Arc<dyn SchemaRegistryApi> = mock.clone();
+
+        assert_eq!(register_if_compatible(api.as_ref(), event("2.0.0")).await.unwrap(), None);
+        assert!(api.health_check().await.unwrap_err().is_retryable());
+        assert_eq!(mock.call_count(Method::RegisterSchema), 0);
+        assert_eq!(
+            mock.calls()[0],
+            MockCall {
+                method: Method::CheckCompatibility,
+                target: "telemetry.InferenceEvent@2.0.0".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "ValidateData: expected 1 call(s), got 0")]
+    fn test_verify_reports_unmet_expectations() {
+        MockSchemaRegistryClient::new()
+            .expect(Method::ValidateData, 1)
+            .verify();
+    }
+}
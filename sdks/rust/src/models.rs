@@ -3,6 +3,7 @@
 //! This module contains all the data structures used to interact with the Schema Registry API,
 //! including schemas, metadata, validation results, and compatibility information.
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -49,11 +50,30 @@ pub enum CompatibilityMode {
     None,
 }
 
+/// Schema lifecycle states.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SchemaState {
+    /// Registered but not yet in use
+    Draft,
+    /// In active use
+    Active,
+    /// Still served, but should no longer be used
+    Deprecated,
+    /// Kept for history only
+    Archived,
+    /// Held for security review
+    Quarantined,
+}
+
 /// Schema metadata containing administrative information.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct SchemaMetadata {
     /// Unique schema identifier
     pub schema_id: String,
+    /// Integer ID embedded in Kafka messages using the standard wire format
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub global_id: Option<i64>,
     /// Schema namespace
     pub namespace: String,
     /// Schema name
@@ -227,6 +247,34 @@ pub struct SchemaVersion {
     pub created_at: String,
 }
 
+/// One version of a subject and the state it is in.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubjectVersionStatus {
+    /// Schema ID for this version
+    pub id: String,
+    /// Subject, e.g. `telemetry.InferenceEvent`
+    pub subject: String,
+    /// Version string
+    pub version: String,
+    /// Lifecycle state, e.g. `ACTIVE`; the registry has more states than
+    /// [`SchemaState`], so it is kept as sent
+    pub state: String,
+    /// Creation timestamp
+    pub created_at: String,
+    /// When waiting for a state, whether the version was in it when the
+    /// registry answered
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reached: Option<bool>,
+}
+
+impl SubjectVersionStatus {
+    /// Returns true if the version can no longer change state.
+    #[must_use]
+    pub fn is_terminal(&self) -> bool {
+        matches!(self.state.as_str(), "ARCHIVED" | "ABANDONED")
+    }
+}
+
 /// Response from listing schema versions.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ListVersionsResponse {
@@ -239,16 +287,39 @@ pub struct ListVersionsResponse {
 }
 
 /// Search query for schemas.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// Use [`SearchQuery::builder`] to combine filters; see [`crate::search`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SearchQuery {
-    /// Search query string
+    /// Search query string (empty to match every schema)
     pub query: String,
     /// Optional namespace filter
     #[serde(skip_serializing_if = "Option::is_none")]
     pub namespace: Option<String>,
+    /// Namespace glob, e.g. `telemetry.*`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub namespace_pattern: Option<String>,
+    /// Schemas must carry all of these tags
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Lifecycle state filter
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<SchemaState>,
+    /// Schema format filter
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<SchemaFormat>,
+    /// Only schemas created at or after this time
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_after: Option<DateTime<Utc>>,
+    /// Only schemas created before this time
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_before: Option<DateTime<Utc>>,
     /// Maximum number of results (default: 10, max: 100)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub limit: Option<u32>,
+    /// Number of results to skip
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u32>,
 }
 
 impl SearchQuery {
@@ -256,11 +327,15 @@ impl SearchQuery {
     pub fn new(query: impl Into<String>) -> Self {
         Self {
             query: query.into(),
-            namespace: None,
-            limit: None,
+            ..Self::default()
         }
     }
 
+    /// Creates a builder for a filtered query.
+    pub fn builder() -> crate::search::SearchQueryBuilder {
+        crate::search::SearchQueryBuilder::new()
+    }
+
     /// Sets the namespace filter.
     pub fn with_namespace(mut self, namespace: impl Into<String>) -> Self {
         self.namespace = Some(namespace.into());
@@ -292,6 +367,44 @@ pub struct SearchResponse {
     pub total: u32,
 }
 
+/// What happened to a schema, as reported by the change feed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ChangeKind {
+    /// The schema was registered
+    Created,
+    /// The schema's state, subject or content changed
+    Updated,
+    /// The schema was deleted
+    Deleted,
+}
+
+/// One entry of the registry's change feed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaChange {
+    /// Position in the feed; later changes have higher sequences
+    pub sequence: i64,
+    /// Schema that changed
+    pub schema_id: String,
+    /// Subject of the schema after the change
+    pub subject: String,
+    /// What happened
+    pub change: ChangeKind,
+    /// When it happened
+    pub changed_at: DateTime<Utc>,
+}
+
+/// A page of the change feed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangesResponse {
+    /// Changes after the requested cursor, oldest first
+    pub changes: Vec<SchemaChange>,
+    /// Cursor to request the following changes with
+    pub cursor: i64,
+    /// Whether more changes follow this page
+    pub has_more: bool,
+}
+
 /// Health check response.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HealthCheckResponse {
@@ -0,0 +1,157 @@
+//! Typed builder for schema search queries.
+//!
+//! [`SearchQueryBuilder`] exposes every filter the server's search endpoint
+//! understands, and nothing else, so a built query is never rejected for an
+//! unsupported option:
+//!
+//! ```
+//! use llm_schema_registry_sdk::{SchemaFormat, SchemaState, SearchQuery};
+//!
+//! let query = SearchQuery::builder()
+//!     .text("inference")
+//!     .namespace_glob("telemetry.*")
+//!     .state(SchemaState::Active)
+//!     .format(SchemaFormat::JsonSchema)
+//!     .page_size(50)
+//!     .build();
+//! ```
+//!
+//! Pass the query to
+//! [`SchemaRegistryClient::search_all`](crate::SchemaRegistryClient::search_all)
+//! to stream every match across pages.
+
+use crate::models::{SchemaFormat, SchemaState, SearchQuery};
+use chrono::{DateTime, Utc};
+
+/// Largest page the server returns.
+pub const MAX_PAGE_SIZE: u32 = 100;
+
+/// Fluent builder for [`SearchQuery`].
+#[derive(Debug, Clone, Default)]
+#[must_use]
+pub struct SearchQueryBuilder {
+    query: SearchQuery,
+}
+
+impl SearchQueryBuilder {
+    /// Creates a builder matching every schema.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Matches `text` against names, namespaces, descriptions and content.
+    pub fn text(mut self, text: impl Into<String>) -> Self {
+        self.query.query = text.into();
+        self
+    }
+
+    /// Only schemas in exactly this namespace.
+    pub fn namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.query.namespace = Some(namespace.into());
+        self
+    }
+
+    /// Only schemas whose namespace matches `pattern`, e.g. `telemetry.*`.
+    pub fn namespace_glob(mut self, pattern: impl Into<String>) -> Self {
+        self.query.namespace_pattern = Some(pattern.into());
+        self
+    }
+
+    /// Only schemas carrying `tag`; repeat to require several tags.
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.query.tags.push(tag.into());
+        self
+    }
+
+    /// Only schemas carrying all of `tags`.
+    pub fn tags<I, T>(mut self, tags: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        self.query.tags.extend(tags.into_iter().map(Into::into));
+        self
+    }
+
+    /// Only schemas in this lifecycle state.
+    pub fn state(mut self, state: SchemaState) -> Self {
+        self.query.state = Some(state);
+        self
+    }
+
+    /// Only schemas in this format.
+    pub fn format(mut self, format: SchemaFormat) -> Self {
+        self.query.format = Some(format);
+        self
+    }
+
+    /// Only schemas created at or after `after`.
+    pub fn created_after(mut self, after: DateTime<Utc>) -> Self {
+        self.query.created_after = Some(after);
+        self
+    }
+
+    /// Only schemas created before `before`.
+    pub fn created_before(mut self, before: DateTime<Utc>) -> Self {
+        self.query.created_before = Some(before);
+        self
+    }
+
+    /// Only schemas created in `[start, end)`; the bounds may be given in
+    /// either order.
+    pub fn created_between(self, start: DateTime<Utc>, end: DateTime<Utc>) -> Self {
+        let (start, end) = if start <= end { (start, end) } else { (end, start) };
+        self.created_after(start).created_before(end)
+    }
+
+    /// Results per page, capped at [`MAX_PAGE_SIZE`].
+    pub fn page_size(mut self, size: u32) -> Self {
+        self.query.limit = Some(size.clamp(1, MAX_PAGE_SIZE));
+        self
+    }
+
+    /// Starts at this result instead of the first.
+    pub fn offset(mut self, offset: u32) -> Self {
+        self.query.offset = Some(offset);
+        self
+    }
+
+    /// Builds the query.
+    pub fn build(self) -> SearchQuery {
+        self.query
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_builder_sets_filters() {
+        let start = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+        let end = Utc.with_ymd_and_hms(2025, 2, 1, 0, 0, 0).unwrap();
+
+        let query = SearchQueryBuilder::new()
+            .text("token usage")
+            .namespace_glob("telemetry.*")
+            .tags(["llm", "billing"])
+            .state(SchemaState::Active)
+            .format(SchemaFormat::Avro)
+            .created_between(end, start)
+            .page_size(500)
+            .build();
+
+        assert_eq!(query.query, "token usage");
+        assert_eq!(query.tags, vec!["llm", "billing"]);
+        assert_eq!((query.created_after, query.created_before), (Some(start), Some(end)));
+        assert_eq!(query.limit, Some(MAX_PAGE_SIZE));
+    }
+
+    #[test]
+    fn test_unset_filters_are_not_sent() {
+        let json = serde_json::to_value(SearchQueryBuilder::new().text("user").build()).unwrap();
+
+        assert_eq!(json, serde_json::json!({"query": "user"}));
+    }
+}
@@ -8,6 +8,11 @@ const axios_1 = __importDefault(require("axios"));
 const axios_retry_1 = __importDefault(require("axios-retry"));
 const lru_cache_1 = require("lru-cache");
 const errors_1 = require("./errors");
+/**
+ * SDK name and version sent in the `X-SDK-Version` header, so the registry
+ * can report which SDK releases are in use; keep in step with package.json
+ */
+const SDK_VERSION = 'typescript/0.1.0';
 /**
  * TypeScript client for the LLM Schema Registry
  */
@@ -18,6 +23,7 @@ class SchemaRegistryClient {
             timeout: config.timeout || 30000,
             headers: {
                 'Content-Type': 'application/json',
+                'X-SDK-Version': SDK_VERSION,
                 ...(config.apiKey && { 'X-API-Key': config.apiKey }),
             },
         });
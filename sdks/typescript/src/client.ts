@@ -19,6 +19,12 @@ import {
   RateLimitError,
 } from './errors';
 
+/**
+ * SDK name and version sent in the `X-SDK-Version` header, so the registry
+ * can report which SDK releases are in use; keep in step with package.json
+ */
+const SDK_VERSION = 'typescript/0.1.0';
+
 /**
  * TypeScript client for the LLM Schema Registry
  */
@@ -32,6 +38,7 @@ export class SchemaRegistryClient {
       timeout: config.timeout || 30000,
       headers: {
         'Content-Type': 'application/json',
+        'X-SDK-Version': SDK_VERSION,
         ...(config.apiKey && { 'X-API-Key': config.apiKey }),
       },
     });
@@ -499,6 +499,755 @@ async fn test_api_search() {
     assert_eq!(results.len(), 3);
 }
 
+#[tokio::test]
+async fn test_search_matches_wildcards_literally() {
+    let env = TestEnvironment::new().await.unwrap();
+    let server = env.start_server().await.unwrap();
+    for subject in ["wildcards.Order", "wildcards.order_line"] {
+        server
+            .register_schema(subject, "JSON", json!({"type": "object"}))
+            .await
+            .unwrap();
+    }
+
+    for (query, expected) in [
+        ("_", vec!["order_line"]),
+        ("%", vec![]),
+        ("order", vec!["Order", "order_line"]),
+    ] {
+        let response = server
+            .post_json(
+                "/api/v1/schemas/search",
+                &json!({"query": query, "namespace": "wildcards"}),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status().as_u16(), 200);
+        let body: serde_json::Value = response.json().await.unwrap();
+        let mut names: Vec<&str> = body["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|result| result["metadata"]["name"].as_str().unwrap())
+            .collect();
+        names.sort();
+        assert_eq!(names, expected, "query {:?}", query);
+    }
+}
+
+#[tokio::test]
+async fn test_register_rejects_unknown_compatibility_mode() {
+    let env = TestEnvironment::new().await.unwrap();
+    let server = env.start_server().await.unwrap();
+
+    let register = |subject: &str, mode: &str| {
+        json!({
+            "subject": subject,
+            "schema_type": "JSON",
+            "schema": {"type": "object"},
+            "compatibility_mode": mode,
+        })
+    };
+
+    let response = server
+        .post_json("/api/v1/schemas", &register("com.modes.Strict", "SIDEWAYS"))
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 400);
+    let body: serde_json::Value = response.json().await.unwrap();
+    let error = body["error"].as_str().unwrap();
+    assert!(error.contains("unknown compatibility mode 'SIDEWAYS'"));
+    assert!(error.contains("BACKWARD_TRANSITIVE"));
+
+    // People may spell modes however they like
+    let response = server
+        .post_json("/api/v1/schemas", &register("com.modes.Lenient", "full-transitive"))
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 201);
+    let id = response.json::<serde_json::Value>().await.unwrap()["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let schema: serde_json::Value = server
+        .client()
+        .get(server.url(&format!("/api/v1/schemas/{}", id)))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(schema["compatibility_mode"], "FULL_TRANSITIVE");
+}
+
+#[tokio::test]
+async fn test_get_schema_returns_only_selected_fields() {
+    let env = TestEnvironment::new().await.unwrap();
+    let server = env.start_server().await.unwrap();
+
+    let response = server
+        .post_json(
+            "/api/v1/schemas",
+            &json!({
+                "subject": "com.fields.Order",
+                "schema_type": "JSON",
+                "schema": {"type": "object", "properties": {"id": {"type": "string"}}},
+                "metadata": {"owner": "payments", "team": "checkout"},
+            }),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 201);
+    let id = response.json::<serde_json::Value>().await.unwrap()["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    // Twice, so the second read comes from the cache
+    for _ in 0..2 {
+        let schema: serde_json::Value = server
+            .client()
+            .get(server.url(&format!(
+                "/api/v1/schemas/{}?fields=id,version,state,metadata.owner",
+                id
+            )))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(
+            schema,
+            json!({
+                "id": id,
+                "version": "1.0.0",
+                "state": "DRAFT",
+                "metadata": {"owner": "payments"},
+            })
+        );
+    }
+
+    let full: serde_json::Value = server
+        .client()
+        .get(server.url(&format!("/api/v1/schemas/{}", id)))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(full["content"].is_string());
+    assert_eq!(full["metadata"]["team"], "checkout");
+
+    let response = server
+        .client()
+        .get(server.url(&format!("/api/v1/schemas/{}?fields=id,owner", id)))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 400);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert!(body["error"].as_str().unwrap().contains("unknown field 'owner'"));
+}
+
 // Add 20+ more API test cases for comprehensive coverage
 // Including: bulk create, bulk delete, versioning, metadata queries,
 // compatibility checks, validation workflows, caching, etc.
+
+#[tokio::test]
+async fn test_admin_adjusts_trace_sampling_at_runtime() {
+    use schema_registry_observability::{ExporterConfig, SamplingControl};
+
+    let env = TestEnvironment::new().await.unwrap();
+    let sampling = SamplingControl::new(&[
+        ExporterConfig::otlp("http://collector:4317", 0.1),
+        ExporterConfig::stdout(0.0),
+    ]);
+    let server = env
+        .start_server_with(|config| {
+            config.admin_token = Some("tracing-admin-token".to_string());
+            config.trace_sampling = Some(sampling.clone());
+        })
+        .await
+        .unwrap();
+    let url = server.url("/api/v1/admin/tracing/sampling");
+
+    let response = server
+        .client()
+        .put(&url)
+        .bearer_auth("tracing-admin-token")
+        .json(&json!({"exporters": {"stdout": 1.0}}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["exporters"], json!({"otlp": 0.1, "stdout": 1.0}));
+    assert_eq!(sampling.rates()["stdout"], 1.0);
+
+    // An unknown exporter rejects the whole update
+    let response = server
+        .client()
+        .put(&url)
+        .bearer_auth("tracing-admin-token")
+        .json(&json!({"exporters": {"otlp": 0.5, "zipkin": 0.5}}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 400);
+    assert_eq!(sampling.rates()["otlp"], 0.1);
+
+    let response = server.client().get(&url).send().await.unwrap();
+    assert_eq!(response.status().as_u16(), 401);
+}
+
+#[tokio::test]
+async fn test_schema_in_use_is_only_retired_with_force_and_reason() {
+    let env = TestEnvironment::new().await.unwrap();
+    let server = env
+        .start_server_with(|config| config.admin_token = Some("retire-admin-token".to_string()))
+        .await
+        .unwrap();
+
+    let registered = server
+        .register_schema("retire.Order", "JSON", json!({"type": "object"}))
+        .await
+        .unwrap();
+    let id = registered["id"].as_str().unwrap().to_string();
+    let response = server
+        .client()
+        .post(server.url("/api/v1/lineage/edges"))
+        .bearer_auth("retire-admin-token")
+        .json(&json!({
+            "from": "retire.Order",
+            "entity": {"id": "checkout", "type": "APPLICATION"},
+            "relation": "USED_BY",
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 201);
+
+    let schema_url = server.url(&format!("/api/v1/schemas/{}", id));
+    let usage_url = server.url(&format!("/api/v1/schemas/{}/usage", id));
+    let response = server
+        .client()
+        .get(&schema_url)
+        .header("x-client-id", "checkout")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+
+    // Usage events are recorded in the background
+    let mut usage = serde_json::Value::Null;
+    for _ in 0..50 {
+        usage = server
+            .client()
+            .get(&usage_url)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        if usage["usage"]["total_operations"] == 1 {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    assert_eq!(usage["usage"]["total_operations"], 1);
+    assert_eq!(usage["usage"]["clients"], json!(["checkout"]));
+    assert_eq!(usage["consumers"][0]["name"], "checkout");
+
+    let response = server
+        .client()
+        .delete(&schema_url)
+        .bearer_auth("retire-admin-token")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 409);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["code"], "SCHEMA_IN_USE");
+    assert_eq!(body["usage"]["clients"], json!(["checkout"]));
+    assert_eq!(body["consumers"][0]["relation"], "USED_BY");
+
+    // Forcing needs a reason for the audit log
+    let response = server
+        .client()
+        .delete(format!("{}?force=true", schema_url))
+        .bearer_auth("retire-admin-token")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 400);
+
+    let response = server
+        .client()
+        .delete(format!("{}?force=true&reason=replaced+by+v2", schema_url))
+        .bearer_auth("retire-admin-token")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["forced"], true);
+    assert_eq!(body["state"], "DELETED");
+
+    let response = server.client().get(&schema_url).send().await.unwrap();
+    assert_eq!(response.status().as_u16(), 404);
+
+    // An unused schema is archived without force, once
+    let registered = server
+        .register_schema("retire.Refund", "JSON", json!({"type": "object"}))
+        .await
+        .unwrap();
+    let archive_url = server.url(&format!(
+        "/api/v1/schemas/{}/archive",
+        registered["id"].as_str().unwrap()
+    ));
+    let response = server
+        .client()
+        .post(&archive_url)
+        .bearer_auth("retire-admin-token")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["state"], "ARCHIVED");
+    assert_eq!(body["forced"], false);
+
+    let response = server
+        .client()
+        .post(&archive_url)
+        .bearer_auth("retire-admin-token")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 409);
+}
+
+#[tokio::test]
+async fn test_capacity_history_counts_stored_versions() {
+    let env = TestEnvironment::new().await.unwrap();
+    let server = env
+        .start_server_with(|config| {
+            config.admin_token = Some("capacity-admin-token".to_string());
+        })
+        .await
+        .unwrap();
+    for subject in ["capacity.Order", "capacity.Refund"] {
+        server
+            .register_schema(subject, "JSON", json!({"type": "object"}))
+            .await
+            .unwrap();
+    }
+    let url = server.url("/api/v1/admin/capacity/history?lookback_days=7");
+
+    let response = server.client().get(&url).send().await.unwrap();
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = server
+        .client()
+        .get(&url)
+        .bearer_auth("capacity-admin-token")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    let history: serde_json::Value = response.json().await.unwrap();
+    // Registered today, so only today's sample has them
+    let schemas = &history["schemas_per_namespace"]["capacity"];
+    assert_eq!(schemas, &json!([{"at": schemas[0]["at"], "value": 2.0}]));
+    let storage = history["storage_bytes"].as_array().unwrap();
+    assert!(storage.last().unwrap()["value"].as_f64().unwrap() > 0.0);
+}
+
+#[tokio::test]
+async fn test_usage_analytics_routes() {
+    let env = TestEnvironment::new().await.unwrap();
+    let server = env.start_server().await.unwrap();
+    let registered = server
+        .register_schema("usage.Order", "JSON", json!({"type": "object"}))
+        .await
+        .unwrap();
+    let id = registered["id"].as_str().unwrap();
+
+    let response = server
+        .get("/api/v1/analytics/subjects/usage.Order/health")
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 404);
+
+    server
+        .get(&format!("/api/v1/schemas/{}", id))
+        .await
+        .unwrap();
+
+    // Usage events are aggregated in the background
+    let mut top = serde_json::Value::Null;
+    for _ in 0..50 {
+        top = server
+            .get("/api/v1/analytics/top?operation=READ&limit=5")
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        if top.as_array().is_some_and(|entries| !entries.is_empty()) {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    assert_eq!(top[0]["schema_id"], id);
+    assert_eq!(top[0]["rank"], 1);
+
+    let response = server
+        .get("/api/v1/analytics/subjects/usage.Order/health")
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    let health: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(health["schema_id"], id);
+
+    let response = server
+        .get("/api/v1/analytics/anomalies?hours=24")
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(response
+        .json::<serde_json::Value>()
+        .await
+        .unwrap()
+        .is_array());
+}
+
+#[tokio::test]
+async fn test_slow_validations_are_logged() {
+    let env = TestEnvironment::new().await.unwrap();
+    let server = env
+        .start_server_with(|config| {
+            config.slow_operation_threshold = std::time::Duration::ZERO;
+        })
+        .await
+        .unwrap();
+    let registered = server
+        .register_schema("slowops.Order", "JSON", json!({"type": "object"}))
+        .await
+        .unwrap();
+    let id = registered["id"].as_str().unwrap();
+
+    let response = server
+        .post_json(&format!("/api/v1/validate/{}", id), &json!({"id": "o-1"}))
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+
+    // The row is written in the background
+    let url = server.url(&format!("/api/v1/operations/slow?schema_id={}", id));
+    let mut logged = serde_json::Value::Null;
+    for _ in 0..50 {
+        logged = server
+            .client()
+            .get(&url)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        if !logged["operations"].as_array().unwrap().is_empty() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    assert_eq!(logged["threshold_ms"], 0);
+    assert_eq!(logged["operations"][0]["operation"], "validation");
+    assert_eq!(logged["operations"][0]["subject"], "slowops.Order");
+    assert_eq!(logged["schemas"][0]["count"], 1);
+}
+
+#[tokio::test]
+async fn test_normalize_policy_stores_canonical_content() {
+    let env = TestEnvironment::new().await.unwrap();
+    let server = env
+        .start_server_with(|config| {
+            config.admin_token = Some("normalize-admin-token".to_string());
+        })
+        .await
+        .unwrap();
+    let config_url = server.url("/api/v1/config/subjects");
+
+    let response = server
+        .client()
+        .post(server.url("/api/v1/admin/config/subjects"))
+        .bearer_auth("normalize-admin-token")
+        .json(&json!({"subjects": {"normalized": {"normalize": true}}}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = server.client().get(&config_url).send().await.unwrap();
+    assert_eq!(response.status().as_u16(), 401);
+    let exported: serde_json::Value = server
+        .client()
+        .get(&config_url)
+        .bearer_auth("normalize-admin-token")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(exported["subjects"]["normalized"]["normalize"], true);
+
+    let content = r#"{ "type": "object",  "title": "Event" }"#;
+    let mut ids = Vec::new();
+    for subject in ["normalized.Event", "verbatim.Event"] {
+        let response = server
+            .post_json(
+                "/api/v1/schemas",
+                &json!({
+                    "subject": subject,
+                    "schema": {"type": "object"},
+                    "schema_type": "JSON",
+                    "content": content,
+                }),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status().as_u16(), 201);
+        let body: serde_json::Value = response.json().await.unwrap();
+        ids.push(body["id"].as_str().unwrap().to_string());
+    }
+
+    let mut stored = Vec::new();
+    for id in &ids {
+        let schema: serde_json::Value = server
+            .get(&format!("/api/v1/schemas/{}", id))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        stored.push(schema["content"].as_str().unwrap().to_string());
+    }
+    assert_eq!(stored[0], r#"{"title":"Event","type":"object"}"#);
+    assert_eq!(stored[1], content);
+}
+
+#[tokio::test]
+async fn test_metadata_patch_requires_credentials_and_keeps_reserved_keys() {
+    let env = TestEnvironment::new().await.unwrap();
+    let server = env
+        .start_server_with(|config| {
+            config.admin_token = Some("metadata-admin-token".to_string());
+        })
+        .await
+        .unwrap();
+    let registered = server
+        .register_schema("metadata.Order", "JSON", json!({"type": "object"}))
+        .await
+        .unwrap();
+    let url = server.url(&format!(
+        "/api/v1/schemas/{}/metadata",
+        registered["id"].as_str().unwrap()
+    ));
+
+    let response = server
+        .client()
+        .patch(&url)
+        .json(&json!({"description": "Orders"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 401);
+
+    for key in ["owner", "deletion"] {
+        let response = server
+            .client()
+            .patch(&url)
+            .bearer_auth("metadata-admin-token")
+            .json(&json!({"metadata": {key: null}}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status().as_u16(), 400, "{}", key);
+    }
+
+    let response = server
+        .client()
+        .patch(&url)
+        .bearer_auth("metadata-admin-token")
+        .json(&json!({"metadata": {"team": "sales", "region": {"z": 1, "a": 2}}}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    let body = response.text().await.unwrap();
+    assert!(
+        body.contains(r#""metadata":{"region":{"a":2,"z":1},"team":"sales"}"#),
+        "{}",
+        body
+    );
+}
+
+#[tokio::test]
+async fn test_feature_usage_counts_only_known_sdks_for_admins() {
+    let env = TestEnvironment::new().await.unwrap();
+    let server = env
+        .start_server_with(|config| {
+            config.admin_token = Some("features-admin-token".to_string());
+        })
+        .await
+        .unwrap();
+    // Counted whatever the request's outcome
+    for sdk in [
+        "rust/0.1.0",
+        "rust/0.1.7",
+        "rust/0.2.0-beta.1",
+        "made-up/9.9.9",
+        "python/latest",
+    ] {
+        server
+            .client()
+            .get(server.url("/api/v1/changes"))
+            .header("x-sdk-version", sdk)
+            .send()
+            .await
+            .unwrap();
+    }
+    let url = server.url("/api/v1/analytics/features");
+
+    let response = server.client().get(&url).send().await.unwrap();
+    assert_eq!(response.status().as_u16(), 401);
+
+    let report: serde_json::Value = server
+        .client()
+        .get(&url)
+        .bearer_auth("features-admin-token")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let sdks = report["dimensions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|d| d["dimension"] == "sdk_version")
+        .unwrap();
+    let uses = |value: &str| {
+        sdks["values"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|v| v["value"] == value)
+            .map(|v| v["uses"].as_u64().unwrap())
+    };
+    // Patch releases share their release line's count
+    assert_eq!(uses("rust/0.1"), Some(2));
+    assert_eq!(uses("rust/0.2"), Some(1));
+    assert_eq!(uses("rust/0.1.0"), None);
+    assert_eq!(uses("other"), Some(2));
+    assert_eq!(uses("made-up/9.9.9"), None);
+}
+
+#[tokio::test]
+async fn test_oversized_requests_are_refused_with_the_limit() {
+    let env = TestEnvironment::new().await.unwrap();
+    let server = env
+        .start_server_with(|config| config.max_request_size = 1024)
+        .await
+        .unwrap();
+    let oversized = json!({
+        "subject": "limits.Order",
+        "schema_type": "JSON",
+        "schema": {"type": "object", "description": "x".repeat(2048)},
+    });
+    let declared = serde_json::to_vec(&oversized).unwrap().len();
+
+    let response = server
+        .post_json("/api/v1/schemas", &oversized)
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 413);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(
+        body,
+        json!({
+            "error": format!(
+                "Request body is {} bytes, exceeding the limit of 1024 bytes",
+                declared
+            ),
+            "code": "request-size-exceeded",
+            "limit": 1024,
+            "actual": declared,
+        })
+    );
+
+    // Without a length the body is cut off at the limit, so its size is unknown
+    let chunks = futures::stream::iter(
+        serde_json::to_vec(&oversized)
+            .unwrap()
+            .chunks(256)
+            .map(|chunk| Ok::<_, std::io::Error>(chunk.to_vec()))
+            .collect::<Vec<_>>(),
+    );
+    let response = server
+        .client()
+        .post(server.url("/api/v1/schemas"))
+        .header("content-type", "application/json")
+        .body(reqwest::Body::wrap_stream(chunks))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 413);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["code"], "request-size-exceeded");
+    assert_eq!(body["limit"], 1024);
+    assert_eq!(body["actual"], serde_json::Value::Null);
+}
+
+#[tokio::test]
+async fn test_declared_oversized_bodies_are_refused_before_they_are_read() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let env = TestEnvironment::new().await.unwrap();
+    let server = env
+        .start_server_with(|config| config.max_request_size = 1024)
+        .await
+        .unwrap();
+
+    // Only the headers are sent; a server waiting for the 1 MiB body would
+    // never answer
+    let mut stream = tokio::net::TcpStream::connect(server.addr()).await.unwrap();
+    let head = format!(
+        "POST /api/v1/schemas HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: 1048576\r\n\r\n{{",
+        server.addr()
+    );
+    stream.write_all(head.as_bytes()).await.unwrap();
+    let mut response = Vec::new();
+    let answered = tokio::time::timeout(std::time::Duration::from_secs(5), async {
+        let mut buffer = [0; 1024];
+        while !response.ends_with(b"}") {
+            let read = stream.read(&mut buffer).await.unwrap();
+            if read == 0 {
+                break;
+            }
+            response.extend_from_slice(&buffer[..read]);
+        }
+    })
+    .await;
+    assert!(answered.is_ok(), "the server waited for the body");
+    let response = String::from_utf8_lossy(&response);
+    assert!(response.starts_with("HTTP/1.1 413"), "{}", response);
+    assert!(response.contains("\"actual\":1048576"), "{}", response);
+}
@@ -0,0 +1,142 @@
+//! Batch validation tests
+//!
+//! Records are read and validated as the body arrives, so these send bodies
+//! in chunks that split records, as a client streaming a large batch would.
+
+use super::*;
+use serde_json::{json, Value};
+
+async fn order_schema(server: &schema_registry_test_env::TestServer) -> String {
+    let registered = server
+        .register_schema(
+            "batch.Order",
+            "JSON",
+            json!({
+                "type": "object",
+                "required": ["id"],
+                "properties": {"id": {"type": "integer"}},
+            }),
+        )
+        .await
+        .unwrap();
+    registered["id"].as_str().unwrap().to_string()
+}
+
+/// Send `chunks` as a body without a `Content-Length`, one chunk at a time
+async fn post_chunked(
+    server: &schema_registry_test_env::TestServer,
+    path: &str,
+    content_type: &str,
+    chunks: Vec<String>,
+) -> reqwest::Response {
+    let chunks = futures::stream::iter(chunks.into_iter().map(Ok::<_, std::io::Error>));
+    server
+        .client()
+        .post(server.url(path))
+        .header("content-type", content_type)
+        .body(reqwest::Body::wrap_stream(chunks))
+        .send()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_batch_records_are_validated_one_by_one() {
+    let env = TestEnvironment::new().await.unwrap();
+    let server = env.start_server().await.unwrap();
+    let id = order_schema(&server).await;
+    let path = format!("/api/v1/validate/{}/batch", id);
+
+    let response = server
+        .post_json(&path, &json!([{"id": 1}, {"id": "x"}, {"id": 2}]))
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["complete"], true);
+    assert_eq!(body["summary"]["total"], 3);
+    assert_eq!(body["summary"]["valid"], 2);
+    assert_eq!(body["summary"]["invalid"], 1);
+    assert_eq!(body["results"].as_array().unwrap().len(), 3);
+    assert_eq!(body["results"][1]["index"], 1);
+    assert_eq!(body["results"][1]["is_valid"], false);
+
+    // Records split across chunks, one line that isn't JSON
+    let response = post_chunked(
+        &server,
+        &format!("{}?only_invalid=true", path),
+        "application/x-ndjson",
+        vec![
+            "{\"id\": 1}\n{\"i".to_string(),
+            "d\": \n\n{\"id\": \"x\"}\n{\"id\"".to_string(),
+            ": 2}\n".to_string(),
+        ],
+    )
+    .await;
+    assert_eq!(response.status().as_u16(), 200);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["summary"]["total"], 4);
+    assert_eq!(body["summary"]["valid"], 2);
+    assert_eq!(body["summary"]["invalid"], 2);
+    let invalid: Vec<&Value> = body["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|result| &result["index"])
+        .collect();
+    assert_eq!(invalid, vec![&json!(1), &json!(2)]);
+    assert!(body["results"][0]["errors"][0]
+        .as_str()
+        .unwrap()
+        .contains("Invalid JSON"));
+
+    let response = post_chunked(
+        &server,
+        &path,
+        "application/json",
+        vec!["[{\"id\": 1},".to_string(), " {\"id\": 2}]".to_string()],
+    )
+    .await;
+    assert_eq!(response.status().as_u16(), 200);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["summary"]["valid"], 2);
+}
+
+#[tokio::test]
+async fn test_batch_rejects_malformed_empty_and_oversized_bodies() {
+    let env = TestEnvironment::new().await.unwrap();
+    let server = env
+        .start_server_with(|config| {
+            config.max_request_size = 64 * 1024;
+        })
+        .await
+        .unwrap();
+    let id = order_schema(&server).await;
+    let path = format!("/api/v1/validate/{}/batch", id);
+
+    let malformed = post_chunked(
+        &server,
+        &path,
+        "application/json",
+        vec!["[{\"id\": 1}, {\"id\"".to_string()],
+    )
+    .await;
+    assert_eq!(malformed.status().as_u16(), 400);
+
+    let empty = server.post_json(&path, &json!([])).await.unwrap();
+    assert_eq!(empty.status().as_u16(), 400);
+
+    // Sent without a length, so the limit is only found while reading
+    let chunks = (0..8_000).map(|i| format!("{{\"id\": {}}}\n", i)).collect();
+    let oversized = post_chunked(&server, &path, "application/x-ndjson", chunks).await;
+    assert_eq!(oversized.status().as_u16(), 413);
+    let body: Value = oversized.json().await.unwrap();
+    assert_eq!(body["code"], "request-size-exceeded");
+    assert_eq!(body["limit"], 64 * 1024);
+
+    let chunks = vec!["{}\n".repeat(10_001)];
+    let too_many = post_chunked(&server, &path, "application/x-ndjson", chunks).await;
+    assert_eq!(too_many.status().as_u16(), 400);
+    let body: Value = too_many.json().await.unwrap();
+    assert!(body["error"].as_str().unwrap().contains("at most 10000"));
+}
@@ -0,0 +1,281 @@
+//! Change freeze tests
+//!
+//! Freeze windows reject changes to their namespaces until they end, unless
+//! a principal with the `freeze:override` permission gives a reason.
+
+use super::*;
+use serde_json::{json, Value};
+
+const ADMIN_TOKEN: &str = "change-freeze-admin-token";
+
+fn register_body(subject: &str) -> Value {
+    json!({
+        "subject": subject,
+        "schema_type": "JSON",
+        "schema": {"type": "object", "properties": {"id": {"type": "string"}}},
+    })
+}
+
+/// A window covering the next hour
+fn freeze_now(namespaces: &[&str], environments: &[&str]) -> Value {
+    let now = chrono::Utc::now();
+    json!({
+        "namespaces": namespaces,
+        "environments": environments,
+        "starts_at": now - chrono::Duration::minutes(1),
+        "ends_at": now + chrono::Duration::hours(1),
+        "reason": "Peak trading",
+    })
+}
+
+#[tokio::test]
+async fn test_changes_rejected_during_freeze_unless_overridden() {
+    let env = TestEnvironment::new().await.unwrap();
+    let server = env
+        .start_server_with(|config| {
+            config.admin_token = Some(ADMIN_TOKEN.to_string());
+            config.environment = Some("production".to_string());
+        })
+        .await
+        .unwrap();
+
+    let response = server
+        .client()
+        .put(server.url("/api/v1/admin/freezes/peak"))
+        .bearer_auth(ADMIN_TOKEN)
+        .json(&freeze_now(&["shop"], &["production"]))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+
+    let frozen = server
+        .post_json("/api/v1/schemas", &register_body("shop.orders.OrderPlaced"))
+        .await
+        .unwrap();
+    assert_eq!(frozen.status().as_u16(), 423);
+    let body: Value = frozen.json().await.unwrap();
+    assert_eq!(body["code"], "CHANGE_FREEZE");
+    assert_eq!(body["freeze"]["window"], "peak");
+    assert!(body["error"].as_str().unwrap().contains("Peak trading"));
+
+    // Other namespaces aren't frozen
+    server.register_schema("billing.Invoice", "JSON", json!({"type": "object"})).await.unwrap();
+
+    // Asking to override isn't enough without the permission
+    let denied = server
+        .client()
+        .post(server.url("/api/v1/schemas"))
+        .header("X-Freeze-Override", "INC-42 hotfix")
+        .json(&register_body("shop.orders.OrderPlaced"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(denied.status().as_u16(), 423);
+    assert_eq!(denied.json::<Value>().await.unwrap()["code"], "FREEZE_OVERRIDE_DENIED");
+
+    // A token nobody recognizes doesn't fall back to anonymous
+    let unknown = server
+        .client()
+        .post(server.url("/api/v1/schemas"))
+        .bearer_auth("not-the-admin-token")
+        .header("X-Freeze-Override", "INC-42 hotfix")
+        .json(&register_body("shop.orders.OrderPlaced"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(unknown.status().as_u16(), 401);
+
+    // Admins may change settings only by overriding too
+    let settings = server
+        .client()
+        .put(server.url("/api/v1/namespaces/shop/settings"))
+        .bearer_auth(ADMIN_TOKEN)
+        .json(&json!({"max_schemas": 10}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(settings.status().as_u16(), 423);
+
+    let overridden = server
+        .client()
+        .post(server.url("/api/v1/schemas"))
+        .bearer_auth(ADMIN_TOKEN)
+        .header("X-Freeze-Override", "INC-42 hotfix")
+        .json(&register_body("shop.orders.OrderPlaced"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(overridden.status().as_u16(), 201);
+
+    let windows: Value = server
+        .client()
+        .get(server.url("/api/v1/admin/freezes"))
+        .bearer_auth(ADMIN_TOKEN)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(windows["windows"][0]["name"], "peak");
+    assert!(windows["windows"][0]["in_force_until"].is_string());
+
+    let response = server
+        .client()
+        .delete(server.url("/api/v1/admin/freezes/peak"))
+        .bearer_auth(ADMIN_TOKEN)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 204);
+    server.register_schema("shop.Cart", "JSON", json!({"type": "object"})).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_freeze_file_windows_apply_only_in_their_environments() {
+    let env = TestEnvironment::new().await.unwrap();
+    let file = std::env::temp_dir().join(format!("freezes-{}.yaml", uuid::Uuid::new_v4()));
+    let mut window = freeze_now(&[], &["production"]);
+    window["name"] = json!("peak");
+    let config = json!({ "windows": [window] });
+    // YAML is a superset of JSON
+    std::fs::write(&file, serde_json::to_string_pretty(&config).unwrap()).unwrap();
+
+    let staging = env
+        .start_server_with(|config| {
+            config.environment = Some("staging".to_string());
+            config.change_freeze_file = Some(file.clone());
+        })
+        .await
+        .unwrap();
+    staging.register_schema("shop.Order", "JSON", json!({"type": "object"})).await.unwrap();
+
+    let production = env
+        .start_server_with(|config| {
+            config.environment = Some("production".to_string());
+            config.change_freeze_file = Some(file.clone());
+        })
+        .await
+        .unwrap();
+    let response = production
+        .post_json("/api/v1/schemas", &register_body("shop.Order2"))
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 423);
+
+    std::fs::remove_file(&file).unwrap();
+}
+
+#[tokio::test]
+async fn test_admin_windows_survive_a_restart_and_are_shared() {
+    let env = TestEnvironment::new().await.unwrap();
+    let first = env
+        .start_server_with(|config| config.admin_token = Some(ADMIN_TOKEN.to_string()))
+        .await
+        .unwrap();
+    let response = first
+        .client()
+        .put(first.url("/api/v1/admin/freezes/peak"))
+        .bearer_auth(ADMIN_TOKEN)
+        .json(&freeze_now(&["shop"], &[]))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+
+    let database_url = first.database_url().to_string();
+    first.shutdown().await;
+    let restarted = env
+        .start_server_with(|config| {
+            config.admin_token = Some(ADMIN_TOKEN.to_string());
+            config.database_url = database_url.clone();
+        })
+        .await
+        .unwrap();
+    let response = restarted
+        .post_json("/api/v1/schemas", &register_body("shop.Order"))
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 423);
+
+    // Lifting the freeze on one replica lifts it on the others
+    let replica = env
+        .start_server_with(|config| {
+            config.admin_token = Some(ADMIN_TOKEN.to_string());
+            config.database_url = database_url;
+        })
+        .await
+        .unwrap();
+    let response = replica
+        .client()
+        .delete(replica.url("/api/v1/admin/freezes/peak"))
+        .bearer_auth(ADMIN_TOKEN)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 204);
+    restarted.register_schema("shop.Order", "JSON", json!({"type": "object"})).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_freeze_covers_metadata_consumers_exemptions_and_lineage() {
+    let env = TestEnvironment::new().await.unwrap();
+    let server = env
+        .start_server_with(|config| {
+            config.admin_token = Some(ADMIN_TOKEN.to_string());
+        })
+        .await
+        .unwrap();
+    let registered = server
+        .register_schema("shop.Order", "JSON", json!({"type": "object"}))
+        .await
+        .unwrap();
+    let id = registered["id"].as_str().unwrap();
+
+    let response = server
+        .client()
+        .put(server.url("/api/v1/admin/freezes/peak"))
+        .bearer_auth(ADMIN_TOKEN)
+        .json(&freeze_now(&["shop"], &[]))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+
+    let client = server.client();
+    let requests = [
+        client
+            .patch(server.url(&format!("/api/v1/schemas/{}/metadata", id)))
+            .bearer_auth(ADMIN_TOKEN)
+            .json(&json!({"description": "Orders"})),
+        client
+            .put(server.url("/api/v1/subjects/shop.Order/consumers/billing"))
+            .json(&json!({"webhook_url": "https://hooks.example.com", "fields": ["id"]})),
+        client
+            .post(server.url("/api/v1/admin/compatibility/exemptions"))
+            .bearer_auth(ADMIN_TOKEN)
+            .json(&json!({
+                "subject": "shop.Order",
+                "violation_type": "FIELD_REMOVED",
+                "reason": "Migration",
+                "expires_at": chrono::Utc::now() + chrono::Duration::days(1),
+            })),
+        client
+            .post(server.url("/api/v1/lineage/edges"))
+            .bearer_auth(ADMIN_TOKEN)
+            .json(&json!({
+                "from": "shop.Order",
+                "entity": {"id": "checkout", "type": "APPLICATION"},
+            })),
+    ];
+    for request in requests {
+        let response = request.send().await.unwrap();
+        let url = response.url().to_string();
+        assert_eq!(response.status().as_u16(), 423, "{}", url);
+        assert_eq!(
+            response.json::<Value>().await.unwrap()["code"],
+            "CHANGE_FREEZE"
+        );
+    }
+}
@@ -0,0 +1,270 @@
+//! Registration compatibility gate tests
+//!
+//! A new version has to stay compatible with the subject's earlier versions
+//! at the subject's compatibility level. An active exemption lets the
+//! breaking changes it covers through, and the version is recorded as
+//! registered under it. Checks are recorded under the caller who
+//! authenticated.
+
+use super::*;
+use serde_json::{json, Value};
+
+const ADMIN_TOKEN: &str = "compatibility-gate-admin-token";
+const SUBJECT: &str = "gate.Order";
+
+async fn register(
+    server: &schema_registry_test_env::TestServer,
+    schema: Value,
+) -> reqwest::Response {
+    let body = json!({"subject": SUBJECT, "schema_type": "JSON", "schema": schema});
+    server.post_json("/api/v1/schemas", &body).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_breaking_registrations_need_an_exemption() {
+    let env = TestEnvironment::new().await.unwrap();
+    let server = env
+        .start_server_with(|config| config.admin_token = Some(ADMIN_TOKEN.to_string()))
+        .await
+        .unwrap();
+    let optional = json!({"type": "object", "properties": {"id": {"type": "string"}}});
+    let required = json!({
+        "type": "object",
+        "properties": {"id": {"type": "string"}},
+        "required": ["id"]
+    });
+
+    assert_eq!(register(&server, optional).await.status().as_u16(), 201);
+
+    // Old data without `id` doesn't pass the new version under BACKWARD
+    let refused = register(&server, required.clone()).await;
+    assert_eq!(refused.status().as_u16(), 409);
+    let body: Value = refused.json().await.unwrap();
+    assert_eq!(body["code"], "INCOMPATIBLE_SCHEMA");
+    assert_eq!(body["mode"], "BACKWARD");
+    assert_eq!(body["violations"][0]["violation_type"], "REQUIRED_ADDED");
+
+    let granted = server
+        .client()
+        .post(server.url("/api/v1/admin/compatibility/exemptions"))
+        .bearer_auth(ADMIN_TOKEN)
+        .header("X-Admin-User", "alice")
+        .json(&json!({
+            "subject": SUBJECT,
+            "violation_type": "REQUIRED_ADDED",
+            "reason": "Every producer sends id",
+            "expires_at": (chrono::Utc::now() + chrono::Duration::days(1)).to_rfc3339(),
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(granted.status().as_u16(), 201);
+    let exemption: Value = granted.json().await.unwrap();
+
+    let registered = register(&server, required).await;
+    assert_eq!(registered.status().as_u16(), 201);
+    let registered: Value = registered.json().await.unwrap();
+    assert_eq!(registered["version"], "2.0.0");
+
+    // The version history names the exemption the version was registered under
+    let versions: Value = server
+        .get(&format!("/api/v1/subjects/{}/versions", SUBJECT))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(versions["versions"][0].get("exemptions").is_none());
+    assert_eq!(versions["versions"][1]["exemptions"], json!([exemption["id"]]));
+
+    // The changelog shows it next to the registration
+    let changelog: Value = server
+        .get(&format!("/api/v1/subjects/{}/changelog", SUBJECT))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let entries: Vec<(&str, &str)> = changelog["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| (e["version"].as_str().unwrap(), e["event_type"].as_str().unwrap()))
+        .collect();
+    assert_eq!(
+        entries,
+        [
+            ("1.0.0", "REGISTERED"),
+            ("2.0.0", "REGISTERED"),
+            ("2.0.0", "EXEMPTION_APPLIED"),
+        ]
+    );
+    let applied = &changelog["entries"][2]["details"];
+    assert_eq!(applied["exemptions"], json!([exemption["id"]]));
+    assert_eq!(applied["violations"][0]["violation_type"], "REQUIRED_ADDED");
+    let gates = changelog["entries"][1]["details"]["gates"].as_array().unwrap();
+    let gate = gates.iter().find(|g| g["gate"] == "compatibility").unwrap();
+    assert_eq!(gate["outcome"], "overridden");
+}
+
+#[tokio::test]
+async fn test_the_subject_level_applies_whatever_mode_is_requested() {
+    let env = TestEnvironment::new().await.unwrap();
+    let server = env.start_server().await.unwrap();
+    let body = |schema: Value| {
+        json!({
+            "subject": "gate.Refund",
+            "schema_type": "JSON",
+            "schema": schema,
+            "compatibility_mode": "NONE",
+        })
+    };
+
+    let first = json!({"type": "object", "properties": {"amount": {"type": "number"}}});
+    let response = server.post_json("/api/v1/schemas", &body(first)).await.unwrap();
+    assert_eq!(response.status().as_u16(), 201);
+
+    let changed = json!({"type": "object", "properties": {"amount": {"type": "string"}}});
+    let response = server.post_json("/api/v1/schemas", &body(changed)).await.unwrap();
+    assert_eq!(response.status().as_u16(), 409);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["code"], "INCOMPATIBLE_SCHEMA");
+}
+
+#[tokio::test]
+async fn test_min_severity_to_fail_decides_which_registrations_fail() {
+    use schema_registry_core::config_manager_adapter::{CompatibilityPolicy, RuleLevel};
+    use schema_registry_core::types::{ViolationSeverity, ViolationType};
+    use std::collections::HashMap;
+
+    let env = TestEnvironment::new().await.unwrap();
+    let policy = CompatibilityPolicy {
+        rules: HashMap::from([(ViolationType::EnumValueRemoved, RuleLevel::Warning)]),
+        namespaces: HashMap::new(),
+    };
+    let status =
+        |values: &[&str]| json!({"type": "object", "properties": {"status": {"enum": values}}});
+
+    // Warnings are kept on the registration without failing it by default
+    let lenient = env
+        .start_server_with(|config| config.compatibility_policy = policy.clone())
+        .await
+        .unwrap();
+    assert_eq!(register(&lenient, status(&["new", "paid"])).await.status().as_u16(), 201);
+    assert_eq!(register(&lenient, status(&["new"])).await.status().as_u16(), 201);
+    let changelog: Value = lenient
+        .get(&format!("/api/v1/subjects/{}/changelog", SUBJECT))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let gates = changelog["entries"][1]["details"]["gates"].as_array().unwrap();
+    let gate = gates.iter().find(|g| g["gate"] == "compatibility").unwrap();
+    assert_eq!(gate["outcome"], "flagged");
+
+    let strict = env
+        .start_server_with(|config| {
+            config.compatibility_policy = policy.clone();
+            config.min_severity_to_fail = ViolationSeverity::Warning;
+        })
+        .await
+        .unwrap();
+    assert_eq!(register(&strict, status(&["new", "paid"])).await.status().as_u16(), 201);
+    let refused = register(&strict, status(&["new"])).await;
+    assert_eq!(refused.status().as_u16(), 409);
+    let body: Value = refused.json().await.unwrap();
+    assert_eq!(body["violations"][0]["violation_type"], "ENUM_VALUE_REMOVED");
+    assert_eq!(body["violations"][0]["severity"], "WARNING");
+}
+
+#[tokio::test]
+async fn test_concurrent_registrations_are_checked_against_each_other() {
+    let env = TestEnvironment::new().await.unwrap();
+    let server = env.start_server().await.unwrap();
+    let amount = |kind: &str| {
+        json!({
+            "type": "object",
+            "properties": {"id": {"type": "string"}, "amount": {"type": kind}}
+        })
+    };
+    let first = json!({"type": "object", "properties": {"id": {"type": "string"}}});
+    assert_eq!(register(&server, first).await.status().as_u16(), 201);
+
+    // Each is compatible with the first version, but not with the other
+    let (number, string) = tokio::join!(
+        register(&server, amount("number")),
+        register(&server, amount("string"))
+    );
+    let mut statuses = [number.status().as_u16(), string.status().as_u16()];
+    statuses.sort();
+    assert_eq!(statuses, [201, 409]);
+
+    let versions: Value = server
+        .get(&format!("/api/v1/subjects/{}/versions", SUBJECT))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(versions["versions"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_check_history_names_the_authenticated_caller() {
+    let env = TestEnvironment::new().await.unwrap();
+    let server = env
+        .start_server_with(|config| config.admin_token = Some(ADMIN_TOKEN.to_string()))
+        .await
+        .unwrap();
+    let first = json!({"type": "object", "properties": {"id": {"type": "string"}}});
+    let second = json!({
+        "type": "object",
+        "properties": {"id": {"type": "string"}, "note": {"type": "string"}}
+    });
+    let first: Value = register(&server, first).await.json().await.unwrap();
+    let second: Value = register(&server, second).await.json().await.unwrap();
+    let check = json!({"schema_id": second["id"], "compared_schema_id": first["id"]});
+
+    // The client ID header claims to be the admin, which proves nothing
+    let response = server
+        .client()
+        .post(server.url("/api/v1/compatibility/check"))
+        .header("x-client-id", "alice")
+        .json(&check)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    let response = server
+        .client()
+        .post(server.url("/api/v1/compatibility/check"))
+        .bearer_auth(ADMIN_TOKEN)
+        .header("X-Admin-User", "alice")
+        .json(&check)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+
+    let history = server.url("/api/v1/compatibility/history");
+    let response = server.client().get(&history).send().await.unwrap();
+    assert_eq!(response.status().as_u16(), 401);
+
+    let history: Value = server
+        .client()
+        .get(&history)
+        .bearer_auth(ADMIN_TOKEN)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let checks = history["checks"].as_array().unwrap();
+    assert_eq!(checks.len(), 2);
+    assert_eq!(checks[0]["caller"], "alice");
+    assert!(checks[0]["client_id"].is_null());
+    assert_eq!(checks[1]["caller"], "anonymous");
+    assert_eq!(checks[1]["client_id"], "alice");
+}
@@ -0,0 +1,262 @@
+//! Confluent-compatible API tests
+//!
+//! Kafka serializers cache version numbers and embed 4-byte schema IDs in
+//! messages, so these check that both stay put as a subject changes through
+//! either API, and that subject-level config outlives the server.
+
+use super::*;
+use serde_json::{json, Value};
+use sqlx::postgres::PgPoolOptions;
+
+const ADMIN_TOKEN: &str = "confluent-admin-token";
+
+/// An Avro record with an `id` and optional string fields
+fn order(optional: &[&str]) -> String {
+    let mut fields = vec![json!({"name": "id", "type": "long"})];
+    fields.extend(
+        optional
+            .iter()
+            .map(|name| json!({"name": name, "type": "string", "default": ""})),
+    );
+    json!({"type": "record", "name": "Order", "fields": fields}).to_string()
+}
+
+async fn register(server: &schema_registry_test_env::TestServer, schema: &str) -> i64 {
+    let response = server
+        .post_json(
+            "/subjects/orders-value/versions",
+            &json!({"schema": schema}),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    let body: Value = response.json().await.unwrap();
+    body["id"].as_i64().unwrap()
+}
+
+async fn get_json(server: &schema_registry_test_env::TestServer, path: &str) -> (u16, Value) {
+    let response = server.get(path).await.unwrap();
+    let status = response.status().as_u16();
+    (status, response.json().await.unwrap())
+}
+
+#[tokio::test]
+async fn test_confluent_versions_keep_their_numbers() {
+    let env = TestEnvironment::new().await.unwrap();
+    let server = env.start_server().await.unwrap();
+    let first = register(&server, &order(&[])).await;
+    let second = register(&server, &order(&["note"])).await;
+    assert_ne!(first, second);
+    assert_eq!(register(&server, &order(&[])).await, first);
+
+    // A minor version registered natively is numbered after the others
+    let response = server
+        .post_json(
+            "/api/v1/schemas",
+            &json!({
+                "subject": "orders-value",
+                "format": "AVRO",
+                "content": order(&["note", "channel"]),
+                "version_major": 1,
+                "version_minor": 1,
+                "version_patch": 0,
+            }),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 201);
+
+    let (_, versions) = get_json(&server, "/subjects/orders-value/versions").await;
+    assert_eq!(versions, json!([1, 2, 3]));
+    let (_, version) = get_json(&server, "/subjects/orders-value/versions/2").await;
+    assert_eq!(version["id"], second);
+    assert_eq!(version["version"], 2);
+    assert_eq!(version["schema"], order(&["note"]));
+    let (_, latest) = get_json(&server, "/subjects/orders-value/versions/latest").await;
+    assert_eq!(latest["version"], 3);
+    assert_eq!(latest["schema"], order(&["note", "channel"]));
+    let (_, by_id) = get_json(&server, &format!("/schemas/ids/{}", second)).await;
+    assert_eq!(by_id["schema"], order(&["note"]));
+
+    let found: Value = server
+        .post_json("/subjects/orders-value", &json!({"schema": order(&[])}))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(found["version"], 1);
+    assert_eq!(found["id"], first);
+
+    let (status, error) = get_json(&server, "/subjects/orders-value/versions/0").await;
+    assert_eq!(status, 422);
+    assert_eq!(error["error_code"], 42202);
+    let (status, error) = get_json(&server, "/subjects/orders-value/versions/9").await;
+    assert_eq!(status, 404);
+    assert_eq!(error["error_code"], 40402);
+
+    // A permanently deleted version leaves the other numbers alone and its
+    // number isn't handed out again
+    let db = PgPoolOptions::new()
+        .connect(server.database_url())
+        .await
+        .unwrap();
+    sqlx::query("DELETE FROM schemas WHERE global_id = $1")
+        .bind(first)
+        .execute(&db)
+        .await
+        .unwrap();
+    let (_, version) = get_json(&server, "/subjects/orders-value/versions/2").await;
+    assert_eq!(version["id"], second);
+    let fourth = register(&server, &order(&["note", "channel", "region"])).await;
+    let (_, versions) = get_json(&server, "/subjects/orders-value/versions").await;
+    assert_eq!(versions, json!([2, 3, 4]));
+    let (_, latest) = get_json(&server, "/subjects/orders-value/versions/latest").await;
+    assert_eq!(latest["id"], fourth);
+    assert_eq!(latest["version"], 4);
+}
+
+#[tokio::test]
+async fn test_renamed_subjects_keep_numbering_their_versions() {
+    let env = TestEnvironment::new().await.unwrap();
+    let server = env
+        .start_server_with(|config| config.admin_token = Some(ADMIN_TOKEN.to_string()))
+        .await
+        .unwrap();
+    register(&server, &order(&[])).await;
+    let second = register(&server, &order(&["note"])).await;
+
+    // The latest number is gone for good before the rename
+    let db = PgPoolOptions::new()
+        .connect(server.database_url())
+        .await
+        .unwrap();
+    sqlx::query("DELETE FROM schemas WHERE global_id = $1")
+        .bind(second)
+        .execute(&db)
+        .await
+        .unwrap();
+
+    let response = server
+        .client()
+        .post(server.url("/api/v1/admin/subjects/orders-value/rename"))
+        .bearer_auth(ADMIN_TOKEN)
+        .json(&json!({"new_subject": "sales-value"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = server
+        .post_json(
+            "/subjects/sales-value/versions",
+            &json!({"schema": order(&["channel"])}),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    let (_, versions) = get_json(&server, "/subjects/sales-value/versions").await;
+    assert_eq!(versions, json!([1, 3]));
+
+    let response = server
+        .post_json(
+            "/api/v1/schemas",
+            &json!({
+                "subject": "sales-value",
+                "format": "AVRO",
+                "content": order(&["channel", "region"]),
+            }),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 201);
+    let (_, versions) = get_json(&server, "/subjects/sales-value/versions").await;
+    assert_eq!(versions, json!([1, 3, 4]));
+}
+
+#[tokio::test]
+async fn test_subject_config_survives_a_restart() {
+    let env = TestEnvironment::new().await.unwrap();
+    let first = env
+        .start_server_with(|config| config.admin_token = Some(ADMIN_TOKEN.to_string()))
+        .await
+        .unwrap();
+    register(&first, &order(&[])).await;
+
+    let response = first
+        .client()
+        .put(first.url("/config/orders-value"))
+        .bearer_auth(ADMIN_TOKEN)
+        .json(&json!({"compatibility": "NONE"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    let response = first
+        .client()
+        .put(first.url("/config/orders-value"))
+        .bearer_auth(ADMIN_TOKEN)
+        .json(&json!({"compatibility": "SIDEWAYS"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 422);
+    let error: Value = response.json().await.unwrap();
+    assert_eq!(error["error_code"], 42203);
+
+    let database_url = first.database_url().to_string();
+    first.shutdown().await;
+    let second = env
+        .start_server_with(|config| {
+            config.admin_token = Some(ADMIN_TOKEN.to_string());
+            config.database_url = database_url;
+        })
+        .await
+        .unwrap();
+    let (status, config) = get_json(&second, "/config/orders-value").await;
+    assert_eq!(status, 200);
+    assert_eq!(config["compatibilityLevel"], "NONE");
+
+    // A required field without a default can't read old data, which only
+    // NONE allows
+    let required = json!({
+        "type": "record",
+        "name": "Order",
+        "fields": [{"name": "id", "type": "long"}, {"name": "region", "type": "string"}],
+    });
+    register(&second, &required.to_string()).await;
+
+    let response = second
+        .client()
+        .delete(second.url("/config/orders-value"))
+        .bearer_auth(ADMIN_TOKEN)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    let (status, error) = get_json(&second, "/config/orders-value").await;
+    assert_eq!(status, 404);
+    assert_eq!(error["error_code"], 40408);
+}
+
+#[tokio::test]
+async fn test_global_ids_fit_the_wire_format() {
+    let env = TestEnvironment::new().await.unwrap();
+    let server = env.start_server().await.unwrap();
+    let id = register(&server, &order(&[])).await;
+    assert!(id > 0 && id <= i64::from(i32::MAX));
+
+    let db = PgPoolOptions::new()
+        .connect(server.database_url())
+        .await
+        .unwrap();
+    let too_large = sqlx::query(
+        "INSERT INTO schemas (namespace, name, version_major, version_minor, version_patch, format, content, content_hash, global_id) \
+         VALUES ('default', 'wide-value', 1, 0, 0, 'AVRO', '\"string\"', $1, $2)",
+    )
+    .bind("0".repeat(64))
+    .bind(i64::from(i32::MAX) + 1)
+    .execute(&db)
+    .await;
+    assert!(too_large.is_err());
+}
@@ -0,0 +1,178 @@
+//! GraphQL API tests
+//!
+//! Lineage is read from the edges stored through the REST API, so any
+//! replica answers with it, and queries are bounded in depth and in the
+//! number of fields they resolve.
+
+use super::*;
+use serde_json::{json, Value};
+
+const ADMIN_TOKEN: &str = "graphql-admin-token";
+
+async fn query(server: &schema_registry_test_env::TestServer, query: &str) -> Value {
+    let response = server
+        .post_json("/api/v1/graphql", &json!({"query": query}))
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    response.json().await.unwrap()
+}
+
+async fn record_edge(server: &schema_registry_test_env::TestServer, edge: Value) {
+    let response = server
+        .client()
+        .post(server.url("/api/v1/lineage/edges"))
+        .bearer_auth(ADMIN_TOKEN)
+        .json(&edge)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 201);
+}
+
+#[tokio::test]
+async fn test_lineage_is_served_by_every_replica() {
+    let env = TestEnvironment::new().await.unwrap();
+    let first = env
+        .start_server_with(|config| config.admin_token = Some(ADMIN_TOKEN.to_string()))
+        .await
+        .unwrap();
+    let order = first
+        .register_schema("graphql.Order", "JSON", json!({"type": "object"}))
+        .await
+        .unwrap();
+    let customer = first
+        .register_schema("graphql.Customer", "JSON", json!({"type": "object"}))
+        .await
+        .unwrap();
+    let order_id = order["id"].as_str().unwrap();
+    let customer_id = customer["id"].as_str().unwrap();
+    record_edge(
+        &first,
+        json!({"from": "graphql.Order", "to": "graphql.Customer"}),
+    )
+    .await;
+    let checkout = json!({
+        "from": "graphql.Order",
+        "entity": {"id": "checkout", "type": "APPLICATION"},
+        "relation": "USED_BY",
+    });
+    record_edge(&first, checkout.clone()).await;
+    // Recording an edge again keeps one
+    record_edge(&first, checkout).await;
+
+    let database_url = first.database_url().to_string();
+    let second = env
+        .start_server_with(|config| config.database_url = database_url)
+        .await
+        .unwrap();
+
+    let body = query(
+        &second,
+        &format!(
+            r#"{{ schema(id: "{}") {{ version subject {{ subject latest {{ id }} }}
+                dependencies {{ relation name entityType schema {{ id }} }}
+                consumers {{ name entityType relation }} }} }}"#,
+            order_id
+        ),
+    )
+    .await;
+    assert!(body["errors"].is_null(), "{}", body);
+    let schema = &body["data"]["schema"];
+    assert_eq!(schema["version"], "1.0.0");
+    assert_eq!(schema["subject"]["subject"], "graphql.Order");
+    assert_eq!(schema["subject"]["latest"]["id"], order_id);
+    assert_eq!(
+        schema["dependencies"],
+        json!([
+            {
+                "relation": "DEPENDS_ON",
+                "name": "graphql.Customer",
+                "entityType": "SCHEMA",
+                "schema": {"id": customer_id},
+            },
+            {
+                "relation": "USED_BY",
+                "name": "checkout",
+                "entityType": "APPLICATION",
+                "schema": null,
+            },
+        ])
+    );
+    assert_eq!(
+        schema["consumers"],
+        json!([{"name": "checkout", "entityType": "APPLICATION", "relation": "USED_BY"}])
+    );
+
+    let body = query(
+        &second,
+        &format!(
+            r#"{{ schema(id: "{}") {{ dependents {{ name schema {{ id }} }} consumers {{ id entityType }} }} }}"#,
+            customer_id
+        ),
+    )
+    .await;
+    let schema = &body["data"]["schema"];
+    assert_eq!(
+        schema["dependents"],
+        json!([{"name": "graphql.Order", "schema": {"id": order_id}}])
+    );
+    assert_eq!(
+        schema["consumers"],
+        json!([{"id": order_id, "entityType": "SCHEMA"}])
+    );
+
+    let usage: Value = second
+        .get(&format!("/api/v1/schemas/{}/usage", order_id))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(usage["consumers"][0]["name"], "checkout");
+}
+
+#[tokio::test]
+async fn test_queries_are_limited_in_depth_and_complexity() {
+    let env = TestEnvironment::new().await.unwrap();
+    let server = env.start_server().await.unwrap();
+    let registered = server
+        .register_schema("graphql.Limits", "JSON", json!({"type": "object"}))
+        .await
+        .unwrap();
+    let id = registered["id"].as_str().unwrap();
+
+    let body = query(
+        &server,
+        &format!(r#"{{ schema(id: "{}") {{ subject {{ subject }} }} }}"#, id),
+    )
+    .await;
+    assert_eq!(
+        body["data"]["schema"]["subject"]["subject"],
+        "graphql.Limits"
+    );
+
+    // Each `subject { latest { ... } }` adds two levels
+    let mut nested = "id".to_string();
+    for _ in 0..7 {
+        nested = format!("subject {{ latest {{ {} }} }}", nested);
+    }
+    let body = query(
+        &server,
+        &format!(r#"{{ schema(id: "{}") {{ {} }} }}"#, id, nested),
+    )
+    .await;
+    assert!(body["data"].is_null(), "{}", body);
+    let message = body["errors"][0]["message"].as_str().unwrap();
+    assert!(message.contains("too deep"), "{}", message);
+
+    let fields: Vec<String> = (0..2_000).map(|i| format!("f{}: id", i)).collect();
+    let body = query(
+        &server,
+        &format!(r#"{{ schema(id: "{}") {{ {} }} }}"#, id, fields.join(" ")),
+    )
+    .await;
+    assert!(body["data"].is_null(), "{}", body);
+    let message = body["errors"][0]["message"].as_str().unwrap();
+    assert!(message.contains("too complex"), "{}", message);
+}
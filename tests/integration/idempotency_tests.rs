@@ -0,0 +1,132 @@
+//! Idempotent registration tests
+//!
+//! A registration retried with the same `Idempotency-Key` gets the first
+//! response back, headers included, while a key reused for another body or
+//! sent again before the first request finished is refused. Keys are scoped
+//! to the caller, so they need credentials.
+
+use super::*;
+use serde_json::{json, Value};
+
+const ADMIN_TOKEN: &str = "idempotency-admin-token";
+
+async fn register(
+    server: &schema_registry_test_env::TestServer,
+    key: &str,
+    body: &Value,
+    token: Option<&str>,
+) -> reqwest::Response {
+    let mut request = server
+        .client()
+        .post(server.url("/api/v1/schemas"))
+        .header("idempotency-key", key)
+        .json(body);
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+    request.send().await.unwrap()
+}
+
+#[tokio::test]
+async fn test_retries_replay_the_first_response() {
+    let env = TestEnvironment::new().await.unwrap();
+    let server = env
+        .start_server_with(|config| config.admin_token = Some(ADMIN_TOKEN.to_string()))
+        .await
+        .unwrap();
+    let body =
+        json!({"subject": "retries.Order", "schema_type": "JSON", "schema": {"type": "object"}});
+
+    let first = register(&server, "order-1", &body, Some(ADMIN_TOKEN)).await;
+    assert_eq!(first.status().as_u16(), 201);
+    let headers = first.headers().clone();
+    assert!(!headers.contains_key("idempotent-replayed"));
+    let registered: Value = first.json().await.unwrap();
+    let location = format!("/api/v1/schemas/{}", registered["id"].as_str().unwrap());
+    assert_eq!(headers["location"], location.as_str());
+
+    let retry = register(&server, "order-1", &body, Some(ADMIN_TOKEN)).await;
+    assert_eq!(retry.status().as_u16(), 201);
+    assert_eq!(retry.headers()["idempotent-replayed"], "true");
+    assert_eq!(retry.headers()["location"], location.as_str());
+    assert_eq!(retry.headers()["content-type"], headers["content-type"]);
+    // The stored body, not a second registration's
+    assert_eq!(retry.json::<Value>().await.unwrap(), registered);
+
+    let changed =
+        json!({"subject": "retries.Order", "schema_type": "JSON", "schema": {"type": "string"}});
+    let response = register(&server, "order-1", &changed, Some(ADMIN_TOKEN)).await;
+    assert_eq!(response.status().as_u16(), 400);
+    let message: Value = response.json().await.unwrap();
+    let message = message["error"].as_str().unwrap();
+    assert!(message.contains("different request"), "{}", message);
+}
+
+#[tokio::test]
+async fn test_keys_need_credentials() {
+    let env = TestEnvironment::new().await.unwrap();
+    let server = env
+        .start_server_with(|config| config.admin_token = Some(ADMIN_TOKEN.to_string()))
+        .await
+        .unwrap();
+    let body =
+        json!({"subject": "retries.Invoice", "schema_type": "JSON", "schema": {"type": "object"}});
+
+    let response = register(&server, "invoice-1", &body, None).await;
+    assert_eq!(response.status().as_u16(), 401);
+    let db = sqlx::PgPool::connect(server.database_url()).await.unwrap();
+    let stored: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM idempotency_keys")
+        .fetch_one(&db)
+        .await
+        .unwrap();
+    assert_eq!(stored.0, 0);
+
+    // Without a key, anonymous registration is unchanged
+    let response = server.post_json("/api/v1/schemas", &body).await.unwrap();
+    assert_eq!(response.status().as_u16(), 201);
+}
+
+#[tokio::test]
+async fn test_retries_wait_for_the_first_request() {
+    let env = TestEnvironment::new().await.unwrap();
+    let server = env
+        .start_server_with(|config| config.admin_token = Some(ADMIN_TOKEN.to_string()))
+        .await
+        .unwrap();
+    let body =
+        json!({"subject": "retries.Refund", "schema_type": "JSON", "schema": {"type": "object"}});
+    let first = register(&server, "refund-1", &body, Some(ADMIN_TOKEN)).await;
+    assert_eq!(first.status().as_u16(), 201);
+
+    // Put the key back as a request still running holds it
+    let db = sqlx::PgPool::connect(server.database_url()).await.unwrap();
+    sqlx::query(
+        "UPDATE idempotency_keys SET response_status = NULL, response_headers = NULL, \
+         response_body = NULL WHERE idempotency_key = $1",
+    )
+    .bind("refund-1")
+    .execute(&db)
+    .await
+    .unwrap();
+    let response = register(&server, "refund-1", &body, Some(ADMIN_TOKEN)).await;
+    assert_eq!(response.status().as_u16(), 409);
+    let message: Value = response.json().await.unwrap();
+    let message = message["error"].as_str().unwrap();
+    assert!(message.contains("still running"), "{}", message);
+
+    // One that never finished is run again once its claim times out
+    sqlx::query(
+        "UPDATE idempotency_keys SET created_at = NOW() - INTERVAL '10 minutes' \
+         WHERE idempotency_key = $1",
+    )
+    .bind("refund-1")
+    .execute(&db)
+    .await
+    .unwrap();
+    let response = register(&server, "refund-1", &body, Some(ADMIN_TOKEN)).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(!response.headers().contains_key("idempotent-replayed"));
+    let retried: Value = response.json().await.unwrap();
+    let registered: Value = first.json().await.unwrap();
+    assert_eq!(retried["id"], registered["id"]);
+}
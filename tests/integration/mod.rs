@@ -3,14 +3,28 @@
 //! This module contains comprehensive integration tests using real services
 //! via testcontainers (PostgreSQL, Redis, LocalStack/S3)
 
-mod test_environment;
 mod database_tests;
 mod redis_tests;
 mod s3_tests;
 mod multi_tier_storage_tests;
 mod api_integration_tests;
+mod public_api_tests;
+mod change_freeze_tests;
+mod sdk_search_tests;
+mod batch_validation_tests;
+mod ownership_transfer_tests;
+mod registration_conflict_tests;
+mod confluent_api_tests;
+mod subject_deletion_tests;
+mod state_change_tests;
+mod schema_read_tests;
+mod graphql_tests;
+mod idempotency_tests;
+mod namespace_quota_tests;
+mod quarantine_tests;
+mod compatibility_gate_tests;
 
-pub use test_environment::TestEnvironment;
+pub use schema_registry_test_env::TestEnvironment;
 
 // Test setup
 #[ctor::ctor]
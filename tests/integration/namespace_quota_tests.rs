@@ -0,0 +1,84 @@
+//! Namespace quota tests
+//!
+//! Quotas set on a parent namespace apply to the namespaces below it.
+//! `max_schemas` counts schema names and `max_versions_per_schema` counts
+//! versions; deleted versions count against neither.
+
+use super::*;
+use serde_json::{json, Value};
+use sqlx::postgres::PgPoolOptions;
+
+const ADMIN_TOKEN: &str = "namespace-quota-admin-token";
+
+fn schema(field: &str) -> Value {
+    json!({"type": "object", "properties": {field: {"type": "string"}}})
+}
+
+async fn register(
+    server: &schema_registry_test_env::TestServer,
+    subject: &str,
+    field: &str,
+) -> reqwest::Response {
+    let body = json!({"subject": subject, "schema_type": "JSON", "schema": schema(field)});
+    server.post_json("/api/v1/schemas", &body).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_inherited_quotas_count_live_schemas_and_versions() {
+    let env = TestEnvironment::new().await.unwrap();
+    let server = env
+        .start_server_with(|config| config.admin_token = Some(ADMIN_TOKEN.to_string()))
+        .await
+        .unwrap();
+    let db = PgPoolOptions::new()
+        .connect(server.database_url())
+        .await
+        .unwrap();
+
+    let response = server
+        .client()
+        .put(server.url("/api/v1/namespaces/shop/settings"))
+        .bearer_auth(ADMIN_TOKEN)
+        .json(&json!({"max_schemas": 2, "max_versions_per_schema": 2}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+
+    // The quota set on `shop` applies to `shop.orders`
+    assert_eq!(register(&server, "shop.orders.Order", "a").await.status().as_u16(), 201);
+    assert_eq!(register(&server, "shop.orders.Order", "b").await.status().as_u16(), 201);
+    assert_eq!(register(&server, "shop.orders.Refund", "a").await.status().as_u16(), 201);
+
+    // Versions of an existing schema don't count as more schemas
+    let versions = register(&server, "shop.orders.Order", "c").await;
+    assert_eq!(versions.status().as_u16(), 422);
+    let body: Value = versions.json().await.unwrap();
+    assert!(body["error"].as_str().unwrap().contains("versions"), "{}", body);
+
+    let schemas = register(&server, "shop.orders.Invoice", "a").await;
+    assert_eq!(schemas.status().as_u16(), 422);
+    let body: Value = schemas.json().await.unwrap();
+    assert!(body["error"].as_str().unwrap().contains("2 schemas"), "{}", body);
+
+    // Other namespaces aren't limited
+    assert_eq!(register(&server, "billing.Invoice", "a").await.status().as_u16(), 201);
+
+    // Deleted versions free their quota
+    sqlx::query(
+        "UPDATE schemas SET state = 'DELETED' \
+         WHERE namespace = 'shop.orders' AND name = 'Order' AND version_major = 1",
+    )
+    .execute(&db)
+    .await
+    .unwrap();
+    assert_eq!(register(&server, "shop.orders.Order", "c").await.status().as_u16(), 201);
+
+    sqlx::query(
+        "UPDATE schemas SET state = 'DELETED' WHERE namespace = 'shop.orders' AND name = 'Refund'",
+    )
+    .execute(&db)
+    .await
+    .unwrap();
+    assert_eq!(register(&server, "shop.orders.Invoice", "a").await.status().as_u16(), 201);
+}
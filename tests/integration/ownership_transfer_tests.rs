@@ -0,0 +1,181 @@
+//! Ownership transfer tests
+//!
+//! Transfers are stored in Postgres and decided by the receiving team, so
+//! these request one on one server, decide it as the team on a second server
+//! sharing the database, and check the admin token alone can't decide.
+
+use super::*;
+use serde_json::{json, Value};
+
+const ADMIN_TOKEN: &str = "ownership-admin-token";
+const SIGNING_KEY: &str = "ownership-impersonation-signing-key-0123456789";
+
+fn configure(config: &mut schema_registry_test_env::ServerConfig) {
+    config.admin_token = Some(ADMIN_TOKEN.to_string());
+    config.impersonation_signing_key = Some(SIGNING_KEY.to_string());
+}
+
+/// A token acting as `team`, issued by the admin
+async fn team_token(server: &schema_registry_test_env::TestServer, team: &str) -> String {
+    let grant: Value = server
+        .client()
+        .post(server.url("/api/v1/admin/impersonate"))
+        .bearer_auth(ADMIN_TOKEN)
+        .json(&json!({
+            "principal": team,
+            "permissions": ["schema:read", "schema:write"],
+            "reason": "Acting for the team in a test",
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    grant["token"].as_str().unwrap().to_string()
+}
+
+async fn request_transfer(server: &schema_registry_test_env::TestServer, to_owner: &str) -> Value {
+    let response = server
+        .client()
+        .post(server.url("/api/v1/namespaces/com.example.payments/ownership-transfers"))
+        .bearer_auth(ADMIN_TOKEN)
+        .json(&json!({
+            "to_owner": to_owner,
+            "notification_channel": format!("http://127.0.0.1:9/{}", to_owner),
+            "reason": "Payments team formed",
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 201);
+    response.json().await.unwrap()
+}
+
+#[tokio::test]
+async fn test_receiving_team_accepts_a_stored_transfer() {
+    let env = TestEnvironment::new().await.unwrap();
+    let first = env.start_server_with(configure).await.unwrap();
+    let transfer = request_transfer(&first, "payments").await;
+    let id = transfer["id"].as_str().unwrap();
+
+    // A second replica on the same database sees the transfer
+    let database_url = first.database_url().to_string();
+    let second = env
+        .start_server_with(|config| {
+            configure(config);
+            config.database_url = database_url;
+        })
+        .await
+        .unwrap();
+    let stored: Value = second
+        .get(&format!("/api/v1/ownership-transfers/{}", id))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(stored["status"], "pending");
+    assert_eq!(stored["to_owner"], "payments");
+
+    let accept_url = second.url(&format!("/api/v1/ownership-transfers/{}/accept", id));
+    let response = second
+        .client()
+        .post(&accept_url)
+        .bearer_auth(ADMIN_TOKEN)
+        .header("x-admin-user", "payments")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 403);
+
+    let other_team = team_token(&second, "billing").await;
+    let response = second
+        .client()
+        .post(&accept_url)
+        .bearer_auth(&other_team)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 403);
+
+    let payments = team_token(&second, "payments").await;
+    let response = second
+        .client()
+        .post(&accept_url)
+        .bearer_auth(&payments)
+        .json(&json!({"note": "Welcome"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    let accepted: Value = response.json().await.unwrap();
+    assert_eq!(accepted["status"], "accepted");
+    assert_eq!(accepted["decided_by"], "payments");
+
+    let response = second
+        .client()
+        .post(&accept_url)
+        .bearer_auth(&payments)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 409);
+
+    let listed: Value = first
+        .get("/api/v1/ownership-transfers?namespace=com.example.payments&status=accepted")
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(listed["transfers"][0]["id"], id);
+    let settings: Value = second
+        .get("/api/v1/namespaces/com.example.payments/effective-settings")
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(settings["owner"]["value"], "payments");
+}
+
+#[tokio::test]
+async fn test_receiving_team_rejects_and_a_new_transfer_can_follow() {
+    let env = TestEnvironment::new().await.unwrap();
+    let server = env.start_server_with(configure).await.unwrap();
+    let transfer = request_transfer(&server, "payments").await;
+    let id = transfer["id"].as_str().unwrap();
+
+    let response = server
+        .client()
+        .post(server.url("/api/v1/namespaces/com.example.payments/ownership-transfers"))
+        .bearer_auth(ADMIN_TOKEN)
+        .json(&json!({
+            "to_owner": "billing",
+            "notification_channel": "http://127.0.0.1:9/billing",
+            "reason": "Billing team formed",
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 409);
+
+    let reject_url = server.url(&format!("/api/v1/ownership-transfers/{}/reject", id));
+    let response = server.client().post(&reject_url).send().await.unwrap();
+    assert_eq!(response.status().as_u16(), 401);
+
+    let payments = team_token(&server, "payments").await;
+    let response = server
+        .client()
+        .post(&reject_url)
+        .bearer_auth(&payments)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    let rejected: Value = response.json().await.unwrap();
+    assert_eq!(rejected["status"], "rejected");
+
+    request_transfer(&server, "billing").await;
+}
@@ -0,0 +1,246 @@
+//! Public read-only API tests
+//!
+//! Partners only ever see namespaces marked public. Every test registers
+//! schemas on both sides of the boundary, including namespaces that merely
+//! share a prefix with a public one, and checks nothing private comes back.
+
+use super::*;
+use serde_json::{json, Value};
+
+const ADMIN_TOKEN: &str = "public-api-admin-token";
+
+fn is_public(namespace: &str) -> bool {
+    (namespace == "partners" || namespace.starts_with("partners."))
+        && namespace != "partners.internal"
+        && !namespace.starts_with("partners.internal.")
+}
+
+async fn set_visibility(
+    server: &schema_registry_test_env::TestServer,
+    namespace: &str,
+    visibility: &str,
+) {
+    let response = server
+        .client()
+        .put(server.url(&format!("/api/v1/namespaces/{}/settings", namespace)))
+        .bearer_auth(ADMIN_TOKEN)
+        .json(&json!({ "visibility": visibility }))
+        .send()
+        .await
+        .unwrap();
+    assert!(response.status().is_success(), "setting visibility of {}", namespace);
+}
+
+/// Register one schema per subject, all mentioning "shared" so a search for
+/// it would match every one of them
+async fn register_all(server: &schema_registry_test_env::TestServer, subjects: &[&str]) -> Vec<String> {
+    let mut ids = Vec::new();
+    for subject in subjects {
+        let body = server
+            .register_schema(
+                subject,
+                "JSON",
+                json!({
+                    "type": "object",
+                    "description": "shared event",
+                    "properties": {"id": {"type": "string"}}
+                }),
+            )
+            .await
+            .unwrap();
+        ids.push(body["id"].as_str().unwrap().to_string());
+    }
+    ids
+}
+
+async fn public_search(server: &schema_registry_test_env::TestServer, request: Value) -> Value {
+    let response = server.post_json("/public/v1/schemas/search", &request).await.unwrap();
+    assert_eq!(response.status().as_u16(), 200, "search {}", request);
+    response.json().await.unwrap()
+}
+
+#[tokio::test]
+async fn test_public_search_never_returns_private_namespaces() {
+    let env = TestEnvironment::new().await.unwrap();
+    let server = env
+        .start_server_with(|config| {
+            config.admin_token = Some(ADMIN_TOKEN.to_string());
+            config.public_rate_limit = 1_000;
+        })
+        .await
+        .unwrap();
+
+    register_all(
+        &server,
+        &[
+            "partners.Catalog",
+            "partners.orders.OrderPlaced",
+            "partners.internal.Billing",
+            "partners.internal.audit.Trail",
+            "partnership.Contract",
+            "partners_eu.Invoice",
+            "internal.Salary",
+            "Untitled",
+        ],
+    )
+    .await;
+
+    // Nothing is public until an admin says so
+    let results = public_search(&server, json!({"query": "shared"})).await;
+    assert_eq!(results["total"], 0);
+
+    set_visibility(&server, "partners", "public").await;
+    set_visibility(&server, "partners.internal", "private").await;
+
+    let requests = [
+        json!({}),
+        json!({"query": "shared"}),
+        json!({"query": "Billing"}),
+        json!({"query": "Salary"}),
+        json!({"query": "partner"}),
+        json!({"query": "internal"}),
+        json!({"namespace": "partners.internal"}),
+        json!({"namespace": "internal"}),
+        json!({"namespace_pattern": "*"}),
+        json!({"namespace_pattern": "partner*"}),
+        json!({"namespace_pattern": "partners.*"}),
+        json!({"namespace_pattern": "partners_??"}),
+        json!({"format": "JSON_SCHEMA", "limit": 100}),
+        json!({"query": "shared", "offset": 1}),
+    ];
+    for request in requests {
+        let body = public_search(&server, request.clone()).await;
+        let results = body["results"].as_array().unwrap();
+        for result in results {
+            let namespace = result["metadata"]["namespace"].as_str().unwrap();
+            assert!(is_public(namespace), "{} leaked through {}", namespace, request);
+        }
+        assert!(body["total"].as_u64().unwrap() <= 2, "total counts private schemas for {}", request);
+    }
+
+    let all = public_search(&server, json!({"query": "shared"})).await;
+    let mut names: Vec<_> = all["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["metadata"]["name"].as_str().unwrap().to_string())
+        .collect();
+    names.sort();
+    assert_eq!(names, vec!["Catalog", "OrderPlaced"]);
+    assert_eq!(all["total"], 2);
+
+    // The authenticated search still sees everything
+    let internal: Value = server
+        .post_json("/api/v1/schemas/search", &json!({"query": "shared"}))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(internal["total"], 8);
+}
+
+#[tokio::test]
+async fn test_public_reads_hide_private_schemas() {
+    let env = TestEnvironment::new().await.unwrap();
+    let server = env
+        .start_server_with(|config| {
+            config.admin_token = Some(ADMIN_TOKEN.to_string());
+            config.public_rate_limit = 1_000;
+        })
+        .await
+        .unwrap();
+
+    let ids = register_all(&server, &["partners.Catalog", "partners.internal.Billing"]).await;
+    set_visibility(&server, "partners", "public").await;
+    set_visibility(&server, "partners.internal", "private").await;
+
+    let response = server.get(&format!("/public/v1/schemas/{}", ids[0])).await.unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(response.headers()["cache-control"].to_str().unwrap().starts_with("public"));
+
+    // A private schema is indistinguishable from one that doesn't exist
+    let private = server.get(&format!("/public/v1/schemas/{}", ids[1])).await.unwrap();
+    assert_eq!(private.status().as_u16(), 404);
+    let missing = server
+        .get(&format!("/public/v1/schemas/{}", uuid::Uuid::new_v4()))
+        .await
+        .unwrap();
+    assert_eq!(missing.status().as_u16(), 404);
+
+    let versions = server.get("/public/v1/subjects/partners.Catalog/versions").await.unwrap();
+    assert_eq!(versions.status().as_u16(), 200);
+    let versions = server
+        .get("/public/v1/subjects/partners.internal.Billing/versions")
+        .await
+        .unwrap();
+    assert_eq!(versions.status().as_u16(), 404);
+
+    // Making a namespace private takes effect at once, cached searches included
+    assert_eq!(public_search(&server, json!({"query": "Catalog"})).await["total"], 1);
+    set_visibility(&server, "partners", "private").await;
+    let response = server.get(&format!("/public/v1/schemas/{}", ids[0])).await.unwrap();
+    assert_eq!(response.status().as_u16(), 404);
+    assert_eq!(public_search(&server, json!({"query": "Catalog"})).await["total"], 0);
+}
+
+#[tokio::test]
+async fn test_public_api_keys_and_rate_limit() {
+    let env = TestEnvironment::new().await.unwrap();
+    let server = env
+        .start_server_with(|config| {
+            config.public_api_tokens = vec!["partner-key".to_string()];
+            config.public_rate_limit = 3;
+        })
+        .await
+        .unwrap();
+    let search = |key: Option<&'static str>| {
+        let mut request = server
+            .client()
+            .post(server.url("/public/v1/schemas/search"))
+            .json(&json!({}));
+        if let Some(key) = key {
+            request = request.header("X-API-Key", key);
+        }
+        request.send()
+    };
+
+    assert_eq!(search(None).await.unwrap().status().as_u16(), 401);
+    assert_eq!(search(Some("guessed")).await.unwrap().status().as_u16(), 401);
+
+    for _ in 0..3 {
+        let response = search(Some("partner-key")).await.unwrap();
+        assert_eq!(response.status().as_u16(), 200);
+        assert!(response.headers()["cache-control"].to_str().unwrap().starts_with("private"));
+    }
+    assert_eq!(search(Some("partner-key")).await.unwrap().status().as_u16(), 429);
+
+    // The limit only applies to the public API
+    let response = server
+        .post_json("/api/v1/schemas/search", &json!({}))
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn test_forwarded_addresses_dont_reset_the_rate_limit() {
+    let env = TestEnvironment::new().await.unwrap();
+    let server = env
+        .start_server_with(|config| config.public_rate_limit = 2)
+        .await
+        .unwrap();
+    let search = |forwarded: &'static str| {
+        server
+            .client()
+            .post(server.url("/public/v1/schemas/search"))
+            .header("X-Forwarded-For", forwarded)
+            .json(&json!({}))
+            .send()
+    };
+
+    // Without trusted proxies, the header is the client's own word
+    assert_eq!(search("198.51.100.1").await.unwrap().status().as_u16(), 200);
+    assert_eq!(search("198.51.100.2").await.unwrap().status().as_u16(), 200);
+    assert_eq!(search("198.51.100.3").await.unwrap().status().as_u16(), 429);
+}
@@ -0,0 +1,197 @@
+//! Quarantine tests
+//!
+//! Schemas flagged by the security scan are stored as quarantined and the
+//! security team is notified. Only a reviewer with `security:review` can
+//! release them, and a registration that isn't stored leaves nothing to
+//! review.
+
+use super::*;
+use serde_json::{json, Value};
+use sqlx::postgres::PgPoolOptions;
+use uuid::Uuid;
+use wiremock::matchers::method;
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const ADMIN_TOKEN: &str = "quarantine-admin-token";
+const SIGNING_KEY: &str = "quarantine-test-signing-key-of-32-bytes!";
+
+/// A schema the default security patterns flag
+fn flagged_body(subject: &str) -> Value {
+    json!({
+        "subject": subject,
+        "schema_type": "JSON",
+        "schema": {"type": "object", "properties": {"__proto__": {"type": "object"}}},
+    })
+}
+
+async fn security_webhook() -> MockServer {
+    let webhook = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&webhook)
+        .await;
+    webhook
+}
+
+async fn notices(webhook: &MockServer) -> Vec<Value> {
+    webhook
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .map(|request| request.body_json().unwrap())
+        .collect()
+}
+
+async fn release(
+    server: &schema_registry_test_env::TestServer,
+    id: &str,
+    token: Option<&str>,
+) -> reqwest::Response {
+    let mut request = server
+        .client()
+        .post(server.url(&format!("/api/v1/schemas/{}/quarantine/release", id)))
+        .json(&json!({"note": "Field name only"}));
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+    request.send().await.unwrap()
+}
+
+#[tokio::test]
+async fn test_flagged_schema_is_held_until_a_reviewer_releases_it() {
+    let webhook = security_webhook().await;
+    let env = TestEnvironment::new().await.unwrap();
+    let server = env
+        .start_server_with(|config| {
+            config.admin_token = Some(ADMIN_TOKEN.to_string());
+            config.token_exchange_signing_key = Some(SIGNING_KEY.to_string());
+            config.security_notification_url = Some(webhook.uri());
+        })
+        .await
+        .unwrap();
+
+    let registered = server
+        .post_json("/api/v1/schemas", &flagged_body("shop.Cart"))
+        .await
+        .unwrap();
+    assert_eq!(registered.status().as_u16(), 201);
+    let id = registered.json::<Value>().await.unwrap()["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let held = server.get(&format!("/api/v1/schemas/{}", id)).await.unwrap();
+    assert_eq!(held.status().as_u16(), 423);
+    let sent = notices(&webhook).await;
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0]["event_type"], "SchemaQuarantined");
+    assert_eq!(sent[0]["record"]["schema_id"], id);
+    assert_eq!(sent[0]["record"]["subject"], "shop.Cart");
+
+    // Neither anonymous callers nor tokens without security:review can release
+    assert_eq!(release(&server, &id, None).await.status().as_u16(), 401);
+    let exchanged = server
+        .client()
+        .post(server.url("/api/v1/auth/token"))
+        .bearer_auth(ADMIN_TOKEN)
+        .json(&json!({"namespaces": ["shop"], "permissions": ["schema:read", "schema:write"]}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(exchanged.status().as_u16(), 201);
+    let writer = exchanged.json::<Value>().await.unwrap()["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    assert_eq!(release(&server, &id, Some(&writer)).await.status().as_u16(), 403);
+    let held = server.get(&format!("/api/v1/schemas/{}", id)).await.unwrap();
+    assert_eq!(held.status().as_u16(), 423);
+
+    // A scoped reviewer can
+    let exchanged = server
+        .client()
+        .post(server.url("/api/v1/auth/token"))
+        .bearer_auth(ADMIN_TOKEN)
+        .header("X-Admin-User", "security-oncall")
+        .json(&json!({
+            "namespaces": ["shop"],
+            "permissions": ["schema:read", "schema:write", "security:review"],
+        }))
+        .send()
+        .await
+        .unwrap();
+    let reviewer = exchanged.json::<Value>().await.unwrap()["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let released = release(&server, &id, Some(&reviewer)).await;
+    assert_eq!(released.status().as_u16(), 200);
+    let body: Value = released.json().await.unwrap();
+    assert_eq!(body["previous_state"], "QUARANTINED");
+    assert_eq!(body["state"], "DRAFT");
+
+    let served = server.get(&format!("/api/v1/schemas/{}", id)).await.unwrap();
+    assert_eq!(served.status().as_u16(), 200);
+    let sent = notices(&webhook).await;
+    assert_eq!(sent.len(), 2);
+    assert_eq!(sent[1]["event_type"], "QuarantineCleared");
+    assert_eq!(sent[1]["record"]["reviewed_by"], "security-oncall");
+
+    // Released versions aren't quarantined any more
+    assert_eq!(release(&server, &id, Some(ADMIN_TOKEN)).await.status().as_u16(), 409);
+}
+
+#[tokio::test]
+async fn test_registration_that_is_not_stored_is_not_quarantined() {
+    let webhook = security_webhook().await;
+    let env = TestEnvironment::new().await.unwrap();
+    let server = env
+        .start_server_with(|config| config.security_notification_url = Some(webhook.uri()))
+        .await
+        .unwrap();
+    let db = PgPoolOptions::new()
+        .connect(server.database_url())
+        .await
+        .unwrap();
+
+    // Another replica holds version 1.0.0 until the registration waits on it
+    let winner = Uuid::new_v4();
+    let mut tx = db.begin().await.unwrap();
+    sqlx::query(
+        "INSERT INTO schemas (id, namespace, name, version_major, version_minor, version_patch, format, content, content_hash) \
+         VALUES ($1, 'shop', 'Cart', 1, 0, 0, 'JSON', '{}', $2)",
+    )
+    .bind(winner)
+    .bind("0".repeat(64))
+    .execute(&mut *tx)
+    .await
+    .unwrap();
+
+    let client = server.client().clone();
+    let url = server.url("/api/v1/schemas");
+    let mut body = flagged_body("shop.Cart");
+    body["version_major"] = json!(1);
+    let registration = tokio::spawn(async move { client.post(url).json(&body).send().await });
+
+    let mut waiting: i64 = 0;
+    for _ in 0..200 {
+        (waiting,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM pg_stat_activity \
+             WHERE datname = current_database() AND wait_event_type = 'Lock'",
+        )
+        .fetch_one(&db)
+        .await
+        .unwrap();
+        if waiting > 0 {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(25)).await;
+    }
+    assert!(waiting > 0, "the registration never waited on the held version");
+    tx.commit().await.unwrap();
+
+    let response = registration.await.unwrap().unwrap();
+    assert_eq!(response.status().as_u16(), 409);
+    assert!(notices(&webhook).await.is_empty());
+}
@@ -0,0 +1,166 @@
+//! Registration conflict tests
+//!
+//! A registration that loses the insert race to another replica answers with
+//! a 409, or with the stored version when the content is the same. The race
+//! is forced by storing the version in a transaction the test holds open,
+//! which the server's pre-insert check can't see, and committing it once the
+//! server's insert is waiting on it. Registering an existing version without
+//! a race gets the same answers.
+
+use super::*;
+use serde_json::{json, Value};
+use sqlx::postgres::PgPoolOptions;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+const SCHEMA: &str = r#"{"properties":{"id":{"type":"integer"}},"type":"object"}"#;
+
+fn register_body() -> Value {
+    json!({
+        "subject": "race.Order",
+        "schema_type": "JSON",
+        "schema": {"type": "object", "properties": {"id": {"type": "integer"}}},
+        "version_major": 1,
+        "version_minor": 0,
+        "version_patch": 0,
+    })
+}
+
+/// Store version 1.0.0 of `race.Order` with `content_hash` in a transaction
+/// committed only once the server's registration of it is blocked
+async fn register_racing(
+    server: &schema_registry_test_env::TestServer,
+    db: &PgPool,
+    content_hash: &str,
+) -> (Uuid, reqwest::Response) {
+    let winner = Uuid::new_v4();
+    let mut tx = db.begin().await.unwrap();
+    sqlx::query(
+        "INSERT INTO schemas (id, namespace, name, version_major, version_minor, version_patch, format, content, content_hash) \
+         VALUES ($1, 'race', 'Order', 1, 0, 0, 'JSON', $2, $3)",
+    )
+    .bind(winner)
+    .bind(SCHEMA)
+    .bind(content_hash)
+    .execute(&mut *tx)
+    .await
+    .unwrap();
+
+    let client = server.client().clone();
+    let url = server.url("/api/v1/schemas");
+    let registration =
+        tokio::spawn(async move { client.post(url).json(&register_body()).send().await });
+
+    let mut waiting: i64 = 0;
+    for _ in 0..200 {
+        (waiting,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM pg_stat_activity \
+             WHERE datname = current_database() AND wait_event_type = 'Lock'",
+        )
+        .fetch_one(db)
+        .await
+        .unwrap();
+        if waiting > 0 {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(25)).await;
+    }
+    assert!(
+        waiting > 0,
+        "the registration never waited on the held version"
+    );
+    tx.commit().await.unwrap();
+
+    (winner, registration.await.unwrap().unwrap())
+}
+
+/// The hash the server stores for the registered schema, read from a sibling subject
+async fn stored_hash(server: &schema_registry_test_env::TestServer, db: &PgPool) -> String {
+    let mut body = register_body();
+    body["subject"] = json!("race.Template");
+    let response = server.post_json("/api/v1/schemas", &body).await.unwrap();
+    assert_eq!(response.status().as_u16(), 201);
+    let (hash,): (String,) =
+        sqlx::query_as("SELECT content_hash FROM schemas WHERE name = 'Template'")
+            .fetch_one(db)
+            .await
+            .unwrap();
+    hash
+}
+
+#[tokio::test]
+async fn test_lost_race_with_same_content_returns_the_stored_version() {
+    let env = TestEnvironment::new().await.unwrap();
+    let server = env.start_server().await.unwrap();
+    let db = PgPoolOptions::new()
+        .connect(server.database_url())
+        .await
+        .unwrap();
+    let hash = stored_hash(&server, &db).await;
+
+    let (winner, response) = register_racing(&server, &db, &hash).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["id"], winner.to_string());
+    assert_eq!(body["version"], "1.0.0");
+    let warnings = body["warnings"].to_string();
+    assert!(warnings.contains("registered concurrently"), "{}", warnings);
+
+    let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM schemas WHERE name = 'Order'")
+        .fetch_one(&db)
+        .await
+        .unwrap();
+    assert_eq!(count, 1);
+}
+
+#[tokio::test]
+async fn test_lost_race_with_different_content_is_a_conflict() {
+    let env = TestEnvironment::new().await.unwrap();
+    let server = env.start_server().await.unwrap();
+    let db = PgPoolOptions::new()
+        .connect(server.database_url())
+        .await
+        .unwrap();
+
+    let (winner, response) = register_racing(&server, &db, &"0".repeat(64)).await;
+    assert_eq!(response.status().as_u16(), 409);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["code"], "REGISTRATION_CONFLICT");
+    assert_eq!(body["existing"]["id"], winner.to_string());
+    assert_eq!(body["existing"]["version"], "1.0.0");
+    assert_eq!(body["existing"]["same_content"], false);
+
+    let (id,): (Uuid,) = sqlx::query_as("SELECT id FROM schemas WHERE name = 'Order'")
+        .fetch_one(&db)
+        .await
+        .unwrap();
+    assert_eq!(id, winner);
+}
+
+#[tokio::test]
+async fn test_registering_an_existing_version_compares_content() {
+    let env = TestEnvironment::new().await.unwrap();
+    let server = env.start_server().await.unwrap();
+
+    let first = server.post_json("/api/v1/schemas", &register_body()).await.unwrap();
+    assert_eq!(first.status().as_u16(), 201);
+    let stored: Value = first.json().await.unwrap();
+
+    // The same version with the same content is the same registration
+    let repeated = server.post_json("/api/v1/schemas", &register_body()).await.unwrap();
+    assert_eq!(repeated.status().as_u16(), 200);
+    let body: Value = repeated.json().await.unwrap();
+    assert_eq!(body["id"], stored["id"]);
+    assert_eq!(body["created_at"], stored["created_at"]);
+
+    // Different content under that version is refused as in the race
+    let mut changed = register_body();
+    changed["schema"]["properties"]["id"]["type"] = json!("string");
+    let conflict = server.post_json("/api/v1/schemas", &changed).await.unwrap();
+    assert_eq!(conflict.status().as_u16(), 409);
+    let body: Value = conflict.json().await.unwrap();
+    assert_eq!(body["code"], "REGISTRATION_CONFLICT");
+    assert_eq!(body["existing"]["id"], stored["id"]);
+    assert_eq!(body["existing"]["version"], "1.0.0");
+    assert_eq!(body["existing"]["same_content"], false);
+}
@@ -0,0 +1,248 @@
+//! Schema read tests
+//!
+//! Reads are conditional on the body they return: a client sending back the
+//! `ETag` gets a `304` until anything in that body changes. A version reads
+//! the same by UUID and by the global ID Kafka serializers embed, and as
+//! the schema file itself for `?raw=true` or an `Accept` naming its type.
+
+use super::*;
+use redis::Commands;
+use serde_json::{json, Value};
+
+const ADMIN_TOKEN: &str = "read-admin-token";
+
+async fn read(
+    server: &schema_registry_test_env::TestServer,
+    path: &str,
+    etag: Option<&str>,
+) -> reqwest::Response {
+    let mut request = server.client().get(server.url(path));
+    if let Some(etag) = etag {
+        request = request.header("if-none-match", etag);
+    }
+    request.send().await.unwrap()
+}
+
+async fn read_as(
+    server: &schema_registry_test_env::TestServer,
+    path: &str,
+    accept: &str,
+) -> reqwest::Response {
+    let request = server.client().get(server.url(path));
+    request.header("accept", accept).send().await.unwrap()
+}
+
+fn etag_of(response: &reqwest::Response) -> String {
+    response.headers()["etag"].to_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn test_unchanged_reads_are_not_modified() {
+    let env = TestEnvironment::new().await.unwrap();
+    let server = env
+        .start_server_with(|config| config.admin_token = Some(ADMIN_TOKEN.to_string()))
+        .await
+        .unwrap();
+    let registered = server
+        .register_schema("reads.Order", "JSON", json!({"type": "object"}))
+        .await
+        .unwrap();
+    let path = format!("/api/v1/schemas/{}", registered["id"].as_str().unwrap());
+
+    let response = read(&server, &path, None).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let etag = etag_of(&response);
+
+    let response = read(&server, &path, Some(&etag)).await;
+    assert_eq!(response.status().as_u16(), 304);
+    assert_eq!(etag_of(&response), etag);
+    assert!(response.bytes().await.unwrap().is_empty());
+    let weak = format!("W/{}", etag);
+    let response = read(&server, &path, Some(&weak)).await;
+    assert_eq!(response.status().as_u16(), 304);
+
+    // Another selection is another body
+    let selected = format!("{}?fields=id,state", path);
+    let response = read(&server, &selected, Some(&etag)).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let selected_etag = etag_of(&response);
+    assert_ne!(selected_etag, etag);
+    let response = read(&server, &selected, Some(&selected_etag)).await;
+    assert_eq!(response.status().as_u16(), 304);
+
+    let response = server
+        .client()
+        .patch(server.url(&format!("{}/metadata", path)))
+        .bearer_auth(ADMIN_TOKEN)
+        .json(&json!({"metadata": {"team": "orders"}}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = read(&server, &path, Some(&etag)).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let changed = etag_of(&response);
+    assert_ne!(changed, etag);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["metadata"]["team"], "orders");
+    let response = read(&server, &path, Some(&changed)).await;
+    assert_eq!(response.status().as_u16(), 304);
+}
+
+#[tokio::test]
+async fn test_reads_by_global_id_match_reads_by_id() {
+    let env = TestEnvironment::new().await.unwrap();
+    let server = env.start_server().await.unwrap();
+    let registered = server
+        .register_schema("reads.Invoice", "JSON", json!({"type": "object"}))
+        .await
+        .unwrap();
+    let global_id = registered["global_id"].as_i64().unwrap();
+    let by_id = format!("/api/v1/schemas/{}", registered["id"].as_str().unwrap());
+    let by_global_id = format!("/api/v1/schemas/ids/{}", global_id);
+
+    let response = read(&server, &by_id, None).await;
+    let etag = etag_of(&response);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["global_id"], global_id);
+    let response = read(&server, &by_global_id, None).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(etag_of(&response), etag);
+    assert_eq!(response.json::<Value>().await.unwrap(), body);
+    let response = read(&server, &by_global_id, Some(&etag)).await;
+    assert_eq!(response.status().as_u16(), 304);
+
+    let missing = format!("/api/v1/schemas/ids/{}", global_id + 1000);
+    assert_eq!(read(&server, &missing, None).await.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn test_entries_cached_by_an_older_release_are_not_served() {
+    let env = TestEnvironment::new().await.unwrap();
+    let server = env.start_server().await.unwrap();
+    let registered = server
+        .register_schema("reads.Refund", "JSON", json!({"type": "object"}))
+        .await
+        .unwrap();
+    let id = registered["id"].as_str().unwrap();
+
+    // An entry in the shape cached before global IDs, with stale content
+    let stale = json!({
+        "id": id,
+        "namespace": "reads",
+        "name": "Refund",
+        "version_major": 1,
+        "version_minor": 0,
+        "version_patch": 0,
+        "format": "JSON",
+        "content": "{\"type\":\"string\"}",
+        "state": "DRAFT",
+        "compatibility_mode": "BACKWARD",
+        "metadata": {},
+    });
+    let mut conn = env.redis_client().get_connection().unwrap();
+    let _: () = conn
+        .set(format!("schema:{}", id), stale.to_string())
+        .unwrap();
+
+    let path = format!("/api/v1/schemas/ids/{}", registered["global_id"]);
+    let response = read(&server, &path, None).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["global_id"], registered["global_id"]);
+    assert_eq!(body["schema"], json!({"type": "object"}));
+}
+
+#[tokio::test]
+async fn test_ids_is_not_a_namespace() {
+    let env = TestEnvironment::new().await.unwrap();
+    let server = env
+        .start_server_with(|config| config.admin_token = Some(ADMIN_TOKEN.to_string()))
+        .await
+        .unwrap();
+    let body = json!({"subject": "ids.Order", "schema_type": "JSON", "schema": {"type": "object"}});
+    let response = server.post_json("/api/v1/schemas", &body).await.unwrap();
+    assert_eq!(response.status().as_u16(), 400);
+
+    server
+        .register_schema("reads.Order", "JSON", json!({"type": "object"}))
+        .await
+        .unwrap();
+    let response = server
+        .client()
+        .post(server.url("/api/v1/admin/subjects/reads.Order/rename"))
+        .bearer_auth(ADMIN_TOKEN)
+        .json(&json!({"new_subject": "ids.Order"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 400);
+}
+
+#[tokio::test]
+async fn test_schema_files_are_read_raw_or_by_media_type() {
+    let env = TestEnvironment::new().await.unwrap();
+    let server = env.start_server().await.unwrap();
+    let schema = json!({"type": "object", "properties": {"id": {"type": "string"}}});
+    let registered = server
+        .register_schema("reads.Shipment", "JSON", schema.clone())
+        .await
+        .unwrap();
+    let path = format!("/api/v1/schemas/{}", registered["id"].as_str().unwrap());
+
+    let raw = format!("{}?raw=true", path);
+    let response = read(&server, &raw, None).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let headers = response.headers().clone();
+    assert_eq!(headers["content-type"], "application/schema+json");
+    assert_eq!(
+        headers["content-disposition"],
+        "attachment; filename=\"Shipment-1.0.0.json\"; filename*=UTF-8''Shipment-1.0.0.json"
+    );
+    assert_eq!(response.json::<Value>().await.unwrap(), schema);
+    let etag = headers["etag"].to_str().unwrap();
+    assert_eq!(read(&server, &raw, Some(etag)).await.status().as_u16(), 304);
+
+    let response = read_as(&server, &path, "application/schema+json;q=0.9").await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(
+        response.headers()["content-type"],
+        "application/schema+json"
+    );
+    assert_eq!(response.json::<Value>().await.unwrap(), schema);
+
+    let response = read_as(&server, &path, "text/x-protobuf, application/json").await;
+    assert_eq!(response.status().as_u16(), 200);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["id"], registered["id"]);
+
+    // Only another format's file is asked for
+    let response = read_as(&server, &path, "text/x-protobuf").await;
+    assert_eq!(response.status().as_u16(), 406);
+    let body: Value = response.json().await.unwrap();
+    let message = body["error"].as_str().unwrap();
+    assert!(message.contains("application/schema+json"), "{}", message);
+}
+
+#[tokio::test]
+async fn test_schema_file_names_are_escaped() {
+    let env = TestEnvironment::new().await.unwrap();
+    let server = env.start_server().await.unwrap();
+    let registered = server
+        .register_schema("reads.Or\"d\u{e9}r\r\n", "JSON", json!({"type": "object"}))
+        .await
+        .unwrap();
+    let path = format!(
+        "/api/v1/schemas/{}?raw=true",
+        registered["id"].as_str().unwrap()
+    );
+
+    let response = read(&server, &path, None).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(
+        response.headers()["content-disposition"],
+        "attachment; filename=\"Or_d_r__-1.0.0.json\"; \
+         filename*=UTF-8''Or%22d%C3%A9r%0D%0A-1.0.0.json"
+    );
+}
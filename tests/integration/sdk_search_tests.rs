@@ -0,0 +1,87 @@
+//! SDK search tests
+//!
+//! Every filter the SDK's search builder can set is sent to the real search
+//! handler, so an option the server doesn't understand fails here rather
+//! than in a caller's code.
+
+use super::*;
+use futures::TryStreamExt;
+use llm_schema_registry_sdk::{SchemaFormat, SchemaRegistryClient, SchemaState, SearchQuery};
+use serde_json::json;
+
+async fn register(
+    server: &schema_registry_test_env::TestServer,
+    subject: &str,
+    state: &str,
+    tags: &[&str],
+) {
+    let response = server
+        .post_json(
+            "/api/v1/schemas",
+            &json!({
+                "subject": subject,
+                "schema_type": "JSON",
+                "schema": {"type": "object", "description": "token usage", "properties": {"id": {"type": "string"}}},
+                "state": state,
+                "tags": tags,
+            }),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 201, "registering {}", subject);
+}
+
+#[tokio::test]
+async fn test_sdk_search_filters_are_accepted() {
+    let env = TestEnvironment::new().await.unwrap();
+    let server = env.start_server().await.unwrap();
+    register(&server, "telemetry.llm.Inference", "ACTIVE", &["billing"]).await;
+    register(&server, "telemetry.llm.Embedding", "ACTIVE", &["billing"]).await;
+    register(
+        &server,
+        "telemetry.llm.Completion",
+        "DEPRECATED",
+        &["billing"],
+    )
+    .await;
+    register(&server, "telemetry.llm.Prompt", "ACTIVE", &[]).await;
+    register(&server, "payments.Invoice", "ACTIVE", &["billing"]).await;
+
+    let client = SchemaRegistryClient::builder()
+        .base_url(server.base_url())
+        .build()
+        .unwrap();
+    let now = chrono::Utc::now();
+    let query = SearchQuery::builder()
+        .text("token")
+        .namespace_glob("telemetry.*")
+        .tag("billing")
+        .state(SchemaState::Active)
+        .format(SchemaFormat::JsonSchema)
+        .created_between(
+            now - chrono::Duration::hours(1),
+            now + chrono::Duration::hours(1),
+        )
+        .page_size(1)
+        .build();
+
+    let first = client.search_schemas(query.clone()).await.unwrap();
+    assert_eq!(first.total, 2);
+    assert_eq!(first.results.len(), 1);
+
+    let mut names: Vec<String> = client
+        .search_all(query)
+        .map_ok(|result| result.metadata.name)
+        .try_collect()
+        .await
+        .unwrap();
+    names.sort();
+    assert_eq!(names, vec!["Embedding", "Inference"]);
+
+    let exact = client
+        .search_schemas(SearchQuery::builder().namespace("payments").build())
+        .await
+        .unwrap();
+    assert_eq!(exact.total, 1);
+    assert_eq!(exact.results[0].metadata.format, SchemaFormat::JsonSchema);
+}
@@ -0,0 +1,90 @@
+//! State change tests
+//!
+//! Deprecating or archiving a version through the state endpoint retires it,
+//! so it is held to the admin token and usage check of the archive endpoint.
+
+use super::*;
+use serde_json::{json, Value};
+
+const ADMIN_TOKEN: &str = "state-admin-token";
+
+async fn change_state(
+    server: &schema_registry_test_env::TestServer,
+    id: &str,
+    body: Value,
+    token: Option<&str>,
+) -> reqwest::Response {
+    let path = format!("/api/v1/schemas/{}/state", id);
+    let mut request = server.client().post(server.url(&path)).json(&body);
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+    request.send().await.unwrap()
+}
+
+#[tokio::test]
+async fn test_retiring_states_need_admin_and_force_while_in_use() {
+    let env = TestEnvironment::new().await.unwrap();
+    let server = env
+        .start_server_with(|config| config.admin_token = Some(ADMIN_TOKEN.to_string()))
+        .await
+        .unwrap();
+    let registered = server
+        .register_schema("lifecycle.Order", "JSON", json!({"type": "object"}))
+        .await
+        .unwrap();
+    let id = registered["id"].as_str().unwrap();
+
+    // Activating retires nothing and needs no token
+    let response = change_state(&server, id, json!({"state": "ACTIVE"}), None).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = server
+        .client()
+        .get(server.url(&format!("/api/v1/schemas/{}", id)))
+        .header("x-client-id", "billing")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    // Usage events are recorded in the background
+    let usage_path = format!("/api/v1/schemas/{}/usage", id);
+    let mut usage = Value::Null;
+    for _ in 0..50 {
+        usage = server.get(&usage_path).await.unwrap().json().await.unwrap();
+        if usage["usage"]["total_operations"] == 1 {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    assert_eq!(usage["usage"]["total_operations"], 1);
+
+    let deprecate = json!({"state": "DEPRECATED"});
+    let response = change_state(&server, id, deprecate.clone(), None).await;
+    assert_eq!(response.status().as_u16(), 401);
+    let response = change_state(&server, id, deprecate, Some(ADMIN_TOKEN)).await;
+    assert_eq!(response.status().as_u16(), 409);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["code"], "SCHEMA_IN_USE");
+    assert_eq!(body["usage"]["clients"], json!(["billing"]));
+
+    let unexplained = json!({"state": "DEPRECATED", "force": true});
+    let response = change_state(&server, id, unexplained, Some(ADMIN_TOKEN)).await;
+    assert_eq!(response.status().as_u16(), 400);
+    let forced = json!({"state": "DEPRECATED", "force": true, "reason": "v2 is out"});
+    let response = change_state(&server, id, forced, Some(ADMIN_TOKEN)).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["state"], "DEPRECATED");
+
+    let archive = json!({"state": "ARCHIVED"});
+    let response = change_state(&server, id, archive.clone(), None).await;
+    assert_eq!(response.status().as_u16(), 401);
+    let response = change_state(&server, id, archive, Some(ADMIN_TOKEN)).await;
+    assert_eq!(response.status().as_u16(), 409);
+    let forced = json!({"state": "ARCHIVED", "force": true, "reason": "v2 is out"});
+    let response = change_state(&server, id, forced, Some(ADMIN_TOKEN)).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["state"], "ARCHIVED");
+}
@@ -0,0 +1,156 @@
+//! Subject and version deletion tests
+//!
+//! Deleting is an admin operation guarded by the same usage check as
+//! retiring a schema, and a tombstone keeps its version number without
+//! holding on to its content.
+
+use super::*;
+use serde_json::{json, Value};
+
+const ADMIN_TOKEN: &str = "deletion-admin-token";
+
+async fn delete(
+    server: &schema_registry_test_env::TestServer,
+    path: &str,
+    token: Option<&str>,
+) -> reqwest::Response {
+    let mut request = server.client().delete(server.url(path));
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+    request.send().await.unwrap()
+}
+
+async fn status_of(server: &schema_registry_test_env::TestServer, id: &str) -> u16 {
+    let response = server
+        .get(&format!("/api/v1/schemas/{}", id))
+        .await
+        .unwrap();
+    response.status().as_u16()
+}
+
+#[tokio::test]
+async fn test_deleted_versions_keep_their_numbers_but_not_their_content() {
+    let env = TestEnvironment::new().await.unwrap();
+    let server = env
+        .start_server_with(|config| config.admin_token = Some(ADMIN_TOKEN.to_string()))
+        .await
+        .unwrap();
+    let schema = json!({"type": "object", "properties": {"id": {"type": "string"}}});
+    let first = server
+        .register_schema("deletion.Order", "JSON", schema.clone())
+        .await
+        .unwrap();
+    let first_id = first["id"].as_str().unwrap();
+    let version_path = "/api/v1/subjects/deletion.Order/versions/1.0.0?reason=leaked+a+field";
+
+    let response = delete(&server, version_path, None).await;
+    assert_eq!(response.status().as_u16(), 401);
+    assert_eq!(status_of(&server, first_id).await, 200);
+
+    let response = delete(&server, version_path, Some(ADMIN_TOKEN)).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["permanent"], false);
+    assert_eq!(body["forced"], false);
+    assert_eq!(body["deleted"][0]["id"], first_id);
+    assert_eq!(status_of(&server, first_id).await, 410);
+
+    let response = delete(&server, version_path, Some(ADMIN_TOKEN)).await;
+    assert_eq!(response.status().as_u16(), 409);
+
+    // The content registers again as a new version
+    let second = server
+        .register_schema("deletion.Order", "JSON", schema.clone())
+        .await
+        .unwrap();
+    let second_id = second["id"].as_str().unwrap();
+    assert_ne!(second_id, first_id);
+    assert_eq!(second["version"], "2.0.0");
+    assert_eq!(status_of(&server, second_id).await, 200);
+    assert_eq!(status_of(&server, first_id).await, 410);
+
+    let response = server
+        .post_json(
+            "/api/v1/schemas",
+            &json!({
+                "subject": "deletion.Order",
+                "schema_type": "JSON",
+                "schema": {"type": "object"},
+                "version_major": 1,
+            }),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 409);
+
+    let response = delete(
+        &server,
+        "/api/v1/subjects/deletion.Order?permanent=true",
+        Some(ADMIN_TOKEN),
+    )
+    .await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(response.headers().contains_key("x-operation-id"));
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["permanent"], true);
+    assert_eq!(body["deleted"].as_array().unwrap().len(), 2);
+    assert_eq!(status_of(&server, first_id).await, 404);
+    assert_eq!(status_of(&server, second_id).await, 404);
+}
+
+#[tokio::test]
+async fn test_versions_in_use_are_only_deleted_with_force_and_reason() {
+    let env = TestEnvironment::new().await.unwrap();
+    let server = env
+        .start_server_with(|config| config.admin_token = Some(ADMIN_TOKEN.to_string()))
+        .await
+        .unwrap();
+    let registered = server
+        .register_schema("deletion.Invoice", "JSON", json!({"type": "object"}))
+        .await
+        .unwrap();
+    let id = registered["id"].as_str().unwrap();
+
+    let response = server
+        .client()
+        .get(server.url(&format!("/api/v1/schemas/{}", id)))
+        .header("x-client-id", "billing")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    // Usage events are recorded in the background
+    let usage_path = format!("/api/v1/schemas/{}/usage", id);
+    let mut usage = Value::Null;
+    for _ in 0..50 {
+        usage = server.get(&usage_path).await.unwrap().json().await.unwrap();
+        if usage["usage"]["total_operations"] == 1 {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    assert_eq!(usage["usage"]["total_operations"], 1);
+
+    let path = "/api/v1/subjects/deletion.Invoice";
+    let response = delete(&server, path, Some(ADMIN_TOKEN)).await;
+    assert_eq!(response.status().as_u16(), 409);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["code"], "SCHEMA_IN_USE");
+    assert_eq!(body["usage"]["clients"], json!(["billing"]));
+
+    let permanent = format!("{}?permanent=true", path);
+    let response = delete(&server, &permanent, Some(ADMIN_TOKEN)).await;
+    assert_eq!(response.status().as_u16(), 409);
+    // Forcing needs a reason for the audit log
+    let unexplained = format!("{}?force=true", path);
+    let response = delete(&server, &unexplained, Some(ADMIN_TOKEN)).await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    let forced = format!("{}?force=true&reason=billing+moved+to+v2", path);
+    let response = delete(&server, &forced, Some(ADMIN_TOKEN)).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["forced"], true);
+    assert_eq!(status_of(&server, id).await, 410);
+}
@@ -0,0 +1,483 @@
+//! `cargo xtask bench-compare`
+//!
+//! Runs the compatibility checker benchmark, takes each case's p95 from
+//! Criterion's samples and compares it with the committed baseline. The
+//! command fails when a case's p95 is over the target or has grown by more
+//! than the allowed percentage since the baseline was recorded.
+//!
+//! Criterion times each sample as a batch of iterations, so the p95 is over
+//! the per-iteration mean of each sample rather than over single calls.
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const BENCH_PACKAGE: &str = "schema-registry-compatibility";
+const BENCH_NAME: &str = "compatibility_check";
+const DEFAULT_BASELINE: &str = "benchmarks/baselines/compatibility_check.json";
+const DEFAULT_P95_TARGET_MS: f64 = 25.0;
+const DEFAULT_MAX_REGRESSION_PCT: f64 = 10.0;
+
+const USAGE: &str = "\
+Usage: cargo xtask bench-compare [options] [-- <criterion args>]
+
+Options:
+  --baseline <path>        Baseline file (default: benchmarks/baselines/compatibility_check.json)
+  --max-regression <pct>   Allowed p95 growth in percent, overriding the baseline file
+  --save-baseline          Record this run as the new baseline instead of failing on regressions
+  --no-run                 Compare the results of the last benchmark run
+  -h, --help               Show this message";
+
+/// Committed p95 results and the limits they're held to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Baseline {
+    /// No case may have a p95 above this, regardless of its baseline
+    p95_target_ms: f64,
+    /// How much a case's p95 may grow over its baseline, in percent
+    max_regression_pct: f64,
+    /// Recorded results by Criterion benchmark ID
+    benchmarks: BTreeMap<String, BenchmarkBaseline>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct BenchmarkBaseline {
+    p95_ns: f64,
+    /// Replaces the global target for cases it doesn't fit, such as a
+    /// transitive check across a long history
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    p95_target_ms: Option<f64>,
+}
+
+impl Default for Baseline {
+    fn default() -> Self {
+        Self {
+            p95_target_ms: DEFAULT_P95_TARGET_MS,
+            max_regression_pct: DEFAULT_MAX_REGRESSION_PCT,
+            benchmarks: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Options {
+    baseline: PathBuf,
+    max_regression: Option<f64>,
+    save_baseline: bool,
+    no_run: bool,
+    bench_args: Vec<String>,
+}
+
+impl Options {
+    /// `None` when help was requested
+    fn parse(args: Vec<String>) -> Result<Option<Self>> {
+        let mut options = Options {
+            baseline: workspace_root().join(DEFAULT_BASELINE),
+            max_regression: None,
+            save_baseline: false,
+            no_run: false,
+            bench_args: Vec::new(),
+        };
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--baseline" => {
+                    options.baseline = args.next().context("--baseline needs a path")?.into();
+                }
+                "--max-regression" => {
+                    let value = args.next().context("--max-regression needs a percentage")?;
+                    options.max_regression = Some(
+                        value
+                            .parse()
+                            .with_context(|| format!("Invalid --max-regression '{}'", value))?,
+                    );
+                }
+                "--save-baseline" => options.save_baseline = true,
+                "--no-run" => options.no_run = true,
+                "-h" | "--help" => return Ok(None),
+                "--" => options.bench_args.extend(args.by_ref()),
+                other => bail!("Unknown option '{}'\n\n{}", other, USAGE),
+            }
+        }
+        Ok(Some(options))
+    }
+}
+
+/// Whether a benchmark case passed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Verdict {
+    Ok,
+    /// Not in the baseline yet
+    New,
+    Regressed,
+    OverTarget,
+}
+
+impl Verdict {
+    fn label(self) -> &'static str {
+        match self {
+            Verdict::Ok => "ok",
+            Verdict::New => "new",
+            Verdict::Regressed => "REGRESSED",
+            Verdict::OverTarget => "OVER TARGET",
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Comparison {
+    id: String,
+    p95_ns: f64,
+    baseline_ns: Option<f64>,
+    verdict: Verdict,
+}
+
+impl Comparison {
+    fn change_pct(&self) -> Option<f64> {
+        self.baseline_ns
+            .filter(|baseline| *baseline > 0.0)
+            .map(|baseline| (self.p95_ns - baseline) / baseline * 100.0)
+    }
+}
+
+/// Run the command; `Ok(false)` means a case failed its limits
+pub fn run(args: Vec<String>) -> Result<bool> {
+    let Some(options) = Options::parse(args)? else {
+        println!("{}", USAGE);
+        return Ok(true);
+    };
+
+    if !options.no_run {
+        run_benchmark(&options.bench_args)?;
+    }
+    let results = read_results(&criterion_dir().join(BENCH_NAME))?;
+    if results.is_empty() {
+        bail!(
+            "No results for the {} benchmark; run it first or drop --no-run",
+            BENCH_NAME
+        );
+    }
+
+    let mut baseline = if options.baseline.exists() {
+        let content = std::fs::read_to_string(&options.baseline)
+            .with_context(|| format!("Reading {}", options.baseline.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Parsing {}", options.baseline.display()))?
+    } else if options.save_baseline {
+        Baseline::default()
+    } else {
+        bail!(
+            "No baseline at {}; record one with --save-baseline",
+            options.baseline.display()
+        );
+    };
+    if let Some(max_regression) = options.max_regression {
+        baseline.max_regression_pct = max_regression;
+    }
+
+    let comparisons = compare(&results, &baseline);
+    let report = report(&comparisons, &baseline);
+    print!("{}", report);
+    for id in baseline
+        .benchmarks
+        .keys()
+        .filter(|id| !results.contains_key(*id))
+    {
+        println!("warning: {} is in the baseline but was not run", id);
+    }
+    if let Ok(summary) = std::env::var("GITHUB_STEP_SUMMARY") {
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(summary)?;
+        std::io::Write::write_all(&mut file, report.as_bytes())?;
+    }
+
+    let over_target = comparisons.iter().any(|c| c.verdict == Verdict::OverTarget);
+    if options.save_baseline {
+        baseline.benchmarks = results
+            .iter()
+            .map(|(id, &p95_ns)| {
+                let p95_target_ms = baseline.benchmarks.get(id).and_then(|b| b.p95_target_ms);
+                (
+                    id.clone(),
+                    BenchmarkBaseline {
+                        p95_ns,
+                        p95_target_ms,
+                    },
+                )
+            })
+            .collect();
+        let content = serde_json::to_string_pretty(&baseline)? + "\n";
+        std::fs::write(&options.baseline, content)
+            .with_context(|| format!("Writing {}", options.baseline.display()))?;
+        println!("Baseline saved to {}", options.baseline.display());
+        return Ok(!over_target);
+    }
+    Ok(!comparisons
+        .iter()
+        .any(|c| matches!(c.verdict, Verdict::Regressed | Verdict::OverTarget)))
+}
+
+fn workspace_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .expect("xtask lives in the workspace root")
+        .to_path_buf()
+}
+
+fn criterion_dir() -> PathBuf {
+    std::env::var_os("CARGO_TARGET_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| workspace_root().join("target"))
+        .join("criterion")
+}
+
+fn run_benchmark(bench_args: &[String]) -> Result<()> {
+    let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    let status = Command::new(cargo)
+        .current_dir(workspace_root())
+        .args([
+            "bench",
+            "--package",
+            BENCH_PACKAGE,
+            "--bench",
+            BENCH_NAME,
+            "--",
+        ])
+        .args(bench_args)
+        .status()
+        .context("Running cargo bench")?;
+    if !status.success() {
+        bail!("cargo bench failed with {}", status);
+    }
+    Ok(())
+}
+
+/// Criterion's description of one benchmark case
+#[derive(Deserialize)]
+struct BenchmarkInfo {
+    full_id: String,
+}
+
+/// Criterion's raw measurements of one benchmark case
+#[derive(Deserialize)]
+struct Samples {
+    iters: Vec<f64>,
+    times: Vec<f64>,
+}
+
+/// p95 in nanoseconds of every case under `dir`, by benchmark ID
+///
+/// Criterion keeps the latest run of each case in a `new` directory.
+fn read_results(dir: &Path) -> Result<BTreeMap<String, f64>> {
+    let mut results = BTreeMap::new();
+    if !dir.is_dir() {
+        return Ok(results);
+    }
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        let latest = current.join("new");
+        if latest.join("sample.json").is_file() && latest.join("benchmark.json").is_file() {
+            let info: BenchmarkInfo = read_json(&latest.join("benchmark.json"))?;
+            let samples: Samples = read_json(&latest.join("sample.json"))?;
+            let per_iteration: Vec<f64> = samples
+                .times
+                .iter()
+                .zip(&samples.iters)
+                .filter(|(_, iters)| **iters > 0.0)
+                .map(|(time, iters)| time / iters)
+                .collect();
+            if let Some(p95) = percentile(per_iteration, 95.0) {
+                results.insert(info.full_id, p95);
+            }
+        }
+        for entry in std::fs::read_dir(&current)? {
+            let path = entry?.path();
+            // Criterion's `base` and `change` copies and the HTML report aren't runs
+            let skip = matches!(
+                path.file_name().and_then(|name| name.to_str()),
+                Some("new" | "base" | "change" | "report")
+            );
+            if path.is_dir() && !skip {
+                pending.push(path);
+            }
+        }
+    }
+    Ok(results)
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(path: &Path) -> Result<T> {
+    let content =
+        std::fs::read_to_string(path).with_context(|| format!("Reading {}", path.display()))?;
+    serde_json::from_str(&content).with_context(|| format!("Parsing {}", path.display()))
+}
+
+/// Nearest-rank percentile
+fn percentile(mut values: Vec<f64>, pct: f64) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(f64::total_cmp);
+    let rank = (pct / 100.0 * values.len() as f64).ceil() as usize;
+    Some(values[rank.clamp(1, values.len()) - 1])
+}
+
+fn compare(results: &BTreeMap<String, f64>, baseline: &Baseline) -> Vec<Comparison> {
+    results
+        .iter()
+        .map(|(id, &p95_ns)| {
+            let recorded = baseline.benchmarks.get(id);
+            let target_ns = recorded
+                .and_then(|b| b.p95_target_ms)
+                .unwrap_or(baseline.p95_target_ms)
+                * 1_000_000.0;
+            let mut comparison = Comparison {
+                id: id.clone(),
+                p95_ns,
+                baseline_ns: recorded.map(|b| b.p95_ns),
+                verdict: Verdict::Ok,
+            };
+            comparison.verdict = if p95_ns > target_ns {
+                Verdict::OverTarget
+            } else if comparison.baseline_ns.is_none() {
+                Verdict::New
+            } else if comparison
+                .change_pct()
+                .is_some_and(|change| change > baseline.max_regression_pct)
+            {
+                Verdict::Regressed
+            } else {
+                Verdict::Ok
+            };
+            comparison
+        })
+        .collect()
+}
+
+/// Markdown table of the comparisons
+fn report(comparisons: &[Comparison], baseline: &Baseline) -> String {
+    let mut out = format!(
+        "## Compatibility check p95 (target {} ms, max regression {}%)\n\n\
+         | Benchmark | p95 | Baseline | Change | Result |\n\
+         |---|---:|---:|---:|---|\n",
+        baseline.p95_target_ms, baseline.max_regression_pct
+    );
+    for c in comparisons {
+        let _ = writeln!(
+            out,
+            "| {} | {} | {} | {} | {} |",
+            c.id,
+            format_duration(c.p95_ns),
+            c.baseline_ns
+                .map(format_duration)
+                .unwrap_or_else(|| "-".to_string()),
+            c.change_pct()
+                .map(|change| format!("{:+.1}%", change))
+                .unwrap_or_else(|| "-".to_string()),
+            c.verdict.label()
+        );
+    }
+    out.push('\n');
+    out
+}
+
+fn format_duration(ns: f64) -> String {
+    if ns >= 1_000_000.0 {
+        format!("{:.2} ms", ns / 1_000_000.0)
+    } else if ns >= 1_000.0 {
+        format!("{:.2} µs", ns / 1_000.0)
+    } else {
+        format!("{:.0} ns", ns)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentile_is_nearest_rank() {
+        let values: Vec<f64> = (1..=100).map(f64::from).collect();
+        assert_eq!(percentile(values.clone(), 95.0), Some(95.0));
+        assert_eq!(percentile(values, 100.0), Some(100.0));
+        assert_eq!(percentile(vec![3.0, 1.0, 2.0], 95.0), Some(3.0));
+        assert_eq!(percentile(Vec::new(), 95.0), None);
+    }
+
+    fn recorded(p95_ns: f64, p95_target_ms: Option<f64>) -> BenchmarkBaseline {
+        BenchmarkBaseline {
+            p95_ns,
+            p95_target_ms,
+        }
+    }
+
+    #[test]
+    fn test_compare_flags_regressions_and_target() {
+        let baseline = Baseline {
+            benchmarks: BTreeMap::from([
+                ("a".to_string(), recorded(1_000.0, None)),
+                ("b".to_string(), recorded(1_000.0, None)),
+                ("c".to_string(), recorded(20_000_000.0, None)),
+                ("e".to_string(), recorded(40_000_000.0, Some(50.0))),
+            ]),
+            ..Default::default()
+        };
+        let results = BTreeMap::from([
+            ("a".to_string(), 1_050.0),
+            ("b".to_string(), 1_200.0),
+            ("c".to_string(), 26_000_000.0),
+            ("d".to_string(), 500.0),
+            ("e".to_string(), 41_000_000.0),
+        ]);
+
+        let verdicts: Vec<_> = compare(&results, &baseline)
+            .iter()
+            .map(|c| c.verdict)
+            .collect();
+        assert_eq!(
+            verdicts,
+            vec![
+                Verdict::Ok,
+                Verdict::Regressed,
+                Verdict::OverTarget,
+                Verdict::New,
+                Verdict::Ok
+            ]
+        );
+        assert!(report(&compare(&results, &baseline), &baseline)
+            .contains("| b | 1.20 µs | 1.00 µs | +20.0% | REGRESSED |"));
+    }
+
+    #[test]
+    fn test_read_results_from_criterion_output() {
+        let dir = std::env::temp_dir().join(format!("bench-compare-{}", std::process::id()));
+        let case = dir.join("JSON_SCHEMA_FULL").join("100");
+        for run in ["new", "base"] {
+            std::fs::create_dir_all(case.join(run)).unwrap();
+            std::fs::write(
+                case.join(run).join("benchmark.json"),
+                r#"{"group_id":"compatibility_check","function_id":"JSON_SCHEMA/FULL","value_str":"100","full_id":"compatibility_check/JSON_SCHEMA/FULL/100"}"#,
+            )
+            .unwrap();
+        }
+        std::fs::write(
+            case.join("new").join("sample.json"),
+            r#"{"sampling_mode":"Linear","iters":[10.0,20.0,30.0],"times":[1000.0,4000.0,3000.0]}"#,
+        )
+        .unwrap();
+
+        let results = read_results(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            results.unwrap(),
+            BTreeMap::from([(
+                "compatibility_check/JSON_SCHEMA/FULL/100".to_string(),
+                200.0
+            )])
+        );
+    }
+}
@@ -0,0 +1,40 @@
+//! Repository automation
+//!
+//! Run with `cargo xtask <command>`; the alias lives in `.cargo/config.toml`.
+
+mod bench_compare;
+
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage: cargo xtask <command> [options]
+
+Commands:
+  bench-compare   Benchmark the compatibility checker and compare p95 latency with the baseline
+  help            Show this message
+
+Run `cargo xtask <command> --help` for a command's options.";
+
+fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1);
+    let result = match args.next().as_deref() {
+        Some("bench-compare") => bench_compare::run(args.collect()),
+        Some("help" | "--help" | "-h") | None => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Some(other) => {
+            eprintln!("Unknown command '{}'\n\n{}", other, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("error: {:#}", e);
+            ExitCode::from(2)
+        }
+    }
+}